@@ -1,7 +1,8 @@
 use crate::{ClientError, KanidmClient};
 use kanidm_proto::scim_v1::{
     client::{ScimEntryApplication, ScimEntryApplicationPost, ScimListApplication},
-    ScimApplicationPassword, ScimApplicationPasswordCreate, ScimEntryGetQuery,
+    ScimApplicationPassword, ScimApplicationPasswordCreate, ScimApplicationPasswordInfo,
+    ScimEntryGetQuery,
 };
 use uuid::Uuid;
 
@@ -77,4 +78,13 @@ impl KanidmClient {
         )
         .await
     }
+
+    /// List the application passwords held by the authenticated account. The
+    /// secrets are not included.
+    pub async fn idm_self_application_password_list(
+        &self,
+    ) -> Result<Vec<ScimApplicationPasswordInfo>, ClientError> {
+        self.perform_get_request("/v1/self/_application_password")
+            .await
+    }
 }
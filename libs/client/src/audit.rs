@@ -0,0 +1,13 @@
+use crate::{ClientError, KanidmClient};
+use kanidm_proto::v1::{AuditQuery, AuditRecordPage};
+
+impl KanidmClient {
+    /// Query the records of changes made to the database. Use the `next_offset` of the
+    /// returned page as the `offset` of the query to retrieve the following page.
+    pub async fn idm_audit_search(
+        &self,
+        query: &AuditQuery,
+    ) -> Result<AuditRecordPage, ClientError> {
+        self.perform_post_request("/v1/audit/_search", query).await
+    }
+}
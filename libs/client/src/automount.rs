@@ -0,0 +1,73 @@
+use crate::{ClientError, KanidmClient};
+use kanidm_proto::constants::{
+    ATTR_AUTOMOUNT_INFORMATION, ATTR_AUTOMOUNT_KEY, ATTR_DESCRIPTION, ATTR_NAME,
+};
+use kanidm_proto::v1::Entry;
+use std::collections::BTreeMap;
+
+impl KanidmClient {
+    pub async fn idm_automount_map_list(&self) -> Result<Vec<Entry>, ClientError> {
+        self.perform_get_request("/v1/automount").await
+    }
+
+    pub async fn idm_automount_map_get(&self, id: &str) -> Result<Option<Entry>, ClientError> {
+        self.perform_get_request(format!("/v1/automount/{id}").as_str())
+            .await
+    }
+
+    pub async fn idm_automount_map_create(
+        &self,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<(), ClientError> {
+        let mut new_map = Entry {
+            attrs: BTreeMap::new(),
+        };
+        new_map
+            .attrs
+            .insert(ATTR_NAME.to_string(), vec![name.to_string()]);
+        if let Some(description) = description {
+            new_map
+                .attrs
+                .insert(ATTR_DESCRIPTION.to_string(), vec![description.to_string()]);
+        }
+        self.perform_post_request("/v1/automount", new_map).await
+    }
+
+    /// Delete an automount map, and all of the keys within it.
+    pub async fn idm_automount_map_delete(&self, id: &str) -> Result<(), ClientError> {
+        self.perform_delete_request(format!("/v1/automount/{id}").as_str())
+            .await
+    }
+
+    pub async fn idm_automount_key_list(&self, id: &str) -> Result<Vec<Entry>, ClientError> {
+        self.perform_get_request(format!("/v1/automount/{id}/_key").as_str())
+            .await
+    }
+
+    pub async fn idm_automount_key_create(
+        &self,
+        id: &str,
+        key: &str,
+        information: &str,
+    ) -> Result<(), ClientError> {
+        let mut new_key = Entry {
+            attrs: BTreeMap::new(),
+        };
+        new_key
+            .attrs
+            .insert(ATTR_AUTOMOUNT_KEY.to_string(), vec![key.to_string()]);
+        new_key.attrs.insert(
+            ATTR_AUTOMOUNT_INFORMATION.to_string(),
+            vec![information.to_string()],
+        );
+        self.perform_post_request(format!("/v1/automount/{id}/_key").as_str(), new_key)
+            .await
+    }
+
+    /// Delete a single automount key by its uuid.
+    pub async fn idm_automount_key_delete(&self, key_uuid: &str) -> Result<(), ClientError> {
+        self.perform_delete_request(format!("/v1/automount/_key/{key_uuid}").as_str())
+            .await
+    }
+}
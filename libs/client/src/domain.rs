@@ -1,5 +1,7 @@
 use crate::{ClientError, KanidmClient};
-use kanidm_proto::constants::{ATTR_DOMAIN_ALLOW_ACCOUNT_RECOVERY, ATTR_DOMAIN_ALLOW_EASTER_EGGS};
+use kanidm_proto::constants::{
+    ATTR_DOMAIN_ALLOW_ACCOUNT_RECOVERY, ATTR_DOMAIN_ALLOW_EASTER_EGGS, ATTR_DOMAIN_SELF_WRITE_ATTRS,
+};
 use kanidm_proto::internal::ImageValue;
 use reqwest::multipart;
 
@@ -29,6 +31,18 @@ impl KanidmClient {
     }
 
     /// Add or update the domain logo/image
+    /// Set the attributes that people are allowed to modify on their own entry.
+    pub async fn idm_set_domain_self_write_attrs(&self, attrs: &[&str]) -> Result<(), ClientError> {
+        self.perform_put_request(
+            &format!("/v1/domain/_attr/{}", ATTR_DOMAIN_SELF_WRITE_ATTRS),
+            attrs
+                .iter()
+                .map(|attr| attr.to_string())
+                .collect::<Vec<_>>(),
+        )
+        .await
+    }
+
     pub async fn idm_domain_update_image(&self, image: ImageValue) -> Result<(), ClientError> {
         let file_content_type = image.filetype.as_content_type_str();
 
@@ -79,6 +79,108 @@ impl KanidmClient {
         .await
     }
 
+    pub async fn group_account_policy_password_minimum_score_set(
+        &self,
+        id: &str,
+        score: u32,
+    ) -> Result<(), ClientError> {
+        self.perform_put_request(
+            &format!("/v1/group/{id}/_attr/auth_password_minimum_score"),
+            vec![score.to_string()],
+        )
+        .await
+    }
+
+    pub async fn group_account_policy_password_minimum_score_reset(
+        &self,
+        id: &str,
+    ) -> Result<(), ClientError> {
+        self.perform_delete_request(&format!("/v1/group/{id}/_attr/auth_password_minimum_score"))
+            .await
+    }
+
+    pub async fn group_account_policy_auth_lockout_set(
+        &self,
+        id: &str,
+        max_attempts: u32,
+        duration: u32,
+    ) -> Result<(), ClientError> {
+        self.perform_put_request::<_, ()>(
+            &format!("/v1/group/{id}/_attr/auth_lockout_max_attempts"),
+            vec![max_attempts.to_string()],
+        )
+        .await?;
+        self.perform_put_request(
+            &format!("/v1/group/{id}/_attr/auth_lockout_duration"),
+            vec![duration.to_string()],
+        )
+        .await
+    }
+
+    pub async fn group_account_policy_auth_lockout_reset(
+        &self,
+        id: &str,
+    ) -> Result<(), ClientError> {
+        self.perform_delete_request(&format!("/v1/group/{id}/_attr/auth_lockout_max_attempts"))
+            .await?;
+        self.perform_delete_request(&format!("/v1/group/{id}/_attr/auth_lockout_duration"))
+            .await
+    }
+
+    pub async fn group_account_policy_credential_max_age_set(
+        &self,
+        id: &str,
+        max_age: u32,
+    ) -> Result<(), ClientError> {
+        self.perform_put_request(
+            &format!("/v1/group/{id}/_attr/credential_max_age"),
+            vec![max_age.to_string()],
+        )
+        .await
+    }
+
+    pub async fn group_account_policy_credential_max_age_reset(
+        &self,
+        id: &str,
+    ) -> Result<(), ClientError> {
+        self.perform_delete_request(&format!("/v1/group/{id}/_attr/credential_max_age"))
+            .await
+    }
+
+    pub async fn group_account_policy_password_breach_check(
+        &self,
+        id: &str,
+        enable: bool,
+    ) -> Result<(), ClientError> {
+        self.perform_put_request(
+            &format!("/v1/group/{id}/_attr/auth_password_breach_check"),
+            vec![enable.to_string()],
+        )
+        .await
+    }
+
+    pub async fn group_account_policy_password_history_length_set(
+        &self,
+        id: &str,
+        length: u32,
+    ) -> Result<(), ClientError> {
+        self.perform_put_request(
+            &format!("/v1/group/{id}/_attr/auth_password_history_length"),
+            vec![length.to_string()],
+        )
+        .await
+    }
+
+    pub async fn group_account_policy_password_history_length_reset(
+        &self,
+        id: &str,
+    ) -> Result<(), ClientError> {
+        self.perform_delete_request(&format!(
+            "/v1/group/{id}/_attr/auth_password_history_length"
+        ))
+        .await
+    }
+
     pub async fn group_account_policy_privilege_expiry_set(
         &self,
         id: &str,
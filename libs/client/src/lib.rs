@@ -51,6 +51,8 @@ use webauthn_rs_proto::{
 };
 
 mod application;
+mod audit;
+mod automount;
 mod domain;
 mod group;
 mod message;
@@ -61,6 +63,7 @@ mod scim;
 mod service_account;
 mod sync_account;
 mod system;
+mod webhook;
 
 const EXPECT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -1571,6 +1574,45 @@ impl KanidmClient {
         Ok(Some(r.youare))
     }
 
+    /// Inspect the api token this client is using, such as the operation scopes it is
+    /// restricted to.
+    pub async fn whoami_api_token(&self) -> Result<ApiToken, ClientError> {
+        self.perform_get_request("/v1/self/_api_token").await
+    }
+
+    /// Set an attribute on the entry of the authenticated person. The attribute must be
+    /// allowed by the domain self write policy.
+    pub async fn idm_self_set_attr(&self, attr: &str, values: &[&str]) -> Result<(), ClientError> {
+        let m: Vec<_> = values.iter().map(|v| (*v).to_string()).collect();
+        self.perform_put_request(&format!("/v1/self/_attr/{attr}"), m)
+            .await
+    }
+
+    /// List the sessions of the authenticated account.
+    pub async fn idm_self_session_list(&self) -> Result<Vec<UatStatus>, ClientError> {
+        self.perform_get_request("/v1/self/_session").await
+    }
+
+    /// Revoke one of the sessions of the authenticated account. Any OAuth2 sessions that
+    /// were derived from it are revoked too.
+    pub async fn idm_self_session_revoke(&self, session_id: Uuid) -> Result<(), ClientError> {
+        self.perform_delete_request(&format!("/v1/self/_session/{session_id}"))
+            .await
+    }
+
+    /// Request a verification message be sent to one of the unverified mail addresses
+    /// of the authenticated person.
+    pub async fn idm_self_mail_verify_send(&self, mail: &str) -> Result<(), ClientError> {
+        self.perform_post_request("/v1/self/_mail/_verify", mail.to_string())
+            .await
+    }
+
+    /// Confirm a mail address with the token that was sent to it.
+    pub async fn mail_verify_confirm(&self, token: &str) -> Result<(), ClientError> {
+        self.perform_simple_post_request("/v1/mail/_verify", &token.to_string())
+            .await
+    }
+
     // Raw DB actions
     pub async fn search(&self, filter: Filter) -> Result<Vec<Entry>, ClientError> {
         let sr = SearchRequest { filter };
@@ -1664,6 +1706,21 @@ impl KanidmClient {
             .await
     }
 
+    /// Add members to a group, which are removed from the group at `expiry`.
+    pub async fn idm_group_add_members_expiring(
+        &self,
+        id: &str,
+        members: &[&str],
+        expiry: time::OffsetDateTime,
+    ) -> Result<(), ClientError> {
+        let request = GroupAddMembersExpiring {
+            members: members.iter().map(|v| (*v).to_string()).collect(),
+            expiry,
+        };
+        self.perform_post_request(&format!("/v1/group/{id}/_member/_expiring"), request)
+            .await
+    }
+
     pub async fn idm_group_remove_members(
         &self,
         group: &str,
@@ -2074,6 +2131,10 @@ impl KanidmClient {
         self.perform_get_request("/v1/schema").await
     }
 
+    pub async fn idm_schema_syntax_list(&self) -> Result<Vec<SyntaxInfo>, ClientError> {
+        self.perform_get_request("/v1/schema/syntax").await
+    }
+
     pub async fn idm_schema_attributetype_list(&self) -> Result<Vec<Entry>, ClientError> {
         self.perform_get_request("/v1/schema/attributetype").await
     }
@@ -393,6 +393,36 @@ impl KanidmClient {
             .await
     }
 
+    pub async fn idm_oauth2_rs_enable_require_pushed_authorisation(
+        &self,
+        id: &str,
+    ) -> Result<(), ClientError> {
+        let mut update_oauth2_rs = Entry {
+            attrs: BTreeMap::new(),
+        };
+        update_oauth2_rs.attrs.insert(
+            Attribute::OAuth2RequirePushedAuthorisation.into(),
+            vec!["true".to_string()],
+        );
+        self.perform_patch_request(format!("/v1/oauth2/{id}").as_str(), update_oauth2_rs)
+            .await
+    }
+
+    pub async fn idm_oauth2_rs_disable_require_pushed_authorisation(
+        &self,
+        id: &str,
+    ) -> Result<(), ClientError> {
+        let mut update_oauth2_rs = Entry {
+            attrs: BTreeMap::new(),
+        };
+        update_oauth2_rs.attrs.insert(
+            Attribute::OAuth2RequirePushedAuthorisation.into(),
+            vec!["false".to_string()],
+        );
+        self.perform_patch_request(format!("/v1/oauth2/{id}").as_str(), update_oauth2_rs)
+            .await
+    }
+
     pub async fn idm_oauth2_rs_update_claim_map(
         &self,
         id: &str,
@@ -421,6 +451,30 @@ impl KanidmClient {
         .await
     }
 
+    pub async fn idm_oauth2_rs_update_claim_map_template(
+        &self,
+        id: &str,
+        claim_name: &str,
+        template: &str,
+    ) -> Result<(), ClientError> {
+        self.perform_post_request(
+            format!("/v1/oauth2/{id}/_claimmap_template/{claim_name}").as_str(),
+            template,
+        )
+        .await
+    }
+
+    pub async fn idm_oauth2_rs_delete_claim_map_template(
+        &self,
+        id: &str,
+        claim_name: &str,
+    ) -> Result<(), ClientError> {
+        self.perform_delete_request(
+            format!("/v1/oauth2/{id}/_claimmap_template/{claim_name}").as_str(),
+        )
+        .await
+    }
+
     pub async fn idm_oauth2_rs_delete_claim_map(
         &self,
         id: &str,
@@ -517,6 +571,34 @@ impl KanidmClient {
         }
     }
 
+    pub async fn idm_oauth2_rs_set_access_token_expiry(
+        &self,
+        id: &str,
+        expiry: Option<u32>,
+    ) -> Result<(), ClientError> {
+        match expiry {
+            Some(exp) => {
+                let mut update_oauth2_rs = Entry {
+                    attrs: BTreeMap::new(),
+                };
+                update_oauth2_rs.attrs.insert(
+                    Attribute::OAuth2AccessTokenExpiry.into(),
+                    vec![exp.to_string()],
+                );
+                self.perform_patch_request(format!("/v1/oauth2/{id}").as_str(), update_oauth2_rs)
+                    .await
+            }
+            None => {
+                self.perform_delete_request(&format!(
+                    "/v1/oauth2/{}/_attr/{}",
+                    id,
+                    Attribute::OAuth2AccessTokenExpiry.as_str()
+                ))
+                .await
+            }
+        }
+    }
+
     pub async fn idm_oauth2_rs_enable_consent_prompt(&self, id: &str) -> Result<(), ClientError> {
         let mut update_oauth2_rs = Entry {
             attrs: BTreeMap::new(),
@@ -1,6 +1,9 @@
 use crate::{ClientError, KanidmClient};
 use kanidm_proto::constants::*;
-use kanidm_proto::internal::{CredentialStatus, IdentifyUserRequest, IdentifyUserResponse};
+use kanidm_proto::internal::{
+    BatchCreateRequest, BatchCreateResponse, BatchCreateStatus, CredentialStatus,
+    IdentifyUserRequest, IdentifyUserResponse,
+};
 use kanidm_proto::v1::{AccountUnixExtend, Entry, SingleStringRequest, UatStatus};
 use std::collections::BTreeMap;
 use uuid::Uuid;
@@ -37,6 +40,52 @@ impl KanidmClient {
         self.perform_post_request("/v1/person", new_acct).await
     }
 
+    /// Create a person, applying the defaults and group memberships of a creation template.
+    pub async fn idm_person_account_create_from_template(
+        &self,
+        name: &str,
+        displayname: &str,
+        template: &str,
+    ) -> Result<(), ClientError> {
+        let mut new_acct = Entry {
+            attrs: BTreeMap::new(),
+        };
+        new_acct
+            .attrs
+            .insert(ATTR_NAME.to_string(), vec![name.to_string()]);
+        new_acct
+            .attrs
+            .insert(ATTR_DISPLAYNAME.to_string(), vec![displayname.to_string()]);
+        let template = urlencoding::encode(template);
+        self.perform_post_request(format!("/v1/person?template={template}").as_str(), new_acct)
+            .await
+    }
+
+    /// Create many persons, returning the outcome of each in order. The entries are sent in
+    /// chunks of at most [BATCH_CREATE_MAX_ENTRIES], unless `all_or_nothing` is set, in which
+    /// case they must all fit in one batch so that they are created in a single transaction.
+    pub async fn idm_person_account_create_batch(
+        &self,
+        entries: Vec<Entry>,
+        all_or_nothing: bool,
+    ) -> Result<Vec<BatchCreateStatus>, ClientError> {
+        if all_or_nothing {
+            let req = BatchCreateRequest::new(entries, true);
+            let resp: BatchCreateResponse =
+                self.perform_post_request("/v1/person/_batch", req).await?;
+            return Ok(resp.results);
+        }
+
+        let mut results = Vec::with_capacity(entries.len());
+        for chunk in entries.chunks(BATCH_CREATE_MAX_ENTRIES) {
+            let req = BatchCreateRequest::new(chunk.to_vec(), false);
+            let resp: BatchCreateResponse =
+                self.perform_post_request("/v1/person/_batch", req).await?;
+            results.extend(resp.results);
+        }
+        Ok(results)
+    }
+
     pub async fn idm_person_account_update(
         &self,
         id: &str,
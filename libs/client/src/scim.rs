@@ -1,7 +1,8 @@
 use crate::{ClientError, KanidmClient};
 use kanidm_proto::scim_v1::{
     client::{ScimEntryPostGeneric, ScimEntryPutGeneric, ScimListEntry},
-    ScimEntryGeneric, ScimEntryGetQuery, ScimSyncRequest, ScimSyncState,
+    ScimBulkRequest, ScimBulkResponse, ScimEntryGeneric, ScimEntryGetQuery, ScimSyncApplyResponse,
+    ScimSyncRequest, ScimSyncState,
 };
 
 impl KanidmClient {
@@ -12,7 +13,7 @@ impl KanidmClient {
     pub async fn scim_v1_sync_update(
         &self,
         scim_sync_request: &ScimSyncRequest,
-    ) -> Result<(), ClientError> {
+    ) -> Result<ScimSyncApplyResponse, ClientError> {
         self.perform_post_request("/scim/v1/Sync", scim_sync_request)
             .await
     }
@@ -64,4 +65,13 @@ impl KanidmClient {
         self.perform_delete_request(format!("/scim/v1/Entry/{id}").as_str())
             .await
     }
+
+    /// Submit a SCIM bulk request. The status of each operation is reported
+    /// individually in the response.
+    pub async fn scim_v1_bulk(
+        &self,
+        request: ScimBulkRequest,
+    ) -> Result<ScimBulkResponse, ClientError> {
+        self.perform_post_request("/scim/v1/Bulk", request).await
+    }
 }
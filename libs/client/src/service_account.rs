@@ -1,7 +1,7 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use kanidm_proto::constants::{ATTR_DISPLAYNAME, ATTR_ENTRY_MANAGED_BY, ATTR_MAIL, ATTR_NAME};
-use kanidm_proto::internal::{ApiToken, CredentialStatus};
+use kanidm_proto::internal::{ApiToken, ApiTokenOperationScope, CredentialStatus};
 use kanidm_proto::v1::{AccountUnixExtend, ApiTokenGenerate, Entry};
 use time::OffsetDateTime;
 use uuid::Uuid;
@@ -43,6 +43,37 @@ impl KanidmClient {
             .await
     }
 
+    /// Create a service account, applying the defaults and group memberships of a
+    /// creation template.
+    pub async fn idm_service_account_create_from_template(
+        &self,
+        name: &str,
+        displayname: &str,
+        entry_managed_by: &str,
+        template: &str,
+    ) -> Result<(), ClientError> {
+        let mut new_acct = Entry {
+            attrs: BTreeMap::new(),
+        };
+        new_acct
+            .attrs
+            .insert(ATTR_NAME.to_string(), vec![name.to_string()]);
+        new_acct
+            .attrs
+            .insert(ATTR_DISPLAYNAME.to_string(), vec![displayname.to_string()]);
+        new_acct.attrs.insert(
+            ATTR_ENTRY_MANAGED_BY.to_string(),
+            vec![entry_managed_by.to_string()],
+        );
+
+        let template = urlencoding::encode(template);
+        self.perform_post_request(
+            format!("/v1/service_account?template={template}").as_str(),
+            new_acct,
+        )
+        .await
+    }
+
     pub async fn idm_service_account_delete(&self, id: &str) -> Result<(), ClientError> {
         self.perform_delete_request(["/v1/service_account/", id].concat().as_str())
             .await
@@ -230,12 +261,35 @@ impl KanidmClient {
         expiry: Option<OffsetDateTime>,
         read_write: bool,
         compact: bool,
+    ) -> Result<String, ClientError> {
+        self.idm_service_account_generate_scoped_api_token(
+            id,
+            label,
+            expiry,
+            read_write,
+            BTreeSet::new(),
+            compact,
+        )
+        .await
+    }
+
+    /// Generate an api token that may only be used for operations within the provided
+    /// operation scopes. If no scopes are provided the token is unrestricted.
+    pub async fn idm_service_account_generate_scoped_api_token(
+        &self,
+        id: &str,
+        label: &str,
+        expiry: Option<OffsetDateTime>,
+        read_write: bool,
+        operation_scopes: BTreeSet<ApiTokenOperationScope>,
+        compact: bool,
     ) -> Result<String, ClientError> {
         let new_token = ApiTokenGenerate {
             label: label.to_string(),
             expiry,
             read_write,
             compact,
+            operation_scopes,
         };
         self.perform_post_request(
             format!("/v1/service_account/{id}/_api_token").as_str(),
@@ -0,0 +1,64 @@
+use crate::{ClientError, KanidmClient};
+use kanidm_proto::constants::{
+    ATTR_DESCRIPTION, ATTR_NAME, ATTR_WEBHOOK_EVENT, ATTR_WEBHOOK_FILTER, ATTR_WEBHOOK_URL,
+};
+use kanidm_proto::v1::Entry;
+use std::collections::BTreeMap;
+
+impl KanidmClient {
+    pub async fn idm_webhook_list(&self) -> Result<Vec<Entry>, ClientError> {
+        self.perform_get_request("/v1/webhook").await
+    }
+
+    pub async fn idm_webhook_get(&self, id: &str) -> Result<Option<Entry>, ClientError> {
+        self.perform_get_request(format!("/v1/webhook/{id}").as_str())
+            .await
+    }
+
+    /// Create a webhook subscription. The `filter` is a JSON filter limiting the entries
+    /// the webhook is notified about. The signing secret is generated by the server, and
+    /// can be read with [`KanidmClient::idm_webhook_get_secret`].
+    pub async fn idm_webhook_create(
+        &self,
+        name: &str,
+        url: &str,
+        events: &[&str],
+        filter: Option<&str>,
+        description: Option<&str>,
+    ) -> Result<(), ClientError> {
+        let mut new_webhook = Entry {
+            attrs: BTreeMap::new(),
+        };
+        new_webhook
+            .attrs
+            .insert(ATTR_NAME.to_string(), vec![name.to_string()]);
+        new_webhook
+            .attrs
+            .insert(ATTR_WEBHOOK_URL.to_string(), vec![url.to_string()]);
+        new_webhook.attrs.insert(
+            ATTR_WEBHOOK_EVENT.to_string(),
+            events.iter().map(|event| event.to_string()).collect(),
+        );
+        if let Some(filter) = filter {
+            new_webhook
+                .attrs
+                .insert(ATTR_WEBHOOK_FILTER.to_string(), vec![filter.to_string()]);
+        }
+        if let Some(description) = description {
+            new_webhook
+                .attrs
+                .insert(ATTR_DESCRIPTION.to_string(), vec![description.to_string()]);
+        }
+        self.perform_post_request("/v1/webhook", new_webhook).await
+    }
+
+    pub async fn idm_webhook_get_secret(&self, id: &str) -> Result<Option<String>, ClientError> {
+        self.perform_get_request(format!("/v1/webhook/{id}/_secret").as_str())
+            .await
+    }
+
+    pub async fn idm_webhook_delete(&self, id: &str) -> Result<(), ClientError> {
+        self.perform_delete_request(format!("/v1/webhook/{id}").as_str())
+            .await
+    }
+}
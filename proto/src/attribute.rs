@@ -30,27 +30,48 @@ pub enum Attribute {
     AcpReceiverGroup,
     AcpSearchAttr,
     AcpTargetScope,
+    AllowedValue,
     ApiTokenSession,
     ApplicationPassword,
+    ApplicationPasswordAutoRevoke,
+    ApplicationPasswordLimit,
     ApplicationUrl,
     AttestedPasskeys,
     #[default]
     Attr,
+    AttrExcludes,
     AttributeName,
     AttributeType,
+    Audit,
+    AuditActor,
+    AuditAttribute,
+    AuditOperation,
+    AuditTarget,
+    AuditTime,
     AuthSessionExpiry,
     AuthPasswordMinimumLength,
+    AuthPasswordMinimumScore,
+    AuthPasswordBreachCheck,
+    AuthPasswordHistoryLength,
+    AuthLockoutMaxAttempts,
+    AuthLockoutDuration,
+    AutomountInformation,
+    AutomountKey,
+    AutomountMapName,
     BadlistPassword,
     Certificate,
     CascadeDeleted,
     Claim,
     Class,
+    ClassKind,
     ClassName,
     Cn,
     CookiePrivateKey,
     CreatedAtCid,
     CredentialUpdateIntentToken,
     CredentialTypeMinimum,
+    CredentialLockout,
+    CredentialMaxAge,
     DeniedName,
     DeleteAfter,
     Description,
@@ -60,15 +81,19 @@ pub enum Attribute {
     Domain,
     DomainAllowEasterEggs,
     DomainAllowAccountRecovery,
+    DomainDenyMembershipCycles,
     DomainDevelopmentTaint,
     DomainDisplayName,
     DomainLdapBasedn,
     DomainName,
+    DomainRenameFrom,
+    DomainSelfWriteAttrs,
     DomainSsid,
     DomainTokenKey,
     DomainUuid,
     DynGroup,
     DynGroupFilter,
+    DynGroupFilterExclude,
     DynMember,
     Enabled,
     Email,
@@ -79,9 +104,12 @@ pub enum Attribute {
     EntryUuid,
     Es256PrivateKeyDer,
     Excludes,
+    ExclusionGroup,
     FernetPrivateKeyStr,
     Gecos,
     GidNumber,
+    GidNumberRange,
+    GidNumberRangeName,
     GrantUiHint,
     Group,
     HmacNameHistory,
@@ -117,6 +145,7 @@ pub enum Attribute {
     May,
     Member,
     MemberCreateOnce,
+    MemberExpiry,
     MemberOf,
     MessageTemplate,
     MultiValue,
@@ -126,6 +155,7 @@ pub enum Attribute {
     NoIndex,
     NsUniqueId,
     NsAccountLock,
+    OAuth2AccessTokenExpiry,
     OAuth2AllowInsecureClientDisablePkce,
     OAuth2AllowLocalhostRedirect,
     OAuth2AuthorisationEndpoint,
@@ -147,6 +177,7 @@ pub enum Attribute {
     OAuth2RsSupScopeMap,
     OAuth2RsTokenKey,
     OAuth2Session,
+    OAuth2RequirePushedAuthorisation,
     OAuth2StrictRedirectUri,
     OAuth2TokenEndpoint,
     OAuth2AccountCredentialUuid,
@@ -154,8 +185,11 @@ pub enum Attribute {
     OAuth2AccountUniqueUserId,
     OAuth2ConsentPromptEnable,
     ObjectClass,
+    OnDelete,
     OtherNoIndex,
     PassKeys,
+    PasskeyUsage,
+    PasswordHistory,
     PasswordImport,
     PasswordChangedTime,
     PatchLevel,
@@ -164,9 +198,12 @@ pub enum Attribute {
     PrivateCookieKey,
     PrivilegeExpiry,
     RadiusSecret,
+    RadiusVlanId,
+    RadiusVlanPriority,
     RecycledDirectMemberOf,
     Refers,
     Replicated,
+    RequiresOneOf,
     Rs256PrivateKeyDer,
     S256,
     /// A set of scim schemas. This is similar to a kanidm class.
@@ -196,11 +233,17 @@ pub enum Attribute {
     SystemExcludes,
     SystemMay,
     SystemMust,
+    TemplateDefault,
+    TemplateGroup,
     Term,
     TotpImport,
     Uid,
     UidNumber,
     Unique,
+    UniqueScope,
+    UnixDefaultShell,
+    UnixHomeDirectory,
+    UnixHomeDirectoryTemplate,
     UnixPassword,
     UnixPasswordImport,
     UserAuthTokenSession,
@@ -209,6 +252,11 @@ pub enum Attribute {
     Uuid,
     Version,
     WebauthnAttestationCaList,
+    WebhookAuthor,
+    WebhookEvent,
+    WebhookFilter,
+    WebhookSecret,
+    WebhookUrl,
     AllowPrimaryCredFallback,
 
     #[cfg(any(debug_assertions, test, feature = "test"))]
@@ -306,27 +354,48 @@ impl Attribute {
             Attribute::AcpReceiverGroup => ATTR_ACP_RECEIVER_GROUP,
             Attribute::AcpSearchAttr => ATTR_ACP_SEARCH_ATTR,
             Attribute::AcpTargetScope => ATTR_ACP_TARGET_SCOPE,
+            Attribute::AllowedValue => ATTR_ALLOWED_VALUE,
             Attribute::AllowPrimaryCredFallback => ATTR_ALLOW_PRIMARY_CRED_FALLBACK,
             Attribute::ApiTokenSession => ATTR_API_TOKEN_SESSION,
             Attribute::ApplicationPassword => ATTR_APPLICATION_PASSWORD,
+            Attribute::ApplicationPasswordAutoRevoke => ATTR_APPLICATION_PASSWORD_AUTO_REVOKE,
+            Attribute::ApplicationPasswordLimit => ATTR_APPLICATION_PASSWORD_LIMIT,
             Attribute::ApplicationUrl => ATTR_APPLICATION_URL,
             Attribute::AttestedPasskeys => ATTR_ATTESTED_PASSKEYS,
             Attribute::Attr => ATTR_ATTR,
+            Attribute::AttrExcludes => ATTR_ATTR_EXCLUDES,
             Attribute::AttributeName => ATTR_ATTRIBUTENAME,
             Attribute::AttributeType => ATTR_ATTRIBUTETYPE,
+            Attribute::Audit => ATTR_AUDIT,
+            Attribute::AuditActor => ATTR_AUDIT_ACTOR,
+            Attribute::AuditAttribute => ATTR_AUDIT_ATTRIBUTE,
+            Attribute::AuditOperation => ATTR_AUDIT_OPERATION,
+            Attribute::AuditTarget => ATTR_AUDIT_TARGET,
+            Attribute::AuditTime => ATTR_AUDIT_TIME,
             Attribute::AuthSessionExpiry => ATTR_AUTH_SESSION_EXPIRY,
             Attribute::AuthPasswordMinimumLength => ATTR_AUTH_PASSWORD_MINIMUM_LENGTH,
+            Attribute::AuthPasswordMinimumScore => ATTR_AUTH_PASSWORD_MINIMUM_SCORE,
+            Attribute::AuthPasswordBreachCheck => ATTR_AUTH_PASSWORD_BREACH_CHECK,
+            Attribute::AuthPasswordHistoryLength => ATTR_AUTH_PASSWORD_HISTORY_LENGTH,
+            Attribute::AuthLockoutMaxAttempts => ATTR_AUTH_LOCKOUT_MAX_ATTEMPTS,
+            Attribute::AuthLockoutDuration => ATTR_AUTH_LOCKOUT_DURATION,
+            Attribute::AutomountInformation => ATTR_AUTOMOUNT_INFORMATION,
+            Attribute::AutomountKey => ATTR_AUTOMOUNT_KEY,
+            Attribute::AutomountMapName => ATTR_AUTOMOUNT_MAP_NAME,
             Attribute::BadlistPassword => ATTR_BADLIST_PASSWORD,
             Attribute::Certificate => ATTR_CERTIFICATE,
             Attribute::CascadeDeleted => ATTR_CASCADE_DELETED,
             Attribute::Claim => ATTR_CLAIM,
             Attribute::Class => ATTR_CLASS,
+            Attribute::ClassKind => ATTR_CLASS_KIND,
             Attribute::ClassName => ATTR_CLASSNAME,
             Attribute::Cn => ATTR_CN,
             Attribute::CookiePrivateKey => ATTR_COOKIE_PRIVATE_KEY,
             Attribute::CreatedAtCid => ATTR_CREATED_AT_CID,
             Attribute::CredentialUpdateIntentToken => ATTR_CREDENTIAL_UPDATE_INTENT_TOKEN,
             Attribute::CredentialTypeMinimum => ATTR_CREDENTIAL_TYPE_MINIMUM,
+            Attribute::CredentialLockout => ATTR_CREDENTIAL_LOCKOUT,
+            Attribute::CredentialMaxAge => ATTR_CREDENTIAL_MAX_AGE,
             Attribute::DeniedName => ATTR_DENIED_NAME,
             Attribute::DeleteAfter => ATTR_DELETE_AFTER,
             Attribute::Description => ATTR_DESCRIPTION,
@@ -336,15 +405,19 @@ impl Attribute {
             Attribute::Domain => ATTR_DOMAIN,
             Attribute::DomainAllowEasterEggs => ATTR_DOMAIN_ALLOW_EASTER_EGGS,
             Attribute::DomainAllowAccountRecovery => ATTR_DOMAIN_ALLOW_ACCOUNT_RECOVERY,
+            Attribute::DomainDenyMembershipCycles => ATTR_DOMAIN_DENY_MEMBERSHIP_CYCLES,
             Attribute::DomainDevelopmentTaint => ATTR_DOMAIN_DEVELOPMENT_TAINT,
             Attribute::DomainDisplayName => ATTR_DOMAIN_DISPLAY_NAME,
             Attribute::DomainLdapBasedn => ATTR_DOMAIN_LDAP_BASEDN,
             Attribute::DomainName => ATTR_DOMAIN_NAME,
+            Attribute::DomainRenameFrom => ATTR_DOMAIN_RENAME_FROM,
+            Attribute::DomainSelfWriteAttrs => ATTR_DOMAIN_SELF_WRITE_ATTRS,
             Attribute::DomainSsid => ATTR_DOMAIN_SSID,
             Attribute::DomainTokenKey => ATTR_DOMAIN_TOKEN_KEY,
             Attribute::DomainUuid => ATTR_DOMAIN_UUID,
             Attribute::DynGroup => ATTR_DYNGROUP,
             Attribute::DynGroupFilter => ATTR_DYNGROUP_FILTER,
+            Attribute::DynGroupFilterExclude => ATTR_DYNGROUP_FILTER_EXCLUDE,
             Attribute::DynMember => ATTR_DYNMEMBER,
             Attribute::Enabled => ATTR_ENABLED,
             Attribute::Email => ATTR_EMAIL,
@@ -355,9 +428,12 @@ impl Attribute {
             Attribute::EntryUuid => ATTR_ENTRYUUID,
             Attribute::Es256PrivateKeyDer => ATTR_ES256_PRIVATE_KEY_DER,
             Attribute::Excludes => ATTR_EXCLUDES,
+            Attribute::ExclusionGroup => ATTR_EXCLUSION_GROUP,
             Attribute::FernetPrivateKeyStr => ATTR_FERNET_PRIVATE_KEY_STR,
             Attribute::Gecos => ATTR_GECOS,
             Attribute::GidNumber => ATTR_GIDNUMBER,
+            Attribute::GidNumberRange => ATTR_GID_NUMBER_RANGE,
+            Attribute::GidNumberRangeName => ATTR_GID_NUMBER_RANGE_NAME,
             Attribute::GrantUiHint => ATTR_GRANT_UI_HINT,
             Attribute::Group => ATTR_GROUP,
             Attribute::HmacNameHistory => ATTR_HMAC_NAME_HISTORY,
@@ -392,6 +468,7 @@ impl Attribute {
             Attribute::May => ATTR_MAY,
             Attribute::Member => ATTR_MEMBER,
             Attribute::MemberCreateOnce => ATTR_MEMBER_CREATE_ONCE,
+            Attribute::MemberExpiry => ATTR_MEMBER_EXPIRY,
             Attribute::MemberOf => ATTR_MEMBEROF,
             Attribute::MessageTemplate => ATTR_MESSAGE_TEMPLATE,
             Attribute::MultiValue => ATTR_MULTIVALUE,
@@ -401,6 +478,7 @@ impl Attribute {
             Attribute::NoIndex => ATTR_NO_INDEX,
             Attribute::NsUniqueId => ATTR_NSUNIQUEID,
             Attribute::NsAccountLock => ATTR_NSACCOUNTLOCK,
+            Attribute::OAuth2AccessTokenExpiry => ATTR_OAUTH2_ACCESS_TOKEN_EXPIRY,
             Attribute::OAuth2AllowInsecureClientDisablePkce => {
                 ATTR_OAUTH2_ALLOW_INSECURE_CLIENT_DISABLE_PKCE
             }
@@ -424,6 +502,7 @@ impl Attribute {
             Attribute::OAuth2RsSupScopeMap => ATTR_OAUTH2_RS_SUP_SCOPE_MAP,
             Attribute::OAuth2RsTokenKey => ATTR_OAUTH2_RS_TOKEN_KEY,
             Attribute::OAuth2Session => ATTR_OAUTH2_SESSION,
+            Attribute::OAuth2RequirePushedAuthorisation => ATTR_OAUTH2_REQUIRE_PUSHED_AUTHORISATION,
             Attribute::OAuth2StrictRedirectUri => ATTR_OAUTH2_STRICT_REDIRECT_URI,
             Attribute::OAuth2TokenEndpoint => ATTR_OAUTH2_TOKEN_ENDPOINT,
             Attribute::OAuth2AccountCredentialUuid => ATTR_OAUTH2_ACCOUNT_CREDENTIAL_UUID,
@@ -431,9 +510,12 @@ impl Attribute {
             Attribute::OAuth2AccountUniqueUserId => ATTR_OAUTH2_ACCOUNT_UNIQUE_USER_ID,
             Attribute::OAuth2ConsentPromptEnable => ATTR_OAUTH2_CONSENT_PROMPT_ENABLE,
             Attribute::ObjectClass => ATTR_OBJECTCLASS,
+            Attribute::OnDelete => ATTR_ON_DELETE,
             Attribute::OtherNoIndex => ATTR_OTHER_NO_INDEX,
             Attribute::PassKeys => ATTR_PASSKEYS,
+            Attribute::PasskeyUsage => ATTR_PASSKEY_USAGE,
             Attribute::PasswordChangedTime => ATTR_PWD_CHANGED_TIME,
+            Attribute::PasswordHistory => ATTR_PASSWORD_HISTORY,
             Attribute::PasswordImport => ATTR_PASSWORD_IMPORT,
             Attribute::PatchLevel => ATTR_PATCH_LEVEL,
             Attribute::Phantom => ATTR_PHANTOM,
@@ -441,9 +523,12 @@ impl Attribute {
             Attribute::PrivateCookieKey => ATTR_PRIVATE_COOKIE_KEY,
             Attribute::PrivilegeExpiry => ATTR_PRIVILEGE_EXPIRY,
             Attribute::RadiusSecret => ATTR_RADIUS_SECRET,
+            Attribute::RadiusVlanId => ATTR_RADIUS_VLAN_ID,
+            Attribute::RadiusVlanPriority => ATTR_RADIUS_VLAN_PRIORITY,
             Attribute::RecycledDirectMemberOf => ATTR_RECYCLEDDIRECTMEMBEROF,
             Attribute::Refers => ATTR_REFERS,
             Attribute::Replicated => ATTR_REPLICATED,
+            Attribute::RequiresOneOf => ATTR_REQUIRES_ONE_OF,
             Attribute::Rs256PrivateKeyDer => ATTR_RS256_PRIVATE_KEY_DER,
             Attribute::S256 => ATTR_S256,
             Attribute::Scope => ATTR_SCOPE,
@@ -468,11 +553,17 @@ impl Attribute {
             Attribute::SystemMay => ATTR_SYSTEMMAY,
             Attribute::SystemMust => ATTR_SYSTEMMUST,
             Attribute::SystemSupplements => ATTR_SYSTEMSUPPLEMENTS,
+            Attribute::TemplateDefault => ATTR_TEMPLATE_DEFAULT,
+            Attribute::TemplateGroup => ATTR_TEMPLATE_GROUP,
             Attribute::Term => ATTR_TERM,
             Attribute::TotpImport => ATTR_TOTP_IMPORT,
             Attribute::Uid => ATTR_UID,
             Attribute::UidNumber => ATTR_UIDNUMBER,
             Attribute::Unique => ATTR_UNIQUE,
+            Attribute::UniqueScope => ATTR_UNIQUE_SCOPE,
+            Attribute::UnixDefaultShell => ATTR_UNIX_DEFAULT_SHELL,
+            Attribute::UnixHomeDirectory => ATTR_UNIX_HOME_DIRECTORY,
+            Attribute::UnixHomeDirectoryTemplate => ATTR_UNIX_HOME_DIRECTORY_TEMPLATE,
             Attribute::UnixPassword => ATTR_UNIX_PASSWORD,
             Attribute::UnixPasswordImport => ATTR_UNIX_PASSWORD_IMPORT,
             Attribute::UserAuthTokenSession => ATTR_USER_AUTH_TOKEN_SESSION,
@@ -481,6 +572,11 @@ impl Attribute {
             Attribute::Uuid => ATTR_UUID,
             Attribute::Version => ATTR_VERSION,
             Attribute::WebauthnAttestationCaList => ATTR_WEBAUTHN_ATTESTATION_CA_LIST,
+            Attribute::WebhookAuthor => ATTR_WEBHOOK_AUTHOR,
+            Attribute::WebhookEvent => ATTR_WEBHOOK_EVENT,
+            Attribute::WebhookFilter => ATTR_WEBHOOK_FILTER,
+            Attribute::WebhookSecret => ATTR_WEBHOOK_SECRET,
+            Attribute::WebhookUrl => ATTR_WEBHOOK_URL,
 
             #[cfg(any(debug_assertions, test, feature = "test"))]
             Attribute::NonExist => TEST_ATTR_NON_EXIST,
@@ -530,27 +626,48 @@ impl Attribute {
             ATTR_ACP_RECEIVER_GROUP => Attribute::AcpReceiverGroup,
             ATTR_ACP_SEARCH_ATTR => Attribute::AcpSearchAttr,
             ATTR_ACP_TARGET_SCOPE => Attribute::AcpTargetScope,
+            ATTR_ALLOWED_VALUE => Attribute::AllowedValue,
             ATTR_ALLOW_PRIMARY_CRED_FALLBACK => Attribute::AllowPrimaryCredFallback,
             ATTR_API_TOKEN_SESSION => Attribute::ApiTokenSession,
             ATTR_APPLICATION_PASSWORD => Attribute::ApplicationPassword,
+            ATTR_APPLICATION_PASSWORD_AUTO_REVOKE => Attribute::ApplicationPasswordAutoRevoke,
+            ATTR_APPLICATION_PASSWORD_LIMIT => Attribute::ApplicationPasswordLimit,
             ATTR_APPLICATION_URL => Attribute::ApplicationUrl,
             ATTR_ATTESTED_PASSKEYS => Attribute::AttestedPasskeys,
             ATTR_ATTR => Attribute::Attr,
+            ATTR_ATTR_EXCLUDES => Attribute::AttrExcludes,
             ATTR_ATTRIBUTENAME => Attribute::AttributeName,
             ATTR_ATTRIBUTETYPE => Attribute::AttributeType,
+            ATTR_AUDIT => Attribute::Audit,
+            ATTR_AUDIT_ACTOR => Attribute::AuditActor,
+            ATTR_AUDIT_ATTRIBUTE => Attribute::AuditAttribute,
+            ATTR_AUDIT_OPERATION => Attribute::AuditOperation,
+            ATTR_AUDIT_TARGET => Attribute::AuditTarget,
+            ATTR_AUDIT_TIME => Attribute::AuditTime,
             ATTR_AUTH_SESSION_EXPIRY => Attribute::AuthSessionExpiry,
             ATTR_AUTH_PASSWORD_MINIMUM_LENGTH => Attribute::AuthPasswordMinimumLength,
+            ATTR_AUTH_PASSWORD_MINIMUM_SCORE => Attribute::AuthPasswordMinimumScore,
+            ATTR_AUTH_PASSWORD_BREACH_CHECK => Attribute::AuthPasswordBreachCheck,
+            ATTR_AUTH_PASSWORD_HISTORY_LENGTH => Attribute::AuthPasswordHistoryLength,
+            ATTR_AUTH_LOCKOUT_MAX_ATTEMPTS => Attribute::AuthLockoutMaxAttempts,
+            ATTR_AUTH_LOCKOUT_DURATION => Attribute::AuthLockoutDuration,
+            ATTR_AUTOMOUNT_INFORMATION => Attribute::AutomountInformation,
+            ATTR_AUTOMOUNT_KEY => Attribute::AutomountKey,
+            ATTR_AUTOMOUNT_MAP_NAME => Attribute::AutomountMapName,
             ATTR_BADLIST_PASSWORD => Attribute::BadlistPassword,
             ATTR_CERTIFICATE => Attribute::Certificate,
             ATTR_CASCADE_DELETED => Attribute::CascadeDeleted,
             ATTR_CLAIM => Attribute::Claim,
             ATTR_CLASS => Attribute::Class,
+            ATTR_CLASS_KIND => Attribute::ClassKind,
             ATTR_CLASSNAME => Attribute::ClassName,
             ATTR_CN => Attribute::Cn,
             ATTR_COOKIE_PRIVATE_KEY => Attribute::CookiePrivateKey,
             ATTR_CREATED_AT_CID => Attribute::CreatedAtCid,
             ATTR_CREDENTIAL_UPDATE_INTENT_TOKEN => Attribute::CredentialUpdateIntentToken,
             ATTR_CREDENTIAL_TYPE_MINIMUM => Attribute::CredentialTypeMinimum,
+            ATTR_CREDENTIAL_LOCKOUT => Attribute::CredentialLockout,
+            ATTR_CREDENTIAL_MAX_AGE => Attribute::CredentialMaxAge,
             ATTR_DENIED_NAME => Attribute::DeniedName,
             ATTR_DELETE_AFTER => Attribute::DeleteAfter,
             ATTR_DESCRIPTION => Attribute::Description,
@@ -560,15 +677,19 @@ impl Attribute {
             ATTR_DOMAIN => Attribute::Domain,
             ATTR_DOMAIN_ALLOW_EASTER_EGGS => Attribute::DomainAllowEasterEggs,
             ATTR_DOMAIN_ALLOW_ACCOUNT_RECOVERY => Attribute::DomainAllowAccountRecovery,
+            ATTR_DOMAIN_DENY_MEMBERSHIP_CYCLES => Attribute::DomainDenyMembershipCycles,
             ATTR_DOMAIN_DISPLAY_NAME => Attribute::DomainDisplayName,
             ATTR_DOMAIN_DEVELOPMENT_TAINT => Attribute::DomainDevelopmentTaint,
             ATTR_DOMAIN_LDAP_BASEDN => Attribute::DomainLdapBasedn,
             ATTR_DOMAIN_NAME => Attribute::DomainName,
+            ATTR_DOMAIN_RENAME_FROM => Attribute::DomainRenameFrom,
+            ATTR_DOMAIN_SELF_WRITE_ATTRS => Attribute::DomainSelfWriteAttrs,
             ATTR_DOMAIN_SSID => Attribute::DomainSsid,
             ATTR_DOMAIN_TOKEN_KEY => Attribute::DomainTokenKey,
             ATTR_DOMAIN_UUID => Attribute::DomainUuid,
             ATTR_DYNGROUP => Attribute::DynGroup,
             ATTR_DYNGROUP_FILTER => Attribute::DynGroupFilter,
+            ATTR_DYNGROUP_FILTER_EXCLUDE => Attribute::DynGroupFilterExclude,
             ATTR_DYNMEMBER => Attribute::DynMember,
             ATTR_ENABLED => Attribute::Enabled,
             ATTR_EMAIL => Attribute::Email,
@@ -579,9 +700,12 @@ impl Attribute {
             ATTR_ENTRYUUID => Attribute::EntryUuid,
             ATTR_ES256_PRIVATE_KEY_DER => Attribute::Es256PrivateKeyDer,
             ATTR_EXCLUDES => Attribute::Excludes,
+            ATTR_EXCLUSION_GROUP => Attribute::ExclusionGroup,
             ATTR_FERNET_PRIVATE_KEY_STR => Attribute::FernetPrivateKeyStr,
             ATTR_GECOS => Attribute::Gecos,
             ATTR_GIDNUMBER => Attribute::GidNumber,
+            ATTR_GID_NUMBER_RANGE => Attribute::GidNumberRange,
+            ATTR_GID_NUMBER_RANGE_NAME => Attribute::GidNumberRangeName,
             ATTR_GRANT_UI_HINT => Attribute::GrantUiHint,
             ATTR_GROUP => Attribute::Group,
             ATTR_HMAC_NAME_HISTORY => Attribute::HmacNameHistory,
@@ -616,6 +740,7 @@ impl Attribute {
             ATTR_MAY => Attribute::May,
             ATTR_MEMBER => Attribute::Member,
             ATTR_MEMBER_CREATE_ONCE => Attribute::MemberCreateOnce,
+            ATTR_MEMBER_EXPIRY => Attribute::MemberExpiry,
             ATTR_MEMBEROF => Attribute::MemberOf,
             ATTR_MESSAGE_TEMPLATE => Attribute::MessageTemplate,
             ATTR_MULTIVALUE => Attribute::MultiValue,
@@ -625,6 +750,7 @@ impl Attribute {
             ATTR_NO_INDEX => Attribute::NoIndex,
             ATTR_NSUNIQUEID => Attribute::NsUniqueId,
             ATTR_NSACCOUNTLOCK => Attribute::NsAccountLock,
+            ATTR_OAUTH2_ACCESS_TOKEN_EXPIRY => Attribute::OAuth2AccessTokenExpiry,
             ATTR_OAUTH2_ALLOW_INSECURE_CLIENT_DISABLE_PKCE => {
                 Attribute::OAuth2AllowInsecureClientDisablePkce
             }
@@ -648,6 +774,7 @@ impl Attribute {
             ATTR_OAUTH2_RS_SUP_SCOPE_MAP => Attribute::OAuth2RsSupScopeMap,
             ATTR_OAUTH2_RS_TOKEN_KEY => Attribute::OAuth2RsTokenKey,
             ATTR_OAUTH2_SESSION => Attribute::OAuth2Session,
+            ATTR_OAUTH2_REQUIRE_PUSHED_AUTHORISATION => Attribute::OAuth2RequirePushedAuthorisation,
             ATTR_OAUTH2_STRICT_REDIRECT_URI => Attribute::OAuth2StrictRedirectUri,
             ATTR_OAUTH2_TOKEN_ENDPOINT => Attribute::OAuth2TokenEndpoint,
             ATTR_OAUTH2_ACCOUNT_CREDENTIAL_UUID => Attribute::OAuth2AccountCredentialUuid,
@@ -655,8 +782,11 @@ impl Attribute {
             ATTR_OAUTH2_ACCOUNT_UNIQUE_USER_ID => Attribute::OAuth2AccountUniqueUserId,
             ATTR_OAUTH2_CONSENT_PROMPT_ENABLE => Attribute::OAuth2ConsentPromptEnable,
             ATTR_OBJECTCLASS => Attribute::ObjectClass,
+            ATTR_ON_DELETE => Attribute::OnDelete,
             ATTR_OTHER_NO_INDEX => Attribute::OtherNoIndex,
             ATTR_PASSKEYS => Attribute::PassKeys,
+            ATTR_PASSKEY_USAGE => Attribute::PasskeyUsage,
+            ATTR_PASSWORD_HISTORY => Attribute::PasswordHistory,
             ATTR_PASSWORD_IMPORT => Attribute::PasswordImport,
             ATTR_PATCH_LEVEL => Attribute::PatchLevel,
             ATTR_PHANTOM => Attribute::Phantom,
@@ -665,9 +795,12 @@ impl Attribute {
             ATTR_PRIVILEGE_EXPIRY => Attribute::PrivilegeExpiry,
             ATTR_PWD_CHANGED_TIME => Attribute::PasswordChangedTime,
             ATTR_RADIUS_SECRET => Attribute::RadiusSecret,
+            ATTR_RADIUS_VLAN_ID => Attribute::RadiusVlanId,
+            ATTR_RADIUS_VLAN_PRIORITY => Attribute::RadiusVlanPriority,
             ATTR_RECYCLEDDIRECTMEMBEROF => Attribute::RecycledDirectMemberOf,
             ATTR_REFERS => Attribute::Refers,
             ATTR_REPLICATED => Attribute::Replicated,
+            ATTR_REQUIRES_ONE_OF => Attribute::RequiresOneOf,
             ATTR_RS256_PRIVATE_KEY_DER => Attribute::Rs256PrivateKeyDer,
             ATTR_S256 => Attribute::S256,
             ATTR_SCIM_SCHEMAS => Attribute::ScimSchemas,
@@ -692,11 +825,17 @@ impl Attribute {
             ATTR_SYSTEMMAY => Attribute::SystemMay,
             ATTR_SYSTEMMUST => Attribute::SystemMust,
             ATTR_SYSTEMSUPPLEMENTS => Attribute::SystemSupplements,
+            ATTR_TEMPLATE_DEFAULT => Attribute::TemplateDefault,
+            ATTR_TEMPLATE_GROUP => Attribute::TemplateGroup,
             ATTR_TERM => Attribute::Term,
             ATTR_TOTP_IMPORT => Attribute::TotpImport,
             ATTR_UID => Attribute::Uid,
             ATTR_UIDNUMBER => Attribute::UidNumber,
             ATTR_UNIQUE => Attribute::Unique,
+            ATTR_UNIQUE_SCOPE => Attribute::UniqueScope,
+            ATTR_UNIX_DEFAULT_SHELL => Attribute::UnixDefaultShell,
+            ATTR_UNIX_HOME_DIRECTORY => Attribute::UnixHomeDirectory,
+            ATTR_UNIX_HOME_DIRECTORY_TEMPLATE => Attribute::UnixHomeDirectoryTemplate,
             ATTR_UNIX_PASSWORD => Attribute::UnixPassword,
             ATTR_UNIX_PASSWORD_IMPORT => Attribute::UnixPasswordImport,
             ATTR_USER_AUTH_TOKEN_SESSION => Attribute::UserAuthTokenSession,
@@ -705,6 +844,11 @@ impl Attribute {
             ATTR_UUID => Attribute::Uuid,
             ATTR_VERSION => Attribute::Version,
             ATTR_WEBAUTHN_ATTESTATION_CA_LIST => Attribute::WebauthnAttestationCaList,
+            ATTR_WEBHOOK_AUTHOR => Attribute::WebhookAuthor,
+            ATTR_WEBHOOK_EVENT => Attribute::WebhookEvent,
+            ATTR_WEBHOOK_FILTER => Attribute::WebhookFilter,
+            ATTR_WEBHOOK_SECRET => Attribute::WebhookSecret,
+            ATTR_WEBHOOK_URL => Attribute::WebhookUrl,
 
             #[cfg(any(debug_assertions, test, feature = "test"))]
             TEST_ATTR_NON_EXIST => Attribute::NonExist,
@@ -41,6 +41,8 @@ pub const DEFAULT_SERVER_LOCALHOST: &str = "localhost:8443";
 pub const DEFAULT_LDAP_LOCALHOST: &str = "localhost:636";
 /// The default amount of attributes that can be queried in LDAP
 pub const DEFAULT_LDAP_MAXIMUM_QUERYABLE_ATTRIBUTES: usize = 48;
+/// The maximum number of entries that can be created in a single batch
+pub const BATCH_CREATE_MAX_ENTRIES: usize = 1000;
 /// Default replication configuration
 pub const DEFAULT_REPLICATION_ADDRESS: &str = "127.0.0.1:8444";
 pub const DEFAULT_REPLICATION_ORIGIN: &str = "repl://localhost:8444";
@@ -72,26 +74,47 @@ pub const ATTR_ACP_RECEIVER_GROUP: &str = "acp_receiver_group";
 pub const ATTR_ACP_RECEIVER: &str = "acp_receiver";
 pub const ATTR_ACP_SEARCH_ATTR: &str = "acp_search_attr";
 pub const ATTR_ACP_TARGET_SCOPE: &str = "acp_targetscope";
+pub const ATTR_ALLOWED_VALUE: &str = "allowedvalue";
 pub const ATTR_API_TOKEN_SESSION: &str = "api_token_session";
 pub const ATTR_APPLICATION_PASSWORD: &str = "application_password";
+pub const ATTR_APPLICATION_PASSWORD_AUTO_REVOKE: &str = "application_password_auto_revoke";
+pub const ATTR_APPLICATION_PASSWORD_LIMIT: &str = "application_password_limit";
 pub const ATTR_APPLICATION_URL: &str = "application_url";
 pub const ATTR_ATTESTED_PASSKEYS: &str = "attested_passkeys";
 pub const ATTR_ATTR: &str = "attr";
+pub const ATTR_ATTR_EXCLUDES: &str = "attrexcludes";
 pub const ATTR_ATTRIBUTENAME: &str = "attributename";
 pub const ATTR_ATTRIBUTETYPE: &str = "attributetype";
+pub const ATTR_AUDIT: &str = "audit";
+pub const ATTR_AUDIT_ACTOR: &str = "audit_actor";
+pub const ATTR_AUDIT_ATTRIBUTE: &str = "audit_attribute";
+pub const ATTR_AUDIT_OPERATION: &str = "audit_operation";
+pub const ATTR_AUDIT_TARGET: &str = "audit_target";
+pub const ATTR_AUDIT_TIME: &str = "audit_time";
 pub const ATTR_AUTH_SESSION_EXPIRY: &str = "authsession_expiry";
 pub const ATTR_AUTH_PASSWORD_MINIMUM_LENGTH: &str = "auth_password_minimum_length";
+pub const ATTR_AUTH_PASSWORD_MINIMUM_SCORE: &str = "auth_password_minimum_score";
+pub const ATTR_AUTH_PASSWORD_BREACH_CHECK: &str = "auth_password_breach_check";
+pub const ATTR_AUTH_PASSWORD_HISTORY_LENGTH: &str = "auth_password_history_length";
+pub const ATTR_AUTH_LOCKOUT_MAX_ATTEMPTS: &str = "auth_lockout_max_attempts";
+pub const ATTR_AUTH_LOCKOUT_DURATION: &str = "auth_lockout_duration";
+pub const ATTR_AUTOMOUNT_INFORMATION: &str = "automountinformation";
+pub const ATTR_AUTOMOUNT_KEY: &str = "automountkey";
+pub const ATTR_AUTOMOUNT_MAP_NAME: &str = "automountmapname";
 pub const ATTR_BADLIST_PASSWORD: &str = "badlist_password";
 pub const ATTR_CASCADE_DELETED: &str = "cascade_deleted";
 pub const ATTR_CERTIFICATE: &str = "certificate";
 pub const ATTR_CLAIM: &str = "claim";
 pub const ATTR_CLASS: &str = "class";
+pub const ATTR_CLASS_KIND: &str = "classkind";
 pub const ATTR_CLASSNAME: &str = "classname";
 pub const ATTR_CN: &str = "cn";
 pub const ATTR_COOKIE_PRIVATE_KEY: &str = "cookie_private_key";
 pub const ATTR_CREATED_AT_CID: &str = "created_at_cid";
 pub const ATTR_CREDENTIAL_UPDATE_INTENT_TOKEN: &str = "credential_update_intent_token";
 pub const ATTR_CREDENTIAL_TYPE_MINIMUM: &str = "credential_type_minimum";
+pub const ATTR_CREDENTIAL_LOCKOUT: &str = "credential_lockout";
+pub const ATTR_CREDENTIAL_MAX_AGE: &str = "credential_max_age";
 pub const ATTR_DENIED_NAME: &str = "denied_name";
 pub const ATTR_DESCRIPTION: &str = "description";
 pub const ATTR_DIRECTMEMBEROF: &str = "directmemberof";
@@ -99,15 +122,19 @@ pub const ATTR_DISPLAYNAME: &str = "displayname";
 pub const ATTR_DN: &str = "dn";
 pub const ATTR_DOMAIN_ALLOW_EASTER_EGGS: &str = "domain_allow_easter_eggs";
 pub const ATTR_DOMAIN_ALLOW_ACCOUNT_RECOVERY: &str = "domain_allow_account_recovery";
+pub const ATTR_DOMAIN_DENY_MEMBERSHIP_CYCLES: &str = "domain_deny_membership_cycles";
 pub const ATTR_DOMAIN_DEVELOPMENT_TAINT: &str = "domain_development_taint";
 pub const ATTR_DOMAIN_DISPLAY_NAME: &str = "domain_display_name";
 pub const ATTR_DOMAIN_LDAP_BASEDN: &str = "domain_ldap_basedn";
 pub const ATTR_DOMAIN_NAME: &str = "domain_name";
+pub const ATTR_DOMAIN_RENAME_FROM: &str = "domain_rename_from";
+pub const ATTR_DOMAIN_SELF_WRITE_ATTRS: &str = "domain_self_write_attrs";
 pub const ATTR_DOMAIN_SSID: &str = "domain_ssid";
 pub const ATTR_DOMAIN_TOKEN_KEY: &str = "domain_token_key";
 pub const ATTR_DOMAIN_UUID: &str = "domain_uuid";
 pub const ATTR_DOMAIN: &str = "domain";
 pub const ATTR_DYNGROUP_FILTER: &str = "dyngroup_filter";
+pub const ATTR_DYNGROUP_FILTER_EXCLUDE: &str = "dyngroup_filter_exclude";
 pub const ATTR_DYNGROUP: &str = "dyngroup";
 pub const ATTR_DYNMEMBER: &str = "dynmember";
 pub const ATTR_ENABLED: &str = "enabled";
@@ -123,10 +150,13 @@ pub const ATTR_LDAP_KEYS: &str = "keys";
 pub const ATTR_LIMIT_SEARCH_MAX_RESULTS: &str = "limit_search_max_results";
 pub const ATTR_LIMIT_SEARCH_MAX_FILTER_TEST: &str = "limit_search_max_filter_test";
 pub const ATTR_EXCLUDES: &str = "excludes";
+pub const ATTR_EXCLUSION_GROUP: &str = "exclusiongroup";
 pub const ATTR_ES256_PRIVATE_KEY_DER: &str = "es256_private_key_der";
 pub const ATTR_FERNET_PRIVATE_KEY_STR: &str = "fernet_private_key_str";
 pub const ATTR_GECOS: &str = "gecos";
 pub const ATTR_GIDNUMBER: &str = "gidnumber";
+pub const ATTR_GID_NUMBER_RANGE: &str = "gid_number_range";
+pub const ATTR_GID_NUMBER_RANGE_NAME: &str = "gid_number_range_name";
 pub const ATTR_GRANT_UI_HINT: &str = "grant_ui_hint";
 pub const ATTR_GROUP: &str = "group";
 pub const ATTR_HMAC_NAME_HISTORY: &str = "hmac_name_history";
@@ -155,6 +185,7 @@ pub const ATTR_MAIL_DESTINATION: &str = "mail_destination";
 pub const ATTR_MAY: &str = "may";
 pub const ATTR_MEMBER: &str = "member";
 pub const ATTR_MEMBER_CREATE_ONCE: &str = "member_create_once";
+pub const ATTR_MEMBER_EXPIRY: &str = "member_expiry";
 pub const ATTR_MEMBEROF: &str = "memberof";
 pub const ATTR_MESSAGE_TEMPLATE: &str = "message_template";
 pub const ATTR_MULTIVALUE: &str = "multivalue";
@@ -166,6 +197,7 @@ pub const ATTR_NSACCOUNTLOCK: &str = "nsaccountlock";
 pub const ATTR_NSUNIQUEID: &str = "nsuniqueid";
 pub const ATTR_PWD_CHANGED_TIME: &str = "pwd_changed_time";
 
+pub const ATTR_OAUTH2_ACCESS_TOKEN_EXPIRY: &str = "oauth2_access_token_expiry";
 pub const ATTR_OAUTH2_ALLOW_INSECURE_CLIENT_DISABLE_PKCE: &str =
     "oauth2_allow_insecure_client_disable_pkce";
 pub const ATTR_OAUTH2_ALLOW_LOCALHOST_REDIRECT: &str = "oauth2_allow_localhost_redirect";
@@ -188,6 +220,7 @@ pub const ATTR_OAUTH2_RS_SCOPE_MAP: &str = "oauth2_rs_scope_map";
 pub const ATTR_OAUTH2_RS_SUP_SCOPE_MAP: &str = "oauth2_rs_sup_scope_map";
 pub const ATTR_OAUTH2_RS_TOKEN_KEY: &str = "oauth2_rs_token_key";
 pub const ATTR_OAUTH2_SESSION: &str = "oauth2_session";
+pub const ATTR_OAUTH2_REQUIRE_PUSHED_AUTHORISATION: &str = "oauth2_require_pushed_authorisation";
 pub const ATTR_OAUTH2_STRICT_REDIRECT_URI: &str = "oauth2_strict_redirect_uri";
 pub const ATTR_OAUTH2_TOKEN_ENDPOINT: &str = "oauth2_token_endpoint";
 pub const ATTR_OAUTH2_ACCOUNT_CREDENTIAL_UUID: &str = "oauth2_account_credential_uuid";
@@ -195,8 +228,11 @@ pub const ATTR_OAUTH2_ACCOUNT_PROVIDER: &str = "oauth2_account_provider";
 pub const ATTR_OAUTH2_ACCOUNT_UNIQUE_USER_ID: &str = "oauth2_account_unique_user_id";
 pub const ATTR_OAUTH2_CONSENT_PROMPT_ENABLE: &str = "oauth2_consent_prompt_enable";
 pub const ATTR_OBJECTCLASS: &str = "objectclass";
+pub const ATTR_ON_DELETE: &str = "ondelete";
 pub const ATTR_OTHER_NO_INDEX: &str = "other-no-index";
 pub const ATTR_PASSKEYS: &str = "passkeys";
+pub const ATTR_PASSKEY_USAGE: &str = "passkey_usage";
+pub const ATTR_PASSWORD_HISTORY: &str = "password_history";
 pub const ATTR_PASSWORD_IMPORT: &str = "password_import";
 pub const ATTR_PATCH_LEVEL: &str = "patch_level";
 pub const ATTR_PHANTOM: &str = "phantom";
@@ -205,10 +241,13 @@ pub const ATTR_TOTP_IMPORT: &str = "totp_import";
 pub const ATTR_PRIVATE_COOKIE_KEY: &str = "private_cookie_key";
 pub const ATTR_PRIVILEGE_EXPIRY: &str = "privilege_expiry";
 pub const ATTR_RADIUS_SECRET: &str = "radius_secret";
+pub const ATTR_RADIUS_VLAN_ID: &str = "radius_vlan_id";
+pub const ATTR_RADIUS_VLAN_PRIORITY: &str = "radius_vlan_priority";
 pub const ATTR_RECYCLED: &str = "recycled";
 pub const ATTR_RECYCLEDDIRECTMEMBEROF: &str = "recycled_directmemberof";
 pub const ATTR_REFERS: &str = "refers";
 pub const ATTR_REPLICATED: &str = "replicated";
+pub const ATTR_REQUIRES_ONE_OF: &str = "requiresoneof";
 pub const ATTR_RS256_PRIVATE_KEY_DER: &str = "rs256_private_key_der";
 pub const ATTR_SCIM_SCHEMAS: &str = "schemas";
 pub const ATTR_SEND_AFTER: &str = "send_after";
@@ -236,10 +275,16 @@ pub const ATTR_SYSTEMEXCLUDES: &str = "systemexcludes";
 pub const ATTR_SYSTEMMAY: &str = "systemmay";
 pub const ATTR_SYSTEMMUST: &str = "systemmust";
 pub const ATTR_SYSTEMSUPPLEMENTS: &str = "systemsupplements";
+pub const ATTR_TEMPLATE_DEFAULT: &str = "template_default";
+pub const ATTR_TEMPLATE_GROUP: &str = "template_group";
 pub const ATTR_TERM: &str = "term";
 pub const ATTR_UID: &str = "uid";
 pub const ATTR_UIDNUMBER: &str = "uidnumber";
 pub const ATTR_UNIQUE: &str = "unique";
+pub const ATTR_UNIQUE_SCOPE: &str = "uniquescope";
+pub const ATTR_UNIX_DEFAULT_SHELL: &str = "unix_default_shell";
+pub const ATTR_UNIX_HOME_DIRECTORY: &str = "unix_home_directory";
+pub const ATTR_UNIX_HOME_DIRECTORY_TEMPLATE: &str = "unix_home_directory_template";
 pub const ATTR_UNIX_PASSWORD: &str = "unix_password";
 pub const ATTR_UNIX_PASSWORD_IMPORT: &str = "unix_password_import";
 pub const ATTR_USER_AUTH_TOKEN_SESSION: &str = "user_auth_token_session";
@@ -248,6 +293,11 @@ pub const ATTR_USERPASSWORD: &str = "userpassword";
 pub const ATTR_UUID: &str = "uuid";
 pub const ATTR_VERSION: &str = "version";
 pub const ATTR_WEBAUTHN_ATTESTATION_CA_LIST: &str = "webauthn_attestation_ca_list";
+pub const ATTR_WEBHOOK_AUTHOR: &str = "webhook_author";
+pub const ATTR_WEBHOOK_EVENT: &str = "webhook_event";
+pub const ATTR_WEBHOOK_FILTER: &str = "webhook_filter";
+pub const ATTR_WEBHOOK_SECRET: &str = "webhook_secret";
+pub const ATTR_WEBHOOK_URL: &str = "webhook_url";
 pub const ATTR_ALLOW_PRIMARY_CRED_FALLBACK: &str = "allow_primary_cred_fallback";
 
 pub const SUB_ATTR_PRIMARY: &str = "primary";
@@ -304,6 +354,8 @@ pub const KSESSIONID: &str = "X-KANIDM-AUTH-SESSION-ID";
 pub const KOPID: &str = "X-KANIDM-OPID";
 /// HTTP Header containing the Kanidm server version
 pub const KVERSION: &str = "X-KANIDM-VERSION";
+/// HTTP Header containing the cursor to request the next page of a list with
+pub const KNEXTCURSOR: &str = "X-KANIDM-NEXT-CURSOR";
 
 /// X-Forwarded-For header
 pub const X_FORWARDED_FOR: &str = "x-forwarded-for";
@@ -331,11 +383,15 @@ pub const ENTRYCLASS_ACCOUNT_POLICY: &str = "account_policy";
 pub const ENTRYCLASS_APPLICATION: &str = "application";
 pub const ENTRYCLASS_ASSERTION_NONCE: &str = "assertion_nonce";
 pub const ENTRYCLASS_ATTRIBUTE_TYPE: &str = "attributetype";
+pub const ENTRYCLASS_AUDIT_RECORD: &str = "audit_record";
+pub const ENTRYCLASS_AUTOMOUNT: &str = "automount";
+pub const ENTRYCLASS_AUTOMOUNT_MAP: &str = "automountmap";
 pub const ENTRYCLASS_CASCADE_DELETED: &str = "cascade_deleted";
 pub const ENTRYCLASS_CLASS: &str = "class";
 pub const ENTRYCLASS_CLASS_TYPE: &str = "classtype";
 pub const ENTRYCLASS_CLIENT_CERTIFICATE: &str = "client_certificate";
 pub const ENTRYCLASS_CONFLICT: &str = "conflict";
+pub const ENTRYCLASS_CREATION_TEMPLATE: &str = "creation_template";
 pub const ENTRYCLASS_DOMAIN_INFO: &str = "domain_info";
 pub const ENTRYCLASS_DYN_GROUP: &str = "dyngroup";
 pub const ENTRYCLASS_EXTENSIBLE_OBJECT: &str = "extensibleobject";
@@ -361,6 +417,7 @@ pub const ENTRYCLASS_SYSTEM_CONFIG: &str = "system_config";
 pub const ENTRYCLASS_SYSTEM_INFO: &str = "system_info";
 pub const ENTRYCLASS_TOMBSTONE: &str = "tombstone";
 pub const ENTRYCLASS_USER: &str = "user";
+pub const ENTRYCLASS_WEBHOOK_SUBSCRIPTION: &str = "webhook_subscription";
 pub const ENTRYCLASS_KEY_PROVIDER: &str = "key_provider";
 pub const ENTRYCLASS_KEY_PROVIDER_INTERNAL: &str = "key_provider_internal";
 pub const ENTRYCLASS_KEY_OBJECT: &str = "key_object";
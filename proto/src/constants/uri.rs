@@ -20,6 +20,8 @@ pub const OAUTH2_TOKEN_ENDPOINT: &str = "/oauth2/token";
 pub const OAUTH2_TOKEN_INTROSPECT_ENDPOINT: &str = "/oauth2/token/introspect";
 /// ⚠️  ⚠️   WARNING DO NOT CHANGE THIS  ⚠️  ⚠️
 pub const OAUTH2_TOKEN_REVOKE_ENDPOINT: &str = "/oauth2/token/revoke";
+/// ⚠️  ⚠️   WARNING DO NOT CHANGE THIS  ⚠️  ⚠️
+pub const OAUTH2_PAR_ENDPOINT: &str = "/oauth2/par";
 
 /// ⚠️  ⚠️   WARNING DO NOT CHANGE THIS  ⚠️  ⚠️
 pub const OAUTH2_DEVICE_LOGIN: &str = "/oauth2/device"; // starts with /ui
@@ -295,6 +295,9 @@ impl fmt::Display for CredentialDetail {
 pub struct PasskeyDetail {
     pub uuid: Uuid,
     pub tag: String,
+    /// When this passkey was last used to authenticate, if it has been used.
+    #[serde(default, with = "time::serde::timestamp::option")]
+    pub last_used: Option<time::OffsetDateTime>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
@@ -336,8 +339,11 @@ pub enum PasswordFeedback {
     CommonNamesAndSurnamesAreEasyToGuess,
     // Custom
     TooShort(u32),
+    TooWeak(u32),
     BadListed,
+    Breached,
     DontReusePasswords,
+    PreviouslyUsed,
 }
 
 /// Human-readable PasswordFeedback result.
@@ -368,6 +374,10 @@ impl fmt::Display for PasswordFeedback {
                 f,
                 "This password has been compromised or otherwise blocked and can not be used."
             ),
+            PasswordFeedback::Breached => write!(
+                f,
+                "This password has appeared in a known data breach and can not be used."
+            ),
             PasswordFeedback::CapitalizationDoesntHelpVeryMuch => {
                 write!(f, "Capitalization doesn't help very much.")
             }
@@ -383,6 +393,10 @@ impl fmt::Display for PasswordFeedback {
                     "Don't reuse passwords that already exist on your account"
                 )
             }
+            PasswordFeedback::PreviouslyUsed => write!(
+                f,
+                "This password has been used recently and can not be used again."
+            ),
             PasswordFeedback::NamesAndSurnamesByThemselvesAreEasyToGuess => {
                 write!(f, "Names and surnames by themselves are easy to guess.")
             }
@@ -424,6 +438,10 @@ impl fmt::Display for PasswordFeedback {
                 f,
                 "Password was too short, needs to be at least {minlength} characters long."
             ),
+            PasswordFeedback::TooWeak(minscore) => write!(
+                f,
+                "Password was too easy to guess, needs a strength score of at least {minscore} out of 4."
+            ),
             PasswordFeedback::UseAFewWordsAvoidCommonPhrases => {
                 write!(f, "Use a few words and avoid common phrases.")
             }
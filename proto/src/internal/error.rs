@@ -15,12 +15,21 @@ pub enum SchemaError {
     MissingMustAttribute(Vec<Attribute>),
     InvalidAttribute(String),
     InvalidAttributeSyntax(String),
+    ValueNotAllowed(String),
+    // Attribute, Operation
+    FilterOperationNotSupported(String, String),
     AttributeNotValidForClass(String),
     SupplementsNotSatisfied(Vec<String>),
     ExcludesNotSatisfied(Vec<String>),
+    AttributeExcludedByClass(Vec<Attribute>),
+    MutuallyExclusiveAttributes(Vec<Attribute>),
+    RequiresOneOfNotSatisfied(Vec<Attribute>),
+    NoStructuralClass,
+    MultipleStructuralClasses(Vec<String>),
     EmptyFilter,
     Corrupted,
     PhantomAttribute(String),
+    CustomSyntaxNotRegistered(String),
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, ToSchema)]
@@ -40,12 +49,19 @@ pub enum ConsistencyError {
     SchemaClassMissingAttribute(String, String),
     SchemaClassPhantomAttribute(String, String),
     SchemaUuidNotUnique(Uuid),
+    SchemaAttributeUniqueMultivalue(String),
+    SchemaIndexedButNoIndexTypes(String),
+    SchemaLdapPhantomMissing(String),
+    SchemaCriticalAttributeAltered(String),
+    SchemaUpdateRejected(String),
     QueryServerSearchFailure,
     EntryUuidCorrupt(u64),
     UuidIndexCorrupt(String),
     UuidNotUnique(String),
     RefintNotUpheld(u64),
     MemberOfInvalid(u64),
+    // The path of groups that form a membership cycle.
+    MemberOfCycle(Vec<Uuid>),
     InvalidAttributeType(String),
     DuplicateUniqueAttribute,
     InvalidSpn(u64),
@@ -59,6 +75,7 @@ pub enum ConsistencyError {
     KeyProviderUuidMissing { key_object: Uuid },
     KeyProviderNoKeys { key_object: Uuid },
     KeyProviderNotFound { key_object: Uuid, provider: Uuid },
+    GidNumberRangeCollision(Uuid),
 }
 
 #[derive(Serialize, Deserialize, Debug, ToSchema)]
@@ -153,6 +170,8 @@ pub enum OperationError {
     AU0006CredentialMayNotReauthenticate,
     AU0007UserAuthTokenInvalid,
     AU0008ClientAuthInfoPrevalidation,
+    AU0009ApiTokenMaxAgeExceeded,
+    AU0010ApiTokenOperationNotInScope,
 
     // Kanidm Generic Errors
     KG001TaskTimeout,
@@ -178,6 +197,21 @@ pub enum OperationError {
     CU0008AccountMissingEmail,
     CU0009AccountEmailNotFound,
     CU0010AccountRecoveryDisabled,
+    CU0011AccountEmailNotVerified,
+
+    // Mail Verification Errors
+    MV0001MailVerificationTokenInvalid,
+    MV0002MailVerificationTokenExpired,
+    MV0003MailAddressNotFound,
+    MV0004MailAddressAlreadyVerified,
+
+    // Creation Template Errors
+    CT0001CreationTemplateNotFound,
+    CT0002CreationTemplateGroupNotFound,
+    CT0003CreationTemplateDefaultInvalid,
+
+    // Application Password Errors
+    AP0001ApplicationPasswordLimitReached,
 
     // ValueSet errors
     VS0001IncomingReplSshPublicKey,
@@ -196,6 +230,14 @@ pub enum OperationError {
     DB0002MismatchedRestoreVersion,
     DB0003FilterResolveCacheBuild,
     DB0004DatabaseTooOld,
+    DB0005InconsistentBackupSnapshot,
+    DB0006BackupVerificationFailed,
+    DB0007InsufficientSpaceForVacuum,
+
+    // Modify errors
+    /// The entry has changed since the expected version. Holds the current version.
+    MD0001EntryVersionConflict(String),
+    MD0002EntryVersionInvalid,
 
     // SCIM
     SC0001IncomingSshPublicKey,
@@ -231,6 +273,10 @@ pub enum OperationError {
     SC0031Int64SyntaxInvalid,
     SC0032Uint64SyntaxInvalid,
     SC0033AssertionContainsDuplicateUuids,
+    SC0034BulkTooManyOperations,
+    SC0035BulkIdUnresolvable,
+    SC0036BulkIdDuplicate,
+    SC0037BulkOperationInvalid,
     // Migration
     MG0001InvalidReMigrationLevel,
     MG0002RaiseDomainLevelExceedsMaximum,
@@ -242,6 +288,7 @@ pub enum OperationError {
     MG0008SkipUpgradeAttempted,
     MG0009InvalidTargetLevelForBootstrap,
     MG0010DowngradeNotAllowed,
+    MG0011DomainRenameInProgress,
     //
     KP0001KeyProviderNotLoaded,
     KP0002KeyProviderInvalidClass,
@@ -331,6 +378,14 @@ pub enum OperationError {
 
     // Plugins
     PL0001GidOverlapsSystemRange,
+    PL0002Oauth2TokenExpiryOutOfRange,
+    PL0003GidNumberRangeInvalid,
+    PL0004GidNumberRangeNotFound,
+    PL0005GidNumberRangeExhausted,
+    PL0006UnixHomeDirectoryTemplateInvalid,
+    PL0007MembershipCycle,
+    PL0008MembershipNestingTooDeep,
+    PL0009SelfWriteAttributeDenied,
 
     // Web UI
     UI0001ChallengeSerialisation,
@@ -451,6 +506,8 @@ impl OperationError {
             Self::AU0006CredentialMayNotReauthenticate => Some("Credential may not reauthenticate".into()),
             Self::AU0007UserAuthTokenInvalid => Some("User auth token was unable to be generated".into()),
             Self::AU0008ClientAuthInfoPrevalidation => Some("Client Authentication Info prevalidation did not occur when expected".into()),
+            Self::AU0009ApiTokenMaxAgeExceeded => Some("The api token is older than the maximum credential age permitted by account policy and must be replaced".into()),
+            Self::AU0010ApiTokenOperationNotInScope => Some("The api token is not scoped to permit this operation".into()),
 
             Self::CU0001WebauthnAttestationNotTrusted => None,
             Self::CU0002WebauthnRegistrationError => None,
@@ -464,11 +521,28 @@ impl OperationError {
             => Some("The target email for a credential update intent token send request is not registered to the account.".into()),
             Self::CU0008AccountMissingEmail => Some("The account has no email addresses and may not have a credential update intent token sent to it.".into()),
             Self::CU0010AccountRecoveryDisabled => Some("The account recovery feature is disabled. See `kanidm system domain set-allow-account-recovery`".into()),
+            Self::CU0011AccountEmailNotVerified => Some("The target email address has not been verified and may not have a credential update intent token sent to it.".into()),
+
+            Self::MV0001MailVerificationTokenInvalid => Some("The mail verification token is not valid.".into()),
+            Self::MV0002MailVerificationTokenExpired => Some("The mail verification token has expired, please request a new one.".into()),
+            Self::MV0003MailAddressNotFound => Some("The email address is not registered to the account.".into()),
+            Self::MV0004MailAddressAlreadyVerified => Some("The email address has already been verified.".into()),
+
+            Self::CT0001CreationTemplateNotFound => Some("The requested creation template does not exist.".into()),
+            Self::CT0002CreationTemplateGroupNotFound => Some("A group named by the creation template does not exist.".into()),
+            Self::CT0003CreationTemplateDefaultInvalid => Some("A default value of the creation template is not valid. Defaults must be in the form 'attribute: value'.".into()),
+
+            Self::AP0001ApplicationPasswordLimitReached => Some("The maximum number of application passwords for this application has been reached. Remove an existing application password first.".into()),
 
             Self::DB0001MismatchedRestoreVersion => None,
             Self::DB0002MismatchedRestoreVersion => None,
             Self::DB0003FilterResolveCacheBuild => None,
             Self::DB0004DatabaseTooOld => Some("The database is too old to be migrated.".into()),
+            Self::DB0005InconsistentBackupSnapshot => Some("The backup snapshot does not match its replication metadata.".into()),
+            Self::DB0006BackupVerificationFailed => Some("The backup failed verification and can not be restored.".into()),
+            Self::DB0007InsufficientSpaceForVacuum => Some("There is not enough free disk space to vacuum the database.".into()),
+            Self::MD0001EntryVersionConflict(current) => Some(format!("The entry has been changed since it was read. The current version is {current}.")),
+            Self::MD0002EntryVersionInvalid => Some("The expected entry version is not valid.".into()),
             Self::KG001TaskTimeout => Some("Task timed out".into()),
             Self::KG002TaskCommFailure => Some("Inter-Task communication failure".into()),
             Self::KG003CacheClearFailed => Some("Failed to clear cache".into()),
@@ -575,7 +649,16 @@ impl OperationError {
             Self::MG0008SkipUpgradeAttempted => Some("Skip Upgrade Attempted.".into()),
             Self::MG0009InvalidTargetLevelForBootstrap => Some("The request target domain level was not valid for bootstrapping a new server instance".into()),
             Self::MG0010DowngradeNotAllowed => Some("Downgrade Attempted".into()),
+            Self::MG0011DomainRenameInProgress => Some("A previous domain rename has not yet completed migration".into()),
             Self::PL0001GidOverlapsSystemRange => None,
+            Self::PL0002Oauth2TokenExpiryOutOfRange => Some("The requested OAuth2 token expiry is outside of the permitted range".into()),
+            Self::PL0003GidNumberRangeInvalid => Some("A gid number allocation range is malformed, overlaps another range or a system range".into()),
+            Self::PL0004GidNumberRangeNotFound => Some("The requested gid number allocation range is not defined on the domain".into()),
+            Self::PL0005GidNumberRangeExhausted => Some("The requested gid number allocation range has no free gid numbers remaining".into()),
+            Self::PL0006UnixHomeDirectoryTemplateInvalid => Some("The unix home directory template must be an absolute path and may only use the variables {name}, {spn} and {uuid}".into()),
+            Self::PL0007MembershipCycle => Some("The change would create a group membership cycle, which is denied by the domain".into()),
+            Self::PL0008MembershipNestingTooDeep => Some("Groups are nested more deeply than the server is able to resolve".into()),
+            Self::PL0009SelfWriteAttributeDenied => Some("The domain self write policy may not grant identity, validity, credential or server managed attributes".into()),
             Self::SC0001IncomingSshPublicKey => None,
             Self::SC0002ReferenceSyntaxInvalid => Some("A SCIM Reference Set contained invalid syntax and can not be processed.".into()),
             Self::SC0003MailSyntaxInvalid => Some("A SCIM Mail Address contained invalid syntax".into()),
@@ -610,6 +693,10 @@ impl OperationError {
             Self::SC0031Int64SyntaxInvalid => Some("A SCIM Int64 contained invalid syntax".into()),
             Self::SC0032Uint64SyntaxInvalid => Some("A SCIM Uint64 contained invalid syntax".into()),
             Self::SC0033AssertionContainsDuplicateUuids => Some("SCIM assertion contains duplicate entry ids, unable to proceed.".into()),
+            Self::SC0034BulkTooManyOperations => Some("SCIM bulk request contains more operations than the server permits.".into()),
+            Self::SC0035BulkIdUnresolvable => Some("SCIM bulk operation references a bulkId that does not exist or failed.".into()),
+            Self::SC0036BulkIdDuplicate => Some("SCIM bulk operation reuses a bulkId that was already defined.".into()),
+            Self::SC0037BulkOperationInvalid => Some("SCIM bulk operation has an invalid path or data for its method.".into()),
             Self::UI0001ChallengeSerialisation => Some("The WebAuthn challenge was unable to be serialised.".into()),
             Self::UI0002InvalidState => Some("The credential update process returned an invalid state transition.".into()),
             Self::UI0003InvalidOauth2Resume => Some("The server attempted to resume OAuth2, but no OAuth2 session is in progress.".into()),
@@ -235,6 +235,8 @@ pub enum Oauth2ClaimMapJoin {
     Ssv,
     #[serde(rename = "array")]
     Array,
+    #[serde(rename = "single")]
+    Single,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -245,6 +247,39 @@ pub struct DomainInfo {
     pub level: u32,
 }
 
+/// The statistics of one of the caches of the server backend.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The number of items the cache was built to hold.
+    pub capacity: u64,
+    /// The number of items that lookups may currently add to the cache.
+    pub target: u64,
+    pub hits: u64,
+    pub misses: u64,
+    /// Items that were removed from the cache to honour a reduced target.
+    pub evictions: u64,
+    /// Lookups that couldn't be answered from an index, and so had to consider every entry.
+    pub include_all_fallbacks: u64,
+}
+
+impl CacheStats {
+    /// The proportion of lookups that were answered by the cache.
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct BackendCacheStats {
+    pub entry: CacheStats,
+    pub idl: CacheStats,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DomainUpgradeCheckReport {
     pub name: String,
@@ -274,6 +309,23 @@ pub struct DomainUpgradeCheckItem {
     pub affected_entries: Vec<String>,
 }
 
+/// A description of a value syntax supported by the server.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct SyntaxInfo {
+    /// The name of the syntax, as used in attribute type definitions.
+    pub name: String,
+    /// The stable numeric identifier of the syntax.
+    pub id: u16,
+    /// The index types that attributes of this syntax may use.
+    pub index_types: Vec<String>,
+    /// If values of this syntax can be compared for ordering in a filter.
+    pub orderable: bool,
+    /// If values of this syntax are secret, and never returned to clients.
+    pub secret: bool,
+    /// If attributes of this syntax may hold more than one value.
+    pub multivalue: bool,
+}
+
 #[test]
 fn test_fstype_deser() {
     assert_eq!(FsType::try_from("zfs"), Ok(FsType::Zfs));
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use uuid::Uuid;
+
+use crate::internal::OperationError;
 use crate::v1::Entry;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, ToSchema)]
@@ -78,6 +81,38 @@ impl CreateRequest {
     }
 }
 
+/// Create a batch of entries in a single transaction, reporting the outcome of each.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BatchCreateRequest {
+    pub entries: Vec<Entry>,
+    /// If any entry fails, none of the entries are created.
+    pub all_or_nothing: bool,
+}
+
+impl BatchCreateRequest {
+    pub fn new(entries: Vec<Entry>, all_or_nothing: bool) -> Self {
+        BatchCreateRequest {
+            entries,
+            all_or_nothing,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchCreateStatus {
+    Created(Uuid),
+    Failed(OperationError),
+    /// The entry was valid, but was not created as another entry of the batch failed.
+    Skipped,
+}
+
+/// The outcome of each entry of a [BatchCreateRequest], in the order they were requested.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BatchCreateResponse {
+    pub results: Vec<BatchCreateStatus>,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct DeleteRequest {
     pub filter: Filter,
@@ -2,6 +2,7 @@ use super::UiHint;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
 use std::fmt;
+use std::str::FromStr;
 use time::OffsetDateTime;
 use utoipa::ToSchema;
 use uuid::Uuid;
@@ -18,6 +19,9 @@ pub enum UatPurpose {
         #[serde(with = "time::serde::timestamp::option")]
         expiry: Option<time::OffsetDateTime>,
     },
+    /// The credential used to authenticate has exceeded its maximum age, and this
+    /// session may only be used to update credentials.
+    CredentialUpdate,
 }
 
 /// The currently authenticated user, and any required metadata for them
@@ -48,6 +52,13 @@ pub struct UserAuthToken {
 
     pub limit_search_max_results: Option<u64>,
     pub limit_search_max_filter_test: Option<u64>,
+    /// The authentication context class that the credential used to establish
+    /// (or most recently re-verify) this session satisfies.
+    #[serde(default)]
+    pub acr: Option<String>,
+    /// The time at which the credential that satisfies `acr` was verified.
+    #[serde(default, with = "time::serde::timestamp::option")]
+    pub acr_verified_at: Option<time::OffsetDateTime>,
 }
 
 impl fmt::Display for UserAuthToken {
@@ -68,6 +79,7 @@ impl fmt::Display for UserAuthToken {
             UatPurpose::ReadWrite { expiry: None } => {
                 writeln!(f, "purpose: read write (expiry: none)")?
             }
+            UatPurpose::CredentialUpdate => writeln!(f, "purpose: credential update")?,
         }
         Ok(())
     }
@@ -105,6 +117,8 @@ impl UserAuthToken {
                 PrivilegesActive::ReauthRequired
             }
             UatPurpose::ReadOnly => PrivilegesActive::False,
+            // The session exists only to replace the expired credential.
+            UatPurpose::CredentialUpdate => PrivilegesActive::True,
         }
     }
 }
@@ -118,6 +132,85 @@ pub enum ApiTokenPurpose {
     Synchronise,
 }
 
+/// A class of operations that an api token can be restricted to. A token without any
+/// operation scopes is limited only by its purpose and the access controls that apply
+/// to the service account.
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema,
+)]
+pub enum ApiTokenOperationScope {
+    #[serde(rename = "person:read")]
+    PersonRead,
+    #[serde(rename = "person:write")]
+    PersonWrite,
+    #[serde(rename = "group:read")]
+    GroupRead,
+    #[serde(rename = "group:write")]
+    GroupWrite,
+    #[serde(rename = "service_account:read")]
+    ServiceAccountRead,
+    #[serde(rename = "service_account:write")]
+    ServiceAccountWrite,
+    #[serde(rename = "radius:read")]
+    RadiusRead,
+}
+
+impl ApiTokenOperationScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiTokenOperationScope::PersonRead => "person:read",
+            ApiTokenOperationScope::PersonWrite => "person:write",
+            ApiTokenOperationScope::GroupRead => "group:read",
+            ApiTokenOperationScope::GroupWrite => "group:write",
+            ApiTokenOperationScope::ServiceAccountRead => "service_account:read",
+            ApiTokenOperationScope::ServiceAccountWrite => "service_account:write",
+            ApiTokenOperationScope::RadiusRead => "radius:read",
+        }
+    }
+
+    /// Determine if holding this scope permits an operation of the required class. A
+    /// write scope also permits reads of the same class.
+    pub fn grants(&self, required: ApiTokenOperationScope) -> bool {
+        *self == required
+            || matches!(
+                (self, required),
+                (
+                    ApiTokenOperationScope::PersonWrite,
+                    ApiTokenOperationScope::PersonRead
+                ) | (
+                    ApiTokenOperationScope::GroupWrite,
+                    ApiTokenOperationScope::GroupRead
+                ) | (
+                    ApiTokenOperationScope::ServiceAccountWrite,
+                    ApiTokenOperationScope::ServiceAccountRead
+                )
+            )
+    }
+}
+
+impl fmt::Display for ApiTokenOperationScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ApiTokenOperationScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "person:read" => Ok(ApiTokenOperationScope::PersonRead),
+            "person:write" => Ok(ApiTokenOperationScope::PersonWrite),
+            "group:read" => Ok(ApiTokenOperationScope::GroupRead),
+            "group:write" => Ok(ApiTokenOperationScope::GroupWrite),
+            "service_account:read" => Ok(ApiTokenOperationScope::ServiceAccountRead),
+            "service_account:write" => Ok(ApiTokenOperationScope::ServiceAccountWrite),
+            "radius:read" => Ok(ApiTokenOperationScope::RadiusRead),
+            _ => Err(format!("Invalid api token scope '{s}'")),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub struct ApiToken {
@@ -132,6 +225,9 @@ pub struct ApiToken {
     // Defaults to ReadOnly if not present
     #[serde(default)]
     pub purpose: ApiTokenPurpose,
+    // If empty the token is not restricted to any class of operations.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub operation_scopes: BTreeSet<ApiTokenOperationScope>,
 }
 
 impl fmt::Display for ApiToken {
@@ -140,6 +236,9 @@ impl fmt::Display for ApiToken {
         writeln!(f, "token_id: {}", self.token_id)?;
         writeln!(f, "label: {}", self.label)?;
         writeln!(f, "issued at: {}", self.issued_at)?;
+        for scope in self.operation_scopes.iter() {
+            writeln!(f, "scope: {scope}")?;
+        }
         if let Some(expiry) = self.expiry {
             // if this fails we're in trouble!
             #[allow(clippy::expect_used)]
@@ -174,6 +273,10 @@ pub struct RadiusAuthToken {
     pub uuid: String,
     pub secret: String,
     pub groups: Vec<Group>,
+    /// The VLAN of the highest priority group that defines one. This is absent if no
+    /// group defines a VLAN, or if groups of equal priority disagree.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vlan: Option<u32>,
 }
 
 impl fmt::Display for RadiusAuthToken {
@@ -182,6 +285,9 @@ impl fmt::Display for RadiusAuthToken {
         writeln!(f, "displayname: {}", self.displayname)?;
         writeln!(f, "uuid: {}", self.uuid)?;
         writeln!(f, "secret: {}", self.secret)?;
+        if let Some(vlan) = self.vlan {
+            writeln!(f, "vlan: {vlan}")?;
+        }
         self.groups
             .iter()
             .try_for_each(|g| writeln!(f, "group: {g}"))
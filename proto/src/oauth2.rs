@@ -75,6 +75,12 @@ pub struct AuthorisationRequest {
     #[serde(default)]
     pub ui_locales: Vec<String>,
 
+    /// The authentication context classes that are acceptable to the client, in order
+    /// of preference.
+    #[serde_as(as = "StringWithSeparator::<SpaceSeparator, String>")]
+    #[serde(default)]
+    pub acr_values: Vec<String>,
+
     #[serde(flatten)]
     pub unknown_keys: BTreeMap<String, serde_json::value::Value>,
 }
@@ -112,6 +118,37 @@ impl AuthorisationRequest {
     }
 }
 
+/// The prefix of a `request_uri` that references a pushed authorisation request.
+pub const OAUTH2_PAR_REQUEST_URI_PREFIX: &str = "urn:ietf:params:oauth:request_uri:";
+
+/// In place of the full Authorisation Request parameters, a client may present a reference
+/// to a request that was previously pushed to the authorisation server.
+/// <https://datatracker.ietf.org/doc/html/rfc9126#section-4>
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PushedAuthorisationReference {
+    pub client_id: String,
+    pub request_uri: String,
+    // Any parameter other than the above conflicts with the pushed request.
+    #[serde(flatten)]
+    pub unknown_keys: BTreeMap<String, serde_json::value::Value>,
+}
+
+/// The Authorisation Request as presented to the authorisation endpoint. This is
+/// either the complete request, or a reference to a previously pushed request.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum AuthorisationRequestKind {
+    Inline(Box<AuthorisationRequest>),
+    Pushed(PushedAuthorisationReference),
+}
+
+/// The response to a successful Pushed Authorisation Request.
+/// <https://datatracker.ietf.org/doc/html/rfc9126#section-2.2>
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PushedAuthorisationResponse {
+    pub request_uri: String,
+    pub expires_in: u32,
+}
+
 /// An OIDC client redirects to the authorisation server with Authorisation Request
 /// parameters.
 #[skip_serializing_none]
@@ -386,6 +423,7 @@ pub struct AccessTokenIntrospectRequest {
 pub struct AccessTokenIntrospectResponse {
     pub active: bool,
     #[serde_as(as = "StringWithSeparator::<SpaceSeparator, String>")]
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
     pub scope: BTreeSet<String>,
     pub client_id: Option<String>,
     pub username: Option<String>,
@@ -397,11 +435,11 @@ pub struct AccessTokenIntrospectResponse {
     pub aud: Option<String>,
     pub iss: Option<String>,
     // JWT ID <https://www.rfc-editor.org/rfc/rfc7519#section-4.1.7> set to session ID
-    pub jti: Uuid,
+    pub jti: Option<Uuid>,
 }
 
 impl AccessTokenIntrospectResponse {
-    pub fn inactive(session_id: Uuid) -> Self {
+    pub fn inactive() -> Self {
         AccessTokenIntrospectResponse {
             active: false,
             scope: BTreeSet::default(),
@@ -414,7 +452,7 @@ impl AccessTokenIntrospectResponse {
             sub: None,
             aud: None,
             iss: None,
-            jti: session_id,
+            jti: None,
         }
     }
 }
@@ -677,6 +715,11 @@ pub struct OidcDiscoveryResponse {
 
     /// Ref <https://www.rfc-editor.org/rfc/rfc8628#section-4>
     pub device_authorization_endpoint: Option<Url>,
+
+    // rfc9126
+    pub pushed_authorization_request_endpoint: Option<Url>,
+    #[serde(default)]
+    pub require_pushed_authorization_requests: bool,
 }
 
 /// The response to an OAuth2 rfc8414 metadata request
@@ -723,6 +766,11 @@ pub struct Oauth2Rfc8414MetadataResponse {
 
     // RFC7636
     pub code_challenge_methods_supported: Vec<PkceAlg>,
+
+    // rfc9126
+    pub pushed_authorization_request_endpoint: Option<Url>,
+    #[serde(default)]
+    pub require_pushed_authorization_requests: bool,
 }
 
 #[skip_serializing_none]
@@ -738,7 +786,7 @@ pub struct ErrorResponse {
 pub struct DeviceAuthorizationResponse {
     /// Base64-encoded bundle of 16 bytes
     device_code: String,
-    /// xxx-yyy-zzz where x/y/z are digits. Stored internally as a u32 because we'll drop the dashes and parse as a number.
+    /// XXXX-XXXX from a consonant-only alphabet. Case and separators are ignored when the user enters it.
     user_code: String,
     verification_uri: Url,
     verification_uri_complete: Url,
@@ -764,6 +812,14 @@ impl DeviceAuthorizationResponse {
             interval: OAUTH2_DEVICE_CODE_INTERVAL_SECONDS,
         }
     }
+
+    pub fn device_code(&self) -> &str {
+        &self.device_code
+    }
+
+    pub fn user_code(&self) -> &str {
+        &self.user_code
+    }
 }
 
 #[cfg(test)]
@@ -120,6 +120,8 @@ pub struct ScimOAuth2ClaimMap {
     pub group_uuid: Option<Uuid>,
     pub claim: String,
     pub join_char: ScimOauth2ClaimMapJoinChar,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
     pub values: BTreeSet<String>,
 }
 
@@ -26,6 +26,8 @@ use std::fmt;
 use std::num::NonZeroU64;
 use std::ops::Not;
 use std::str::FromStr;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
@@ -66,6 +68,9 @@ pub struct ScimEntryGetQuery {
     pub attributes: Option<Vec<Attribute>>,
     #[serde(default, skip_serializing_if = "<&bool>::not")]
     pub ext_access_check: bool,
+    /// Include when each attribute was last modified.
+    #[serde(default, skip_serializing_if = "<&bool>::not")]
+    pub ext_attr_meta: bool,
 
     // Sorting per https://www.rfc-editor.org/rfc/rfc7644#section-3.4.2.3
     #[serde(default)]
@@ -106,6 +111,10 @@ pub struct ScimMail {
     #[serde(default)]
     pub primary: bool,
     pub value: String,
+    /// If the owner of this address has confirmed they control it. This is ignored
+    /// when provided by a client.
+    #[serde(default)]
+    pub verified: bool,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
@@ -132,6 +141,8 @@ pub enum ScimOauth2ClaimMapJoinChar {
     SpaceSeparatedValue,
     #[serde(rename = ";", alias = "json_array")]
     JsonArray,
+    #[serde(rename = "single")]
+    Single,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
@@ -149,6 +160,101 @@ pub struct ScimApplicationPasswordCreate {
     pub label: String,
 }
 
+/// An application password held by an account, without its secret.
+#[serde_as]
+#[skip_serializing_none]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimApplicationPasswordInfo {
+    pub uuid: Uuid,
+    pub application_uuid: Uuid,
+    pub label: String,
+    #[serde_as(as = "Option<Rfc3339>")]
+    pub created: Option<OffsetDateTime>,
+}
+
+pub const SCIM_SCHEMA_BULK_REQUEST: &str = "urn:ietf:params:scim:api:messages:2.0:BulkRequest";
+pub const SCIM_SCHEMA_BULK_RESPONSE: &str = "urn:ietf:params:scim:api:messages:2.0:BulkResponse";
+
+/// The prefix used to reference the result of another operation within the
+/// same bulk request, for example `bulkId:qwerty`.
+pub const SCIM_BULK_ID_PREFIX: &str = "bulkId:";
+
+/// The HTTP method of an operation within a bulk request.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ScimBulkMethod {
+    Post,
+    Put,
+    Delete,
+}
+
+/// A single operation within a bulk request.
+/// <https://datatracker.ietf.org/doc/html/rfc7644#section-3.7>
+#[skip_serializing_none]
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimBulkOperation {
+    pub method: ScimBulkMethod,
+    /// A transient identifier for the resource created by this operation. Other
+    /// operations in the same request may reference it as `bulkId:<id>`.
+    pub bulk_id: Option<String>,
+    /// The resource path relative to the SCIM root, such as `/Entry` or `/Entry/{id}`.
+    pub path: String,
+    #[schema(value_type = Object)]
+    pub data: Option<JsonValue>,
+}
+
+#[skip_serializing_none]
+#[derive(Deserialize, Serialize, Debug, Clone, Default, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimBulkRequest {
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    /// The number of errors that may occur before the remaining operations are
+    /// abandoned. If unset, every operation is attempted. Operations that succeed are
+    /// committed, regardless of the errors of other operations.
+    pub fail_on_errors: Option<u32>,
+    #[serde(rename = "Operations")]
+    pub operations: Vec<ScimBulkOperation>,
+}
+
+#[serde_as]
+#[skip_serializing_none]
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimBulkOperationResponse {
+    pub method: ScimBulkMethod,
+    pub bulk_id: Option<String>,
+    /// The HTTP status code of this operation.
+    #[serde_as(as = "DisplayFromStr")]
+    #[schema(value_type = String)]
+    pub status: u16,
+    /// The resulting entry on success, or the error that occurred.
+    #[schema(value_type = Object)]
+    pub response: Option<JsonValue>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimBulkResponse {
+    pub schemas: Vec<String>,
+    /// The results of the processed operations, in request order. Operations that
+    /// were not attempted due to `failOnErrors` are omitted.
+    #[serde(rename = "Operations")]
+    pub operations: Vec<ScimBulkOperationResponse>,
+}
+
+/// The operation of a single change within a patch request.
+/// <https://datatracker.ietf.org/doc/html/rfc7644#section-3.5.2>
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ScimPatchOp {
+    Add,
+    Remove,
+    Replace,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, ToSchema)]
 pub struct AttrPath {
     pub a: Attribute,
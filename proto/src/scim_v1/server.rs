@@ -28,6 +28,8 @@ pub struct ScimEntryKanidm {
     pub header: ScimEntryHeader,
 
     pub ext_access_check: Option<ScimEffectiveAccess>,
+    /// When each attribute of the entry was last modified, if this was requested.
+    pub ext_attr_meta: Option<BTreeMap<Attribute, ScimAttributeMeta>>,
     #[serde(flatten)]
     pub attrs: BTreeMap<Attribute, ScimValueKanidm>,
 }
@@ -120,12 +122,16 @@ pub struct ScimAddress {
     pub country: String,
 }
 
+#[serde_as]
+#[skip_serializing_none]
 #[derive(Serialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ScimApplicationPasswordReference {
     pub uuid: Uuid,
     pub application_uuid: Uuid,
     pub label: String,
+    #[serde_as(as = "Option<Rfc3339>")]
+    pub created: Option<OffsetDateTime>,
 }
 
 #[serde_as]
@@ -147,6 +153,18 @@ pub struct ScimCertificate {
     pub der: Vec<u8>,
 }
 
+/// The modification metadata of a single attribute of an entry.
+#[serde_as]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimAttributeMeta {
+    #[serde_as(as = "Rfc3339")]
+    pub last_modified: OffsetDateTime,
+    /// The change identifier of the last modification. Sync tools can compare this to
+    /// their cursor to skip attributes that haven't changed.
+    pub version: String,
+}
+
 #[serde_as]
 #[derive(Serialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -156,6 +174,16 @@ pub struct ScimAuditString {
     pub value: String,
 }
 
+#[serde_as]
+#[derive(Serialize, Debug, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimPasskeyUsage {
+    pub id: Uuid,
+    #[serde_as(as = "Rfc3339")]
+    pub last_used: OffsetDateTime,
+    pub sign_count: u32,
+}
+
 #[derive(Serialize, Debug, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub enum ScimIntentTokenState {
@@ -201,6 +229,8 @@ pub struct ScimAuthSession {
     pub credential_id: Uuid,
     pub auth_type: String,
     pub session_scope: String,
+    pub source_ip: Option<String>,
+    pub user_agent: Option<String>,
 }
 
 #[serde_as]
@@ -232,6 +262,8 @@ pub struct ScimApiToken {
     pub issued_at: OffsetDateTime,
     pub issued_by: Uuid,
     pub scope: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub operation_scopes: Vec<String>,
 }
 
 #[serde_as]
@@ -251,6 +283,8 @@ pub struct ScimOAuth2ClaimMap {
     pub group_uuid: Uuid,
     pub claim: String,
     pub join_char: ScimOauth2ClaimMapJoinChar,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
     pub values: BTreeSet<String>,
 }
 
@@ -299,6 +333,7 @@ pub enum ScimValueKanidm {
     OAuth2Session(Vec<ScimOAuth2Session>),
     ApiToken(Vec<ScimApiToken>),
     IntentToken(Vec<ScimIntentToken>),
+    PasskeyUsage(Vec<ScimPasskeyUsage>),
     OAuth2ScopeMap(Vec<ScimOAuth2ScopeMap>),
     OAuth2ClaimMap(Vec<ScimOAuth2ClaimMap>),
     KeyInternal(Vec<ScimKeyInternal>),
@@ -524,6 +559,12 @@ impl From<Vec<ScimSshPublicKey>> for ScimValueKanidm {
     }
 }
 
+impl From<Vec<ScimPasskeyUsage>> for ScimValueKanidm {
+    fn from(set: Vec<ScimPasskeyUsage>) -> Self {
+        Self::PasskeyUsage(set)
+    }
+}
+
 impl From<Vec<ScimAuthSession>> for ScimValueKanidm {
     fn from(set: Vec<ScimAuthSession>) -> Self {
         Self::AuthSession(set)
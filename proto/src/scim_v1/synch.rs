@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use serde_with::{base64, formats, serde_as};
+use std::collections::{BTreeMap, BTreeSet};
 use utoipa::ToSchema;
 use uuid::Uuid;
 
@@ -53,6 +54,15 @@ impl ScimSyncRequest {
     }
 }
 
+/// The outcome of a sync request that was applied.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, ToSchema)]
+pub struct ScimSyncApplyResponse {
+    /// Entries that held references which could not be resolved to an entry within the
+    /// sync agreement or the database, keyed by the uuid of the entry. These references
+    /// were skipped, and the remainder of the entry was applied.
+    pub dangling_references: BTreeMap<Uuid, BTreeSet<String>>,
+}
+
 pub const SCIM_SCHEMA_SYNC_1: &str = "urn:ietf:params:scim:schemas:kanidm:sync:1:";
 pub const SCIM_SCHEMA_SYNC_ACCOUNT: &str = "urn:ietf:params:scim:schemas:kanidm:sync:1:account";
 pub const SCIM_SCHEMA_SYNC_GROUP: &str = "urn:ietf:params:scim:schemas:kanidm:sync:1:group";
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// The kind of change that an audit record describes.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, ToSchema, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditOperation {
+    Create,
+    Modify,
+    Delete,
+}
+
+impl AuditOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOperation::Create => "create",
+            AuditOperation::Modify => "modify",
+            AuditOperation::Delete => "delete",
+        }
+    }
+}
+
+impl fmt::Display for AuditOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AuditOperation {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "create" => Ok(AuditOperation::Create),
+            "modify" => Ok(AuditOperation::Modify),
+            "delete" => Ok(AuditOperation::Delete),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A query over the audit records of the server. All criteria that are set must
+/// match. Records are returned oldest first.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema, PartialEq, Eq)]
+pub struct AuditQuery {
+    /// The identity that performed the change.
+    #[serde(default)]
+    pub actor: Option<Uuid>,
+    /// An entry that was affected by the change.
+    #[serde(default)]
+    pub target: Option<Uuid>,
+    #[serde(default)]
+    pub operation: Option<AuditOperation>,
+    /// Only return records at or after this time.
+    #[serde(default, with = "time::serde::timestamp::option")]
+    pub not_before: Option<OffsetDateTime>,
+    /// Only return records before this time.
+    #[serde(default, with = "time::serde::timestamp::option")]
+    pub not_after: Option<OffsetDateTime>,
+    /// The number of records to skip, as returned by a prior page.
+    #[serde(default)]
+    pub offset: usize,
+    /// The maximum number of records to return. The server may limit this further.
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// A single audited change.
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema, PartialEq, Eq)]
+pub struct AuditRecord {
+    pub uuid: Uuid,
+    pub actor: Uuid,
+    pub operation: AuditOperation,
+    pub targets: Vec<Uuid>,
+    /// The attributes that were changed. Empty for deletes.
+    pub attributes: Vec<String>,
+    #[serde(with = "time::serde::timestamp")]
+    pub time: OffsetDateTime,
+}
+
+/// A page of audit records. If more records match the query then `next_offset` is
+/// the offset to request the following page with.
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema, PartialEq, Eq)]
+pub struct AuditRecordPage {
+    pub records: Vec<AuditRecord>,
+    pub next_offset: Option<usize>,
+}
@@ -13,6 +13,13 @@ pub enum OutboundMessage {
         #[serde(with = "time::serde::timestamp")]
         expiry_time: OffsetDateTime,
     },
+    MailVerificationV1 {
+        display_name: String,
+        mail: String,
+        token: String,
+        #[serde(with = "time::serde::timestamp")]
+        expiry_time: OffsetDateTime,
+    },
 }
 
 impl OutboundMessage {
@@ -20,6 +27,7 @@ impl OutboundMessage {
         match self {
             Self::TestMessageV1 { .. } => "test_message_v1",
             Self::CredentialResetV1 { .. } => "credential_reset_v1",
+            Self::MailVerificationV1 { .. } => "mail_verification_v1",
         }
     }
 }
@@ -5,17 +5,20 @@
 
 #![allow(non_upper_case_globals)]
 
+use crate::internal::ApiTokenOperationScope;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::fmt::Display;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
+mod audit;
 mod auth;
 mod message;
 mod unix;
 
+pub use self::audit::*;
 pub use self::auth::*;
 pub use self::message::*;
 pub use self::unix::*;
@@ -82,6 +85,12 @@ pub struct UatStatus {
     #[serde(with = "time::serde::timestamp")]
     pub issued_at: time::OffsetDateTime,
     pub purpose: UatPurposeStatus,
+    /// The address of the client that established the session, if known.
+    #[serde(default)]
+    pub source_ip: Option<String>,
+    /// The user agent of the client that established the session, if known.
+    #[serde(default)]
+    pub user_agent: Option<String>,
 }
 
 impl fmt::Display for UatStatus {
@@ -95,6 +104,12 @@ impl fmt::Display for UatStatus {
             UatPurposeStatus::ReadWrite => writeln!(f, "purpose: read write")?,
             UatPurposeStatus::PrivilegeCapable => writeln!(f, "purpose: privilege capable")?,
         }
+        if let Some(source_ip) = &self.source_ip {
+            writeln!(f, "source_ip: {source_ip}")?;
+        }
+        if let Some(user_agent) = &self.user_agent {
+            writeln!(f, "user_agent: {user_agent}")?;
+        }
         Ok(())
     }
 }
@@ -109,6 +124,8 @@ pub struct ApiTokenGenerate {
     pub read_write: bool,
     #[serde(default)]
     pub compact: bool,
+    #[serde(default)]
+    pub operation_scopes: BTreeSet<ApiTokenOperationScope>,
 }
 
 /* ===== low level proto types ===== */
@@ -128,6 +145,38 @@ impl fmt::Display for Entry {
     }
 }
 
+/// The number of members of a group. Members are only counted if the requester may
+/// read the attribute that holds them, otherwise the count is zero.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, ToSchema)]
+pub struct GroupMemberCounts {
+    /// Members that were directly added to the group.
+    pub member: u64,
+    /// Members of a dynamic group that match its filter.
+    pub dyn_member: u64,
+    /// All entries that are members of the group, either directly or through
+    /// membership of another group, and whose memberships the requester may read.
+    pub nested: u64,
+}
+
+/// Request that members are added to a group until `expiry`, after which they are
+/// removed from the group.
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct GroupAddMembersExpiring {
+    pub members: Vec<String>,
+    #[serde(with = "time::serde::timestamp")]
+    pub expiry: time::OffsetDateTime,
+}
+
+/// A group in a list of groups. This serialises identically to [Entry] unless member
+/// counts were requested.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default, ToSchema)]
+pub struct GroupListEntry {
+    #[serde(flatten)]
+    pub entry: Entry,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub member_counts: Option<GroupMemberCounts>,
+}
+
 /// A response to a whoami request
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
 pub struct WhoamiResponse {
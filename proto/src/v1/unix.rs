@@ -76,6 +76,9 @@ pub struct UnixUserToken {
     pub gidnumber: u32,
     pub uuid: Uuid,
     pub shell: Option<String>,
+    /// The home directory of the account, rendered by the server from the account or
+    /// its templates.
+    pub home_directory: Option<String>,
     pub groups: Vec<UnixGroupToken>,
     #[schema(value_type = Vec<String>)]
     pub sshkeys: Vec<SshPublicKey>,
@@ -96,6 +99,10 @@ impl Display for UnixUserToken {
             Some(s) => writeln!(f, "shell: {s}")?,
             None => writeln!(f, "shell: <none>")?,
         }
+        match &self.home_directory {
+            Some(s) => writeln!(f, "home_directory: {s}")?,
+            None => writeln!(f, "home_directory: <none>")?,
+        }
         self.sshkeys
             .iter()
             .try_for_each(|s| writeln!(f, "{ATTR_LDAP_SSHPUBLICKEY}: {s}"))?;
@@ -154,13 +154,18 @@ def authorize(
         logging.info("User %s doesn't have a group from the required list.", name)
         return radiusd.RLM_MODULE_REJECT
 
-    # look up them in config for group vlan if possible.
-    # TODO: work out the typing on this, WTF.
-    uservlan: int = reduce(
-        check_vlan,
-        tok.groups,
-        kanidm_client.config.radius_default_vlan,
-    )
+    # A vlan assigned by the server takes precedence, otherwise look
+    # them up in config for group vlan if possible.
+    uservlan: int
+    if tok.vlan is not None:
+        uservlan = tok.vlan
+    else:
+        # TODO: work out the typing on this, WTF.
+        uservlan = reduce(
+            check_vlan,
+            tok.groups,
+            kanidm_client.config.radius_default_vlan,
+        )
     if uservlan == int(0):
         logging.info("Invalid uservlan of 0")
 
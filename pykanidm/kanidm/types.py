@@ -111,6 +111,8 @@ class RadiusTokenResponse(BaseModel):
     uuid: str
 
     groups: List[RadiusTokenGroup]
+    # Assigned by the server from the groups' radius_vlan_id, absent if no group assigns one.
+    vlan: Optional[int] = None
     model_config = ConfigDict(arbitrary_types_allowed=True)
 
 
@@ -185,7 +185,10 @@ impl Module {
             return Err(AuthError::Reject);
         }
 
-        let selected_vlan = self.resolve_vlan(&token.groups);
+        // A vlan assigned by the server takes precedence over the configured group mapping.
+        let selected_vlan = token
+            .vlan
+            .unwrap_or_else(|| self.resolve_vlan(&token.groups));
 
         let reply = ResponseReplyAttributes {
             user_name: token.name.clone(),
@@ -249,6 +252,7 @@ mod tests {
             uuid: "u-1".to_string(),
             secret: "radius-secret".to_string(),
             groups,
+            vlan: None,
         }
     }
 
@@ -5,12 +5,17 @@
 use crate::{QueryServerReadV1, QueryServerWriteV1};
 use crypto_glue::s256::Sha256Output;
 use kanidm_proto::internal::{
-    DomainInfo as ProtoDomainInfo, DomainUpgradeCheckReport as ProtoDomainUpgradeCheckReport,
+    BackendCacheStats, DomainInfo as ProtoDomainInfo,
+    DomainUpgradeCheckReport as ProtoDomainUpgradeCheckReport,
 };
 use kanidm_proto::scim_v1::client::ScimAssertGeneric;
 use kanidmd_lib::prelude::*;
 use kanidmd_lib::{
-    event::{PurgeDeleteAfterEvent, PurgeRecycledEvent, PurgeTombstoneEvent},
+    be::{BackendTransaction, Vacuum, VacuumReport},
+    event::{
+        AccountExpiryEvent, DomainRenameMigrateEvent, MemberExpiryEvent, PurgeDeleteAfterEvent,
+        PurgeRecycledEvent, PurgeTombstoneEvent,
+    },
     idm::delayed::DelayedAction,
     server::scim::ScimAssertEvent,
 };
@@ -44,6 +49,20 @@ impl QueryServerReadV1 {
 
         idms_prox_read.qs_read.domain_upgrade_check()
     }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub(crate) async fn handle_backend_cache_stats(
+        &self,
+        eventid: Uuid,
+    ) -> Result<BackendCacheStats, OperationError> {
+        let mut idms_prox_read = self.idms.proxy_read().await?;
+
+        Ok(idms_prox_read.qs_read.get_be_txn().cache_stats())
+    }
 }
 
 impl QueryServerWriteV1 {
@@ -123,6 +142,101 @@ impl QueryServerWriteV1 {
             .inspect_err(|err| error!(?err, "Unable to purge delete after entries"));
     }
 
+    /// Continue any in progress domain rename, regenerating spns in chunks so that
+    /// the write lock is regularly released to other operations.
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?msg.eventid)
+    )]
+    pub async fn handle_domain_rename_migrate_event(&self, msg: DomainRenameMigrateEvent) {
+        let mut cursor = None;
+        loop {
+            let Ok(mut idms_prox_write) = self.idms.proxy_write(duration_from_epoch_now()).await
+            else {
+                warn!("Unable to start domain rename migration, will retry later");
+                return;
+            };
+
+            let res = idms_prox_write
+                .qs_write
+                .domain_rename_migrate(cursor.as_deref(), DOMAIN_RENAME_MIGRATE_CHUNK)
+                .and_then(|next| idms_prox_write.commit().map(|_| next));
+
+            match res {
+                Ok(None) => return,
+                Ok(next) => {
+                    debug!("Domain rename migration in progress");
+                    cursor = next;
+                }
+                Err(err) => {
+                    error!(?err, "Unable to migrate domain rename");
+                    return;
+                }
+            }
+        }
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?msg.eventid)
+    )]
+    pub async fn handle_member_expiry_event(&self, msg: MemberExpiryEvent) {
+        let ct = duration_from_epoch_now();
+        let Ok(mut idms_prox_write) = self.idms.proxy_write(ct).await else {
+            warn!("Unable to start member expiry event, will retry later");
+            return;
+        };
+        let _ = idms_prox_write
+            .qs_write
+            .process_member_expiry()
+            .and_then(|removed| {
+                // don't need to commit a txn with no changes
+                if removed > 0 {
+                    idms_prox_write.commit()
+                } else {
+                    Ok(())
+                }
+            })
+            .inspect_err(|err| error!(?err, "Unable to remove expired group memberships"));
+    }
+
+    /// Remove pushed authorisation requests that expired without being used.
+    pub fn handle_oauth2_pushed_request_expiry(&self) {
+        self.idms
+            .expire_oauth2_pushed_requests(duration_from_epoch_now());
+    }
+
+    /// Process account expiry, returning the time that it was processed at, which is
+    /// the `since` of the next event.
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?msg.eventid)
+    )]
+    pub async fn handle_account_expiry_event(&self, msg: AccountExpiryEvent) -> Duration {
+        let ct = duration_from_epoch_now();
+        let Ok(mut idms_prox_write) = self.idms.proxy_write(ct).await else {
+            warn!("Unable to start account expiry event, will retry later");
+            return msg.since;
+        };
+        let _ = idms_prox_write
+            .qs_write
+            .process_account_expiry(msg.since, msg.notice.as_ref())
+            .and_then(|report| {
+                // don't need to commit a txn with no changes
+                if report.deactivated > 0 {
+                    idms_prox_write.commit()
+                } else {
+                    Ok(())
+                }
+            })
+            .inspect_err(|err| error!(?err, "Unable to process account expiry"));
+        // Notices have been sent even if the commit failed, so always advance.
+        ct
+    }
+
     pub(crate) async fn handle_delayedaction(&self, da_batch: &mut Vec<DelayedAction>) {
         let eventid = Uuid::new_v4();
         let span = span!(Level::INFO, "process_delayed_action", uuid = ?eventid);
@@ -235,6 +349,57 @@ impl QueryServerWriteV1 {
         idms_prox_write.commit().map(|()| DOMAIN_MAX_LEVEL)
     }
 
+    #[instrument(
+        level = "info",
+        skip(self, eventid),
+        fields(uuid = ?eventid)
+    )]
+    pub(crate) async fn handle_backend_cache_targets(
+        &self,
+        entry: Option<usize>,
+        idl: Option<usize>,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await?;
+
+        // The targets are applied as this transaction commits.
+        idms_prox_write
+            .qs_write
+            .get_be_txn()
+            .set_cache_targets(entry, idl);
+
+        idms_prox_write.commit()
+    }
+
+    #[instrument(
+        level = "info",
+        skip(self, eventid),
+        fields(uuid = ?eventid)
+    )]
+    pub(crate) async fn handle_db_vacuum(
+        &self,
+        available_space: Option<u64>,
+        eventid: Uuid,
+    ) -> Result<VacuumReport, OperationError> {
+        let mut vacuum = Vacuum::new(available_space);
+
+        // Each step takes the write lock only for as long as it needs, so that other
+        // writes can proceed between steps.
+        loop {
+            let ct = duration_from_epoch_now();
+            let mut idms_prox_write = self.idms.proxy_write(ct).await?;
+            let done = vacuum.step(idms_prox_write.qs_write.get_be_txn())?;
+            idms_prox_write.commit()?;
+
+            if done {
+                break;
+            }
+        }
+
+        Ok(vacuum.into_report())
+    }
+
     #[instrument(
         level = "info",
         skip(self, eventid),
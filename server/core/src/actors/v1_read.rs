@@ -3,11 +3,13 @@ use kanidm_proto::backup::BackupCompression;
 use kanidm_proto::internal::{
     ApiToken, AppLink, CURequest, CUSessionToken, CUStatus, CredentialStatus, IdentifyUserRequest,
     IdentifyUserResponse, ImageValue, OperationError, RadiusAuthToken, SearchRequest,
-    SearchResponse, UserAuthToken,
+    SearchResponse, SyntaxInfo, UserAuthToken,
 };
 use kanidm_proto::oauth2::OidcWebfingerResponse;
+use kanidm_proto::scim_v1::ScimApplicationPasswordInfo;
 use kanidm_proto::v1::{
-    AuthIssueSession, Entry as ProtoEntry, UatStatus, UnixGroupToken, UnixUserToken, WhoamiResponse,
+    AuditQuery, AuditRecordPage, AuthIssueSession, Entry as ProtoEntry, GroupListEntry, UatStatus,
+    UnixGroupToken, UnixUserToken, WhoamiResponse,
 };
 use kanidmd_lib::be::BackendTransaction;
 use kanidmd_lib::idm::identityverification::{
@@ -27,8 +29,9 @@ use kanidmd_lib::{
     idm::ldap::{LdapBoundToken, LdapResponseState},
     idm::oauth2::{
         AccessTokenIntrospectRequest, AccessTokenIntrospectResponse, AuthorisationRequest,
-        AuthorisationRequestContext, AuthoriseReject, AuthoriseResponse, JwkKeySet, Oauth2Error,
-        Oauth2Rfc8414MetadataResponse, OidcDiscoveryResponse, OidcToken,
+        AuthorisationRequestContext, AuthorisationRequestKind, AuthoriseReject, AuthoriseResponse,
+        JwkKeySet, Oauth2Error, Oauth2Rfc8414MetadataResponse, OidcDiscoveryResponse, OidcToken,
+        PushedAuthorisationResponse,
     },
     idm::server::{DomainInfoRead, IdmServerTransaction},
     idm::serviceaccount::ListApiTokenEvent,
@@ -67,6 +70,7 @@ impl QueryServerReadV1 {
         &self,
         client_auth_info: ClientAuthInfo,
         req: SearchRequest,
+        attrs: Option<Vec<String>>,
         eventid: Uuid,
     ) -> Result<SearchResponse, OperationError> {
         // Begin a read
@@ -81,7 +85,8 @@ impl QueryServerReadV1 {
 
         // Make an event from the request
         let search =
-            SearchEvent::from_message(ident, &req, &mut idms_prox_read.qs_read).map_err(|e| {
+            SearchEvent::from_message(ident, &req, attrs.as_deref(), &mut idms_prox_read.qs_read)
+                .map_err(|e| {
                 error!(?e, "Failed to begin search");
                 e
             })?;
@@ -198,7 +203,7 @@ impl QueryServerReadV1 {
         outpath: &Path,
         versions: usize,
         compression: BackupCompression,
-    ) -> Result<(), OperationError> {
+    ) -> Result<(PathBuf, Option<Cid>), OperationError> {
         trace!(eventid = ?msg.eventid, "Begin online backup event");
 
         #[allow(clippy::disallowed_methods)]
@@ -222,15 +227,20 @@ impl QueryServerReadV1 {
             OperationError::FsError
         })?;
 
-        // Scope to limit the read txn.
-        {
+        // Scope to limit the read txn. Writes continue while the backup is taken, but are
+        // not included in it.
+        let snapshot_cid = {
             let mut idms_prox_read = self.idms.proxy_read().await?;
             idms_prox_read
                 .qs_read
                 .get_be_txn()
                 .backup(output, compression)
-                .map(|()| {
-                    info!("Online backup created {} successfully", dest_file.display());
+                .inspect(|snapshot_cid| {
+                    info!(
+                        ?snapshot_cid,
+                        "Online backup created {} successfully",
+                        dest_file.display()
+                    );
                 })
                 .map_err(|e| {
                     error!(
@@ -239,8 +249,8 @@ impl QueryServerReadV1 {
                         e
                     );
                     OperationError::InvalidState
-                })?;
-        }
+                })?
+        };
 
         // TODO: make the file rotation a separate function
 
@@ -329,7 +339,7 @@ impl QueryServerReadV1 {
             debug!("Online backup cleanup had no files to remove");
         };
 
-        Ok(())
+        Ok((dest_file, snapshot_cid))
     }
 
     #[instrument(
@@ -420,6 +430,70 @@ impl QueryServerReadV1 {
             })
     }
 
+    #[instrument(
+        level = "info",
+        name = "whoami_api_token",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_whoami_api_token(
+        &self,
+        client_auth_info: &ClientAuthInfo,
+        eventid: Uuid,
+    ) -> Result<ApiToken, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await?;
+        idms_prox_read
+            .validate_client_auth_info_to_api_token(client_auth_info, ct)
+            .map_err(|e| {
+                error!(?e, "Invalid identity");
+                e
+            })
+    }
+
+    #[instrument(
+        level = "info",
+        name = "self_application_password_list",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_self_application_password_list(
+        &self,
+        client_auth_info: ClientAuthInfo,
+        eventid: Uuid,
+    ) -> Result<Vec<ScimApplicationPasswordInfo>, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await?;
+        let ident = idms_prox_read
+            .validate_client_auth_info_to_ident(client_auth_info, ct)
+            .inspect_err(|err| error!(?err, "Invalid identity"))?;
+
+        idms_prox_read.list_self_application_passwords(&ident)
+    }
+
+    #[instrument(
+        level = "info",
+        name = "self_session_list",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_self_user_auth_token_get(
+        &self,
+        client_auth_info: ClientAuthInfo,
+        eventid: Uuid,
+    ) -> Result<Vec<UatStatus>, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await?;
+        let ident = idms_prox_read
+            .validate_client_auth_info_to_ident(client_auth_info, ct)
+            .inspect_err(|err| error!(?err, "Invalid identity"))?;
+
+        let target = ident.get_uuid();
+        let lte = ListUserAuthTokenEvent { ident, target };
+
+        idms_prox_read.account_list_user_auth_tokens(&lte)
+    }
+
     #[instrument(level = "debug", skip_all)]
     /// pull an image so we can present it to the user
     pub async fn handle_oauth2_rs_image_get_image(
@@ -451,6 +525,63 @@ impl QueryServerReadV1 {
             .and_then(|entry| entry.get_ava_single_image(Attribute::Image)))
     }
 
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_group_list(
+        &self,
+        client_auth_info: ClientAuthInfo,
+        filter: Filter<FilterInvalid>,
+        member_counts: bool,
+        eventid: Uuid,
+    ) -> Result<Vec<GroupListEntry>, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await?;
+        let ident = idms_prox_read
+            .validate_client_auth_info_to_ident(client_auth_info, ct)
+            .map_err(|e| {
+                error!("Invalid identity: {:?}", e);
+                e
+            })?;
+
+        let srch =
+            SearchEvent::from_internal_message(ident, &filter, None, &mut idms_prox_read.qs_read)
+                .inspect_err(|err| error!(?err, "Failed to begin group list search"))?;
+
+        trace!(?srch, "Begin event");
+
+        let entries = idms_prox_read.qs_read.search_ext(&srch)?;
+
+        entries
+            .iter()
+            .map(|entry| {
+                let member_counts = if member_counts {
+                    Some(
+                        idms_prox_read
+                            .qs_read
+                            .group_member_counts(&srch.ident, entry)?,
+                    )
+                } else {
+                    None
+                };
+
+                let mut entry = entry.to_pe(&mut idms_prox_read.qs_read)?;
+                // The counts replace the member lists, which may be very large.
+                if member_counts.is_some() {
+                    entry.attrs.remove(Attribute::Member.as_str());
+                    entry.attrs.remove(Attribute::DynMember.as_str());
+                }
+
+                Ok(GroupListEntry {
+                    entry,
+                    member_counts,
+                })
+            })
+            .collect()
+    }
+
     #[instrument(
         level = "info",
         skip_all,
@@ -494,6 +625,51 @@ impl QueryServerReadV1 {
         }
     }
 
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_internalsearch_page(
+        &self,
+        client_auth_info: ClientAuthInfo,
+        filter: Filter<FilterInvalid>,
+        attrs: Option<Vec<String>>,
+        cursor: Option<String>,
+        limit: usize,
+        eventid: Uuid,
+    ) -> Result<(Vec<ProtoEntry>, Option<String>), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await?;
+        let ident = idms_prox_read
+            .validate_client_auth_info_to_ident(client_auth_info, ct)
+            .map_err(|e| {
+                error!("Invalid identity: {:?}", e);
+                e
+            })?;
+
+        let srch = SearchEvent::from_internal_message(
+            ident,
+            &filter,
+            attrs.as_deref(),
+            &mut idms_prox_read.qs_read,
+        )
+        .map_err(|e| {
+            error!("Failed to begin internal api search: {:?}", e);
+            e
+        })?;
+
+        trace!(?srch, "Begin event");
+
+        let (entries, next) =
+            idms_prox_read
+                .qs_read
+                .search_ext_page(&srch, cursor.as_deref(), limit, ct)?;
+
+        SearchResult::new(&mut idms_prox_read.qs_read, &entries)
+            .map(|ok_sr| (ok_sr.into_proto_array(), next))
+    }
+
     #[instrument(
         level = "info",
         skip_all,
@@ -1343,6 +1519,43 @@ impl QueryServerReadV1 {
         }
     }
 
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_webhook_secret_read(
+        &self,
+        client_auth_info: ClientAuthInfo,
+        filter: Filter<FilterInvalid>,
+        eventid: Uuid,
+    ) -> Result<Option<String>, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await?;
+        let ident = idms_prox_read
+            .validate_client_auth_info_to_ident(client_auth_info, ct)
+            .inspect_err(|err| {
+                error!(?err, "Invalid identity");
+            })?;
+
+        let srch =
+            SearchEvent::from_internal_message(ident, &filter, None, &mut idms_prox_read.qs_read)
+                .inspect_err(|err| {
+                error!(?err, "Failed to begin webhook secret read");
+            })?;
+
+        trace!(?srch, "Begin event");
+
+        // We have to use search_ext to guarantee acs was applied.
+        idms_prox_read.qs_read.search_ext(&srch).map(|mut entries| {
+            entries.pop().and_then(|entry| {
+                entry
+                    .get_ava_single(Attribute::WebhookSecret)
+                    .and_then(|v| v.get_secret_str().map(str::to_string))
+            })
+        })
+    }
+
     #[instrument(
         level = "info",
         skip_all,
@@ -1351,7 +1564,7 @@ impl QueryServerReadV1 {
     pub async fn handle_oauth2_authorise(
         &self,
         client_auth_info: ClientAuthInfo,
-        auth_req: AuthorisationRequest,
+        auth_req: AuthorisationRequestKind,
         auth_req_ctx: AuthorisationRequestContext,
         eventid: Uuid,
     ) -> Result<AuthoriseResponse, Oauth2Error> {
@@ -1369,7 +1582,32 @@ impl QueryServerReadV1 {
             .ok();
 
         // Now we can send to the idm server for authorisation checking.
-        idms_prox_read.check_oauth2_authorisation(ident.as_ref(), &auth_req, &auth_req_ctx, ct)
+        match auth_req {
+            AuthorisationRequestKind::Inline(auth_req) => idms_prox_read
+                .check_oauth2_authorisation(ident.as_ref(), &auth_req, &auth_req_ctx, ct),
+            AuthorisationRequestKind::Pushed(par_ref) => idms_prox_read
+                .check_oauth2_authorisation_pushed(ident.as_ref(), &par_ref, &auth_req_ctx, ct),
+        }
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_oauth2_pushed_authorisation_request(
+        &self,
+        client_auth_info: ClientAuthInfo,
+        auth_req: AuthorisationRequest,
+        eventid: Uuid,
+    ) -> Result<PushedAuthorisationResponse, Oauth2Error> {
+        let ct = duration_from_epoch_now();
+        let idms_prox_read = self
+            .idms
+            .proxy_read()
+            .await
+            .map_err(Oauth2Error::ServerError)?;
+        idms_prox_read.check_oauth2_pushed_authorisation_request(&client_auth_info, auth_req, ct)
     }
 
     #[instrument(
@@ -1402,6 +1640,7 @@ impl QueryServerReadV1 {
     )]
     pub async fn handle_oauth2_token_introspect(
         &self,
+        client_auth_info: ClientAuthInfo,
         intr_req: AccessTokenIntrospectRequest,
         eventid: Uuid,
     ) -> Result<AccessTokenIntrospectResponse, Oauth2Error> {
@@ -1412,7 +1651,7 @@ impl QueryServerReadV1 {
             .await
             .map_err(Oauth2Error::ServerError)?;
         // Now we can send to the idm server for introspection checking.
-        idms_prox_read.check_oauth2_token_introspect(&intr_req, ct)
+        idms_prox_read.check_oauth2_token_introspect(&client_auth_info, &intr_req, ct)
     }
 
     #[instrument(
@@ -1515,6 +1754,50 @@ impl QueryServerReadV1 {
         idms_prox_read.list_applinks(&ident)
     }
 
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_schema_syntax_catalog(
+        &self,
+        client_auth_info: ClientAuthInfo,
+        eventid: Uuid,
+    ) -> Result<Vec<SyntaxInfo>, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await?;
+        idms_prox_read
+            .validate_client_auth_info_to_ident(client_auth_info, ct)
+            .map_err(|e| {
+                error!("Invalid identity: {:?}", e);
+                e
+            })?;
+
+        Ok(idms_prox_read.qs_read.get_schema().syntax_catalog())
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_audit_search(
+        &self,
+        client_auth_info: ClientAuthInfo,
+        query: AuditQuery,
+        eventid: Uuid,
+    ) -> Result<AuditRecordPage, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_read = self.idms.proxy_read().await?;
+        let ident = idms_prox_read
+            .validate_client_auth_info_to_ident(client_auth_info, ct)
+            .inspect_err(|err| {
+                error!(?err, "Invalid identity");
+            })?;
+
+        idms_prox_read.qs_read.audit_search(&ident, &query)
+    }
+
     #[instrument(
         level = "info",
         skip_all,
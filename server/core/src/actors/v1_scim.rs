@@ -2,8 +2,8 @@ use super::{QueryServerReadV1, QueryServerWriteV1};
 use kanidm_proto::scim_v1::{
     client::{ScimEntryPostGeneric, ScimEntryPutGeneric},
     server::{ScimEntryKanidm, ScimListResponse},
-    ScimApplicationPassword, ScimApplicationPasswordCreate, ScimEntryGetQuery, ScimFilter,
-    ScimSyncRequest, ScimSyncState,
+    ScimApplicationPassword, ScimApplicationPasswordCreate, ScimBulkRequest, ScimEntryGetQuery,
+    ScimFilter, ScimSyncApplyResponse, ScimSyncRequest, ScimSyncState,
 };
 use kanidmd_lib::idm::application::GenerateApplicationPasswordEvent;
 use kanidmd_lib::idm::scim::{
@@ -11,7 +11,9 @@ use kanidmd_lib::idm::scim::{
 };
 use kanidmd_lib::idm::server::IdmServerTransaction;
 use kanidmd_lib::prelude::*;
-use kanidmd_lib::server::scim::{ScimCreateEvent, ScimDeleteEvent, ScimEntryPutEvent};
+use kanidmd_lib::server::scim::{
+    ScimBulkEvent, ScimBulkItemResult, ScimCreateEvent, ScimDeleteEvent, ScimEntryPutEvent,
+};
 
 impl QueryServerWriteV1 {
     #[instrument(
@@ -168,7 +170,7 @@ impl QueryServerWriteV1 {
         client_auth_info: ClientAuthInfo,
         changes: ScimSyncRequest,
         eventid: Uuid,
-    ) -> Result<(), OperationError> {
+    ) -> Result<ScimSyncApplyResponse, OperationError> {
         let ct = duration_from_epoch_now();
         let mut idms_prox_write = self.idms.proxy_write(ct).await?;
 
@@ -179,7 +181,11 @@ impl QueryServerWriteV1 {
 
         idms_prox_write
             .scim_sync_apply(&sse, &changes, ct)
-            .and_then(|r| idms_prox_write.commit().map(|_| r))
+            .and_then(|report| {
+                idms_prox_write.commit().map(|()| ScimSyncApplyResponse {
+                    dangling_references: report.dangling_references,
+                })
+            })
     }
 
     #[instrument(
@@ -398,6 +404,26 @@ impl QueryServerWriteV1 {
             .scim_put(scim_entry_put_event)
             .and_then(|r| idms_prox_write.commit().map(|_| r))
     }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_scim_bulk(
+        &self,
+        client_auth_info: ClientAuthInfo,
+        eventid: Uuid,
+        request: ScimBulkRequest,
+        max_operations: usize,
+    ) -> Result<Vec<ScimBulkItemResult>, OperationError> {
+        let ct = duration_from_epoch_now();
+        let scim_bulk_event = ScimBulkEvent::try_from(request, max_operations)?;
+
+        self.idms
+            .scim_bulk(ct, client_auth_info, scim_bulk_event)
+            .await
+    }
 }
 
 impl QueryServerReadV1 {
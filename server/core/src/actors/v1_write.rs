@@ -2,11 +2,14 @@ use std::iter;
 
 use compact_jwt::JweCompact;
 use kanidm_proto::internal::{
-    CUIntentToken, CUSessionToken, CUStatus, CreateRequest, DeleteRequest, ImageValue,
-    Modify as ProtoModify, ModifyList as ProtoModifyList, ModifyRequest,
-    Oauth2ClaimMapJoin as ProtoOauth2ClaimMapJoin, OperationError,
+    ApiTokenOperationScope, BatchCreateRequest, BatchCreateResponse, CUIntentToken, CUSessionToken,
+    CUStatus, CreateRequest, DeleteRequest, ImageValue, Modify as ProtoModify,
+    ModifyList as ProtoModifyList, ModifyRequest, Oauth2ClaimMapJoin as ProtoOauth2ClaimMapJoin,
+    OperationError,
+};
+use kanidm_proto::v1::{
+    AccountUnixExtend, Entry as ProtoEntry, GroupAddMembersExpiring, GroupUnixExtend,
 };
-use kanidm_proto::v1::{AccountUnixExtend, Entry as ProtoEntry, GroupUnixExtend};
 use kanidmd_lib::valueset::image::ImageValueThings;
 use std::str::FromStr;
 use time::OffsetDateTime;
@@ -14,15 +17,16 @@ use tracing::{info, instrument, trace};
 use uuid::Uuid;
 
 use kanidmd_lib::{
-    event::{CreateEvent, DeleteEvent, ModifyEvent, ReviveRecycledEvent},
+    event::{BatchCreateEvent, CreateEvent, DeleteEvent, ModifyEvent, ReviveRecycledEvent},
     filter::{Filter, FilterInvalid},
-    idm::account::DestroySessionTokenEvent,
+    idm::account::{DestroySessionTokenEvent, SelfWriteAttributeEvent},
     idm::credupdatesession::{
         CredentialUpdateIntentTokenExchange, CredentialUpdateSessionToken,
         InitCredentialUpdateEvent, InitCredentialUpdateIntentEvent,
         InitCredentialUpdateIntentSendEvent,
     },
     idm::event::{GeneratePasswordEvent, RegenerateRadiusSecretEvent, UnixPasswordChangeEvent},
+    idm::mail::MailVerificationSendEvent,
     idm::oauth2::{
         AccessTokenRequest, AccessTokenResponse, AuthorisePermitSuccess, Oauth2Error,
         TokenRevokeRequest,
@@ -176,6 +180,73 @@ impl QueryServerWriteV1 {
             .and_then(|_| idms_prox_write.commit())
     }
 
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_create_with_template(
+        &self,
+        client_auth_info: ClientAuthInfo,
+        req: CreateRequest,
+        template: String,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await?;
+
+        let ident = idms_prox_write
+            .validate_client_auth_info_to_ident(client_auth_info, ct)
+            .map_err(|e| {
+                error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let crt = CreateEvent::from_message(ident, &req, &mut idms_prox_write.qs_write)
+            .inspect_err(|e| {
+                admin_warn!(err = ?e, "Failed to begin create");
+            })?;
+
+        trace!(?crt, %template, "Begin create from template event");
+
+        idms_prox_write
+            .qs_write
+            .create_with_template(&crt, &template)
+            .and_then(|_| idms_prox_write.commit())
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_create_batch(
+        &self,
+        client_auth_info: ClientAuthInfo,
+        req: BatchCreateRequest,
+        eventid: Uuid,
+    ) -> Result<BatchCreateResponse, OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await?;
+
+        let ident = idms_prox_write
+            .validate_client_auth_info_to_ident(client_auth_info, ct)
+            .map_err(|e| {
+                error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let bce = BatchCreateEvent::from_message(ident, &req, &mut idms_prox_write.qs_write);
+
+        trace!(?bce, "Begin batch create event");
+
+        let results = idms_prox_write.qs_write.create_batch(bce)?;
+
+        idms_prox_write
+            .commit()
+            .map(|()| BatchCreateResponse { results })
+    }
+
     #[instrument(
         level = "info",
         skip_all,
@@ -257,8 +328,19 @@ impl QueryServerWriteV1 {
         client_auth_info: ClientAuthInfo,
         filter: Filter<FilterInvalid>,
         update: ProtoEntry,
+        expected_version: Option<String>,
         eventid: Uuid,
     ) -> Result<(), OperationError> {
+        // The version is the cid the entry was last changed at, as an etag may be quoted.
+        let expected_cid = expected_version
+            .map(|version| {
+                version.trim_matches('"').parse::<Cid>().map_err(|_| {
+                    error!(?version, "Invalid expected entry version");
+                    OperationError::MD0002EntryVersionInvalid
+                })
+            })
+            .transpose()?;
+
         // Given a protoEntry, turn this into a modification set.
         let ct = duration_from_epoch_now();
         let mut idms_prox_write = self.idms.proxy_write(ct).await?;
@@ -279,9 +361,10 @@ impl QueryServerWriteV1 {
         let mdf =
             ModifyEvent::from_internal_parts(ident, &modlist, &filter, &idms_prox_write.qs_write)
                 .map_err(|e| {
-                error!(err = ?e, "Failed to begin modify during handle_internalpatch");
-                e
-            })?;
+                    error!(err = ?e, "Failed to begin modify during handle_internalpatch");
+                    e
+                })?
+                .with_expected_cid(expected_cid);
 
         trace!(?mdf, "Begin modify event");
 
@@ -418,6 +501,7 @@ impl QueryServerWriteV1 {
         label: String,
         expiry: Option<OffsetDateTime>,
         read_write: bool,
+        operation_scopes: BTreeSet<ApiTokenOperationScope>,
         compact: bool,
         eventid: Uuid,
     ) -> Result<String, OperationError> {
@@ -444,6 +528,7 @@ impl QueryServerWriteV1 {
             label,
             expiry,
             read_write,
+            operation_scopes,
             compact,
         };
 
@@ -533,6 +618,40 @@ impl QueryServerWriteV1 {
             .and_then(|r| idms_prox_write.commit().map(|_| r))
     }
 
+    #[instrument(
+        level = "info",
+        name = "self_session_revoke",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_self_user_auth_token_destroy(
+        &self,
+        client_auth_info: ClientAuthInfo,
+        token_id: Uuid,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await?;
+        let ident = idms_prox_write
+            .validate_client_auth_info_to_ident(client_auth_info, ct)
+            .map_err(|e| {
+                error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let target = ident.get_uuid();
+
+        let dte = DestroySessionTokenEvent {
+            ident,
+            target,
+            token_id,
+        };
+
+        idms_prox_write
+            .account_destroy_session_token(&dte)
+            .and_then(|r| idms_prox_write.commit().map(|_| r))
+    }
+
     #[instrument(
         level = "info",
         skip_all,
@@ -776,6 +895,61 @@ impl QueryServerWriteV1 {
             })
     }
 
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_self_mail_verification_send(
+        &self,
+        client_auth_info: ClientAuthInfo,
+        mail: String,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await?;
+        let ident = idms_prox_write
+            .validate_client_auth_info_to_ident(client_auth_info, ct)
+            .inspect_err(|err| {
+                error!(?err, "Invalid identity");
+            })?;
+
+        let target = ident.get_uuid();
+
+        let event = MailVerificationSendEvent {
+            ident,
+            target,
+            mail,
+        };
+
+        idms_prox_write
+            .mail_verification_send(&event, ct)
+            .and_then(|_| idms_prox_write.commit())
+            .inspect_err(|err| {
+                error!(?err, "Failed to process mail_verification_send");
+            })
+    }
+
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_mail_verification_confirm(
+        &self,
+        token: String,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await?;
+        idms_prox_write
+            .mail_verification_confirm(&token, ct)
+            .and_then(|_| idms_prox_write.commit())
+            .inspect_err(|err| {
+                error!(?err, "Failed to process mail_verification_confirm");
+            })
+    }
+
     #[instrument(
         level = "info",
         skip_all,
@@ -1056,6 +1230,52 @@ impl QueryServerWriteV1 {
             .await
     }
 
+    #[instrument(
+        level = "info",
+        name = "group_add_members_expiring",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_idmgroupaddmembersexpiring(
+        &self,
+        client_auth_info: ClientAuthInfo,
+        uuid_or_name: String,
+        request: GroupAddMembersExpiring,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let expiry = request
+            .expiry
+            .to_offset(time::UtcOffset::UTC)
+            .format(&Rfc3339)
+            .map_err(|err| {
+                error!(?err, "Unable to format member expiry");
+                OperationError::InvalidRequestState
+            })?;
+
+        // The membership and its expiry are applied in the same modification, so that
+        // a member is never added without its expiry.
+        let proto_ml = ProtoModifyList::new_list(
+            request
+                .members
+                .into_iter()
+                .flat_map(|member| {
+                    [
+                        ProtoModify::Present(
+                            Attribute::MemberExpiry.to_string(),
+                            format!("{member}: {expiry}"),
+                        ),
+                        ProtoModify::Present(Attribute::Member.to_string(), member),
+                    ]
+                })
+                .collect(),
+        );
+
+        let filter = filter_all!(f_eq(Attribute::Class, EntryClass::Group.into()));
+
+        self.modify_from_parts(client_auth_info, &uuid_or_name, &proto_ml, filter)
+            .await
+    }
+
     #[instrument(
         level = "info",
         name = "set_attribute",
@@ -1086,6 +1306,36 @@ impl QueryServerWriteV1 {
             .await
     }
 
+    #[instrument(
+        level = "info",
+        name = "self_set_attribute",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_self_setattribute(
+        &self,
+        client_auth_info: ClientAuthInfo,
+        attr: String,
+        values: Vec<String>,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await?;
+        let ident = idms_prox_write
+            .validate_client_auth_info_to_ident(client_auth_info, ct)
+            .map_err(|e| {
+                error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let swe =
+            SelfWriteAttributeEvent::from_parts(ident, Attribute::from(attr.as_str()), values);
+
+        idms_prox_write
+            .self_write_attribute(&swe)
+            .and_then(|r| idms_prox_write.commit().map(|_| r))
+    }
+
     #[instrument(
         level = "info",
         name = "ssh_key_create",
@@ -1503,6 +1753,7 @@ impl QueryServerWriteV1 {
             ProtoOauth2ClaimMapJoin::Csv => OauthClaimMapJoin::CommaSeparatedValue,
             ProtoOauth2ClaimMapJoin::Ssv => OauthClaimMapJoin::SpaceSeparatedValue,
             ProtoOauth2ClaimMapJoin::Array => OauthClaimMapJoin::JsonArray,
+            ProtoOauth2ClaimMapJoin::Single => OauthClaimMapJoin::Single,
         };
 
         let ml = ModifyList::new_append(
@@ -1531,6 +1782,57 @@ impl QueryServerWriteV1 {
             .and_then(|_| idms_prox_write.commit().map(|_| ()))
     }
 
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(uuid = ?eventid)
+    )]
+    pub async fn handle_oauth2_claimmap_template_update(
+        &self,
+        client_auth_info: ClientAuthInfo,
+        claim_name: String,
+        template: Option<String>,
+        filter: Filter<FilterInvalid>,
+        eventid: Uuid,
+    ) -> Result<(), OperationError> {
+        // Because this is from internal, we can generate a real modlist, rather
+        // than relying on the proto ones.
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = self.idms.proxy_write(ct).await?;
+
+        let ident = idms_prox_write
+            .validate_client_auth_info_to_ident(client_auth_info, ct)
+            .map_err(|e| {
+                error!(err = ?e, "Invalid identity");
+                e
+            })?;
+
+        let ml = ModifyList::new_append(
+            Attribute::OAuth2RsClaimMap,
+            Value::OauthClaimTemplate(claim_name, template),
+        );
+
+        let mdf = match ModifyEvent::from_internal_parts(
+            ident,
+            &ml,
+            &filter,
+            &idms_prox_write.qs_write,
+        ) {
+            Ok(m) => m,
+            Err(e) => {
+                error!(err = ?e, "Failed to begin modify");
+                return Err(e);
+            }
+        };
+
+        trace!(?mdf, "Begin modify event");
+
+        idms_prox_write
+            .qs_write
+            .modify(&mdf)
+            .and_then(|_| idms_prox_write.commit().map(|_| ()))
+    }
+
     #[instrument(
         level = "info",
         skip_all,
@@ -1796,7 +2098,7 @@ impl QueryServerWriteV1 {
             .await
             .map_err(Oauth2Error::ServerError)?;
         idms_prox_write
-            .handle_oauth2_start_device_flow(client_auth_info, client_id, scope, eventid)
+            .handle_oauth2_start_device_flow(&client_auth_info, client_id, scope, eventid, ct)
             .and_then(|res| {
                 idms_prox_write.commit().map_err(Oauth2Error::ServerError)?;
                 Ok(res)
@@ -1,17 +1,23 @@
 use crate::actors::{QueryServerReadV1, QueryServerWriteV1};
+use crate::config::OnlineBackup;
 use crate::repl::ReplCtrl;
 use crate::CoreAction;
 use bytes::{BufMut, BytesMut};
 use crypto_glue::x509::x509b64;
 use futures::{SinkExt, StreamExt};
 pub use kanidm_proto::internal::{
-    DomainInfo as ProtoDomainInfo, DomainUpgradeCheckReport as ProtoDomainUpgradeCheckReport,
+    BackendCacheStats, DomainInfo as ProtoDomainInfo,
+    DomainUpgradeCheckReport as ProtoDomainUpgradeCheckReport,
     DomainUpgradeCheckStatus as ProtoDomainUpgradeCheckStatus,
 };
 use kanidm_utils_users::get_current_uid;
+use kanidmd_lib::event::OnlineBackupEvent;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
+use std::ffi::CString;
 use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tokio::net::{UnixListener, UnixStream};
 use tokio::sync::broadcast;
@@ -27,8 +33,12 @@ const REPL_CTRL_TIMEOUT: Duration = Duration::from_secs(15);
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum AdminTaskRequest {
-    RecoverAccount { name: String },
-    DisableAccount { name: String },
+    RecoverAccount {
+        name: String,
+    },
+    DisableAccount {
+        name: String,
+    },
     ShowReplicationCertificate,
     ShowReplicationCertificateMetadata,
     RenewReplicationCertificate,
@@ -36,8 +46,17 @@ pub enum AdminTaskRequest {
     DomainShow,
     DomainUpgradeCheck,
     DomainRaise,
-    DomainRemigrate { level: Option<u32> },
+    DomainRemigrate {
+        level: Option<u32>,
+    },
     Reload,
+    OnlineBackup,
+    BackendCacheStats,
+    BackendCacheTargets {
+        entry: Option<usize>,
+        idl: Option<usize>,
+    },
+    DbVacuum,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -63,6 +82,18 @@ pub enum AdminTaskResponse {
     DomainShow {
         domain_info: ProtoDomainInfo,
     },
+    OnlineBackup {
+        path: String,
+        snapshot_cid: Option<String>,
+    },
+    BackendCacheStats {
+        stats: BackendCacheStats,
+    },
+    DbVacuum {
+        dropped_idxs: Vec<String>,
+        size_before: u64,
+        size_after: u64,
+    },
     Success,
     Error,
 }
@@ -93,6 +124,27 @@ impl std::fmt::Debug for AdminTaskResponse {
             AdminTaskResponse::DomainShow { domain_info } => {
                 write!(f, "DomainShow {{ domain_info: {:?} }}", domain_info)
             }
+            AdminTaskResponse::OnlineBackup { path, snapshot_cid } => {
+                write!(
+                    f,
+                    "OnlineBackup {{ path: {:?}, snapshot_cid: {:?} }}",
+                    path, snapshot_cid
+                )
+            }
+            AdminTaskResponse::BackendCacheStats { stats } => {
+                write!(f, "BackendCacheStats {{ stats: {:?} }}", stats)
+            }
+            AdminTaskResponse::DbVacuum {
+                dropped_idxs,
+                size_before,
+                size_after,
+            } => {
+                write!(
+                    f,
+                    "DbVacuum {{ dropped_idxs: {:?}, size_before: {}, size_after: {} }}",
+                    dropped_idxs, size_before, size_after
+                )
+            }
             AdminTaskResponse::Success => write!(f, "Success"),
             AdminTaskResponse::Error => write!(f, "Error"),
         }
@@ -176,6 +228,8 @@ impl AdminActor {
         server_ro: &'static QueryServerReadV1,
         broadcast_tx: broadcast::Sender<CoreAction>,
         repl_ctrl_tx: Option<mpsc::Sender<ReplCtrl>>,
+        online_backup: Option<OnlineBackup>,
+        db_path: Option<PathBuf>,
     ) -> Result<tokio::task::JoinHandle<()>, ()> {
         debug!("🧹 Cleaning up sockets from previous invocations");
         rm_if_exist(sock_path);
@@ -226,8 +280,10 @@ impl AdminActor {
                                 // spawn the worker.
                                 let task_repl_ctrl_tx = repl_ctrl_tx.clone();
                                 let broadcast_tx_ = broadcast_tx.clone();
+                                let task_online_backup = online_backup.clone();
+                                let task_db_path = db_path.clone();
                                 tokio::spawn(async move {
-                                    if let Err(e) = handle_client(socket, server_rw, server_ro, task_repl_ctrl_tx, broadcast_tx_).await {
+                                    if let Err(e) = handle_client(socket, server_rw, server_ro, task_repl_ctrl_tx, broadcast_tx_, task_online_backup, task_db_path).await {
                                         error!(err = ?e, "admin client error");
                                     }
                                 });
@@ -397,12 +453,36 @@ async fn replication_consumer_refresh(ctrl_tx: &mut mpsc::Sender<ReplCtrl>) -> A
     }
 }
 
+/// The free space in bytes of the filesystem that holds `path`.
+fn available_space(path: &Path) -> io::Result<u64> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let c_dir = CString::new(dir.as_os_str().as_bytes())?;
+
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: c_dir is a valid nul terminated string, and statvfs initialises stat when
+    // it succeeds.
+    let stat = unsafe {
+        if libc::statvfs(c_dir.as_ptr(), stat.as_mut_ptr()) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        stat.assume_init()
+    };
+
+    #[allow(clippy::useless_conversion)]
+    Ok(u64::from(stat.f_bavail) * u64::from(stat.f_frsize))
+}
+
 async fn handle_client(
     sock: UnixStream,
     server_rw: &'static QueryServerWriteV1,
     server_ro: &'static QueryServerReadV1,
     mut repl_ctrl_tx: Option<mpsc::Sender<ReplCtrl>>,
     broadcast_tx: broadcast::Sender<CoreAction>,
+    online_backup: Option<OnlineBackup>,
+    db_path: Option<PathBuf>,
 ) -> Result<(), Box<dyn Error>> {
     debug!("Accepted admin socket connection");
 
@@ -505,6 +585,81 @@ async fn handle_client(
                         AdminTaskResponse::Error
                     }
                 },
+                AdminTaskRequest::OnlineBackup => {
+                    match online_backup
+                        .as_ref()
+                        .and_then(|ob| ob.path.as_ref().map(|outpath| (ob, outpath)))
+                    {
+                        Some((ob, outpath)) => {
+                            match server_ro
+                                .handle_online_backup(
+                                    OnlineBackupEvent::new(),
+                                    outpath,
+                                    ob.versions,
+                                    ob.compression,
+                                )
+                                .await
+                            {
+                                Ok((path, snapshot_cid)) => AdminTaskResponse::OnlineBackup {
+                                    path: path.display().to_string(),
+                                    snapshot_cid: snapshot_cid.map(|cid| cid.to_string()),
+                                },
+                                Err(e) => {
+                                    error!(err = ?e, "error during online backup");
+                                    AdminTaskResponse::Error
+                                }
+                            }
+                        }
+                        None => {
+                            error!("online backup path is not configured, unable to create backup.");
+                            AdminTaskResponse::Error
+                        }
+                    }
+                }
+                AdminTaskRequest::BackendCacheStats => {
+                    match server_ro.handle_backend_cache_stats(eventid).await {
+                        Ok(stats) => AdminTaskResponse::BackendCacheStats { stats },
+                        Err(e) => {
+                            error!(err = ?e, "error during backend cache stats");
+                            AdminTaskResponse::Error
+                        }
+                    }
+                }
+                AdminTaskRequest::BackendCacheTargets { entry, idl } => {
+                    match server_rw
+                        .handle_backend_cache_targets(entry, idl, eventid)
+                        .await
+                    {
+                        Ok(()) => AdminTaskResponse::Success,
+                        Err(e) => {
+                            error!(err = ?e, "error during backend cache targets");
+                            AdminTaskResponse::Error
+                        }
+                    }
+                }
+                AdminTaskRequest::DbVacuum => {
+                    // Without a path the database is in memory, and uses no disk space.
+                    let available_space = match db_path.as_deref().map(available_space) {
+                        Some(Ok(available)) => Some(available),
+                        Some(Err(e)) => {
+                            error!(err = ?e, "unable to determine free space for the database");
+                            return AdminTaskResponse::Error;
+                        }
+                        None => None,
+                    };
+
+                    match server_rw.handle_db_vacuum(available_space, eventid).await {
+                        Ok(report) => AdminTaskResponse::DbVacuum {
+                            dropped_idxs: report.dropped_idxs,
+                            size_before: report.size_before,
+                            size_after: report.size_after,
+                        },
+                        Err(e) => {
+                            error!(err = ?e, "error during database vacuum");
+                            AdminTaskResponse::Error
+                        }
+                    }
+                }
             }
         }
         .instrument(nspan)
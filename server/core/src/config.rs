@@ -14,13 +14,19 @@ use sketching::LogLevel;
 use std::fmt::{self, Display};
 use std::fs::File;
 use std::io::Read;
-use std::net::IpAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use url::Url;
 
 use crate::repl::config::ReplicationConfiguration;
+use kanidmd_lib::server::expiry::AccountExpiryNotice;
+use kanidmd_lib::server::scim::SCIM_BULK_DEFAULT_MAX_OPERATIONS;
+
+/// The default maximum size of a SCIM bulk request body.
+const DEFAULT_SCIM_BULK_MAX_PAYLOAD: usize = 4 * 1024 * 1024;
 
 #[derive(Debug, Deserialize)]
 struct VersionDetection {
@@ -106,6 +112,104 @@ fn default_online_backup_versions() -> usize {
     7
 }
 
+/// Notify that accounts are approaching their `account_expire` time. Notices are emitted as
+/// security events, so a [SecurityEventSinkConfig] should also be configured. Regardless of
+/// this setting, every session and token of an account is revoked once it expires.
+///
+/// eg:
+///
+/// ```toml
+/// [account_expiry]
+/// # Begin notifying a week before the account expires
+/// notice_window = 604800
+/// # and repeat each day until it expires.
+/// notice_interval = 86400
+/// ```
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct AccountExpiryConfig {
+    /// How long before an account expires that the first notice is sent, in seconds.
+    pub notice_window: u64,
+    /// How often the notice is repeated until the account expires, in seconds. Defaults
+    /// to one day. If zero, only the first notice is sent.
+    #[serde(default = "default_account_expiry_notice_interval")]
+    pub notice_interval: u64,
+}
+
+impl AccountExpiryConfig {
+    pub fn notice(&self) -> AccountExpiryNotice {
+        AccountExpiryNotice {
+            window: Duration::from_secs(self.notice_window),
+            interval: Duration::from_secs(self.notice_interval),
+        }
+    }
+}
+
+fn default_account_expiry_notice_interval() -> u64 {
+    24 * 60 * 60
+}
+
+/// A destination that security events (authentications, credential changes, access denials
+/// and replication conflicts) are exported to.
+///
+/// eg:
+///
+/// ```toml
+/// [[security_event_sinks]]
+/// type = "syslog"
+/// address = "192.0.2.1:514"
+/// transport = "tcp"
+///
+/// [[security_event_sinks]]
+/// type = "json_file"
+/// path = "/var/log/kanidm/security.jsonl"
+/// ```
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SecurityEventSinkConfig {
+    /// RFC 5424 syslog messages, sent to a collector over UDP or TCP.
+    Syslog {
+        address: SocketAddr,
+        #[serde(default)]
+        transport: SyslogTransport,
+    },
+    /// Newline delimited JSON, appended to a file.
+    JsonFile { path: PathBuf },
+    /// Newline delimited JSON, sent over a TCP connection.
+    JsonTcp { address: SocketAddr },
+}
+
+impl Display for SecurityEventSinkConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecurityEventSinkConfig::Syslog { address, transport } => {
+                write!(f, "syslog ({transport}) {address}")
+            }
+            SecurityEventSinkConfig::JsonFile { path } => {
+                write!(f, "json file {}", path.display())
+            }
+            SecurityEventSinkConfig::JsonTcp { address } => write!(f, "json tcp {address}"),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SyslogTransport {
+    #[default]
+    Udp,
+    Tcp,
+}
+
+impl Display for SyslogTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyslogTransport::Udp => write!(f, "udp"),
+            SyslogTransport::Tcp => write!(f, "tcp"),
+        }
+    }
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct TlsConfiguration {
     pub chain: PathBuf,
@@ -325,6 +429,9 @@ pub struct ServerConfig {
     /// Backup Configuration, see [OnlineBackup] for details on sub-keys.
     online_backup: Option<OnlineBackup>,
 
+    /// Account expiry notices, see [AccountExpiryConfig] for details on sub-keys.
+    account_expiry: Option<AccountExpiryConfig>,
+
     /// Trust the X-Forwarded-For header for client IP address. Defaults to false if unset.
     trust_x_forward_for: Option<bool>,
 
@@ -338,6 +445,12 @@ pub struct ServerConfig {
     /// Maximum Request Size in bytes
     maximum_request_size_bytes: Option<usize>,
 
+    /// Maximum number of operations accepted in a single SCIM bulk request. Defaults to 1000.
+    scim_bulk_max_operations: Option<usize>,
+
+    /// Maximum SCIM bulk request size in bytes. Defaults to 4MiB.
+    scim_bulk_max_payload_bytes: Option<usize>,
+
     /// Don't touch this unless you know what you're doing!
     #[allow(dead_code)]
     db_arc_size: Option<usize>,
@@ -347,6 +460,12 @@ pub struct ServerConfig {
     repl_config: Option<ReplicationConfiguration>,
     /// An optional OpenTelemetry collector (GRPC) url to send trace and log data to, eg `localhost:4317`. If not set, disables the feature.
     otel_grpc_endpoint: Option<String>,
+    /// Destinations that security events are exported to, see [SecurityEventSinkConfig] for details on sub-keys.
+    #[serde(default)]
+    security_event_sinks: Vec<SecurityEventSinkConfig>,
+    /// Client addresses that may read Prometheus metrics from `/metrics`. If empty, the endpoint is disabled.
+    #[serde(default)]
+    metrics_allow_list: Vec<IpCidr>,
 }
 
 impl ServerConfigUntagged {
@@ -412,6 +531,7 @@ pub struct ServerConfigV2 {
     tls_client_ca: Option<PathBuf>,
 
     migration_path: Option<PathBuf>,
+    password_breach_list_path: Option<PathBuf>,
 
     #[serde_as(as = "Option<OneOrMany<_, PreferOne>>")]
     bindaddress: Option<Vec<String>>,
@@ -421,6 +541,7 @@ pub struct ServerConfigV2 {
     role: Option<ServerRole>,
     log_level: Option<LogLevel>,
     online_backup: Option<OnlineBackup>,
+    account_expiry: Option<AccountExpiryConfig>,
 
     http_client_address_info: Option<HttpAddressInfo>,
     ldap_client_address_info: Option<LdapAddressInfo>,
@@ -428,12 +549,18 @@ pub struct ServerConfigV2 {
     adminbindpath: Option<String>,
     thread_count: Option<usize>,
     maximum_request_size_bytes: Option<usize>,
+    scim_bulk_max_operations: Option<usize>,
+    scim_bulk_max_payload_bytes: Option<usize>,
     #[allow(dead_code)]
     db_arc_size: Option<usize>,
     #[serde(default)]
     #[serde(rename = "replication")]
     repl_config: Option<ReplicationConfiguration>,
     otel_grpc_endpoint: Option<String>,
+    #[serde(default)]
+    security_event_sinks: Vec<SecurityEventSinkConfig>,
+    #[serde(default)]
+    metrics_allow_list: Vec<IpCidr>,
 }
 
 #[derive(Debug, Clone)]
@@ -465,8 +592,12 @@ pub struct Configuration {
     pub db_fs_type: Option<FsType>,
     pub db_arc_size: Option<usize>,
     pub maximum_request: usize,
+    pub scim_bulk_max_operations: usize,
+    pub scim_bulk_max_payload: usize,
 
     pub migration_path: Option<PathBuf>,
+    /// A directory of breached password range files, see [kanidmd_lib::idm::breachlist].
+    pub password_breach_list_path: Option<PathBuf>,
 
     pub http_client_address_info: HttpAddressInfo,
     pub ldap_client_address_info: LdapAddressInfo,
@@ -474,6 +605,7 @@ pub struct Configuration {
     pub tls_config: Option<TlsConfiguration>,
     pub integration_test_config: Option<Box<IntegrationTestConfig>>,
     pub online_backup: Option<OnlineBackup>,
+    pub account_expiry: Option<AccountExpiryConfig>,
     pub domain: String,
     pub origin: Url,
     pub role: ServerRole,
@@ -483,6 +615,8 @@ pub struct Configuration {
     /// This allows internally setting some unsafe options for replication.
     pub integration_repl_config: Option<Box<IntegrationReplConfig>>,
     pub otel_grpc_endpoint: Option<String>,
+    pub security_event_sinks: Vec<SecurityEventSinkConfig>,
+    pub metrics_allow_list: Vec<IpCidr>,
 }
 
 impl Configuration {
@@ -502,19 +636,25 @@ impl Configuration {
             db_fs_type: None,
             db_arc_size: None,
             migration_path: None,
+            password_breach_list_path: None,
             maximum_request: 256 * 1024, // 256k
+            scim_bulk_max_operations: SCIM_BULK_DEFAULT_MAX_OPERATIONS,
+            scim_bulk_max_payload: DEFAULT_SCIM_BULK_MAX_PAYLOAD,
             http_client_address_info: HttpAddressInfo::default(),
             ldap_client_address_info: LdapAddressInfo::default(),
             tls_key: None,
             tls_chain: None,
             tls_client_ca: None,
             online_backup: None,
+            account_expiry: None,
             domain: None,
             origin: None,
             log_level: None,
             role: None,
             repl_config: None,
             otel_grpc_endpoint: None,
+            security_event_sinks: Vec::new(),
+            metrics_allow_list: Vec::new(),
         }
     }
 
@@ -529,12 +669,16 @@ impl Configuration {
             db_fs_type: None,
             db_arc_size: None,
             migration_path: None,
+            password_breach_list_path: None,
             maximum_request: 256 * 1024, // 256k
+            scim_bulk_max_operations: SCIM_BULK_DEFAULT_MAX_OPERATIONS,
+            scim_bulk_max_payload: DEFAULT_SCIM_BULK_MAX_PAYLOAD,
             http_client_address_info: HttpAddressInfo::default(),
             ldap_client_address_info: LdapAddressInfo::default(),
             tls_config: None,
             integration_test_config: None,
             online_backup: None,
+            account_expiry: None,
             domain: "idm.example.com".to_string(),
             origin: Url::from_str("https://idm.example.com")
                 .expect("Failed to parse built-in string as URL"),
@@ -543,6 +687,8 @@ impl Configuration {
             repl_config: None,
             integration_repl_config: None,
             otel_grpc_endpoint: None,
+            security_event_sinks: Vec::new(),
+            metrics_allow_list: Vec::new(),
         }
     }
 }
@@ -577,6 +723,16 @@ impl fmt::Display for Configuration {
             None => write!(f, "arcsize: AUTO, "),
         }?;
         write!(f, "max request size: {}b, ", self.maximum_request)?;
+        write!(
+            f,
+            "scim bulk max operations: {}, ",
+            self.scim_bulk_max_operations
+        )?;
+        write!(
+            f,
+            "scim bulk max payload: {}b, ",
+            self.scim_bulk_max_payload
+        )?;
         write!(
             f,
             "http client address info: {}, ",
@@ -603,6 +759,14 @@ impl fmt::Display for Configuration {
             ),
             None => write!(f, "online_backup: disabled, "),
         }?;
+        match &self.account_expiry {
+            Some(expiry) => write!(
+                f,
+                "account expiry notice: window: {}s interval: {}s, ",
+                expiry.notice_window, expiry.notice_interval
+            ),
+            None => write!(f, "account expiry notice: disabled, "),
+        }?;
         write!(
             f,
             "integration mode: {}, ",
@@ -625,7 +789,23 @@ impl fmt::Display for Configuration {
                 write!(f, "replication: disabled, ")?;
             }
         }
-        write!(f, "otel_grpc_endpoint: {:?}", self.otel_grpc_endpoint)?;
+        write!(f, "otel_grpc_endpoint: {:?}, ", self.otel_grpc_endpoint)?;
+        if self.security_event_sinks.is_empty() {
+            write!(f, "security event sinks: disabled, ")?;
+        } else {
+            for sink in &self.security_event_sinks {
+                write!(f, "security event sink: {sink}, ")?;
+            }
+        }
+        if self.metrics_allow_list.is_empty() {
+            write!(f, "metrics: disabled")?;
+        } else {
+            f.write_str("metrics allow list: [ ")?;
+            for ip in &self.metrics_allow_list {
+                write!(f, "{ip} ")?;
+            }
+            f.write_str("]")?;
+        }
         Ok(())
     }
 }
@@ -641,19 +821,25 @@ pub struct ConfigurationBuilder {
     db_fs_type: Option<FsType>,
     db_arc_size: Option<usize>,
     migration_path: Option<PathBuf>,
+    password_breach_list_path: Option<PathBuf>,
     maximum_request: usize,
+    scim_bulk_max_operations: usize,
+    scim_bulk_max_payload: usize,
     http_client_address_info: HttpAddressInfo,
     ldap_client_address_info: LdapAddressInfo,
     tls_key: Option<PathBuf>,
     tls_chain: Option<PathBuf>,
     tls_client_ca: Option<PathBuf>,
     online_backup: Option<OnlineBackup>,
+    account_expiry: Option<AccountExpiryConfig>,
     domain: Option<String>,
     origin: Option<Url>,
     role: Option<ServerRole>,
     log_level: Option<LogLevel>,
     repl_config: Option<ReplicationConfiguration>,
     otel_grpc_endpoint: Option<String>,
+    security_event_sinks: Vec<SecurityEventSinkConfig>,
+    metrics_allow_list: Vec<IpCidr>,
 }
 
 impl ConfigurationBuilder {
@@ -872,6 +1058,14 @@ impl ConfigurationBuilder {
             self.maximum_request = maximum;
         }
 
+        if let Some(maximum) = config.scim_bulk_max_operations {
+            self.scim_bulk_max_operations = maximum;
+        }
+
+        if let Some(maximum) = config.scim_bulk_max_payload_bytes {
+            self.scim_bulk_max_payload = maximum;
+        }
+
         if config.db_arc_size.is_some() {
             self.db_arc_size = config.db_arc_size;
         }
@@ -884,6 +1078,10 @@ impl ConfigurationBuilder {
             self.online_backup = config.online_backup;
         }
 
+        if config.account_expiry.is_some() {
+            self.account_expiry = config.account_expiry;
+        }
+
         if config.repl_config.is_some() {
             self.repl_config = config.repl_config;
         }
@@ -892,6 +1090,14 @@ impl ConfigurationBuilder {
             self.otel_grpc_endpoint = config.otel_grpc_endpoint;
         }
 
+        if !config.security_event_sinks.is_empty() {
+            self.security_event_sinks = config.security_event_sinks;
+        }
+
+        if !config.metrics_allow_list.is_empty() {
+            self.metrics_allow_list = config.metrics_allow_list;
+        }
+
         self
     }
 
@@ -912,6 +1118,10 @@ impl ConfigurationBuilder {
             self.migration_path = config.migration_path;
         }
 
+        if config.password_breach_list_path.is_some() {
+            self.password_breach_list_path = config.password_breach_list_path;
+        }
+
         if config.db_fs_type.is_some() {
             self.db_fs_type = config.db_fs_type;
         }
@@ -956,6 +1166,14 @@ impl ConfigurationBuilder {
             self.maximum_request = maximum;
         }
 
+        if let Some(maximum) = config.scim_bulk_max_operations {
+            self.scim_bulk_max_operations = maximum;
+        }
+
+        if let Some(maximum) = config.scim_bulk_max_payload_bytes {
+            self.scim_bulk_max_payload = maximum;
+        }
+
         if config.db_arc_size.is_some() {
             self.db_arc_size = config.db_arc_size;
         }
@@ -972,6 +1190,10 @@ impl ConfigurationBuilder {
             self.online_backup = config.online_backup;
         }
 
+        if config.account_expiry.is_some() {
+            self.account_expiry = config.account_expiry;
+        }
+
         if config.repl_config.is_some() {
             self.repl_config = config.repl_config;
         }
@@ -980,6 +1202,14 @@ impl ConfigurationBuilder {
             self.otel_grpc_endpoint = config.otel_grpc_endpoint;
         }
 
+        if !config.security_event_sinks.is_empty() {
+            self.security_event_sinks = config.security_event_sinks;
+        }
+
+        if !config.metrics_allow_list.is_empty() {
+            self.metrics_allow_list = config.metrics_allow_list;
+        }
+
         self
     }
 
@@ -1001,19 +1231,25 @@ impl ConfigurationBuilder {
             db_fs_type,
             db_arc_size,
             migration_path,
+            password_breach_list_path,
             maximum_request,
+            scim_bulk_max_operations,
+            scim_bulk_max_payload,
             http_client_address_info,
             ldap_client_address_info,
             tls_key,
             tls_chain,
             tls_client_ca,
             mut online_backup,
+            account_expiry,
             domain,
             origin,
             role,
             log_level,
             repl_config,
             otel_grpc_endpoint,
+            security_event_sinks,
+            metrics_allow_list,
         } = self;
 
         let tls_config = match (tls_key, tls_chain, tls_client_ca) {
@@ -1068,17 +1304,23 @@ impl ConfigurationBuilder {
             db_fs_type,
             db_arc_size,
             migration_path,
+            password_breach_list_path,
             maximum_request,
+            scim_bulk_max_operations,
+            scim_bulk_max_payload,
             http_client_address_info,
             ldap_client_address_info,
             tls_config,
             online_backup,
+            account_expiry,
             domain,
             origin,
             role,
             log_level,
             repl_config,
             otel_grpc_endpoint,
+            security_event_sinks,
+            metrics_allow_list,
             integration_repl_config: None,
             integration_test_config: None,
         })
@@ -47,6 +47,7 @@ impl Modify for SecurityAddon {
 
     paths(
         super::generic::status,
+        super::generic::metrics,
         super::generic::robots_txt,
 
         super::oauth2::oauth2_image_get,
@@ -70,6 +71,8 @@ impl Modify for SecurityAddon {
         super::v1_oauth2::oauth2_id_sup_scopemap_post,
         super::v1_oauth2::oauth2_id_sup_scopemap_delete,
         super::v1_oauth2::oauth2_id_claimmap_join_post,
+        super::v1_oauth2::oauth2_id_claimmap_template_post,
+        super::v1_oauth2::oauth2_id_claimmap_template_delete,
         super::v1_oauth2::oauth2_id_claimmap_post,
         super::v1_oauth2::oauth2_id_claimmap_delete,
 
@@ -80,6 +83,7 @@ impl Modify for SecurityAddon {
         super::v1_scim::scim_entry_put,
         super::v1_scim::scim_entry_id_get,
         super::v1_scim::scim_entry_id_delete,
+        super::v1_scim::scim_bulk_post,
         super::v1_scim::scim_person_id_get,
         super::v1_scim::scim_person_id_application_create_password,
         super::v1_scim::scim_person_id_application_delete_password,
@@ -96,8 +100,16 @@ impl Modify for SecurityAddon {
         super::v1_scim::scim_message_id_sent_post,
 
         super::v1::schema_get,
+        super::v1::schema_syntax_get,
         super::v1::whoami,
         super::v1::whoami_uat,
+        super::v1::whoami_api_token,
+        super::v1::self_application_password_list,
+        super::v1::self_put_attr,
+        super::v1::self_mail_verify_send,
+        super::v1::mail_verify_confirm,
+        super::v1::self_session_list,
+        super::v1::self_session_revoke,
         super::v1::applinks_get,
         super::v1::schema_attributetype_get,
         super::v1::schema_attributetype_get_id,
@@ -105,6 +117,7 @@ impl Modify for SecurityAddon {
         super::v1::schema_classtype_get_id,
         super::v1::person_get,
         super::v1::person_post,
+        super::v1::person_batch_post,
         super::v1::service_account_credential_generate,
         super::v1::service_account_api_token_delete,
         super::v1::service_account_api_token_get,
@@ -185,6 +198,7 @@ impl Modify for SecurityAddon {
 
         super::v1::group_id_unix_token_get,
         super::v1::group_id_unix_post,
+        super::v1::group_id_member_expiring_post,
         super::v1::group_get,
         super::v1::group_post,
         super::v1::group_search_id,
@@ -195,6 +209,19 @@ impl Modify for SecurityAddon {
         super::v1::group_id_attr_get,
         super::v1::group_id_attr_put,
         super::v1::group_id_attr_post,
+        super::v1::automount_get,
+        super::v1::automount_post,
+        super::v1::automount_id_get,
+        super::v1::automount_id_delete,
+        super::v1::automount_id_key_get,
+        super::v1::automount_id_key_post,
+        super::v1::automount_key_id_delete,
+        super::v1::webhook_get,
+        super::v1::webhook_post,
+        super::v1::webhook_id_get,
+        super::v1::webhook_id_delete,
+        super::v1::webhook_id_get_secret,
+        super::v1::audit_search_post,
         super::v1::system_get,
         super::v1::system_attr_get,
         super::v1::system_attr_post,
@@ -227,6 +254,7 @@ impl Modify for SecurityAddon {
 
             scim_v1::ScimSyncState,
             scim_v1::ScimSyncRequest,
+            scim_v1::ScimSyncApplyResponse,
             scim_v1::ScimSyncRetentionMode,
             scim_v1::ScimEntry,
             scim_v1::ScimValue,
@@ -234,12 +262,22 @@ impl Modify for SecurityAddon {
             scim_v1::ScimAttr,
             scim_v1::ScimApplicationPasswordCreate,
             scim_v1::ScimApplicationPassword,
+            scim_v1::ScimApplicationPasswordInfo,
             scim_v1::client::ScimEntryPostGeneric,
+            scim_v1::ScimBulkMethod,
+            scim_v1::ScimBulkOperation,
+            scim_v1::ScimBulkRequest,
+            scim_v1::ScimBulkOperationResponse,
+            scim_v1::ScimBulkResponse,
 
             internal::ApiToken,
+            internal::ApiTokenOperationScope,
             internal::ApiTokenPurpose,
             internal::BackupCodesView,
             internal::ConsistencyError,
+            internal::BatchCreateRequest,
+            internal::BatchCreateResponse,
+            internal::BatchCreateStatus,
             internal::CreateRequest,
             internal::CredentialDetail,
             internal::CredentialDetailType,
@@ -264,12 +302,17 @@ impl Modify for SecurityAddon {
             internal::SchemaError,
             internal::SearchRequest,
             internal::SearchResponse,
+            internal::SyntaxInfo,
             internal::TotpAlgo,
             internal::TotpSecret,
             internal::UatPurpose,
             internal::UserAuthToken,
             v1::AccountUnixExtend,
             v1::ApiTokenGenerate,
+            v1::AuditOperation,
+            v1::AuditQuery,
+            v1::AuditRecord,
+            v1::AuditRecordPage,
             v1::AuthAllowed,
             v1::AuthCredential,
             v1::AuthIssueSession,
@@ -279,7 +322,10 @@ impl Modify for SecurityAddon {
             v1::AuthState,
             v1::AuthStep,
             v1::Entry,
+            v1::GroupListEntry,
+            v1::GroupMemberCounts,
             v1::GroupUnixExtend,
+            v1::GroupAddMembersExpiring,
             v1::PublicKeyKindSchema,
             v1::SingleStringRequest,
             v1::SshPublicKeySchema,
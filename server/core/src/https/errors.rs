@@ -6,7 +6,7 @@ use axum::http::{HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 
-use hyper::header::WWW_AUTHENTICATE;
+use hyper::header::{ETAG, WWW_AUTHENTICATE};
 use kanidm_proto::oauth2::ErrorResponse;
 use kanidmd_lib::idm::oauth2::Oauth2Error;
 use utoipa::ToSchema;
@@ -47,6 +47,35 @@ impl WebError {
     }
 }
 
+/// Determine the HTTP status code that represents an operation error.
+pub(crate) fn operation_error_status_code(inner: &OperationError) -> StatusCode {
+    match inner {
+        OperationError::NotAuthenticated
+        | OperationError::SessionExpired
+        | OperationError::AU0009ApiTokenMaxAgeExceeded => StatusCode::UNAUTHORIZED,
+        OperationError::SystemProtectedObject
+        | OperationError::AccessDenied
+        | OperationError::AU0010ApiTokenOperationNotInScope => StatusCode::FORBIDDEN,
+        OperationError::NoMatchingEntries => StatusCode::NOT_FOUND,
+        OperationError::AttributeUniqueness(_)
+        | OperationError::SC0035BulkIdUnresolvable
+        | OperationError::AP0001ApplicationPasswordLimitReached => StatusCode::CONFLICT,
+        OperationError::SC0034BulkTooManyOperations => StatusCode::PAYLOAD_TOO_LARGE,
+        OperationError::PasswordQuality(_)
+        | OperationError::EmptyRequest
+        | OperationError::InvalidAttribute(_)
+        | OperationError::InvalidAttributeName(_)
+        | OperationError::SchemaViolation(_)
+        | OperationError::CU0003WebauthnUserNotVerified
+        | OperationError::VL0001ValueSshPublicKeyString
+        | OperationError::SC0036BulkIdDuplicate
+        | OperationError::SC0037BulkOperationInvalid
+        | OperationError::MD0002EntryVersionInvalid => StatusCode::BAD_REQUEST,
+        OperationError::MD0001EntryVersionConflict(_) => StatusCode::PRECONDITION_FAILED,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
 impl IntoResponse for WebError {
     fn into_response(self) -> Response {
         match self {
@@ -93,30 +122,24 @@ impl IntoResponse for WebError {
                 })),
             )
                 .into_response(),
+            WebError::OperationError(OperationError::MD0001EntryVersionConflict(current)) => {
+                let code = StatusCode::PRECONDITION_FAILED;
+                let etag = format!("\"{current}\"");
+                let body =
+                    serde_json::to_string(&OperationError::MD0001EntryVersionConflict(current))
+                        .unwrap_or_default();
+                (code, [(ETAG, etag)], body).into_response()
+            }
             WebError::OperationError(inner) => {
-                let (code, headers) = match &inner {
-                    OperationError::NotAuthenticated | OperationError::SessionExpired => {
+                let code = operation_error_status_code(&inner);
+                let headers = match &inner {
+                    OperationError::NotAuthenticated
+                    | OperationError::SessionExpired
+                    | OperationError::AU0009ApiTokenMaxAgeExceeded => {
                         // https://datatracker.ietf.org/doc/html/rfc7235#section-4.1
-                        (
-                            StatusCode::UNAUTHORIZED,
-                            Some([("WWW-Authenticate", "Bearer"); 1]),
-                        )
+                        Some([("WWW-Authenticate", "Bearer"); 1])
                     }
-                    OperationError::SystemProtectedObject | OperationError::AccessDenied => {
-                        (StatusCode::FORBIDDEN, None)
-                    }
-
-                    OperationError::NoMatchingEntries => (StatusCode::NOT_FOUND, None),
-                    OperationError::PasswordQuality(_)
-                    | OperationError::EmptyRequest
-                    | OperationError::InvalidAttribute(_)
-                    | OperationError::InvalidAttributeName(_)
-                    | OperationError::SchemaViolation(_)
-                    | OperationError::CU0003WebauthnUserNotVerified
-                    | OperationError::VL0001ValueSshPublicKeyString => {
-                        (StatusCode::BAD_REQUEST, None)
-                    }
-                    _ => (StatusCode::INTERNAL_SERVER_ERROR, None),
+                    _ => None,
                 };
                 let body = serde_json::to_string(&inner).unwrap_or(inner.to_string());
                 debug!(?body);
@@ -144,4 +167,20 @@ mod tests {
         let response = err.into_response();
         assert_eq!(response.status(), StatusCode::CONFLICT);
     }
+
+    #[test]
+    fn test_entry_version_conflict_error_response() {
+        use super::*;
+        use axum::response::IntoResponse;
+
+        let err = WebError::OperationError(OperationError::MD0001EntryVersionConflict(
+            "current".to_string(),
+        ));
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+        assert_eq!(
+            response.headers().get(ETAG).and_then(|v| v.to_str().ok()),
+            Some("\"current\"")
+        );
+    }
 }
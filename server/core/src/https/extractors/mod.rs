@@ -1,11 +1,15 @@
 use crate::https::ServerState;
 use axum::{
     extract::{connect_info::Connected, FromRequestParts},
-    http::{header::AUTHORIZATION, request::Parts, StatusCode},
+    http::{
+        header::{AUTHORIZATION, USER_AGENT},
+        request::Parts,
+        Method, StatusCode,
+    },
 };
 use axum_extra::extract::cookie::CookieJar;
 use compact_jwt::JwsCompact;
-use kanidm_proto::internal::COOKIE_BEARER_TOKEN;
+use kanidm_proto::internal::{ApiTokenOperationScope, COOKIE_BEARER_TOKEN};
 use kanidmd_lib::prelude::{ClientAuthInfo, ClientCertInfo, Source};
 use std::net::{IpAddr, SocketAddr};
 use std::str::FromStr;
@@ -78,6 +82,10 @@ impl FromRequestParts<ServerState> for VerifiedClientInformation {
             basic_authz,
         );
 
+        client_auth_info
+            .set_operation_scope(api_token_operation_scope(&parts.method, parts.uri.path()));
+        client_auth_info.set_user_agent(user_agent(parts));
+
         // now, we want to update the client auth info with the sessions user-auth-token
         // if any. We ignore errors here as the auth info MAY NOT be a valid token
         // and so in that case no prevalidation will occur.
@@ -90,6 +98,35 @@ impl FromRequestParts<ServerState> for VerifiedClientInformation {
     }
 }
 
+/// The user agent presented by the client, if it is valid.
+fn user_agent(parts: &Parts) -> Option<&str> {
+    parts
+        .headers
+        .get(USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+}
+
+/// Determine the class of operation a request performs, so that api tokens restricted to a
+/// set of operation scopes can be limited to them. Requests that are not part of any class
+/// can not be made with a scoped api token.
+fn api_token_operation_scope(method: &Method, path: &str) -> Option<ApiTokenOperationScope> {
+    let read = method == Method::GET || method == Method::HEAD;
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    match segments.as_slice() {
+        ["v1", "account", _, "_radius", "_token"] | ["v1", "person", _, "_radius"] if read => {
+            Some(ApiTokenOperationScope::RadiusRead)
+        }
+        ["v1", "person", ..] if read => Some(ApiTokenOperationScope::PersonRead),
+        ["v1", "person", ..] => Some(ApiTokenOperationScope::PersonWrite),
+        ["v1", "group", ..] if read => Some(ApiTokenOperationScope::GroupRead),
+        ["v1", "group", ..] => Some(ApiTokenOperationScope::GroupWrite),
+        ["v1", "service_account", ..] if read => Some(ApiTokenOperationScope::ServiceAccountRead),
+        ["v1", "service_account", ..] => Some(ApiTokenOperationScope::ServiceAccountWrite),
+        _ => None,
+    }
+}
+
 pub struct AuthorisationHeaders(pub ClientAuthInfo);
 
 impl FromRequestParts<ServerState> for AuthorisationHeaders {
@@ -141,13 +178,15 @@ impl FromRequestParts<ServerState> for AuthorisationHeaders {
             (None, None)
         };
 
-        let client_auth_info = ClientAuthInfo::new(
+        let mut client_auth_info = ClientAuthInfo::new(
             Source::Https(client_ip_addr),
             client_cert,
             bearer_token,
             basic_authz,
         );
 
+        client_auth_info.set_user_agent(user_agent(parts));
+
         Ok(AuthorisationHeaders(client_auth_info))
     }
 }
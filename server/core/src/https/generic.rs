@@ -1,11 +1,14 @@
 use axum::extract::State;
 use axum::http::header::CONTENT_TYPE;
-use axum::response::{IntoResponse, Redirect};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Redirect, Response};
 use axum::{Extension, Json};
+use kanidmd_lib::metrics::METRICS;
 use kanidmd_lib::prelude::APPLICATION_JSON;
 use kanidmd_lib::status::StatusRequestEvent;
 use url::Url;
 
+use super::extractors::ClientConnInfo;
 use super::middleware::KOpId;
 use super::views::constants::Urls;
 use super::ServerState;
@@ -34,6 +37,39 @@ pub async fn status(
         .into()
 }
 
+/// The content type of the Prometheus text exposition format.
+const PROMETHEUS_TEXT: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses(
+        (status = 200, description = "Ok", content_type = PROMETHEUS_TEXT, body=String),
+        (status = 403, description = "The client address is not in the metrics allow list"),
+        (status = 404, description = "Metrics are not enabled"),
+    ),
+    tag = "system",
+    operation_id = "metrics"
+)]
+/// Server metrics in the Prometheus text exposition format. Only clients in the
+/// configured metrics allow list may read these.
+pub async fn metrics(
+    State(state): State<ServerState>,
+    Extension(client_conn_info): Extension<ClientConnInfo>,
+) -> Response {
+    let Some(allow_list) = state.metrics_allow_list.as_ref() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let client_ip_addr = client_conn_info.client_ip_addr.to_canonical();
+    if !allow_list.contains(&client_ip_addr) {
+        warn!(%client_ip_addr, "Denied access to metrics");
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    ([(CONTENT_TYPE, PROMETHEUS_TEXT)], METRICS.render()).into_response()
+}
+
 #[utoipa::path(
     get,
     path = "/robots.txt",
@@ -10,7 +10,9 @@ use axum::{
     RequestExt,
 };
 use kanidm_proto::constants::{KOPID, KVERSION, X_FORWARDED_FOR};
+use kanidmd_lib::metrics::METRICS;
 use std::net::IpAddr;
+use std::time::Instant;
 use uuid::Uuid;
 
 #[allow(clippy::declare_interior_mutable_const)]
@@ -33,6 +35,14 @@ pub async fn version_middleware(request: Request<Body>, next: Next) -> Response
     response
 }
 
+/// Records the time taken to respond to each request in the server metrics.
+pub async fn metrics_middleware(request: Request<Body>, next: Next) -> Response {
+    let start = Instant::now();
+    let response = next.run(request).await;
+    METRICS.http_request_duration.observe(start.elapsed());
+    response
+}
+
 #[cfg(any(test, debug_assertions))]
 /// This is a debug middleware to ensure that /v1/ endpoints only return JSON
 #[instrument(level = "trace", name = "are_we_json_yet", skip_all)]
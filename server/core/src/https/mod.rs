@@ -73,6 +73,8 @@ pub struct ServerState {
     // Store the token management parts.
     pub(crate) jws_signer: JwsHs256Signer,
     pub(crate) trust_x_forward_for_ips: Option<Arc<AddressSet>>,
+    /// The client addresses that may read metrics. If `None` the metrics endpoint is disabled.
+    pub(crate) metrics_allow_list: Option<Arc<AddressSet>>,
     pub(crate) csp_header: HeaderValue,
     pub(crate) csp_header_no_form_action: HeaderValue,
     pub(crate) origin: Url,
@@ -81,6 +83,10 @@ pub struct ServerState {
     pub(crate) secure_cookies: bool,
     /// So that we can work out which ID to use for spans
     pub(crate) logging_pipeline: LoggerType,
+    /// The maximum number of operations in a single SCIM bulk request.
+    pub(crate) scim_bulk_max_operations: usize,
+    /// The maximum size in bytes of a SCIM bulk request body.
+    pub(crate) scim_bulk_max_payload: usize,
 }
 
 impl ServerState {
@@ -267,6 +273,14 @@ pub async fn create_https_server(
 
     let trusted_tcp_info_ips = config.http_client_address_info.trusted_tcp_info();
 
+    let metrics_allow_list = if config.metrics_allow_list.is_empty() {
+        None
+    } else {
+        Some(Arc::new(AddressSet::NonContiguousIpSet(
+            config.metrics_allow_list.clone(),
+        )))
+    };
+
     let logging_pipeline = if config.otel_grpc_endpoint.is_some() {
         LoggerType::OpenTelemetry
     } else {
@@ -279,12 +293,15 @@ pub async fn create_https_server(
         qe_r_ref,
         jws_signer,
         trust_x_forward_for_ips,
+        metrics_allow_list,
         csp_header,
         csp_header_no_form_action,
         origin: config.origin,
         domain: config.domain.clone(),
         secure_cookies: config.integration_test_config.is_none(),
         logging_pipeline,
+        scim_bulk_max_operations: config.scim_bulk_max_operations,
+        scim_bulk_max_payload: config.scim_bulk_max_payload,
     };
 
     let static_routes = match config.role {
@@ -305,7 +322,7 @@ pub async fn create_https_server(
     };
     let app = Router::new()
         .merge(oauth2::route_setup(state.clone()))
-        .merge(v1_scim::route_setup())
+        .merge(v1_scim::route_setup(state.clone()))
         .merge(v1::route_setup(state.clone()))
         .route("/robots.txt", get(generic::robots_txt))
         .route(
@@ -364,6 +381,7 @@ pub async fn create_https_server(
 
     let app = app
         .route("/status", get(generic::status))
+        .route("/metrics", get(generic::metrics))
         // 404 handler
         .fallback(handler_404)
         // This must be the LAST middleware.
@@ -380,6 +398,8 @@ pub async fn create_https_server(
             StatusCode::REQUEST_TIMEOUT,
             HTTPS_CLIENT_REQUEST_TIMEOUT,
         ))
+        // Record latency outside of the timeout, so that requests that time out are included.
+        .layer(from_fn(middleware::metrics_middleware))
         // this MUST be the last functional layer before with_state else the span never starts and everything breaks.
         .layer(trace_layer)
         // OK except for the ip_address_middleware.
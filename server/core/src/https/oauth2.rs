@@ -6,7 +6,7 @@ use super::ServerState;
 use crate::https::extractors::{AuthorisationHeaders, VerifiedClientInformation};
 use axum::{
     body::Body,
-    extract::{Path, Query, State},
+    extract::{Path, Query, RawQuery, State},
     http::{
         header::{
             ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_ORIGIN, CONTENT_TYPE, LOCATION,
@@ -27,7 +27,8 @@ use kanidm_proto::constants::APPLICATION_JSON;
 use kanidm_proto::oauth2::AuthorisationResponse;
 use kanidmd_lib::idm::oauth2::{
     AccessTokenIntrospectRequest, AccessTokenRequest, AuthorisationRequest,
-    AuthorisationRequestContext, AuthoriseResponse, ErrorResponse, Oauth2Error, TokenRevokeRequest,
+    AuthorisationRequestContext, AuthorisationRequestKind, AuthoriseResponse, ErrorResponse,
+    Oauth2Error, PushedAuthorisationReference, TokenRevokeRequest,
 };
 use kanidmd_lib::prelude::f_eq;
 use kanidmd_lib::prelude::*;
@@ -41,7 +42,10 @@ use kanidm_proto::oauth2::DeviceAuthorizationResponse;
 
 #[cfg(feature = "dev-oauth2-device-flow")]
 use uri::OAUTH2_AUTHORISE_DEVICE;
-use uri::{OAUTH2_TOKEN_ENDPOINT, OAUTH2_TOKEN_INTROSPECT_ENDPOINT, OAUTH2_TOKEN_REVOKE_ENDPOINT};
+use uri::{
+    OAUTH2_PAR_ENDPOINT, OAUTH2_TOKEN_ENDPOINT, OAUTH2_TOKEN_INTROSPECT_ENDPOINT,
+    OAUTH2_TOKEN_REVOKE_ENDPOINT,
+};
 
 // == Oauth2 Configuration Endpoints ==
 
@@ -153,14 +157,49 @@ pub(crate) async fn oauth2_image_get(
 //  cases. As a result, we must have our state in localised tokens so that any
 //  valid Kanidm instance in the topology can handle these request.
 //
+//  The exception is pushed authorisation requests (oauth2_pushed_authorisation_post),
+//  which are held in memory by the instance that received them. In a HA setup the
+//  client and User-Agent must reach the same instance for these to succeed.
+//
+
+/// A query carrying a `request_uri` refers to a pushed authorisation request, otherwise
+/// the query must contain the full authorisation request.
+pub(crate) fn authorisation_request_from_query(
+    query: Option<&str>,
+) -> Result<AuthorisationRequestKind, serde_urlencoded::de::Error> {
+    let query = query.unwrap_or_default();
+    let params: Vec<(String, String)> = serde_urlencoded::from_str(query)?;
+
+    if params.iter().any(|(k, _)| k == "request_uri") {
+        serde_urlencoded::from_str::<PushedAuthorisationReference>(query)
+            .map(AuthorisationRequestKind::Pushed)
+    } else {
+        serde_urlencoded::from_str::<AuthorisationRequest>(query)
+            .map(|auth_req| AuthorisationRequestKind::Inline(Box::new(auth_req)))
+    }
+}
 
 #[instrument(level = "debug", skip(state, kopid))]
 pub async fn oauth2_authorise_post(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
     AuthorisationHeaders(client_auth_info): AuthorisationHeaders,
-    Json(auth_req): Json<AuthorisationRequest>,
+    Json(auth_req): Json<serde_json::Value>,
 ) -> impl IntoResponse {
+    let auth_req = if auth_req.get("request_uri").is_some() {
+        serde_json::from_value(auth_req).map(AuthorisationRequestKind::Pushed)
+    } else {
+        serde_json::from_value(auth_req).map(AuthorisationRequestKind::Inline)
+    };
+
+    let auth_req = match auth_req {
+        Ok(auth_req) => auth_req,
+        Err(err) => {
+            warn!(?err, "Unable to deserialise authorisation request");
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+    };
+
     let mut res = oauth2_authorise(state, auth_req, kopid, client_auth_info)
         .await
         .into_response();
@@ -176,15 +215,25 @@ pub async fn oauth2_authorise_get(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
     AuthorisationHeaders(client_auth_info): AuthorisationHeaders,
-    Query(auth_req): Query<AuthorisationRequest>,
+    RawQuery(query): RawQuery,
 ) -> impl IntoResponse {
+    let auth_req = match authorisation_request_from_query(query.as_deref()) {
+        Ok(auth_req) => auth_req,
+        Err(err) => {
+            warn!(?err, "Unable to deserialise authorisation request");
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+    };
+
     // Start the oauth2 authorisation flow to present to the user.
-    oauth2_authorise(state, auth_req, kopid, client_auth_info).await
+    oauth2_authorise(state, auth_req, kopid, client_auth_info)
+        .await
+        .into_response()
 }
 
 async fn oauth2_authorise(
     state: ServerState,
-    auth_req: AuthorisationRequest,
+    auth_req: AuthorisationRequestKind,
     kopid: KOpId,
     client_auth_info: ClientAuthInfo,
 ) -> impl IntoResponse {
@@ -476,6 +525,32 @@ pub async fn oauth2_token_post(
     }
 }
 
+/// Pushed Authorisation Request [RFC9126](https://datatracker.ietf.org/doc/html/rfc9126)
+///
+/// The client submits the authorisation request parameters directly, and in return is
+/// given a short lived `request_uri` to send the User-Agent to the authorisation endpoint with.
+#[instrument(level = "debug", skip(state, kopid, client_auth_info))]
+pub async fn oauth2_pushed_authorisation_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    AuthorisationHeaders(client_auth_info): AuthorisationHeaders,
+    Form(auth_req): Form<AuthorisationRequest>,
+) -> impl IntoResponse {
+    match state
+        .qe_r_ref
+        .handle_oauth2_pushed_authorisation_request(client_auth_info, auth_req, kopid.eventid)
+        .await
+    {
+        Ok(par_res) => (
+            StatusCode::CREATED,
+            [(ACCESS_CONTROL_ALLOW_ORIGIN, "*")],
+            Json(par_res),
+        )
+            .into_response(),
+        Err(e) => WebError::OAuth2(e).into_response(),
+    }
+}
+
 // For future openid integration
 pub async fn oauth2_openid_discovery_get(
     State(state): State<ServerState>,
@@ -617,12 +692,13 @@ pub async fn oauth2_openid_publickey_get(
 pub async fn oauth2_token_introspect_post(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
+    AuthorisationHeaders(client_auth_info): AuthorisationHeaders,
     Form(intr_req): Form<AccessTokenIntrospectRequest>,
 ) -> impl IntoResponse {
     request_trace!("Introspect Request - {:?}", intr_req);
     let res = state
         .qe_r_ref
-        .handle_oauth2_token_introspect(intr_req, kopid.eventid)
+        .handle_oauth2_token_introspect(client_auth_info, intr_req, kopid.eventid)
         .await;
 
     match res {
@@ -843,6 +919,12 @@ pub fn route_setup(state: ServerState) -> Router<ServerState> {
         )
         // ⚠️  ⚠️   WARNING  ⚠️  ⚠️
         // IF YOU CHANGE THESE VALUES YOU MUST UPDATE OIDC DISCOVERY URLS
+        .route(
+            OAUTH2_PAR_ENDPOINT,
+            post(oauth2_pushed_authorisation_post).options(oauth2_preflight_options),
+        )
+        // ⚠️  ⚠️   WARNING  ⚠️  ⚠️
+        // IF YOU CHANGE THESE VALUES YOU MUST UPDATE OIDC DISCOVERY URLS
         .route(
             OAUTH2_TOKEN_INTROSPECT_ENDPOINT,
             post(oauth2_token_introspect_post),
@@ -6,7 +6,8 @@ use super::middleware::KOpId;
 use super::ServerState;
 use crate::https::apidocs::response_schema::{ApiResponseWithout200, DefaultApiResponse};
 use crate::https::extractors::{ClientConnInfo, VerifiedClientInformation};
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
+use axum::http::header::IF_MATCH;
 use axum::http::{HeaderMap, HeaderValue};
 use axum::middleware::from_fn;
 use axum::response::{IntoResponse, Response};
@@ -15,21 +16,25 @@ use axum::{Extension, Json, Router};
 use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
 use compact_jwt::{Jwk, Jws, JwsSigner};
 use kanidm_proto::constants::uri::V1_AUTH_VALID;
+use kanidm_proto::constants::KNEXTCURSOR;
 use kanidm_proto::internal::{
-    ApiToken, AppLink, CUIntentSend, CUIntentToken, CURequest, CUSessionToken, CUStatus,
-    CreateRequest, CredentialStatus, DeleteRequest, IdentifyUserRequest, IdentifyUserResponse,
-    ModifyRequest, RadiusAuthToken, SearchRequest, SearchResponse, UserAuthToken,
-    COOKIE_AUTH_SESSION_ID, COOKIE_BEARER_TOKEN,
+    ApiToken, AppLink, BatchCreateRequest, BatchCreateResponse, CUIntentSend, CUIntentToken,
+    CURequest, CUSessionToken, CUStatus, CreateRequest, CredentialStatus, DeleteRequest,
+    IdentifyUserRequest, IdentifyUserResponse, ModifyRequest, RadiusAuthToken, SearchRequest,
+    SearchResponse, SyntaxInfo, UserAuthToken, COOKIE_AUTH_SESSION_ID, COOKIE_BEARER_TOKEN,
 };
+use kanidm_proto::scim_v1::ScimApplicationPasswordInfo;
 use kanidm_proto::v1::{
-    AccountUnixExtend, ApiTokenGenerate, AuthIssueSession, AuthRequest, AuthResponse,
-    AuthState as ProtoAuthState, Entry as ProtoEntry, GroupUnixExtend, SingleStringRequest,
-    UatStatus, UnixGroupToken, UnixUserToken, WhoamiResponse,
+    AccountUnixExtend, ApiTokenGenerate, AuditQuery, AuditRecordPage, AuthIssueSession,
+    AuthRequest, AuthResponse, AuthState as ProtoAuthState, Entry as ProtoEntry,
+    GroupAddMembersExpiring, GroupListEntry, GroupUnixExtend, SingleStringRequest, UatStatus,
+    UnixGroupToken, UnixUserToken, WhoamiResponse,
 };
 use kanidmd_lib::idm::authentication::{AuthState, AuthStep, ReauthRequest};
 use kanidmd_lib::idm::event::AuthResult;
 use kanidmd_lib::prelude::*;
 use kanidmd_lib::value::PartialValue;
+use serde::Deserialize;
 use std::net::IpAddr;
 use uuid::Uuid;
 
@@ -111,9 +116,88 @@ pub async fn raw_delete(
         .map_err(WebError::from)
 }
 
+/// Query parameters limiting which attributes are returned by a search. `attrs` is a comma
+/// separated list of attribute names.
+#[derive(Deserialize)]
+pub struct AttrsQuery {
+    attrs: Option<String>,
+}
+
+impl AttrsQuery {
+    fn into_attrs(self) -> Option<Vec<String>> {
+        split_attrs_query(self.attrs)
+    }
+}
+
+fn split_attrs_query(attrs: Option<String>) -> Option<Vec<String>> {
+    attrs.map(|attrs| {
+        attrs
+            .split(',')
+            .map(str::trim)
+            .filter(|attr| !attr.is_empty())
+            .map(str::to_string)
+            .collect()
+    })
+}
+
+/// The number of entries in a page of a list when a cursor is given without a limit.
+const LIST_PAGE_DEFAULT_LIMIT: usize = 100;
+
+/// Query parameters of list endpoints. If `limit` or `cursor` are set the list is returned in
+/// pages, and the cursor of the next page is returned in the [`KNEXTCURSOR`] header.
+#[derive(Deserialize)]
+pub struct ListQuery {
+    attrs: Option<String>,
+    limit: Option<usize>,
+    cursor: Option<String>,
+}
+
+/// Common event handler to list the entries matching a filter, optionally in pages, and
+/// return the result as json proto entries.
+pub async fn json_rest_event_get_list(
+    state: ServerState,
+    filter: Filter<FilterInvalid>,
+    query: ListQuery,
+    kopid: KOpId,
+    client_auth_info: ClientAuthInfo,
+) -> Result<(HeaderMap, Json<Vec<ProtoEntry>>), WebError> {
+    let attrs = split_attrs_query(query.attrs);
+
+    if query.limit.is_none() && query.cursor.is_none() {
+        return json_rest_event_get(state, attrs, filter, kopid, client_auth_info)
+            .await
+            .map(|entries| (HeaderMap::new(), entries));
+    }
+
+    let limit = query.limit.unwrap_or(LIST_PAGE_DEFAULT_LIMIT);
+
+    let (entries, next) = state
+        .qe_r_ref
+        .handle_internalsearch_page(
+            client_auth_info,
+            filter,
+            attrs,
+            query.cursor,
+            limit,
+            kopid.eventid,
+        )
+        .await?;
+
+    let mut headers = HeaderMap::new();
+    if let Some(next) = next {
+        let value = HeaderValue::from_str(&next).map_err(|_| OperationError::InvalidState)?;
+        headers.insert(KNEXTCURSOR, value);
+    }
+
+    Ok((headers, Json::from(entries)))
+}
+
 #[utoipa::path(
     post,
     path = "/v1/raw/search",
+    params(
+        ("attrs" = Option<String>, Query, description="Comma separated list of attributes to return")
+    ),
     responses(
         (status = 200, body=SearchResponse, content_type=APPLICATION_JSON),
         ApiResponseWithout200,
@@ -128,11 +212,12 @@ pub async fn raw_search(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
     VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Query(query): Query<AttrsQuery>,
     Json(msg): Json<SearchRequest>,
 ) -> Result<Json<SearchResponse>, WebError> {
     state
         .qe_r_ref
-        .handle_search(client_auth_info, msg, kopid.eventid)
+        .handle_search(client_auth_info, msg, query.into_attrs(), kopid.eventid)
         .await
         .map(Json::from)
         .map_err(WebError::from)
@@ -188,6 +273,186 @@ pub async fn whoami_uat(
         .map_err(WebError::from)
 }
 
+#[utoipa::path(
+    get,
+    path = "/v1/self/_api_token",
+    responses(
+        (status = 200, description = "Ok", body=ApiToken, content_type=APPLICATION_JSON),
+        ApiResponseWithout200,
+    ),
+    security(("token_jwt" = [])),
+    tag = "self",
+    operation_id="whoami_api_token"
+)]
+/// Inspect the api token used to make this request, such as the operation scopes it is
+/// restricted to.
+pub async fn whoami_api_token(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+) -> Result<Json<ApiToken>, WebError> {
+    state
+        .qe_r_ref
+        .handle_whoami_api_token(&client_auth_info, kopid.eventid)
+        .await
+        .map(Json::from)
+        .map_err(WebError::from)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/self/_application_password",
+    responses(
+        (status = 200, description = "Ok", body=Vec<ScimApplicationPasswordInfo>, content_type=APPLICATION_JSON),
+        ApiResponseWithout200,
+    ),
+    security(("token_jwt" = [])),
+    tag = "self",
+    operation_id="self_application_password_list"
+)]
+/// List the application passwords held by the current account. The secrets are never
+/// returned.
+pub async fn self_application_password_list(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+) -> Result<Json<Vec<ScimApplicationPasswordInfo>>, WebError> {
+    state
+        .qe_r_ref
+        .handle_self_application_password_list(client_auth_info, kopid.eventid)
+        .await
+        .map(Json::from)
+        .map_err(WebError::from)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/self/_session",
+    responses(
+        (status = 200, description = "Ok", body=Vec<UatStatus>, content_type=APPLICATION_JSON),
+        ApiResponseWithout200,
+    ),
+    security(("token_jwt" = [])),
+    tag = "self",
+    operation_id = "self_session_list"
+)]
+/// List the sessions of the current account, including where they were started from.
+pub async fn self_session_list(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+) -> Result<Json<Vec<UatStatus>>, WebError> {
+    state
+        .qe_r_ref
+        .handle_self_user_auth_token_get(client_auth_info, kopid.eventid)
+        .await
+        .map(Json::from)
+        .map_err(WebError::from)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/self/_session/{session_id}",
+    responses(
+        DefaultApiResponse,
+    ),
+    security(("token_jwt" = [])),
+    tag = "self",
+    operation_id = "self_session_revoke"
+)]
+/// Revoke one of the sessions of the current account. OAuth2 sessions that were derived
+/// from it are revoked too.
+pub async fn self_session_revoke(
+    State(state): State<ServerState>,
+    Path(session_id): Path<Uuid>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+) -> Result<Json<()>, WebError> {
+    state
+        .qe_w_ref
+        .handle_self_user_auth_token_destroy(client_auth_info, session_id, kopid.eventid)
+        .await
+        .map(Json::from)
+        .map_err(WebError::from)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/self/_mail/_verify",
+    request_body=String,
+    responses(
+        DefaultApiResponse,
+    ),
+    security(("token_jwt" = [])),
+    tag = "self",
+    operation_id = "self_mail_verify_send"
+)]
+/// Send a verification message to one of the unverified mail addresses of the current account.
+pub async fn self_mail_verify_send(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Json(mail): Json<String>,
+) -> Result<Json<()>, WebError> {
+    state
+        .qe_w_ref
+        .handle_self_mail_verification_send(client_auth_info, mail, kopid.eventid)
+        .await
+        .map(Json::from)
+        .map_err(WebError::from)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/mail/_verify",
+    request_body=String,
+    responses(
+        DefaultApiResponse,
+    ),
+    tag = "self",
+    operation_id = "mail_verify_confirm"
+)]
+/// Confirm a mail address using the token that was sent to it.
+pub async fn mail_verify_confirm(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    Json(token): Json<String>,
+) -> Result<Json<()>, WebError> {
+    state
+        .qe_w_ref
+        .handle_mail_verification_confirm(token, kopid.eventid)
+        .await
+        .map(Json::from)
+        .map_err(WebError::from)
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/self/_attr/{attr}",
+    responses(
+        DefaultApiResponse,
+    ),
+    security(("token_jwt" = [])),
+    tag = "self",
+    operation_id = "self_put_attr",
+)]
+/// Replace an attribute on your own entry. The attribute must be allowed by the domain
+/// self write policy.
+pub async fn self_put_attr(
+    State(state): State<ServerState>,
+    Path(attr): Path<String>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Json(values): Json<Vec<String>>,
+) -> Result<Json<()>, WebError> {
+    state
+        .qe_w_ref
+        .handle_self_setattribute(client_auth_info, attr, values, kopid.eventid)
+        .await
+        .map_err(WebError::from)
+        .map(Json::from)
+}
+
 #[utoipa::path(
     get,
     path = "/v1/logout",
@@ -339,6 +604,38 @@ pub async fn json_rest_event_post(
         .map_err(WebError::from)
 }
 
+/// Query parameters of the person and service account create endpoints.
+#[derive(Deserialize)]
+pub struct CreateQuery {
+    /// The name of a creation template to apply to the new entry.
+    template: Option<String>,
+}
+
+/// As [`json_rest_event_post`], applying the creation template if one was requested.
+pub async fn json_rest_event_post_template(
+    state: ServerState,
+    classes: Vec<String>,
+    obj: ProtoEntry,
+    query: CreateQuery,
+    kopid: KOpId,
+    client_auth_info: ClientAuthInfo,
+) -> Result<Json<()>, WebError> {
+    let Some(template) = query.template else {
+        return json_rest_event_post(state, classes, obj, kopid, client_auth_info).await;
+    };
+
+    let mut obj = obj;
+    obj.attrs.insert(Attribute::Class.to_string(), classes);
+    let msg = CreateRequest { entries: vec![obj] };
+
+    state
+        .qe_w_ref
+        .handle_create_with_template(client_auth_info, msg, template, kopid.eventid)
+        .await
+        .map(Json::from)
+        .map_err(WebError::from)
+}
+
 pub async fn json_rest_event_post_id_attr(
     state: ServerState,
     id: String,
@@ -472,6 +769,31 @@ pub async fn schema_get(
     json_rest_event_get(state, None, filter, kopid, client_auth_info).await
 }
 
+#[utoipa::path(
+    get,
+    path = "/v1/schema/syntax",
+    responses(
+        (status=200, content_type=APPLICATION_JSON, body=Vec<SyntaxInfo>),
+        ApiResponseWithout200,
+    ),
+    security(("token_jwt" = [])),
+    tag = "schema",
+    operation_id = "schema_syntax_get",
+)]
+/// Describe every value syntax supported by the server.
+pub async fn schema_syntax_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+) -> Result<Json<Vec<SyntaxInfo>>, WebError> {
+    state
+        .qe_r_ref
+        .handle_schema_syntax_catalog(client_auth_info, kopid.eventid)
+        .await
+        .map(Json::from)
+        .map_err(WebError::from)
+}
+
 #[utoipa::path(
     get,
     path = "/v1/schema/attributetype",
@@ -581,8 +903,14 @@ pub async fn schema_classtype_get_id(
 #[utoipa::path(
     get,
     path = "/v1/person",
+    params(
+        ("attrs" = Option<String>, Query, description="Comma separated list of attributes to return"),
+        ("limit" = Option<usize>, Query, description="Return the list in pages of at most this many entries"),
+        ("cursor" = Option<String>, Query, description="The cursor of the page to return, from the X-KANIDM-NEXT-CURSOR header of the prior page")
+    ),
     responses(
-        (status=200, body=Vec<ProtoEntry>, content_type=APPLICATION_JSON),
+        (status=200, body=Vec<ProtoEntry>, content_type=APPLICATION_JSON,
+            headers(("X-KANIDM-NEXT-CURSOR" = String, description="The cursor of the next page, if more entries remain"))),
         ApiResponseWithout200,
     ),
     security(("token_jwt" = [])),
@@ -593,14 +921,18 @@ pub async fn person_get(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
     VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
-) -> Result<Json<Vec<ProtoEntry>>, WebError> {
+    Query(query): Query<ListQuery>,
+) -> Result<(HeaderMap, Json<Vec<ProtoEntry>>), WebError> {
     let filter = filter_all!(f_eq(Attribute::Class, EntryClass::Person.into()));
-    json_rest_event_get(state, None, filter, kopid, client_auth_info).await
+    json_rest_event_get_list(state, filter, query, kopid, client_auth_info).await
 }
 
 #[utoipa::path(
     post,
     path = "/v1/person",
+    params(
+        ("template" = Option<String>, Query, description="The name of a creation template to apply to the new person")
+    ),
     responses(
         DefaultApiResponse,
     ),
@@ -614,6 +946,7 @@ pub async fn person_post(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
     VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Query(query): Query<CreateQuery>,
     Json(obj): Json<ProtoEntry>,
 ) -> Result<Json<()>, WebError> {
     let classes: Vec<String> = vec![
@@ -621,7 +954,45 @@ pub async fn person_post(
         EntryClass::Account.into(),
         EntryClass::Object.into(),
     ];
-    json_rest_event_post(state, classes, obj, kopid, client_auth_info).await
+    json_rest_event_post_template(state, classes, obj, query, kopid, client_auth_info).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/person/_batch",
+    responses(
+        (status=200, body=BatchCreateResponse, content_type=APPLICATION_JSON),
+        ApiResponseWithout200,
+    ),
+    request_body=BatchCreateRequest,
+    security(("token_jwt" = [])),
+    tag = "person",
+    operation_id = "person_batch_post",
+)]
+/// Create a batch of persons in a single transaction, reporting the outcome of each. Each
+/// entry expects the same fields as a single person create.
+pub async fn person_batch_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Json(mut msg): Json<BatchCreateRequest>,
+) -> Result<Json<BatchCreateResponse>, WebError> {
+    let classes: Vec<String> = vec![
+        EntryClass::Person.into(),
+        EntryClass::Account.into(),
+        EntryClass::Object.into(),
+    ];
+    for obj in msg.entries.iter_mut() {
+        obj.attrs
+            .insert(Attribute::Class.to_string(), classes.clone());
+    }
+
+    state
+        .qe_w_ref
+        .handle_create_batch(client_auth_info, msg, kopid.eventid)
+        .await
+        .map(Json::from)
+        .map_err(WebError::from)
 }
 
 #[utoipa::path(
@@ -651,6 +1022,9 @@ pub async fn person_search_id(
 #[utoipa::path(
     get,
     path = "/v1/person/{id}",
+    params(
+        ("attrs" = Option<String>, Query, description="Comma separated list of attributes to return")
+    ),
     responses(
         (status=200, body=Option<ProtoEntry>, content_type=APPLICATION_JSON),
         ApiResponseWithout200,
@@ -664,9 +1038,18 @@ pub async fn person_id_get(
     Path(id): Path<String>,
     Extension(kopid): Extension<KOpId>,
     VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Query(query): Query<AttrsQuery>,
 ) -> Result<Json<Option<ProtoEntry>>, WebError> {
     let filter = filter_all!(f_eq(Attribute::Class, EntryClass::Person.into()));
-    json_rest_event_get_id(state, id, filter, None, kopid, client_auth_info).await
+    json_rest_event_get_id(
+        state,
+        id,
+        filter,
+        query.into_attrs(),
+        kopid,
+        client_auth_info,
+    )
+    .await
 }
 
 #[utoipa::path(
@@ -747,8 +1130,14 @@ pub async fn person_post_id_certificate(
 #[utoipa::path(
     get,
     path = "/v1/service_account",
+    params(
+        ("attrs" = Option<String>, Query, description="Comma separated list of attributes to return"),
+        ("limit" = Option<usize>, Query, description="Return the list in pages of at most this many entries"),
+        ("cursor" = Option<String>, Query, description="The cursor of the page to return, from the X-KANIDM-NEXT-CURSOR header of the prior page")
+    ),
     responses(
-        (status=200, body=Vec<ProtoEntry>, content_type=APPLICATION_JSON),
+        (status=200, body=Vec<ProtoEntry>, content_type=APPLICATION_JSON,
+            headers(("X-KANIDM-NEXT-CURSOR" = String, description="The cursor of the next page, if more entries remain"))),
         ApiResponseWithout200,
     ),
     security(("token_jwt" = [])),
@@ -759,14 +1148,18 @@ pub async fn service_account_get(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
     VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
-) -> Result<Json<Vec<ProtoEntry>>, WebError> {
+    Query(query): Query<ListQuery>,
+) -> Result<(HeaderMap, Json<Vec<ProtoEntry>>), WebError> {
     let filter = filter_all!(f_eq(Attribute::Class, EntryClass::ServiceAccount.into()));
-    json_rest_event_get(state, None, filter, kopid, client_auth_info).await
+    json_rest_event_get_list(state, filter, query, kopid, client_auth_info).await
 }
 
 #[utoipa::path(
     post,
     path = "/v1/service_account",
+    params(
+        ("template" = Option<String>, Query, description="The name of a creation template to apply to the new service account")
+    ),
     request_body=ProtoEntry,
     responses(
         DefaultApiResponse,
@@ -779,6 +1172,7 @@ pub async fn service_account_post(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
     VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Query(query): Query<CreateQuery>,
     Json(obj): Json<ProtoEntry>,
 ) -> Result<Json<()>, WebError> {
     let classes: Vec<String> = vec![
@@ -786,7 +1180,7 @@ pub async fn service_account_post(
         EntryClass::Account.into(),
         EntryClass::Object.into(),
     ];
-    json_rest_event_post(state, classes, obj, kopid, client_auth_info).await
+    json_rest_event_post_template(state, classes, obj, query, kopid, client_auth_info).await
 }
 
 #[utoipa::path(
@@ -812,7 +1206,7 @@ pub async fn service_account_id_patch(
     let filter = Filter::join_parts_and(filter, filter_all!(f_id(id.as_str())));
     state
         .qe_w_ref
-        .handle_internalpatch(client_auth_info, filter, obj, kopid.eventid)
+        .handle_internalpatch(client_auth_info, filter, obj, None, kopid.eventid)
         .await
         .map(Json::from)
         .map_err(WebError::from)
@@ -963,6 +1357,7 @@ pub async fn service_account_api_token_post(
             obj.label,
             obj.expiry,
             obj.read_write,
+            obj.operation_scopes,
             obj.compact,
             kopid.eventid,
         )
@@ -1167,6 +1562,9 @@ pub async fn service_account_id_put_attr(
 #[utoipa::path(
     patch,
     path = "/v1/person/{id}",
+    params(
+        ("If-Match" = Option<String>, Header, description="The last_modified_cid of the entry when it was read. If the entry has since changed, the patch is rejected with 412 and the current version in the ETag header.")
+    ),
     responses(
         DefaultApiResponse,
     ),
@@ -1180,16 +1578,27 @@ pub async fn person_id_patch(
     Extension(kopid): Extension<KOpId>,
     VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
     Path(id): Path<String>,
+    headers: HeaderMap,
     Json(obj): Json<ProtoEntry>,
 ) -> Result<Json<()>, WebError> {
+    let expected_version = headers
+        .get(IF_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
     // Update a value / attrs
     let filter = filter_all!(f_eq(Attribute::Class, EntryClass::Account.into()));
     let filter = Filter::join_parts_and(filter, filter_all!(f_id(id.as_str())));
     state
         .qe_w_ref
-        .handle_internalpatch(client_auth_info, filter, obj, kopid.eventid)
-        .await
-        .map(Json::from)
+        .handle_internalpatch(
+            client_auth_info,
+            filter,
+            obj,
+            expected_version,
+            kopid.eventid,
+        )
+        .await
+        .map(Json::from)
         .map_err(WebError::from)
 }
 
@@ -2200,11 +2609,19 @@ pub async fn person_identify_user_post(
         .map_err(WebError::from)
 }
 
+#[derive(Deserialize)]
+pub struct GroupListQuery {
+    include: Option<String>,
+}
+
 #[utoipa::path(
     get,
     path = "/v1/group",
+    params(
+        ("include" = Option<String>, Query, description="Set to member_counts to return the number of members of each group in place of the member lists")
+    ),
     responses(
-        (status=200,body=Vec<ProtoEntry>, content_type=APPLICATION_JSON),
+        (status=200,body=Vec<GroupListEntry>, content_type=APPLICATION_JSON),
         ApiResponseWithout200,
     ),
     security(("token_jwt" = [])),
@@ -2216,9 +2633,21 @@ pub async fn group_get(
     State(state): State<ServerState>,
     Extension(kopid): Extension<KOpId>,
     VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
-) -> Result<Json<Vec<ProtoEntry>>, WebError> {
+    Query(query): Query<GroupListQuery>,
+) -> Result<Json<Vec<GroupListEntry>>, WebError> {
     let filter = filter_all!(f_eq(Attribute::Class, EntryClass::Group.into()));
-    json_rest_event_get(state, None, filter, kopid, client_auth_info).await
+    let member_counts = query
+        .include
+        .as_deref()
+        .map(|include| include.split(',').any(|item| item == "member_counts"))
+        .unwrap_or_default();
+
+    state
+        .qe_r_ref
+        .handle_group_list(client_auth_info, filter, member_counts, kopid.eventid)
+        .await
+        .map(Json::from)
+        .map_err(WebError::from)
 }
 
 #[utoipa::path(
@@ -2268,6 +2697,9 @@ pub async fn group_post(
 #[utoipa::path(
     get,
     path = "/v1/group/{id}",
+    params(
+        ("attrs" = Option<String>, Query, description="Comma separated list of attributes to return")
+    ),
     responses(
         (status=200, body=Option<ProtoEntry>, content_type=APPLICATION_JSON),
         ApiResponseWithout200,
@@ -2281,9 +2713,18 @@ pub async fn group_id_get(
     Extension(kopid): Extension<KOpId>,
     VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
     Path(id): Path<String>,
+    Query(query): Query<AttrsQuery>,
 ) -> Result<Json<Option<ProtoEntry>>, WebError> {
     let filter = filter_all!(f_eq(Attribute::Class, EntryClass::Group.into()));
-    json_rest_event_get_id(state, id, filter, None, kopid, client_auth_info).await
+    json_rest_event_get_id(
+        state,
+        id,
+        filter,
+        query.into_attrs(),
+        kopid,
+        client_auth_info,
+    )
+    .await
 }
 
 #[utoipa::path(
@@ -2309,7 +2750,7 @@ pub async fn group_id_patch(
     let filter = Filter::join_parts_and(filter, filter_all!(f_id(id.as_str())));
     state
         .qe_w_ref
-        .handle_internalpatch(client_auth_info, filter, obj, kopid.eventid)
+        .handle_internalpatch(client_auth_info, filter, obj, None, kopid.eventid)
         .await
         .map(Json::from)
         .map_err(WebError::from)
@@ -2423,6 +2864,33 @@ pub async fn group_id_attr_put(
     json_rest_event_put_attr(state, id, attr, filter, values, kopid, client_auth_info).await
 }
 
+#[utoipa::path(
+    post,
+    path = "/v1/group/{id}/_member/_expiring",
+    request_body = GroupAddMembersExpiring,
+    responses(
+        DefaultApiResponse,
+    ),
+    security(("token_jwt" = [])),
+    tag = "group/attr",
+    operation_id = "group_id_member_expiring_post",
+)]
+/// Add members to a group that are removed from the group once they expire.
+pub async fn group_id_member_expiring_post(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Json(obj): Json<GroupAddMembersExpiring>,
+) -> Result<Json<()>, WebError> {
+    state
+        .qe_w_ref
+        .handle_idmgroupaddmembersexpiring(client_auth_info, id, obj, kopid.eventid)
+        .await
+        .map(Json::from)
+        .map_err(WebError::from)
+}
+
 #[utoipa::path(
     post,
     path = "/v1/group/{id}/_unix",
@@ -2586,6 +3054,322 @@ pub async fn domain_attr_delete(
     .await
 }
 
+// == audit ==
+
+#[utoipa::path(
+    post,
+    path = "/v1/audit/_search",
+    responses(
+        (status=200, content_type=APPLICATION_JSON, body=AuditRecordPage),
+        ApiResponseWithout200,
+    ),
+    request_body=AuditQuery,
+    security(("token_jwt" = [])),
+    tag = "audit",
+    operation_id = "audit_search_post",
+)]
+/// Query the records of changes made to the database. Requires membership of
+/// `idm_audit_readers`.
+pub async fn audit_search_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Json(query): Json<AuditQuery>,
+) -> Result<Json<AuditRecordPage>, WebError> {
+    state
+        .qe_r_ref
+        .handle_audit_search(client_auth_info, query, kopid.eventid)
+        .await
+        .map(Json::from)
+        .map_err(WebError::from)
+}
+
+// == automount ==
+
+#[utoipa::path(
+    get,
+    path = "/v1/automount",
+    responses(
+        (status=200,body=Vec<ProtoEntry>, content_type=APPLICATION_JSON),
+        ApiResponseWithout200,
+    ),
+    security(("token_jwt" = [])),
+    tag = "automount",
+    operation_id = "automount_get",
+)]
+/// Returns all automount maps visible to the user
+pub async fn automount_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+) -> Result<Json<Vec<ProtoEntry>>, WebError> {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::AutomountMap.into()));
+    json_rest_event_get(state, None, filter, kopid, client_auth_info).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/automount",
+    responses(
+        DefaultApiResponse,
+    ),
+    request_body=ProtoEntry,
+    security(("token_jwt" = [])),
+    tag = "automount",
+    operation_id = "automount_post",
+)]
+/// Expects the following fields in the attrs field of the req: [name]
+pub async fn automount_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Json(obj): Json<ProtoEntry>,
+) -> Result<Json<()>, WebError> {
+    let classes: Vec<String> = vec![EntryClass::AutomountMap.into(), EntryClass::Object.into()];
+    json_rest_event_post(state, classes, obj, kopid, client_auth_info).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/automount/{id}",
+    responses(
+        (status=200, body=Option<ProtoEntry>, content_type=APPLICATION_JSON),
+        ApiResponseWithout200,
+    ),
+    security(("token_jwt" = [])),
+    tag = "automount",
+    operation_id = "automount_id_get",
+)]
+pub async fn automount_id_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Path(id): Path<String>,
+) -> Result<Json<Option<ProtoEntry>>, WebError> {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::AutomountMap.into()));
+    json_rest_event_get_id(state, id, filter, None, kopid, client_auth_info).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/automount/{id}",
+    responses(
+        DefaultApiResponse,
+    ),
+    security(("token_jwt" = [])),
+    tag = "automount",
+    operation_id = "automount_id_delete",
+)]
+/// Deletes the map. The keys of the map are deleted with it.
+pub async fn automount_id_delete(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Path(id): Path<String>,
+) -> Result<Json<()>, WebError> {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::AutomountMap.into()));
+    json_rest_event_delete_id(state, id, filter, kopid, client_auth_info).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/automount/{id}/_key",
+    responses(
+        (status=200, body=Vec<ProtoEntry>, content_type=APPLICATION_JSON),
+        ApiResponseWithout200,
+    ),
+    security(("token_jwt" = [])),
+    tag = "automount",
+    operation_id = "automount_id_key_get",
+)]
+pub async fn automount_id_key_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<ProtoEntry>>, WebError> {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::Automount.into()));
+    json_rest_event_get_refers_id(state, id, filter, None, kopid, client_auth_info).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/automount/{id}/_key",
+    responses(
+        DefaultApiResponse,
+    ),
+    request_body=ProtoEntry,
+    security(("token_jwt" = [])),
+    tag = "automount",
+    operation_id = "automount_id_key_post",
+)]
+/// Expects the following fields in the attrs field of the req: [automountkey, automountinformation]
+///
+/// The map's id will be added implicitly as a reference.
+pub async fn automount_id_key_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Path(id): Path<String>,
+    Json(mut obj): Json<ProtoEntry>,
+) -> Result<Json<()>, WebError> {
+    let classes: Vec<String> = vec![EntryClass::Automount.into(), EntryClass::Object.into()];
+    obj.attrs.insert(Attribute::Refers.to_string(), vec![id]);
+
+    json_rest_event_post(state, classes, obj, kopid, client_auth_info).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/automount/_key/{id}",
+    responses(
+        DefaultApiResponse,
+    ),
+    security(("token_jwt" = [])),
+    tag = "automount",
+    operation_id = "automount_key_id_delete",
+)]
+/// Deletes a single automount key by its uuid.
+pub async fn automount_key_id_delete(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Path(id): Path<String>,
+) -> Result<Json<()>, WebError> {
+    let filter = filter_all!(f_eq(Attribute::Class, EntryClass::Automount.into()));
+    json_rest_event_delete_id(state, id, filter, kopid, client_auth_info).await
+}
+
+// == webhook ==
+
+#[utoipa::path(
+    get,
+    path = "/v1/webhook",
+    responses(
+        (status=200,body=Vec<ProtoEntry>, content_type=APPLICATION_JSON),
+        ApiResponseWithout200,
+    ),
+    security(("token_jwt" = [])),
+    tag = "webhook",
+    operation_id = "webhook_get",
+)]
+/// Returns all webhook subscriptions visible to the user
+pub async fn webhook_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+) -> Result<Json<Vec<ProtoEntry>>, WebError> {
+    let filter = filter_all!(f_eq(
+        Attribute::Class,
+        EntryClass::WebhookSubscription.into()
+    ));
+    json_rest_event_get(state, None, filter, kopid, client_auth_info).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/webhook",
+    responses(
+        DefaultApiResponse,
+    ),
+    request_body=ProtoEntry,
+    security(("token_jwt" = [])),
+    tag = "webhook",
+    operation_id = "webhook_post",
+)]
+/// Expects the following fields in the attrs field of the req: [name, webhook_url, webhook_event].
+/// The secret of the subscription is generated by the server.
+pub async fn webhook_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Json(obj): Json<ProtoEntry>,
+) -> Result<Json<()>, WebError> {
+    let classes: Vec<String> = vec![
+        EntryClass::WebhookSubscription.into(),
+        EntryClass::Object.into(),
+    ];
+    json_rest_event_post(state, classes, obj, kopid, client_auth_info).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/webhook/{id}",
+    responses(
+        (status=200, body=Option<ProtoEntry>, content_type=APPLICATION_JSON),
+        ApiResponseWithout200,
+    ),
+    security(("token_jwt" = [])),
+    tag = "webhook",
+    operation_id = "webhook_id_get",
+)]
+pub async fn webhook_id_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Path(id): Path<String>,
+) -> Result<Json<Option<ProtoEntry>>, WebError> {
+    let filter = filter_all!(f_eq(
+        Attribute::Class,
+        EntryClass::WebhookSubscription.into()
+    ));
+    json_rest_event_get_id(state, id, filter, None, kopid, client_auth_info).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/webhook/{id}",
+    responses(
+        DefaultApiResponse,
+    ),
+    security(("token_jwt" = [])),
+    tag = "webhook",
+    operation_id = "webhook_id_delete",
+)]
+pub async fn webhook_id_delete(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Path(id): Path<String>,
+) -> Result<Json<()>, WebError> {
+    let filter = filter_all!(f_eq(
+        Attribute::Class,
+        EntryClass::WebhookSubscription.into()
+    ));
+    json_rest_event_delete_id(state, id, filter, kopid, client_auth_info).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/webhook/{id}/_secret",
+    responses(
+        (status=200, body=Option<String>, content_type=APPLICATION_JSON),
+        ApiResponseWithout200,
+    ),
+    security(("token_jwt" = [])),
+    tag = "webhook",
+    operation_id = "webhook_id_get_secret",
+)]
+/// Get the secret that deliveries to the webhook are signed with.
+pub async fn webhook_id_get_secret(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Path(id): Path<String>,
+) -> Result<Json<Option<String>>, WebError> {
+    let filter = filter_all!(f_eq(
+        Attribute::Class,
+        EntryClass::WebhookSubscription.into()
+    ));
+    let filter = Filter::join_parts_and(filter, filter_all!(f_id(id.as_str())));
+    state
+        .qe_r_ref
+        .handle_webhook_secret_read(client_auth_info, filter, kopid.eventid)
+        .await
+        .map(Json::from)
+        .map_err(WebError::from)
+}
+
 #[utoipa::path(
     get,
     path = "/v1/system",
@@ -3180,11 +3964,17 @@ pub(crate) fn route_setup(state: ServerState) -> Router<ServerState> {
             "/v1/oauth2/{rs_name}/_claimmap/{claim_name}",
             post(super::v1_oauth2::oauth2_id_claimmap_join_post),
         )
+        .route(
+            "/v1/oauth2/{rs_name}/_claimmap_template/{claim_name}",
+            post(super::v1_oauth2::oauth2_id_claimmap_template_post)
+                .delete(super::v1_oauth2::oauth2_id_claimmap_template_delete),
+        )
         .route("/v1/raw/create", post(raw_create))
         .route("/v1/raw/modify", post(raw_modify))
         .route("/v1/raw/delete", post(raw_delete))
         .route("/v1/raw/search", post(raw_search))
         .route("/v1/schema", get(schema_get))
+        .route("/v1/schema/syntax", get(schema_syntax_get))
         .route(
             "/v1/schema/attributetype",
             get(schema_attributetype_get), // post(|| async { "TODO" })
@@ -3205,7 +3995,19 @@ pub(crate) fn route_setup(state: ServerState) -> Router<ServerState> {
         )
         .route("/v1/self", get(whoami))
         .route("/v1/self/_uat", get(whoami_uat))
-        // .route("/v1/self/_attr/{attr}", get(|| async { "TODO" }))
+        .route("/v1/self/_api_token", get(whoami_api_token))
+        .route(
+            "/v1/self/_application_password",
+            get(self_application_password_list),
+        )
+        .route("/v1/self/_attr/{attr}", put(self_put_attr))
+        .route("/v1/self/_mail/_verify", post(self_mail_verify_send))
+        .route("/v1/mail/_verify", post(mail_verify_confirm))
+        .route("/v1/self/_session", get(self_session_list))
+        .route(
+            "/v1/self/_session/{session_id}",
+            delete(self_session_revoke),
+        )
         // .route("/v1/self/_credential", get(|| async { "TODO" }))
         // .route("/v1/self/_credential/{cid}/_lock", get(|| async { "TODO" }))
         // .route(
@@ -3224,6 +4026,7 @@ pub(crate) fn route_setup(state: ServerState) -> Router<ServerState> {
         .route("/v1/self/_applinks", get(applinks_get))
         // Person routes
         .route("/v1/person", get(person_get).post(person_post))
+        .route("/v1/person/_batch", post(person_batch_post))
         .route("/v1/person/_search/{id}", get(person_search_id))
         .route(
             "/v1/person/{id}",
@@ -3398,6 +4201,10 @@ pub(crate) fn route_setup(state: ServerState) -> Router<ServerState> {
         )
         .route("/v1/group/{id}/_unix/_token", get(group_id_unix_token_get))
         .route("/v1/group/{id}/_unix", post(group_id_unix_post))
+        .route(
+            "/v1/group/{id}/_member/_expiring",
+            post(group_id_member_expiring_post),
+        )
         .route("/v1/group", get(group_get).post(group_post))
         .route("/v1/group/_search/{id}", get(group_search_id))
         .route(
@@ -3413,6 +4220,23 @@ pub(crate) fn route_setup(state: ServerState) -> Router<ServerState> {
                 .put(group_id_attr_put)
                 .post(group_id_attr_post),
         )
+        .route("/v1/automount", get(automount_get).post(automount_post))
+        .route(
+            "/v1/automount/{id}",
+            get(automount_id_get).delete(automount_id_delete),
+        )
+        .route(
+            "/v1/automount/{id}/_key",
+            get(automount_id_key_get).post(automount_id_key_post),
+        )
+        .route("/v1/automount/_key/{id}", delete(automount_key_id_delete))
+        .route("/v1/webhook", get(webhook_get).post(webhook_post))
+        .route(
+            "/v1/webhook/{id}",
+            get(webhook_id_get).delete(webhook_id_delete),
+        )
+        .route("/v1/webhook/{id}/_secret", get(webhook_id_get_secret))
+        .route("/v1/audit/_search", post(audit_search_post))
         .with_state(state.clone())
         .route("/v1/system", get(system_get))
         .route(
@@ -172,7 +172,7 @@ pub(crate) async fn oauth2_id_patch(
 
     state
         .qe_w_ref
-        .handle_internalpatch(client_auth_info, filter, obj, kopid.eventid)
+        .handle_internalpatch(client_auth_info, filter, obj, None, kopid.eventid)
         .await
         .map(Json::from)
         .map_err(WebError::from)
@@ -353,6 +353,73 @@ pub(crate) async fn oauth2_id_claimmap_join_post(
         .map_err(WebError::from)
 }
 
+#[utoipa::path(
+    post,
+    path = "/v1/oauth2/{rs_name}/_claimmap_template/{claim_name}",
+    request_body=String,
+    responses(
+        DefaultApiResponse,
+    ),
+    security(("token_jwt" = [])),
+    tag = "oauth2",
+    operation_id = "oauth2_id_claimmap_template_post"
+)]
+/// Set the value template applied to a claim map for a given OAuth2 Resource Server.
+/// The template may reference `{group}` and `{value}`.
+pub(crate) async fn oauth2_id_claimmap_template_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Path((rs_name, claim_name)): Path<(String, String)>,
+    Json(template): Json<String>,
+) -> Result<Json<()>, WebError> {
+    let filter = oauth2_id(&rs_name);
+    state
+        .qe_w_ref
+        .handle_oauth2_claimmap_template_update(
+            client_auth_info,
+            claim_name,
+            Some(template),
+            filter,
+            kopid.eventid,
+        )
+        .await
+        .map(Json::from)
+        .map_err(WebError::from)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/oauth2/{rs_name}/_claimmap_template/{claim_name}",
+    responses(
+        DefaultApiResponse,
+    ),
+    security(("token_jwt" = [])),
+    tag = "oauth2",
+    operation_id = "oauth2_id_claimmap_template_delete"
+)]
+/// Remove the value template from a claim map for a given OAuth2 Resource Server
+pub(crate) async fn oauth2_id_claimmap_template_delete(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Path((rs_name, claim_name)): Path<(String, String)>,
+) -> Result<Json<()>, WebError> {
+    let filter = oauth2_id(&rs_name);
+    state
+        .qe_w_ref
+        .handle_oauth2_claimmap_template_update(
+            client_auth_info,
+            claim_name,
+            None,
+            filter,
+            kopid.eventid,
+        )
+        .await
+        .map(Json::from)
+        .map_err(WebError::from)
+}
+
 #[utoipa::path(
     delete,
     path = "/v1/oauth2/{rs_name}/_claimmap/{claim_name}/{group}",
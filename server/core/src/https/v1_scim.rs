@@ -1,5 +1,5 @@
 use super::apidocs::response_schema::{ApiResponseWithout200, DefaultApiResponse};
-use super::errors::WebError;
+use super::errors::{operation_error_status_code, WebError};
 use super::middleware::KOpId;
 use super::v1::{
     json_rest_event_get, json_rest_event_get_id, json_rest_event_get_id_attr, json_rest_event_post,
@@ -8,6 +8,7 @@ use super::v1::{
 use super::ServerState;
 use crate::https::extractors::VerifiedClientInformation;
 use axum::extract::{rejection::JsonRejection, DefaultBodyLimit, Path, Query, State};
+use axum::http::StatusCode;
 use axum::response::{Html, IntoResponse, Response};
 use axum::routing::{delete, get, post};
 use axum::{Extension, Json, Router};
@@ -15,11 +16,13 @@ use kanidm_proto::scim_v1::ScimEntry;
 use kanidm_proto::scim_v1::{
     client::{ScimEntryPostGeneric, ScimEntryPutGeneric},
     server::{ScimEntryKanidm, ScimListResponse},
-    ScimApplicationPassword, ScimApplicationPasswordCreate, ScimEntryGetQuery, ScimSyncRequest,
-    ScimSyncState,
+    ScimApplicationPassword, ScimApplicationPasswordCreate, ScimBulkMethod,
+    ScimBulkOperationResponse, ScimBulkRequest, ScimBulkResponse, ScimEntryGetQuery,
+    ScimSyncApplyResponse, ScimSyncRequest, ScimSyncState, SCIM_SCHEMA_BULK_RESPONSE,
 };
 use kanidm_proto::v1::Entry as ProtoEntry;
 use kanidmd_lib::prelude::*;
+use kanidmd_lib::server::scim::ScimBulkItemResult;
 
 const DEFAULT_SCIM_SYNC_BYTES: usize = 1024 * 1024 * 32;
 
@@ -110,7 +113,7 @@ pub async fn sync_account_id_patch(
 
     state
         .qe_w_ref
-        .handle_internalpatch(client_auth_info, filter, obj, kopid.eventid)
+        .handle_internalpatch(client_auth_info, filter, obj, None, kopid.eventid)
         .await
         .map(Json::from)
         .map_err(WebError::from)
@@ -267,7 +270,8 @@ async fn scim_sink_get() -> Html<&'static str> {
     path = "/scim/v1/Sync",
     request_body = ScimSyncRequest,
     responses(
-        DefaultApiResponse,
+        (status = 200, content_type=APPLICATION_JSON, body=ScimSyncApplyResponse),
+        ApiResponseWithout200,
     ),
     security(("token_jwt" = [])),
     tag = "scim",
@@ -861,8 +865,78 @@ async fn scim_person_id_message_send_test_get(
         .map_err(WebError::from)
 }
 
+#[utoipa::path(
+    post,
+    path = "/scim/v1/Bulk",
+    request_body = ScimBulkRequest,
+    responses(
+        (status = 200, content_type=APPLICATION_JSON, body=ScimBulkResponse),
+        ApiResponseWithout200,
+    ),
+    security(("token_jwt" = [])),
+    tag = "scim",
+    operation_id = "scim_bulk_post"
+)]
+/// Apply a set of create, put and delete operations in a single transaction. Each operation
+/// reports its own status, and operations that succeed are kept even if others fail.
+async fn scim_bulk_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    Json(request): Json<ScimBulkRequest>,
+) -> Result<Json<ScimBulkResponse>, WebError> {
+    let results = state
+        .qe_w_ref
+        .handle_scim_bulk(
+            client_auth_info,
+            kopid.eventid,
+            request,
+            state.scim_bulk_max_operations,
+        )
+        .await?;
+
+    let operations = results
+        .into_iter()
+        .map(
+            |ScimBulkItemResult {
+                 method,
+                 bulk_id,
+                 outcome,
+             }| {
+                let (status, response) = match outcome {
+                    Ok(entry) => {
+                        let status = match method {
+                            ScimBulkMethod::Post => StatusCode::CREATED,
+                            ScimBulkMethod::Put => StatusCode::OK,
+                            ScimBulkMethod::Delete => StatusCode::NO_CONTENT,
+                        };
+                        let response = entry.and_then(|entry| serde_json::to_value(entry).ok());
+                        (status, response)
+                    }
+                    Err(err) => (
+                        operation_error_status_code(&err),
+                        serde_json::to_value(err).ok(),
+                    ),
+                };
+
+                ScimBulkOperationResponse {
+                    method,
+                    bulk_id,
+                    status: status.as_u16(),
+                    response,
+                }
+            },
+        )
+        .collect();
+
+    Ok(Json(ScimBulkResponse {
+        schemas: vec![SCIM_SCHEMA_BULK_RESPONSE.to_string()],
+        operations,
+    }))
+}
+
 #[instrument(level = "debug", skip_all, name = "https_v1_scim_route_setup")]
-pub fn route_setup() -> Router<ServerState> {
+pub fn route_setup(state: ServerState) -> Router<ServerState> {
     Router::new()
         .route(
             "/v1/sync_account",
@@ -1018,6 +1092,12 @@ pub fn route_setup() -> Router<ServerState> {
             "/scim/v1/Message/{id}/_sent",
             post(scim_message_id_sent_post),
         )
+        //  Bulk     /Bulk            POST (Section 3.7)     Bulk updates to one
+        //                                                   or more resources.
+        .route(
+            "/scim/v1/Bulk",
+            post(scim_bulk_post).layer(DefaultBodyLimit::max(state.scim_bulk_max_payload)),
+        )
         // Synchronisation routes.
         .route(
             "/scim/v1/Sync",
@@ -284,6 +284,7 @@ pub(crate) async fn add_member(
     let get_group_members_query = ScimEntryGetQuery {
         attributes: Some(vec![Attribute::Member]),
         ext_access_check: false,
+        ext_attr_meta: false,
         sort_by: None,
         sort_order: None,
         start_index: None,
@@ -293,6 +294,7 @@ pub(crate) async fn add_member(
     let get_member_query = ScimEntryGetQuery {
         attributes: Some(vec![Attribute::Spn]),
         ext_access_check: false,
+        ext_attr_meta: false,
         sort_by: None,
         sort_order: None,
         start_index: None,
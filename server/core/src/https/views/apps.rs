@@ -2,12 +2,12 @@ use askama::Template;
 use askama_web::WebTemplate;
 use axum::{
     extract::State,
-    response::{IntoResponse, Response},
+    response::{IntoResponse, Redirect, Response},
     Extension,
 };
 use axum_htmx::HxPushUrl;
 
-use kanidm_proto::internal::{AppLink, UserAuthToken};
+use kanidm_proto::internal::{AppLink, UatPurpose, UserAuthToken};
 
 use super::constants::Urls;
 use super::navbar::NavbarCtx;
@@ -38,14 +38,20 @@ pub(crate) async fn view_apps_get(
     // Because this is the route where the login page can land, we need to actually alter
     // our response as a result. If the user comes here directly we need to render the full
     // page, otherwise we need to render the partial.
+    let uat: &UserAuthToken = client_auth_info
+        .pre_validated_uat()
+        .map_err(|op_err| HtmxError::new(&kopid, op_err, domain_info.clone()))?;
+
+    // A session with an expired credential can only be used to replace it.
+    if matches!(uat.purpose, UatPurpose::CredentialUpdate) {
+        return Ok(Redirect::to(Urls::UpdateCredentials.as_ref()).into_response());
+    }
+
     let app_links = state
         .qe_r_ref
         .handle_list_applinks(client_auth_info.clone(), kopid.eventid)
         .await
         .map_err(|old| HtmxError::new(&kopid, old, domain_info.clone()))?;
-    let uat: &UserAuthToken = client_auth_info
-        .pre_validated_uat()
-        .map_err(|op_err| HtmxError::new(&kopid, op_err, domain_info.clone()))?;
 
     let apps_partial = AppsPartialView { apps: app_links };
 
@@ -31,6 +31,7 @@ use kanidmd_lib::idm::authentication::{
 use kanidmd_lib::idm::event::AuthResult;
 use kanidmd_lib::prelude::OperationError;
 use kanidmd_lib::prelude::*;
+use kanidmd_lib::value::Acr;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::str::FromStr;
@@ -371,7 +372,14 @@ pub async fn view_oauth2_reauth_get(
     kopid: KOpId,
     jar: CookieJar,
     display_ctx: LoginDisplayCtx,
+    acr: Option<Acr>,
 ) -> Response {
+    // If the client requires a stronger class of credential than this session was
+    // established with, the user must step up rather than re-verify.
+    let reauth_req = acr
+        .map(ReauthRequest::StepUp)
+        .unwrap_or(ReauthRequest::VerifyCredentials);
+
     view_reauth_get(
         state,
         client_auth_info,
@@ -379,7 +387,7 @@ pub async fn view_oauth2_reauth_get(
         jar,
         Urls::Oauth2Resume.as_ref(),
         display_ctx,
-        ReauthRequest::VerifyCredentials,
+        reauth_req,
     )
     .await
 }
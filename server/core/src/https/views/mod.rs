@@ -112,6 +112,10 @@ pub fn view_router(state: ServerState) -> Router<ServerState> {
             "/revoke",
             get(reset::view_revoke_get).post(reset::view_revoke_post),
         )
+        .route(
+            "/mail/verify",
+            get(reset::view_mail_verify_get).post(reset::view_mail_verify_post),
+        )
         .route("/update_credentials", get(reset::view_self_reset_get))
         .route("/profile", get(profile::view_profile_get))
         .route("/profile/diff", get(profile::view_profile_get))
@@ -7,12 +7,13 @@ use crate::https::views::{
 use crate::https::{
     extractors::{DomainInfo, DomainInfoRead, VerifiedClientInformation},
     middleware::KOpId,
+    oauth2::authorisation_request_from_query,
     ServerState,
 };
 use askama::Template;
 use askama_web::WebTemplate;
 use axum::{
-    extract::{Query, State},
+    extract::{RawQuery, State},
     http::header::ACCESS_CONTROL_ALLOW_ORIGIN,
     response::{IntoResponse, Redirect, Response},
     Extension, Form,
@@ -22,14 +23,16 @@ use axum_htmx::HX_REDIRECT;
 use kanidm_proto::internal::UserAuthToken;
 use kanidm_proto::internal::COOKIE_OAUTH2_REQ;
 use kanidmd_lib::idm::oauth2::{
-    AuthorisationRequest, AuthorisationRequestContext, AuthoriseResponse, Oauth2Error,
+    AuthorisationRequestContext, AuthorisationRequestKind, AuthoriseResponse, Oauth2Error,
 };
 use kanidmd_lib::prelude::*;
 use serde::Deserialize;
 use std::collections::BTreeSet;
 
 #[cfg(feature = "dev-oauth2-device-flow")]
-use axum::http::StatusCode;
+use axum::{extract::Query, http::StatusCode};
+#[cfg(feature = "dev-oauth2-device-flow")]
+use kanidmd_lib::idm::oauth2::normalise_user_code;
 
 #[derive(Template, WebTemplate)]
 #[template(path = "oauth2_consent_request.html")]
@@ -51,8 +54,8 @@ struct AccessDeniedView {
 enum AuthReqState {
     #[default]
     None,
-    Initial(AuthorisationRequest),
-    Resumed(AuthorisationRequest),
+    Initial(AuthorisationRequestKind),
+    Resumed(AuthorisationRequestKind),
 }
 
 pub async fn view_index_get(
@@ -61,8 +64,24 @@ pub async fn view_index_get(
     VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
     DomainInfo(domain_info): DomainInfo,
     jar: CookieJar,
-    Query(auth_req): Query<AuthorisationRequest>,
+    RawQuery(query): RawQuery,
 ) -> Response {
+    let auth_req = match authorisation_request_from_query(query.as_deref()) {
+        Ok(auth_req) => auth_req,
+        Err(err) => {
+            error!(
+                ?err,
+                "unable to deserialise the oauth2 authorisation request"
+            );
+            return UnrecoverableErrorView {
+                err_code: OperationError::InvalidRequestState,
+                operation_id: kopid.eventid,
+                domain_info,
+            }
+            .into_response();
+        }
+    };
+
     oauth2_auth_req(
         state,
         kopid,
@@ -82,7 +101,7 @@ pub async fn view_resume_get(
     jar: CookieJar,
 ) -> Response {
     let maybe_auth_req =
-        cookies::get_signed::<AuthorisationRequest>(&state, &jar, COOKIE_OAUTH2_REQ)
+        cookies::get_signed::<AuthorisationRequestKind>(&state, &jar, COOKIE_OAUTH2_REQ)
             .map(AuthReqState::Resumed)
             .unwrap_or_default();
 
@@ -174,7 +193,7 @@ async fn oauth2_auth_req(
             )
                 .into_response()
         }
-        Ok(AuthoriseResponse::ReauthenticationRequired { client_name }) => {
+        Ok(AuthoriseResponse::ReauthenticationRequired { client_name, acr }) => {
             // Sign the auth req and hide it in our cookie - we'll come back for
             // you later.
             let maybe_jar = cookies::make_signed(&state, COOKIE_OAUTH2_REQ, &auth_req)
@@ -226,6 +245,7 @@ async fn oauth2_auth_req(
                 kopid,
                 new_jar,
                 display_ctx,
+                acr,
             )
             .await
         }
@@ -402,10 +422,17 @@ pub async fn view_device_get(
     Query(user_code): Query<QueryUserCode>,
 ) -> Result<Oauth2DeviceLoginView, (StatusCode, String)> {
     // TODO: if we have a valid auth session and the user code is valid, prompt the user to allow the session to start
+    // Prefill the form with the code in the same form it was issued, so a hand typed
+    // verification_uri_complete still looks right to the user.
+    let user_code = user_code
+        .user_code
+        .and_then(|user_code| normalise_user_code(&user_code).ok())
+        .unwrap_or_default();
+
     Ok(Oauth2DeviceLoginView {
         domain_custom_image: state.qe_r_ref.domain_info_read().has_custom_image(),
         title: "Device Login".to_string(),
-        user_code: user_code.user_code.unwrap_or("".to_string()),
+        user_code,
     })
 }
 
@@ -424,7 +451,9 @@ pub async fn view_device_post(
     VerifiedClientInformation(_client_auth_info): VerifiedClientInformation,
     Form(form): Form<Oauth2DeviceLoginForm>,
 ) -> Result<String, (StatusCode, &'static str)> {
-    debug!("User code: {}", form.user_code);
+    let user_code = normalise_user_code(&form.user_code)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid user code"))?;
+    debug!("User code: {}", user_code);
     debug!("User confirmed: {}", form.confirm_login);
 
     // TODO: when the user POST's this form we need to check the user code and see if it's valid
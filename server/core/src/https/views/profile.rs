@@ -179,6 +179,11 @@ pub(crate) async fn view_profile_diff_start_save_post(
                 .map(|(ei, email)| ScimMail {
                     primary: ei == primary_index,
                     value: email.to_string(),
+                    // The server ignores this, but it must match for the comparison below.
+                    verified: scim_person
+                        .mails
+                        .iter()
+                        .any(|mail| mail.value == *email && mail.verified),
                 })
                 .collect();
 
@@ -249,6 +254,7 @@ pub(crate) async fn view_profile_diff_confirm_save_post(
                 .map(|secondary_mail| ScimMail {
                     primary: false,
                     value: secondary_mail,
+                    verified: false,
                 })
                 .collect::<Vec<_>>()
         } else {
@@ -259,6 +265,7 @@ pub(crate) async fn view_profile_diff_confirm_save_post(
             scim_mails.push(ScimMail {
                 primary: true,
                 value: primary_mail,
+                verified: false,
             })
         }
 
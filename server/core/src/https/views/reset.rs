@@ -74,6 +74,13 @@ struct RevokeCredFormView {
     token: String,
 }
 
+#[derive(Template, WebTemplate)]
+#[template(path = "mail_verify_form.html")]
+struct MailVerifyFormView {
+    domain_info: DomainInfoRead,
+    token: String,
+}
+
 #[derive(Template, WebTemplate)]
 #[template(path = "credentials_status.html")]
 struct CredStatusView {
@@ -1119,6 +1126,50 @@ pub(crate) async fn view_revoke_post(
     }
 }
 
+pub(crate) async fn view_mail_verify_get(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    HxRequest(_hx_request): HxRequest,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    DomainInfo(domain_info): DomainInfo,
+    Query(params): Query<ResetTokenParam>,
+    jar: CookieJar,
+) -> axum::response::Result<Response> {
+    if let Some(token) = params.token {
+        let view = MailVerifyFormView { domain_info, token };
+
+        Ok(view.into_response())
+    } else {
+        end_session_response(state, kopid, client_auth_info, jar).await
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MailVerifyForm {
+    token: String,
+}
+
+pub(crate) async fn view_mail_verify_post(
+    State(state): State<ServerState>,
+    Extension(kopid): Extension<KOpId>,
+    HxRequest(_hx_request): HxRequest,
+    VerifiedClientInformation(client_auth_info): VerifiedClientInformation,
+    DomainInfo(domain_info): DomainInfo,
+    jar: CookieJar,
+    Form(verify_form): Form<MailVerifyForm>,
+) -> axum::response::Result<Response> {
+    match state
+        .qe_w_ref
+        .handle_mail_verification_confirm(verify_form.token, kopid.eventid)
+        .await
+    {
+        Ok(()) => end_session_response(state, kopid, client_auth_info, jar).await,
+        Err(op_err) => Err(ErrorResponse::from(
+            HtmxError::new(&kopid, op_err, domain_info).into_response(),
+        )),
+    }
+}
+
 fn get_cu_partial(cu_status: CUStatus) -> CredResetPartialView {
     let CUStatus {
         ext_cred_portal,
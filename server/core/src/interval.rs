@@ -17,20 +17,27 @@ use crate::CoreAction;
 use crate::actors::{QueryServerReadV1, QueryServerWriteV1};
 use kanidmd_lib::constants::PURGE_FREQUENCY;
 use kanidmd_lib::event::{
-    OnlineBackupEvent, PurgeDeleteAfterEvent, PurgeRecycledEvent, PurgeTombstoneEvent,
+    AccountExpiryEvent, DomainRenameMigrateEvent, MemberExpiryEvent, OnlineBackupEvent,
+    PurgeDeleteAfterEvent, PurgeRecycledEvent, PurgeTombstoneEvent,
 };
+use kanidmd_lib::prelude::duration_from_epoch_now;
+use kanidmd_lib::server::expiry::AccountExpiryNotice;
 
 pub(crate) struct IntervalActor;
 
 impl IntervalActor {
     pub fn start(
         server: &'static QueryServerWriteV1,
+        account_expiry_notice: Option<AccountExpiryNotice>,
         mut rx: broadcast::Receiver<CoreAction>,
     ) -> tokio::task::JoinHandle<()> {
         tokio::spawn(async move {
             let mut inter = interval(Duration::from_secs(PURGE_FREQUENCY));
             inter.set_missed_tick_behavior(MissedTickBehavior::Skip);
 
+            // Expiry notices that fell due while the server was stopped are not sent.
+            let mut account_expiry_since = duration_from_epoch_now();
+
             loop {
                 server
                     .handle_purgetombstoneevent(PurgeTombstoneEvent::new())
@@ -41,6 +48,19 @@ impl IntervalActor {
                 server
                     .handle_purge_delete_after_event(PurgeDeleteAfterEvent::new())
                     .await;
+                server
+                    .handle_domain_rename_migrate_event(DomainRenameMigrateEvent::new())
+                    .await;
+                server
+                    .handle_member_expiry_event(MemberExpiryEvent::new())
+                    .await;
+                server.handle_oauth2_pushed_request_expiry();
+                account_expiry_since = server
+                    .handle_account_expiry_event(AccountExpiryEvent::new(
+                        account_expiry_since,
+                        account_expiry_notice,
+                    ))
+                    .await;
 
                 tokio::select! {
                     Ok(action) = rx.recv() => {
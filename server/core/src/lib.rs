@@ -35,8 +35,10 @@ mod https;
 mod interval;
 mod ldaps;
 mod repl;
+mod security_events;
 mod tcp;
 mod utils;
+mod webhooks;
 
 use crate::actors::{QueryServerReadV1, QueryServerWriteV1};
 use crate::admin::AdminActor;
@@ -51,7 +53,8 @@ use crypto_glue::{
 use kanidm_proto::backup::BackupCompression;
 use kanidm_proto::internal::OperationError;
 use kanidm_proto::scim_v1::client::ScimAssertGeneric;
-use kanidmd_lib::be::{Backend, BackendConfig, BackendTransaction};
+use kanidmd_lib::be::{Backend, BackendConfig, BackendTransaction, BackupArchive, RestoreSchema};
+use kanidmd_lib::idm::breachlist::PasswordBreachList;
 use kanidmd_lib::idm::ldap::LdapServer;
 use kanidmd_lib::prelude::*;
 use kanidmd_lib::schema::Schema;
@@ -124,12 +127,19 @@ async fn setup_qs_idms(
         .initialise_helper(curtime, DOMAIN_TGT_LEVEL)
         .await?;
 
+    let password_breach_list = config
+        .password_breach_list_path
+        .clone()
+        .map(PasswordBreachList::new)
+        .transpose()?;
+
     // We generate a SINGLE idms only!
     let is_integration_test = config.integration_test_config.is_some();
     let (idms, idms_delayed, idms_audit) = IdmServer::new(
         query_server.clone(),
         &config.origin,
         is_integration_test,
+        password_breach_list,
         curtime,
     )
     .await?;
@@ -419,7 +429,65 @@ pub fn backup_server_core(config: &Configuration, dst_path: Option<&Path>) {
     // Let the txn abort, even on success.
 }
 
-pub async fn restore_server_core(config: &Configuration, dst_path: &Path) {
+pub async fn restore_server_core(
+    config: &Configuration,
+    dst_path: &Path,
+    dry_run: bool,
+    use_current_schema: bool,
+) {
+    let compression = BackupCompression::identify_file(dst_path);
+
+    let input = match std::fs::File::open(dst_path) {
+        Ok(output) => output,
+        Err(err) => {
+            error!(?err, "File::open error reading {}", dst_path.display());
+            return;
+        }
+    };
+
+    // Verify the backup before anything is changed, as restoring removes all existing content.
+    let archive = match BackupArchive::read(std::io::BufReader::new(input), compression) {
+        Ok(archive) => archive,
+        Err(err) => {
+            error!(?err, "Unable to read backup {}", dst_path.display());
+            std::process::exit(1);
+        }
+    };
+
+    let restore_schema = if use_current_schema {
+        RestoreSchema::Current
+    } else {
+        RestoreSchema::Bundled
+    };
+
+    let report = match archive.verify(restore_schema) {
+        Ok(report) => report,
+        Err(err) => {
+            error!(?err, "Unable to verify backup {}", dst_path.display());
+            std::process::exit(1);
+        }
+    };
+
+    info!(
+        "backup_version: {}",
+        report.version.as_deref().unwrap_or("unknown")
+    );
+    info!("entry_count   : {}", report.entry_count);
+    info!("issues        : {}", report.issues.len());
+    for issue in report.issues.iter() {
+        error!("{}", issue);
+    }
+
+    if !report.is_ok() {
+        error!("Backup verification failed, the database has not been changed.");
+        std::process::exit(1);
+    }
+
+    if dry_run {
+        info!("✅ Backup verification passed, the database has not been changed.");
+        return;
+    }
+
     // If it's an in memory database, we don't need to touch anything
     if let Some(db_path) = config.db_path.as_ref() {
         touch_file_or_quit(db_path);
@@ -453,18 +521,8 @@ pub async fn restore_server_core(config: &Configuration, dst_path: &Path) {
         }
     };
 
-    let compression = BackupCompression::identify_file(dst_path);
-
-    let input = match std::fs::File::open(dst_path) {
-        Ok(output) => output,
-        Err(err) => {
-            error!(?err, "File::open error reading {}", dst_path.display());
-            return;
-        }
-    };
-
     let r = be_wr_txn
-        .restore(input, compression)
+        .restore_archive(archive)
         .and_then(|_| be_wr_txn.commit());
 
     if r.is_err() {
@@ -628,16 +686,43 @@ pub async fn domain_rename_core(config: &Configuration) {
         return;
     };
     let r = qs_write
-        .danger_domain_rename(new_domain_name)
+        .domain_rename(new_domain_name)
         .and_then(|_| qs_write.commit());
 
-    match r {
-        Ok(_) => info!("Domain Rename Success!"),
-        Err(e) => {
-            error!("Domain Rename Failed - Rollback has occurred: {:?}", e);
-            std::process::exit(1);
+    if let Err(e) = r {
+        error!("Domain Rename Failed - Rollback has occurred: {:?}", e);
+        std::process::exit(1);
+    }
+
+    // Regenerate spns now. If this is interrupted the server resumes the migration
+    // in the background when it next starts.
+    let mut cursor = None;
+    loop {
+        let Ok(mut qs_write) = qs.write(duration_from_epoch_now()).await else {
+            error!("Unable to acquire write transaction");
+            return;
+        };
+        let r = qs_write
+            .domain_rename_migrate(cursor.as_deref(), DOMAIN_RENAME_MIGRATE_CHUNK)
+            .and_then(|next| qs_write.commit().map(|_| next));
+
+        match r {
+            Ok(None) => break,
+            Ok(next) => {
+                info!("Domain rename migration in progress");
+                cursor = next;
+            }
+            Err(e) => {
+                error!(
+                    "Domain Rename Migration Failed - it will be resumed on next start: {:?}",
+                    e
+                );
+                std::process::exit(1);
+            }
         }
-    };
+    }
+
+    info!("Domain Rename Success!");
 }
 
 pub async fn verify_server_core(config: &Configuration) {
@@ -670,6 +755,11 @@ pub async fn verify_server_core(config: &Configuration) {
     // Run verifications.
     let r = server.verify().await;
 
+    // Warnings don't fail verification, but are reported so that they can be reviewed.
+    for warning in server.verify_warnings().await {
+        warn!("{:?}", warning);
+    }
+
     if r.is_empty() {
         eprintln!("Verification passed!");
         std::process::exit(0);
@@ -921,6 +1011,8 @@ pub(crate) enum TaskName {
     Replication,
     TlsAcceptorReload,
     MigrationReload,
+    SecurityEventActor,
+    WebhookActor,
 }
 
 impl Display for TaskName {
@@ -939,6 +1031,8 @@ impl Display for TaskName {
                 TaskName::Replication => "Replication",
                 TaskName::TlsAcceptorReload => "TlsAcceptor Reload Monitor",
                 TaskName::MigrationReload => "Migration Reload Monitor",
+                TaskName::SecurityEventActor => "Security Event Actor",
+                TaskName::WebhookActor => "Webhook Actor",
             }
         )
     }
@@ -1047,7 +1141,7 @@ pub async fn create_server_core(
         }
     };
     // Start the IDM server.
-    let (_qs, idms, mut idms_delayed, mut idms_audit) =
+    let (qs, idms, mut idms_delayed, mut idms_audit) =
         match setup_qs_idms(be, schema, &config).await {
             Ok(t) => t,
             Err(e) => {
@@ -1286,8 +1380,24 @@ pub async fn create_server_core(
         info!("Stopped {}", TaskName::TlsAcceptorReload);
     });
 
+    // Export security events to any configured collectors.
+    let maybe_security_event_handle = security_events::start(
+        qs.security_events().clone(),
+        &config.security_event_sinks,
+        &config.domain,
+        broadcast_tx.subscribe(),
+    )
+    .await?;
+
+    // Deliver webhook notifications of committed changes.
+    let webhook_handle = webhooks::start(qs.webhooks().clone(), broadcast_tx.subscribe()).await?;
+
     // Setup timed events associated to the write thread
-    let interval_handle = IntervalActor::start(server_write_ref, broadcast_tx.subscribe());
+    let interval_handle = IntervalActor::start(
+        server_write_ref,
+        config.account_expiry.as_ref().map(|expiry| expiry.notice()),
+        broadcast_tx.subscribe(),
+    );
     // Setup timed events associated to the read thread
     let maybe_backup_handle = match &config.online_backup {
         Some(online_backup_config) => {
@@ -1393,6 +1503,8 @@ pub async fn create_server_core(
             server_read_ref,
             broadcast_tx_,
             maybe_repl_ctrl_tx,
+            config.online_backup.clone(),
+            config.db_path.clone(),
         )
         .await?;
 
@@ -1433,6 +1545,12 @@ pub async fn create_server_core(
         handles.push((TaskName::Replication, repl_handle))
     }
 
+    if let Some(security_event_handle) = maybe_security_event_handle {
+        handles.push((TaskName::SecurityEventActor, security_event_handle))
+    }
+
+    handles.push((TaskName::WebhookActor, webhook_handle));
+
     Ok(CoreHandle {
         clean_shutdown: false,
         tx: broadcast_tx,
@@ -0,0 +1,342 @@
+//! Export of security events to external collectors, such as a SIEM. Events are read from
+//! the bounded queue of the query server and written to each configured sink in turn. If
+//! the sinks fall behind then the queue fills and further events are dropped (and counted)
+//! as they are submitted, so a slow sink can never block request handling.
+
+use std::io;
+use std::net::SocketAddr;
+
+use kanidmd_lib::server::security_event::{
+    SecurityEvent, SecurityEventOutcome, SecurityEventQueue,
+};
+use std::sync::Arc;
+use time::format_description::well_known::Rfc3339;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::broadcast;
+use tokio::task;
+
+use crate::config::{SecurityEventSinkConfig, SyslogTransport};
+use crate::{CoreAction, TaskName};
+
+/// The number of security events that may be waiting for the sinks before further events
+/// are dropped.
+pub(crate) const SECURITY_EVENT_QUEUE_LENGTH: usize = 1024;
+
+/// The syslog "security/authorization messages" facility.
+const SYSLOG_FACILITY_AUTHPRIV: u8 = 10;
+const SYSLOG_SEVERITY_WARNING: u8 = 4;
+const SYSLOG_SEVERITY_INFO: u8 = 6;
+const SYSLOG_APP_NAME: &str = "kanidmd";
+
+enum SinkFormat {
+    Syslog { hostname: String },
+    JsonLines,
+}
+
+enum SinkTransport {
+    Udp(UdpSocket),
+    Tcp {
+        address: SocketAddr,
+        stream: Option<TcpStream>,
+    },
+    File(File),
+}
+
+pub(crate) struct SecurityEventSink {
+    format: SinkFormat,
+    transport: SinkTransport,
+}
+
+impl SecurityEventSink {
+    pub(crate) async fn new(config: &SecurityEventSinkConfig, hostname: &str) -> io::Result<Self> {
+        let syslog = SinkFormat::Syslog {
+            hostname: hostname.to_string(),
+        };
+
+        let (format, transport) = match config {
+            SecurityEventSinkConfig::Syslog {
+                address,
+                transport: SyslogTransport::Udp,
+            } => {
+                let bind_address: SocketAddr = if address.is_ipv4() {
+                    ([0, 0, 0, 0], 0).into()
+                } else {
+                    ([0u16; 8], 0).into()
+                };
+                let socket = UdpSocket::bind(bind_address).await?;
+                socket.connect(address).await?;
+                (syslog, SinkTransport::Udp(socket))
+            }
+            SecurityEventSinkConfig::Syslog {
+                address,
+                transport: SyslogTransport::Tcp,
+            } => (
+                syslog,
+                SinkTransport::Tcp {
+                    address: *address,
+                    stream: None,
+                },
+            ),
+            SecurityEventSinkConfig::JsonFile { path } => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .await?;
+                (SinkFormat::JsonLines, SinkTransport::File(file))
+            }
+            SecurityEventSinkConfig::JsonTcp { address } => (
+                SinkFormat::JsonLines,
+                SinkTransport::Tcp {
+                    address: *address,
+                    stream: None,
+                },
+            ),
+        };
+
+        Ok(SecurityEventSink { format, transport })
+    }
+
+    fn encode(&self, event: &SecurityEvent) -> io::Result<Vec<u8>> {
+        let json = serde_json::to_string(event)?;
+
+        let message = match &self.format {
+            SinkFormat::JsonLines => {
+                let mut line = json.into_bytes();
+                line.push(b'\n');
+                return Ok(line);
+            }
+            SinkFormat::Syslog { hostname } => {
+                let severity = match event.outcome {
+                    SecurityEventOutcome::Success => SYSLOG_SEVERITY_INFO,
+                    SecurityEventOutcome::Failure => SYSLOG_SEVERITY_WARNING,
+                };
+                let priority = SYSLOG_FACILITY_AUTHPRIV * 8 + severity;
+                let timestamp = event
+                    .time
+                    .format(&Rfc3339)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                // <PRI>VERSION TIMESTAMP HOSTNAME APP-NAME PROCID MSGID STRUCTURED-DATA MSG
+                format!(
+                    "<{}>1 {} {} {} {} {} - {}",
+                    priority,
+                    timestamp,
+                    hostname,
+                    SYSLOG_APP_NAME,
+                    std::process::id(),
+                    event.kind.as_str(),
+                    json
+                )
+            }
+        };
+
+        match self.transport {
+            // Syslog over a stream uses octet counting to frame messages (RFC 6587).
+            SinkTransport::Tcp { .. } => Ok(format!("{} {}", message.len(), message).into_bytes()),
+            SinkTransport::Udp(_) | SinkTransport::File(_) => Ok(message.into_bytes()),
+        }
+    }
+
+    pub(crate) async fn send(&mut self, event: &SecurityEvent) -> io::Result<()> {
+        let data = self.encode(event)?;
+
+        match &mut self.transport {
+            SinkTransport::Udp(socket) => socket.send(&data).await.map(|_| ()),
+            SinkTransport::File(file) => {
+                file.write_all(&data).await?;
+                file.flush().await
+            }
+            SinkTransport::Tcp { address, stream } => {
+                let mut connected = match stream.take() {
+                    Some(connected) => connected,
+                    None => TcpStream::connect(*address).await?,
+                };
+                let result = connected.write_all(&data).await;
+                // On failure the connection is dropped, and reopened for the next event.
+                if result.is_ok() {
+                    *stream = Some(connected);
+                }
+                result
+            }
+        }
+    }
+}
+
+/// Start the task that exports security events to the configured sinks. This does nothing
+/// if no sinks are configured.
+pub(crate) async fn start(
+    queue: Arc<SecurityEventQueue>,
+    configs: &[SecurityEventSinkConfig],
+    hostname: &str,
+    mut broadcast_rx: broadcast::Receiver<CoreAction>,
+) -> Result<Option<task::JoinHandle<()>>, ()> {
+    if configs.is_empty() {
+        debug!("Security event export not requested, skipping");
+        return Ok(None);
+    }
+
+    let mut sinks = Vec::with_capacity(configs.len());
+    for config in configs {
+        let sink = SecurityEventSink::new(config, hostname)
+            .await
+            .map_err(|err| {
+                error!(?err, %config, "Failed to configure security event sink");
+            })?;
+        sinks.push(sink);
+    }
+
+    let Some(mut event_rx) = queue.subscribe(SECURITY_EVENT_QUEUE_LENGTH) else {
+        error!("Security events are already being exported");
+        return Err(());
+    };
+
+    let handle = task::spawn(async move {
+        let mut reported_dropped = 0;
+        loop {
+            tokio::select! {
+                Ok(action) = broadcast_rx.recv() => {
+                    match action {
+                        CoreAction::Shutdown => break,
+                        CoreAction::Reload => {},
+                    }
+                }
+                maybe_event = event_rx.recv() => {
+                    let Some(event) = maybe_event else {
+                        break;
+                    };
+
+                    for sink in sinks.iter_mut() {
+                        if let Err(err) = sink.send(&event).await {
+                            error!(?err, "Unable to export security event");
+                        }
+                    }
+
+                    let dropped = queue.dropped();
+                    if dropped != reported_dropped {
+                        warn!(
+                            dropped = dropped - reported_dropped,
+                            total_dropped = dropped,
+                            "Security events were dropped as the sinks are not keeping up"
+                        );
+                        reported_dropped = dropped;
+                    }
+                }
+            }
+        }
+        info!("Stopped {}", TaskName::SecurityEventActor);
+    });
+
+    Ok(Some(handle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kanidmd_lib::prelude::Uuid;
+    use kanidmd_lib::server::security_event::{SecurityEventKind, SECURITY_EVENT_VERSION};
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+    use tokio::net::TcpListener;
+
+    fn test_event() -> SecurityEvent {
+        let mut event = SecurityEvent::new(
+            SecurityEventKind::Authentication,
+            SecurityEventOutcome::Failure,
+            Duration::from_secs(60),
+        );
+        event.actor = Some(Uuid::new_v4());
+        event.source = Some([192, 0, 2, 1].into());
+        event
+    }
+
+    #[tokio::test]
+    async fn test_security_event_syslog_udp() {
+        let collector = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let config = SecurityEventSinkConfig::Syslog {
+            address: collector.local_addr().unwrap(),
+            transport: SyslogTransport::Udp,
+        };
+        let mut sink = SecurityEventSink::new(&config, "idm.example.com")
+            .await
+            .unwrap();
+
+        let event = test_event();
+        sink.send(&event).await.unwrap();
+
+        let mut buf = vec![0; 4096];
+        let len = collector.recv(&mut buf).await.unwrap();
+        let message = String::from_utf8(buf[..len].to_vec()).unwrap();
+
+        // authpriv.warning
+        let prefix = format!(
+            "<84>1 1970-01-01T00:01:00Z idm.example.com kanidmd {} authentication - ",
+            std::process::id()
+        );
+        assert!(message.starts_with(&prefix));
+        let received: SecurityEvent = serde_json::from_str(&message[prefix.len()..]).unwrap();
+        assert_eq!(received, event);
+    }
+
+    #[tokio::test]
+    async fn test_security_event_json_tcp() {
+        let collector = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = SecurityEventSinkConfig::JsonTcp {
+            address: collector.local_addr().unwrap(),
+        };
+        let mut sink = SecurityEventSink::new(&config, "idm.example.com")
+            .await
+            .unwrap();
+
+        let event = test_event();
+        sink.send(&event).await.unwrap();
+        sink.send(&event).await.unwrap();
+
+        let (stream, _) = collector.accept().await.unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        for _ in 0..2 {
+            let line = lines.next_line().await.unwrap().unwrap();
+            let received: serde_json::Value = serde_json::from_str(&line).unwrap();
+            assert_eq!(received["version"], SECURITY_EVENT_VERSION);
+            assert_eq!(received["kind"], "authentication");
+            assert_eq!(received["outcome"], "failure");
+            assert_eq!(received["source"], "192.0.2.1");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_security_event_export_drops_when_full() {
+        let collector = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let queue = Arc::new(SecurityEventQueue::default());
+        let configs = [SecurityEventSinkConfig::JsonTcp {
+            address: collector.local_addr().unwrap(),
+        }];
+        let (broadcast_tx, broadcast_rx) = broadcast::channel(1);
+
+        let handle = start(queue.clone(), &configs, "idm.example.com", broadcast_rx)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // Submitting many more events than the queue holds never blocks, the excess is
+        // dropped and counted instead. The export task can't run until we yield, so
+        // exactly one queue worth of events is kept.
+        for _ in 0..(SECURITY_EVENT_QUEUE_LENGTH * 4) {
+            queue.submit(test_event());
+        }
+        assert_eq!(queue.dropped(), (SECURITY_EVENT_QUEUE_LENGTH * 3) as u64);
+
+        let (stream, _) = collector.accept().await.unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        let line = lines.next_line().await.unwrap().unwrap();
+        let received: SecurityEvent = serde_json::from_str(&line).unwrap();
+        assert_eq!(received.kind, SecurityEventKind::Authentication);
+
+        // Stop collecting so that the remaining events fail quickly.
+        drop(lines);
+        drop(collector);
+        broadcast_tx.send(CoreAction::Shutdown).unwrap();
+        handle.await.unwrap();
+    }
+}
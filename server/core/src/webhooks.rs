@@ -0,0 +1,304 @@
+//! Delivery of webhook notifications. Deliveries are read from the bounded queue of the query
+//! server once their transaction has committed, and posted to the url of their subscription
+//! as signed JSON. A delivery that fails is retried with an increasing delay, and once its
+//! attempts are exhausted it is abandoned and counted as a dead letter.
+//!
+//! The body is signed with HMAC-SHA256 using the secret of the subscription, and the
+//! signature is sent as `sha256=<hex>` in the [`WEBHOOK_SIGNATURE_HEADER`] header. Receivers
+//! must verify the signature over the exact bytes of the body before trusting it.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crypto_glue::{hmac_s256::HmacSha256, traits::Mac};
+use kanidmd_lib::server::webhook::{WebhookDelivery, WebhookQueue};
+use reqwest::header::CONTENT_TYPE;
+use tokio::sync::{broadcast, Semaphore};
+use tokio::task;
+use tokio::time::sleep;
+
+use crate::{CoreAction, TaskName};
+
+/// The number of deliveries that may be waiting to be sent before further deliveries are
+/// dropped.
+pub(crate) const WEBHOOK_QUEUE_LENGTH: usize = 1024;
+
+/// The number of deliveries that may be in progress at once, including those waiting to
+/// retry.
+const WEBHOOK_MAX_IN_FLIGHT: usize = 32;
+
+const WEBHOOK_MAX_ATTEMPTS: u32 = 5;
+const WEBHOOK_RETRY_DELAY: Duration = Duration::from_secs(2);
+const WEBHOOK_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub(crate) const WEBHOOK_SIGNATURE_HEADER: &str = "X-Kanidm-Webhook-Signature";
+
+/// Sign the body of a delivery, returning the value of the signature header.
+pub(crate) fn sign(secret: &str, body: &[u8]) -> Option<String> {
+    let mut hmac = HmacSha256::new_from_slice(secret.as_bytes()).ok()?;
+    hmac.update(body);
+    let signature: String = hmac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    Some(format!("sha256={signature}"))
+}
+
+struct WebhookDeliverer {
+    client: reqwest::Client,
+    max_attempts: u32,
+    retry_delay: Duration,
+}
+
+impl WebhookDeliverer {
+    fn new(max_attempts: u32, retry_delay: Duration) -> Result<Self, ()> {
+        let client = reqwest::ClientBuilder::new()
+            .timeout(WEBHOOK_REQUEST_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|err| {
+                error!(?err, "Invalid webhook http client builder parameters");
+            })?;
+
+        Ok(WebhookDeliverer {
+            client,
+            max_attempts: max_attempts.max(1),
+            retry_delay,
+        })
+    }
+
+    /// Post the delivery to its url, retrying until it is accepted or the attempts are
+    /// exhausted. Returns true if the receiver accepted the delivery.
+    async fn deliver(&self, delivery: &WebhookDelivery) -> bool {
+        let body = match serde_json::to_vec(&delivery.payload) {
+            Ok(body) => body,
+            Err(err) => {
+                error!(?err, "Unable to serialise webhook payload");
+                return false;
+            }
+        };
+
+        let Some(signature) = sign(&delivery.secret, &body) else {
+            error!(?delivery, "Unable to sign webhook payload");
+            return false;
+        };
+
+        let mut retry_delay = self.retry_delay;
+        for attempt in 1..=self.max_attempts {
+            let result = self
+                .client
+                .post(delivery.url.as_str())
+                .header(CONTENT_TYPE, "application/json")
+                .header(WEBHOOK_SIGNATURE_HEADER, signature.as_str())
+                .body(body.clone())
+                .send()
+                .await;
+
+            match result {
+                Ok(response) if response.status().is_success() => return true,
+                Ok(response) => {
+                    warn!(url = %delivery.url, status = %response.status(), %attempt, "Webhook receiver refused delivery");
+                }
+                Err(err) => {
+                    warn!(url = %delivery.url, ?err, %attempt, "Unable to deliver webhook");
+                }
+            }
+
+            if attempt < self.max_attempts {
+                sleep(retry_delay).await;
+                retry_delay = retry_delay.saturating_mul(2);
+            }
+        }
+
+        false
+    }
+
+    async fn deliver_or_dead_letter(&self, queue: &WebhookQueue, delivery: WebhookDelivery) {
+        if !self.deliver(&delivery).await {
+            queue.dead_letter(&delivery);
+        }
+    }
+}
+
+pub(crate) async fn start(
+    queue: Arc<WebhookQueue>,
+    mut broadcast_rx: broadcast::Receiver<CoreAction>,
+) -> Result<task::JoinHandle<()>, ()> {
+    let deliverer = Arc::new(WebhookDeliverer::new(
+        WEBHOOK_MAX_ATTEMPTS,
+        WEBHOOK_RETRY_DELAY,
+    )?);
+
+    let Some(mut delivery_rx) = queue.subscribe(WEBHOOK_QUEUE_LENGTH) else {
+        error!("Webhooks are already being delivered");
+        return Err(());
+    };
+
+    let in_flight = Arc::new(Semaphore::new(WEBHOOK_MAX_IN_FLIGHT));
+
+    let handle = task::spawn(async move {
+        let mut reported_dropped = 0;
+        loop {
+            tokio::select! {
+                Ok(action) = broadcast_rx.recv() => {
+                    match action {
+                        CoreAction::Shutdown => break,
+                        CoreAction::Reload => {},
+                    }
+                }
+                maybe_delivery = delivery_rx.recv() => {
+                    let Some(delivery) = maybe_delivery else {
+                        break;
+                    };
+
+                    // While the receivers are slow, stop reading so that the queue fills
+                    // and further deliveries are dropped rather than held in memory.
+                    let Ok(permit) = in_flight.clone().acquire_owned().await else {
+                        break;
+                    };
+
+                    let deliverer = deliverer.clone();
+                    let task_queue = queue.clone();
+                    task::spawn(async move {
+                        deliverer.deliver_or_dead_letter(&task_queue, delivery).await;
+                        drop(permit);
+                    });
+
+                    let dropped = queue.dropped();
+                    if dropped != reported_dropped {
+                        warn!(
+                            dropped = dropped - reported_dropped,
+                            total_dropped = dropped,
+                            "Webhook deliveries were dropped as the receivers are not keeping up"
+                        );
+                        reported_dropped = dropped;
+                    }
+                }
+            }
+        }
+        info!("Stopped {}", TaskName::WebhookActor);
+    });
+
+    Ok(handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Bytes;
+    use axum::extract::State;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::routing::post;
+    use axum::Router;
+    use kanidmd_lib::prelude::{Url, Uuid};
+    use kanidmd_lib::server::webhook::{WebhookEventKind, WebhookPayload, WEBHOOK_PAYLOAD_VERSION};
+    use std::sync::Mutex;
+    use tokio::net::TcpListener;
+
+    type Received = Arc<Mutex<Vec<(Option<String>, Bytes)>>>;
+
+    /// Start a receiver that refuses the first `failures` deliveries, returning the url
+    /// of the receiver and the deliveries it has seen.
+    async fn start_receiver(failures: usize) -> (Url, Received) {
+        let received: Received = Arc::new(Mutex::new(Vec::new()));
+
+        let app = Router::new()
+            .route(
+                "/hook",
+                post(
+                    move |State(received): State<Received>, headers: HeaderMap, body: Bytes| async move {
+                        let mut received = received.lock().unwrap();
+                        let signature = headers
+                            .get(WEBHOOK_SIGNATURE_HEADER)
+                            .and_then(|v| v.to_str().ok())
+                            .map(str::to_string);
+                        received.push((signature, body));
+                        if received.len() <= failures {
+                            StatusCode::SERVICE_UNAVAILABLE
+                        } else {
+                            StatusCode::NO_CONTENT
+                        }
+                    },
+                ),
+            )
+            .with_state(received.clone());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        (
+            Url::parse(&format!("http://{addr}/hook")).unwrap(),
+            received,
+        )
+    }
+
+    fn test_delivery(url: Url) -> WebhookDelivery {
+        WebhookDelivery {
+            url,
+            secret: "webhook_secret".to_string(),
+            payload: WebhookPayload {
+                version: WEBHOOK_PAYLOAD_VERSION,
+                subscription: Uuid::new_v4(),
+                kind: WebhookEventKind::Modify,
+                entry: Uuid::new_v4(),
+                attributes: vec!["displayname".to_string()],
+                cid: "00000000000000000000000000000001-00000000-0000-0000-0000-000000000000"
+                    .to_string(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_webhook_delivery_signed_and_retried() {
+        let (url, received) = start_receiver(1).await;
+        let delivery = test_delivery(url);
+
+        let deliverer = WebhookDeliverer::new(3, Duration::from_millis(10)).unwrap();
+        assert!(deliverer.deliver(&delivery).await);
+
+        let received = received.lock().unwrap();
+        // The first attempt was refused, so the delivery was sent again.
+        assert_eq!(received.len(), 2);
+
+        for (signature, body) in received.iter() {
+            let mut hmac = HmacSha256::new_from_slice(b"webhook_secret").unwrap();
+            hmac.update(body);
+            let expected: String = hmac
+                .finalize()
+                .into_bytes()
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect();
+            assert_eq!(
+                signature.as_deref(),
+                Some(format!("sha256={expected}").as_str())
+            );
+
+            let payload: WebhookPayload = serde_json::from_slice(body).unwrap();
+            assert_eq!(payload, delivery.payload);
+        }
+
+        // The signature is over the exact body, so any change to it is detected.
+        let (signature, body) = &received[0];
+        let mut tampered = body.to_vec();
+        tampered.push(b' ');
+        assert_ne!(sign("webhook_secret", &tampered), signature.clone());
+    }
+
+    #[tokio::test]
+    async fn test_webhook_delivery_dead_letter() {
+        let (url, received) = start_receiver(usize::MAX).await;
+        let queue = WebhookQueue::default();
+
+        let deliverer = WebhookDeliverer::new(3, Duration::from_millis(10)).unwrap();
+        deliverer
+            .deliver_or_dead_letter(&queue, test_delivery(url))
+            .await;
+
+        assert_eq!(received.lock().unwrap().len(), 3);
+        assert_eq!(queue.dead_lettered(), 1);
+    }
+}
@@ -299,6 +299,38 @@ async fn submit_admin_req_human(path: &str, req: AdminTaskRequest) -> ExitCode {
             info!("domain_uuid   : {}", uuid);
             info!("domain_level  : {}", level);
         }
+        Some(Ok(AdminTaskResponse::OnlineBackup { path, snapshot_cid })) => {
+            info!("backup_path   : {}", path);
+            match snapshot_cid {
+                Some(cid) => info!("snapshot_cid  : {}", cid),
+                None => info!("snapshot_cid  : none"),
+            }
+        }
+        Some(Ok(AdminTaskResponse::BackendCacheStats { stats })) => {
+            for (name, cache) in [("entry", stats.entry), ("idl", stats.idl)] {
+                info!("{}_cache_capacity       : {}", name, cache.capacity);
+                info!("{}_cache_target         : {}", name, cache.target);
+                info!("{}_cache_hits           : {}", name, cache.hits);
+                info!("{}_cache_misses         : {}", name, cache.misses);
+                info!("{}_cache_hit_rate       : {:.3}", name, cache.hit_rate());
+                info!("{}_cache_evictions      : {}", name, cache.evictions);
+                info!(
+                    "{}_cache_include_all    : {}",
+                    name, cache.include_all_fallbacks
+                );
+            }
+        }
+        Some(Ok(AdminTaskResponse::DbVacuum {
+            dropped_idxs,
+            size_before,
+            size_after,
+        })) => {
+            for idx in dropped_idxs {
+                info!("dropped_index : {}", idx);
+            }
+            info!("size_before   : {}", size_before);
+            info!("size_after    : {}", size_after);
+        }
         Some(Ok(AdminTaskResponse::Success)) => info!("success"),
         Some(Ok(AdminTaskResponse::Error)) => {
             info!("Error - you should inspect the logs.");
@@ -952,11 +984,47 @@ async fn kanidm_main(config: Configuration, opt: KanidmdParser) -> ExitCode {
 
             backup_server_core(&config, Some(&bopt.path));
         }
+        KanidmdOpt::Database {
+            commands: DbCommands::OnlineBackup,
+        } => {
+            info!("Running online backup ...");
+            submit_admin_req_human(
+                config.adminbindpath.as_str(),
+                AdminTaskRequest::OnlineBackup,
+            )
+            .await;
+        }
+        KanidmdOpt::Database {
+            commands: DbCommands::OnlineVacuum,
+        } => {
+            info!("Running online vacuum ...");
+            submit_admin_req_human(config.adminbindpath.as_str(), AdminTaskRequest::DbVacuum).await;
+        }
+        KanidmdOpt::Database {
+            commands: DbCommands::CacheStats,
+        } => {
+            info!("Running show cache stats ...");
+            submit_admin_req_human(
+                config.adminbindpath.as_str(),
+                AdminTaskRequest::BackendCacheStats,
+            )
+            .await;
+        }
+        KanidmdOpt::Database {
+            commands: DbCommands::CacheTargets { entry, idl },
+        } => {
+            info!("Running change cache targets ...");
+            submit_admin_req_human(
+                config.adminbindpath.as_str(),
+                AdminTaskRequest::BackendCacheTargets { entry, idl },
+            )
+            .await;
+        }
         KanidmdOpt::Database {
             commands: DbCommands::Restore(ropt),
         } => {
             info!("Running in restore mode ...");
-            restore_server_core(&config, &ropt.path).await;
+            restore_server_core(&config, &ropt.path, ropt.dry_run, ropt.use_current_schema).await;
         }
         KanidmdOpt::Database {
             commands: DbCommands::Verify,
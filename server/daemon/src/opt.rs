@@ -14,6 +14,15 @@ struct RestoreOpt {
     #[clap(value_parser)]
     /// Restore from this path. Should be created with "backup".
     path: PathBuf,
+
+    /// Verify the backup and report any issues, without restoring it.
+    #[clap(long)]
+    dry_run: bool,
+
+    /// Validate entries against only the schema of this server, ignoring schema
+    /// definitions stored in the backup.
+    #[clap(long)]
+    use_current_schema: bool,
 }
 
 #[derive(Debug, Subcommand)]
@@ -48,6 +57,12 @@ enum DbCommands {
     #[clap(name = "backup")]
     /// Backup the database content (offline)
     Backup(BackupOpt),
+    #[clap(name = "online-backup")]
+    /// Backup the database content of the running server to the configured online backup path
+    OnlineBackup,
+    #[clap(name = "online-vacuum")]
+    /// Remove unused indexes and release free space from the database of the running server
+    OnlineVacuum,
     #[clap(name = "restore")]
     /// Restore the database content (offline)
     Restore(RestoreOpt),
@@ -57,6 +72,20 @@ enum DbCommands {
     #[clap(name = "reindex")]
     /// Reindex the database (offline)
     Reindex,
+    #[clap(name = "cache-stats")]
+    /// Show the hit rates and size targets of the caches of the running server
+    CacheStats,
+    #[clap(name = "cache-targets")]
+    /// Change the number of items the caches of the running server may hold. These are
+    /// reset when the server restarts.
+    CacheTargets {
+        /// The number of entries the entry cache may hold
+        #[clap(long)]
+        entry: Option<usize>,
+        /// The number of id lists the idl cache may hold
+        #[clap(long)]
+        idl: Option<usize>,
+    },
 }
 
 #[derive(Debug, Args)]
@@ -0,0 +1,449 @@
+//! Reading and verifying backups before they are restored. A restore removes all existing
+//! content before the backup is imported, so a damaged or incompatible backup must be
+//! found before that point. Verification only reads the backup, which allows it to be
+//! reported on without restoring (a dry run).
+
+use super::dbentry::{DbBackup, DbEntry};
+use super::dbrepl::DbReplMeta;
+use crate::migration_data;
+use crate::prelude::*;
+use crate::schema::{Schema, SchemaAttribute, SchemaClass, SchemaTransaction};
+use kanidm_proto::backup::BackupCompression;
+use kanidm_proto::internal::SchemaError;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// The schema that the entries of a backup are validated against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RestoreSchema {
+    /// The schema of this server, extended with any definitions stored in the backup.
+    #[default]
+    Bundled,
+    /// The schema of this server only.
+    Current,
+}
+
+/// A problem found in a backup that prevents it from being restored.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RestoreIssue {
+    /// The backup was created by a different server version, or one too old to record it.
+    VersionMismatch {
+        found: Option<String>,
+    },
+    /// The backup does not contain the number of entries recorded when it was taken.
+    EntryCountMismatch {
+        expected: u64,
+        found: usize,
+    },
+    /// The newest change recorded when the backup was taken does not match the
+    /// replication metadata of the backup.
+    SnapshotMismatch,
+    /// The entry at this position in the backup could not be decoded.
+    InvalidEntry {
+        index: usize,
+    },
+    DuplicateUuid(Uuid),
+    /// A schema definition stored in the backup could not be applied.
+    SchemaRejected {
+        uuid: Uuid,
+        reason: String,
+    },
+    SchemaViolation {
+        uuid: Uuid,
+        error: SchemaError,
+    },
+    /// An entry refers to another entry that is not in the backup.
+    DanglingReference {
+        uuid: Uuid,
+        attr: Attribute,
+        target: Uuid,
+    },
+}
+
+impl fmt::Display for RestoreIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RestoreIssue::VersionMismatch { found: Some(found) } => write!(
+                f,
+                "backup is from server version {} but this is version {}",
+                found,
+                env!("KANIDM_PKG_SERIES")
+            ),
+            RestoreIssue::VersionMismatch { found: None } => {
+                write!(f, "backup is from an older server version")
+            }
+            RestoreIssue::EntryCountMismatch { expected, found } => write!(
+                f,
+                "backup should contain {} entries but contains {}",
+                expected, found
+            ),
+            RestoreIssue::SnapshotMismatch => {
+                write!(f, "backup snapshot does not match its replication metadata")
+            }
+            RestoreIssue::InvalidEntry { index } => {
+                write!(f, "entry {} could not be decoded", index)
+            }
+            RestoreIssue::DuplicateUuid(uuid) => write!(f, "uuid {} is not unique", uuid),
+            RestoreIssue::SchemaRejected { uuid, reason } => {
+                write!(f, "schema definition {} was rejected - {}", uuid, reason)
+            }
+            RestoreIssue::SchemaViolation { uuid, error } => {
+                write!(f, "entry {} does not conform to schema - {:?}", uuid, error)
+            }
+            RestoreIssue::DanglingReference { uuid, attr, target } => write!(
+                f,
+                "entry {} refers to {} in {} which is not in the backup",
+                uuid, target, attr
+            ),
+        }
+    }
+}
+
+/// The outcome of verifying a backup.
+#[derive(Debug)]
+pub struct RestoreReport {
+    pub version: Option<String>,
+    pub entry_count: usize,
+    pub issues: Vec<RestoreIssue>,
+}
+
+impl RestoreReport {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// A backup that has been read, ready to be verified and restored.
+pub struct BackupArchive {
+    pub(super) dbbak: DbBackup,
+}
+
+impl BackupArchive {
+    pub fn read<IN>(input: IN, compression: BackupCompression) -> Result<Self, OperationError>
+    where
+        IN: std::io::Read,
+    {
+        let dbbak_option: Result<DbBackup, serde_json::Error> = match compression {
+            BackupCompression::NoCompression => serde_json::from_reader(input),
+            BackupCompression::Gzip => {
+                let decoder = flate2::read::GzDecoder::new(input);
+                serde_json::from_reader(decoder)
+            }
+        };
+
+        dbbak_option
+            .map(|dbbak| BackupArchive { dbbak })
+            .map_err(|e| {
+                admin_error!("serde_json error {:?}", e);
+                OperationError::SerdeJsonError
+            })
+    }
+
+    fn version(&self) -> Option<&str> {
+        match &self.dbbak {
+            DbBackup::V6 { version, .. } | DbBackup::V5 { version, .. } => Some(version),
+            DbBackup::V4 { .. } | DbBackup::V3 { .. } | DbBackup::V2 { .. } | DbBackup::V1(_) => {
+                None
+            }
+        }
+    }
+
+    fn entries(&self) -> &[DbEntry] {
+        match &self.dbbak {
+            DbBackup::V6 { entries, .. }
+            | DbBackup::V5 { entries, .. }
+            | DbBackup::V4 { entries, .. }
+            | DbBackup::V3 { entries, .. }
+            | DbBackup::V2 { entries, .. }
+            | DbBackup::V1(entries) => entries,
+        }
+    }
+
+    /// Check that this backup can be restored on this server, and that its content is
+    /// complete. This does not examine the entries themselves.
+    pub(super) fn verify_header(&self) -> Vec<RestoreIssue> {
+        let mut issues = Vec::with_capacity(0);
+
+        let version = self.version();
+        if version != Some(env!("KANIDM_PKG_SERIES")) {
+            issues.push(RestoreIssue::VersionMismatch {
+                found: version.map(str::to_string),
+            });
+        }
+
+        if let DbBackup::V6 {
+            repl_meta: DbReplMeta::V1 { ruv },
+            snapshot,
+            entries,
+            ..
+        } = &self.dbbak
+        {
+            if entries.len() as u64 != snapshot.entry_count {
+                issues.push(RestoreIssue::EntryCountMismatch {
+                    expected: snapshot.entry_count,
+                    found: entries.len(),
+                });
+            }
+
+            if ruv.last() != snapshot.ruv_max_cid.as_ref() {
+                issues.push(RestoreIssue::SnapshotMismatch);
+            }
+        }
+
+        issues
+    }
+
+    /// Check that this backup can be restored, and that every entry conforms to `schema`
+    /// and only refers to entries that are also in the backup.
+    pub fn verify(&self, schema: RestoreSchema) -> Result<RestoreReport, OperationError> {
+        let mut issues = self.verify_header();
+
+        let mut entries = Vec::with_capacity(self.entries().len());
+        for (index, db_e) in self.entries().iter().enumerate() {
+            // DbEntry can't be cloned, so decode it from the form that restore stores.
+            let entry = serde_json::to_vec(db_e)
+                .ok()
+                .and_then(|data| serde_json::from_slice::<DbEntry>(&data).ok())
+                .and_then(|db_e| EntrySealedCommitted::from_dbentry(db_e, index as u64 + 1));
+
+            match entry {
+                Some(entry) => entries.push(entry),
+                None => issues.push(RestoreIssue::InvalidEntry { index }),
+            }
+        }
+
+        let mut uuids = BTreeSet::new();
+        for entry in &entries {
+            if !uuids.insert(entry.get_uuid()) {
+                issues.push(RestoreIssue::DuplicateUuid(entry.get_uuid()));
+            }
+        }
+
+        let server_schema = current_schema()?;
+        let mut schema_txn = server_schema.write();
+
+        if schema == RestoreSchema::Bundled {
+            // Definitions the server already has are left as they are, as they may be
+            // older than this server's own.
+            for entry in entries.iter().filter(|e| {
+                e.attribute_equality(Attribute::Class, &EntryClass::AttributeType.into())
+            }) {
+                let result = SchemaAttribute::try_from(entry).and_then(|a| {
+                    if schema_txn.get_attributes().contains_key(&a.name) {
+                        Ok(())
+                    } else {
+                        schema_txn.upsert_attribute(a)
+                    }
+                });
+                if let Err(err) = result {
+                    issues.push(RestoreIssue::SchemaRejected {
+                        uuid: entry.get_uuid(),
+                        reason: format!("{:?}", err),
+                    });
+                }
+            }
+
+            for entry in entries
+                .iter()
+                .filter(|e| e.attribute_equality(Attribute::Class, &EntryClass::ClassType.into()))
+            {
+                let result = SchemaClass::try_from(entry).and_then(|c| {
+                    if schema_txn.get_classes().contains_key(&c.name) {
+                        Ok(())
+                    } else {
+                        schema_txn.upsert_class(c)
+                    }
+                });
+                if let Err(err) = result {
+                    issues.push(RestoreIssue::SchemaRejected {
+                        uuid: entry.get_uuid(),
+                        reason: format!("{:?}", err),
+                    });
+                }
+            }
+        }
+
+        let ref_types = schema_txn.get_reference_types();
+
+        for entry in &entries {
+            if let Err(error) = entry.validate_schema(&schema_txn) {
+                issues.push(RestoreIssue::SchemaViolation {
+                    uuid: entry.get_uuid(),
+                    error,
+                });
+            }
+
+            for rtype in ref_types.values() {
+                let Some(targets) = entry
+                    .get_ava_set(&rtype.name)
+                    .and_then(|vs| vs.as_ref_uuid_iter())
+                else {
+                    continue;
+                };
+
+                issues.extend(
+                    targets
+                        .filter(|target| !uuids.contains(target))
+                        .map(|target| RestoreIssue::DanglingReference {
+                            uuid: entry.get_uuid(),
+                            attr: rtype.name.clone(),
+                            target,
+                        }),
+                );
+            }
+        }
+
+        Ok(RestoreReport {
+            version: self.version().map(str::to_string),
+            entry_count: self.entries().len(),
+            issues,
+        })
+    }
+}
+
+/// The schema of this server. This must load the same definitions as
+/// `QueryServerWriteTransaction::reload_schema` at the target domain level.
+fn current_schema() -> Result<Schema, OperationError> {
+    let schema = Schema::new()?;
+    let mut schema_txn = schema.write();
+    schema_txn.extend_in_memory(
+        migration_data::dl15::phase_1_schema_attrs(),
+        migration_data::dl15::phase_2_schema_classes(),
+    )?;
+    schema_txn.commit()?;
+    Ok(schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::be::dbentry::DbEntryVers;
+    use crate::be::dbvalue::DbValueSetV2;
+    use crate::be::BackendTransaction;
+    use std::collections::BTreeMap;
+
+    async fn take_backup(server: &QueryServer) -> Vec<u8> {
+        let mut buf = Vec::new();
+        let mut server_txn = server.read().await.expect("txn");
+        server_txn
+            .get_be_txn()
+            .backup(&mut buf, BackupCompression::NoCompression)
+            .expect("backup failed");
+        buf
+    }
+
+    async fn create_group(server: &QueryServer) -> Uuid {
+        let mut server_txn = server.write(duration_from_epoch_now()).await.expect("txn");
+        let group_uuid = Uuid::new_v4();
+        let e_group = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Name, Value::new_iname("restore_group")),
+            (Attribute::Uuid, Value::Uuid(group_uuid)),
+            (Attribute::Member, Value::Refer(UUID_ADMIN))
+        );
+        server_txn
+            .internal_create(vec![e_group])
+            .expect("failed to create group");
+        server_txn.commit().expect("failed to commit");
+        group_uuid
+    }
+
+    fn entry_attrs(
+        archive: &mut BackupArchive,
+        uuid: Uuid,
+    ) -> &mut BTreeMap<Attribute, DbValueSetV2> {
+        let DbBackup::V6 { entries, .. } = &mut archive.dbbak else {
+            panic!("unexpected backup version");
+        };
+        entries
+            .iter_mut()
+            .find_map(|e| {
+                let DbEntryVers::V3 { attrs, .. } = &mut e.ent;
+                matches!(attrs.get(&Attribute::Uuid), Some(DbValueSetV2::Uuid(u)) if u.contains(&uuid))
+                    .then_some(attrs)
+            })
+            .expect("entry not found in backup")
+    }
+
+    #[qs_test]
+    async fn test_backup_verify_clean(server: &QueryServer) {
+        create_group(server).await;
+        let buf = take_backup(server).await;
+
+        let archive = BackupArchive::read(buf.as_slice(), BackupCompression::NoCompression)
+            .expect("failed to read backup");
+
+        for schema in [RestoreSchema::Bundled, RestoreSchema::Current] {
+            let report = archive.verify(schema).expect("failed to verify backup");
+            assert!(report.is_ok(), "{:?}", report.issues);
+            assert_eq!(report.version.as_deref(), Some(env!("KANIDM_PKG_SERIES")));
+            assert!(report.entry_count > 0);
+        }
+    }
+
+    #[qs_test]
+    async fn test_backup_verify_corrupted(server: &QueryServer) {
+        create_group(server).await;
+        let buf = take_backup(server).await;
+
+        // A truncated backup can't be read at all.
+        let truncated = &buf[..buf.len() / 2];
+        assert!(matches!(
+            BackupArchive::read(truncated, BackupCompression::NoCompression),
+            Err(OperationError::SerdeJsonError)
+        ));
+
+        // A backup missing entries is detected from its snapshot.
+        let mut archive = BackupArchive::read(buf.as_slice(), BackupCompression::NoCompression)
+            .expect("failed to read backup");
+        let DbBackup::V6 { entries, .. } = &mut archive.dbbak else {
+            panic!("unexpected backup version");
+        };
+        let found = entries.len() - 1;
+        entries.pop();
+
+        let report = archive
+            .verify(RestoreSchema::Bundled)
+            .expect("failed to verify backup");
+        assert!(report.issues.contains(&RestoreIssue::EntryCountMismatch {
+            expected: found as u64 + 1,
+            found,
+        }));
+    }
+
+    #[qs_test]
+    async fn test_backup_verify_schema_incompatible(server: &QueryServer) {
+        let group_uuid = create_group(server).await;
+        let buf = take_backup(server).await;
+
+        let mut archive = BackupArchive::read(buf.as_slice(), BackupCompression::NoCompression)
+            .expect("failed to read backup");
+
+        let missing_uuid = Uuid::new_v4();
+        let attrs = entry_attrs(&mut archive, group_uuid);
+        attrs.insert(
+            Attribute::from("restore_test_unknown"),
+            DbValueSetV2::Utf8(vec!["value".to_string()]),
+        );
+        attrs.insert(
+            Attribute::Member,
+            DbValueSetV2::Reference(vec![UUID_ADMIN, missing_uuid]),
+        );
+
+        let report = archive
+            .verify(RestoreSchema::Bundled)
+            .expect("failed to verify backup");
+
+        assert_eq!(report.issues.len(), 2, "{:?}", report.issues);
+        assert!(report.issues.iter().any(|issue| matches!(
+            issue,
+            RestoreIssue::SchemaViolation { uuid, .. } if *uuid == group_uuid
+        )));
+        assert!(report.issues.contains(&RestoreIssue::DanglingReference {
+            uuid: group_uuid,
+            attr: Attribute::Member,
+            target: missing_uuid,
+        }));
+    }
+}
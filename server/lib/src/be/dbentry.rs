@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use super::dbrepl::{DbEntryChangeState, DbReplMeta};
-use super::dbvalue::DbValueSetV2;
+use super::dbvalue::{DbCidV1, DbValueSetV2};
 use super::keystorage::{KeyHandle, KeyHandleId};
 use crate::prelude::entries::Attribute;
 
@@ -43,6 +43,16 @@ pub struct DbEntry {
 pub enum DbBackup {
     // Because of untagged, this has to be in order of newest
     // to oldest as untagged does a first-match when deserialising.
+    V6 {
+        version: String,
+        db_s_uuid: Uuid,
+        db_d_uuid: Uuid,
+        db_ts_max: Duration,
+        keyhandles: BTreeMap<KeyHandleId, KeyHandle>,
+        repl_meta: DbReplMeta,
+        snapshot: DbBackupSnapshotV1,
+        entries: Vec<DbEntry>,
+    },
     V5 {
         version: String,
         db_s_uuid: Uuid,
@@ -76,6 +86,16 @@ pub enum DbBackup {
     V1(Vec<DbEntry>),
 }
 
+/// The state of the database at the point that a backup was taken, allowing a restore
+/// to be checked for consistency.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DbBackupSnapshotV1 {
+    pub entry_count: u64,
+    /// The newest change in the replication update vector. This is `None` only if the
+    /// database had no changes.
+    pub ruv_max_cid: Option<DbCidV1>,
+}
+
 impl std::fmt::Debug for DbEntry {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match &self.ent {
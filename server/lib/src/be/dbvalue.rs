@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
+use std::net::IpAddr;
 use std::time::Duration;
 use time::OffsetDateTime;
 use url::Url;
@@ -482,6 +483,14 @@ pub struct DbValueAddressV1 {
     pub country: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct DbValueEmailAddressV1 {
+    #[serde(rename = "a")]
+    pub address: String,
+    #[serde(rename = "v")]
+    pub verified: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
 pub enum DbValueOauthClaimMapJoinV1 {
     #[serde(rename = "c")]
@@ -490,6 +499,8 @@ pub enum DbValueOauthClaimMapJoinV1 {
     SpaceSeparatedValue,
     #[serde(rename = "a")]
     JsonArray,
+    #[serde(rename = "o")]
+    Single,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -499,6 +510,8 @@ pub enum DbValueOauthClaimMap {
         name: String,
         #[serde(rename = "j")]
         join: DbValueOauthClaimMapJoinV1,
+        #[serde(rename = "t", default, skip_serializing_if = "Option::is_none")]
+        template: Option<String>,
         #[serde(rename = "d")]
         values: BTreeMap<Uuid, BTreeSet<String>>,
     },
@@ -650,6 +663,10 @@ pub enum DbValueSession {
         type_: DbValueAuthTypeV1,
         #[serde(rename = "x", default)]
         ext_metadata: DbValueSessionExtMetadataV1,
+        #[serde(rename = "a", default, skip_serializing_if = "Option::is_none")]
+        source_ip: Option<IpAddr>,
+        #[serde(rename = "g", default, skip_serializing_if = "Option::is_none")]
+        user_agent: Option<String>,
     },
 }
 
@@ -664,6 +681,24 @@ pub enum DbValueApiTokenScopeV1 {
     Synchronise,
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DbValueApiTokenOperationScopeV1 {
+    #[serde(rename = "pr")]
+    PersonRead,
+    #[serde(rename = "pw")]
+    PersonWrite,
+    #[serde(rename = "gr")]
+    GroupRead,
+    #[serde(rename = "gw")]
+    GroupWrite,
+    #[serde(rename = "sr")]
+    ServiceAccountRead,
+    #[serde(rename = "sw")]
+    ServiceAccountWrite,
+    #[serde(rename = "rr")]
+    RadiusRead,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub enum DbValueApiToken {
     V1 {
@@ -679,6 +714,8 @@ pub enum DbValueApiToken {
         issued_by: DbValueIdentityId,
         #[serde(rename = "s", default)]
         scope: DbValueApiTokenScopeV1,
+        #[serde(rename = "o", default, skip_serializing_if = "Vec::is_empty")]
+        operation_scopes: Vec<DbValueApiTokenOperationScopeV1>,
     },
 }
 
@@ -777,9 +814,37 @@ pub enum DbValueApplicationPassword {
         label: String,
         #[serde(rename = "p")]
         password: DbPasswordV1,
+        #[serde(rename = "c", default, skip_serializing_if = "Option::is_none")]
+        created: Option<u64>,
     },
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct DbValueCredentialLockoutV1 {
+    #[serde(rename = "u")]
+    pub refer: Uuid,
+    #[serde(rename = "t")]
+    pub unlock_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct DbValuePasskeyUsageV1 {
+    #[serde(rename = "u")]
+    pub refer: Uuid,
+    #[serde(rename = "t")]
+    pub last_used: u64,
+    #[serde(rename = "c")]
+    pub counter: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct DbValueMemberExpiryV1 {
+    #[serde(rename = "u")]
+    pub refer: Uuid,
+    #[serde(rename = "t")]
+    pub expire_at: u64,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub enum DbValueSetV2 {
     #[serde(rename = "U8")]
@@ -822,6 +887,8 @@ pub enum DbValueSetV2 {
     DateTime(Vec<String>),
     #[serde(rename = "EM")]
     EmailAddress(String, Vec<String>),
+    #[serde(rename = "EV")]
+    EmailAddressV2(String, Vec<DbValueEmailAddressV1>),
     #[serde(rename = "PN")]
     PhoneNumber(String, Vec<String>),
     #[serde(rename = "AD")]
@@ -886,6 +953,12 @@ pub enum DbValueSetV2 {
     Message(OutboundMessage),
     #[serde(rename = "S256")]
     Sha256(BTreeSet<Sha256Output>),
+    #[serde(rename = "CL")]
+    CredentialLockout(Vec<DbValueCredentialLockoutV1>),
+    #[serde(rename = "PU")]
+    PasskeyUsage(Vec<DbValuePasskeyUsageV1>),
+    #[serde(rename = "ME")]
+    MemberExpiry(Vec<DbValueMemberExpiryV1>),
 }
 
 impl DbValueSetV2 {
@@ -912,6 +985,7 @@ impl DbValueSetV2 {
             DbValueSetV2::NsUniqueId(set) => set.len(),
             DbValueSetV2::DateTime(set) => set.len(),
             DbValueSetV2::EmailAddress(_primary, set) => set.len(),
+            DbValueSetV2::EmailAddressV2(_primary, set) => set.len(),
             DbValueSetV2::PhoneNumber(_primary, set) => set.len(),
             DbValueSetV2::Address(set) => set.len(),
             DbValueSetV2::Url(set) => set.len(),
@@ -942,6 +1016,9 @@ impl DbValueSetV2 {
             DbValueSetV2::Certificate(set) => set.len(),
             DbValueSetV2::ApplicationPassword(set) => set.len(),
             DbValueSetV2::Sha256(set) => set.len(),
+            DbValueSetV2::CredentialLockout(set) => set.len(),
+            DbValueSetV2::PasskeyUsage(set) => set.len(),
+            DbValueSetV2::MemberExpiry(set) => set.len(),
             DbValueSetV2::Json(_) | DbValueSetV2::Message(_) => 1,
         }
     }
@@ -2,6 +2,7 @@ use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::convert::TryInto;
 use std::ops::DerefMut;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -10,7 +11,7 @@ use concread::cowcell::*;
 use hashbrown::HashMap;
 use idlset::v2::IDLBitRange;
 use idlset::AndNot;
-use kanidm_proto::internal::{ConsistencyError, OperationError};
+use kanidm_proto::internal::{BackendCacheStats, CacheStats, ConsistencyError, OperationError};
 use tracing::trace;
 use uuid::Uuid;
 
@@ -22,8 +23,10 @@ use crate::be::idxkey::{
     IdxSlope,
 };
 use crate::be::keystorage::{KeyHandle, KeyHandleId};
+use crate::be::vacuum::DbStorageStats;
 use crate::be::{BackendConfig, IdList, IdRawEntry};
 use crate::entry::{Entry, EntryCommitted, EntrySealed};
+use crate::metrics::{CacheMetrics, METRICS};
 use crate::prelude::*;
 use crate::value::{IndexType, Value};
 
@@ -55,6 +58,98 @@ enum NameCacheValue {
     S(Box<Value>),
 }
 
+/// Runtime adjustable limits and statistics of a backend cache.
+///
+/// An ARCache can't be resized once it is built, so instead the target limits how many
+/// items lookups may add to the cache. When the target is reduced below the number of
+/// items in the cache it is emptied at the next write commit, so that concurrent readers
+/// keep their existing view of it.
+#[derive(Debug)]
+struct CacheTuning {
+    metrics: &'static CacheMetrics,
+    capacity: usize,
+    target: AtomicUsize,
+    /// An upper bound of the number of items in the cache. This may over count as the
+    /// cache can discard items, but is reset when the cache is emptied.
+    included: AtomicUsize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    include_all_fallbacks: AtomicU64,
+}
+
+impl CacheTuning {
+    fn new(capacity: usize, metrics: &'static CacheMetrics) -> Self {
+        CacheTuning {
+            metrics,
+            capacity,
+            target: AtomicUsize::new(capacity),
+            included: AtomicUsize::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            include_all_fallbacks: AtomicU64::new(0),
+        }
+    }
+
+    fn record(&self, hit: bool) {
+        self.metrics.record(hit);
+        if hit {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn record_include_all(&self) {
+        self.include_all_fallbacks.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Reserve space for an item that a lookup wants to add to the cache. This returns
+    /// false if the cache is at its target and the item should not be added.
+    fn try_include(&self) -> bool {
+        let target = self.target.load(Ordering::Relaxed);
+        self.included
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+                (n < target).then_some(n + 1)
+            })
+            .is_ok()
+    }
+
+    /// Record items that a write transaction added to the cache. These can't be refused
+    /// as the cache must reflect the changes of the transaction.
+    fn record_written(&self, count: usize) {
+        self.included.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Apply a new target, limited to the capacity of the cache. Returns true if the cache
+    /// must be emptied to honour the target.
+    fn apply_target(&self, target: usize) -> bool {
+        let target = target.min(self.capacity);
+        self.target.store(target, Ordering::Relaxed);
+
+        let included = self.included.load(Ordering::Relaxed);
+        if included > target {
+            self.included.store(0, Ordering::Relaxed);
+            self.evictions.fetch_add(included as u64, Ordering::Relaxed);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            capacity: self.capacity as u64,
+            target: self.target.load(Ordering::Relaxed) as u64,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            include_all_fallbacks: self.include_all_fallbacks.load(Ordering::Relaxed),
+        }
+    }
+}
+
 pub struct IdlArcSqlite {
     db: IdlSqlite,
     entry_cache: ARCache<u64, Arc<EntrySealedCommitted>>,
@@ -63,6 +158,9 @@ pub struct IdlArcSqlite {
 
     idx_exists_cache: ARCache<IdxNameKey, bool>,
 
+    entry_tuning: CacheTuning,
+    idl_tuning: CacheTuning,
+
     op_ts_max: CowCell<Option<Duration>>,
     allids: CowCell<IDLBitRange>,
     maxid: CowCell<u64>,
@@ -77,6 +175,9 @@ pub struct IdlArcSqliteReadTransaction<'a> {
 
     idx_exists_cache: ARCacheReadTxn<'a, IdxNameKey, bool, ()>,
     allids: CowCellReadTxn<IDLBitRange>,
+
+    entry_tuning: &'a CacheTuning,
+    idl_tuning: &'a CacheTuning,
 }
 
 pub struct IdlArcSqliteWriteTransaction<'a> {
@@ -91,6 +192,12 @@ pub struct IdlArcSqliteWriteTransaction<'a> {
     allids: CowCellWriteTxn<'a, IDLBitRange>,
     maxid: CowCellWriteTxn<'a, u64>,
     pub(super) keyhandles: CowCellWriteTxn<'a, HashMap<KeyHandleId, KeyHandle>>,
+
+    entry_tuning: &'a CacheTuning,
+    idl_tuning: &'a CacheTuning,
+    /// New cache targets to apply when this transaction commits.
+    entry_target: Option<usize>,
+    idl_target: Option<usize>,
 }
 
 macro_rules! get_identry {
@@ -108,8 +215,14 @@ macro_rules! get_identry {
                     // For all the id's in idl.
                     // is it in the cache?
                     match $self.entry_cache.get(&i) {
-                        Some(eref) => result.push(eref.clone()),
-                        None => unsafe { nidl.push_id(i) },
+                        Some(eref) => {
+                            $self.entry_tuning.record(true);
+                            result.push(eref.clone())
+                        }
+                        None => {
+                            $self.entry_tuning.record(false);
+                            unsafe { nidl.push_id(i) }
+                        }
                     }
                 });
 
@@ -119,7 +232,9 @@ macro_rules! get_identry {
                     // Clone everything from db_result into the cache.
                     if $is_read_op {
                         db_result.iter().for_each(|e| {
-                            $self.entry_cache.insert(e.get_id(), e.clone());
+                            if $self.entry_tuning.try_include() {
+                                $self.entry_cache.insert(e.get_id(), e.clone());
+                            }
                         });
                     }
                     // Merge the two vecs
@@ -129,14 +244,21 @@ macro_rules! get_identry {
             IdList::AllIds => {
                 // VERY similar to above, but we skip adding the entries to the cache
                 // on miss to prevent scan/invalidation attacks.
+                $self.entry_tuning.record_include_all();
                 let idli = (*$self.allids).clone();
                 let mut nidl = IDLBitRange::new();
 
                 (&idli)
                     .into_iter()
                     .for_each(|i| match $self.entry_cache.get(&i) {
-                        Some(eref) => result.push(eref.clone()),
-                        None => unsafe { nidl.push_id(i) },
+                        Some(eref) => {
+                            $self.entry_tuning.record(true);
+                            result.push(eref.clone())
+                        }
+                        None => {
+                            $self.entry_tuning.record(false);
+                            unsafe { nidl.push_id(i) }
+                        }
                     });
 
                 if !nidl.is_empty() {
@@ -188,6 +310,7 @@ macro_rules! get_idl {
             k: $idx_key,
         };
         let cache_r = $self.idl_cache.get(&cache_key as &dyn IdlCacheKeyToRef);
+        $self.idl_tuning.record(cache_r.is_some());
         // If hit, continue.
         if let Some(ref data) = cache_r {
             trace!(
@@ -206,6 +329,7 @@ macro_rules! get_idl {
         let idx_r = $self.idx_exists_cache.get(&idx_key);
         if idx_r == Some(&false) {
             // The idx does not exist - bail early.
+            $self.idl_tuning.record_include_all();
             return Ok(None)
         }
 
@@ -227,7 +351,9 @@ macro_rules! get_idl {
                 i: $itype.clone(),
                 k: $idx_key.into(),
             };
-            $self.idl_cache.insert(ncache_key, Box::new(idl.clone()))
+            if $self.idl_tuning.try_include() {
+                $self.idl_cache.insert(ncache_key, Box::new(idl.clone()))
+            }
         } else {
             // The DB was unable to return this idx because table backing the
             // idx does not exist. We should cache this to prevent repeat hits
@@ -236,6 +362,7 @@ macro_rules! get_idl {
             //
             // NOTE: If the db idx misses it returns Some(empty_set), so this
             // only caches missing index tables.
+            $self.idl_tuning.record_include_all();
             $self.idx_exists_cache.insert(idx_key, false)
         };
         Ok(db_r)
@@ -249,6 +376,7 @@ macro_rules! name2uuid {
     ) => {{
         let cache_key = NameCacheKey::Name2Uuid($name.to_string());
         let cache_r = $self.name_cache.get(&cache_key);
+        METRICS.name_cache.record(cache_r.is_some());
         if let Some(NameCacheValue::U(uuid)) = cache_r {
             trace!(?uuid, "Got cached name2uuid");
             return Ok(Some(uuid.clone()));
@@ -273,6 +401,7 @@ macro_rules! externalid2uuid {
     ) => {{
         let cache_key = NameCacheKey::ExternalId2Uuid($name.to_string());
         let cache_r = $self.name_cache.get(&cache_key);
+        METRICS.name_cache.record(cache_r.is_some());
         if let Some(NameCacheValue::U(uuid)) = cache_r {
             trace!(?uuid, "Got cached externalid2uuid");
             return Ok(Some(uuid.clone()));
@@ -297,6 +426,7 @@ macro_rules! uuid2spn {
     ) => {{
         let cache_key = NameCacheKey::Uuid2Spn($uuid);
         let cache_r = $self.name_cache.get(&cache_key);
+        METRICS.name_cache.record(cache_r.is_some());
         if let Some(NameCacheValue::S(ref spn)) = cache_r {
             trace!(?spn, "Got cached uuid2spn");
             return Ok(Some(spn.as_ref().clone()));
@@ -321,6 +451,7 @@ macro_rules! uuid2rdn {
     ) => {{
         let cache_key = NameCacheKey::Uuid2Rdn($uuid);
         let cache_r = $self.name_cache.get(&cache_key);
+        METRICS.name_cache.record(cache_r.is_some());
         if let Some(NameCacheValue::R(ref rdn)) = cache_r {
             return Ok(Some(rdn.clone()));
         } else {
@@ -375,6 +506,9 @@ pub trait IdlArcSqliteTransaction {
 
     fn get_identry_raw(&self, idl: &IdList) -> Result<Vec<IdRawEntry>, OperationError>;
 
+    /// The ids of all entries visible to this transaction.
+    fn get_allids(&self) -> IDLBitRange;
+
     // fn exists_idx(&mut self, attr: &str, itype: IndexType) -> Result<bool, OperationError>;
 
     fn get_idl(
@@ -384,6 +518,14 @@ pub trait IdlArcSqliteTransaction {
         idx_key: &str,
     ) -> Result<Option<IDLBitRange>, OperationError>;
 
+    /// The ids of entries with any key of the index less than `idx_key`.
+    fn get_idl_lt(
+        &mut self,
+        attr: &Attribute,
+        itype: IndexType,
+        idx_key: &str,
+    ) -> Result<Option<IDLBitRange>, OperationError>;
+
     fn get_db_s_uuid(&self) -> Result<Option<Uuid>, OperationError>;
 
     fn get_db_d_uuid(&self) -> Result<Option<Uuid>, OperationError>;
@@ -396,6 +538,8 @@ pub trait IdlArcSqliteTransaction {
 
     fn is_dirty(&self) -> bool;
 
+    fn cache_stats(&self) -> BackendCacheStats;
+
     fn name2uuid(&mut self, name: &str) -> Result<Option<Uuid>, OperationError>;
 
     fn externalid2uuid(&mut self, name: &str) -> Result<Option<Uuid>, OperationError>;
@@ -430,6 +574,10 @@ impl IdlArcSqliteTransaction for IdlArcSqliteReadTransaction<'_> {
         get_identry_raw!(self, idl)
     }
 
+    fn get_allids(&self) -> IDLBitRange {
+        (*self.allids).clone()
+    }
+
     // fn exists_idx(&mut self, attr: &str, itype: IndexType) -> Result<bool, OperationError> {
     //     exists_idx!(self, attr, itype)
     // }
@@ -444,6 +592,21 @@ impl IdlArcSqliteTransaction for IdlArcSqliteReadTransaction<'_> {
         get_idl!(self, attr, itype, idx_key)
     }
 
+    fn get_idl_lt(
+        &mut self,
+        attr: &Attribute,
+        itype: IndexType,
+        idx_key: &str,
+    ) -> Result<Option<IDLBitRange>, OperationError> {
+        // The cache only holds individual keys, so a range must always come from the db.
+        self.db.get_idl_lt(attr, itype, idx_key).map(|maybe_keys| {
+            maybe_keys.map(|keys| {
+                keys.into_iter()
+                    .fold(IDLBitRange::new(), |acc, (_, idl)| acc | idl)
+            })
+        })
+    }
+
     fn get_db_s_uuid(&self) -> Result<Option<Uuid>, OperationError> {
         self.db.get_db_s_uuid()
     }
@@ -468,6 +631,13 @@ impl IdlArcSqliteTransaction for IdlArcSqliteReadTransaction<'_> {
         false
     }
 
+    fn cache_stats(&self) -> BackendCacheStats {
+        BackendCacheStats {
+            entry: self.entry_tuning.stats(),
+            idl: self.idl_tuning.stats(),
+        }
+    }
+
     fn name2uuid(&mut self, name: &str) -> Result<Option<Uuid>, OperationError> {
         name2uuid!(self, name)
     }
@@ -522,7 +692,45 @@ impl IdlArcSqliteTransaction for IdlArcSqliteWriteTransaction<'_> {
     }
 
     fn get_identry_raw(&self, idl: &IdList) -> Result<Vec<IdRawEntry>, OperationError> {
-        get_identry_raw!(self, idl)
+        // Entries changed in this transaction are only written to the db on commit, so
+        // they must be taken from the cache instead.
+        let dirty: BTreeMap<u64, Option<&Arc<EntrySealedCommitted>>> = self
+            .entry_cache
+            .iter_dirty()
+            .filter(|(id, _)| match idl {
+                IdList::AllIds => true,
+                IdList::Partial(idli) | IdList::PartialThreshold(idli) | IdList::Indexed(idli) => {
+                    idli.contains(**id)
+                }
+            })
+            .map(|(id, e)| (*id, e))
+            .collect();
+
+        if dirty.is_empty() {
+            return get_identry_raw!(self, idl);
+        }
+
+        let mut raw_entries: Vec<IdRawEntry> = get_identry_raw!(self, idl)?
+            .into_iter()
+            .filter(|raw| !dirty.contains_key(&raw.id))
+            .collect();
+
+        for (id, e) in dirty {
+            if let Some(e) = e {
+                let data = serde_json::to_vec(&e.to_dbentry()).map_err(|e| {
+                    admin_error!(?e, "serde error");
+                    OperationError::SerdeJsonError
+                })?;
+                raw_entries.push(IdRawEntry { id, data });
+            }
+        }
+        raw_entries.sort_unstable_by_key(|raw| raw.id);
+
+        Ok(raw_entries)
+    }
+
+    fn get_allids(&self) -> IDLBitRange {
+        (*self.allids).clone()
     }
 
     // fn exists_idx(&mut self, attr: &str, itype: IndexType) -> Result<bool, OperationError> {
@@ -539,6 +747,38 @@ impl IdlArcSqliteTransaction for IdlArcSqliteWriteTransaction<'_> {
         get_idl!(self, attr, itype, idx_key)
     }
 
+    fn get_idl_lt(
+        &mut self,
+        attr: &Attribute,
+        itype: IndexType,
+        idx_key: &str,
+    ) -> Result<Option<IDLBitRange>, OperationError> {
+        let Some(keys) = self.db.get_idl_lt(attr, itype, idx_key)? else {
+            return Ok(None);
+        };
+
+        // Keys changed by this transaction are only in the cache until we commit, so
+        // they take precedence over the content of the db.
+        let mut keys: BTreeMap<String, IDLBitRange> = keys.into_iter().collect();
+        self.idl_cache.iter_dirty().for_each(|(k, maybe_idl)| {
+            if k.a == *attr && k.i == itype && k.k.as_str() < idx_key {
+                match maybe_idl {
+                    Some(idl) => {
+                        keys.insert(k.k.clone(), idl.as_ref().clone());
+                    }
+                    None => {
+                        keys.remove(&k.k);
+                    }
+                }
+            }
+        });
+
+        Ok(Some(
+            keys.into_values()
+                .fold(IDLBitRange::new(), |acc, idl| acc | idl),
+        ))
+    }
+
     fn get_db_s_uuid(&self) -> Result<Option<Uuid>, OperationError> {
         self.db.get_db_s_uuid()
     }
@@ -566,6 +806,13 @@ impl IdlArcSqliteTransaction for IdlArcSqliteWriteTransaction<'_> {
         self.entry_cache.is_dirty()
     }
 
+    fn cache_stats(&self) -> BackendCacheStats {
+        BackendCacheStats {
+            entry: self.entry_tuning.stats(),
+            idl: self.idl_tuning.stats(),
+        }
+    }
+
     fn name2uuid(&mut self, name: &str) -> Result<Option<Uuid>, OperationError> {
         name2uuid!(self, name)
     }
@@ -643,13 +890,23 @@ impl IdlArcSqliteWriteTransaction<'_> {
             allids,
             maxid,
             keyhandles,
+            entry_tuning,
+            idl_tuning,
+            entry_target,
+            idl_target,
         } = self;
 
+        let mut entries_written = 0;
+        let mut idls_written = 0;
+
         // Write any dirty items to the disk.
         entry_cache
             .iter_mut_mark_clean()
             .try_for_each(|(k, v)| match v {
-                Some(e) => db.write_identry(e),
+                Some(e) => {
+                    entries_written += 1;
+                    db.write_identry(e)
+                }
                 None => db.delete_identry(*k),
             })
             .map_err(|e| {
@@ -661,7 +918,10 @@ impl IdlArcSqliteWriteTransaction<'_> {
             .iter_mut_mark_clean()
             .try_for_each(|(k, v)| {
                 match v {
-                    Some(idl) => db.write_idl(&k.a, k.i, k.k.as_str(), idl),
+                    Some(idl) => {
+                        idls_written += 1;
+                        db.write_idl(&k.a, k.i, k.k.as_str(), idl)
+                    }
                     #[allow(clippy::unreachable)]
                     None => {
                         // Due to how we remove items, we always write an empty idl
@@ -710,6 +970,18 @@ impl IdlArcSqliteWriteTransaction<'_> {
         db.commit()?;
 
         // Can no longer fail from this point.
+        entry_tuning.record_written(entries_written);
+        idl_tuning.record_written(idls_written);
+
+        // Readers keep their own view of the caches, so they are unaffected by emptying
+        // them here to honour a reduced target.
+        if entry_target.is_some_and(|target| entry_tuning.apply_target(target)) {
+            entry_cache.clear();
+        }
+        if idl_target.is_some_and(|target| idl_tuning.apply_target(target)) {
+            idl_cache.clear();
+        }
+
         op_ts_max.commit();
         name_cache.commit();
         idx_exists_cache.commit();
@@ -727,6 +999,13 @@ impl IdlArcSqliteWriteTransaction<'_> {
         self.db.get_db_ruv()
     }
 
+    /// Set new targets for the number of items in the entry and idl caches, which are
+    /// applied when this transaction commits. A target of `None` is left unchanged.
+    pub fn set_cache_targets(&mut self, entry: Option<usize>, idl: Option<usize>) {
+        self.entry_target = entry.or(self.entry_target);
+        self.idl_target = idl.or(self.idl_target);
+    }
+
     pub fn write_db_ruv<I, J>(&mut self, added: I, removed: J) -> Result<(), OperationError>
     where
         I: Iterator<Item = Cid>,
@@ -1183,6 +1462,22 @@ impl IdlArcSqliteWriteTransaction<'_> {
         Ok(())
     }
 
+    /// Remove an index table that is no longer used.
+    pub fn drop_idx_table(&mut self, idx_table: &str) -> Result<(), OperationError> {
+        self.db.drop_idx_table(idx_table).map(|()| {
+            self.idl_cache.clear();
+            self.idx_exists_cache.clear();
+        })
+    }
+
+    pub fn get_storage_stats(&self) -> Result<DbStorageStats, OperationError> {
+        self.db.get_storage_stats()
+    }
+
+    pub fn release_free_pages(&self, pages: u64) -> Result<(), OperationError> {
+        self.db.release_free_pages(pages)
+    }
+
     /// ⚠️  - This function will destroy all indexes in the database.
     ///
     /// It should only be called internally by the backend in limited and
@@ -1341,12 +1636,17 @@ impl IdlArcSqlite {
 
         let op_ts_max = CowCell::new(None);
 
+        let entry_tuning = CacheTuning::new(cache_size, &METRICS.entry_cache);
+        let idl_tuning = CacheTuning::new(cache_size * DEFAULT_IDL_CACHE_RATIO, &METRICS.idl_cache);
+
         Ok(IdlArcSqlite {
             db,
             entry_cache,
             idl_cache,
             name_cache,
             idx_exists_cache,
+            entry_tuning,
+            idl_tuning,
             op_ts_max,
             allids,
             maxid,
@@ -1376,6 +1676,8 @@ impl IdlArcSqlite {
             name_cache: name_cache_read,
             idx_exists_cache: idx_exists_cache_read,
             allids: allids_read,
+            entry_tuning: &self.entry_tuning,
+            idl_tuning: &self.idl_tuning,
         })
     }
 
@@ -1401,6 +1703,10 @@ impl IdlArcSqlite {
             allids: allids_write,
             maxid: maxid_write,
             keyhandles: keyhandles_write,
+            entry_tuning: &self.entry_tuning,
+            idl_tuning: &self.idl_tuning,
+            entry_target: None,
+            idl_target: None,
         })
     }
 
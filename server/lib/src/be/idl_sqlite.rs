@@ -1,6 +1,7 @@
 use super::keystorage::{KeyHandle, KeyHandleId};
 use crate::be::dbentry::DbIdentSpn;
 use crate::be::dbvalue::DbCidV1;
+use crate::be::vacuum::DbStorageStats;
 use crate::be::{BackendConfig, IdList, IdRawEntry, IdxKey, IdxSlope};
 use crate::entry::{Entry, EntryCommitted, EntrySealed};
 use crate::prelude::*;
@@ -265,6 +266,50 @@ pub(crate) trait IdlSqliteTransaction {
         Ok(Some(idl))
     }
 
+    /// The keys of an index that are less than `idx_key`, and the idl of each key.
+    #[instrument(level = "trace", skip_all)]
+    fn get_idl_lt(
+        &self,
+        attr: &Attribute,
+        itype: IndexType,
+        idx_key: &str,
+    ) -> Result<Option<Vec<(String, IDLBitRange)>>, OperationError> {
+        if !(self.exists_idx(attr, itype)?) {
+            debug!(
+                "IdlSqliteTransaction: Index {:?} {:?} not found",
+                itype, attr
+            );
+            return Ok(None);
+        }
+
+        // Keys are compared bytewise, so this is a range scan over the primary key.
+        let query = format!(
+            "SELECT key, idl FROM {}.idx_{}_{} WHERE key < :idx_key",
+            self.get_db_name(),
+            itype.as_idx_str(),
+            attr.as_str()
+        );
+        let mut stmt = self.get_conn()?.prepare(&query).map_err(sqlite_error)?;
+        let idx_iter = stmt
+            .query_map(&[(":idx_key", &idx_key)], |row| {
+                Ok(KeyIdl {
+                    key: row.get(0)?,
+                    data: row.get(1)?,
+                })
+            })
+            .map_err(sqlite_error)?;
+        idx_iter
+            .map(|v| {
+                v.map_err(sqlite_error).and_then(|KeyIdl { key, data }| {
+                    serde_json::from_slice(data.as_slice())
+                        .map_err(serde_json_error)
+                        .map(|idl| (key, idl))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some)
+    }
+
     fn name2uuid(&mut self, name: &str) -> Result<Option<Uuid>, OperationError> {
         // The table exists - lets now get the actual index itself.
         let mut stmt = self
@@ -1157,6 +1202,51 @@ impl IdlSqliteWriteTransaction {
             .map_err(sqlite_error)
     }
 
+    /// Remove a single index table and its content.
+    #[instrument(level = "debug", skip(self))]
+    pub fn drop_idx_table(&self, idx_table: &str) -> Result<(), OperationError> {
+        self.get_conn()?
+            .execute(
+                &format!("DROP TABLE IF EXISTS {}.{}", self.get_db_name(), idx_table),
+                [],
+            )
+            .map(|_| ())
+            .map_err(sqlite_error)
+    }
+
+    pub fn get_storage_stats(&self) -> Result<DbStorageStats, OperationError> {
+        let conn = self.get_conn()?;
+        let pragma = |name: &str| -> Result<u64, OperationError> {
+            conn.query_row(
+                &format!("PRAGMA {}.{}", self.get_db_name(), name),
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .map(|v| v as u64)
+            .map_err(sqlite_error)
+        };
+
+        Ok(DbStorageStats {
+            page_size: pragma("page_size")?,
+            page_count: pragma("page_count")?,
+            freelist_count: pragma("freelist_count")?,
+            // 2 is incremental, 1 is full and 0 is none.
+            incremental_vacuum: pragma("auto_vacuum")? == 2,
+        })
+    }
+
+    /// Return up to `pages` free pages of the database to the filesystem. This only has
+    /// an effect if the database was created (or vacuumed) with incremental auto vacuum.
+    pub fn release_free_pages(&self, pages: u64) -> Result<(), OperationError> {
+        self.get_conn()?
+            .execute_batch(&format!(
+                "PRAGMA {}.incremental_vacuum({})",
+                self.get_db_name(),
+                pages
+            ))
+            .map_err(sqlite_error)
+    }
+
     /// ⚠️  - This function will destroy all indexes in the database.
     ///
     /// It should only be called internally by the backend in limited and
@@ -1701,6 +1791,10 @@ impl IdlSqlite {
         let checkpoint_pages = cfg.fstype.checkpoint_pages();
 
         // Initial setup routines.
+        //
+        // Incremental auto vacuum is only applied by sqlite to a database that has no tables
+        // yet, so this only affects new databases. Existing databases are converted the next
+        // time they are vacuumed offline, below.
         {
             let vconn = Connection::open_with_flags(&cfg.path, flags).map_err(sqlite_error)?;
 
@@ -1708,6 +1802,7 @@ impl IdlSqlite {
                 .execute_batch(
                     format!(
                         "PRAGMA page_size={fs_page_size};
+                         PRAGMA auto_vacuum=INCREMENTAL;
                          PRAGMA cache_size={cache_pages};
                          PRAGMA journal_mode=WAL;
                          PRAGMA wal_autocheckpoint={checkpoint_pages};
@@ -1761,6 +1856,15 @@ impl IdlSqlite {
                     OperationError::SqliteError
                 })?;
 
+            // Changing to incremental auto vacuum only takes effect on an existing database
+            // after a vacuum, and allows free pages to be released while the server runs.
+            vconn
+                .pragma_update(None, "auto_vacuum", "INCREMENTAL")
+                .map_err(|e| {
+                    admin_error!(?e, "rusqlite auto_vacuum update error");
+                    OperationError::SqliteError
+                })?;
+
             vconn.execute_batch("VACUUM").map_err(|e| {
                 admin_error!(?e, "rusqlite vacuum error");
                 OperationError::SqliteError
@@ -4,7 +4,7 @@
 //! is to persist content safely to disk, load that content, and execute queries
 //! utilising indexes in the most effective way possible.
 
-use crate::be::dbentry::{DbBackup, DbEntry};
+use crate::be::dbentry::{DbBackup, DbBackupSnapshotV1, DbEntry};
 use crate::be::dbrepl::DbReplMeta;
 use crate::entry::Entry;
 use crate::filter::{Filter, FilterPlan, FilterResolved, FilterValidResolved};
@@ -22,7 +22,7 @@ use hashbrown::{HashMap, HashSet};
 use idlset::v2::IDLBitRange;
 use idlset::AndNot;
 use kanidm_proto::backup::BackupCompression;
-use kanidm_proto::internal::{ConsistencyError, OperationError};
+use kanidm_proto::internal::{BackendCacheStats, ConsistencyError, OperationError};
 use std::collections::BTreeMap;
 use std::io::prelude::*;
 use std::ops::DerefMut;
@@ -35,6 +35,7 @@ use uuid::Uuid;
 use flate2::write::GzEncoder;
 use flate2::Compression;
 
+mod backup;
 pub(crate) mod dbentry;
 pub(crate) mod dbrepl;
 pub(crate) mod dbvalue;
@@ -43,8 +44,11 @@ mod idl_arc_sqlite;
 mod idl_sqlite;
 pub(crate) mod idxkey;
 pub(crate) mod keystorage;
+mod vacuum;
 
+pub use self::backup::{BackupArchive, RestoreIssue, RestoreReport, RestoreSchema};
 pub(crate) use self::idxkey::{IdxKey, IdxKeyRef, IdxKeyToRef, IdxSlope};
+pub use self::vacuum::{DbStorageStats, Vacuum, VacuumReport};
 use crate::be::idl_arc_sqlite::{
     IdlArcSqlite, IdlArcSqliteReadTransaction, IdlArcSqliteTransaction,
     IdlArcSqliteWriteTransaction,
@@ -56,6 +60,9 @@ const FILTER_SEARCH_TEST_THRESHOLD: usize = 0;
 const FILTER_EXISTS_TEST_THRESHOLD: usize = 0;
 const FILTER_SUBSTR_TEST_THRESHOLD: usize = 4;
 
+/// The number of entries read from the database at a time while writing a backup.
+const BACKUP_CHUNK_SIZE: usize = 256;
+
 #[derive(Debug, Clone)]
 /// Limits on the resources a single event can consume. These are defined per-event
 /// as they are derived from the userAuthToken based on that individual session
@@ -287,12 +294,25 @@ pub trait BackendTransaction {
                     (IdList::AllIds, FilterPlan::PresUnindexed(attr.clone()))
                 }
             }
-            FilterResolved::LessThan(attr, _subvalue, idx) => {
-                if idx.is_some() {
-                    // TODO: Temporary but we get the PRESENCE index for Ordering operations to
-                    // reduce the amount of entries we need to filter in memory. In future we need
-                    // a true ordering index, but that's a large block of work on it's own. For now
-                    // this already helps a lot for in memory processing.
+            FilterResolved::LessThan(attr, subvalue, idx) => {
+                if let (Some(_), Some(idx_key)) = (idx, subvalue.get_idx_ord_key()) {
+                    // Range scan the ordering index. Some syntaxes require all values to
+                    // be less for the term to match, where the index finds entries with any
+                    // value that is less, so this is still filtered in memory.
+                    match self
+                        .get_idlayer()
+                        .get_idl_lt(attr, IndexType::Ordering, &idx_key)?
+                    {
+                        Some(idl) => (
+                            IdList::Partial(idl),
+                            FilterPlan::LessThanIndexed(attr.clone()),
+                        ),
+                        None => (IdList::AllIds, FilterPlan::LessThanCorrupt(attr.clone())),
+                    }
+                } else if idx.is_some() {
+                    // Not all orderable syntaxes generate ordering keys yet, so we use the
+                    // PRESENCE index to reduce the amount of entries we need to filter in
+                    // memory.
                     match self.get_idlayer().get_idl(attr, IndexType::Presence, "_")? {
                         Some(idl) => (
                             IdList::Partial(idl),
@@ -823,6 +843,93 @@ pub trait BackendTransaction {
         } // end match idl
     }
 
+    /// Search for a single page of the entries that match a filter. Entries are returned in
+    /// the order they were added to the backend, starting after the entry with the id `after`.
+    /// At most `limit` entries are returned, along with the id of the last entry of the page if
+    /// more entries remain to be read.
+    ///
+    /// Since new entries are always added with a greater id, entries created while the pages
+    /// are being read are never returned twice, and never cause an entry to be skipped.
+    fn search_page(
+        &mut self,
+        erl: &Limits,
+        filt: &Filter<FilterValidResolved>,
+        after: u64,
+        limit: usize,
+    ) -> Result<(Vec<Arc<EntrySealedCommitted>>, Option<u64>), OperationError> {
+        if limit == 0 || limit > erl.search_max_results {
+            admin_error!(
+                ?limit,
+                "page size is not within search_max_results allowed by resource limits"
+            );
+            return Err(OperationError::ResourceLimit);
+        }
+
+        trace!(filter_optimised = ?filt);
+
+        let (idl, fplan) = trace_span!("be::search_page -> filter2idl")
+            .in_scope(|| self.filter2idl(filt.to_inner(), FILTER_SEARCH_TEST_THRESHOLD))?;
+
+        debug!(search_filter_executed_plan = %fplan);
+
+        let mut entries = match &idl {
+            IdList::Indexed(idl_br) => {
+                // The index fully resolved, so only the entries of this page need to be
+                // loaded. We take one extra to know if any entries remain.
+                let page_idl = IDLBitRange::from_iter(
+                    idl_br.into_iter().filter(|id| *id > after).take(limit + 1),
+                );
+
+                let mut entries = self
+                    .get_idlayer()
+                    .get_identry(&IdList::Indexed(page_idl))
+                    .map_err(|e| {
+                        admin_error!(?e, "get_identry failed");
+                        e
+                    })?;
+                entries.sort_unstable_by_key(|e| e.get_id());
+                entries
+            }
+            _ => {
+                match &idl {
+                    IdList::AllIds if !erl.unindexed_allow => {
+                        admin_error!(
+                            "filter (search) is fully unindexed, and not allowed by resource limits"
+                        );
+                        return Err(OperationError::ResourceLimit);
+                    }
+                    IdList::Partial(idl_br)
+                        if !idl_br.below_threshold(erl.search_max_filter_test) =>
+                    {
+                        admin_error!("filter (search) is partial indexed and greater than search_max_filter_test allowed by resource limits");
+                        return Err(OperationError::ResourceLimit);
+                    }
+                    _ => {}
+                }
+
+                let mut entries = self.get_idlayer().get_identry(&idl).map_err(|e| {
+                    admin_error!(?e, "get_identry failed");
+                    e
+                })?;
+                entries.sort_unstable_by_key(|e| e.get_id());
+                entries
+                    .into_iter()
+                    .filter(|e| e.get_id() > after && e.entry_match_no_index(filt))
+                    .take(limit + 1)
+                    .collect()
+            }
+        };
+
+        let next = if entries.len() > limit {
+            entries.truncate(limit);
+            entries.last().map(|e| e.get_id())
+        } else {
+            None
+        };
+
+        Ok((entries, next))
+    }
+
     fn retrieve_range(
         &mut self,
         ranges: &BTreeMap<Uuid, ReplCidRange>,
@@ -855,6 +962,11 @@ pub trait BackendTransaction {
         self.get_idlayer().verify()
     }
 
+    /// The hit rates and limits of the entry and idl caches.
+    fn cache_stats(&mut self) -> BackendCacheStats {
+        self.get_idlayer().cache_stats()
+    }
+
     fn verify_entry_index(&mut self, e: &EntrySealedCommitted) -> Result<(), ConsistencyError> {
         // First, check our references in name2uuid, uuid2spn and uuid2rdn
         if e.mask_recycled_ts().is_some() {
@@ -958,86 +1070,54 @@ pub trait BackendTransaction {
         self.get_ruv().verify(&entries, results);
     }
 
+    /// Write a backup of the database as seen by this transaction to `output`. When this is
+    /// a read transaction writes may continue while the backup is taken, and they will not
+    /// be included in it. Entries are read and written in chunks so that the memory used
+    /// does not depend on the size of the database. Indexes are not included, as they are
+    /// rebuilt on restore.
+    ///
+    /// Returns the newest change included in the backup, if any.
     fn backup<OUT>(
         &mut self,
-        mut output: OUT,
+        output: OUT,
         compression: BackupCompression,
-    ) -> Result<(), OperationError>
+    ) -> Result<Option<Cid>, OperationError>
     where
         OUT: std::io::Write,
     {
-        let repl_meta = self.get_ruv().to_db_backup_ruv();
-
-        // load all entries into RAM, may need to change this later
-        // if the size of the database compared to RAM is an issue
-        let idl = IdList::AllIds;
-        let idlayer = self.get_idlayer();
-        let raw_entries: Vec<IdRawEntry> = idlayer.get_identry_raw(&idl)?;
-
-        let entries: Result<Vec<DbEntry>, _> = raw_entries
-            .iter()
-            .map(|id_ent| {
-                serde_json::from_slice(id_ent.data.as_slice())
-                    .map_err(|_| OperationError::SerdeJsonError) // log?
-            })
-            .collect();
-
-        let entries = entries?;
-
-        let db_s_uuid = idlayer
-            .get_db_s_uuid()
-            .and_then(|u| u.ok_or(OperationError::InvalidDbState))?;
-        let db_d_uuid = idlayer
-            .get_db_d_uuid()
-            .and_then(|u| u.ok_or(OperationError::InvalidDbState))?;
-        let db_ts_max = idlayer
-            .get_db_ts_max()
-            .and_then(|u| u.ok_or(OperationError::InvalidDbState))?;
-
-        let keyhandles = idlayer.get_key_handles()?;
-
-        let bak = DbBackup::V5 {
-            // remember env is evaled at compile time.
-            version: env!("KANIDM_PKG_SERIES").to_string(),
-            db_s_uuid,
-            db_d_uuid,
-            db_ts_max,
-            keyhandles,
-            repl_meta,
-            entries,
-        };
-
-        let serialized_entries_str = serde_json::to_string(&bak).map_err(|e| {
-            admin_error!(?e, "serde error");
-            OperationError::SerdeJsonError
-        })?;
-
-        match compression {
+        let snapshot_cid = match compression {
             BackupCompression::NoCompression => {
-                output
-                    .write(serialized_entries_str.as_bytes())
-                    .map_err(|e| {
-                        error!(?e, "fs::write error");
-                        OperationError::FsError
-                    })?;
+                let mut output = std::io::BufWriter::new(output);
+                let snapshot_cid = write_backup(self, &mut output)?;
+                output.flush().map_err(|err| {
+                    error!(?err, "Unable to flush backup output stream");
+                    OperationError::FsError
+                })?;
+                snapshot_cid
             }
             BackupCompression::Gzip => {
-                let mut encoder = GzEncoder::new(&mut output, Compression::best());
+                let mut encoder = GzEncoder::new(output, Compression::best());
+                let snapshot_cid = {
+                    let mut buffered = std::io::BufWriter::new(&mut encoder);
+                    let snapshot_cid = write_backup(self, &mut buffered)?;
+                    buffered.flush().map_err(|err| {
+                        error!(?err, "Gzip compression error writing backup");
+                        OperationError::FsError
+                    })?;
+                    snapshot_cid
+                };
                 encoder
-                    .write_all(serialized_entries_str.as_bytes())
-                    .map_err(|e| {
-                        error!(?e, "Gzip compression error writing backup");
+                    .finish()
+                    .and_then(|mut output| output.flush())
+                    .map_err(|err| {
+                        error!(?err, "Unable to flush backup output stream");
                         OperationError::FsError
                     })?;
+                snapshot_cid
             }
-        }
-
-        output.flush().map_err(|err| {
-            error!(?err, "Unable to flush backup output stream");
-            OperationError::FsError
-        })?;
+        };
 
-        Ok(())
+        Ok(snapshot_cid)
     }
 
     fn name2uuid(&mut self, name: &str) -> Result<Option<Uuid>, OperationError> {
@@ -1057,6 +1137,100 @@ pub trait BackendTransaction {
     }
 }
 
+/// Write a [`DbBackup::V6`] of the database as seen by `be` to `output`. The document is
+/// written by hand so that entries can be streamed from the database in chunks, rather
+/// than collected before serialising.
+fn write_backup<BE, W>(be: &mut BE, output: &mut W) -> Result<Option<Cid>, OperationError>
+where
+    BE: BackendTransaction + ?Sized,
+    W: std::io::Write,
+{
+    let repl_meta = be.get_ruv().to_db_backup_ruv();
+    let ruv_max_cid = match &repl_meta {
+        DbReplMeta::V1 { ruv } => ruv.last().cloned(),
+    };
+
+    let idlayer = be.get_idlayer();
+    let allids = idlayer.get_allids();
+
+    let db_s_uuid = idlayer
+        .get_db_s_uuid()
+        .and_then(|u| u.ok_or(OperationError::InvalidDbState))?;
+    let db_d_uuid = idlayer
+        .get_db_d_uuid()
+        .and_then(|u| u.ok_or(OperationError::InvalidDbState))?;
+    let db_ts_max = idlayer
+        .get_db_ts_max()
+        .and_then(|u| u.ok_or(OperationError::InvalidDbState))?;
+
+    let keyhandles = idlayer.get_key_handles()?;
+
+    let snapshot = DbBackupSnapshotV1 {
+        entry_count: allids.len() as u64,
+        ruv_max_cid: ruv_max_cid.clone(),
+    };
+
+    let write_err = |err: std::io::Error| {
+        error!(?err, "Unable to write backup");
+        OperationError::FsError
+    };
+
+    output.write_all(b"{").map_err(write_err)?;
+    // remember env is evaled at compile time.
+    write_backup_field(output, "version", &env!("KANIDM_PKG_SERIES"))?;
+    write_backup_field(output, "db_s_uuid", &db_s_uuid)?;
+    write_backup_field(output, "db_d_uuid", &db_d_uuid)?;
+    write_backup_field(output, "db_ts_max", &db_ts_max)?;
+    write_backup_field(output, "keyhandles", &keyhandles)?;
+    write_backup_field(output, "repl_meta", &repl_meta)?;
+    write_backup_field(output, "snapshot", &snapshot)?;
+    output.write_all(b"\"entries\":[").map_err(write_err)?;
+
+    let mut ids = (&allids).into_iter();
+    let mut first = true;
+    loop {
+        let chunk: IDLBitRange = ids.by_ref().take(BACKUP_CHUNK_SIZE).collect();
+        if chunk.is_empty() {
+            break;
+        }
+
+        for id_ent in idlayer.get_identry_raw(&IdList::Partial(chunk))? {
+            let entry: DbEntry = serde_json::from_slice(id_ent.data.as_slice()).map_err(|e| {
+                admin_error!(?e, id = id_ent.id, "serde error");
+                OperationError::SerdeJsonError
+            })?;
+
+            if !first {
+                output.write_all(b",").map_err(write_err)?;
+            }
+            first = false;
+
+            serde_json::to_writer(&mut *output, &entry).map_err(|e| {
+                admin_error!(?e, "serde error");
+                OperationError::SerdeJsonError
+            })?;
+        }
+    }
+
+    output.write_all(b"]}").map_err(write_err)?;
+
+    Ok(ruv_max_cid.map(Cid::from))
+}
+
+fn write_backup_field<W, T>(output: &mut W, name: &str, value: &T) -> Result<(), OperationError>
+where
+    W: std::io::Write,
+    T: serde::Serialize + ?Sized,
+{
+    write!(output, "\"{}\":", name)
+        .and_then(|()| serde_json::to_writer(&mut *output, value).map_err(std::io::Error::from))
+        .and_then(|()| output.write_all(b","))
+        .map_err(|err| {
+            error!(?err, field = name, "Unable to write backup");
+            OperationError::FsError
+        })
+}
+
 impl<'a> BackendTransaction for BackendReadTransaction<'a> {
     type IdlLayerType = IdlArcSqliteReadTransaction<'a>;
     type RuvType = ReplicationUpdateVectorReadTransaction<'a>;
@@ -1121,6 +1295,56 @@ impl<'a> BackendWriteTransaction<'a> {
         &mut self.ruv
     }
 
+    /// The index tables in the database that are not part of the current index metadata.
+    /// These are left behind when an attribute stops being indexed.
+    pub fn list_orphan_idxs(&mut self) -> Result<Vec<String>, OperationError> {
+        let expected: HashSet<_> = self
+            .idxmeta_wr
+            .idxkeys
+            .keys()
+            .map(|ikey| format!("idx_{}_{}", ikey.itype.as_idx_str(), ikey.attr.as_str()))
+            .collect();
+
+        let orphans = self
+            .idlayer
+            .list_idxs()?
+            .into_iter()
+            .filter(|idx_table| {
+                // These are always present, and are not described by the index metadata.
+                let fixed = matches!(
+                    idx_table.as_str(),
+                    "idx_name2uuid" | "idx_externalid2uuid" | "idx_uuid2spn" | "idx_uuid2rdn"
+                );
+                !fixed && !expected.contains(idx_table)
+            })
+            .collect();
+        Ok(orphans)
+    }
+
+    /// Drop an index table, provided that it is still orphaned.
+    pub fn drop_orphan_idx(&mut self, idx_table: &str) -> Result<(), OperationError> {
+        if !self.list_orphan_idxs()?.iter().any(|o| o == idx_table) {
+            error!(%idx_table, "Refusing to drop an index table that is not orphaned");
+            return Err(OperationError::InvalidState);
+        }
+        self.idlayer.drop_idx_table(idx_table)
+    }
+
+    pub fn get_storage_stats(&self) -> Result<DbStorageStats, OperationError> {
+        self.idlayer.get_storage_stats()
+    }
+
+    pub fn release_free_pages(&self, pages: u64) -> Result<(), OperationError> {
+        self.idlayer.release_free_pages(pages)
+    }
+
+    /// Set new targets for the number of items in the entry and idl caches. These are
+    /// applied when this transaction commits, so they never affect transactions that are
+    /// already in progress.
+    pub fn set_cache_targets(&mut self, entry: Option<usize>, idl: Option<usize>) {
+        self.idlayer.set_cache_targets(entry, idl)
+    }
+
     #[instrument(level = "debug", name = "be::create", skip_all)]
     pub fn create(
         &mut self,
@@ -1874,19 +2098,30 @@ impl<'a> BackendWriteTransaction<'a> {
     {
         // load all entries into RAM, may need to change this later
         // if the size of the database compared to RAM is an issue
+        let archive = BackupArchive::read(input, compression)?;
+        self.restore_archive(archive)
+    }
 
-        let dbbak_option: Result<DbBackup, serde_json::Error> = match compression {
-            BackupCompression::NoCompression => serde_json::from_reader(input),
-            BackupCompression::Gzip => {
-                let decoder = flate2::read::GzDecoder::new(input);
-                serde_json::from_reader(decoder)
-            }
-        };
+    /// Replace all content of the database with the content of `archive`. The archive is
+    /// checked to be complete and from this server version before anything is removed,
+    /// but its entries are not checked against the schema - use [`BackupArchive::verify`]
+    /// for that first.
+    pub fn restore_archive(&mut self, archive: BackupArchive) -> Result<(), OperationError> {
+        if let Some(issue) = archive.verify_header().into_iter().next() {
+            error!(%issue, "The provided backup is unable to be restored.");
+            return Err(match issue {
+                RestoreIssue::VersionMismatch { found: Some(_) } => {
+                    OperationError::DB0001MismatchedRestoreVersion
+                }
+                RestoreIssue::VersionMismatch { found: None } => {
+                    OperationError::DB0002MismatchedRestoreVersion
+                }
+                RestoreIssue::SnapshotMismatch => OperationError::DB0005InconsistentBackupSnapshot,
+                _ => OperationError::DB0006BackupVerificationFailed,
+            });
+        }
 
-        let dbbak = dbbak_option.map_err(|e| {
-            admin_error!("serde_json error {:?}", e);
-            OperationError::SerdeJsonError
-        })?;
+        let dbbak = archive.dbbak;
 
         self.danger_delete_all_db_content().map_err(|e| {
             error!("delete_all_db_content failed {:?}", e);
@@ -1895,8 +2130,8 @@ impl<'a> BackendWriteTransaction<'a> {
 
         let idlayer = self.get_idlayer();
 
-        let (dbentries, repl_meta, maybe_version) = match dbbak {
-            DbBackup::V1(dbentries) => (dbentries, None, None),
+        let (dbentries, repl_meta) = match dbbak {
+            DbBackup::V1(dbentries) => (dbentries, None),
             DbBackup::V2 {
                 db_s_uuid,
                 db_d_uuid,
@@ -1907,7 +2142,7 @@ impl<'a> BackendWriteTransaction<'a> {
                 idlayer.write_db_s_uuid(db_s_uuid)?;
                 idlayer.write_db_d_uuid(db_d_uuid)?;
                 idlayer.set_db_ts_max(db_ts_max)?;
-                (entries, None, None)
+                (entries, None)
             }
             DbBackup::V3 {
                 db_s_uuid,
@@ -1921,7 +2156,7 @@ impl<'a> BackendWriteTransaction<'a> {
                 idlayer.write_db_d_uuid(db_d_uuid)?;
                 idlayer.set_db_ts_max(db_ts_max)?;
                 idlayer.set_key_handles(keyhandles)?;
-                (entries, None, None)
+                (entries, None)
             }
             DbBackup::V4 {
                 db_s_uuid,
@@ -1936,10 +2171,20 @@ impl<'a> BackendWriteTransaction<'a> {
                 idlayer.write_db_d_uuid(db_d_uuid)?;
                 idlayer.set_db_ts_max(db_ts_max)?;
                 idlayer.set_key_handles(keyhandles)?;
-                (entries, Some(repl_meta), None)
+                (entries, Some(repl_meta))
+            }
+            DbBackup::V6 {
+                version: _,
+                db_s_uuid,
+                db_d_uuid,
+                db_ts_max,
+                keyhandles,
+                repl_meta,
+                snapshot: _,
+                entries,
             }
-            DbBackup::V5 {
-                version,
+            | DbBackup::V5 {
+                version: _,
                 db_s_uuid,
                 db_d_uuid,
                 db_ts_max,
@@ -1952,20 +2197,10 @@ impl<'a> BackendWriteTransaction<'a> {
                 idlayer.write_db_d_uuid(db_d_uuid)?;
                 idlayer.set_db_ts_max(db_ts_max)?;
                 idlayer.set_key_handles(keyhandles)?;
-                (entries, Some(repl_meta), Some(version))
+                (entries, Some(repl_meta))
             }
         };
 
-        if let Some(version) = maybe_version {
-            if version != env!("KANIDM_PKG_SERIES") {
-                error!("The provided backup data is from server version {} and is unable to be restored on this instance ({})", version, env!("KANIDM_PKG_SERIES"));
-                return Err(OperationError::DB0001MismatchedRestoreVersion);
-            }
-        } else {
-            error!("The provided backup data is from an older server version and is unable to be restored.");
-            return Err(OperationError::DB0002MismatchedRestoreVersion);
-        };
-
         // Rebuild the RUV from the backup.
         match repl_meta {
             Some(DbReplMeta::V1 { ruv: db_ruv }) => {
@@ -2279,8 +2514,8 @@ mod tests {
     use super::super::entry::{Entry, EntryInit, EntryNew};
     use super::Limits;
     use super::{
-        Backend, BackendConfig, BackendTransaction, BackendWriteTransaction, DbBackup, IdList,
-        IdxKey, OperationError,
+        Backend, BackendConfig, BackendTransaction, BackendWriteTransaction, DbBackup, FilterPlan,
+        FilterResolved, IdList, IdxKey, OperationError, Vacuum,
     };
     use crate::prelude::*;
     use crate::repl::cid::Cid;
@@ -2288,8 +2523,10 @@ mod tests {
     use idlset::v2::IDLBitRange;
     use kanidm_proto::backup::BackupCompression;
     use std::iter::FromIterator;
+    use std::num::NonZeroU8;
     use std::sync::{Arc, LazyLock};
     use std::time::Duration;
+    use time::OffsetDateTime;
 
     static CID_ZERO: LazyLock<Cid> = LazyLock::new(Cid::new_zero);
     static CID_ONE: LazyLock<Cid> = LazyLock::new(|| Cid::new_count(1));
@@ -2767,13 +3004,14 @@ mod tests {
             let mut dbbak: DbBackup = serde_json::from_reader(&mut buf).unwrap();
 
             match &mut dbbak {
-                DbBackup::V5 {
+                DbBackup::V6 {
                     version: _,
                     db_s_uuid: _,
                     db_d_uuid: _,
                     db_ts_max: _,
                     keyhandles: _,
                     repl_meta: _,
+                    snapshot: _,
                     entries,
                 } => {
                     let _ = entries.pop();
@@ -2792,13 +3030,174 @@ mod tests {
             // Rewind
             buf.set_position(0);
 
-            be.restore(&mut buf, BackupCompression::NoCompression)
-                .expect("Restore failed!");
+            // The missing entry is detected before any content is removed.
+            assert_eq!(
+                be.restore(&mut buf, BackupCompression::NoCompression),
+                Err(OperationError::DB0006BackupVerificationFailed)
+            );
 
+            assert!(entry_exists!(be, e1));
+            assert!(entry_exists!(be, e2));
+            assert!(entry_exists!(be, e3));
             assert!(be.verify().is_empty());
         });
     }
 
+    #[test]
+    fn test_be_backup_online_concurrent_write() {
+        sketching::test_init();
+
+        let idxmeta = vec![IdxKey {
+            attr: Attribute::Uuid,
+            itype: IndexType::Equality,
+        }];
+
+        // An in memory database only has a single connection, so a file is needed for
+        // the read and write transactions to proceed at the same time.
+        let db_path = std::env::temp_dir().join(format!("kanidm-backup-{}.db", Uuid::new_v4()));
+        let cfg = BackendConfig::new(
+            Some(&db_path),
+            2,
+            kanidm_proto::internal::FsType::Generic,
+            Some(2048),
+        );
+        let be = Backend::new(cfg, idxmeta.clone(), false).expect("Failed to setup backend");
+
+        let mut be_txn = be.write().unwrap();
+        be_txn.reset_db_s_uuid().unwrap();
+        be_txn.reset_db_d_uuid().unwrap();
+        be_txn.set_db_ts_max(Duration::from_secs(1)).unwrap();
+
+        let entries: Vec<_> = [
+            ("william", "db237e8a-0079-4b8c-8a56-593b22aa44d1"),
+            ("alice", "4b6228ab-1dbe-42a4-a9f5-f6368222438e"),
+            ("lucy", "7b23c99d-c06b-4a9a-a958-3afa56383e1d"),
+        ]
+        .into_iter()
+        .map(|(name, uuid)| {
+            let mut e: Entry<EntryInit, EntryNew> = Entry::new();
+            e.add_ava(Attribute::UserId, Value::from(name));
+            e.add_ava(Attribute::Uuid, Value::from(uuid));
+            e.into_sealed_new()
+        })
+        .collect();
+        assert!(be_txn.create(&CID_ZERO, entries).is_ok());
+        assert!(be_txn.commit().is_ok());
+
+        // The backup is taken from this snapshot.
+        let mut be_read_txn = be.read().unwrap();
+
+        // Writes proceed while the snapshot is held.
+        let mut be_txn = be.write().unwrap();
+        let mut e: Entry<EntryInit, EntryNew> = Entry::new();
+        e.add_ava(Attribute::UserId, Value::from("claire"));
+        e.add_ava(
+            Attribute::Uuid,
+            Value::from("0c680959-0944-47d6-9dea-53304d124266"),
+        );
+        assert!(be_txn.create(&CID_ZERO, vec![e.into_sealed_new()]).is_ok());
+        assert!(be_txn.commit().is_ok());
+
+        let mut buf = std::io::Cursor::new(Vec::new());
+        let snapshot_cid = be_read_txn
+            .backup(&mut buf, BackupCompression::NoCompression)
+            .expect("Backup failed!");
+        drop(be_read_txn);
+
+        assert_eq!(snapshot_cid.as_ref(), Some(&*CID_ZERO));
+
+        buf.set_position(0);
+        let dbbak: DbBackup = serde_json::from_reader(&mut buf).unwrap();
+        match &dbbak {
+            DbBackup::V6 {
+                snapshot, entries, ..
+            } => {
+                assert_eq!(snapshot.entry_count, 3);
+                assert_eq!(entries.len(), 3);
+            }
+            _ => unreachable!(),
+        }
+
+        // Restore into a new instance.
+        let be_restored = Backend::new(BackendConfig::new_test("db_2"), idxmeta, false)
+            .expect("Failed to setup backend");
+
+        let mut be_restored_txn = be_restored.write().unwrap();
+        buf.set_position(0);
+        be_restored_txn
+            .restore(&mut buf, BackupCompression::NoCompression)
+            .expect("Restore failed!");
+        assert!(be_restored_txn.verify().is_empty());
+
+        let filt = filter_resolved!(f_pres(Attribute::UserId));
+        let restored = be_restored_txn
+            .search(&Limits::unlimited(), &filt)
+            .expect("Search failed!");
+        assert_eq!(restored.len(), 3);
+        assert!(!restored
+            .iter()
+            .any(|e| e.get_ava_single_utf8(Attribute::UserId) == Some("claire")));
+
+        // The replication metadata matches the recorded snapshot.
+        let mut rebackup = std::io::Cursor::new(Vec::new());
+        let restored_cid = be_restored_txn
+            .backup(&mut rebackup, BackupCompression::NoCompression)
+            .expect("Backup failed!");
+        assert_eq!(restored_cid, snapshot_cid);
+        assert!(be_restored_txn.commit().is_ok());
+
+        drop(be);
+        for suffix in ["", "-wal", "-shm"] {
+            let mut path = db_path.clone().into_os_string();
+            path.push(suffix);
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn test_be_backup_restore_inconsistent_snapshot() {
+        run_test!(|be: &mut BackendWriteTransaction| {
+            be.reset_db_s_uuid().unwrap();
+            be.reset_db_d_uuid().unwrap();
+            be.set_db_ts_max(Duration::from_secs(1)).unwrap();
+
+            let mut e1: Entry<EntryInit, EntryNew> = Entry::new();
+            e1.add_ava(Attribute::UserId, Value::from("william"));
+            e1.add_ava(
+                Attribute::Uuid,
+                Value::from("db237e8a-0079-4b8c-8a56-593b22aa44d1"),
+            );
+            assert!(be
+                .create(&CID_ZERO, vec![e1.clone().into_sealed_new()])
+                .is_ok());
+
+            let mut buf = std::io::Cursor::new(Vec::new());
+            be.backup(&mut buf, BackupCompression::NoCompression)
+                .expect("Backup failed!");
+
+            buf.set_position(0);
+            let mut dbbak: DbBackup = serde_json::from_reader(&mut buf).unwrap();
+            match &mut dbbak {
+                DbBackup::V6 { snapshot, .. } => {
+                    snapshot.ruv_max_cid = Some(CID_ONE.clone().into());
+                }
+                _ => unreachable!(),
+            };
+
+            buf.get_mut().clear();
+            buf.set_position(0);
+            serde_json::to_writer(&mut buf, &dbbak).unwrap();
+            buf.set_position(0);
+
+            assert_eq!(
+                be.restore(&mut buf, BackupCompression::NoCompression),
+                Err(OperationError::DB0005InconsistentBackupSnapshot)
+            );
+            // Nothing was removed.
+            assert!(entry_exists!(be, e1));
+        });
+    }
+
     #[test]
     fn test_be_sid_generation_and_reset() {
         run_test!(|be: &mut BackendWriteTransaction| {
@@ -3673,6 +4072,312 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_be_index_ordering_lessthan() {
+        sketching::test_init();
+
+        let idxmeta = vec![
+            IdxKey {
+                attr: Attribute::Uuid,
+                itype: IndexType::Equality,
+            },
+            IdxKey {
+                attr: Attribute::Uuid,
+                itype: IndexType::Presence,
+            },
+            IdxKey {
+                attr: Attribute::GidNumber,
+                itype: IndexType::Presence,
+            },
+            IdxKey {
+                attr: Attribute::GidNumber,
+                itype: IndexType::Ordering,
+            },
+            IdxKey {
+                attr: Attribute::AccountExpire,
+                itype: IndexType::Presence,
+            },
+            IdxKey {
+                attr: Attribute::AccountExpire,
+                itype: IndexType::Ordering,
+            },
+        ];
+
+        let be = Backend::new(BackendConfig::new_test("main"), idxmeta, false)
+            .expect("Failed to setup backend");
+
+        let day = Duration::from_secs(86400);
+        // The values are chosen so that their decimal strings don't sort in numeric
+        // order, and so that one time is before the epoch.
+        let values = [
+            (9, OffsetDateTime::UNIX_EPOCH - day),
+            (10, OffsetDateTime::UNIX_EPOCH + day),
+            (70000, OffsetDateTime::UNIX_EPOCH + day * 1000),
+        ];
+
+        let mut be_txn = be.write().unwrap();
+        assert!(be_txn.reindex(false).is_ok());
+
+        let entries = values
+            .iter()
+            .map(|(gid, expire)| {
+                let mut e: Entry<EntryInit, EntryNew> = Entry::new();
+                e.add_ava(Attribute::Uuid, Value::Uuid(Uuid::new_v4()));
+                e.add_ava(Attribute::GidNumber, Value::Uint32(*gid));
+                e.add_ava(Attribute::AccountExpire, Value::DateTime(*expire));
+                e.into_sealed_new()
+            })
+            .collect();
+        be_txn.create(&CID_ZERO, entries).unwrap();
+
+        let terms = [
+            (Attribute::GidNumber, PartialValue::Uint32(9)),
+            (Attribute::GidNumber, PartialValue::Uint32(10)),
+            (Attribute::GidNumber, PartialValue::Uint32(70001)),
+            (
+                Attribute::AccountExpire,
+                PartialValue::DateTime(OffsetDateTime::UNIX_EPOCH),
+            ),
+            (
+                Attribute::AccountExpire,
+                PartialValue::DateTime(OffsetDateTime::UNIX_EPOCH + day * 2),
+            ),
+        ];
+
+        fn check_terms<BE: BackendTransaction>(be: &mut BE, terms: &[(Attribute, PartialValue)]) {
+            for (attr, pv) in terms {
+                // Without the index every entry must be checked.
+                let f_unindexed = filter_resolved!(f_lt(attr.clone(), pv.clone()));
+                let (r, plan) = be.filter2idl(f_unindexed.to_inner(), 0).unwrap();
+                assert!(matches!(r, IdList::AllIds));
+                assert!(matches!(plan, FilterPlan::LessThanUnindexed(_)));
+
+                let expect: IDLBitRange = be
+                    .search(&Limits::unlimited(), &f_unindexed)
+                    .expect("failed to search")
+                    .iter()
+                    .map(|e| e.get_id())
+                    .collect();
+
+                // The ordering index yields exactly the entries that match.
+                let f_indexed =
+                    FilterResolved::LessThan(attr.clone(), pv.clone(), NonZeroU8::new(1));
+                let (r, plan) = be.filter2idl(&f_indexed, 0).unwrap();
+                assert!(matches!(plan, FilterPlan::LessThanIndexed(_)));
+                match r {
+                    IdList::Partial(idl) => assert_eq!(idl, expect, "{attr} < {pv:?}"),
+                    _ => panic!("ordering index was not used for {attr} < {pv:?}"),
+                }
+            }
+        }
+
+        // Before commit the changes are only in the cache.
+        check_terms(&mut be_txn, &terms);
+        assert!(be_txn.commit().is_ok());
+
+        let mut be_txn = be.read().unwrap();
+        check_terms(&mut be_txn, &terms);
+    }
+
+    #[test]
+    fn test_be_vacuum_orphaned_index() {
+        sketching::test_init();
+
+        let uuid_idxs = vec![
+            IdxKey {
+                attr: Attribute::Uuid,
+                itype: IndexType::Equality,
+            },
+            IdxKey {
+                attr: Attribute::Uuid,
+                itype: IndexType::Presence,
+            },
+        ];
+        let mut idxmeta = uuid_idxs.clone();
+        idxmeta.push(IdxKey {
+            attr: Attribute::Name,
+            itype: IndexType::Equality,
+        });
+
+        let be = Backend::new(BackendConfig::new_test("main"), idxmeta, false)
+            .expect("Failed to setup backend");
+
+        let mut be_txn = be.write().unwrap();
+        assert!(be_txn.reindex(false).is_ok());
+        let entries = ["alice", "bob", "claire"]
+            .into_iter()
+            .map(|name| {
+                let mut e: Entry<EntryInit, EntryNew> = Entry::new();
+                e.add_ava(Attribute::Uuid, Value::Uuid(Uuid::new_v4()));
+                e.add_ava(Attribute::Name, Value::new_iname(name));
+                e.into_sealed_new()
+            })
+            .collect();
+        be_txn.create(&CID_ZERO, entries).unwrap();
+        assert!(be_txn.list_orphan_idxs().unwrap().is_empty());
+
+        // Name stops being indexed, but the table remains.
+        be_txn.update_idxmeta(uuid_idxs).unwrap();
+        let orphan = "idx_eq_name".to_string();
+        assert_eq!(be_txn.list_orphan_idxs().unwrap(), vec![orphan.clone()]);
+        assert!(be_txn.commit().is_ok());
+
+        // Without enough free space the vacuum refuses to start.
+        let mut vacuum = Vacuum::new(Some(0));
+        let mut be_txn = be.write().unwrap();
+        assert_eq!(
+            vacuum.step(&mut be_txn),
+            Err(OperationError::DB0007InsufficientSpaceForVacuum)
+        );
+        drop(be_txn);
+
+        let mut vacuum = Vacuum::new(Some(u64::MAX));
+        let mut steps = 0;
+        loop {
+            let mut be_txn = be.write().unwrap();
+            let done = vacuum.step(&mut be_txn).expect("vacuum step failed");
+            assert!(be_txn.commit().is_ok());
+            steps += 1;
+            if done {
+                break;
+            }
+            assert!(steps < 100, "vacuum did not complete");
+        }
+
+        let report = vacuum.into_report();
+        assert_eq!(report.dropped_idxs, vec![orphan.clone()]);
+        assert!(report.size_after <= report.size_before);
+
+        let mut be_txn = be.read().unwrap();
+        let idxs = be_txn.list_indexes().unwrap();
+        assert!(!idxs.contains(&orphan));
+        assert!(idxs.contains(&"idx_eq_uuid".to_string()));
+    }
+
+    #[test]
+    fn test_be_cache_targets() {
+        sketching::test_init();
+
+        let idxmeta = vec![
+            IdxKey {
+                attr: Attribute::Uuid,
+                itype: IndexType::Equality,
+            },
+            IdxKey {
+                attr: Attribute::Uuid,
+                itype: IndexType::Presence,
+            },
+        ];
+
+        // A reader is held open across a write and another read, which needs more
+        // connections than an in memory database can provide.
+        let db_path = std::env::temp_dir().join(format!("kanidm-cache-{}.db", Uuid::new_v4()));
+        let cfg = BackendConfig::new(
+            Some(&db_path),
+            3,
+            kanidm_proto::internal::FsType::Generic,
+            Some(2048),
+        );
+        let be = Backend::new(cfg, idxmeta, false).expect("Failed to setup backend");
+
+        let mut be_txn = be.write().unwrap();
+        assert!(be_txn.reindex(false).is_ok());
+        let entries = (0..20)
+            .map(|_| {
+                let mut e: Entry<EntryInit, EntryNew> = Entry::new();
+                e.add_ava(Attribute::Uuid, Value::Uuid(Uuid::new_v4()));
+                e.into_sealed_new()
+            })
+            .collect();
+        be_txn.create(&CID_ZERO, entries).unwrap();
+        assert!(be_txn.commit().is_ok());
+
+        let filt = filter_resolved!(f_pres(Attribute::Uuid));
+
+        // Search for every entry, returning the entry cache hits and misses of the search.
+        let workload = || {
+            let mut be_txn = be.read().unwrap();
+            let before = be_txn.cache_stats().entry;
+            let r = be_txn
+                .search(&Limits::unlimited(), &filt)
+                .expect("failed to search");
+            assert_eq!(r.len(), 20);
+            let after = be_txn.cache_stats().entry;
+            drop(be_txn);
+            // Add anything this reader included to the cache.
+            be.try_quiesce();
+            (after.hits - before.hits, after.misses - before.misses)
+        };
+
+        // The created entries were added to the cache as they were written.
+        assert_eq!(workload(), (20, 0));
+        let stats = be.read().unwrap().cache_stats().entry;
+        assert_eq!(stats.capacity, 2048);
+        assert_eq!(stats.target, 2048);
+        assert_eq!(stats.evictions, 0);
+
+        // This reader is open while the cache is shrunk.
+        let mut held_txn = be.read().unwrap();
+
+        let mut be_txn = be.write().unwrap();
+        be_txn.set_cache_targets(Some(5), None);
+        // The target isn't applied until commit.
+        assert_eq!(be_txn.cache_stats().entry.target, 2048);
+        assert!(be_txn.commit().is_ok());
+
+        let stats = be.read().unwrap().cache_stats();
+        assert_eq!(stats.entry.target, 5);
+        assert_eq!(stats.entry.evictions, 20);
+        assert_eq!(stats.idl.target, stats.idl.capacity);
+        assert_eq!(stats.idl.evictions, 0);
+
+        let r = held_txn
+            .search(&Limits::unlimited(), &filt)
+            .expect("failed to search");
+        assert_eq!(r.len(), 20);
+        drop(held_txn);
+
+        // At most 5 entries can be added back, so repeating the search keeps missing.
+        for _ in 0..3 {
+            let (hits, misses) = workload();
+            assert_eq!(hits + misses, 20);
+            assert!(hits <= 5);
+        }
+
+        // Growing the cache doesn't need to evict anything.
+        let mut be_txn = be.write().unwrap();
+        be_txn.set_cache_targets(Some(usize::MAX), None);
+        assert!(be_txn.commit().is_ok());
+
+        let stats = be.read().unwrap().cache_stats().entry;
+        assert_eq!(stats.target, stats.capacity);
+        assert_eq!(stats.evictions, 20);
+
+        // Once the misses have been added to the cache every lookup hits again.
+        let (hits, misses) = workload();
+        assert_eq!(hits + misses, 20);
+        assert_eq!(workload(), (20, 0));
+
+        // A search that can't use an index must consider every entry.
+        let mut be_txn = be.read().unwrap();
+        let before = be_txn.cache_stats().entry.include_all_fallbacks;
+        be_txn
+            .search(
+                &Limits::unlimited(),
+                &filter_resolved!(f_pres(Attribute::Name)),
+            )
+            .expect("failed to search");
+        assert_eq!(be_txn.cache_stats().entry.include_all_fallbacks, before + 1);
+        drop(be_txn);
+
+        drop(be);
+        for suffix in ["", "-wal", "-shm"] {
+            let mut path = db_path.clone().into_os_string();
+            path.push(suffix);
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
     #[test]
     fn test_be_index_slope_generation() {
         run_test!(|be: &mut BackendWriteTransaction| {
@@ -3864,6 +4569,52 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_be_search_page() {
+        run_test!(|be: &mut BackendWriteTransaction| {
+            let lims = Limits::unlimited();
+
+            let entries: Vec<_> = (0..10)
+                .map(|i| {
+                    let mut e: Entry<EntryInit, EntryNew> = Entry::new();
+                    e.add_ava(Attribute::Name, Value::new_iname(&format!("user{i}")));
+                    e.add_ava(Attribute::Uuid, Value::Uuid(Uuid::new_v4()));
+                    e.into_sealed_new()
+                })
+                .collect();
+            assert!(be.create(&CID_ZERO, entries).is_ok());
+
+            // Name presence is indexed, so only the entries of each page are loaded.
+            let filt = filter_resolved!(f_pres(Attribute::Name));
+
+            // Walk the set in pages of three.
+            let mut seen = Vec::new();
+            let mut after = 0;
+            loop {
+                let (page, next) = be
+                    .search_page(&lims, &filt, after, 3)
+                    .expect("failed to search page");
+                assert!(page.len() <= 3);
+                seen.extend(page.iter().map(|e| e.get_id()));
+                match next {
+                    Some(next) => after = next,
+                    None => break,
+                }
+            }
+
+            assert_eq!(seen.len(), 10);
+            assert!(seen.windows(2).all(|w| w[0] < w[1]));
+
+            // A page may not exceed the result limit.
+            let mut lim_deny = Limits::unlimited();
+            lim_deny.search_max_results = 2;
+            assert_eq!(
+                be.search_page(&lim_deny, &filt, 0, 3),
+                Err(OperationError::ResourceLimit)
+            );
+        })
+    }
+
     #[test]
     fn test_be_limits_partial_filter() {
         run_test!(|be: &mut BackendWriteTransaction| {
@@ -0,0 +1,137 @@
+//! Vacuum of the database while the server is running. Index tables of attributes that are
+//! no longer indexed are dropped, and then the free pages of the database are released to
+//! the filesystem.
+//!
+//! The vacuum is performed in steps, each of which is applied in its own write transaction.
+//! This means the write lock is only held briefly, and other writes can proceed between
+//! the steps of a large vacuum.
+
+use super::BackendWriteTransaction;
+use crate::prelude::*;
+
+/// The maximum number of free pages that a single step releases to the filesystem.
+const VACUUM_STEP_PAGES: u64 = 1024;
+
+/// The size and layout of the database storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DbStorageStats {
+    pub page_size: u64,
+    pub page_count: u64,
+    pub freelist_count: u64,
+    /// Free pages can be released without rewriting the whole database.
+    pub incremental_vacuum: bool,
+}
+
+impl DbStorageStats {
+    pub fn size(&self) -> u64 {
+        self.page_size * self.page_count
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct VacuumReport {
+    /// The index tables that were dropped.
+    pub dropped_idxs: Vec<String>,
+    /// The size of the database in bytes before and after the vacuum.
+    pub size_before: u64,
+    pub size_after: u64,
+}
+
+enum VacuumPhase {
+    Start,
+    DropIdxs(Vec<String>),
+    Compact,
+    Done,
+}
+
+pub struct Vacuum {
+    available_space: Option<u64>,
+    phase: VacuumPhase,
+    report: VacuumReport,
+}
+
+impl Vacuum {
+    /// Prepare a vacuum. `available_space` is the free space in bytes of the filesystem
+    /// that holds the database, if it is known.
+    pub fn new(available_space: Option<u64>) -> Self {
+        Vacuum {
+            available_space,
+            phase: VacuumPhase::Start,
+            report: VacuumReport::default(),
+        }
+    }
+
+    /// Perform the next step of the vacuum in this transaction, which must be committed
+    /// before the next step. Returns `true` once the vacuum is complete.
+    pub fn step(&mut self, be_txn: &mut BackendWriteTransaction) -> Result<bool, OperationError> {
+        match std::mem::replace(&mut self.phase, VacuumPhase::Done) {
+            VacuumPhase::Start => {
+                let stats = be_txn.get_storage_stats()?;
+                // Released pages pass through the write ahead log before they are
+                // checkpointed, so in the worst case this needs as much space again as
+                // the database itself.
+                let required = stats.size();
+                if let Some(available) = self.available_space {
+                    if available < required {
+                        error!(
+                            ?available,
+                            ?required,
+                            "Insufficient free space to vacuum the database"
+                        );
+                        return Err(OperationError::DB0007InsufficientSpaceForVacuum);
+                    }
+                }
+
+                let orphans = be_txn.list_orphan_idxs()?;
+                info!(
+                    size = ?required,
+                    orphaned_indexes = orphans.len(),
+                    "Starting database vacuum"
+                );
+
+                self.report.size_before = required;
+                self.phase = VacuumPhase::DropIdxs(orphans);
+                Ok(false)
+            }
+            VacuumPhase::DropIdxs(mut orphans) => {
+                match orphans.pop() {
+                    Some(idx_table) => {
+                        be_txn.drop_orphan_idx(&idx_table)?;
+                        info!(%idx_table, remaining = orphans.len(), "Dropped orphaned index");
+                        self.report.dropped_idxs.push(idx_table);
+                        self.phase = VacuumPhase::DropIdxs(orphans);
+                    }
+                    None => self.phase = VacuumPhase::Compact,
+                }
+                Ok(false)
+            }
+            VacuumPhase::Compact => {
+                let stats = be_txn.get_storage_stats()?;
+                if !stats.incremental_vacuum {
+                    warn!("This database must be vacuumed offline once before free pages can be released while the server is running");
+                } else if stats.freelist_count > 0 {
+                    be_txn.release_free_pages(VACUUM_STEP_PAGES)?;
+                    info!(
+                        remaining_pages = stats.freelist_count.saturating_sub(VACUUM_STEP_PAGES),
+                        "Released free pages"
+                    );
+                    self.phase = VacuumPhase::Compact;
+                    return Ok(false);
+                }
+
+                self.report.size_after = stats.size();
+                info!(
+                    size_before = ?self.report.size_before,
+                    size_after = ?self.report.size_after,
+                    "Database vacuum complete"
+                );
+                Ok(true)
+            }
+            VacuumPhase::Done => Ok(true),
+        }
+    }
+
+    pub fn into_report(self) -> VacuumReport {
+        self.report
+    }
+}
@@ -29,11 +29,15 @@ pub enum EntryClass {
     Application,
     AssertionNonce,
     AttributeType,
+    AuditRecord,
+    Automount,
+    AutomountMap,
     Builtin,
     Class,
     ClassType,
     ClientCertificate,
     Conflict,
+    CreationTemplate,
     DomainInfo,
     DynGroup,
     ExtensibleObject,
@@ -69,6 +73,7 @@ pub enum EntryClass {
     SyncObject,
     Tombstone,
     User,
+    WebhookSubscription,
     System,
     SystemInfo,
     SystemConfig,
@@ -98,11 +103,15 @@ impl From<EntryClass> for &'static str {
             EntryClass::Application => ENTRYCLASS_APPLICATION,
             EntryClass::AssertionNonce => ENTRYCLASS_ASSERTION_NONCE,
             EntryClass::AttributeType => ENTRYCLASS_ATTRIBUTE_TYPE,
+            EntryClass::AuditRecord => ENTRYCLASS_AUDIT_RECORD,
+            EntryClass::Automount => ENTRYCLASS_AUTOMOUNT,
+            EntryClass::AutomountMap => ENTRYCLASS_AUTOMOUNT_MAP,
             EntryClass::Builtin => ENTRYCLASS_BUILTIN,
             EntryClass::Class => ENTRYCLASS_CLASS,
             EntryClass::ClassType => ENTRYCLASS_CLASS_TYPE,
             EntryClass::ClientCertificate => ENTRYCLASS_CLIENT_CERTIFICATE,
             EntryClass::Conflict => ENTRYCLASS_CONFLICT,
+            EntryClass::CreationTemplate => ENTRYCLASS_CREATION_TEMPLATE,
             EntryClass::DomainInfo => ENTRYCLASS_DOMAIN_INFO,
             EntryClass::DynGroup => ENTRYCLASS_DYN_GROUP,
             EntryClass::ExtensibleObject => ENTRYCLASS_EXTENSIBLE_OBJECT,
@@ -143,6 +152,7 @@ impl From<EntryClass> for &'static str {
             #[cfg(any(test, debug_assertions))]
             EntryClass::TestClass => TEST_ENTRYCLASS_TEST_CLASS,
             EntryClass::User => ENTRYCLASS_USER,
+            EntryClass::WebhookSubscription => ENTRYCLASS_WEBHOOK_SUBSCRIPTION,
         }
     }
 }
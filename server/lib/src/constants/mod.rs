@@ -15,7 +15,7 @@ use std::time::Duration;
 // index is performed on first-run.
 //
 // It's also useful if we need to force a reindex due to a bug though :)
-pub const SYSTEM_INDEX_VERSION: i64 = 32;
+pub const SYSTEM_INDEX_VERSION: i64 = 33;
 
 /*
  * domain functional levels
@@ -140,10 +140,16 @@ pub const PURGE_FREQUENCY: u64 = 60;
 #[cfg(not(test))]
 pub const PURGE_FREQUENCY: u64 = 600;
 
+/// The number of entries whose spn is regenerated per transaction during a domain rename.
+pub const DOMAIN_RENAME_MIGRATE_CHUNK: usize = 256;
+
 /// The duration for which messages will be retained after their send_after time. Defaults to
 /// 7 days
 pub const DEFAULT_MESSAGE_RETENTION: Duration = Duration::from_secs(86400 * 7);
 
+/// The duration for which audit records of changes are retained. Defaults to 180 days
+pub const DEFAULT_AUDIT_RECORD_RETENTION: Duration = Duration::from_secs(86400 * 180);
+
 /// The number of delayed actions to consider per write transaction. Higher
 /// values allow more coalescing to occur, but may consume more ram and cause
 /// some latency while dequeuing and writing those operations.
@@ -168,11 +174,52 @@ pub const RECYCLEBIN_MAX_AGE: u64 = 300;
 /// In production we allow 1 week
 pub const RECYCLEBIN_MAX_AGE: u64 = 7 * 86400;
 
+/// The attributes that LDAP presents by masking or mapping other attributes. These must
+/// exist in the schema as phantoms so they can never be stored or reused.
+pub const LDAP_PHANTOM_ATTRS: &[Attribute] = &[
+    Attribute::AutomountMapName,
+    Attribute::Cn,
+    Attribute::Dn,
+    Attribute::Email,
+    Attribute::EmailAlternative,
+    Attribute::EmailPrimary,
+    Attribute::EntryDn,
+    Attribute::EntryUuid,
+    Attribute::Gecos,
+    Attribute::HomeDirectory,
+    Attribute::LdapEmailAddress,
+    Attribute::LdapKeys,
+    Attribute::LdapSshPublicKey,
+    Attribute::ObjectClass,
+    Attribute::SudoHost,
+    Attribute::Uid,
+    Attribute::UidNumber,
+];
+
+/// The reference attributes that access control and group membership resolve on every
+/// operation. These must remain indexed references, else every access check becomes a full
+/// table scan.
+pub const SCHEMA_CRITICAL_REFERENCE_ATTRS: &[Attribute] = &[
+    Attribute::AcpReceiverGroup,
+    Attribute::EntryManagedBy,
+    Attribute::Member,
+    Attribute::MemberOf,
+];
+
 // 5 minute auth session window.
 pub const AUTH_SESSION_TIMEOUT: u64 = 300;
 // 5 minute mfa reg window
 pub const MFAREG_SESSION_TIMEOUT: u64 = 300;
 pub const PW_MIN_LENGTH: u32 = 10;
+// The zxcvbn score required of passwords when no account policy sets one.
+pub const PW_MIN_SCORE: u32 = 4;
+// How long a credential is locked once it exceeds an account policy's maximum attempts.
+pub const DEFAULT_AUTH_LOCKOUT_DURATION: u32 = 900;
+// The number of recent passwords, including the current one, that may not be reused
+// when no account policy sets a length.
+pub const PW_HISTORY_LEN: u32 = 8;
+// The upper bound of the password history length, as each entry must be verified.
+pub const PW_HISTORY_MAX_LEN: u32 = 24;
 
 // Maximum - Sessions have no upper bound.
 pub const MAXIMUM_AUTH_SESSION_EXPIRY: u32 = u32::MAX;
@@ -190,6 +237,28 @@ pub const OAUTH_REFRESH_TOKEN_EXPIRY: u32 = 3600 * 16;
 /// How long access tokens should last. This is NOT the length
 /// of the refresh token, which is bound to the issuing session.
 pub const OAUTH2_ACCESS_TOKEN_EXPIRY: u32 = 15 * 60;
+/// The bounds that a client may override the access token expiry within.
+pub const OAUTH2_ACCESS_TOKEN_EXPIRY_MIN: u32 = 60;
+pub const OAUTH2_ACCESS_TOKEN_EXPIRY_MAX: u32 = 3600 * 24;
+/// The bounds that a client may override the refresh token expiry within.
+pub const OAUTH_REFRESH_TOKEN_EXPIRY_MIN: u32 = 60;
+pub const OAUTH_REFRESH_TOKEN_EXPIRY_MAX: u32 = 3600 * 24 * 365;
+
+/// How long a pushed authorisation request remains valid for before the client
+/// must redirect the user agent to the authorisation endpoint.
+pub const OAUTH2_PAR_EXPIRY: u32 = 60;
+
+/// The number of pushed authorisation requests that a single client may have awaiting
+/// use. Public clients push without authenticating, so this must be bounded.
+pub const OAUTH2_PAR_MAX_PER_CLIENT: usize = 64;
+
+/// The number of pushed authorisation requests that may be awaiting use across all
+/// clients, after which further requests are refused until some expire.
+pub const OAUTH2_PAR_MAX: usize = 4096;
+
+/// How long after a credential is verified that it may satisfy the acr_values of an
+/// OAuth2 authorisation request. Older sessions must step up again.
+pub const OAUTH2_ACR_MAX_AGE: u64 = 5 * 60;
 
 /// The absolute maximum that can be requested for max-age requests in OIDC.
 pub const OAUTH2_OIDC_MAX_AGE_CLAMP: i64 = 86400;
@@ -221,6 +290,9 @@ pub const DEFAULT_LIMIT_FILTER_DEPTH_MAX: u64 = 12;
 /// The maximum number of sessions allowed on a single entry.
 pub(crate) const SESSION_MAXIMUM: usize = 48;
 
+/// The maximum length in bytes of a user agent recorded on a session.
+pub const SESSION_USER_AGENT_MAX_LEN: usize = 128;
+
 #[cfg(test)]
 // Test only certificate. This is a self-signed server cert.
 pub(crate) const TEST_X509_CERT_DATA: &str = r#"-----BEGIN CERTIFICATE-----
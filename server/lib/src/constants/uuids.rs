@@ -83,6 +83,7 @@ pub const UUID_IDM_MESSAGE_SENDERS: Uuid = uuid!("00000000-0000-0000-0000-000000
 pub const UUID_IDM_OAUTH2_CLIENT_ADMINS: Uuid = uuid!("00000000-0000-0000-0000-000000000056");
 pub const UUID_IDM_OAUTH2_ACCOUNT_ADMINS: Uuid = uuid!("00000000-0000-0000-0000-000000000057");
 pub const UUID_HMAC_NAME_FEATURE: Uuid = uuid!("00000000-0000-0000-0000-000000000058");
+pub const UUID_IDM_AUDIT_READERS: Uuid = uuid!("00000000-0000-0000-0000-000000000059");
 
 //
 pub const UUID_IDM_HIGH_PRIVILEGE: Uuid = uuid!("00000000-0000-0000-0000-000000001000");
@@ -394,6 +395,77 @@ pub const UUID_SCHEMA_ATTR_DOMAIN_ALLOW_ACCOUNT_RECOVERY: Uuid =
     uuid!("00000000-0000-0000-0000-ffff00000224");
 pub const UUID_SCHEMA_ATTR_OAUTH2_REFRESH_TOKEN_EXPIRY: Uuid =
     uuid!("00000000-0000-0000-0000-ffff00000225");
+pub const UUID_SCHEMA_ATTR_ATTR_EXCLUDES: Uuid = uuid!("00000000-0000-0000-0000-ffff00000226");
+pub const UUID_SCHEMA_ATTR_OAUTH2_ACCESS_TOKEN_EXPIRY: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000227");
+pub const UUID_SCHEMA_ATTR_OAUTH2_REQUIRE_PUSHED_AUTHORISATION: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000228");
+pub const UUID_SCHEMA_ATTR_AUTH_PASSWORD_MINIMUM_SCORE: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000229");
+pub const UUID_SCHEMA_ATTR_AUTH_PASSWORD_BREACH_CHECK: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff0000022a");
+pub const UUID_SCHEMA_ATTR_PASSWORD_HISTORY: Uuid = uuid!("00000000-0000-0000-0000-ffff0000022b");
+pub const UUID_SCHEMA_ATTR_AUTH_LOCKOUT_MAX_ATTEMPTS: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff0000022c");
+pub const UUID_SCHEMA_ATTR_AUTH_LOCKOUT_DURATION: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff0000022d");
+pub const UUID_SCHEMA_ATTR_CREDENTIAL_LOCKOUT: Uuid = uuid!("00000000-0000-0000-0000-ffff0000022e");
+pub const UUID_SCHEMA_ATTR_CREDENTIAL_MAX_AGE: Uuid = uuid!("00000000-0000-0000-0000-ffff0000022f");
+pub const UUID_SCHEMA_ATTR_PASSKEY_USAGE: Uuid = uuid!("00000000-0000-0000-0000-ffff00000230");
+pub const UUID_SCHEMA_ATTR_EXCLUSION_GROUP: Uuid = uuid!("00000000-0000-0000-0000-ffff00000231");
+pub const UUID_SCHEMA_ATTR_APPLICATION_PASSWORD_LIMIT: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000232");
+pub const UUID_SCHEMA_ATTR_APPLICATION_PASSWORD_AUTO_REVOKE: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000233");
+pub const UUID_SCHEMA_ATTR_GID_NUMBER_RANGE: Uuid = uuid!("00000000-0000-0000-0000-ffff00000234");
+pub const UUID_SCHEMA_ATTR_GID_NUMBER_RANGE_NAME: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000235");
+pub const UUID_SCHEMA_ATTR_UNIX_HOME_DIRECTORY_TEMPLATE: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000236");
+pub const UUID_SCHEMA_ATTR_UNIX_DEFAULT_SHELL: Uuid = uuid!("00000000-0000-0000-0000-ffff00000237");
+pub const UUID_SCHEMA_ATTR_UNIX_HOME_DIRECTORY: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000238");
+pub const UUID_SCHEMA_CLASS_AUTOMOUNT_MAP: Uuid = uuid!("00000000-0000-0000-0000-ffff00000239");
+pub const UUID_SCHEMA_CLASS_AUTOMOUNT: Uuid = uuid!("00000000-0000-0000-0000-ffff0000023a");
+pub const UUID_SCHEMA_ATTR_AUTOMOUNT_KEY: Uuid = uuid!("00000000-0000-0000-0000-ffff0000023b");
+pub const UUID_SCHEMA_ATTR_AUTOMOUNT_INFORMATION: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff0000023c");
+pub const UUID_SCHEMA_ATTR_AUTOMOUNT_MAP_NAME: Uuid = uuid!("00000000-0000-0000-0000-ffff0000023d");
+pub const UUID_SCHEMA_ATTR_RADIUS_VLAN_ID: Uuid = uuid!("00000000-0000-0000-0000-ffff0000023e");
+pub const UUID_SCHEMA_ATTR_RADIUS_VLAN_PRIORITY: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff0000023f");
+pub const UUID_SCHEMA_ATTR_AUDIT_ACTOR: Uuid = uuid!("00000000-0000-0000-0000-ffff00000240");
+pub const UUID_SCHEMA_ATTR_AUDIT_TARGET: Uuid = uuid!("00000000-0000-0000-0000-ffff00000241");
+pub const UUID_SCHEMA_ATTR_AUDIT_OPERATION: Uuid = uuid!("00000000-0000-0000-0000-ffff00000242");
+pub const UUID_SCHEMA_ATTR_AUDIT_ATTRIBUTE: Uuid = uuid!("00000000-0000-0000-0000-ffff00000243");
+pub const UUID_SCHEMA_ATTR_AUDIT_TIME: Uuid = uuid!("00000000-0000-0000-0000-ffff00000244");
+pub const UUID_SCHEMA_CLASS_AUDIT_RECORD: Uuid = uuid!("00000000-0000-0000-0000-ffff00000245");
+pub const UUID_SCHEMA_ATTR_REQUIRES_ONE_OF: Uuid = uuid!("00000000-0000-0000-0000-ffff00000246");
+pub const UUID_SCHEMA_ATTR_CLASS_KIND: Uuid = uuid!("00000000-0000-0000-0000-ffff00000247");
+pub const UUID_SCHEMA_ATTR_ALLOWED_VALUE: Uuid = uuid!("00000000-0000-0000-0000-ffff00000248");
+pub const UUID_SCHEMA_ATTR_DYNGROUP_FILTER_EXCLUDE: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000249");
+pub const UUID_SCHEMA_ATTR_DOMAIN_DENY_MEMBERSHIP_CYCLES: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff0000024a");
+pub const UUID_SCHEMA_ATTR_ON_DELETE: Uuid = uuid!("00000000-0000-0000-0000-ffff0000024b");
+pub const UUID_SCHEMA_ATTR_UNIQUE_SCOPE: Uuid = uuid!("00000000-0000-0000-0000-ffff0000024c");
+pub const UUID_SCHEMA_ATTR_DOMAIN_SELF_WRITE_ATTRS: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff0000024d");
+pub const UUID_SCHEMA_ATTR_AUDIT: Uuid = uuid!("00000000-0000-0000-0000-ffff0000024e");
+pub const UUID_SCHEMA_ATTR_DOMAIN_RENAME_FROM: Uuid = uuid!("00000000-0000-0000-0000-ffff0000024f");
+pub const UUID_SCHEMA_ATTR_MEMBER_EXPIRY: Uuid = uuid!("00000000-0000-0000-0000-ffff00000250");
+pub const UUID_SCHEMA_ATTR_TEMPLATE_DEFAULT: Uuid = uuid!("00000000-0000-0000-0000-ffff00000251");
+pub const UUID_SCHEMA_ATTR_TEMPLATE_GROUP: Uuid = uuid!("00000000-0000-0000-0000-ffff00000252");
+pub const UUID_SCHEMA_CLASS_CREATION_TEMPLATE: Uuid = uuid!("00000000-0000-0000-0000-ffff00000253");
+pub const UUID_SCHEMA_ATTR_WEBHOOK_URL: Uuid = uuid!("00000000-0000-0000-0000-ffff00000254");
+pub const UUID_SCHEMA_ATTR_WEBHOOK_FILTER: Uuid = uuid!("00000000-0000-0000-0000-ffff00000255");
+pub const UUID_SCHEMA_ATTR_WEBHOOK_EVENT: Uuid = uuid!("00000000-0000-0000-0000-ffff00000256");
+pub const UUID_SCHEMA_ATTR_WEBHOOK_SECRET: Uuid = uuid!("00000000-0000-0000-0000-ffff00000257");
+pub const UUID_SCHEMA_CLASS_WEBHOOK_SUBSCRIPTION: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000258");
+pub const UUID_SCHEMA_ATTR_AUTH_PASSWORD_HISTORY_LENGTH: Uuid =
+    uuid!("00000000-0000-0000-0000-ffff00000259");
+pub const UUID_SCHEMA_ATTR_WEBHOOK_AUTHOR: Uuid = uuid!("00000000-0000-0000-0000-ffff0000025a");
 
 // =====
 // Incorrectly name spaced.
@@ -532,6 +604,16 @@ pub const UUID_INTERNAL_SESSION_ID: Uuid = uuid!("00000000-0000-0000-0000-ffffff
 pub const UUID_INTERNAL_ACCOUNT_REQUEST: Uuid = uuid!("00000000-0000-0000-0000-ffffff000084");
 pub const UUID_INTERNAL_MESSAGE_QUEUE: Uuid = uuid!("00000000-0000-0000-0000-ffffff000085");
 
+pub const UUID_IDM_ACP_AUTOMOUNT_READ: Uuid = uuid!("00000000-0000-0000-0000-ffffff000086");
+pub const UUID_IDM_ACP_AUTOMOUNT_MANAGE: Uuid = uuid!("00000000-0000-0000-0000-ffffff000087");
+pub const UUID_IDM_ACP_GROUP_RADIUS_MANAGE: Uuid = uuid!("00000000-0000-0000-0000-ffffff000088");
+pub const UUID_IDM_ACP_AUDIT_READ: Uuid = uuid!("00000000-0000-0000-0000-ffffff000089");
+pub const UUID_IDM_ACP_SELF_WRITE_POLICY: Uuid = uuid!("00000000-0000-0000-0000-ffffff00008a");
+pub const UUID_IDM_ACP_CREATION_TEMPLATE_MANAGE: Uuid =
+    uuid!("00000000-0000-0000-0000-ffffff00008b");
+pub const UUID_IDM_ACP_WEBHOOK_SUBSCRIPTION_MANAGE: Uuid =
+    uuid!("00000000-0000-0000-0000-ffffff00008c");
+
 // End of system ranges
 pub const UUID_DOES_NOT_EXIST: Uuid = uuid!("00000000-0000-0000-0000-fffffffffffe");
 pub const UUID_ANONYMOUS: Uuid = uuid!("00000000-0000-0000-0000-ffffffffffff");
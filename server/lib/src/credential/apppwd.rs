@@ -3,6 +3,7 @@ use crate::prelude::*;
 use kanidm_proto::internal::OperationError;
 use std::cmp::Ordering;
 use std::fmt;
+use time::OffsetDateTime;
 
 #[derive(Clone)]
 pub struct ApplicationPassword {
@@ -10,6 +11,9 @@ pub struct ApplicationPassword {
     pub(crate) application: Uuid,
     pub(crate) label: String,
     pub(crate) password: Password,
+    /// When this application password was generated. Passwords that predate this
+    /// being recorded have no creation time.
+    pub(crate) created: Option<OffsetDateTime>,
 }
 
 impl fmt::Debug for ApplicationPassword {
@@ -18,6 +22,7 @@ impl fmt::Debug for ApplicationPassword {
             .field("uuid", &self.uuid)
             .field("application", &self.application)
             .field("label", &self.label)
+            .field("created", &self.created)
             .finish()
     }
 }
@@ -28,6 +33,7 @@ impl ApplicationPassword {
         label: &str,
         cleartext: &str,
         policy: &CryptoPolicy,
+        created: OffsetDateTime,
     ) -> Result<ApplicationPassword, OperationError> {
         let pw = Password::new(policy, cleartext).map_err(|e| {
             error!(crypto_err = ?e);
@@ -38,6 +44,7 @@ impl ApplicationPassword {
             application,
             label: label.to_string(),
             password: pw,
+            created: Some(created),
         };
         Ok(ap)
     }
@@ -0,0 +1,134 @@
+//! The history of an account's recent passwords. This is used to prevent a password
+//! from being reused within a bounded number of changes.
+//!
+//! The history is stored as a single private (non-replicated) binary value on the
+//! account, ordered from most to least recent. Each element is the salted hash of the
+//! password, so imported hashes participate as long as we are able to verify them.
+
+use crate::be::dbvalue::DbPasswordV1;
+use crate::credential::Password;
+use crate::prelude::*;
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Default)]
+pub struct PasswordHistory {
+    passwords: VecDeque<Password>,
+}
+
+impl PasswordHistory {
+    pub(crate) fn from_bytes(data: &[u8]) -> Result<Self, OperationError> {
+        let db_passwords: Vec<DbPasswordV1> = serde_json::from_slice(data).map_err(|err| {
+            error!(?err, "Unable to deserialise password history");
+            OperationError::SerdeJsonError
+        })?;
+
+        let passwords = db_passwords
+            .into_iter()
+            .map(Password::try_from)
+            .collect::<Result<_, _>>()
+            .map_err(|_| {
+                error!("Invalid password in password history");
+                OperationError::InvalidValueState
+            })?;
+
+        Ok(PasswordHistory { passwords })
+    }
+
+    pub(crate) fn to_bytes(&self) -> Result<Vec<u8>, OperationError> {
+        let db_passwords: Vec<DbPasswordV1> = self
+            .passwords
+            .iter()
+            .map(|pw| pw.to_dbpasswordv1())
+            .collect();
+
+        serde_json::to_vec(&db_passwords).map_err(|err| {
+            error!(?err, "Unable to serialise password history");
+            OperationError::SerdeJsonError
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.passwords.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.passwords.is_empty()
+    }
+
+    /// Check if this cleartext matches the current password, or any of the `limit` most
+    /// recent passwords in the history. Hashes that can't be verified are skipped.
+    pub(crate) fn contains(
+        &self,
+        current: Option<&Password>,
+        cleartext: &str,
+        limit: usize,
+    ) -> bool {
+        current
+            .into_iter()
+            .chain(
+                self.passwords
+                    .iter()
+                    .take(limit)
+                    .filter(|pw| Some(*pw) != current),
+            )
+            .any(|pw| {
+                pw.verify(cleartext).unwrap_or_else(|err| {
+                    debug!(?err, "Unable to verify password from history, skipping");
+                    false
+                })
+            })
+    }
+
+    /// Record that the password changed from `previous` to `next`, trimming the history
+    /// so that it holds at most `limit` passwords.
+    pub(crate) fn record(&mut self, previous: Option<&Password>, next: &Password, limit: usize) {
+        // The previous password may have been imported, or set before history was kept.
+        if let Some(previous) = previous {
+            if !self.passwords.contains(previous) {
+                self.passwords.push_front(previous.clone());
+            }
+        }
+
+        if !self.passwords.contains(next) {
+            self.passwords.push_front(next.clone());
+        }
+
+        self.passwords.truncate(limit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PasswordHistory;
+    use crate::credential::{CryptoPolicy, Password};
+
+    #[test]
+    fn test_password_history_trim() {
+        let p = CryptoPolicy::minimum();
+        let passwords: Vec<_> = (0..4)
+            .map(|i| Password::new(&p, &format!("password {i}")).expect("Failed to hash"))
+            .collect();
+
+        let mut history = PasswordHistory::default();
+        history.record(None, &passwords[0], 3);
+        history.record(Some(&passwords[0]), &passwords[1], 3);
+        history.record(Some(&passwords[1]), &passwords[2], 3);
+        assert_eq!(history.len(), 3);
+        assert!(history.contains(Some(&passwords[2]), "password 0", 3));
+        // A shorter limit, such as from a relaxed account policy, ignores older passwords.
+        assert!(!history.contains(Some(&passwords[2]), "password 0", 2));
+
+        history.record(Some(&passwords[2]), &passwords[3], 3);
+        assert_eq!(history.len(), 3);
+        assert!(!history.contains(Some(&passwords[3]), "password 0", 3));
+        assert!(history.contains(Some(&passwords[3]), "password 1", 3));
+        assert!(history.contains(Some(&passwords[3]), "password 3", 3));
+
+        // The current password is always checked, even if it isn't in the history yet.
+        assert!(PasswordHistory::default().contains(Some(&passwords[0]), "password 0", 0));
+
+        let restored = PasswordHistory::from_bytes(&history.to_bytes().expect("Failed to encode"))
+            .expect("Failed to decode");
+        assert_eq!(restored.passwords, history.passwords);
+    }
+}
@@ -10,6 +10,7 @@ use webauthn_rs_core::proto::{Credential as WebauthnCredential, CredentialV3};
 use crate::be::dbvalue::{DbBackupCodeV1, DbCred};
 
 pub mod apppwd;
+pub mod history;
 pub mod softlock;
 pub mod totp;
 
@@ -573,16 +574,25 @@ impl Credential {
             }
             CredentialType::Webauthn(map) => {
                 let mut nmap = map.clone();
+                let mut changed = false;
                 nmap.values_mut().for_each(|pk| {
-                    pk.update_credential(auth_result);
+                    changed |= pk.update_credential(auth_result) == Some(true);
                 });
+                if !changed {
+                    return Ok(None);
+                }
                 CredentialType::Webauthn(nmap)
             }
             CredentialType::PasswordMfa(pw, totp, map, backup_code) => {
                 let mut nmap = map.clone();
+                let mut changed = false;
                 nmap.values_mut().for_each(|sk| {
-                    sk.update_credential(auth_result);
+                    changed |= sk.update_credential(auth_result) == Some(true);
                 });
+                // The authentication used a different credential, such as a passkey.
+                if !changed {
+                    return Ok(None);
+                }
                 CredentialType::PasswordMfa(pw.clone(), totp.clone(), nmap, backup_code.clone())
             }
         };
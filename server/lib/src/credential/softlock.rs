@@ -242,6 +242,14 @@ impl CredSoftLock {
         !matches!(self.state, LockState::Locked { .. })
     }
 
+    /// The number of failures recorded in the current cycle.
+    pub fn failure_count(&self) -> usize {
+        match self.state {
+            LockState::Init => 0,
+            LockState::Locked { count, .. } | LockState::Unlocked(count, _) => count,
+        }
+    }
+
     /// Document a failure of authentication at this time.
     pub fn record_failure(&mut self, ct: Duration) {
         let mut next_state = match self.state {
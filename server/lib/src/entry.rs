@@ -36,11 +36,13 @@ use crate::prelude::*;
 use crate::repl::cid::Cid;
 use crate::repl::entry::EntryChangeState;
 use crate::repl::proto::{ReplEntryV1, ReplIncrementalEntryV1};
-use crate::schema::{SchemaAttribute, SchemaClass, SchemaTransaction};
+use crate::schema::{
+    ClassKind, ReferenceDeletePolicy, SchemaAttribute, SchemaClass, SchemaTransaction, UniqueScope,
+};
 use crate::server::access::AccessEffectivePermission;
 use crate::value::{
-    ApiToken, CredentialType, IndexType, IntentTokenState, Oauth2Session, PartialValue, Session,
-    SyntaxType, Value,
+    ApiToken, CredentialType, IndexType, IntentTokenState, Oauth2Session, PartialValue,
+    PasskeyUsage, Session, SyntaxType, Value,
 };
 use crate::valueset::{self, ScimResolveStatus, ValueSet, ValueSetSpn};
 use compact_jwt::JwsEs256Signer;
@@ -50,9 +52,10 @@ use kanidm_proto::internal::ImageValue;
 use kanidm_proto::internal::{
     ConsistencyError, Filter as ProtoFilter, OperationError, SchemaError, UiHint,
 };
-use kanidm_proto::scim_v1::server::ScimEffectiveAccess;
+use kanidm_proto::scim_v1::server::{ScimAttributeMeta, ScimEffectiveAccess};
 use kanidm_proto::v1::Entry as ProtoEntry;
 use ldap3_proto::simple::{LdapPartialAttribute, LdapSearchResultEntry};
+use serde::Serialize;
 use std::cmp::Ordering;
 use std::collections::{BTreeMap as Map, BTreeMap, BTreeSet};
 use std::sync::Arc;
@@ -159,6 +162,8 @@ pub struct EntrySealed {
 pub struct EntryReduced {
     uuid: Uuid,
     effective_access: Option<Box<AccessEffectivePermission>>,
+    // The cids of the last changes to the attributes that were allowed to be seen.
+    attr_cids: BTreeMap<Attribute, Cid>,
 }
 
 // One day this is going to be Map<Attribute, ValueSet> - @yaleman
@@ -537,6 +542,44 @@ impl From<&SchemaAttribute> for EntryInitNew {
             ],
         );
 
+        if let Some(group) = &s.exclusion_group {
+            attrs.insert(Attribute::ExclusionGroup, vs_iutf8![group.as_str()]);
+        }
+
+        if let Some(allowed) = &s.allowed_values {
+            let mut values = allowed.iter().cloned();
+            if let Some(first) = values.next() {
+                let mut vs = ValueSetUtf8::new(first);
+                values.for_each(|v| {
+                    vs.push(v);
+                });
+                attrs.insert(Attribute::AllowedValue, vs as ValueSet);
+            }
+        }
+
+        match &s.unique_scope {
+            UniqueScope::All => {}
+            UniqueScope::Classes(classes) => {
+                if let Some(vs) = ValueSetIutf8::from_iter(classes.iter().map(|c| c.as_str())) {
+                    attrs.insert(Attribute::UniqueScope, vs as ValueSet);
+                }
+            }
+            UniqueScope::PerSyncParent => {
+                attrs.insert(
+                    Attribute::UniqueScope,
+                    vs_iutf8![UniqueScope::PER_SYNC_PARENT],
+                );
+            }
+        }
+
+        if s.on_delete != ReferenceDeletePolicy::RemoveReference {
+            attrs.insert(Attribute::OnDelete, vs_iutf8![s.on_delete.as_str()]);
+        }
+
+        if s.audit {
+            attrs.insert(Attribute::Audit, vs_bool![true]);
+        }
+
         // Insert stuff.
 
         Entry {
@@ -585,6 +628,34 @@ impl From<&SchemaClass> for EntryInitNew {
             attrs.insert(Attribute::SystemExcludes, vs);
         }
 
+        let vs_attr_excludes =
+            ValueSetIutf8::from_iter(s.attr_excludes.iter().map(|attr| attr.as_str()));
+        if let Some(vs) = vs_attr_excludes {
+            attrs.insert(Attribute::AttrExcludes, vs);
+        }
+
+        let requires_one_of: Vec<String> = s
+            .requires_one_of
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|attr| attr.as_str())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .collect();
+        let vs_requires_one_of =
+            ValueSetIutf8::from_iter(requires_one_of.iter().map(|group| group.as_str()));
+        if let Some(vs) = vs_requires_one_of {
+            attrs.insert(Attribute::RequiresOneOf, vs);
+        }
+
+        // Auxiliary is the default, so only other kinds need to be recorded.
+        if s.kind != ClassKind::default() {
+            attrs.insert(Attribute::ClassKind, vs_iutf8![s.kind.as_str()]);
+        }
+
         let vs_systemsupplements =
             ValueSetIutf8::from_iter(s.systemsupplements.iter().map(|sm| sm.as_str()));
         if let Some(vs) = vs_systemsupplements {
@@ -1145,6 +1216,16 @@ impl<STATE> Entry<EntryInvalid, STATE> {
         self.get_ava_mut(attr).and_then(|vs| vs.as_refer_set_mut())
     }
 
+    /// Access the set of verified mail addresses in a directly mutable form. The
+    /// valueset validates that these remain a subset of the addresses present.
+    pub(crate) fn get_ava_mail_verified_mut<A: AsRef<Attribute>>(
+        &mut self,
+        attr: A,
+    ) -> Option<&mut BTreeSet<String>> {
+        self.get_ava_mut(attr)
+            .and_then(|vs| vs.as_email_verified_set_mut())
+    }
+
     pub(crate) fn get_ava_mut<A: AsRef<Attribute>>(&mut self, attr: A) -> Option<&mut ValueSet> {
         let attr_ref = attr.as_ref();
         self.valid.ecstate.change_ava(&self.valid.cid, attr_ref);
@@ -1189,6 +1270,12 @@ impl Entry<EntryInvalid, EntryCommitted> {
         // This will put the modify ahead of the recycle transition.
         self.add_ava(Attribute::Class, EntryClass::Recycled.into());
 
+        // Password history is never needed again once an account is deleted, even if
+        // it is later revived.
+        if self.attrs.contains_key(&Attribute::PasswordHistory) {
+            self.purge_ava(Attribute::PasswordHistory);
+        }
+
         // Change state repl doesn't need this flag
         // self.valid.ecstate.recycled(&self.valid.cid);
 
@@ -1362,7 +1449,6 @@ impl<STATE> Entry<EntrySealed, STATE> {
 }
 
 impl Entry<EntrySealed, EntryCommitted> {
-    #[cfg(test)]
     pub(crate) fn get_last_changed(&self) -> Cid {
         self.valid.ecstate.get_max_cid().clone()
     }
@@ -1394,6 +1480,15 @@ impl Entry<EntrySealed, EntryCommitted> {
         self.add_ava_int(Attribute::Claim, Value::new_iutf8(value));
     }
 
+    /// A view of this entry as it was before it was placed in the recycle bin. Like a claim
+    /// this can NOT be persisted to disk, this is only used during a single Event session.
+    pub(crate) fn into_revived_view(mut self, cid: &Cid) -> Self {
+        if let Some(classes) = self.attrs.get_mut(&Attribute::Class) {
+            classes.remove(&EntryClass::Recycled.into(), cid);
+        }
+        self
+    }
+
     pub fn compare(&self, rhs: &Entry<EntrySealed, EntryCommitted>) -> bool {
         compare_attrs(&self.attrs, &rhs.attrs)
     }
@@ -1927,6 +2022,12 @@ impl Entry<EntrySealed, EntryCommitted> {
             valid: EntryReduced {
                 uuid: self.valid.uuid,
                 effective_access: None,
+                attr_cids: self
+                    .valid
+                    .ecstate
+                    .attr_cid_iter()
+                    .map(|(attr, cid)| (attr.clone(), cid.clone()))
+                    .collect(),
             },
             state: self.state,
             attrs: self.attrs,
@@ -1953,9 +2054,19 @@ impl Entry<EntrySealed, EntryCommitted> {
             })
             .collect();
 
+        // Only disclose when attributes changed if they may be seen.
+        let attr_cids = self
+            .valid
+            .ecstate
+            .attr_cid_iter()
+            .filter(|(attr, _)| allowed_attrs.contains(*attr))
+            .map(|(attr, cid)| (attr.clone(), cid.clone()))
+            .collect();
+
         let valid = EntryReduced {
             uuid: self.valid.uuid,
             effective_access,
+            attr_cids,
         };
         let state = self.state.clone();
 
@@ -2012,6 +2123,23 @@ impl Entry<EntrySealed, EntryCommitted> {
             .ecstate
             .verify(schema, &self.attrs, self.state.id, results);
     }
+
+    /// Check that this entry conforms to `schema`, such as when it has been read from a
+    /// backup rather than written by this server.
+    pub(crate) fn validate_schema(
+        &self,
+        schema: &dyn SchemaTransaction,
+    ) -> Result<(), SchemaError> {
+        let entry: Entry<EntryValid, EntryCommitted> = Entry {
+            valid: EntryValid {
+                uuid: self.valid.uuid,
+                ecstate: self.valid.ecstate.clone(),
+            },
+            state: self.state.clone(),
+            attrs: self.attrs.clone(),
+        };
+        entry.validate(schema)
+    }
 }
 
 impl<STATE> Entry<EntryValid, STATE> {
@@ -2098,19 +2226,15 @@ impl<STATE> Entry<EntryValid, STATE> {
             return Err(SchemaError::SupplementsNotSatisfied(supplements_classes));
         }
 
-        let excludes_classes: Vec<_> = classes
+        let entry_class_names: BTreeSet<&str> = entry_classes.iter().map(|s| s.as_str()).collect();
+
+        let invalid_excludes: Vec<String> = classes
             .iter()
-            .flat_map(|cls| cls.systemexcludes.iter().chain(cls.excludes.iter()))
+            .filter_map(|cls| cls.compatible_with(&entry_class_names).err())
+            .flatten()
+            .map(|class| class.to_string())
             .collect();
 
-        let mut invalid_excludes = Vec::with_capacity(0);
-
-        excludes_classes.iter().for_each(|class| {
-            if entry_classes.contains(class.as_str()) {
-                invalid_excludes.push(class.to_string())
-            }
-        });
-
         if !invalid_excludes.is_empty() {
             admin_warn!(
                 "Validation error, the following excluded classes are present - {:?}",
@@ -2119,6 +2243,49 @@ impl<STATE> Entry<EntryValid, STATE> {
             return Err(SchemaError::ExcludesNotSatisfied(invalid_excludes));
         }
 
+        let invalid_attrs: Vec<Attribute> = classes
+            .iter()
+            .flat_map(|cls| cls.attr_excludes.iter())
+            .filter(|attr| self.attrs.contains_key(*attr))
+            .cloned()
+            .collect();
+
+        if !invalid_attrs.is_empty() {
+            admin_warn!(
+                "Validation error, the following attributes are excluded by a present class - {:?}",
+                invalid_attrs
+            );
+            return Err(SchemaError::AttributeExcludedByClass(invalid_attrs));
+        }
+
+        if let Some(group) = classes
+            .iter()
+            .flat_map(|cls| cls.requires_one_of.iter())
+            .find(|group| !group.iter().any(|attr| self.attrs.contains_key(attr)))
+        {
+            admin_warn!(
+                "Validation error, at least one of the following attributes must be present - {:?}",
+                group
+            );
+            return Err(SchemaError::RequiresOneOfNotSatisfied(group.clone()));
+        }
+
+        for group_attrs in schema.get_exclusion_groups().values() {
+            let present: Vec<Attribute> = group_attrs
+                .iter()
+                .filter(|attr| self.attrs.contains_key(*attr))
+                .cloned()
+                .collect();
+
+            if present.len() > 1 {
+                admin_warn!(
+                    "Validation error, the following mutually exclusive attributes are present - {:?}",
+                    present
+                );
+                return Err(SchemaError::MutuallyExclusiveAttributes(present));
+            }
+        }
+
         // What this is really doing is taking a set of classes, and building an
         // "overall" class that describes this exact object for checking. IE we
         // build a super must/may set from the small class must/may sets.
@@ -2346,6 +2513,11 @@ where
         &self.valid.ecstate
     }
 
+    /// The cid of the last change to this attribute, including its removal.
+    pub fn get_ava_last_modified<A: AsRef<Attribute>>(&self, attr: A) -> Option<&Cid> {
+        self.valid.ecstate.get_attr_cid(attr.as_ref())
+    }
+
     /// Determine if any attribute of this entry changed excluding the attribute named.
     /// This allows for detection of entry changes unless the change was to a specific
     /// attribute.
@@ -2400,6 +2572,29 @@ impl Entry<EntryReduced, EntryCommitted> {
         self.valid.uuid
     }
 
+    /// The cid of the last change to this attribute, if the attribute may be seen.
+    pub fn get_ava_last_modified<A: AsRef<Attribute>>(&self, attr: A) -> Option<&Cid> {
+        self.valid.attr_cids.get(attr.as_ref())
+    }
+
+    /// When each attribute that may be seen was last modified, for the SCIM
+    /// `ext_attr_meta` extension.
+    pub fn to_scim_attr_meta(&self) -> BTreeMap<Attribute, ScimAttributeMeta> {
+        self.valid
+            .attr_cids
+            .iter()
+            .map(|(attr, cid)| {
+                (
+                    attr.clone(),
+                    ScimAttributeMeta {
+                        last_modified: OffsetDateTime::UNIX_EPOCH + cid.ts,
+                        version: cid.to_string(),
+                    },
+                )
+            })
+            .collect()
+    }
+
     /// Transform this reduced entry into a JSON protocol form that can be sent to clients.
     pub fn to_pe(&self, qs: &mut QueryServerReadTransaction) -> Result<ProtoEntry, OperationError> {
         // Turn values -> Strings.
@@ -2473,6 +2668,7 @@ impl Entry<EntryReduced, EntryCommitted> {
                 meta: None,
             },
             ext_access_check,
+            ext_attr_meta: None,
             attrs,
         })
     }
@@ -2499,6 +2695,8 @@ impl Entry<EntryReduced, EntryCommitted> {
         let attr_map: Result<Map<&str, Vec<Vec<u8>>>, _> = self
             .attrs
             .iter()
+            // Password history must never be disclosed, even in its hashed form.
+            .filter(|(k, _)| **k != Attribute::PasswordHistory)
             .map(|(k, vs)| {
                 qs.resolve_valueset_ldap(vs, basedn)
                     .map(|pvs| (k.as_str(), pvs))
@@ -2668,6 +2866,60 @@ impl<VALID, STATE> Entry<VALID, STATE> {
         self.attrs.iter()
     }
 
+    /// Compare this entry to `other`, which is considered to be the newer state of the
+    /// entry. Every attribute present on either side is described in the result.
+    pub fn diff<V2, S2>(&self, other: &Entry<V2, S2>) -> EntryDiff {
+        let attrs = self
+            .attrs
+            .keys()
+            .chain(other.attrs.keys().filter(|a| !self.attrs.contains_key(*a)))
+            .map(|attr| (attr.clone(), self.diff_ava(other, attr)))
+            .collect();
+
+        EntryDiff { attrs }
+    }
+
+    /// Compare the values of a single attribute of this entry to those of `other`, which
+    /// is considered to be the newer state of the entry.
+    pub fn diff_ava<V2, S2, A: AsRef<Attribute>>(
+        &self,
+        other: &Entry<V2, S2>,
+        attr: A,
+    ) -> AttributeDiff {
+        let pre_vs = self.get_ava_set(attr.as_ref());
+        let post_vs = other.get_ava_set(attr.as_ref());
+
+        let mut diff = AttributeDiff::default();
+
+        if let Some(pre) = pre_vs {
+            for pv in pre.to_partialvalue_iter() {
+                if post_vs.map(|post| post.contains(&pv)).unwrap_or_default() {
+                    diff.unchanged.insert(pv);
+                } else {
+                    diff.removed.insert(pv);
+                }
+            }
+        }
+
+        if let Some(post) = post_vs {
+            diff.added.extend(
+                post.to_partialvalue_iter()
+                    .filter(|pv| !diff.unchanged.contains(pv)),
+            );
+        }
+
+        // Partial values only identify a value, so a single value can have its content
+        // replaced without its partial value changing, for example a credential that is
+        // updated in place. Compare the full values in this case.
+        if let (Some(pre), Some(post)) = (pre_vs, post_vs) {
+            if pre.len() == 1 && post.len() == 1 && pre != post {
+                diff.replaced = true;
+            }
+        }
+
+        diff
+    }
+
     /// Return a reference to the current set of values that are associated to this attribute.
     pub fn get_ava_set<A: AsRef<Attribute>>(&self, attr: A) -> Option<&ValueSet> {
         self.attrs.get(attr.as_ref())
@@ -2900,6 +3152,11 @@ impl<VALID, STATE> Entry<VALID, STATE> {
         self.get_ava_set(attr).and_then(|vs| vs.as_email_str_iter())
     }
 
+    pub fn get_ava_mail_verified<A: AsRef<Attribute>>(&self, attr: A) -> Option<&BTreeSet<String>> {
+        self.get_ava_set(attr)
+            .and_then(|vs| vs.as_email_verified_set())
+    }
+
     /// Return a single protocol filter, if valid to transform this value.
     pub fn get_ava_single_protofilter<A: AsRef<Attribute>>(&self, attr: A) -> Option<&ProtoFilter> {
         self.get_ava_set(attr)
@@ -2935,6 +3192,33 @@ impl<VALID, STATE> Entry<VALID, STATE> {
             .and_then(|vs| vs.as_application_password_map())
     }
 
+    /// Get the set of locked credentials on this account, and when they unlock.
+    pub fn get_ava_credential_lockouts<A: AsRef<Attribute>>(
+        &self,
+        attr: A,
+    ) -> Option<&BTreeMap<Uuid, OffsetDateTime>> {
+        self.get_ava_set(attr)
+            .and_then(|vs| vs.as_credential_lockout_map())
+    }
+
+    /// Get when each passkey on this account was last used.
+    pub fn get_ava_passkey_usage<A: AsRef<Attribute>>(
+        &self,
+        attr: A,
+    ) -> Option<&BTreeMap<Uuid, PasskeyUsage>> {
+        self.get_ava_set(attr)
+            .and_then(|vs| vs.as_passkey_usage_map())
+    }
+
+    /// Get the members of this group that expire, and when they are removed.
+    pub fn get_ava_member_expiry<A: AsRef<Attribute>>(
+        &self,
+        attr: A,
+    ) -> Option<&BTreeMap<Uuid, OffsetDateTime>> {
+        self.get_ava_set(attr)
+            .and_then(|vs| vs.as_member_expiry_map())
+    }
+
     /// Return a single security principle name, if valid to transform this value.
     pub(crate) fn generate_spn(&self, domain_name: &str) -> Option<ValueSet> {
         if let Some(name) = self.get_ava_single_iname(Attribute::Name) {
@@ -3406,6 +3690,75 @@ impl<VALID, STATE> PartialEq for Entry<VALID, STATE> {
     }
 }
 
+/// The changes to the values of a single attribute between two states of an entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AttributeDiff {
+    pub added: BTreeSet<PartialValue>,
+    pub removed: BTreeSet<PartialValue>,
+    #[serde(skip)]
+    pub unchanged: BTreeSet<PartialValue>,
+    /// The attribute held a single value in both states, and the content of that value
+    /// changed. This may be true even when `added` and `removed` are empty.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub replaced: bool,
+}
+
+impl AttributeDiff {
+    pub fn is_changed(&self) -> bool {
+        self.replaced || !self.added.is_empty() || !self.removed.is_empty()
+    }
+
+    /// All values that were either added or removed.
+    pub fn changed_values(&self) -> impl Iterator<Item = &PartialValue> {
+        self.added.iter().chain(self.removed.iter())
+    }
+}
+
+/// The changes between two states of an entry, as created by [`Entry::diff`]. When
+/// serialised only the attributes that changed are included, making this suitable for
+/// audit output.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntryDiff {
+    attrs: BTreeMap<Attribute, AttributeDiff>,
+}
+
+impl EntryDiff {
+    pub fn get<A: AsRef<Attribute>>(&self, attr: A) -> Option<&AttributeDiff> {
+        self.attrs.get(attr.as_ref())
+    }
+
+    pub fn is_changed(&self) -> bool {
+        self.attrs.values().any(|diff| diff.is_changed())
+    }
+
+    pub fn is_attr_changed<A: AsRef<Attribute>>(&self, attr: A) -> bool {
+        self.get(attr)
+            .map(|diff| diff.is_changed())
+            .unwrap_or_default()
+    }
+
+    /// Iterate over the attributes that changed.
+    pub fn changed(&self) -> impl Iterator<Item = (&Attribute, &AttributeDiff)> {
+        self.attrs.iter().filter(|(_, diff)| diff.is_changed())
+    }
+
+    /// Remove attributes that are not replicated, such as those that are derived locally
+    /// on each server, so that only changes that will be replicated remain.
+    pub fn skip_non_replicated(mut self, schema: &dyn SchemaTransaction) -> Self {
+        self.attrs.retain(|attr, _| schema.is_replicated(attr));
+        self
+    }
+}
+
+impl Serialize for EntryDiff {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_map(self.changed())
+    }
+}
+
 /// This is a helper function to create an entry from an iterator of attribute-value pairs. This is a replacement for the old `entry_init!`` macro
 pub(crate) fn entry_init_fn<T>(args: T) -> EntryInitNew
 where
@@ -3899,4 +4252,107 @@ mod tests {
                 == Some(Ok("spn=renameperson@example.com".to_string()))
         );
     }
+
+    #[test]
+    fn test_entry_diff_multivalue() {
+        let u1 = Uuid::new_v4();
+        let u2 = Uuid::new_v4();
+        let u3 = Uuid::new_v4();
+
+        let mut e1: Entry<EntryInit, EntryNew> = Entry::new();
+        e1.add_ava(Attribute::Member, Value::Refer(u1));
+        e1.add_ava(Attribute::Member, Value::Refer(u2));
+
+        let mut e2: Entry<EntryInit, EntryNew> = Entry::new();
+        e2.add_ava(Attribute::Member, Value::Refer(u2));
+        e2.add_ava(Attribute::Member, Value::Refer(u3));
+
+        let diff = e1.diff(&e2);
+        assert!(diff.is_changed());
+
+        let member = diff.get(Attribute::Member).expect("No diff for member");
+        assert_eq!(member.added, Set::from([PartialValue::Refer(u3)]));
+        assert_eq!(member.removed, Set::from([PartialValue::Refer(u1)]));
+        assert_eq!(member.unchanged, Set::from([PartialValue::Refer(u2)]));
+        assert!(!member.replaced);
+
+        // The reverse diff swaps the added and removed values.
+        let member = e2.diff_ava(&e1, Attribute::Member);
+        assert_eq!(member.added, Set::from([PartialValue::Refer(u1)]));
+        assert_eq!(member.removed, Set::from([PartialValue::Refer(u3)]));
+    }
+
+    #[test]
+    fn test_entry_diff_purge() {
+        let mut e1: Entry<EntryInit, EntryNew> = Entry::new();
+        e1.add_ava(Attribute::Name, Value::new_iname("testperson"));
+        e1.add_ava(Attribute::Description, Value::new_utf8s("a"));
+        e1.add_ava(Attribute::Description, Value::new_utf8s("b"));
+
+        let e1 = e1.into_invalid_new();
+        let mut e2 = e1.clone();
+        e2.purge_ava(Attribute::Description);
+
+        let diff = e1.diff(&e2);
+        assert!(diff.is_attr_changed(Attribute::Description));
+        assert!(!diff.is_attr_changed(Attribute::Name));
+
+        let description = diff
+            .get(Attribute::Description)
+            .expect("No diff for description");
+        assert!(description.added.is_empty());
+        assert_eq!(description.removed.len(), 2);
+        assert!(description.unchanged.is_empty());
+
+        // Only the changed attribute is included in the audit output.
+        let audit = serde_json::to_value(&diff).expect("Failed to serialise diff");
+        let audit = audit.as_object().expect("Diff is not a map");
+        assert_eq!(audit.len(), 1);
+        assert!(audit.contains_key(Attribute::Description.as_str()));
+    }
+
+    #[test]
+    fn test_entry_diff_identical() {
+        let mut e1: Entry<EntryInit, EntryNew> = Entry::new();
+        e1.add_ava(Attribute::Name, Value::new_iname("testperson"));
+        e1.add_ava(Attribute::Member, Value::Refer(Uuid::new_v4()));
+        let e2 = e1.clone();
+
+        let diff = e1.diff(&e2);
+        assert!(!diff.is_changed());
+        assert_eq!(diff.changed().count(), 0);
+        assert_eq!(
+            diff.get(Attribute::Name).map(|d| d.unchanged.len()),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_entry_diff_single_value_replace() {
+        let mut e1: Entry<EntryInit, EntryNew> = Entry::new();
+        e1.add_ava(Attribute::Name, Value::new_iname("testperson"));
+
+        let mut e2: Entry<EntryInit, EntryNew> = Entry::new();
+        e2.add_ava(Attribute::Name, Value::new_iname("renameperson"));
+        e2.add_ava(Attribute::Description, Value::new_utf8s("new"));
+
+        let diff = e1.diff(&e2);
+        let name = diff.get(Attribute::Name).expect("No diff for name");
+        assert!(name.replaced);
+        assert_eq!(
+            name.added,
+            Set::from([PartialValue::new_iname("renameperson")])
+        );
+        assert_eq!(
+            name.removed,
+            Set::from([PartialValue::new_iname("testperson")])
+        );
+
+        // An attribute that was only present on the new entry is added, not replaced.
+        let description = diff
+            .get(Attribute::Description)
+            .expect("No diff for description");
+        assert!(!description.replaced);
+        assert_eq!(description.added.len(), 1);
+    }
 }
@@ -20,8 +20,8 @@ use std::collections::BTreeSet;
 use std::sync::Arc;
 
 use kanidm_proto::internal::{
-    CreateRequest, DeleteRequest, ModifyList as ProtoModifyList, ModifyRequest, OperationError,
-    SearchRequest, SearchResponse,
+    BatchCreateRequest, CreateRequest, DeleteRequest, ModifyList as ProtoModifyList, ModifyRequest,
+    OperationError, SearchRequest, SearchResponse,
 };
 use kanidm_proto::v1::{Entry as ProtoEntry, WhoamiResponse};
 use ldap3_proto::simple::LdapFilter;
@@ -32,6 +32,7 @@ use crate::filter::{Filter, FilterInvalid, FilterValid};
 use crate::modify::{ModifyInvalid, ModifyList, ModifyValid};
 use crate::prelude::*;
 use crate::schema::SchemaTransaction;
+use crate::server::expiry::AccountExpiryNotice;
 use crate::value::PartialValue;
 
 #[derive(Debug)]
@@ -80,12 +81,36 @@ pub struct SearchEvent {
     pub effective_access_check: bool,
 }
 
+/// Resolve a requested attribute projection against the schema. Unknown attributes are
+/// ignored, but a projection that resolves to nothing is rejected.
+fn normalise_search_attrs(
+    attrs: Option<&[String]>,
+    qs: &QueryServerReadTransaction,
+) -> Result<Option<BTreeSet<Attribute>>, OperationError> {
+    let r_attrs: Option<BTreeSet<Attribute>> = attrs.map(|vs| {
+        vs.iter()
+            .filter_map(|a| qs.get_schema().normalise_attr_if_exists(a.as_str()))
+            .collect()
+    });
+
+    if let Some(s) = &r_attrs {
+        if s.is_empty() {
+            request_error!("EmptyRequest for attributes");
+            return Err(OperationError::EmptyRequest);
+        }
+    }
+
+    Ok(r_attrs)
+}
+
 impl SearchEvent {
     pub fn from_message(
         ident: Identity,
         req: &SearchRequest,
+        attrs: Option<&[String]>,
         qs: &mut QueryServerReadTransaction,
     ) -> Result<Self, OperationError> {
+        let r_attrs = normalise_search_attrs(attrs, qs)?;
         let f = Filter::from_ro(&ident, &req.filter, qs)?;
         // We do need to do this twice to account for the ignore_hidden
         // changes.
@@ -97,9 +122,7 @@ impl SearchEvent {
             ident,
             filter,
             filter_orig,
-            // We can't get this from the SearchMessage because it's annoying with the
-            // current macro design.
-            attrs: None,
+            attrs: r_attrs,
             effective_access_check: false,
         })
     }
@@ -110,18 +133,7 @@ impl SearchEvent {
         attrs: Option<&[String]>,
         qs: &mut QueryServerReadTransaction,
     ) -> Result<Self, OperationError> {
-        let r_attrs: Option<BTreeSet<Attribute>> = attrs.map(|vs| {
-            vs.iter()
-                .filter_map(|a| qs.get_schema().normalise_attr_if_exists(a.as_str()))
-                .collect()
-        });
-
-        if let Some(s) = &r_attrs {
-            if s.is_empty() {
-                request_error!("EmptyRequest for attributes");
-                return Err(OperationError::EmptyRequest);
-            }
-        }
+        let r_attrs = normalise_search_attrs(attrs, qs)?;
 
         let filter_orig = filter.validate(qs.get_schema()).map_err(|e| {
             request_error!(?e, "filter schema violation");
@@ -394,6 +406,53 @@ impl CreateEvent {
     }
 }
 
+#[derive(Debug)]
+pub struct BatchCreateEvent {
+    pub ident: Identity,
+    /// The entries of the batch, or the error from converting each of them, in the
+    /// order they were requested.
+    pub entries: Vec<Result<Entry<EntryInit, EntryNew>, OperationError>>,
+    /// If any entry fails, none of the entries are created.
+    pub all_or_nothing: bool,
+}
+
+impl BatchCreateEvent {
+    pub fn from_message(
+        ident: Identity,
+        req: &BatchCreateRequest,
+        qs: &mut QueryServerWriteTransaction,
+    ) -> Self {
+        // Conversion errors are reported against the entry, rather than failing the batch.
+        let entries = req
+            .entries
+            .iter()
+            .map(|e| Entry::from_proto_entry(e, qs))
+            .collect();
+
+        BatchCreateEvent {
+            ident,
+            entries,
+            all_or_nothing: req.all_or_nothing,
+        }
+    }
+
+    pub fn new_impersonate_identity(
+        ident: Identity,
+        entries: Vec<Entry<EntryInit, EntryNew>>,
+        all_or_nothing: bool,
+    ) -> Self {
+        BatchCreateEvent {
+            ident,
+            entries: entries.into_iter().map(Ok).collect(),
+            all_or_nothing,
+        }
+    }
+
+    pub fn new_internal(entries: Vec<Entry<EntryInit, EntryNew>>, all_or_nothing: bool) -> Self {
+        Self::new_impersonate_identity(Identity::from_internal(), entries, all_or_nothing)
+    }
+}
+
 #[derive(Debug)]
 pub struct ExistsEvent {
     pub ident: Identity,
@@ -520,6 +579,10 @@ pub struct ModifyEvent {
     // This is the original filter, for the purpose of ACI checking.
     pub filter_orig: Filter<FilterValid>,
     pub modlist: ModifyList<ModifyValid>,
+    /// If set, the modification is only applied if every matching entry was last changed
+    /// at this cid. This allows a client to detect that an entry was changed by another
+    /// client since it was read.
+    pub expected_cid: Option<Cid>,
 }
 
 impl ModifyEvent {
@@ -542,6 +605,7 @@ impl ModifyEvent {
             filter,
             filter_orig,
             modlist,
+            expected_cid: None,
         })
     }
 
@@ -570,6 +634,7 @@ impl ModifyEvent {
             filter,
             filter_orig,
             modlist,
+            expected_cid: None,
         })
     }
 
@@ -592,6 +657,7 @@ impl ModifyEvent {
             filter,
             filter_orig,
             modlist,
+            expected_cid: None,
         })
     }
 
@@ -619,6 +685,7 @@ impl ModifyEvent {
             filter,
             filter_orig,
             modlist,
+            expected_cid: None,
         })
     }
 
@@ -628,6 +695,7 @@ impl ModifyEvent {
             filter: filter.clone(),
             filter_orig: filter,
             modlist,
+            expected_cid: None,
         }
     }
 
@@ -643,6 +711,7 @@ impl ModifyEvent {
             filter: filter.clone().into_valid(),
             filter_orig: filter.into_valid(),
             modlist: modlist.into_valid(),
+            expected_cid: None,
         }
     }
 
@@ -659,6 +728,7 @@ impl ModifyEvent {
             filter: filter.clone().into_valid(),
             filter_orig: filter.into_valid(),
             modlist: modlist.into_valid(),
+            expected_cid: None,
         }
     }
 
@@ -675,9 +745,16 @@ impl ModifyEvent {
             filter: filter.clone().into_valid(),
             filter_orig: filter.into_valid(),
             modlist: modlist.into_valid(),
+            expected_cid: None,
         }
     }
 
+    /// Only apply this modification if the matching entries were last changed at `cid`.
+    pub fn with_expected_cid(mut self, expected_cid: Option<Cid>) -> Self {
+        self.expected_cid = expected_cid;
+        self
+    }
+
     pub fn new_impersonate(
         ident: &Identity,
         filter: Filter<FilterValid>,
@@ -689,6 +766,7 @@ impl ModifyEvent {
             filter,
             filter_orig,
             modlist,
+            expected_cid: None,
         }
     }
 }
@@ -777,6 +855,68 @@ impl PurgeDeleteAfterEvent {
     }
 }
 
+#[derive(Debug)]
+pub struct DomainRenameMigrateEvent {
+    pub ident: Identity,
+    pub eventid: Uuid,
+}
+
+impl Default for DomainRenameMigrateEvent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DomainRenameMigrateEvent {
+    pub fn new() -> Self {
+        DomainRenameMigrateEvent {
+            ident: Identity::from_internal(),
+            eventid: Uuid::new_v4(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct MemberExpiryEvent {
+    pub ident: Identity,
+    pub eventid: Uuid,
+}
+
+impl Default for MemberExpiryEvent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemberExpiryEvent {
+    pub fn new() -> Self {
+        MemberExpiryEvent {
+            ident: Identity::from_internal(),
+            eventid: Uuid::new_v4(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct AccountExpiryEvent {
+    pub ident: Identity,
+    pub eventid: Uuid,
+    /// The time that account expiry was last processed.
+    pub since: Duration,
+    pub notice: Option<AccountExpiryNotice>,
+}
+
+impl AccountExpiryEvent {
+    pub fn new(since: Duration, notice: Option<AccountExpiryNotice>) -> Self {
+        AccountExpiryEvent {
+            ident: Identity::from_internal(),
+            eventid: Uuid::new_v4(),
+            since,
+            notice,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct OnlineBackupEvent {
     pub ident: Identity,
@@ -136,6 +136,30 @@ pub enum FC {
     // Not(Box<FC>),
 }
 
+/// The kinds of assertion that a filter can make about the values of an attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterOpKind {
+    /// The attribute has a value equal to the assertion.
+    Equality,
+    /// The attribute has a value containing, starting or ending with the assertion.
+    SubString,
+    /// The attribute has any value.
+    Presence,
+    /// The attribute has a value ordered before the assertion.
+    Ordering,
+}
+
+impl fmt::Display for FilterOpKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterOpKind::Equality => write!(f, "eq"),
+            FilterOpKind::SubString => write!(f, "sub"),
+            FilterOpKind::Presence => write!(f, "pres"),
+            FilterOpKind::Ordering => write!(f, "ord"),
+        }
+    }
+}
+
 /// This is the filters internal representation
 #[derive(Clone, Hash, PartialEq, PartialOrd, Ord, Eq)]
 enum FilterComp {
@@ -881,52 +905,28 @@ impl FilterComp {
             FilterComp::Eq(attr, value) => {
                 // Check the requested attribute exists, and this PV type matches something
                 // that can be queried of the attribute.
-                match schema_attributes.get(attr) {
-                    Some(schema_a) => {
-                        schema_a
-                            .validate_partialvalue(attr, value)
-                            // Okay, it worked, transform to a filter component
-                            .map(|_| FilterComp::Eq(attr.clone(), value.clone()))
-                        // On error, pass the error back out.
-                    }
-                    None => Err(SchemaError::InvalidAttribute(attr.to_string())),
-                }
+                schema
+                    .validate_partialvalue(attr, value)
+                    // Okay, it worked, transform to a filter component
+                    .map(|_| FilterComp::Eq(attr.clone(), value.clone()))
             }
             FilterComp::Cnt(attr, value) => {
-                match schema_attributes.get(attr) {
-                    Some(schema_a) => {
-                        schema_a
-                            .validate_partialvalue(attr, value)
-                            // Okay, it worked, transform to a filter component
-                            .map(|_| FilterComp::Cnt(attr.clone(), value.clone()))
-                        // On error, pass the error back out.
-                    }
-                    None => Err(SchemaError::InvalidAttribute(attr.to_string())),
-                }
+                schema
+                    .validate_partialvalue(attr, value)
+                    // Okay, it worked, transform to a filter component
+                    .map(|_| FilterComp::Cnt(attr.clone(), value.clone()))
             }
             FilterComp::Stw(attr, value) => {
-                match schema_attributes.get(attr) {
-                    Some(schema_a) => {
-                        schema_a
-                            .validate_partialvalue(attr, value)
-                            // Okay, it worked, transform to a filter component
-                            .map(|_| FilterComp::Stw(attr.clone(), value.clone()))
-                        // On error, pass the error back out.
-                    }
-                    None => Err(SchemaError::InvalidAttribute(attr.to_string())),
-                }
+                schema
+                    .validate_partialvalue(attr, value)
+                    // Okay, it worked, transform to a filter component
+                    .map(|_| FilterComp::Stw(attr.clone(), value.clone()))
             }
             FilterComp::Enw(attr, value) => {
-                match schema_attributes.get(attr) {
-                    Some(schema_a) => {
-                        schema_a
-                            .validate_partialvalue(attr, value)
-                            // Okay, it worked, transform to a filter component
-                            .map(|_| FilterComp::Enw(attr.clone(), value.clone()))
-                        // On error, pass the error back out.
-                    }
-                    None => Err(SchemaError::InvalidAttribute(attr.to_string())),
-                }
+                schema
+                    .validate_partialvalue(attr, value)
+                    // Okay, it worked, transform to a filter component
+                    .map(|_| FilterComp::Enw(attr.clone(), value.clone()))
             }
             FilterComp::Pres(attr) => {
                 match schema_attributes.get(attr) {
@@ -938,16 +938,10 @@ impl FilterComp {
                 }
             }
             FilterComp::LessThan(attr, value) => {
-                match schema_attributes.get(attr) {
-                    Some(schema_a) => {
-                        schema_a
-                            .validate_partialvalue(attr, value)
-                            // Okay, it worked, transform to a filter component
-                            .map(|_| FilterComp::LessThan(attr.clone(), value.clone()))
-                        // On error, pass the error back out.
-                    }
-                    None => Err(SchemaError::InvalidAttribute(attr.to_string())),
-                }
+                schema
+                    .validate_partialvalue(attr, value)
+                    // Okay, it worked, transform to a filter component
+                    .map(|_| FilterComp::LessThan(attr.clone(), value.clone()))
             }
             FilterComp::Or(filters) => {
                 // * If all filters are okay, return Ok(Filter::Or())
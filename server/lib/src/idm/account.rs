@@ -2,7 +2,7 @@ use super::accountpolicy::ResolvedAccountPolicy;
 use super::group::{load_account_policy, load_all_groups_from_account, Group, Unix};
 use crate::constants::UUID_ANONYMOUS;
 use crate::credential::softlock::CredSoftLockPolicy;
-use crate::credential::{apppwd::ApplicationPassword, Credential};
+use crate::credential::{apppwd::ApplicationPassword, history::PasswordHistory, Credential};
 use crate::entry::{Entry, EntryCommitted, EntryReduced, EntrySealed};
 use crate::event::SearchEvent;
 use crate::idm::application::Application;
@@ -11,7 +11,8 @@ use crate::idm::server::{IdmServerProxyReadTransaction, IdmServerProxyWriteTrans
 use crate::modify::{ModifyInvalid, ModifyList};
 use crate::prelude::*;
 use crate::schema::SchemaTransaction;
-use crate::value::{IntentTokenState, PartialValue, SessionState, Value};
+use crate::server::DomainInfo;
+use crate::value::{IntentTokenState, PartialValue, PasskeyUsage, SessionState, Value};
 use kanidm_lib_crypto::CryptoPolicy;
 use kanidm_proto::internal::{CredentialStatus, UatPurpose, UiHint, UserAuthToken};
 use kanidm_proto::v1::{UatStatus, UatStatusState, UnixGroupToken, UnixUserToken};
@@ -24,10 +25,29 @@ use webauthn_rs::prelude::{
     AttestedPasskey as AttestedPasskeyV4, AuthenticationResult, CredentialID, Passkey as PasskeyV4,
 };
 
+/// Where a point in time falls relative to the validity window of an account, as defined
+/// by its `account_valid_from` and `account_expire` attributes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountValidity {
+    /// The account is not valid until a later time.
+    NotYetValid,
+    /// The account may be used.
+    Valid,
+    /// The account has expired.
+    Expired,
+}
+
+impl AccountValidity {
+    pub fn is_valid(self) -> bool {
+        self == AccountValidity::Valid
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct UnixExtensions {
     ucred: Option<Credential>,
     shell: Option<String>,
+    home_directory: Option<String>,
     gidnumber: u32,
     groups: Vec<Group<Unix>>,
 }
@@ -56,15 +76,19 @@ pub struct Account {
     pub sync_parent_uuid: Option<Uuid>,
     pub groups: Vec<Group<()>>,
     pub primary: Option<Credential>,
+    pub(crate) password_history: PasswordHistory,
     pub passkeys: BTreeMap<Uuid, (String, PasskeyV4)>,
     pub attested_passkeys: BTreeMap<Uuid, (String, AttestedPasskeyV4)>,
+    passkey_usage: BTreeMap<Uuid, PasskeyUsage>,
     pub valid_from: Option<OffsetDateTime>,
     pub expire: Option<OffsetDateTime>,
     softlock_expire: Option<OffsetDateTime>,
+    credential_lockouts: BTreeMap<Uuid, OffsetDateTime>,
     pub radius_secret: Option<String>,
     pub ui_hints: BTreeSet<UiHint>,
     pub mail_primary: Option<String>,
     pub mail: Vec<String>,
+    pub mail_verified: BTreeSet<String>,
     pub credential_update_intent_tokens: BTreeMap<String, IntentTokenState>,
     pub(crate) unix_extn: Option<UnixExtensions>,
     pub(crate) sshkeys: BTreeMap<String, SshPublicKey>,
@@ -111,6 +135,19 @@ macro_rules! try_from_entry {
             .get_ava_single_credential(Attribute::PrimaryCredential)
             .cloned();
 
+        let password_history = $value
+            .get_ava_single_private_binary(Attribute::PasswordHistory)
+            .and_then(|data| {
+                // The history only guards against reuse, so an unreadable history must
+                // not prevent the account from being used.
+                PasswordHistory::from_bytes(data)
+                    .inspect_err(|err| {
+                        warn!(?err, "Ignoring password history that could not be read")
+                    })
+                    .ok()
+            })
+            .unwrap_or_default();
+
         let passkeys = $value
             .get_ava_passkeys(Attribute::PassKeys)
             .cloned()
@@ -134,12 +171,27 @@ macro_rules! try_from_entry {
             .map(|i| i.map(str::to_string).collect())
             .unwrap_or_default();
 
+        let mail_verified = $value
+            .get_ava_mail_verified(Attribute::Mail)
+            .cloned()
+            .unwrap_or_default();
+
         let valid_from = $value.get_ava_single_datetime(Attribute::AccountValidFrom);
 
         let expire = $value.get_ava_single_datetime(Attribute::AccountExpire);
 
         let softlock_expire = $value.get_ava_single_datetime(Attribute::AccountSoftlockExpire);
 
+        let credential_lockouts = $value
+            .get_ava_credential_lockouts(Attribute::CredentialLockout)
+            .cloned()
+            .unwrap_or_default();
+
+        let passkey_usage = $value
+            .get_ava_passkey_usage(Attribute::PasskeyUsage)
+            .cloned()
+            .unwrap_or_default();
+
         let radius_secret = $value
             .get_ava_single_secret(Attribute::RadiusSecret)
             .map(str::to_string);
@@ -191,6 +243,10 @@ macro_rules! try_from_entry {
                 .get_ava_single_iutf8(Attribute::LoginShell)
                 .map(|s| s.to_string());
 
+            let home_directory = $value
+                .get_ava_single_utf8(Attribute::UnixHomeDirectory)
+                .map(|s| s.to_string());
+
             let gidnumber = $value
                 .get_ava_single_uint32(Attribute::GidNumber)
                 .ok_or_else(|| OperationError::MissingAttribute(Attribute::GidNumber))?;
@@ -200,6 +256,7 @@ macro_rules! try_from_entry {
             Some(UnixExtensions {
                 ucred,
                 shell,
+                home_directory,
                 gidnumber,
                 groups,
             })
@@ -245,16 +302,20 @@ macro_rules! try_from_entry {
             displayname,
             groups,
             primary,
+            password_history,
             passkeys,
             attested_passkeys,
+            passkey_usage,
             valid_from,
             expire,
             softlock_expire,
+            credential_lockouts,
             radius_secret,
             spn,
             ui_hints,
             mail_primary,
             mail,
+            mail_verified,
             credential_update_intent_tokens,
             unix_extn,
             sshkeys,
@@ -274,6 +335,20 @@ impl Account {
         self.primary.as_ref()
     }
 
+    pub(crate) fn password_history(&self) -> &PasswordHistory {
+        &self.password_history
+    }
+
+    /// Check if this cleartext is the current primary password, or one of the
+    /// `limit` most recently used passwords of this account.
+    pub(crate) fn password_previously_used(&self, cleartext: &str, limit: usize) -> bool {
+        let current = self
+            .primary
+            .as_ref()
+            .and_then(|cred| cred.password_ref().ok());
+        self.password_history.contains(current, cleartext, limit)
+    }
+
     pub(crate) fn sshkeys(&self) -> &BTreeMap<String, SshPublicKey> {
         &self.sshkeys
     }
@@ -298,10 +373,62 @@ impl Account {
         self.mail.as_slice()
     }
 
+    pub(crate) fn mail_is_verified(&self, mail: &str) -> bool {
+        self.mail_verified.contains(mail)
+    }
+
+    /// The address to use when a verified mail address is required. This is the primary
+    /// address if it is verified, otherwise any other verified address.
+    pub(crate) fn mail_verified_primary(&self) -> Option<&str> {
+        self.mail_primary()
+            .filter(|mail| self.mail_is_verified(mail))
+            .or_else(|| self.mail_verified.iter().next().map(String::as_str))
+    }
+
     pub(crate) fn softlock_expire(&self) -> Option<OffsetDateTime> {
         self.softlock_expire
     }
 
+    pub(crate) fn passkey_usage(&self) -> &BTreeMap<Uuid, PasskeyUsage> {
+        &self.passkey_usage
+    }
+
+    /// The passkeys and attested passkeys of this account that have not been used to
+    /// authenticate since `unused_since`. Passkeys with no recorded use are included.
+    #[allow(dead_code)]
+    pub(crate) fn stale_passkeys(&self, unused_since: OffsetDateTime) -> Vec<Uuid> {
+        self.passkeys
+            .keys()
+            .chain(self.attested_passkeys.keys())
+            .filter(|pk_id| {
+                self.passkey_usage
+                    .get(pk_id)
+                    .is_none_or(|usage| usage.last_used < unused_since)
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Determine if this credential has been locked out by the account lockout policy
+    /// at the current time.
+    pub(crate) fn is_credential_locked(&self, cred_id: Uuid, ct: Duration) -> bool {
+        let ct = OffsetDateTime::UNIX_EPOCH + ct;
+        self.credential_lockouts
+            .get(&cred_id)
+            .is_some_and(|unlock_at| *unlock_at > ct)
+    }
+
+    /// Determine if this credential is a password that was last changed longer than
+    /// `max_age` seconds ago. Credentials that predate change times being recorded
+    /// are treated as expired.
+    pub(crate) fn is_credential_expired(&self, cred_id: Uuid, max_age: u32, ct: Duration) -> bool {
+        let ct = OffsetDateTime::UNIX_EPOCH + ct;
+        self.primary
+            .as_ref()
+            .filter(|cred| cred.uuid == cred_id && cred.password_ref().is_ok())
+            .is_some_and(|cred| cred.timestamp() + Duration::from_secs(max_age as u64) <= ct)
+    }
+
     #[instrument(level = "trace", skip_all)]
     pub(crate) fn try_from_entry_ro(
         value: &Entry<EntrySealed, EntryCommitted>,
@@ -412,6 +539,8 @@ impl Account {
             // groups: self.groups.iter().map(|g| g.to_proto()).collect(),
             limit_search_max_results,
             limit_search_max_filter_test,
+            acr: None,
+            acr_verified_at: None,
         })
     }
 
@@ -472,6 +601,8 @@ impl Account {
             // groups: self.groups.iter().map(|g| g.to_proto()).collect(),
             limit_search_max_results,
             limit_search_max_filter_test,
+            acr: None,
+            acr_verified_at: None,
         })
     }
 
@@ -509,9 +640,47 @@ impl Account {
             // groups: self.groups.iter().map(|g| g.to_proto()).collect(),
             limit_search_max_results,
             limit_search_max_filter_test,
+            acr: None,
+            acr_verified_at: None,
         })
     }
 
+    /// Determine where `ct` falls relative to the validity window given by `valid_from` and
+    /// `expire`. Both bounds are inclusive, and a missing bound is unlimited. This is the
+    /// single definition of the window that every authentication path relies on.
+    pub fn validity_at(
+        ct: Duration,
+        valid_from: Option<&OffsetDateTime>,
+        expire: Option<&OffsetDateTime>,
+    ) -> AccountValidity {
+        let cot = OffsetDateTime::UNIX_EPOCH + ct;
+        trace!("Checking within valid time: {:?} {:?}", valid_from, expire);
+
+        if valid_from.is_some_and(|vft| &cot < vft) {
+            AccountValidity::NotYetValid
+        } else if expire.is_some_and(|ext| ext < &cot) {
+            AccountValidity::Expired
+        } else {
+            AccountValidity::Valid
+        }
+    }
+
+    /// Determine the validity of the account held by `entry` at `ct`.
+    pub(crate) fn entry_validity<VALID, STATE>(
+        entry: &Entry<VALID, STATE>,
+        ct: Duration,
+    ) -> AccountValidity {
+        Self::validity_at(
+            ct,
+            entry
+                .get_ava_single_datetime(Attribute::AccountValidFrom)
+                .as_ref(),
+            entry
+                .get_ava_single_datetime(Attribute::AccountExpire)
+                .as_ref(),
+        )
+    }
+
     /// Determine if an entry is within it's validity period using it's `valid_from` and
     /// `expire` attributes. `true` indicates the account is within the valid period.
     pub fn check_within_valid_time(
@@ -519,31 +688,18 @@ impl Account {
         valid_from: Option<&OffsetDateTime>,
         expire: Option<&OffsetDateTime>,
     ) -> bool {
-        let cot = OffsetDateTime::UNIX_EPOCH + ct;
-        trace!("Checking within valid time: {:?} {:?}", valid_from, expire);
+        Self::validity_at(ct, valid_from, expire).is_valid()
+    }
 
-        let vmin = if let Some(vft) = valid_from {
-            // If current time greater than start time window
-            vft <= &cot
-        } else {
-            // We have no time, not expired.
-            true
-        };
-        let vmax = if let Some(ext) = expire {
-            // If exp greater than ct then expired.
-            &cot <= ext
-        } else {
-            // If not present, we are not expired
-            true
-        };
-        // Mix the results
-        vmin && vmax
+    /// Determine the validity of this account at `ct`.
+    pub fn validity(&self, ct: Duration) -> AccountValidity {
+        Self::validity_at(ct, self.valid_from.as_ref(), self.expire.as_ref())
     }
 
     /// Determine if this account is within it's validity period. `true` indicates the
     /// account is within the valid period.
     pub fn is_within_valid_time(&self, ct: Duration) -> bool {
-        Self::check_within_valid_time(ct, self.valid_from.as_ref(), self.expire.as_ref())
+        self.validity(ct).is_valid()
     }
 
     /// Get related inputs, such as account name, email, etc. This is used for password
@@ -641,8 +797,52 @@ impl Account {
     pub(crate) fn gen_webauthn_counter_mod(
         &mut self,
         auth_result: &AuthenticationResult,
+        ct: Duration,
     ) -> Result<Option<ModifyList<ModifyInvalid>>, OperationError> {
-        let mut ml = Vec::with_capacity(2);
+        let mut ml = Vec::with_capacity(3);
+
+        // Record the use of the passkey. This is not replicated, so it is safe to
+        // update on every authentication.
+        let used_passkey = self
+            .passkeys
+            .iter()
+            .find(|(_, (_, k))| k.cred_id() == auth_result.cred_id())
+            .map(|(u, _)| *u)
+            .or_else(|| {
+                self.attested_passkeys
+                    .iter()
+                    .find(|(_, (_, k))| k.cred_id() == auth_result.cred_id())
+                    .map(|(u, _)| *u)
+            });
+
+        if let Some(pk_id) = used_passkey {
+            let counter = auth_result.counter();
+            let previous = self.passkey_usage.get(&pk_id).map(|usage| usage.counter);
+
+            // Authenticators that don't support sign counts always present zero.
+            if let Some(previous) = previous.filter(|previous| counter != 0 && counter <= *previous)
+            {
+                security_critical!(
+                    account = %self.uuid,
+                    passkey = %pk_id,
+                    counter,
+                    previous,
+                    "Passkey sign count did not increase. This passkey may have been cloned!"
+                );
+            }
+
+            ml.push(Modify::Present(
+                Attribute::PasskeyUsage,
+                Value::PasskeyUsage(
+                    pk_id,
+                    PasskeyUsage {
+                        last_used: OffsetDateTime::UNIX_EPOCH + ct,
+                        counter,
+                    },
+                ),
+            ));
+        }
+
         // Where is the credential we need to update?
         let opt_ncred = match self.primary.as_ref() {
             Some(primary) => primary.update_webauthn_properties(auth_result)?,
@@ -755,17 +955,7 @@ impl Account {
         // If we wanted we could check other properties of the uat here?
         // Alternatively, we could always store LESS in the uat because of this?
 
-        let within_valid_window = Account::check_within_valid_time(
-            ct,
-            entry
-                .get_ava_single_datetime(Attribute::AccountValidFrom)
-                .as_ref(),
-            entry
-                .get_ava_single_datetime(Attribute::AccountExpire)
-                .as_ref(),
-        );
-
-        if !within_valid_window {
+        if !Account::entry_validity(entry, ct).is_valid() {
             security_info!("Account has expired or is not yet valid, not allowing to proceed");
             return false;
         }
@@ -853,6 +1043,7 @@ impl Account {
                             application.uuid,
                             self.uuid,
                         ),
+                        source_ip: None,
                     }));
                 }
             }
@@ -860,28 +1051,51 @@ impl Account {
         Ok(None)
     }
 
-    pub(crate) fn to_unixusertoken(&self, ct: Duration) -> Result<UnixUserToken, OperationError> {
-        let (gidnumber, shell, sshkeys, groups) = match &self.unix_extn {
-            Some(ue) => {
-                let sshkeys: Vec<_> = self.sshkeys.values().cloned().collect();
-                (ue.gidnumber, ue.shell.clone(), sshkeys, ue.groups.clone())
-            }
-            None => {
-                return Err(OperationError::MissingClass(
-                    ENTRYCLASS_POSIX_ACCOUNT.into(),
-                ));
-            }
+    pub(crate) fn to_unixusertoken(
+        &self,
+        ct: Duration,
+        domain_info: &DomainInfo,
+    ) -> Result<UnixUserToken, OperationError> {
+        let Some(ue) = &self.unix_extn else {
+            return Err(OperationError::MissingClass(
+                ENTRYCLASS_POSIX_ACCOUNT.into(),
+            ));
         };
 
-        let groups: Vec<UnixGroupToken> = groups.iter().map(|g| g.to_unixgrouptoken()).collect();
+        let sshkeys: Vec<_> = self.sshkeys.values().cloned().collect();
+
+        // Group templates override the domain. If more than one group provides a value
+        // the group with the lowest spn is used so that the result is stable.
+        let mut template_groups: Vec<_> = ue.groups.iter().collect();
+        template_groups.sort_unstable_by(|a, b| a.spn().cmp(b.spn()));
+
+        // The account's own values always take precedence over any template.
+        let home_directory = ue.home_directory.clone().or_else(|| {
+            template_groups
+                .iter()
+                .find_map(|g| g.unix_home_directory_template())
+                .or(domain_info.d_unix_home_directory_template.as_ref())
+                .map(|template| template.render(self.name(), &self.spn, self.uuid))
+        });
+
+        let shell = ue.shell.clone().or_else(|| {
+            template_groups
+                .iter()
+                .find_map(|g| g.unix_default_shell())
+                .or(domain_info.d_unix_default_shell.as_deref())
+                .map(str::to_string)
+        });
+
+        let groups: Vec<UnixGroupToken> = ue.groups.iter().map(|g| g.to_unixgrouptoken()).collect();
 
         Ok(UnixUserToken {
             name: self.name().into(),
             spn: self.spn.clone(),
             displayname: self.displayname.clone(),
-            gidnumber,
+            gidnumber: ue.gidnumber,
             uuid: self.uuid,
-            shell: shell.clone(),
+            shell,
+            home_directory,
             groups,
             sshkeys,
             valid: self.is_within_valid_time(ct),
@@ -929,6 +1143,25 @@ impl DestroySessionTokenEvent {
     }
 }
 
+pub struct SelfWriteAttributeEvent {
+    // Who initiated this? This is also the entry that is modified.
+    pub ident: Identity,
+    // Which attribute to replace.
+    pub attr: Attribute,
+    // The new values of the attribute.
+    pub values: Vec<String>,
+}
+
+impl SelfWriteAttributeEvent {
+    pub fn from_parts(ident: Identity, attr: Attribute, values: Vec<String>) -> Self {
+        SelfWriteAttributeEvent {
+            ident,
+            attr,
+            values,
+        }
+    }
+}
+
 impl IdmServerProxyWriteTransaction<'_> {
     pub fn account_destroy_session_token(
         &mut self,
@@ -970,6 +1203,55 @@ impl IdmServerProxyWriteTransaction<'_> {
             })
     }
 
+    /// Replace the values of an attribute on the entry of the person who initiated the
+    /// event. The attribute must be allowed by the domain self write policy, regardless of
+    /// any other access controls that may grant the person write access to their entry.
+    pub fn self_write_attribute(
+        &mut self,
+        swe: &SelfWriteAttributeEvent,
+    ) -> Result<(), OperationError> {
+        let Some(target) = swe.ident.get_user_entry().map(|entry| entry.get_uuid()) else {
+            security_info!("Only authenticated persons may modify their own entry");
+            return Err(OperationError::AccessDenied);
+        };
+
+        if !self
+            .qs_write
+            .domain_info()
+            .self_write_attrs()
+            .contains(&swe.attr)
+        {
+            security_info!(
+                attr = %swe.attr,
+                "Attribute is not permitted by the domain self write policy"
+            );
+            return Err(OperationError::AccessDenied);
+        }
+
+        let mut modlist = ModifyList::new_purge(swe.attr.clone());
+        for value in swe.values.iter() {
+            let value = self.qs_write.clone_value(&swe.attr, value)?;
+            modlist.push_mod(Modify::Present(swe.attr.clone(), value));
+        }
+
+        self.qs_write
+            .impersonate_modify(
+                // Filter as executed
+                &filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(target))),
+                // Filter as intended (acp)
+                &filter_all!(f_and!([
+                    f_eq(Attribute::Uuid, PartialValue::Uuid(target)),
+                    f_eq(Attribute::Class, EntryClass::Person.into())
+                ])),
+                &modlist,
+                &swe.ident,
+            )
+            .map_err(|e| {
+                admin_error!("Failed to modify own entry {:?}", e);
+                e
+            })
+    }
+
     pub fn service_account_into_person(
         &mut self,
         ident: &Identity,
@@ -1104,6 +1386,8 @@ impl IdmServerProxyReadTransaction<'_> {
                                                 state,
                                                 issued_at: s.issued_at,
                                                 purpose,
+                                                source_ip: s.source_ip.map(|ip| ip.to_string()),
+                                                user_agent: s.user_agent.clone(),
                                             })
                                             .inspect_err(|_e| {
                                                 admin_error!("Invalid user auth token {}", u);
@@ -1128,6 +1412,38 @@ mod tests {
     use crate::prelude::*;
     use kanidm_proto::internal::UiHint;
 
+    use super::{Account, AccountValidity, SelfWriteAttributeEvent};
+    use time::OffsetDateTime;
+
+    #[test]
+    fn test_idm_account_validity_at() {
+        let valid_from = OffsetDateTime::UNIX_EPOCH + Duration::from_secs(100);
+        let expire = OffsetDateTime::UNIX_EPOCH + Duration::from_secs(200);
+
+        let validity = |secs| {
+            Account::validity_at(Duration::from_secs(secs), Some(&valid_from), Some(&expire))
+        };
+
+        assert_eq!(validity(99), AccountValidity::NotYetValid);
+        // Both bounds are inclusive.
+        assert_eq!(validity(100), AccountValidity::Valid);
+        assert_eq!(validity(150), AccountValidity::Valid);
+        assert_eq!(validity(200), AccountValidity::Valid);
+        assert_eq!(validity(201), AccountValidity::Expired);
+
+        // Missing bounds are unlimited.
+        let ct = Duration::from_secs(1000);
+        assert_eq!(Account::validity_at(ct, None, None), AccountValidity::Valid);
+        assert_eq!(
+            Account::validity_at(ct, Some(&valid_from), None),
+            AccountValidity::Valid
+        );
+        assert_eq!(
+            Account::validity_at(ct, None, Some(&expire)),
+            AccountValidity::Expired
+        );
+    }
+
     #[idm_test]
     async fn test_idm_account_ui_hints(idms: &IdmServer, _idms_delayed: &mut IdmServerDelayed) {
         let ct = duration_from_epoch_now();
@@ -1234,4 +1550,169 @@ mod tests {
 
         assert!(idms_prox_write.commit().is_ok());
     }
+
+    #[idm_test]
+    async fn test_idm_account_self_write_policy(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = duration_from_epoch_now();
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+
+        let target_uuid = Uuid::new_v4();
+
+        let e = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::Person.to_value()),
+            (Attribute::Name, Value::new_iname("testaccount")),
+            (Attribute::Uuid, Value::Uuid(target_uuid)),
+            (Attribute::Description, Value::new_utf8s("testaccount")),
+            (Attribute::DisplayName, Value::new_utf8s("Test Account")),
+            (Attribute::LegalName, Value::new_utf8s("Test Account"))
+        );
+
+        let ce = CreateEvent::new_internal(vec![e]);
+        assert!(idms_prox_write.qs_write.create(&ce).is_ok());
+
+        let ident = idms_prox_write
+            .qs_write
+            .internal_search_uuid(target_uuid)
+            .map(Identity::from_impersonate_entry_readwrite)
+            .expect("Unable to impersonate account");
+
+        let set_attr = |ident: &Identity, attr: Attribute, value: &str| {
+            SelfWriteAttributeEvent::from_parts(ident.clone(), attr, vec![value.to_string()])
+        };
+
+        // By default no attributes are permitted.
+        assert_eq!(
+            idms_prox_write.self_write_attribute(&set_attr(
+                &ident,
+                Attribute::DisplayName,
+                "Renamed"
+            )),
+            Err(OperationError::AccessDenied)
+        );
+
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_DOMAIN_INFO,
+                &ModifyList::new_purge_and_set(
+                    Attribute::DomainSelfWriteAttrs,
+                    Value::new_iutf8(Attribute::DisplayName.as_str()),
+                ),
+            )
+            .is_ok());
+
+        assert!(idms_prox_write.commit().is_ok());
+
+        // The policy change regenerated the builtin access control.
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+
+        let acp_entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_IDM_ACP_SELF_WRITE_POLICY)
+            .expect("Self write policy access control must exist");
+        for acp_attr in [
+            Attribute::AcpModifyPresentAttr,
+            Attribute::AcpModifyRemovedAttr,
+        ] {
+            let granted: Vec<_> = acp_entry
+                .get_ava_iter_iutf8(acp_attr)
+                .expect("Self write policy must grant attributes")
+                .collect();
+            assert_eq!(granted, vec![Attribute::DisplayName.as_str()]);
+        }
+
+        // Permitted attributes can be changed.
+        assert!(idms_prox_write
+            .self_write_attribute(&set_attr(&ident, Attribute::DisplayName, "Renamed"))
+            .is_ok());
+
+        let entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(target_uuid)
+            .expect("Unable to find account");
+        assert_eq!(
+            entry.get_ava_single_utf8(Attribute::DisplayName),
+            Some("Renamed")
+        );
+
+        // Membership of self name write grants legalname through an access control, but
+        // the self write policy must still be enforced.
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_IDM_PEOPLE_SELF_NAME_WRITE,
+                &ModifyList::new_append(Attribute::Member, Value::Refer(target_uuid)),
+            )
+            .is_ok());
+
+        let ident = idms_prox_write
+            .qs_write
+            .internal_search_uuid(target_uuid)
+            .map(Identity::from_impersonate_entry_readwrite)
+            .expect("Unable to impersonate account");
+
+        // Forbidden attributes are rejected, even though the entry is self.
+        assert_eq!(
+            idms_prox_write.self_write_attribute(&set_attr(
+                &ident,
+                Attribute::LegalName,
+                "Renamed"
+            )),
+            Err(OperationError::AccessDenied)
+        );
+        assert_eq!(
+            idms_prox_write.self_write_attribute(&set_attr(
+                &ident,
+                Attribute::Mail,
+                "test@example.com"
+            )),
+            Err(OperationError::AccessDenied)
+        );
+
+        let entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(target_uuid)
+            .expect("Unable to find account");
+        assert_eq!(
+            entry.get_ava_single_utf8(Attribute::LegalName),
+            Some("Test Account")
+        );
+        assert!(!entry.attribute_pres(Attribute::Mail));
+
+        // Clearing the policy removes the granted attributes again.
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_DOMAIN_INFO,
+                &ModifyList::new_purge(Attribute::DomainSelfWriteAttrs),
+            )
+            .is_ok());
+
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+
+        let acp_entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_IDM_ACP_SELF_WRITE_POLICY)
+            .expect("Self write policy access control must exist");
+        assert!(!acp_entry.attribute_pres(Attribute::AcpModifyPresentAttr));
+        assert!(!acp_entry.attribute_pres(Attribute::AcpModifyRemovedAttr));
+
+        assert_eq!(
+            idms_prox_write.self_write_attribute(&set_attr(
+                &ident,
+                Attribute::DisplayName,
+                "Again"
+            )),
+            Err(OperationError::AccessDenied)
+        );
+
+        assert!(idms_prox_write.commit().is_ok());
+    }
 }
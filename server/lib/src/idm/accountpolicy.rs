@@ -8,6 +8,12 @@ pub(crate) struct AccountPolicy {
     privilege_expiry: u32,
     authsession_expiry: u32,
     pw_min_length: u32,
+    pw_min_score: Option<u32>,
+    pw_breach_check: bool,
+    pw_history_length: Option<u32>,
+    auth_lockout_max_attempts: Option<u32>,
+    auth_lockout_duration: Option<u32>,
+    credential_max_age: Option<u32>,
     credential_policy: CredentialType,
     webauthn_att_ca_list: Option<AttestationCaList>,
     limit_search_max_filter_test: Option<u64>,
@@ -36,6 +42,28 @@ impl From<&EntrySealedCommitted> for Option<AccountPolicy> {
             .get_ava_single_uint32(Attribute::AuthPasswordMinimumLength)
             .unwrap_or(PW_MIN_LENGTH);
 
+        // zxcvbn scores range from 0 to 4.
+        let pw_min_score = val
+            .get_ava_single_uint32(Attribute::AuthPasswordMinimumScore)
+            .map(|score| score.min(PW_MIN_SCORE));
+
+        let pw_breach_check = val
+            .get_ava_single_bool(Attribute::AuthPasswordBreachCheck)
+            .unwrap_or(false);
+
+        let pw_history_length = val
+            .get_ava_single_uint32(Attribute::AuthPasswordHistoryLength)
+            .map(|length| length.min(PW_HISTORY_MAX_LEN));
+
+        // A policy of zero attempts would lock the credential before it could ever be used.
+        let auth_lockout_max_attempts = val
+            .get_ava_single_uint32(Attribute::AuthLockoutMaxAttempts)
+            .filter(|attempts| *attempts > 0);
+
+        let auth_lockout_duration = val.get_ava_single_uint32(Attribute::AuthLockoutDuration);
+
+        let credential_max_age = val.get_ava_single_uint32(Attribute::CredentialMaxAge);
+
         let credential_policy = val
             .get_ava_single_credential_type(Attribute::CredentialTypeMinimum)
             .unwrap_or(CredentialType::Any);
@@ -59,6 +87,12 @@ impl From<&EntrySealedCommitted> for Option<AccountPolicy> {
             privilege_expiry,
             authsession_expiry,
             pw_min_length,
+            pw_min_score,
+            pw_breach_check,
+            pw_history_length,
+            auth_lockout_max_attempts,
+            auth_lockout_duration,
+            credential_max_age,
             credential_policy,
             webauthn_att_ca_list,
             limit_search_max_filter_test,
@@ -74,6 +108,12 @@ pub(crate) struct ResolvedAccountPolicy {
     privilege_expiry: u32,
     authsession_expiry: u32,
     pw_min_length: u32,
+    pw_min_score: Option<u32>,
+    pw_breach_check: bool,
+    pw_history_length: Option<u32>,
+    auth_lockout_max_attempts: Option<u32>,
+    auth_lockout_duration: Option<u32>,
+    credential_max_age: Option<u32>,
     credential_policy: CredentialType,
     webauthn_att_ca_list: Option<AttestationCaList>,
     limit_search_max_filter_test: Option<u64>,
@@ -88,6 +128,12 @@ impl ResolvedAccountPolicy {
             privilege_expiry: DEFAULT_AUTH_PRIVILEGE_EXPIRY,
             authsession_expiry: DEFAULT_AUTH_SESSION_EXPIRY,
             pw_min_length: PW_MIN_LENGTH,
+            pw_min_score: None,
+            pw_breach_check: false,
+            pw_history_length: None,
+            auth_lockout_max_attempts: None,
+            auth_lockout_duration: None,
+            credential_max_age: None,
             credential_policy: CredentialType::Any,
             webauthn_att_ca_list: None,
             limit_search_max_filter_test: Some(DEFAULT_LIMIT_SEARCH_MAX_FILTER_TEST),
@@ -105,6 +151,12 @@ impl ResolvedAccountPolicy {
             privilege_expiry: MAXIMUM_AUTH_PRIVILEGE_EXPIRY,
             authsession_expiry: MAXIMUM_AUTH_SESSION_EXPIRY,
             pw_min_length: PW_MIN_LENGTH,
+            pw_min_score: None,
+            pw_breach_check: false,
+            pw_history_length: None,
+            auth_lockout_max_attempts: None,
+            auth_lockout_duration: None,
+            credential_max_age: None,
             credential_policy: CredentialType::Any,
             webauthn_att_ca_list: None,
             limit_search_max_filter_test: None,
@@ -128,6 +180,61 @@ impl ResolvedAccountPolicy {
                 accumulate.pw_min_length = acc_pol.pw_min_length
             }
 
+            // Take the larger pw min score of the policies that set one
+            if let Some(pol_score) = acc_pol.pw_min_score {
+                if accumulate
+                    .pw_min_score
+                    .is_none_or(|acc_score| pol_score > acc_score)
+                {
+                    accumulate.pw_min_score = Some(pol_score);
+                }
+            }
+
+            // Any policy requesting a breach check enables it
+            if acc_pol.pw_breach_check {
+                accumulate.pw_breach_check = true;
+            }
+
+            // Take the longer password history of the policies that set one
+            if let Some(pol_length) = acc_pol.pw_history_length {
+                if accumulate
+                    .pw_history_length
+                    .is_none_or(|acc_length| pol_length > acc_length)
+                {
+                    accumulate.pw_history_length = Some(pol_length);
+                }
+            }
+
+            // Take the fewer attempts before lockout
+            if let Some(pol_attempts) = acc_pol.auth_lockout_max_attempts {
+                if accumulate
+                    .auth_lockout_max_attempts
+                    .is_none_or(|acc_attempts| pol_attempts < acc_attempts)
+                {
+                    accumulate.auth_lockout_max_attempts = Some(pol_attempts);
+                }
+            }
+
+            // Take the longer lockout duration
+            if let Some(pol_duration) = acc_pol.auth_lockout_duration {
+                if accumulate
+                    .auth_lockout_duration
+                    .is_none_or(|acc_duration| pol_duration > acc_duration)
+                {
+                    accumulate.auth_lockout_duration = Some(pol_duration);
+                }
+            }
+
+            // Take the shorter credential age
+            if let Some(pol_max_age) = acc_pol.credential_max_age {
+                if accumulate
+                    .credential_max_age
+                    .is_none_or(|acc_max_age| pol_max_age < acc_max_age)
+                {
+                    accumulate.credential_max_age = Some(pol_max_age);
+                }
+            }
+
             // Take the greater credential type policy
             if acc_pol.credential_policy > accumulate.credential_policy {
                 accumulate.credential_policy = acc_pol.credential_policy
@@ -185,6 +292,41 @@ impl ResolvedAccountPolicy {
         self.pw_min_length
     }
 
+    /// The minimum zxcvbn score. If no policy sets a score this is [PW_MIN_SCORE],
+    /// otherwise the highest score of the policies that do.
+    pub(crate) fn pw_min_score(&self) -> u32 {
+        self.pw_min_score.unwrap_or(PW_MIN_SCORE)
+    }
+
+    pub(crate) fn pw_breach_check(&self) -> bool {
+        self.pw_breach_check
+    }
+
+    /// The number of recent passwords, including the current one, that may not be
+    /// reused. If no policy sets a length this is [PW_HISTORY_LEN].
+    pub(crate) fn pw_history_length(&self) -> usize {
+        self.pw_history_length.unwrap_or(PW_HISTORY_LEN) as usize
+    }
+
+    /// The number of failed authentication attempts after which a credential is
+    /// locked, and the number of seconds it remains locked for. If no policy sets
+    /// a number of attempts, credentials are only subject to the softlock.
+    pub(crate) fn auth_lockout(&self) -> Option<(u32, u32)> {
+        self.auth_lockout_max_attempts.map(|max_attempts| {
+            (
+                max_attempts,
+                self.auth_lockout_duration
+                    .unwrap_or(DEFAULT_AUTH_LOCKOUT_DURATION),
+            )
+        })
+    }
+
+    /// The number of seconds a password or api token may be used for after it
+    /// was set or issued. If no policy sets an age, credentials do not expire.
+    pub(crate) fn credential_max_age(&self) -> Option<u32> {
+        self.credential_max_age
+    }
+
     pub(crate) fn credential_policy(&self) -> CredentialType {
         self.credential_policy
     }
@@ -280,6 +422,12 @@ jAGGiQIwHFj+dJZYUJR786osByBelJYsVZd2GbHQu209b5RCmGQ21gpSAk9QZW4B
             privilege_expiry: 100,
             authsession_expiry: 100,
             pw_min_length: 11,
+            pw_min_score: Some(2),
+            pw_breach_check: false,
+            pw_history_length: Some(4),
+            auth_lockout_max_attempts: None,
+            auth_lockout_duration: None,
+            credential_max_age: None,
             credential_policy: CredentialType::Mfa,
             webauthn_att_ca_list: Some(att_ca_list_a),
             limit_search_max_filter_test: Some(10),
@@ -302,6 +450,12 @@ jAGGiQIwHFj+dJZYUJR786osByBelJYsVZd2GbHQu209b5RCmGQ21gpSAk9QZW4B
             privilege_expiry: 150,
             authsession_expiry: 50,
             pw_min_length: 15,
+            pw_min_score: Some(3),
+            pw_breach_check: true,
+            pw_history_length: None,
+            auth_lockout_max_attempts: None,
+            auth_lockout_duration: None,
+            credential_max_age: None,
             credential_policy: CredentialType::Passkey,
             webauthn_att_ca_list: Some(att_ca_list_b),
             limit_search_max_filter_test: Some(5),
@@ -314,6 +468,9 @@ jAGGiQIwHFj+dJZYUJR786osByBelJYsVZd2GbHQu209b5RCmGQ21gpSAk9QZW4B
         assert_eq!(rap.privilege_expiry(), 100);
         assert_eq!(rap.authsession_expiry(), 50);
         assert_eq!(rap.pw_min_length(), 15);
+        assert_eq!(rap.pw_min_score(), 3);
+        assert!(rap.pw_breach_check());
+        assert_eq!(rap.pw_history_length(), 4);
         assert_eq!(rap.credential_policy, CredentialType::Passkey);
         assert_eq!(rap.limit_search_max_results(), Some(15));
         assert_eq!(rap.limit_search_max_filter_test(), Some(10));
@@ -329,4 +486,85 @@ jAGGiQIwHFj+dJZYUJR786osByBelJYsVZd2GbHQu209b5RCmGQ21gpSAk9QZW4B
 
         assert_eq!(rap.webauthn_att_ca_list, Some(att_ca_list_ex));
     }
+
+    #[test]
+    fn test_idm_account_policy_resolve_password_quality() {
+        sketching::test_init();
+
+        // With no policy we get the defaults.
+        let rap = ResolvedAccountPolicy::fold_from(std::iter::empty());
+        assert_eq!(rap.pw_min_length(), PW_MIN_LENGTH);
+        assert_eq!(rap.pw_min_score(), PW_MIN_SCORE);
+        assert!(!rap.pw_breach_check());
+        assert_eq!(rap.pw_history_length(), PW_HISTORY_LEN as usize);
+
+        // A group that sets a score may lower it from the default ...
+        let relaxed = AccountPolicy {
+            pw_min_score: Some(1),
+            ..Default::default()
+        };
+        let rap = ResolvedAccountPolicy::fold_from([relaxed.clone()].into_iter());
+        assert_eq!(rap.pw_min_score(), 1);
+
+        // ... but a stricter group takes precedence, regardless of order.
+        let strict = AccountPolicy {
+            pw_min_score: Some(3),
+            pw_breach_check: true,
+            ..Default::default()
+        };
+        let rap = ResolvedAccountPolicy::fold_from([strict.clone(), relaxed.clone()].into_iter());
+        assert_eq!(rap.pw_min_score(), 3);
+        assert!(rap.pw_breach_check());
+
+        let rap = ResolvedAccountPolicy::fold_from([relaxed, strict].into_iter());
+        assert_eq!(rap.pw_min_score(), 3);
+        assert!(rap.pw_breach_check());
+
+        // A policy that doesn't set a score doesn't reset it to the default.
+        let unset = AccountPolicy {
+            pw_min_length: 20,
+            ..Default::default()
+        };
+        let relaxed = AccountPolicy {
+            pw_min_score: Some(2),
+            ..Default::default()
+        };
+        let rap = ResolvedAccountPolicy::fold_from([unset, relaxed].into_iter());
+        assert_eq!(rap.pw_min_length(), 20);
+        assert_eq!(rap.pw_min_score(), 2);
+        assert!(!rap.pw_breach_check());
+    }
+
+    #[test]
+    fn test_idm_account_policy_resolve_lockout() {
+        sketching::test_init();
+
+        // Without a policy only the softlock applies.
+        let rap = ResolvedAccountPolicy::fold_from(std::iter::empty());
+        assert_eq!(rap.auth_lockout(), None);
+
+        let default_policy = AccountPolicy {
+            auth_lockout_max_attempts: Some(10),
+            ..Default::default()
+        };
+        let rap = ResolvedAccountPolicy::fold_from([default_policy.clone()].into_iter());
+        assert_eq!(
+            rap.auth_lockout(),
+            Some((10, DEFAULT_AUTH_LOCKOUT_DURATION))
+        );
+
+        // The strictest policy wins: fewer attempts, and a longer lock.
+        let privileged = AccountPolicy {
+            auth_lockout_max_attempts: Some(3),
+            auth_lockout_duration: Some(1800),
+            ..Default::default()
+        };
+        let relaxed = AccountPolicy {
+            auth_lockout_duration: Some(60),
+            ..Default::default()
+        };
+        let rap =
+            ResolvedAccountPolicy::fold_from([default_policy, privileged, relaxed].into_iter());
+        assert_eq!(rap.auth_lockout(), Some((3, 1800)));
+    }
 }
@@ -3,13 +3,15 @@ use crate::credential::apppwd::ApplicationPassword;
 use crate::idm::account::Account;
 use crate::idm::event::LdapApplicationAuthEvent;
 use crate::idm::server::{
-    IdmServerAuthTransaction, IdmServerProxyWriteTransaction, IdmServerTransaction,
+    IdmServerAuthTransaction, IdmServerProxyReadTransaction, IdmServerProxyWriteTransaction,
+    IdmServerTransaction,
 };
 use crate::prelude::*;
 use crate::utils::readable_password_from_random;
 use concread::cowcell::*;
 use hashbrown::HashMap;
 use kanidm_proto::internal::OperationError;
+use kanidm_proto::scim_v1::ScimApplicationPasswordInfo;
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -156,8 +158,7 @@ impl IdmServerAuthTransaction<'_> {
             return Err(OperationError::InvalidUuid);
         }
 
-        if !account.is_within_valid_time(ct) {
-            security_info!("Account has expired or is not yet valid, not allowing to proceed");
+        if !self.check_account_validity(account.uuid, account.validity(ct), ct) {
             return Err(OperationError::SessionExpired);
         }
 
@@ -201,6 +202,7 @@ impl IdmServerAuthTransaction<'_> {
                     spn: account.spn().into(),
                     session_id,
                     effective_session: LdapSession::UnixBind(account.uuid),
+                    source_ip: None,
                 }))
             }
             None => {
@@ -250,6 +252,60 @@ impl IdmServerProxyWriteTransaction<'_> {
         &mut self,
         ev: &GenerateApplicationPasswordEvent,
     ) -> Result<(String, Uuid), OperationError> {
+        let app_entry = self
+            .qs_write
+            .internal_search_uuid(ev.application)
+            .inspect_err(|err| error!(?err, "Unable to find application"))?;
+
+        let mut mods = Vec::with_capacity(1);
+
+        if let Some(limit) = app_entry.get_ava_single_uint32(Attribute::ApplicationPasswordLimit) {
+            let auto_revoke = app_entry
+                .get_ava_single_bool(Attribute::ApplicationPasswordAutoRevoke)
+                .unwrap_or_default();
+
+            let target_entry = self.qs_write.internal_search_uuid(ev.target)?;
+
+            // A password with the same label is replaced, so it doesn't count against the limit.
+            let mut existing: Vec<&ApplicationPassword> = target_entry
+                .get_ava_application_password(Attribute::ApplicationPassword)
+                .and_then(|apps| apps.get(&ev.application))
+                .map(|aps| aps.iter().filter(|ap| ap.label != ev.label).collect())
+                .unwrap_or_default();
+
+            let limit = limit as usize;
+            if existing.len() >= limit {
+                // With a limit of zero there is nothing that could be revoked to make room.
+                if !auto_revoke || limit == 0 {
+                    security_info!(
+                        target = ?ev.target,
+                        application = ?ev.application,
+                        "Application password limit of {} reached",
+                        limit
+                    );
+                    return Err(OperationError::AP0001ApplicationPasswordLimitReached);
+                }
+
+                // Oldest first. Passwords without a creation time predate it being
+                // recorded, so they are the oldest of all.
+                existing.sort_unstable_by_key(|ap| (ap.created, ap.uuid));
+                let excess = existing.len() + 1 - limit;
+
+                for ap in existing.into_iter().take(excess) {
+                    security_info!(
+                        target = ?ev.target,
+                        application = ?ev.application,
+                        apppwd_id = ?ap.uuid,
+                        "Revoking oldest application password to remain within limit"
+                    );
+                    mods.push(Modify::Removed(
+                        Attribute::ApplicationPassword,
+                        PartialValue::Uuid(ap.uuid),
+                    ));
+                }
+            }
+        }
+
         // This is intended to be read/copied by a human
         let cleartext = readable_password_from_random();
         let policy = self.crypto_policy();
@@ -259,6 +315,7 @@ impl IdmServerProxyWriteTransaction<'_> {
             ev.label.as_str(),
             cleartext.as_str(),
             policy,
+            self.qs_write.get_curtime_odt(),
         )
         .inspect_err(|err| {
             error!(
@@ -269,7 +326,8 @@ impl IdmServerProxyWriteTransaction<'_> {
 
         let ap_uuid = ap.uuid;
         let vap = Value::ApplicationPassword(ap);
-        let modlist = ModifyList::new_append(Attribute::ApplicationPassword, vap);
+        mods.push(Modify::Present(Attribute::ApplicationPassword, vap));
+        let modlist = ModifyList::new_list(mods);
 
         // Apply it
         self.qs_write
@@ -311,13 +369,49 @@ impl IdmServerProxyWriteTransaction<'_> {
     }
 }
 
+impl IdmServerProxyReadTransaction<'_> {
+    /// List the application passwords held by the caller. Only the label, application
+    /// and creation time are returned, never the secret.
+    #[instrument(level = "debug", skip_all)]
+    pub fn list_self_application_passwords(
+        &mut self,
+        ident: &Identity,
+    ) -> Result<Vec<ScimApplicationPasswordInfo>, OperationError> {
+        let target = ident.get_uuid();
+
+        let srch = SearchEvent::from_target_uuid_request(ident.clone(), target, &self.qs_read)
+            .inspect_err(|err| error!(?err, "Failed to begin application password list"))?;
+
+        let mut entries = self.qs_read.search_ext(&srch)?;
+
+        Ok(entries
+            .pop()
+            .and_then(|entry| {
+                entry
+                    .get_ava_application_password(Attribute::ApplicationPassword)
+                    .map(|apps| {
+                        apps.values()
+                            .flatten()
+                            .map(|ap| ScimApplicationPasswordInfo {
+                                uuid: ap.uuid,
+                                application_uuid: ap.application,
+                                label: ap.label.clone(),
+                                created: ap.created,
+                            })
+                            .collect()
+                    })
+            })
+            .unwrap_or_default())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::event::CreateEvent;
     use crate::idm::account::Account;
     use crate::idm::application::Application;
     use crate::idm::application::GenerateApplicationPasswordEvent;
-    use crate::idm::server::IdmServerTransaction;
+    use crate::idm::server::{IdmServerProxyWriteTransaction, IdmServerTransaction};
     use crate::idm::serviceaccount::{DestroyApiTokenEvent, GenerateApiTokenEvent};
     use crate::prelude::*;
     use compact_jwt::{dangernoverify::JwsDangerReleaseWithoutVerify, JwsVerifier};
@@ -656,4 +750,186 @@ mod tests {
 
         assert!(idms_prox_write.commit().is_ok());
     }
+
+    fn create_application_password_fixture(
+        idms_prox_write: &mut IdmServerProxyWriteTransaction<'_>,
+        usr_uuid: Uuid,
+        app_uuid: Uuid,
+        limit: u32,
+    ) {
+        let grp_uuid = Uuid::new_v4();
+
+        let e1 = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::Person.to_value()),
+            (Attribute::Name, Value::new_iname("testuser1")),
+            (Attribute::Uuid, Value::Uuid(usr_uuid)),
+            (Attribute::DisplayName, Value::new_utf8s("testuser1"))
+        );
+
+        let e2 = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Name, Value::new_iname("testgroup1")),
+            (Attribute::Uuid, Value::Uuid(grp_uuid)),
+            (Attribute::Member, Value::Refer(usr_uuid))
+        );
+
+        let e3 = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::ServiceAccount.to_value()),
+            (Attribute::Class, EntryClass::Application.to_value()),
+            (Attribute::DisplayName, Value::new_utf8s("Application")),
+            (Attribute::Name, Value::new_iname("testapp1")),
+            (Attribute::Uuid, Value::Uuid(app_uuid)),
+            (Attribute::LinkedGroup, Value::Refer(grp_uuid)),
+            (Attribute::ApplicationPasswordLimit, Value::Uint32(limit))
+        );
+
+        let ce = CreateEvent::new_internal(vec![e1, e2, e3]);
+        assert!(idms_prox_write.qs_write.create(&ce).is_ok());
+    }
+
+    fn application_password_labels(
+        idms_prox_write: &mut IdmServerProxyWriteTransaction<'_>,
+        usr_uuid: Uuid,
+    ) -> Vec<String> {
+        let account = idms_prox_write
+            .qs_write
+            .internal_search_uuid(usr_uuid)
+            .and_then(|entry| Account::try_from_entry_rw(&entry, &mut idms_prox_write.qs_write))
+            .expect("Failed to search for account");
+
+        let mut labels: Vec<String> = account
+            .apps_pwds
+            .values()
+            .flatten()
+            .map(|ap| ap.label.clone())
+            .collect();
+        labels.sort_unstable();
+        labels
+    }
+
+    #[idm_test]
+    async fn test_idm_application_password_limit(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let usr_uuid = Uuid::new_v4();
+        let app_uuid = Uuid::new_v4();
+
+        let mut idms_prox_write = idms
+            .proxy_write(Duration::from_secs(TEST_CURRENT_TIME))
+            .await
+            .unwrap();
+        create_application_password_fixture(&mut idms_prox_write, usr_uuid, app_uuid, 2);
+        assert!(idms_prox_write.commit().is_ok());
+
+        // Fill the allowance, each password generated at a distinct time.
+        for (offset, label) in [(1, "apppwd1"), (2, "apppwd2")] {
+            let mut idms_prox_write = idms
+                .proxy_write(Duration::from_secs(TEST_CURRENT_TIME + offset))
+                .await
+                .unwrap();
+            let ev =
+                GenerateApplicationPasswordEvent::new_internal(usr_uuid, app_uuid, label.into());
+            idms_prox_write
+                .generate_application_password(&ev)
+                .expect("Failed to generate application password");
+            assert!(idms_prox_write.commit().is_ok());
+        }
+
+        let mut idms_prox_write = idms
+            .proxy_write(Duration::from_secs(TEST_CURRENT_TIME + 3))
+            .await
+            .unwrap();
+
+        // Without auto revoke, the limit is enforced.
+        let ev =
+            GenerateApplicationPasswordEvent::new_internal(usr_uuid, app_uuid, "apppwd3".into());
+        assert_eq!(
+            idms_prox_write.generate_application_password(&ev),
+            Err(OperationError::AP0001ApplicationPasswordLimitReached)
+        );
+
+        // Replacing an existing label does not count against the limit.
+        let ev =
+            GenerateApplicationPasswordEvent::new_internal(usr_uuid, app_uuid, "apppwd2".into());
+        assert!(idms_prox_write.generate_application_password(&ev).is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        // Enable auto revoke on the application.
+        let mut idms_prox_write = idms
+            .proxy_write(Duration::from_secs(TEST_CURRENT_TIME + 4))
+            .await
+            .unwrap();
+        let me = ModifyEvent::new_internal_invalid(
+            filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(app_uuid))),
+            ModifyList::new_purge_and_set(
+                Attribute::ApplicationPasswordAutoRevoke,
+                Value::Bool(true),
+            ),
+        );
+        assert!(idms_prox_write.qs_write.modify(&me).is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_write = idms
+            .proxy_write(Duration::from_secs(TEST_CURRENT_TIME + 5))
+            .await
+            .unwrap();
+        let ev =
+            GenerateApplicationPasswordEvent::new_internal(usr_uuid, app_uuid, "apppwd3".into());
+        assert!(idms_prox_write.generate_application_password(&ev).is_ok());
+
+        // The oldest password was revoked to make room.
+        assert_eq!(
+            application_password_labels(&mut idms_prox_write, usr_uuid),
+            vec!["apppwd2".to_string(), "apppwd3".to_string()]
+        );
+        assert!(idms_prox_write.commit().is_ok());
+    }
+
+    #[idm_test]
+    async fn test_idm_application_password_self_list(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let usr_uuid = Uuid::new_v4();
+        let app_uuid = Uuid::new_v4();
+
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        create_application_password_fixture(&mut idms_prox_write, usr_uuid, app_uuid, 4);
+
+        let ev =
+            GenerateApplicationPasswordEvent::new_internal(usr_uuid, app_uuid, "apppwd1".into());
+        let (secret, apppwd_uuid) = idms_prox_write
+            .generate_application_password(&ev)
+            .expect("Failed to generate application password");
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+        let ident = idms_prox_read
+            .qs_read
+            .internal_search_uuid(usr_uuid)
+            .map(Identity::from_impersonate_entry_readonly)
+            .expect("Failed to impersonate identity");
+
+        let apppwds = idms_prox_read
+            .list_self_application_passwords(&ident)
+            .expect("Failed to list application passwords");
+
+        assert_eq!(apppwds.len(), 1);
+        let apppwd = &apppwds[0];
+        assert_eq!(apppwd.uuid, apppwd_uuid);
+        assert_eq!(apppwd.application_uuid, app_uuid);
+        assert_eq!(apppwd.label, "apppwd1");
+        assert_eq!(apppwd.created, Some(time::OffsetDateTime::UNIX_EPOCH + ct));
+
+        // No secret material is present in what is returned.
+        let json = serde_json::to_string(&apppwds).expect("Failed to serialise");
+        assert!(!json.contains(secret.as_str()));
+    }
 }
@@ -1,9 +1,10 @@
-use crate::prelude::{OperationError, Url};
+use crate::prelude::{OperationError, Url, SESSION_USER_AGENT_MAX_LEN};
 use crate::server::identity::Source;
+use crate::value::Acr;
 use compact_jwt::JwsCompact;
 use crypto_glue::{s256::Sha256Output, x509::Certificate};
 use kanidm_proto::{
-    internal::UserAuthToken,
+    internal::{ApiTokenOperationScope, UserAuthToken},
     oauth2::{AccessTokenRequest, AccessTokenResponse, AuthorisationRequest},
     v1::{
         AuthAllowed, AuthCredential as ProtoAuthCredential, AuthIssueSession, AuthMech,
@@ -146,6 +147,9 @@ pub enum ReauthRequest {
     #[default]
     VerifyCredentials,
     GrantReadWrite,
+    /// Verify the session with a credential that satisfies at least this class. If the
+    /// credential used to establish the session is insufficient, a passkey is required.
+    StepUp(Acr),
 }
 
 #[derive(Debug, Clone, Default)]
@@ -164,6 +168,11 @@ pub struct ClientAuthInfo {
     pub(crate) bearer_token: Option<JwsCompact>,
     pub(crate) basic_authz: Option<String>,
     pub(crate) pre_validated_token: PreValidatedTokenStatus,
+    /// The class of operation this request performs. Api tokens that are restricted
+    /// to a set of operation scopes may only be used when this is within their scopes.
+    pub(crate) operation_scope: Option<ApiTokenOperationScope>,
+    /// The user agent the client presented, truncated to [`SESSION_USER_AGENT_MAX_LEN`].
+    pub(crate) user_agent: Option<String>,
 }
 
 impl ClientAuthInfo {
@@ -179,6 +188,8 @@ impl ClientAuthInfo {
             bearer_token,
             basic_authz,
             pre_validated_token: Default::default(),
+            operation_scope: None,
+            user_agent: None,
         }
     }
 
@@ -198,6 +209,21 @@ impl ClientAuthInfo {
     pub(crate) fn set_pre_validated_uat(&mut self, status: PreValidatedTokenStatus) {
         self.pre_validated_token = status
     }
+
+    pub fn set_operation_scope(&mut self, operation_scope: Option<ApiTokenOperationScope>) {
+        self.operation_scope = operation_scope
+    }
+
+    pub fn set_user_agent(&mut self, user_agent: Option<&str>) {
+        self.user_agent = user_agent.map(|ua| {
+            let mut end = ua.len().min(SESSION_USER_AGENT_MAX_LEN);
+            // Don't split a multibyte character.
+            while !ua.is_char_boundary(end) {
+                end -= 1;
+            }
+            ua[..end].to_string()
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -215,6 +241,8 @@ impl ClientAuthInfo {
             bearer_token: None,
             basic_authz: None,
             pre_validated_token: Default::default(),
+            operation_scope: None,
+            user_agent: None,
         }
     }
 }
@@ -228,6 +256,8 @@ impl From<Source> for ClientAuthInfo {
             bearer_token: None,
             basic_authz: None,
             pre_validated_token: Default::default(),
+            operation_scope: None,
+            user_agent: None,
         }
     }
 }
@@ -241,6 +271,8 @@ impl From<JwsCompact> for ClientAuthInfo {
             bearer_token: Some(value),
             basic_authz: None,
             pre_validated_token: Default::default(),
+            operation_scope: None,
+            user_agent: None,
         }
     }
 }
@@ -254,6 +286,8 @@ impl From<ClientCertInfo> for ClientAuthInfo {
             bearer_token: None,
             basic_authz: None,
             pre_validated_token: Default::default(),
+            operation_scope: None,
+            user_agent: None,
         }
     }
 }
@@ -267,6 +301,8 @@ impl From<&str> for ClientAuthInfo {
             bearer_token: None,
             basic_authz: Some(value.to_string()),
             pre_validated_token: Default::default(),
+            operation_scope: None,
+            user_agent: None,
         }
     }
 }
@@ -283,6 +319,8 @@ impl ClientAuthInfo {
             bearer_token: None,
             basic_authz: Some(value),
             pre_validated_token: Default::default(),
+            operation_scope: None,
+            user_agent: None,
         }
     }
 }
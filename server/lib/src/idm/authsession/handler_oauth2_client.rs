@@ -90,6 +90,7 @@ impl CredHandlerOAuth2Client {
                 max_age: None,
                 prompt: Vec::new(),
                 ui_locales: Vec::new(),
+                acr_values: Vec::new(),
                 unknown_keys: Default::default(),
             },
         )
@@ -15,10 +15,10 @@ use crate::idm::delayed::{
 use crate::idm::oauth2_client::OAuth2ClientProvider;
 use crate::prelude::*;
 use crate::server::keys::KeyObject;
-use crate::value::{AuthType, Session, SessionExtMetadata, SessionState};
+use crate::value::{Acr, AuthType, Session, SessionExtMetadata, SessionState};
 use compact_jwt::Jws;
 use hashbrown::HashSet;
-use kanidm_proto::internal::UserAuthToken;
+use kanidm_proto::internal::{UatPurpose, UserAuthToken};
 use kanidm_proto::v1::{AuthAllowed, AuthIssueSession, AuthMech};
 use nonempty::NonEmpty;
 use std::collections::BTreeMap;
@@ -794,20 +794,15 @@ impl CredHandler {
                     Ok(auth_result) => {
                         if let Some(cred_id) = cred_ids.get(auth_result.cred_id()).copied() {
                             wan_cred.state = CredVerifyState::Success;
-                            // Success. Determine if we need to update the counter
-                            // async from r.
-                            if auth_result.needs_update() {
-                                // Do async
-                                if let Err(_e) =
-                                    async_tx.send(DelayedAction::WebauthnCounterIncrement(
-                                        WebauthnCounterIncrement {
-                                            target_uuid: who,
-                                            auth_result,
-                                        },
-                                    ))
-                                {
-                                    admin_warn!("unable to queue delayed webauthn property update, continuing ... ");
-                                };
+                            // Success. Always queue the update, as the use of the passkey
+                            // is recorded even when the counter hasn't changed.
+                            if let Err(_e) = async_tx.send(DelayedAction::WebauthnCounterIncrement(
+                                WebauthnCounterIncrement {
+                                    target_uuid: who,
+                                    auth_result,
+                                },
+                            )) {
+                                admin_warn!("unable to queue delayed webauthn property update, continuing ... ");
                             };
 
                             CredState::Success {
@@ -871,20 +866,15 @@ impl CredHandler {
                             }
 
                             wan_cred.state = CredVerifyState::Success;
-                            // Success. Determine if we need to update the counter
-                            // async from r.
-                            if auth_result.needs_update() {
-                                // Do async
-                                if let Err(_e) =
-                                    async_tx.send(DelayedAction::WebauthnCounterIncrement(
-                                        WebauthnCounterIncrement {
-                                            target_uuid: who,
-                                            auth_result,
-                                        },
-                                    ))
-                                {
-                                    admin_warn!("unable to queue delayed webauthn property update, continuing ... ");
-                                };
+                            // Success. Always queue the update, as the use of the passkey
+                            // is recorded even when the counter hasn't changed.
+                            if let Err(_e) = async_tx.send(DelayedAction::WebauthnCounterIncrement(
+                                WebauthnCounterIncrement {
+                                    target_uuid: who,
+                                    auth_result,
+                                },
+                            )) {
+                                admin_warn!("unable to queue delayed webauthn property update, continuing ... ");
                             };
 
                             CredState::Success {
@@ -1121,6 +1111,8 @@ pub(crate) struct AuthSession {
 
     // Where did the event come from?
     source: Source,
+    // The user agent of the client, if any, already truncated for storage.
+    user_agent: Option<String>,
 
     // The cryptographic provider to encrypt or sign anything in this operation.
     key_object: Arc<KeyObject>,
@@ -1131,6 +1123,14 @@ impl AuthSession {
         &self.account
     }
 
+    pub(crate) fn account_policy(&self) -> &ResolvedAccountPolicy {
+        &self.account_policy
+    }
+
+    pub(crate) fn source(&self) -> &Source {
+        &self.source
+    }
+
     /// Create a new auth session, based on the available credential handlers of the account.
     /// the session is a whole encapsulated unit of what we need to proceed, so that subsequent
     /// or interleved write operations do not cause inconsistency in this process.
@@ -1252,6 +1252,7 @@ impl AuthSession {
                 issue: asd.issue,
                 intent: AuthIntent::InitialAuth { privileged },
                 source: asd.client_auth_info.source,
+                user_agent: asd.client_auth_info.user_agent,
                 key_object,
             };
             // Get the set of mechanisms that can proceed. This is tied
@@ -1294,82 +1295,129 @@ impl AuthSession {
 
             let mut cred_handler = None;
 
-            match session.type_ {
-                AuthType::Password
-                | AuthType::GeneratedPassword
-                // If a backup code was used, since the code was scrubbed at use we need to
-                // fall back to the password of the account instead.
-                | AuthType::PasswordBackupCode => {
-                    if let Some(primary) = asd.account.primary.as_ref() {
-                        if primary.uuid == cred_id {
-                            cred_handler = CredHandler::build_from_password_only(primary)
-                        }
-                    }
+            // If the credential that established this session does not satisfy the class
+            // being requested, the user must step up to a passkey rather than re-verifying
+            // the credential they originally used.
+            let step_up = match reauth_req {
+                ReauthRequest::StepUp(required)
+                    if Acr::from_auth_type(session.type_) < Some(*required) =>
+                {
+                    Some(*required)
+                }
+                _ => None,
+            };
+
+            if let Some(required) = step_up {
+                security_info!(
+                    ?required,
+                    session_type = %session.type_,
+                    "step up authentication required"
+                );
+
+                if let Some(att_ca_list) = asd.account_policy.webauthn_attestation_ca_list() {
+                    cred_handler = CredHandler::build_from_set_attested_pk(
+                        &asd.account.attested_passkeys,
+                        att_ca_list,
+                        asd.webauthn,
+                    );
+                } else if required == Acr::AttestedPasskey {
+                    // Without an attestation ca list there is no way to prove the passkey
+                    // is attested.
+                    debug!("Account policy has no attestation ca list, unable to step up");
+                } else {
+                    let credential_iter = asd
+                        .account
+                        .passkeys
+                        .iter()
+                        .map(|(u, (_, pk))| (*u, pk.clone()))
+                        .chain(
+                            asd.account
+                                .attested_passkeys
+                                .iter()
+                                .map(|(u, (_, pk))| (*u, pk.into())),
+                        );
+
+                    cred_handler =
+                        CredHandler::build_from_set_passkey(credential_iter, asd.webauthn);
                 }
-                AuthType::PasswordTotp => {
-                    if let Some(primary) = asd.account.primary.as_ref() {
-                        if primary.uuid == cred_id {
-                            cred_handler = CredHandler::build_from_password_totp(primary)
+            } else {
+                match session.type_ {
+                    AuthType::Password
+                    | AuthType::GeneratedPassword
+                    // If a backup code was used, since the code was scrubbed at use we need to
+                    // fall back to the password of the account instead.
+                    | AuthType::PasswordBackupCode => {
+                        if let Some(primary) = asd.account.primary.as_ref() {
+                            if primary.uuid == cred_id {
+                                cred_handler = CredHandler::build_from_password_only(primary)
+                            }
                         }
                     }
-                }
-                AuthType::PasswordSecurityKey => {
-                    if let Some(primary) = asd.account.primary.as_ref() {
-                        if primary.uuid == cred_id {
-                            cred_handler =
-                                CredHandler::build_from_password_security_key(primary, asd.webauthn)
+                    AuthType::PasswordTotp => {
+                        if let Some(primary) = asd.account.primary.as_ref() {
+                            if primary.uuid == cred_id {
+                                cred_handler = CredHandler::build_from_password_totp(primary)
+                            }
                         }
                     }
-                }
-                AuthType::Passkey => {
-                    // Scan both attested and passkeys for the possible credential.
-                    let maybe_pk: Option<PasskeyV4> = asd
-                        .account
-                        .attested_passkeys
-                        .get(&cred_id)
-                        .map(|(_, apk)| apk.into())
-                        .or_else(|| asd.account.passkeys.get(&cred_id).map(|(_, pk)| pk.clone()));
-
-                    if let Some(pk) = maybe_pk {
-                        if let Some(ch) =
-                            CredHandler::build_from_single_passkey(cred_id, pk, asd.webauthn)
-                        {
-                            // Update it.
-                            debug_assert!(cred_handler.is_none());
-                            cred_handler = Some(ch);
-                        } else {
-                            security_critical!(
-                                "corrupt credentials, unable to start passkey credhandler"
-                            );
+                    AuthType::PasswordSecurityKey => {
+                        if let Some(primary) = asd.account.primary.as_ref() {
+                            if primary.uuid == cred_id {
+                                cred_handler =
+                                    CredHandler::build_from_password_security_key(primary, asd.webauthn)
+                            }
                         }
                     }
-                }
-                AuthType::AttestedPasskey => {
-                    if let Some(att_ca_list) = asd.account_policy.webauthn_attestation_ca_list() {
-                        if let Some(pk) = asd
+                    AuthType::Passkey => {
+                        // Scan both attested and passkeys for the possible credential.
+                        let maybe_pk: Option<PasskeyV4> = asd
                             .account
                             .attested_passkeys
                             .get(&cred_id)
-                            .map(|(_, pk)| pk)
-                        {
-                            if let Some(ch) = CredHandler::build_from_single_attested_pk(
-                                cred_id,
-                                pk,
-                                att_ca_list,
-                                asd.webauthn,
-                            ) {
+                            .map(|(_, apk)| apk.into())
+                            .or_else(|| asd.account.passkeys.get(&cred_id).map(|(_, pk)| pk.clone()));
+
+                        if let Some(pk) = maybe_pk {
+                            if let Some(ch) =
+                                CredHandler::build_from_single_passkey(cred_id, pk, asd.webauthn)
+                            {
                                 // Update it.
                                 debug_assert!(cred_handler.is_none());
                                 cred_handler = Some(ch);
                             } else {
                                 security_critical!(
-                            "corrupt credentials, unable to start attested passkey credhandler"
-                        );
+                                    "corrupt credentials, unable to start passkey credhandler"
+                                );
                             }
                         }
                     }
+                    AuthType::AttestedPasskey => {
+                        if let Some(att_ca_list) = asd.account_policy.webauthn_attestation_ca_list() {
+                            if let Some(pk) = asd
+                                .account
+                                .attested_passkeys
+                                .get(&cred_id)
+                                .map(|(_, pk)| pk)
+                            {
+                                if let Some(ch) = CredHandler::build_from_single_attested_pk(
+                                    cred_id,
+                                    pk,
+                                    att_ca_list,
+                                    asd.webauthn,
+                                ) {
+                                    // Update it.
+                                    debug_assert!(cred_handler.is_none());
+                                    cred_handler = Some(ch);
+                                } else {
+                                    security_critical!(
+                                "corrupt credentials, unable to start attested passkey credhandler"
+                            );
+                                }
+                            }
+                        }
+                    }
+                    AuthType::Anonymous | AuthType::OAuth2Trust => {}
                 }
-                AuthType::Anonymous | AuthType::OAuth2Trust => {}
             }
 
             // Did anything get set-up?
@@ -1399,7 +1447,7 @@ impl AuthSession {
                 let next_auth_state = handler.next_auth_state();
                 let read_write = match reauth_req {
                     ReauthRequest::GrantReadWrite => true,
-                    ReauthRequest::VerifyCredentials => false,
+                    ReauthRequest::VerifyCredentials | ReauthRequest::StepUp(_) => false,
                 };
 
                 let auth_session = AuthSession {
@@ -1413,6 +1461,7 @@ impl AuthSession {
                         session_expiry,
                     },
                     source: asd.client_auth_info.source,
+                    user_agent: asd.client_auth_info.user_agent,
                     key_object,
                 };
 
@@ -1657,11 +1706,15 @@ impl AuthSession {
                     self.account.uuid
                 );
 
-                let uat = self
+                let mut uat = self
                     .account
                     .to_userauthtoken(session_id, scope, time, &self.account_policy)
                     .ok_or(OperationError::AU0004UserAuthTokenInvalid)?;
 
+                self.restrict_if_credential_expired(&mut uat, cred_id, time);
+                uat.acr = Acr::from_auth_type(auth_type).map(|acr| acr.to_string());
+                uat.acr_verified_at = Some(OffsetDateTime::UNIX_EPOCH + time);
+
                 // Queue the session info write.
                 // This is dependent on the type of authentication factors
                 // used. Generally we won't submit for Anonymous. Add an extra
@@ -1691,6 +1744,8 @@ impl AuthSession {
                             scope,
                             type_: auth_type,
                             ext_metadata,
+                            source_ip: self.source.ip_addr(),
+                            user_agent: self.user_agent.clone(),
                         }))
                         .map_err(|e| {
                             debug!(?e, "queue failure");
@@ -1722,7 +1777,7 @@ impl AuthSession {
                     | AuthType::AttestedPasskey => SessionScope::PrivilegeCapable,
                 };
 
-                let uat = self
+                let mut uat = self
                     .account
                     .to_reissue_userauthtoken(
                         session_id,
@@ -1734,11 +1789,39 @@ impl AuthSession {
                     )
                     .ok_or(OperationError::AU0007UserAuthTokenInvalid)?;
 
+                // A reauth must not lift the restriction of an expired credential.
+                self.restrict_if_credential_expired(&mut uat, cred_id, time);
+                // The session now satisfies the class of the credential that was just verified.
+                uat.acr = Acr::from_auth_type(auth_type).map(|acr| acr.to_string());
+                uat.acr_verified_at = Some(OffsetDateTime::UNIX_EPOCH + time);
+
                 Ok(uat)
             }
         }
     }
 
+    /// If the credential used to authenticate has exceeded the maximum age set by the
+    /// account policy, restrict the session so that it can only update credentials.
+    fn restrict_if_credential_expired(
+        &self,
+        uat: &mut UserAuthToken,
+        cred_id: Uuid,
+        time: Duration,
+    ) {
+        let Some(max_age) = self.account_policy.credential_max_age() else {
+            return;
+        };
+
+        if self.account.is_credential_expired(cred_id, max_age, time) {
+            security_info!(
+                "Credential {} of {} has expired, restricting session to credential update",
+                cred_id,
+                self.account.uuid
+            );
+            uat.purpose = UatPurpose::CredentialUpdate;
+        }
+    }
+
     /// End the session, defaulting to a denied.
     pub fn end_session(&mut self, reason: &'static str) -> Result<AuthState, OperationError> {
         let mut next_state = AuthSessionState::Denied(reason);
@@ -1968,6 +2051,7 @@ mod tests {
         let uat = start_session_simple_password_mech(false);
         match uat.purpose {
             UatPurpose::ReadOnly => panic!("Unexpected UatPurpose::ReadOnly"),
+            UatPurpose::CredentialUpdate => panic!("Unexpected UatPurpose::CredentialUpdate"),
             UatPurpose::ReadWrite { expiry } => {
                 // Long lived RO session capable of reauth
                 assert!(expiry.is_none())
@@ -1981,6 +2065,7 @@ mod tests {
         let uat = start_session_simple_password_mech(true);
         match uat.purpose {
             UatPurpose::ReadOnly => panic!("Unexpected UatPurpose::ReadOnly"),
+            UatPurpose::CredentialUpdate => panic!("Unexpected UatPurpose::CredentialUpdate"),
             UatPurpose::ReadWrite { expiry } => {
                 // Short lived RW session
                 assert!(expiry.is_some())
@@ -0,0 +1,93 @@
+//! A local breached password list, laid out in the same manner as the Have I Been Pwned
+//! range API. The list is a directory of files, each named by the first five characters of
+//! the hex encoded SHA1 of a password (for example `5BAA6.txt`). Each file contains lines of
+//! `SUFFIX:COUNT` where suffix is the remaining 35 characters of the hash.
+//!
+//! This means that only a single small file needs to be read to check a password, and
+//! the cleartext password never needs to be written anywhere.
+
+use crate::prelude::*;
+use crypto_glue::{sha1::Sha1, traits::Digest};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+const BREACH_LIST_PREFIX_LEN: usize = 5;
+
+#[derive(Debug, Clone)]
+pub struct PasswordBreachList {
+    path: PathBuf,
+}
+
+impl PasswordBreachList {
+    pub fn new(path: PathBuf) -> Result<Self, OperationError> {
+        if !path.is_dir() {
+            error!(
+                ?path,
+                "Password breach list must be a directory of range files"
+            );
+            return Err(OperationError::FsError);
+        }
+        Ok(PasswordBreachList { path })
+    }
+
+    pub(crate) fn contains(&self, cleartext: &str) -> Result<bool, OperationError> {
+        let digest = hex::encode_upper(Sha1::digest(cleartext.as_bytes()));
+        let (prefix, suffix) = digest.split_at(BREACH_LIST_PREFIX_LEN);
+
+        let range_path = self.path.join(format!("{prefix}.txt"));
+        let range = match fs::read_to_string(&range_path) {
+            Ok(range) => range,
+            // No range file means that no breached password shares this prefix.
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(false),
+            Err(err) => {
+                error!(?err, path = ?range_path, "Unable to read password breach list");
+                return Err(OperationError::FsError);
+            }
+        };
+
+        Ok(range.lines().any(|line| {
+            line.split(':')
+                .next()
+                .is_some_and(|line_suffix| line_suffix.trim().eq_ignore_ascii_case(suffix))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PasswordBreachList;
+    use crate::prelude::*;
+    use std::fs;
+
+    #[test]
+    fn test_password_breach_list() {
+        let path = std::env::temp_dir().join(format!("kanidm-breachlist-{}", Uuid::new_v4()));
+        fs::create_dir_all(&path).expect("Unable to create breach list dir");
+
+        // sha1("password") = 5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8
+        fs::write(
+            path.join("5BAA6.txt"),
+            "003D68EB55068C33ACE09247EE4C639306B:3\r\n1E4C9B93F3F0682250B6CF8331B7EE68FD8:10437277\r\n",
+        )
+        .expect("Unable to write range file");
+
+        let breach_list = PasswordBreachList::new(path.clone()).expect("Invalid breach list");
+
+        assert_eq!(breach_list.contains("password"), Ok(true));
+        // No range file exists for this prefix.
+        assert_eq!(
+            breach_list.contains("correct horse battery staple"),
+            Ok(false)
+        );
+        // Case matters for the password itself.
+        assert_eq!(breach_list.contains("Password"), Ok(false));
+
+        assert_eq!(
+            PasswordBreachList::new(path.join("5BAA6.txt")).unwrap_err(),
+            OperationError::FsError
+        );
+
+        fs::remove_dir_all(&path).expect("Unable to remove breach list dir");
+    }
+}
@@ -5,6 +5,7 @@ use crate::idm::account::Account;
 use crate::idm::server::{IdmServerCredUpdateTransaction, IdmServerProxyWriteTransaction};
 use crate::prelude::*;
 use crate::server::access::Access;
+use crate::server::security_event::{SecurityEvent, SecurityEventKind, SecurityEventOutcome};
 use crate::utils::{backup_code_from_random, readable_password_from_random, uuid_from_duration};
 use crate::value::{CredUpdateSessionPerms, CredentialType, IntentTokenState, LABEL_RE};
 use compact_jwt::compact::JweCompact;
@@ -18,6 +19,7 @@ use kanidm_proto::internal::{
 use kanidm_proto::v1::OutboundMessage;
 use serde::{Deserialize, Serialize};
 use sshkey_attest::proto::PublicKey as SshPublicKey;
+use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::fmt::{self, Display};
 use std::sync::{Arc, Mutex};
@@ -27,7 +29,7 @@ use webauthn_rs::prelude::{
     AttestedPasskey as AttestedPasskeyV4, AttestedPasskeyRegistration, CreationChallengeResponse,
     Passkey as PasskeyV4, PasskeyRegistration, RegisterPublicKeyCredential, WebauthnError,
 };
-use zxcvbn::{zxcvbn, Score};
+use zxcvbn::zxcvbn;
 
 // A user can take up to 15 minutes to update their credentials before we automatically
 // cancel on them.
@@ -43,7 +45,10 @@ const MAXIMUM_INTENT_TTL: Duration = Duration::from_secs(86400);
 pub enum PasswordQuality {
     TooShort(u32),
     BadListed,
+    Breached,
+    BreachListUnavailable(OperationError),
     DontReusePasswords,
+    PreviouslyUsed,
     Feedback(Vec<PasswordFeedback>),
 }
 
@@ -173,6 +178,7 @@ impl fmt::Debug for CredentialUpdateSession {
             .map(|(uuid, (tag, _pk))| PasskeyDetail {
                 tag: tag.clone(),
                 uuid: *uuid,
+                last_used: self.passkey_last_used(uuid),
             })
             .collect();
         let attested_passkeys: Vec<PasskeyDetail> = self
@@ -181,6 +187,7 @@ impl fmt::Debug for CredentialUpdateSession {
             .map(|(uuid, (tag, _pk))| PasskeyDetail {
                 tag: tag.clone(),
                 uuid: *uuid,
+                last_used: self.passkey_last_used(uuid),
             })
             .collect();
         f.debug_struct("CredentialUpdateSession")
@@ -200,6 +207,13 @@ impl fmt::Debug for CredentialUpdateSession {
 }
 
 impl CredentialUpdateSession {
+    fn passkey_last_used(&self, uuid: &Uuid) -> Option<OffsetDateTime> {
+        self.account
+            .passkey_usage()
+            .get(uuid)
+            .map(|usage| usage.last_used)
+    }
+
     // Vec of the issues with the current session so that UI's can highlight properly how to proceed.
     fn can_commit(&self) -> (bool, Vec<CredentialUpdateSessionStatusWarnings>) {
         let mut warnings = Vec::with_capacity(0);
@@ -462,6 +476,7 @@ impl From<&CredentialUpdateSession> for CredentialUpdateSessionStatus {
                 .map(|(uuid, (tag, _pk))| PasskeyDetail {
                     tag: tag.clone(),
                     uuid: *uuid,
+                    last_used: session.passkey_last_used(uuid),
                 })
                 .collect(),
             passkeys_state: session.passkeys_state,
@@ -471,6 +486,7 @@ impl From<&CredentialUpdateSession> for CredentialUpdateSessionStatus {
                 .map(|(uuid, (tag, _pk))| PasskeyDetail {
                     tag: tag.clone(),
                     uuid: *uuid,
+                    last_used: session.passkey_last_used(uuid),
                 })
                 .collect(),
             attested_passkeys_state: session.attested_passkeys_state,
@@ -586,12 +602,19 @@ impl IdmServerProxyWriteTransaction<'_> {
         );
 
         // The initiating identity must be in readwrite mode! Effective permission assumes you
-        // are in rw.
-        if ident.access_scope() != AccessScope::ReadWrite {
-            security_access!("identity access scope is not permitted to modify");
-            security_access!("denied ❌");
-            return Err(OperationError::AccessDenied);
-        }
+        // are in rw. A session restricted due to an expired credential may only update its
+        // own credentials.
+        let ident = match ident.access_scope() {
+            AccessScope::ReadWrite => Cow::Borrowed(ident),
+            AccessScope::CredentialUpdate if ident.get_uuid() == target => {
+                Cow::Owned(ident.project_with_scope(AccessScope::ReadWrite))
+            }
+            _ => {
+                security_access!("identity access scope is not permitted to modify");
+                security_access!("denied ❌");
+                return Err(OperationError::AccessDenied);
+            }
+        };
 
         // Is target an account? This checks for us.
         let (account, resolved_account_policy) =
@@ -601,7 +624,7 @@ impl IdmServerProxyWriteTransaction<'_> {
             .qs_write
             .get_accesscontrols()
             .effective_permission_check(
-                ident,
+                &ident,
                 Some(btreeset![
                     Attribute::PrimaryCredential,
                     Attribute::PassKeys,
@@ -742,7 +765,7 @@ impl IdmServerProxyWriteTransaction<'_> {
                 .qs_write
                 .get_accesscontrols()
                 .effective_permission_check(
-                    ident,
+                    &ident,
                     Some(btreeset![Attribute::SyncCredentialPortal]),
                     &[entry],
                 )?;
@@ -1011,6 +1034,15 @@ impl IdmServerProxyWriteTransaction<'_> {
 
         let target = entry.get_uuid();
 
+        // Only a verified address may be used to recover an account. We report this the
+        // same as a missing address to avoid disclosing which addresses are registered.
+        if !entry
+            .get_ava_mail_verified(Attribute::Mail)
+            .is_some_and(|verified| verified.contains(&event.email))
+        {
+            return Err(OperationError::CU0009AccountEmailNotFound);
+        }
+
         // Verify our internal service account has access to modify the target.
         //
         // IMPORTANT: We use the permissions of the *target* as this is effectively a "self-request"
@@ -1047,12 +1079,23 @@ impl IdmServerProxyWriteTransaction<'_> {
                     error!(spn = %account.spn(), "Requested email address is not present on account, unable to send credential reset.");
                     OperationError::CU0007AccountEmailNotFound
                 })
+                .and_then(|to_email| {
+                    account.mail_is_verified(&to_email)
+                        .then_some(to_email)
+                        .ok_or_else(|| {
+                            error!(spn = %account.spn(), "Requested email address is not verified, unable to send credential reset.");
+                            OperationError::CU0011AccountEmailNotVerified
+                        })
+                })
+        } else if account.mail().is_empty() {
+            error!(spn = %account.spn(), "account does not have a primary email address, unable to send credential reset.");
+            Err(OperationError::CU0008AccountMissingEmail)
         } else {
-            let maybe_to_email = account.mail_primary().map(String::from);
+            let maybe_to_email = account.mail_verified_primary().map(String::from);
 
             maybe_to_email.ok_or_else(|| {
-                error!(spn = %account.spn(), "account does not have a primary email address, unable to send credential reset.");
-                OperationError::CU0008AccountMissingEmail
+                error!(spn = %account.spn(), "account does not have a verified email address, unable to send credential reset.");
+                OperationError::CU0011AccountEmailNotVerified
             })
         }?;
 
@@ -1636,6 +1679,22 @@ impl IdmServerProxyWriteTransaction<'_> {
                     modlist.push_mod(Modify::Present(Attribute::PrimaryCredential, vcred));
 
                     cred_changed.get_or_insert(ncred.timestamp());
+
+                    // Remember the outgoing password so that it can't be reused.
+                    let history_length = session.resolved_account_policy.pw_history_length();
+                    let previous = account.primary().and_then(|c| c.password_ref().ok());
+                    if let Some(next) = ncred.password_ref().ok().filter(|pw| Some(*pw) != previous)
+                    {
+                        modlist.push_mod(Modify::Purged(Attribute::PasswordHistory));
+                        if history_length > 0 {
+                            let mut password_history = account.password_history().clone();
+                            password_history.record(previous, next, history_length);
+                            modlist.push_mod(Modify::Present(
+                                Attribute::PasswordHistory,
+                                Value::PrivateBinary(password_history.to_bytes()?),
+                            ));
+                        }
+                    }
                 };
             }
             CredentialState::DeleteOnly | CredentialState::PolicyDeny => {
@@ -1690,6 +1749,30 @@ impl IdmServerProxyWriteTransaction<'_> {
             CredentialState::AccessDeny => {}
         };
 
+        // Forget the usage of any passkeys that no longer exist.
+        let passkey_retained = |uuid: &Uuid| {
+            let passkey = match session.passkeys_state {
+                CredentialState::AccessDeny => account.passkeys.contains_key(uuid),
+                _ => session.passkeys.contains_key(uuid),
+            };
+            let attested_passkey = match session.attested_passkeys_state {
+                CredentialState::AccessDeny => account.attested_passkeys.contains_key(uuid),
+                _ => session.attested_passkeys.contains_key(uuid),
+            };
+            passkey || attested_passkey
+        };
+
+        account
+            .passkey_usage()
+            .keys()
+            .filter(|uuid| !passkey_retained(uuid))
+            .for_each(|uuid| {
+                modlist.push_mod(Modify::Removed(
+                    Attribute::PasskeyUsage,
+                    PartialValue::PasskeyUsage(*uuid),
+                ))
+            });
+
         match session.sshkeys_state {
             CredentialState::DeleteOnly | CredentialState::Modifiable => {
                 modlist.push_mod(Modify::Purged(Attribute::SshPublicKey));
@@ -1711,7 +1794,8 @@ impl IdmServerProxyWriteTransaction<'_> {
             trace!("no changes to apply");
             Ok(())
         } else {
-            self.qs_write
+            let result = self
+                .qs_write
                 .internal_modify(
                     // Filter as executed
                     &filter!(f_eq(
@@ -1723,7 +1807,19 @@ impl IdmServerProxyWriteTransaction<'_> {
                 .map_err(|e| {
                     request_error!(error = ?e);
                     e
-                })
+                });
+
+            let outcome = if result.is_ok() {
+                SecurityEventOutcome::Success
+            } else {
+                SecurityEventOutcome::Failure
+            };
+            self.qs_write.submit_security_event(
+                SecurityEvent::new(SecurityEventKind::CredentialChange, outcome, ct)
+                    .with_targets([session.account.uuid]),
+            );
+
+            result
         }
     }
 
@@ -1870,11 +1966,12 @@ impl IdmServerCredUpdateTransaction<'_> {
         resolved_account_policy: &ResolvedAccountPolicy,
         related_inputs: &[&str],
         radius_secret: Option<&str>,
+        previous_passwords: Option<&Account>,
     ) -> Result<(), PasswordQuality> {
-        // password strength and badlisting is always global, rather than per-pw-policy.
-        // pw-policy as check on the account is about requirements for mfa for example.
+        // The badlist is always global, but length, strength and the breach list check
+        // are resolved from the account policy of the groups the account is a member of.
 
-        // is the password at least 10 char?
+        // is the password long enough?
         let pw_min_length = resolved_account_policy.pw_min_length();
         if cleartext.len() < pw_min_length as usize {
             return Err(PasswordQuality::TooShort(pw_min_length));
@@ -1901,8 +1998,8 @@ impl IdmServerCredUpdateTransaction<'_> {
         // does the password pass zxcvbn?
         let entropy = zxcvbn(cleartext, related_inputs);
 
-        // PW's should always be enforced as strong as possible.
-        if entropy.score() < Score::Four {
+        let pw_min_score = resolved_account_policy.pw_min_score();
+        if u32::from(u8::from(entropy.score())) < pw_min_score {
             // The password is too week as per:
             // https://docs.rs/zxcvbn/2.0.0/zxcvbn/struct.Entropy.html
             let feedback: zxcvbn::feedback::Feedback = entropy
@@ -1913,6 +2010,7 @@ impl IdmServerCredUpdateTransaction<'_> {
                     security_info!("zxcvbn returned no feedback when score < 3 -> {:?}", e);
                     // Return some generic feedback when the password is this bad.
                     PasswordQuality::Feedback(vec![
+                        PasswordFeedback::TooWeak(pw_min_score),
                         PasswordFeedback::UseAFewWordsAvoidCommonPhrases,
                         PasswordFeedback::AddAnotherWordOrTwo,
                         PasswordFeedback::NoNeedForSymbolsDigitsOrUppercaseLetters,
@@ -1921,7 +2019,7 @@ impl IdmServerCredUpdateTransaction<'_> {
 
             security_info!(?feedback, "pw quality feedback");
 
-            let feedback: Vec<_> = feedback
+            let mut feedback: Vec<_> = feedback
                 .suggestions()
                 .iter()
                 .map(|s| {
@@ -2013,6 +2111,9 @@ impl IdmServerCredUpdateTransaction<'_> {
                 }))
                 .collect();
 
+            // Lead with the requirement that wasn't met.
+            feedback.insert(0, PasswordFeedback::TooWeak(pw_min_score));
+
             return Err(PasswordQuality::Feedback(feedback));
         }
 
@@ -2025,10 +2126,37 @@ impl IdmServerCredUpdateTransaction<'_> {
             .contains(&cleartext.to_lowercase())
         {
             security_info!("Password found in badlist, rejecting");
-            Err(PasswordQuality::BadListed)
-        } else {
-            Ok(())
+            return Err(PasswordQuality::BadListed);
         }
+
+        if resolved_account_policy.pw_breach_check() {
+            match self.password_breach_list {
+                Some(breach_list) => {
+                    let breached = breach_list
+                        .contains(cleartext)
+                        .map_err(PasswordQuality::BreachListUnavailable)?;
+                    if breached {
+                        security_info!("Password found in breach list, rejecting");
+                        return Err(PasswordQuality::Breached);
+                    }
+                }
+                None => {
+                    warn!("Account policy requires a password breach check, but no password breach list is configured");
+                }
+            }
+        }
+
+        // Checked last, as each entry in the history requires a full hash verification.
+        if let Some(account) = previous_passwords {
+            if account
+                .password_previously_used(cleartext, resolved_account_policy.pw_history_length())
+            {
+                security_info!("Password found in password history, rejecting");
+                return Err(PasswordQuality::PreviouslyUsed);
+            }
+        }
+
+        Ok(())
     }
 
     #[instrument(level = "trace", skip(cust, self))]
@@ -2050,6 +2178,7 @@ impl IdmServerCredUpdateTransaction<'_> {
             &session.resolved_account_policy,
             session.account.related_inputs().as_slice(),
             session.account.radius_secret.as_deref(),
+            Some(&session.account),
         )
         .map_err(|e| match e {
             PasswordQuality::TooShort(sz) => {
@@ -2058,9 +2187,16 @@ impl IdmServerCredUpdateTransaction<'_> {
             PasswordQuality::BadListed => {
                 OperationError::PasswordQuality(vec![PasswordFeedback::BadListed])
             }
+            PasswordQuality::Breached => {
+                OperationError::PasswordQuality(vec![PasswordFeedback::Breached])
+            }
+            PasswordQuality::BreachListUnavailable(err) => err,
             PasswordQuality::DontReusePasswords => {
                 OperationError::PasswordQuality(vec![PasswordFeedback::DontReusePasswords])
             }
+            PasswordQuality::PreviouslyUsed => {
+                OperationError::PasswordQuality(vec![PasswordFeedback::PreviouslyUsed])
+            }
             PasswordQuality::Feedback(feedback) => OperationError::PasswordQuality(feedback),
         })?;
 
@@ -2093,6 +2229,7 @@ impl IdmServerCredUpdateTransaction<'_> {
             &session.resolved_account_policy,
             session.account.related_inputs().as_slice(),
             session.account.radius_secret.as_deref(),
+            Some(&session.account),
         )
         .map_err(|e| match e {
             PasswordQuality::TooShort(sz) => {
@@ -2101,9 +2238,16 @@ impl IdmServerCredUpdateTransaction<'_> {
             PasswordQuality::BadListed => {
                 OperationError::PasswordQuality(vec![PasswordFeedback::BadListed])
             }
+            PasswordQuality::Breached => {
+                OperationError::PasswordQuality(vec![PasswordFeedback::Breached])
+            }
+            PasswordQuality::BreachListUnavailable(err) => err,
             PasswordQuality::DontReusePasswords => {
                 OperationError::PasswordQuality(vec![PasswordFeedback::DontReusePasswords])
             }
+            PasswordQuality::PreviouslyUsed => {
+                OperationError::PasswordQuality(vec![PasswordFeedback::PreviouslyUsed])
+            }
             PasswordQuality::Feedback(feedback) => OperationError::PasswordQuality(feedback),
         })?;
 
@@ -2708,6 +2852,7 @@ impl IdmServerCredUpdateTransaction<'_> {
             &session.resolved_account_policy,
             session.account.related_inputs().as_slice(),
             session.account.radius_secret.as_deref(),
+            None,
         )
         .map_err(|e| match e {
             PasswordQuality::TooShort(sz) => {
@@ -2716,9 +2861,16 @@ impl IdmServerCredUpdateTransaction<'_> {
             PasswordQuality::BadListed => {
                 OperationError::PasswordQuality(vec![PasswordFeedback::BadListed])
             }
+            PasswordQuality::Breached => {
+                OperationError::PasswordQuality(vec![PasswordFeedback::Breached])
+            }
+            PasswordQuality::BreachListUnavailable(err) => err,
             PasswordQuality::DontReusePasswords => {
                 OperationError::PasswordQuality(vec![PasswordFeedback::DontReusePasswords])
             }
+            PasswordQuality::PreviouslyUsed => {
+                OperationError::PasswordQuality(vec![PasswordFeedback::PreviouslyUsed])
+            }
             PasswordQuality::Feedback(feedback) => OperationError::PasswordQuality(feedback),
         })?;
 
@@ -2855,10 +3007,13 @@ mod tests {
         InitCredentialUpdateIntentSendEvent, MfaRegStateStatus, MAXIMUM_CRED_UPDATE_TTL,
         MAXIMUM_INTENT_TTL, MINIMUM_INTENT_TTL,
     };
+    use crate::credential::history::PasswordHistory;
     use crate::credential::totp::Totp;
     use crate::event::CreateEvent;
+    use crate::idm::account::Account;
     use crate::idm::audit::AuditEvent;
     use crate::idm::authentication::AuthState;
+    use crate::idm::breachlist::PasswordBreachList;
     use crate::idm::delayed::DelayedAction;
     use crate::idm::event::{
         AuthEvent, AuthResult, RegenerateRadiusSecretEvent, UnixUserAuthEvent,
@@ -3821,6 +3976,7 @@ mod tests {
         assert!(
             matches!(err, OperationError::PasswordQuality(details) if details
             == vec!(
+                PasswordFeedback::TooWeak(PW_MIN_SCORE),
                 PasswordFeedback::AddAnotherWordOrTwo,
                 PasswordFeedback::ThisIsACommonPassword,
             ))
@@ -3937,6 +4093,263 @@ mod tests {
         commit_session(idms, ct, cust).await;
     }
 
+    #[idm_test]
+    async fn credential_update_password_min_score_account_policy(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+
+        // One group relaxes the score, but another of the account's groups is stricter.
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+
+        let modlist =
+            ModifyList::new_purge_and_set(Attribute::AuthPasswordMinimumScore, Value::Uint32(0));
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(UUID_IDM_ALL_ACCOUNTS, &modlist)
+            .expect("Unable to change password minimum score");
+
+        let modlist =
+            ModifyList::new_purge_and_set(Attribute::AuthPasswordMinimumScore, Value::Uint32(4));
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(UUID_IDM_ALL_PERSONS, &modlist)
+            .expect("Unable to change password minimum score");
+
+        assert!(idms_prox_write.commit().is_ok());
+
+        let (cust, _) = setup_test_session(idms, ct).await;
+
+        let cutxn = idms.cred_update_transaction().await.unwrap();
+
+        let err = cutxn
+            .credential_primary_set_password(&cust, ct, "password12345")
+            .unwrap_err();
+        trace!(?err);
+        assert!(
+            matches!(err, OperationError::PasswordQuality(details) if details.first() == Some(&PasswordFeedback::TooWeak(4)))
+        );
+
+        drop(cutxn);
+    }
+
+    #[idm_test]
+    async fn credential_update_password_breach_check_account_policy(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+
+        // Relax the score so that only the breach list decides.
+        let modlist =
+            ModifyList::new_purge_and_set(Attribute::AuthPasswordMinimumScore, Value::Uint32(0));
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(UUID_IDM_ALL_ACCOUNTS, &modlist)
+            .expect("Unable to change password minimum score");
+
+        let modlist =
+            ModifyList::new_purge_and_set(Attribute::AuthPasswordBreachCheck, Value::Bool(true));
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(UUID_IDM_ALL_PERSONS, &modlist)
+            .expect("Unable to enable password breach check");
+
+        assert!(idms_prox_write.commit().is_ok());
+
+        // sha1("password1234") = E6B6AFBD6D76BB5D2041542D7D2E3FAC5BB05593
+        let breach_list_path =
+            std::env::temp_dir().join(format!("kanidm-breachlist-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&breach_list_path).expect("Unable to create breach list dir");
+        std::fs::write(
+            breach_list_path.join("E6B6A.txt"),
+            "FBD6D76BB5D2041542D7D2E3FAC5BB05593:2179\n",
+        )
+        .expect("Unable to write range file");
+        let breach_list =
+            PasswordBreachList::new(breach_list_path.clone()).expect("Invalid breach list");
+
+        let (cust, _) = setup_test_session(idms, ct).await;
+
+        let mut cutxn = idms.cred_update_transaction().await.unwrap();
+        cutxn.password_breach_list = Some(&breach_list);
+
+        let err = cutxn
+            .credential_primary_set_password(&cust, ct, "password1234")
+            .unwrap_err();
+        trace!(?err);
+        assert!(
+            matches!(err, OperationError::PasswordQuality(details) if details == vec!(PasswordFeedback::Breached))
+        );
+
+        // A password that isn't in the list is accepted, as the score was relaxed.
+        let c_status = cutxn
+            .credential_primary_set_password(&cust, ct, "password12345")
+            .expect("Failed to update the primary cred password");
+        assert!(c_status.can_commit);
+
+        drop(cutxn);
+        commit_session(idms, ct, cust).await;
+
+        std::fs::remove_dir_all(&breach_list_path).expect("Unable to remove breach list dir");
+    }
+
+    #[idm_test]
+    async fn credential_update_password_history(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let test_pw = |i: u32| {
+            format!("fo3EitierohF9AelaNgiem0Ei6vup4equo1Oogeevaetehah8Tobeengae3Ci0ooh0uki{i}")
+        };
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+
+        let (cust, _) = setup_test_session(idms, ct).await;
+        let cutxn = idms.cred_update_transaction().await.unwrap();
+        cutxn
+            .credential_primary_set_password(&cust, ct, &test_pw(0))
+            .expect("Failed to update the primary cred password");
+        drop(cutxn);
+        commit_session(idms, ct, cust).await;
+
+        // Fill the history, so that the first password is the oldest we remember.
+        for i in 1..PW_HISTORY_LEN {
+            let (cust, _) = renew_test_session(idms, ct).await;
+            let cutxn = idms.cred_update_transaction().await.unwrap();
+            cutxn
+                .credential_primary_set_password(&cust, ct, &test_pw(i))
+                .expect("Failed to update the primary cred password");
+            drop(cutxn);
+            commit_session(idms, ct, cust).await;
+        }
+
+        let (cust, _) = renew_test_session(idms, ct).await;
+        let cutxn = idms.cred_update_transaction().await.unwrap();
+
+        // Neither the oldest password nor the current one may be reused.
+        for i in [0, PW_HISTORY_LEN - 1] {
+            let err = cutxn
+                .credential_primary_set_password(&cust, ct, &test_pw(i))
+                .unwrap_err();
+            assert!(
+                matches!(err, OperationError::PasswordQuality(details) if details == vec!(PasswordFeedback::PreviouslyUsed))
+            );
+        }
+
+        cutxn
+            .credential_primary_set_password(&cust, ct, &test_pw(PW_HISTORY_LEN))
+            .expect("Failed to update the primary cred password");
+        drop(cutxn);
+        commit_session(idms, ct, cust).await;
+
+        // The history has been trimmed, so the first password is now allowed again.
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        let entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(TESTPERSON_UUID)
+            .expect("failed");
+        let history = entry
+            .get_ava_single_private_binary(Attribute::PasswordHistory)
+            .map(PasswordHistory::from_bytes)
+            .expect("Missing password history")
+            .expect("Invalid password history");
+        assert_eq!(history.len(), PW_HISTORY_LEN as usize);
+        drop(idms_prox_write);
+
+        let (cust, _) = renew_test_session(idms, ct).await;
+        let cutxn = idms.cred_update_transaction().await.unwrap();
+        cutxn
+            .credential_primary_set_password(&cust, ct, &test_pw(0))
+            .expect("Failed to update the primary cred password");
+        drop(cutxn);
+        commit_session(idms, ct, cust).await;
+
+        // Deleting the account purges the history.
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        idms_prox_write
+            .qs_write
+            .internal_delete_uuid(TESTPERSON_UUID)
+            .expect("Failed to delete account");
+        let entry = idms_prox_write
+            .qs_write
+            .internal_search_all_uuid(TESTPERSON_UUID)
+            .expect("failed");
+        assert!(!entry.attribute_pres(Attribute::PasswordHistory));
+        assert!(idms_prox_write.commit().is_ok());
+    }
+
+    #[idm_test]
+    async fn credential_update_password_history_account_policy(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let test_pw = |i: u32| {
+            format!("fo3EitierohF9AelaNgiem0Ei6vup4equo1Oogeevaetehah8Tobeengae3Ci0ooh0uki{i}")
+        };
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+
+        let (cust, _) = setup_test_session(idms, ct).await;
+        let cutxn = idms.cred_update_transaction().await.unwrap();
+        cutxn
+            .credential_primary_set_password(&cust, ct, &test_pw(0))
+            .expect("Failed to update the primary cred password");
+        drop(cutxn);
+        commit_session(idms, ct, cust).await;
+
+        // A history that can't be read is ignored rather than locking the account out, and
+        // the policy shortens the history.
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                TESTPERSON_UUID,
+                &ModifyList::new_purge_and_set(
+                    Attribute::PasswordHistory,
+                    Value::PrivateBinary(b"not a password history".to_vec()),
+                ),
+            )
+            .expect("Unable to corrupt password history");
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_IDM_ALL_ACCOUNTS,
+                &ModifyList::new_purge_and_set(
+                    Attribute::AuthPasswordHistoryLength,
+                    Value::Uint32(2),
+                ),
+            )
+            .expect("Unable to change password history length");
+        assert!(idms_prox_write.commit().is_ok());
+
+        for i in 1..=2 {
+            let (cust, _) = renew_test_session(idms, ct).await;
+            let cutxn = idms.cred_update_transaction().await.unwrap();
+            cutxn
+                .credential_primary_set_password(&cust, ct, &test_pw(i))
+                .expect("Failed to update the primary cred password");
+            drop(cutxn);
+            commit_session(idms, ct, cust).await;
+        }
+
+        // Only the two most recent passwords are remembered.
+        let (cust, _) = renew_test_session(idms, ct).await;
+        let cutxn = idms.cred_update_transaction().await.unwrap();
+        let err = cutxn
+            .credential_primary_set_password(&cust, ct, &test_pw(1))
+            .unwrap_err();
+        assert!(
+            matches!(err, OperationError::PasswordQuality(details) if details == vec!(PasswordFeedback::PreviouslyUsed))
+        );
+        cutxn
+            .credential_primary_set_password(&cust, ct, &test_pw(0))
+            .expect("Failed to update the primary cred password");
+        drop(cutxn);
+        commit_session(idms, ct, cust).await;
+    }
+
     // Test set of primary account password
     //    - fail pw quality checks etc
     //    - set correctly.
@@ -5754,11 +6167,16 @@ mod tests {
             .expect("Unable to set allow_primary_cred_fallback");
         idms_prox_write.commit().expect("Failed to commit txn");
 
-        // With fallback enabled PrimaryCred timestamp is used despite not being POSIX account
+        // With fallback enabled PrimaryCred timestamp is used despite not being POSIX account.
+        // The previous password may not be reused, so set a new one.
         let (cust, _) = renew_test_session(idms, ct).await;
         let cutxn = idms.cred_update_transaction().await.unwrap();
         let _ = cutxn
-            .credential_primary_set_password(&cust, ct, TESTPERSON_PASSWORD)
+            .credential_primary_set_password(
+                &cust,
+                ct,
+                "fo3EitierohF9AelaNgiem0Ei6vup4equo1Oogeevaetehah8Tobeengae3Ci0ooh0uki",
+            )
             .expect("Failed to set primary password");
         drop(cutxn);
         commit_session(idms, ct, cust).await;
@@ -5918,4 +6336,128 @@ mod tests {
             .expect("PasswordChangedTime should be set after switching to passkey");
         assert_eq!(pwd_changed_2, OffsetDateTime::UNIX_EPOCH + ct);
     }
+
+    async fn testperson_account(idms: &IdmServer) -> Account {
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+        let entry = idms_prox_read
+            .qs_read
+            .internal_search_uuid(TESTPERSON_UUID)
+            .expect("Failed to find testperson");
+        Account::try_from_entry_ro(&entry, &mut idms_prox_read.qs_read)
+            .expect("Failed to load account")
+    }
+
+    #[idm_test]
+    async fn credential_update_passkey_usage(
+        idms: &IdmServer,
+        idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+
+        let (cust, _) = setup_test_session(idms, ct).await;
+        let cutxn = idms.cred_update_transaction().await.unwrap();
+        let origin = cutxn.get_origin().clone();
+
+        let mut wa_used = SoftPasskey::new(true);
+        let mut wa_stale = SoftPasskey::new(true);
+        let c_status = create_new_passkey(ct, &origin, &cutxn, &cust, &mut wa_used).await;
+        let used_uuid = c_status.passkeys.first().map(|pkd| pkd.uuid).unwrap();
+        // Nothing has authenticated yet.
+        assert!(c_status.passkeys.iter().all(|pkd| pkd.last_used.is_none()));
+        drop(cutxn);
+        commit_session(idms, ct, cust).await;
+
+        let (cust, _) = renew_test_session(idms, ct).await;
+        let cutxn = idms.cred_update_transaction().await.unwrap();
+        let c_status = cutxn
+            .credential_passkey_init(&cust, ct)
+            .expect("Failed to initiate passkey registration");
+        let passkey_chal = match c_status.mfaregstate {
+            MfaRegStateStatus::Passkey(c) => Some(c),
+            _ => None,
+        }
+        .expect("Unable to access passkey challenge, invalid state");
+        let passkey_resp = wa_stale
+            .do_registration(origin.clone(), passkey_chal)
+            .expect("Failed to create soft passkey");
+        let c_status = cutxn
+            .credential_passkey_finish(&cust, ct, "stale".to_string(), &passkey_resp)
+            .expect("Failed to finish passkey registration");
+        let stale_uuid = c_status
+            .passkeys
+            .iter()
+            .map(|pkd| pkd.uuid)
+            .find(|uuid| *uuid != used_uuid)
+            .unwrap();
+        drop(cutxn);
+        commit_session(idms, ct, cust).await;
+
+        // Authenticate with both, but only use one of them again much later.
+        let stale_ct = ct + Duration::from_secs(60);
+        assert!(check_testperson_passkey(
+            idms,
+            idms_delayed,
+            &mut wa_stale,
+            origin.clone(),
+            stale_ct
+        )
+        .await
+        .is_some());
+        let used_ct = ct + Duration::from_secs(86400 * 400);
+        assert!(check_testperson_passkey(
+            idms,
+            idms_delayed,
+            &mut wa_used,
+            origin.clone(),
+            used_ct
+        )
+        .await
+        .is_some());
+
+        let account = testperson_account(idms).await;
+        let usage = account.passkey_usage();
+        assert_eq!(
+            usage.get(&used_uuid).map(|pu| pu.last_used),
+            Some(OffsetDateTime::UNIX_EPOCH + used_ct)
+        );
+        assert_eq!(
+            usage.get(&stale_uuid).map(|pu| pu.last_used),
+            Some(OffsetDateTime::UNIX_EPOCH + stale_ct)
+        );
+        assert!(usage.values().all(|pu| pu.counter > 0));
+
+        // Only the passkey that hasn't been used in the last year is reported.
+        let unused_since = OffsetDateTime::UNIX_EPOCH + used_ct - time::Duration::days(365);
+        assert_eq!(account.stale_passkeys(unused_since), vec![stale_uuid]);
+
+        // The credential status reports the last use of each passkey.
+        let (cust, c_status) = renew_test_session(idms, used_ct).await;
+        let last_used = |uuid| {
+            c_status
+                .passkeys
+                .iter()
+                .find(|pkd| pkd.uuid == uuid)
+                .and_then(|pkd| pkd.last_used)
+        };
+        assert_eq!(
+            last_used(used_uuid),
+            Some(OffsetDateTime::UNIX_EPOCH + used_ct)
+        );
+        assert_eq!(
+            last_used(stale_uuid),
+            Some(OffsetDateTime::UNIX_EPOCH + stale_ct)
+        );
+
+        // Removing the stale passkey forgets its usage.
+        let cutxn = idms.cred_update_transaction().await.unwrap();
+        cutxn
+            .credential_passkey_remove(&cust, used_ct, stale_uuid)
+            .expect("Failed to delete the passkey");
+        drop(cutxn);
+        commit_session(idms, used_ct, cust).await;
+
+        let account = testperson_account(idms).await;
+        assert!(account.passkey_usage().contains_key(&used_uuid));
+        assert!(!account.passkey_usage().contains_key(&stale_uuid));
+    }
 }
@@ -2,6 +2,7 @@ use crate::prelude::*;
 use crate::value::AuthType;
 use crate::value::SessionExtMetadata;
 use std::fmt;
+use std::net::IpAddr;
 use time::OffsetDateTime;
 use uuid::Uuid;
 use webauthn_rs::prelude::AuthenticationResult;
@@ -13,6 +14,7 @@ pub enum DelayedAction {
     WebauthnCounterIncrement(WebauthnCounterIncrement),
     BackupCodeRemoval(BackupCodeRemoval),
     AuthSessionRecord(AuthSessionRecord),
+    CredentialLockout(CredentialLockout),
 }
 
 pub struct PasswordUpgrade {
@@ -65,4 +67,13 @@ pub struct AuthSessionRecord {
     pub scope: SessionScope,
     pub type_: AuthType,
     pub ext_metadata: SessionExtMetadata,
+    pub source_ip: Option<IpAddr>,
+    pub user_agent: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct CredentialLockout {
+    pub target_uuid: Uuid,
+    pub cred_id: Uuid,
+    pub unlock_at: OffsetDateTime,
 }
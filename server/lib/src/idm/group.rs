@@ -9,6 +9,7 @@ use crate::prelude::*;
 use crate::value::PartialValue;
 
 use super::accountpolicy::{AccountPolicy, ResolvedAccountPolicy};
+use super::unix::UnixHomeDirectoryTemplate;
 
 // I hate that rust is forcing this to be public
 pub trait GroupType {}
@@ -17,10 +18,20 @@ pub trait GroupType {}
 pub(crate) struct Unix {
     name: String,
     gidnumber: u32,
+    home_directory_template: Option<UnixHomeDirectoryTemplate>,
+    default_shell: Option<String>,
 }
 
 impl GroupType for Unix {}
 
+#[derive(Debug, Clone)]
+pub(crate) struct Radius {
+    vlan_id: Option<u32>,
+    vlan_priority: u32,
+}
+
+impl GroupType for Radius {}
+
 impl GroupType for () {}
 
 #[derive(Debug, Clone)]
@@ -183,7 +194,16 @@ impl Group<Unix> {
             .get_ava_single_uint32(Attribute::GidNumber)
             .ok_or_else(|| OperationError::MissingAttribute(Attribute::GidNumber))?;
 
-        let user_group = try_from_entry!(value, Unix { name, gidnumber })?;
+        // An account's own group never carries unix templates.
+        let user_group = try_from_entry!(
+            value,
+            Unix {
+                name,
+                gidnumber,
+                home_directory_template: None,
+                default_shell: None,
+            }
+        )?;
 
         Ok(std::iter::once(user_group)
             .chain(Self::try_from_account_reduced(value, qs)?)
@@ -242,7 +262,31 @@ impl Group<Unix> {
             .get_ava_single_uint32(Attribute::GidNumber)
             .ok_or_else(|| OperationError::MissingAttribute(Attribute::GidNumber))?;
 
-        try_from_entry!(value, Unix { name, gidnumber })
+        let home_directory_template = value
+            .get_ava_single_utf8(Attribute::UnixHomeDirectoryTemplate)
+            .and_then(|template| template.parse().ok());
+
+        let default_shell = value
+            .get_ava_single_iutf8(Attribute::UnixDefaultShell)
+            .map(str::to_string);
+
+        try_from_entry!(
+            value,
+            Unix {
+                name,
+                gidnumber,
+                home_directory_template,
+                default_shell,
+            }
+        )
+    }
+
+    pub(crate) fn unix_home_directory_template(&self) -> Option<&UnixHomeDirectoryTemplate> {
+        self.inner.home_directory_template.as_ref()
+    }
+
+    pub(crate) fn unix_default_shell(&self) -> Option<&str> {
+        self.inner.default_shell.as_deref()
     }
 
     pub(crate) fn to_unixgrouptoken(&self) -> UnixGroupToken {
@@ -255,6 +299,50 @@ impl Group<Unix> {
     }
 }
 
+impl Group<Radius> {
+    pub fn try_from_account_reduced<'a, E, TXN>(
+        value: &Entry<E, EntryCommitted>,
+        qs: &mut TXN,
+    ) -> Result<Vec<Group<Radius>>, OperationError>
+    where
+        E: Committed,
+        TXN: QueryServerTransaction<'a>,
+    {
+        try_from_account!(value, qs)
+    }
+
+    pub fn try_from_entry<E>(value: &Entry<E, EntryCommitted>) -> Result<Self, OperationError>
+    where
+        E: Committed,
+        Entry<E, EntryCommitted>: GetUuid,
+    {
+        if !value.attribute_equality(Attribute::Class, &EntryClass::Group.into()) {
+            return Err(OperationError::MissingAttribute(Attribute::Group));
+        }
+
+        let vlan_id = value.get_ava_single_uint32(Attribute::RadiusVlanId);
+
+        let vlan_priority = value
+            .get_ava_single_uint32(Attribute::RadiusVlanPriority)
+            .unwrap_or_default();
+
+        try_from_entry!(
+            value,
+            Radius {
+                vlan_id,
+                vlan_priority,
+            }
+        )
+    }
+
+    /// The (priority, vlan) of this group, if it assigns a vlan.
+    pub(crate) fn radius_vlan(&self) -> Option<(u32, u32)> {
+        self.inner
+            .vlan_id
+            .map(|vlan_id| (self.inner.vlan_priority, vlan_id))
+    }
+}
+
 pub(crate) fn load_account_policy<'a, T>(
     value: &Entry<EntrySealed, EntryCommitted>,
     qs: &mut T,
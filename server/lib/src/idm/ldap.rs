@@ -54,6 +54,8 @@ pub struct LdapBoundToken {
     // In a way, this is a stepping stone to an "ident" but allows us to check
     // the session is still "valid" depending on it's origin.
     pub effective_session: LdapSession,
+    // The address the bind was made from, so that we can tell where the session originated.
+    pub source_ip: Option<IpAddr>,
 }
 
 pub struct LdapServer {
@@ -217,9 +219,10 @@ impl LdapServer {
                 // OneLevel and Child searches are **very** similar for us because child
                 // is a "subtree search excluding base". Because we don't have a tree structure at
                 // all, this is the same as a one level (all children of base excluding base).
-                (LdapSearchScope::Children, Some(_r)) | (LdapSearchScope::OneLevel, Some(_r)) => {
-                    return Ok(vec![sr.gen_success()])
-                }
+                // The only entries that have children are automount maps, whose keys refer to
+                // the map. For everything else this matches nothing.
+                (LdapSearchScope::Children, Some((_a, v)))
+                | (LdapSearchScope::OneLevel, Some((_a, v))) => Some(automount_children_filter(v)),
                 (LdapSearchScope::Children, None) | (LdapSearchScope::OneLevel, None) => {
                     // exclude domain_info
                     Some(LdapFilter::Not(Box::new(LdapFilter::Equality(
@@ -227,10 +230,13 @@ impl LdapServer {
                         STR_UUID_DOMAIN_INFO.to_string(),
                     ))))
                 }
-                // because we request a specific DN, these are the same since we want the same
-                // entry.
-                (LdapSearchScope::Base, Some((a, v)))
-                | (LdapSearchScope::Subtree, Some((a, v))) => Some(LdapFilter::Equality(a, v)),
+                // because we request a specific DN, this is the entry itself.
+                (LdapSearchScope::Base, Some((a, v))) => Some(LdapFilter::Equality(a, v)),
+                // A subtree is the entry and, for automount maps, the keys beneath it.
+                (LdapSearchScope::Subtree, Some((a, v))) => Some(LdapFilter::Or(vec![
+                    automount_children_filter(v.clone()),
+                    LdapFilter::Equality(a, v),
+                ])),
                 (LdapSearchScope::Base, None) => {
                     // domain_info
                     Some(LdapFilter::Equality(
@@ -621,7 +627,16 @@ impl LdapServer {
                 .do_bind(idms, sbr.dn.as_str(), sbr.pw.as_str())
                 .await
                 .map(|r| match r {
-                    Some(lbt) => LdapResponseState::Bind(lbt, sbr.gen_success()),
+                    Some(mut lbt) => {
+                        security_info!(
+                            session_id = %lbt.session_id,
+                            spn = %lbt.spn,
+                            %ip_addr,
+                            "LDAP session bound"
+                        );
+                        lbt.source_ip = Some(ip_addr);
+                        LdapResponseState::Bind(lbt, sbr.gen_success())
+                    }
                     None => LdapResponseState::Respond(sbr.gen_invalid_cred()),
                 })
                 .or_else(|e| {
@@ -798,9 +813,21 @@ fn operationerr_to_ldapresultcode(e: OperationError) -> (LdapResultCode, String)
     }
 }
 
+/// Automount keys are presented as children of the map they refer to.
+fn automount_children_filter(map: String) -> LdapFilter {
+    LdapFilter::And(vec![
+        LdapFilter::Equality(
+            Attribute::Class.to_string(),
+            EntryClass::Automount.to_string(),
+        ),
+        LdapFilter::Equality(Attribute::Refers.to_string(), map),
+    ])
+}
+
 #[inline]
 pub(crate) fn ldap_all_vattrs() -> Vec<String> {
     vec![
+        ATTR_AUTOMOUNT_MAP_NAME.to_string(),
         ATTR_CN.to_string(),
         ATTR_EMAIL.to_string(),
         ATTR_LDAP_EMAIL_ADDRESS.to_string(),
@@ -826,8 +853,8 @@ pub(crate) fn ldap_all_vattrs() -> Vec<String> {
 pub(crate) fn ldap_vattr_map(input: &str) -> Option<&str> {
     // ⚠️  WARNING ⚠️
     // If you modify this list you MUST add these values to
-    // corresponding phantom attributes in the schema to prevent
-    // incorrect future or duplicate usage.
+    // corresponding phantom attributes in the schema, and to
+    // LDAP_PHANTOM_ATTRS, to prevent incorrect future or duplicate usage.
     //
     //   LDAP NAME     KANI ATTR SOURCE NAME
     match input {
@@ -837,6 +864,7 @@ pub(crate) fn ldap_vattr_map(input: &str) -> Option<&str> {
         // allowing the access control tests to take place. Otherwise no entries
         // would be returned.
         ATTR_CN | ATTR_UID | LDAP_ATTR_ENTRYDN | LDAP_ATTR_DN => Some(ATTR_NAME),
+        ATTR_AUTOMOUNT_MAP_NAME => Some(ATTR_NAME),
         ATTR_GECOS => Some(ATTR_DISPLAYNAME),
         ATTR_EMAIL => Some(ATTR_MAIL),
         ATTR_LDAP_EMAIL_ADDRESS => Some(ATTR_MAIL),
@@ -872,14 +900,46 @@ mod tests {
         LdapFilter, LdapMsg, LdapOp, LdapResultCode, LdapSearchScope, LdapSubstringFilter,
     };
     use ldap3_proto::simple::*;
+    use std::net::IpAddr;
 
-    use super::{LdapServer, LdapSession};
+    use super::{LdapResponseState, LdapServer, LdapSession};
     use crate::idm::application::GenerateApplicationPasswordEvent;
     use crate::idm::event::{LdapApplicationAuthEvent, UnixPasswordChangeEvent};
     use crate::idm::serviceaccount::GenerateApiTokenEvent;
+    use crate::server::security_event::SecurityEventKind;
 
     const TEST_PASSWORD: &str = "ntaoeuntnaoeuhraohuercahu😍";
 
+    #[idm_test]
+    async fn test_ldap_bind_records_source_ip(idms: &IdmServer, _idms_delayed: &IdmServerDelayed) {
+        let ldaps = LdapServer::new(idms).await.expect("failed to start ldap");
+        let source_ip: IpAddr = [192, 0, 2, 20].into();
+
+        let sbr = SimpleBindRequest {
+            msgid: 1,
+            dn: "".to_string(),
+            pw: "".to_string(),
+        };
+
+        let r = ldaps
+            .do_op(
+                idms,
+                ServerOps::SimpleBind(sbr),
+                None,
+                source_ip,
+                Uuid::new_v4(),
+            )
+            .await
+            .expect("Failed to bind");
+
+        let LdapResponseState::Bind(lbt, _) = r else {
+            panic!("Expected a bound ldap session");
+        };
+
+        assert_eq!(lbt.effective_session, LdapSession::UnixBind(UUID_ANONYMOUS));
+        assert_eq!(lbt.source_ip, Some(source_ip));
+    }
+
     #[idm_test]
     async fn test_ldap_simple_bind(idms: &IdmServer, _idms_delayed: &IdmServerDelayed) {
         let ldaps = LdapServer::new(idms).await.expect("failed to start ldap");
@@ -1730,6 +1790,11 @@ mod tests {
         let time = Duration::from_secs(TEST_CURRENT_TIME);
         let time_high = Duration::from_secs(TEST_AFTER_EXPIRY);
 
+        let mut event_rx = idms
+            .security_events()
+            .subscribe(8)
+            .expect("failed to subscribe");
+
         let mut idms_auth = idms.auth().await.unwrap();
         let lae = LdapApplicationAuthEvent::new(app1_name, usr_uuid, pass_app1)
             .expect("Failed to build auth event");
@@ -1740,17 +1805,30 @@ mod tests {
             .expect_err("Authentication succeeded");
         assert_eq!(r1, OperationError::SessionExpired);
 
+        let event = event_rx
+            .try_recv()
+            .expect("no security event was submitted");
+        assert_eq!(event.kind, SecurityEventKind::AccountNotYetValid);
+        assert_eq!(event.targets, vec![usr_uuid]);
+
         let r1 = idms_auth
             .application_auth_ldap(&lae, time)
             .await
             .expect("Failed auth");
         assert!(r1.is_some());
+        assert!(event_rx.try_recv().is_err());
 
         let r1 = idms_auth
             .application_auth_ldap(&lae, time_high)
             .await
             .expect_err("Authentication succeeded");
         assert_eq!(r1, OperationError::SessionExpired);
+
+        let event = event_rx
+            .try_recv()
+            .expect("no security event was submitted");
+        assert_eq!(event.kind, SecurityEventKind::AccountExpired);
+        assert_eq!(event.targets, vec![usr_uuid]);
     }
 
     macro_rules! assert_entry_contains {
@@ -2597,6 +2675,161 @@ mod tests {
         };
     }
 
+    #[idm_test]
+    async fn test_ldap_sssd_automount(idms: &IdmServer, _idms_delayed: &IdmServerDelayed) {
+        let ldaps = LdapServer::new(idms).await.expect("failed to start ldap");
+
+        let map_uuid = uuid!("6cf0ecda-3d0f-4a7e-9b4b-3e7c1f35c0d1");
+
+        {
+            let mut server_txn = idms.proxy_write(duration_from_epoch_now()).await.unwrap();
+
+            server_txn
+                .qs_write
+                .internal_modify_uuid(
+                    UUID_IDM_UNIX_AUTHENTICATION_READ,
+                    &ModifyList::new_append(Attribute::Member, Value::Refer(UUID_ANONYMOUS)),
+                )
+                .expect("Unable to modify UNIX_AUTHENTICATION_READ group");
+
+            let map = entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::AutomountMap.to_value()),
+                (Attribute::Name, Value::new_iname("auto.master")),
+                (Attribute::Uuid, Value::Uuid(map_uuid))
+            );
+            let key_home = entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Automount.to_value()),
+                (Attribute::AutomountKey, Value::new_utf8s("/home")),
+                (
+                    Attribute::AutomountInformation,
+                    Value::new_utf8s("auto.home")
+                ),
+                (Attribute::Refers, Value::Refer(map_uuid))
+            );
+            let key_shares = entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Automount.to_value()),
+                (Attribute::AutomountKey, Value::new_utf8s("/shares")),
+                (
+                    Attribute::AutomountInformation,
+                    Value::new_utf8s("-fstype=nfs4 nfs.example.com:/shares")
+                ),
+                (Attribute::Refers, Value::Refer(map_uuid))
+            );
+
+            assert!(server_txn
+                .qs_write
+                .internal_create(vec![map, key_home, key_shares])
+                .and_then(|_| server_txn.commit())
+                .is_ok());
+        }
+
+        let anon_t = ldaps.do_bind(idms, "", "").await.unwrap().unwrap();
+
+        // SSSD first locates the map by name.
+        let sr = SearchRequest {
+            msgid: 1,
+            base: "dc=example,dc=com".to_string(),
+            scope: LdapSearchScope::Subtree,
+            filter: LdapFilter::And(vec![
+                LdapFilter::Equality(
+                    Attribute::ObjectClass.to_string(),
+                    "automountMap".to_string(),
+                ),
+                LdapFilter::Equality("automountMapName".to_string(), "auto.master".to_string()),
+            ]),
+            attrs: vec![
+                "objectClass".to_string(),
+                "automountMapName".to_string(),
+                "entryUUID".to_string(),
+            ],
+        };
+        let r1 = ldaps
+            .do_search(idms, &sr, &anon_t, Source::Internal)
+            .await
+            .unwrap();
+
+        assert_eq!(r1.len(), 2);
+        match &r1[0].op {
+            LdapOp::SearchResultEntry(lsre) => {
+                assert_entry_contains!(
+                    lsre,
+                    "name=auto.master,dc=example,dc=com",
+                    (Attribute::ObjectClass, EntryClass::AutomountMap.as_ref()),
+                    (Attribute::AutomountMapName, "auto.master"),
+                    (Attribute::EntryUuid, "6cf0ecda-3d0f-4a7e-9b4b-3e7c1f35c0d1")
+                );
+            }
+            _ => panic!("Oh no"),
+        };
+
+        // Then the keys of the map, using the map as the search base.
+        let sr = SearchRequest {
+            msgid: 2,
+            base: "name=auto.master,dc=example,dc=com".to_string(),
+            scope: LdapSearchScope::OneLevel,
+            filter: LdapFilter::And(vec![
+                LdapFilter::Equality(Attribute::ObjectClass.to_string(), "automount".to_string()),
+                LdapFilter::Present("automountKey".to_string()),
+            ]),
+            attrs: vec![
+                "automountKey".to_string(),
+                "automountInformation".to_string(),
+            ],
+        };
+        let r1 = ldaps
+            .do_search(idms, &sr, &anon_t, Source::Internal)
+            .await
+            .unwrap();
+
+        // Two keys, and the ldap proto success msg.
+        assert_eq!(r1.len(), 3);
+        let mut keys: Vec<_> = r1
+            .iter()
+            .filter_map(|msg| match &msg.op {
+                LdapOp::SearchResultEntry(lsre) => Some(lsre),
+                _ => None,
+            })
+            .flat_map(|lsre| {
+                let get = |attr: Attribute| {
+                    lsre.attributes
+                        .iter()
+                        .find(|a| a.atype == attr.as_str())
+                        .and_then(|a| a.vals.first())
+                        .map(|v| String::from_utf8_lossy(v).to_string())
+                };
+                get(Attribute::AutomountKey).zip(get(Attribute::AutomountInformation))
+            })
+            .collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![
+                ("/home".to_string(), "auto.home".to_string()),
+                (
+                    "/shares".to_string(),
+                    "-fstype=nfs4 nfs.example.com:/shares".to_string()
+                ),
+            ]
+        );
+
+        // A base search of the map must not return the keys.
+        let sr = SearchRequest {
+            msgid: 3,
+            base: "name=auto.master,dc=example,dc=com".to_string(),
+            scope: LdapSearchScope::Base,
+            filter: LdapFilter::Present(Attribute::ObjectClass.to_string()),
+            attrs: vec!["automountMapName".to_string()],
+        };
+        let r1 = ldaps
+            .do_search(idms, &sr, &anon_t, Source::Internal)
+            .await
+            .unwrap();
+        assert_eq!(r1.len(), 2);
+    }
+
     #[idm_test]
     async fn test_ldap_compare_request(idms: &IdmServer, _idms_delayed: &IdmServerDelayed) {
         let ldaps = LdapServer::new(idms).await.expect("failed to start ldap");
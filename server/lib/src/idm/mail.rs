@@ -0,0 +1,361 @@
+//! Verification of the mail addresses that a person adds to their own account. Until
+//! an address is verified it is not released to OAuth2 clients or used for account
+//! recovery.
+
+use crate::idm::account::Account;
+use crate::idm::server::IdmServerProxyWriteTransaction;
+use crate::prelude::*;
+use compact_jwt::{Jws, JwsCompact};
+use kanidm_proto::v1::OutboundMessage;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+const MAIL_VERIFICATION_TTL: Duration = Duration::from_secs(86400);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct MailVerificationToken {
+    target: Uuid,
+    mail: String,
+    #[serde(with = "time::serde::timestamp")]
+    expiry_time: OffsetDateTime,
+}
+
+#[derive(Debug)]
+pub struct MailVerificationSendEvent {
+    pub ident: Identity,
+    pub target: Uuid,
+    pub mail: String,
+}
+
+impl IdmServerProxyWriteTransaction<'_> {
+    /// Send a verification token to an unverified address on the requesting account.
+    #[instrument(level = "debug", skip_all)]
+    pub fn mail_verification_send(
+        &mut self,
+        event: &MailVerificationSendEvent,
+        ct: Duration,
+    ) -> Result<(), OperationError> {
+        // Only the owner of an address is able to prove they control it.
+        if event.ident.get_uuid() != event.target
+            || event.ident.access_scope() != AccessScope::ReadWrite
+        {
+            security_access!("Mail verification may only be requested by the account owner");
+            return Err(OperationError::AccessDenied);
+        }
+
+        let entry = self.qs_write.internal_search_uuid(event.target)?;
+        let account = Account::try_from_entry_rw(&entry, &mut self.qs_write)?;
+
+        if !account.mail().contains(&event.mail) {
+            return Err(OperationError::MV0003MailAddressNotFound);
+        }
+
+        if account.mail_is_verified(&event.mail) {
+            return Err(OperationError::MV0004MailAddressAlreadyVerified);
+        }
+
+        let expiry_time = OffsetDateTime::UNIX_EPOCH + ct + MAIL_VERIFICATION_TTL;
+
+        let token = MailVerificationToken {
+            target: event.target,
+            mail: event.mail.clone(),
+            expiry_time,
+        };
+
+        let token = Jws::into_json(&token).map_err(|err| {
+            error!(?err, "Unable to serialise JWS");
+            OperationError::SerdeJsonError
+        })?;
+
+        let token = self
+            .qs_write
+            .get_domain_key_object_handle()?
+            .jws_es256_sign(&token, ct)?;
+
+        let message = OutboundMessage::MailVerificationV1 {
+            display_name: account.display_name().to_owned(),
+            mail: event.mail.clone(),
+            token: token.to_string(),
+            expiry_time,
+        };
+
+        self.qs_write
+            .queue_message(&Identity::message_queue(), message, event.mail.clone())
+    }
+
+    /// Mark the address in a verification token as verified. Confirming an address
+    /// that is already verified succeeds.
+    #[instrument(level = "debug", skip_all)]
+    pub fn mail_verification_confirm(
+        &mut self,
+        token: &str,
+        ct: Duration,
+    ) -> Result<(), OperationError> {
+        let jwsc = JwsCompact::from_str(token).map_err(|err| {
+            security_info!(?err, "Malformed mail verification token");
+            OperationError::MV0001MailVerificationTokenInvalid
+        })?;
+
+        let token = self
+            .qs_write
+            .get_domain_key_object_handle()?
+            .jws_verify(&jwsc)
+            .and_then(|jws| {
+                jws.from_json::<MailVerificationToken>().map_err(|err| {
+                    error!(?err, "Unable to deserialise JWS");
+                    OperationError::SerdeJsonError
+                })
+            })
+            .map_err(|err| {
+                security_info!(?err, "Unable to verify mail verification token");
+                OperationError::MV0001MailVerificationTokenInvalid
+            })?;
+
+        if OffsetDateTime::UNIX_EPOCH + ct > token.expiry_time {
+            return Err(OperationError::MV0002MailVerificationTokenExpired);
+        }
+
+        let entry = self
+            .qs_write
+            .internal_search_uuid(token.target)
+            .map_err(|_| OperationError::MV0001MailVerificationTokenInvalid)?;
+
+        let mut mail = entry
+            .get_ava_set(Attribute::Mail)
+            .filter(|vs| vs.contains(&PartialValue::EmailAddress(token.mail.clone())))
+            .cloned()
+            .ok_or(OperationError::MV0003MailAddressNotFound)?;
+
+        let verified = mail
+            .as_email_verified_set_mut()
+            .ok_or(OperationError::InvalidValueState)?;
+
+        if !verified.insert(token.mail) {
+            return Ok(());
+        }
+
+        let modlist = ModifyList::new_list(vec![Modify::Set(Attribute::Mail, mail)]);
+
+        self.qs_write.internal_modify(
+            &filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(token.target))),
+            &modlist,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MailVerificationSendEvent;
+    use crate::idm::server::{IdmServer, IdmServerDelayed};
+    use crate::prelude::*;
+    use kanidm_proto::v1::OutboundMessage;
+    use std::time::Duration;
+
+    const TEST_CURRENT_TIME: u64 = 6000;
+
+    fn setup_self_ident(
+        idms_prox_write: &mut crate::idm::server::IdmServerProxyWriteTransaction<'_>,
+    ) -> Identity {
+        let entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_TESTPERSON_1)
+            .expect("Failed to load testperson1");
+        Identity::from_impersonate_entry_readwrite(entry)
+    }
+
+    #[idm_test]
+    async fn test_idm_mail_verification(idms: &IdmServer, _idms_delayed: &mut IdmServerDelayed) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+
+        // Unverified addresses are only tracked from 1.12, which can't be bootstrapped directly.
+        assert!(idms_prox_write
+            .qs_write
+            .internal_apply_domain_migration(DOMAIN_LEVEL_1_12)
+            .is_ok());
+
+        // An administrator provided address is verified.
+        let e1 = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::Person.to_value()),
+            (Attribute::Name, Value::new_iname("testperson1")),
+            (Attribute::Uuid, Value::Uuid(UUID_TESTPERSON_1)),
+            (Attribute::Description, Value::new_utf8s("testperson1")),
+            (Attribute::DisplayName, Value::new_utf8s("Test Person 1")),
+            (
+                Attribute::Mail,
+                Value::new_email_address_primary_s("admin@example.com").unwrap()
+            )
+        );
+        assert!(idms_prox_write.qs_write.internal_create(vec![e1]).is_ok());
+
+        // Allow the person to manage their own mail addresses.
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_IDM_PEOPLE_SELF_MAIL_WRITE,
+                &ModifyList::new_append(Attribute::Member, Value::Refer(UUID_TESTPERSON_1)),
+            )
+            .is_ok());
+
+        let ident = setup_self_ident(&mut idms_prox_write);
+
+        // The person adds an address of their own, which is not verified.
+        let me = ModifyEvent::new_impersonate_identity(
+            ident.clone(),
+            filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(UUID_TESTPERSON_1))),
+            ModifyList::new_append(
+                Attribute::Mail,
+                Value::new_email_address_s("self@example.com").unwrap(),
+            ),
+        );
+        assert!(idms_prox_write.qs_write.modify(&me).is_ok());
+
+        let entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_TESTPERSON_1)
+            .unwrap();
+        let verified = entry.get_ava_mail_verified(Attribute::Mail).unwrap();
+        assert!(verified.contains("admin@example.com"));
+        assert!(!verified.contains("self@example.com"));
+
+        // Verified addresses can't be sent a new verification.
+        let ev = MailVerificationSendEvent {
+            ident: ident.clone(),
+            target: UUID_TESTPERSON_1,
+            mail: "admin@example.com".to_string(),
+        };
+        assert_eq!(
+            idms_prox_write.mail_verification_send(&ev, ct),
+            Err(OperationError::MV0004MailAddressAlreadyVerified)
+        );
+
+        // Only the owner may request verification.
+        let ev = MailVerificationSendEvent {
+            ident: Identity::from_internal(),
+            target: UUID_TESTPERSON_1,
+            mail: "self@example.com".to_string(),
+        };
+        assert_eq!(
+            idms_prox_write.mail_verification_send(&ev, ct),
+            Err(OperationError::AccessDenied)
+        );
+
+        let ev = MailVerificationSendEvent {
+            ident,
+            target: UUID_TESTPERSON_1,
+            mail: "self@example.com".to_string(),
+        };
+        assert!(idms_prox_write.mail_verification_send(&ev, ct).is_ok());
+
+        // Recover the token from the queued message.
+        let mut messages = idms_prox_write
+            .qs_write
+            .internal_search(filter!(f_eq(
+                Attribute::Class,
+                EntryClass::OutboundMessage.into()
+            )))
+            .unwrap();
+        let message = messages.pop().expect("No message was queued");
+        assert!(messages.is_empty());
+
+        let Some(OutboundMessage::MailVerificationV1 { mail, token, .. }) = message
+            .get_ava_set(Attribute::MessageTemplate)
+            .and_then(|vs| vs.as_message())
+        else {
+            panic!("Unexpected message template");
+        };
+        assert_eq!(mail, "self@example.com");
+        let token = token.clone();
+
+        // Expired tokens are rejected.
+        let expired = ct + Duration::from_secs(86400 * 2);
+        assert_eq!(
+            idms_prox_write.mail_verification_confirm(&token, expired),
+            Err(OperationError::MV0002MailVerificationTokenExpired)
+        );
+
+        // Invalid tokens are rejected.
+        assert_eq!(
+            idms_prox_write.mail_verification_confirm("not a token", ct),
+            Err(OperationError::MV0001MailVerificationTokenInvalid)
+        );
+
+        assert!(idms_prox_write
+            .mail_verification_confirm(&token, ct)
+            .is_ok());
+        // Confirming again is a no-op.
+        assert!(idms_prox_write
+            .mail_verification_confirm(&token, ct)
+            .is_ok());
+
+        let entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_TESTPERSON_1)
+            .unwrap();
+        let verified = entry.get_ava_mail_verified(Attribute::Mail).unwrap();
+        assert!(verified.contains("admin@example.com"));
+        assert!(verified.contains("self@example.com"));
+
+        assert!(idms_prox_write.commit().is_ok());
+    }
+
+    #[idm_test]
+    async fn test_idm_mail_self_service_trusted_before_1_12(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+
+        let e1 = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::Person.to_value()),
+            (Attribute::Name, Value::new_iname("testperson1")),
+            (Attribute::Uuid, Value::Uuid(UUID_TESTPERSON_1)),
+            (Attribute::Description, Value::new_utf8s("testperson1")),
+            (Attribute::DisplayName, Value::new_utf8s("Test Person 1"))
+        );
+        assert!(idms_prox_write.qs_write.internal_create(vec![e1]).is_ok());
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_IDM_PEOPLE_SELF_MAIL_WRITE,
+                &ModifyList::new_append(Attribute::Member, Value::Refer(UUID_TESTPERSON_1)),
+            )
+            .is_ok());
+
+        let ident = setup_self_ident(&mut idms_prox_write);
+        let me = ModifyEvent::new_impersonate_identity(
+            ident,
+            filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(UUID_TESTPERSON_1))),
+            ModifyList::new_append(
+                Attribute::Mail,
+                Value::new_email_address_primary_s("self@example.com").unwrap(),
+            ),
+        );
+        assert!(idms_prox_write.qs_write.modify(&me).is_ok());
+
+        // Older replicas can't store unverified addresses, so until the domain level is
+        // raised the address is trusted, and stored in the encoding they understand.
+        let entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_TESTPERSON_1)
+            .unwrap();
+        let mail = entry.get_ava_set(Attribute::Mail).unwrap();
+        assert!(mail
+            .as_email_verified_set()
+            .unwrap()
+            .contains("self@example.com"));
+        assert!(matches!(
+            mail.to_db_valueset_v2(),
+            crate::be::dbvalue::DbValueSetV2::EmailAddress(..)
+        ));
+
+        assert!(idms_prox_write.commit().is_ok());
+    }
+}
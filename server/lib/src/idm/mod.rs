@@ -10,12 +10,14 @@ pub(crate) mod applinks;
 pub mod audit;
 pub mod authentication;
 pub(crate) mod authsession;
+pub mod breachlist;
 pub mod credupdatesession;
 pub mod delayed;
 pub mod event;
 pub mod group;
 pub mod identityverification;
 pub mod ldap;
+pub mod mail;
 pub mod oauth2;
 pub(crate) mod oauth2_client;
 pub(crate) mod radius;
@@ -23,3 +25,4 @@ pub(crate) mod reauth;
 pub mod scim;
 pub mod server;
 pub mod serviceaccount;
+pub(crate) mod unix;
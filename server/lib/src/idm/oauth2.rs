@@ -13,7 +13,7 @@ use crate::server::keys::{
     KeyId, KeyObject, KeyProvidersTransaction, KeyProvidersWriteTransaction,
 };
 use crate::utils;
-use crate::value::{Oauth2Session, OauthClaimMapJoin, SessionState, OAUTHSCOPE_RE};
+use crate::value::{Acr, Oauth2Session, OauthClaimMapJoin, SessionState, OAUTHSCOPE_RE};
 use base64::{engine::general_purpose, Engine as _};
 pub use compact_jwt::{compact::JwkKeySet, OidcToken};
 use compact_jwt::{
@@ -22,6 +22,7 @@ use compact_jwt::{
     jws::JwsBuilder,
     JweCompact, JwsCompact, OidcClaims, OidcSubject,
 };
+use concread::bptree::BptreeMapWriteTxn;
 use concread::cowcell::*;
 use crypto_glue::{s256::Sha256, traits::Digest};
 use hashbrown::HashMap;
@@ -29,14 +30,17 @@ use hashbrown::HashSet;
 use kanidm_proto::constants::*;
 pub use kanidm_proto::oauth2::{
     AccessTokenIntrospectRequest, AccessTokenIntrospectResponse, AccessTokenRequest,
-    AccessTokenResponse, AccessTokenType, AuthorisationRequest, ClaimType, ClientAuth,
-    ClientPostAuth, CodeChallengeMethod, DeviceAuthorizationResponse, DisplayValue,
-    EndpointAuthMethod, ErrorResponse, GrantType, GrantTypeReq, IdTokenSignAlg, OAuth2RFC9068Token,
-    OAuth2RFC9068TokenExtensions, Oauth2Rfc8414MetadataResponse, OidcDiscoveryResponse,
-    OidcWebfingerRel, OidcWebfingerResponse, PkceAlg, PkceRequest, ResponseMode, ResponseType,
-    SubjectType, TokenRevokeRequest, OAUTH2_TOKEN_TYPE_ACCESS_TOKEN,
+    AccessTokenResponse, AccessTokenType, AuthorisationRequest, AuthorisationRequestKind,
+    ClaimType, ClientAuth, ClientPostAuth, CodeChallengeMethod, DeviceAuthorizationResponse,
+    DisplayValue, EndpointAuthMethod, ErrorResponse, GrantType, GrantTypeReq, IdTokenSignAlg,
+    OAuth2RFC9068Token, OAuth2RFC9068TokenExtensions, Oauth2Rfc8414MetadataResponse,
+    OidcDiscoveryResponse, OidcWebfingerRel, OidcWebfingerResponse, PkceAlg, PkceRequest,
+    PushedAuthorisationReference, PushedAuthorisationResponse, ResponseMode, ResponseType,
+    SubjectType, TokenRevokeRequest, OAUTH2_PAR_REQUEST_URI_PREFIX, OAUTH2_TOKEN_TYPE_ACCESS_TOKEN,
+};
+use kanidm_proto::oauth2::{
+    IssuedTokenType, Prompt, OAUTH2_DEVICE_CODE_EXPIRY_SECONDS, OAUTH2_DEVICE_CODE_INTERVAL_SECONDS,
 };
-use kanidm_proto::oauth2::{IssuedTokenType, Prompt};
 use serde::{Deserialize, Serialize};
 use serde_with::{formats, serde_as};
 use std::collections::btree_map::Entry as BTreeEntry;
@@ -53,6 +57,14 @@ use url::{Host, Origin, Url};
 use utoipa::ToSchema;
 
 const TOKEN_EXCHANGE_SUBJECT_TOKEN_TYPE_ACCESS: &str = OAUTH2_TOKEN_TYPE_ACCESS_TOKEN;
+const OAUTH2_PAR_REQUEST_URI: &str = "request_uri";
+const OAUTH2_CLIENT_SECRET: &str = "client_secret";
+/// User codes avoid vowels so they can't spell words, and avoid digits so they can't be
+/// confused with letters when read aloud or typed on a limited input device.
+const OAUTH2_DEVICE_USER_CODE_ALPHABET: &[u8] = b"BCDFGHJKLMNPQRSTVWXZ";
+const OAUTH2_DEVICE_USER_CODE_LEN: usize = 8;
+/// How much to extend the polling interval by when a client polls too early, as per RFC8628.
+const OAUTH2_DEVICE_CODE_SLOW_DOWN_SECONDS: u64 = 5;
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, ToSchema)]
 #[serde(rename_all = "snake_case")]
@@ -184,8 +196,42 @@ impl AuthorisationRequestContext {
     }
 }
 
+/// An authorisation request that a client has pushed to us, awaiting the user agent
+/// to arrive at the authorisation endpoint with a reference to it.
+#[derive(Clone)]
+pub(crate) struct Oauth2PushedRequest {
+    client_id: String,
+    auth_req: AuthorisationRequest,
+    expiry: Duration,
+}
+
+/// Remove the pushed authorisation requests that expired without being used.
+pub(crate) fn prune_oauth2_pushed_requests(
+    pushed_requests: &mut BptreeMapWriteTxn<'_, Uuid, Oauth2PushedRequest>,
+    ct: Duration,
+) {
+    let expired: Vec<Uuid> = pushed_requests
+        .iter()
+        .filter_map(|(id, pushed_request)| (pushed_request.expiry <= ct).then_some(*id))
+        .collect();
+    for id in expired {
+        pushed_requests.remove(&id);
+    }
+}
+
+/// A device authorisation grant that is waiting for the user to approve it.
+#[derive(Clone)]
+pub(crate) struct Oauth2DeviceCode {
+    client_id: String,
+    expiry: Duration,
+    interval: Duration,
+    /// The client must not poll before this time, else it is told to slow down.
+    next_poll: Duration,
+}
+
 struct OAuth2SessionContext {
     pub(crate) auth_time: Option<OffsetDateTime>,
+    pub(crate) acr: Option<Acr>,
     pub(crate) nonce: Option<String>,
     pub(crate) account_uuid: Uuid,
 }
@@ -246,6 +292,8 @@ struct TokenExchangeCode {
     // We stash some details here for oidc.
     pub nonce: Option<String>,
     pub auth_time: Option<OffsetDateTime>,
+    #[serde(default)]
+    pub acr: Option<Acr>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -262,6 +310,8 @@ pub(crate) enum Oauth2TokenType {
         // We stash some details here for oidc.
         nonce: Option<String>,
         auth_time: Option<OffsetDateTime>,
+        #[serde(default)]
+        acr: Option<Acr>,
     },
     ClientAccess {
         scopes: BTreeSet<String>,
@@ -297,6 +347,9 @@ pub enum AuthoriseResponse {
     ReauthenticationRequired {
         // A pretty-name of the client
         client_name: String,
+        // The class of credential the user must verify with, if the client requested one
+        // that the current session does not satisfy.
+        acr: Option<Acr>,
     },
     ConsentRequested {
         // A pretty-name of the client
@@ -472,6 +525,7 @@ impl std::fmt::Debug for OauthRSType {
 #[derive(Clone, Debug)]
 struct ClaimValue {
     join: OauthClaimMapJoin,
+    template: Option<String>,
     values: BTreeSet<String>,
 }
 
@@ -480,6 +534,29 @@ impl ClaimValue {
         self.values.extend(other.values.iter().cloned())
     }
 
+    /// Apply the claim's value template (if any) for a specific group membership,
+    /// substituting `{group}` with the group name and `{value}` with each claim value.
+    fn render(&self, group_name: &str) -> Self {
+        let values = match &self.template {
+            Some(template) => self
+                .values
+                .iter()
+                .map(|value| {
+                    template
+                        .replace("{group}", group_name)
+                        .replace("{value}", value)
+                })
+                .collect(),
+            None => self.values.clone(),
+        };
+
+        ClaimValue {
+            join: self.join,
+            template: None,
+            values,
+        }
+    }
+
     fn to_json_value(&self) -> serde_json::Value {
         let join_str = match self.join {
             OauthClaimMapJoin::JsonArray => {
@@ -519,6 +596,7 @@ pub struct Oauth2RS {
     redirect_uris: HashSet<Url>,
     origin_secure_required: bool,
     strict_redirect_uri: bool,
+    require_pushed_authorisation: bool,
 
     claim_map: BTreeMap<Uuid, Vec<(String, ClaimValue)>>,
     scope_maps: BTreeMap<Uuid, BTreeSet<String>>,
@@ -529,6 +607,7 @@ pub struct Oauth2RS {
     sign_alg: SignatureAlgo,
     key_object: Arc<KeyObject>,
 
+    access_token_expiry: u32,
     refresh_token_expiry: u32,
 
     // For oidc we also need our issuer url.
@@ -538,6 +617,7 @@ pub struct Oauth2RS {
     token_endpoint: Url,
     revocation_endpoint: Url,
     introspection_endpoint: Url,
+    pushed_authorization_request_endpoint: Url,
     userinfo_endpoint: Url,
     jwks_uri: Url,
     scopes_supported: BTreeSet<String>,
@@ -888,6 +968,7 @@ impl Oauth2ResourceServersWriteTransaction<'_> {
                                         claim_name.clone(),
                                         ClaimValue {
                                             join: claim_mapping.join(),
+                                            template: claim_mapping.template().map(str::to_string),
                                             values: claim_values.clone(),
                                         },
                                     )]);
@@ -897,6 +978,7 @@ impl Oauth2ResourceServersWriteTransaction<'_> {
                                         claim_name.clone(),
                                         ClaimValue {
                                             join: claim_mapping.join(),
+                                            template: claim_mapping.template().map(str::to_string),
                                             values: claim_values.clone(),
                                         },
                                     ));
@@ -947,8 +1029,16 @@ impl Oauth2ResourceServersWriteTransaction<'_> {
                     .get_ava_single_bool(Attribute::OAuth2PreferShortUsername)
                     .unwrap_or(false);
 
+                let require_pushed_authorisation = ent
+                    .get_ava_single_bool(Attribute::OAuth2RequirePushedAuthorisation)
+                    .unwrap_or(false);
+
                 let has_custom_image = ent.get_ava_single_image(Attribute::Image).is_some();
 
+                let access_token_expiry = ent
+                    .get_ava_single_uint32(Attribute::OAuth2AccessTokenExpiry)
+                    .unwrap_or(OAUTH2_ACCESS_TOKEN_EXPIRY);
+
                 let refresh_token_expiry = ent
                     .get_ava_single_uint32(Attribute::OAuth2RefreshTokenExpiry)
                     .unwrap_or(OAUTH_REFRESH_TOKEN_EXPIRY);
@@ -965,6 +1055,9 @@ impl Oauth2ResourceServersWriteTransaction<'_> {
                 let mut introspection_endpoint = self.inner.origin.clone();
                 introspection_endpoint.set_path(OAUTH2_TOKEN_INTROSPECT_ENDPOINT);
 
+                let mut pushed_authorization_request_endpoint = self.inner.origin.clone();
+                pushed_authorization_request_endpoint.set_path(uri::OAUTH2_PAR_ENDPOINT);
+
                 let mut userinfo_endpoint = self.inner.origin.clone();
                 userinfo_endpoint.set_path(&format!("/oauth2/openid/{client_id}/userinfo"));
 
@@ -982,7 +1075,7 @@ impl Oauth2ResourceServersWriteTransaction<'_> {
                     .collect();
 
                 let device_authorization_endpoint: Option<Url> =
-                    match cfg!(feature = "dev-oauth2-device-flow") {
+                    match cfg!(any(feature = "dev-oauth2-device-flow", test)) {
                         true => {
                             match ent
                                 .get_ava_single_bool(Attribute::OAuth2DeviceFlowEnable)
@@ -1010,6 +1103,7 @@ impl Oauth2ResourceServersWriteTransaction<'_> {
                     redirect_uris,
                     origin_secure_required,
                     strict_redirect_uri,
+                    require_pushed_authorisation,
                     scope_maps,
                     sup_scope_maps,
                     client_scopes,
@@ -1017,12 +1111,14 @@ impl Oauth2ResourceServersWriteTransaction<'_> {
                     claim_map,
                     sign_alg,
                     key_object,
+                    access_token_expiry,
                     refresh_token_expiry,
                     iss,
                     authorization_endpoint,
                     token_endpoint,
                     revocation_endpoint,
                     introspection_endpoint,
+                    pushed_authorization_request_endpoint,
                     userinfo_endpoint,
                     jwks_uri,
                     scopes_supported,
@@ -1295,7 +1391,7 @@ impl IdmServerProxyWriteTransaction<'_> {
                 )
             }
             GrantTypeReq::DeviceCode { device_code, scope } => {
-                self.check_oauth2_device_code_status(device_code, scope)
+                self.check_oauth2_device_code_status(&o2rs.name, device_code, scope, ct)
             }
         }
     }
@@ -1303,66 +1399,110 @@ impl IdmServerProxyWriteTransaction<'_> {
     #[instrument(level = "info", skip(self))]
     pub fn handle_oauth2_start_device_flow(
         &mut self,
-        _client_auth_info: ClientAuthInfo,
-        _client_id: &str,
-        _scope: &Option<BTreeSet<String>>,
-        _eventid: Uuid,
+        client_auth_info: &ClientAuthInfo,
+        client_id: &str,
+        scope: &Option<BTreeSet<String>>,
+        eventid: Uuid,
+        ct: Duration,
     ) -> Result<DeviceAuthorizationResponse, Oauth2Error> {
-        // let o2rs = self.get_client(client_id)?;
+        let o2rs = self.oauth2rs.inner.rs_set_get(client_id).ok_or_else(|| {
+            debug!("Invalid OAuth2 client_id {}", client_id);
+            Oauth2Error::InvalidClientId
+        })?;
+
+        if !o2rs.device_flow_enabled() {
+            security_info!(%client_id, "Device flow is not enabled for this client");
+            return Err(Oauth2Error::UnauthorizedClient);
+        }
 
-        // info!("Got Client: {:?}", o2rs);
+        if let Some(scope) = scope {
+            validate_scopes(scope)?;
+        }
+
+        info!(
+            "Starting device flow for client_id={} scopes={:?} source={:?}",
+            client_id, scope, client_auth_info.source
+        );
+
+        let mut verification_uri = self.oauth2rs.inner.origin.clone();
+        verification_uri.set_path(uri::OAUTH2_DEVICE_LOGIN);
 
-        // // TODO: change this to checking if it's got device flow enabled
-        // if !o2rs.require_pkce() {
-        //     security_info!("Device flow is only available for PKCE-enabled clients");
-        //     return Err(Oauth2Error::InvalidRequest);
-        // }
+        let user_code = gen_user_code();
+        let device_code = gen_device_code()?;
 
-        // info!(
-        //     "Starting device flow for client_id={} scopes={} source={:?}",
-        //     client_id,
-        //     scope
-        //         .as_ref()
-        //         .map(|s| s.iter().cloned().collect::<Vec<_>>().into_iter().join(","))
-        //         .unwrap_or("[]".to_string()),
-        //     client_auth_info.source
-        // );
+        let response = DeviceAuthorizationResponse::new(verification_uri, device_code, user_code);
 
-        // let mut verification_uri = self.oauth2rs.inner.origin.clone();
-        // verification_uri.set_path(uri::OAUTH2_DEVICE_LOGIN);
+        let interval = Duration::from_secs(OAUTH2_DEVICE_CODE_INTERVAL_SECONDS);
 
-        // let (user_code_string, _user_code) = gen_user_code();
-        // let expiry =
-        //     Duration::from_secs(OAUTH2_DEVICE_CODE_EXPIRY_SECONDS) + duration_from_epoch_now();
-        // let device_code = gen_device_code()
-        //     .inspect_err(|err| error!("Failed to generate a device code! {:?}", err))?;
+        let mut device_codes = self.oauth2_device_codes.write();
+
+        // Prune anything that was abandoned before it expired.
+        let expired: Vec<String> = device_codes
+            .iter()
+            .filter(|(_, device)| device.expiry <= ct)
+            .map(|(code, _)| code.clone())
+            .collect();
+        for code in expired {
+            device_codes.remove(&code);
+        }
 
-        Err(Oauth2Error::InvalidGrant)
+        device_codes.insert(
+            response.device_code().to_string(),
+            Oauth2DeviceCode {
+                client_id: client_id.to_string(),
+                expiry: ct + Duration::from_secs(OAUTH2_DEVICE_CODE_EXPIRY_SECONDS),
+                interval,
+                next_poll: ct + interval,
+            },
+        );
+        device_codes.commit();
 
-        // TODO: store user_code / expiry / client_id / device_code in the backend, needs to be checked on the token exchange.
-        // Ok(DeviceAuthorizationResponse::new(
-        //     verification_uri,
-        //     device_code,
-        //     user_code_string,
-        // ))
+        Ok(response)
     }
 
     #[instrument(level = "info", skip(self))]
     fn check_oauth2_device_code_status(
         &mut self,
+        client_id: &str,
         device_code: &str,
         scope: &Option<BTreeSet<String>>,
+        ct: Duration,
     ) -> Result<AccessTokenResponse, Oauth2Error> {
-        // TODO: check the device code is valid, do the needful
+        let mut device_codes = self.oauth2_device_codes.write();
 
-        error!(
-            "haven't done the device grant yet! Got device_code={} scope={:?}",
-            device_code, scope
-        );
-        Err(Oauth2Error::AuthorizationPending)
+        let Some(mut device) = device_codes.get(device_code).cloned() else {
+            debug!("Device code not found");
+            return Err(Oauth2Error::InvalidGrant);
+        };
+
+        if device.client_id != client_id {
+            security_info!(%client_id, "Device code was issued to a different client");
+            return Err(Oauth2Error::InvalidGrant);
+        }
 
-        // if it's an expired code, then just delete it from the db and return an error.
-        // Err(Oauth2Error::ExpiredToken)
+        // Expiry is checked here rather than waiting for the next prune so that a code
+        // can never be used past the moment it expires.
+        if device.expiry <= ct {
+            debug!("Device code has expired");
+            device_codes.remove(&device_code.to_string());
+            device_codes.commit();
+            return Err(Oauth2Error::ExpiredToken);
+        }
+
+        let result = if ct < device.next_poll {
+            debug!("Device code polled before the interval elapsed");
+            device.interval += Duration::from_secs(OAUTH2_DEVICE_CODE_SLOW_DOWN_SECONDS);
+            Err(Oauth2Error::SlowDown)
+        } else {
+            // TODO: once the user can approve the grant, issue the tokens here.
+            Err(Oauth2Error::AuthorizationPending)
+        };
+
+        device.next_poll = ct + device.interval;
+        device_codes.insert(device_code.to_string(), device);
+        device_codes.commit();
+
+        result
     }
 
     #[instrument(level = "debug", skip_all)]
@@ -1436,6 +1576,7 @@ impl IdmServerProxyWriteTransaction<'_> {
             scopes: consent_req.scopes.clone(),
             nonce: consent_req.nonce,
             auth_time: ident.last_verified_at(),
+            acr: ident.acr(),
         };
 
         // Encrypt the exchange token
@@ -1580,6 +1721,7 @@ impl IdmServerProxyWriteTransaction<'_> {
         let scopes = code_xchg.scopes;
         let session_ctx = OAuth2SessionContext {
             auth_time: code_xchg.auth_time,
+            acr: code_xchg.acr,
             nonce: code_xchg.nonce,
             account_uuid: code_xchg.account_uuid,
         };
@@ -1638,6 +1780,7 @@ impl IdmServerProxyWriteTransaction<'_> {
                 nbf: _,
                 nonce,
                 auth_time,
+                acr,
             } => {
                 if exp <= ct.as_secs() as i64 {
                     security_info!(?uuid, "refresh token has expired, ");
@@ -1721,6 +1864,7 @@ impl IdmServerProxyWriteTransaction<'_> {
                 let account_uuid = uuid;
                 let session_ctx = OAuth2SessionContext {
                     auth_time,
+                    acr,
                     nonce,
                     account_uuid,
                 };
@@ -1822,9 +1966,13 @@ impl IdmServerProxyWriteTransaction<'_> {
         };
 
         let ident = self
-            .process_apit_to_identity(&apit, Source::Internal, entry, ct)
+            // Token exchange is not within any operation scope, as the issued token
+            // would not carry the restriction.
+            .process_apit_to_identity(&apit, Source::Internal, entry, None, ct)
             .map_err(|err| match err {
-                OperationError::SessionExpired | OperationError::NotAuthenticated => {
+                OperationError::SessionExpired
+                | OperationError::NotAuthenticated
+                | OperationError::AU0010ApiTokenOperationNotInScope => {
                     security_info!(
                         ?err,
                         "Service account api token rejected during token exchange"
@@ -1842,6 +1990,7 @@ impl IdmServerProxyWriteTransaction<'_> {
         let session_ctx = OAuth2SessionContext {
             // Service accounts don't have an auth time
             auth_time: None,
+            acr: None,
             account_uuid: apit.account_id,
             nonce: None,
         };
@@ -1856,6 +2005,10 @@ impl IdmServerProxyWriteTransaction<'_> {
         )
     }
 
+    /// Issue an access token to a confidential client acting as itself. The token is bound to
+    /// the client's own entry and carries only the scopes that the client is a member of. As
+    /// there is no user, no id_token or refresh token is issued and the token can not be used
+    /// at the userinfo endpoint - introspection reports the client's name as the username.
     fn check_oauth2_token_client_credentials(
         &mut self,
         o2rs: &Oauth2RS,
@@ -1892,9 +2045,9 @@ impl IdmServerProxyWriteTransaction<'_> {
 
         let odt_ct = OffsetDateTime::UNIX_EPOCH + ct;
         let iat = ct.as_secs() as i64;
-        let exp = iat + OAUTH2_ACCESS_TOKEN_EXPIRY as i64;
-        let odt_exp = odt_ct + Duration::from_secs(OAUTH2_ACCESS_TOKEN_EXPIRY as u64);
-        let expires_in = OAUTH2_ACCESS_TOKEN_EXPIRY;
+        let exp = iat + o2rs.access_token_expiry as i64;
+        let odt_exp = odt_ct + Duration::from_secs(o2rs.access_token_expiry as u64);
+        let expires_in = o2rs.access_token_expiry;
 
         let session_id = Uuid::new_v4();
 
@@ -1982,11 +2135,11 @@ impl IdmServerProxyWriteTransaction<'_> {
         // expiries are *purely* for the tokens we issue and are *not related* to the expiries of the
         // the session - these are enforced as above!
 
-        // Refresh tokens can be configured, but access token expiry can not. This is because
-        // OAuth2 has no *revocation* mechanism, so we need to be validating and re-issuing
-        // access tokens frequently.
-        let expiry = odt_ct + Duration::from_secs(OAUTH2_ACCESS_TOKEN_EXPIRY as u64);
-        let expires_in = OAUTH2_ACCESS_TOKEN_EXPIRY;
+        // Both token expiries can be configured per client, but access tokens are bounded to a
+        // much shorter maximum. This is because OAuth2 has no *revocation* mechanism, so we need
+        // to be validating and re-issuing access tokens frequently.
+        let expiry = odt_ct + Duration::from_secs(o2rs.access_token_expiry as u64);
+        let expires_in = o2rs.access_token_expiry;
         let refresh_expiry = iat + o2rs.refresh_token_expiry as i64;
         let odt_refresh_expiry = odt_ct + Duration::from_secs(o2rs.refresh_token_expiry as u64);
 
@@ -2007,6 +2160,7 @@ impl IdmServerProxyWriteTransaction<'_> {
         };
 
         let auth_time = session_ctx.auth_time;
+        let acr = session_ctx.acr;
 
         let id_token = if scopes.contains(OAUTH2_SCOPE_OPENID) {
             // TODO: Scopes map to claims:
@@ -2044,7 +2198,7 @@ impl IdmServerProxyWriteTransaction<'_> {
                 auth_time: auth_time.map(|at| at.unix_timestamp()),
                 nonce: session_ctx.nonce.clone(),
                 at_hash: None,
-                acr: None,
+                acr: acr.map(|acr| acr.to_string()),
                 amr,
                 azp: Some(o2rs.name.clone()),
                 jti: Some(session_id.to_string()),
@@ -2087,7 +2241,7 @@ impl IdmServerProxyWriteTransaction<'_> {
             client_id,
             extensions: OAuth2RFC9068TokenExtensions {
                 auth_time: auth_time.map(|at| at.unix_timestamp()),
-                acr: None,
+                acr: acr.map(|acr| acr.to_string()),
                 amr: None,
                 scope: scopes.clone(),
                 nonce: session_ctx.nonce.clone(),
@@ -2122,6 +2276,7 @@ impl IdmServerProxyWriteTransaction<'_> {
             nbf: iat,
             nonce: session_ctx.nonce,
             auth_time,
+            acr,
         };
 
         let refresh_token_data = JweBuilder::into_json(&refresh_token_raw)
@@ -2229,11 +2384,206 @@ impl IdmServerProxyReadTransaction<'_> {
         auth_req_ctx: &AuthorisationRequestContext,
         ct: Duration,
     ) -> Result<AuthoriseResponse, Oauth2Error> {
-        // due to identity processing we already know that:
-        // * the session must be authenticated, and valid
-        // * is within it's valid time window.
-        trace!(?auth_req, ?auth_req_ctx);
+        // A request_uri refers to a pushed request, and can't be mixed with inline parameters.
+        if auth_req.unknown_keys.contains_key(OAUTH2_PAR_REQUEST_URI) {
+            warn!("Invalid OAuth2 request - request_uri conflicts with inline authorisation request parameters");
+            return Err(Oauth2Error::InvalidRequest);
+        }
+
+        self.check_oauth2_authorisation_inner(maybe_ident, auth_req, auth_req_ctx, false, ct)
+    }
+
+    /// Resume an authorisation request that was previously pushed to us by the client. The
+    /// pushed request is consumed once the user has been presented with an outcome.
+    #[instrument(level = "debug", skip_all)]
+    pub fn check_oauth2_authorisation_pushed(
+        &self,
+        maybe_ident: Option<&Identity>,
+        par_ref: &PushedAuthorisationReference,
+        auth_req_ctx: &AuthorisationRequestContext,
+        ct: Duration,
+    ) -> Result<AuthoriseResponse, Oauth2Error> {
+        trace!(?par_ref, ?auth_req_ctx);
+
+        if !par_ref.unknown_keys.is_empty() {
+            warn!(
+                unknown_keys = ?par_ref.unknown_keys.keys().collect::<Vec<_>>(),
+                "Invalid OAuth2 request - inline parameters conflict with the pushed authorisation request"
+            );
+            return Err(Oauth2Error::InvalidRequest);
+        }
+
+        let par_id = par_ref
+            .request_uri
+            .strip_prefix(OAUTH2_PAR_REQUEST_URI_PREFIX)
+            .and_then(|id| Uuid::parse_str(id).ok())
+            .ok_or_else(|| {
+                warn!("Invalid OAuth2 request - request_uri is not a valid reference");
+                Oauth2Error::InvalidRequest
+            })?;
+
+        // Prune on read too, so that expired requests don't linger between pushes.
+        let pushed_request = {
+            let mut pushed_requests = self.oauth2_pushed_requests.write();
+            prune_oauth2_pushed_requests(&mut pushed_requests, ct);
+            let pushed_request = pushed_requests.get(&par_id).cloned();
+            pushed_requests.commit();
+            pushed_request
+        }
+        .ok_or_else(|| {
+            warn!("Invalid OAuth2 request - request_uri does not exist, has expired, or was already used");
+            Oauth2Error::InvalidRequest
+        })?;
+
+        if pushed_request.client_id != par_ref.client_id {
+            warn!("Invalid OAuth2 request - request_uri was not issued to this client_id");
+            return Err(Oauth2Error::InvalidRequest);
+        }
+
+        let res = self.check_oauth2_authorisation_inner(
+            maybe_ident,
+            &pushed_request.auth_req,
+            auth_req_ctx,
+            true,
+            ct,
+        );
+
+        // Until the user has (re)authenticated we need to keep the request so that the
+        // flow can be resumed. Any other outcome is final and the request_uri is spent.
+        if !matches!(
+            res,
+            Ok(AuthoriseResponse::AuthenticationRequired { .. })
+                | Ok(AuthoriseResponse::ReauthenticationRequired { .. })
+        ) {
+            let mut pushed_requests = self.oauth2_pushed_requests.write();
+            pushed_requests.remove(&par_id);
+            pushed_requests.commit();
+        }
+
+        res
+    }
+
+    /// Accept a pushed authorisation request from a client. The request is validated in
+    /// the same manner as the authorisation endpoint and then held for a short time so
+    /// that the client can redirect the user agent with only a reference to it.
+    #[instrument(level = "debug", skip_all)]
+    pub fn check_oauth2_pushed_authorisation_request(
+        &self,
+        client_auth_info: &ClientAuthInfo,
+        mut auth_req: AuthorisationRequest,
+        ct: Duration,
+    ) -> Result<PushedAuthorisationResponse, Oauth2Error> {
+        // Clients may authenticate with their secret in the form body, which means
+        // it lands amongst the unknown keys.
+        let client_secret = match auth_req.unknown_keys.remove(OAUTH2_CLIENT_SECRET) {
+            Some(serde_json::Value::String(secret)) => Some(secret),
+            Some(_) => {
+                warn!("Invalid OAuth2 request - client_secret must be a string");
+                return Err(Oauth2Error::InvalidRequest);
+            }
+            None => None,
+        };
+
+        let client_post_auth = ClientPostAuth {
+            client_id: Some(auth_req.client_id.clone()),
+            client_secret,
+        };
+
+        let client_auth =
+            get_client_auth(client_auth_info, &client_post_auth).inspect_err(|_| {
+                warn!("OAuth2 Client Authentication Required");
+            })?;
+
+        let o2rs = self
+            .oauth2rs
+            .inner
+            .rs_set_get(&client_auth.client_id)
+            .ok_or_else(|| {
+                debug!("Invalid OAuth2 client_id {}", &client_auth.client_id);
+                Oauth2Error::AuthenticationRequired
+            })?;
+
+        match (&o2rs.type_, client_auth.client_secret) {
+            (OauthRSType::Basic { authz_secret, .. }, Some(secret)) => {
+                if !authz_secret.ct_eq(&secret) {
+                    info!("Invalid OAuth2 client_id secret");
+                    return Err(Oauth2Error::AuthenticationRequired);
+                }
+            }
+            (OauthRSType::Basic { .. }, None) => {
+                info!("Invalid OAuth2 authentication - no secret in pushed authorisation request");
+                return Err(Oauth2Error::AuthenticationRequired);
+            }
+            // Public clients have no credentials, so they identify by their client_id.
+            (OauthRSType::Public { .. }, _) => {}
+        }
+
+        if client_auth.client_id != auth_req.client_id {
+            warn!("Invalid OAuth2 request - authenticated client does not match the client_id of the request");
+            return Err(Oauth2Error::InvalidRequest);
+        }
+
+        // rfc9126 2.1 - request_uri MUST NOT be provided in a pushed request.
+        if auth_req.unknown_keys.contains_key(OAUTH2_PAR_REQUEST_URI) {
+            warn!("Invalid OAuth2 request - request_uri can not be part of a pushed authorisation request");
+            return Err(Oauth2Error::InvalidRequest);
+        }
+
+        self.validate_oauth2_authorisation_request(&auth_req)?;
+
+        if auth_req.scope.is_empty() {
+            admin_error!("Invalid OAuth2 request - must contain at least one requested scope");
+            return Err(Oauth2Error::InvalidRequest);
+        }
+
+        validate_scopes(&auth_req.scope)?;
+
+        let par_id = Uuid::new_v4();
+        let expiry = ct + Duration::from_secs(OAUTH2_PAR_EXPIRY as u64);
+
+        let mut pushed_requests = self.oauth2_pushed_requests.write();
+
+        // Prune anything that was never used before it expired.
+        prune_oauth2_pushed_requests(&mut pushed_requests, ct);
+
+        // Public clients push without authenticating, so the number of requests that
+        // are held is bounded both per client and overall.
+        if pushed_requests.len() >= OAUTH2_PAR_MAX {
+            warn!("Refusing pushed authorisation request - too many requests are awaiting use");
+            return Err(Oauth2Error::TemporarilyUnavailable);
+        }
+
+        let client_pushed = pushed_requests
+            .values()
+            .filter(|pushed_request| pushed_request.client_id == auth_req.client_id)
+            .count();
+        if client_pushed >= OAUTH2_PAR_MAX_PER_CLIENT {
+            warn!(client_id = %auth_req.client_id, "Refusing pushed authorisation request - too many requests from this client are awaiting use");
+            return Err(Oauth2Error::TemporarilyUnavailable);
+        }
+
+        pushed_requests.insert(
+            par_id,
+            Oauth2PushedRequest {
+                client_id: auth_req.client_id.clone(),
+                auth_req,
+                expiry,
+            },
+        );
+        pushed_requests.commit();
+
+        Ok(PushedAuthorisationResponse {
+            request_uri: format!("{OAUTH2_PAR_REQUEST_URI_PREFIX}{par_id}"),
+            expires_in: OAUTH2_PAR_EXPIRY,
+        })
+    }
 
+    /// Validate the parts of an authorisation request that don't depend on the identity
+    /// of the user, returning the client along with the response mode and code challenge.
+    fn validate_oauth2_authorisation_request(
+        &self,
+        auth_req: &AuthorisationRequest,
+    ) -> Result<(&Oauth2RS, SupportedResponseMode, Option<Vec<u8>>), Oauth2Error> {
         if auth_req.response_type != ResponseType::Code {
             admin_warn!("Unsupported OAuth2 response_type (should be 'code')");
             return Err(Oauth2Error::UnsupportedResponseType);
@@ -2416,6 +2766,30 @@ impl IdmServerProxyReadTransaction<'_> {
             None
         };
 
+        Ok((o2rs, response_mode, code_challenge))
+    }
+
+    fn check_oauth2_authorisation_inner(
+        &self,
+        maybe_ident: Option<&Identity>,
+        auth_req: &AuthorisationRequest,
+        auth_req_ctx: &AuthorisationRequestContext,
+        pushed: bool,
+        ct: Duration,
+    ) -> Result<AuthoriseResponse, Oauth2Error> {
+        // due to identity processing we already know that:
+        // * the session must be authenticated, and valid
+        // * is within it's valid time window.
+        trace!(?auth_req, ?auth_req_ctx);
+
+        let (o2rs, response_mode, code_challenge) =
+            self.validate_oauth2_authorisation_request(auth_req)?;
+
+        if o2rs.require_pushed_authorisation && !pushed {
+            warn!(?o2rs.name, "OAuth2 client requires pushed authorisation requests, but the request was provided inline");
+            return Err(Oauth2Error::InvalidRequest);
+        }
+
         // =============================================================================
         // By this point, we have validated the majority of the security related
         // parameters of the request. We can now inspect the identity and decide
@@ -2466,6 +2840,14 @@ impl IdmServerProxyReadTransaction<'_> {
 
         let auth_time = ident.last_verified_at();
 
+        // acr_values are a list of acceptable classes, so the weakest one we recognise is
+        // what the session must satisfy. Unknown values are ignored as they are voluntary.
+        let required_acr = auth_req
+            .acr_values
+            .iter()
+            .filter_map(|acr| acr.parse::<Acr>().ok())
+            .min();
+
         if let Some(max_age) = max_age {
             let session_recently_validated = if max_age <= 0 {
                 // Reauth will be forced.
@@ -2495,6 +2877,43 @@ impl IdmServerProxyReadTransaction<'_> {
                 debug!("prompt=login was requested, forcing re-authentication");
                 return Ok(AuthoriseResponse::ReauthenticationRequired {
                     client_name: o2rs.displayname.clone(),
+                    acr: required_acr,
+                });
+            }
+        }
+
+        // If the client requested a class of authentication that the credential used for this
+        // session does not satisfy, or that credential wasn't verified recently, the user must
+        // step up before we can proceed.
+        if let Some(required_acr) = required_acr {
+            let session_acr = ident.acr();
+            let acr_expiry = ident
+                .acr_verified_at()
+                .map(|verified_at| verified_at + Duration::from_secs(OAUTH2_ACR_MAX_AGE));
+            let acr_fresh = acr_expiry
+                .map(|expiry| OffsetDateTime::UNIX_EPOCH + ct <= expiry)
+                .unwrap_or(false);
+
+            if session_acr < Some(required_acr) || !acr_fresh {
+                if auth_req.prompt.contains(&Prompt::None) {
+                    debug!(
+                        ?session_acr,
+                        ?acr_expiry,
+                        ?required_acr,
+                        "prompt=none was requested, but the session acr is insufficient or stale, returning error"
+                    );
+                    return Err(Oauth2Error::LoginRequired);
+                }
+
+                debug!(
+                    ?session_acr,
+                    ?acr_expiry,
+                    ?required_acr,
+                    "session acr is insufficient or stale, forcing step up authentication"
+                );
+                return Ok(AuthoriseResponse::ReauthenticationRequired {
+                    client_name: o2rs.displayname.clone(),
+                    acr: Some(required_acr),
                 });
             }
         }
@@ -2553,6 +2972,7 @@ impl IdmServerProxyReadTransaction<'_> {
                 scopes: granted_scopes.into_iter().collect(),
                 nonce: auth_req.nonce.clone(),
                 auth_time,
+                acr: ident.acr(),
             };
 
             // Encrypt the exchange token with the key of the client
@@ -2728,20 +3148,85 @@ impl IdmServerProxyReadTransaction<'_> {
     #[instrument(level = "debug", skip_all)]
     pub fn check_oauth2_token_introspect(
         &mut self,
+        client_auth_info: &ClientAuthInfo,
         intr_req: &AccessTokenIntrospectRequest,
         ct: Duration,
     ) -> Result<AccessTokenIntrospectResponse, Oauth2Error> {
-        // We don't need to authenticate, since possession of the access token is already enough
-        // to prove identity, and we enforce it is cryptographically valid so it can't be bruteforced
-        // by a scanning attack.
+        // Confidential clients must authenticate, and can only introspect tokens that were
+        // issued to them. Public clients can't hold a secret, so a request with no (or only
+        // a public client's) credentials may only introspect tokens of public clients. That
+        // keeps the server from being an oracle for tokens issued to confidential clients.
+        let authenticated_client_id = match get_client_auth(
+            client_auth_info,
+            &intr_req.client_post_auth,
+        ) {
+            Ok(client_auth) => {
+                let o2rs = self
+                    .oauth2rs
+                    .inner
+                    .rs_set_get(&client_auth.client_id)
+                    .ok_or_else(|| {
+                        debug!("Invalid OAuth2 client_id {}", &client_auth.client_id);
+                        Oauth2Error::AuthenticationRequired
+                    })?;
+
+                match (&o2rs.type_, client_auth.client_secret) {
+                    (OauthRSType::Basic { authz_secret, .. }, Some(secret)) => {
+                        if !authz_secret.ct_eq(&secret) {
+                            info!("Invalid OAuth2 client_id secret");
+                            return Err(Oauth2Error::AuthenticationRequired);
+                        }
+                    }
+                    (OauthRSType::Basic { .. }, None) => {
+                        info!("Invalid OAuth2 authentication - no secret in introspection request");
+                        return Err(Oauth2Error::AuthenticationRequired);
+                    }
+                    (OauthRSType::Public { .. }, _) => {}
+                }
 
-        if let Ok(jwsc) = JwsCompact::from_str(&intr_req.token) {
+                Some(o2rs.name.clone())
+            }
+            Err(_) => None,
+        };
+
+        let intr_response = if let Ok(jwsc) = JwsCompact::from_str(&intr_req.token) {
             self.oauth2_token_introspect_jwt(&jwsc, ct)
         } else if let Ok(jwec) = JweCompact::from_str(&intr_req.token) {
             self.oauth2_token_introspect_jwe(&jwec, ct)
         } else {
             error!("Failed to deserialise a valid JWE");
-            Err(Oauth2Error::AuthenticationRequired)
+            Err(Oauth2Error::InvalidRequest)
+        };
+
+        match intr_response {
+            Ok(intr_response) => {
+                let token_client_id = intr_response.client_id.as_deref();
+
+                let permitted = match authenticated_client_id.as_deref() {
+                    Some(client_id) => token_client_id == Some(client_id),
+                    None => token_client_id
+                        .and_then(|client_id| self.oauth2rs.inner.rs_set_get(client_id))
+                        .map(|o2rs| matches!(o2rs.type_, OauthRSType::Public { .. }))
+                        .unwrap_or(false),
+                };
+
+                if permitted {
+                    Ok(intr_response)
+                } else {
+                    // The token is valid, but the caller isn't allowed to know about it.
+                    security_info!(
+                        client_id = ?authenticated_client_id,
+                        "token was not issued to the requesting client, returning inactive"
+                    );
+                    Ok(AccessTokenIntrospectResponse::inactive())
+                }
+            }
+            // Tokens that can't be decoded or verified are simply inactive, so that we don't
+            // reveal anything about why the token was rejected.
+            Err(Oauth2Error::AuthenticationRequired) | Err(Oauth2Error::InvalidRequest) => {
+                Ok(AccessTokenIntrospectResponse::inactive())
+            }
+            Err(err) => Err(err),
         }
     }
 
@@ -2803,7 +3288,7 @@ impl IdmServerProxyReadTransaction<'_> {
         // Has this token expired?
         if exp <= ct.as_secs() as i64 {
             security_info!(?sub, "access token has expired, returning inactive");
-            return Ok(AccessTokenIntrospectResponse::inactive(jti));
+            return Ok(AccessTokenIntrospectResponse::inactive());
         }
 
         let prefer_short_username = o2rs.prefer_short_username;
@@ -2820,7 +3305,7 @@ impl IdmServerProxyReadTransaction<'_> {
                 ?sub,
                 "access token account is not valid, returning inactive"
             );
-            return Ok(AccessTokenIntrospectResponse::inactive(jti));
+            return Ok(AccessTokenIntrospectResponse::inactive());
         };
 
         let account = match Account::try_from_entry_ro(&entry, &mut self.qs_read) {
@@ -2851,7 +3336,7 @@ impl IdmServerProxyReadTransaction<'_> {
             sub: Some(sub.to_string()),
             aud: Some(client_id),
             iss: Some(iss),
-            jti,
+            jti: Some(jti),
         })
     }
 
@@ -2901,7 +3386,7 @@ impl IdmServerProxyReadTransaction<'_> {
                 // Has this token expired?
                 if exp <= ct.as_secs() as i64 {
                     security_info!(?uuid, "access token has expired, returning inactive");
-                    return Ok(AccessTokenIntrospectResponse::inactive(session_id));
+                    return Ok(AccessTokenIntrospectResponse::inactive());
                 }
 
                 let prefer_short_username = o2rs.prefer_short_username;
@@ -2918,7 +3403,7 @@ impl IdmServerProxyReadTransaction<'_> {
                         ?uuid,
                         "access token account is not valid, returning inactive"
                     );
-                    return Ok(AccessTokenIntrospectResponse::inactive(session_id));
+                    return Ok(AccessTokenIntrospectResponse::inactive());
                 };
 
                 let scope = scopes.clone();
@@ -2945,12 +3430,10 @@ impl IdmServerProxyReadTransaction<'_> {
                     sub: Some(uuid.to_string()),
                     aud: Some(client_id),
                     iss: Some(iss),
-                    jti: session_id,
+                    jti: Some(session_id),
                 })
             }
-            Oauth2TokenType::Refresh { session_id, .. } => {
-                Ok(AccessTokenIntrospectResponse::inactive(session_id))
-            }
+            Oauth2TokenType::Refresh { .. } => Ok(AccessTokenIntrospectResponse::inactive()),
         }
     }
 
@@ -3001,7 +3484,7 @@ impl IdmServerProxyReadTransaction<'_> {
             extensions:
                 OAuth2RFC9068TokenExtensions {
                     auth_time,
-                    acr: _,
+                    acr,
                     amr: _,
                     scope: scopes,
                     nonce,
@@ -3052,7 +3535,7 @@ impl IdmServerProxyReadTransaction<'_> {
             auth_time,
             nonce,
             at_hash: None,
-            acr: None,
+            acr,
             amr,
             azp: Some(client_id.to_string()),
             jti: Some(session_id.to_string()),
@@ -3121,6 +3604,10 @@ impl IdmServerProxyReadTransaction<'_> {
             introspection_endpoint_auth_methods_supported,
             introspection_endpoint_auth_signing_alg_values_supported: None,
             code_challenge_methods_supported,
+            pushed_authorization_request_endpoint: Some(
+                o2rs.pushed_authorization_request_endpoint.clone(),
+            ),
+            require_pushed_authorization_requests: o2rs.require_pushed_authorisation,
         })
     }
 
@@ -3191,7 +3678,7 @@ impl IdmServerProxyReadTransaction<'_> {
             response_types_supported,
             response_modes_supported,
             grant_types_supported,
-            acr_values_supported: None,
+            acr_values_supported: Some(Acr::ALL.iter().map(|acr| acr.to_string()).collect()),
             subject_types_supported,
             id_token_signing_alg_values_supported,
             id_token_encryption_alg_values_supported: None,
@@ -3227,6 +3714,10 @@ impl IdmServerProxyReadTransaction<'_> {
             introspection_endpoint_auth_methods_supported,
             introspection_endpoint_auth_signing_alg_values_supported: None,
             device_authorization_endpoint: o2rs.device_authorization_endpoint.clone(),
+            pushed_authorization_request_endpoint: Some(
+                o2rs.pushed_authorization_request_endpoint.clone(),
+            ),
+            require_pushed_authorization_requests: o2rs.require_pushed_authorisation,
         })
     }
 
@@ -3344,8 +3835,9 @@ fn s_claims_for_account(
     };
 
     let (email, email_verified) = if scopes.contains(OAUTH2_SCOPE_EMAIL) {
-        if let Some(mp) = &account.mail_primary {
-            (Some(mp.clone()), Some(true))
+        // Only addresses the owner has proven control of are released to clients.
+        if let Some(mp) = account.mail_verified_primary() {
+            (Some(mp.to_string()), Some(true))
         } else {
             (None, None)
         }
@@ -3386,20 +3878,23 @@ fn extra_claims_for_account(
     let mut account_claims: BTreeMap<&str, ClaimValue> = BTreeMap::new();
 
     // for each group
-    for group_uuid in account.groups.iter().map(|g| g.uuid()) {
+    for group in account.groups.iter() {
         // Does this group have any custom claims?
-        if let Some(claim) = claim_map.get(group_uuid) {
+        if let Some(claim) = claim_map.get(group.uuid()) {
+            let group_name = group.name().unwrap_or_else(|| group.spn());
             // If so, iterate over the set of claims and values.
             for (claim_name, claim_value) in claim.iter() {
+                // Templates reference the group, so they must be applied before merging.
+                let claim_value = claim_value.render(group_name);
                 // Does this claim name already exist in our in-progress map?
                 match account_claims.entry(claim_name.as_str()) {
                     BTreeEntry::Vacant(e) => {
-                        e.insert(claim_value.clone());
+                        e.insert(claim_value);
                     }
                     BTreeEntry::Occupied(mut e) => {
                         let mut_claim_value = e.get_mut();
                         // Merge the extra details into this.
-                        mut_claim_value.merge(claim_value);
+                        mut_claim_value.merge(&claim_value);
                     }
                 }
             }
@@ -3535,8 +4030,6 @@ fn validate_scopes(req_scopes: &BTreeSet<String>) -> Result<(), Oauth2Error> {
 
 /// device code is a random bucket of bytes used in the device flow
 #[inline]
-#[cfg(any(feature = "dev-oauth2-device-flow", test))]
-#[allow(dead_code)]
 fn gen_device_code() -> Result<[u8; 16], Oauth2Error> {
     use rand::TryRng;
 
@@ -3551,29 +4044,44 @@ fn gen_device_code() -> Result<[u8; 16], Oauth2Error> {
 }
 
 #[inline]
-#[cfg(any(feature = "dev-oauth2-device-flow", test))]
-#[allow(dead_code)]
-/// Returns (xxx-yyy-zzz, digits) where one's the human-facing code, the other is what we store in the DB.
-fn gen_user_code() -> (String, u32) {
+/// Returns the human-facing code in its normalised XXXX-XXXX form.
+fn gen_user_code() -> String {
     use rand::RngExt;
     let mut rng = rand::rng();
-    let num: u32 = rng.random_range(0..=999999999);
-    let result = format!("{num:09}");
-    (
-        format!("{}-{}-{}", &result[0..3], &result[3..6], &result[6..9]),
-        num,
-    )
+    let code: String = (0..OAUTH2_DEVICE_USER_CODE_LEN)
+        .filter_map(|_| {
+            OAUTH2_DEVICE_USER_CODE_ALPHABET
+                .get(rng.random_range(0..OAUTH2_DEVICE_USER_CODE_ALPHABET.len()))
+                .map(|c| *c as char)
+        })
+        .collect();
+    format_user_code(&code)
 }
 
-/// Take the supplied user code and check it's a valid u32
-#[allow(dead_code)]
-fn parse_user_code(val: &str) -> Result<u32, Oauth2Error> {
-    let mut val = val.to_string();
-    val.retain(|c| c.is_ascii_digit());
-    val.parse().map_err(|err| {
-        debug!("Failed to parse value={} as u32: {:?}", val, err);
-        Oauth2Error::InvalidRequest
-    })
+fn format_user_code(code: &str) -> String {
+    let (left, right) = code.split_at(OAUTH2_DEVICE_USER_CODE_LEN / 2);
+    format!("{left}-{right}")
+}
+
+/// Take a user code as entered by the user, and return it in the same normalised form
+/// that it was issued in. Case, separators and whitespace are ignored.
+pub fn normalise_user_code(val: &str) -> Result<String, Oauth2Error> {
+    let code: String = val
+        .chars()
+        .filter(|c| *c != '-' && !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+
+    if code.len() != OAUTH2_DEVICE_USER_CODE_LEN
+        || !code
+            .bytes()
+            .all(|b| OAUTH2_DEVICE_USER_CODE_ALPHABET.contains(&b))
+    {
+        debug!("Invalid user code value={}", val);
+        return Err(Oauth2Error::InvalidRequest);
+    }
+
+    Ok(format_user_code(&code))
 }
 
 /// Check if a host is local (loopback or localhost)
@@ -3600,14 +4108,17 @@ mod tests {
         TOKEN_EXCHANGE_SUBJECT_TOKEN_TYPE_ACCESS,
     };
     use crate::credential::Credential;
+    use crate::idm::account::DestroySessionTokenEvent;
     use crate::idm::accountpolicy::ResolvedAccountPolicy;
     use crate::idm::oauth2::{
-        host_is_local, parse_basic_authz, AuthoriseResponse, Oauth2Error, OauthRSType,
+        host_is_local, normalise_user_code, parse_basic_authz, AuthoriseResponse, Oauth2Error,
+        OauthRSType,
     };
     use crate::idm::server::{IdmServer, IdmServerTransaction};
     use crate::idm::serviceaccount::GenerateApiTokenEvent;
     use crate::prelude::*;
-    use crate::value::{AuthType, OauthClaimMapJoin, SessionState};
+    use crate::server::security_event::SecurityEventKind;
+    use crate::value::{Acr, AuthType, OauthClaimMapJoin, SessionState};
     use crate::valueset::{ValueSetOauthScopeMap, ValueSetSshKey, ValueSetUint32};
     use base64::{engine::general_purpose, Engine as _};
     use compact_jwt::{
@@ -3619,6 +4130,7 @@ mod tests {
     use kanidm_proto::constants::*;
     use kanidm_proto::internal::{SshPublicKey, UserAuthToken};
     use kanidm_proto::oauth2::*;
+    use kanidm_proto::v1::{AuditOperation, AuditQuery};
     use std::collections::{BTreeMap, BTreeSet};
     use std::convert::TryFrom;
     use std::str::FromStr;
@@ -3656,6 +4168,7 @@ mod tests {
                 max_age: None,
                 prompt: Default::default(),
                 ui_locales: Default::default(),
+                acr_values: Default::default(),
                 unknown_keys: Default::default(),
             };
 
@@ -3814,6 +4327,8 @@ mod tests {
                 scope: SessionScope::ReadWrite,
                 type_: AuthType::Passkey,
                 ext_metadata: Default::default(),
+                source_ip: None,
+                user_agent: None,
             },
         );
 
@@ -3952,6 +4467,8 @@ mod tests {
                 scope: SessionScope::ReadWrite,
                 type_: AuthType::Passkey,
                 ext_metadata: Default::default(),
+                source_ip: None,
+                user_agent: None,
             },
         );
 
@@ -4208,10 +4725,41 @@ mod tests {
             .check_oauth2_token_exchange(&ClientAuthInfo::none(), &token_req, ct)
             .expect("Failed to perform OAuth2 token exchange");
 
-        // 🎉 We got a token! In the future we can then check introspection from this point.
+        // 🎉 We got a token!
         assert_eq!(token_response.token_type, AccessTokenType::Bearer);
 
         assert!(idms_prox_write.commit().is_ok());
+
+        // Public clients can't authenticate, but can still introspect their own tokens.
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+
+        let intr_request = AccessTokenIntrospectRequest {
+            token: token_response.access_token.clone(),
+            token_type_hint: None,
+            client_post_auth: ClientPostAuth::default(),
+        };
+        let intr_response = idms_prox_read
+            .check_oauth2_token_introspect(&ClientAuthInfo::none(), &intr_request, ct)
+            .expect("Failed to inspect token");
+        assert!(intr_response.active);
+        assert_eq!(
+            intr_response.client_id.as_deref(),
+            Some("test_resource_server")
+        );
+
+        let intr_request = AccessTokenIntrospectRequest {
+            token: token_response.access_token,
+            token_type_hint: None,
+            client_post_auth: ClientPostAuth {
+                client_id: Some("test_resource_server".to_string()),
+                client_secret: None,
+            },
+        };
+        let intr_response = idms_prox_read
+            .check_oauth2_token_introspect(&ClientAuthInfo::none(), &intr_request, ct)
+            .expect("Failed to inspect token");
+        assert!(intr_response.active);
+        assert!(intr_response.jti.is_some());
     }
 
     #[idm_test]
@@ -4250,6 +4798,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt: Default::default(),
             unknown_keys: Default::default(),
         };
@@ -4274,6 +4823,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt: Default::default(),
             unknown_keys: Default::default(),
         };
@@ -4298,6 +4848,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt: Default::default(),
             unknown_keys: Default::default(),
         };
@@ -4323,6 +4874,7 @@ mod tests {
             max_age: None,
             prompt: Default::default(),
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             unknown_keys: Default::default(),
         };
 
@@ -4346,6 +4898,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt: Default::default(),
             unknown_keys: Default::default(),
         };
@@ -4370,6 +4923,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt: Default::default(),
             unknown_keys: Default::default(),
         };
@@ -4393,6 +4947,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt: Default::default(),
             unknown_keys: Default::default(),
         };
@@ -4416,6 +4971,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt: Default::default(),
             unknown_keys: Default::default(),
         };
@@ -4440,6 +4996,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt: Default::default(),
             unknown_keys: Default::default(),
         };
@@ -4466,6 +5023,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt: Default::default(),
             unknown_keys: Default::default(),
         };
@@ -4490,6 +5048,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt: Default::default(),
             unknown_keys: Default::default(),
         };
@@ -4514,6 +5073,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt: Default::default(),
             unknown_keys: Default::default(),
         };
@@ -4815,6 +5375,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt: Default::default(),
             unknown_keys: Default::default(),
         };
@@ -4899,6 +5460,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt: Default::default(),
             unknown_keys: Default::default(),
         };
@@ -4998,7 +5560,7 @@ mod tests {
             client_post_auth: ClientPostAuth::default(),
         };
         let intr_response = idms_prox_read
-            .check_oauth2_token_introspect(&intr_request, ct)
+            .check_oauth2_token_introspect(&client_authz, &intr_request, ct)
             .expect("Failed to inspect token");
 
         eprintln!("👉  {intr_response:?}");
@@ -5037,7 +5599,7 @@ mod tests {
         // check again.
         let mut idms_prox_read = idms.proxy_read().await.unwrap();
         let intr_response = idms_prox_read
-            .check_oauth2_token_introspect(&intr_request, ct)
+            .check_oauth2_token_introspect(&client_authz, &intr_request, ct)
             .expect("Failed to inspect token");
 
         assert!(!intr_response.active);
@@ -5099,7 +5661,7 @@ mod tests {
             client_post_auth: ClientPostAuth::default(),
         };
         let intr_response = idms_prox_read
-            .check_oauth2_token_introspect(&intr_request, ct)
+            .check_oauth2_token_introspect(&client_authz, &intr_request, ct)
             .expect("Failed to inspect token");
         eprintln!("👉  {intr_response:?}");
         assert!(intr_response.active);
@@ -5122,7 +5684,7 @@ mod tests {
         // Check our token is still valid.
         let mut idms_prox_read = idms.proxy_read().await.unwrap();
         let intr_response = idms_prox_read
-            .check_oauth2_token_introspect(&intr_request, ct)
+            .check_oauth2_token_introspect(&client_authz, &intr_request, ct)
             .expect("Failed to inspect token");
         assert!(intr_response.active);
         drop(idms_prox_read);
@@ -5142,7 +5704,7 @@ mod tests {
         // Assert it is now invalid.
         let mut idms_prox_read = idms.proxy_read().await.unwrap();
         let intr_response = idms_prox_read
-            .check_oauth2_token_introspect(&intr_request, ct)
+            .check_oauth2_token_introspect(&client_authz, &intr_request, ct)
             .expect("Failed to inspect token");
 
         assert!(!intr_response.active);
@@ -5168,14 +5730,14 @@ mod tests {
         let mut idms_prox_read = idms.proxy_read().await.unwrap();
         // Grace window in effect.
         let intr_response = idms_prox_read
-            .check_oauth2_token_introspect(&intr_request, ct)
+            .check_oauth2_token_introspect(&client_authz, &intr_request, ct)
             .expect("Failed to inspect token");
         assert!(intr_response.active);
 
         // Grace window passed, it will now be invalid.
         let ct = ct + AUTH_TOKEN_GRACE_WINDOW;
         let intr_response = idms_prox_read
-            .check_oauth2_token_introspect(&intr_request, ct)
+            .check_oauth2_token_introspect(&client_authz, &intr_request, ct)
             .expect("Failed to inspect token");
         assert!(!intr_response.active);
 
@@ -5195,11 +5757,10 @@ mod tests {
     }
 
     #[idm_test]
-    async fn test_idm_oauth2_session_cleanup_post_rs_delete(
+    async fn test_idm_oauth2_parent_session_revoke_cascades(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
     ) {
-        // First, setup to get a token.
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let (secret, _uat, ident, _) =
             setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
@@ -5207,7 +5768,6 @@ mod tests {
 
         let idms_prox_read = idms.proxy_read().await.unwrap();
 
-        // == Setup the authorisation request
         let pkce_secret = PkceS256Secret::default();
 
         let consent_request = good_authorisation_request!(
@@ -5222,7 +5782,129 @@ mod tests {
             unreachable!();
         };
 
-        // == Manually submit the consent token to the permit for the permit_success
+        drop(idms_prox_read);
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+
+        let permit_success = idms_prox_write
+            .check_oauth2_authorise_permit(&ident, &consent_token, ct)
+            .expect("Failed to perform OAuth2 permit");
+
+        let token_req: AccessTokenRequest = GrantTypeReq::AuthorizationCode {
+            code: permit_success.code,
+            redirect_uri: Url::parse("https://demo.example.com/oauth2/result").unwrap(),
+            code_verifier: Some(pkce_secret.to_verifier()),
+        }
+        .into();
+        let oauth2_token = idms_prox_write
+            .check_oauth2_token_exchange(&client_authz, &token_req, ct)
+            .expect("Unable to exchange for OAuth2 token");
+
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+        let intr_request = AccessTokenIntrospectRequest {
+            token: oauth2_token.access_token.clone(),
+            token_type_hint: None,
+            client_post_auth: ClientPostAuth::default(),
+        };
+        let intr_response = idms_prox_read
+            .check_oauth2_token_introspect(&client_authz, &intr_request, ct)
+            .expect("Failed to inspect token");
+        assert!(intr_response.active);
+        drop(idms_prox_read);
+
+        // The user revokes the session that the oauth2 session was derived from. This is
+        // within the grace window, which must not delay the revocation.
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        let dte = DestroySessionTokenEvent {
+            ident: ident.clone(),
+            target: ident.get_uuid(),
+            token_id: ident.get_session_id(),
+        };
+        assert!(idms_prox_write.account_destroy_session_token(&dte).is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+        let intr_response = idms_prox_read
+            .check_oauth2_token_introspect(&client_authz, &intr_request, ct)
+            .expect("Failed to inspect token");
+        assert!(!intr_response.active);
+
+        let entry = idms_prox_read
+            .qs_read
+            .internal_search_uuid(ident.get_uuid())
+            .expect("failed");
+        let sessions = entry
+            .get_ava_as_oauth2session_map(Attribute::OAuth2Session)
+            .expect("No oauth2 sessions");
+        assert!(sessions
+            .values()
+            .all(|session| matches!(session.state, SessionState::RevokedAt(_))));
+        drop(idms_prox_read);
+
+        // Allow idm_admin to read the audit records.
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_IDM_AUDIT_READERS,
+                &ModifyList::new_append(Attribute::Member, Value::Refer(UUID_IDM_ADMIN)),
+            )
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        // The revocation was audited as a change made by the user.
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+        let idm_admin = idms_prox_read
+            .qs_read
+            .internal_search_uuid(UUID_IDM_ADMIN)
+            .expect("Failed to find idm_admin");
+        let reader_ident = Identity::from_impersonate_entry_readonly(idm_admin);
+        let query = AuditQuery {
+            actor: Some(ident.get_uuid()),
+            target: Some(ident.get_uuid()),
+            operation: Some(AuditOperation::Modify),
+            ..Default::default()
+        };
+        let page = idms_prox_read
+            .qs_read
+            .audit_search(&reader_ident, &query)
+            .expect("Failed to search audit records");
+        assert!(page.records.iter().any(|record| record
+            .attributes
+            .iter()
+            .any(|attr| attr == Attribute::UserAuthTokenSession.as_str())));
+    }
+
+    #[idm_test]
+    async fn test_idm_oauth2_session_cleanup_post_rs_delete(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        // First, setup to get a token.
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (secret, _uat, ident, _) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+        let client_authz = ClientAuthInfo::encode_basic("test_resource_server", secret.as_str());
+
+        let idms_prox_read = idms.proxy_read().await.unwrap();
+
+        // == Setup the authorisation request
+        let pkce_secret = PkceS256Secret::default();
+
+        let consent_request = good_authorisation_request!(
+            idms_prox_read,
+            &ident,
+            ct,
+            pkce_secret.to_request(),
+            OAUTH2_SCOPE_OPENID.to_string()
+        );
+
+        let AuthoriseResponse::ConsentRequested { consent_token, .. } = consent_request else {
+            unreachable!();
+        };
+
+        // == Manually submit the consent token to the permit for the permit_success
         drop(idms_prox_read);
         let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
 
@@ -5624,7 +6306,15 @@ mod tests {
         assert!(discovery.service_documentation.is_some());
 
         assert!(discovery.registration_endpoint.is_none());
-        assert!(discovery.acr_values_supported.is_none());
+        assert_eq!(
+            discovery.acr_values_supported,
+            Some(vec![
+                "password".to_string(),
+                "mfa".to_string(),
+                "passkey".to_string(),
+                "attested_passkey".to_string(),
+            ])
+        );
         assert!(discovery.id_token_encryption_alg_values_supported.is_none());
         assert!(discovery.id_token_encryption_enc_values_supported.is_none());
         assert!(discovery.userinfo_encryption_alg_values_supported.is_none());
@@ -5787,7 +6477,8 @@ mod tests {
         // Is nonce correctly passed through?
         assert_eq!(oidc.nonce, Some("abcdef".to_string()));
         assert!(oidc.at_hash.is_none());
-        assert!(oidc.acr.is_none());
+        // The session was established with a passkey.
+        assert_eq!(oidc.acr.as_deref(), Some("passkey"));
         assert!(oidc.amr.is_none());
         assert_eq!(oidc.azp, Some("test_resource_server".to_string()));
         assert!(oidc.jti.is_some());
@@ -5823,7 +6514,7 @@ mod tests {
         assert_eq!(oidc.auth_time, userinfo.auth_time);
         assert_eq!(userinfo.nonce, Some("abcdef".to_string()));
         assert!(userinfo.at_hash.is_none());
-        assert!(userinfo.acr.is_none());
+        assert_eq!(oidc.acr, userinfo.acr);
         assert_eq!(oidc.amr, userinfo.amr);
         assert_eq!(oidc.azp, userinfo.azp);
         assert!(userinfo.jti.is_some());
@@ -5871,7 +6562,7 @@ mod tests {
         assert_eq!(oidc.auth_time, userinfo.auth_time);
         assert_eq!(userinfo.nonce, Some("abcdef".to_string()));
         assert!(userinfo.at_hash.is_none());
-        assert!(userinfo.acr.is_none());
+        assert_eq!(oidc.acr, userinfo.acr);
         assert_eq!(oidc.amr, userinfo.amr);
         assert_eq!(oidc.azp, userinfo.azp);
         assert!(userinfo.jti.is_some());
@@ -6261,6 +6952,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt: Default::default(),
             unknown_keys: Default::default(),
         };
@@ -6514,6 +7206,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt: Default::default(),
             unknown_keys: Default::default(),
         };
@@ -6577,6 +7270,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt: Default::default(),
             unknown_keys: Default::default(),
         };
@@ -6720,6 +7414,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt: Default::default(),
             unknown_keys: Default::default(),
         };
@@ -6801,6 +7496,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt: Default::default(),
             unknown_keys: Default::default(),
         };
@@ -6910,6 +7606,234 @@ mod tests {
         (access_token_response_1, client_authz, oauth2_rs_uuid)
     }
 
+    #[idm_test]
+    async fn test_idm_oauth2_domain_rename(idms: &IdmServer, idms_delayed: &mut IdmServerDelayed) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+
+        let (access_token_response_1, client_authz, _oauth2_rs_uuid) =
+            setup_refresh_token(idms, idms_delayed, ct).await;
+
+        let id_token = access_token_response_1
+            .id_token
+            .as_ref()
+            .expect("no id_token was issued");
+        let oidc = validate_id_token(idms, ct, id_token).await;
+        assert_eq!(
+            oidc.s_claims.preferred_username.as_deref(),
+            Some("testperson1@example.com")
+        );
+
+        // Rename the domain and migrate all spns.
+        let ct = Duration::from_secs(TEST_CURRENT_TIME + 10);
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        idms_prox_write
+            .qs_write
+            .domain_rename("new.example.com")
+            .expect("Failed to rename domain");
+        let mut cursor = None;
+        while let Some(next) = idms_prox_write
+            .qs_write
+            .domain_rename_migrate(cursor.as_deref(), 16)
+            .expect("Failed to migrate domain rename")
+        {
+            cursor = Some(next);
+        }
+        assert!(idms_prox_write.commit().is_ok());
+
+        // The issuer is derived from the origin, so discovery and existing tokens remain
+        // valid, but the presented names follow the new domain.
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+        let discovery = idms_prox_read
+            .oauth2_openid_discovery("test_resource_server")
+            .expect("Failed to get discovery");
+        assert_eq!(discovery.issuer, oidc.iss);
+
+        let access_token = JwsCompact::from_str(&access_token_response_1.access_token)
+            .expect("Invalid Access Token");
+        let userinfo = idms_prox_read
+            .oauth2_openid_userinfo("test_resource_server", &access_token, ct)
+            .expect("failed to get userinfo");
+        assert_eq!(
+            userinfo.s_claims.preferred_username.as_deref(),
+            Some("testperson1@new.example.com")
+        );
+        drop(idms_prox_read);
+
+        // A refreshed id_token carries the new name.
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        let token_req: AccessTokenRequest = GrantTypeReq::RefreshToken {
+            refresh_token: access_token_response_1
+                .refresh_token
+                .clone()
+                .expect("no refresh token was issued"),
+            scope: None,
+        }
+        .into();
+        let access_token_response_2 = idms_prox_write
+            .check_oauth2_token_exchange(&client_authz, &token_req, ct)
+            .expect("Unable to exchange for OAuth2 token");
+        assert!(idms_prox_write.commit().is_ok());
+
+        let oidc = validate_id_token(
+            idms,
+            ct,
+            access_token_response_2
+                .id_token
+                .as_ref()
+                .expect("no id_token was issued"),
+        )
+        .await;
+        assert_eq!(
+            oidc.s_claims.preferred_username.as_deref(),
+            Some("testperson1@new.example.com")
+        );
+    }
+
+    #[idm_test]
+    async fn test_idm_oauth2_email_claim_requires_verification(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (secret, uat, _ident, _) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+        let client_authz = ClientAuthInfo::encode_basic("test_resource_server", secret.as_str());
+
+        // Allow the email scope, and let the person manage their own mail.
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        // Unverified addresses are only tracked from 1.12, which can't be bootstrapped directly.
+        assert!(idms_prox_write
+            .qs_write
+            .internal_apply_domain_migration(DOMAIN_LEVEL_1_12)
+            .is_ok());
+        let modlist = ModifyList::new_list(vec![Modify::Present(
+            Attribute::OAuth2RsScopeMap,
+            Value::new_oauthscopemap(
+                UUID_IDM_ALL_ACCOUNTS,
+                btreeset![
+                    OAUTH2_SCOPE_EMAIL.to_string(),
+                    OAUTH2_SCOPE_PROFILE.to_string(),
+                    OAUTH2_SCOPE_OPENID.to_string()
+                ],
+            )
+            .expect("invalid oauthscope"),
+        )]);
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify(
+                &filter!(f_eq(
+                    Attribute::Name,
+                    PartialValue::new_iname("test_resource_server")
+                )),
+                &modlist
+            )
+            .is_ok());
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_IDM_PEOPLE_SELF_MAIL_WRITE,
+                &ModifyList::new_append(Attribute::Member, Value::Refer(UUID_TESTPERSON_1)),
+            )
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        // The person adds their own address, which is not yet verified.
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        let ident = idms_prox_write
+            .process_uat_to_identity(&uat, ct, Source::Internal)
+            .expect("Unable to process uat");
+        let me = ModifyEvent::new_impersonate_identity(
+            ident.clone(),
+            filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(UUID_TESTPERSON_1))),
+            ModifyList::new_append(
+                Attribute::Mail,
+                Value::new_email_address_primary_s("self@example.com").unwrap(),
+            ),
+        );
+        assert!(idms_prox_write.qs_write.modify(&me).is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let idms_prox_read = idms.proxy_read().await.unwrap();
+        let pkce_secret = PkceS256Secret::default();
+        let consent_request = good_authorisation_request!(
+            idms_prox_read,
+            &ident,
+            ct,
+            pkce_secret.to_request(),
+            format!("{OAUTH2_SCOPE_OPENID} {OAUTH2_SCOPE_EMAIL}")
+        );
+        let AuthoriseResponse::ConsentRequested { consent_token, .. } = consent_request else {
+            unreachable!();
+        };
+        drop(idms_prox_read);
+
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        let permit_success = idms_prox_write
+            .check_oauth2_authorise_permit(&ident, &consent_token, ct)
+            .expect("Failed to perform OAuth2 permit");
+        let token_req: AccessTokenRequest = GrantTypeReq::AuthorizationCode {
+            code: permit_success.code,
+            redirect_uri: Url::parse("https://demo.example.com/oauth2/result").unwrap(),
+            code_verifier: Some(pkce_secret.to_verifier()),
+        }
+        .into();
+        let token_response = idms_prox_write
+            .check_oauth2_token_exchange(&client_authz, &token_req, ct)
+            .expect("Failed to perform OAuth2 token exchange");
+        assert!(idms_prox_write.commit().is_ok());
+
+        let access_token =
+            JwsCompact::from_str(&token_response.access_token).expect("Invalid Access Token");
+
+        // The unverified address is withheld.
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+        let userinfo = idms_prox_read
+            .oauth2_openid_userinfo("test_resource_server", &access_token, ct)
+            .expect("failed to get userinfo");
+        assert!(userinfo.s_claims.email.is_none());
+        assert!(userinfo.s_claims.email_verified.is_none());
+        drop(idms_prox_read);
+
+        // Verify the address.
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        let ev = crate::idm::mail::MailVerificationSendEvent {
+            ident,
+            target: UUID_TESTPERSON_1,
+            mail: "self@example.com".to_string(),
+        };
+        assert!(idms_prox_write.mail_verification_send(&ev, ct).is_ok());
+
+        let message = idms_prox_write
+            .qs_write
+            .internal_search(filter!(f_eq(
+                Attribute::Class,
+                EntryClass::OutboundMessage.into()
+            )))
+            .ok()
+            .and_then(|mut messages| messages.pop())
+            .expect("No message was queued");
+        let Some(kanidm_proto::v1::OutboundMessage::MailVerificationV1 { token, .. }) = message
+            .get_ava_set(Attribute::MessageTemplate)
+            .and_then(|vs| vs.as_message())
+        else {
+            panic!("Unexpected message template");
+        };
+        let token = token.clone();
+
+        assert!(idms_prox_write
+            .mail_verification_confirm(&token, ct)
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        // Now the address is released.
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+        let userinfo = idms_prox_read
+            .oauth2_openid_userinfo("test_resource_server", &access_token, ct)
+            .expect("failed to get userinfo");
+        assert_eq!(userinfo.s_claims.email.as_deref(), Some("self@example.com"));
+        assert_eq!(userinfo.s_claims.email_verified, Some(true));
+    }
+
     #[idm_test]
     async fn test_idm_oauth2_refresh_token_basic(
         idms: &IdmServer,
@@ -7163,24 +8087,34 @@ mod tests {
         assert!(idms_prox_write.commit().is_ok());
     }
 
-    // refresh with wrong client id/authz
     #[idm_test]
-    async fn test_idm_oauth2_refresh_token_invalid_client_authz(
+    async fn test_idm_oauth2_refresh_token_account_valid_from_expire(
         idms: &IdmServer,
         idms_delayed: &mut IdmServerDelayed,
     ) {
-        // First, setup to get a token.
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
 
-        let (access_token_response_1, _client_authz, _oauth2_rs_uuid) =
+        let (access_token_response_1, client_authz, _oauth2_rs_uuid) =
             setup_refresh_token(idms, idms_delayed, ct).await;
 
-        let bad_client_authz = ClientAuthInfo::encode_basic("test_resource_server", "12345");
-
-        // ============================================
-        // Refresh with invalid client authz
-
+        // The account is only valid from a minute after the session was issued, for two
+        // minutes.
         let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        let modlist = ModifyList::new_list(vec![
+            Modify::Present(
+                Attribute::AccountValidFrom,
+                Value::new_datetime_epoch(Duration::from_secs(TEST_CURRENT_TIME + 60)),
+            ),
+            Modify::Present(
+                Attribute::AccountExpire,
+                Value::new_datetime_epoch(Duration::from_secs(TEST_CURRENT_TIME + 180)),
+            ),
+        ]);
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify_uuid(UUID_TESTPERSON_1, &modlist)
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
 
         let refresh_token = access_token_response_1
             .refresh_token
@@ -7193,8 +8127,80 @@ mod tests {
             scope: None,
         }
         .into();
-        let access_token_response_2 = idms_prox_write
-            .check_oauth2_token_exchange(&bad_client_authz, &token_req, ct)
+
+        let mut event_rx = idms
+            .security_events()
+            .subscribe(8)
+            .expect("failed to subscribe");
+
+        for (ct, kind) in [
+            (
+                Duration::from_secs(TEST_CURRENT_TIME + 10),
+                SecurityEventKind::AccountNotYetValid,
+            ),
+            (
+                Duration::from_secs(TEST_CURRENT_TIME + 240),
+                SecurityEventKind::AccountExpired,
+            ),
+        ] {
+            let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+            assert_eq!(
+                idms_prox_write
+                    .check_oauth2_token_exchange(&client_authz, &token_req, ct)
+                    .unwrap_err(),
+                Oauth2Error::InvalidGrant
+            );
+            drop(idms_prox_write);
+
+            let event = event_rx
+                .try_recv()
+                .expect("no security event was submitted");
+            assert_eq!(event.kind, kind);
+            assert_eq!(event.targets, vec![UUID_TESTPERSON_1]);
+        }
+
+        // Within the window the refresh proceeds, and nothing is recorded.
+        let ct = Duration::from_secs(TEST_CURRENT_TIME + 120);
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        assert!(idms_prox_write
+            .check_oauth2_token_exchange(&client_authz, &token_req, ct)
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+        assert!(event_rx.try_recv().is_err());
+    }
+
+    // refresh with wrong client id/authz
+    #[idm_test]
+    async fn test_idm_oauth2_refresh_token_invalid_client_authz(
+        idms: &IdmServer,
+        idms_delayed: &mut IdmServerDelayed,
+    ) {
+        // First, setup to get a token.
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+
+        let (access_token_response_1, _client_authz, _oauth2_rs_uuid) =
+            setup_refresh_token(idms, idms_delayed, ct).await;
+
+        let bad_client_authz = ClientAuthInfo::encode_basic("test_resource_server", "12345");
+
+        // ============================================
+        // Refresh with invalid client authz
+
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+
+        let refresh_token = access_token_response_1
+            .refresh_token
+            .as_ref()
+            .expect("no refresh token was issued")
+            .clone();
+
+        let token_req: AccessTokenRequest = GrantTypeReq::RefreshToken {
+            refresh_token,
+            scope: None,
+        }
+        .into();
+        let access_token_response_2 = idms_prox_write
+            .check_oauth2_token_exchange(&bad_client_authz, &token_req, ct)
             .unwrap_err();
 
         assert_eq!(access_token_response_2, Oauth2Error::AuthenticationRequired);
@@ -7728,7 +8734,8 @@ mod tests {
         // Is nonce correctly passed through?
         assert_eq!(oidc.nonce, Some("abcdef".to_string()));
         assert!(oidc.at_hash.is_none());
-        assert!(oidc.acr.is_none());
+        // The session was established with a passkey.
+        assert_eq!(oidc.acr.as_deref(), Some("passkey"));
         assert!(oidc.amr.is_none());
         assert_eq!(oidc.azp, Some("test_resource_server".to_string()));
         assert!(oidc.jti.is_some());
@@ -7795,7 +8802,7 @@ mod tests {
             client_post_auth: ClientPostAuth::default(),
         };
         let intr_response = idms_prox_read
-            .check_oauth2_token_introspect(&intr_request, ct)
+            .check_oauth2_token_introspect(&client_authz, &intr_request, ct)
             .expect("Failed to inspect token");
 
         eprintln!("👉  {intr_response:?}");
@@ -7820,6 +8827,160 @@ mod tests {
         drop(idms_prox_read);
     }
 
+    #[idm_test]
+    async fn test_idm_oauth2_custom_claims_join_and_template(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (secret, _uat, ident, oauth2_rs_uuid) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+
+        let claim_maps = [
+            ("join_csv", OauthClaimMapJoin::CommaSeparatedValue, None),
+            ("join_ssv", OauthClaimMapJoin::SpaceSeparatedValue, None),
+            ("join_array", OauthClaimMapJoin::JsonArray, None),
+            ("join_single", OauthClaimMapJoin::Single, None),
+            (
+                "role_array",
+                OauthClaimMapJoin::JsonArray,
+                Some("ROLE_{group}"),
+            ),
+            (
+                "role_single",
+                OauthClaimMapJoin::Single,
+                Some("{group}:{value}"),
+            ),
+        ];
+
+        let mut mods = Vec::with_capacity(claim_maps.len() * 4);
+        for (claim_name, join, template) in claim_maps {
+            mods.push(Modify::Present(
+                Attribute::OAuth2RsClaimMap,
+                Value::OauthClaimMap(claim_name.to_string(), join),
+            ));
+            mods.push(Modify::Present(
+                Attribute::OAuth2RsClaimMap,
+                Value::OauthClaimTemplate(claim_name.to_string(), template.map(str::to_string)),
+            ));
+            // Both groups contribute values, so they have to be joined.
+            mods.push(Modify::Present(
+                Attribute::OAuth2RsClaimMap,
+                Value::OauthClaimValue(
+                    claim_name.to_string(),
+                    UUID_TESTGROUP,
+                    btreeset!["value_a".to_string()],
+                ),
+            ));
+            mods.push(Modify::Present(
+                Attribute::OAuth2RsClaimMap,
+                Value::OauthClaimValue(
+                    claim_name.to_string(),
+                    UUID_IDM_ALL_ACCOUNTS,
+                    btreeset!["value_b".to_string()],
+                ),
+            ));
+        }
+
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify(
+                &filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(oauth2_rs_uuid))),
+                &ModifyList::new_list(mods),
+            )
+            .is_ok());
+
+        assert!(idms_prox_write.commit().is_ok());
+
+        let idms_prox_read = idms.proxy_read().await.unwrap();
+
+        let pkce_secret = PkceS256Secret::default();
+
+        let consent_request = good_authorisation_request!(
+            idms_prox_read,
+            &ident,
+            ct,
+            pkce_secret.to_request(),
+            OAUTH2_SCOPE_OPENID.to_string()
+        );
+
+        let AuthoriseResponse::ConsentRequested { consent_token, .. } = consent_request else {
+            unreachable!();
+        };
+
+        drop(idms_prox_read);
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+
+        let permit_success = idms_prox_write
+            .check_oauth2_authorise_permit(&ident, &consent_token, ct)
+            .expect("Failed to perform OAuth2 permit");
+
+        let token_req: AccessTokenRequest = GrantTypeReq::AuthorizationCode {
+            code: permit_success.code,
+            redirect_uri: Url::parse("https://demo.example.com/oauth2/result").unwrap(),
+            code_verifier: Some(pkce_secret.to_verifier()),
+        }
+        .into();
+
+        let client_authz = ClientAuthInfo::encode_basic("test_resource_server", secret.as_str());
+
+        let token_response = idms_prox_write
+            .check_oauth2_token_exchange(&client_authz, &token_req, ct)
+            .expect("Failed to perform OAuth2 token exchange");
+
+        assert!(idms_prox_write.commit().is_ok());
+
+        let id_token = token_response.id_token.expect("No id_token in response!");
+        let access_token =
+            JwsCompact::from_str(&token_response.access_token).expect("Invalid Access Token");
+
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+
+        let mut jwkset = idms_prox_read
+            .oauth2_openid_publickey("test_resource_server")
+            .expect("Failed to get public key");
+
+        let public_jwk = jwkset.keys.pop().expect("no such jwk");
+
+        let jws_validator =
+            JwsEs256Verifier::try_from(&public_jwk).expect("failed to build validator");
+
+        let oidc_unverified =
+            OidcUnverified::from_str(&id_token).expect("Failed to parse id_token");
+
+        let oidc = jws_validator
+            .verify(&oidc_unverified)
+            .unwrap()
+            .verify_exp(ct.as_secs() as i64)
+            .expect("Failed to verify oidc");
+
+        let userinfo = idms_prox_read
+            .oauth2_openid_userinfo("test_resource_server", &access_token, ct)
+            .expect("failed to get userinfo");
+
+        let expected = [
+            ("join_csv", serde_json::json!("value_a,value_b")),
+            ("join_ssv", serde_json::json!("value_a value_b")),
+            ("join_array", serde_json::json!(["value_a", "value_b"])),
+            ("join_single", serde_json::json!("value_a;value_b")),
+            (
+                "role_array",
+                serde_json::json!(["ROLE_idm_all_accounts", "ROLE_testgroup"]),
+            ),
+            (
+                "role_single",
+                serde_json::json!("idm_all_accounts:value_b;testgroup:value_a"),
+            ),
+        ];
+
+        for (claim_name, expected_value) in expected {
+            assert_eq!(oidc.claims.get(claim_name), Some(&expected_value));
+            assert_eq!(userinfo.claims.get(claim_name), Some(&expected_value));
+        }
+    }
+
     #[idm_test]
     async fn test_idm_oauth2_public_allow_localhost_redirect(
         idms: &IdmServer,
@@ -7868,6 +9029,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt: Default::default(),
             unknown_keys: Default::default(),
         };
@@ -8030,7 +9192,7 @@ mod tests {
             client_post_auth: ClientPostAuth::default(),
         };
         let intr_response = idms_prox_read
-            .check_oauth2_token_introspect(&intr_request, ct)
+            .check_oauth2_token_introspect(&client_authz, &intr_request, ct)
             .expect("Failed to introspect service account token");
 
         assert!(intr_response.active);
@@ -8053,6 +9215,8 @@ mod tests {
         let (secret, _uat, _ident, _) =
             setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
 
+        let client_authz = ClientAuthInfo::encode_basic("test_resource_server", secret.as_str());
+
         // scope: Some(btreeset!["invalid_scope".to_string()]),
         let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
 
@@ -8083,7 +9247,7 @@ mod tests {
             client_post_auth: ClientPostAuth::default(),
         };
         let intr_response = idms_prox_read
-            .check_oauth2_token_introspect(&intr_request, ct)
+            .check_oauth2_token_introspect(&client_authz, &intr_request, ct)
             .expect("Failed to inspect token");
 
         eprintln!("👉  {intr_response:?}");
@@ -8126,7 +9290,7 @@ mod tests {
         };
 
         let intr_response = idms_prox_read
-            .check_oauth2_token_introspect(&intr_request, ct)
+            .check_oauth2_token_introspect(&client_authz, &intr_request, ct)
             .expect("Failed to inspect token");
         assert!(!intr_response.active);
 
@@ -8134,71 +9298,636 @@ mod tests {
     }
 
     #[idm_test]
-    async fn test_idm_oauth2_basic_client_credentials_grant_invalid(
+    async fn test_idm_oauth2_token_introspect_client_authentication(
         idms: &IdmServer,
         _idms_delayed: &mut IdmServerDelayed,
     ) {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let (secret, _uat, _ident, _) =
             setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+        let client_authz = ClientAuthInfo::encode_basic("test_resource_server", secret.as_str());
 
         let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
 
-        // Public Client
+        // A second client that must not be able to see tokens of the first.
+        let other_rs_uuid = Uuid::new_v4();
+        let other_rs: Entry<EntryInit, EntryNew> = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (
+                Attribute::Class,
+                EntryClass::OAuth2ResourceServer.to_value()
+            ),
+            (
+                Attribute::Class,
+                EntryClass::OAuth2ResourceServerBasic.to_value()
+            ),
+            (Attribute::Uuid, Value::Uuid(other_rs_uuid)),
+            (Attribute::Name, Value::new_iname("other_resource_server")),
+            (
+                Attribute::DisplayName,
+                Value::new_utf8s("other_resource_server")
+            ),
+            (
+                Attribute::OAuth2RsOriginLanding,
+                Value::new_url_s("https://other.example.com").unwrap()
+            )
+        );
+
+        idms_prox_write
+            .qs_write
+            .internal_create(vec![other_rs])
+            .expect("Failed to create second resource server");
+
+        let other_secret = idms_prox_write
+            .qs_write
+            .internal_search_uuid(other_rs_uuid)
+            .expect("Failed to retrieve OAuth2 resource entry")
+            .get_ava_single_secret(Attribute::OAuth2RsBasicSecret)
+            .map(str::to_string)
+            .expect("No oauth2_rs_basic_secret found");
+
         let token_req = AccessTokenRequest {
             grant_type: GrantTypeReq::ClientCredentials { scope: None },
             client_post_auth: ClientPostAuth {
                 client_id: Some("test_resource_server".to_string()),
-                client_secret: None,
+                client_secret: Some(secret.clone()),
             },
         };
 
-        assert_eq!(
-            idms_prox_write
-                .check_oauth2_token_exchange(&ClientAuthInfo::none(), &token_req, ct)
-                .unwrap_err(),
-            Oauth2Error::AuthenticationRequired
-        );
+        let oauth2_token = idms_prox_write
+            .check_oauth2_token_exchange(&ClientAuthInfo::none(), &token_req, ct)
+            .expect("Failed to perform OAuth2 token exchange");
 
-        // Incorrect Password
-        let token_req = AccessTokenRequest {
-            grant_type: GrantTypeReq::ClientCredentials { scope: None },
+        assert!(idms_prox_write.commit().is_ok());
 
-            client_post_auth: ClientPostAuth {
-                client_id: Some("test_resource_server".to_string()),
-                client_secret: Some("wrong password".to_string()),
-            },
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+
+        let intr_request = AccessTokenIntrospectRequest {
+            token: oauth2_token.access_token.clone(),
+            token_type_hint: None,
+            client_post_auth: ClientPostAuth::default(),
         };
 
+        // Unauthenticated callers learn nothing about a confidential client's token.
+        let intr_response = idms_prox_read
+            .check_oauth2_token_introspect(&ClientAuthInfo::none(), &intr_request, ct)
+            .expect("Failed to inspect token");
+        assert!(!intr_response.active);
+        assert!(intr_response.client_id.is_none());
+
+        // Callers with the wrong secret are rejected.
+        let bad_authz = ClientAuthInfo::encode_basic("test_resource_server", "not the secret");
         assert_eq!(
-            idms_prox_write
-                .check_oauth2_token_exchange(&ClientAuthInfo::none(), &token_req, ct)
+            idms_prox_read
+                .check_oauth2_token_introspect(&bad_authz, &intr_request, ct)
                 .unwrap_err(),
             Oauth2Error::AuthenticationRequired
         );
 
-        // Invalid scope
-        let scope = Some(btreeset!["💅".to_string()]);
-        let token_req = AccessTokenRequest {
-            grant_type: GrantTypeReq::ClientCredentials { scope },
-
+        // The owning client sees the live token, authenticated via the post body.
+        let post_intr_request = AccessTokenIntrospectRequest {
+            token: oauth2_token.access_token.clone(),
+            token_type_hint: None,
             client_post_auth: ClientPostAuth {
                 client_id: Some("test_resource_server".to_string()),
-                client_secret: Some(secret.clone()),
+                client_secret: Some(secret),
             },
         };
-
+        let intr_response = idms_prox_read
+            .check_oauth2_token_introspect(&ClientAuthInfo::none(), &post_intr_request, ct)
+            .expect("Failed to inspect token");
+        assert!(intr_response.active);
         assert_eq!(
-            idms_prox_write
-                .check_oauth2_token_exchange(&ClientAuthInfo::none(), &token_req, ct)
-                .unwrap_err(),
-            Oauth2Error::InvalidScope
+            intr_response.client_id.as_deref(),
+            Some("test_resource_server")
         );
+        assert!(intr_response.exp.is_some());
+        assert!(intr_response.sub.is_some());
 
-        // Scopes we aren't a member-of
-        let scope = Some(btreeset!["invalid_scope".to_string()]);
-        let token_req = AccessTokenRequest {
-            grant_type: GrantTypeReq::ClientCredentials { scope },
+        // A different client learns nothing about the token.
+        let other_authz =
+            ClientAuthInfo::encode_basic("other_resource_server", other_secret.as_str());
+        let intr_response = idms_prox_read
+            .check_oauth2_token_introspect(&other_authz, &intr_request, ct)
+            .expect("Failed to inspect token");
+        assert!(!intr_response.active);
+        assert!(intr_response.client_id.is_none());
+        assert!(intr_response.sub.is_none());
+
+        // Garbage is inactive rather than an error.
+        let garbage_request = AccessTokenIntrospectRequest {
+            token: "this is not a token".to_string(),
+            token_type_hint: None,
+            client_post_auth: ClientPostAuth::default(),
+        };
+        let intr_response = idms_prox_read
+            .check_oauth2_token_introspect(&client_authz, &garbage_request, ct)
+            .expect("Failed to inspect token");
+        assert!(!intr_response.active);
+        assert!(intr_response.jti.is_none());
+        assert_eq!(
+            serde_json::to_value(&intr_response).expect("Failed to serialise response"),
+            serde_json::json!({ "active": false })
+        );
+
+        // Once expired, the token is inactive.
+        let expired_ct = ct + Duration::from_secs(OAUTH2_ACCESS_TOKEN_EXPIRY as u64 + 1);
+        let intr_response = idms_prox_read
+            .check_oauth2_token_introspect(&client_authz, &intr_request, expired_ct)
+            .expect("Failed to inspect token");
+        assert!(!intr_response.active);
+    }
+
+    #[idm_test]
+    async fn test_idm_oauth2_access_token_expiry_override(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (secret, _uat, _ident, rs_uuid) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+
+        let token_req = AccessTokenRequest {
+            grant_type: GrantTypeReq::ClientCredentials { scope: None },
+            client_post_auth: ClientPostAuth {
+                client_id: Some("test_resource_server".to_string()),
+                client_secret: Some(secret),
+            },
+        };
+
+        // Without an override, the domain default applies.
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        let oauth2_token = idms_prox_write
+            .check_oauth2_token_exchange(&ClientAuthInfo::none(), &token_req, ct)
+            .expect("Failed to perform OAuth2 token exchange");
+        assert_eq!(oauth2_token.expires_in, OAUTH2_ACCESS_TOKEN_EXPIRY);
+
+        // Set a shorter access token lifetime on the client.
+        let custom_exp = 300;
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                rs_uuid,
+                &ModifyList::new_purge_and_set(
+                    Attribute::OAuth2AccessTokenExpiry,
+                    Value::Uint32(custom_exp)
+                ),
+            )
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        let oauth2_token = idms_prox_write
+            .check_oauth2_token_exchange(&ClientAuthInfo::none(), &token_req, ct)
+            .expect("Failed to perform OAuth2 token exchange");
+        assert_eq!(oauth2_token.expires_in, custom_exp);
+
+        // The recorded session must expire with the token so that it is pruned correctly.
+        let custom_odt_exp =
+            time::OffsetDateTime::UNIX_EPOCH + ct + Duration::from_secs(custom_exp as u64);
+        let rs_entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(rs_uuid)
+            .expect("Failed to retrieve OAuth2 resource entry");
+        assert!(rs_entry
+            .get_ava_as_oauth2session_map(Attribute::OAuth2Session)
+            .expect("No oauth2 sessions found")
+            .values()
+            .any(|session| session.state == SessionState::ExpiresAt(custom_odt_exp)));
+        assert!(idms_prox_write.commit().is_ok());
+
+        // Introspection agrees, and the token is inactive once the override has passed.
+        let client_authz = ClientAuthInfo::encode_basic(
+            "test_resource_server",
+            token_req
+                .client_post_auth
+                .client_secret
+                .as_deref()
+                .expect("No client secret"),
+        );
+        let intr_request = AccessTokenIntrospectRequest {
+            token: oauth2_token.access_token,
+            token_type_hint: None,
+            client_post_auth: ClientPostAuth::default(),
+        };
+
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+        let intr_response = idms_prox_read
+            .check_oauth2_token_introspect(&client_authz, &intr_request, ct)
+            .expect("Failed to inspect token");
+        assert!(intr_response.active);
+        assert_eq!(intr_response.exp, Some(custom_odt_exp.unix_timestamp()));
+
+        let expired_ct = ct + Duration::from_secs(custom_exp as u64);
+        let intr_response = idms_prox_read
+            .check_oauth2_token_introspect(&client_authz, &intr_request, expired_ct)
+            .expect("Failed to inspect token");
+        assert!(!intr_response.active);
+    }
+
+    fn pushed_authorisation_request(pkce_request: PkceRequest) -> AuthorisationRequest {
+        AuthorisationRequest {
+            response_type: ResponseType::Code,
+            response_mode: None,
+            client_id: "test_resource_server".to_string(),
+            state: Some("123".to_string()),
+            pkce_request: Some(pkce_request),
+            redirect_uri: Url::parse("https://demo.example.com/oauth2/result").unwrap(),
+            scope: btreeset![OAUTH2_SCOPE_OPENID.to_string()],
+            nonce: Some("abcdef".to_string()),
+            oidc_ext: Default::default(),
+            max_age: None,
+            prompt: Default::default(),
+            ui_locales: Default::default(),
+            acr_values: Default::default(),
+            unknown_keys: Default::default(),
+        }
+    }
+
+    #[idm_test]
+    async fn test_idm_oauth2_pushed_authorisation_request(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (secret, _uat, ident, _) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+
+        let client_authz = ClientAuthInfo::encode_basic("test_resource_server", secret.as_str());
+        let auth_req_ctx = AuthorisationRequestContext::default();
+        let pkce_secret = PkceS256Secret::default();
+
+        let idms_prox_read = idms.proxy_read().await.unwrap();
+
+        // The client must authenticate to push a request.
+        assert_eq!(
+            idms_prox_read
+                .check_oauth2_pushed_authorisation_request(
+                    &ClientAuthInfo::none(),
+                    pushed_authorisation_request(pkce_secret.to_request()),
+                    ct
+                )
+                .unwrap_err(),
+            Oauth2Error::AuthenticationRequired
+        );
+
+        let mut bad_secret_req = pushed_authorisation_request(pkce_secret.to_request());
+        bad_secret_req.unknown_keys.insert(
+            "client_secret".to_string(),
+            serde_json::Value::String("12345".to_string()),
+        );
+        assert_eq!(
+            idms_prox_read
+                .check_oauth2_pushed_authorisation_request(
+                    &ClientAuthInfo::none(),
+                    bad_secret_req,
+                    ct
+                )
+                .unwrap_err(),
+            Oauth2Error::AuthenticationRequired
+        );
+
+        // The pushed request is validated in the same way as the authorisation endpoint.
+        let mut bad_redirect_req = pushed_authorisation_request(pkce_secret.to_request());
+        bad_redirect_req.redirect_uri =
+            Url::parse("https://totes.not.sus.org/oauth2/result").unwrap();
+        assert_eq!(
+            idms_prox_read
+                .check_oauth2_pushed_authorisation_request(&client_authz, bad_redirect_req, ct)
+                .unwrap_err(),
+            Oauth2Error::InvalidOrigin
+        );
+
+        let mut no_pkce_req = pushed_authorisation_request(pkce_secret.to_request());
+        no_pkce_req.pkce_request = None;
+        assert_eq!(
+            idms_prox_read
+                .check_oauth2_pushed_authorisation_request(&client_authz, no_pkce_req, ct)
+                .unwrap_err(),
+            Oauth2Error::InvalidRequest
+        );
+
+        let mut bad_scope_req = pushed_authorisation_request(pkce_secret.to_request());
+        bad_scope_req.scope = btreeset!["invalid_scope\"".to_string()];
+        assert_eq!(
+            idms_prox_read
+                .check_oauth2_pushed_authorisation_request(&client_authz, bad_scope_req, ct)
+                .unwrap_err(),
+            Oauth2Error::InvalidScope
+        );
+
+        let par_res = idms_prox_read
+            .check_oauth2_pushed_authorisation_request(
+                &client_authz,
+                pushed_authorisation_request(pkce_secret.to_request()),
+                ct,
+            )
+            .expect("Failed to push authorisation request");
+
+        assert_eq!(par_res.expires_in, OAUTH2_PAR_EXPIRY);
+        assert!(par_res
+            .request_uri
+            .starts_with(OAUTH2_PAR_REQUEST_URI_PREFIX));
+
+        let par_ref = PushedAuthorisationReference {
+            client_id: "test_resource_server".to_string(),
+            request_uri: par_res.request_uri.clone(),
+            unknown_keys: Default::default(),
+        };
+
+        // Parameters alongside the reference conflict with the pushed request.
+        let mut conflicting_ref = par_ref.clone();
+        conflicting_ref.unknown_keys.insert(
+            "scope".to_string(),
+            serde_json::Value::String("openid".to_string()),
+        );
+        assert_eq!(
+            idms_prox_read
+                .check_oauth2_authorisation_pushed(
+                    Some(&ident),
+                    &conflicting_ref,
+                    &auth_req_ctx,
+                    ct
+                )
+                .unwrap_err(),
+            Oauth2Error::InvalidRequest
+        );
+
+        // The reference is bound to the client that pushed it.
+        let mut other_client_ref = par_ref.clone();
+        other_client_ref.client_id = "other_resource_server".to_string();
+        assert_eq!(
+            idms_prox_read
+                .check_oauth2_authorisation_pushed(
+                    Some(&ident),
+                    &other_client_ref,
+                    &auth_req_ctx,
+                    ct
+                )
+                .unwrap_err(),
+            Oauth2Error::InvalidRequest
+        );
+
+        // A request_uri can't be mixed into an inline request either.
+        let mut inline_req = pushed_authorisation_request(pkce_secret.to_request());
+        inline_req.unknown_keys.insert(
+            "request_uri".to_string(),
+            serde_json::Value::String(par_res.request_uri.clone()),
+        );
+        assert_eq!(
+            idms_prox_read
+                .check_oauth2_authorisation(Some(&ident), &inline_req, &auth_req_ctx, ct)
+                .unwrap_err(),
+            Oauth2Error::InvalidRequest
+        );
+
+        // Without an identity we must authenticate, and the request remains available.
+        assert!(matches!(
+            idms_prox_read.check_oauth2_authorisation_pushed(None, &par_ref, &auth_req_ctx, ct),
+            Ok(AuthoriseResponse::AuthenticationRequired { .. })
+        ));
+
+        assert!(matches!(
+            idms_prox_read.check_oauth2_authorisation_pushed(
+                Some(&ident),
+                &par_ref,
+                &auth_req_ctx,
+                ct
+            ),
+            Ok(AuthoriseResponse::ConsentRequested { .. })
+        ));
+
+        // The request_uri is single use.
+        assert_eq!(
+            idms_prox_read
+                .check_oauth2_authorisation_pushed(Some(&ident), &par_ref, &auth_req_ctx, ct)
+                .unwrap_err(),
+            Oauth2Error::InvalidRequest
+        );
+
+        // A pushed request that is not used in time expires.
+        let par_res = idms_prox_read
+            .check_oauth2_pushed_authorisation_request(
+                &client_authz,
+                pushed_authorisation_request(pkce_secret.to_request()),
+                ct,
+            )
+            .expect("Failed to push authorisation request");
+
+        let par_ref = PushedAuthorisationReference {
+            client_id: "test_resource_server".to_string(),
+            request_uri: par_res.request_uri,
+            unknown_keys: Default::default(),
+        };
+
+        let expired_ct = ct + Duration::from_secs(OAUTH2_PAR_EXPIRY as u64 + 1);
+        assert_eq!(
+            idms_prox_read
+                .check_oauth2_authorisation_pushed(
+                    Some(&ident),
+                    &par_ref,
+                    &auth_req_ctx,
+                    expired_ct
+                )
+                .unwrap_err(),
+            Oauth2Error::InvalidRequest
+        );
+    }
+
+    #[idm_test]
+    async fn test_idm_oauth2_pushed_authorisation_request_limit(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (secret, _uat, _ident, _) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+
+        let client_authz = ClientAuthInfo::encode_basic("test_resource_server", secret.as_str());
+        let pkce_secret = PkceS256Secret::default();
+
+        let idms_prox_read = idms.proxy_read().await.unwrap();
+
+        for _ in 0..OAUTH2_PAR_MAX_PER_CLIENT {
+            assert!(idms_prox_read
+                .check_oauth2_pushed_authorisation_request(
+                    &client_authz,
+                    pushed_authorisation_request(pkce_secret.to_request()),
+                    ct,
+                )
+                .is_ok());
+        }
+
+        // A client can't hold more than its share of pushed requests.
+        assert_eq!(
+            idms_prox_read
+                .check_oauth2_pushed_authorisation_request(
+                    &client_authz,
+                    pushed_authorisation_request(pkce_secret.to_request()),
+                    ct,
+                )
+                .unwrap_err(),
+            Oauth2Error::TemporarilyUnavailable
+        );
+
+        // Once the requests expire and are pruned, the client may push again.
+        let expired_ct = ct + Duration::from_secs(OAUTH2_PAR_EXPIRY as u64 + 1);
+        idms.expire_oauth2_pushed_requests(expired_ct);
+
+        assert!(idms_prox_read
+            .check_oauth2_pushed_authorisation_request(
+                &client_authz,
+                pushed_authorisation_request(pkce_secret.to_request()),
+                expired_ct,
+            )
+            .is_ok());
+    }
+
+    #[idm_test]
+    async fn test_idm_oauth2_require_pushed_authorisation(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (secret, _uat, ident, rs_uuid) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                rs_uuid,
+                &ModifyList::new_purge_and_set(
+                    Attribute::OAuth2RequirePushedAuthorisation,
+                    Value::new_bool(true)
+                ),
+            )
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let client_authz = ClientAuthInfo::encode_basic("test_resource_server", secret.as_str());
+        let auth_req_ctx = AuthorisationRequestContext::default();
+        let pkce_secret = PkceS256Secret::default();
+
+        let idms_prox_read = idms.proxy_read().await.unwrap();
+
+        let discovery = idms_prox_read
+            .oauth2_openid_discovery("test_resource_server")
+            .expect("Failed to get discovery");
+        assert!(discovery.require_pushed_authorization_requests);
+        assert_eq!(
+            discovery
+                .pushed_authorization_request_endpoint
+                .as_ref()
+                .map(|u| u.path()),
+            Some("/oauth2/par")
+        );
+
+        // Inline requests are now refused.
+        assert_eq!(
+            idms_prox_read
+                .check_oauth2_authorisation(
+                    Some(&ident),
+                    &pushed_authorisation_request(pkce_secret.to_request()),
+                    &auth_req_ctx,
+                    ct
+                )
+                .unwrap_err(),
+            Oauth2Error::InvalidRequest
+        );
+
+        let par_res = idms_prox_read
+            .check_oauth2_pushed_authorisation_request(
+                &client_authz,
+                pushed_authorisation_request(pkce_secret.to_request()),
+                ct,
+            )
+            .expect("Failed to push authorisation request");
+
+        let par_ref = PushedAuthorisationReference {
+            client_id: "test_resource_server".to_string(),
+            request_uri: par_res.request_uri,
+            unknown_keys: Default::default(),
+        };
+
+        assert!(matches!(
+            idms_prox_read.check_oauth2_authorisation_pushed(
+                Some(&ident),
+                &par_ref,
+                &auth_req_ctx,
+                ct
+            ),
+            Ok(AuthoriseResponse::ConsentRequested { .. })
+        ));
+    }
+
+    #[idm_test]
+    async fn test_idm_oauth2_basic_client_credentials_grant_invalid(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (secret, _uat, _ident, _) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+
+        // Public Client
+        let token_req = AccessTokenRequest {
+            grant_type: GrantTypeReq::ClientCredentials { scope: None },
+            client_post_auth: ClientPostAuth {
+                client_id: Some("test_resource_server".to_string()),
+                client_secret: None,
+            },
+        };
+
+        assert_eq!(
+            idms_prox_write
+                .check_oauth2_token_exchange(&ClientAuthInfo::none(), &token_req, ct)
+                .unwrap_err(),
+            Oauth2Error::AuthenticationRequired
+        );
+
+        // Incorrect Password
+        let token_req = AccessTokenRequest {
+            grant_type: GrantTypeReq::ClientCredentials { scope: None },
+
+            client_post_auth: ClientPostAuth {
+                client_id: Some("test_resource_server".to_string()),
+                client_secret: Some("wrong password".to_string()),
+            },
+        };
+
+        assert_eq!(
+            idms_prox_write
+                .check_oauth2_token_exchange(&ClientAuthInfo::none(), &token_req, ct)
+                .unwrap_err(),
+            Oauth2Error::AuthenticationRequired
+        );
+
+        // Invalid scope
+        let scope = Some(btreeset!["💅".to_string()]);
+        let token_req = AccessTokenRequest {
+            grant_type: GrantTypeReq::ClientCredentials { scope },
+
+            client_post_auth: ClientPostAuth {
+                client_id: Some("test_resource_server".to_string()),
+                client_secret: Some(secret.clone()),
+            },
+        };
+
+        assert_eq!(
+            idms_prox_write
+                .check_oauth2_token_exchange(&ClientAuthInfo::none(), &token_req, ct)
+                .unwrap_err(),
+            Oauth2Error::InvalidScope
+        );
+
+        // Scopes we aren't a member-of
+        let scope = Some(btreeset!["invalid_scope".to_string()]);
+        let token_req = AccessTokenRequest {
+            grant_type: GrantTypeReq::ClientCredentials { scope },
 
             client_post_auth: ClientPostAuth {
                 client_id: Some("test_resource_server".to_string()),
@@ -8216,25 +9945,169 @@ mod tests {
         assert!(idms_prox_write.commit().is_ok());
     }
 
+    #[idm_test]
+    async fn test_idm_oauth2_basic_client_credentials_grant_scope_narrowing(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (secret, _uat, _ident, rs_uuid) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+
+        // The client is a member of idm_all_accounts, so could be granted openid and profile,
+        // but only asks for openid.
+        let token_req = AccessTokenRequest {
+            grant_type: GrantTypeReq::ClientCredentials {
+                scope: Some(btreeset![OAUTH2_SCOPE_OPENID.to_string()]),
+            },
+            client_post_auth: ClientPostAuth {
+                client_id: Some("test_resource_server".to_string()),
+                client_secret: Some(secret.clone()),
+            },
+        };
+
+        let oauth2_token = idms_prox_write
+            .check_oauth2_token_exchange(&ClientAuthInfo::none(), &token_req, ct)
+            .expect("Failed to perform OAuth2 token exchange");
+
+        // Supplemental scopes are always added.
+        assert_eq!(
+            oauth2_token.scope,
+            btreeset![OAUTH2_SCOPE_OPENID.to_string(), "supplement".to_string()]
+        );
+        assert!(oauth2_token.refresh_token.is_none());
+        assert!(oauth2_token.id_token.is_none());
+
+        // The session is recorded on the client itself so that it can be revoked.
+        let rs_entry = idms_prox_write
+            .qs_write
+            .internal_search_uuid(rs_uuid)
+            .expect("Failed to retrieve OAuth2 resource entry");
+        assert_eq!(
+            rs_entry
+                .get_ava_as_oauth2session_map(Attribute::OAuth2Session)
+                .map(|sessions| sessions.len()),
+            Some(1)
+        );
+
+        assert!(idms_prox_write.commit().is_ok());
+
+        // Introspection reflects the narrowed scopes. Userinfo refuses these tokens since
+        // they have no user, which is covered by the integration tests as the token is
+        // rejected as a bearer before it reaches the idm layer.
+        let client_authz = ClientAuthInfo::encode_basic("test_resource_server", secret.as_str());
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+
+        let intr_request = AccessTokenIntrospectRequest {
+            token: oauth2_token.access_token.clone(),
+            token_type_hint: None,
+            client_post_auth: ClientPostAuth::default(),
+        };
+        let intr_response = idms_prox_read
+            .check_oauth2_token_introspect(&client_authz, &intr_request, ct)
+            .expect("Failed to inspect token");
+
+        assert!(intr_response.active);
+        assert_eq!(
+            intr_response.scope,
+            btreeset![OAUTH2_SCOPE_OPENID.to_string(), "supplement".to_string()]
+        );
+        assert_eq!(
+            intr_response.client_id.as_deref(),
+            Some("test_resource_server")
+        );
+        assert_eq!(
+            intr_response.username.as_deref(),
+            Some("test_resource_server@example.com")
+        );
+        assert_eq!(intr_response.token_type, Some(AccessTokenType::Bearer));
+    }
+
+    #[idm_test]
+    async fn test_idm_oauth2_public_client_credentials_grant_rejected(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (_uat, _ident, _rs_uuid) = setup_oauth2_resource_server_public(idms, ct).await;
+
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+
+        let token_req = AccessTokenRequest {
+            grant_type: GrantTypeReq::ClientCredentials { scope: None },
+            client_post_auth: ClientPostAuth {
+                client_id: Some("test_resource_server".to_string()),
+                client_secret: None,
+            },
+        };
+
+        assert_eq!(
+            idms_prox_write
+                .check_oauth2_token_exchange(&ClientAuthInfo::none(), &token_req, ct)
+                .unwrap_err(),
+            Oauth2Error::AuthenticationRequired
+        );
+
+        // Even if a secret is presented, public clients have nothing to validate it against.
+        let token_req = AccessTokenRequest {
+            grant_type: GrantTypeReq::ClientCredentials { scope: None },
+            client_post_auth: ClientPostAuth {
+                client_id: Some("test_resource_server".to_string()),
+                client_secret: Some("a secret".to_string()),
+            },
+        };
+
+        assert_eq!(
+            idms_prox_write
+                .check_oauth2_token_exchange(&ClientAuthInfo::none(), &token_req, ct)
+                .unwrap_err(),
+            Oauth2Error::AuthenticationRequired
+        );
+
+        assert!(idms_prox_write.commit().is_ok());
+    }
+
     #[test]
     fn test_get_code() {
-        use super::{gen_device_code, gen_user_code, parse_user_code};
+        use super::{gen_device_code, gen_user_code, normalise_user_code};
 
         assert!(gen_device_code().is_ok());
 
-        let (res_string, res_value) = gen_user_code();
+        let user_code = gen_user_code();
+        assert_eq!(user_code.len(), 9);
+        assert_eq!(user_code.split('-').count(), 2);
 
-        assert!(res_string.split('-').count() == 3);
+        // Issued codes are already in their normalised form.
+        assert_eq!(
+            normalise_user_code(&user_code).expect("Failed to normalise code"),
+            user_code
+        );
 
-        let res_string_clean = res_string.replace("-", "");
-        let res_string_as_num = res_string_clean
-            .parse::<u32>()
-            .expect("Failed to parse as number");
-        assert_eq!(res_string_as_num, res_value);
+        // Users may type it however they like.
+        assert_eq!(
+            normalise_user_code(&user_code.to_lowercase().replace('-', ""))
+                .expect("Failed to normalise code"),
+            user_code
+        );
+        assert_eq!(
+            normalise_user_code(" bcdf - GhJk ").expect("Failed to normalise code"),
+            "BCDF-GHJK"
+        );
 
+        // Wrong length, or characters outside the alphabet.
+        assert_eq!(
+            normalise_user_code("BCDF-GHJ"),
+            Err(Oauth2Error::InvalidRequest)
+        );
         assert_eq!(
-            parse_user_code(&res_string).expect("Failed to parse code"),
-            res_value
+            normalise_user_code("BCDF-GHJKL"),
+            Err(Oauth2Error::InvalidRequest)
+        );
+        assert_eq!(
+            normalise_user_code("ABCD-1234"),
+            Err(Oauth2Error::InvalidRequest)
         );
     }
 
@@ -8256,9 +10129,133 @@ mod tests {
             .proxy_write(ct)
             .await
             .expect("Failed to get idmspwt")
-            .handle_oauth2_start_device_flow(client_auth_info, "test_rs_id", &None, eventid);
+            .handle_oauth2_start_device_flow(&client_auth_info, "test_rs_id", &None, eventid, ct);
         dbg!(&res);
-        assert!(res.is_err());
+        assert_eq!(res.unwrap_err(), Oauth2Error::InvalidClientId);
+    }
+
+    #[idm_test]
+    async fn test_idm_oauth2_device_flow_polling(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (secret, _uat, _ident, rs_uuid) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+
+        let client_auth_info = || {
+            ClientAuthInfo::from(Source::Https(
+                "127.0.0.1"
+                    .parse()
+                    .expect("Failed to parse 127.0.0.1 as an IP!"),
+            ))
+        };
+
+        // Device flow must be enabled on the client first.
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        assert_eq!(
+            idms_prox_write
+                .handle_oauth2_start_device_flow(
+                    &client_auth_info(),
+                    "test_resource_server",
+                    &None,
+                    Uuid::new_v4(),
+                    ct,
+                )
+                .unwrap_err(),
+            Oauth2Error::UnauthorizedClient
+        );
+
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                rs_uuid,
+                &ModifyList::new_purge_and_set(
+                    Attribute::OAuth2DeviceFlowEnable,
+                    Value::new_bool(true)
+                ),
+            )
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        let response = idms_prox_write
+            .handle_oauth2_start_device_flow(
+                &client_auth_info(),
+                "test_resource_server",
+                &None,
+                Uuid::new_v4(),
+                ct,
+            )
+            .expect("Failed to start device flow");
+        assert!(idms_prox_write.commit().is_ok());
+
+        // The code the user is shown is normalised, and mixed case entry maps back to it.
+        let user_code = response.user_code().to_string();
+        assert_eq!(normalise_user_code(&user_code), Ok(user_code.clone()));
+        let mixed_case: String = user_code
+            .replace('-', "")
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                if i % 2 == 0 {
+                    c.to_ascii_lowercase()
+                } else {
+                    c
+                }
+            })
+            .collect();
+        assert_eq!(normalise_user_code(&mixed_case), Ok(user_code));
+
+        let client_authz = ClientAuthInfo::encode_basic("test_resource_server", secret.as_str());
+        let device_code = response.device_code().to_string();
+
+        let poll = |ct: Duration| {
+            let client_authz = client_authz.clone();
+            let token_req: AccessTokenRequest = GrantTypeReq::DeviceCode {
+                device_code: device_code.clone(),
+                scope: None,
+            }
+            .into();
+            async move {
+                let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+                idms_prox_write
+                    .check_oauth2_token_exchange(&client_authz, &token_req, ct)
+                    .unwrap_err()
+            }
+        };
+
+        // Polling before the interval has elapsed is told to slow down, and the
+        // interval grows from 5 to 10 seconds.
+        assert_eq!(poll(ct).await, Oauth2Error::SlowDown);
+        assert_eq!(
+            poll(ct + Duration::from_secs(9)).await,
+            Oauth2Error::SlowDown
+        );
+        // That early poll pushed the interval out to 15 seconds.
+        assert_eq!(
+            poll(ct + Duration::from_secs(23)).await,
+            Oauth2Error::SlowDown
+        );
+        assert_eq!(
+            poll(ct + Duration::from_secs(43)).await,
+            Oauth2Error::AuthorizationPending
+        );
+
+        // Just before expiry the code is still pending, but not a moment after.
+        assert_eq!(
+            poll(ct + Duration::from_secs(OAUTH2_DEVICE_CODE_EXPIRY_SECONDS - 1)).await,
+            Oauth2Error::AuthorizationPending
+        );
+        assert_eq!(
+            poll(ct + Duration::from_secs(OAUTH2_DEVICE_CODE_EXPIRY_SECONDS)).await,
+            Oauth2Error::ExpiredToken
+        );
+        // And once expired it's gone.
+        assert_eq!(
+            poll(ct + Duration::from_secs(OAUTH2_DEVICE_CODE_EXPIRY_SECONDS + 60)).await,
+            Oauth2Error::InvalidGrant
+        );
     }
 
     #[test]
@@ -8358,6 +10355,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt: Default::default(),
             unknown_keys: Default::default(),
         };
@@ -8434,6 +10432,7 @@ mod tests {
             oidc_ext: Default::default(),
             max_age: None,
             ui_locales: Default::default(),
+            acr_values: Default::default(),
             prompt,
             unknown_keys: Default::default(),
         }
@@ -8725,6 +10724,160 @@ mod tests {
         );
     }
 
+    /// When a client requests an acr that the session's credential does not satisfy, the
+    /// user must step up their authentication before the authorisation can proceed.
+    #[idm_test]
+    async fn test_idm_oauth2_acr_values_force_step_up(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (_secret, _uat, ident, _) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+
+        // The basic setup establishes the session with a passkey.
+        assert_eq!(ident.acr(), Some(Acr::Passkey));
+        let verified_at = ident.acr_verified_at().expect("session has no auth time");
+        let password_ident = ident.clone().with_acr(Some((Acr::Password, verified_at)));
+
+        let idms_prox_read = idms.proxy_read().await.unwrap();
+        let pkce_secret = PkceS256Secret::default();
+        let auth_req_ctx = AuthorisationRequestContext { resumed: false };
+
+        let mut auth_req = auth_req_with_prompt(pkce_secret.to_request(), Vec::from([]));
+        auth_req.acr_values = vec![Acr::Passkey.to_string()];
+
+        // A passkey session satisfies the requirement.
+        let result = idms_prox_read
+            .check_oauth2_authorisation(Some(&ident), &auth_req, &auth_req_ctx, ct)
+            .expect("acr_values should not error");
+
+        assert!(matches!(result, AuthoriseResponse::ConsentRequested { .. }));
+
+        // A valid password session must step up to a passkey.
+        let result = idms_prox_read
+            .check_oauth2_authorisation(Some(&password_ident), &auth_req, &auth_req_ctx, ct)
+            .expect("acr_values should not error");
+
+        assert!(matches!(
+            result,
+            AuthoriseResponse::ReauthenticationRequired {
+                acr: Some(Acr::Passkey),
+                ..
+            }
+        ));
+
+        // Even if the authorisation was just resumed, the session must still satisfy the acr.
+        let result = idms_prox_read
+            .check_oauth2_authorisation(
+                Some(&password_ident),
+                &auth_req,
+                &AuthorisationRequestContext { resumed: true },
+                ct,
+            )
+            .expect("acr_values should not error");
+
+        assert!(matches!(
+            result,
+            AuthoriseResponse::ReauthenticationRequired { .. }
+        ));
+
+        // With prompt=none we can't step up, so login is required.
+        let mut auth_req_none =
+            auth_req_with_prompt(pkce_secret.to_request(), Vec::from([Prompt::None]));
+        auth_req_none.acr_values = vec![Acr::Passkey.to_string()];
+
+        let result = idms_prox_read.check_oauth2_authorisation(
+            Some(&password_ident),
+            &auth_req_none,
+            &auth_req_ctx,
+            ct,
+        );
+
+        assert_eq!(result.unwrap_err(), Oauth2Error::LoginRequired);
+
+        // Any of the listed acr values is acceptable, and unknown values are ignored.
+        auth_req.acr_values = vec![
+            "urn:example:unknown".to_string(),
+            Acr::Passkey.to_string(),
+            Acr::Password.to_string(),
+        ];
+
+        let result = idms_prox_read
+            .check_oauth2_authorisation(Some(&password_ident), &auth_req, &auth_req_ctx, ct)
+            .expect("acr_values should not error");
+
+        assert!(matches!(result, AuthoriseResponse::ConsentRequested { .. }));
+    }
+
+    /// A session only satisfies acr_values for a short time after its credential was
+    /// verified, after which the user must authenticate again.
+    #[idm_test]
+    async fn test_idm_oauth2_acr_values_expired_auth(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let (_secret, _uat, ident, _) =
+            setup_oauth2_resource_server_basic(idms, ct, true, false, false).await;
+
+        assert_eq!(ident.acr(), Some(Acr::Passkey));
+        assert_eq!(
+            ident.acr_verified_at(),
+            Some(OffsetDateTime::UNIX_EPOCH + ct)
+        );
+
+        let idms_prox_read = idms.proxy_read().await.unwrap();
+        let pkce_secret = PkceS256Secret::default();
+        let auth_req_ctx = AuthorisationRequestContext { resumed: false };
+
+        let mut auth_req = auth_req_with_prompt(pkce_secret.to_request(), Vec::from([]));
+        auth_req.acr_values = vec![Acr::Passkey.to_string()];
+
+        // Right up to the limit the passkey is still good enough.
+        let limit_ct = ct + Duration::from_secs(OAUTH2_ACR_MAX_AGE);
+        let result = idms_prox_read
+            .check_oauth2_authorisation(Some(&ident), &auth_req, &auth_req_ctx, limit_ct)
+            .expect("acr_values should not error");
+
+        assert!(matches!(result, AuthoriseResponse::ConsentRequested { .. }));
+
+        // After that, even though the credential is strong enough, the user must verify it again.
+        let expired_ct = limit_ct + Duration::from_secs(1);
+        let result = idms_prox_read
+            .check_oauth2_authorisation(Some(&ident), &auth_req, &auth_req_ctx, expired_ct)
+            .expect("acr_values should not error");
+
+        assert!(matches!(
+            result,
+            AuthoriseResponse::ReauthenticationRequired {
+                acr: Some(Acr::Passkey),
+                ..
+            }
+        ));
+
+        let mut auth_req_none =
+            auth_req_with_prompt(pkce_secret.to_request(), Vec::from([Prompt::None]));
+        auth_req_none.acr_values = vec![Acr::Passkey.to_string()];
+
+        let result = idms_prox_read.check_oauth2_authorisation(
+            Some(&ident),
+            &auth_req_none,
+            &auth_req_ctx,
+            expired_ct,
+        );
+
+        assert_eq!(result.unwrap_err(), Oauth2Error::LoginRequired);
+
+        // Without acr_values the session is still valid.
+        let auth_req = auth_req_with_prompt(pkce_secret.to_request(), Vec::from([]));
+        let result = idms_prox_read
+            .check_oauth2_authorisation(Some(&ident), &auth_req, &auth_req_ctx, expired_ct)
+            .expect("authorisation should not error");
+
+        assert!(matches!(result, AuthoriseResponse::ConsentRequested { .. }));
+    }
+
     //TODO: Implement prompt=consent. Requires supporting prompt=login%20consent which will require extra thinking
 
     // /// OIDC Core 1.0 §3.1.2.1 prompt=consent:
@@ -5,7 +5,8 @@ use time::OffsetDateTime;
 use uuid::Uuid;
 
 use crate::entry::{Entry, EntryCommitted, EntryReduced};
-use crate::idm::group::Group;
+use crate::idm::account::{Account, AccountValidity};
+use crate::idm::group::{Group, Radius};
 use crate::prelude::*;
 
 #[derive(Debug, Clone)]
@@ -13,7 +14,7 @@ pub(crate) struct RadiusAccount {
     pub name: String,
     pub displayname: String,
     pub uuid: Uuid,
-    pub groups: Vec<Group<()>>,
+    pub groups: Vec<Group<Radius>>,
     pub radius_secret: String,
     pub valid_from: Option<OffsetDateTime>,
     pub expire: Option<OffsetDateTime>,
@@ -45,7 +46,7 @@ impl RadiusAccount {
             .map(|s| s.to_string())
             .ok_or_else(|| OperationError::MissingAttribute(Attribute::DisplayName))?;
 
-        let groups = Group::<()>::try_from_account_reduced(value, qs)?;
+        let groups = Group::<Radius>::try_from_account_reduced(value, qs)?;
 
         let valid_from = value.get_ava_single_datetime(Attribute::AccountValidFrom);
 
@@ -62,32 +63,45 @@ impl RadiusAccount {
         })
     }
 
-    fn is_within_valid_time(&self, ct: Duration) -> bool {
-        let cot = OffsetDateTime::UNIX_EPOCH + ct;
+    pub(crate) fn validity(&self, ct: Duration) -> AccountValidity {
+        Account::validity_at(ct, self.valid_from.as_ref(), self.expire.as_ref())
+    }
 
-        let vmin = if let Some(vft) = &self.valid_from {
-            // If current time greater than start time window
-            vft < &cot
-        } else {
-            // We have no time, not expired.
-            true
-        };
-        let vmax = if let Some(ext) = &self.expire {
-            // If exp greater than ct then expired.
-            &cot < ext
+    /// Select the vlan of the highest priority group. If groups of the same priority
+    /// assign different vlans the choice is ambiguous, and no vlan is selected.
+    fn resolve_vlan(&self) -> Option<u32> {
+        let mut selected: Option<(u32, u32)> = None;
+        let mut ambiguous = false;
+
+        for (priority, vlan) in self.groups.iter().filter_map(|g| g.radius_vlan()) {
+            match selected {
+                Some((sel_priority, _)) if priority < sel_priority => {}
+                Some((sel_priority, sel_vlan)) if priority == sel_priority => {
+                    ambiguous |= vlan != sel_vlan;
+                }
+                _ => {
+                    selected = Some((priority, vlan));
+                    ambiguous = false;
+                }
+            }
+        }
+
+        if ambiguous {
+            warn!(
+                name = %self.name,
+                "groups of equal priority assign different radius vlans, omitting vlan"
+            );
+            None
         } else {
-            // If not present, we are not expired
-            true
-        };
-        // Mix the results
-        vmin && vmax
+            selected.map(|(_, vlan)| vlan)
+        }
     }
 
     pub(crate) fn to_radiusauthtoken(
         &self,
         ct: Duration,
     ) -> Result<RadiusAuthToken, OperationError> {
-        if !self.is_within_valid_time(ct) {
+        if !self.validity(ct).is_valid() {
             return Err(OperationError::InvalidAccountState(
                 "Account Expired".to_string(),
             ));
@@ -101,6 +115,7 @@ impl RadiusAccount {
             uuid: self.uuid.as_hyphenated().to_string(),
             secret: self.radius_secret.clone(),
             groups: self.groups.iter().map(|g| g.to_proto()).collect(),
+            vlan: self.resolve_vlan(),
         })
     }
 }
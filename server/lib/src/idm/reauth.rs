@@ -5,7 +5,7 @@ use crate::idm::account::Account;
 use crate::idm::authentication::{AuthState, ReauthRequest};
 use crate::idm::authsession::{AuthSession, AuthSessionData};
 use crate::idm::event::AuthResult;
-use crate::idm::server::IdmServerAuthTransaction;
+use crate::idm::server::{IdmServerAuthTransaction, IdmServerTransaction};
 use crate::utils::uuid_from_duration;
 
 // use webauthn_rs::prelude::Webauthn;
@@ -140,6 +140,10 @@ impl IdmServerAuthTransaction<'_> {
             oauth2_client_provider: None,
         };
 
+        // The auth session denies an account outside of its validity window, but the
+        // refusal is recorded here.
+        self.check_account_validity(asd.account.uuid, asd.account.validity(ct), ct);
+
         let domain_keys = self.qs_read.get_domain_key_object_handle()?;
 
         let (auth_session, state) = AuthSession::new_reauth(
@@ -186,6 +190,8 @@ mod tests {
     use crate::idm::event::{AuthEvent, AuthResult};
     use crate::idm::server::IdmServerTransaction;
     use crate::prelude::*;
+    use crate::server::security_event::SecurityEventKind;
+    use crate::value::Acr;
     use compact_jwt::JwsCompact;
     use kanidm_proto::v1::{AuthAllowed, AuthIssueSession, AuthMech};
     use uuid::uuid;
@@ -692,6 +698,64 @@ mod tests {
         assert!(matches!(ident.access_scope(), AccessScope::ReadOnly));
     }
 
+    #[idm_test]
+    async fn test_idm_reauth_step_up_passkey(
+        idms: &IdmServer,
+        idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = duration_from_epoch_now();
+
+        // Setup the test account with both a password + totp, and a passkey.
+        setup_testaccount(idms, ct).await;
+        let (pw, totp) = setup_testaccount_password_totp(idms, ct).await;
+        let mut passkey = setup_testaccount_passkey(idms, ct).await;
+
+        // Do an initial auth with the password.
+        let token = auth_password_totp(idms, ct, &pw, &totp, idms_delayed)
+            .await
+            .expect("failed to authenticate with password totp");
+
+        let ident = token_to_ident(idms, ct, token.into()).await;
+        assert_eq!(ident.acr(), Some(Acr::MultiFactor));
+
+        // A step up that the session already satisfies re-verifies the original credential.
+        let mut idms_auth = idms.auth().await.unwrap();
+        let auth_allowed = idms_auth
+            .reauth_init(
+                ident.clone(),
+                AuthIssueSession::Token,
+                ct,
+                Source::Internal.into(),
+                ReauthRequest::StepUp(Acr::MultiFactor),
+            )
+            .await
+            .expect("Failed to start reauth.");
+        drop(idms_auth);
+
+        assert!(matches!(
+            auth_allowed.state,
+            AuthState::Continue(ref allowed) if allowed.contains(&AuthAllowed::Totp)
+        ));
+
+        // Requiring a passkey forces a webauthn challenge even though the session is valid.
+        let token = reauth_passkey(
+            idms,
+            ct,
+            &ident,
+            &mut passkey,
+            idms_delayed,
+            ReauthRequest::StepUp(Acr::Passkey),
+        )
+        .await
+        .expect("Failed to step up to a passkey");
+
+        // The session now reflects the stronger credential, but step up does not grant
+        // any privileges.
+        let ident = token_to_ident(idms, ct, token.into()).await;
+        assert_eq!(ident.acr(), Some(Acr::Passkey));
+        assert!(matches!(ident.access_scope(), AccessScope::ReadOnly));
+    }
+
     #[idm_test(audit = 1)]
     async fn test_idm_reauth_softlocked_pw(
         idms: &IdmServer,
@@ -749,4 +813,95 @@ mod tests {
                 .is_none()
         );
     }
+
+    #[idm_test]
+    async fn test_idm_reauth_valid_from_expire(
+        idms: &IdmServer,
+        idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = duration_from_epoch_now();
+
+        setup_testaccount(idms, ct).await;
+        let mut passkey = setup_testaccount_passkey(idms, ct).await;
+
+        let token = auth_passkey(idms, ct, &mut passkey, idms_delayed)
+            .await
+            .expect("failed to authenticate with passkey");
+
+        // The account is only valid from a minute after the session was issued, for two
+        // minutes.
+        let time_low = ct;
+        let time = ct + Duration::from_secs(120);
+        let time_high = ct + Duration::from_secs(240);
+
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        let modlist = ModifyList::new_list(vec![
+            Modify::Present(
+                Attribute::AccountValidFrom,
+                Value::new_datetime_epoch(ct + Duration::from_secs(60)),
+            ),
+            Modify::Present(
+                Attribute::AccountExpire,
+                Value::new_datetime_epoch(ct + Duration::from_secs(180)),
+            ),
+        ]);
+        assert!(idms_prox_write
+            .qs_write
+            .internal_modify_uuid(TESTPERSON_UUID, &modlist)
+            .is_ok());
+        assert!(idms_prox_write.commit().is_ok());
+
+        let ident = token_to_ident(idms, time, token.into()).await;
+
+        let mut event_rx = idms
+            .security_events()
+            .subscribe(8)
+            .expect("failed to subscribe");
+
+        for (ct, kind) in [
+            (time_low, SecurityEventKind::AccountNotYetValid),
+            (time_high, SecurityEventKind::AccountExpired),
+        ] {
+            let mut idms_auth = idms.auth().await.unwrap();
+            let AuthResult { state, .. } = idms_auth
+                .reauth_init(
+                    ident.clone(),
+                    AuthIssueSession::Token,
+                    ct,
+                    Source::Internal.into(),
+                    ReauthRequest::GrantReadWrite,
+                )
+                .await
+                .expect("Failed to start reauth.");
+            assert!(matches!(state, AuthState::Denied(_)));
+            idms_auth.commit().expect("Must not fail");
+
+            let event = event_rx
+                .try_recv()
+                .expect("no security event was submitted");
+            assert_eq!(event.kind, kind);
+            assert_eq!(event.targets, vec![TESTPERSON_UUID]);
+        }
+
+        // Within the window the re-auth proceeds.
+        let token = reauth_passkey(
+            idms,
+            time,
+            &ident,
+            &mut passkey,
+            idms_delayed,
+            ReauthRequest::GrantReadWrite,
+        )
+        .await
+        .expect("Failed to get new session token");
+        while let Ok(event) = event_rx.try_recv() {
+            assert!(!matches!(
+                event.kind,
+                SecurityEventKind::AccountNotYetValid | SecurityEventKind::AccountExpired
+            ));
+        }
+
+        let ident = token_to_ident(idms, time, token.into()).await;
+        assert!(matches!(ident.access_scope(), AccessScope::ReadWrite));
+    }
 }
@@ -1,7 +1,10 @@
 use crate::credential::totp::{Totp, TotpAlgo, TotpDigits};
-use crate::idm::server::{IdmServerProxyReadTransaction, IdmServerProxyWriteTransaction};
+use crate::idm::server::{
+    IdmServerProxyReadTransaction, IdmServerProxyWriteTransaction, IdmServerTransaction,
+};
 use crate::prelude::*;
 use crate::schema::{SchemaClass, SchemaTransaction};
+use crate::server::scim::{ScimBulkApplied, ScimBulkEvent, ScimBulkItemResult};
 use crate::value::ApiToken;
 use crate::valueset::ValueSetDateTime;
 use base64::{
@@ -129,6 +132,7 @@ impl IdmServerProxyWriteTransaction<'_> {
                 // What is the access scope of this session? This is
                 // for auditing purposes.
                 scope,
+                operation_scopes: BTreeSet::new(),
             },
         );
 
@@ -505,6 +509,107 @@ pub struct ScimSyncUpdateEvent {
     pub ident: Identity,
 }
 
+/// The outcome of a successfully applied sync request.
+#[derive(Debug, Default)]
+pub struct ScimSyncApplyReport {
+    /// Entries that contained references which could not be resolved to an entry within
+    /// this sync agreement or the database. These references were skipped, and the
+    /// remainder of the entry was applied.
+    pub dangling_references: BTreeMap<Uuid, BTreeSet<String>>,
+}
+
+/// Group sync assertions into phases, so that every entry is in a later phase than the entries
+/// of the same request that it references. A batch modify doesn't preserve the order of its
+/// entries, so each phase must be applied separately. Entries that take part in a reference
+/// cycle can not be ordered, and form a final phase once every other entry has been placed.
+fn order_sync_asserts(
+    asserts: Vec<(Uuid, ModifyList<ModifyInvalid>)>,
+) -> Vec<Vec<(Uuid, ModifyList<ModifyInvalid>)>> {
+    let index: BTreeMap<Uuid, usize> = asserts
+        .iter()
+        .enumerate()
+        .map(|(idx, (u, _))| (*u, idx))
+        .collect();
+
+    // For each entry, the entries that must wait for it, and the number of entries it waits on.
+    let mut dependants: Vec<Vec<usize>> = vec![Vec::new(); asserts.len()];
+    let mut waiting_on: Vec<usize> = vec![0; asserts.len()];
+
+    for (idx, (_, modlist)) in asserts.iter().enumerate() {
+        let depends_on: BTreeSet<usize> = modlist
+            .iter()
+            .filter_map(|m| match m {
+                Modify::Present(_, Value::Refer(target)) => index.get(target).copied(),
+                _ => None,
+            })
+            .filter(|dep| *dep != idx)
+            .collect();
+
+        if let Some(waiting) = waiting_on.get_mut(idx) {
+            *waiting = depends_on.len();
+        }
+        for dep in depends_on {
+            if let Some(deps) = dependants.get_mut(dep) {
+                deps.push(idx);
+            }
+        }
+    }
+
+    let mut phase: Vec<usize> = waiting_on
+        .iter()
+        .enumerate()
+        .filter(|(_, waiting)| **waiting == 0)
+        .map(|(idx, _)| idx)
+        .collect();
+    let mut phases = Vec::new();
+    let mut placed = 0;
+
+    while !phase.is_empty() {
+        let mut next_phase = Vec::new();
+        for dependant in phase
+            .iter()
+            .filter_map(|idx| dependants.get(*idx))
+            .flatten()
+        {
+            if let Some(waiting) = waiting_on.get_mut(*dependant) {
+                *waiting -= 1;
+                if *waiting == 0 {
+                    next_phase.push(*dependant);
+                }
+            }
+        }
+        placed += phase.len();
+        phases.push(phase);
+        phase = next_phase;
+    }
+
+    if placed < asserts.len() {
+        debug!(
+            count = asserts.len() - placed,
+            "Sync entries contain reference cycles, applying them in a final phase"
+        );
+        phases.push(
+            waiting_on
+                .iter()
+                .enumerate()
+                .filter(|(_, waiting)| **waiting != 0)
+                .map(|(idx, _)| idx)
+                .collect(),
+        );
+    }
+
+    let mut asserts: Vec<Option<_>> = asserts.into_iter().map(Some).collect();
+    phases
+        .into_iter()
+        .map(|phase| {
+            phase
+                .into_iter()
+                .filter_map(|idx| asserts.get_mut(idx).and_then(Option::take))
+                .collect()
+        })
+        .collect()
+}
+
 impl IdmServerProxyWriteTransaction<'_> {
     #[instrument(level = "info", skip_all)]
     pub fn scim_sync_apply(
@@ -512,7 +617,7 @@ impl IdmServerProxyWriteTransaction<'_> {
         sse: &ScimSyncUpdateEvent,
         changes: &ScimSyncRequest,
         _ct: Duration,
-    ) -> Result<(), OperationError> {
+    ) -> Result<ScimSyncApplyReport, OperationError> {
         let (sync_uuid, sync_authority_set, change_entries, sync_refresh) =
             self.scim_sync_apply_phase_1(sse, changes)?;
 
@@ -532,8 +637,11 @@ impl IdmServerProxyWriteTransaction<'_> {
             self.scim_sync_apply_phase_refresh_cleanup(&change_entries, sync_uuid)?;
         }
 
-        // All stubs are now set-up. We can proceed to assert entry content.
-        self.scim_sync_apply_phase_3(&change_entries, sync_uuid, &sync_authority_set)?;
+        // All stubs are now set-up. We can proceed to assert entry content. Since every entry
+        // in the change set has a stub, references between entries in this request (such as
+        // nested groups) resolve regardless of the order they were sent in.
+        let report =
+            self.scim_sync_apply_phase_3(&change_entries, sync_uuid, &sync_authority_set)?;
 
         // Remove entries that now need deletion, We do this post assert in case an
         // entry was mistakenly ALSO in the assert set.
@@ -544,7 +652,7 @@ impl IdmServerProxyWriteTransaction<'_> {
 
         info!("success");
 
-        Ok(())
+        Ok(report)
     }
 
     #[instrument(level = "info", skip_all)]
@@ -574,7 +682,7 @@ impl IdmServerProxyWriteTransaction<'_> {
         };
 
         match sse.ident.access_scope() {
-            AccessScope::ReadOnly | AccessScope::ReadWrite => {
+            AccessScope::ReadOnly | AccessScope::ReadWrite | AccessScope::CredentialUpdate => {
                 warn!("Ident access scope is not synchronise");
                 return Err(OperationError::AccessDenied);
             }
@@ -818,10 +926,39 @@ impl IdmServerProxyWriteTransaction<'_> {
             })
     }
 
+    /// Resolve a reference from a sync entry to the uuid of an existing entry. References that
+    /// do not resolve are recorded in `dangling` rather than failing the entry.
+    fn scim_sync_resolve_reference(
+        &mut self,
+        value: &str,
+        dangling: &mut BTreeSet<String>,
+    ) -> Result<Option<Uuid>, OperationError> {
+        let maybe_uuid = self
+            .qs_write
+            .sync_external_id_to_uuid(value)
+            .inspect_err(|err| {
+                error!(?err, "Unable to resolve external_id to uuid");
+            })?;
+
+        // A uuid may be provided that doesn't exist, so we must check it's present.
+        let maybe_uuid = match maybe_uuid {
+            Some(uuid) if self.qs_write.internal_exists_uuid(uuid)? => Some(uuid),
+            _ => None,
+        };
+
+        if maybe_uuid.is_none() {
+            debug!("Could not convert external_id to reference - {}", value);
+            dangling.insert(value.to_string());
+        }
+
+        Ok(maybe_uuid)
+    }
+
     fn scim_attr_to_values(
         &mut self,
         scim_attr_name: &Attribute,
         scim_attr: &ScimValue,
+        dangling: &mut BTreeSet<String>,
     ) -> Result<Vec<Value>, OperationError> {
         let schema = self.qs_write.get_schema();
 
@@ -860,22 +997,9 @@ impl IdmServerProxyWriteTransaction<'_> {
                 .map(|value| vec![Value::Uint32(value)]),
             (SyntaxType::ReferenceUuid, false,
                 ScimValue::Simple(ScimAttr::String(value)),
-            ) => {
-                let maybe_uuid =
-                    self.qs_write.sync_external_id_to_uuid(value).map_err(|e| {
-                        error!(?e, "Unable to resolve external_id to uuid");
-                        e
-                    })?;
-
-                if let Some(uuid) = maybe_uuid {
-                    Ok(vec![Value::Refer(uuid)])
-                } else {
-                    debug!("Could not convert external_id to reference - {}", value);
-                    Err(OperationError::InvalidAttribute(format!(
-                        "Reference uuid must a uuid or external_id - {scim_attr_name}"
-                    )))
-                }
-            }
+            ) => self
+                .scim_sync_resolve_reference(value, dangling)
+                .map(|maybe_uuid| maybe_uuid.map(Value::Refer).into_iter().collect()),
             (SyntaxType::ReferenceUuid, true, ScimValue::MultiComplex(values)) => {
                 // In this case, because it's a reference uuid only, despite the multicomplex structure, it's a list of
                 // "external_id" to external_ids. These *might* also be uuids. So we need to use sync_external_id_to_uuid
@@ -883,7 +1007,7 @@ impl IdmServerProxyWriteTransaction<'_> {
                 //
                 // This is why in phase 2 we "precreate" all objects to make sure they resolve.
                 //
-                // If an id does NOT resolve, we warn and SKIP since it's possible it may have been filtered.
+                // If an id does NOT resolve, we record it and SKIP since it's possible it may have been filtered.
 
                 let mut vs = Vec::with_capacity(values.len());
                 for complex in values.iter() {
@@ -904,16 +1028,8 @@ impl IdmServerProxyWriteTransaction<'_> {
                         }
                     }?;
 
-                    let maybe_uuid =
-                        self.qs_write.sync_external_id_to_uuid(value).map_err(|e| {
-                            error!(?e, "Unable to resolve external_id to uuid");
-                            e
-                        })?;
-
-                    if let Some(uuid) = maybe_uuid {
+                    if let Some(uuid) = self.scim_sync_resolve_reference(value, dangling)? {
                         vs.push(Value::Refer(uuid))
-                    } else {
-                        debug!("Could not convert external_id to reference - {}", value);
                     }
                 }
                 Ok(vs)
@@ -1156,6 +1272,7 @@ impl IdmServerProxyWriteTransaction<'_> {
         sync_allow_class_set: &BTreeMap<String, SchemaClass>,
         sync_allow_attr_set: &BTreeSet<Attribute>,
         phantom_attr_set: &BTreeSet<Attribute>,
+        dangling: &mut BTreeSet<String>,
     ) -> Result<ModifyList<ModifyInvalid>, OperationError> {
         // What classes did they request for this entry to sync?
         let requested_classes = scim_ent.schemas.iter()
@@ -1259,7 +1376,7 @@ impl IdmServerProxyWriteTransaction<'_> {
 
             // Convert each scim_attr to a set of values.
             let values = self
-                .scim_attr_to_values(&scim_attr_name, scim_attr)
+                .scim_attr_to_values(&scim_attr_name, scim_attr, dangling)
                 .inspect_err(|err| {
                     error!(
                         ?err,
@@ -1285,10 +1402,12 @@ impl IdmServerProxyWriteTransaction<'_> {
         change_entries: &BTreeMap<Uuid, &ScimEntry>,
         sync_uuid: Uuid,
         sync_authority_set: &BTreeSet<Attribute>,
-    ) -> Result<(), OperationError> {
+    ) -> Result<ScimSyncApplyReport, OperationError> {
+        let mut report = ScimSyncApplyReport::default();
+
         if change_entries.is_empty() {
             info!("No change_entries requested");
-            return Ok(());
+            return Ok(report);
         }
 
         // Generally this is just assembling a large batch modify. Since we rely on external_id
@@ -1341,28 +1460,46 @@ impl IdmServerProxyWriteTransaction<'_> {
             })
             .collect();
 
-        let asserts = change_entries
-            .iter()
-            .map(|(u, scim_ent)| {
-                self.scim_entry_to_mod(
-                    scim_ent,
-                    sync_uuid,
-                    &sync_allow_class_set,
-                    &sync_allow_attr_set,
-                    &phantom_attr_set,
-                )
-                .map(|e| (*u, e))
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+        let mut asserts = Vec::with_capacity(change_entries.len());
+        for (u, scim_ent) in change_entries.iter() {
+            let mut dangling = BTreeSet::new();
+
+            let modlist = self.scim_entry_to_mod(
+                scim_ent,
+                sync_uuid,
+                &sync_allow_class_set,
+                &sync_allow_attr_set,
+                &phantom_attr_set,
+                &mut dangling,
+            )?;
+
+            if !dangling.is_empty() {
+                warn!(
+                    entry = %u,
+                    references = ?dangling,
+                    "Skipping references that do not resolve to an entry"
+                );
+                report.dangling_references.insert(*u, dangling);
+            }
+
+            asserts.push((*u, modlist));
+        }
 
         // We can't just pass the above iter in here since it's fallible due to the
         // external resolve phase.
+        //
+        // Entries are asserted in a later phase than the entries of this request that they
+        // refer to, so that the referenced entries are in their final state when plugins
+        // such as memberof process the referring entry.
+        for phase in order_sync_asserts(asserts) {
+            self.qs_write
+                .internal_batch_modify(phase.into_iter())
+                .inspect_err(|err| {
+                    error!(?err, "Unable to apply modifications to sync entries.");
+                })?;
+        }
 
-        self.qs_write
-            .internal_batch_modify(asserts.into_iter())
-            .inspect_err(|err| {
-                error!(?err, "Unable to apply modifications to sync entries.");
-            })
+        Ok(report)
     }
 
     #[instrument(level = "info", skip_all)]
@@ -1496,6 +1633,81 @@ impl IdmServerProxyWriteTransaction<'_> {
     }
 }
 
+impl IdmServer {
+    /// Apply a SCIM bulk request on behalf of the client. Operations are applied in order,
+    /// each exactly once, within a single write transaction that also resolves the identity
+    /// of the client. Operations that succeed are committed, and operations that fail report
+    /// their own error. Once the number of failures reaches failOnErrors, the remaining
+    /// operations are not attempted.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn scim_bulk(
+        &self,
+        ct: Duration,
+        client_auth_info: ClientAuthInfo,
+        scim_bulk: ScimBulkEvent,
+    ) -> Result<Vec<ScimBulkItemResult>, OperationError> {
+        self.scim_bulk_apply(ct, scim_bulk, |idms_prox_write, ct| {
+            idms_prox_write.validate_client_auth_info_to_ident(client_auth_info.clone(), ct)
+        })
+        .await
+    }
+
+    pub(crate) async fn scim_bulk_apply<F>(
+        &self,
+        ct: Duration,
+        mut scim_bulk: ScimBulkEvent,
+        resolve_ident: F,
+    ) -> Result<Vec<ScimBulkItemResult>, OperationError>
+    where
+        F: Fn(
+            &mut IdmServerProxyWriteTransaction<'_>,
+            Duration,
+        ) -> Result<Identity, OperationError>,
+    {
+        let mut failed = std::mem::take(&mut scim_bulk.rejected);
+
+        let ScimBulkApplied {
+            mut entries,
+            processed,
+        } = loop {
+            let mut idms_prox_write = self.proxy_write(ct).await?;
+            let ident = resolve_ident(&mut idms_prox_write, ct).inspect_err(|err| {
+                admin_error!(?err, "Invalid identity");
+            })?;
+
+            // An operation that fails to apply may have partially altered the transaction, so
+            // it is discarded and the request is replayed without that operation. Each replay
+            // records one more failure, so this is bounded by the number of operations.
+            if let Some(applied) =
+                idms_prox_write
+                    .qs_write
+                    .scim_bulk_apply(&ident, &scim_bulk, &mut failed)
+            {
+                idms_prox_write.commit()?;
+                break applied;
+            }
+        };
+
+        Ok(scim_bulk
+            .items
+            .into_iter()
+            .enumerate()
+            .take(processed)
+            .filter_map(|(idx, item)| {
+                let outcome = match entries.remove(&idx) {
+                    Some(entry) => Ok(entry),
+                    None => Err(failed.remove(&idx)?),
+                };
+                Some(ScimBulkItemResult {
+                    method: item.method,
+                    bulk_id: item.bulk_id,
+                    outcome,
+                })
+            })
+            .collect())
+    }
+}
+
 impl IdmServerProxyReadTransaction<'_> {
     pub fn scim_sync_get_state(
         &mut self,
@@ -1517,7 +1729,7 @@ impl IdmServerProxyReadTransaction<'_> {
         };
 
         match ident.access_scope() {
-            AccessScope::ReadOnly | AccessScope::ReadWrite => {
+            AccessScope::ReadOnly | AccessScope::ReadWrite | AccessScope::CredentialUpdate => {
                 warn!("Ident access scope is not synchronise");
                 return Err(OperationError::AccessDenied);
             }
@@ -1548,11 +1760,13 @@ mod tests {
     use kanidm_proto::scim_v1::*;
     use std::sync::Arc;
     use std::time::Duration;
+    use uuid::uuid;
 
     use super::{
-        GenerateScimSyncTokenEvent, ScimSyncFinaliseEvent, ScimSyncTerminateEvent, ScimSyncToken,
-        ScimSyncUpdateEvent,
+        order_sync_asserts, GenerateScimSyncTokenEvent, ScimSyncApplyReport, ScimSyncFinaliseEvent,
+        ScimSyncTerminateEvent, ScimSyncToken, ScimSyncUpdateEvent,
     };
+    use crate::server::scim::ScimBulkEvent;
 
     const TEST_CURRENT_TIME: u64 = 6000;
 
@@ -1919,7 +2133,7 @@ mod tests {
     async fn apply_phase_3_test(
         idms: &IdmServer,
         entries: Vec<ScimEntry>,
-    ) -> Result<(), OperationError> {
+    ) -> Result<ScimSyncApplyReport, OperationError> {
         let ct = Duration::from_secs(TEST_CURRENT_TIME);
         let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
         let (_sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
@@ -2917,6 +3131,165 @@ mod tests {
         assert!(idms_prox_write.commit().is_ok());
     }
 
+    #[test]
+    fn test_idm_scim_sync_assert_order() {
+        let outer = uuid!("51e7ff5e-2b4f-4b7e-9f57-6f3b3b0c6a01");
+        let inner = uuid!("51e7ff5e-2b4f-4b7e-9f57-6f3b3b0c6a02");
+        let user = uuid!("51e7ff5e-2b4f-4b7e-9f57-6f3b3b0c6a03");
+        let cycle_a = uuid!("51e7ff5e-2b4f-4b7e-9f57-6f3b3b0c6a04");
+        let cycle_b = uuid!("51e7ff5e-2b4f-4b7e-9f57-6f3b3b0c6a05");
+
+        let refers_to = |targets: &[Uuid]| {
+            ModifyList::new_list(
+                targets
+                    .iter()
+                    .map(|u| Modify::Present(Attribute::Member, Value::Refer(*u)))
+                    .collect(),
+            )
+        };
+
+        // Outer refers to inner which refers to user, but they are sent outermost first.
+        let asserts = vec![
+            (cycle_a, refers_to(&[cycle_b])),
+            (outer, refers_to(&[inner])),
+            (inner, refers_to(&[user])),
+            (cycle_b, refers_to(&[cycle_a])),
+            (user, ModifyList::new()),
+        ];
+
+        let phases: Vec<Vec<Uuid>> = order_sync_asserts(asserts)
+            .into_iter()
+            .map(|phase| phase.into_iter().map(|(u, _)| u).collect())
+            .collect();
+
+        assert_eq!(
+            phases,
+            vec![vec![user], vec![inner], vec![outer], vec![cycle_a, cycle_b]]
+        );
+    }
+
+    #[idm_test]
+    /// Groups nested across a single sync request must resolve to each other regardless of
+    /// the order they are sent in, and memberof must reflect the full depth of nesting.
+    async fn test_idm_scim_sync_nested_groups(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        let (_sync_uuid, ident) = test_scim_sync_apply_setup_ident(&mut idms_prox_write, ct);
+        let sse = ScimSyncUpdateEvent { ident };
+
+        let person_uuid = Uuid::new_v4();
+        let outer_uuid = Uuid::new_v4();
+        let middle_uuid = Uuid::new_v4();
+        let inner_uuid = Uuid::new_v4();
+
+        let person = ScimSyncPerson::builder(
+            person_uuid,
+            "cn=nest_person,ou=people,dc=test".to_string(),
+            "nest_person".to_string(),
+            "Nest Person".to_string(),
+        )
+        .build()
+        .try_into()
+        .unwrap();
+
+        // The outer group is sent first, and refers to groups that are only created later
+        // in the request. It also refers to an entry that is not part of this sync.
+        let outer = ScimSyncGroup::builder(
+            outer_uuid,
+            "cn=nest_outer,ou=groups,dc=test".to_string(),
+            "nest_outer".to_string(),
+        )
+        .set_members(
+            [
+                "cn=nest_middle,ou=groups,dc=test".to_string(),
+                "cn=not_synced,ou=groups,dc=test".to_string(),
+            ]
+            .into_iter(),
+        )
+        .build()
+        .try_into()
+        .unwrap();
+
+        let middle = ScimSyncGroup::builder(
+            middle_uuid,
+            "cn=nest_middle,ou=groups,dc=test".to_string(),
+            "nest_middle".to_string(),
+        )
+        .set_members(["cn=nest_inner,ou=groups,dc=test".to_string()].into_iter())
+        .build()
+        .try_into()
+        .unwrap();
+
+        let inner = ScimSyncGroup::builder(
+            inner_uuid,
+            "cn=nest_inner,ou=groups,dc=test".to_string(),
+            "nest_inner".to_string(),
+        )
+        .set_members(["cn=nest_person,ou=people,dc=test".to_string()].into_iter())
+        .build()
+        .try_into()
+        .unwrap();
+
+        let changes = ScimSyncRequest {
+            from_state: ScimSyncState::Refresh,
+            to_state: ScimSyncState::Active {
+                cookie: vec![1, 2, 3, 4],
+            },
+            entries: vec![outer, middle, inner, person],
+            retain: ScimSyncRetentionMode::Ignore,
+        };
+
+        let report = idms_prox_write
+            .scim_sync_apply(&sse, &changes, ct)
+            .expect("Failed to apply nested groups");
+
+        // Only the dangling reference is reported, and only against the entry that held it.
+        assert_eq!(report.dangling_references.len(), 1);
+        assert!(report
+            .dangling_references
+            .get(&outer_uuid)
+            .is_some_and(|refs| refs.contains("cn=not_synced,ou=groups,dc=test")));
+
+        assert!(idms_prox_write.commit().is_ok());
+
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+
+        let person = get_single_entry("nest_person", &mut idms_prox_write);
+        let person_mo = person
+            .get_ava_refer(Attribute::MemberOf)
+            .expect("No memberof on person");
+        assert!(person_mo.contains(&inner_uuid));
+        assert!(person_mo.contains(&middle_uuid));
+        assert!(person_mo.contains(&outer_uuid));
+
+        let person_dmo = person
+            .get_ava_refer(Attribute::DirectMemberOf)
+            .expect("No directmemberof on person");
+        assert!(person_dmo.contains(&inner_uuid));
+        assert!(!person_dmo.contains(&middle_uuid));
+        assert!(!person_dmo.contains(&outer_uuid));
+
+        let middle = get_single_entry("nest_middle", &mut idms_prox_write);
+        let middle_mo = middle
+            .get_ava_refer(Attribute::MemberOf)
+            .expect("No memberof on middle group");
+        assert!(middle_mo.contains(&outer_uuid));
+        assert!(!middle_mo.contains(&inner_uuid));
+
+        let outer = get_single_entry("nest_outer", &mut idms_prox_write);
+        assert_eq!(
+            outer
+                .get_ava_refer(Attribute::Member)
+                .map(|members| members.len()),
+            Some(1)
+        );
+
+        assert!(idms_prox_write.commit().is_ok());
+    }
+
     #[idm_test]
     async fn test_idm_scim_sync_yield_authority(
         idms: &IdmServer,
@@ -3289,6 +3662,330 @@ mod tests {
         assert!(idms_prox_write.commit().is_ok());
     }
 
+    fn idm_admin_ident(
+        idms_prox_write: &mut IdmServerProxyWriteTransaction<'_>,
+        _ct: Duration,
+    ) -> Result<Identity, OperationError> {
+        idms_prox_write
+            .qs_write
+            .internal_search_uuid(UUID_IDM_ADMIN)
+            .map(Identity::from_impersonate_entry_readwrite)
+    }
+
+    fn scim_bulk_request(
+        fail_on_errors: Option<u32>,
+        operations: serde_json::Value,
+    ) -> ScimBulkRequest {
+        ScimBulkRequest {
+            schemas: vec![SCIM_SCHEMA_BULK_REQUEST.to_string()],
+            fail_on_errors,
+            operations: serde_json::from_value(operations).expect("Invalid bulk operations"),
+        }
+    }
+
+    #[idm_test]
+    async fn test_idm_scim_bulk_id_resolution(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+
+        // The group references the member that is created earlier in the same request.
+        let request = scim_bulk_request(
+            None,
+            serde_json::json!([
+                {
+                    "method": "POST",
+                    "bulkId": "m1",
+                    "path": "/Entry",
+                    "data": {
+                        "class": ["group"],
+                        "name": "bulk_member"
+                    }
+                },
+                {
+                    "method": "POST",
+                    "bulkId": "g1",
+                    "path": "/Entry",
+                    "data": {
+                        "class": ["group"],
+                        "name": "bulk_group",
+                        "member": [{ "value": "bulkId:m1" }]
+                    }
+                },
+                {
+                    "method": "PUT",
+                    "path": "/scim/v1/Entry/bulkId:g1",
+                    "data": {
+                        "description": "Created in bulk"
+                    }
+                }
+            ]),
+        );
+
+        let event = ScimBulkEvent::try_from(request, 10).expect("Failed to parse bulk request");
+        let results = idms
+            .scim_bulk_apply(ct, event, idm_admin_ident)
+            .await
+            .expect("Bulk request failed");
+
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|result| result.outcome.is_ok()));
+        assert_eq!(results[0].bulk_id.as_deref(), Some("m1"));
+        assert_eq!(results[2].method, ScimBulkMethod::Put);
+
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+        let member_uuid = idms_prox_read
+            .qs_read
+            .name_to_uuid("bulk_member")
+            .expect("Member was not created");
+        let group_uuid = idms_prox_read
+            .qs_read
+            .name_to_uuid("bulk_group")
+            .expect("Group was not created");
+        let group = idms_prox_read
+            .qs_read
+            .internal_search_uuid(group_uuid)
+            .unwrap();
+
+        assert!(group
+            .get_ava_refer(Attribute::Member)
+            .is_some_and(|members| members.contains(&member_uuid)));
+        assert_eq!(
+            group.get_ava_single_utf8(Attribute::Description),
+            Some("Created in bulk")
+        );
+    }
+
+    #[idm_test]
+    async fn test_idm_scim_bulk_partial_failure(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+
+        let request = scim_bulk_request(
+            None,
+            serde_json::json!([
+                {
+                    "method": "POST",
+                    "path": "/Entry",
+                    "data": { "class": ["group"], "name": "bulk_ok_1" }
+                },
+                {
+                    // Rejected before anything is applied.
+                    "method": "DELETE",
+                    "path": "/Attribute/name"
+                },
+                {
+                    "method": "POST",
+                    "path": "/Entry",
+                    "data": { "class": ["group"], "name": "bulk_ok_2" }
+                },
+                {
+                    // Fails in the backend after the first create was applied.
+                    "method": "POST",
+                    "path": "/Entry",
+                    "data": { "class": ["group"], "name": "bulk_ok_1" }
+                },
+                {
+                    "method": "POST",
+                    "path": "/Entry",
+                    "data": { "class": ["group"], "name": "bulk_ok_3" }
+                }
+            ]),
+        );
+
+        let event = ScimBulkEvent::try_from(request, 10).expect("Failed to parse bulk request");
+        let results = idms
+            .scim_bulk_apply(ct, event, idm_admin_ident)
+            .await
+            .expect("Bulk request failed");
+
+        // Every operation is attempted, and each reports its own outcome.
+        assert_eq!(results.len(), 5);
+        assert!(results[0].outcome.is_ok());
+        assert!(matches!(
+            results[1].outcome,
+            Err(OperationError::SC0037BulkOperationInvalid)
+        ));
+        assert!(results[2].outcome.is_ok());
+        assert!(matches!(
+            results[3].outcome,
+            Err(OperationError::AttributeUniqueness(_))
+        ));
+        assert!(results[4].outcome.is_ok());
+
+        // The operations that succeeded are committed.
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+        assert!(idms_prox_read.qs_read.name_to_uuid("bulk_ok_1").is_ok());
+        assert!(idms_prox_read.qs_read.name_to_uuid("bulk_ok_2").is_ok());
+        assert!(idms_prox_read.qs_read.name_to_uuid("bulk_ok_3").is_ok());
+    }
+
+    #[idm_test]
+    async fn test_idm_scim_bulk_fail_on_errors(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+
+        let request = scim_bulk_request(
+            Some(2),
+            serde_json::json!([
+                {
+                    "method": "POST",
+                    "path": "/Entry",
+                    "data": { "class": ["group"], "name": "bulk_before" }
+                },
+                {
+                    "method": "DELETE",
+                    "path": "/Attribute/name"
+                },
+                {
+                    "method": "POST",
+                    "path": "/Entry",
+                    "data": { "class": ["group"], "name": "bulk_before" }
+                },
+                {
+                    "method": "POST",
+                    "path": "/Entry",
+                    "data": { "class": ["group"], "name": "bulk_after" }
+                }
+            ]),
+        );
+
+        let event = ScimBulkEvent::try_from(request, 10).expect("Failed to parse bulk request");
+        let results = idms
+            .scim_bulk_apply(ct, event, idm_admin_ident)
+            .await
+            .expect("Bulk request failed");
+
+        // Processing stops at the second error, the remaining operation is not reported.
+        assert_eq!(results.len(), 3);
+        assert!(results[0].outcome.is_ok());
+        assert!(matches!(
+            results[1].outcome,
+            Err(OperationError::SC0037BulkOperationInvalid)
+        ));
+        assert!(matches!(
+            results[2].outcome,
+            Err(OperationError::AttributeUniqueness(_))
+        ));
+
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+        assert!(idms_prox_read.qs_read.name_to_uuid("bulk_before").is_ok());
+        assert!(idms_prox_read.qs_read.name_to_uuid("bulk_after").is_err());
+    }
+
+    #[idm_test]
+    async fn test_idm_scim_bulk_not_authenticated(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+
+        let request = scim_bulk_request(
+            None,
+            serde_json::json!([
+                {
+                    "method": "POST",
+                    "path": "/Entry",
+                    "data": { "class": ["group"], "name": "bulk_anonymous" }
+                }
+            ]),
+        );
+
+        // The client is resolved within the write transaction, and without any credential
+        // nothing is applied.
+        let event = ScimBulkEvent::try_from(request, 10).expect("Failed to parse bulk request");
+        let result = idms
+            .scim_bulk(ct, ClientAuthInfo::from(Source::Internal), event)
+            .await;
+        assert!(matches!(result, Err(OperationError::NotAuthenticated)));
+
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+        assert!(idms_prox_read
+            .qs_read
+            .name_to_uuid("bulk_anonymous")
+            .is_err());
+    }
+
+    #[idm_test]
+    async fn test_idm_scim_bulk_id_unresolvable(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+
+        let request = scim_bulk_request(
+            None,
+            serde_json::json!([
+                {
+                    // References a bulkId that is never defined, so this create fails.
+                    "method": "POST",
+                    "bulkId": "bad",
+                    "path": "/Entry",
+                    "data": {
+                        "class": ["group"],
+                        "name": "bulk_bad",
+                        "member": [{ "value": "bulkId:unknown" }]
+                    }
+                },
+                {
+                    "method": "POST",
+                    "path": "/Entry",
+                    "data": {
+                        "class": ["group"],
+                        "name": "bulk_depends_on_bad",
+                        "member": [{ "value": "bulkId:bad" }]
+                    }
+                },
+                {
+                    "method": "DELETE",
+                    "path": "/Entry/bulkId:unknown"
+                },
+                {
+                    "method": "POST",
+                    "bulkId": "bad",
+                    "path": "/Entry",
+                    "data": { "class": ["group"], "name": "bulk_duplicate_id" }
+                }
+            ]),
+        );
+
+        let event = ScimBulkEvent::try_from(request, 10).expect("Failed to parse bulk request");
+        let results = idms
+            .scim_bulk_apply(ct, event, idm_admin_ident)
+            .await
+            .expect("Bulk request failed");
+
+        // None of these failures altered the transaction, so every operation is reported.
+        assert_eq!(results.len(), 4);
+        assert!(matches!(
+            results[0].outcome,
+            Err(OperationError::SC0035BulkIdUnresolvable)
+        ));
+        assert!(matches!(
+            results[1].outcome,
+            Err(OperationError::SC0035BulkIdUnresolvable)
+        ));
+        assert!(matches!(
+            results[2].outcome,
+            Err(OperationError::SC0035BulkIdUnresolvable)
+        ));
+        assert!(matches!(
+            results[3].outcome,
+            Err(OperationError::SC0036BulkIdDuplicate)
+        ));
+
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+        assert!(idms_prox_read
+            .qs_read
+            .name_to_uuid("bulk_depends_on_bad")
+            .is_err());
+    }
+
     const TEST_SYNC_SCIM_IPA_1: &str = r#"
 {
   "from_state": "Refresh",
@@ -1,44 +1,48 @@
 use super::ldap::{LdapBoundToken, LdapSession};
 use crate::credential::{softlock::CredSoftLock, Credential};
-use crate::idm::account::Account;
+use crate::idm::account::{Account, AccountValidity};
+use crate::idm::accountpolicy::ResolvedAccountPolicy;
 use crate::idm::application::{
     LdapApplications, LdapApplicationsReadTransaction, LdapApplicationsWriteTransaction,
 };
 use crate::idm::audit::AuditEvent;
 use crate::idm::authentication::{AuthState, PreValidatedTokenStatus};
 use crate::idm::authsession::{AuthSession, AuthSessionData};
+use crate::idm::breachlist::PasswordBreachList;
 use crate::idm::credupdatesession::CredentialUpdateSessionMutex;
 use crate::idm::delayed::{
-    AuthSessionRecord, BackupCodeRemoval, DelayedAction, PasswordUpgrade, UnixPasswordUpgrade,
-    WebauthnCounterIncrement,
+    AuthSessionRecord, BackupCodeRemoval, CredentialLockout, DelayedAction, PasswordUpgrade,
+    UnixPasswordUpgrade, WebauthnCounterIncrement,
 };
 use crate::idm::event::{
     AuthEvent, AuthEventStep, AuthResult, CredentialStatusEvent, LdapAuthEvent, LdapTokenAuthEvent,
     RadiusAuthTokenEvent, RegenerateRadiusSecretEvent, UnixGroupTokenEvent,
     UnixPasswordChangeEvent, UnixUserAuthEvent, UnixUserTokenEvent,
 };
-use crate::idm::group::{Group, Unix};
+use crate::idm::group::{load_account_policy, Group, Unix};
 use crate::idm::oauth2::{
-    Oauth2ResourceServers, Oauth2ResourceServersReadTransaction,
-    Oauth2ResourceServersWriteTransaction,
+    prune_oauth2_pushed_requests, Oauth2DeviceCode, Oauth2PushedRequest, Oauth2ResourceServers,
+    Oauth2ResourceServersReadTransaction, Oauth2ResourceServersWriteTransaction,
 };
 use crate::idm::oauth2_client::OAuth2ClientProvider;
 use crate::idm::radius::RadiusAccount;
 use crate::idm::scim::SyncAccount;
 use crate::idm::serviceaccount::ServiceAccount;
+use crate::metrics::METRICS;
 use crate::prelude::*;
 use crate::server::keys::KeyProvidersTransaction;
+use crate::server::security_event::{SecurityEvent, SecurityEventKind, SecurityEventOutcome};
 use crate::server::DomainInfo;
 use crate::utils::{password_from_random, readable_password_from_random, uuid_from_duration, Sid};
-use crate::value::{Session, SessionState};
+use crate::value::{Acr, Session, SessionState};
 use compact_jwt::{Jwk, JwsCompact};
 use concread::bptree::{BptreeMap, BptreeMapReadTxn, BptreeMapWriteTxn};
 use concread::cowcell::CowCellReadTxn;
 use concread::hashmap::{HashMap, HashMapReadTxn, HashMapWriteTxn};
 use kanidm_lib_crypto::CryptoPolicy;
 use kanidm_proto::internal::{
-    ApiToken, CredentialStatus, PasswordFeedback, RadiusAuthToken, ScimSyncToken, UatPurpose,
-    UserAuthToken,
+    ApiToken, ApiTokenOperationScope, CredentialStatus, PasswordFeedback, RadiusAuthToken,
+    ScimSyncToken, UatPurpose, UserAuthToken,
 };
 use kanidm_proto::v1::{UnixGroupToken, UnixUserToken};
 use rand::prelude::*;
@@ -51,10 +55,12 @@ use tokio::sync::{Mutex, Semaphore};
 use tracing::trace;
 use url::Url;
 use webauthn_rs::prelude::{Webauthn, WebauthnBuilder};
-use zxcvbn::{zxcvbn, Score};
+use zxcvbn::zxcvbn;
 
 #[cfg(test)]
 use crate::idm::event::PasswordChangeEvent;
+#[cfg(test)]
+use crate::server::security_event::SecurityEventQueue;
 
 pub(crate) type AuthSessionMutex = Arc<Mutex<AuthSession>>;
 pub(crate) type CredSoftLockMutex = Arc<Mutex<CredSoftLock>>;
@@ -75,11 +81,17 @@ pub struct IdmServer {
     qs: QueryServer,
     /// The configured crypto policy for the IDM server. Later this could be transactional and loaded from the db similar to access. But today it's just to allow dynamic pbkdf2rounds
     crypto_policy: CryptoPolicy,
+    /// A local breached password list, checked when account policy requests it.
+    password_breach_list: Option<PasswordBreachList>,
     async_tx: UnboundedSender<DelayedAction>,
     audit_tx: UnboundedSender<AuditEvent>,
     /// [Webauthn] verifier/config
     webauthn: Webauthn,
     oauth2rs: Arc<Oauth2ResourceServers>,
+    /// Pushed authorisation requests that are awaiting the user agent.
+    oauth2_pushed_requests: BptreeMap<Uuid, Oauth2PushedRequest>,
+    /// Device authorisation grants that are awaiting the user, keyed by device code.
+    oauth2_device_codes: BptreeMap<String, Oauth2DeviceCode>,
     applications: Arc<LdapApplications>,
 
     /// OAuth2ClientProviders
@@ -110,12 +122,14 @@ pub struct IdmServerCredUpdateTransaction<'a> {
     pub(crate) webauthn: &'a Webauthn,
     pub(crate) cred_update_sessions: BptreeMapReadTxn<'a, Uuid, CredentialUpdateSessionMutex>,
     pub(crate) crypto_policy: &'a CryptoPolicy,
+    pub(crate) password_breach_list: Option<&'a PasswordBreachList>,
 }
 
 /// This contains read-only methods, like getting users, groups and other structured content.
 pub struct IdmServerProxyReadTransaction<'a> {
     pub qs_read: QueryServerReadTransaction<'a>,
     pub(crate) oauth2rs: Oauth2ResourceServersReadTransaction,
+    pub(crate) oauth2_pushed_requests: &'a BptreeMap<Uuid, Oauth2PushedRequest>,
 }
 
 pub struct IdmServerProxyWriteTransaction<'a> {
@@ -126,12 +140,14 @@ pub struct IdmServerProxyWriteTransaction<'a> {
     pub(crate) cred_update_sessions: BptreeMapWriteTxn<'a, Uuid, CredentialUpdateSessionMutex>,
     pub(crate) sid: Sid,
     crypto_policy: &'a CryptoPolicy,
+    pub(crate) password_breach_list: Option<&'a PasswordBreachList>,
     webauthn: &'a Webauthn,
     pub(crate) oauth2rs: Oauth2ResourceServersWriteTransaction<'a>,
     pub(crate) applications: LdapApplicationsWriteTransaction<'a>,
 
     pub(crate) origin: &'a Url,
     pub(crate) oauth2_client_providers: HashMapWriteTxn<'a, Uuid, OAuth2ClientProvider>,
+    pub(crate) oauth2_device_codes: &'a BptreeMap<String, Oauth2DeviceCode>,
 }
 
 pub struct IdmServerDelayed {
@@ -147,6 +163,7 @@ impl IdmServer {
         qs: QueryServer,
         origin: &Url,
         is_integration_test: bool,
+        password_breach_list: Option<PasswordBreachList>,
         current_time: Duration,
     ) -> Result<(IdmServer, IdmServerDelayed, IdmServerAudit), OperationError> {
         let crypto_policy = if cfg!(test) || is_integration_test {
@@ -218,10 +235,13 @@ impl IdmServer {
             cred_update_sessions: BptreeMap::new(),
             qs,
             crypto_policy,
+            password_breach_list,
             async_tx,
             audit_tx,
             webauthn,
             oauth2rs: Arc::new(oauth2rs),
+            oauth2_pushed_requests: BptreeMap::new(),
+            oauth2_device_codes: BptreeMap::new(),
             applications: Arc::new(applications),
             origin: origin.clone(),
             oauth2_client_providers: HashMap::new(),
@@ -270,6 +290,14 @@ impl IdmServer {
         self.qs.d_info.read()
     }
 
+    /// Remove the pushed authorisation requests that expired without being used.
+    #[instrument(level = "debug", skip_all)]
+    pub fn expire_oauth2_pushed_requests(&self, ct: Duration) {
+        let mut pushed_requests = self.oauth2_pushed_requests.write();
+        prune_oauth2_pushed_requests(&mut pushed_requests, ct);
+        pushed_requests.commit();
+    }
+
     /// Read from the database, in a transaction.
     #[instrument(level = "debug", skip_all)]
     pub async fn proxy_read(&self) -> Result<IdmServerProxyReadTransaction<'_>, OperationError> {
@@ -277,6 +305,7 @@ impl IdmServer {
         Ok(IdmServerProxyReadTransaction {
             qs_read,
             oauth2rs: self.oauth2rs.read(),
+            oauth2_pushed_requests: &self.oauth2_pushed_requests,
             // async_tx: self.async_tx.clone(),
         })
     }
@@ -297,14 +326,21 @@ impl IdmServer {
             qs_write,
             sid,
             crypto_policy: &self.crypto_policy,
+            password_breach_list: self.password_breach_list.as_ref(),
             webauthn: &self.webauthn,
             oauth2rs: self.oauth2rs.write(),
             applications: self.applications.write(),
             origin: &self.origin,
             oauth2_client_providers: self.oauth2_client_providers.write(),
+            oauth2_device_codes: &self.oauth2_device_codes,
         })
     }
 
+    #[cfg(test)]
+    pub(crate) fn security_events(&self) -> &Arc<SecurityEventQueue> {
+        self.qs.security_events()
+    }
+
     pub async fn cred_update_transaction(
         &self,
     ) -> Result<IdmServerCredUpdateTransaction<'_>, OperationError> {
@@ -315,6 +351,7 @@ impl IdmServer {
             webauthn: &self.webauthn,
             cred_update_sessions: self.cred_update_sessions.read(),
             crypto_policy: &self.crypto_policy,
+            password_breach_list: self.password_breach_list.as_ref(),
         })
     }
 
@@ -387,7 +424,12 @@ impl IdmServerDelayed {
     pub async fn recv_many(&mut self, buffer: &mut Vec<DelayedAction>) -> usize {
         debug_assert!(buffer.is_empty());
         let limit = buffer.capacity();
-        self.async_rx.recv_many(buffer, limit).await
+        let added = self.async_rx.recv_many(buffer, limit).await;
+        // The received batch is still waiting to be applied, so it counts towards the depth.
+        METRICS
+            .delayed_action_queue_depth
+            .set((added + self.async_rx.len()) as u64);
+        added
     }
 }
 
@@ -401,6 +443,29 @@ pub trait IdmServerTransaction<'a> {
 
     fn get_qs_txn(&mut self) -> &mut Self::QsTransactionType;
 
+    /// Determine if an account may be used given its `validity` at the current time. Every
+    /// authentication path must check this, so that an account outside of its validity
+    /// window is refused consistently, and the refusal is recorded as a security event.
+    fn check_account_validity(
+        &mut self,
+        account: Uuid,
+        validity: AccountValidity,
+        ct: Duration,
+    ) -> bool {
+        match SecurityEvent::account_validity(account, validity, ct) {
+            Some(event) => {
+                security_info!(
+                    %account,
+                    ?validity,
+                    "Account is outside of its validity window, not allowing to proceed"
+                );
+                self.get_qs_txn().submit_security_event(event);
+                false
+            }
+            None => true,
+        }
+    }
+
     /// This is the preferred method to transform and securely verify a token into
     /// an identity that can be used for operations and access enforcement. This
     /// function *is* aware of the various classes of tokens that may exist, and can
@@ -421,6 +486,8 @@ pub trait IdmServerTransaction<'a> {
             bearer_token,
             basic_authz: _,
             pre_validated_token,
+            operation_scope,
+            user_agent: _,
         } = client_auth_info;
 
         // If there is a pre-validated UAT, use that. This pre-validation is an "optimisation"
@@ -448,7 +515,7 @@ pub trait IdmServerTransaction<'a> {
                 match self.validate_and_parse_token_to_identity_token(&token, ct)? {
                     Token::UserAuthToken(uat) => self.process_uat_to_identity(&uat, ct, source),
                     Token::ApiToken(apit, entry) => {
-                        self.process_apit_to_identity(&apit, source, entry, ct)
+                        self.process_apit_to_identity(&apit, source, entry, operation_scope, ct)
                     }
                 }
             }
@@ -521,6 +588,41 @@ pub trait IdmServerTransaction<'a> {
         }
     }
 
+    /// This function is not used in authentication flows - it is a reflector of the
+    /// api token that was presented, allowing the holder to inspect properties of the
+    /// token such as the operation scopes it is restricted to.
+    #[instrument(level = "debug", skip_all)]
+    fn validate_client_auth_info_to_api_token(
+        &mut self,
+        client_auth_info: &ClientAuthInfo,
+        ct: Duration,
+    ) -> Result<ApiToken, OperationError> {
+        let token = client_auth_info.bearer_token.as_ref().ok_or_else(|| {
+            debug!("No bearer token was supplied");
+            OperationError::NotAuthenticated
+        })?;
+
+        match self.validate_and_parse_token_to_identity_token(token, ct)? {
+            Token::ApiToken(apit, entry) => {
+                let validity = Account::entry_validity(&entry, ct);
+                if !self.check_account_validity(entry.get_uuid(), validity, ct) {
+                    return Err(OperationError::SessionExpired);
+                }
+
+                if ServiceAccount::check_api_token_valid(ct, &apit, &entry) {
+                    Ok(apit)
+                } else {
+                    // Check_api token logs this.
+                    Err(OperationError::SessionExpired)
+                }
+            }
+            Token::UserAuthToken(_) => {
+                debug!("Unable to process non api token");
+                Err(OperationError::NotAuthenticated)
+            }
+        }
+    }
+
     fn validate_and_parse_token_to_identity_token(
         &mut self,
         jwsu: &JwsCompact,
@@ -621,6 +723,7 @@ pub trait IdmServerTransaction<'a> {
                 expiry: api_token_internal.expiry,
                 issued_at: api_token_internal.issued_at,
                 purpose,
+                operation_scopes: api_token_internal.operation_scopes.clone(),
             };
 
             if let Some(expiry) = apit.expiry {
@@ -650,18 +753,8 @@ pub trait IdmServerTransaction<'a> {
             e
         })?;
 
-        let within_valid_window = Account::check_within_valid_time(
-            ct,
-            entry
-                .get_ava_single_datetime(Attribute::AccountValidFrom)
-                .as_ref(),
-            entry
-                .get_ava_single_datetime(Attribute::AccountExpire)
-                .as_ref(),
-        );
-
-        if !within_valid_window {
-            security_info!("Account has expired or is not yet valid, not allowing to proceed");
+        let validity = Account::entry_validity(&entry, ct);
+        if !self.check_account_validity(uuid, validity, ct) {
             return Ok(None);
         }
 
@@ -766,6 +859,11 @@ pub trait IdmServerTransaction<'a> {
                 }
             })?;
 
+        let validity = Account::entry_validity(&entry, ct);
+        if !self.check_account_validity(uat.uuid, validity, ct) {
+            return Err(OperationError::SessionExpired);
+        }
+
         let valid = Account::check_user_auth_token_valid(ct, uat, &entry);
 
         if !valid {
@@ -787,6 +885,7 @@ pub trait IdmServerTransaction<'a> {
                     AccessScope::ReadOnly
                 }
             }
+            UatPurpose::CredentialUpdate => AccessScope::CredentialUpdate,
         };
 
         let mut limits = Limits::default();
@@ -816,6 +915,22 @@ pub trait IdmServerTransaction<'a> {
         trace!(claims = ?entry.get_ava_set("claim"), "Applied claims");
         */
 
+        // Tokens issued before the acr was recorded fall back to the type of the
+        // credential that established the session, and when it was established.
+        let acr = uat
+            .acr
+            .as_deref()
+            .and_then(|acr| acr.parse::<Acr>().ok())
+            .zip(uat.acr_verified_at)
+            .or_else(|| {
+                entry
+                    .get_ava_as_session_map(Attribute::UserAuthTokenSession)
+                    .and_then(|sessions| sessions.get(&uat.session_id))
+                    .and_then(|session| {
+                        Acr::from_auth_type(session.type_).map(|acr| (acr, session.issued_at))
+                    })
+            });
+
         Ok(Identity::new(
             IdentType::User(IdentUser { entry }),
             source,
@@ -825,7 +940,8 @@ pub trait IdmServerTransaction<'a> {
             // This strictly is the "last_verified_at" time but due to the current
             // design of uat, issued_at is the same as last verification.
             Some(uat.issued_at),
-        ))
+        )
+        .with_acr(acr))
     }
 
     #[instrument(level = "debug", skip_all)]
@@ -834,8 +950,14 @@ pub trait IdmServerTransaction<'a> {
         apit: &ApiToken,
         source: Source,
         entry: Arc<EntrySealedCommitted>,
+        operation_scope: Option<ApiTokenOperationScope>,
         ct: Duration,
     ) -> Result<Identity, OperationError> {
+        let validity = Account::entry_validity(&entry, ct);
+        if !self.check_account_validity(apit.account_id, validity, ct) {
+            return Err(OperationError::SessionExpired);
+        }
+
         let valid = ServiceAccount::check_api_token_valid(ct, apit, &entry);
 
         if !valid {
@@ -843,6 +965,39 @@ pub trait IdmServerTransaction<'a> {
             return Err(OperationError::SessionExpired);
         }
 
+        // A token restricted to operation scopes may only be used for operations that
+        // are known to be within those scopes, regardless of what access controls
+        // would otherwise permit the account to do.
+        if !apit.operation_scopes.is_empty() {
+            let in_scope = operation_scope.is_some_and(|required| {
+                apit.operation_scopes
+                    .iter()
+                    .any(|op_scope| op_scope.grants(required))
+            });
+
+            if !in_scope {
+                security_info!(
+                    token_id = %apit.token_id,
+                    ?operation_scope,
+                    "Api token is not scoped for this operation, not allowing to proceed"
+                );
+                return Err(OperationError::AU0010ApiTokenOperationNotInScope);
+            }
+        }
+
+        let account_policy = load_account_policy(&entry, self.get_qs_txn())?;
+        if let Some(max_age) = account_policy.credential_max_age() {
+            let max_issued_at =
+                OffsetDateTime::UNIX_EPOCH + ct - Duration::from_secs(max_age as u64);
+            if apit.issued_at <= max_issued_at {
+                security_info!(
+                    token_id = %apit.token_id,
+                    "Api token has exceeded the maximum credential age, not allowing to proceed"
+                );
+                return Err(OperationError::AU0009ApiTokenMaxAgeExceeded);
+            }
+        }
+
         let scope = (&apit.purpose).into();
         // While we did just verify the token, that's different to an interactive
         // proof of presence like a human would provide.
@@ -918,7 +1073,7 @@ pub trait IdmServerTransaction<'a> {
             Account::try_from_entry_with_policy(entry.as_ref(), self.get_qs_txn())?;
 
         // Is the account in it's valid window?
-        if !account.is_within_valid_time(ct) {
+        if !self.check_account_validity(account.uuid, account.validity(ct), ct) {
             // Nope, expired
             return Err(OperationError::SessionExpired);
         };
@@ -982,7 +1137,7 @@ pub trait IdmServerTransaction<'a> {
             Account::try_from_entry_with_policy(entry.as_ref(), self.get_qs_txn())?;
 
         // Is the account in it's valid window?
-        if !account.is_within_valid_time(ct) {
+        if !self.check_account_validity(account.uuid, account.validity(ct), ct) {
             // Nope, expired
             return Err(OperationError::SessionExpired);
         };
@@ -1012,8 +1167,7 @@ pub trait IdmServerTransaction<'a> {
         let (account, account_policy) =
             Account::try_from_entry_with_policy(entry.as_ref(), self.get_qs_txn())?;
 
-        if !account.is_within_valid_time(ct) {
-            info!("Account is expired or not yet valid.");
+        if !self.check_account_validity(account.uuid, account.validity(ct), ct) {
             return Err(OperationError::SessionExpired);
         }
 
@@ -1081,7 +1235,8 @@ pub trait IdmServerTransaction<'a> {
                         e
                     })?;
 
-                self.process_apit_to_identity(apit, source, entry, ct)
+                // Ldap operations are not within any operation scope.
+                self.process_apit_to_identity(apit, source, entry, None, ct)
             }
         }
     }
@@ -1266,6 +1421,10 @@ impl IdmServerAuthTransaction<'_> {
                             slock_ref
                         });
 
+                // The auth session denies an account outside of its validity window, but
+                // the refusal is recorded here.
+                self.check_account_validity(account.uuid, account.validity(ct), ct);
+
                 // Does the account have any auth trusts?
                 let oauth2_client_provider =
                     account.oauth2_client_provider().and_then(|trust_provider| {
@@ -1330,6 +1489,12 @@ impl IdmServerAuthTransaction<'_> {
                 let auth_result = auth_session.start_session(&mech.mech);
 
                 let is_valid = match auth_session.get_credential_uuid()? {
+                    Some(cred_uuid)
+                        if auth_session.account().is_credential_locked(cred_uuid, ct) =>
+                    {
+                        security_info!(%cred_uuid, "Credential is locked by account policy");
+                        false
+                    }
                     Some(cred_uuid) => {
                         // From the auth_session, determine if the current account
                         // credential that we are using has become softlocked or not.
@@ -1384,7 +1549,9 @@ impl IdmServerAuthTransaction<'_> {
 
                 let mut auth_session = auth_session_ref.lock().await;
 
-                let maybe_slock_ref = match auth_session.get_credential_uuid()? {
+                let maybe_cred_uuid = auth_session.get_credential_uuid()?;
+
+                let maybe_slock_ref = match maybe_cred_uuid {
                     Some(cred_uuid) => {
                         let softlock_read = self.softlocks.read();
                         softlock_read.get(&cred_uuid).cloned()
@@ -1400,7 +1567,13 @@ impl IdmServerAuthTransaction<'_> {
                     None
                 };
 
-                let is_valid = if let Some(ref mut slock) = maybe_slock {
+                let is_locked = maybe_cred_uuid.is_some_and(|cred_uuid| {
+                    auth_session.account().is_credential_locked(cred_uuid, ct)
+                });
+
+                let is_valid = if is_locked {
+                    false
+                } else if let Some(ref mut slock) = maybe_slock {
                     // Apply the current time.
                     slock.apply_time_step(ct, None);
                     // Now check the results
@@ -1410,6 +1583,12 @@ impl IdmServerAuthTransaction<'_> {
                     true
                 };
 
+                // If the account policy limits the number of failures, we persist a
+                // lockout once the softlock has counted that many failures.
+                let target_uuid = auth_session.account().uuid;
+                let lockout_policy =
+                    maybe_cred_uuid.zip(auth_session.account_policy().auth_lockout());
+
                 if is_valid {
                     // Process the credentials here as required.
                     // Basically throw them at the auth_session and see what
@@ -1430,6 +1609,34 @@ impl IdmServerAuthTransaction<'_> {
                                 // Update it.
                                 if let Some(ref mut slock) = maybe_slock {
                                     slock.record_failure(ct);
+
+                                    if let Some((cred_id, (max_attempts, duration))) =
+                                        lockout_policy
+                                    {
+                                        // Every run of max attempts locks the credential, so
+                                        // that failures after an unlock lock it again.
+                                        if slock.failure_count() % max_attempts as usize == 0 {
+                                            let unlock_at = OffsetDateTime::UNIX_EPOCH
+                                                + ct
+                                                + Duration::from_secs(duration as u64);
+                                            security_info!(
+                                                %cred_id,
+                                                %unlock_at,
+                                                "Credential exceeded maximum attempts, locking"
+                                            );
+                                            let lockout = CredentialLockout {
+                                                target_uuid,
+                                                cred_id,
+                                                unlock_at,
+                                            };
+                                            if let Err(err) = self
+                                                .async_tx
+                                                .send(DelayedAction::CredentialLockout(lockout))
+                                            {
+                                                error!(?err, "Unable to queue credential lockout");
+                                            }
+                                        }
+                                    }
                                 }
                             };
                         })
@@ -1438,6 +1645,25 @@ impl IdmServerAuthTransaction<'_> {
                     trace!("lock step cred");
                     auth_session.end_session("Account is temporarily locked")
                 }
+                .inspect(|aus| {
+                    let outcome = match aus {
+                        AuthState::Success(..) => {
+                            METRICS.auth_success.inc();
+                            SecurityEventOutcome::Success
+                        }
+                        AuthState::Denied(_) => {
+                            METRICS.auth_failure.inc();
+                            SecurityEventOutcome::Failure
+                        }
+                        _ => return,
+                    };
+                    self.qs_read.submit_security_event(
+                        SecurityEvent::new(SecurityEventKind::Authentication, outcome, ct)
+                            .with_actor(target_uuid)
+                            .with_targets([target_uuid])
+                            .with_source(auth_session.source()),
+                    );
+                })
                 .map(|aus| AuthResult {
                     sessionid: creds.sessionid,
                     state: aus,
@@ -1463,8 +1689,7 @@ impl IdmServerAuthTransaction<'_> {
         let (account, acp) =
             Account::try_from_entry_with_policy(entry.as_ref(), &mut self.qs_read)?;
 
-        if !account.is_within_valid_time(ct) {
-            security_info!("Account is expired or not yet valid.");
+        if !self.check_account_validity(account.uuid, account.validity(ct), ct) {
             return Ok(None);
         }
 
@@ -1561,7 +1786,7 @@ impl IdmServerAuthTransaction<'_> {
         Ok(self
             .auth_with_unix_pass(uae.target, &uae.cleartext, ct)
             .await?
-            .and_then(|acc| acc.to_unixusertoken(ct).ok()))
+            .and_then(|acc| acc.to_unixusertoken(ct, self.qs_read.domain_info()).ok()))
     }
 
     pub async fn auth_ldap(
@@ -1578,8 +1803,7 @@ impl IdmServerAuthTransaction<'_> {
             let account = Account::try_from_entry_ro(account_entry.as_ref(), &mut self.qs_read)?;
 
             // Check if the anon account has been locked.
-            if !account.is_within_valid_time(ct) {
-                security_info!("Account is not within valid time period");
+            if !self.check_account_validity(account.uuid, account.validity(ct), ct) {
                 return Ok(None);
             }
 
@@ -1596,6 +1820,7 @@ impl IdmServerAuthTransaction<'_> {
                 session_id,
                 spn: account.spn().into(),
                 effective_session: LdapSession::UnixBind(UUID_ANONYMOUS),
+                source_ip: None,
             }))
         } else {
             if !self.qs_read.d_info.d_ldap_allow_unix_pw_bind {
@@ -1621,6 +1846,7 @@ impl IdmServerAuthTransaction<'_> {
                         spn: account.spn().into(),
                         session_id,
                         effective_session: LdapSession::UnixBind(account.uuid),
+                        source_ip: None,
                     }))
                 }
                 None => Ok(None),
@@ -1635,14 +1861,26 @@ impl IdmServerAuthTransaction<'_> {
     ) -> Result<Option<LdapBoundToken>, OperationError> {
         match self.validate_and_parse_token_to_identity_token(&lae.token, ct)? {
             Token::UserAuthToken(uat) => {
+                let entry = self.qs_read.internal_search_uuid(uat.uuid)?;
+                let validity = Account::entry_validity(&entry, ct);
+                if !self.check_account_validity(uat.uuid, validity, ct) {
+                    return Ok(None);
+                }
+
                 let spn = uat.spn.clone();
                 Ok(Some(LdapBoundToken {
                     session_id: uat.session_id,
                     spn,
                     effective_session: LdapSession::UserAuthToken(uat),
+                    source_ip: None,
                 }))
             }
             Token::ApiToken(apit, entry) => {
+                let validity = Account::entry_validity(&entry, ct);
+                if !self.check_account_validity(apit.account_id, validity, ct) {
+                    return Ok(None);
+                }
+
                 let spn = entry
                     .get_ava_single_proto_string(Attribute::Spn)
                     .ok_or_else(|| OperationError::MissingAttribute(Attribute::Spn))?;
@@ -1651,6 +1889,7 @@ impl IdmServerAuthTransaction<'_> {
                     session_id: apit.token_id,
                     spn,
                     effective_session: LdapSession::ApiToken(apit),
+                    source_ip: None,
                 }))
             }
         }
@@ -1726,6 +1965,9 @@ impl IdmServerProxyReadTransaction<'_> {
                 e
             })?;
 
+        // The token itself is refused if the account is not valid.
+        self.check_account_validity(account.uuid, account.validity(ct), ct);
+
         account.to_radiusauthtoken(ct)
     }
 
@@ -1743,7 +1985,7 @@ impl IdmServerProxyReadTransaction<'_> {
                 e
             })?;
 
-        account.to_unixusertoken(ct)
+        account.to_unixusertoken(ct, self.qs_read.domain_info())
     }
 
     pub fn get_unixgrouptoken(
@@ -1801,16 +2043,17 @@ impl IdmServerProxyWriteTransaction<'_> {
     fn check_password_quality(
         &mut self,
         cleartext: &str,
+        resolved_account_policy: &ResolvedAccountPolicy,
         related_inputs: &[&str],
     ) -> Result<(), OperationError> {
-        // password strength and badlisting is always global, rather than per-pw-policy.
-        // pw-policy as check on the account is about requirements for mfa for example.
-        //
+        // The badlist is always global, but length, strength and the breach list check
+        // are resolved from the account policy of the groups the account is a member of.
 
-        // is the password at least 10 char?
-        if cleartext.len() < PW_MIN_LENGTH as usize {
+        // is the password long enough?
+        let pw_min_length = resolved_account_policy.pw_min_length();
+        if cleartext.len() < pw_min_length as usize {
             return Err(OperationError::PasswordQuality(vec![
-                PasswordFeedback::TooShort(PW_MIN_LENGTH),
+                PasswordFeedback::TooShort(pw_min_length),
             ]));
         }
 
@@ -1819,7 +2062,8 @@ impl IdmServerProxyWriteTransaction<'_> {
         let entropy = zxcvbn(cleartext, related_inputs);
 
         // Unix PW's are a single factor, so we enforce good pws
-        if entropy.score() < Score::Four {
+        let pw_min_score = resolved_account_policy.pw_min_score();
+        if u32::from(u8::from(entropy.score())) < pw_min_score {
             // The password is too week as per:
             // https://docs.rs/zxcvbn/2.0.0/zxcvbn/struct.Entropy.html
             let feedback: zxcvbn::feedback::Feedback = entropy
@@ -1832,10 +2076,8 @@ impl IdmServerProxyWriteTransaction<'_> {
 
             security_info!(?feedback, "pw quality feedback");
 
-            // return Err(OperationError::PasswordTooWeak(feedback))
-            // return Err(OperationError::PasswordTooWeak);
             return Err(OperationError::PasswordQuality(vec![
-                PasswordFeedback::BadListed,
+                PasswordFeedback::TooWeak(pw_min_score),
             ]));
         }
 
@@ -1848,12 +2090,28 @@ impl IdmServerProxyWriteTransaction<'_> {
             .contains(&cleartext.to_lowercase())
         {
             security_info!("Password found in badlist, rejecting");
-            Err(OperationError::PasswordQuality(vec![
+            return Err(OperationError::PasswordQuality(vec![
                 PasswordFeedback::BadListed,
-            ]))
-        } else {
-            Ok(())
+            ]));
         }
+
+        if resolved_account_policy.pw_breach_check() {
+            match self.password_breach_list {
+                Some(breach_list) => {
+                    if breach_list.contains(cleartext)? {
+                        security_info!("Password found in breach list, rejecting");
+                        return Err(OperationError::PasswordQuality(vec![
+                            PasswordFeedback::Breached,
+                        ]));
+                    }
+                }
+                None => {
+                    warn!("Account policy requires a password breach check, but no password breach list is configured");
+                }
+            }
+        }
+
+        Ok(())
     }
 
     pub(crate) fn target_to_account(&mut self, target: Uuid) -> Result<Account, OperationError> {
@@ -1939,12 +2197,12 @@ impl IdmServerProxyWriteTransaction<'_> {
         pce: &UnixPasswordChangeEvent,
     ) -> Result<(), OperationError> {
         // Get the account
-        let account = self
+        let (account, resolved_account_policy) = self
             .qs_write
             .internal_search_uuid(pce.target)
             .and_then(|account_entry| {
                 // Assert the account is unix and valid.
-                Account::try_from_entry_rw(&account_entry, &mut self.qs_write)
+                Account::try_from_entry_with_policy(&account_entry, &mut self.qs_write)
             })
             .map_err(|e| {
                 admin_error!("Failed to start set unix account password {:?}", e);
@@ -2002,11 +2260,15 @@ impl IdmServerProxyWriteTransaction<'_> {
         // If we got here, then pre-apply succeeded, and that means access control
         // passed. Now we can do the extra checks.
 
-        self.check_password_quality(pce.cleartext.as_str(), account.related_inputs().as_slice())
-            .map_err(|e| {
-                admin_error!(?e, "Failed to checked password quality");
-                e
-            })?;
+        self.check_password_quality(
+            pce.cleartext.as_str(),
+            &resolved_account_policy,
+            account.related_inputs().as_slice(),
+        )
+        .map_err(|e| {
+            admin_error!(?e, "Failed to checked password quality");
+            e
+        })?;
 
         // And actually really apply it now.
         self.qs_write.modify_apply(mp).map_err(|e| {
@@ -2207,6 +2469,7 @@ impl IdmServerProxyWriteTransaction<'_> {
     pub(crate) fn process_webauthncounterinc(
         &mut self,
         wci: &WebauthnCounterIncrement,
+        ct: Duration,
     ) -> Result<(), OperationError> {
         info!(session_id = %wci.target_uuid, "Processing webauthn counter increment");
 
@@ -2214,7 +2477,7 @@ impl IdmServerProxyWriteTransaction<'_> {
 
         // Generate an optional mod and then attempt to apply it.
         let opt_modlist = account
-            .gen_webauthn_counter_mod(&wci.auth_result)
+            .gen_webauthn_counter_mod(&wci.auth_result, ct)
             .map_err(|e| {
                 admin_error!("Unable to generate webauthn counter mod {:?}", e);
                 e
@@ -2282,6 +2545,9 @@ impl IdmServerProxyWriteTransaction<'_> {
                 scope: asr.scope,
                 type_: asr.type_,
                 ext_metadata: Default::default(),
+                // Where was this session established from?
+                source_ip: asr.source_ip,
+                user_agent: asr.user_agent.clone(),
             },
         );
 
@@ -2302,18 +2568,49 @@ impl IdmServerProxyWriteTransaction<'_> {
         // Done!
     }
 
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) fn process_credentiallockout(
+        &mut self,
+        cl: &CredentialLockout,
+    ) -> Result<(), OperationError> {
+        info!(
+            target_uuid = %cl.target_uuid,
+            cred_id = %cl.cred_id,
+            unlock_at = %cl.unlock_at,
+            "Persisting credential lockout"
+        );
+
+        let modlist = ModifyList::new_append(
+            Attribute::CredentialLockout,
+            Value::CredentialLockout(cl.cred_id, cl.unlock_at),
+        );
+
+        self.qs_write
+            .internal_modify(
+                &filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(cl.target_uuid))),
+                &modlist,
+            )
+            .map_err(|e| {
+                admin_error!("Failed to persist credential lockout {:?}", e);
+                e
+            })
+    }
+
     #[instrument(level = "debug", skip_all)]
     pub fn process_delayedaction(
         &mut self,
         da: &DelayedAction,
-        _ct: Duration,
+        ct: Duration,
     ) -> Result<(), OperationError> {
         match da {
             DelayedAction::PwUpgrade(pwu) => self.process_pwupgrade(pwu),
             DelayedAction::UnixPwUpgrade(upwu) => self.process_unixpwupgrade(upwu),
-            DelayedAction::WebauthnCounterIncrement(wci) => self.process_webauthncounterinc(wci),
+            DelayedAction::WebauthnCounterIncrement(wci) => {
+                self.process_webauthncounterinc(wci, ct)
+            }
             DelayedAction::BackupCodeRemoval(bcr) => self.process_backupcoderemoval(bcr),
             DelayedAction::AuthSessionRecord(asr) => self.process_authsessionrecord(asr),
+            DelayedAction::CredentialLockout(cl) => self.process_credentiallockout(cl),
         }
     }
 
@@ -2370,6 +2667,7 @@ impl IdmServerProxyWriteTransaction<'_> {
 #[cfg(test)]
 mod tests {
     use std::convert::TryFrom;
+    use std::net::IpAddr;
     use std::time::Duration;
 
     use crate::credential::{Credential, Password};
@@ -2377,6 +2675,7 @@ mod tests {
     use crate::idm::accountpolicy::ResolvedAccountPolicy;
     use crate::idm::audit::AuditEvent;
     use crate::idm::authentication::AuthState;
+    use crate::idm::credupdatesession::InitCredentialUpdateEvent;
     use crate::idm::delayed::{AuthSessionRecord, DelayedAction};
     use crate::idm::event::{AuthEvent, AuthResult};
     use crate::idm::event::{
@@ -2387,6 +2686,7 @@ mod tests {
     use crate::modify::{Modify, ModifyList};
     use crate::prelude::*;
     use crate::server::keys::KeyProvidersTransaction;
+    use crate::server::security_event::{SecurityEventKind, SecurityEventOutcome};
     use crate::value::{AuthType, SessionState};
     use compact_jwt::{traits::JwsVerifiable, JwsCompact, JwsEs256Verifier, JwsVerifier};
     use kanidm_lib_crypto::CryptoPolicy;
@@ -2675,6 +2975,78 @@ mod tests {
         idms_delayed.check_is_empty_or_panic();
     }
 
+    #[idm_test]
+    async fn test_idm_password_auth_records_session_origin(
+        idms: &IdmServer,
+        idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = duration_from_epoch_now();
+        init_testperson_w_password(idms, TEST_PASSWORD)
+            .await
+            .expect("Failed to setup admin account");
+
+        let source_ip: IpAddr = [192, 0, 2, 10].into();
+        let long_user_agent = "a".repeat(SESSION_USER_AGENT_MAX_LEN * 2);
+
+        let mut client_auth_info = ClientAuthInfo::from(Source::Https(source_ip));
+        client_auth_info.set_user_agent(Some(&long_user_agent));
+
+        let mut idms_auth = idms.auth().await.unwrap();
+        let r1 = idms_auth
+            .auth(&AuthEvent::named_init("testperson1"), ct, client_auth_info)
+            .await
+            .expect("Failed to init auth");
+        assert!(matches!(r1.state, AuthState::Choose(_)));
+
+        let r2 = idms_auth
+            .auth(
+                &AuthEvent::begin_mech(r1.sessionid, AuthMech::Password),
+                ct,
+                Source::Internal.into(),
+            )
+            .await
+            .expect("Failed to begin mech");
+        assert!(matches!(r2.state, AuthState::Continue(_)));
+
+        let r3 = idms_auth
+            .auth(
+                &AuthEvent::cred_step_password(r2.sessionid, TEST_PASSWORD),
+                ct,
+                Source::Internal.into(),
+            )
+            .await
+            .expect("Failed to step password");
+        assert!(matches!(r3.state, AuthState::Success(..)));
+        idms_auth.commit().expect("Must not fail");
+
+        // The origin of the session is carried through to the session record.
+        let da = idms_delayed.try_recv().expect("invalid");
+        let DelayedAction::AuthSessionRecord(ref asr) = da else {
+            panic!("Expected an auth session record");
+        };
+        let session_id = asr.session_id;
+        assert_eq!(asr.source_ip, Some(source_ip));
+        let r = idms.delayed_action(ct, da).await;
+        assert_eq!(Ok(true), r);
+
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+        let entry = idms_prox_read
+            .qs_read
+            .internal_search_uuid(UUID_TESTPERSON_1)
+            .expect("Failed to get testperson");
+        let session = entry
+            .get_ava_as_session_map(Attribute::UserAuthTokenSession)
+            .and_then(|sessions| sessions.get(&session_id))
+            .expect("Session must be present");
+
+        assert_eq!(session.source_ip, Some(source_ip));
+        // The user agent is truncated so that it can't grow the entry without bound.
+        assert_eq!(
+            session.user_agent.as_deref(),
+            Some(&long_user_agent[..SESSION_USER_AGENT_MAX_LEN])
+        );
+    }
+
     #[idm_test]
     async fn test_idm_simple_password_spn_auth(
         idms: &IdmServer,
@@ -2875,6 +3247,72 @@ mod tests {
         assert_eq!(r1, tok_r.secret);
     }
 
+    #[idm_test]
+    async fn test_idm_radiusauthtoken_vlan(idms: &IdmServer, _idms_delayed: &IdmServerDelayed) {
+        let mut idms_prox_write = idms.proxy_write(duration_from_epoch_now()).await.unwrap();
+
+        idms_prox_write
+            .qs_write
+            .internal_create(vec![E_TESTPERSON_1.clone()])
+            .expect("unable to create test person");
+
+        let rrse = RegenerateRadiusSecretEvent::new_internal(UUID_TESTPERSON_1);
+        idms_prox_write
+            .regenerate_radius_secret(&rrse)
+            .expect("Failed to reset radius credential");
+        idms_prox_write.commit().expect("failed to commit");
+
+        async fn get_vlan(idms: &IdmServer) -> Option<u32> {
+            let mut idms_prox_read = idms.proxy_read().await.unwrap();
+            let person_entry = idms_prox_read
+                .qs_read
+                .internal_search_uuid(UUID_TESTPERSON_1)
+                .expect("Can't access person entry.");
+
+            let rate = RadiusAuthTokenEvent::new_impersonate(person_entry, UUID_TESTPERSON_1);
+            idms_prox_read
+                .get_radiusauthtoken(&rate, duration_from_epoch_now())
+                .expect("Failed to generate radius auth token")
+                .vlan
+        }
+
+        // In no vlan groups, there is no vlan.
+        assert_eq!(get_vlan(idms).await, None);
+
+        let mut idms_prox_write = idms.proxy_write(duration_from_epoch_now()).await.unwrap();
+        let vlan_group = |name: &str, vlan: u32, priority: u32| {
+            entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Group.to_value()),
+                (Attribute::Name, Value::new_iname(name)),
+                (Attribute::Member, Value::Refer(UUID_TESTPERSON_1)),
+                (Attribute::RadiusVlanId, Value::Uint32(vlan)),
+                (Attribute::RadiusVlanPriority, Value::Uint32(priority))
+            )
+        };
+        idms_prox_write
+            .qs_write
+            .internal_create(vec![
+                vlan_group("vlan_staff", 10, 5),
+                vlan_group("vlan_admin", 20, 10),
+            ])
+            .expect("unable to create vlan groups");
+        idms_prox_write.commit().expect("failed to commit");
+
+        // The higher priority group wins.
+        assert_eq!(get_vlan(idms).await, Some(20));
+
+        // A group of equal priority with a different vlan is ambiguous, so no vlan is given.
+        let mut idms_prox_write = idms.proxy_write(duration_from_epoch_now()).await.unwrap();
+        idms_prox_write
+            .qs_write
+            .internal_create(vec![vlan_group("vlan_guest", 30, 10)])
+            .expect("unable to create vlan group");
+        idms_prox_write.commit().expect("failed to commit");
+
+        assert_eq!(get_vlan(idms).await, None);
+    }
+
     #[idm_test]
     async fn test_idm_unixusertoken(idms: &IdmServer, _idms_delayed: &IdmServerDelayed) {
         let mut idms_prox_write = idms.proxy_write(duration_from_epoch_now()).await.unwrap();
@@ -2954,6 +3392,105 @@ mod tests {
         assert_eq!(tok_g.spn, "admin@example.com");
     }
 
+    #[idm_test]
+    async fn test_idm_unixusertoken_home_directory_precedence(
+        idms: &IdmServer,
+        _idms_delayed: &IdmServerDelayed,
+    ) {
+        let group_uuid = uuid!("01609135-a1c4-43d5-966b-a28227644445");
+
+        // The domain provides the defaults.
+        let mut idms_prox_write = idms.proxy_write(duration_from_epoch_now()).await.unwrap();
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_DOMAIN_INFO,
+                &ModifyList::new_list(vec![
+                    Modify::Present(
+                        Attribute::UnixHomeDirectoryTemplate,
+                        Value::new_utf8s("/home/{spn}"),
+                    ),
+                    Modify::Present(Attribute::UnixDefaultShell, Value::new_iutf8("/bin/sh")),
+                ]),
+            )
+            .expect("Failed to set domain unix templates");
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_ADMIN,
+                &ModifyList::new_list(vec![
+                    Modify::Present(Attribute::Class, EntryClass::PosixAccount.into()),
+                    Modify::Present(Attribute::GidNumber, Value::new_uint32(2001)),
+                ]),
+            )
+            .expect("Failed to extend admin");
+        idms_prox_write.commit().expect("failed to commit");
+
+        let uute = UnixUserTokenEvent::new_internal(UUID_ADMIN);
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+        let tok_r = idms_prox_read
+            .get_unixusertoken(&uute, duration_from_epoch_now())
+            .expect("Failed to generate unix user token");
+        assert_eq!(
+            tok_r.home_directory.as_deref(),
+            Some("/home/admin@example.com")
+        );
+        assert_eq!(tok_r.shell.as_deref(), Some("/bin/sh"));
+        drop(idms_prox_read);
+
+        // A group template overrides the domain.
+        let mut idms_prox_write = idms.proxy_write(duration_from_epoch_now()).await.unwrap();
+        idms_prox_write
+            .qs_write
+            .internal_create(vec![entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Group.to_value()),
+                (Attribute::Class, EntryClass::PosixGroup.to_value()),
+                (Attribute::Name, Value::new_iname("testgroup")),
+                (Attribute::Uuid, Value::Uuid(group_uuid)),
+                (Attribute::Member, Value::Refer(UUID_ADMIN)),
+                (
+                    Attribute::UnixHomeDirectoryTemplate,
+                    Value::new_utf8s("/exports/{uuid}/{name}")
+                ),
+                (Attribute::UnixDefaultShell, Value::new_iutf8("/bin/zsh"))
+            )])
+            .expect("Failed to create group");
+        idms_prox_write.commit().expect("failed to commit");
+
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+        let tok_r = idms_prox_read
+            .get_unixusertoken(&uute, duration_from_epoch_now())
+            .expect("Failed to generate unix user token");
+        assert_eq!(
+            tok_r.home_directory.as_deref(),
+            Some("/exports/00000000-0000-0000-0000-000000000000/admin")
+        );
+        assert_eq!(tok_r.shell.as_deref(), Some("/bin/zsh"));
+        drop(idms_prox_read);
+
+        // The account's own values take precedence over all templates.
+        let mut idms_prox_write = idms.proxy_write(duration_from_epoch_now()).await.unwrap();
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_ADMIN,
+                &ModifyList::new_list(vec![
+                    Modify::Present(Attribute::LoginShell, Value::new_iutf8("/bin/bash")),
+                    Modify::Present(Attribute::UnixHomeDirectory, Value::new_utf8s("/srv/admin")),
+                ]),
+            )
+            .expect("Failed to modify admin");
+        idms_prox_write.commit().expect("failed to commit");
+
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+        let tok_r = idms_prox_read
+            .get_unixusertoken(&uute, duration_from_epoch_now())
+            .expect("Failed to generate unix user token");
+        assert_eq!(tok_r.home_directory.as_deref(), Some("/srv/admin"));
+        assert_eq!(tok_r.shell.as_deref(), Some("/bin/bash"));
+    }
+
     #[idm_test]
     async fn test_idm_simple_unix_password_reset(
         idms: &IdmServer,
@@ -3189,9 +3726,9 @@ mod tests {
     #[idm_test]
     async fn test_idm_account_valid_from_expire(
         idms: &IdmServer,
-        _idms_delayed: &mut IdmServerDelayed,
+        idms_delayed: &mut IdmServerDelayed,
     ) {
-        // Any account that is not yet valrid / expired can't auth.
+        // Any account that is not yet valid / expired can't auth.
 
         init_testperson_w_password(idms, TEST_PASSWORD)
             .await
@@ -3201,50 +3738,71 @@ mod tests {
         set_testperson_valid_time(idms).await;
 
         let time_low = Duration::from_secs(TEST_NOT_YET_VALID_TIME);
+        let time = Duration::from_secs(TEST_CURRENT_TIME);
         let time_high = Duration::from_secs(TEST_AFTER_EXPIRY);
 
-        let mut idms_auth = idms.auth().await.unwrap();
-        let admin_init = AuthEvent::named_init("admin");
-        let r1 = idms_auth
-            .auth(&admin_init, time_low, Source::Internal.into())
-            .await;
+        let mut event_rx = idms
+            .qs
+            .security_events()
+            .subscribe(8)
+            .expect("failed to subscribe");
 
-        let ar = r1.unwrap();
-        let AuthResult {
-            sessionid: _,
-            state,
-        } = ar;
+        for (ct, kind) in [
+            (time_low, SecurityEventKind::AccountNotYetValid),
+            (time_high, SecurityEventKind::AccountExpired),
+        ] {
+            let mut idms_auth = idms.auth().await.unwrap();
+            let person_init = AuthEvent::named_init("testperson1");
+            let r1 = idms_auth
+                .auth(&person_init, ct, Source::Internal.into())
+                .await;
 
-        match state {
-            AuthState::Denied(_) => {}
-            _ => {
-                panic!();
-            }
-        };
+            let AuthResult {
+                sessionid: _,
+                state,
+            } = r1.unwrap();
+            assert!(matches!(state, AuthState::Denied(_)));
 
-        idms_auth.commit().expect("Must not fail");
+            idms_auth.commit().expect("Must not fail");
 
-        // And here!
-        let mut idms_auth = idms.auth().await.unwrap();
-        let admin_init = AuthEvent::named_init("admin");
-        let r1 = idms_auth
-            .auth(&admin_init, time_high, Source::Internal.into())
-            .await;
+            let event = event_rx
+                .try_recv()
+                .expect("no security event was submitted");
+            assert_eq!(event.kind, kind);
+            assert_eq!(event.outcome, SecurityEventOutcome::Failure);
+            assert_eq!(event.targets, vec![UUID_TESTPERSON_1]);
+        }
 
-        let ar = r1.unwrap();
-        let AuthResult {
-            sessionid: _,
-            state,
-        } = ar;
+        // Within the window the account can authenticate.
+        let token = check_testperson_password(idms, TEST_PASSWORD, time).await;
+        let da = idms_delayed.try_recv().expect("invalid");
+        assert!(matches!(da, DelayedAction::AuthSessionRecord(_)));
+        assert!(idms.delayed_action(time, da).await.is_ok());
+        while let Ok(event) = event_rx.try_recv() {
+            assert!(!matches!(
+                event.kind,
+                SecurityEventKind::AccountNotYetValid | SecurityEventKind::AccountExpired
+            ));
+        }
 
-        match state {
-            AuthState::Denied(_) => {}
-            _ => {
-                panic!();
-            }
-        };
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+        assert!(idms_prox_read
+            .validate_client_auth_info_to_ident(token.clone().into(), time)
+            .is_ok());
 
-        idms_auth.commit().expect("Must not fail");
+        // A session issued inside the window can't be used once the account expires.
+        assert_eq!(
+            idms_prox_read
+                .validate_client_auth_info_to_ident(token.into(), time_high)
+                .map(|_| ()),
+            Err(OperationError::SessionExpired)
+        );
+        drop(idms_prox_read);
+
+        let event = event_rx
+            .try_recv()
+            .expect("no security event was submitted");
+        assert_eq!(event.kind, SecurityEventKind::AccountExpired);
     }
 
     #[idm_test]
@@ -3277,6 +3835,12 @@ mod tests {
         assert!(idms_prox_write.set_unix_account_password(&pce).is_ok());
         assert!(idms_prox_write.commit().is_ok());
 
+        let mut event_rx = idms
+            .qs
+            .security_events()
+            .subscribe(8)
+            .expect("failed to subscribe");
+
         // Now check auth when the time is too high or too low.
         let mut idms_auth = idms.auth().await.unwrap();
         let uuae_good = UnixUserAuthEvent::new_internal(UUID_TESTPERSON_1, TEST_PASSWORD);
@@ -3289,12 +3853,32 @@ mod tests {
             _ => panic!("Oh no"),
         };
 
+        let event = event_rx
+            .try_recv()
+            .expect("no security event was submitted");
+        assert_eq!(event.kind, SecurityEventKind::AccountNotYetValid);
+        assert_eq!(event.outcome, SecurityEventOutcome::Failure);
+        assert_eq!(event.targets, vec![UUID_TESTPERSON_1]);
+
         let a2 = idms_auth.auth_unix(&uuae_good, time_high).await;
         match a2 {
             Ok(None) => {}
             _ => panic!("Oh no"),
         };
 
+        let event = event_rx
+            .try_recv()
+            .expect("no security event was submitted");
+        assert_eq!(event.kind, SecurityEventKind::AccountExpired);
+        assert_eq!(event.targets, vec![UUID_TESTPERSON_1]);
+
+        // Within the window the account is allowed, and nothing is recorded.
+        let a3 = idms_auth
+            .auth_unix(&uuae_good, Duration::from_secs(TEST_CURRENT_TIME))
+            .await;
+        assert!(matches!(a3, Ok(Some(_))));
+        assert!(event_rx.try_recv().is_err());
+
         idms_auth.commit().expect("Must not fail");
         // Also check the generated unix tokens are invalid.
         let mut idms_prox_read = idms.proxy_read().await.unwrap();
@@ -3337,28 +3921,38 @@ mod tests {
             .expect("Failed to reset radius credential 1");
         idms_prox_write.commit().expect("failed to commit");
 
+        let mut event_rx = idms
+            .qs
+            .security_events()
+            .subscribe(8)
+            .expect("failed to subscribe");
+
         let mut idms_prox_read = idms.proxy_read().await.unwrap();
-        let admin_entry = idms_prox_read
+        let person_entry = idms_prox_read
             .qs_read
-            .internal_search_uuid(UUID_ADMIN)
-            .expect("Can't access admin entry.");
+            .internal_search_uuid(UUID_TESTPERSON_1)
+            .expect("Can't access testperson1 entry.");
 
-        let rate = RadiusAuthTokenEvent::new_impersonate(admin_entry, UUID_ADMIN);
-        let tok_r = idms_prox_read.get_radiusauthtoken(&rate, time_low);
+        let rate = RadiusAuthTokenEvent::new_impersonate(person_entry, UUID_TESTPERSON_1);
 
-        if tok_r.is_err() {
-            // Ok?
-        } else {
-            debug_assert!(false);
+        for (ct, kind) in [
+            (time_low, SecurityEventKind::AccountNotYetValid),
+            (time_high, SecurityEventKind::AccountExpired),
+        ] {
+            assert!(idms_prox_read.get_radiusauthtoken(&rate, ct).is_err());
+
+            let event = event_rx
+                .try_recv()
+                .expect("no security event was submitted");
+            assert_eq!(event.kind, kind);
+            assert_eq!(event.targets, vec![UUID_TESTPERSON_1]);
         }
 
-        let tok_r = idms_prox_read.get_radiusauthtoken(&rate, time_high);
-
-        if tok_r.is_err() {
-            // Ok?
-        } else {
-            debug_assert!(false);
-        }
+        // Within the window the token is issued, and nothing is recorded.
+        assert!(idms_prox_read
+            .get_radiusauthtoken(&rate, Duration::from_secs(TEST_CURRENT_TIME))
+            .is_ok());
+        assert!(event_rx.try_recv().is_err());
     }
 
     #[idm_test(audit = 1)]
@@ -3619,6 +4213,275 @@ mod tests {
         idms_auth.commit().expect("Must not fail");
     }
 
+    /// Attempt a password authentication as testperson1, returning the reason if it
+    /// was denied.
+    async fn attempt_testperson_password(
+        idms: &IdmServer,
+        pw: &str,
+        ct: Duration,
+    ) -> Result<(), String> {
+        let mut idms_auth = idms.auth().await.unwrap();
+        let AuthResult { sessionid, state } = idms_auth
+            .auth(
+                &AuthEvent::named_init("testperson1"),
+                ct,
+                Source::Internal.into(),
+            )
+            .await
+            .expect("Failed to init auth session");
+        assert!(matches!(state, AuthState::Choose(_)));
+
+        let AuthResult { sessionid, state } = idms_auth
+            .auth(
+                &AuthEvent::begin_mech(sessionid, AuthMech::Password),
+                ct,
+                Source::Internal.into(),
+            )
+            .await
+            .expect("Failed to begin auth session");
+
+        let state = match state {
+            AuthState::Continue(_) => {
+                idms_auth
+                    .auth(
+                        &AuthEvent::cred_step_password(sessionid, pw),
+                        ct,
+                        Source::Internal.into(),
+                    )
+                    .await
+                    .expect("Failed to step auth session")
+                    .state
+            }
+            state => state,
+        };
+
+        idms_auth.commit().expect("Must not fail");
+
+        match state {
+            AuthState::Success(_, AuthIssueSession::Token) => Ok(()),
+            AuthState::Denied(reason) => Err(reason),
+            state => panic!("Unexpected auth state {state:?}"),
+        }
+    }
+
+    #[idm_test(audit = 1)]
+    async fn test_idm_account_policy_lockout(
+        idms: &IdmServer,
+        idms_delayed: &mut IdmServerDelayed,
+        idms_audit: &mut IdmServerAudit,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let cred_id = init_testperson_w_password(idms, TEST_PASSWORD)
+            .await
+            .expect("Failed to setup admin account");
+
+        // All accounts tolerate many failures, but privileged accounts lock after 3.
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_IDM_ALL_ACCOUNTS,
+                &ModifyList::new_purge_and_set(
+                    Attribute::AuthLockoutMaxAttempts,
+                    Value::Uint32(50),
+                ),
+            )
+            .expect("Unable to set default lockout policy");
+
+        let e: Entry<EntryInit, EntryNew> = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Class, EntryClass::AccountPolicy.to_value()),
+            (Attribute::Name, Value::new_iname("test_privileged")),
+            (Attribute::Uuid, Value::Uuid(Uuid::new_v4())),
+            (Attribute::Member, Value::Refer(UUID_TESTPERSON_1)),
+            (Attribute::AuthLockoutMaxAttempts, Value::Uint32(3)),
+            (Attribute::AuthLockoutDuration, Value::Uint32(900))
+        );
+        idms_prox_write
+            .qs_write
+            .internal_create(vec![e])
+            .expect("Unable to create privileged group");
+        assert!(idms_prox_write.commit().is_ok());
+
+        // Fail three times, spaced so that the softlock has passed between each.
+        let mut ct = ct;
+        for _ in 0..3 {
+            ct += Duration::from_secs(5);
+            let reason = attempt_testperson_password(idms, TEST_PASSWORD_INC, ct)
+                .await
+                .expect_err("Authentication must fail");
+            assert_ne!(reason, "Account is temporarily locked");
+            assert!(matches!(
+                idms_audit.audit_rx().try_recv(),
+                Ok(AuditEvent::AuthenticationDenied { .. })
+            ));
+        }
+
+        // The third failure locks the credential.
+        let da = idms_delayed.try_recv().expect("invalid");
+        let DelayedAction::CredentialLockout(lockout) = &da else {
+            panic!("Expected a credential lockout");
+        };
+        assert_eq!(lockout.cred_id, cred_id);
+        assert_eq!(
+            lockout.unlock_at,
+            OffsetDateTime::UNIX_EPOCH + ct + Duration::from_secs(900)
+        );
+        assert_eq!(Ok(true), idms.delayed_action(ct, da).await);
+        idms_delayed.check_is_empty_or_panic();
+
+        // Even the correct password is denied, long after the softlock has passed.
+        let reason = attempt_testperson_password(idms, TEST_PASSWORD, ct + Duration::from_secs(60))
+            .await
+            .expect_err("Authentication must fail");
+        assert_eq!(reason, "Account is temporarily locked");
+
+        // Once the duration has passed the credential is usable again.
+        ct += Duration::from_secs(901);
+        attempt_testperson_password(idms, TEST_PASSWORD, ct)
+            .await
+            .expect("Authentication must succeed");
+        let da = idms_delayed.try_recv().expect("invalid");
+        assert!(matches!(da, DelayedAction::AuthSessionRecord(_)));
+        idms_delayed.check_is_empty_or_panic();
+
+        // Lock the credential again.
+        for _ in 0..3 {
+            ct += Duration::from_secs(5);
+            attempt_testperson_password(idms, TEST_PASSWORD_INC, ct)
+                .await
+                .expect_err("Authentication must fail");
+            assert!(matches!(
+                idms_audit.audit_rx().try_recv(),
+                Ok(AuditEvent::AuthenticationDenied { .. })
+            ));
+        }
+        let da = idms_delayed.try_recv().expect("invalid");
+        assert!(matches!(da, DelayedAction::CredentialLockout(_)));
+        assert_eq!(Ok(true), idms.delayed_action(ct, da).await);
+        idms_delayed.check_is_empty_or_panic();
+
+        ct += Duration::from_secs(60);
+        let reason = attempt_testperson_password(idms, TEST_PASSWORD, ct)
+            .await
+            .expect_err("Authentication must fail");
+        assert_eq!(reason, "Account is temporarily locked");
+
+        // An administrator can remove the lockout before it expires.
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_TESTPERSON_1,
+                &ModifyList::new_remove(
+                    Attribute::CredentialLockout,
+                    PartialValue::CredentialLockout(cred_id),
+                ),
+            )
+            .expect("Unable to remove credential lockout");
+        assert!(idms_prox_write.commit().is_ok());
+
+        attempt_testperson_password(idms, TEST_PASSWORD, ct)
+            .await
+            .expect("Authentication must succeed");
+        let da = idms_delayed.try_recv().expect("invalid");
+        assert!(matches!(da, DelayedAction::AuthSessionRecord(_)));
+    }
+
+    #[idm_test]
+    async fn test_idm_account_policy_credential_max_age(
+        idms: &IdmServer,
+        idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        // The credential is set at the unix epoch, so is already older than the policy allows.
+        init_testperson_w_password(idms, TEST_PASSWORD)
+            .await
+            .expect("Failed to setup admin account");
+
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_IDM_ALL_ACCOUNTS,
+                &ModifyList::new_purge_and_set(Attribute::CredentialMaxAge, Value::Uint32(3600)),
+            )
+            .expect("Unable to set credential max age");
+        assert!(idms_prox_write.commit().is_ok());
+
+        let token = check_testperson_password(idms, TEST_PASSWORD, ct).await;
+        let da = idms_delayed.try_recv().expect("invalid");
+        assert!(matches!(da, DelayedAction::AuthSessionRecord(_)));
+        assert_eq!(Ok(true), idms.delayed_action(ct, da).await);
+        idms_delayed.check_is_empty_or_panic();
+
+        // The session may not be used for anything other than a credential update.
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        let ident = idms_prox_write
+            .validate_client_auth_info_to_ident(token.into(), ct)
+            .expect("Failed to validate");
+        assert_eq!(ident.access_scope(), AccessScope::CredentialUpdate);
+
+        let se = SearchEvent::new_impersonate_identity(
+            ident.clone(),
+            filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(UUID_TESTPERSON_1))),
+        );
+        assert_eq!(
+            idms_prox_write
+                .qs_write
+                .search(&se)
+                .map(|entries| entries.len()),
+            Ok(0)
+        );
+
+        idms_prox_write
+            .init_credential_update(
+                &InitCredentialUpdateEvent::new(ident.clone(), UUID_TESTPERSON_1),
+                ct,
+            )
+            .expect("Failed to begin credential update");
+
+        // It can't be used to update anyone else's credentials.
+        assert_eq!(
+            idms_prox_write
+                .init_credential_update(&InitCredentialUpdateEvent::new(ident, UUID_ADMIN), ct)
+                .map(|_| ()),
+            Err(OperationError::AccessDenied)
+        );
+        drop(idms_prox_write);
+
+        // Once the password is rotated, sessions are no longer restricted.
+        let p = CryptoPolicy::minimum();
+        let cred =
+            Credential::new_password_only(&p, TEST_PASSWORD_INC, OffsetDateTime::UNIX_EPOCH + ct)
+                .expect("Failed to create credential");
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_TESTPERSON_1,
+                &ModifyList::new_purge_and_set(
+                    Attribute::PrimaryCredential,
+                    Value::new_credential("primary", cred),
+                ),
+            )
+            .expect("Unable to rotate credential");
+        assert!(idms_prox_write.commit().is_ok());
+
+        let ct = ct + Duration::from_secs(60);
+        let token = check_testperson_password(idms, TEST_PASSWORD_INC, ct).await;
+        let da = idms_delayed.try_recv().expect("invalid");
+        assert!(matches!(da, DelayedAction::AuthSessionRecord(_)));
+        assert_eq!(Ok(true), idms.delayed_action(ct, da).await);
+
+        let mut idms_prox_read = idms.proxy_read().await.unwrap();
+        let ident = idms_prox_read
+            .validate_client_auth_info_to_ident(token.into(), ct)
+            .expect("Failed to validate");
+        assert_eq!(ident.access_scope(), AccessScope::ReadOnly);
+    }
+
     #[idm_test]
     async fn test_idm_account_unix_softlocking(
         idms: &IdmServer,
@@ -3745,6 +4608,8 @@ mod tests {
             scope: SessionScope::ReadOnly,
             type_: AuthType::Passkey,
             ext_metadata: Default::default(),
+            source_ip: None,
+            user_agent: None,
         });
         // Persist it.
         let r = idms.delayed_action(ct, da).await;
@@ -3778,6 +4643,8 @@ mod tests {
             scope: SessionScope::ReadOnly,
             type_: AuthType::Passkey,
             ext_metadata: Default::default(),
+            source_ip: None,
+            user_agent: None,
         });
         // Persist it.
         let r = idms.delayed_action(expiry_a, da).await;
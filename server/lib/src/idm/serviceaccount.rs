@@ -1,8 +1,8 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::time::Duration;
 
 use compact_jwt::{jws::JwsBuilder, Jws, JwsCompact};
-use kanidm_proto::internal::ApiToken as ProtoApiToken;
+use kanidm_proto::internal::{ApiToken as ProtoApiToken, ApiTokenOperationScope};
 use time::OffsetDateTime;
 
 use crate::credential::Credential;
@@ -67,15 +67,7 @@ impl ServiceAccount {
         apit: &ProtoApiToken,
         entry: &Entry<EntrySealed, EntryCommitted>,
     ) -> bool {
-        let within_valid_window = Account::check_within_valid_time(
-            ct,
-            entry
-                .get_ava_single_datetime(Attribute::AccountValidFrom)
-                .as_ref(),
-            entry
-                .get_ava_single_datetime(Attribute::AccountExpire)
-                .as_ref(),
-        );
+        let within_valid_window = Account::entry_validity(entry, ct).is_valid();
 
         if !within_valid_window {
             security_info!("Account has expired or is not yet valid, not allowing to proceed");
@@ -126,6 +118,8 @@ pub struct GenerateApiTokenEvent {
     pub expiry: Option<time::OffsetDateTime>,
     // Is it read_write capable?
     pub read_write: bool,
+    // Which classes of operation is it restricted to? If empty, it is unrestricted.
+    pub operation_scopes: BTreeSet<ApiTokenOperationScope>,
     // Limits?
 
     // Should it be compact?
@@ -141,6 +135,7 @@ impl GenerateApiTokenEvent {
             label: label.to_string(),
             expiry: expiry.map(|ct| time::OffsetDateTime::UNIX_EPOCH + ct),
             read_write: false,
+            operation_scopes: BTreeSet::new(),
             compact: false,
         }
     }
@@ -207,6 +202,7 @@ impl IdmServerProxyWriteTransaction<'_> {
                 // What is the access scope of this session? This is
                 // for auditing purposes.
                 scope,
+                operation_scopes: gte.operation_scopes.clone(),
             },
         );
 
@@ -225,6 +221,7 @@ impl IdmServerProxyWriteTransaction<'_> {
                 expiry: gte.expiry,
                 issued_at,
                 purpose,
+                operation_scopes: gte.operation_scopes.clone(),
             };
 
             let token = Jws::into_json(&proto_api_token).map_err(|err| {
@@ -382,6 +379,7 @@ impl IdmServerProxyReadTransaction<'_> {
                                                 expiry: s.expiry,
                                                 issued_at: s.issued_at,
                                                 purpose,
+                                                operation_scopes: s.operation_scopes.clone(),
                                             })
                                             .inspect_err(|err| {
                                                 admin_error!(?err, "Invalid api_token {}", u);
@@ -405,11 +403,13 @@ mod tests {
     use std::time::Duration;
 
     use compact_jwt::{dangernoverify::JwsDangerReleaseWithoutVerify, JwsVerifier};
-    use kanidm_proto::internal::ApiToken;
+    use kanidm_proto::internal::{ApiToken, ApiTokenOperationScope};
+    use std::collections::BTreeSet;
 
     use super::{DestroyApiTokenEvent, GenerateApiTokenEvent};
     use crate::idm::server::IdmServerTransaction;
     use crate::prelude::*;
+    use crate::server::security_event::SecurityEventKind;
 
     const TEST_CURRENT_TIME: u64 = 6000;
 
@@ -499,6 +499,141 @@ mod tests {
         assert!(idms_prox_write.commit().is_ok());
     }
 
+    #[idm_test]
+    async fn test_idm_service_account_api_token_valid_from_expire(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let time_low = Duration::from_secs(TEST_CURRENT_TIME - 240);
+        let time_high = Duration::from_secs(TEST_CURRENT_TIME + 240);
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+
+        let testaccount_uuid = Uuid::new_v4();
+
+        let e1 = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::ServiceAccount.to_value()),
+            (Attribute::Name, Value::new_iname("test_account_only")),
+            (Attribute::Uuid, Value::Uuid(testaccount_uuid)),
+            (Attribute::Description, Value::new_utf8s("testaccount")),
+            (Attribute::DisplayName, Value::new_utf8s("testaccount")),
+            (
+                Attribute::AccountValidFrom,
+                Value::new_datetime_epoch(Duration::from_secs(TEST_CURRENT_TIME - 120))
+            ),
+            (
+                Attribute::AccountExpire,
+                Value::new_datetime_epoch(Duration::from_secs(TEST_CURRENT_TIME + 120))
+            )
+        );
+
+        idms_prox_write
+            .qs_write
+            .internal_create(vec![e1])
+            .expect("Failed to create service account");
+
+        let gte = GenerateApiTokenEvent::new_internal(testaccount_uuid, "TestToken", None);
+
+        let api_token = idms_prox_write
+            .service_account_generate_api_token(&gte, ct)
+            .expect("failed to generate new api token");
+
+        let mut event_rx = idms
+            .security_events()
+            .subscribe(8)
+            .expect("failed to subscribe");
+
+        for (ct, kind) in [
+            (time_low, SecurityEventKind::AccountNotYetValid),
+            (time_high, SecurityEventKind::AccountExpired),
+        ] {
+            assert_eq!(
+                idms_prox_write
+                    .validate_client_auth_info_to_ident(api_token.clone().into(), ct)
+                    .expect_err("Should not succeed"),
+                OperationError::SessionExpired
+            );
+
+            let event = event_rx
+                .try_recv()
+                .expect("no security event was submitted");
+            assert_eq!(event.kind, kind);
+            assert_eq!(event.targets, vec![testaccount_uuid]);
+        }
+
+        // Within the window the token is accepted, and nothing is recorded.
+        let ident = idms_prox_write
+            .validate_client_auth_info_to_ident(api_token.into(), ct)
+            .expect("Unable to verify api token.");
+        assert_eq!(ident.get_uuid(), testaccount_uuid);
+        assert!(event_rx.try_recv().is_err());
+
+        assert!(idms_prox_write.commit().is_ok());
+    }
+
+    #[idm_test]
+    async fn test_idm_service_account_api_token_max_age(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+
+        let testaccount_uuid = Uuid::new_v4();
+
+        let e1 = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::ServiceAccount.to_value()),
+            (Attribute::Name, Value::new_iname("test_account_only")),
+            (Attribute::Uuid, Value::Uuid(testaccount_uuid)),
+            (Attribute::Description, Value::new_utf8s("testaccount")),
+            (Attribute::DisplayName, Value::new_utf8s("testaccount"))
+        );
+
+        idms_prox_write
+            .qs_write
+            .internal_create(vec![e1])
+            .expect("Failed to create service account");
+
+        idms_prox_write
+            .qs_write
+            .internal_modify_uuid(
+                UUID_IDM_ALL_ACCOUNTS,
+                &ModifyList::new_purge_and_set(Attribute::CredentialMaxAge, Value::Uint32(3600)),
+            )
+            .expect("Unable to set credential max age");
+
+        // The token itself never expires, only the policy limits it.
+        let gte = GenerateApiTokenEvent::new_internal(testaccount_uuid, "TestToken", None);
+
+        let api_token = idms_prox_write
+            .service_account_generate_api_token(&gte, ct)
+            .expect("failed to generate new api token");
+
+        let ident = idms_prox_write
+            .validate_client_auth_info_to_ident(
+                api_token.clone().into(),
+                ct + Duration::from_secs(60),
+            )
+            .expect("Unable to verify api token.");
+        assert_eq!(ident.get_uuid(), testaccount_uuid);
+
+        assert_eq!(
+            idms_prox_write
+                .validate_client_auth_info_to_ident(
+                    api_token.into(),
+                    ct + Duration::from_secs(3601)
+                )
+                .expect_err("Should not succeed"),
+            OperationError::AU0009ApiTokenMaxAgeExceeded
+        );
+
+        assert!(idms_prox_write.commit().is_ok());
+    }
+
     #[idm_test]
     async fn test_idm_service_account_compact_api_token(
         idms: &IdmServer,
@@ -571,4 +706,96 @@ mod tests {
 
         assert!(idms_prox_write.commit().is_ok());
     }
+
+    #[idm_test]
+    async fn test_idm_service_account_api_token_operation_scopes(
+        idms: &IdmServer,
+        _idms_delayed: &mut IdmServerDelayed,
+    ) {
+        let ct = Duration::from_secs(TEST_CURRENT_TIME);
+        let mut idms_prox_write = idms.proxy_write(ct).await.unwrap();
+
+        let testaccount_uuid = Uuid::new_v4();
+
+        let e1 = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::ServiceAccount.to_value()),
+            (Attribute::Name, Value::new_iname("test_account_only")),
+            (Attribute::Uuid, Value::Uuid(testaccount_uuid)),
+            (Attribute::Description, Value::new_utf8s("testaccount")),
+            (Attribute::DisplayName, Value::new_utf8s("testaccount"))
+        );
+
+        idms_prox_write
+            .qs_write
+            .internal_create(vec![e1])
+            .expect("Failed to create service account");
+
+        // An unscoped token is unchanged, and can be used for any operation.
+        let gte = GenerateApiTokenEvent::new_internal(testaccount_uuid, "Unscoped", None);
+        let unscoped_token = idms_prox_write
+            .service_account_generate_api_token(&gte, ct)
+            .expect("failed to generate new api token");
+
+        for operation_scope in [None, Some(ApiTokenOperationScope::PersonWrite)] {
+            let mut client_auth_info = ClientAuthInfo::from(unscoped_token.clone());
+            client_auth_info.set_operation_scope(operation_scope);
+            let ident = idms_prox_write
+                .validate_client_auth_info_to_ident(client_auth_info, ct)
+                .expect("Unable to verify api token.");
+            assert_eq!(ident.get_uuid(), testaccount_uuid);
+        }
+
+        let mut gte = GenerateApiTokenEvent::new_internal(testaccount_uuid, "Scoped", None);
+        gte.operation_scopes = BTreeSet::from([
+            ApiTokenOperationScope::GroupWrite,
+            ApiTokenOperationScope::RadiusRead,
+        ]);
+
+        for compact in [false, true] {
+            gte.compact = compact;
+            let scoped_token = idms_prox_write
+                .service_account_generate_api_token(&gte, ct)
+                .expect("failed to generate new api token");
+
+            // The token reports the scopes it was issued with.
+            let apit = idms_prox_write
+                .validate_client_auth_info_to_api_token(&scoped_token.clone().into(), ct)
+                .expect("Unable to introspect api token.");
+            assert_eq!(apit.operation_scopes, gte.operation_scopes);
+
+            // Within scope, including reads permitted by a write scope.
+            for operation_scope in [
+                ApiTokenOperationScope::GroupRead,
+                ApiTokenOperationScope::GroupWrite,
+                ApiTokenOperationScope::RadiusRead,
+            ] {
+                let mut client_auth_info = ClientAuthInfo::from(scoped_token.clone());
+                client_auth_info.set_operation_scope(Some(operation_scope));
+                let ident = idms_prox_write
+                    .validate_client_auth_info_to_ident(client_auth_info, ct)
+                    .expect("Unable to verify api token.");
+                assert_eq!(ident.get_uuid(), testaccount_uuid);
+            }
+
+            // Out of scope, or an operation that isn't part of any scope.
+            for operation_scope in [
+                Some(ApiTokenOperationScope::PersonRead),
+                Some(ApiTokenOperationScope::ServiceAccountWrite),
+                None,
+            ] {
+                let mut client_auth_info = ClientAuthInfo::from(scoped_token.clone());
+                client_auth_info.set_operation_scope(operation_scope);
+                assert_eq!(
+                    idms_prox_write
+                        .validate_client_auth_info_to_ident(client_auth_info, ct)
+                        .expect_err("Should not succeed"),
+                    OperationError::AU0010ApiTokenOperationNotInScope
+                );
+            }
+        }
+
+        assert!(idms_prox_write.commit().is_ok());
+    }
 }
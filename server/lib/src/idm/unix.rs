@@ -0,0 +1,146 @@
+//! Server side rendering of the home directory templates provided to unix clients.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplatePart {
+    Literal(String),
+    Name,
+    Spn,
+    Uuid,
+}
+
+/// A home directory template such as `/home/{spn}`, defined on the domain or a posix
+/// group. Only the `{name}`, `{spn}` and `{uuid}` variables may be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct UnixHomeDirectoryTemplate {
+    parts: Vec<TemplatePart>,
+}
+
+impl FromStr for UnixHomeDirectoryTemplate {
+    type Err = OperationError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if !value.starts_with('/') {
+            error!(?value, "Home directory template must be an absolute path");
+            return Err(OperationError::PL0006UnixHomeDirectoryTemplateInvalid);
+        }
+
+        let mut parts = Vec::new();
+        let mut rest = value;
+
+        while let Some(start) = rest.find(['{', '}']) {
+            let (literal, tail) = rest.split_at(start);
+            if !literal.is_empty() {
+                parts.push(TemplatePart::Literal(literal.to_string()));
+            }
+
+            let end = tail
+                .strip_prefix('{')
+                .and_then(|tail| tail.find('}'))
+                .ok_or_else(|| {
+                    error!(?value, "Home directory template has unbalanced braces");
+                    OperationError::PL0006UnixHomeDirectoryTemplateInvalid
+                })?;
+
+            // Skip the leading brace.
+            let variable = &tail[1..end + 1];
+            let part = match variable {
+                "name" => TemplatePart::Name,
+                "spn" => TemplatePart::Spn,
+                "uuid" => TemplatePart::Uuid,
+                _ => {
+                    error!(
+                        ?value,
+                        ?variable,
+                        "Home directory template variable is not supported"
+                    );
+                    return Err(OperationError::PL0006UnixHomeDirectoryTemplateInvalid);
+                }
+            };
+            parts.push(part);
+
+            rest = &tail[end + 2..];
+        }
+
+        if !rest.is_empty() {
+            parts.push(TemplatePart::Literal(rest.to_string()));
+        }
+
+        Ok(UnixHomeDirectoryTemplate { parts })
+    }
+}
+
+impl fmt::Display for UnixHomeDirectoryTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for part in self.parts.iter() {
+            match part {
+                TemplatePart::Literal(literal) => f.write_str(literal)?,
+                TemplatePart::Name => f.write_str("{name}")?,
+                TemplatePart::Spn => f.write_str("{spn}")?,
+                TemplatePart::Uuid => f.write_str("{uuid}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl UnixHomeDirectoryTemplate {
+    pub(crate) fn render(&self, name: &str, spn: &str, uuid: Uuid) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                TemplatePart::Literal(literal) => literal.clone(),
+                TemplatePart::Name => name.to_string(),
+                TemplatePart::Spn => spn.to_string(),
+                TemplatePart::Uuid => uuid.as_hyphenated().to_string(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UnixHomeDirectoryTemplate;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_unix_home_directory_template_render() {
+        let uuid = uuid!("0d5086b0-74f9-4518-92b4-89df0c55971b");
+
+        let template: UnixHomeDirectoryTemplate = "/home/{spn}".parse().expect("valid template");
+        assert_eq!(
+            template.render("testperson", "testperson@example.com", uuid),
+            "/home/testperson@example.com"
+        );
+
+        let template: UnixHomeDirectoryTemplate =
+            "/exports/{uuid}/{name}.d".parse().expect("valid template");
+        assert_eq!(
+            template.render("testperson", "testperson@example.com", uuid),
+            "/exports/0d5086b0-74f9-4518-92b4-89df0c55971b/testperson.d"
+        );
+        assert_eq!(template.to_string(), "/exports/{uuid}/{name}.d");
+    }
+
+    #[test]
+    fn test_unix_home_directory_template_invalid() {
+        for value in [
+            "home/{name}",
+            "/home/{displayname}",
+            "/home/{}",
+            "/home/{name",
+            "/home/name}",
+            "/home/{{name}}",
+        ] {
+            assert_eq!(
+                value.parse::<UnixHomeDirectoryTemplate>(),
+                Err(OperationError::PL0006UnixHomeDirectoryTemplateInvalid),
+                "{value}"
+            );
+        }
+    }
+}
@@ -63,6 +63,7 @@ pub mod valueset;
 #[macro_use]
 mod plugins;
 pub mod idm;
+pub mod metrics;
 pub mod repl;
 pub mod schema;
 pub mod server;
@@ -0,0 +1,286 @@
+//! Lightweight counters and histograms describing the runtime behaviour of the server,
+//! rendered in the Prometheus text exposition format.
+//!
+//! Metrics are fixed-size atomics in a single static so that recording them is cheap
+//! enough to do on every request, and never allocates or takes a lock.
+//!
+//! The metric names below are part of the external interface of the server, as dashboards
+//! and alerts are built against them. Once released a metric must not be renamed or change
+//! meaning - add a new metric instead.
+//!
+//! | Name                                                | Type      | Description                                       |
+//! |-----------------------------------------------------|-----------|---------------------------------------------------|
+//! | `kanidm_http_request_duration_seconds`              | histogram | Time taken to respond to HTTP requests            |
+//! | `kanidm_search_operations_total`                    | counter   | Searches performed on behalf of an identity       |
+//! | `kanidm_auth_total{outcome}`                        | counter   | Credential checks, by `success` or `failure`      |
+//! | `kanidm_replication_lag_seconds`                    | gauge     | Age of the newest change received from a supplier |
+//! | `kanidm_backend_cache_requests_total{cache,result}` | counter   | Backend cache lookups, by `hit` or `miss`         |
+//! | `kanidm_delayed_action_queue_depth`                 | gauge     | Delayed actions waiting to be applied             |
+
+use std::fmt::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// The upper bounds of the buckets of duration histograms, in seconds.
+const DURATION_BUCKETS: [f64; 12] = [
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0,
+];
+
+/// A value that only increases.
+#[derive(Debug)]
+pub struct Counter(AtomicU64);
+
+impl Counter {
+    const fn new() -> Self {
+        Counter(AtomicU64::new(0))
+    }
+
+    pub fn inc(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A value that may increase or decrease.
+#[derive(Debug)]
+pub struct Gauge(AtomicU64);
+
+impl Gauge {
+    const fn new() -> Self {
+        Gauge(AtomicU64::new(0))
+    }
+
+    pub fn set(&self, value: u64) {
+        self.0.store(value, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// The distribution of durations over [`DURATION_BUCKETS`].
+#[derive(Debug)]
+pub struct DurationHistogram {
+    buckets: [AtomicU64; DURATION_BUCKETS.len()],
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl DurationHistogram {
+    const fn new() -> Self {
+        DurationHistogram {
+            buckets: [const { AtomicU64::new(0) }; DURATION_BUCKETS.len()],
+            count: AtomicU64::new(0),
+            sum_micros: AtomicU64::new(0),
+        }
+    }
+
+    pub fn observe(&self, duration: Duration) {
+        let secs = duration.as_secs_f64();
+        // Buckets are not cumulative here, they are summed when rendered.
+        if let Some(bucket) = DURATION_BUCKETS
+            .iter()
+            .position(|bound| secs <= *bound)
+            .and_then(|idx| self.buckets.get(idx))
+        {
+            bucket.fetch_add(1, Ordering::Relaxed);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        let micros = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+        self.sum_micros.fetch_add(micros, Ordering::Relaxed);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+}
+
+/// A backend cache's hits and misses.
+#[derive(Debug)]
+pub struct CacheMetrics {
+    pub hits: Counter,
+    pub misses: Counter,
+}
+
+impl CacheMetrics {
+    const fn new() -> Self {
+        CacheMetrics {
+            hits: Counter::new(),
+            misses: Counter::new(),
+        }
+    }
+
+    pub fn record(&self, hit: bool) {
+        if hit {
+            self.hits.inc()
+        } else {
+            self.misses.inc()
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ServerMetrics {
+    pub http_request_duration: DurationHistogram,
+    pub search_operations: Counter,
+    pub auth_success: Counter,
+    pub auth_failure: Counter,
+    pub replication_lag_seconds: Gauge,
+    pub entry_cache: CacheMetrics,
+    pub idl_cache: CacheMetrics,
+    pub name_cache: CacheMetrics,
+    pub delayed_action_queue_depth: Gauge,
+}
+
+/// The metrics of this server process.
+pub static METRICS: ServerMetrics = ServerMetrics::new();
+
+impl ServerMetrics {
+    const fn new() -> Self {
+        ServerMetrics {
+            http_request_duration: DurationHistogram::new(),
+            search_operations: Counter::new(),
+            auth_success: Counter::new(),
+            auth_failure: Counter::new(),
+            replication_lag_seconds: Gauge::new(),
+            entry_cache: CacheMetrics::new(),
+            idl_cache: CacheMetrics::new(),
+            name_cache: CacheMetrics::new(),
+            delayed_action_queue_depth: Gauge::new(),
+        }
+    }
+
+    /// Render these metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        // Writing to a String can't fail.
+        let _ = self.render_into(&mut out);
+        out
+    }
+
+    fn render_into(&self, out: &mut String) -> fmt::Result {
+        let name = "kanidm_http_request_duration_seconds";
+        writeln!(out, "# HELP {name} Time taken to respond to HTTP requests.")?;
+        writeln!(out, "# TYPE {name} histogram")?;
+        let mut cumulative = 0;
+        for (bound, bucket) in DURATION_BUCKETS
+            .iter()
+            .zip(self.http_request_duration.buckets.iter())
+        {
+            cumulative += bucket.load(Ordering::Relaxed);
+            writeln!(out, "{name}_bucket{{le=\"{bound}\"}} {cumulative}")?;
+        }
+        let count = self.http_request_duration.count();
+        writeln!(out, "{name}_bucket{{le=\"+Inf\"}} {count}")?;
+        let sum_micros = self
+            .http_request_duration
+            .sum_micros
+            .load(Ordering::Relaxed);
+        writeln!(out, "{name}_sum {}", sum_micros as f64 / 1_000_000.0)?;
+        writeln!(out, "{name}_count {count}")?;
+
+        let name = "kanidm_search_operations_total";
+        writeln!(
+            out,
+            "# HELP {name} Searches performed on behalf of an identity."
+        )?;
+        writeln!(out, "# TYPE {name} counter")?;
+        writeln!(out, "{name} {}", self.search_operations.get())?;
+
+        let name = "kanidm_auth_total";
+        writeln!(out, "# HELP {name} Credential checks by outcome.")?;
+        writeln!(out, "# TYPE {name} counter")?;
+        writeln!(
+            out,
+            "{name}{{outcome=\"success\"}} {}",
+            self.auth_success.get()
+        )?;
+        writeln!(
+            out,
+            "{name}{{outcome=\"failure\"}} {}",
+            self.auth_failure.get()
+        )?;
+
+        let name = "kanidm_replication_lag_seconds";
+        writeln!(
+            out,
+            "# HELP {name} Age of the newest change received from a replication supplier."
+        )?;
+        writeln!(out, "# TYPE {name} gauge")?;
+        writeln!(out, "{name} {}", self.replication_lag_seconds.get())?;
+
+        let name = "kanidm_backend_cache_requests_total";
+        writeln!(out, "# HELP {name} Backend cache lookups by result.")?;
+        writeln!(out, "# TYPE {name} counter")?;
+        for (cache, metrics) in [
+            ("entry", &self.entry_cache),
+            ("idl", &self.idl_cache),
+            ("name", &self.name_cache),
+        ] {
+            writeln!(
+                out,
+                "{name}{{cache=\"{cache}\",result=\"hit\"}} {}",
+                metrics.hits.get()
+            )?;
+            writeln!(
+                out,
+                "{name}{{cache=\"{cache}\",result=\"miss\"}} {}",
+                metrics.misses.get()
+            )?;
+        }
+
+        let name = "kanidm_delayed_action_queue_depth";
+        writeln!(out, "# HELP {name} Delayed actions waiting to be applied.")?;
+        writeln!(out, "# TYPE {name} gauge")?;
+        writeln!(out, "{name} {}", self.delayed_action_queue_depth.get())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_render() {
+        let metrics = ServerMetrics::new();
+        metrics
+            .http_request_duration
+            .observe(Duration::from_millis(3));
+        metrics
+            .http_request_duration
+            .observe(Duration::from_millis(40));
+        metrics
+            .http_request_duration
+            .observe(Duration::from_secs(9));
+        metrics.auth_failure.inc();
+        metrics.entry_cache.record(true);
+        metrics.entry_cache.record(false);
+        metrics.entry_cache.record(true);
+        metrics.delayed_action_queue_depth.set(4);
+
+        let rendered = metrics.render();
+        let lines: Vec<&str> = rendered.lines().collect();
+
+        for expect in [
+            "kanidm_http_request_duration_seconds_bucket{le=\"0.0025\"} 0",
+            "kanidm_http_request_duration_seconds_bucket{le=\"0.005\"} 1",
+            "kanidm_http_request_duration_seconds_bucket{le=\"0.05\"} 2",
+            "kanidm_http_request_duration_seconds_bucket{le=\"5\"} 2",
+            "kanidm_http_request_duration_seconds_bucket{le=\"+Inf\"} 3",
+            "kanidm_http_request_duration_seconds_sum 9.043",
+            "kanidm_http_request_duration_seconds_count 3",
+            "kanidm_search_operations_total 0",
+            "kanidm_auth_total{outcome=\"success\"} 0",
+            "kanidm_auth_total{outcome=\"failure\"} 1",
+            "kanidm_backend_cache_requests_total{cache=\"entry\",result=\"hit\"} 2",
+            "kanidm_backend_cache_requests_total{cache=\"entry\",result=\"miss\"} 1",
+            "kanidm_delayed_action_queue_depth 4",
+        ] {
+            assert!(lines.contains(&expect), "missing {expect} in {rendered}");
+        }
+    }
+}
@@ -511,6 +511,12 @@ pub static IDM_ACP_GROUP_ACCOUNT_POLICY_MANAGE_DL8: LazyLock<BuiltinAcp> =
             Attribute::Uuid,
             Attribute::AuthSessionExpiry,
             Attribute::AuthPasswordMinimumLength,
+            Attribute::AuthPasswordMinimumScore,
+            Attribute::AuthPasswordBreachCheck,
+            Attribute::AuthPasswordHistoryLength,
+            Attribute::AuthLockoutMaxAttempts,
+            Attribute::AuthLockoutDuration,
+            Attribute::CredentialMaxAge,
             Attribute::CredentialTypeMinimum,
             Attribute::PrivilegeExpiry,
             Attribute::WebauthnAttestationCaList,
@@ -522,6 +528,12 @@ pub static IDM_ACP_GROUP_ACCOUNT_POLICY_MANAGE_DL8: LazyLock<BuiltinAcp> =
             Attribute::Class,
             Attribute::AuthSessionExpiry,
             Attribute::AuthPasswordMinimumLength,
+            Attribute::AuthPasswordMinimumScore,
+            Attribute::AuthPasswordBreachCheck,
+            Attribute::AuthPasswordHistoryLength,
+            Attribute::AuthLockoutMaxAttempts,
+            Attribute::AuthLockoutDuration,
+            Attribute::CredentialMaxAge,
             Attribute::CredentialTypeMinimum,
             Attribute::PrivilegeExpiry,
             Attribute::WebauthnAttestationCaList,
@@ -533,6 +545,12 @@ pub static IDM_ACP_GROUP_ACCOUNT_POLICY_MANAGE_DL8: LazyLock<BuiltinAcp> =
             Attribute::Class,
             Attribute::AuthSessionExpiry,
             Attribute::AuthPasswordMinimumLength,
+            Attribute::AuthPasswordMinimumScore,
+            Attribute::AuthPasswordBreachCheck,
+            Attribute::AuthPasswordHistoryLength,
+            Attribute::AuthLockoutMaxAttempts,
+            Attribute::AuthLockoutDuration,
+            Attribute::CredentialMaxAge,
             Attribute::CredentialTypeMinimum,
             Attribute::PrivilegeExpiry,
             Attribute::WebauthnAttestationCaList,
@@ -578,6 +596,8 @@ pub static IDM_ACP_OAUTH2_MANAGE: LazyLock<BuiltinAcp> = LazyLock::new(|| Builti
         Attribute::OAuth2JwtLegacyCryptoEnable,
         Attribute::OAuth2PreferShortUsername,
         Attribute::OAuth2RefreshTokenExpiry,
+        Attribute::OAuth2AccessTokenExpiry,
+        Attribute::OAuth2RequirePushedAuthorisation,
         Attribute::OAuth2AllowLocalhostRedirect,
         Attribute::OAuth2RsClaimMap,
         Attribute::Image,
@@ -599,6 +619,8 @@ pub static IDM_ACP_OAUTH2_MANAGE: LazyLock<BuiltinAcp> = LazyLock::new(|| Builti
         Attribute::OAuth2JwtLegacyCryptoEnable,
         Attribute::OAuth2PreferShortUsername,
         Attribute::OAuth2RefreshTokenExpiry,
+        Attribute::OAuth2AccessTokenExpiry,
+        Attribute::OAuth2RequirePushedAuthorisation,
         Attribute::OAuth2AllowLocalhostRedirect,
         Attribute::OAuth2RsClaimMap,
         Attribute::Image,
@@ -619,6 +641,8 @@ pub static IDM_ACP_OAUTH2_MANAGE: LazyLock<BuiltinAcp> = LazyLock::new(|| Builti
         Attribute::OAuth2JwtLegacyCryptoEnable,
         Attribute::OAuth2PreferShortUsername,
         Attribute::OAuth2RefreshTokenExpiry,
+        Attribute::OAuth2AccessTokenExpiry,
+        Attribute::OAuth2RequirePushedAuthorisation,
         Attribute::OAuth2AllowLocalhostRedirect,
         Attribute::OAuth2RsClaimMap,
         Attribute::Image,
@@ -641,6 +665,8 @@ pub static IDM_ACP_OAUTH2_MANAGE: LazyLock<BuiltinAcp> = LazyLock::new(|| Builti
         Attribute::OAuth2JwtLegacyCryptoEnable,
         Attribute::OAuth2PreferShortUsername,
         Attribute::OAuth2RefreshTokenExpiry,
+        Attribute::OAuth2AccessTokenExpiry,
+        Attribute::OAuth2RequirePushedAuthorisation,
         Attribute::OAuth2AllowLocalhostRedirect,
         Attribute::OAuth2RsClaimMap,
         Attribute::Image,
@@ -702,6 +728,11 @@ pub static IDM_ACP_DOMAIN_ADMIN_DL9: LazyLock<BuiltinAcp> = LazyLock::new(|| Bui
         Attribute::Uuid,
         Attribute::DomainAllowEasterEggs,
         Attribute::DomainAllowAccountRecovery,
+        Attribute::DomainDenyMembershipCycles,
+        Attribute::DomainSelfWriteAttrs,
+        Attribute::GidNumberRange,
+        Attribute::UnixHomeDirectoryTemplate,
+        Attribute::UnixDefaultShell,
         Attribute::DomainDisplayName,
         Attribute::DomainName,
         Attribute::DomainLdapBasedn,
@@ -720,6 +751,11 @@ pub static IDM_ACP_DOMAIN_ADMIN_DL9: LazyLock<BuiltinAcp> = LazyLock::new(|| Bui
         Attribute::LdapMaxQueryableAttrs,
         Attribute::DomainAllowEasterEggs,
         Attribute::DomainAllowAccountRecovery,
+        Attribute::DomainDenyMembershipCycles,
+        Attribute::DomainSelfWriteAttrs,
+        Attribute::GidNumberRange,
+        Attribute::UnixHomeDirectoryTemplate,
+        Attribute::UnixDefaultShell,
         Attribute::LdapAllowUnixPwBind,
         Attribute::KeyActionRevoke,
         Attribute::KeyActionRotate,
@@ -732,6 +768,11 @@ pub static IDM_ACP_DOMAIN_ADMIN_DL9: LazyLock<BuiltinAcp> = LazyLock::new(|| Bui
         Attribute::DomainSsid,
         Attribute::DomainAllowEasterEggs,
         Attribute::DomainAllowAccountRecovery,
+        Attribute::DomainDenyMembershipCycles,
+        Attribute::DomainSelfWriteAttrs,
+        Attribute::GidNumberRange,
+        Attribute::UnixHomeDirectoryTemplate,
+        Attribute::UnixDefaultShell,
         Attribute::LdapAllowUnixPwBind,
         Attribute::KeyActionRevoke,
         Attribute::KeyActionRotate,
@@ -816,11 +857,20 @@ pub static IDM_ACP_GROUP_ENTRY_MANAGER_V1: LazyLock<BuiltinAcp> = LazyLock::new(
         Attribute::Uuid,
         Attribute::Description,
         Attribute::Member,
+        Attribute::MemberExpiry,
         Attribute::DynMember,
         Attribute::EntryManagedBy,
     ],
-    modify_present_attrs: vec![Attribute::Description, Attribute::Member],
-    modify_removed_attrs: vec![Attribute::Description, Attribute::Member],
+    modify_present_attrs: vec![
+        Attribute::Description,
+        Attribute::Member,
+        Attribute::MemberExpiry,
+    ],
+    modify_removed_attrs: vec![
+        Attribute::Description,
+        Attribute::Member,
+        Attribute::MemberExpiry,
+    ],
     ..Default::default()
 });
 
@@ -847,6 +897,8 @@ pub static IDM_ACP_RADIUS_SERVERS_V1: LazyLock<BuiltinAcp> = LazyLock::new(|| Bu
         Attribute::MemberOf,
         Attribute::Spn,
         Attribute::RadiusSecret,
+        Attribute::RadiusVlanId,
+        Attribute::RadiusVlanPriority,
     ],
     ..Default::default()
 });
@@ -872,6 +924,61 @@ pub static IDM_ACP_RADIUS_SECRET_MANAGE_V1: LazyLock<BuiltinAcp> = LazyLock::new
     ..Default::default()
 });
 
+pub static IDM_ACP_GROUP_RADIUS_MANAGE: LazyLock<BuiltinAcp> = LazyLock::new(|| BuiltinAcp {
+    classes: vec![
+        EntryClass::Object,
+        EntryClass::AccessControlProfile,
+        EntryClass::AccessControlModify,
+        EntryClass::AccessControlSearch,
+    ],
+    name: "idm_acp_group_radius_manage",
+    uuid: UUID_IDM_ACP_GROUP_RADIUS_MANAGE,
+    description: "Builtin IDM Control allowing reads and writes to group radius vlans.",
+    receiver: BuiltinAcpReceiver::Group(vec![UUID_IDM_RADIUS_ADMINS]),
+    target: BuiltinAcpTarget::Filter(ProtoFilter::And(vec![
+        match_class_filter!(EntryClass::Group),
+        FILTER_ANDNOT_TOMBSTONE_OR_RECYCLED.clone(),
+    ])),
+    search_attrs: vec![
+        Attribute::Class,
+        Attribute::Name,
+        Attribute::Uuid,
+        Attribute::Spn,
+        Attribute::RadiusVlanId,
+        Attribute::RadiusVlanPriority,
+    ],
+    modify_present_attrs: vec![Attribute::RadiusVlanId, Attribute::RadiusVlanPriority],
+    modify_removed_attrs: vec![Attribute::RadiusVlanId, Attribute::RadiusVlanPriority],
+    ..Default::default()
+});
+
+pub static IDM_ACP_AUDIT_READ: LazyLock<BuiltinAcp> = LazyLock::new(|| BuiltinAcp {
+    classes: vec![
+        EntryClass::Object,
+        EntryClass::AccessControlProfile,
+        EntryClass::AccessControlSearch,
+    ],
+    name: "idm_acp_audit_read",
+    uuid: UUID_IDM_ACP_AUDIT_READ,
+    description: "Builtin IDM Control for reading audit records.",
+    receiver: BuiltinAcpReceiver::Group(vec![UUID_IDM_AUDIT_READERS]),
+    target: BuiltinAcpTarget::Filter(ProtoFilter::And(vec![
+        match_class_filter!(EntryClass::AuditRecord),
+        FILTER_ANDNOT_TOMBSTONE_OR_RECYCLED.clone(),
+    ])),
+    search_attrs: vec![
+        Attribute::Class,
+        Attribute::Uuid,
+        Attribute::AuditActor,
+        Attribute::AuditTarget,
+        Attribute::AuditOperation,
+        Attribute::AuditAttribute,
+        Attribute::AuditTime,
+        Attribute::DeleteAfter,
+    ],
+    ..Default::default()
+});
+
 pub static IDM_ACP_MAIL_SERVERS_DL8: LazyLock<BuiltinAcp> = LazyLock::new(|| BuiltinAcp {
     classes: vec![
         EntryClass::Object,
@@ -961,6 +1068,7 @@ pub static IDM_ACP_SELF_READ_DL8: LazyLock<BuiltinAcp> = LazyLock::new(|| Builti
         Attribute::UnixPassword,
         Attribute::LastModifiedCid,
         Attribute::PasswordChangedTime,
+        Attribute::PasskeyUsage,
     ],
     ..Default::default()
 });
@@ -1030,6 +1138,28 @@ pub static IDM_ACP_SELF_NAME_WRITE_DL7: LazyLock<BuiltinAcp> = LazyLock::new(||
 }
 });
 
+/// The attributes this profile grants are regenerated from the domain
+/// [`Attribute::DomainSelfWriteAttrs`] policy, so the lists here are intentionally empty.
+pub static IDM_ACP_SELF_WRITE_POLICY: LazyLock<BuiltinAcp> = LazyLock::new(|| {
+    BuiltinAcp {
+    classes: vec![
+        EntryClass::Object,
+        EntryClass::AccessControlProfile,
+        EntryClass::AccessControlModify,
+    ],
+    name: "idm_acp_self_write_policy",
+    uuid: UUID_IDM_ACP_SELF_WRITE_POLICY,
+    description: "Builtin IDM Control for self write of the attributes allowed by the domain self write policy.",
+    receiver: BuiltinAcpReceiver::Group(vec![UUID_IDM_ALL_PERSONS]),
+    target: BuiltinAcpTarget::Filter(ProtoFilter::And(vec![
+        ProtoFilter::SelfUuid,
+        match_class_filter!(EntryClass::Person).clone(),
+        FILTER_ANDNOT_TOMBSTONE_OR_RECYCLED.clone(),
+    ])),
+    ..Default::default()
+}
+});
+
 pub static IDM_ACP_ACCOUNT_SELF_WRITE_V1: LazyLock<BuiltinAcp> = LazyLock::new(|| {
     BuiltinAcp {
         name: "idm_acp_account_self_write",
@@ -1184,9 +1314,14 @@ pub static IDM_ACP_HP_GROUP_UNIX_MANAGE_V1: LazyLock<BuiltinAcp> = LazyLock::new
         Attribute::Member,
         Attribute::MemberOf,
         Attribute::GidNumber,
+        Attribute::GidNumberRangeName,
+    ],
+    modify_removed_attrs: vec![Attribute::GidNumber, Attribute::GidNumberRangeName],
+    modify_present_attrs: vec![
+        Attribute::Class,
+        Attribute::GidNumber,
+        Attribute::GidNumberRangeName,
     ],
-    modify_removed_attrs: vec![Attribute::GidNumber],
-    modify_present_attrs: vec![Attribute::Class, Attribute::GidNumber],
     modify_classes: vec![EntryClass::PosixGroup],
     ..Default::default()
 });
@@ -1218,6 +1353,7 @@ pub static IDM_ACP_GROUP_MANAGE_DL9: LazyLock<BuiltinAcp> = LazyLock::new(|| Bui
         Attribute::Description,
         Attribute::Mail,
         Attribute::Member,
+        Attribute::MemberExpiry,
         Attribute::MemberOf,
         Attribute::DynMember,
         Attribute::EntryManagedBy,
@@ -1229,6 +1365,7 @@ pub static IDM_ACP_GROUP_MANAGE_DL9: LazyLock<BuiltinAcp> = LazyLock::new(|| Bui
         Attribute::Description,
         Attribute::Mail,
         Attribute::Member,
+        Attribute::MemberExpiry,
         Attribute::EntryManagedBy,
     ],
     create_classes: vec![EntryClass::Object, EntryClass::Group],
@@ -1237,6 +1374,7 @@ pub static IDM_ACP_GROUP_MANAGE_DL9: LazyLock<BuiltinAcp> = LazyLock::new(|| Bui
         Attribute::Description,
         Attribute::Mail,
         Attribute::Member,
+        Attribute::MemberExpiry,
         Attribute::EntryManagedBy,
     ],
     modify_removed_attrs: vec![
@@ -1244,6 +1382,7 @@ pub static IDM_ACP_GROUP_MANAGE_DL9: LazyLock<BuiltinAcp> = LazyLock::new(|| Bui
         Attribute::Description,
         Attribute::Mail,
         Attribute::Member,
+        Attribute::MemberExpiry,
         Attribute::EntryManagedBy,
     ],
     ..Default::default()
@@ -1273,9 +1412,23 @@ pub static IDM_ACP_GROUP_UNIX_MANAGE_V1: LazyLock<BuiltinAcp> = LazyLock::new(||
         Attribute::Description,
         Attribute::Member,
         Attribute::GidNumber,
+        Attribute::GidNumberRangeName,
+        Attribute::UnixHomeDirectoryTemplate,
+        Attribute::UnixDefaultShell,
+    ],
+    modify_removed_attrs: vec![
+        Attribute::GidNumber,
+        Attribute::GidNumberRangeName,
+        Attribute::UnixHomeDirectoryTemplate,
+        Attribute::UnixDefaultShell,
+    ],
+    modify_present_attrs: vec![
+        Attribute::Class,
+        Attribute::GidNumber,
+        Attribute::GidNumberRangeName,
+        Attribute::UnixHomeDirectoryTemplate,
+        Attribute::UnixDefaultShell,
     ],
-    modify_removed_attrs: vec![Attribute::GidNumber],
-    modify_present_attrs: vec![Attribute::Class, Attribute::GidNumber],
     modify_classes: vec![EntryClass::PosixGroup],
     ..Default::default()
 });
@@ -1302,20 +1455,26 @@ pub static IDM_ACP_ACCOUNT_UNIX_EXTEND_V1: LazyLock<BuiltinAcp> = LazyLock::new(
         Attribute::Spn,
         Attribute::Description,
         Attribute::GidNumber,
+        Attribute::GidNumberRangeName,
         Attribute::LoginShell,
+        Attribute::UnixHomeDirectory,
         Attribute::UnixPassword,
         Attribute::SshPublicKey,
     ],
     modify_removed_attrs: vec![
         Attribute::GidNumber,
+        Attribute::GidNumberRangeName,
         Attribute::LoginShell,
+        Attribute::UnixHomeDirectory,
         Attribute::UnixPassword,
         Attribute::SshPublicKey,
     ],
     modify_present_attrs: vec![
         Attribute::Class,
         Attribute::GidNumber,
+        Attribute::GidNumberRangeName,
         Attribute::LoginShell,
+        Attribute::UnixHomeDirectory,
         Attribute::UnixPassword,
         Attribute::SshPublicKey,
     ],
@@ -1515,6 +1674,8 @@ pub static IDM_ACP_PEOPLE_CREDENTIAL_RESET_V1: LazyLock<BuiltinAcp> =
             Attribute::PassKeys,
             Attribute::AttestedPasskeys,
             Attribute::AccountSoftlockExpire,
+            Attribute::CredentialLockout,
+            Attribute::PasskeyUsage,
         ],
         modify_removed_attrs: vec![
             Attribute::PrimaryCredential,
@@ -1523,6 +1684,8 @@ pub static IDM_ACP_PEOPLE_CREDENTIAL_RESET_V1: LazyLock<BuiltinAcp> =
             Attribute::AccountValidFrom,
             Attribute::AccountExpire,
             Attribute::AccountSoftlockExpire,
+            Attribute::CredentialLockout,
+            Attribute::PasskeyUsage,
         ],
         modify_present_attrs: vec![
             Attribute::PrimaryCredential,
@@ -1565,6 +1728,8 @@ pub static IDM_ACP_HP_PEOPLE_CREDENTIAL_RESET_V1: LazyLock<BuiltinAcp> =
             Attribute::AccountValidFrom,
             Attribute::PassKeys,
             Attribute::AttestedPasskeys,
+            Attribute::CredentialLockout,
+            Attribute::PasskeyUsage,
         ],
         modify_removed_attrs: vec![
             Attribute::PrimaryCredential,
@@ -1572,6 +1737,8 @@ pub static IDM_ACP_HP_PEOPLE_CREDENTIAL_RESET_V1: LazyLock<BuiltinAcp> =
             Attribute::AccountValidFrom,
             Attribute::PassKeys,
             Attribute::AttestedPasskeys,
+            Attribute::CredentialLockout,
+            Attribute::PasskeyUsage,
         ],
         modify_present_attrs: vec![
             Attribute::PrimaryCredential,
@@ -1860,6 +2027,8 @@ pub static IDM_ACP_APPLICATION_MANAGE_DL8: LazyLock<BuiltinAcp> = LazyLock::new(
         Attribute::ApiTokenSession,
         Attribute::UserAuthTokenSession,
         Attribute::LinkedGroup,
+        Attribute::ApplicationPasswordLimit,
+        Attribute::ApplicationPasswordAutoRevoke,
         Attribute::EntryManagedBy,
     ],
     create_attrs: vec![
@@ -1870,6 +2039,8 @@ pub static IDM_ACP_APPLICATION_MANAGE_DL8: LazyLock<BuiltinAcp> = LazyLock::new(
         Attribute::DisplayName,
         Attribute::Mail,
         Attribute::LinkedGroup,
+        Attribute::ApplicationPasswordLimit,
+        Attribute::ApplicationPasswordAutoRevoke,
         Attribute::EntryManagedBy,
     ],
     create_classes: vec![
@@ -1886,6 +2057,8 @@ pub static IDM_ACP_APPLICATION_MANAGE_DL8: LazyLock<BuiltinAcp> = LazyLock::new(
         Attribute::UnixPassword,
         Attribute::ApiTokenSession,
         Attribute::LinkedGroup,
+        Attribute::ApplicationPasswordLimit,
+        Attribute::ApplicationPasswordAutoRevoke,
         Attribute::EntryManagedBy,
     ],
     modify_removed_attrs: vec![
@@ -1897,6 +2070,8 @@ pub static IDM_ACP_APPLICATION_MANAGE_DL8: LazyLock<BuiltinAcp> = LazyLock::new(
         Attribute::ApiTokenSession,
         Attribute::UserAuthTokenSession,
         Attribute::LinkedGroup,
+        Attribute::ApplicationPasswordLimit,
+        Attribute::ApplicationPasswordAutoRevoke,
         Attribute::EntryManagedBy,
     ],
     ..Default::default()
@@ -1932,6 +2107,8 @@ pub static IDM_ACP_APPLICATION_ENTRY_MANAGER_DL8: LazyLock<BuiltinAcp> =
             Attribute::UserAuthTokenSession,
             Attribute::Description,
             Attribute::LinkedGroup,
+            Attribute::ApplicationPasswordLimit,
+            Attribute::ApplicationPasswordAutoRevoke,
             Attribute::EntryManagedBy,
         ],
         modify_present_attrs: vec![
@@ -1942,6 +2119,8 @@ pub static IDM_ACP_APPLICATION_ENTRY_MANAGER_DL8: LazyLock<BuiltinAcp> =
             Attribute::UnixPassword,
             Attribute::ApiTokenSession,
             Attribute::LinkedGroup,
+            Attribute::ApplicationPasswordLimit,
+            Attribute::ApplicationPasswordAutoRevoke,
         ],
         modify_removed_attrs: vec![
             Attribute::Name,
@@ -1952,6 +2131,8 @@ pub static IDM_ACP_APPLICATION_ENTRY_MANAGER_DL8: LazyLock<BuiltinAcp> =
             Attribute::ApiTokenSession,
             Attribute::UserAuthTokenSession,
             Attribute::LinkedGroup,
+            Attribute::ApplicationPasswordLimit,
+            Attribute::ApplicationPasswordAutoRevoke,
         ],
         ..Default::default()
     });
@@ -2119,3 +2300,195 @@ pub static IDM_ACP_OAUTH2_ACCOUNT_ENROL: LazyLock<BuiltinAcp> = LazyLock::new(||
     modify_classes: vec![EntryClass::OAuth2Account],
     ..Default::default()
 });
+
+pub static IDM_ACP_AUTOMOUNT_READ: LazyLock<BuiltinAcp> = LazyLock::new(|| BuiltinAcp {
+    classes: vec![
+        EntryClass::Object,
+        EntryClass::AccessControlProfile,
+        EntryClass::AccessControlSearch,
+    ],
+    name: "idm_acp_automount_read",
+    uuid: UUID_IDM_ACP_AUTOMOUNT_READ,
+    description: "Builtin IDM Control for reading automount maps. Required for UNIX clients.",
+    receiver: BuiltinAcpReceiver::Group(vec![UUID_IDM_UNIX_AUTHENTICATION_READ]),
+    target: BuiltinAcpTarget::Filter(ProtoFilter::And(vec![
+        ProtoFilter::Or(vec![
+            match_class_filter!(EntryClass::AutomountMap),
+            match_class_filter!(EntryClass::Automount),
+        ]),
+        FILTER_ANDNOT_TOMBSTONE_OR_RECYCLED.clone(),
+    ])),
+    search_attrs: vec![
+        Attribute::Class,
+        Attribute::Uuid,
+        Attribute::Name,
+        Attribute::Description,
+        Attribute::AutomountKey,
+        Attribute::AutomountInformation,
+        Attribute::Refers,
+    ],
+    ..Default::default()
+});
+
+pub static IDM_ACP_AUTOMOUNT_MANAGE: LazyLock<BuiltinAcp> = LazyLock::new(|| BuiltinAcp {
+    classes: vec![
+        EntryClass::Object,
+        EntryClass::AccessControlProfile,
+        EntryClass::AccessControlCreate,
+        EntryClass::AccessControlDelete,
+        EntryClass::AccessControlModify,
+        EntryClass::AccessControlSearch,
+    ],
+    name: "idm_acp_automount_manage",
+    uuid: UUID_IDM_ACP_AUTOMOUNT_MANAGE,
+    description: "Builtin IDM Control for creating and deleting automount maps and their entries.",
+    receiver: BuiltinAcpReceiver::Group(vec![UUID_IDM_UNIX_ADMINS]),
+    target: BuiltinAcpTarget::Filter(ProtoFilter::And(vec![
+        ProtoFilter::Or(vec![
+            match_class_filter!(EntryClass::AutomountMap),
+            match_class_filter!(EntryClass::Automount),
+        ]),
+        FILTER_ANDNOT_TOMBSTONE_OR_RECYCLED.clone(),
+    ])),
+    search_attrs: vec![
+        Attribute::Class,
+        Attribute::Uuid,
+        Attribute::Name,
+        Attribute::Description,
+        Attribute::AutomountKey,
+        Attribute::AutomountInformation,
+        Attribute::Refers,
+    ],
+    modify_removed_attrs: vec![
+        Attribute::Name,
+        Attribute::Description,
+        Attribute::AutomountKey,
+        Attribute::AutomountInformation,
+    ],
+    modify_present_attrs: vec![
+        Attribute::Name,
+        Attribute::Description,
+        Attribute::AutomountKey,
+        Attribute::AutomountInformation,
+    ],
+    create_attrs: vec![
+        Attribute::Class,
+        Attribute::Name,
+        Attribute::Description,
+        Attribute::AutomountKey,
+        Attribute::AutomountInformation,
+        Attribute::Refers,
+    ],
+    create_classes: vec![
+        EntryClass::Object,
+        EntryClass::AutomountMap,
+        EntryClass::Automount,
+    ],
+    ..Default::default()
+});
+
+pub static IDM_ACP_CREATION_TEMPLATE_MANAGE: LazyLock<BuiltinAcp> = LazyLock::new(|| {
+    BuiltinAcp {
+    classes: vec![
+        EntryClass::Object,
+        EntryClass::AccessControlProfile,
+        EntryClass::AccessControlCreate,
+        EntryClass::AccessControlDelete,
+        EntryClass::AccessControlModify,
+        EntryClass::AccessControlSearch,
+    ],
+    name: "idm_acp_creation_template_manage",
+    uuid: UUID_IDM_ACP_CREATION_TEMPLATE_MANAGE,
+    description: "Builtin IDM Control for managing the templates applied when creating persons and service accounts.",
+    receiver: BuiltinAcpReceiver::Group(vec![UUID_IDM_ADMINS]),
+    target: BuiltinAcpTarget::Filter(ProtoFilter::And(vec![
+        match_class_filter!(EntryClass::CreationTemplate),
+        FILTER_ANDNOT_TOMBSTONE_OR_RECYCLED.clone(),
+    ])),
+    search_attrs: vec![
+        Attribute::Class,
+        Attribute::Uuid,
+        Attribute::Name,
+        Attribute::Description,
+        Attribute::TemplateDefault,
+        Attribute::TemplateGroup,
+    ],
+    modify_removed_attrs: vec![
+        Attribute::Name,
+        Attribute::Description,
+        Attribute::TemplateDefault,
+        Attribute::TemplateGroup,
+    ],
+    modify_present_attrs: vec![
+        Attribute::Name,
+        Attribute::Description,
+        Attribute::TemplateDefault,
+        Attribute::TemplateGroup,
+    ],
+    create_attrs: vec![
+        Attribute::Class,
+        Attribute::Name,
+        Attribute::Description,
+        Attribute::TemplateDefault,
+        Attribute::TemplateGroup,
+    ],
+    create_classes: vec![EntryClass::Object, EntryClass::CreationTemplate],
+    ..Default::default()
+}
+});
+
+pub static IDM_ACP_WEBHOOK_SUBSCRIPTION_MANAGE: LazyLock<BuiltinAcp> =
+    LazyLock::new(|| BuiltinAcp {
+        classes: vec![
+            EntryClass::Object,
+            EntryClass::AccessControlProfile,
+            EntryClass::AccessControlCreate,
+            EntryClass::AccessControlDelete,
+            EntryClass::AccessControlModify,
+            EntryClass::AccessControlSearch,
+        ],
+        name: "idm_acp_webhook_subscription_manage",
+        uuid: UUID_IDM_ACP_WEBHOOK_SUBSCRIPTION_MANAGE,
+        description: "Builtin IDM Control for managing webhook subscriptions.",
+        receiver: BuiltinAcpReceiver::Group(vec![UUID_IDM_ADMINS]),
+        target: BuiltinAcpTarget::Filter(ProtoFilter::And(vec![
+            match_class_filter!(EntryClass::WebhookSubscription),
+            FILTER_ANDNOT_TOMBSTONE_OR_RECYCLED.clone(),
+        ])),
+        search_attrs: vec![
+            Attribute::Class,
+            Attribute::Uuid,
+            Attribute::Name,
+            Attribute::Description,
+            Attribute::WebhookUrl,
+            Attribute::WebhookFilter,
+            Attribute::WebhookEvent,
+            Attribute::WebhookSecret,
+            Attribute::WebhookAuthor,
+        ],
+        modify_removed_attrs: vec![
+            Attribute::Name,
+            Attribute::Description,
+            Attribute::WebhookUrl,
+            Attribute::WebhookFilter,
+            Attribute::WebhookEvent,
+            Attribute::WebhookSecret,
+        ],
+        modify_present_attrs: vec![
+            Attribute::Name,
+            Attribute::Description,
+            Attribute::WebhookUrl,
+            Attribute::WebhookFilter,
+            Attribute::WebhookEvent,
+        ],
+        create_attrs: vec![
+            Attribute::Class,
+            Attribute::Name,
+            Attribute::Description,
+            Attribute::WebhookUrl,
+            Attribute::WebhookFilter,
+            Attribute::WebhookEvent,
+        ],
+        create_classes: vec![EntryClass::Object, EntryClass::WebhookSubscription],
+        ..Default::default()
+    });
@@ -370,6 +370,16 @@ pub static BUILTIN_GROUP_OAUTH2_ACCOUNT_ADMINS: LazyLock<BuiltinGroup> =
         ..Default::default()
     });
 
+/// Builtin IDM Group for reading the records of changes made to the database.
+pub static BUILTIN_GROUP_AUDIT_READERS: LazyLock<BuiltinGroup> = LazyLock::new(|| BuiltinGroup {
+    name: "idm_audit_readers",
+    description: "Builtin IDM Group for reading the audit records of changes to the database.",
+    uuid: UUID_IDM_AUDIT_READERS,
+    entry_managed_by: Some(UUID_IDM_ADMINS),
+    members: Vec::with_capacity(0),
+    ..Default::default()
+});
+
 // at some point vs code just gives up on syntax highlighting inside ...
 
 pub static IDM_ALL_PERSONS: LazyLock<BuiltinGroup> = LazyLock::new(|| BuiltinGroup {
@@ -471,6 +481,7 @@ pub static IDM_HIGH_PRIVILEGE_DL8: LazyLock<BuiltinGroup> = LazyLock::new(|| {
             UUID_IDM_MESSAGE_SENDERS,
             UUID_IDM_OAUTH2_CLIENT_ADMINS,
             UUID_IDM_OAUTH2_ACCOUNT_ADMINS,
+            UUID_IDM_AUDIT_READERS,
             UUID_IDM_HIGH_PRIVILEGE,
         ],
         ..Default::default()
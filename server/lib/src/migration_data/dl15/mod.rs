@@ -129,6 +129,46 @@ pub fn phase_1_schema_attrs() -> Vec<SchemaAttribute> {
         SCHEMA_ATTR_PASSWORD_CHANGED_TIME.clone(),
         SCHEMA_ATTR_OAUTH2_REFRESH_TOKEN_EXPIRY.clone(),
         SCHEMA_ATTR_DOMAIN_ALLOW_ACCOUNT_RECOVERY.clone(),
+        SCHEMA_ATTR_DOMAIN_DENY_MEMBERSHIP_CYCLES.clone(),
+        // DL15
+        SCHEMA_ATTR_OAUTH2_ACCESS_TOKEN_EXPIRY.clone(),
+        SCHEMA_ATTR_OAUTH2_REQUIRE_PUSHED_AUTHORISATION.clone(),
+        SCHEMA_ATTR_AUTH_PASSWORD_MINIMUM_SCORE.clone(),
+        SCHEMA_ATTR_AUTH_PASSWORD_BREACH_CHECK.clone(),
+        SCHEMA_ATTR_PASSWORD_HISTORY.clone(),
+        SCHEMA_ATTR_AUTH_PASSWORD_HISTORY_LENGTH.clone(),
+        SCHEMA_ATTR_AUTH_LOCKOUT_MAX_ATTEMPTS.clone(),
+        SCHEMA_ATTR_AUTH_LOCKOUT_DURATION.clone(),
+        SCHEMA_ATTR_CREDENTIAL_LOCKOUT.clone(),
+        SCHEMA_ATTR_CREDENTIAL_MAX_AGE.clone(),
+        SCHEMA_ATTR_PASSKEY_USAGE.clone(),
+        SCHEMA_ATTR_APPLICATION_PASSWORD_LIMIT.clone(),
+        SCHEMA_ATTR_APPLICATION_PASSWORD_AUTO_REVOKE.clone(),
+        SCHEMA_ATTR_GID_NUMBER_RANGE.clone(),
+        SCHEMA_ATTR_GID_NUMBER_RANGE_NAME.clone(),
+        SCHEMA_ATTR_UNIX_HOME_DIRECTORY_TEMPLATE.clone(),
+        SCHEMA_ATTR_UNIX_DEFAULT_SHELL.clone(),
+        SCHEMA_ATTR_UNIX_HOME_DIRECTORY.clone(),
+        SCHEMA_ATTR_AUTOMOUNT_KEY.clone(),
+        SCHEMA_ATTR_AUTOMOUNT_INFORMATION.clone(),
+        SCHEMA_ATTR_RADIUS_VLAN_ID.clone(),
+        SCHEMA_ATTR_RADIUS_VLAN_PRIORITY.clone(),
+        SCHEMA_ATTR_AUDIT_ACTOR.clone(),
+        SCHEMA_ATTR_AUDIT_TARGET.clone(),
+        SCHEMA_ATTR_AUDIT_OPERATION.clone(),
+        SCHEMA_ATTR_AUDIT_ATTRIBUTE.clone(),
+        SCHEMA_ATTR_AUDIT_TIME.clone(),
+        SCHEMA_ATTR_DYNGROUP_FILTER_EXCLUDE.clone(),
+        SCHEMA_ATTR_DOMAIN_SELF_WRITE_ATTRS.clone(),
+        SCHEMA_ATTR_DOMAIN_RENAME_FROM.clone(),
+        SCHEMA_ATTR_MEMBER_EXPIRY.clone(),
+        SCHEMA_ATTR_TEMPLATE_DEFAULT.clone(),
+        SCHEMA_ATTR_TEMPLATE_GROUP.clone(),
+        SCHEMA_ATTR_WEBHOOK_URL.clone(),
+        SCHEMA_ATTR_WEBHOOK_FILTER.clone(),
+        SCHEMA_ATTR_WEBHOOK_EVENT.clone(),
+        SCHEMA_ATTR_WEBHOOK_SECRET.clone(),
+        SCHEMA_ATTR_WEBHOOK_AUTHOR.clone(),
     ]
 }
 
@@ -178,6 +218,12 @@ pub fn phase_2_schema_classes() -> Vec<SchemaClass> {
         SCHEMA_CLASS_ASSERTION_NONCE.clone(),
         SCHEMA_CLASS_KEY_OBJECT_JWT_HS256_DL6.clone(),
         SCHEMA_CLASS_ASSERTION_NONCE.clone(),
+        // DL15
+        SCHEMA_CLASS_AUTOMOUNT_MAP.clone(),
+        SCHEMA_CLASS_AUTOMOUNT.clone(),
+        SCHEMA_CLASS_AUDIT_RECORD.clone(),
+        SCHEMA_CLASS_CREATION_TEMPLATE.clone(),
+        SCHEMA_CLASS_WEBHOOK_SUBSCRIPTION.clone(),
     ]
 }
 
@@ -242,6 +288,7 @@ pub fn phase_6_builtin_non_admin_entries() -> Result<Vec<EntryInitNew>, Operatio
         BUILTIN_GROUP_MESSAGE_SENDERS.clone().try_into()?,
         BUILTIN_GROUP_OAUTH2_CLIENT_ADMINS.clone().try_into()?,
         BUILTIN_GROUP_OAUTH2_ACCOUNT_ADMINS.clone().try_into()?,
+        BUILTIN_GROUP_AUDIT_READERS.clone().try_into()?,
         // Write deps on read.clone().try_into()?, so write must be added first.
         // All members must exist before we write HP
         IDM_HIGH_PRIVILEGE_DL8.clone().try_into()?,
@@ -318,6 +365,14 @@ pub fn phase_7_builtin_access_control_profiles() -> Vec<EntryInitNew> {
         IDM_ACP_OAUTH2_ACCOUNT_ENROL.clone().into(),
         // DL13
         IDM_ACP_OAUTH2_MANAGE_BASIC.clone().into(),
+        // DL15
+        IDM_ACP_AUTOMOUNT_READ.clone().into(),
+        IDM_ACP_AUTOMOUNT_MANAGE.clone().into(),
+        IDM_ACP_GROUP_RADIUS_MANAGE.clone().into(),
+        IDM_ACP_AUDIT_READ.clone().into(),
+        IDM_ACP_SELF_WRITE_POLICY.clone().into(),
+        IDM_ACP_CREATION_TEMPLATE_MANAGE.clone().into(),
+        IDM_ACP_WEBHOOK_SUBSCRIPTION_MANAGE.clone().into(),
     ]
 }
 
@@ -1,5 +1,7 @@
 //! Schema Entries
 use crate::prelude::*;
+use crate::schema::{ReferenceDeletePolicy, Replicated};
+use crate::server::webhook::WebhookEventKind;
 
 pub static SCHEMA_ATTR_DISPLAYNAME_DL7: LazyLock<SchemaAttribute> =
     LazyLock::new(|| SchemaAttribute {
@@ -238,6 +240,96 @@ pub static SCHEMA_ATTR_AUTH_PASSWORD_MINIMUM_LENGTH: LazyLock<SchemaAttribute> =
         ..Default::default()
     });
 
+pub static SCHEMA_ATTR_AUTH_PASSWORD_MINIMUM_SCORE: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_AUTH_PASSWORD_MINIMUM_SCORE,
+        name: Attribute::AuthPasswordMinimumScore,
+        description: "Minimum zxcvbn strength score (0 to 4) of passwords".to_string(),
+        syntax: SyntaxType::Uint32,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_AUTH_PASSWORD_BREACH_CHECK: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_AUTH_PASSWORD_BREACH_CHECK,
+        name: Attribute::AuthPasswordBreachCheck,
+        description: "Reject passwords found in the server's breached password list".to_string(),
+        syntax: SyntaxType::Boolean,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_AUTH_PASSWORD_HISTORY_LENGTH: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_AUTH_PASSWORD_HISTORY_LENGTH,
+        name: Attribute::AuthPasswordHistoryLength,
+        description: "Number of recent passwords that may not be reused".to_string(),
+        syntax: SyntaxType::Uint32,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_PASSWORD_HISTORY: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_PASSWORD_HISTORY,
+        name: Attribute::PasswordHistory,
+        description: "Hashes of an account's previous passwords, used to prevent reuse".to_string(),
+        replicated: Replicated::False,
+        syntax: SyntaxType::PrivateBinary,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_AUTH_LOCKOUT_MAX_ATTEMPTS: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_AUTH_LOCKOUT_MAX_ATTEMPTS,
+        name: Attribute::AuthLockoutMaxAttempts,
+        description: "Number of failed authentication attempts before a credential is locked"
+            .to_string(),
+        syntax: SyntaxType::Uint32,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_AUTH_LOCKOUT_DURATION: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_AUTH_LOCKOUT_DURATION,
+        name: Attribute::AuthLockoutDuration,
+        description: "Number of seconds a credential remains locked after too many failures"
+            .to_string(),
+        syntax: SyntaxType::Uint32,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_CREDENTIAL_LOCKOUT: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_CREDENTIAL_LOCKOUT,
+        name: Attribute::CredentialLockout,
+        description: "The credentials of an account that are locked, and when they unlock"
+            .to_string(),
+        multivalue: true,
+        syntax: SyntaxType::CredentialLockout,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_CREDENTIAL_MAX_AGE: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_CREDENTIAL_MAX_AGE,
+        name: Attribute::CredentialMaxAge,
+        description: "Maximum age in seconds of a password or api token before it must be replaced"
+            .to_string(),
+        syntax: SyntaxType::Uint32,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_PASSKEY_USAGE: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_PASSKEY_USAGE,
+        name: Attribute::PasskeyUsage,
+        description: "When each passkey of an account was last used, and its sign count"
+            .to_string(),
+        multivalue: true,
+        replicated: Replicated::False,
+        syntax: SyntaxType::PasskeyUsage,
+        ..Default::default()
+    });
+
 pub static SCHEMA_ATTR_LOGINSHELL: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     uuid: UUID_SCHEMA_ATTR_LOGINSHELL,
     name: Attribute::LoginShell,
@@ -383,6 +475,7 @@ pub static SCHEMA_ATTR_OAUTH2_RS_SCOPE_MAP: LazyLock<SchemaAttribute> =
         indexed: true,
         multivalue: true,
         syntax: SyntaxType::OauthScopeMap,
+        on_delete: ReferenceDeletePolicy::Cascade,
         ..Default::default()
     });
 
@@ -551,6 +644,16 @@ pub static SCHEMA_ATTR_DYNGROUP_FILTER: LazyLock<SchemaAttribute> =
         ..Default::default()
     });
 
+pub static SCHEMA_ATTR_DYNGROUP_FILTER_EXCLUDE: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_DYNGROUP_FILTER_EXCLUDE,
+        name: Attribute::DynGroupFilterExclude,
+        description: "A filter describing the set of entries to exclude from a dynamic group"
+            .to_string(),
+        syntax: SyntaxType::JsonFilter,
+        ..Default::default()
+    });
+
 pub static SCHEMA_ATTR_OAUTH2_PREFER_SHORT_USERNAME: LazyLock<SchemaAttribute> =
     LazyLock::new(|| SchemaAttribute {
         uuid: UUID_SCHEMA_ATTR_OAUTH2_PREFER_SHORT_USERNAME,
@@ -570,6 +673,25 @@ pub static SCHEMA_ATTR_OAUTH2_REFRESH_TOKEN_EXPIRY: LazyLock<SchemaAttribute> =
         ..Default::default()
     });
 
+pub static SCHEMA_ATTR_OAUTH2_ACCESS_TOKEN_EXPIRY: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_OAUTH2_ACCESS_TOKEN_EXPIRY,
+        name: Attribute::OAuth2AccessTokenExpiry,
+        description: "Defines the duration in seconds that an access token is valid for."
+            .to_string(),
+        syntax: SyntaxType::Uint32,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_OAUTH2_REQUIRE_PUSHED_AUTHORISATION: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_OAUTH2_REQUIRE_PUSHED_AUTHORISATION,
+        name: Attribute::OAuth2RequirePushedAuthorisation,
+        description: "Represents if the client must use pushed authorisation requests.".to_string(),
+        syntax: SyntaxType::Boolean,
+        ..Default::default()
+    });
+
 pub static SCHEMA_ATTR_API_TOKEN_SESSION: LazyLock<SchemaAttribute> =
     LazyLock::new(|| SchemaAttribute {
         uuid: UUID_SCHEMA_ATTR_API_TOKEN_SESSION,
@@ -806,6 +928,124 @@ pub static SCHEMA_ATTR_DOMAIN_ALLOW_ACCOUNT_RECOVERY: LazyLock<SchemaAttribute>
         ..Default::default()
     });
 
+pub static SCHEMA_ATTR_DOMAIN_DENY_MEMBERSHIP_CYCLES: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_DOMAIN_DENY_MEMBERSHIP_CYCLES,
+        name: Attribute::DomainDenyMembershipCycles,
+        description: "A flag to reject changes that would create a group membership cycle."
+            .to_string(),
+        syntax: SyntaxType::Boolean,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_DOMAIN_SELF_WRITE_ATTRS: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_DOMAIN_SELF_WRITE_ATTRS,
+        name: Attribute::DomainSelfWriteAttrs,
+        description: "The attributes that people are allowed to modify on their own entry."
+            .to_string(),
+        multivalue: true,
+        syntax: SyntaxType::Utf8StringInsensitive,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_DOMAIN_RENAME_FROM: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_DOMAIN_RENAME_FROM,
+        name: Attribute::DomainRenameFrom,
+        description: "The previous domain name while a domain rename migration is in progress."
+            .to_string(),
+        syntax: SyntaxType::Utf8StringIname,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_MEMBER_EXPIRY: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_MEMBER_EXPIRY,
+        name: Attribute::MemberExpiry,
+        description: "When a member of a group is removed from the group".to_string(),
+        multivalue: true,
+        indexed: true,
+        syntax: SyntaxType::MemberExpiry,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_TEMPLATE_DEFAULT: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_TEMPLATE_DEFAULT,
+        name: Attribute::TemplateDefault,
+        description:
+            "A default value applied by a creation template, in the form 'attribute: value'"
+                .to_string(),
+        multivalue: true,
+        syntax: SyntaxType::Utf8String,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_TEMPLATE_GROUP: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_TEMPLATE_GROUP,
+        name: Attribute::TemplateGroup,
+        description: "The name of a group that entries created from a creation template join"
+            .to_string(),
+        multivalue: true,
+        syntax: SyntaxType::Utf8StringIname,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_WEBHOOK_URL: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_WEBHOOK_URL,
+    name: Attribute::WebhookUrl,
+    description: "The url that webhook notifications are delivered to".to_string(),
+    syntax: SyntaxType::Url,
+    ..Default::default()
+});
+
+pub static SCHEMA_ATTR_WEBHOOK_FILTER: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_WEBHOOK_FILTER,
+        name: Attribute::WebhookFilter,
+        description: "A filter describing the set of entries that a webhook is notified about"
+            .to_string(),
+        syntax: SyntaxType::JsonFilter,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_WEBHOOK_AUTHOR: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_WEBHOOK_AUTHOR,
+        name: Attribute::WebhookAuthor,
+        description: "The identity whose access a webhook subscription is delivered with"
+            .to_string(),
+        syntax: SyntaxType::Uuid,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_WEBHOOK_EVENT: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_WEBHOOK_EVENT,
+        name: Attribute::WebhookEvent,
+        description: "The kinds of entry change that a webhook is notified about".to_string(),
+        multivalue: true,
+        syntax: SyntaxType::Utf8StringInsensitive,
+        allowed_values: Some(
+            WebhookEventKind::ALL
+                .iter()
+                .map(|kind| kind.as_str().to_string())
+                .collect(),
+        ),
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_WEBHOOK_SECRET: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_WEBHOOK_SECRET,
+        name: Attribute::WebhookSecret,
+        description: "The secret used to sign the notifications delivered to a webhook".to_string(),
+        syntax: SyntaxType::SecretUtf8String,
+        ..Default::default()
+    });
+
 pub static SCHEMA_ATTR_IMAGE: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     uuid: UUID_SCHEMA_ATTR_IMAGE,
     name: Attribute::Image,
@@ -874,6 +1114,30 @@ pub static SCHEMA_ATTR_APPLICATION_URL: LazyLock<SchemaAttribute> =
         ..Default::default()
     });
 
+pub static SCHEMA_ATTR_APPLICATION_PASSWORD_LIMIT: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_APPLICATION_PASSWORD_LIMIT,
+        name: Attribute::ApplicationPasswordLimit,
+        description:
+            "The maximum number of application passwords an account may hold for an application"
+                .to_string(),
+        syntax: SyntaxType::Uint32,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_APPLICATION_PASSWORD_AUTO_REVOKE: LazyLock<SchemaAttribute> = LazyLock::new(
+    || {
+        SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_APPLICATION_PASSWORD_AUTO_REVOKE,
+        name: Attribute::ApplicationPasswordAutoRevoke,
+        description: "Revoke the oldest application password rather than refusing a new one when the limit is reached"
+            .to_string(),
+        syntax: SyntaxType::Boolean,
+        ..Default::default()
+    }
+    },
+);
+
 pub static SCHEMA_ATTR_MESSAGE_TEMPLATE: LazyLock<SchemaAttribute> = LazyLock::new(|| {
     SchemaAttribute {
     uuid: UUID_SCHEMA_ATTR_MESSAGE_TEMPLATE,
@@ -1123,10 +1387,13 @@ pub static SCHEMA_CLASS_GROUP_DL6: LazyLock<SchemaClass> = LazyLock::new(|| Sche
     sync_allowed: true,
     systemmay: vec![
         Attribute::Member,
+        Attribute::MemberExpiry,
         Attribute::GrantUiHint,
         Attribute::Description,
         Attribute::Mail,
         Attribute::Name,
+        Attribute::RadiusVlanId,
+        Attribute::RadiusVlanPriority,
     ],
     systemmust: vec![Attribute::Spn],
     ..Default::default()
@@ -1138,7 +1405,7 @@ pub static SCHEMA_CLASS_DYNGROUP: LazyLock<SchemaClass> = LazyLock::new(|| Schem
     description: "Object representation of a dynamic group".to_string(),
 
     systemmust: vec![Attribute::DynGroupFilter],
-    systemmay: vec![Attribute::DynMember],
+    systemmay: vec![Attribute::DynMember, Attribute::DynGroupFilterExclude],
     systemsupplements: vec![EntryClass::Group.into()],
     ..Default::default()
 });
@@ -1152,6 +1419,12 @@ pub static SCHEMA_CLASS_ACCOUNT_POLICY_DL8: LazyLock<SchemaClass> = LazyLock::ne
         Attribute::AuthSessionExpiry,
         Attribute::PrivilegeExpiry,
         Attribute::AuthPasswordMinimumLength,
+        Attribute::AuthPasswordMinimumScore,
+        Attribute::AuthPasswordBreachCheck,
+        Attribute::AuthPasswordHistoryLength,
+        Attribute::AuthLockoutMaxAttempts,
+        Attribute::AuthLockoutDuration,
+        Attribute::CredentialMaxAge,
         Attribute::CredentialTypeMinimum,
         Attribute::WebauthnAttestationCaList,
         Attribute::LimitSearchMaxResults,
@@ -1174,6 +1447,9 @@ pub static SCHEMA_CLASS_ACCOUNT_DL5: LazyLock<SchemaClass> = LazyLock::new(|| Sc
         Attribute::NameHistory,
         Attribute::HmacNameHistory,
         Attribute::AccountSoftlockExpire,
+        Attribute::PasswordHistory,
+        Attribute::CredentialLockout,
+        Attribute::PasskeyUsage,
     ],
     systemmust: vec![Attribute::DisplayName, Attribute::Spn],
     systemsupplements: vec![
@@ -1222,6 +1498,150 @@ pub static SCHEMA_CLASS_SYNC_ACCOUNT_DL7: LazyLock<SchemaClass> = LazyLock::new(
     ..Default::default()
 });
 
+pub static SCHEMA_ATTR_GID_NUMBER_RANGE: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_GID_NUMBER_RANGE,
+        name: Attribute::GidNumberRange,
+        description:
+            "A named range that gid numbers may be allocated from, in the form name:start-end"
+                .to_string(),
+        multivalue: true,
+        syntax: SyntaxType::Utf8StringInsensitive,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_GID_NUMBER_RANGE_NAME: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_GID_NUMBER_RANGE_NAME,
+        name: Attribute::GidNumberRangeName,
+        description:
+            "The name of the domain gid number range to allocate this entry's gid number from"
+                .to_string(),
+        syntax: SyntaxType::Utf8StringInsensitive,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_UNIX_HOME_DIRECTORY_TEMPLATE: LazyLock<SchemaAttribute> = LazyLock::new(
+    || {
+        SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_UNIX_HOME_DIRECTORY_TEMPLATE,
+        name: Attribute::UnixHomeDirectoryTemplate,
+        description: "A template for POSIX home directories, such as /home/{spn}. The variables {name}, {spn} and {uuid} are available".to_string(),
+        syntax: SyntaxType::Utf8String,
+        ..Default::default()
+    }
+    },
+);
+
+pub static SCHEMA_ATTR_UNIX_DEFAULT_SHELL: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_UNIX_DEFAULT_SHELL,
+        name: Attribute::UnixDefaultShell,
+        description: "The login shell for POSIX accounts that do not define their own".to_string(),
+        syntax: SyntaxType::Utf8StringInsensitive,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_UNIX_HOME_DIRECTORY: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_UNIX_HOME_DIRECTORY,
+        name: Attribute::UnixHomeDirectory,
+        description: "A POSIX user's home directory, overriding any home directory template"
+            .to_string(),
+        sync_allowed: true,
+        syntax: SyntaxType::Utf8String,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_AUTOMOUNT_KEY: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_AUTOMOUNT_KEY,
+        name: Attribute::AutomountKey,
+        description: "The key of an automount entry, such as a mount point or /-".to_string(),
+        indexed: true,
+        syntax: SyntaxType::Utf8String,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_RADIUS_VLAN_ID: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_RADIUS_VLAN_ID,
+        name: Attribute::RadiusVlanId,
+        description: "The VLAN that RADIUS assigns to members of this group".to_string(),
+        syntax: SyntaxType::Uint32,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_RADIUS_VLAN_PRIORITY: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_RADIUS_VLAN_PRIORITY,
+        name: Attribute::RadiusVlanPriority,
+        description:
+            "The priority of this group's RADIUS VLAN, where the highest wins. Defaults to 0"
+                .to_string(),
+        syntax: SyntaxType::Uint32,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_AUTOMOUNT_INFORMATION: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_AUTOMOUNT_INFORMATION,
+        name: Attribute::AutomountInformation,
+        description: "The mount options and location of an automount entry".to_string(),
+        syntax: SyntaxType::Utf8String,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_AUDIT_ACTOR: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_AUDIT_ACTOR,
+    name: Attribute::AuditActor,
+    description: "The uuid of the identity that performed an audited operation".to_string(),
+    indexed: true,
+    syntax: SyntaxType::Uuid,
+    ..Default::default()
+});
+
+pub static SCHEMA_ATTR_AUDIT_TARGET: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_AUDIT_TARGET,
+        name: Attribute::AuditTarget,
+        description: "The uuids of the entries affected by an audited operation".to_string(),
+        indexed: true,
+        multivalue: true,
+        syntax: SyntaxType::Uuid,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_AUDIT_OPERATION: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_AUDIT_OPERATION,
+        name: Attribute::AuditOperation,
+        description: "The type of an audited operation, such as create, modify or delete"
+            .to_string(),
+        indexed: true,
+        syntax: SyntaxType::Utf8StringInsensitive,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_AUDIT_ATTRIBUTE: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        uuid: UUID_SCHEMA_ATTR_AUDIT_ATTRIBUTE,
+        name: Attribute::AuditAttribute,
+        description: "The attributes changed by an audited operation".to_string(),
+        multivalue: true,
+        syntax: SyntaxType::Utf8StringInsensitive,
+        ..Default::default()
+    });
+
+pub static SCHEMA_ATTR_AUDIT_TIME: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
+    uuid: UUID_SCHEMA_ATTR_AUDIT_TIME,
+    name: Attribute::AuditTime,
+    description: "The time at which an audited operation was performed".to_string(),
+    indexed: true,
+    syntax: SyntaxType::DateTime,
+    ..Default::default()
+});
+
 pub static SCHEMA_CLASS_DOMAIN_INFO_DL10: LazyLock<SchemaClass> = LazyLock::new(|| SchemaClass {
     uuid: UUID_SCHEMA_CLASS_DOMAIN_INFO,
     name: EntryClass::DomainInfo.into(),
@@ -1237,7 +1657,13 @@ pub static SCHEMA_CLASS_DOMAIN_INFO_DL10: LazyLock<SchemaClass> = LazyLock::new(
         Attribute::DomainDevelopmentTaint,
         Attribute::DomainAllowEasterEggs,
         Attribute::DomainAllowAccountRecovery,
+        Attribute::DomainDenyMembershipCycles,
+        Attribute::DomainSelfWriteAttrs,
+        Attribute::DomainRenameFrom,
         Attribute::DomainDisplayName,
+        Attribute::GidNumberRange,
+        Attribute::UnixHomeDirectoryTemplate,
+        Attribute::UnixDefaultShell,
     ],
     systemmust: vec![
         Attribute::Name,
@@ -1254,6 +1680,11 @@ pub static SCHEMA_CLASS_POSIXGROUP: LazyLock<SchemaClass> = LazyLock::new(|| Sch
     description: "Object representation of a posix group, requires group".to_string(),
 
     sync_allowed: true,
+    systemmay: vec![
+        Attribute::GidNumberRangeName,
+        Attribute::UnixHomeDirectoryTemplate,
+        Attribute::UnixDefaultShell,
+    ],
     systemmust: vec![Attribute::GidNumber],
     systemsupplements: vec![EntryClass::Group.into()],
     ..Default::default()
@@ -1265,7 +1696,12 @@ pub static SCHEMA_CLASS_POSIXACCOUNT: LazyLock<SchemaClass> = LazyLock::new(|| S
     description: "Object representation of a posix account, requires account".to_string(),
 
     sync_allowed: true,
-    systemmay: vec![Attribute::LoginShell, Attribute::UnixPassword],
+    systemmay: vec![
+        Attribute::LoginShell,
+        Attribute::UnixPassword,
+        Attribute::GidNumberRangeName,
+        Attribute::UnixHomeDirectory,
+    ],
     systemmust: vec![Attribute::GidNumber],
     systemsupplements: vec![EntryClass::Account.into()],
     ..Default::default()
@@ -1298,6 +1734,8 @@ pub static SCHEMA_CLASS_OAUTH2_RS_DL9: LazyLock<SchemaClass> = LazyLock::new(||
         Attribute::OAuth2JwtLegacyCryptoEnable,
         Attribute::OAuth2PreferShortUsername,
         Attribute::OAuth2RefreshTokenExpiry,
+        Attribute::OAuth2AccessTokenExpiry,
+        Attribute::OAuth2RequirePushedAuthorisation,
         Attribute::Image,
         Attribute::OAuth2RsClaimMap,
         Attribute::OAuth2Session,
@@ -1477,7 +1915,11 @@ pub static SCHEMA_CLASS_APPLICATION: LazyLock<SchemaClass> = LazyLock::new(|| Sc
 
     description: "The class representing an application".to_string(),
     systemmust: vec![Attribute::LinkedGroup],
-    systemmay: vec![Attribute::ApplicationUrl],
+    systemmay: vec![
+        Attribute::ApplicationUrl,
+        Attribute::ApplicationPasswordLimit,
+        Attribute::ApplicationPasswordAutoRevoke,
+    ],
     // I think this could change before release - I can see a world
     // when we may want an oauth2 application to have application passwords,
     // or for this to be it's own thing. But service accounts also don't
@@ -1539,3 +1981,74 @@ pub static SCHEMA_CLASS_ASSERTION_NONCE: LazyLock<SchemaClass> = LazyLock::new(|
     ..Default::default()
 }
 });
+
+pub static SCHEMA_CLASS_AUTOMOUNT_MAP: LazyLock<SchemaClass> = LazyLock::new(|| SchemaClass {
+    uuid: UUID_SCHEMA_CLASS_AUTOMOUNT_MAP,
+    name: EntryClass::AutomountMap.into(),
+    description: "An automount map, such as auto.master, holding automount entries".to_string(),
+    systemmust: vec![Attribute::Name],
+    systemmay: vec![Attribute::Description],
+    ..Default::default()
+});
+
+pub static SCHEMA_CLASS_AUTOMOUNT: LazyLock<SchemaClass> = LazyLock::new(|| SchemaClass {
+    uuid: UUID_SCHEMA_CLASS_AUTOMOUNT,
+    name: EntryClass::Automount.into(),
+    description: "An automount entry, belonging to the automount map it refers to".to_string(),
+    systemmust: vec![
+        Attribute::AutomountKey,
+        Attribute::AutomountInformation,
+        Attribute::Refers,
+    ],
+    systemmay: vec![Attribute::Description],
+    ..Default::default()
+});
+
+pub static SCHEMA_CLASS_AUDIT_RECORD: LazyLock<SchemaClass> = LazyLock::new(|| SchemaClass {
+    uuid: UUID_SCHEMA_CLASS_AUDIT_RECORD,
+    name: EntryClass::AuditRecord.into(),
+    description: "A record of a change made to the database by an identity".to_string(),
+    systemmust: vec![
+        Attribute::AuditActor,
+        Attribute::AuditOperation,
+        Attribute::AuditTarget,
+        Attribute::AuditTime,
+        Attribute::DeleteAfter,
+    ],
+    systemmay: vec![Attribute::AuditAttribute],
+    ..Default::default()
+});
+
+pub static SCHEMA_CLASS_CREATION_TEMPLATE: LazyLock<SchemaClass> = LazyLock::new(|| SchemaClass {
+    uuid: UUID_SCHEMA_CLASS_CREATION_TEMPLATE,
+    name: EntryClass::CreationTemplate.into(),
+    description: "Default values and group memberships applied to new persons and service accounts"
+        .to_string(),
+    systemmust: vec![Attribute::Name],
+    systemmay: vec![
+        Attribute::Description,
+        Attribute::TemplateDefault,
+        Attribute::TemplateGroup,
+    ],
+    ..Default::default()
+});
+
+pub static SCHEMA_CLASS_WEBHOOK_SUBSCRIPTION: LazyLock<SchemaClass> =
+    LazyLock::new(|| SchemaClass {
+        uuid: UUID_SCHEMA_CLASS_WEBHOOK_SUBSCRIPTION,
+        name: EntryClass::WebhookSubscription.into(),
+        description: "A subscription that delivers notifications of entry changes to a webhook"
+            .to_string(),
+        systemmust: vec![
+            Attribute::Name,
+            Attribute::WebhookUrl,
+            Attribute::WebhookEvent,
+            Attribute::WebhookSecret,
+        ],
+        systemmay: vec![
+            Attribute::Description,
+            Attribute::WebhookFilter,
+            Attribute::WebhookAuthor,
+        ],
+        ..Default::default()
+    });
@@ -31,6 +31,14 @@ pub fn attributes() -> Vec<SchemaAttribute> {
         SCHEMA_ATTR_SUPPLEMENTS.clone(),
         SCHEMA_ATTR_SYSTEM_EXCLUDES.clone(),
         SCHEMA_ATTR_EXCLUDES.clone(),
+        SCHEMA_ATTR_ATTR_EXCLUDES.clone(),
+        SCHEMA_ATTR_REQUIRES_ONE_OF.clone(),
+        SCHEMA_ATTR_CLASS_KIND.clone(),
+        SCHEMA_ATTR_EXCLUSION_GROUP.clone(),
+        SCHEMA_ATTR_ALLOWED_VALUE.clone(),
+        SCHEMA_ATTR_ON_DELETE.clone(),
+        SCHEMA_ATTR_AUDIT.clone(),
+        SCHEMA_ATTR_UNIQUE_SCOPE.clone(),
         SCHEMA_ATTR_ACP_ENABLE.clone(),
         SCHEMA_ATTR_ACP_RECEIVER.clone(),
         SCHEMA_ATTR_ACP_RECEIVER_GROUP.clone(),
@@ -77,6 +85,7 @@ pub fn attributes() -> Vec<SchemaAttribute> {
         SCHEMA_ATTR_UID_NUMBER.clone(),
         SCHEMA_ATTR_SUDO_HOST.clone(),
         SCHEMA_ATTR_HOME_DIRECTORY.clone(),
+        SCHEMA_ATTR_AUTOMOUNT_MAP_NAME.clone(),
     ]
 }
 
@@ -1,5 +1,5 @@
 use crate::prelude::*;
-use crate::schema::Replicated;
+use crate::schema::{ReferenceDeletePolicy, Replicated, UniqueScope};
 
 pub static SCHEMA_ATTR_CLASS: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::Class,
@@ -7,11 +7,18 @@ pub static SCHEMA_ATTR_CLASS: LazyLock<SchemaAttribute> = LazyLock::new(|| Schem
     description: String::from("The set of classes defining an object"),
     multivalue: true,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: true,
     syntax: SyntaxType::Utf8StringInsensitive,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_UUID: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::Uuid,
@@ -21,11 +28,18 @@ pub static SCHEMA_ATTR_UUID: LazyLock<SchemaAttribute> = LazyLock::new(|| Schema
     // Uniqueness is handled by base.rs, not attrunique here due to
     // needing to check recycled objects too.
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: true,
     syntax: SyntaxType::Uuid,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_SOURCE_UUID: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::SourceUuid,
@@ -37,11 +51,18 @@ pub static SCHEMA_ATTR_SOURCE_UUID: LazyLock<SchemaAttribute> = LazyLock::new(||
     // Uniqueness is handled by base.rs, not attrunique here due to
     // needing to check recycled objects too.
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: true,
     syntax: SyntaxType::Uuid,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_CREATED_AT_CID: LazyLock<SchemaAttribute> =
     LazyLock::new(|| SchemaAttribute {
@@ -52,11 +73,18 @@ pub static SCHEMA_ATTR_CREATED_AT_CID: LazyLock<SchemaAttribute> =
         // Uniqueness is handled by base.rs, not attrunique here due to
         // needing to check recycled objects too.
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: false,
         sync_allowed: false,
         replicated: Replicated::False,
         indexed: false,
         syntax: SyntaxType::Cid,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 pub static SCHEMA_ATTR_LAST_MODIFIED_CID: LazyLock<SchemaAttribute> =
     LazyLock::new(|| SchemaAttribute {
@@ -67,11 +95,18 @@ pub static SCHEMA_ATTR_LAST_MODIFIED_CID: LazyLock<SchemaAttribute> =
         // Uniqueness is handled by base.rs, not attrunique here due to
         // needing to check recycled objects too.
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: false,
         sync_allowed: false,
         replicated: Replicated::False,
         indexed: false,
         syntax: SyntaxType::Cid,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 pub static SCHEMA_ATTR_NAME: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::Name,
@@ -79,11 +114,18 @@ pub static SCHEMA_ATTR_NAME: LazyLock<SchemaAttribute> = LazyLock::new(|| Schema
     description: String::from("The shortform name of an object"),
     multivalue: false,
     unique: true,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: true,
     replicated: Replicated::True,
     indexed: true,
     syntax: SyntaxType::Utf8StringIname,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_SPN: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::Spn,
@@ -93,11 +135,18 @@ pub static SCHEMA_ATTR_SPN: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaA
     ),
     multivalue: false,
     unique: true,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: true,
     syntax: SyntaxType::SecurityPrincipalName,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_ATTRIBUTE_NAME: LazyLock<SchemaAttribute> =
     LazyLock::new(|| SchemaAttribute {
@@ -106,11 +155,18 @@ pub static SCHEMA_ATTR_ATTRIBUTE_NAME: LazyLock<SchemaAttribute> =
         description: String::from("The name of a schema attribute"),
         multivalue: false,
         unique: true,
+        unique_scope: UniqueScope::All,
         phantom: false,
         sync_allowed: false,
         replicated: Replicated::True,
         indexed: true,
         syntax: SyntaxType::Utf8StringInsensitive,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 pub static SCHEMA_ATTR_CLASS_NAME: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::ClassName,
@@ -118,11 +174,18 @@ pub static SCHEMA_ATTR_CLASS_NAME: LazyLock<SchemaAttribute> = LazyLock::new(||
     description: String::from("The name of a schema class"),
     multivalue: false,
     unique: true,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: true,
     syntax: SyntaxType::Utf8StringInsensitive,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_DESCRIPTION: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::Description,
@@ -130,11 +193,18 @@ pub static SCHEMA_ATTR_DESCRIPTION: LazyLock<SchemaAttribute> = LazyLock::new(||
     description: String::from("A description of an attribute, object or class"),
     multivalue: false,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: true,
     replicated: Replicated::True,
     indexed: false,
     syntax: SyntaxType::Utf8String,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_MULTI_VALUE: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::MultiValue,
@@ -144,11 +214,18 @@ pub static SCHEMA_ATTR_MULTI_VALUE: LazyLock<SchemaAttribute> = LazyLock::new(||
     ),
     multivalue: false,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: false,
     syntax: SyntaxType::Boolean,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_PHANTOM: LazyLock<SchemaAttribute> = LazyLock::new(|| {
     SchemaAttribute {
@@ -157,11 +234,18 @@ pub static SCHEMA_ATTR_PHANTOM: LazyLock<SchemaAttribute> = LazyLock::new(|| {
                 description: String::from("If true, this attribute must NOT be present in any may/must sets of a class as. This represents generated attributes."),
                 multivalue: false,
                 unique: false,
+                unique_scope: UniqueScope::All,
                 phantom: false,
                 sync_allowed: false,
                 replicated: Replicated::True,
                 indexed: false,
                 syntax: SyntaxType::Boolean,
+                system: true,
+                exclusion_group: None,
+                allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
             }
 });
 pub static SCHEMA_ATTR_SYNC_ALLOWED: LazyLock<SchemaAttribute> =
@@ -173,11 +257,18 @@ pub static SCHEMA_ATTR_SYNC_ALLOWED: LazyLock<SchemaAttribute> =
         ),
         multivalue: false,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: false,
         sync_allowed: false,
         replicated: Replicated::True,
         indexed: false,
         syntax: SyntaxType::Boolean,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 pub static SCHEMA_ATTR_REPLICATED: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::Replicated,
@@ -187,11 +278,18 @@ pub static SCHEMA_ATTR_REPLICATED: LazyLock<SchemaAttribute> = LazyLock::new(||
     ),
     multivalue: false,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: false,
     syntax: SyntaxType::Boolean,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_UNIQUE: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::Unique,
@@ -201,11 +299,18 @@ pub static SCHEMA_ATTR_UNIQUE: LazyLock<SchemaAttribute> = LazyLock::new(|| Sche
     ),
     multivalue: false,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: false,
     syntax: SyntaxType::Boolean,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_INDEX: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::Index,
@@ -213,11 +318,18 @@ pub static SCHEMA_ATTR_INDEX: LazyLock<SchemaAttribute> = LazyLock::new(|| Schem
     description: String::from("Describe the indexes to apply to instances of this attribute."),
     multivalue: true,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: false,
     syntax: SyntaxType::IndexId,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_INDEXED: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::Indexed,
@@ -227,11 +339,18 @@ pub static SCHEMA_ATTR_INDEXED: LazyLock<SchemaAttribute> = LazyLock::new(|| Sch
     ),
     multivalue: false,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: false,
     syntax: SyntaxType::Boolean,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_SYNTAX: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::Syntax,
@@ -241,11 +360,18 @@ pub static SCHEMA_ATTR_SYNTAX: LazyLock<SchemaAttribute> = LazyLock::new(|| Sche
     ),
     multivalue: false,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: false,
     syntax: SyntaxType::SyntaxId,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_SYSTEM_MAY: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::SystemMay,
@@ -255,11 +381,18 @@ pub static SCHEMA_ATTR_SYSTEM_MAY: LazyLock<SchemaAttribute> = LazyLock::new(||
     ),
     multivalue: true,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: false,
     syntax: SyntaxType::Utf8StringInsensitive,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_MAY: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::May,
@@ -269,11 +402,18 @@ pub static SCHEMA_ATTR_MAY: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaA
     ),
     multivalue: true,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: false,
     syntax: SyntaxType::Utf8StringInsensitive,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_SYSTEM_MUST: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::SystemMust,
@@ -283,11 +423,18 @@ pub static SCHEMA_ATTR_SYSTEM_MUST: LazyLock<SchemaAttribute> = LazyLock::new(||
     ),
     multivalue: true,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: false,
     syntax: SyntaxType::Utf8StringInsensitive,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_MUST: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::Must,
@@ -297,11 +444,18 @@ pub static SCHEMA_ATTR_MUST: LazyLock<SchemaAttribute> = LazyLock::new(|| Schema
     ),
     multivalue: true,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: false,
     syntax: SyntaxType::Utf8StringInsensitive,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_SYSTEM_SUPPLEMENTS: LazyLock<SchemaAttribute> = LazyLock::new(|| {
     SchemaAttribute {
@@ -312,11 +466,18 @@ pub static SCHEMA_ATTR_SYSTEM_SUPPLEMENTS: LazyLock<SchemaAttribute> = LazyLock:
                 ),
                 multivalue: true,
                 unique: false,
+                unique_scope: UniqueScope::All,
                 phantom: false,
                 sync_allowed: false,
                 replicated: Replicated::True,
                 indexed: false,
                 syntax: SyntaxType::Utf8StringInsensitive,
+                system: true,
+                exclusion_group: None,
+                allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
             }
 });
 pub static SCHEMA_ATTR_SUPPLEMENTS: LazyLock<SchemaAttribute> = LazyLock::new(|| {
@@ -328,11 +489,18 @@ pub static SCHEMA_ATTR_SUPPLEMENTS: LazyLock<SchemaAttribute> = LazyLock::new(||
                 ),
                 multivalue: true,
                 unique: false,
+                unique_scope: UniqueScope::All,
                 phantom: false,
                 sync_allowed: false,
                 replicated: Replicated::True,
                 indexed: false,
                 syntax: SyntaxType::Utf8StringInsensitive,
+                system: true,
+                exclusion_group: None,
+                allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
             }
 });
 pub static SCHEMA_ATTR_SYSTEM_EXCLUDES: LazyLock<SchemaAttribute> =
@@ -344,11 +512,18 @@ pub static SCHEMA_ATTR_SYSTEM_EXCLUDES: LazyLock<SchemaAttribute> =
         ),
         multivalue: true,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: false,
         sync_allowed: false,
         replicated: Replicated::True,
         indexed: false,
         syntax: SyntaxType::Utf8StringInsensitive,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 pub static SCHEMA_ATTR_EXCLUDES: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::Excludes,
@@ -358,11 +533,204 @@ pub static SCHEMA_ATTR_EXCLUDES: LazyLock<SchemaAttribute> = LazyLock::new(|| Sc
     ),
     multivalue: true,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: false,
     syntax: SyntaxType::Utf8StringInsensitive,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
+});
+pub static SCHEMA_ATTR_ATTR_EXCLUDES: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        name: Attribute::AttrExcludes,
+        uuid: UUID_SCHEMA_ATTR_ATTR_EXCLUDES,
+        description: String::from(
+            "A set of attributes that are denied presence in connection to this class",
+        ),
+        multivalue: true,
+        unique: false,
+        unique_scope: UniqueScope::All,
+        phantom: false,
+        sync_allowed: false,
+        replicated: Replicated::True,
+        indexed: false,
+        syntax: SyntaxType::Utf8StringInsensitive,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
+    });
+pub static SCHEMA_ATTR_REQUIRES_ONE_OF: LazyLock<SchemaAttribute> = LazyLock::new(|| {
+    SchemaAttribute {
+        name: Attribute::RequiresOneOf,
+        uuid: UUID_SCHEMA_ATTR_REQUIRES_ONE_OF,
+        description: String::from(
+            "Comma separated groups of attributes that require at least one member present in connection to this class",
+        ),
+        multivalue: true,
+        unique: false,
+        unique_scope: UniqueScope::All,
+        phantom: false,
+        sync_allowed: false,
+        replicated: Replicated::True,
+        indexed: false,
+        syntax: SyntaxType::Utf8StringInsensitive,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
+    }
+});
+
+pub static SCHEMA_ATTR_CLASS_KIND: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
+    name: Attribute::ClassKind,
+    uuid: UUID_SCHEMA_ATTR_CLASS_KIND,
+    description: String::from(
+        "If this class is structural, auxiliary or abstract, as understood by LDAP",
+    ),
+    multivalue: false,
+    unique: false,
+    unique_scope: UniqueScope::All,
+    phantom: false,
+    sync_allowed: false,
+    replicated: Replicated::True,
+    indexed: false,
+    syntax: SyntaxType::Utf8StringInsensitive,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
+});
+
+pub static SCHEMA_ATTR_EXCLUSION_GROUP: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        name: Attribute::ExclusionGroup,
+        uuid: UUID_SCHEMA_ATTR_EXCLUSION_GROUP,
+        description: String::from(
+            "The name of a group of attributes that may not be present together on an entry",
+        ),
+        multivalue: false,
+        unique: false,
+        unique_scope: UniqueScope::All,
+        phantom: false,
+        sync_allowed: false,
+        replicated: Replicated::True,
+        indexed: false,
+        syntax: SyntaxType::Utf8StringInsensitive,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
+    });
+
+pub static SCHEMA_ATTR_ALLOWED_VALUE: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        name: Attribute::AllowedValue,
+        uuid: UUID_SCHEMA_ATTR_ALLOWED_VALUE,
+        description: String::from(
+            "A value that a string attribute may hold. If present, no other values are permitted",
+        ),
+        multivalue: true,
+        unique: false,
+        unique_scope: UniqueScope::All,
+        phantom: false,
+        sync_allowed: false,
+        replicated: Replicated::True,
+        indexed: false,
+        syntax: SyntaxType::Utf8String,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
+    });
+
+pub static SCHEMA_ATTR_AUDIT: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
+    name: Attribute::Audit,
+    uuid: UUID_SCHEMA_ATTR_AUDIT,
+    description: String::from(
+        "If true, changes to this attribute are always recorded in the audit log",
+    ),
+    multivalue: false,
+    unique: false,
+    unique_scope: UniqueScope::All,
+    phantom: false,
+    sync_allowed: false,
+    replicated: Replicated::True,
+    indexed: false,
+    syntax: SyntaxType::Boolean,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
+});
+
+pub static SCHEMA_ATTR_ON_DELETE: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
+    name: Attribute::OnDelete,
+    uuid: UUID_SCHEMA_ATTR_ON_DELETE,
+    description: String::from(
+        "What happens to entries holding a reference when the referenced entry is deleted",
+    ),
+    multivalue: false,
+    unique: false,
+    unique_scope: UniqueScope::All,
+    phantom: false,
+    sync_allowed: false,
+    replicated: Replicated::True,
+    indexed: false,
+    syntax: SyntaxType::Utf8StringInsensitive,
+    system: true,
+    exclusion_group: None,
+    allowed_values: Some(
+        ReferenceDeletePolicy::ALL
+            .iter()
+            .map(|policy| policy.as_str().to_string())
+            .collect(),
+    ),
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
+});
+
+pub static SCHEMA_ATTR_UNIQUE_SCOPE: LazyLock<SchemaAttribute> = LazyLock::new(|| {
+    SchemaAttribute {
+        name: Attribute::UniqueScope,
+        uuid: UUID_SCHEMA_ATTR_UNIQUE_SCOPE,
+        description: String::from(
+            "The classes of entries that the values of a unique attribute must be unique amongst, or per_sync_parent to make values unique within each sync agreement. If absent, values are unique amongst all entries",
+        ),
+        multivalue: true,
+        unique: false,
+        unique_scope: UniqueScope::All,
+        phantom: false,
+        sync_allowed: false,
+        replicated: Replicated::True,
+        indexed: false,
+        syntax: SyntaxType::Utf8StringInsensitive,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
+    }
 });
 
 // SYSINFO attrs
@@ -374,11 +742,18 @@ pub static SCHEMA_ATTR_ACP_ENABLE: LazyLock<SchemaAttribute> = LazyLock::new(||
                 description: String::from("A flag to determine if this ACP is active for application. True is enabled, and enforced. False is checked but not enforced."),
                 multivalue: false,
                 unique: false,
+                unique_scope: UniqueScope::All,
                 phantom: false,
                 sync_allowed: false,
                 replicated: Replicated::True,
                 indexed: true,
                 syntax: SyntaxType::Boolean,
+                system: true,
+                exclusion_group: None,
+                allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
             }
 });
 
@@ -389,11 +764,18 @@ pub static SCHEMA_ATTR_ACP_RECEIVER: LazyLock<SchemaAttribute> =
         description: String::from("Who the ACP applies to, constraining or allowing operations."),
         multivalue: false,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: false,
         sync_allowed: false,
         replicated: Replicated::True,
         indexed: true,
         syntax: SyntaxType::JsonFilter,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 pub static SCHEMA_ATTR_ACP_RECEIVER_GROUP: LazyLock<SchemaAttribute> =
     LazyLock::new(|| SchemaAttribute {
@@ -402,11 +784,18 @@ pub static SCHEMA_ATTR_ACP_RECEIVER_GROUP: LazyLock<SchemaAttribute> =
         description: String::from("The group that receives this access control to allow access"),
         multivalue: true,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: false,
         sync_allowed: false,
         replicated: Replicated::True,
         indexed: true,
         syntax: SyntaxType::ReferenceUuid,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 
 pub static SCHEMA_ATTR_ACP_TARGET_SCOPE: LazyLock<SchemaAttribute> =
@@ -418,11 +807,18 @@ pub static SCHEMA_ATTR_ACP_TARGET_SCOPE: LazyLock<SchemaAttribute> =
         ),
         multivalue: false,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: false,
         sync_allowed: false,
         replicated: Replicated::True,
         indexed: true,
         syntax: SyntaxType::JsonFilter,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 pub static SCHEMA_ATTR_ACP_SEARCH_ATTR: LazyLock<SchemaAttribute> =
     LazyLock::new(|| SchemaAttribute {
@@ -433,11 +829,18 @@ pub static SCHEMA_ATTR_ACP_SEARCH_ATTR: LazyLock<SchemaAttribute> =
         ),
         multivalue: true,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: false,
         sync_allowed: false,
         replicated: Replicated::True,
         indexed: true,
         syntax: SyntaxType::Utf8StringInsensitive,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 pub static SCHEMA_ATTR_ACP_CREATE_CLASS: LazyLock<SchemaAttribute> =
     LazyLock::new(|| SchemaAttribute {
@@ -446,11 +849,18 @@ pub static SCHEMA_ATTR_ACP_CREATE_CLASS: LazyLock<SchemaAttribute> =
         description: String::from("The set of classes that can be created on a new entry."),
         multivalue: true,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: false,
         sync_allowed: false,
         replicated: Replicated::True,
         indexed: true,
         syntax: SyntaxType::Utf8StringInsensitive,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 pub static SCHEMA_ATTR_ACP_CREATE_ATTR: LazyLock<SchemaAttribute> =
     LazyLock::new(|| SchemaAttribute {
@@ -459,11 +869,18 @@ pub static SCHEMA_ATTR_ACP_CREATE_ATTR: LazyLock<SchemaAttribute> =
         description: String::from("The set of attribute types that can be created on an entry."),
         multivalue: true,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: false,
         sync_allowed: false,
         replicated: Replicated::True,
         indexed: true,
         syntax: SyntaxType::Utf8StringInsensitive,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 
 pub static SCHEMA_ATTR_ACP_MODIFY_REMOVED_ATTR: LazyLock<SchemaAttribute> =
@@ -475,11 +892,18 @@ pub static SCHEMA_ATTR_ACP_MODIFY_REMOVED_ATTR: LazyLock<SchemaAttribute> =
         ),
         multivalue: true,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: false,
         sync_allowed: false,
         replicated: Replicated::True,
         indexed: true,
         syntax: SyntaxType::Utf8StringInsensitive,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 pub static SCHEMA_ATTR_ACP_MODIFY_PRESENT_ATTR: LazyLock<SchemaAttribute> =
     LazyLock::new(|| SchemaAttribute {
@@ -490,11 +914,18 @@ pub static SCHEMA_ATTR_ACP_MODIFY_PRESENT_ATTR: LazyLock<SchemaAttribute> =
         ),
         multivalue: true,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: false,
         sync_allowed: false,
         replicated: Replicated::True,
         indexed: true,
         syntax: SyntaxType::Utf8StringInsensitive,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 pub static SCHEMA_ATTR_ACP_MODIFY_CLASS: LazyLock<SchemaAttribute> = LazyLock::new(|| {
     SchemaAttribute {
@@ -503,11 +934,18 @@ pub static SCHEMA_ATTR_ACP_MODIFY_CLASS: LazyLock<SchemaAttribute> = LazyLock::n
                 description: String::from("The set of class values that could be asserted or added to an entry. Only applies to modify::present operations on class."),
                 multivalue: true,
                 unique: false,
+                unique_scope: UniqueScope::All,
                 phantom: false,
                 sync_allowed: false,
                 replicated: Replicated::True,
                 indexed: true,
                 syntax: SyntaxType::Utf8StringInsensitive,
+                system: true,
+                exclusion_group: None,
+                allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
             }
 });
 pub static SCHEMA_ATTR_ACP_MODIFY_PRESENT_CLASS: LazyLock<SchemaAttribute> = LazyLock::new(|| {
@@ -517,11 +955,18 @@ pub static SCHEMA_ATTR_ACP_MODIFY_PRESENT_CLASS: LazyLock<SchemaAttribute> = Laz
                     description: String::from("The set of class values that could be asserted or added to an entry. Only applies to modify::present operations on class."),
                     multivalue: true,
                     unique: false,
+                    unique_scope: UniqueScope::All,
                     phantom: false,
                     sync_allowed: false,
                     replicated: Replicated::True,
                     indexed: false,
                     syntax: SyntaxType::Utf8StringInsensitive,
+                    system: true,
+                    exclusion_group: None,
+                    allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
                 }
 });
 pub static SCHEMA_ATTR_ACP_MODIFY_REMOVE_CLASS: LazyLock<SchemaAttribute> = LazyLock::new(|| {
@@ -531,11 +976,18 @@ pub static SCHEMA_ATTR_ACP_MODIFY_REMOVE_CLASS: LazyLock<SchemaAttribute> = Lazy
                     description: String::from("The set of class values that could be asserted or added to an entry. Only applies to modify::remove operations on class."),
                     multivalue: true,
                     unique: false,
+                    unique_scope: UniqueScope::All,
                     phantom: false,
                     sync_allowed: false,
                     replicated: Replicated::True,
                     indexed: false,
                     syntax: SyntaxType::Utf8StringInsensitive,
+                    system: true,
+                    exclusion_group: None,
+                    allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
                 }
 });
 pub static SCHEMA_ATTR_ENTRY_MANAGED_BY: LazyLock<SchemaAttribute> =
@@ -547,11 +999,18 @@ pub static SCHEMA_ATTR_ENTRY_MANAGED_BY: LazyLock<SchemaAttribute> =
         ),
         multivalue: false,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: false,
         sync_allowed: false,
         replicated: Replicated::True,
         indexed: true,
         syntax: SyntaxType::ReferenceUuid,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::BlockDelete,
+        audit: false,
+        custom_validator: None,
     });
 // MO/Member
 pub static SCHEMA_ATTR_MEMBER_OF: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
@@ -560,11 +1019,18 @@ pub static SCHEMA_ATTR_MEMBER_OF: LazyLock<SchemaAttribute> = LazyLock::new(|| S
     description: String::from("reverse group membership of the object"),
     multivalue: true,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::False,
     indexed: true,
     syntax: SyntaxType::ReferenceUuid,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_DIRECT_MEMBER_OF: LazyLock<SchemaAttribute> =
     LazyLock::new(|| SchemaAttribute {
@@ -573,11 +1039,18 @@ pub static SCHEMA_ATTR_DIRECT_MEMBER_OF: LazyLock<SchemaAttribute> =
         description: String::from("reverse direct group membership of the object"),
         multivalue: true,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: false,
         sync_allowed: false,
         replicated: Replicated::False,
         indexed: true,
         syntax: SyntaxType::ReferenceUuid,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 pub static SCHEMA_ATTR_RECYCLED_DIRECT_MEMBER_OF: LazyLock<SchemaAttribute> = LazyLock::new(|| {
     SchemaAttribute {
@@ -586,6 +1059,7 @@ pub static SCHEMA_ATTR_RECYCLED_DIRECT_MEMBER_OF: LazyLock<SchemaAttribute> = La
                 description: String::from("recycled reverse direct group membership of the object to assist in revive operations."),
                 multivalue: true,
                 unique: false,
+                unique_scope: UniqueScope::All,
                 phantom: false,
                 sync_allowed: false,
                 // Unlike DMO this must be replicated so that on a recycle event, these groups
@@ -595,6 +1069,12 @@ pub static SCHEMA_ATTR_RECYCLED_DIRECT_MEMBER_OF: LazyLock<SchemaAttribute> = La
                 replicated: Replicated::True,
                 indexed: true,
                 syntax: SyntaxType::ReferenceUuid,
+                system: true,
+                exclusion_group: None,
+                allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
             }
 });
 pub static SCHEMA_ATTR_MEMBER: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
@@ -603,11 +1083,18 @@ pub static SCHEMA_ATTR_MEMBER: LazyLock<SchemaAttribute> = LazyLock::new(|| Sche
     description: String::from("List of members of the group"),
     multivalue: true,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: true,
     replicated: Replicated::True,
     indexed: true,
     syntax: SyntaxType::ReferenceUuid,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_DYN_MEMBER: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::DynMember,
@@ -615,11 +1102,18 @@ pub static SCHEMA_ATTR_DYN_MEMBER: LazyLock<SchemaAttribute> = LazyLock::new(||
     description: String::from("List of dynamic members of the group"),
     multivalue: true,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: true,
     replicated: Replicated::False,
     indexed: true,
     syntax: SyntaxType::ReferenceUuid,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 
 pub static SCHEMA_ATTR_REFERS: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
@@ -628,11 +1122,18 @@ pub static SCHEMA_ATTR_REFERS: LazyLock<SchemaAttribute> = LazyLock::new(|| Sche
     description: String::from("A reference to another object"),
     multivalue: false,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: true,
     syntax: SyntaxType::ReferenceUuid,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 
 pub static SCHEMA_ATTR_CASCADE_DELETED: LazyLock<SchemaAttribute> = LazyLock::new(|| {
@@ -642,6 +1143,7 @@ pub static SCHEMA_ATTR_CASCADE_DELETED: LazyLock<SchemaAttribute> = LazyLock::ne
                 description: String::from("A marker attribute denoting that this entry was deleted by cascade when this UUID was deleted."),
                 multivalue: false,
                 unique: false,
+                unique_scope: UniqueScope::All,
                 phantom: false,
                 sync_allowed: false,
                 replicated: Replicated::True,
@@ -649,6 +1151,12 @@ pub static SCHEMA_ATTR_CASCADE_DELETED: LazyLock<SchemaAttribute> = LazyLock::ne
                 // NOTE: This has to be Uuid so that referential integrity doesn't consider
                 // this value in its operation.
                 syntax: SyntaxType::Uuid,
+                system: true,
+                exclusion_group: None,
+                allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
             }
 });
 
@@ -659,11 +1167,18 @@ pub static SCHEMA_ATTR_VERSION: LazyLock<SchemaAttribute> = LazyLock::new(|| Sch
     description: String::from("The systems internal migration version for provided objects"),
     multivalue: false,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: false,
     syntax: SyntaxType::Uint32,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 // Domain for sysinfo
 pub static SCHEMA_ATTR_DOMAIN: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
@@ -672,11 +1187,18 @@ pub static SCHEMA_ATTR_DOMAIN: LazyLock<SchemaAttribute> = LazyLock::new(|| Sche
     description: String::from("A DNS Domain name entry."),
     multivalue: true,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: true,
     syntax: SyntaxType::Utf8StringIname,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_CLAIM: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::Claim,
@@ -684,11 +1206,18 @@ pub static SCHEMA_ATTR_CLAIM: LazyLock<SchemaAttribute> = LazyLock::new(|| Schem
     description: String::from("The string identifier of an extracted claim that can be filtered"),
     multivalue: true,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: true,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: false,
     syntax: SyntaxType::Utf8StringInsensitive,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_SCOPE: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::Scope,
@@ -696,11 +1225,18 @@ pub static SCHEMA_ATTR_SCOPE: LazyLock<SchemaAttribute> = LazyLock::new(|| Schem
     description: String::from("The string identifier of a permission scope in a session"),
     multivalue: true,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: true,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: false,
     syntax: SyntaxType::Utf8StringInsensitive,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 
 // External Scim Sync
@@ -713,11 +1249,18 @@ pub static SCHEMA_ATTR_SYNC_EXTERNAL_ID: LazyLock<SchemaAttribute> =
         ),
         multivalue: false,
         unique: true,
+        unique_scope: UniqueScope::All,
         phantom: false,
         sync_allowed: false,
         replicated: Replicated::True,
         indexed: true,
         syntax: SyntaxType::Utf8StringInsensitive,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 pub static SCHEMA_ATTR_SYNC_PARENT_UUID: LazyLock<SchemaAttribute> =
     LazyLock::new(|| SchemaAttribute {
@@ -726,11 +1269,18 @@ pub static SCHEMA_ATTR_SYNC_PARENT_UUID: LazyLock<SchemaAttribute> =
         description: String::from("The UUID of the parent sync agreement that created this entry."),
         multivalue: false,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: false,
         sync_allowed: false,
         replicated: Replicated::True,
         indexed: true,
         syntax: SyntaxType::ReferenceUuid,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 pub static SCHEMA_ATTR_SYNC_CLASS: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::SyncClass,
@@ -738,11 +1288,18 @@ pub static SCHEMA_ATTR_SYNC_CLASS: LazyLock<SchemaAttribute> = LazyLock::new(||
     description: String::from("The set of classes requested by the sync client."),
     multivalue: true,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: false,
     sync_allowed: false,
     replicated: Replicated::True,
     indexed: false,
     syntax: SyntaxType::Utf8StringInsensitive,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 
 pub static SCHEMA_ATTR_PASSWORD_IMPORT: LazyLock<SchemaAttribute> =
@@ -752,11 +1309,18 @@ pub static SCHEMA_ATTR_PASSWORD_IMPORT: LazyLock<SchemaAttribute> =
         description: String::from("An imported password hash from an external system."),
         multivalue: false,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: true,
         sync_allowed: true,
         replicated: Replicated::False,
         indexed: false,
         syntax: SyntaxType::Utf8String,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 
 pub static SCHEMA_ATTR_UNIX_PASSWORD_IMPORT: LazyLock<SchemaAttribute> =
@@ -766,11 +1330,18 @@ pub static SCHEMA_ATTR_UNIX_PASSWORD_IMPORT: LazyLock<SchemaAttribute> =
         description: String::from("An imported unix password hash from an external system."),
         multivalue: false,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: true,
         sync_allowed: true,
         replicated: Replicated::False,
         indexed: false,
         syntax: SyntaxType::Utf8String,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 
 pub static SCHEMA_ATTR_TOTP_IMPORT: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
@@ -779,11 +1350,18 @@ pub static SCHEMA_ATTR_TOTP_IMPORT: LazyLock<SchemaAttribute> = LazyLock::new(||
     description: String::from("An imported totp secret from an external system."),
     multivalue: true,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: true,
     sync_allowed: true,
     replicated: Replicated::False,
     indexed: false,
     syntax: SyntaxType::TotpSecret,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 
 // LDAP Masking Phantoms
@@ -793,11 +1371,18 @@ pub static SCHEMA_ATTR_DN: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAt
     description: String::from("An LDAP Compatible DN"),
     multivalue: false,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: true,
     sync_allowed: false,
     replicated: Replicated::False,
     indexed: false,
     syntax: SyntaxType::Utf8StringInsensitive,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_ENTRY_DN: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::EntryDn,
@@ -805,11 +1390,18 @@ pub static SCHEMA_ATTR_ENTRY_DN: LazyLock<SchemaAttribute> = LazyLock::new(|| Sc
     description: String::from("An LDAP Compatible EntryDN"),
     multivalue: false,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: true,
     sync_allowed: false,
     replicated: Replicated::False,
     indexed: false,
     syntax: SyntaxType::Utf8StringInsensitive,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_ENTRY_UUID: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::EntryUuid,
@@ -817,11 +1409,18 @@ pub static SCHEMA_ATTR_ENTRY_UUID: LazyLock<SchemaAttribute> = LazyLock::new(||
     description: String::from("An LDAP Compatible entryUUID"),
     multivalue: false,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: true,
     sync_allowed: false,
     replicated: Replicated::False,
     indexed: false,
     syntax: SyntaxType::Uuid,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_OBJECT_CLASS: LazyLock<SchemaAttribute> =
     LazyLock::new(|| SchemaAttribute {
@@ -830,11 +1429,18 @@ pub static SCHEMA_ATTR_OBJECT_CLASS: LazyLock<SchemaAttribute> =
         description: String::from("An LDAP Compatible objectClass"),
         multivalue: true,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: true,
         sync_allowed: false,
         replicated: Replicated::False,
         indexed: false,
         syntax: SyntaxType::Utf8StringInsensitive,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 pub static SCHEMA_ATTR_CN: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::Cn,
@@ -842,11 +1448,18 @@ pub static SCHEMA_ATTR_CN: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAt
     description: String::from("An LDAP Compatible objectClass"),
     multivalue: false,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: true,
     sync_allowed: false,
     replicated: Replicated::False,
     indexed: false,
     syntax: SyntaxType::Utf8StringIname,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_LDAP_KEYS: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::LdapKeys, // keys
@@ -854,11 +1467,18 @@ pub static SCHEMA_ATTR_LDAP_KEYS: LazyLock<SchemaAttribute> = LazyLock::new(|| S
     description: String::from("An LDAP Compatible keys (ssh)"),
     multivalue: true,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: true,
     sync_allowed: false,
     replicated: Replicated::False,
     indexed: false,
     syntax: SyntaxType::SshKey,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_LDAP_SSH_PUBLIC_KEYS: LazyLock<SchemaAttribute> =
     LazyLock::new(|| SchemaAttribute {
@@ -867,11 +1487,18 @@ pub static SCHEMA_ATTR_LDAP_SSH_PUBLIC_KEYS: LazyLock<SchemaAttribute> =
         description: String::from("An LDAP Compatible sshPublicKey"),
         multivalue: true,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: true,
         sync_allowed: false,
         replicated: Replicated::False,
         indexed: false,
         syntax: SyntaxType::SshKey,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 pub static SCHEMA_ATTR_EMAIL: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::Email,
@@ -879,11 +1506,18 @@ pub static SCHEMA_ATTR_EMAIL: LazyLock<SchemaAttribute> = LazyLock::new(|| Schem
     description: String::from("An LDAP Compatible email"),
     multivalue: true,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: true,
     sync_allowed: false,
     replicated: Replicated::False,
     indexed: false,
     syntax: SyntaxType::EmailAddress,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_EMAIL_PRIMARY: LazyLock<SchemaAttribute> =
     LazyLock::new(|| SchemaAttribute {
@@ -892,11 +1526,18 @@ pub static SCHEMA_ATTR_EMAIL_PRIMARY: LazyLock<SchemaAttribute> =
         description: String::from("An LDAP Compatible primary email"),
         multivalue: false,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: true,
         sync_allowed: false,
         replicated: Replicated::False,
         indexed: false,
         syntax: SyntaxType::EmailAddress,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 pub static SCHEMA_ATTR_EMAIL_ALTERNATIVE: LazyLock<SchemaAttribute> =
     LazyLock::new(|| SchemaAttribute {
@@ -905,11 +1546,18 @@ pub static SCHEMA_ATTR_EMAIL_ALTERNATIVE: LazyLock<SchemaAttribute> =
         description: String::from("An LDAP Compatible alternative email"),
         multivalue: false,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: true,
         sync_allowed: false,
         replicated: Replicated::False,
         indexed: false,
         syntax: SyntaxType::EmailAddress,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 pub static SCHEMA_ATTR_LDAP_EMAIL_ADDRESS: LazyLock<SchemaAttribute> =
     LazyLock::new(|| SchemaAttribute {
@@ -918,11 +1566,18 @@ pub static SCHEMA_ATTR_LDAP_EMAIL_ADDRESS: LazyLock<SchemaAttribute> =
         description: String::from("An LDAP Compatible emailAddress"),
         multivalue: true,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: true,
         sync_allowed: false,
         replicated: Replicated::False,
         indexed: false,
         syntax: SyntaxType::EmailAddress,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 pub static SCHEMA_ATTR_GECOS: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::Gecos,
@@ -930,11 +1585,18 @@ pub static SCHEMA_ATTR_GECOS: LazyLock<SchemaAttribute> = LazyLock::new(|| Schem
     description: String::from("An LDAP Compatible gecos."),
     multivalue: false,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: true,
     sync_allowed: false,
     replicated: Replicated::False,
     indexed: false,
     syntax: SyntaxType::Utf8String,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_UID: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::Uid,
@@ -942,11 +1604,18 @@ pub static SCHEMA_ATTR_UID: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaA
     description: String::from("An LDAP Compatible uid."),
     multivalue: false,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: true,
     sync_allowed: false,
     replicated: Replicated::False,
     indexed: false,
     syntax: SyntaxType::Utf8String,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_UID_NUMBER: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::UidNumber,
@@ -954,11 +1623,18 @@ pub static SCHEMA_ATTR_UID_NUMBER: LazyLock<SchemaAttribute> = LazyLock::new(||
     description: String::from("An LDAP Compatible uidNumber."),
     multivalue: false,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: true,
     sync_allowed: false,
     replicated: Replicated::False,
     indexed: false,
     syntax: SyntaxType::Uint32,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_SUDO_HOST: LazyLock<SchemaAttribute> = LazyLock::new(|| SchemaAttribute {
     name: Attribute::SudoHost,
@@ -966,11 +1642,18 @@ pub static SCHEMA_ATTR_SUDO_HOST: LazyLock<SchemaAttribute> = LazyLock::new(|| S
     description: String::from("An LDAP Compatible sudohost."),
     multivalue: false,
     unique: false,
+    unique_scope: UniqueScope::All,
     phantom: true,
     sync_allowed: false,
     replicated: Replicated::False,
     indexed: false,
     syntax: SyntaxType::Utf8String,
+    system: true,
+    exclusion_group: None,
+    allowed_values: None,
+    on_delete: ReferenceDeletePolicy::RemoveReference,
+    audit: false,
+    custom_validator: None,
 });
 pub static SCHEMA_ATTR_HOME_DIRECTORY: LazyLock<SchemaAttribute> =
     LazyLock::new(|| SchemaAttribute {
@@ -979,11 +1662,38 @@ pub static SCHEMA_ATTR_HOME_DIRECTORY: LazyLock<SchemaAttribute> =
         description: String::from("An LDAP Compatible homeDirectory."),
         multivalue: false,
         unique: false,
+        unique_scope: UniqueScope::All,
         phantom: true,
         sync_allowed: false,
         replicated: Replicated::False,
         indexed: false,
         syntax: SyntaxType::Utf8String,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
+    });
+pub static SCHEMA_ATTR_AUTOMOUNT_MAP_NAME: LazyLock<SchemaAttribute> =
+    LazyLock::new(|| SchemaAttribute {
+        name: Attribute::AutomountMapName,
+        uuid: UUID_SCHEMA_ATTR_AUTOMOUNT_MAP_NAME,
+        description: String::from("An LDAP Compatible automountMapName."),
+        multivalue: false,
+        unique: false,
+        unique_scope: UniqueScope::All,
+        phantom: true,
+        sync_allowed: false,
+        replicated: Replicated::False,
+        indexed: false,
+        syntax: SyntaxType::Utf8StringInsensitive,
+        system: true,
+        exclusion_group: None,
+        allowed_values: None,
+        on_delete: ReferenceDeletePolicy::RemoveReference,
+        audit: false,
+        custom_validator: None,
     });
 // end LDAP masking phantoms
 
@@ -1001,6 +1711,11 @@ pub static SCHEMA_CLASS_ATTRIBUTE_TYPE: LazyLock<SchemaClass> = LazyLock::new(||
         Attribute::SyncAllowed,
         Attribute::Index,
         Attribute::Indexed,
+        Attribute::ExclusionGroup,
+        Attribute::AllowedValue,
+        Attribute::OnDelete,
+        Attribute::UniqueScope,
+        Attribute::Audit,
     ],
     systemmust: vec![
         Attribute::Class,
@@ -1011,6 +1726,7 @@ pub static SCHEMA_CLASS_ATTRIBUTE_TYPE: LazyLock<SchemaClass> = LazyLock::new(||
         Attribute::Description,
     ],
     systemexcludes: vec![EntryClass::ClassType.into()],
+    system: true,
     ..Default::default()
 });
 pub static SCHEMA_CLASS_CLASS_TYPE: LazyLock<SchemaClass> = LazyLock::new(|| SchemaClass {
@@ -1027,6 +1743,9 @@ pub static SCHEMA_CLASS_CLASS_TYPE: LazyLock<SchemaClass> = LazyLock::new(|| Sch
         Attribute::Supplements,
         Attribute::SystemExcludes,
         Attribute::Excludes,
+        Attribute::AttrExcludes,
+        Attribute::RequiresOneOf,
+        Attribute::ClassKind,
     ],
     systemmust: vec![
         Attribute::Class,
@@ -1034,6 +1753,7 @@ pub static SCHEMA_CLASS_CLASS_TYPE: LazyLock<SchemaClass> = LazyLock::new(|| Sch
         Attribute::Description,
     ],
     systemexcludes: vec![Attribute::AttributeType.into()],
+    system: true,
     ..Default::default()
 });
 pub static SCHEMA_CLASS_OBJECT: LazyLock<SchemaClass> = LazyLock::new(|| SchemaClass {
@@ -1052,12 +1772,14 @@ pub static SCHEMA_CLASS_OBJECT: LazyLock<SchemaClass> = LazyLock::new(|| SchemaC
         Attribute::LastModifiedCid,
         Attribute::CreatedAtCid,
     ],
+    system: true,
     ..Default::default()
 });
 pub static SCHEMA_CLASS_BUILTIN: LazyLock<SchemaClass> = LazyLock::new(|| SchemaClass {
     name: EntryClass::Builtin.into(),
     uuid: UUID_SCHEMA_CLASS_BUILTIN,
     description: String::from("A marker class denoting builtin entries"),
+    system: true,
     ..Default::default()
 });
 pub static SCHEMA_CLASS_MEMBER_OF: LazyLock<SchemaClass> = LazyLock::new(|| {
@@ -1067,6 +1789,7 @@ pub static SCHEMA_CLASS_MEMBER_OF: LazyLock<SchemaClass> = LazyLock::new(|| {
                 description: String::from(
                     "Class that is dynamically added to recipients of memberof or directmemberof. TO BE REMOVED.",
                 ),
+                system: true,
                 ..Default::default()
             }
 });
@@ -1074,6 +1797,7 @@ pub static SCHEMA_CLASS_EXTENSIBLE_OBJECT: LazyLock<SchemaClass> = LazyLock::new
     name: EntryClass::ExtensibleObject.into(),
     uuid: UUID_SCHEMA_CLASS_EXTENSIBLEOBJECT,
     description: String::from("A class type that has green hair and turns off all rules ..."),
+    system: true,
     ..Default::default()
 });
 /* These two classes are core to the entry lifecycle for recycling and tombstoning */
@@ -1083,6 +1807,7 @@ pub static SCHEMA_CLASS_RECYCLED: LazyLock<SchemaClass> = LazyLock::new(|| {
                     uuid: UUID_SCHEMA_CLASS_RECYCLED,
                     description: String::from("An object that has been deleted, but still recoverable via the revive operation. Recycled objects are not modifiable, only revivable."),
                     systemmay: vec![Attribute::RecycledDirectMemberOf, Attribute::CascadeDeleted],
+                    system: true,
                     .. Default::default()
                 }
 });
@@ -1095,6 +1820,7 @@ pub static SCHEMA_CLASS_TOMBSTONE: LazyLock<SchemaClass> = LazyLock::new(|| {
                         Attribute::Class,
                         Attribute::Uuid,
                     ],
+                    system: true,
                     .. Default::default()
                 }
 });
@@ -1104,6 +1830,7 @@ pub static SCHEMA_CLASS_CONFLICT: LazyLock<SchemaClass> = LazyLock::new(|| Schem
     description: String::from("An entry representing conflicts that occurred during replication"),
     systemmust: vec![Attribute::SourceUuid],
     systemsupplements: vec![EntryClass::Recycled.into()],
+    system: true,
     ..Default::default()
 });
 // sysinfo
@@ -1112,6 +1839,7 @@ pub static SCHEMA_CLASS_SYSTEM_INFO: LazyLock<SchemaClass> = LazyLock::new(|| Sc
     uuid: UUID_SCHEMA_CLASS_SYSTEM_INFO,
     description: String::from("System metadata object class"),
     systemmust: vec![Attribute::Version],
+    system: true,
     ..Default::default()
 });
 // ACP
@@ -1121,6 +1849,7 @@ pub static SCHEMA_CLASS_ACCESS_CONTROL_SEARCH: LazyLock<SchemaClass> =
         uuid: UUID_SCHEMA_CLASS_ACCESS_CONTROL_SEARCH,
         description: String::from("System Access Control Search Class"),
         systemmust: vec![Attribute::AcpSearchAttr],
+        system: true,
         ..Default::default()
     });
 pub static SCHEMA_CLASS_ACCESS_CONTROL_DELETE: LazyLock<SchemaClass> =
@@ -1128,6 +1857,7 @@ pub static SCHEMA_CLASS_ACCESS_CONTROL_DELETE: LazyLock<SchemaClass> =
         name: EntryClass::AccessControlDelete.into(),
         uuid: UUID_SCHEMA_CLASS_ACCESS_CONTROL_DELETE,
         description: String::from("System Access Control DELETE Class"),
+        system: true,
         ..Default::default()
     });
 pub static SCHEMA_CLASS_ACCESS_CONTROL_MODIFY: LazyLock<SchemaClass> =
@@ -1142,6 +1872,7 @@ pub static SCHEMA_CLASS_ACCESS_CONTROL_MODIFY: LazyLock<SchemaClass> =
             Attribute::AcpModifyPresentClass,
             Attribute::AcpModifyRemoveClass,
         ],
+        system: true,
         ..Default::default()
     });
 pub static SCHEMA_CLASS_ACCESS_CONTROL_CREATE: LazyLock<SchemaClass> =
@@ -1150,6 +1881,7 @@ pub static SCHEMA_CLASS_ACCESS_CONTROL_CREATE: LazyLock<SchemaClass> =
         uuid: UUID_SCHEMA_CLASS_ACCESS_CONTROL_CREATE,
         description: String::from("System Access Control Create Class"),
         systemmay: vec![Attribute::AcpCreateClass, Attribute::AcpCreateAttr],
+        system: true,
         ..Default::default()
     });
 pub static SCHEMA_CLASS_ACCESS_CONTROL_PROFILE: LazyLock<SchemaClass> =
@@ -1165,6 +1897,7 @@ pub static SCHEMA_CLASS_ACCESS_CONTROL_PROFILE: LazyLock<SchemaClass> =
             EntryClass::AccessControlModify.into(),
             EntryClass::AccessControlCreate.into(),
         ],
+        system: true,
         ..Default::default()
     });
 pub static SCHEMA_CLASS_ACCESS_CONTROL_RECEIVER_ENTRY_MANAGER: LazyLock<SchemaClass> =
@@ -1174,6 +1907,7 @@ pub static SCHEMA_CLASS_ACCESS_CONTROL_RECEIVER_ENTRY_MANAGER: LazyLock<SchemaCl
         description: String::from("System Access Control Profile Receiver - Entry Manager"),
         systemexcludes: vec![EntryClass::AccessControlReceiverGroup.into()],
         systemsupplements: vec![EntryClass::AccessControlProfile.into()],
+        system: true,
         ..Default::default()
     });
 pub static SCHEMA_CLASS_ACCESS_CONTROL_RECEIVER_GROUP: LazyLock<SchemaClass> =
@@ -1185,6 +1919,7 @@ pub static SCHEMA_CLASS_ACCESS_CONTROL_RECEIVER_GROUP: LazyLock<SchemaClass> =
         systemmust: vec![Attribute::AcpReceiverGroup],
         systemsupplements: vec![EntryClass::AccessControlProfile.into()],
         systemexcludes: vec![EntryClass::AccessControlReceiverEntryManager.into()],
+        system: true,
         ..Default::default()
     });
 pub static SCHEMA_CLASS_ACCESS_COUNTROL_TARGET_SCOPE: LazyLock<SchemaClass> =
@@ -1194,6 +1929,7 @@ pub static SCHEMA_CLASS_ACCESS_COUNTROL_TARGET_SCOPE: LazyLock<SchemaClass> =
         description: String::from("System Access Control Profile Target - Scope"),
         systemmust: vec![Attribute::AcpTargetScope],
         systemsupplements: vec![EntryClass::AccessControlProfile.into()],
+        system: true,
         ..Default::default()
     });
 
@@ -1203,6 +1939,7 @@ pub static SCHEMA_CLASS_SYSTEM: LazyLock<SchemaClass> = LazyLock::new(|| {
                 name: EntryClass::System.into(),
                 uuid: UUID_SCHEMA_CLASS_SYSTEM,
                 description: String::from("A class denoting that a type is system generated and protected. It has special internal behaviour."),
+                system: true,
                 .. Default::default()
             }
 });
@@ -1218,6 +1955,7 @@ pub static SCHEMA_CLASS_SYNC_OBJECT: LazyLock<SchemaClass> = LazyLock::new(|| {
                     Attribute::SyncExternalId,
                     Attribute::SyncClass,
                 ],
+                system: true,
                 .. Default::default()
             }
 });
@@ -191,18 +191,12 @@ impl ModifyList<ModifyInvalid> {
                         .map(|_| Modify::Present(attr.clone(), value.clone())),
                     None => Err(SchemaError::InvalidAttribute(attr.to_string())),
                 },
-                Modify::Removed(attr, value) => match schema_attributes.get(attr) {
-                    Some(schema_a) => schema_a
-                        .validate_partialvalue(attr, value)
-                        .map(|_| Modify::Removed(attr.clone(), value.clone())),
-                    None => Err(SchemaError::InvalidAttribute(attr.to_string())),
-                },
-                Modify::Assert(attr, value) => match schema_attributes.get(attr) {
-                    Some(schema_a) => schema_a
-                        .validate_partialvalue(attr, value)
-                        .map(|_| Modify::Assert(attr.clone(), value.clone())),
-                    None => Err(SchemaError::InvalidAttribute(attr.to_string())),
-                },
+                Modify::Removed(attr, value) => schema
+                    .validate_partialvalue(attr, value)
+                    .map(|_| Modify::Removed(attr.clone(), value.clone())),
+                Modify::Assert(attr, value) => schema
+                    .validate_partialvalue(attr, value)
+                    .map(|_| Modify::Assert(attr.clone(), value.clone())),
                 Modify::Purged(attr) => match schema_attributes.get(attr) {
                     Some(_attr_name) => Ok(Modify::Purged(attr.clone())),
                     None => Err(SchemaError::InvalidAttribute(attr.to_string())),
@@ -264,13 +258,13 @@ impl ModifyList<ModifyValid> {
             mods,
         }
     }
+}
 
+impl<VALID> ModifyList<VALID> {
     pub fn iter(&self) -> slice::Iter<'_, Modify> {
         self.mods.iter()
     }
-}
 
-impl<VALID> ModifyList<VALID> {
     pub fn len(&self) -> usize {
         self.mods.len()
     }
@@ -7,22 +7,84 @@
 use crate::event::{CreateEvent, ModifyEvent};
 use crate::plugins::Plugin;
 use crate::prelude::*;
-use crate::schema::SchemaTransaction;
+use crate::schema::{SchemaTransaction, UniqueScope};
+use hashbrown::HashMap;
 use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 use tracing::trace;
 
 pub struct AttrUnique;
 
+/// Determine if an entry is within the scope of a unique attribute, and so must not share
+/// a value with any other entry in that scope.
+fn in_unique_scope<VALID, STATE>(scope: &UniqueScope, e: &Entry<VALID, STATE>) -> bool {
+    match scope {
+        UniqueScope::All => true,
+        UniqueScope::Classes(classes) => classes.iter().any(|class| {
+            e.attribute_equality(Attribute::Class, &PartialValue::new_iutf8(class.as_str()))
+        }),
+        UniqueScope::PerSyncParent => true,
+    }
+}
+
+/// The partition of the unique scope that an entry belongs to. Entries may only conflict
+/// with others in the same partition. For a per sync parent scope this is the sync
+/// agreement that provided the entry, if any.
+fn unique_partition<VALID, STATE>(scope: &UniqueScope, e: &Entry<VALID, STATE>) -> Option<Uuid> {
+    match scope {
+        UniqueScope::PerSyncParent => e.get_ava_single_refer(Attribute::SyncParentUuid),
+        UniqueScope::All | UniqueScope::Classes(_) => None,
+    }
+}
+
+/// A unique value held by a candidate, and the partition of the scope it must be unique within.
+type UniqueKey = (Attribute, PartialValue, Option<Uuid>);
+
+/// A filter for the entries, other than `uuid`, that hold the value and are in the scope of
+/// the unique attribute. Any entry that matches is a conflict.
+fn conflict_filter(
+    uniqueattrs: &HashMap<Attribute, UniqueScope>,
+    (attr, v, partition): &UniqueKey,
+    uuid: Uuid,
+) -> FC {
+    // and[ attr eq k, andnot [ uuid eq v ]]
+    // Basically this says where name but also not self.
+    let mut inner = vec![
+        FC::Eq(attr.clone(), v.clone()),
+        f_andnot(FC::Eq(Attribute::Uuid, PartialValue::Uuid(uuid))),
+    ];
+
+    match uniqueattrs.get(attr) {
+        Some(UniqueScope::Classes(classes)) => {
+            inner.push(f_or(
+                classes
+                    .iter()
+                    .map(|class| f_eq(Attribute::Class, PartialValue::new_iutf8(class.as_str())))
+                    .collect(),
+            ));
+        }
+        Some(UniqueScope::PerSyncParent) => match partition {
+            Some(parent) => inner.push(f_eq(
+                Attribute::SyncParentUuid,
+                PartialValue::Refer(*parent),
+            )),
+            None => inner.push(f_andnot(f_pres(Attribute::SyncParentUuid))),
+        },
+        Some(UniqueScope::All) | None => {}
+    }
+
+    f_and(inner)
+}
+
 fn get_cand_attr_set<'a, VALID: 'a, STATE: 'a, T>(
     // cand: &[Entry<VALID, STATE>],
     cand: T,
-    uniqueattrs: &[Attribute],
-) -> Result<BTreeMap<(Attribute, PartialValue), Vec<Uuid>>, OperationError>
+    uniqueattrs: &HashMap<Attribute, UniqueScope>,
+) -> Result<BTreeMap<UniqueKey, Vec<Uuid>>, OperationError>
 where
     T: IntoIterator<Item = &'a Entry<VALID, STATE>>,
 {
-    let mut cand_attr: BTreeMap<(Attribute, PartialValue), Vec<Uuid>> = BTreeMap::new();
+    let mut cand_attr: BTreeMap<UniqueKey, Vec<Uuid>> = BTreeMap::new();
 
     cand.into_iter()
         // We don't need to consider recycled or tombstoned entries
@@ -36,10 +98,14 @@ where
                 })?;
 
             // Faster to iterate over the attr vec inside this loop.
-            for attr in uniqueattrs.iter() {
+            for (attr, scope) in uniqueattrs.iter() {
+                if !in_unique_scope(scope, e) {
+                    continue;
+                }
+                let partition = unique_partition(scope, e);
                 if let Some(vs) = e.get_ava_set(attr) {
                 for pv in vs.to_partialvalue_iter() {
-                    let key = (attr.clone(), pv);
+                    let key = (attr.clone(), pv, partition);
                     cand_attr.entry(key)
                         // Must have conflicted, lets append.
                         .and_modify(|v| {
@@ -61,18 +127,41 @@ where
         .map(|()| cand_attr)
 }
 
+/// Find the candidates that share the value of a unique attribute with another candidate
+/// of the same set, along with the attributes that conflict.
+pub(crate) fn find_candidate_conflicts<'a, VALID: 'a, STATE: 'a, T>(
+    qs: &QueryServerWriteTransaction,
+    cand: T,
+) -> Result<BTreeMap<Uuid, BTreeSet<Attribute>>, OperationError>
+where
+    T: IntoIterator<Item = &'a Entry<VALID, STATE>>,
+{
+    let uniqueattrs = qs.get_schema().get_attributes_unique();
+
+    let cand_attr_set = get_cand_attr_set(cand, uniqueattrs)?;
+
+    let mut conflicts: BTreeMap<Uuid, BTreeSet<Attribute>> = BTreeMap::new();
+    for ((attr, _, _), uuids) in cand_attr_set.into_iter() {
+        if uuids.len() > 1 {
+            for uuid in uuids {
+                conflicts.entry(uuid).or_default().insert(attr.clone());
+            }
+        }
+    }
+
+    Ok(conflicts)
+}
+
 fn enforce_unique<VALID, STATE>(
     qs: &mut QueryServerWriteTransaction,
     cand: &[Entry<VALID, STATE>],
 ) -> Result<(), OperationError> {
-    let uniqueattrs = {
-        let schema = qs.get_schema();
-        schema.get_attributes_unique()
-    };
+    // Cloned, as the scopes are needed to build filters while searching.
+    let uniqueattrs = qs.get_schema().get_attributes_unique().clone();
 
     // Build a set of all the value -> uuid for the cands.
     // If already exist, reject due to dup.
-    let cand_attr_set = get_cand_attr_set(cand, uniqueattrs).map_err(|e| {
+    let cand_attr_set = get_cand_attr_set(cand, &uniqueattrs).map_err(|e| {
         error!(err = ?e, "failed to get cand attr set");
         e
     })?;
@@ -111,13 +200,8 @@ fn enforce_unique<VALID, STATE>(
 
     // Now do an internal search on name and !uuid for each
     let mut cand_filters = Vec::with_capacity(0);
-    for ((attr, v), uuid) in cand_attr.iter() {
-        // and[ attr eq k, andnot [ uuid eq v ]]
-        // Basically this says where name but also not self.
-        cand_filters.push(f_and(vec![
-            FC::Eq(attr.clone(), v.clone()),
-            f_andnot(FC::Eq(Attribute::Uuid, PartialValue::Uuid(*uuid))),
-        ]));
+    for (key, uuid) in cand_attr.iter() {
+        cand_filters.push(conflict_filter(&uniqueattrs, key, *uuid));
     }
 
     // Or
@@ -134,11 +218,9 @@ fn enforce_unique<VALID, STATE>(
         // Some kind of conflict exists. We need to isolate which parts of the filter were suspect.
         let mut err_attr: Vec<Attribute> = Default::default();
 
-        for ((attr, v), uuid) in cand_attr.iter() {
-            let filt_in = filter!(f_and(vec![
-                FC::Eq(attr.clone(), v.clone()),
-                f_andnot(FC::Eq(Attribute::Uuid, PartialValue::Uuid(*uuid))),
-            ]));
+        for (key, uuid) in cand_attr.iter() {
+            let (attr, v, _) = key;
+            let filt_in = filter!(conflict_filter(&uniqueattrs, key, *uuid));
 
             let conflict_cand = qs.internal_search(filt_in).inspect_err(|err| {
                 error!(?err, "internal exists error");
@@ -238,15 +320,13 @@ impl Plugin for AttrUnique {
         //   conflicts that exist in reality.
         // * All entries that are involved in the attr unique collision must become conflicts.
 
-        let uniqueattrs = {
-            let schema = qs.get_schema();
-            schema.get_attributes_unique()
-        };
+        // Cloned, as the scopes are needed to build filters while searching.
+        let uniqueattrs = qs.get_schema().get_attributes_unique().clone();
 
         // Build a set of all the value -> uuid for the cands.
         // If already exist, reject due to dup.
         let cand_attr_set =
-            get_cand_attr_set(cand.iter().map(|(e, _)| e), uniqueattrs).map_err(|e| {
+            get_cand_attr_set(cand.iter().map(|(e, _)| e), &uniqueattrs).map_err(|e| {
                 error!(err = ?e, "failed to get cand attr set");
                 e
             })?;
@@ -260,15 +340,10 @@ impl Plugin for AttrUnique {
         // we can *proceed*, nothing has conflicted.
         let cand_filters: Vec<_> = cand_attr_set
             .iter()
-            .flat_map(|((attr, v), uuids)| {
-                uuids.iter().map(|uuid| {
-                    // and[ attr eq k, andnot [ uuid eq v ]]
-                    // Basically this says where name but also not self.
-                    f_and(vec![
-                        FC::Eq(attr.clone(), v.clone()),
-                        f_andnot(FC::Eq(Attribute::Uuid, PartialValue::Uuid(*uuid))),
-                    ])
-                })
+            .flat_map(|(key, uuids)| {
+                uuids
+                    .iter()
+                    .map(|uuid| conflict_filter(&uniqueattrs, key, *uuid))
             })
             .collect();
 
@@ -318,12 +393,7 @@ impl Plugin for AttrUnique {
             for (uuid, ava_set) in cand_attr_map.into_iter() {
                 let cand_filters: Vec<_> = ava_set
                     .iter()
-                    .map(|(attr, pv)| {
-                        f_and(vec![
-                            FC::Eq(attr.clone(), pv.clone()),
-                            f_andnot(FC::Eq(Attribute::Uuid, PartialValue::Uuid(uuid))),
-                        ])
-                    })
+                    .map(|key| conflict_filter(&uniqueattrs, key, uuid))
                     .collect();
 
                 let filt_in = filter!(f_or(cand_filters.clone()));
@@ -563,6 +633,59 @@ mod tests {
         );
     }
 
+    // Schema entries are only loaded from the database before DL15.
+    #[qs_test(domain_level=DOMAIN_LEVEL_14)]
+    async fn test_unique_scope_classes(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+
+        // Mail is only unique amongst persons.
+        assert!(server_txn
+            .internal_modify_uuid(
+                UUID_SCHEMA_ATTR_MAIL,
+                &ModifyList::new_append(Attribute::UniqueScope, EntryClass::Person.to_value())
+            )
+            .is_ok());
+        assert!(server_txn.reload().is_ok());
+
+        let mail = || Value::new_email_address_s("shared@example.com").expect("invalid email");
+
+        assert!(server_txn
+            .internal_create(vec![entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Account.to_value()),
+                (Attribute::Class, EntryClass::Person.to_value()),
+                (Attribute::Name, Value::new_iname("testperson1")),
+                (Attribute::DisplayName, Value::new_utf8s("testperson1")),
+                (Attribute::Mail, mail())
+            )])
+            .is_ok());
+
+        // A service account may share the address of a person.
+        assert!(server_txn
+            .internal_create(vec![entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Account.to_value()),
+                (Attribute::Class, EntryClass::ServiceAccount.to_value()),
+                (Attribute::Name, Value::new_iname("testservice1")),
+                (Attribute::DisplayName, Value::new_utf8s("testservice1")),
+                (Attribute::Mail, mail())
+            )])
+            .is_ok());
+
+        // But another person may not.
+        assert_eq!(
+            server_txn.internal_create(vec![entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Account.to_value()),
+                (Attribute::Class, EntryClass::Person.to_value()),
+                (Attribute::Name, Value::new_iname("testperson2")),
+                (Attribute::DisplayName, Value::new_utf8s("testperson2")),
+                (Attribute::Mail, mail())
+            )]),
+            Err(OperationError::AttributeUniqueness(vec![Attribute::Mail]))
+        );
+    }
+
     #[test]
     fn test_verify_name_unique() {
         // Can we preload two dups and verify to show we detect?
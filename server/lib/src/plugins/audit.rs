@@ -0,0 +1,230 @@
+// Emit a structured audit record for each change that an identity makes, so that
+// changes can later be queried by actor, target, operation and time.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use kanidm_proto::v1::AuditOperation;
+
+use crate::event::{CreateEvent, DeleteEvent, ModifyEvent};
+use crate::plugins::Plugin;
+use crate::prelude::*;
+
+pub struct Audit {}
+
+impl Plugin for Audit {
+    fn id() -> &'static str {
+        "plugin_audit"
+    }
+
+    #[instrument(level = "debug", name = "audit_post_create", skip_all)]
+    fn post_create(
+        qs: &mut QueryServerWriteTransaction,
+        cand: &[EntrySealedCommitted],
+        ce: &CreateEvent,
+    ) -> Result<(), OperationError> {
+        let targets = cand.iter().map(|e| e.get_uuid()).collect();
+        let attributes = ce
+            .entries
+            .iter()
+            .flat_map(|e| e.attr_keys())
+            .cloned()
+            .collect();
+        qs.audit_record(&ce.ident, AuditOperation::Create, targets, &attributes)
+    }
+
+    #[instrument(level = "debug", name = "audit_post_modify", skip_all)]
+    fn post_modify(
+        qs: &mut QueryServerWriteTransaction,
+        _pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &[EntrySealedCommitted],
+        me: &ModifyEvent,
+    ) -> Result<(), OperationError> {
+        let targets = cand.iter().map(|e| e.get_uuid()).collect();
+        let attributes = Self::modified_attributes(&me.modlist).collect();
+        qs.audit_record(&me.ident, AuditOperation::Modify, targets, &attributes)
+    }
+
+    #[instrument(level = "debug", name = "audit_post_batch_modify", skip_all)]
+    fn post_batch_modify(
+        qs: &mut QueryServerWriteTransaction,
+        _pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &[EntrySealedCommitted],
+        me: &BatchModifyEvent,
+    ) -> Result<(), OperationError> {
+        let targets = cand.iter().map(|e| e.get_uuid()).collect();
+        let attributes = me
+            .modset
+            .values()
+            .flat_map(Self::modified_attributes)
+            .collect();
+        qs.audit_record(&me.ident, AuditOperation::Modify, targets, &attributes)
+    }
+
+    #[instrument(level = "debug", name = "audit_post_delete", skip_all)]
+    fn post_delete(
+        qs: &mut QueryServerWriteTransaction,
+        cand: &[EntrySealedCommitted],
+        de: &DeleteEvent,
+    ) -> Result<(), OperationError> {
+        let targets = cand.iter().map(|e| e.get_uuid()).collect();
+        qs.audit_record(&de.ident, AuditOperation::Delete, targets, &BTreeSet::new())
+    }
+}
+
+impl Audit {
+    /// The attributes that a modlist changes. Assertions don't change the entry, so
+    /// they are not included.
+    fn modified_attributes(
+        modlist: &ModifyList<ModifyValid>,
+    ) -> impl Iterator<Item = Attribute> + '_ {
+        modlist.iter().filter_map(|m| match m {
+            Modify::Present(attr, _)
+            | Modify::Removed(attr, _)
+            | Modify::Purged(attr)
+            | Modify::Set(attr, _) => Some(attr.clone()),
+            Modify::Assert(..) => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use kanidm_proto::v1::{AuditOperation, AuditQuery};
+    use time::OffsetDateTime;
+
+    #[qs_test]
+    async fn test_audit_record_query(server: &QueryServer) {
+        let ct = duration_from_epoch_now();
+        let mut server_txn = server.write(ct).await.expect("txn");
+
+        let idm_admin = server_txn
+            .internal_search_uuid(UUID_IDM_ADMIN)
+            .expect("failed to find idm_admin");
+        let idm_admin_ident = Identity::from_impersonate_entry_readwrite(idm_admin);
+
+        let reader_uuid = Uuid::new_v4();
+        let e_reader = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::ServiceAccount.to_value()),
+            (Attribute::Name, Value::new_iname("audit_reader")),
+            (Attribute::Uuid, Value::Uuid(reader_uuid)),
+            (Attribute::DisplayName, Value::new_utf8s("Audit Reader"))
+        );
+        server_txn
+            .internal_create(vec![e_reader])
+            .expect("failed to create reader");
+        server_txn
+            .internal_modify_uuid(
+                UUID_IDM_AUDIT_READERS,
+                &ModifyList::new_append(Attribute::Member, Value::Refer(reader_uuid)),
+            )
+            .expect("failed to add reader to audit readers");
+
+        // Internal changes are never audited.
+        let target_uuid = Uuid::new_v4();
+        let e_target = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Name, Value::new_iname("audit_target")),
+            (Attribute::Uuid, Value::Uuid(target_uuid))
+        );
+        server_txn
+            .internal_create(vec![e_target])
+            .expect("failed to create target");
+
+        assert!(server_txn.commit().is_ok());
+
+        // idm_admin changes the target at three distinct times.
+        for (offset, description) in [(1, "first"), (2, "second"), (3, "third")] {
+            let mut server_txn = server
+                .write(ct + Duration::from_secs(offset * 60))
+                .await
+                .expect("txn");
+            let me = ModifyEvent::new_impersonate_identity(
+                idm_admin_ident.clone(),
+                filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(target_uuid))),
+                ModifyList::new_purge_and_set(
+                    Attribute::Description,
+                    Value::new_utf8s(description),
+                ),
+            );
+            server_txn.modify(&me).expect("failed to modify target");
+            assert!(server_txn.commit().is_ok());
+        }
+
+        let mut server_txn = server.read().await.expect("txn");
+
+        let reader = server_txn
+            .internal_search_uuid(reader_uuid)
+            .expect("failed to find reader");
+        let reader_ident = Identity::from_impersonate_entry_readonly(reader);
+
+        // All changes by idm_admin, paged.
+        let query = AuditQuery {
+            actor: Some(UUID_IDM_ADMIN),
+            limit: Some(2),
+            ..Default::default()
+        };
+        let page = server_txn
+            .audit_search(&reader_ident, &query)
+            .expect("failed to query audit records");
+        assert_eq!(page.records.len(), 2);
+        assert_eq!(page.next_offset, Some(2));
+        assert!(page.records.iter().all(|r| r.actor == UUID_IDM_ADMIN
+            && r.operation == AuditOperation::Modify
+            && r.targets == vec![target_uuid]
+            && r.attributes == vec![Attribute::Description.to_string()]));
+
+        let query = AuditQuery { offset: 2, ..query };
+        let page = server_txn
+            .audit_search(&reader_ident, &query)
+            .expect("failed to query audit records");
+        assert_eq!(page.records.len(), 1);
+        assert_eq!(page.next_offset, None);
+
+        // Only the second change falls inside the window.
+        let window_start = OffsetDateTime::UNIX_EPOCH + ct + Duration::from_secs(90);
+        let query = AuditQuery {
+            actor: Some(UUID_IDM_ADMIN),
+            target: Some(target_uuid),
+            not_before: Some(window_start),
+            not_after: Some(window_start + Duration::from_secs(60)),
+            ..Default::default()
+        };
+        let page = server_txn
+            .audit_search(&reader_ident, &query)
+            .expect("failed to query audit records");
+        assert_eq!(page.records.len(), 1);
+        assert_eq!(
+            page.records[0].time,
+            OffsetDateTime::UNIX_EPOCH + ct + Duration::from_secs(120)
+        );
+
+        // Nothing was created by idm_admin, and the internal create was not recorded.
+        let query = AuditQuery {
+            operation: Some(AuditOperation::Create),
+            ..Default::default()
+        };
+        let page = server_txn
+            .audit_search(&reader_ident, &query)
+            .expect("failed to query audit records");
+        assert!(page.records.is_empty());
+
+        // Identities outside of the audit readers can't see any records.
+        let idm_admin = server_txn
+            .internal_search_uuid(UUID_IDM_ADMIN)
+            .expect("failed to find idm_admin");
+        let idm_admin_ident = Identity::from_impersonate_entry_readonly(idm_admin);
+        let query = AuditQuery {
+            actor: Some(UUID_IDM_ADMIN),
+            ..Default::default()
+        };
+        let page = server_txn
+            .audit_search(&idm_admin_ident, &query)
+            .expect("failed to query audit records");
+        assert!(page.records.is_empty());
+    }
+}
@@ -0,0 +1,113 @@
+// Validate the defaults of creation templates as they are saved. The defaults are stored as
+// strings, so without this check a template with a misspelt attribute or a value that does
+// not match the attribute syntax would only be found when an account is created from it.
+use crate::entry::{EntryInvalidCommitted, EntrySealedCommitted};
+use crate::event::{CreateEvent, ModifyEvent};
+use crate::plugins::Plugin;
+use crate::prelude::*;
+use crate::server::creation_template::validate_template_defaults;
+use std::sync::Arc;
+
+pub struct CreationTemplate {}
+
+impl Plugin for CreationTemplate {
+    fn id() -> &'static str {
+        "plugin_creation_template"
+    }
+
+    #[instrument(
+        level = "debug",
+        name = "creation_template_pre_create_transform",
+        skip_all
+    )]
+    fn pre_create_transform(
+        qs: &mut QueryServerWriteTransaction,
+        cand: &mut Vec<EntryInvalidNew>,
+        _ce: &CreateEvent,
+    ) -> Result<(), OperationError> {
+        Self::validate(qs, cand)
+    }
+
+    #[instrument(level = "debug", name = "creation_template_pre_modify", skip_all)]
+    fn pre_modify(
+        qs: &mut QueryServerWriteTransaction,
+        _pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut Vec<EntryInvalidCommitted>,
+        _me: &ModifyEvent,
+    ) -> Result<(), OperationError> {
+        Self::validate(qs, cand)
+    }
+
+    #[instrument(level = "debug", name = "creation_template_pre_batch_modify", skip_all)]
+    fn pre_batch_modify(
+        qs: &mut QueryServerWriteTransaction,
+        _pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut Vec<EntryInvalidCommitted>,
+        _me: &BatchModifyEvent,
+    ) -> Result<(), OperationError> {
+        Self::validate(qs, cand)
+    }
+}
+
+impl CreationTemplate {
+    fn validate<STATE>(
+        qs: &mut QueryServerWriteTransaction,
+        cand: &[Entry<EntryInvalid, STATE>],
+    ) -> Result<(), OperationError> {
+        cand.iter()
+            .filter(|e| {
+                e.attribute_equality(Attribute::Class, &EntryClass::CreationTemplate.into())
+            })
+            .try_for_each(|e| validate_template_defaults(qs, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn template_with_default(default: &str) -> EntryInitNew {
+        entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::CreationTemplate.to_value()),
+            (Attribute::Name, Value::new_iname("template")),
+            (Attribute::TemplateDefault, Value::new_utf8s(default))
+        )
+    }
+
+    #[qs_test]
+    async fn test_creation_template_defaults_validated(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+
+        for default in [
+            "loginshell",
+            "not_an_attribute: value",
+            "mail: not an address",
+            "name: {name}",
+        ] {
+            assert_eq!(
+                server_txn.internal_create(vec![template_with_default(default)]),
+                Err(OperationError::CT0003CreationTemplateDefaultInvalid),
+                "{default}"
+            );
+        }
+
+        assert!(server_txn
+            .internal_create(vec![template_with_default("mail: {name}@example.com")])
+            .is_ok());
+
+        // Templates are validated when modified too.
+        assert_eq!(
+            server_txn.internal_modify(
+                &filter!(f_eq(Attribute::Name, PartialValue::new_iname("template"))),
+                &ModifyList::new_append(
+                    Attribute::TemplateDefault,
+                    Value::new_utf8s("gidnumber: not a number")
+                ),
+            ),
+            Err(OperationError::CT0003CreationTemplateDefaultInvalid)
+        );
+
+        assert!(server_txn.commit().is_ok());
+    }
+}
@@ -80,6 +80,10 @@ impl Domain {
                     }
                 }
 
+                // The self write policy can't grant attributes that a person must never
+                // change on their own entry.
+                qs.validate_self_write_attrs(e)?;
+
                 // We always set this, because the DB uuid is authoritative.
                 let u = Value::Uuid(qs.get_domain_uuid());
                 e.set_ava(&Attribute::DomainUuid, once(u));
@@ -145,4 +149,72 @@ mod tests {
 
         assert!(e_dom.attribute_equality(Attribute::DomainUuid, &PartialValue::Uuid(u_dom)));
     }
+
+    #[qs_test]
+    async fn test_domain_self_write_policy_denied(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+
+        let set_policy = |attr: &str| {
+            ModifyList::new_purge_and_set(Attribute::DomainSelfWriteAttrs, Value::new_iutf8(attr))
+        };
+
+        for attr in [
+            Attribute::LegalName,
+            Attribute::Mail,
+            Attribute::Name,
+            Attribute::AccountExpire,
+            Attribute::AccountValidFrom,
+            Attribute::PrimaryCredential,
+            Attribute::Class,
+            Attribute::MemberOf,
+        ] {
+            assert_eq!(
+                server_txn.internal_modify_uuid(UUID_DOMAIN_INFO, &set_policy(attr.as_str())),
+                Err(OperationError::PL0009SelfWriteAttributeDenied)
+            );
+        }
+
+        assert_eq!(
+            server_txn.internal_modify_uuid(UUID_DOMAIN_INFO, &set_policy("not_an_attribute")),
+            Err(OperationError::InvalidAttributeName(
+                "not_an_attribute".to_string()
+            ))
+        );
+
+        assert!(server_txn
+            .internal_modify_uuid(
+                UUID_DOMAIN_INFO,
+                &set_policy(Attribute::DisplayName.as_str())
+            )
+            .is_ok());
+
+        // A policy that bypassed validation, such as from an older server, is refused on
+        // reload rather than granted.
+        let pre = server_txn
+            .internal_search_uuid(UUID_DOMAIN_INFO)
+            .expect("Failed to find domain");
+        let cid = server_txn.get_cid().clone();
+        let mut post = pre
+            .as_ref()
+            .clone()
+            .invalidate(cid.clone(), server_txn.trim_cid());
+        post.set_ava(
+            &Attribute::DomainSelfWriteAttrs,
+            std::iter::once(Value::new_iutf8(Attribute::LegalName.as_str())),
+        );
+        let post = post.into_sealed_committed();
+        server_txn
+            .get_be_txn()
+            .modify(&cid, &[pre], &[post])
+            .expect("Failed to write domain");
+
+        assert_eq!(
+            server_txn.reload_domain_info(),
+            Err(OperationError::PL0009SelfWriteAttributeDenied)
+        );
+        assert_eq!(
+            server_txn.reload_self_write_policy(),
+            Err(OperationError::PL0009SelfWriteAttributeDenied)
+        );
+    }
 }
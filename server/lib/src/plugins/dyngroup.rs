@@ -15,6 +15,30 @@ pub struct DynGroupCache {
 pub struct DynGroup;
 
 impl DynGroup {
+    /// The filter selecting the members of a dynamic group. Entries that match the
+    /// exclusion filter, if any, are removed from those matched by the dyngroup filter.
+    fn dyngroup_scope<VALID, STATE>(
+        nd_group: &Entry<VALID, STATE>,
+    ) -> Result<ProtoFilter, OperationError> {
+        let include: ProtoFilter = nd_group
+            .get_ava_single_protofilter(Attribute::DynGroupFilter)
+            .cloned()
+            .ok_or_else(|| {
+                error!("Missing {}", Attribute::DynGroupFilter);
+                OperationError::InvalidEntryState
+            })?;
+
+        Ok(
+            match nd_group.get_ava_single_protofilter(Attribute::DynGroupFilterExclude) {
+                Some(exclude) => ProtoFilter::And(vec![
+                    include,
+                    ProtoFilter::AndNot(Box::new(exclude.clone())),
+                ]),
+                None => include,
+            },
+        )
+    }
+
     /// Determine if any dynamic groups changed as part of this operation.
     #[allow(clippy::too_many_arguments)]
     fn apply_dyngroup_change(
@@ -62,13 +86,7 @@ impl DynGroup {
         for (ref pre, ref mut nd_group) in work_set.iter_mut() {
             trace!(dyngroup_id = %nd_group.get_display_id());
             // Load the dyngroups filter
-            let scope_f: ProtoFilter = nd_group
-                .get_ava_single_protofilter(Attribute::DynGroupFilter)
-                .cloned()
-                .ok_or_else(|| {
-                    error!("Missing {}", Attribute::DynGroupFilter);
-                    OperationError::InvalidEntryState
-                })?;
+            let scope_f = Self::dyngroup_scope(nd_group)?;
 
             let scope_i = Filter::from_rw(ident_internal, &scope_f, qs).map_err(|e| {
                 error!("{} validation failed {:?}", Attribute::DynGroupFilter, e);
@@ -140,13 +158,7 @@ impl DynGroup {
         let mut reload_groups = BTreeMap::default();
 
         for nd_group in entries.into_iter() {
-            let scope_f: ProtoFilter = nd_group
-                .get_ava_single_protofilter(Attribute::DynGroupFilter)
-                .cloned()
-                .ok_or_else(|| {
-                    error!("Missing {}", Attribute::DynGroupFilter);
-                    OperationError::InvalidEntryState
-                })?;
+            let scope_f = Self::dyngroup_scope(&nd_group)?;
 
             let scope_i = Filter::from_rw(&ident_internal, &scope_f, qs).map_err(|e| {
                 error!("dyngroup_filter validation failed {:?}", e);
@@ -1049,4 +1061,122 @@ mod tests {
             }
         );
     }
+
+    fn is_member_of(server_txn: &mut QueryServerWriteTransaction, uuid: Uuid, group: Uuid) -> bool {
+        server_txn
+            .internal_search_uuid(uuid)
+            .expect("Unable to access entry")
+            .attribute_equality(Attribute::MemberOf, &PartialValue::Refer(group))
+    }
+
+    #[qs_test]
+    async fn test_dyngroup_filter_exclude_and_pinned_member(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await.expect("txn");
+
+        let dyn_uuid = uuid::uuid!("0f4b42ad-3d3c-4a36-a3d8-6c3f5c1a2ae4");
+        let group_a = uuid::uuid!("8c0fe2a4-8d55-4a11-9d0b-1d35d1e4bf51");
+        let group_b = uuid::uuid!("6b5d1c53-1d61-4c33-8d0f-8a8a3c7d9a02");
+        // Excluded by the filter, but pinned as a static member.
+        let group_c = uuid::uuid!("2e0b7f3a-4c5e-47b4-b3f8-3f1c9de8c713");
+
+        let candidate = |name: &str, uuid: Uuid| {
+            entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Group.to_value()),
+                (Attribute::Name, Value::new_iname(name)),
+                (Attribute::Uuid, Value::Uuid(uuid)),
+                (Attribute::Description, Value::new_utf8s("dyn_candidate"))
+            )
+        };
+
+        assert!(server_txn
+            .internal_create(vec![
+                candidate("testgroup_a", group_a),
+                candidate("testgroup_b", group_b),
+                candidate("testgroup_c", group_c),
+                entry_init!(
+                    (Attribute::Class, EntryClass::Object.to_value()),
+                    (Attribute::Class, EntryClass::Group.to_value()),
+                    (Attribute::Class, EntryClass::DynGroup.to_value()),
+                    (Attribute::Name, Value::new_iname("test_dyngroup")),
+                    (Attribute::Uuid, Value::Uuid(dyn_uuid)),
+                    (Attribute::Member, Value::Refer(group_c)),
+                    (
+                        Attribute::DynGroupFilter,
+                        Value::JsonFilt(ProtoFilter::Eq(
+                            Attribute::Description.to_string(),
+                            "dyn_candidate".to_string()
+                        ))
+                    )
+                ),
+            ])
+            .is_ok());
+
+        assert!(is_member_of(&mut server_txn, group_a, dyn_uuid));
+        assert!(is_member_of(&mut server_txn, group_b, dyn_uuid));
+        assert!(is_member_of(&mut server_txn, group_c, dyn_uuid));
+
+        // Exclude b and c - c remains a member as it is pinned.
+        let exclude = |names: &[&str]| {
+            Value::JsonFilt(ProtoFilter::Or(
+                names
+                    .iter()
+                    .map(|name| ProtoFilter::Eq(Attribute::Name.to_string(), name.to_string()))
+                    .collect(),
+            ))
+        };
+
+        assert!(server_txn
+            .internal_modify_uuid(
+                dyn_uuid,
+                &ModifyList::new_purge_and_set(
+                    Attribute::DynGroupFilterExclude,
+                    exclude(&["testgroup_b", "testgroup_c"])
+                )
+            )
+            .is_ok());
+
+        let d_group = server_txn
+            .internal_search_uuid(dyn_uuid)
+            .expect("Unable to access dyn group");
+        assert_eq!(
+            d_group
+                .get_ava_set(Attribute::DynMember)
+                .and_then(|vs| vs.to_refer_single()),
+            Some(group_a)
+        );
+
+        assert!(is_member_of(&mut server_txn, group_a, dyn_uuid));
+        assert!(!is_member_of(&mut server_txn, group_b, dyn_uuid));
+        assert!(is_member_of(&mut server_txn, group_c, dyn_uuid));
+
+        // Changing the exclusion reconverges the membership.
+        assert!(server_txn
+            .internal_modify_uuid(
+                dyn_uuid,
+                &ModifyList::new_purge_and_set(
+                    Attribute::DynGroupFilterExclude,
+                    exclude(&["testgroup_a"])
+                )
+            )
+            .is_ok());
+
+        assert!(!is_member_of(&mut server_txn, group_a, dyn_uuid));
+        assert!(is_member_of(&mut server_txn, group_b, dyn_uuid));
+        assert!(is_member_of(&mut server_txn, group_c, dyn_uuid));
+
+        // As does removing it.
+        assert!(server_txn
+            .internal_modify_uuid(
+                dyn_uuid,
+                &ModifyList::new_purge(Attribute::DynGroupFilterExclude)
+            )
+            .is_ok());
+
+        assert!(is_member_of(&mut server_txn, group_a, dyn_uuid));
+        assert!(is_member_of(&mut server_txn, group_b, dyn_uuid));
+        assert!(is_member_of(&mut server_txn, group_c, dyn_uuid));
+
+        assert!(server_txn.commit().is_ok());
+    }
 }
@@ -1,7 +1,9 @@
 // A plugin that generates gid numbers on types that require them for posix
 // support.
 
+use std::collections::BTreeSet;
 use std::iter::once;
+use std::str::FromStr;
 use std::sync::Arc;
 
 use crate::event::{CreateEvent, ModifyEvent};
@@ -60,9 +62,176 @@ pub const GID_UNUSED_D_MAX: u32 = 0x7fff_ffff;
 // can't be accessed.
 // const GID_UNSAFE_MAX: u32 = 2147483648;
 
+// The ranges that a gid may be set within.
+const GID_PERMITTED_RANGES: [(u32, u32); 6] = [
+    (GID_REGULAR_USER_MIN, GID_REGULAR_USER_MAX),
+    (GID_UNUSED_A_MIN, GID_UNUSED_A_MAX),
+    (GID_UNUSED_B_MIN, GID_UNUSED_B_MAX),
+    (GID_UNUSED_C_MIN, GID_UNUSED_C_MAX),
+    // We won't ever generate an id in the nspawn range, but we do secretly allow
+    // it to be set for compatibility with services like freeipa or openldap. TBH
+    // most people don't even use systemd nspawn anyway ...
+    //
+    // I made this design choice to avoid a tunable that may confuse people to
+    // its purpose. This way things "just work" for imports and existing systems
+    // but we do the right thing in the future.
+    (GID_NSPAWN_MIN, GID_NSPAWN_MAX),
+    (GID_UNUSED_D_MIN, GID_UNUSED_D_MAX),
+];
+
+fn gid_range_is_permitted(start: u32, end: u32) -> bool {
+    GID_PERMITTED_RANGES
+        .iter()
+        .any(|(min, max)| *min <= start && end <= *max)
+}
+
+/// A named range of gid numbers defined on the domain in the form `name:start-end`.
+/// Entries tagged with the range name have their gid number allocated from it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct GidNumberRange {
+    pub name: String,
+    pub start: u32,
+    pub end: u32,
+}
+
+impl FromStr for GidNumberRange {
+    type Err = OperationError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (name, range) = value
+            .split_once(':')
+            .ok_or(OperationError::PL0003GidNumberRangeInvalid)?;
+        let (start, end) = range
+            .split_once('-')
+            .ok_or(OperationError::PL0003GidNumberRangeInvalid)?;
+
+        let name = name.trim();
+        let start = start
+            .trim()
+            .parse::<u32>()
+            .map_err(|_| OperationError::PL0003GidNumberRangeInvalid)?;
+        let end = end
+            .trim()
+            .parse::<u32>()
+            .map_err(|_| OperationError::PL0003GidNumberRangeInvalid)?;
+
+        if name.is_empty() || start > end || !gid_range_is_permitted(start, end) {
+            return Err(OperationError::PL0003GidNumberRangeInvalid);
+        }
+
+        Ok(GidNumberRange {
+            name: name.to_string(),
+            start,
+            end,
+        })
+    }
+}
+
+impl GidNumberRange {
+    /// A filter matching entries with a gid number inside this range.
+    fn filter(&self) -> Filter<FilterInvalid> {
+        // Permitted ranges end well below u32::MAX, so this can't overflow.
+        filter!(f_and(vec![
+            f_andnot(f_lt(Attribute::GidNumber, PartialValue::Uint32(self.start))),
+            f_lt(Attribute::GidNumber, PartialValue::Uint32(self.end + 1)),
+        ]))
+    }
+}
+
+/// Parse the gid number ranges of the domain, rejecting duplicate names or ranges that
+/// overlap each other.
+pub(crate) fn parse_gid_number_ranges<'a>(
+    values: impl Iterator<Item = &'a str>,
+) -> Result<Vec<GidNumberRange>, OperationError> {
+    let mut ranges = values
+        .map(GidNumberRange::from_str)
+        .collect::<Result<Vec<_>, _>>()
+        .inspect_err(|_| error!("Invalid gid number range, expected name:start-end"))?;
+
+    ranges.sort_unstable_by_key(|range| range.start);
+
+    let overlapping = ranges
+        .windows(2)
+        .any(|pair| matches!(pair, [a, b] if a.end >= b.start));
+
+    let mut names = BTreeSet::new();
+    let duplicate_name = ranges
+        .iter()
+        .any(|range| !names.insert(range.name.as_str()));
+
+    if overlapping || duplicate_name {
+        error!("Gid number ranges must have unique names and may not overlap");
+        return Err(OperationError::PL0003GidNumberRangeInvalid);
+    }
+
+    Ok(ranges)
+}
+
+fn load_gid_number_ranges<'a, QS: QueryServerTransaction<'a>>(
+    qs: &mut QS,
+) -> Result<Vec<GidNumberRange>, OperationError> {
+    let domain = qs.internal_search_uuid(UUID_DOMAIN_INFO)?;
+
+    domain
+        .get_ava_iter_iutf8(Attribute::GidNumberRange)
+        .map(parse_gid_number_ranges)
+        .unwrap_or_else(|| Ok(Vec::with_capacity(0)))
+}
+
+/// Find the lowest free gid number within the range, skipping those already in use and
+/// those allocated earlier in this operation. The gid that `target` held before this
+/// operation is free, since it is being replaced.
+fn allocate_gid_from_range(
+    qs: &mut QueryServerWriteTransaction,
+    range: &GidNumberRange,
+    target: Uuid,
+    allocated: &BTreeSet<u32>,
+) -> Result<u32, OperationError> {
+    let in_use: BTreeSet<u32> = qs
+        .internal_search(range.filter())?
+        .iter()
+        .filter(|e| e.get_uuid() != target)
+        .filter_map(|e| e.get_ava_single_uint32(Attribute::GidNumber))
+        .collect();
+
+    (range.start..=range.end)
+        .find(|gid| !in_use.contains(gid) && !allocated.contains(gid))
+        .ok_or_else(|| {
+            error!(range = %range.name, "Gid number range has been exhausted");
+            OperationError::PL0005GidNumberRangeExhausted
+        })
+}
+
 pub struct GidNumber {}
 
-fn apply_gidnumber<T: Clone>(e: &mut Entry<EntryInvalid, T>) -> Result<(), OperationError> {
+fn apply_gidnumber<T: Clone>(
+    qs: &mut QueryServerWriteTransaction,
+    cand: &mut [Entry<EntryInvalid, T>],
+) -> Result<(), OperationError> {
+    // The domain ranges are only loaded once an entry needs to allocate from one.
+    let mut ranges: Option<Vec<GidNumberRange>> = None;
+    // Gids given to earlier candidates, which aren't yet visible to searches.
+    let mut allocated = BTreeSet::new();
+
+    for e in cand.iter_mut() {
+        if e.get_uuid() == Some(UUID_DOMAIN_INFO) {
+            if let Some(values) = e.get_ava_iter_iutf8(Attribute::GidNumberRange) {
+                parse_gid_number_ranges(values)?;
+            }
+        }
+
+        apply_gidnumber_entry(qs, e, &mut ranges, &mut allocated)?;
+    }
+
+    Ok(())
+}
+
+fn apply_gidnumber_entry<T: Clone>(
+    qs: &mut QueryServerWriteTransaction,
+    e: &mut Entry<EntryInvalid, T>,
+    ranges: &mut Option<Vec<GidNumberRange>>,
+    allocated: &mut BTreeSet<u32>,
+) -> Result<(), OperationError> {
     if (e.attribute_equality(Attribute::Class, &EntryClass::PosixGroup.into())
         || e.attribute_equality(Attribute::Class, &EntryClass::PosixAccount.into()))
         && !e.attribute_pres(Attribute::GidNumber)
@@ -74,12 +243,31 @@ fn apply_gidnumber<T: Clone>(e: &mut Entry<EntryInvalid, T>) -> Result<(), Opera
                 admin_error!("Invalid Entry State - Missing UUID");
             })?;
 
-        let gid = uuid_to_gid_u32(u_ref);
+        let gid = if let Some(range_name) = e.get_ava_single_iutf8(Attribute::GidNumberRangeName) {
+            if ranges.is_none() {
+                *ranges = Some(load_gid_number_ranges(qs)?);
+            }
 
-        // Apply the mask to only take the last 24 bits, and then move them
-        // to the correct range.
-        let gid = gid & GID_SYSTEM_NUMBER_MASK;
-        let gid = gid | GID_SYSTEM_NUMBER_PREFIX;
+            let range = ranges
+                .iter()
+                .flatten()
+                .find(|range| range.name == range_name)
+                .ok_or_else(|| {
+                    error!(range = %range_name, "Gid number range is not defined on the domain");
+                    OperationError::PL0004GidNumberRangeNotFound
+                })?;
+
+            let gid = allocate_gid_from_range(qs, range, u_ref, allocated)?;
+            allocated.insert(gid);
+            gid
+        } else {
+            let gid = uuid_to_gid_u32(u_ref);
+
+            // Apply the mask to only take the last 24 bits, and then move them
+            // to the correct range.
+            let gid = gid & GID_SYSTEM_NUMBER_MASK;
+            gid | GID_SYSTEM_NUMBER_PREFIX
+        };
 
         let gid_v = Value::new_uint32(gid);
         admin_info!("Generated {} for {:?}", gid, u_ref);
@@ -87,20 +275,7 @@ fn apply_gidnumber<T: Clone>(e: &mut Entry<EntryInvalid, T>) -> Result<(), Opera
         Ok(())
     } else if let Some(gid) = e.get_ava_single_uint32(Attribute::GidNumber) {
         // If they provided us with a gid number, ensure it's in a safe range.
-        if (GID_REGULAR_USER_MIN..=GID_REGULAR_USER_MAX).contains(&gid)
-            || (GID_UNUSED_A_MIN..=GID_UNUSED_A_MAX).contains(&gid)
-            || (GID_UNUSED_B_MIN..= GID_UNUSED_B_MAX).contains(&gid)
-            || (GID_UNUSED_C_MIN..=GID_UNUSED_C_MAX).contains(&gid)
-            // We won't ever generate an id in the nspawn range, but we do secretly allow
-            // it to be set for compatibility with services like freeipa or openldap. TBH
-            // most people don't even use systemd nspawn anyway ...
-            //
-            // I made this design choice to avoid a tunable that may confuse people to
-            // its purpose. This way things "just work" for imports and existing systems
-            // but we do the right thing in the future.
-            || (GID_NSPAWN_MIN..=GID_NSPAWN_MAX).contains(&gid)
-            || (GID_UNUSED_D_MIN..=GID_UNUSED_D_MAX).contains(&gid)
-        {
+        if gid_range_is_permitted(gid, gid) {
             Ok(())
         } else {
             // Note that here we don't advertise that we allow the nspawn range to be set, even
@@ -126,31 +301,73 @@ impl Plugin for GidNumber {
 
     #[instrument(level = "debug", name = "gidnumber_pre_create_transform", skip_all)]
     fn pre_create_transform(
-        _qs: &mut QueryServerWriteTransaction,
+        qs: &mut QueryServerWriteTransaction,
         cand: &mut Vec<Entry<EntryInvalid, EntryNew>>,
         _ce: &CreateEvent,
     ) -> Result<(), OperationError> {
-        cand.iter_mut().try_for_each(apply_gidnumber)
+        apply_gidnumber(qs, cand)
     }
 
     #[instrument(level = "debug", name = "gidnumber_pre_modify", skip_all)]
     fn pre_modify(
-        _qs: &mut QueryServerWriteTransaction,
+        qs: &mut QueryServerWriteTransaction,
         _pre_cand: &[Arc<EntrySealedCommitted>],
         cand: &mut Vec<Entry<EntryInvalid, EntryCommitted>>,
         _me: &ModifyEvent,
     ) -> Result<(), OperationError> {
-        cand.iter_mut().try_for_each(apply_gidnumber)
+        apply_gidnumber(qs, cand)
     }
 
     #[instrument(level = "debug", name = "gidnumber_pre_batch_modify", skip_all)]
     fn pre_batch_modify(
-        _qs: &mut QueryServerWriteTransaction,
+        qs: &mut QueryServerWriteTransaction,
         _pre_cand: &[Arc<EntrySealedCommitted>],
         cand: &mut Vec<Entry<EntryInvalid, EntryCommitted>>,
         _me: &BatchModifyEvent,
     ) -> Result<(), OperationError> {
-        cand.iter_mut().try_for_each(apply_gidnumber)
+        apply_gidnumber(qs, cand)
+    }
+
+    #[instrument(level = "debug", name = "gidnumber::verify", skip_all)]
+    fn verify(qs: &mut QueryServerReadTransaction) -> Vec<Result<(), ConsistencyError>> {
+        let ranges = match load_gid_number_ranges(qs) {
+            Ok(ranges) => ranges,
+            Err(err) => {
+                error!(?err, "Unable to load gid number ranges");
+                return vec![Err(ConsistencyError::QueryServerSearchFailure)];
+            }
+        };
+
+        let mut results = Vec::with_capacity(0);
+
+        // Any gid inside a range must belong to an entry tagged with that range, else
+        // the range may allocate it a second time.
+        for range in ranges.iter() {
+            match qs.internal_search(range.filter()) {
+                Ok(entries) => {
+                    for entry in entries {
+                        if entry.get_ava_single_iutf8(Attribute::GidNumberRangeName)
+                            != Some(range.name.as_str())
+                        {
+                            error!(
+                                uuid = %entry.get_uuid(),
+                                range = %range.name,
+                                "Entry has a gid number inside a range it does not belong to"
+                            );
+                            results.push(Err(ConsistencyError::GidNumberRangeCollision(
+                                entry.get_uuid(),
+                            )));
+                        }
+                    }
+                }
+                Err(err) => {
+                    error!(?err);
+                    results.push(Err(ConsistencyError::QueryServerSearchFailure))
+                }
+            }
+        }
+
+        results
     }
 }
 
@@ -397,4 +614,167 @@ mod tests {
 
         assert!(server_txn.commit().is_ok());
     }
+
+    #[qs_test]
+    async fn test_gidnumber_range_allocate(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await.expect("txn");
+
+        // Overlapping ranges are rejected.
+        let modlist = modlist!([
+            m_pres(
+                Attribute::GidNumberRange,
+                &Value::new_iutf8("alpha:200000-200010")
+            ),
+            m_pres(
+                Attribute::GidNumberRange,
+                &Value::new_iutf8("beta:200005-200020")
+            )
+        ]);
+        assert_eq!(
+            server_txn.internal_modify_uuid(UUID_DOMAIN_INFO, &modlist),
+            Err(OperationError::PL0003GidNumberRangeInvalid)
+        );
+
+        // As are ranges outside of the permitted gid ranges.
+        let modlist = modlist!([m_pres(
+            Attribute::GidNumberRange,
+            &Value::new_iutf8("alpha:100-200")
+        )]);
+        assert_eq!(
+            server_txn.internal_modify_uuid(UUID_DOMAIN_INFO, &modlist),
+            Err(OperationError::PL0003GidNumberRangeInvalid)
+        );
+
+        let modlist = modlist!([
+            m_pres(
+                Attribute::GidNumberRange,
+                &Value::new_iutf8("alpha:200000-200001")
+            ),
+            m_pres(
+                Attribute::GidNumberRange,
+                &Value::new_iutf8("beta:300000-300009")
+            )
+        ]);
+        server_txn
+            .internal_modify_uuid(UUID_DOMAIN_INFO, &modlist)
+            .expect("Unable to set gid number ranges");
+
+        let group_a_uuid = Uuid::new_v4();
+        let group_b_uuid = Uuid::new_v4();
+        let group_c_uuid = Uuid::new_v4();
+
+        // Allocate from both ranges in a single create.
+        server_txn
+            .internal_create(vec![
+                entry_init!(
+                    (Attribute::Class, EntryClass::Group.to_value()),
+                    (Attribute::Class, EntryClass::PosixGroup.to_value()),
+                    (Attribute::Name, Value::new_iname("testgroup_a")),
+                    (Attribute::Uuid, Value::Uuid(group_a_uuid)),
+                    (Attribute::GidNumberRangeName, Value::new_iutf8("alpha"))
+                ),
+                entry_init!(
+                    (Attribute::Class, EntryClass::Group.to_value()),
+                    (Attribute::Class, EntryClass::PosixGroup.to_value()),
+                    (Attribute::Name, Value::new_iname("testgroup_b")),
+                    (Attribute::Uuid, Value::Uuid(group_b_uuid)),
+                    (Attribute::GidNumberRangeName, Value::new_iutf8("alpha"))
+                ),
+                entry_init!(
+                    (Attribute::Class, EntryClass::Group.to_value()),
+                    (Attribute::Class, EntryClass::PosixGroup.to_value()),
+                    (Attribute::Name, Value::new_iname("testgroup_c")),
+                    (Attribute::Uuid, Value::Uuid(group_c_uuid)),
+                    (Attribute::GidNumberRangeName, Value::new_iutf8("beta"))
+                ),
+            ])
+            .expect("Unable to create groups");
+
+        let gid_of = |server_txn: &mut QueryServerWriteTransaction, uuid: Uuid| {
+            server_txn
+                .internal_search_uuid(uuid)
+                .expect("Unable to access group")
+                .get_ava_single_uint32(Attribute::GidNumber)
+                .expect("gidnumber not present on group")
+        };
+
+        assert_eq!(gid_of(&mut server_txn, group_a_uuid), 200000);
+        assert_eq!(gid_of(&mut server_txn, group_b_uuid), 200001);
+        assert_eq!(gid_of(&mut server_txn, group_c_uuid), 300000);
+
+        // The alpha range is now exhausted.
+        let op_result = server_txn.internal_create(vec![entry_init!(
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Class, EntryClass::PosixGroup.to_value()),
+            (Attribute::Name, Value::new_iname("testgroup_d")),
+            (Attribute::GidNumberRangeName, Value::new_iutf8("alpha"))
+        )]);
+        assert_eq!(
+            op_result,
+            Err(OperationError::PL0005GidNumberRangeExhausted)
+        );
+
+        // Ranges that don't exist can't be allocated from.
+        let op_result = server_txn.internal_create(vec![entry_init!(
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Class, EntryClass::PosixGroup.to_value()),
+            (Attribute::Name, Value::new_iname("testgroup_e")),
+            (Attribute::GidNumberRangeName, Value::new_iutf8("gamma"))
+        )]);
+        assert_eq!(op_result, Err(OperationError::PL0004GidNumberRangeNotFound));
+
+        // Once a gid is freed it can be allocated again.
+        server_txn
+            .internal_modify_uuid(group_a_uuid, &modlist!([m_purge(Attribute::GidNumber)]))
+            .expect("Unable to modify group");
+        assert_eq!(gid_of(&mut server_txn, group_a_uuid), 200000);
+
+        assert!(server_txn.commit().is_ok());
+    }
+
+    #[qs_test]
+    async fn test_gidnumber_range_collision(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await.expect("txn");
+
+        let modlist = modlist!([m_pres(
+            Attribute::GidNumberRange,
+            &Value::new_iutf8("alpha:200000-200009")
+        )]);
+        server_txn
+            .internal_modify_uuid(UUID_DOMAIN_INFO, &modlist)
+            .expect("Unable to set gid number ranges");
+
+        // An explicit gid inside a range the entry isn't tagged with.
+        let group_uuid = Uuid::new_v4();
+        server_txn
+            .internal_create(vec![entry_init!(
+                (Attribute::Class, EntryClass::Group.to_value()),
+                (Attribute::Class, EntryClass::PosixGroup.to_value()),
+                (Attribute::Name, Value::new_iname("testgroup_a")),
+                (Attribute::Uuid, Value::Uuid(group_uuid)),
+                (Attribute::GidNumber, Value::Uint32(200005))
+            )])
+            .expect("Unable to create group");
+
+        assert!(server_txn.commit().is_ok());
+
+        let results = server.verify().await;
+        assert!(results.contains(&Err(ConsistencyError::GidNumberRangeCollision(group_uuid))));
+
+        // Tagging the entry with the range resolves the collision.
+        let mut server_txn = server.write(duration_from_epoch_now()).await.expect("txn");
+        server_txn
+            .internal_modify_uuid(
+                group_uuid,
+                &modlist!([m_pres(
+                    Attribute::GidNumberRangeName,
+                    &Value::new_iutf8("alpha")
+                )]),
+            )
+            .expect("Unable to modify group");
+        assert!(server_txn.commit().is_ok());
+
+        let results = server.verify().await;
+        assert!(!results.contains(&Err(ConsistencyError::GidNumberRangeCollision(group_uuid))));
+    }
 }
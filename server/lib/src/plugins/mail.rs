@@ -0,0 +1,116 @@
+// Track which mail addresses on an entry have been verified. Addresses provided by
+// administrators or sync providers are trusted, but addresses a person adds to their
+// own entry must be verified before they can be relied upon.
+//
+// Unverified addresses need a newer database encoding that older replicas can't read,
+// so until the domain level shows that every replica supports it, all addresses are
+// trusted as they were before verification was tracked.
+use crate::entry::{EntryInvalidCommitted, EntrySealedCommitted};
+use crate::event::{CreateEvent, ModifyEvent};
+use crate::plugins::Plugin;
+use crate::prelude::*;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+pub struct Mail {}
+
+impl Plugin for Mail {
+    fn id() -> &'static str {
+        "plugin_mail"
+    }
+
+    #[instrument(level = "debug", name = "mail_pre_create_transform", skip_all)]
+    fn pre_create_transform(
+        _qs: &mut QueryServerWriteTransaction,
+        cand: &mut Vec<EntryInvalidNew>,
+        _ce: &CreateEvent,
+    ) -> Result<(), OperationError> {
+        // A person can never create their own entry, so all addresses are trusted.
+        for ent in cand.iter_mut() {
+            if let Some(addresses) = ent
+                .get_ava_set(Attribute::Mail)
+                .and_then(|vs| vs.as_emailaddress_set())
+                .map(|(_primary, set)| set.clone())
+            {
+                if let Some(verified) = ent.get_ava_mail_verified_mut(Attribute::Mail) {
+                    *verified = addresses;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "mail_pre_modify", skip_all)]
+    fn pre_modify(
+        qs: &mut QueryServerWriteTransaction,
+        pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut Vec<EntryInvalidCommitted>,
+        me: &ModifyEvent,
+    ) -> Result<(), OperationError> {
+        Self::modify_inner(qs, pre_cand, cand, &me.ident)
+    }
+
+    #[instrument(level = "debug", name = "mail_pre_batch_modify", skip_all)]
+    fn pre_batch_modify(
+        qs: &mut QueryServerWriteTransaction,
+        pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut Vec<EntryInvalidCommitted>,
+        me: &BatchModifyEvent,
+    ) -> Result<(), OperationError> {
+        Self::modify_inner(qs, pre_cand, cand, &me.ident)
+    }
+}
+
+impl Mail {
+    fn modify_inner(
+        qs: &mut QueryServerWriteTransaction,
+        pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut [EntryInvalidCommitted],
+        ident: &Identity,
+    ) -> Result<(), OperationError> {
+        let empty = BTreeSet::new();
+        let track_unverified = qs.get_domain_version() >= DOMAIN_LEVEL_1_12;
+
+        for (pre, post) in pre_cand.iter().zip(cand.iter_mut()) {
+            let Some((_primary, addresses)) = post
+                .get_ava_set(Attribute::Mail)
+                .and_then(|vs| vs.as_emailaddress_set())
+            else {
+                continue;
+            };
+
+            let current = post
+                .get_ava_mail_verified(Attribute::Mail)
+                .unwrap_or(&empty);
+
+            let (pre_addresses, pre_verified) = pre
+                .get_ava_set(Attribute::Mail)
+                .and_then(|vs| vs.as_emailaddress_set())
+                .map(|(_primary, set)| set)
+                .zip(pre.get_ava_mail_verified(Attribute::Mail))
+                .unwrap_or((&empty, &empty));
+
+            let self_service = track_unverified && Some(ident.get_uuid()) == post.get_uuid();
+
+            // Addresses keep their prior state, and new addresses are only trusted if
+            // they were not added by the owner of the entry.
+            let verified: BTreeSet<String> = addresses
+                .iter()
+                .filter(|address| {
+                    current.contains(*address)
+                        || pre_verified.contains(*address)
+                        || (!self_service && !pre_addresses.contains(*address))
+                })
+                .cloned()
+                .collect();
+
+            if &verified != current {
+                if let Some(current) = post.get_ava_mail_verified_mut(Attribute::Mail) {
+                    *current = verified;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+// Keep the expiry of group memberships consistent with the members of the group. When
+// a member is removed by any means, the expiry of its membership is removed with it.
+use crate::entry::{EntryInvalidCommitted, EntrySealedCommitted};
+use crate::event::{CreateEvent, ModifyEvent};
+use crate::plugins::Plugin;
+use crate::prelude::*;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+pub struct MemberExpiry {}
+
+impl Plugin for MemberExpiry {
+    fn id() -> &'static str {
+        "plugin_member_expiry"
+    }
+
+    #[instrument(level = "debug", name = "member_expiry_pre_create_transform", skip_all)]
+    fn pre_create_transform(
+        _qs: &mut QueryServerWriteTransaction,
+        cand: &mut Vec<EntryInvalidNew>,
+        _ce: &CreateEvent,
+    ) -> Result<(), OperationError> {
+        cand.iter_mut().for_each(Self::remove_orphaned_expiry);
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "member_expiry_pre_modify", skip_all)]
+    fn pre_modify(
+        _qs: &mut QueryServerWriteTransaction,
+        _pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut Vec<EntryInvalidCommitted>,
+        _me: &ModifyEvent,
+    ) -> Result<(), OperationError> {
+        cand.iter_mut().for_each(Self::remove_orphaned_expiry);
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "member_expiry_pre_batch_modify", skip_all)]
+    fn pre_batch_modify(
+        _qs: &mut QueryServerWriteTransaction,
+        _pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut Vec<EntryInvalidCommitted>,
+        _me: &BatchModifyEvent,
+    ) -> Result<(), OperationError> {
+        cand.iter_mut().for_each(Self::remove_orphaned_expiry);
+        Ok(())
+    }
+}
+
+impl MemberExpiry {
+    fn remove_orphaned_expiry<STATE: Clone>(ent: &mut Entry<EntryInvalid, STATE>) {
+        let Some(expiry) = ent.get_ava_member_expiry(Attribute::MemberExpiry) else {
+            return;
+        };
+
+        let members = ent.get_ava_refer(Attribute::Member);
+
+        let orphaned: BTreeSet<PartialValue> = expiry
+            .keys()
+            .filter(|u| !members.is_some_and(|m| m.contains(*u)))
+            .copied()
+            .map(PartialValue::MemberExpiry)
+            .collect();
+
+        if !orphaned.is_empty() {
+            trace!(
+                ?orphaned,
+                "removing expiry of memberships that no longer exist"
+            );
+            ent.remove_avas(Attribute::MemberExpiry, &orphaned);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use time::OffsetDateTime;
+
+    #[qs_test]
+    async fn test_member_expiry_removed_with_member(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+
+        let member_a = Uuid::new_v4();
+        let member_b = Uuid::new_v4();
+        let group = Uuid::new_v4();
+        let expire_at = OffsetDateTime::UNIX_EPOCH + Duration::from_secs(86400);
+
+        let entries = [member_a, member_b].into_iter().enumerate().map(|(i, u)| {
+            entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Group.to_value()),
+                (Attribute::Name, Value::new_iname(&format!("member_{i}"))),
+                (Attribute::Uuid, Value::Uuid(u))
+            )
+        });
+
+        // An expiry of an entry that is not a member is never retained.
+        let group_entry = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Name, Value::new_iname("expiring_group")),
+            (Attribute::Uuid, Value::Uuid(group)),
+            (Attribute::Member, Value::Refer(member_a)),
+            (
+                Attribute::MemberExpiry,
+                Value::MemberExpiry(member_a, expire_at)
+            ),
+            (
+                Attribute::MemberExpiry,
+                Value::MemberExpiry(member_b, expire_at)
+            )
+        );
+
+        assert!(server_txn
+            .internal_create(entries.chain(std::iter::once(group_entry)).collect())
+            .is_ok());
+
+        let entry = server_txn.internal_search_uuid(group).unwrap();
+        let expiry = entry
+            .get_ava_member_expiry(Attribute::MemberExpiry)
+            .unwrap();
+        assert!(expiry.contains_key(&member_a));
+        assert!(!expiry.contains_key(&member_b));
+
+        // Removing the member removes its expiry.
+        assert!(server_txn
+            .internal_modify_uuid(
+                group,
+                &ModifyList::new_remove(Attribute::Member, PartialValue::Refer(member_a)),
+            )
+            .is_ok());
+
+        let entry = server_txn.internal_search_uuid(group).unwrap();
+        assert!(entry.get_ava_set(Attribute::MemberExpiry).is_none());
+
+        assert!(server_txn.commit().is_ok());
+    }
+}
@@ -21,6 +21,12 @@ use crate::value::PartialValue;
 
 pub struct MemberOf;
 
+/// The maximum depth of nested groups that a local change may resolve. Each level of nesting
+/// needs another pass over the affected groups, so this bounds the work that a single change
+/// can cause. Groups that are part of a membership cycle don't deepen the nesting of their
+/// members, so cycles converge without counting towards this.
+const MEMBEROF_MAX_NESTING_DEPTH: usize = 128;
+
 fn do_group_memberof(
     qs: &mut QueryServerWriteTransaction,
     uuid: Uuid,
@@ -247,9 +253,8 @@ fn do_leaf_memberof(
         .into_iter()
         .try_for_each(|(auuid, (pre, tgte))| {
             // Only write if a change occurred.
-            if pre.get_ava_set(Attribute::MemberOf) != tgte.get_ava_set(Attribute::MemberOf)
-                || pre.get_ava_set(Attribute::DirectMemberOf)
-                    != tgte.get_ava_set(Attribute::DirectMemberOf)
+            if pre.diff_ava(&tgte, Attribute::MemberOf).is_changed()
+                || pre.diff_ava(&tgte, Attribute::DirectMemberOf).is_changed()
             {
                 trace!("=> processing affected uuid {:?}", auuid);
 
@@ -299,7 +304,12 @@ fn apply_memberof(
     qs: &mut QueryServerWriteTransaction,
     // TODO: Experiment with HashSet/BTreeSet here instead of vec.
     // May require https://github.com/rust-lang/rust/issues/62924 to allow popping
-    mut affected_uuids: BTreeSet<Uuid>,
+    affected_uuids: BTreeSet<Uuid>,
+    // If set, reject any change that causes a group to become a member of itself.
+    deny_cycles: bool,
+    // If set, reject any change that nests groups more deeply than this. This only applies
+    // to local changes, as replicated changes must always be accepted.
+    max_depth: Option<usize>,
 ) -> Result<(), OperationError> {
     trace!(" => entering apply_memberof");
 
@@ -311,6 +321,11 @@ fn apply_memberof(
     // changing.
     let mut all_affected_uuids: BTreeSet<_> = affected_uuids.iter().copied().collect();
 
+    // The affected uuids, and the depth of nesting at which they were reached from the
+    // groups that changed.
+    let mut affected_uuids: BTreeMap<Uuid, usize> =
+        affected_uuids.into_iter().map(|u| (u, 1)).collect();
+
     // While there are still affected uuids.
     while !affected_uuids.is_empty() {
         trace!(?affected_uuids);
@@ -320,7 +335,7 @@ fn apply_memberof(
             f_eq(Attribute::Class, EntryClass::Group.into()),
             FC::Or(
                 affected_uuids
-                    .iter()
+                    .keys()
                     .copied()
                     .map(|u| f_eq(Attribute::Uuid, PartialValue::Uuid(u)))
                     .collect()
@@ -328,26 +343,53 @@ fn apply_memberof(
         ]));
 
         // Clear the set for the next iteration
-        affected_uuids.clear();
+        let depths = std::mem::take(&mut affected_uuids);
 
         let work_set = qs.internal_search_writeable(&filt)?;
         let mut changes = Vec::with_capacity(work_set.len());
 
         for (pre, mut tgte) in work_set.into_iter() {
             let guuid = pre.get_uuid();
+            let depth = depths.get(&guuid).copied().unwrap_or(1);
 
             trace!(
-                "=> processing group update -> {:?} {}",
+                "=> processing group update -> {:?} {} at depth {}",
                 guuid,
-                tgte.get_display_id()
+                tgte.get_display_id(),
+                depth
             );
 
+            if let Some(max_depth) = max_depth.filter(|max_depth| depth > *max_depth) {
+                error!(
+                    group_id = %tgte.get_display_id(),
+                    ?max_depth,
+                    "Groups are nested too deeply to resolve memberof"
+                );
+                return Err(OperationError::PL0008MembershipNestingTooDeep);
+            }
+
             do_group_memberof(qs, guuid, &mut tgte)?;
 
+            // A group that is now a member of itself has become part of a membership cycle.
+            let self_ref = PartialValue::Refer(guuid);
+            let in_cycle = tgte.attribute_equality(Attribute::MemberOf, &self_ref);
+            if in_cycle && !pre.attribute_equality(Attribute::MemberOf, &self_ref) {
+                if deny_cycles {
+                    error!(
+                        group_id = %tgte.get_display_id(),
+                        "Denying change that creates a group membership cycle"
+                    );
+                    return Err(OperationError::PL0007MembershipCycle);
+                }
+                warn!(
+                    group_id = %tgte.get_display_id(),
+                    "Group membership cycle detected"
+                );
+            }
+
             // Did we change? Note we don't check if the class changed, only if mo changed.
-            if pre.get_ava_set(Attribute::MemberOf) != tgte.get_ava_set(Attribute::MemberOf)
-                || pre.get_ava_set(Attribute::DirectMemberOf)
-                    != tgte.get_ava_set(Attribute::DirectMemberOf)
+            if pre.diff_ava(&tgte, Attribute::MemberOf).is_changed()
+                || pre.diff_ava(&tgte, Attribute::DirectMemberOf).is_changed()
             {
                 // Yes we changed - we now must process all our members, as they need to
                 // inherit changes. Some of these members COULD be non groups, but we
@@ -361,17 +403,19 @@ fn apply_memberof(
                 // Since our groups memberof (and related, direct member of) has changed, we
                 // need to propagate these values forward into our members. At this point we
                 // mark all our members as being part of the affected set.
+                let mut members = BTreeSet::new();
+
                 let pre_member = pre.get_ava_refer(Attribute::Member);
                 let post_member = tgte.get_ava_refer(Attribute::Member);
 
                 match (pre_member, post_member) {
                     (Some(pre_m), Some(post_m)) => {
-                        affected_uuids.extend(pre_m);
-                        affected_uuids.extend(post_m);
+                        members.extend(pre_m);
+                        members.extend(post_m);
                     }
-                    (Some(members), None) | (None, Some(members)) => {
+                    (Some(m), None) | (None, Some(m)) => {
                         // Doesn't matter what order, just that they are affected
-                        affected_uuids.extend(members);
+                        members.extend(m);
                     }
                     (None, None) => {}
                 };
@@ -381,16 +425,24 @@ fn apply_memberof(
 
                 match (pre_dynmember, post_dynmember) {
                     (Some(pre_m), Some(post_m)) => {
-                        affected_uuids.extend(pre_m);
-                        affected_uuids.extend(post_m);
+                        members.extend(pre_m);
+                        members.extend(post_m);
                     }
-                    (Some(members), None) | (None, Some(members)) => {
+                    (Some(m), None) | (None, Some(m)) => {
                         // Doesn't matter what order, just that they are affected
-                        affected_uuids.extend(members);
+                        members.extend(m);
                     }
                     (None, None) => {}
                 };
 
+                // Members of a group within a cycle are revisited each time the cycle's
+                // memberof grows, which is not a deeper level of nesting.
+                let member_depth = if in_cycle { depth } else { depth + 1 };
+                for member in members {
+                    let member_depth_entry = affected_uuids.entry(member).or_insert(member_depth);
+                    *member_depth_entry = (*member_depth_entry).max(member_depth);
+                }
+
                 // push the entries to pre/cand
                 changes.push((pre, tgte));
             } else {
@@ -419,7 +471,7 @@ fn apply_memberof(
         }
 
         // Reflect the full set of affected uuids into our all affected set.
-        all_affected_uuids.extend(affected_uuids.iter());
+        all_affected_uuids.extend(affected_uuids.keys());
 
         // Next loop!
         trace!("-------------------------------------");
@@ -440,7 +492,14 @@ impl Plugin for MemberOf {
         cand: &[Entry<EntrySealed, EntryCommitted>],
         ce: &CreateEvent,
     ) -> Result<(), OperationError> {
-        Self::post_create_inner(qs, cand, &ce.ident)
+        let deny_cycles = qs.domain_info().deny_membership_cycles();
+        Self::post_create_inner(
+            qs,
+            cand,
+            &ce.ident,
+            deny_cycles,
+            Some(MEMBEROF_MAX_NESTING_DEPTH),
+        )
     }
 
     #[instrument(level = "debug", name = "memberof_post_repl_refresh", skip_all)]
@@ -449,7 +508,9 @@ impl Plugin for MemberOf {
         cand: &[Entry<EntrySealed, EntryCommitted>],
     ) -> Result<(), OperationError> {
         let ident = Identity::from_internal();
-        Self::post_create_inner(qs, cand, &ident)
+        // Replicated changes must always be accepted, so cycles and deep nesting are never
+        // denied here.
+        Self::post_create_inner(qs, cand, &ident, false, None)
     }
 
     #[instrument(level = "debug", name = "memberof_post_repl_incremental", skip_all)]
@@ -476,6 +537,8 @@ impl Plugin for MemberOf {
             cand,
             &ident_internal,
             force_dyngroup_cand_update,
+            false,
+            None,
         )
     }
 
@@ -486,7 +549,16 @@ impl Plugin for MemberOf {
         cand: &[Entry<EntrySealed, EntryCommitted>],
         me: &ModifyEvent,
     ) -> Result<(), OperationError> {
-        Self::post_modify_inner(qs, pre_cand, cand, &me.ident, false)
+        let deny_cycles = qs.domain_info().deny_membership_cycles();
+        Self::post_modify_inner(
+            qs,
+            pre_cand,
+            cand,
+            &me.ident,
+            false,
+            deny_cycles,
+            Some(MEMBEROF_MAX_NESTING_DEPTH),
+        )
     }
 
     #[instrument(level = "debug", name = "memberof_post_batch_modify", skip_all)]
@@ -496,7 +568,16 @@ impl Plugin for MemberOf {
         cand: &[Entry<EntrySealed, EntryCommitted>],
         me: &BatchModifyEvent,
     ) -> Result<(), OperationError> {
-        Self::post_modify_inner(qs, pre_cand, cand, &me.ident, false)
+        let deny_cycles = qs.domain_info().deny_membership_cycles();
+        Self::post_modify_inner(
+            qs,
+            pre_cand,
+            cand,
+            &me.ident,
+            false,
+            deny_cycles,
+            Some(MEMBEROF_MAX_NESTING_DEPTH),
+        )
     }
 
     #[instrument(level = "debug", name = "memberof_pre_delete", skip_all)]
@@ -554,7 +635,8 @@ impl Plugin for MemberOf {
             )
             .collect();
 
-        apply_memberof(qs, affected_uuids)
+        // Removing groups can never create a cycle.
+        apply_memberof(qs, affected_uuids, false, Some(MEMBEROF_MAX_NESTING_DEPTH))
     }
 
     #[instrument(level = "debug", name = "memberof::verify", skip_all)]
@@ -668,6 +750,8 @@ impl MemberOf {
         qs: &mut QueryServerWriteTransaction,
         cand: &[Entry<EntrySealed, EntryCommitted>],
         ident: &Identity,
+        deny_cycles: bool,
+        max_depth: Option<usize>,
     ) -> Result<(), OperationError> {
         let dyngroup_change = super::dyngroup::DynGroup::post_create(qs, cand, ident)?;
 
@@ -690,7 +774,7 @@ impl MemberOf {
             )
             .collect();
 
-        apply_memberof(qs, affected_uuids)
+        apply_memberof(qs, affected_uuids, deny_cycles, max_depth)
     }
 
     fn post_modify_inner(
@@ -699,6 +783,8 @@ impl MemberOf {
         cand: &[EntrySealedCommitted],
         ident: &Identity,
         force_dyngroup_cand_update: bool,
+        deny_cycles: bool,
+        max_depth: Option<usize>,
     ) -> Result<(), OperationError> {
         let dyngroup_change = super::dyngroup::DynGroup::post_modify(
             qs,
@@ -718,44 +804,113 @@ impl MemberOf {
             post.attribute_equality(Attribute::Class, &EntryClass::Group.into())
                 || pre.attribute_equality(Attribute::Class, &EntryClass::Group.into())
         }) {
-            let pre_member = pre.get_ava_refer(Attribute::Member);
-            let post_member = post.get_ava_refer(Attribute::Member);
+            // Show only the *changed* uuids for leaf resolution. If the attribute was
+            // added or purged, then all members are affected.
+            for attr in [Attribute::Member, Attribute::DynMember] {
+                affected_uuids.extend(pre.diff_ava(post, attr).changed_values().filter_map(|pv| {
+                    match pv {
+                        PartialValue::Refer(u) => Some(*u),
+                        _ => None,
+                    }
+                }));
+            }
+        }
 
-            match (pre_member, post_member) {
-                (Some(pre_m), Some(post_m)) => {
-                    // Show only the *changed* uuids for leaf resolution.
-                    affected_uuids.extend(pre_m.symmetric_difference(post_m));
-                }
-                (Some(members), None) | (None, Some(members)) => {
-                    // Doesn't matter what order, just that they are affected
-                    affected_uuids.extend(members);
-                }
-                (None, None) => {}
-            };
+        apply_memberof(qs, affected_uuids, deny_cycles, max_depth)
+    }
 
-            let pre_dynmember = pre.get_ava_refer(Attribute::DynMember);
-            let post_dynmember = post.get_ava_refer(Attribute::DynMember);
+    /// Find the group membership cycles in the database. Cycles are permitted, so these
+    /// are reported as warnings rather than inconsistencies, but they are rarely intended.
+    #[instrument(level = "debug", name = "memberof::verify_warnings", skip_all)]
+    pub(crate) fn verify_warnings(qs: &mut QueryServerReadTransaction) -> Vec<ConsistencyError> {
+        let filt = filter!(f_eq(Attribute::Class, EntryClass::Group.into()));
 
-            match (pre_dynmember, post_dynmember) {
-                (Some(pre_m), Some(post_m)) => {
-                    // Show only the *changed* uuids.
-                    affected_uuids.extend(pre_m.symmetric_difference(post_m));
-                }
-                (Some(members), None) | (None, Some(members)) => {
-                    // Doesn't matter what order, just that they are affected
-                    affected_uuids.extend(members);
-                }
-                (None, None) => {}
-            };
+        let Ok(groups) = qs.internal_search(filt) else {
+            return vec![ConsistencyError::QueryServerSearchFailure];
+        };
+
+        let membership: BTreeMap<Uuid, BTreeSet<Uuid>> = groups
+            .iter()
+            .map(|group| {
+                let members = group
+                    .get_ava_refer(Attribute::Member)
+                    .into_iter()
+                    .flat_map(|set| set.iter())
+                    .chain(
+                        group
+                            .get_ava_refer(Attribute::DynMember)
+                            .into_iter()
+                            .flat_map(|set| set.iter()),
+                    )
+                    .copied()
+                    .collect();
+                (group.get_uuid(), members)
+            })
+            .collect();
+
+        find_membership_cycles(&membership)
+            .into_iter()
+            .map(|cycle| {
+                warn!(?cycle, "Group membership cycle");
+                ConsistencyError::MemberOfCycle(cycle)
+            })
+            .collect()
+    }
+}
+
+/// Find the cycles in a map of groups to their members. Each cycle is the path of groups
+/// that leads back to the first, and starts with its lowest uuid so that it is only
+/// reported once.
+fn find_membership_cycles(membership: &BTreeMap<Uuid, BTreeSet<Uuid>>) -> BTreeSet<Vec<Uuid>> {
+    let members_of = |group: &Uuid| membership.get(group).into_iter().flatten();
+
+    let mut visited = BTreeSet::new();
+    let mut cycles = BTreeSet::new();
+
+    for root in membership.keys() {
+        if !visited.insert(*root) {
+            continue;
         }
 
-        apply_memberof(qs, affected_uuids)
+        // This is a depth first search, using an explicit stack so that deep nesting can't
+        // exhaust the call stack. Each group on the current path is paired with the members
+        // that remain to be visited.
+        let mut path = vec![*root];
+        let mut remaining = vec![members_of(root)];
+
+        while let Some(members) = remaining.last_mut() {
+            let Some(member) = members.next() else {
+                path.pop();
+                remaining.pop();
+                continue;
+            };
+
+            if let Some(start) = path.iter().position(|u| u == member) {
+                // We have returned to a group on the current path.
+                let mut cycle = path.get(start..).unwrap_or_default().to_vec();
+                if let Some(lowest) = cycle
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, u)| **u)
+                    .map(|(i, _)| i)
+                {
+                    cycle.rotate_left(lowest);
+                }
+                cycles.insert(cycle);
+            } else if visited.insert(*member) {
+                path.push(*member);
+                remaining.push(members_of(member));
+            }
+        }
     }
+
+    cycles
 }
 
 #[cfg(test)]
 mod tests {
     use crate::prelude::*;
+    use std::collections::{BTreeMap, BTreeSet};
 
     const UUID_A: &str = "aaaaaaaa-f82e-4484-a407-181aa03bda5c";
     const UUID_B: &str = "bbbbbbbb-2438-4384-9891-48f4c8172e9b";
@@ -1921,4 +2076,224 @@ mod tests {
             }
         );
     }
+
+    #[qs_test]
+    async fn test_memberof_cycle_verify_warning(server: &QueryServer) {
+        let uuid_a = uuid::uuid!(UUID_A);
+        let uuid_b = uuid::uuid!(UUID_B);
+        let uuid_c = uuid::uuid!(UUID_C);
+        let uuid_d = uuid::uuid!(UUID_D);
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await.expect("txn");
+        assert!(server_txn
+            .internal_create(vec![EA.clone(), EB.clone(), EC.clone(), ED.clone()])
+            .is_ok());
+
+        // A cycle created by two successive modifications.
+        assert!(server_txn
+            .internal_modify_uuid(
+                uuid_b,
+                &ModifyList::new_append(Attribute::Member, Value::Refer(uuid_a))
+            )
+            .is_ok());
+        assert!(server_txn
+            .internal_modify_uuid(
+                uuid_a,
+                &ModifyList::new_append(Attribute::Member, Value::Refer(uuid_b))
+            )
+            .is_ok());
+
+        // A cycle created in a single batch, as a sync import would.
+        assert!(server_txn
+            .internal_batch_modify(
+                [
+                    (
+                        uuid_c,
+                        ModifyList::new_append(Attribute::Member, Value::Refer(uuid_d))
+                    ),
+                    (
+                        uuid_d,
+                        ModifyList::new_append(Attribute::Member, Value::Refer(uuid_c))
+                    ),
+                ]
+                .into_iter()
+            )
+            .is_ok());
+
+        // Cycles are permitted, and each group sees itself through the cycle.
+        let group_a = server_txn
+            .internal_search_uuid(uuid_a)
+            .expect("Unable to access group");
+        assert!(group_a.attribute_equality(Attribute::MemberOf, &PartialValue::Refer(uuid_a)));
+
+        assert!(server_txn.commit().is_ok());
+
+        let mut server_txn = server.read().await.expect("txn");
+        let warnings = server_txn.verify_warnings();
+
+        assert!(warnings.contains(&ConsistencyError::MemberOfCycle(vec![uuid_a, uuid_b])));
+        assert!(warnings.contains(&ConsistencyError::MemberOfCycle(vec![uuid_c, uuid_d])));
+    }
+
+    #[qs_test]
+    async fn test_memberof_cycle_denied(server: &QueryServer) {
+        let uuid_a = uuid::uuid!(UUID_A);
+        let uuid_b = uuid::uuid!(UUID_B);
+        let uuid_c = uuid::uuid!(UUID_C);
+        let uuid_d = uuid::uuid!(UUID_D);
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await.expect("txn");
+        assert!(server_txn
+            .internal_create(vec![EA.clone(), EB.clone(), EC.clone(), ED.clone()])
+            .is_ok());
+        assert!(server_txn
+            .internal_modify_uuid(
+                UUID_DOMAIN_INFO,
+                &ModifyList::new_purge_and_set(
+                    Attribute::DomainDenyMembershipCycles,
+                    Value::Bool(true)
+                )
+            )
+            .is_ok());
+        assert!(server_txn.commit().is_ok());
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await.expect("txn");
+
+        // Nesting without a cycle is still allowed.
+        assert!(server_txn
+            .internal_modify_uuid(
+                uuid_b,
+                &ModifyList::new_append(Attribute::Member, Value::Refer(uuid_a))
+            )
+            .is_ok());
+
+        assert_eq!(
+            server_txn.internal_modify_uuid(
+                uuid_a,
+                &ModifyList::new_append(Attribute::Member, Value::Refer(uuid_b))
+            ),
+            Err(OperationError::PL0007MembershipCycle)
+        );
+
+        assert_eq!(
+            server_txn.internal_batch_modify(
+                [
+                    (
+                        uuid_c,
+                        ModifyList::new_append(Attribute::Member, Value::Refer(uuid_d))
+                    ),
+                    (
+                        uuid_d,
+                        ModifyList::new_append(Attribute::Member, Value::Refer(uuid_c))
+                    ),
+                ]
+                .into_iter()
+            ),
+            Err(OperationError::PL0007MembershipCycle)
+        );
+    }
+
+    fn nested_groups(prefix: &str, count: usize) -> (Vec<Uuid>, Vec<EntryInitNew>) {
+        let uuids: Vec<Uuid> = (0..count).map(|_| Uuid::new_v4()).collect();
+        let entries = uuids
+            .iter()
+            .enumerate()
+            .map(|(i, uuid)| {
+                let mut entry = entry_init!(
+                    (Attribute::Class, EntryClass::Object.to_value()),
+                    (Attribute::Class, EntryClass::Group.to_value()),
+                    (Attribute::Name, Value::new_iname(&format!("{prefix}_{i}"))),
+                    (Attribute::Uuid, Value::Uuid(*uuid))
+                );
+                if let Some(member) = uuids.get(i + 1) {
+                    entry.add_ava(Attribute::Member, Value::Refer(*member));
+                }
+                entry
+            })
+            .collect();
+        (uuids, entries)
+    }
+
+    #[qs_test]
+    async fn test_memberof_nesting_depth_denied(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await.expect("txn");
+
+        let (_, entries) = nested_groups("testgroup_deep", super::MEMBEROF_MAX_NESTING_DEPTH + 2);
+        assert_eq!(
+            server_txn.internal_create(entries),
+            Err(OperationError::PL0008MembershipNestingTooDeep)
+        );
+    }
+
+    #[qs_test]
+    async fn test_memberof_nesting_depth_ignores_cycles(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await.expect("txn");
+
+        // A ring of groups, which are all members of each other.
+        let (ring, mut entries) = nested_groups("testgroup_ring", 10);
+        entries[9].add_ava(Attribute::Member, Value::Refer(ring[0]));
+        assert!(server_txn.internal_create(entries).is_ok());
+
+        // A chain of groups above the ring, that is nested less deeply than the limit.
+        let (chain, mut entries) =
+            nested_groups("testgroup_chain", super::MEMBEROF_MAX_NESTING_DEPTH - 8);
+        entries
+            .last_mut()
+            .expect("No groups")
+            .add_ava(Attribute::Member, Value::Refer(ring[0]));
+        assert!(server_txn.internal_create(entries).is_ok());
+
+        // Changing the top of the chain takes more passes than the limit to converge, as the
+        // ring is revisited, but the ring doesn't deepen the nesting.
+        let (_, entries) = nested_groups("testgroup_top", 1);
+        assert!(server_txn.internal_create(entries).is_ok());
+        let top = server_txn
+            .internal_search(filter!(f_eq(
+                Attribute::Name,
+                PartialValue::new_iname("testgroup_top_0")
+            )))
+            .expect("Unable to access group")
+            .pop()
+            .expect("No group")
+            .get_uuid();
+        assert!(server_txn
+            .internal_modify_uuid(
+                top,
+                &ModifyList::new_append(Attribute::Member, Value::Refer(chain[0]))
+            )
+            .is_ok());
+
+        let ring_member = server_txn
+            .internal_search_uuid(ring[5])
+            .expect("Unable to access group");
+        assert!(ring_member.attribute_equality(Attribute::MemberOf, &PartialValue::Refer(top)));
+    }
+
+    #[test]
+    fn test_find_membership_cycles() {
+        let groups: Vec<Uuid> = (0..100_000).map(|_| Uuid::new_v4()).collect();
+
+        // A long chain doesn't exhaust the stack, and has no cycles.
+        let mut membership: BTreeMap<Uuid, BTreeSet<Uuid>> = groups
+            .windows(2)
+            .map(|pair| (pair[0], BTreeSet::from([pair[1]])))
+            .collect();
+        assert!(super::find_membership_cycles(&membership).is_empty());
+
+        // Closing part of the chain into a loop reports the cycle once, from its lowest uuid.
+        membership.insert(groups[3], BTreeSet::from([groups[4], groups[1]]));
+        let mut expected = groups[1..4].to_vec();
+        let lowest = expected
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, u)| **u)
+            .map(|(i, _)| i)
+            .expect("No groups");
+        expected.rotate_left(lowest);
+
+        assert_eq!(
+            super::find_membership_cycles(&membership),
+            BTreeSet::from([expected])
+        );
+    }
 }
@@ -10,7 +10,9 @@ use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 
 mod attrunique;
+mod audit;
 mod base;
+mod creation_template;
 mod cred_import;
 mod default_values;
 mod domain;
@@ -19,13 +21,17 @@ mod eckeygen;
 pub(crate) mod gidnumber;
 pub(crate) mod hmac_name_unique;
 mod keyobject;
+mod mail;
+mod member_expiry;
 mod memberof;
 mod namehistory;
 mod oauth2;
 mod refint;
 mod session;
 mod spn;
+mod unixtemplate;
 mod valuedeny;
+mod webhook;
 
 trait Plugin {
     fn id() -> &'static str;
@@ -262,9 +268,14 @@ impl Plugins {
 
         gidnumber::GidNumber::pre_create_transform(qs, cand, ce)?;
         domain::Domain::pre_create_transform(qs, cand, ce)?;
+        unixtemplate::UnixTemplate::pre_create_transform(qs, cand, ce)?;
         spn::Spn::pre_create_transform(qs, cand, ce)?;
         default_values::DefaultValues::pre_create_transform(qs, cand, ce)?;
         namehistory::NameHistory::pre_create_transform(qs, cand, ce)?;
+        mail::Mail::pre_create_transform(qs, cand, ce)?;
+        member_expiry::MemberExpiry::pre_create_transform(qs, cand, ce)?;
+        creation_template::CreationTemplate::pre_create_transform(qs, cand, ce)?;
+        webhook::Webhook::pre_create_transform(qs, cand, ce)?;
         hmac_name_unique::HmacNameUnique::pre_create_transform(qs, cand, ce)?;
         // Should always be last
         attrunique::AttrUnique::pre_create_transform(qs, cand, ce)
@@ -279,6 +290,14 @@ impl Plugins {
         Ok(())
     }
 
+    /// Find the candidates of a create that conflict with each other on a unique attribute.
+    pub fn find_create_unique_conflicts<'a>(
+        qs: &QueryServerWriteTransaction,
+        cand: impl IntoIterator<Item = &'a Entry<EntrySealed, EntryNew>>,
+    ) -> Result<BTreeMap<Uuid, BTreeSet<Attribute>>, OperationError> {
+        attrunique::find_candidate_conflicts(qs, cand)
+    }
+
     #[instrument(level = "debug", name = "plugins::run_post_create", skip_all)]
     pub fn run_post_create(
         qs: &mut QueryServerWriteTransaction,
@@ -286,7 +305,9 @@ impl Plugins {
         ce: &CreateEvent,
     ) -> Result<(), OperationError> {
         refint::ReferentialIntegrity::post_create(qs, cand, ce)?;
-        memberof::MemberOf::post_create(qs, cand, ce)
+        memberof::MemberOf::post_create(qs, cand, ce)?;
+        audit::Audit::post_create(qs, cand, ce)?;
+        webhook::Webhook::post_create(qs, cand, ce)
     }
 
     #[instrument(level = "debug", name = "plugins::run_teardown_memorials", skip_all)]
@@ -315,10 +336,15 @@ impl Plugins {
 
         gidnumber::GidNumber::pre_modify(qs, pre_cand, cand, me)?;
         domain::Domain::pre_modify(qs, pre_cand, cand, me)?;
+        unixtemplate::UnixTemplate::pre_modify(qs, pre_cand, cand, me)?;
         spn::Spn::pre_modify(qs, pre_cand, cand, me)?;
         session::SessionConsistency::pre_modify(qs, pre_cand, cand, me)?;
         default_values::DefaultValues::pre_modify(qs, pre_cand, cand, me)?;
         namehistory::NameHistory::pre_modify(qs, pre_cand, cand, me)?;
+        mail::Mail::pre_modify(qs, pre_cand, cand, me)?;
+        member_expiry::MemberExpiry::pre_modify(qs, pre_cand, cand, me)?;
+        creation_template::CreationTemplate::pre_modify(qs, pre_cand, cand, me)?;
+        webhook::Webhook::pre_modify(qs, pre_cand, cand, me)?;
         hmac_name_unique::HmacNameUnique::pre_modify(qs, pre_cand, cand, me)?;
         // attr unique should always be last
         attrunique::AttrUnique::pre_modify(qs, pre_cand, cand, me)
@@ -333,7 +359,9 @@ impl Plugins {
     ) -> Result<(), OperationError> {
         refint::ReferentialIntegrity::post_modify(qs, pre_cand, cand, me)?;
         spn::Spn::post_modify(qs, pre_cand, cand, me)?;
-        memberof::MemberOf::post_modify(qs, pre_cand, cand, me)
+        memberof::MemberOf::post_modify(qs, pre_cand, cand, me)?;
+        audit::Audit::post_modify(qs, pre_cand, cand, me)?;
+        webhook::Webhook::post_modify(qs, pre_cand, cand, me)
     }
 
     #[instrument(level = "debug", name = "plugins::run_pre_batch_modify", skip_all)]
@@ -353,10 +381,15 @@ impl Plugins {
 
         gidnumber::GidNumber::pre_batch_modify(qs, pre_cand, cand, me)?;
         domain::Domain::pre_batch_modify(qs, pre_cand, cand, me)?;
+        unixtemplate::UnixTemplate::pre_batch_modify(qs, pre_cand, cand, me)?;
         spn::Spn::pre_batch_modify(qs, pre_cand, cand, me)?;
         session::SessionConsistency::pre_batch_modify(qs, pre_cand, cand, me)?;
         default_values::DefaultValues::pre_batch_modify(qs, pre_cand, cand, me)?;
         namehistory::NameHistory::pre_batch_modify(qs, pre_cand, cand, me)?;
+        mail::Mail::pre_batch_modify(qs, pre_cand, cand, me)?;
+        member_expiry::MemberExpiry::pre_batch_modify(qs, pre_cand, cand, me)?;
+        creation_template::CreationTemplate::pre_batch_modify(qs, pre_cand, cand, me)?;
+        webhook::Webhook::pre_batch_modify(qs, pre_cand, cand, me)?;
         hmac_name_unique::HmacNameUnique::pre_batch_modify(qs, pre_cand, cand, me)?;
         // attr unique should always be last
         attrunique::AttrUnique::pre_batch_modify(qs, pre_cand, cand, me)
@@ -371,7 +404,9 @@ impl Plugins {
     ) -> Result<(), OperationError> {
         refint::ReferentialIntegrity::post_batch_modify(qs, pre_cand, cand, me)?;
         spn::Spn::post_batch_modify(qs, pre_cand, cand, me)?;
-        memberof::MemberOf::post_batch_modify(qs, pre_cand, cand, me)
+        memberof::MemberOf::post_batch_modify(qs, pre_cand, cand, me)?;
+        audit::Audit::post_batch_modify(qs, pre_cand, cand, me)?;
+        webhook::Webhook::post_batch_modify(qs, pre_cand, cand, me)
     }
 
     #[instrument(level = "debug", name = "plugins::run_build_memorials", skip_all)]
@@ -400,7 +435,9 @@ impl Plugins {
         de: &DeleteEvent,
     ) -> Result<(), OperationError> {
         refint::ReferentialIntegrity::post_delete(qs, cand, de)?;
-        memberof::MemberOf::post_delete(qs, cand, de)
+        memberof::MemberOf::post_delete(qs, cand, de)?;
+        audit::Audit::post_delete(qs, cand, de)?;
+        webhook::Webhook::post_delete(qs, cand, de)
     }
 
     #[instrument(level = "debug", name = "plugins::run_pre_repl_refresh", skip_all)]
@@ -477,5 +514,14 @@ impl Plugins {
         run_verify_plugin!(qs, results, dyngroup::DynGroup);
         run_verify_plugin!(qs, results, memberof::MemberOf);
         run_verify_plugin!(qs, results, spn::Spn);
+        run_verify_plugin!(qs, results, gidnumber::GidNumber);
+    }
+
+    #[instrument(level = "debug", name = "plugins::run_verify_warnings", skip_all)]
+    pub fn run_verify_warnings(
+        qs: &mut QueryServerReadTransaction,
+        results: &mut Vec<ConsistencyError>,
+    ) {
+        results.extend(memberof::MemberOf::verify_warnings(qs));
     }
 }
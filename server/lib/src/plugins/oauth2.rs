@@ -44,6 +44,38 @@ impl Plugin for OAuth2 {
 }
 
 impl OAuth2 {
+    fn validate_token_expiry<T: Clone>(
+        entry: &Entry<EntryInvalid, T>,
+    ) -> Result<(), OperationError> {
+        let bounds = [
+            (
+                Attribute::OAuth2AccessTokenExpiry,
+                OAUTH2_ACCESS_TOKEN_EXPIRY_MIN..=OAUTH2_ACCESS_TOKEN_EXPIRY_MAX,
+            ),
+            (
+                Attribute::OAuth2RefreshTokenExpiry,
+                OAUTH_REFRESH_TOKEN_EXPIRY_MIN..=OAUTH_REFRESH_TOKEN_EXPIRY_MAX,
+            ),
+        ];
+
+        for (attr, range) in bounds {
+            if let Some(expiry) = entry.get_ava_single_uint32(&attr) {
+                if !range.contains(&expiry) {
+                    error!(
+                        "Requested {} ({}) is outside of the permitted range {} to {}",
+                        attr,
+                        expiry,
+                        range.start(),
+                        range.end()
+                    );
+                    return Err(OperationError::PL0002Oauth2TokenExpiryOutOfRange);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     fn modify_inner<T: Clone>(
         qs: &mut QueryServerWriteTransaction,
         cand: &mut [Entry<EntryInvalid, T>],
@@ -72,6 +104,8 @@ impl OAuth2 {
                 entry.attribute_equality(Attribute::Class, &EntryClass::OAuth2ResourceServer.into())
             })
             .try_for_each(|entry| {
+                Self::validate_token_expiry(entry)?;
+
                 // Regenerate the basic secret, if needed
                 if entry.attribute_equality(Attribute::Class, &EntryClass::OAuth2ResourceServerBasic.into()) &&
                     !entry.attribute_pres(Attribute::OAuth2RsBasicSecret) {
@@ -244,4 +278,86 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_modify_oauth2_token_expiry_range() {
+        let uuid = Uuid::new_v4();
+
+        let e: Entry<EntryInit, EntryNew> = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (
+                Attribute::Class,
+                EntryClass::OAuth2ResourceServer.to_value()
+            ),
+            (
+                Attribute::Class,
+                EntryClass::OAuth2ResourceServerBasic.to_value()
+            ),
+            (Attribute::Uuid, Value::Uuid(uuid)),
+            (Attribute::Name, Value::new_iname("test_resource_server")),
+            (
+                Attribute::DisplayName,
+                Value::new_utf8s("test_resource_server")
+            ),
+            (
+                Attribute::OAuth2RsOriginLanding,
+                Value::new_url_s("https://demo.example.com").unwrap()
+            )
+        );
+
+        let preload_valid = vec![e.clone()];
+        let preload_access = vec![e.clone()];
+        let preload_refresh = vec![e];
+
+        run_modify_test!(
+            Ok(()),
+            preload_valid,
+            filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(uuid))),
+            ModifyList::new_list(vec![
+                Modify::Present(Attribute::OAuth2AccessTokenExpiry, Value::Uint32(300)),
+                Modify::Present(
+                    Attribute::OAuth2RefreshTokenExpiry,
+                    Value::Uint32(3600 * 12)
+                ),
+            ]),
+            None,
+            |_| {},
+            |qs: &mut QueryServerWriteTransaction| {
+                let e = qs
+                    .internal_search_uuid(uuid)
+                    .expect("failed to get oauth2 config");
+                assert_eq!(
+                    e.get_ava_single_uint32(Attribute::OAuth2AccessTokenExpiry),
+                    Some(300)
+                );
+            }
+        );
+
+        run_modify_test!(
+            Err(OperationError::PL0002Oauth2TokenExpiryOutOfRange),
+            preload_access,
+            filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(uuid))),
+            ModifyList::new_list(vec![Modify::Present(
+                Attribute::OAuth2AccessTokenExpiry,
+                Value::Uint32(OAUTH2_ACCESS_TOKEN_EXPIRY_MAX + 1)
+            )]),
+            None,
+            |_| {},
+            |_| {}
+        );
+
+        run_modify_test!(
+            Err(OperationError::PL0002Oauth2TokenExpiryOutOfRange),
+            preload_refresh,
+            filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(uuid))),
+            ModifyList::new_list(vec![Modify::Present(
+                Attribute::OAuth2RefreshTokenExpiry,
+                Value::Uint32(0)
+            )]),
+            None,
+            |_| {},
+            |_| {}
+        );
+    }
 }
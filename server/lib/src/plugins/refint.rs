@@ -12,7 +12,7 @@
 use crate::event::{CreateEvent, DeleteEvent, ModifyEvent};
 use crate::plugins::Plugin;
 use crate::prelude::*;
-use crate::schema::{SchemaAttribute, SchemaTransaction};
+use crate::schema::{ReferenceDeletePolicy, SchemaAttribute, SchemaTransaction};
 use hashbrown::{HashMap, HashSet};
 use std::collections::BTreeSet;
 use std::sync::Arc;
@@ -122,6 +122,52 @@ impl ReferentialIntegrity {
         qs.internal_apply_writable(work_set)
     }
 
+    /// Refuse the delete if any live entry still references a deleted entry through an
+    /// attribute with the block delete policy. Referrers that are removed by this same
+    /// delete, such as by cascade, are already recycled and so don't block it.
+    #[instrument(level = "debug", skip_all)]
+    fn check_delete_not_blocked(
+        qs: &mut QueryServerWriteTransaction,
+        uuids: &[Uuid],
+    ) -> Result<(), OperationError> {
+        let block_types: Vec<Attribute> = qs
+            .get_schema()
+            .get_reference_types()
+            .values()
+            .filter(|a| a.on_delete == ReferenceDeletePolicy::BlockDelete)
+            .map(|a| a.name.clone())
+            .collect();
+
+        if block_types.is_empty() || uuids.is_empty() {
+            return Ok(());
+        }
+
+        let filt = filter!(f_or(
+            uuids
+                .iter()
+                .flat_map(|u| block_types
+                    .iter()
+                    .map(move |attr| f_eq(attr.clone(), PartialValue::Refer(*u))))
+                .collect(),
+        ));
+
+        let referrers = qs.internal_search(filt)?;
+
+        if referrers.is_empty() {
+            return Ok(());
+        }
+
+        let referrers: Vec<String> = referrers.iter().map(|e| e.get_display_id()).collect();
+        error!(?referrers, "delete is blocked by entries that reference it");
+
+        Err(OperationError::Plugin(PluginError::ReferentialIntegrity(
+            format!(
+                "delete is blocked by references from {}",
+                referrers.join(", ")
+            ),
+        )))
+    }
+
     #[instrument(level = "debug", skip_all)]
     fn check_refers_to_target_loop_fast(
         qs: &mut QueryServerWriteTransaction,
@@ -406,6 +452,8 @@ impl Plugin for ReferentialIntegrity {
         // Get the UUID of all entries we are deleting
         let uuids: Vec<Uuid> = cand.iter().map(|e| e.get_uuid()).collect();
 
+        Self::check_delete_not_blocked(qs, &uuids)?;
+
         Self::remove_references(qs, uuids)
     }
 
@@ -591,6 +639,7 @@ mod tests {
     use crate::credential::Credential;
     use crate::event::CreateEvent;
     use crate::prelude::*;
+    use crate::schema::ReferenceDeletePolicy;
     use crate::value::{AuthType, Oauth2Session, OauthClaimMapJoin, Session, SessionState};
     use crypto_glue::{traits::DecodePem, x509::Certificate};
     use kanidm_lib_crypto::CryptoPolicy;
@@ -1086,10 +1135,10 @@ mod tests {
     }
 
     #[test]
-    fn test_delete_remove_reference_oauth2() {
+    fn test_delete_cascade_oauth2_scope_map() {
         // Oauth2 types are also capable of uuid referencing to groups for their
-        // scope maps, so we need to check that when the group is deleted, that the
-        // scope map is also appropriately affected.
+        // scope maps. Scope maps cascade, so when the group is deleted the resource
+        // server is deleted with it.
         let ea: Entry<EntryInit, EntryNew> = entry_init!(
             (Attribute::Class, EntryClass::Object.to_value()),
             (Attribute::Class, EntryClass::Account.to_value()),
@@ -1141,10 +1190,7 @@ mod tests {
                         PartialValue::new_iname("test_resource_server")
                     )))
                     .expect("Internal search failure");
-                let ue = cands.first().expect("No entry");
-                assert!(ue
-                    .get_ava_as_oauthscopemaps(Attribute::OAuth2RsScopeMap)
-                    .is_none())
+                assert!(cands.is_empty());
             }
         );
     }
@@ -1255,6 +1301,8 @@ mod tests {
                         scope,
                         type_: AuthType::Passkey,
                         ext_metadata: Default::default(),
+                        source_ip: None,
+                        user_agent: None,
                     },
                 )
             ),
@@ -1394,15 +1442,24 @@ mod tests {
 
         assert_eq!(entry_managed_by, manages_uuid);
 
-        // It's valid to delete this, since entryManagedBy is may not must.
-        assert!(server_txn.internal_delete_uuid(manages_uuid).is_ok());
-
-        let group = server_txn
-            .internal_search_uuid(group_uuid)
-            .expect("Failed to access group");
+        assert!(server_txn.commit().is_ok());
 
-        assert!(group.get_ava_refer(Attribute::EntryManagedBy).is_none());
+        // A group can't be deleted while it manages other entries.
+        let mut server_txn = server.write(curtime).await.unwrap();
+        match server_txn.internal_delete_uuid(manages_uuid) {
+            Err(OperationError::Plugin(PluginError::ReferentialIntegrity(msg))) => {
+                assert!(msg.contains("entry_managed_by"));
+            }
+            res => panic!("unexpected delete result {res:?}"),
+        }
+        drop(server_txn);
 
+        // Once the managed entry is released, the group may be deleted.
+        let mut server_txn = server.write(curtime).await.unwrap();
+        assert!(server_txn
+            .internal_modify_uuid(group_uuid, &modlist!([m_purge(Attribute::EntryManagedBy)]))
+            .is_ok());
+        assert!(server_txn.internal_delete_uuid(manages_uuid).is_ok());
         assert!(server_txn.commit().is_ok());
     }
 
@@ -1709,6 +1766,257 @@ mod tests {
         drop(server_txn);
     }
 
+    fn test_reference_attr(attr: &Attribute, on_delete: ReferenceDeletePolicy) -> EntryInitNew {
+        entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::AttributeType.to_value()),
+            (Attribute::Uuid, Value::Uuid(Uuid::new_v4())),
+            (Attribute::AttributeName, Value::from(attr.clone())),
+            (Attribute::Description, Value::new_utf8s("test reference")),
+            (Attribute::MultiValue, Value::new_bool(true)),
+            (Attribute::Unique, Value::new_bool(false)),
+            (
+                Attribute::Syntax,
+                Value::new_syntaxs("REFERENCE_UUID").expect("syntax")
+            ),
+            (Attribute::OnDelete, Value::new_iutf8(on_delete.as_str()))
+        )
+    }
+
+    fn test_referrer(name: &str, uuid: Uuid, attr: &Attribute, target: Uuid) -> EntryInitNew {
+        entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::ExtensibleObject.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Name, Value::new_iname(name)),
+            (Attribute::Uuid, Value::Uuid(uuid)),
+            (attr.clone(), Value::Refer(target))
+        )
+    }
+
+    // Schema entries are only loaded from the database before DL15.
+    #[qs_test(domain_level=DOMAIN_LEVEL_14)]
+    async fn test_delete_policy_remove_reference(server: &QueryServer) {
+        let attr = Attribute::from("test_remove_ref");
+        let target_uuid = Uuid::new_v4();
+        let referrer_uuid = Uuid::new_v4();
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        assert!(server_txn
+            .internal_create(vec![test_reference_attr(
+                &attr,
+                ReferenceDeletePolicy::RemoveReference
+            )])
+            .is_ok());
+        assert!(server_txn.reload().is_ok());
+
+        assert!(server_txn
+            .internal_create(vec![
+                entry_init!(
+                    (Attribute::Class, EntryClass::Group.to_value()),
+                    (Attribute::Name, Value::new_iname("target")),
+                    (Attribute::Uuid, Value::Uuid(target_uuid))
+                ),
+                test_referrer("referrer", referrer_uuid, &attr, target_uuid),
+            ])
+            .is_ok());
+
+        assert!(server_txn.internal_delete_uuid(target_uuid).is_ok());
+
+        let referrer = server_txn
+            .internal_search_uuid(referrer_uuid)
+            .expect("referrer was deleted");
+        assert!(referrer.get_ava_refer(attr).is_none());
+
+        assert!(server_txn.commit().is_ok());
+    }
+
+    #[qs_test(domain_level=DOMAIN_LEVEL_14)]
+    async fn test_delete_policy_block_delete(server: &QueryServer) {
+        let attr = Attribute::from("test_block_ref");
+        let target_uuid = Uuid::new_v4();
+        let referrer_uuid = Uuid::new_v4();
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        assert!(server_txn
+            .internal_create(vec![test_reference_attr(
+                &attr,
+                ReferenceDeletePolicy::BlockDelete
+            )])
+            .is_ok());
+        assert!(server_txn.reload().is_ok());
+
+        assert!(server_txn
+            .internal_create(vec![
+                entry_init!(
+                    (Attribute::Class, EntryClass::Group.to_value()),
+                    (Attribute::Name, Value::new_iname("target")),
+                    (Attribute::Uuid, Value::Uuid(target_uuid))
+                ),
+                test_referrer("referrer", referrer_uuid, &attr, target_uuid),
+            ])
+            .is_ok());
+        assert!(server_txn.commit().is_ok());
+
+        // The referrer blocks the delete, and is named in the error.
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        match server_txn.internal_delete_uuid(target_uuid) {
+            Err(OperationError::Plugin(PluginError::ReferentialIntegrity(msg))) => {
+                assert!(msg.contains("referrer"));
+            }
+            res => panic!("unexpected delete result {res:?}"),
+        }
+        drop(server_txn);
+
+        // Deleting the referrer in the same operation is allowed.
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        assert!(server_txn
+            .internal_delete(&filter!(f_or(vec![
+                f_eq(Attribute::Uuid, PartialValue::Uuid(target_uuid)),
+                f_eq(Attribute::Uuid, PartialValue::Uuid(referrer_uuid)),
+            ])))
+            .is_ok());
+        assert!(server_txn.commit().is_ok());
+    }
+
+    #[qs_test(domain_level=DOMAIN_LEVEL_14)]
+    async fn test_delete_policy_cascade(server: &QueryServer) {
+        let attr = Attribute::from("test_cascade_ref");
+        let target_uuid = Uuid::new_v4();
+        let referrer_uuid = Uuid::new_v4();
+        let indirect_uuid = Uuid::new_v4();
+        let member_uuid = Uuid::new_v4();
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        assert!(server_txn
+            .internal_create(vec![test_reference_attr(
+                &attr,
+                ReferenceDeletePolicy::Cascade
+            )])
+            .is_ok());
+        assert!(server_txn.reload().is_ok());
+
+        assert!(server_txn
+            .internal_create(vec![
+                entry_init!(
+                    (Attribute::Class, EntryClass::Group.to_value()),
+                    (Attribute::Name, Value::new_iname("target")),
+                    (Attribute::Uuid, Value::Uuid(target_uuid))
+                ),
+                test_referrer("referrer", referrer_uuid, &attr, target_uuid),
+                test_referrer("indirect", indirect_uuid, &attr, referrer_uuid),
+                entry_init!(
+                    (Attribute::Class, EntryClass::Group.to_value()),
+                    (Attribute::Name, Value::new_iname("member_of_target")),
+                    (Attribute::Uuid, Value::Uuid(member_uuid)),
+                    (Attribute::Member, Value::Refer(target_uuid))
+                ),
+            ])
+            .is_ok());
+
+        assert!(server_txn.internal_delete_uuid(target_uuid).is_ok());
+
+        // Both the direct and the indirect referrer are deleted with the target.
+        for uuid in [target_uuid, referrer_uuid, indirect_uuid] {
+            assert!(server_txn.internal_search_uuid(uuid).is_err());
+            let entry = server_txn
+                .internal_search_all_uuid(uuid)
+                .expect("entry not recycled");
+            assert!(entry.mask_recycled_ts().is_none());
+        }
+
+        // References through other attributes are only removed.
+        let member = server_txn
+            .internal_search_uuid(member_uuid)
+            .expect("member was deleted");
+        assert!(member.get_ava_refer(Attribute::Member).is_none());
+
+        assert!(server_txn.commit().is_ok());
+    }
+
+    #[qs_test]
+    async fn test_delete_policy_cascade_access(server: &QueryServer) {
+        let group_uuid = Uuid::new_v4();
+        let rs_uuid = Uuid::new_v4();
+        let person_uuid = Uuid::new_v4();
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        assert!(server_txn
+            .internal_create(vec![
+                entry_init!(
+                    (Attribute::Class, EntryClass::Group.to_value()),
+                    (Attribute::Name, Value::new_iname("testgroup")),
+                    (Attribute::Uuid, Value::Uuid(group_uuid))
+                ),
+                entry_init!(
+                    (Attribute::Class, EntryClass::Object.to_value()),
+                    (Attribute::Class, EntryClass::Account.to_value()),
+                    (
+                        Attribute::Class,
+                        EntryClass::OAuth2ResourceServer.to_value()
+                    ),
+                    (Attribute::Uuid, Value::Uuid(rs_uuid)),
+                    (Attribute::Name, Value::new_iname("test_resource_server")),
+                    (
+                        Attribute::DisplayName,
+                        Value::new_utf8s("test_resource_server")
+                    ),
+                    (
+                        Attribute::OAuth2RsOriginLanding,
+                        Value::new_url_s("https://demo.example.com").unwrap()
+                    ),
+                    (
+                        Attribute::OAuth2RsScopeMap,
+                        Value::new_oauthscopemap(
+                            group_uuid,
+                            btreeset![OAUTH2_SCOPE_READ.to_string()]
+                        )
+                        .expect("Invalid scope")
+                    )
+                ),
+            ])
+            .is_ok());
+        assert!(server_txn.commit().is_ok());
+
+        // A group admin may delete the group, but not the resource server that it cascades to.
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        assert!(server_txn
+            .internal_create(vec![entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Person.to_value()),
+                (Attribute::Class, EntryClass::Account.to_value()),
+                (Attribute::Name, Value::new_iname("testperson1")),
+                (Attribute::Uuid, Value::Uuid(person_uuid)),
+                (Attribute::DisplayName, Value::new_utf8s("testperson1"))
+            )])
+            .is_ok());
+        assert!(server_txn
+            .internal_modify_uuid(
+                UUID_IDM_GROUP_ADMINS,
+                &modlist!([m_pres(Attribute::Member, &Value::Refer(person_uuid))])
+            )
+            .is_ok());
+        let person = server_txn.internal_search_uuid(person_uuid).unwrap();
+        let de = DeleteEvent::new_impersonate_entry(
+            person,
+            filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(group_uuid))),
+        );
+        assert_eq!(server_txn.delete(&de), Err(OperationError::AccessDenied));
+        drop(server_txn);
+
+        // idm_admin may also manage oauth2 resource servers, so may delete both.
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        let idm_admin = server_txn.internal_search_uuid(UUID_IDM_ADMIN).unwrap();
+        let de = DeleteEvent::new_impersonate_entry(
+            idm_admin,
+            filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(group_uuid))),
+        );
+        assert!(server_txn.delete(&de).is_ok());
+        assert!(server_txn.internal_search_uuid(group_uuid).is_err());
+        assert!(server_txn.internal_search_uuid(rs_uuid).is_err());
+        assert!(server_txn.commit().is_ok());
+    }
+
     // Test with replication that on a conflict that the refers is deleted too?
 
     // Ensure that the refers are all removed when conflict occurs.
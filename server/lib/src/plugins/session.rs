@@ -139,6 +139,17 @@ impl SessionConsistency {
                             trace!("Skip already revoked session");
                             None
                         }
+                        // The grace window allows for a parent that has not been replicated
+                        // yet. A parent that is present and revoked has ended, and so have
+                        // the sessions derived from it.
+                        _ if session
+                            .parent
+                            .as_ref()
+                            .and_then(|parent_session_id| sessions.and_then(|session_map| session_map.get(parent_session_id)))
+                            .is_some_and(|parent_session| matches!(parent_session.state, SessionState::RevokedAt(_))) => {
+                            info!(%o2_session_id, parent_id = ?session.parent, "Removing oauth2 session of revoked parent");
+                            Some(PartialValue::Refer(*o2_session_id))
+                        }
                         _ => {
                             // Okay, now check the issued / grace time for parent enforcement.
                                 if sessions.map(|session_map| {
@@ -262,6 +273,8 @@ mod tests {
                 scope,
                 type_: AuthType::Passkey,
                 ext_metadata: Default::default(),
+                source_ip: None,
+                user_agent: None,
             },
         );
 
@@ -427,6 +440,8 @@ mod tests {
                         scope,
                         type_: AuthType::Passkey,
                         ext_metadata: Default::default(),
+                        source_ip: None,
+                        user_agent: None,
                     },
                 )
             ),
@@ -602,6 +617,8 @@ mod tests {
                         scope,
                         type_: AuthType::Passkey,
                         ext_metadata: Default::default(),
+                        source_ip: None,
+                        user_agent: None,
                     },
                 )
             ),
@@ -852,6 +869,8 @@ mod tests {
                 scope,
                 type_: AuthType::Passkey,
                 ext_metadata: Default::default(),
+                source_ip: None,
+                user_agent: None,
             },
         );
 
@@ -88,6 +88,14 @@ impl Plugin for Spn {
 
         let domain_name = qs.get_domain_name().to_string();
 
+        // While an online rename is migrating, spns may still carry the previous name.
+        let rename_from = match qs.get_db_domain() {
+            Ok(domain_entry) => domain_entry
+                .get_ava_single_iname(Attribute::DomainRenameFrom)
+                .map(str::to_string),
+            Err(_) => return vec![Err(ConsistencyError::QueryServerSearchFailure)],
+        };
+
         let filt_in = filter!(f_or!([
             f_eq(Attribute::Class, EntryClass::Group.into()),
             f_eq(Attribute::Class, EntryClass::Account.into()),
@@ -116,7 +124,11 @@ impl Plugin for Spn {
             match e.get_ava_set(Attribute::Spn) {
                 Some(r_spn) => {
                     trace!("verify spn: s {:?} == ex {:?} ?", r_spn, g_spn);
-                    if r_spn != &g_spn {
+                    let migrating = rename_from
+                        .as_deref()
+                        .and_then(|rename_from| e.generate_spn(rename_from))
+                        .is_some_and(|o_spn| r_spn == &o_spn);
+                    if r_spn != &g_spn && !migrating {
                         admin_error!(
                             uuid = ?e.get_uuid(),
                             "Entry SPN does not match expected s {:?} != ex {:?}",
@@ -177,13 +189,13 @@ impl Spn {
             if post.attribute_equality(Attribute::Uuid, &PVUUID_DOMAIN_INFO)
                 && domain_name != pre.get_ava_single(Attribute::DomainName)
             {
-                domain_name
+                domain_name.map(|domain_name| (post, domain_name))
             } else {
                 None
             }
         });
 
-        let Some(domain_name) = domain_name_changed else {
+        let Some((domain_entry, domain_name)) = domain_name_changed else {
             return Ok(());
         };
 
@@ -193,6 +205,18 @@ impl Spn {
         // spn rename fails as we recurse and just populate the old name.
         qs.reload_domain_info()?;
 
+        // An online rename regenerates spns in chunks, so leave them for the migration.
+        if domain_entry
+            .get_ava_single_iname(Attribute::DomainRenameFrom)
+            .is_some()
+        {
+            admin_info!(
+                "Changing domain name to \"{:?}\", spns will be migrated in the background",
+                domain_name
+            );
+            return Ok(());
+        }
+
         admin_info!(
             "IMPORTANT!!! Changing domain name to \"{:?}\". THIS MAY TAKE A LONG TIME ...",
             domain_name
@@ -392,4 +416,88 @@ mod tests {
 
         assert!(server_txn.commit().is_ok());
     }
+
+    #[qs_test]
+    async fn test_spn_regen_domain_rename_online(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+
+        let ex1 = Value::new_spn_str("a_testperson1", "example.com");
+        let ex2 = Value::new_spn_str("a_testperson1", "new.example.com");
+
+        let t_uuid = Uuid::new_v4();
+
+        assert!(server_txn
+            .internal_create(vec![entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Account.to_value()),
+                (Attribute::Class, EntryClass::Person.to_value()),
+                (Attribute::Name, Value::new_iname("a_testperson1")),
+                (Attribute::Uuid, Value::Uuid(t_uuid)),
+                (Attribute::Description, Value::new_utf8s("testperson1")),
+                (Attribute::DisplayName, Value::new_utf8s("testperson1"))
+            ),])
+            .is_ok());
+
+        server_txn
+            .domain_rename("new.example.com")
+            .expect("should not fail!");
+        assert!(server_txn.commit().is_ok());
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        assert_eq!(server_txn.get_domain_name(), "new.example.com");
+
+        // A second rename is refused while the first is migrating.
+        assert_eq!(
+            server_txn.domain_rename("other.example.com"),
+            Err(OperationError::MG0011DomainRenameInProgress)
+        );
+
+        // Migrate a single page, then commit to show progress survives the txn.
+        let cursor = server_txn
+            .domain_rename_migrate(None, 1)
+            .expect("must not fail")
+            .expect("more pages must remain");
+        assert!(server_txn.commit().is_ok());
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        let mut cursor = server_txn
+            .domain_rename_migrate(Some(&cursor), 1)
+            .expect("must not fail");
+        assert!(cursor.is_some());
+
+        while let Some(next) = server_txn
+            .domain_rename_migrate(cursor.as_deref(), 8)
+            .expect("must not fail")
+        {
+            cursor = Some(next);
+        }
+        assert!(server_txn.commit().is_ok());
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+
+        let e_post = server_txn
+            .internal_search_uuid(t_uuid)
+            .expect("must not fail");
+        let e_post_spn = e_post
+            .get_ava_single(Attribute::Spn)
+            .expect("must not fail");
+        assert_ne!(e_post_spn, ex1);
+        assert_eq!(e_post_spn, ex2);
+
+        let testuser_spn = server_txn
+            .uuid_to_spn(t_uuid)
+            .expect("Must be able to retrieve the spn")
+            .expect("Value must not be none");
+        assert_eq!(testuser_spn, ex2);
+
+        // The marker is removed, so a further rename may now proceed.
+        let domain_entry = server_txn.get_db_domain().expect("must not fail");
+        assert!(!domain_entry.attribute_pres(Attribute::DomainRenameFrom));
+
+        server_txn
+            .domain_rename("other.example.com")
+            .expect("should not fail!");
+
+        assert!(server_txn.commit().is_ok());
+    }
 }
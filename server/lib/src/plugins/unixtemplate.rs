@@ -0,0 +1,100 @@
+// Validate the unix home directory templates that are rendered into unix user tokens.
+// Templates may be set on the domain or on posix groups, and must be rejected on write
+// so that an invalid template is never presented to clients.
+
+use std::sync::Arc;
+
+use crate::event::{CreateEvent, ModifyEvent};
+use crate::idm::unix::UnixHomeDirectoryTemplate;
+use crate::plugins::Plugin;
+use crate::prelude::*;
+
+pub struct UnixTemplate {}
+
+impl Plugin for UnixTemplate {
+    fn id() -> &'static str {
+        "plugin_unix_template"
+    }
+
+    #[instrument(level = "debug", name = "unix_template_pre_create_transform", skip_all)]
+    fn pre_create_transform(
+        _qs: &mut QueryServerWriteTransaction,
+        cand: &mut Vec<Entry<EntryInvalid, EntryNew>>,
+        _ce: &CreateEvent,
+    ) -> Result<(), OperationError> {
+        Self::validate(cand)
+    }
+
+    #[instrument(level = "debug", name = "unix_template_pre_modify", skip_all)]
+    fn pre_modify(
+        _qs: &mut QueryServerWriteTransaction,
+        _pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut Vec<Entry<EntryInvalid, EntryCommitted>>,
+        _me: &ModifyEvent,
+    ) -> Result<(), OperationError> {
+        Self::validate(cand)
+    }
+
+    #[instrument(level = "debug", name = "unix_template_pre_batch_modify", skip_all)]
+    fn pre_batch_modify(
+        _qs: &mut QueryServerWriteTransaction,
+        _pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut Vec<Entry<EntryInvalid, EntryCommitted>>,
+        _me: &BatchModifyEvent,
+    ) -> Result<(), OperationError> {
+        Self::validate(cand)
+    }
+}
+
+impl UnixTemplate {
+    fn validate<T>(cand: &[Entry<EntryInvalid, T>]) -> Result<(), OperationError> {
+        cand.iter()
+            .filter_map(|e| e.get_ava_single_utf8(Attribute::UnixHomeDirectoryTemplate))
+            .try_for_each(|template| template.parse::<UnixHomeDirectoryTemplate>().map(|_| ()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[qs_test]
+    async fn test_unix_template_rejects_invalid(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await.expect("txn");
+
+        for template in ["home/{name}", "/home/{displayname}", "/home/{name"] {
+            let modlist = modlist!([m_pres(
+                Attribute::UnixHomeDirectoryTemplate,
+                &Value::new_utf8s(template)
+            )]);
+            assert_eq!(
+                server_txn.internal_modify_uuid(UUID_DOMAIN_INFO, &modlist),
+                Err(OperationError::PL0006UnixHomeDirectoryTemplateInvalid)
+            );
+        }
+
+        let op_result = server_txn.internal_create(vec![entry_init!(
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Class, EntryClass::PosixGroup.to_value()),
+            (Attribute::Name, Value::new_iname("testgroup")),
+            (
+                Attribute::UnixHomeDirectoryTemplate,
+                Value::new_utf8s("/home/{gecos}")
+            )
+        )]);
+        assert_eq!(
+            op_result,
+            Err(OperationError::PL0006UnixHomeDirectoryTemplateInvalid)
+        );
+
+        let modlist = modlist!([m_pres(
+            Attribute::UnixHomeDirectoryTemplate,
+            &Value::new_utf8s("/home/{spn}")
+        )]);
+        assert!(server_txn
+            .internal_modify_uuid(UUID_DOMAIN_INFO, &modlist)
+            .is_ok());
+
+        assert!(server_txn.commit().is_ok());
+    }
+}
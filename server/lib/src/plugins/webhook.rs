@@ -0,0 +1,141 @@
+// Generate the signing secret of webhook subscriptions, and match the entries changed by
+// each write against the subscriptions. Changes that other plugins make through internal
+// operations, such as memberof, are matched as those operations complete.
+//
+// Each subscription records its author, the identity that created or last changed it. Changes
+// are delivered with the access of the author, so a subscription only reveals entries and
+// attributes that its author can read.
+use crate::entry::{EntryInvalidCommitted, EntrySealedCommitted};
+use crate::event::{CreateEvent, DeleteEvent, ModifyEvent};
+use crate::plugins::Plugin;
+use crate::prelude::*;
+use crate::server::webhook::WebhookEventKind;
+use crate::utils::password_from_random;
+use std::sync::Arc;
+
+pub struct Webhook {}
+
+impl Plugin for Webhook {
+    fn id() -> &'static str {
+        "plugin_webhook"
+    }
+
+    #[instrument(level = "debug", name = "webhook_pre_create_transform", skip_all)]
+    fn pre_create_transform(
+        _qs: &mut QueryServerWriteTransaction,
+        cand: &mut Vec<EntryInvalidNew>,
+        ce: &CreateEvent,
+    ) -> Result<(), OperationError> {
+        cand.iter_mut().for_each(|entry| {
+            Self::set_author(&ce.ident, entry);
+            Self::generate_secret(entry);
+        });
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "webhook_pre_modify", skip_all)]
+    fn pre_modify(
+        _qs: &mut QueryServerWriteTransaction,
+        _pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut Vec<EntryInvalidCommitted>,
+        me: &ModifyEvent,
+    ) -> Result<(), OperationError> {
+        Self::modify_subscriptions(&me.ident, cand);
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "webhook_pre_batch_modify", skip_all)]
+    fn pre_batch_modify(
+        _qs: &mut QueryServerWriteTransaction,
+        _pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &mut Vec<EntryInvalidCommitted>,
+        me: &BatchModifyEvent,
+    ) -> Result<(), OperationError> {
+        Self::modify_subscriptions(&me.ident, cand);
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "webhook_post_create", skip_all)]
+    fn post_create(
+        qs: &mut QueryServerWriteTransaction,
+        cand: &[EntrySealedCommitted],
+        _ce: &CreateEvent,
+    ) -> Result<(), OperationError> {
+        qs.queue_webhooks(WebhookEventKind::Create, cand)
+    }
+
+    #[instrument(level = "debug", name = "webhook_post_modify", skip_all)]
+    fn post_modify(
+        qs: &mut QueryServerWriteTransaction,
+        _pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &[EntrySealedCommitted],
+        _me: &ModifyEvent,
+    ) -> Result<(), OperationError> {
+        qs.queue_webhooks(WebhookEventKind::Modify, cand)
+    }
+
+    #[instrument(level = "debug", name = "webhook_post_batch_modify", skip_all)]
+    fn post_batch_modify(
+        qs: &mut QueryServerWriteTransaction,
+        _pre_cand: &[Arc<EntrySealedCommitted>],
+        cand: &[EntrySealedCommitted],
+        _me: &BatchModifyEvent,
+    ) -> Result<(), OperationError> {
+        qs.queue_webhooks(WebhookEventKind::Modify, cand)
+    }
+
+    #[instrument(level = "debug", name = "webhook_post_delete", skip_all)]
+    fn post_delete(
+        qs: &mut QueryServerWriteTransaction,
+        cand: &[EntrySealedCommitted],
+        _de: &DeleteEvent,
+    ) -> Result<(), OperationError> {
+        qs.queue_webhooks(WebhookEventKind::Delete, cand)
+    }
+}
+
+impl Webhook {
+    /// The author can't be supplied by clients, it is always the identity making the change.
+    /// Changes made by the server itself are delivered without access controls.
+    fn set_author<STATE: Clone>(ident: &Identity, entry: &mut Entry<EntryInvalid, STATE>) {
+        if !entry.attribute_equality(Attribute::Class, &EntryClass::WebhookSubscription.into()) {
+            return;
+        }
+
+        let author = if ident.is_internal() {
+            UUID_SYSTEM
+        } else {
+            ident.get_uuid()
+        };
+        entry.set_ava(
+            &Attribute::WebhookAuthor,
+            std::iter::once(Value::Uuid(author)),
+        );
+    }
+
+    /// Any change to a subscription by a client, such as to its url or filter, makes that
+    /// client the author. Internal changes, such as those from other plugins, don't alter
+    /// the author of the subscription.
+    fn modify_subscriptions(ident: &Identity, cand: &mut [EntryInvalidCommitted]) {
+        cand.iter_mut().for_each(|entry| {
+            if !ident.is_internal() || !entry.attribute_pres(Attribute::WebhookAuthor) {
+                Self::set_author(ident, entry);
+            }
+            Self::generate_secret(entry);
+        });
+    }
+
+    /// Secrets can't be supplied by clients, so a subscription is given one when it is
+    /// created. Purging the secret rotates it.
+    fn generate_secret<STATE: Clone>(entry: &mut Entry<EntryInvalid, STATE>) {
+        if entry.attribute_equality(Attribute::Class, &EntryClass::WebhookSubscription.into())
+            && !entry.attribute_pres(Attribute::WebhookSecret)
+        {
+            security_info!("regenerating webhook secret");
+            entry.add_ava(
+                Attribute::WebhookSecret,
+                Value::SecretValue(password_from_random()),
+            );
+        }
+    }
+}
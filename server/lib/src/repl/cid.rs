@@ -1,4 +1,5 @@
 use std::fmt;
+use std::str::FromStr;
 use std::time::Duration;
 use time::OffsetDateTime;
 
@@ -39,6 +40,25 @@ impl fmt::Display for Cid {
     }
 }
 
+impl FromStr for Cid {
+    type Err = OperationError;
+
+    /// Parse a cid from the form that it is displayed in.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (ts, s_uuid) = s
+            .split_once('-')
+            .ok_or(OperationError::InvalidReplChangeId)?;
+        let ts = ts
+            .parse::<u128>()
+            .ok()
+            .and_then(|nanos| u64::try_from(nanos).ok())
+            .map(Duration::from_nanos)
+            .ok_or(OperationError::InvalidReplChangeId)?;
+        let s_uuid = Uuid::parse_str(s_uuid).map_err(|_| OperationError::InvalidReplChangeId)?;
+        Ok(Cid { ts, s_uuid })
+    }
+}
+
 impl From<&Cid> for OffsetDateTime {
     fn from(cid: &Cid) -> Self {
         OffsetDateTime::UNIX_EPOCH + cid.ts
@@ -144,4 +164,20 @@ mod tests {
         let cid_c = Cid::new_lamport(s_uuid, ts10, &ts15);
         assert_eq!(cid_c.cmp(&cid_b), Ordering::Greater);
     }
+
+    #[test]
+    fn test_cid_from_str() {
+        let cid = Cid::new(
+            uuid!("00000000-0000-0000-0000-000000000001"),
+            Duration::new(5, 12),
+        );
+        assert_eq!(cid.to_string().parse::<Cid>(), Ok(cid));
+
+        assert!("".parse::<Cid>().is_err());
+        assert!("5".parse::<Cid>().is_err());
+        assert!("abc-00000000-0000-0000-0000-000000000001"
+            .parse::<Cid>()
+            .is_err());
+        assert!("5-not-a-uuid".parse::<Cid>().is_err());
+    }
 }
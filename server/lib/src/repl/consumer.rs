@@ -1,6 +1,8 @@
 use super::proto::*;
+use crate::metrics::METRICS;
 use crate::plugins::Plugins;
 use crate::prelude::*;
+use crate::server::security_event::{SecurityEvent, SecurityEventKind, SecurityEventOutcome};
 use crate::server::{ChangeFlag, ServerPhase};
 use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
@@ -116,6 +118,17 @@ impl QueryServerWriteTransaction<'_> {
             })
             .collect();
 
+        if !conflict_uuids.is_empty() {
+            self.submit_security_event(
+                SecurityEvent::new(
+                    SecurityEventKind::ReplicationConflict,
+                    SecurityEventOutcome::Failure,
+                    self.get_curtime(),
+                )
+                .with_targets(conflict_uuids.iter().copied()),
+            );
+        }
+
         // Filter out None from conflict_create
         let conflict_create: Vec<EntrySealedNew> = conflict_create.into_iter().flatten().collect();
 
@@ -280,6 +293,17 @@ impl QueryServerWriteTransaction<'_> {
             self.changed_flags.insert(ChangeFlag::FEATURE)
         }
 
+        if !self.changed_flags.contains(ChangeFlag::WEBHOOK)
+            && cand
+                .iter()
+                .chain(pre_cand.iter().map(|e| e.as_ref()))
+                .any(|e| {
+                    e.attribute_equality(Attribute::Class, &EntryClass::WebhookSubscription.into())
+                })
+        {
+            self.changed_flags.insert(ChangeFlag::WEBHOOK)
+        }
+
         if !self.changed_flags.contains(ChangeFlag::APPLICATION)
             && cand
                 .iter()
@@ -329,6 +353,8 @@ impl QueryServerWriteTransaction<'_> {
             }
             ReplIncrementalContext::NoChangesAvailable => {
                 debug!("no changes are available");
+                // We hold everything the supplier has, so we are not lagging behind it.
+                METRICS.replication_lag_seconds.set(0);
                 Ok(ConsumerState::Ok)
             }
             ReplIncrementalContext::RefreshRequired => {
@@ -420,6 +446,11 @@ impl QueryServerWriteTransaction<'_> {
 
         debug!(?ctx_ranges);
 
+        if let Some(newest_change) = ctx_ranges.values().map(|range| range.ts_max).max() {
+            let lag = self.get_curtime().saturating_sub(newest_change);
+            METRICS.replication_lag_seconds.set(lag.as_secs());
+        }
+
         if ctx_domain_version < DOMAIN_LEVEL_1_11 {
             // Schema is an in memory property attached to domain level from 1_11, and so the meta entries
             // will trigger the schema to reload if required.
@@ -688,6 +719,7 @@ impl QueryServerWriteTransaction<'_> {
                 | ChangeFlag::OAUTH2
                 | ChangeFlag::OAUTH2_CLIENT
                 | ChangeFlag::FEATURE
+                | ChangeFlag::WEBHOOK
                 | ChangeFlag::DOMAIN
                 | ChangeFlag::APPLICATION
                 | ChangeFlag::SYSTEM_CONFIG
@@ -216,7 +216,6 @@ impl EntryChangeState {
         }
     }
 
-    #[cfg(test)]
     pub(crate) fn get_attr_cid(&self, attr: &Attribute) -> Option<&Cid> {
         match &self.st {
             State::Live { at: _, changes } => changes.get(attr),
@@ -224,6 +223,16 @@ impl EntryChangeState {
         }
     }
 
+    /// The cid of the last change to each attribute. Attributes that were removed from
+    /// the entry are included, as their removal is a change.
+    pub(crate) fn attr_cid_iter(&self) -> impl Iterator<Item = (&Attribute, &Cid)> {
+        let changes = match &self.st {
+            State::Live { at: _, changes } => Some(changes),
+            State::Tombstone { at: _ } => None,
+        };
+        changes.into_iter().flatten()
+    }
+
     pub(crate) fn cid_iter(&self) -> Vec<&Cid> {
         match &self.st {
             State::Live { at: _, changes } => {
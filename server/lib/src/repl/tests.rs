@@ -712,6 +712,74 @@ async fn test_repl_increment_basic_deleted_attr(server_a: &QueryServer, server_b
     drop(server_a_txn);
 }
 
+// Modify a single attribute on A, and check that the last modified cids of all
+// attributes are the same on B after replication.
+#[qs_pair_test]
+async fn test_repl_increment_attr_last_modified(server_a: &QueryServer, server_b: &QueryServer) {
+    let mut server_a_txn = server_a.write(duration_from_epoch_now()).await.unwrap();
+    let mut server_b_txn = server_b.read().await.unwrap();
+
+    assert!(repl_initialise(&mut server_b_txn, &mut server_a_txn)
+        .and_then(|_| server_a_txn.commit())
+        .is_ok());
+    drop(server_b_txn);
+
+    let mut server_a_txn = server_a.write(duration_from_epoch_now()).await.unwrap();
+    let t_uuid = Uuid::new_v4();
+    assert!(server_a_txn
+        .internal_create(vec![entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::Person.to_value()),
+            (Attribute::Name, Value::new_iname("testperson1")),
+            (Attribute::Uuid, Value::Uuid(t_uuid)),
+            (Attribute::Description, Value::new_utf8s("testperson1")),
+            (Attribute::DisplayName, Value::new_utf8s("testperson1"))
+        ),])
+        .is_ok());
+    server_a_txn.commit().expect("Failed to commit");
+
+    let mut server_a_txn = server_a.write(duration_from_epoch_now()).await.unwrap();
+    assert!(server_a_txn
+        .internal_modify_uuid(
+            t_uuid,
+            &ModifyList::new_purge_and_set(Attribute::DisplayName, Value::new_utf8s("changed"))
+        )
+        .is_ok());
+    server_a_txn.commit().expect("Failed to commit");
+
+    let mut server_a_txn = server_a.read().await.unwrap();
+    let mut server_b_txn = server_b.write(duration_from_epoch_now()).await.unwrap();
+
+    //               from               to
+    repl_incremental(&mut server_a_txn, &mut server_b_txn);
+
+    let e1 = server_a_txn
+        .internal_search_all_uuid(t_uuid)
+        .expect("Unable to access new entry.");
+    let e2 = server_b_txn
+        .internal_search_all_uuid(t_uuid)
+        .expect("Unable to access entry.");
+
+    assert!(
+        e1.get_ava_last_modified(Attribute::DisplayName)
+            > e1.get_ava_last_modified(Attribute::Description)
+    );
+    for attr in [
+        Attribute::Name,
+        Attribute::Description,
+        Attribute::DisplayName,
+    ] {
+        assert_eq!(
+            e1.get_ava_last_modified(&attr),
+            e2.get_ava_last_modified(&attr)
+        );
+    }
+
+    server_b_txn.commit().expect("Failed to commit");
+    drop(server_a_txn);
+}
+
 // Create Entry on A -> B
 // Write to both
 // B -> A and A -> B become consistent.
@@ -3303,6 +3371,8 @@ async fn test_repl_increment_session_new(server_a: &QueryServer, server_b: &Quer
             scope,
             type_,
             ext_metadata: Default::default(),
+            source_ip: None,
+            user_agent: None,
         },
     );
 
@@ -3345,6 +3415,8 @@ async fn test_repl_increment_session_new(server_a: &QueryServer, server_b: &Quer
             scope,
             type_,
             ext_metadata: Default::default(),
+            source_ip: None,
+            user_agent: None,
         },
     );
 
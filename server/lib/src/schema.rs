@@ -12,18 +12,175 @@
 //!
 
 use crate::be::IdxKey;
+use crate::filter::FilterOpKind;
 use crate::migration_data;
 use crate::prelude::*;
+use crate::value::OAUTH_CLAIMNAME_RE;
 use crate::valueset::ValueSet;
 use concread::cowcell::*;
 use hashbrown::{HashMap, HashSet};
+use kanidm_proto::internal::SyntaxInfo;
+use std::cmp::Ordering;
 use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex, OnceLock};
 use tracing::trace;
 use uuid::Uuid;
 
 // representations of schema that confines object types, classes
 // and attributes. This ties in deeply with "Entry".
 //
+
+/// The names of the attributes bootstrapped by [`SchemaWriteTransaction::generate_in_memory`].
+/// These may only be (re)defined by a system provided definition.
+static RESERVED_ATTRIBUTE_NAMES: LazyLock<HashSet<Attribute>> = LazyLock::new(|| {
+    migration_data::system::attributes()
+        .into_iter()
+        .map(|a| a.name)
+        .collect()
+});
+
+/// The names of the classes bootstrapped by [`SchemaWriteTransaction::generate_in_memory`].
+/// These may only be (re)defined by a system provided definition.
+static RESERVED_CLASS_NAMES: LazyLock<HashSet<AttrString>> = LazyLock::new(|| {
+    migration_data::system::classes()
+        .into_iter()
+        .map(|c| c.name)
+        .collect()
+});
+
+/// A sink that is informed of the outcome of each attribute validation, allowing the
+/// attributes that are validated most often, or that fail most often, to be found.
+pub trait SchemaValidationMetrics: Send + Sync {
+    fn validation_succeeded(&self, attr: &Attribute);
+
+    fn validation_failed(&self, attr: &Attribute);
+}
+
+/// The installed metrics sink. When unset, validation only pays for checking this.
+static SCHEMA_VALIDATION_METRICS: OnceLock<Arc<dyn SchemaValidationMetrics>> = OnceLock::new();
+
+/// Install the metrics sink for schema validation for the lifetime of the process. Only
+/// the first sink installed is used - if one is already present, the given sink is
+/// returned as the error.
+pub fn set_schema_validation_metrics(
+    sink: Arc<dyn SchemaValidationMetrics>,
+) -> Result<(), Arc<dyn SchemaValidationMetrics>> {
+    SCHEMA_VALIDATION_METRICS.set(sink)
+}
+
+fn record_validation(attr: &Attribute, result: &Result<(), SchemaError>) {
+    if let Some(sink) = SCHEMA_VALIDATION_METRICS.get() {
+        if result.is_ok() {
+            sink.validation_succeeded(attr);
+        } else {
+            sink.validation_failed(attr);
+        }
+    }
+}
+
+/// The number of successful and failed validations of an attribute.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SchemaValidationCount {
+    pub succeeded: u64,
+    pub failed: u64,
+}
+
+/// A validator of the values of an attribute with the [`SyntaxType::Custom`] syntax. Values
+/// of a custom syntax are stored as strings, and are only accepted if the validator that is
+/// registered for their attribute returns true.
+///
+/// Validators are called concurrently by every thread that validates entries, including
+/// while the write transaction is held. They must be cheap, must not block, and must not
+/// call back into the server.
+pub type CustomSyntaxValidator = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// The validators of custom syntaxes, keyed by the attribute that they validate. These are
+/// given to the schema with [`Schema::with_custom_syntax_validators`].
+#[derive(Default, Clone)]
+pub struct CustomSyntaxRegistry {
+    validators: HashMap<Attribute, CustomSyntaxValidator>,
+}
+
+impl fmt::Debug for CustomSyntaxRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.validators.keys()).finish()
+    }
+}
+
+impl CustomSyntaxRegistry {
+    /// Register the validator of an attribute, replacing any that was already registered.
+    pub fn register(
+        &mut self,
+        attr: Attribute,
+        validator: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.validators.insert(attr, Arc::new(validator));
+        self
+    }
+
+    fn attach(&self, a: &mut SchemaAttribute) {
+        a.custom_validator = if a.syntax == SyntaxType::Custom {
+            self.validators.get(&a.name).cloned().map(AttachedValidator)
+        } else {
+            None
+        };
+    }
+}
+
+/// The validator of a custom syntax attribute, attached to its definition as it is inserted
+/// into the schema.
+#[derive(Clone)]
+pub(crate) struct AttachedValidator(CustomSyntaxValidator);
+
+impl fmt::Debug for AttachedValidator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CustomSyntaxValidator")
+    }
+}
+
+/// A [`SchemaValidationMetrics`] sink that counts validations per attribute.
+#[derive(Debug, Default)]
+pub struct SchemaValidationCounter {
+    counts: Mutex<HashMap<Attribute, SchemaValidationCount>>,
+}
+
+impl SchemaValidationCounter {
+    /// A copy of the counts recorded so far.
+    pub fn snapshot(&self) -> HashMap<Attribute, SchemaValidationCount> {
+        self.counts
+            .lock()
+            .map(|counts| counts.clone())
+            .unwrap_or_default()
+    }
+
+    fn update(&self, attr: &Attribute, f: impl FnOnce(&mut SchemaValidationCount)) {
+        // A poisoned lock only means another thread panicked while counting, so keep going.
+        let mut counts = self
+            .counts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(count) = counts.get_mut(attr) {
+            f(count)
+        } else {
+            let mut count = SchemaValidationCount::default();
+            f(&mut count);
+            counts.insert(attr.clone(), count);
+        }
+    }
+}
+
+impl SchemaValidationMetrics for SchemaValidationCounter {
+    fn validation_succeeded(&self, attr: &Attribute) {
+        self.update(attr, |count| count.succeeded += 1)
+    }
+
+    fn validation_failed(&self, attr: &Attribute) {
+        self.update(attr, |count| count.failed += 1)
+    }
+}
+
 // In the future this will parse/read it's schema from the db
 // but we have to bootstrap with some core types.
 
@@ -40,8 +197,25 @@ use uuid::Uuid;
 pub struct Schema {
     classes: CowCell<HashMap<AttrString, SchemaClass>>,
     attributes: CowCell<HashMap<Attribute, SchemaAttribute>>,
-    unique_cache: CowCell<Vec<Attribute>>,
+    unique_cache: CowCell<HashMap<Attribute, UniqueScope>>,
     ref_cache: CowCell<HashMap<Attribute, SchemaAttribute>>,
+    exclusion_cache: CowCell<HashMap<AttrString, Vec<Attribute>>>,
+    /// Incremented each time a write transaction is committed.
+    generation: AtomicU64,
+    reload_mode: SchemaReloadMode,
+    custom_syntax: Arc<CustomSyntaxRegistry>,
+}
+
+/// How the schema reacts to a malformed definition when it is reloaded from the database.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaReloadMode {
+    /// Any malformed definition aborts the reload. This is the default, since a partial
+    /// schema may reject entries that were previously valid.
+    #[default]
+    Strict,
+    /// Malformed attribute definitions are skipped and reported, so that an administrator
+    /// can recover a mostly working schema and then correct the bad definition.
+    Lenient,
 }
 
 /// A writable transaction of the working schema set. You should not change this directly,
@@ -51,17 +225,25 @@ pub struct SchemaWriteTransaction<'a> {
     classes: CowCellWriteTxn<'a, HashMap<AttrString, SchemaClass>>,
     attributes: CowCellWriteTxn<'a, HashMap<Attribute, SchemaAttribute>>,
 
-    unique_cache: CowCellWriteTxn<'a, Vec<Attribute>>,
+    unique_cache: CowCellWriteTxn<'a, HashMap<Attribute, UniqueScope>>,
     ref_cache: CowCellWriteTxn<'a, HashMap<Attribute, SchemaAttribute>>,
+    exclusion_cache: CowCellWriteTxn<'a, HashMap<AttrString, Vec<Attribute>>>,
+    generation: &'a AtomicU64,
+    reload_mode: SchemaReloadMode,
+    custom_syntax: Arc<CustomSyntaxRegistry>,
 }
 
-/// A readonly transaction of the working schema set.
+/// A readonly transaction of the working schema set. Cloning this is cheap, and the
+/// clone refers to the same snapshot, allowing it to be moved into other tasks.
+#[derive(Clone)]
 pub struct SchemaReadTransaction {
     classes: CowCellReadTxn<HashMap<AttrString, SchemaClass>>,
     attributes: CowCellReadTxn<HashMap<Attribute, SchemaAttribute>>,
 
-    unique_cache: CowCellReadTxn<Vec<Attribute>>,
+    unique_cache: CowCellReadTxn<HashMap<Attribute, UniqueScope>>,
     ref_cache: CowCellReadTxn<HashMap<Attribute, SchemaAttribute>>,
+    exclusion_cache: CowCellReadTxn<HashMap<AttrString, Vec<Attribute>>>,
+    generation: u64,
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -89,6 +271,64 @@ impl From<bool> for Replicated {
     }
 }
 
+/// The entries amongst which the values of a unique attribute must be unique.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum UniqueScope {
+    /// Values must be unique amongst all entries.
+    #[default]
+    All,
+    /// Values must be unique amongst entries of any of these classes. Entries of other
+    /// classes may share a value, both with each other and with the entries in scope.
+    Classes(BTreeSet<AttrString>),
+    /// Values must be unique amongst entries provided by the same sync agreement. Entries
+    /// that are not synchronised must be unique amongst each other.
+    PerSyncParent,
+}
+
+impl UniqueScope {
+    /// The reserved value of `unique_scope` that selects [`UniqueScope::PerSyncParent`].
+    pub const PER_SYNC_PARENT: &'static str = "per_sync_parent";
+}
+
+/// What happens to the entries that hold a reference when the entry it refers to is deleted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReferenceDeletePolicy {
+    /// The reference is removed from the referring entry.
+    #[default]
+    RemoveReference,
+    /// The delete is refused while any live entry holds the reference.
+    BlockDelete,
+    /// The referring entry is deleted as well.
+    Cascade,
+}
+
+impl ReferenceDeletePolicy {
+    pub const ALL: [ReferenceDeletePolicy; 3] = [
+        ReferenceDeletePolicy::RemoveReference,
+        ReferenceDeletePolicy::BlockDelete,
+        ReferenceDeletePolicy::Cascade,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReferenceDeletePolicy::RemoveReference => "remove_reference",
+            ReferenceDeletePolicy::BlockDelete => "block_delete",
+            ReferenceDeletePolicy::Cascade => "cascade",
+        }
+    }
+}
+
+impl std::str::FromStr for ReferenceDeletePolicy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|policy| policy.as_str().eq_ignore_ascii_case(s))
+            .ok_or(())
+    }
+}
+
 /// An item representing an attribute and the rules that enforce it. These rules enforce if an
 /// attribute on an [`Entry`] may be single or multi value, must be unique amongst all other types
 /// of this attribute, if the attribute should be [`indexed`], and what type of data [`syntax`] it may hold.
@@ -105,6 +345,8 @@ pub struct SchemaAttribute {
     pub multivalue: bool,
     /// If this flag is set, all instances of this attribute must be a unique value in the database.
     pub unique: bool,
+    /// The entries that a unique value must be unique amongst.
+    pub unique_scope: UniqueScope,
     /// This defines that the value is a phantom - it is "not real", can never "be real". It
     /// is synthesised in memory, and will never be written to the database. This can exist for
     /// placeholders like cn/uid in ldap.
@@ -119,6 +361,24 @@ pub struct SchemaAttribute {
     pub indexed: bool,
     /// THe type of data that this attribute may hold.
     pub syntax: SyntaxType,
+    /// If this definition is provided by the server itself (it carries the `system` class).
+    /// Only system definitions may use the name of a reserved attribute.
+    pub system: bool,
+    /// Attributes that share an exclusion group are mutually exclusive, and at most one
+    /// of them may be present on an entry.
+    pub exclusion_group: Option<AttrString>,
+    /// If set, the values of this attribute are restricted to the members of this set. This
+    /// only applies to string syntaxes.
+    pub allowed_values: Option<BTreeSet<String>>,
+    /// For reference attributes, what happens to the entries holding a reference when
+    /// the entry it refers to is deleted.
+    pub on_delete: ReferenceDeletePolicy,
+    /// If set, changes to this attribute are always recorded in the audit log, regardless
+    /// of any other audit configuration.
+    pub audit: bool,
+    /// The validator of a [`SyntaxType::Custom`] attribute, taken from the schema's
+    /// [`CustomSyntaxRegistry`] when the definition is inserted.
+    pub(crate) custom_validator: Option<AttachedValidator>,
 }
 
 impl SchemaAttribute {
@@ -173,6 +433,30 @@ impl SchemaAttribute {
                 OperationError::InvalidSchemaState("missing unique".to_string())
             })?;
 
+        let unique_scope = match value
+            .get_ava_set(Attribute::UniqueScope)
+            .and_then(|vs| vs.as_iutf8_iter())
+            .map(|iter| iter.map(AttrString::from).collect::<BTreeSet<_>>())
+        {
+            Some(scope) if scope.contains(UniqueScope::PER_SYNC_PARENT) => {
+                if scope.len() != 1 {
+                    admin_error!(
+                        "{} {} can not be combined with classes - {}",
+                        Attribute::UniqueScope,
+                        UniqueScope::PER_SYNC_PARENT,
+                        name
+                    );
+                    return Err(OperationError::InvalidSchemaState(format!(
+                        "invalid {}",
+                        Attribute::UniqueScope
+                    )));
+                }
+                UniqueScope::PerSyncParent
+            }
+            Some(classes) => UniqueScope::Classes(classes),
+            None => UniqueScope::default(),
+        };
+
         let phantom = value
             .get_ava_single_bool(Attribute::Phantom)
             .unwrap_or_default();
@@ -200,6 +484,32 @@ impl SchemaAttribute {
                 OperationError::InvalidSchemaState(format!("missing {}", Attribute::Syntax))
             })?;
 
+        let system = value.attribute_equality(Attribute::Class, &EntryClass::System.into());
+
+        let exclusion_group = value
+            .get_ava_single_iutf8(Attribute::ExclusionGroup)
+            .map(AttrString::from);
+
+        let allowed_values = value
+            .get_ava_set(Attribute::AllowedValue)
+            .and_then(|vs| vs.as_utf8_iter())
+            .map(|iter| {
+                iter.map(|s| Self::normalise_allowed_value(syntax, s))
+                    .collect::<BTreeSet<_>>()
+            });
+
+        let on_delete = match value.get_ava_single_iutf8(Attribute::OnDelete) {
+            Some(policy) => policy.parse().map_err(|_| {
+                admin_error!("invalid {} {} - {}", Attribute::OnDelete, policy, name);
+                OperationError::InvalidSchemaState(format!("invalid {}", Attribute::OnDelete))
+            })?,
+            None => ReferenceDeletePolicy::default(),
+        };
+
+        let audit = value
+            .get_ava_single_bool(Attribute::Audit)
+            .unwrap_or_default();
+
         trace!(?name, ?indexed);
 
         Ok(SchemaAttribute {
@@ -208,14 +518,37 @@ impl SchemaAttribute {
             description,
             multivalue,
             unique,
+            unique_scope,
             phantom,
             sync_allowed,
             replicated,
             indexed,
             syntax,
+            system,
+            exclusion_group,
+            allowed_values,
+            on_delete,
+            audit,
+            custom_validator: None,
         })
     }
 
+    /// Values of the insensitive string syntaxes are stored in lowercase, so the allowed
+    /// values must be too for them to be comparable.
+    fn normalise_allowed_value(syntax: SyntaxType, value: &str) -> String {
+        match syntax {
+            SyntaxType::Utf8StringInsensitive | SyntaxType::Utf8StringIname => value.to_lowercase(),
+            _ => value.to_string(),
+        }
+    }
+
+    fn is_value_allowed(&self, value: &str) -> bool {
+        self.allowed_values
+            .as_ref()
+            .map(|allowed| allowed.contains(value))
+            .unwrap_or(true)
+    }
+
     // There may be a difference between a value and a filter value on complex
     // types - IE a complex type may have multiple parts that are secret, but a filter
     // on that may only use a single tagged attribute for example.
@@ -282,6 +615,10 @@ impl SchemaAttribute {
                 matches!(v, PartialValue::Uuid(_)) || matches!(v, PartialValue::Refer(_))
             }
             SyntaxType::Sha256 => matches!(v, PartialValue::Sha256(_)),
+            SyntaxType::CredentialLockout => matches!(v, PartialValue::CredentialLockout(_)),
+            SyntaxType::PasskeyUsage => matches!(v, PartialValue::PasskeyUsage(_)),
+            SyntaxType::MemberExpiry => matches!(v, PartialValue::MemberExpiry(_)),
+            SyntaxType::Custom => matches!(v, PartialValue::Utf8(_)),
             // SyntaxType::Json => matches!(v, PartialValue::Json),
             // Should not be queried
             SyntaxType::Json | SyntaxType::Message => false,
@@ -300,6 +637,12 @@ impl SchemaAttribute {
     }
 
     pub fn validate_value(&self, a: &Attribute, v: &Value) -> Result<(), SchemaError> {
+        let result = self.validate_value_inner(a, v);
+        record_validation(a, &result);
+        result
+    }
+
+    fn validate_value_inner(&self, a: &Attribute, v: &Value) -> Result<(), SchemaError> {
         let r = v.validate()
             && match self.syntax {
                 SyntaxType::Boolean => matches!(v, Value::Bool(_)),
@@ -325,10 +668,7 @@ impl SchemaAttribute {
                 SyntaxType::Url => matches!(v, Value::Url(_)),
                 SyntaxType::OauthScope => matches!(v, Value::OauthScope(_)),
                 SyntaxType::OauthScopeMap => matches!(v, Value::OauthScopeMap(_, _)),
-                SyntaxType::OauthClaimMap => {
-                    matches!(v, Value::OauthClaimValue(_, _, _))
-                        || matches!(v, Value::OauthClaimMap(_, _))
-                }
+                SyntaxType::OauthClaimMap => Self::validate_oauth_claim_map(v),
                 SyntaxType::PrivateBinary => matches!(v, Value::PrivateBinary(_)),
                 SyntaxType::IntentToken => matches!(v, Value::IntentToken(_, _)),
                 SyntaxType::Passkey => matches!(v, Value::Passkey(_, _, _)),
@@ -352,11 +692,24 @@ impl SchemaAttribute {
                 SyntaxType::ApplicationPassword => matches!(v, Value::ApplicationPassword(..)),
                 SyntaxType::Json => matches!(v, Value::Json(_)),
                 SyntaxType::Sha256 => matches!(v, Value::Sha256(_)),
+                SyntaxType::CredentialLockout => matches!(v, Value::CredentialLockout(..)),
+                SyntaxType::PasskeyUsage => matches!(v, Value::PasskeyUsage(..)),
+                SyntaxType::MemberExpiry => matches!(v, Value::MemberExpiry(..)),
+                SyntaxType::Custom => matches!(v, Value::Utf8(_)),
                 SyntaxType::EcKeyPrivate => matches!(v, Value::SecretValue(_)),
                 SyntaxType::Message => false,
             };
         if r {
-            Ok(())
+            if let (SyntaxType::Custom, Value::Utf8(s)) = (self.syntax, v) {
+                self.validate_custom_syntax(a, s)?;
+            }
+            match v {
+                Value::Utf8(s) | Value::Iutf8(s) | Value::Iname(s) if !self.is_value_allowed(s) => {
+                    error!(?a, ?v, "validate_value failure - ValueNotAllowed");
+                    Err(SchemaError::ValueNotAllowed(a.to_string()))
+                }
+                _ => Ok(()),
+            }
         } else {
             error!(
                 ?a,
@@ -368,18 +721,79 @@ impl SchemaAttribute {
         }
     }
 
+    fn validate_custom_syntax(&self, a: &Attribute, value: &str) -> Result<(), SchemaError> {
+        let Some(AttachedValidator(validator)) = &self.custom_validator else {
+            error!(?a, "no validator is registered for this custom syntax");
+            return Err(SchemaError::CustomSyntaxNotRegistered(a.to_string()));
+        };
+
+        if validator(value) {
+            Ok(())
+        } else {
+            // The value itself isn't logged, as custom syntaxes may hold sensitive data.
+            error!(?a, "custom syntax validator rejected value");
+            Err(SchemaError::InvalidAttributeSyntax(a.to_string()))
+        }
+    }
+
+    /// Claim maps are checked beyond their value type so that a malformed claim name or
+    /// group reference is rejected when it is written, rather than when a token is issued.
+    fn validate_oauth_claim_map(v: &Value) -> bool {
+        match v {
+            Value::OauthClaimValue(name, group_uuid, claims) => {
+                OAUTH_CLAIMNAME_RE.is_match(name)
+                    && !group_uuid.is_nil()
+                    && !claims.is_empty()
+                    && claims
+                        .iter()
+                        .all(|claim| OAUTH_CLAIMNAME_RE.is_match(claim))
+            }
+            Value::OauthClaimMap(name, _) => OAUTH_CLAIMNAME_RE.is_match(name),
+            Value::OauthClaimTemplate(name, template) => {
+                OAUTH_CLAIMNAME_RE.is_match(name)
+                    && template
+                        .as_deref()
+                        .map(Value::validate_oauth_claim_template)
+                        .unwrap_or(true)
+            }
+            _ => false,
+        }
+    }
+
     pub fn validate_ava(&self, a: &Attribute, ava: &ValueSet) -> Result<(), SchemaError> {
+        let result = self.validate_ava_inner(a, ava);
+        record_validation(a, &result);
+        result
+    }
+
+    fn validate_ava_inner(&self, a: &Attribute, ava: &ValueSet) -> Result<(), SchemaError> {
         trace!("Checking for valid {:?} -> {:?}", self.name, ava);
+        // An empty set can exist mid-modification and has no values to check. Presence of
+        // must attributes is asserted by the entry, not here.
+        if ava.is_empty() {
+            return Ok(());
+        }
         // Check multivalue
         if !self.multivalue && ava.len() > 1 {
             // lrequest_error!("Ava len > 1 on single value attribute!");
             admin_error!("Ava len > 1 on single value attribute!");
             return Err(SchemaError::InvalidAttributeSyntax(a.to_string()));
         };
-        // If syntax, check the type is correct
-        let valid = self.syntax == ava.syntax();
+        // If syntax, check the type is correct. Custom syntaxes are stored as strings.
+        let valid = self.syntax == ava.syntax()
+            || (self.syntax == SyntaxType::Custom && ava.syntax() == SyntaxType::Utf8String);
         if valid && ava.validate(self) {
-            Ok(())
+            if self.syntax == SyntaxType::Custom {
+                for value in ava.as_utf8_iter().into_iter().flatten() {
+                    self.validate_custom_syntax(a, value)?;
+                }
+            }
+            if ava.any_str_matches(&|s| !self.is_value_allowed(s)) {
+                error!(?a, "validate_ava - ValueNotAllowed");
+                Err(SchemaError::ValueNotAllowed(a.to_string()))
+            } else {
+                Ok(())
+            }
         } else {
             error!(
                 ?a,
@@ -388,6 +802,254 @@ impl SchemaAttribute {
             Err(SchemaError::InvalidAttributeSyntax(a.to_string()))
         }
     }
+
+    /// The index types that are maintained for this attribute. Unique attributes are always
+    /// indexed so that uniqueness can be checked.
+    pub fn effective_index_types(&self) -> &[IndexType] {
+        if self.indexed || self.unique {
+            self.syntax.index_types()
+        } else {
+            &[]
+        }
+    }
+
+    /// Compare two values of this attribute for sorting. Numbers are compared numerically,
+    /// times chronologically and strings lexically. Values of other syntaxes, or that don't
+    /// match the syntax of this attribute, are considered equal so that a stable sort keeps
+    /// them in their existing order.
+    pub fn compare_values(&self, a: &Value, b: &Value) -> Ordering {
+        match (self.syntax, a, b) {
+            (SyntaxType::Uint32, Value::Uint32(a), Value::Uint32(b)) => a.cmp(b),
+            (SyntaxType::Int64, Value::Int64(a), Value::Int64(b)) => a.cmp(b),
+            (SyntaxType::Uint64, Value::Uint64(a), Value::Uint64(b)) => a.cmp(b),
+            (SyntaxType::DateTime, Value::DateTime(a), Value::DateTime(b)) => a.cmp(b),
+            (SyntaxType::Cid, Value::Cid(a), Value::Cid(b)) => a.cmp(b),
+            (SyntaxType::Boolean, Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (SyntaxType::Utf8String, Value::Utf8(a), Value::Utf8(b))
+            | (SyntaxType::Utf8StringInsensitive, Value::Iutf8(a), Value::Iutf8(b))
+            | (SyntaxType::Utf8StringIname, Value::Iname(a), Value::Iname(b))
+            | (SyntaxType::EmailAddress, Value::EmailAddress(a, _), Value::EmailAddress(b, _)) => {
+                a.cmp(b)
+            }
+            (
+                SyntaxType::SecurityPrincipalName,
+                Value::Spn(a_name, a_realm),
+                Value::Spn(b_name, b_realm),
+            ) => (a_realm, a_name).cmp(&(b_realm, b_name)),
+            (SyntaxType::Uuid, Value::Uuid(a), Value::Uuid(b))
+            | (SyntaxType::ReferenceUuid, Value::Refer(a), Value::Refer(b)) => a.cmp(b),
+            _ => Ordering::Equal,
+        }
+    }
+}
+
+/// The changes that entries need once an attribute has been renamed by
+/// [`SchemaWriteTransaction::rename_attribute`]. The schema no longer knows the previous
+/// name, so the values held under it must be moved to the new one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttrRenamePlan {
+    pub from: Attribute,
+    pub to: Attribute,
+    /// The classes whose definitions referred to the attribute by its previous name. Their
+    /// definitions have been updated, and so must their entries.
+    pub classes: Vec<AttrString>,
+}
+
+impl AttrRenamePlan {
+    /// Move the values of the renamed attribute on this entry. Returns `true` if the entry
+    /// held the attribute and was changed.
+    pub fn apply<STATE: Clone>(&self, entry: &mut Entry<EntryInvalid, STATE>) -> bool {
+        match entry.pop_ava(&self.from) {
+            Some(vs) => {
+                entry.set_ava_set(&self.to, vs);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// The combined definition of a set of classes, describing what an entry holding exactly
+/// those classes must and may contain. See [`SchemaTransaction::effective_schema_for`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EffectiveSchema {
+    /// Attributes that must be present.
+    pub must: BTreeSet<Attribute>,
+    /// Attributes that may be present. This includes every attribute in `must`.
+    pub may: BTreeSet<Attribute>,
+    /// Attributes that can not be present because one of the classes excludes them.
+    pub attr_excludes: BTreeSet<Attribute>,
+}
+
+impl EffectiveSchema {
+    /// The attributes that an entry with these classes is allowed to hold.
+    pub fn allowed(&self) -> Vec<&Attribute> {
+        self.may
+            .iter()
+            .filter(|attr| !self.attr_excludes.contains(*attr))
+            .collect()
+    }
+
+    /// Check if an entry with these classes is allowed to hold `attr`.
+    pub fn allows(&self, attr: &Attribute) -> bool {
+        self.may.contains(attr) && !self.attr_excludes.contains(attr)
+    }
+}
+
+/// A builder for a [`SchemaAttribute`]. If no uuid is provided, a random one is
+/// assigned during [`SchemaAttributeBuilder::build`].
+pub struct SchemaAttributeBuilder {
+    inner: SchemaAttribute,
+}
+
+impl SchemaAttribute {
+    pub fn builder(name: Attribute, syntax: SyntaxType) -> SchemaAttributeBuilder {
+        SchemaAttributeBuilder {
+            inner: SchemaAttribute {
+                name,
+                uuid: Uuid::nil(),
+                syntax,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl SchemaAttributeBuilder {
+    pub fn uuid(mut self, uuid: Uuid) -> Self {
+        self.inner.uuid = uuid;
+        self
+    }
+
+    pub fn description<S: Into<String>>(mut self, description: S) -> Self {
+        self.inner.description = description.into();
+        self
+    }
+
+    pub fn multivalue(mut self, multivalue: bool) -> Self {
+        self.inner.multivalue = multivalue;
+        self
+    }
+
+    pub fn unique(mut self, unique: bool) -> Self {
+        self.inner.unique = unique;
+        self
+    }
+
+    pub fn unique_scope<I, S>(mut self, classes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<AttrString>,
+    {
+        self.inner.unique_scope =
+            UniqueScope::Classes(classes.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn unique_per_sync_parent(mut self) -> Self {
+        self.inner.unique_scope = UniqueScope::PerSyncParent;
+        self
+    }
+
+    pub fn phantom(mut self, phantom: bool) -> Self {
+        self.inner.phantom = phantom;
+        self
+    }
+
+    pub fn sync_allowed(mut self, sync_allowed: bool) -> Self {
+        self.inner.sync_allowed = sync_allowed;
+        self
+    }
+
+    pub fn replicated(mut self, replicated: Replicated) -> Self {
+        self.inner.replicated = replicated;
+        self
+    }
+
+    pub fn indexed(mut self, indexed: bool) -> Self {
+        self.inner.indexed = indexed;
+        self
+    }
+
+    pub fn system(mut self, system: bool) -> Self {
+        self.inner.system = system;
+        self
+    }
+
+    pub fn exclusion_group<S: Into<AttrString>>(mut self, exclusion_group: S) -> Self {
+        self.inner.exclusion_group = Some(exclusion_group.into());
+        self
+    }
+
+    pub fn allowed_values<I, S>(mut self, allowed_values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let syntax = self.inner.syntax;
+        self.inner.allowed_values = Some(
+            allowed_values
+                .into_iter()
+                .map(|s| SchemaAttribute::normalise_allowed_value(syntax, s.as_ref()))
+                .collect(),
+        );
+        self
+    }
+
+    pub fn on_delete(mut self, on_delete: ReferenceDeletePolicy) -> Self {
+        self.inner.on_delete = on_delete;
+        self
+    }
+
+    pub fn audit(mut self, audit: bool) -> Self {
+        self.inner.audit = audit;
+        self
+    }
+
+    pub fn build(self) -> SchemaAttribute {
+        let mut inner = self.inner;
+        if inner.uuid.is_nil() {
+            inner.uuid = Uuid::new_v4();
+        }
+        inner
+    }
+}
+
+/// How a class may be combined with other classes on an entry. This follows the object
+/// class kinds of LDAP, where an entry has exactly one structural class which may be
+/// extended by any number of auxiliary classes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ClassKind {
+    /// The class that defines what an entry is.
+    Structural,
+    /// A class that adds attributes to an entry of any structural class.
+    #[default]
+    Auxiliary,
+    /// A class that is only a base for other classes, and never describes an entry alone.
+    Abstract,
+}
+
+impl ClassKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClassKind::Structural => "structural",
+            ClassKind::Auxiliary => "auxiliary",
+            ClassKind::Abstract => "abstract",
+        }
+    }
+}
+
+impl TryFrom<&str> for ClassKind {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "structural" => Ok(ClassKind::Structural),
+            "auxiliary" => Ok(ClassKind::Auxiliary),
+            "abstract" => Ok(ClassKind::Abstract),
+            _ => Err(()),
+        }
+    }
 }
 
 /// An item representing a class and the rules for that class. These rules enforce that an
@@ -427,6 +1089,17 @@ pub struct SchemaClass {
     /// A list of classes that can not co-exist with this item at the same time.
     pub systemexcludes: Vec<AttrString>,
     pub excludes: Vec<AttrString>,
+    /// A list of attributes that can not be present on an entry while this class is.
+    pub attr_excludes: Vec<Attribute>,
+    /// Groups of attributes where at least one attribute of each group must be present
+    /// on an entry while this class is. Each group is stored as a single comma separated
+    /// value of [`Attribute::RequiresOneOf`].
+    pub requires_one_of: Vec<Vec<Attribute>>,
+    /// If this definition is provided by the server itself (it carries the `system` class).
+    /// Only system definitions may use the name of a reserved class.
+    pub system: bool,
+    /// Classes without a kind are auxiliary, as all classes were before kinds existed.
+    pub kind: ClassKind,
 }
 
 impl SchemaClass {
@@ -501,6 +1174,36 @@ impl SchemaClass {
             .get_ava_iter_iutf8(Attribute::Excludes)
             .map(|i| i.map(|v| v.into()).collect())
             .unwrap_or_default();
+        let attr_excludes = value
+            .get_ava_iter_iutf8(Attribute::AttrExcludes)
+            .into_iter()
+            .flat_map(|iter| iter.map(Attribute::from))
+            .collect();
+        let requires_one_of = value
+            .get_ava_iter_iutf8(Attribute::RequiresOneOf)
+            .into_iter()
+            .flat_map(|iter| {
+                iter.map(|group| {
+                    group
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|attr| !attr.is_empty())
+                        .map(Attribute::from)
+                        .collect::<Vec<_>>()
+                })
+            })
+            .filter(|group| !group.is_empty())
+            .collect();
+
+        let kind = match value.get_ava_single_iutf8(Attribute::ClassKind) {
+            Some(kind) => ClassKind::try_from(kind).map_err(|_| {
+                error!("invalid {} {} - {}", Attribute::ClassKind, kind, name);
+                OperationError::InvalidSchemaState(format!("invalid {}", Attribute::ClassKind))
+            })?,
+            None => ClassKind::default(),
+        };
+
+        let system = value.attribute_equality(Attribute::Class, &EntryClass::System.into());
 
         Ok(SchemaClass {
             name,
@@ -515,6 +1218,10 @@ impl SchemaClass {
             supplements,
             systemexcludes,
             excludes,
+            attr_excludes,
+            requires_one_of,
+            system,
+            kind,
         })
     }
 
@@ -527,62 +1234,432 @@ impl SchemaClass {
             .chain(self.systemmust.iter())
             .chain(self.must.iter())
     }
+
+    /// Check if this class can be present alongside `others`, returning the classes
+    /// from `others` that this class excludes. Only the excludes of this class are
+    /// considered - the excludes of `others` must be checked against this class
+    /// separately.
+    pub fn compatible_with(&self, others: &BTreeSet<&str>) -> Result<(), Vec<AttrString>> {
+        let conflicts: Vec<AttrString> = self
+            .systemexcludes
+            .iter()
+            .chain(self.excludes.iter())
+            .filter(|class| others.contains(class.as_str()))
+            .cloned()
+            .collect();
+
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(conflicts)
+        }
+    }
 }
 
-pub trait SchemaTransaction {
-    fn get_classes(&self) -> &HashMap<AttrString, SchemaClass>;
-    fn get_attributes(&self) -> &HashMap<Attribute, SchemaAttribute>;
+/// A builder for a [`SchemaClass`]. If no uuid is provided, a random one is
+/// assigned during [`SchemaClassBuilder::build`].
+pub struct SchemaClassBuilder {
+    inner: SchemaClass,
+}
 
-    fn get_attributes_unique(&self) -> &Vec<Attribute>;
-    fn get_reference_types(&self) -> &HashMap<Attribute, SchemaAttribute>;
+impl SchemaClass {
+    pub fn builder<S: Into<AttrString>>(name: S) -> SchemaClassBuilder {
+        SchemaClassBuilder {
+            inner: SchemaClass {
+                name: name.into(),
+                uuid: Uuid::nil(),
+                ..Default::default()
+            },
+        }
+    }
+}
 
-    fn validate(&self) -> Vec<Result<(), ConsistencyError>> {
-        let mut res = Vec::with_capacity(0);
+impl SchemaClassBuilder {
+    pub fn uuid(mut self, uuid: Uuid) -> Self {
+        self.inner.uuid = uuid;
+        self
+    }
 
-        let class_snapshot = self.get_classes();
-        let attribute_snapshot = self.get_attributes();
+    pub fn description<S: Into<String>>(mut self, description: S) -> Self {
+        self.inner.description = description.into();
+        self
+    }
 
-        // We need to check that every uuid is unique because during tests we aren't doing
-        // a disk reload, which means we were missing this and causing potential migration
-        // failures on upgrade.
+    pub fn sync_allowed(mut self, sync_allowed: bool) -> Self {
+        self.inner.sync_allowed = sync_allowed;
+        self
+    }
 
-        let mut unique_uuid_set = HashSet::new();
-        class_snapshot
-            .values()
-            .map(|class| &class.uuid)
-            .chain(attribute_snapshot.values().map(|attr| &attr.uuid))
-            .for_each(|uuid| {
-                // If the set did not have this value present, true is returned.
-                if !unique_uuid_set.insert(uuid) {
-                    res.push(Err(ConsistencyError::SchemaUuidNotUnique(*uuid)))
-                }
-            });
+    pub fn systemmay(mut self, systemmay: Vec<Attribute>) -> Self {
+        self.inner.systemmay = systemmay;
+        self
+    }
 
-        class_snapshot.values().for_each(|class| {
-            // report the class we are checking
-            class
-                .systemmay
-                .iter()
-                .chain(class.may.iter())
-                .chain(class.systemmust.iter())
-                .chain(class.must.iter())
-                .for_each(|a| {
-                    match attribute_snapshot.get(a) {
-                        Some(attr) => {
-                            // We have the attribute, ensure it's not a phantom.
-                            if attr.phantom {
-                                res.push(Err(ConsistencyError::SchemaClassPhantomAttribute(
-                                    class.name.to_string(),
-                                    a.to_string(),
-                                )))
-                            }
-                        }
-                        None => {
+    pub fn may(mut self, may: Vec<Attribute>) -> Self {
+        self.inner.may = may;
+        self
+    }
+
+    pub fn systemmust(mut self, systemmust: Vec<Attribute>) -> Self {
+        self.inner.systemmust = systemmust;
+        self
+    }
+
+    pub fn must(mut self, must: Vec<Attribute>) -> Self {
+        self.inner.must = must;
+        self
+    }
+
+    pub fn systemsupplements(mut self, systemsupplements: Vec<AttrString>) -> Self {
+        self.inner.systemsupplements = systemsupplements;
+        self
+    }
+
+    pub fn supplements(mut self, supplements: Vec<AttrString>) -> Self {
+        self.inner.supplements = supplements;
+        self
+    }
+
+    pub fn systemexcludes(mut self, systemexcludes: Vec<AttrString>) -> Self {
+        self.inner.systemexcludes = systemexcludes;
+        self
+    }
+
+    pub fn excludes(mut self, excludes: Vec<AttrString>) -> Self {
+        self.inner.excludes = excludes;
+        self
+    }
+
+    pub fn attr_excludes(mut self, attr_excludes: Vec<Attribute>) -> Self {
+        self.inner.attr_excludes = attr_excludes;
+        self
+    }
+
+    pub fn requires_one_of(mut self, requires_one_of: Vec<Vec<Attribute>>) -> Self {
+        self.inner.requires_one_of = requires_one_of;
+        self
+    }
+
+    pub fn system(mut self, system: bool) -> Self {
+        self.inner.system = system;
+        self
+    }
+
+    pub fn kind(mut self, kind: ClassKind) -> Self {
+        self.inner.kind = kind;
+        self
+    }
+
+    pub fn build(self) -> SchemaClass {
+        let mut inner = self.inner;
+        if inner.uuid.is_nil() {
+            inner.uuid = Uuid::new_v4();
+        }
+        inner
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The schema is usable, but something is likely not what was intended.
+    Warning,
+    /// The schema is inconsistent and must not be used.
+    Error,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SchemaValidationIssue {
+    pub severity: Severity,
+    pub error: ConsistencyError,
+}
+
+impl SchemaValidationIssue {
+    fn warning(error: ConsistencyError) -> Self {
+        SchemaValidationIssue {
+            severity: Severity::Warning,
+            error,
+        }
+    }
+
+    fn error(error: ConsistencyError) -> Self {
+        SchemaValidationIssue {
+            severity: Severity::Error,
+            error,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+pub trait SchemaTransaction {
+    fn get_classes(&self) -> &HashMap<AttrString, SchemaClass>;
+    fn get_attributes(&self) -> &HashMap<Attribute, SchemaAttribute>;
+
+    fn get_attributes_unique(&self) -> &HashMap<Attribute, UniqueScope>;
+
+    /// Determine if values of this attribute must be unique, either across all entries
+    /// or within its unique scope.
+    fn is_unique(&self, attr: &Attribute) -> bool {
+        self.get_attributes_unique().contains_key(attr)
+    }
+
+    /// The scope that the values of this attribute must be unique within, or `None` if
+    /// the attribute is not unique.
+    fn unique_scope(&self, attr: &Attribute) -> Option<&UniqueScope> {
+        self.get_attributes_unique().get(attr)
+    }
+    fn get_reference_types(&self) -> &HashMap<Attribute, SchemaAttribute>;
+
+    /// Check that a partial value may be used with the named attribute, such as in a
+    /// filter or an assertion.
+    fn validate_partialvalue(
+        &self,
+        attr: &Attribute,
+        pv: &PartialValue,
+    ) -> Result<(), SchemaError> {
+        match self.get_attributes().get(attr) {
+            Some(schema_a) => schema_a.validate_partialvalue(attr, pv),
+            None => Err(SchemaError::InvalidAttribute(attr.to_string())),
+        }
+    }
+
+    /// Check that the attribute exists, and that the filter operation is meaningful for
+    /// its syntax. For example, a uuid can be asserted equal but not matched by substring.
+    fn validate_filter_op(&self, attr: &Attribute, op: FilterOpKind) -> Result<(), SchemaError> {
+        let schema_a = self
+            .get_attributes()
+            .get(attr)
+            .ok_or_else(|| SchemaError::InvalidAttribute(attr.to_string()))?;
+
+        let supported = match op {
+            FilterOpKind::Equality | FilterOpKind::Presence => true,
+            FilterOpKind::SubString => schema_a.syntax.supports_substring(),
+            FilterOpKind::Ordering => schema_a.syntax.is_orderable(),
+        };
+
+        if supported {
+            Ok(())
+        } else {
+            Err(SchemaError::FilterOperationNotSupported(
+                attr.to_string(),
+                op.to_string(),
+            ))
+        }
+    }
+
+    /// The attribute that an index, as produced by [SchemaWriteTransaction::reload_idxmeta],
+    /// belongs to.
+    fn attr_for_idxkey(&self, key: &IdxKey) -> Option<&SchemaAttribute> {
+        self.get_attributes().get(&key.attr)
+    }
+
+    /// The attributes of each exclusion group, keyed by the group name.
+    fn get_exclusion_groups(&self) -> &HashMap<AttrString, Vec<Attribute>>;
+
+    /// All attributes, sorted by name. Unlike iterating [Self::get_attributes] the
+    /// order is stable, so this is suitable for producing comparable output.
+    fn attributes_sorted(&self) -> Vec<(&Attribute, &SchemaAttribute)> {
+        let mut attributes: Vec<_> = self.get_attributes().iter().collect();
+        attributes.sort_unstable_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+        attributes
+    }
+
+    /// The attributes whose changes must always be audited, sorted by name.
+    fn audited_attrs(&self) -> Vec<&Attribute> {
+        let mut attrs: Vec<&Attribute> = self
+            .get_attributes()
+            .iter()
+            .filter(|(_, a)| a.audit)
+            .map(|(name, _)| name)
+            .collect();
+        attrs.sort_unstable_by(|a, b| a.as_str().cmp(b.as_str()));
+        attrs
+    }
+
+    /// All classes, sorted by name.
+    fn classes_sorted(&self) -> Vec<(&AttrString, &SchemaClass)> {
+        let mut classes: Vec<_> = self.get_classes().iter().collect();
+        classes.sort_unstable_by_key(|(name, _)| *name);
+        classes
+    }
+
+    /// All classes that supplement `class`, that is, classes whose `supplements` or
+    /// `systemsupplements` name it. These are the classes that depend on `class` being
+    /// present, so this is the reverse of walking the supplements of a class. The result
+    /// is sorted by class name.
+    fn supplemented_by(&self, class: &str) -> Vec<&SchemaClass> {
+        let mut classes: Vec<&SchemaClass> = self
+            .get_classes()
+            .values()
+            .filter(|sc| {
+                sc.systemsupplements
+                    .iter()
+                    .chain(sc.supplements.iter())
+                    .any(|s| s.as_str() == class)
+            })
+            .collect();
+        classes.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        classes
+    }
+
+    /// Determine which of `classes` is the single structural class of an entry, as
+    /// required by LDAP. All other classes of the entry must be auxiliary or abstract.
+    fn structural_class<'c>(&self, classes: &[&'c str]) -> Result<&'c str, SchemaError> {
+        let schema_classes = self.get_classes();
+
+        let unknown: Vec<String> = classes
+            .iter()
+            .filter(|c| !schema_classes.contains_key(**c))
+            .map(|c| c.to_string())
+            .collect();
+        if !unknown.is_empty() {
+            return Err(SchemaError::InvalidClass(unknown));
+        }
+
+        let structural: Vec<&'c str> = classes
+            .iter()
+            .copied()
+            .filter(|c| {
+                schema_classes
+                    .get(*c)
+                    .map(|sc| sc.kind == ClassKind::Structural)
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        match structural.as_slice() {
+            [] => Err(SchemaError::NoStructuralClass),
+            [class] => Ok(class),
+            _ => Err(SchemaError::MultipleStructuralClasses(
+                structural.iter().map(|c| c.to_string()).collect(),
+            )),
+        }
+    }
+
+    /// Validate the schema, returning only the issues that must prevent it being
+    /// used. Warnings are logged but otherwise ignored.
+    fn validate(&self) -> Vec<Result<(), ConsistencyError>> {
+        self.validate_detailed()
+            .into_iter()
+            .filter_map(|issue| match issue.severity {
+                Severity::Error => Some(Err(issue.error)),
+                Severity::Warning => {
+                    warn!(err = ?issue.error, "schema validate -> warning");
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Validate the schema, returning every issue found along with how severe it is.
+    fn validate_detailed(&self) -> Vec<SchemaValidationIssue> {
+        let mut res = Vec::with_capacity(0);
+
+        let class_snapshot = self.get_classes();
+        let attribute_snapshot = self.get_attributes();
+
+        // We need to check that every uuid is unique because during tests we aren't doing
+        // a disk reload, which means we were missing this and causing potential migration
+        // failures on upgrade.
+
+        let mut unique_uuid_set = HashSet::new();
+        class_snapshot
+            .values()
+            .map(|class| &class.uuid)
+            .chain(attribute_snapshot.values().map(|attr| &attr.uuid))
+            .for_each(|uuid| {
+                // If the set did not have this value present, true is returned.
+                if !unique_uuid_set.insert(uuid) {
+                    res.push(SchemaValidationIssue::error(
+                        ConsistencyError::SchemaUuidNotUnique(*uuid),
+                    ))
+                }
+            });
+
+        // Uniqueness is enforced per value, so a multivalue unique attribute may behave
+        // differently to what the schema author expects.
+        attribute_snapshot
+            .values()
+            .filter(|attr| attr.unique && attr.multivalue)
+            .for_each(|attr| {
+                res.push(SchemaValidationIssue::warning(
+                    ConsistencyError::SchemaAttributeUniqueMultivalue(attr.name.to_string()),
+                ))
+            });
+
+        // Requesting an index on a syntax that has no index types does nothing, which is
+        // likely not what the schema author intended.
+        attribute_snapshot
+            .values()
+            .filter(|attr| attr.indexed && attr.effective_index_types().is_empty())
+            .for_each(|attr| {
+                res.push(SchemaValidationIssue::warning(
+                    ConsistencyError::SchemaIndexedButNoIndexTypes(attr.name.to_string()),
+                ))
+            });
+
+        // LDAP relies on these being phantoms, else it silently omits them.
+        LDAP_PHANTOM_ATTRS
+            .iter()
+            .filter(|a| {
+                !attribute_snapshot
+                    .get(*a)
+                    .map(|attr| attr.phantom)
+                    .unwrap_or_default()
+            })
+            .for_each(|a| {
+                res.push(SchemaValidationIssue::error(
+                    ConsistencyError::SchemaLdapPhantomMissing(a.to_string()),
+                ))
+            });
+
+        // Access control and memberof depend on these being indexed references. A missing
+        // attribute is reported by the classes that require it.
+        SCHEMA_CRITICAL_REFERENCE_ATTRS
+            .iter()
+            .filter(|a| {
+                attribute_snapshot
+                    .get(*a)
+                    .is_some_and(|attr| attr.syntax != SyntaxType::ReferenceUuid || !attr.indexed)
+            })
+            .for_each(|a| {
+                res.push(SchemaValidationIssue::error(
+                    ConsistencyError::SchemaCriticalAttributeAltered(a.to_string()),
+                ))
+            });
+
+        class_snapshot.values().for_each(|class| {
+            // report the class we are checking
+            class
+                .systemmay
+                .iter()
+                .chain(class.may.iter())
+                .chain(class.systemmust.iter())
+                .chain(class.must.iter())
+                .for_each(|a| {
+                    match attribute_snapshot.get(a) {
+                        Some(attr) => {
+                            // We have the attribute, ensure it's not a phantom. A phantom
+                            // is never stored, so the class can never actually hold it.
+                            if attr.phantom {
+                                res.push(SchemaValidationIssue::warning(
+                                    ConsistencyError::SchemaClassPhantomAttribute(
+                                        class.name.to_string(),
+                                        a.to_string(),
+                                    ),
+                                ))
+                            }
+                        }
+                        None => {
                             // No such attr, something is missing!
-                            res.push(Err(ConsistencyError::SchemaClassMissingAttribute(
-                                class.name.to_string(),
-                                a.to_string(),
-                            )))
+                            res.push(SchemaValidationIssue::error(
+                                ConsistencyError::SchemaClassMissingAttribute(
+                                    class.name.to_string(),
+                                    a.to_string(),
+                                ),
+                            ))
                         }
                     }
                 })
@@ -607,6 +1684,26 @@ pub trait SchemaTransaction {
         }
     }
 
+    /// The attributes that may be returned over LDAP, ordered by name. These are the
+    /// phantoms that LDAP maps onto real attributes, and the replicated real attributes
+    /// that do not hold secrets. Other phantoms, such as imported credentials, are never
+    /// stored and so are never returned.
+    fn ldap_visible_attrs(&self) -> Vec<&SchemaAttribute> {
+        let mut attrs: Vec<&SchemaAttribute> = self
+            .get_attributes()
+            .values()
+            .filter(|a_schema| {
+                if a_schema.phantom {
+                    LDAP_PHANTOM_ATTRS.contains(&a_schema.name)
+                } else {
+                    a_schema.replicated.into() && a_schema.syntax != SyntaxType::SecretUtf8String
+                }
+            })
+            .collect();
+        attrs.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        attrs
+    }
+
     fn is_multivalue(&self, attr: &Attribute) -> Result<bool, SchemaError> {
         match self.get_attributes().get(attr) {
             Some(a_schema) => Ok(a_schema.multivalue),
@@ -626,52 +1723,110 @@ pub trait SchemaTransaction {
         }
     }
 
-    fn query_attrs_difference(
-        &self,
-        prev_class: &BTreeSet<&str>,
-        new_iutf8: &BTreeSet<&str>,
-    ) -> Result<(BTreeSet<&str>, BTreeSet<&str>), SchemaError> {
+    /// Resolve class names to their definitions, failing if any are not known.
+    fn resolve_classes(&self, classes: &BTreeSet<&str>) -> Result<Vec<&SchemaClass>, SchemaError> {
         let schema_classes = self.get_classes();
 
         let mut invalid_classes = Vec::with_capacity(0);
 
-        let prev_attrs: BTreeSet<&str> = prev_class
+        let resolved: Vec<&SchemaClass> = classes
             .iter()
             .filter_map(|cls| match schema_classes.get(*cls) {
-                Some(x) => Some(x.may_iter()),
+                Some(x) => Some(x),
                 None => {
                     admin_debug!("invalid class: {:?}", cls);
                     invalid_classes.push(cls.to_string());
                     None
                 }
             })
-            // flatten all the inner iters.
-            .flatten()
-            .map(|s| s.as_str())
             .collect();
 
         if !invalid_classes.is_empty() {
             return Err(SchemaError::InvalidClass(invalid_classes));
         };
 
-        let new_attrs: BTreeSet<&str> = new_iutf8
+        Ok(resolved)
+    }
+
+    /// Determine the combined must and may attributes of an entry that holds exactly
+    /// `classes`. The classes must be a valid combination, so the supplements and
+    /// excludes of each class are checked in the same manner as entry validation.
+    fn effective_schema_for(
+        &self,
+        classes: &BTreeSet<&str>,
+    ) -> Result<EffectiveSchema, SchemaError> {
+        let resolved = self.resolve_classes(classes)?;
+
+        let supplements_classes: Vec<&AttrString> = resolved
             .iter()
-            .filter_map(|cls| match schema_classes.get(*cls) {
-                Some(x) => Some(x.may_iter()),
-                None => {
-                    admin_debug!("invalid class: {:?}", cls);
-                    invalid_classes.push(cls.to_string());
-                    None
-                }
-            })
-            // flatten all the inner iters.
+            .flat_map(|cls| cls.systemsupplements.iter().chain(cls.supplements.iter()))
+            .collect();
+
+        if !supplements_classes.is_empty()
+            && !supplements_classes
+                .iter()
+                .any(|class| classes.contains(class.as_str()))
+        {
+            return Err(SchemaError::SupplementsNotSatisfied(
+                supplements_classes.iter().map(|s| s.to_string()).collect(),
+            ));
+        }
+
+        let invalid_excludes: Vec<String> = resolved
+            .iter()
+            .filter_map(|cls| cls.compatible_with(classes).err())
             .flatten()
+            .map(|class| class.to_string())
+            .collect();
+
+        if !invalid_excludes.is_empty() {
+            return Err(SchemaError::ExcludesNotSatisfied(invalid_excludes));
+        }
+
+        let must = resolved
+            .iter()
+            .flat_map(|cls| cls.systemmust.iter().chain(cls.must.iter()))
+            .cloned()
+            .collect();
+
+        let may = resolved
+            .iter()
+            .flat_map(|cls| cls.may_iter())
+            .cloned()
+            .collect();
+
+        let attr_excludes = resolved
+            .iter()
+            .flat_map(|cls| cls.attr_excludes.iter())
+            .cloned()
+            .collect();
+
+        Ok(EffectiveSchema {
+            must,
+            may,
+            attr_excludes,
+        })
+    }
+
+    fn query_attrs_difference(
+        &self,
+        prev_class: &BTreeSet<&str>,
+        new_iutf8: &BTreeSet<&str>,
+    ) -> Result<(BTreeSet<&str>, BTreeSet<&str>), SchemaError> {
+        let prev_attrs: BTreeSet<&str> = self
+            .resolve_classes(prev_class)?
+            .into_iter()
+            // flatten all the inner iters.
+            .flat_map(|cls| cls.may_iter())
             .map(|s| s.as_str())
             .collect();
 
-        if !invalid_classes.is_empty() {
-            return Err(SchemaError::InvalidClass(invalid_classes));
-        };
+        let new_attrs: BTreeSet<&str> = self
+            .resolve_classes(new_iutf8)?
+            .into_iter()
+            .flat_map(|cls| cls.may_iter())
+            .map(|s| s.as_str())
+            .collect();
 
         let removed = prev_attrs.difference(&new_attrs).copied().collect();
         let added = new_attrs.difference(&prev_attrs).copied().collect();
@@ -692,12 +1847,20 @@ impl SchemaWriteTransaction<'_> {
             attributes,
             unique_cache,
             ref_cache,
+            exclusion_cache,
+            generation,
+            reload_mode: _,
+            custom_syntax: _,
         } = self;
 
         unique_cache.commit();
         ref_cache.commit();
+        exclusion_cache.commit();
         classes.commit();
         attributes.commit();
+        // Only advance the generation once the new schema is visible. Writers are
+        // serialised by the write transactions above.
+        generation.fetch_add(1, AtomicOrdering::Release);
         Ok(())
     }
 
@@ -705,86 +1868,409 @@ impl SchemaWriteTransaction<'_> {
         &mut self,
         attributetypes: I,
     ) -> Result<(), OperationError> {
+        let attributetypes: Vec<_> = attributetypes.collect();
+
+        // Refuse to clobber a core definition, and do so before we purge anything so
+        // that the current schema remains intact.
+        attributetypes
+            .iter()
+            .try_for_each(|a| self.check_attribute_update(a))?;
+
         // purge all old attributes.
         self.attributes.clear();
 
         self.unique_cache.clear();
         self.ref_cache.clear();
+        self.exclusion_cache.clear();
         // Update with new ones.
         // Do we need to check for dups?
         // No, they'll over-write each other ... but we do need name uniqueness.
-        attributetypes.for_each(|a| {
-            // Update the unique and ref caches.
-            if a.syntax == SyntaxType::ReferenceUuid ||
-                a.syntax == SyntaxType::OauthScopeMap ||
-                a.syntax == SyntaxType::OauthClaimMap ||
-                // So that when an rs is removed we trigger removal of the sessions.
-                a.syntax == SyntaxType::Oauth2Session ||
-                // When an application is removed we trigger removal of passwords
-                a.syntax == SyntaxType::ApplicationPassword
-            // May not need to be a ref type since it doesn't have external links/impact?
-            // || a.syntax == SyntaxType::Session
-            {
-                self.ref_cache.insert(a.name.clone(), a.clone());
-            }
-            if a.unique {
-                self.unique_cache.push(a.name.clone());
-            }
-            // Finally insert.
-            self.attributes.insert(a.name.clone(), a);
-        });
+        attributetypes
+            .into_iter()
+            .for_each(|a| self.insert_attribute(a));
 
         Ok(())
     }
 
-    pub fn update_classes<I: Iterator<Item = SchemaClass>>(
-        &mut self,
-        classtypes: I,
-    ) -> Result<(), OperationError> {
-        // purge all old attributes.
-        self.classes.clear();
-        // Update with new ones.
-        // Do we need to check for dups?
-        // No, they'll over-write each other ... but we do need name uniqueness.
-        classtypes.into_iter().for_each(|a| {
-            self.classes.insert(a.name.clone(), a);
-        });
-        Ok(())
+    /// The mode that reloads of this schema from the database should use.
+    pub fn reload_mode(&self) -> SchemaReloadMode {
+        self.reload_mode
     }
 
-    pub fn to_entries(&self) -> Vec<Entry<EntryInit, EntryNew>> {
-        let r: Vec<_> = self
-            .attributes
-            .values()
-            .map(Entry::<EntryInit, EntryNew>::from)
-            .chain(
-                self.classes
-                    .values()
-                    .map(Entry::<EntryInit, EntryNew>::from),
-            )
+    /// Replace all attributes with those parsed from `entries`, skipping any entry that
+    /// is malformed or would be refused by [Self::update_attributes]. Returns the number of
+    /// attributes that were loaded, and the uuid and error of each entry that was skipped.
+    pub fn update_attributes_lenient(
+        &mut self,
+        entries: &[Arc<EntrySealedCommitted>],
+    ) -> (usize, Vec<(Uuid, OperationError)>) {
+        let mut failures = Vec::new();
+
+        let attributetypes: Vec<_> = entries
+            .iter()
+            .filter_map(|e| {
+                match SchemaAttribute::try_from(e)
+                    .and_then(|a| self.check_attribute_update(&a).map(|_| a))
+                {
+                    Ok(a) => Some(a),
+                    Err(err) => {
+                        warn!(uuid = ?e.get_uuid(), ?err, "skipping malformed attribute definition");
+                        failures.push((e.get_uuid(), err));
+                        None
+                    }
+                }
+            })
             .collect();
-        r
-    }
 
-    pub fn reload_idxmeta(&self) -> Vec<IdxKey> {
+        let loaded = attributetypes.len();
+
+        self.attributes.clear();
+
+        self.unique_cache.clear();
+        self.ref_cache.clear();
+        self.exclusion_cache.clear();
+        attributetypes
+            .into_iter()
+            .for_each(|a| self.insert_attribute(a));
+
+        (loaded, failures)
+    }
+
+    /// Insert or replace a single attribute definition, updating only the caches that
+    /// relate to it rather than rebuilding the whole attribute set.
+    pub fn upsert_attribute(&mut self, a: SchemaAttribute) -> Result<(), OperationError> {
+        self.check_attribute_update(&a)?;
+
+        if let Some(prev) = self.attributes.remove(&a.name) {
+            self.purge_attribute_caches(&prev);
+        }
+        self.insert_attribute(a);
+
+        Ok(())
+    }
+
+    /// Remove a single attribute definition and its cache entries. Removing an attribute
+    /// that does not exist is not an error.
+    pub fn remove_attribute(&mut self, name: &Attribute) -> Result<(), OperationError> {
+        if RESERVED_ATTRIBUTE_NAMES.contains(name) {
+            error!(%name, "attempt to remove reserved attribute");
+            return Err(OperationError::InvalidSchemaState(format!(
+                "attribute {} is reserved and may not be removed",
+                name
+            )));
+        }
+
+        if let Some(prev) = self.attributes.remove(name) {
+            self.purge_attribute_caches(&prev);
+        }
+
+        Ok(())
+    }
+
+    /// Rename an attribute, keeping its definition. Classes that refer to the attribute are
+    /// updated to the new name. The values held by entries are not moved, the returned plan
+    /// must be applied to them.
+    pub fn rename_attribute(
+        &mut self,
+        from: &Attribute,
+        to: Attribute,
+    ) -> Result<AttrRenamePlan, OperationError> {
+        let Some(prev) = self.attributes.get(from) else {
+            error!(%from, "attempt to rename attribute that does not exist");
+            return Err(OperationError::InvalidSchemaState(format!(
+                "attribute {} does not exist",
+                from
+            )));
+        };
+
+        if prev.system || RESERVED_ATTRIBUTE_NAMES.contains(from) {
+            error!(%from, "attempt to rename system attribute");
+            return Err(OperationError::InvalidSchemaState(format!(
+                "attribute {} is a system attribute and may not be renamed",
+                from
+            )));
+        }
+
+        if self.attributes.contains_key(&to) {
+            error!(%from, %to, "attempt to rename attribute to an existing name");
+            return Err(OperationError::InvalidSchemaState(format!(
+                "attribute {} already exists",
+                to
+            )));
+        }
+
+        let renamed = SchemaAttribute {
+            name: to.clone(),
+            ..prev.clone()
+        };
+        self.check_attribute_update(&renamed)?;
+
+        if let Some(prev) = self.attributes.remove(from) {
+            self.purge_attribute_caches(&prev);
+        }
+        self.insert_attribute(renamed);
+
+        let mut classes = Vec::with_capacity(0);
+        for class in self.classes.values_mut() {
+            let mut changed = false;
+            for attr in class
+                .systemmay
+                .iter_mut()
+                .chain(class.may.iter_mut())
+                .chain(class.systemmust.iter_mut())
+                .chain(class.must.iter_mut())
+                .chain(class.attr_excludes.iter_mut())
+                .chain(class.requires_one_of.iter_mut().flatten())
+                .filter(|attr| *attr == from)
+            {
+                *attr = to.clone();
+                changed = true;
+            }
+            if changed {
+                classes.push(class.name.clone());
+            }
+        }
+        classes.sort_unstable();
+
+        info!(%from, %to, ?classes, "renamed attribute");
+
+        Ok(AttrRenamePlan {
+            from: from.clone(),
+            to,
+            classes,
+        })
+    }
+
+    fn check_attribute_update(&self, a: &SchemaAttribute) -> Result<(), OperationError> {
+        if !a.system && RESERVED_ATTRIBUTE_NAMES.contains(&a.name) {
+            error!(name = %a.name, uuid = ?a.uuid, "attempt to redefine reserved attribute");
+            return Err(OperationError::InvalidSchemaState(format!(
+                "attribute {} is reserved and may only be defined by the system",
+                a.name
+            )));
+        }
+
+        // Existing values must remain valid if an attribute's syntax has been changed.
+        if let Some(prev) = self
+            .attributes
+            .get(&a.name)
+            .filter(|prev| !prev.syntax.can_migrate_to(a.syntax))
+        {
+            error!(
+                name = %a.name,
+                from = %prev.syntax,
+                to = %a.syntax,
+                "refusing unsafe attribute syntax migration"
+            );
+            return Err(OperationError::InvalidSchemaState(format!(
+                "attribute {} can not migrate from syntax {} to {}",
+                a.name, prev.syntax, a.syntax
+            )));
+        }
+
+        if a.allowed_values.is_some() && !a.syntax.is_string() {
+            error!(
+                name = %a.name,
+                syntax = %a.syntax,
+                "allowed values may only be set on string attributes"
+            );
+            return Err(OperationError::InvalidSchemaState(format!(
+                "attribute {} has allowed values but syntax {} is not a string",
+                a.name, a.syntax
+            )));
+        }
+
+        if a.unique_scope != UniqueScope::All && !a.unique {
+            error!(name = %a.name, "a unique scope may only be set on unique attributes");
+            return Err(OperationError::InvalidSchemaState(format!(
+                "attribute {} has a unique scope but is not unique",
+                a.name
+            )));
+        }
+
+        if a.on_delete != ReferenceDeletePolicy::RemoveReference && !a.syntax.is_reference_like() {
+            error!(
+                name = %a.name,
+                syntax = %a.syntax,
+                "a delete policy may only be set on reference attributes"
+            );
+            return Err(OperationError::InvalidSchemaState(format!(
+                "attribute {} has a delete policy but syntax {} is not a reference",
+                a.name, a.syntax
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn insert_attribute(&mut self, mut a: SchemaAttribute) {
+        self.custom_syntax.attach(&mut a);
+        // Update the unique and ref caches.
+        if a.syntax.is_reference_like() {
+            self.ref_cache.insert(a.name.clone(), a.clone());
+        }
+        if a.unique {
+            self.unique_cache
+                .insert(a.name.clone(), a.unique_scope.clone());
+        }
+        if let Some(group) = &a.exclusion_group {
+            self.exclusion_cache
+                .entry(group.clone())
+                .or_default()
+                .push(a.name.clone());
+        }
+        // Finally insert.
+        self.attributes.insert(a.name.clone(), a);
+    }
+
+    fn purge_attribute_caches(&mut self, prev: &SchemaAttribute) {
+        self.ref_cache.remove(&prev.name);
+        self.unique_cache.remove(&prev.name);
+        if let Some(group) = &prev.exclusion_group {
+            if let Some(members) = self.exclusion_cache.get_mut().get_mut(group) {
+                members.retain(|name| name != &prev.name);
+                if members.is_empty() {
+                    self.exclusion_cache.remove(group);
+                }
+            }
+        }
+    }
+
+    pub fn update_classes<I: Iterator<Item = SchemaClass>>(
+        &mut self,
+        classtypes: I,
+    ) -> Result<(), OperationError> {
+        let classtypes: Vec<_> = classtypes.collect();
+
+        classtypes
+            .iter()
+            .try_for_each(|c| self.check_class_update(c))?;
+
+        // purge all old attributes.
+        self.classes.clear();
+        // Update with new ones.
+        // Do we need to check for dups?
+        // No, they'll over-write each other ... but we do need name uniqueness.
+        classtypes.into_iter().for_each(|a| {
+            self.classes.insert(a.name.clone(), a);
+        });
+        Ok(())
+    }
+
+    /// Insert or replace a single class definition.
+    pub fn upsert_class(&mut self, c: SchemaClass) -> Result<(), OperationError> {
+        self.check_class_update(&c)?;
+        self.classes.insert(c.name.clone(), c);
+        Ok(())
+    }
+
+    fn check_class_update(&self, c: &SchemaClass) -> Result<(), OperationError> {
+        if !c.system && RESERVED_CLASS_NAMES.contains(&c.name) {
+            error!(name = %c.name, uuid = ?c.uuid, "attempt to redefine reserved class");
+            return Err(OperationError::InvalidSchemaState(format!(
+                "class {} is reserved and may only be defined by the system",
+                c.name
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn to_entries(&self) -> Vec<Entry<EntryInit, EntryNew>> {
+        let r: Vec<_> = self
+            .attributes
+            .values()
+            .map(Entry::<EntryInit, EntryNew>::from)
+            .chain(
+                self.classes
+                    .values()
+                    .map(Entry::<EntryInit, EntryNew>::from),
+            )
+            .collect();
+        r
+    }
+
+    /// As [Self::to_entries], but with attributes and then classes sorted by name so
+    /// that repeated dumps of the same schema are identical.
+    pub fn to_entries_sorted(&self) -> Vec<Entry<EntryInit, EntryNew>> {
+        self.attributes_sorted()
+            .into_iter()
+            .map(|(_, a)| Entry::<EntryInit, EntryNew>::from(a))
+            .chain(
+                self.classes_sorted()
+                    .into_iter()
+                    .map(|(_, c)| Entry::<EntryInit, EntryNew>::from(c)),
+            )
+            .collect()
+    }
+
+    /// As [Self::to_entries_sorted], but only the definitions that were added or modified
+    /// compared to `against`, which is usually a snapshot of the schema from before a
+    /// reload. Unchanged definitions don't need to be written again.
+    pub fn to_changed_entries(
+        &self,
+        against: &SchemaReadTransaction,
+    ) -> Vec<Entry<EntryInit, EntryNew>> {
+        let prior_attributes = against.get_attributes();
+        let prior_classes = against.get_classes();
+
+        let changed_attributes = self
+            .attributes_sorted()
+            .into_iter()
+            .filter_map(|(name, a)| {
+                let entry = Entry::<EntryInit, EntryNew>::from(a);
+                match prior_attributes.get(name) {
+                    Some(prior) if !EntryInitNew::from(prior).diff(&entry).is_changed() => None,
+                    _ => Some(entry),
+                }
+            });
+
+        let changed_classes = self.classes_sorted().into_iter().filter_map(|(name, c)| {
+            let entry = Entry::<EntryInit, EntryNew>::from(c);
+            match prior_classes.get(name) {
+                Some(prior) if !EntryInitNew::from(prior).diff(&entry).is_changed() => None,
+                _ => Some(entry),
+            }
+        });
+
+        changed_attributes.chain(changed_classes).collect()
+    }
+
+    pub fn reload_idxmeta(&self) -> Vec<IdxKey> {
         self.get_attributes()
             .values()
             .flat_map(|a| {
-                // Unique values must be indexed
-                if a.indexed || a.unique {
-                    a.syntax.index_types()
-                } else {
-                    &[]
-                }
-                .iter()
-                .map(move |itype: &IndexType| IdxKey {
-                    attr: a.name.clone(),
-                    itype: *itype,
-                })
+                a.effective_index_types()
+                    .iter()
+                    .map(move |itype: &IndexType| IdxKey {
+                        attr: a.name.clone(),
+                        itype: *itype,
+                    })
             })
             .collect()
     }
 
+    /// Validate the schema, failing only if an error level issue is found.
+    fn check_validation(&self) -> Result<(), OperationError> {
+        let (errors, warnings): (Vec<_>, Vec<_>) = self
+            .validate_detailed()
+            .into_iter()
+            .partition(SchemaValidationIssue::is_error);
+
+        if !warnings.is_empty() {
+            warn!(warn = ?warnings, "schema validate -> warnings");
+        }
+
+        if errors.is_empty() {
+            debug!("schema validate -> passed");
+            Ok(())
+        } else {
+            error!(err = ?errors, "schema validate -> errors");
+            Err(OperationError::ConsistencyError(
+                errors.into_iter().map(|issue| issue.error).collect(),
+            ))
+        }
+    }
+
     /// Generate the minimal in memory schema needed to begin the server bootstrap
     /// process. This should contain the most critical schema definitions that the
     /// server requires to be able to read in other schema objects and persist them
@@ -800,16 +2286,7 @@ impl SchemaWriteTransaction<'_> {
         self.update_attributes(migration_data::system::attributes().into_iter())?;
         self.update_classes(migration_data::system::classes().into_iter())?;
 
-        let r = self.validate();
-        if r.is_empty() {
-            debug!("schema validate -> passed");
-            Ok(())
-        } else {
-            error!(err = ?r, "schema validate -> errors");
-            Err(OperationError::ConsistencyError(
-                r.into_iter().filter_map(|v| v.err()).collect(),
-            ))
-        }
+        self.check_validation()
     }
 
     #[instrument(level = "debug", name = "schema::extend_in_memory", skip_all)]
@@ -829,21 +2306,12 @@ impl SchemaWriteTransaction<'_> {
                 .chain(extra_classes.into_iter()),
         )?;
 
-        let r = self.validate();
-        if r.is_empty() {
-            debug!("schema validate -> passed");
-            Ok(())
-        } else {
-            error!(err = ?r, "schema validate -> errors");
-            Err(OperationError::ConsistencyError(
-                r.into_iter().filter_map(|v| v.err()).collect(),
-            ))
-        }
+        self.check_validation()
     }
 }
 
 impl SchemaTransaction for SchemaWriteTransaction<'_> {
-    fn get_attributes_unique(&self) -> &Vec<Attribute> {
+    fn get_attributes_unique(&self) -> &HashMap<Attribute, UniqueScope> {
         &self.unique_cache
     }
 
@@ -851,6 +2319,10 @@ impl SchemaTransaction for SchemaWriteTransaction<'_> {
         &self.ref_cache
     }
 
+    fn get_exclusion_groups(&self) -> &HashMap<AttrString, Vec<Attribute>> {
+        &self.exclusion_cache
+    }
+
     fn get_classes(&self) -> &HashMap<AttrString, SchemaClass> {
         &self.classes
     }
@@ -860,8 +2332,83 @@ impl SchemaTransaction for SchemaWriteTransaction<'_> {
     }
 }
 
+impl SchemaReadTransaction {
+    /// The generation of the schema in this snapshot. This changes every time the schema
+    /// is committed, so clients can use it to know when to refetch schema derived data.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Describe every value syntax this server supports. This is independent of the
+    /// attributes currently defined, so that clients can generate stable descriptors.
+    pub fn syntax_catalog(&self) -> Vec<SyntaxInfo> {
+        SyntaxType::all()
+            .map(|syntax| SyntaxInfo {
+                name: syntax.to_string(),
+                id: syntax as u16,
+                index_types: syntax
+                    .index_types()
+                    .iter()
+                    .map(|idx| idx.to_string())
+                    .collect(),
+                orderable: syntax.is_orderable(),
+                secret: syntax.is_secret(),
+                multivalue: syntax.is_multivalue_capable(),
+            })
+            .collect()
+    }
+
+    /// Describe the attributes and classes of this schema as a stable JSON document for
+    /// integrators. Unlike [SchemaWriteTransaction::to_entries] this doesn't expose the
+    /// internal entry form, and system and local definitions of a class are combined.
+    pub fn to_json_schema(&self) -> serde_json::Value {
+        fn names<'a, T: AsRef<str> + 'a>(
+            lists: impl IntoIterator<Item = &'a Vec<T>>,
+        ) -> Vec<&'a str> {
+            let names: BTreeSet<&str> = lists.into_iter().flatten().map(|n| n.as_ref()).collect();
+            names.into_iter().collect()
+        }
+
+        let attributes: Vec<_> = self
+            .attributes_sorted()
+            .into_iter()
+            .map(|(name, attr)| {
+                serde_json::json!({
+                    "name": name.as_str(),
+                    "syntax": attr.syntax.to_string(),
+                    "multivalue": attr.multivalue,
+                    "unique": attr.unique,
+                    "indexed": attr.indexed,
+                    "description": attr.description,
+                })
+            })
+            .collect();
+
+        let classes: Vec<_> = self
+            .classes_sorted()
+            .into_iter()
+            .map(|(name, class)| {
+                serde_json::json!({
+                    "name": name.as_str(),
+                    "must": names([&class.systemmust, &class.must]),
+                    "may": names([&class.systemmay, &class.may]),
+                    "supplements": names([&class.systemsupplements, &class.supplements]),
+                    "excludes": names([&class.systemexcludes, &class.excludes]),
+                    "description": class.description,
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "version": 1,
+            "attributes": attributes,
+            "classes": classes,
+        })
+    }
+}
+
 impl SchemaTransaction for SchemaReadTransaction {
-    fn get_attributes_unique(&self) -> &Vec<Attribute> {
+    fn get_attributes_unique(&self) -> &HashMap<Attribute, UniqueScope> {
         &self.unique_cache
     }
 
@@ -869,6 +2416,10 @@ impl SchemaTransaction for SchemaReadTransaction {
         &self.ref_cache
     }
 
+    fn get_exclusion_groups(&self) -> &HashMap<AttrString, Vec<Attribute>> {
+        &self.exclusion_cache
+    }
+
     fn get_classes(&self) -> &HashMap<AttrString, SchemaClass> {
         &self.classes
     }
@@ -883,8 +2434,12 @@ impl Schema {
         let s = Schema {
             classes: CowCell::new(HashMap::with_capacity(128)),
             attributes: CowCell::new(HashMap::with_capacity(128)),
-            unique_cache: CowCell::new(Vec::with_capacity(0)),
+            unique_cache: CowCell::new(HashMap::new()),
             ref_cache: CowCell::new(HashMap::with_capacity(64)),
+            exclusion_cache: CowCell::new(HashMap::new()),
+            generation: AtomicU64::new(0),
+            reload_mode: SchemaReloadMode::default(),
+            custom_syntax: Arc::default(),
         };
         let mut sw = s.write();
         let r1 = sw.generate_in_memory();
@@ -896,11 +2451,16 @@ impl Schema {
     }
 
     pub fn read(&self) -> SchemaReadTransaction {
+        // Load the generation before the snapshot, so that it can never be ahead of
+        // the schema we are reading.
+        let generation = self.generation.load(AtomicOrdering::Acquire);
         SchemaReadTransaction {
             classes: self.classes.read(),
             attributes: self.attributes.read(),
             unique_cache: self.unique_cache.read(),
             ref_cache: self.ref_cache.read(),
+            exclusion_cache: self.exclusion_cache.read(),
+            generation,
         }
     }
 
@@ -910,7 +2470,69 @@ impl Schema {
             attributes: self.attributes.write(),
             unique_cache: self.unique_cache.write(),
             ref_cache: self.ref_cache.write(),
+            exclusion_cache: self.exclusion_cache.write(),
+            generation: &self.generation,
+            reload_mode: self.reload_mode,
+            custom_syntax: self.custom_syntax.clone(),
+        }
+    }
+
+    /// Set how malformed definitions are handled when the schema is reloaded from the
+    /// database.
+    pub fn with_reload_mode(mut self, reload_mode: SchemaReloadMode) -> Self {
+        self.reload_mode = reload_mode;
+        self
+    }
+
+    /// Set the validators of custom syntaxes. Values of a custom syntax attribute without a
+    /// validator are rejected, so this must be done before the schema is given to the server.
+    pub fn with_custom_syntax_validators(mut self, registry: CustomSyntaxRegistry) -> Self {
+        self.custom_syntax = Arc::new(registry);
+        // Attributes that are already loaded need their validators attached too.
+        let mut attributes = self.attributes.write();
+        attributes
+            .values_mut()
+            .for_each(|a| self.custom_syntax.attach(a));
+        attributes.commit();
+        self
+    }
+
+    /// Preview upserting `attributes` and `classes` into the current schema, returning
+    /// every issue that would be found, including updates that would be refused outright.
+    /// The changes are made in a write transaction that is dropped without being
+    /// committed, so the live schema is never modified.
+    pub fn try_apply(
+        &self,
+        attributes: Vec<SchemaAttribute>,
+        classes: Vec<SchemaClass>,
+    ) -> Vec<SchemaValidationIssue> {
+        let mut txn = self.write();
+        let mut issues = Vec::with_capacity(0);
+
+        let rejected = |err: OperationError| {
+            let reason = match err {
+                OperationError::InvalidSchemaState(reason) => reason,
+                err => format!("{err:?}"),
+            };
+            SchemaValidationIssue::error(ConsistencyError::SchemaUpdateRejected(reason))
+        };
+
+        for a in attributes {
+            if let Err(err) = txn.upsert_attribute(a) {
+                issues.push(rejected(err));
+            }
+        }
+
+        for c in classes {
+            if let Err(err) = txn.upsert_class(c) {
+                issues.push(rejected(err));
+            }
         }
+
+        issues.extend(txn.validate_detailed());
+        // Dropping the transaction discards the changes.
+        drop(txn);
+        issues
     }
 
     #[cfg(test)]
@@ -921,8 +2543,19 @@ impl Schema {
 
 #[cfg(test)]
 mod tests {
+    use crate::be::IdxKey;
+    use crate::filter::FilterOpKind;
     use crate::prelude::*;
-    use crate::schema::{Schema, SchemaAttribute, SchemaClass, SchemaTransaction, SyntaxType};
+    use crate::schema::{
+        set_schema_validation_metrics, ClassKind, CustomSyntaxRegistry, Schema, SchemaAttribute,
+        SchemaClass, SchemaReloadMode, SchemaTransaction, SchemaValidationCount,
+        SchemaValidationCounter, SchemaValidationIssue, Severity, SyntaxType, UniqueScope,
+    };
+    use crate::value::OauthClaimMapJoin;
+    use crate::valueset::ValueSetDateTime;
+    use std::collections::BTreeSet;
+    use std::sync::Arc;
+    use time::OffsetDateTime;
     use uuid::Uuid;
 
     // use crate::proto_v1::Filter as ProtoFilter;
@@ -1227,13 +2860,11 @@ mod tests {
         // Test schemaAttribute validation of types.
 
         // Test single value string
-        let single_value_string = SchemaAttribute {
-            name: Attribute::from("single_value"),
-            uuid: Uuid::new_v4(),
-            description: String::from(""),
-            syntax: SyntaxType::Utf8StringInsensitive,
-            ..Default::default()
-        };
+        let single_value_string = SchemaAttribute::builder(
+            Attribute::from("single_value"),
+            SyntaxType::Utf8StringInsensitive,
+        )
+        .build();
 
         let r1 = single_value_string
             .validate_ava(&Attribute::from("single_value"), &(vs_iutf8!["test"] as _));
@@ -1250,27 +2881,19 @@ mod tests {
 
         // test multivalue string, boolean
 
-        let multi_value_string = SchemaAttribute {
-            name: Attribute::from("mv_string"),
-            uuid: Uuid::new_v4(),
-            description: String::from(""),
-            multivalue: true,
-            syntax: SyntaxType::Utf8String,
-            ..Default::default()
-        };
+        let multi_value_string =
+            SchemaAttribute::builder(Attribute::from("mv_string"), SyntaxType::Utf8String)
+                .multivalue(true)
+                .build();
 
         let rvs = vs_utf8!["test1".to_string(), "test2".to_string()] as _;
         let r5 = multi_value_string.validate_ava(&Attribute::from("mv_string"), &rvs);
         assert_eq!(r5, Ok(()));
 
-        let multi_value_boolean = SchemaAttribute {
-            name: Attribute::from("mv_bool"),
-            uuid: Uuid::new_v4(),
-            description: String::from(""),
-            multivalue: true,
-            syntax: SyntaxType::Boolean,
-            ..Default::default()
-        };
+        let multi_value_boolean =
+            SchemaAttribute::builder(Attribute::from("mv_bool"), SyntaxType::Boolean)
+                .multivalue(true)
+                .build();
 
         // Since valueset now disallows such shenanigans at a type level, this can't occur
         /*
@@ -1293,13 +2916,8 @@ mod tests {
         assert_eq!(r4, Ok(()));
 
         // syntax_id and index_type values
-        let single_value_syntax = SchemaAttribute {
-            name: Attribute::from("sv_syntax"),
-            uuid: Uuid::new_v4(),
-            description: String::from(""),
-            syntax: SyntaxType::SyntaxId,
-            ..Default::default()
-        };
+        let single_value_syntax =
+            SchemaAttribute::builder(Attribute::from("sv_syntax"), SyntaxType::SyntaxId).build();
 
         let rvs = vs_syntax![SyntaxType::try_from("UTF8STRING").unwrap()] as _;
         let r6 = single_value_syntax.validate_ava(&Attribute::from("sv_syntax"), &rvs);
@@ -1312,13 +2930,8 @@ mod tests {
             Err(SchemaError::InvalidAttributeSyntax("sv_syntax".to_string()))
         );
 
-        let single_value_index = SchemaAttribute {
-            name: Attribute::from("sv_index"),
-            uuid: Uuid::new_v4(),
-            description: String::from(""),
-            syntax: SyntaxType::IndexId,
-            ..Default::default()
-        };
+        let single_value_index =
+            SchemaAttribute::builder(Attribute::from("sv_index"), SyntaxType::IndexId).build();
 
         let rvs = vs_utf8!["thaeountaheu".to_string()] as _;
         let r9 = single_value_index.validate_ava(&Attribute::from("sv_index"), &rvs);
@@ -1326,22 +2939,378 @@ mod tests {
             r9,
             Err(SchemaError::InvalidAttributeSyntax("sv_index".to_string()))
         );
+
+        // An empty set is trivially valid, regardless of syntax.
+        let mut rvs = vs_utf8!["thaeountaheu".to_string()] as ValueSet;
+        rvs.clear();
+        let r10 = single_value_index.validate_ava(&Attribute::from("sv_index"), &rvs);
+        assert_eq!(r10, Ok(()));
     }
 
     #[test]
-    fn test_schema_simple() {
-        let schema = Schema::new().expect("failed to create schema");
-        let schema_ro = schema.read();
-        validate_schema!(schema_ro);
+    fn test_schema_builders() {
+        let attr = SchemaAttribute::builder(Attribute::from("built"), SyntaxType::Utf8String)
+            .multivalue(true)
+            .unique(true)
+            .indexed(true)
+            .build();
+
+        assert!(!attr.uuid.is_nil());
+        assert!(attr.multivalue && attr.unique && attr.indexed);
+        assert!(!attr.phantom);
+
+        let class_uuid = Uuid::new_v4();
+        let class = SchemaClass::builder("built")
+            .uuid(class_uuid)
+            .may(vec![Attribute::from("built")])
+            .build();
+
+        assert_eq!(class.uuid, class_uuid);
+        assert_eq!(class.may, vec![Attribute::from("built")]);
     }
 
     #[test]
-    fn test_schema_entries() {
-        sketching::test_init();
-        // Given an entry, assert it's schema is valid
-        // We do
+    fn test_schema_reserved_names() {
         let schema_outer = Schema::new().expect("failed to create schema");
-        let schema = schema_outer.read();
+        let mut schema = schema_outer.write_blocking();
+
+        // A user supplied definition may not clobber a core attribute.
+        let attr_name = SchemaAttribute::builder(Attribute::Name, SyntaxType::Utf8String)
+            .description("not really a name")
+            .build();
+
+        let attrs = crate::migration_data::system::attributes()
+            .into_iter()
+            .filter(|a| a.name != Attribute::Name)
+            .chain(std::iter::once(attr_name));
+
+        assert!(matches!(
+            schema.update_attributes(attrs),
+            Err(OperationError::InvalidSchemaState(_))
+        ));
+
+        // The rejection must leave the existing definition in place.
+        let name = schema
+            .get_attributes()
+            .get(&Attribute::Name)
+            .expect("name was removed");
+        assert_eq!(name.syntax, SyntaxType::Utf8StringIname);
+
+        // The same applies to classes.
+        let class_object = SchemaClass::builder(EntryClass::Object)
+            .description("not really an object")
+            .build();
+
+        assert!(matches!(
+            schema.update_classes(std::iter::once(class_object)),
+            Err(OperationError::InvalidSchemaState(_))
+        ));
+        assert!(schema
+            .get_classes()
+            .contains_key(EntryClass::Object.as_ref()));
+
+        // System definitions may still be reloaded.
+        let class_object = SchemaClass::builder(EntryClass::Object)
+            .description("object")
+            .system(true)
+            .build();
+
+        assert!(schema.update_classes(std::iter::once(class_object)).is_ok());
+        assert!(schema
+            .update_attributes(crate::migration_data::system::attributes().into_iter())
+            .is_ok());
+    }
+
+    #[test]
+    fn test_schema_syntax_migration() {
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let mut schema = schema_outer.write_blocking();
+
+        let with_test_attr = |syntax| {
+            crate::migration_data::system::attributes()
+                .into_iter()
+                .chain(std::iter::once(
+                    SchemaAttribute::builder(Attribute::from("test_migrate"), syntax)
+                        .description("test attribute")
+                        .build(),
+                ))
+        };
+
+        assert!(schema
+            .update_attributes(with_test_attr(SyntaxType::Utf8StringInsensitive))
+            .is_ok());
+
+        // A safe transition is accepted.
+        assert!(schema
+            .update_attributes(with_test_attr(SyntaxType::Utf8StringIname))
+            .is_ok());
+
+        // An unsafe one is refused, and the existing definition remains.
+        assert!(matches!(
+            schema.update_attributes(with_test_attr(SyntaxType::Uuid)),
+            Err(OperationError::InvalidSchemaState(_))
+        ));
+        let test_attr = schema
+            .get_attributes()
+            .get(&Attribute::from("test_migrate"))
+            .expect("test_migrate was removed");
+        assert_eq!(test_attr.syntax, SyntaxType::Utf8StringIname);
+    }
+
+    #[tokio::test]
+    async fn test_schema_read_txn_clone() {
+        let schema = Schema::new().expect("failed to create schema");
+        let schema_ro = schema.read();
+        let schema_ro_clone = schema_ro.clone();
+
+        // A write committed after the clone must not be visible to either txn.
+        let mut schema_wr = schema.write_blocking();
+        assert!(schema_wr.update_classes(std::iter::empty()).is_ok());
+        assert!(schema_wr.commit().is_ok());
+
+        let class_count = schema_ro.get_classes().len();
+        assert!(class_count > 0);
+
+        let task_class_count = tokio::spawn(async move {
+            assert!(schema_ro_clone
+                .get_attributes()
+                .contains_key(&Attribute::Class));
+            schema_ro_clone.get_classes().len()
+        })
+        .await
+        .expect("task failed");
+
+        assert_eq!(class_count, task_class_count);
+        assert!(schema.read().get_classes().is_empty());
+    }
+
+    #[test]
+    fn test_schema_sorted_order() {
+        let schema = Schema::new().expect("failed to create schema");
+        let schema_wr = schema.write_blocking();
+
+        let attributes = schema_wr.attributes_sorted();
+        assert_eq!(attributes.len(), schema_wr.get_attributes().len());
+        assert!(attributes
+            .windows(2)
+            .all(|w| w[0].0.as_str() < w[1].0.as_str()));
+
+        let classes = schema_wr.classes_sorted();
+        assert_eq!(classes.len(), schema_wr.get_classes().len());
+        assert!(classes.windows(2).all(|w| w[0].0 < w[1].0));
+
+        // Dumps follow the sorted order, and are identical between calls.
+        let expect: Vec<Uuid> = attributes
+            .iter()
+            .map(|(_, a)| a.uuid)
+            .chain(classes.iter().map(|(_, c)| c.uuid))
+            .collect();
+        let dump_uuids = |entries: Vec<Entry<EntryInit, EntryNew>>| -> Vec<Uuid> {
+            entries
+                .iter()
+                .filter_map(|e| e.get_ava_single_uuid(Attribute::Uuid))
+                .collect()
+        };
+        assert_eq!(dump_uuids(schema_wr.to_entries_sorted()), expect);
+        assert_eq!(dump_uuids(schema_wr.to_entries_sorted()), expect);
+    }
+
+    #[test]
+    fn test_schema_generation() {
+        let schema = Schema::new().expect("failed to create schema");
+        let snapshot = schema.read();
+        let generation = snapshot.generation();
+
+        // A write that is not committed leaves the generation as is.
+        {
+            let mut schema_wr = schema.write_blocking();
+            assert!(schema_wr.generate_in_memory().is_ok());
+        }
+        assert_eq!(schema.read().generation(), generation);
+
+        let mut schema_wr = schema.write_blocking();
+        assert!(schema_wr.generate_in_memory().is_ok());
+        assert!(schema_wr.commit().is_ok());
+
+        assert_eq!(schema.read().generation(), generation + 1);
+        // Existing snapshots are unchanged.
+        assert_eq!(snapshot.generation(), generation);
+    }
+
+    #[test]
+    fn test_schema_to_changed_entries() {
+        let schema = Schema::new().expect("failed to create schema");
+        let snapshot = schema.read();
+        let mut schema_wr = schema.write_blocking();
+
+        // Nothing has changed yet.
+        assert!(schema_wr.to_changed_entries(&snapshot).is_empty());
+
+        // Add one attribute, and modify the description of another.
+        let test_attr =
+            SchemaAttribute::builder(Attribute::from("test_changed"), SyntaxType::Utf8String)
+                .description("test attribute")
+                .build();
+        let test_uuid = test_attr.uuid;
+
+        let attrs = crate::migration_data::system::attributes()
+            .into_iter()
+            .map(|mut a| {
+                if a.name == Attribute::Description {
+                    a.description = "modified description".to_string();
+                }
+                a
+            })
+            .chain(std::iter::once(test_attr));
+        assert!(schema_wr.update_attributes(attrs).is_ok());
+
+        let description_uuid = schema_wr
+            .get_attributes()
+            .get(&Attribute::Description)
+            .map(|a| a.uuid)
+            .expect("description missing");
+
+        let mut changed: Vec<Uuid> = schema_wr
+            .to_changed_entries(&snapshot)
+            .iter()
+            .filter_map(|e| e.get_ava_single_uuid(Attribute::Uuid))
+            .collect();
+        changed.sort_unstable();
+        let mut expect = vec![test_uuid, description_uuid];
+        expect.sort_unstable();
+        assert_eq!(changed, expect);
+    }
+
+    #[test]
+    fn test_schema_syntax_catalog() {
+        let schema = Schema::new().expect("failed to create schema");
+        let schema_ro = schema.read();
+
+        let catalog = schema_ro.syntax_catalog();
+
+        // Every syntax is described in identifier order, and the name can be parsed back
+        // to the same syntax.
+        assert!(catalog.iter().enumerate().all(|(i, info)| {
+            info.id as usize == i
+                && SyntaxType::try_from(info.name.as_str()).map(|syntax| syntax as u16)
+                    == Ok(info.id)
+        }));
+        assert!(catalog
+            .iter()
+            .any(|info| info.id == SyntaxType::Uint64 as u16));
+
+        let uint32 = &catalog[SyntaxType::Uint32 as usize];
+        assert_eq!(uint32.name, "UINT32");
+        assert!(uint32.orderable);
+        assert!(!uint32.secret);
+        assert!(uint32.index_types.contains(&"ORDERING".to_string()));
+
+        let secret = &catalog[SyntaxType::SecretUtf8String as usize];
+        assert!(secret.secret);
+        assert!(!secret.orderable);
+        assert!(secret.index_types.is_empty());
+
+        assert!(!catalog[SyntaxType::Json as usize].multivalue);
+        assert!(catalog[SyntaxType::ReferenceUuid as usize].multivalue);
+    }
+
+    #[test]
+    fn test_schema_attribute_datetime_utc() {
+        let expiry = SchemaAttribute {
+            name: Attribute::AccountExpire,
+            uuid: Uuid::new_v4(),
+            description: String::from(""),
+            multivalue: false,
+            syntax: SyntaxType::DateTime,
+            ..Default::default()
+        };
+        let attr = Attribute::AccountExpire;
+        let err = Err(SchemaError::InvalidAttributeSyntax(attr.to_string()));
+
+        let utc = OffsetDateTime::parse("2020-09-25T01:22:02Z", &Rfc3339).expect("Must be valid");
+        assert_eq!(expiry.validate_value(&attr, &Value::DateTime(utc)), Ok(()));
+        assert_eq!(
+            expiry.validate_ava(&attr, &(ValueSetDateTime::new(utc) as _)),
+            Ok(())
+        );
+
+        // The same instant with a local offset is rejected if it bypasses normalisation.
+        let local =
+            OffsetDateTime::parse("2020-09-25T11:22:02+10:00", &Rfc3339).expect("Must be valid");
+        assert_eq!(local, utc);
+        assert_eq!(expiry.validate_value(&attr, &Value::DateTime(local)), err);
+        // But is converted to UTC when inserted through the constructors.
+        assert_eq!(
+            expiry.validate_value(&attr, &Value::new_datetime(local)),
+            Ok(())
+        );
+        assert_eq!(
+            expiry.validate_ava(&attr, &(ValueSetDateTime::new(local) as _)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_schema_attribute_oauth_claim_map() {
+        let claim_map = SchemaAttribute {
+            name: Attribute::OAuth2RsClaimMap,
+            uuid: Uuid::new_v4(),
+            description: String::from(""),
+            multivalue: true,
+            syntax: SyntaxType::OauthClaimMap,
+            ..Default::default()
+        };
+        let attr = Attribute::OAuth2RsClaimMap;
+        let err = Err(SchemaError::InvalidAttributeSyntax(attr.to_string()));
+        let group_uuid = Uuid::new_v4();
+
+        let claims = |values: &[&str]| values.iter().map(|s| s.to_string()).collect();
+
+        let v = Value::OauthClaimValue("claim-name".to_string(), group_uuid, claims(&["a:b"]));
+        assert_eq!(claim_map.validate_value(&attr, &v), Ok(()));
+
+        let v = Value::OauthClaimMap("claim-name".to_string(), OauthClaimMapJoin::JsonArray);
+        assert_eq!(claim_map.validate_value(&attr, &v), Ok(()));
+
+        // Empty and malformed claim names are rejected.
+        let v = Value::OauthClaimValue(String::new(), group_uuid, claims(&["a"]));
+        assert_eq!(claim_map.validate_value(&attr, &v), err);
+
+        let v = Value::OauthClaimValue("claim name".to_string(), group_uuid, claims(&["a"]));
+        assert_eq!(claim_map.validate_value(&attr, &v), err);
+
+        let v = Value::OauthClaimMap("-claim".to_string(), OauthClaimMapJoin::JsonArray);
+        assert_eq!(claim_map.validate_value(&attr, &v), err);
+
+        // The group must be a real uuid, and at least one valid claim value is required.
+        let v = Value::OauthClaimValue("claim".to_string(), Uuid::nil(), claims(&["a"]));
+        assert_eq!(claim_map.validate_value(&attr, &v), err);
+
+        let v = Value::OauthClaimValue("claim".to_string(), group_uuid, claims(&[]));
+        assert_eq!(claim_map.validate_value(&attr, &v), err);
+
+        let v = Value::OauthClaimValue("claim".to_string(), group_uuid, claims(&["a b"]));
+        assert_eq!(claim_map.validate_value(&attr, &v), err);
+
+        // Other value types are not a claim map.
+        let v = Value::new_iutf8("claim");
+        assert_eq!(claim_map.validate_value(&attr, &v), err);
+    }
+
+    #[test]
+    fn test_schema_simple() {
+        let schema = Schema::new().expect("failed to create schema");
+        let schema_ro = schema.read();
+        validate_schema!(schema_ro);
+    }
+
+    #[test]
+    fn test_schema_entries() {
+        sketching::test_init();
+        // Given an entry, assert it's schema is valid
+        // We do
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let schema = schema_outer.read();
 
         let e_no_uuid = entry_init!().into_invalid_new();
 
@@ -1600,9 +3569,239 @@ mod tests {
 
         assert!(schema.update_classes(std::iter::once(class)).is_ok());
 
+        // The phantom can never be stored, so this is only a warning.
+        assert!(schema.validate().is_empty());
+        assert_eq!(
+            schema
+                .validate_detailed()
+                .into_iter()
+                .filter(|issue| matches!(
+                    issue.error,
+                    ConsistencyError::SchemaClassPhantomAttribute(..)
+                ))
+                .map(|issue| issue.severity)
+                .collect::<Vec<_>>(),
+            vec![Severity::Warning]
+        );
+    }
+
+    #[test]
+    fn test_schema_try_apply() {
+        let schema = Schema::new().expect("failed to create schema");
+
+        // A valid attribute and class produce no errors.
+        let attr = SchemaAttribute::builder(Attribute::from("testpreview"), SyntaxType::Utf8String)
+            .build();
+        let class = SchemaClass::builder("testpreviewobject")
+            .may(vec![Attribute::from("testpreview")])
+            .build();
+        assert!(!schema
+            .try_apply(vec![attr], vec![class])
+            .iter()
+            .any(SchemaValidationIssue::is_error));
+
+        // A class referencing an attribute that doesn't exist is reported, as is the
+        // redefinition of a reserved class.
+        let bad_class = SchemaClass::builder("testpreviewbad")
+            .may(vec![Attribute::from("testmissing")])
+            .build();
+        let reserved_class = SchemaClass::builder(EntryClass::Object).build();
+
+        let issues = schema.try_apply(Vec::with_capacity(0), vec![bad_class, reserved_class]);
+        assert!(issues.contains(&SchemaValidationIssue {
+            severity: Severity::Error,
+            error: ConsistencyError::SchemaClassMissingAttribute(
+                "testpreviewbad".to_string(),
+                "testmissing".to_string()
+            ),
+        }));
+        assert!(issues.iter().any(|issue| issue.is_error()
+            && matches!(issue.error, ConsistencyError::SchemaUpdateRejected(_))));
+
+        // Nothing was committed to the live schema.
+        let schema_ro = schema.read();
+        assert!(schema_ro.validate().is_empty());
+        assert!(!schema_ro.get_classes().contains_key("testpreviewobject"));
+        assert!(!schema_ro.get_classes().contains_key("testpreviewbad"));
+        assert!(!schema_ro
+            .get_attributes()
+            .contains_key(&Attribute::from("testpreview")));
+    }
+
+    #[test]
+    fn test_schema_validate_severity() {
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let mut schema = schema_outer.write_blocking();
+
+        assert!(!schema
+            .validate_detailed()
+            .iter()
+            .any(SchemaValidationIssue::is_error));
+
+        // A unique multivalue attribute is allowed, but warned about.
+        let attr =
+            SchemaAttribute::builder(Attribute::from("testuniquemv"), SyntaxType::Utf8String)
+                .multivalue(true)
+                .unique(true)
+                .build();
+
+        assert!(schema
+            .extend_in_memory(vec![attr.clone()], Vec::with_capacity(0))
+            .is_ok());
+        assert!(schema.validate_detailed().contains(&SchemaValidationIssue {
+            severity: Severity::Warning,
+            error: ConsistencyError::SchemaAttributeUniqueMultivalue("testuniquemv".to_string()),
+        }));
+
+        // A class referencing an attribute that doesn't exist is fatal.
+        let class = SchemaClass::builder("testobject")
+            .may(vec![Attribute::from("testmissing")])
+            .build();
+
+        assert!(matches!(
+            schema.extend_in_memory(vec![attr], vec![class]),
+            Err(OperationError::ConsistencyError(errs))
+                if errs == vec![ConsistencyError::SchemaClassMissingAttribute(
+                    "testobject".to_string(),
+                    "testmissing".to_string()
+                )]
+        ));
         assert_eq!(schema.validate().len(), 1);
     }
 
+    #[test]
+    fn test_schema_validate_indexed_no_index_types() {
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let mut schema = schema_outer.write_blocking();
+
+        let indexed = SchemaAttribute::builder(Attribute::from("testindexedurl"), SyntaxType::Url)
+            .indexed(true)
+            .build();
+        let unindexed =
+            SchemaAttribute::builder(Attribute::from("testunindexedurl"), SyntaxType::Url).build();
+
+        assert!(indexed.effective_index_types().is_empty());
+
+        // This is only a warning, so extending the schema still succeeds.
+        assert!(schema
+            .extend_in_memory(vec![indexed, unindexed], Vec::with_capacity(0))
+            .is_ok());
+
+        let issues = schema.validate_detailed();
+        assert!(issues.contains(&SchemaValidationIssue {
+            severity: Severity::Warning,
+            error: ConsistencyError::SchemaIndexedButNoIndexTypes("testindexedurl".to_string()),
+        }));
+        assert!(!issues.iter().any(|issue| issue.error
+            == ConsistencyError::SchemaIndexedButNoIndexTypes("testunindexedurl".to_string())));
+        assert!(!issues.iter().any(SchemaValidationIssue::is_error));
+    }
+
+    #[test]
+    fn test_schema_ldap_phantoms() {
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let mut schema = schema_outer.write_blocking();
+
+        assert!(schema.validate().is_empty());
+
+        // Redefining an LDAP phantom as a real attribute must be refused.
+        let attr = SchemaAttribute::builder(Attribute::Dn, SyntaxType::Utf8StringInsensitive)
+            .uuid(UUID_SCHEMA_ATTR_DN)
+            .system(true)
+            .build();
+
+        assert!(matches!(
+            schema.extend_in_memory(vec![attr], Vec::with_capacity(0)),
+            Err(OperationError::ConsistencyError(errs))
+                if errs == vec![ConsistencyError::SchemaLdapPhantomMissing(
+                    Attribute::Dn.to_string()
+                )]
+        ));
+    }
+
+    #[test]
+    fn test_schema_ldap_visible_attrs() {
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let schema = schema_outer.read();
+
+        let visible: Vec<&Attribute> = schema
+            .ldap_visible_attrs()
+            .into_iter()
+            .map(|a_schema| &a_schema.name)
+            .collect();
+
+        assert!(visible.contains(&&Attribute::Cn));
+        assert!(visible.contains(&&Attribute::Uid));
+        assert!(visible.contains(&&Attribute::Name));
+        // An unmapped phantom is never visible.
+        assert!(!visible.contains(&&Attribute::PasswordImport));
+    }
+
+    #[test]
+    fn test_schema_critical_reference_attrs() {
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let mut schema = schema_outer.write_blocking();
+
+        assert!(schema.validate().is_empty());
+
+        // Removing the index of a reference that access control relies on is refused.
+        let attr = SchemaAttribute::builder(Attribute::EntryManagedBy, SyntaxType::ReferenceUuid)
+            .uuid(UUID_SCHEMA_ATTR_ENTRY_MANAGED_BY)
+            .system(true)
+            .build();
+
+        assert!(matches!(
+            schema.extend_in_memory(vec![attr], Vec::with_capacity(0)),
+            Err(OperationError::ConsistencyError(errs))
+                if errs == vec![ConsistencyError::SchemaCriticalAttributeAltered(
+                    Attribute::EntryManagedBy.to_string()
+                )]
+        ));
+
+        // As is changing its syntax. The syntax change is refused before validation since
+        // existing values can't be migrated, so alter the definition in place to show that
+        // validation refuses it too.
+        let attr = SchemaAttribute::builder(Attribute::Member, SyntaxType::Utf8String)
+            .uuid(UUID_SCHEMA_ATTR_MEMBER)
+            .indexed(true)
+            .build();
+
+        assert!(schema
+            .extend_in_memory(vec![attr], Vec::with_capacity(0))
+            .is_err());
+
+        if let Some(member) = schema.attributes.get_mut().get_mut(&Attribute::Member) {
+            member.syntax = SyntaxType::Utf8String;
+        }
+
+        assert!(schema.validate().contains(&Err(
+            ConsistencyError::SchemaCriticalAttributeAltered(Attribute::Member.to_string())
+        )));
+    }
+
+    #[test]
+    fn test_schema_class_compatible_with() {
+        let class = SchemaClass::builder("test_class")
+            .systemexcludes(vec!["excluded_a".into()])
+            .excludes(vec!["excluded_b".into(), "excluded_c".into()])
+            .build();
+
+        assert_eq!(class.compatible_with(&BTreeSet::new()), Ok(()));
+        assert_eq!(
+            class.compatible_with(&BTreeSet::from(["object", "test_class"])),
+            Ok(())
+        );
+
+        // Every conflict is reported, from both the system and user excludes.
+        assert_eq!(
+            class.compatible_with(&BTreeSet::from(["object", "excluded_a", "excluded_c"])),
+            Err(vec![
+                AttrString::from("excluded_a"),
+                AttrString::from("excluded_c")
+            ])
+        );
+    }
+
     #[test]
     fn test_schema_class_exclusion_requires() {
         sketching::test_init();
@@ -1614,46 +3813,37 @@ mod tests {
 
         // We setup some classes that have requires and excludes and check that they
         // are enforced correctly.
-        let class_account = SchemaClass {
-            name: Attribute::Account.into(),
-            uuid: Uuid::new_v4(),
-            description: String::from("account object"),
-            systemmust: vec![
+        let class_account = SchemaClass::builder(Attribute::Account)
+            .description("account object")
+            .systemmust(vec![
                 Attribute::Class,
                 Attribute::Uuid,
                 Attribute::LastModifiedCid,
                 Attribute::CreatedAtCid,
-            ],
-            systemsupplements: vec![EntryClass::Service.into(), EntryClass::Person.into()],
-            ..Default::default()
-        };
+            ])
+            .systemsupplements(vec![EntryClass::Service.into(), EntryClass::Person.into()])
+            .build();
 
-        let class_person = SchemaClass {
-            name: EntryClass::Person.into(),
-            uuid: Uuid::new_v4(),
-            description: String::from("person object"),
-            systemmust: vec![
+        let class_person = SchemaClass::builder(EntryClass::Person)
+            .description("person object")
+            .systemmust(vec![
                 Attribute::Class,
                 Attribute::Uuid,
                 Attribute::LastModifiedCid,
                 Attribute::CreatedAtCid,
-            ],
-            ..Default::default()
-        };
+            ])
+            .build();
 
-        let class_service = SchemaClass {
-            name: EntryClass::Service.into(),
-            uuid: Uuid::new_v4(),
-            description: String::from("service object"),
-            systemmust: vec![
+        let class_service = SchemaClass::builder(EntryClass::Service)
+            .description("service object")
+            .systemmust(vec![
                 Attribute::Class,
                 Attribute::Uuid,
                 Attribute::LastModifiedCid,
                 Attribute::CreatedAtCid,
-            ],
-            excludes: vec![EntryClass::Person.into()],
-            ..Default::default()
-        };
+            ])
+            .excludes(vec![EntryClass::Person.into()])
+            .build();
 
         assert!(schema
             .update_classes([class_account, class_person, class_service].into_iter())
@@ -1730,4 +3920,1174 @@ mod tests {
 
         assert!(e_person_valid.validate(&schema).is_ok());
     }
+
+    #[test]
+    fn test_schema_class_supplemented_by() {
+        sketching::test_init();
+
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let mut schema = schema_outer.write_blocking();
+
+        let class_account = SchemaClass::builder(EntryClass::Account)
+            .description("account object")
+            .systemmust(vec![Attribute::Class, Attribute::Uuid])
+            .systemsupplements(vec![EntryClass::Service.into()])
+            .supplements(vec![EntryClass::Person.into()])
+            .build();
+
+        let class_person = SchemaClass::builder(EntryClass::Person)
+            .description("person object")
+            .systemmust(vec![Attribute::Class, Attribute::Uuid])
+            .build();
+
+        let class_service = SchemaClass::builder(EntryClass::Service)
+            .description("service object")
+            .systemmust(vec![Attribute::Class, Attribute::Uuid])
+            .supplements(vec![EntryClass::Person.into()])
+            .build();
+
+        assert!(schema
+            .update_classes([class_account, class_person, class_service].into_iter())
+            .is_ok());
+
+        let names = |class: EntryClass| -> Vec<String> {
+            schema
+                .supplemented_by(class.into())
+                .into_iter()
+                .map(|sc| sc.name.to_string())
+                .collect()
+        };
+
+        // Both system and user supplements are found, sorted by name.
+        assert_eq!(
+            names(EntryClass::Person),
+            vec![
+                EntryClass::Account.to_string(),
+                EntryClass::Service.to_string()
+            ]
+        );
+        assert_eq!(
+            names(EntryClass::Service),
+            vec![EntryClass::Account.to_string()]
+        );
+        assert!(names(EntryClass::Account).is_empty());
+        assert!(schema.supplemented_by("no_such_class").is_empty());
+    }
+
+    #[test]
+    fn test_schema_class_attribute_exclusion() {
+        sketching::test_init();
+
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let mut schema = schema_outer.write_blocking();
+
+        assert!(schema.validate().is_empty());
+
+        // A class that must never carry mail, alongside one that may.
+        let class_nomail = SchemaClass {
+            name: AttrString::from("nomail"),
+            uuid: Uuid::new_v4(),
+            description: String::from("object that can not receive mail"),
+            systemmust: vec![
+                Attribute::Class,
+                Attribute::Uuid,
+                Attribute::LastModifiedCid,
+                Attribute::CreatedAtCid,
+            ],
+            attr_excludes: vec![Attribute::Mail],
+            ..Default::default()
+        };
+
+        let class_mailbox = SchemaClass {
+            name: AttrString::from("mailbox"),
+            uuid: Uuid::new_v4(),
+            description: String::from("object that can receive mail"),
+            systemmust: vec![
+                Attribute::Class,
+                Attribute::Uuid,
+                Attribute::LastModifiedCid,
+                Attribute::CreatedAtCid,
+            ],
+            systemmay: vec![Attribute::Mail],
+            ..Default::default()
+        };
+
+        let attr_mail = SchemaAttribute {
+            name: Attribute::Mail,
+            uuid: Uuid::new_v4(),
+            description: String::from("mail addresses"),
+            multivalue: true,
+            syntax: SyntaxType::EmailAddress,
+            ..Default::default()
+        };
+
+        assert!(schema
+            .extend_in_memory(vec![attr_mail], vec![class_nomail, class_mailbox])
+            .is_ok());
+
+        let e_mailbox = entry_init!(
+            (Attribute::Class, Value::new_iutf8("mailbox")),
+            (Attribute::Uuid, Value::Uuid(Uuid::new_v4())),
+            (
+                Attribute::Mail,
+                Value::new_email_address_s("a@example.com").expect("invalid email")
+            )
+        )
+        .into_invalid_new();
+
+        assert!(e_mailbox.validate(&schema).is_ok());
+
+        let e_nomail = entry_init!(
+            (Attribute::Class, Value::new_iutf8("nomail")),
+            (Attribute::Class, Value::new_iutf8("mailbox")),
+            (Attribute::Uuid, Value::Uuid(Uuid::new_v4())),
+            (
+                Attribute::Mail,
+                Value::new_email_address_s("a@example.com").expect("invalid email")
+            )
+        )
+        .into_invalid_new();
+
+        assert_eq!(
+            e_nomail.validate(&schema),
+            Err(SchemaError::AttributeExcludedByClass(vec![Attribute::Mail]))
+        );
+
+        // Without the attribute both classes can co-exist.
+        let e_nomail_valid = entry_init!(
+            (Attribute::Class, Value::new_iutf8("nomail")),
+            (Attribute::Class, Value::new_iutf8("mailbox")),
+            (Attribute::Uuid, Value::Uuid(Uuid::new_v4()))
+        )
+        .into_invalid_new();
+
+        assert!(e_nomail_valid.validate(&schema).is_ok());
+    }
+
+    #[test]
+    fn test_schema_class_requires_one_of() {
+        sketching::test_init();
+
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let mut schema = schema_outer.write_blocking();
+
+        let attr_phone = SchemaAttribute::builder(Attribute::from("phone"), SyntaxType::Utf8String)
+            .uuid(Uuid::new_v4())
+            .description("phone numbers")
+            .multivalue(true)
+            .build();
+
+        let attr_mail = SchemaAttribute::builder(Attribute::Mail, SyntaxType::EmailAddress)
+            .uuid(Uuid::new_v4())
+            .description("mail addresses")
+            .multivalue(true)
+            .build();
+
+        // A contact must be reachable somehow.
+        let class_contact = SchemaClass::builder("contact")
+            .description("object that can be contacted")
+            .systemmust(vec![
+                Attribute::Class,
+                Attribute::Uuid,
+                Attribute::LastModifiedCid,
+                Attribute::CreatedAtCid,
+            ])
+            .systemmay(vec![Attribute::Mail, Attribute::from("phone")])
+            .requires_one_of(vec![vec![Attribute::Mail, Attribute::from("phone")]])
+            .build();
+
+        // The groups survive being stored in and loaded from an entry.
+        let e_class = EntryInitNew::from(&class_contact).into_sealed_committed();
+        let loaded = SchemaClass::try_from(&e_class).expect("invalid class entry");
+        assert_eq!(loaded.requires_one_of, class_contact.requires_one_of);
+
+        assert!(schema
+            .extend_in_memory(vec![attr_phone, attr_mail], vec![class_contact])
+            .is_ok());
+
+        let e_unreachable = entry_init!(
+            (Attribute::Class, Value::new_iutf8("contact")),
+            (Attribute::Uuid, Value::Uuid(Uuid::new_v4()))
+        )
+        .into_invalid_new();
+
+        assert_eq!(
+            e_unreachable.validate(&schema),
+            Err(SchemaError::RequiresOneOfNotSatisfied(vec![
+                Attribute::Mail,
+                Attribute::from("phone")
+            ]))
+        );
+
+        let e_mail = entry_init!(
+            (Attribute::Class, Value::new_iutf8("contact")),
+            (Attribute::Uuid, Value::Uuid(Uuid::new_v4())),
+            (
+                Attribute::Mail,
+                Value::new_email_address_s("a@example.com").expect("invalid email")
+            )
+        )
+        .into_invalid_new();
+
+        assert!(e_mail.validate(&schema).is_ok());
+    }
+
+    #[test]
+    fn test_schema_reload_idxmeta_ordering() {
+        sketching::test_init();
+
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let mut schema = schema_outer.write_blocking();
+
+        let attr_level =
+            SchemaAttribute::builder(Attribute::from("clearancelevel"), SyntaxType::Uint32)
+                .uuid(Uuid::new_v4())
+                .description("clearance level")
+                .indexed(true)
+                .build();
+
+        assert!(schema
+            .extend_in_memory(vec![attr_level], Vec::new())
+            .is_ok());
+
+        // Indexed orderable attributes gain an ordering index, so a reindex builds it.
+        let idxmeta = schema.reload_idxmeta();
+        for itype in [
+            IndexType::Equality,
+            IndexType::Presence,
+            IndexType::Ordering,
+        ] {
+            assert!(idxmeta.contains(&IdxKey {
+                attr: Attribute::from("clearancelevel"),
+                itype,
+            }));
+        }
+    }
+
+    #[test]
+    fn test_schema_to_json_schema() {
+        sketching::test_init();
+
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let schema = schema_outer.read();
+
+        let doc = schema.to_json_schema();
+        assert_eq!(doc["version"], 1);
+
+        let find = |list: &str, name: &str| {
+            doc[list]
+                .as_array()
+                .and_then(|items| items.iter().find(|item| item["name"] == name))
+                .cloned()
+                .unwrap_or_else(|| panic!("{name} not in {list}"))
+        };
+
+        let name = find("attributes", "name");
+        assert_eq!(name["syntax"], "UTF8STRING_INAME");
+        assert_eq!(name["multivalue"], false);
+        assert_eq!(name["unique"], true);
+        assert_eq!(name["indexed"], true);
+        assert_eq!(name["description"], "The shortform name of an object");
+
+        let class = find("attributes", "class");
+        assert_eq!(class["multivalue"], true);
+        assert_eq!(class["unique"], false);
+
+        let attributetype = find("classes", "attributetype");
+        let must = attributetype["must"].as_array().expect("must is a list");
+        assert!(must.contains(&serde_json::json!("attributename")));
+        assert!(must.contains(&serde_json::json!("syntax")));
+        let may = attributetype["may"].as_array().expect("may is a list");
+        assert!(may.contains(&serde_json::json!("indexed")));
+        assert_eq!(attributetype["excludes"], serde_json::json!(["classtype"]));
+        assert_eq!(attributetype["supplements"], serde_json::json!([]));
+
+        // The document is stable between calls.
+        assert_eq!(doc, schema.to_json_schema());
+    }
+
+    #[test]
+    fn test_schema_attr_for_idxkey() {
+        sketching::test_init();
+
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let schema = schema_outer.write_blocking();
+
+        for key in schema.reload_idxmeta() {
+            let attr = schema
+                .attr_for_idxkey(&key)
+                .expect("index without an owning attribute");
+            assert_eq!(attr.name, key.attr);
+            assert!(attr.syntax.index_types().contains(&key.itype));
+        }
+
+        let key = IdxKey {
+            attr: Attribute::Name,
+            itype: IndexType::Equality,
+        };
+        let attr = schema.attr_for_idxkey(&key).expect("name not in schema");
+        assert_eq!(attr.syntax, SyntaxType::Utf8StringIname);
+
+        let key = IdxKey {
+            attr: Attribute::from("nonexistent"),
+            itype: IndexType::Equality,
+        };
+        assert!(schema.attr_for_idxkey(&key).is_none());
+    }
+
+    #[test]
+    fn test_schema_attribute_compare_values() {
+        let attr_name = SchemaAttribute::builder(Attribute::Name, SyntaxType::Utf8StringIname)
+            .uuid(Uuid::new_v4())
+            .build();
+
+        let mut names = vec![
+            Value::new_iname("claire"),
+            Value::new_iname("alice"),
+            Value::new_iname("bob10"),
+            Value::new_iname("bob9"),
+        ];
+        names.sort_by(|a, b| attr_name.compare_values(a, b));
+        assert_eq!(
+            names,
+            vec![
+                Value::new_iname("alice"),
+                Value::new_iname("bob10"),
+                Value::new_iname("bob9"),
+                Value::new_iname("claire"),
+            ]
+        );
+
+        let attr_gid = SchemaAttribute::builder(Attribute::GidNumber, SyntaxType::Uint32)
+            .uuid(Uuid::new_v4())
+            .build();
+
+        // Numeric, where a lexical sort would put 10 before 9.
+        let mut gids = vec![Value::Uint32(70000), Value::Uint32(10), Value::Uint32(9)];
+        gids.sort_by(|a, b| attr_gid.compare_values(a, b));
+        assert_eq!(
+            gids,
+            vec![Value::Uint32(9), Value::Uint32(10), Value::Uint32(70000)]
+        );
+
+        // Values that don't match the syntax keep their order.
+        assert_eq!(
+            attr_gid.compare_values(&Value::new_iname("alice"), &Value::Uint32(9)),
+            std::cmp::Ordering::Equal
+        );
+
+        let attr_key = SchemaAttribute::builder(Attribute::SshPublicKey, SyntaxType::SshKey)
+            .uuid(Uuid::new_v4())
+            .build();
+        assert_eq!(
+            attr_key.compare_values(&Value::Bool(true), &Value::Bool(false)),
+            std::cmp::Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn test_schema_is_unique() {
+        sketching::test_init();
+
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let mut schema = schema_outer.write_blocking();
+
+        assert!(schema.is_unique(&Attribute::Name));
+        assert!(schema.is_unique(&Attribute::Spn));
+        // Uuid uniqueness is enforced by the backend, not by attribute uniqueness.
+        assert!(!schema.is_unique(&Attribute::Uuid));
+        assert!(!schema.is_unique(&Attribute::DisplayName));
+        assert!(!schema.is_unique(&Attribute::from("employeenumber")));
+
+        let attr_employee = SchemaAttribute::builder(
+            Attribute::from("employeenumber"),
+            SyntaxType::Utf8StringInsensitive,
+        )
+        .uuid(Uuid::new_v4())
+        .description("employee number")
+        .unique(true)
+        .build();
+
+        assert!(schema
+            .extend_in_memory(vec![attr_employee], Vec::with_capacity(0))
+            .is_ok());
+
+        assert!(schema.is_unique(&Attribute::from("employeenumber")));
+        // The set is kept consistent with the attribute definitions.
+        assert_eq!(
+            schema.get_attributes_unique().len(),
+            schema
+                .get_attributes()
+                .values()
+                .filter(|a| a.unique)
+                .count()
+        );
+
+        // And carried through to readers once committed.
+        assert!(schema.commit().is_ok());
+        let schema = schema_outer.read();
+        assert!(schema.is_unique(&Attribute::from("employeenumber")));
+        assert!(!schema.is_unique(&Attribute::DisplayName));
+    }
+
+    #[test]
+    fn test_schema_audited_attrs() {
+        sketching::test_init();
+
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let mut schema = schema_outer.write_blocking();
+
+        // Nothing is audited unless requested.
+        assert!(schema.audited_attrs().is_empty());
+
+        let attr_salary = SchemaAttribute::builder(Attribute::from("salary"), SyntaxType::Uint32)
+            .uuid(Uuid::new_v4())
+            .description("the salary of a person")
+            .audit(true)
+            .build();
+
+        // The flag survives conversion to and from an entry.
+        let e = EntryInitNew::from(&attr_salary).into_sealed_committed();
+        assert_eq!(e.get_ava_single_bool(Attribute::Audit), Some(true));
+        let parsed = SchemaAttribute::try_from(&e).expect("failed to parse");
+        assert!(parsed.audit);
+
+        // And defaults to false when absent.
+        let attr_badge = SchemaAttribute::builder(Attribute::from("badge"), SyntaxType::Utf8String)
+            .uuid(Uuid::new_v4())
+            .description("a badge")
+            .build();
+        let e = EntryInitNew::from(&attr_badge).into_sealed_committed();
+        assert_eq!(e.get_ava_single_bool(Attribute::Audit), None);
+        let parsed = SchemaAttribute::try_from(&e).expect("failed to parse");
+        assert!(!parsed.audit);
+
+        let attr_clearance =
+            SchemaAttribute::builder(Attribute::from("clearance"), SyntaxType::Utf8String)
+                .uuid(Uuid::new_v4())
+                .description("a security clearance")
+                .audit(true)
+                .build();
+
+        assert!(schema
+            .extend_in_memory(
+                vec![attr_salary, attr_badge, attr_clearance],
+                Vec::with_capacity(0)
+            )
+            .is_ok());
+
+        assert_eq!(
+            schema.audited_attrs(),
+            vec![&Attribute::from("clearance"), &Attribute::from("salary")]
+        );
+    }
+
+    #[test]
+    fn test_schema_update_attributes_lenient() {
+        sketching::test_init();
+
+        let attr_entry = |name: &str, syntax: Option<SyntaxType>| {
+            let mut e = entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::AttributeType.to_value()),
+                (Attribute::AttributeName, Value::new_iutf8(name)),
+                (Attribute::Uuid, Value::Uuid(Uuid::new_v4())),
+                (Attribute::Description, Value::new_utf8s("Test attr")),
+                (Attribute::MultiValue, Value::Bool(false)),
+                (Attribute::Unique, Value::Bool(false))
+            );
+            if let Some(syntax) = syntax {
+                e.add_ava(Attribute::Syntax, Value::Syntax(syntax));
+            }
+            Arc::new(e.into_sealed_committed())
+        };
+
+        let good = [
+            attr_entry("lenient_attr_a", Some(SyntaxType::Utf8String)),
+            attr_entry("lenient_attr_b", Some(SyntaxType::Boolean)),
+            attr_entry("lenient_attr_c", Some(SyntaxType::Uuid)),
+        ];
+        // Missing its syntax.
+        let bad = attr_entry("lenient_attr_bad", None);
+        let bad_uuid = bad.get_uuid();
+
+        let mut entries = good.to_vec();
+        entries.insert(1, bad);
+
+        // Production remains fail fast unless lenient reloads are chosen.
+        assert_eq!(SchemaReloadMode::default(), SchemaReloadMode::Strict);
+
+        let schema_outer = Schema::new()
+            .expect("failed to create schema")
+            .with_reload_mode(SchemaReloadMode::Lenient);
+        let mut schema = schema_outer.write_blocking();
+        assert_eq!(schema.reload_mode(), SchemaReloadMode::Lenient);
+
+        let (loaded, failures) = schema.update_attributes_lenient(&entries);
+
+        assert_eq!(loaded, 3);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, bad_uuid);
+        assert!(matches!(
+            failures[0].1,
+            OperationError::InvalidSchemaState(_)
+        ));
+
+        let attributes = schema.get_attributes();
+        assert!(attributes.contains_key(&Attribute::from("lenient_attr_a")));
+        assert!(attributes.contains_key(&Attribute::from("lenient_attr_b")));
+        assert!(attributes.contains_key(&Attribute::from("lenient_attr_c")));
+        assert!(!attributes.contains_key(&Attribute::from("lenient_attr_bad")));
+    }
+
+    #[test]
+    fn test_schema_unique_scope() {
+        sketching::test_init();
+
+        let base = || {
+            entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::AttributeType.to_value()),
+                (
+                    Attribute::AttributeName,
+                    Value::new_iutf8("schema_attr_test")
+                ),
+                (
+                    Attribute::Uuid,
+                    Value::Uuid(uuid::uuid!("66c68b2f-d02c-4243-8013-7946e40fe321"))
+                ),
+                (Attribute::Description, Value::new_utf8s("Test attr")),
+                (Attribute::MultiValue, Value::Bool(false)),
+                (Attribute::Unique, Value::Bool(true)),
+                (
+                    Attribute::Syntax,
+                    Value::Syntax(SyntaxType::Utf8StringInsensitive)
+                )
+            )
+        };
+
+        // Without a scope, values are unique amongst all entries.
+        let e = base().into_sealed_committed();
+        let attr = SchemaAttribute::try_from(&e).expect("failed to parse");
+        assert_eq!(attr.unique_scope, UniqueScope::All);
+
+        let mut e = base();
+        e.add_ava(
+            Attribute::UniqueScope,
+            Value::new_iutf8(UniqueScope::PER_SYNC_PARENT),
+        );
+        let e = e.into_sealed_committed();
+        let attr = SchemaAttribute::try_from(&e).expect("failed to parse");
+        assert_eq!(attr.unique_scope, UniqueScope::PerSyncParent);
+
+        let mut e = base();
+        e.add_ava(Attribute::UniqueScope, Value::new_iutf8("person"));
+        let e = e.into_sealed_committed();
+        let attr = SchemaAttribute::try_from(&e).expect("failed to parse");
+        assert_eq!(
+            attr.unique_scope,
+            UniqueScope::Classes(btreeset![AttrString::from("person")])
+        );
+
+        // The per sync parent scope is exclusive of classes.
+        let mut e = base();
+        e.add_ava(
+            Attribute::UniqueScope,
+            Value::new_iutf8(UniqueScope::PER_SYNC_PARENT),
+        );
+        e.add_ava(Attribute::UniqueScope, Value::new_iutf8("person"));
+        let e = e.into_sealed_committed();
+        assert!(SchemaAttribute::try_from(&e).is_err());
+
+        // The scope is exposed through the schema once loaded.
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let mut schema = schema_outer.write_blocking();
+        assert_eq!(
+            schema.unique_scope(&Attribute::Name),
+            Some(&UniqueScope::All)
+        );
+        assert_eq!(schema.unique_scope(&Attribute::DisplayName), None);
+
+        let attr_sync_id = SchemaAttribute::builder(
+            Attribute::from("syncscopedid"),
+            SyntaxType::Utf8StringInsensitive,
+        )
+        .uuid(Uuid::new_v4())
+        .unique(true)
+        .unique_per_sync_parent()
+        .build();
+
+        assert!(schema
+            .extend_in_memory(vec![attr_sync_id], Vec::with_capacity(0))
+            .is_ok());
+        assert_eq!(
+            schema.unique_scope(&Attribute::from("syncscopedid")),
+            Some(&UniqueScope::PerSyncParent)
+        );
+    }
+
+    #[test]
+    fn test_schema_attribute_exclusion_group() {
+        sketching::test_init();
+
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let mut schema = schema_outer.write_blocking();
+
+        assert!(schema.validate().is_empty());
+
+        let class_contact = SchemaClass {
+            name: AttrString::from("contact"),
+            uuid: Uuid::new_v4(),
+            description: String::from("object with a single contact method"),
+            systemmust: vec![
+                Attribute::Class,
+                Attribute::Uuid,
+                Attribute::LastModifiedCid,
+                Attribute::CreatedAtCid,
+            ],
+            systemmay: vec![Attribute::Mail, Attribute::LegalName],
+            ..Default::default()
+        };
+
+        let attr_mail = SchemaAttribute::builder(Attribute::Mail, SyntaxType::EmailAddress)
+            .uuid(Uuid::new_v4())
+            .description("mail addresses")
+            .multivalue(true)
+            .exclusion_group("contact")
+            .build();
+
+        let attr_legalname = SchemaAttribute::builder(Attribute::LegalName, SyntaxType::Utf8String)
+            .uuid(Uuid::new_v4())
+            .description("legal name")
+            .exclusion_group("contact")
+            .build();
+
+        assert!(schema
+            .extend_in_memory(vec![attr_mail, attr_legalname], vec![class_contact])
+            .is_ok());
+
+        let e_mail = entry_init!(
+            (Attribute::Class, Value::new_iutf8("contact")),
+            (Attribute::Uuid, Value::Uuid(Uuid::new_v4())),
+            (
+                Attribute::Mail,
+                Value::new_email_address_s("a@example.com").expect("invalid email")
+            )
+        )
+        .into_invalid_new();
+
+        assert!(e_mail.validate(&schema).is_ok());
+
+        let e_both = entry_init!(
+            (Attribute::Class, Value::new_iutf8("contact")),
+            (Attribute::Uuid, Value::Uuid(Uuid::new_v4())),
+            (
+                Attribute::Mail,
+                Value::new_email_address_s("a@example.com").expect("invalid email")
+            ),
+            (Attribute::LegalName, Value::new_utf8s("Alice"))
+        )
+        .into_invalid_new();
+
+        let Err(SchemaError::MutuallyExclusiveAttributes(mut present)) = e_both.validate(&schema)
+        else {
+            panic!("mutually exclusive attributes were accepted");
+        };
+        present.sort_unstable();
+        let mut expect = vec![Attribute::Mail, Attribute::LegalName];
+        expect.sort_unstable();
+        assert_eq!(present, expect);
+    }
+
+    #[test]
+    fn test_schema_attribute_allowed_values() {
+        sketching::test_init();
+
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let mut schema = schema_outer.write_blocking();
+
+        let attr_status = Attribute::from("status");
+
+        let status =
+            SchemaAttribute::builder(attr_status.clone(), SyntaxType::Utf8StringInsensitive)
+                .description("the status of an entry")
+                .multivalue(true)
+                .allowed_values(["Active", "Disabled"])
+                .build();
+
+        // Allowed values are compared after the value is normalised.
+        assert_eq!(
+            status.validate_value(&attr_status, &Value::new_iutf8("Active")),
+            Ok(())
+        );
+        assert_eq!(
+            status.validate_value(&attr_status, &Value::new_iutf8("deleted")),
+            Err(SchemaError::ValueNotAllowed("status".to_string()))
+        );
+
+        assert_eq!(
+            status.validate_ava(&attr_status, &(vs_iutf8!["active", "disabled"])),
+            Ok(())
+        );
+        assert_eq!(
+            status.validate_ava(&attr_status, &(vs_iutf8!["active", "deleted"])),
+            Err(SchemaError::ValueNotAllowed("status".to_string()))
+        );
+
+        // Attributes without allowed values accept anything.
+        let description =
+            SchemaAttribute::builder(Attribute::Description, SyntaxType::Utf8String).build();
+        assert_eq!(
+            description.validate_value(&Attribute::Description, &Value::new_utf8s("deleted")),
+            Ok(())
+        );
+
+        assert!(schema.upsert_attribute(status).is_ok());
+
+        // Allowed values are only meaningful for string syntaxes.
+        let priority = SchemaAttribute::builder(Attribute::from("priority"), SyntaxType::Uint32)
+            .description("the priority of an entry")
+            .allowed_values(["1", "2"])
+            .build();
+
+        assert!(matches!(
+            schema.upsert_attribute(priority),
+            Err(OperationError::InvalidSchemaState(_))
+        ));
+    }
+
+    #[test]
+    fn test_schema_attribute_upsert_remove() {
+        sketching::test_init();
+
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let mut schema = schema_outer.write_blocking();
+
+        let attr_name = Attribute::from("sponsor");
+        let attr_count = schema.get_attributes().len();
+
+        // Insert a new unique reference attribute.
+        let attr_sponsor = SchemaAttribute::builder(attr_name.clone(), SyntaxType::ReferenceUuid)
+            .uuid(Uuid::new_v4())
+            .description("the sponsor of this entry")
+            .unique(true)
+            .exclusion_group("sponsorship")
+            .build();
+
+        assert!(schema.upsert_attribute(attr_sponsor.clone()).is_ok());
+        assert_eq!(schema.get_attributes().len(), attr_count + 1);
+        assert!(schema.is_unique(&attr_name));
+        assert!(schema.get_reference_types().contains_key(&attr_name));
+        assert_eq!(
+            schema.get_exclusion_groups().get("sponsorship"),
+            Some(&vec![attr_name.clone()])
+        );
+
+        // Update it so that it no longer belongs in either cache.
+        let attr_sponsor_updated = SchemaAttribute {
+            unique: false,
+            syntax: SyntaxType::ReferenceUuid,
+            exclusion_group: None,
+            description: String::from("the sponsors of this entry"),
+            multivalue: true,
+            ..attr_sponsor.clone()
+        };
+        assert!(schema.upsert_attribute(attr_sponsor_updated).is_ok());
+        assert_eq!(schema.get_attributes().len(), attr_count + 1);
+        assert!(!schema.is_unique(&attr_name));
+        assert!(schema
+            .get_reference_types()
+            .get(&attr_name)
+            .is_some_and(|a| a.multivalue));
+        assert!(!schema.get_exclusion_groups().contains_key("sponsorship"));
+
+        // An unsafe syntax change is refused and leaves the definition intact.
+        let attr_sponsor_bool = SchemaAttribute {
+            syntax: SyntaxType::Boolean,
+            ..attr_sponsor
+        };
+        assert!(schema.upsert_attribute(attr_sponsor_bool).is_err());
+        assert!(schema.get_reference_types().contains_key(&attr_name));
+
+        // Remove it, and all the caches are cleared of it.
+        assert!(schema.remove_attribute(&attr_name).is_ok());
+        assert_eq!(schema.get_attributes().len(), attr_count);
+        assert!(!schema.get_attributes().contains_key(&attr_name));
+        assert!(!schema.is_unique(&attr_name));
+        assert!(!schema.get_reference_types().contains_key(&attr_name));
+
+        // Removing it again is a no-op, but core attributes are protected.
+        assert!(schema.remove_attribute(&attr_name).is_ok());
+        assert!(schema.remove_attribute(&Attribute::Name).is_err());
+        assert!(schema.is_unique(&Attribute::Name));
+
+        assert!(schema.validate().is_empty());
+    }
+
+    #[test]
+    fn test_schema_rename_attribute() {
+        sketching::test_init();
+
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let mut schema = schema_outer.write_blocking();
+
+        let from = Attribute::from("badgenumber");
+        let to = Attribute::from("employeebadge");
+
+        let attr_badge = SchemaAttribute::builder(from.clone(), SyntaxType::Utf8StringInsensitive)
+            .uuid(Uuid::new_v4())
+            .description("badge number")
+            .unique(true)
+            .build();
+        let class_staff = SchemaClass::builder("staff")
+            .uuid(Uuid::new_v4())
+            .description("staff member")
+            .may(vec![from.clone(), Attribute::Description])
+            .build();
+
+        assert!(schema
+            .extend_in_memory(vec![attr_badge.clone()], vec![class_staff])
+            .is_ok());
+        let attr_count = schema.get_attributes().len();
+
+        let plan = schema
+            .rename_attribute(&from, to.clone())
+            .expect("rename failed");
+        assert_eq!(plan.from, from);
+        assert_eq!(plan.to, to);
+        assert_eq!(plan.classes, vec![AttrString::from("staff")]);
+
+        // The definition and caches now only know the new name.
+        assert_eq!(schema.get_attributes().len(), attr_count);
+        assert!(!schema.get_attributes().contains_key(&from));
+        assert!(!schema.is_unique(&from));
+        assert!(schema.is_unique(&to));
+        let renamed = schema.get_attributes().get(&to).expect("renamed missing");
+        assert_eq!(renamed.uuid, attr_badge.uuid);
+        let staff = schema.get_classes().get("staff").expect("class missing");
+        assert!(staff.may.contains(&to));
+        assert!(!staff.may.contains(&from));
+        assert!(schema.validate().is_empty());
+
+        // The plan moves the values of an entry.
+        let mut e = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, Value::new_iutf8("staff")),
+            (from.clone(), Value::new_iutf8("1234"))
+        )
+        .into_invalid_new();
+        assert!(plan.apply(&mut e));
+        assert!(!plan.apply(&mut e));
+        assert!(e.get_ava_set(&from).is_none());
+        assert!(e.attribute_equality(to.clone(), &PartialValue::new_iutf8("1234")));
+
+        // The old name is gone, and the new one can't be taken twice.
+        assert!(schema
+            .rename_attribute(&from, Attribute::from("other"))
+            .is_err());
+        assert!(schema
+            .rename_attribute(&to, Attribute::Description)
+            .is_err());
+
+        // System attributes may not be renamed.
+        assert!(schema
+            .rename_attribute(&Attribute::Description, Attribute::from("summary"))
+            .is_err());
+        assert!(schema
+            .rename_attribute(&Attribute::Name, Attribute::from("username"))
+            .is_err());
+        assert!(schema
+            .get_attributes()
+            .contains_key(&Attribute::Description));
+        assert!(!schema
+            .get_attributes()
+            .contains_key(&Attribute::from("summary")));
+    }
+
+    #[test]
+    fn test_schema_validate_filter_op() {
+        sketching::test_init();
+
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let schema = schema_outer.read();
+
+        assert_eq!(
+            schema.validate_filter_op(&Attribute::Uuid, FilterOpKind::Equality),
+            Ok(())
+        );
+        assert_eq!(
+            schema.validate_filter_op(&Attribute::Uuid, FilterOpKind::Presence),
+            Ok(())
+        );
+        assert_eq!(
+            schema.validate_filter_op(&Attribute::Uuid, FilterOpKind::SubString),
+            Err(SchemaError::FilterOperationNotSupported(
+                Attribute::Uuid.to_string(),
+                "sub".to_string()
+            ))
+        );
+        assert!(schema
+            .validate_filter_op(&Attribute::Uuid, FilterOpKind::Ordering)
+            .is_err());
+
+        assert_eq!(
+            schema.validate_filter_op(&Attribute::Name, FilterOpKind::SubString),
+            Ok(())
+        );
+        assert_eq!(
+            schema.validate_filter_op(&Attribute::from("nonexistent"), FilterOpKind::Equality),
+            Err(SchemaError::InvalidAttribute("nonexistent".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_schema_validate_partialvalue_by_name() {
+        sketching::test_init();
+
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let schema = schema_outer.read();
+
+        assert!(schema
+            .validate_partialvalue(&Attribute::Name, &PartialValue::new_iname("testperson"))
+            .is_ok());
+        assert_eq!(
+            schema.validate_partialvalue(&Attribute::Name, &PartialValue::new_bool(true)),
+            Err(SchemaError::InvalidAttributeSyntax(
+                Attribute::Name.to_string()
+            ))
+        );
+        assert_eq!(
+            schema.validate_partialvalue(
+                &Attribute::from("nonexistent"),
+                &PartialValue::new_utf8s("value")
+            ),
+            Err(SchemaError::InvalidAttribute("nonexistent".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_schema_validation_metrics() {
+        let counter = Arc::new(SchemaValidationCounter::default());
+        // Tests share the process, so this is the only test that may install a sink.
+        assert!(set_schema_validation_metrics(counter.clone()).is_ok());
+        assert!(
+            set_schema_validation_metrics(Arc::new(SchemaValidationCounter::default())).is_err()
+        );
+
+        let attr = Attribute::from("metrics_test");
+        let schema_attr = SchemaAttribute::builder(attr.clone(), SyntaxType::Utf8String).build();
+
+        assert!(schema_attr
+            .validate_ava(&attr, &(vs_utf8!["a".to_string()] as _))
+            .is_ok());
+        assert!(schema_attr
+            .validate_value(&attr, &Value::new_utf8s("b"))
+            .is_ok());
+        assert!(schema_attr
+            .validate_ava(&attr, &(vs_bool![true] as _))
+            .is_err());
+
+        assert_eq!(
+            counter.snapshot().get(&attr),
+            Some(&SchemaValidationCount {
+                succeeded: 2,
+                failed: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_schema_custom_syntax_validator() {
+        let attr = Attribute::from("custom_syntax_test");
+        let unregistered = Attribute::from("custom_syntax_unregistered");
+
+        let mut registry = CustomSyntaxRegistry::default();
+        registry.register(attr.clone(), |value| {
+            value.len() == 6 && value.chars().all(|c| c.is_ascii_digit())
+        });
+        let schema_outer = Schema::new()
+            .expect("failed to create schema")
+            .with_custom_syntax_validators(registry);
+        let mut schema = schema_outer.write_blocking();
+
+        for name in [&attr, &unregistered] {
+            let a = SchemaAttribute::builder(name.clone(), SyntaxType::Custom)
+                .multivalue(true)
+                .build();
+            assert!(schema.upsert_attribute(a).is_ok());
+        }
+
+        let schema_attr = schema
+            .get_attributes()
+            .get(&attr)
+            .expect("custom attribute not found");
+
+        assert!(schema_attr
+            .validate_value(&attr, &Value::new_utf8s("123456"))
+            .is_ok());
+        assert_eq!(
+            schema_attr.validate_value(&attr, &Value::new_utf8s("12345a")),
+            Err(SchemaError::InvalidAttributeSyntax(attr.to_string()))
+        );
+        assert!(schema_attr
+            .validate_ava(&attr, &(vs_utf8!["123456".to_string()] as _))
+            .is_ok());
+        assert_eq!(
+            schema_attr.validate_ava(
+                &attr,
+                &(vs_utf8!["123456".to_string(), "nope".to_string()] as _)
+            ),
+            Err(SchemaError::InvalidAttributeSyntax(attr.to_string()))
+        );
+        // Values are stored as strings, so other syntaxes are still rejected.
+        assert!(schema_attr
+            .validate_ava(&attr, &(vs_bool![true] as _))
+            .is_err());
+
+        // An attribute of a custom syntax without a validator accepts no values.
+        let schema_attr = schema
+            .get_attributes()
+            .get(&unregistered)
+            .expect("custom attribute not found");
+        assert_eq!(
+            schema_attr.validate_value(&unregistered, &Value::new_utf8s("123456")),
+            Err(SchemaError::CustomSyntaxNotRegistered(
+                unregistered.to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_schema_structural_class() {
+        let schema_outer = Schema::new().expect("failed to create schema");
+        let mut schema = schema_outer.write_blocking();
+
+        let class_structural = SchemaClass::builder("test_structural")
+            .description("structural test class")
+            .kind(ClassKind::Structural)
+            .build();
+        let class_other_structural = SchemaClass::builder("test_other_structural")
+            .description("another structural test class")
+            .kind(ClassKind::Structural)
+            .build();
+        let class_auxiliary = SchemaClass::builder("test_auxiliary")
+            .description("auxiliary test class")
+            .build();
+        let class_abstract = SchemaClass::builder("test_abstract")
+            .description("abstract test class")
+            .kind(ClassKind::Abstract)
+            .build();
+
+        // The kind survives being stored in and loaded from an entry, and defaults to
+        // auxiliary when absent.
+        let e_class = EntryInitNew::from(&class_structural).into_sealed_committed();
+        let loaded = SchemaClass::try_from(&e_class).expect("invalid class entry");
+        assert_eq!(loaded.kind, ClassKind::Structural);
+        assert!(!EntryInitNew::from(&class_auxiliary).attribute_pres(Attribute::ClassKind));
+        let e_class = EntryInitNew::from(&class_auxiliary).into_sealed_committed();
+        let loaded = SchemaClass::try_from(&e_class).expect("invalid class entry");
+        assert_eq!(loaded.kind, ClassKind::Auxiliary);
+
+        assert!(schema
+            .extend_in_memory(
+                vec![],
+                vec![
+                    class_structural,
+                    class_other_structural,
+                    class_auxiliary,
+                    class_abstract
+                ]
+            )
+            .is_ok());
+
+        assert_eq!(
+            schema.structural_class(&["test_auxiliary", "test_structural", "test_abstract"]),
+            Ok("test_structural")
+        );
+        assert_eq!(
+            schema.structural_class(&["test_auxiliary", "test_abstract"]),
+            Err(SchemaError::NoStructuralClass)
+        );
+        assert_eq!(
+            schema.structural_class(&["test_structural", "test_other_structural"]),
+            Err(SchemaError::MultipleStructuralClasses(vec![
+                "test_structural".to_string(),
+                "test_other_structural".to_string()
+            ]))
+        );
+        assert_eq!(
+            schema.structural_class(&["test_structural", "test_missing"]),
+            Err(SchemaError::InvalidClass(vec!["test_missing".to_string()]))
+        );
+    }
+
+    #[qs_test]
+    async fn test_schema_effective_schema_for(server: &QueryServer) {
+        let server_txn = server.read().await.unwrap();
+        let schema = server_txn.get_schema();
+
+        let person: BTreeSet<&str> = [EntryClass::Object, EntryClass::Account, EntryClass::Person]
+            .into_iter()
+            .map(<&str>::from)
+            .collect();
+
+        let effective = schema
+            .effective_schema_for(&person)
+            .expect("Failed to resolve effective schema");
+
+        // Must attributes are drawn from every class.
+        assert!(effective.must.contains(&Attribute::Class));
+        assert!(effective.must.contains(&Attribute::Uuid));
+        assert!(effective.must.contains(&Attribute::Name));
+        assert!(effective.must.contains(&Attribute::DisplayName));
+        assert!(effective.must.contains(&Attribute::Spn));
+        assert!(!effective.must.contains(&Attribute::Mail));
+
+        // Must attributes are also allowed.
+        assert!(effective.must.is_subset(&effective.may));
+        assert!(effective.allows(&Attribute::Name));
+        assert!(effective.allows(&Attribute::Mail));
+        assert!(effective.allows(&Attribute::AccountExpire));
+        assert!(!effective.allows(&Attribute::ApiTokenSession));
+        assert!(!effective.allows(&Attribute::Member));
+
+        let allowed = effective.allowed();
+        assert!(allowed.contains(&&Attribute::LegalName));
+        assert_eq!(allowed.len(), effective.may.len());
+
+        // This agrees with the difference used when classes are changed.
+        let object: BTreeSet<&str> = [EntryClass::Object].into_iter().map(<&str>::from).collect();
+        let (added, removed) = schema
+            .query_attrs_difference(&object, &person)
+            .expect("Failed to query difference");
+        assert!(removed.is_empty());
+        assert!(added
+            .iter()
+            .all(|attr| effective.allows(&Attribute::from(*attr))));
+
+        // An account must be supplemented by a person or service account.
+        let account: BTreeSet<&str> = [EntryClass::Object, EntryClass::Account]
+            .into_iter()
+            .map(<&str>::from)
+            .collect();
+        assert!(matches!(
+            schema.effective_schema_for(&account),
+            Err(SchemaError::SupplementsNotSatisfied(_))
+        ));
+
+        // A person can't also be a service account.
+        let mut conflicting = person.clone();
+        conflicting.insert(EntryClass::ServiceAccount.into());
+        assert!(matches!(
+            schema.effective_schema_for(&conflicting),
+            Err(SchemaError::ExcludesNotSatisfied(_))
+        ));
+
+        let mut unknown = person.clone();
+        unknown.insert("not_a_class");
+        assert_eq!(
+            schema.effective_schema_for(&unknown),
+            Err(SchemaError::InvalidClass(vec!["not_a_class".to_string()]))
+        );
+    }
 }
@@ -149,7 +149,7 @@ fn create_filter_entry<'a>(
     debug!(event = %ident, "Access check for create event");
 
     match ident.access_scope() {
-        AccessScope::ReadOnly | AccessScope::Synchronise => {
+        AccessScope::ReadOnly | AccessScope::Synchronise | AccessScope::CredentialUpdate => {
             security_access!("denied ❌ - identity access scope is not permitted to create");
             return IResult::Deny;
         }
@@ -92,7 +92,7 @@ fn delete_filter_entry<'a>(
     debug!(event = %ident, "Access check for delete event");
 
     match ident.access_scope() {
-        AccessScope::ReadOnly | AccessScope::Synchronise => {
+        AccessScope::ReadOnly | AccessScope::Synchronise | AccessScope::CredentialUpdate => {
             security_access!("denied ❌ - identity access scope is not permitted to delete");
             return IResult::Deny;
         }
@@ -112,6 +112,12 @@ pub fn migration_entry_attrs(
         allow_attrs.extend([
             Attribute::AuthSessionExpiry,
             Attribute::AuthPasswordMinimumLength,
+            Attribute::AuthPasswordMinimumScore,
+            Attribute::AuthPasswordBreachCheck,
+            Attribute::AuthPasswordHistoryLength,
+            Attribute::AuthLockoutMaxAttempts,
+            Attribute::AuthLockoutDuration,
+            Attribute::CredentialMaxAge,
             Attribute::CredentialTypeMinimum,
             Attribute::PrivilegeExpiry,
             Attribute::WebauthnAttestationCaList,
@@ -362,6 +362,38 @@ pub trait AccessControlsTransaction<'a> {
         Ok(allowed_entries)
     }
 
+    /// Determine which of `attrs` on `entry` can be read by `ident`, if the entry could be
+    /// found by `ident` with the filter `filter_orig`. None is returned if the entry can't be
+    /// found by this identity.
+    #[instrument(
+        level = "debug",
+        name = "access::filter_entry_readable_attrs",
+        skip_all
+    )]
+    fn filter_entry_readable_attrs(
+        &self,
+        ident: &Identity,
+        filter_orig: &Filter<FilterValid>,
+        entry: &Arc<EntrySealedCommitted>,
+        attrs: &BTreeSet<Attribute>,
+    ) -> Option<BTreeSet<Attribute>> {
+        let requested_attrs: BTreeSet<Attribute> = filter_orig.get_attr_set();
+
+        if requested_attrs.is_empty() {
+            return None;
+        }
+
+        let related_acp = self.search_related_acp(ident, None);
+
+        match apply_search_access(ident, related_acp.as_slice(), entry) {
+            SearchResult::Deny => None,
+            SearchResult::Grant => Some(attrs.clone()),
+            SearchResult::Allow(allowed_attrs) => requested_attrs
+                .is_subset(&allowed_attrs)
+                .then(|| attrs.intersection(&allowed_attrs).cloned().collect()),
+        }
+    }
+
     // Contains all the way to eval acps to entries
     #[inline(always)]
     fn search_filter_entries(
@@ -2047,6 +2079,40 @@ mod tests {
         test_acp_search_reduce!(&se_anon, vec![acp], r_set, ex_anon);
     }
 
+    #[test]
+    fn test_access_enforce_search_attrs_req_projection() {
+        // The projection must never widen access. Here class is requested but
+        // not permitted, and uuid is permitted but not requested, so only name
+        // is returned.
+        let ev1 = E_TESTPERSON_1.clone().into_sealed_committed();
+        let r_set = vec![Arc::new(ev1)];
+
+        let exv1 = E_TESTPERSON_1_REDUCED.clone().into_sealed_committed();
+        let ex_anon = vec![exv1];
+
+        let mut se_anon = SearchEvent::new_impersonate_entry(
+            E_TEST_ACCOUNT_1.clone(),
+            filter_all!(f_eq(
+                Attribute::Name,
+                PartialValue::new_iname("testperson1")
+            )),
+        );
+        se_anon.attrs = Some(btreeset![Attribute::Name, Attribute::Class]);
+
+        let acp = AccessControlSearch::from_raw(
+            "test_acp",
+            Uuid::new_v4(),
+            UUID_TEST_GROUP_1,
+            filter_valid!(f_eq(
+                Attribute::Name,
+                PartialValue::new_iname("testperson1")
+            )),
+            "name uuid",
+        );
+
+        test_acp_search_reduce!(&se_anon, vec![acp], r_set, ex_anon);
+    }
+
     macro_rules! test_acp_modify {
         (
             $me:expr,
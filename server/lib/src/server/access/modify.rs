@@ -267,7 +267,7 @@ fn modify_ident_test(ident: &Identity) -> AccessBasicResult {
     debug!(event = %ident, "Access check for modify event");
 
     match ident.access_scope() {
-        AccessScope::ReadOnly | AccessScope::Synchronise => {
+        AccessScope::ReadOnly | AccessScope::Synchronise | AccessScope::CredentialUpdate => {
             security_access!("denied ❌ - identity access scope is not permitted to modify");
             return AccessBasicResult::Deny;
         }
@@ -434,6 +434,8 @@ fn modify_protected_entry_attrs<'a>(classes: &BTreeSet<String>) -> AccessModResu
             Attribute::Image,
             Attribute::DomainAllowEasterEggs,
             Attribute::DomainAllowAccountRecovery,
+            Attribute::DomainDenyMembershipCycles,
+            Attribute::DomainSelfWriteAttrs,
         ]);
     }
 
@@ -461,6 +463,12 @@ fn modify_protected_entry_attrs<'a>(classes: &BTreeSet<String>) -> AccessModResu
         constrain_attrs.extend([
             Attribute::AuthSessionExpiry,
             Attribute::AuthPasswordMinimumLength,
+            Attribute::AuthPasswordMinimumScore,
+            Attribute::AuthPasswordBreachCheck,
+            Attribute::AuthPasswordHistoryLength,
+            Attribute::AuthLockoutMaxAttempts,
+            Attribute::AuthLockoutDuration,
+            Attribute::CredentialMaxAge,
             Attribute::CredentialTypeMinimum,
             Attribute::PrivilegeExpiry,
             Attribute::WebauthnAttestationCaList,
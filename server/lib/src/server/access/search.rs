@@ -150,6 +150,12 @@ fn search_filter_entry(
             );
             return AccessSrchResult::Deny;
         }
+        AccessScope::CredentialUpdate => {
+            security_debug!(
+                "denied ❌ - identity access scope 'CredentialUpdate' is not permitted to search"
+            );
+            return AccessSrchResult::Deny;
+        }
         AccessScope::ReadOnly | AccessScope::ReadWrite => {
             // As you were
         }
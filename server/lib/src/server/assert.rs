@@ -328,6 +328,7 @@ impl QueryServerWriteTransaction<'_> {
                     let batch_modify_event = BatchModifyEvent {
                         ident: ident.clone(),
                         modset,
+                        expected_cids: BTreeMap::new(),
                     };
 
                     self.batch_modify(&batch_modify_event)?;
@@ -0,0 +1,155 @@
+//! Structured audit records of the changes that identities make to the database. These
+//! are stored as entries so that they replicate and are subject to access controls, and
+//! are indexed by actor, target and operation so they can be queried efficiently.
+
+use crate::prelude::*;
+use crate::valueset::ValueSetDateTime;
+use kanidm_proto::v1::{AuditOperation, AuditQuery, AuditRecord, AuditRecordPage};
+use std::collections::BTreeSet;
+use std::str::FromStr;
+use time::OffsetDateTime;
+
+/// The largest number of audit records that a single query may return.
+const AUDIT_QUERY_PAGE_LIMIT: usize = 256;
+
+impl QueryServerWriteTransaction<'_> {
+    /// Store a record that `ident` performed `operation` on the `targets`. Internal
+    /// operations are not recorded, which also prevents the creation of the record from
+    /// being recorded itself.
+    pub(crate) fn audit_record(
+        &mut self,
+        ident: &Identity,
+        operation: AuditOperation,
+        targets: BTreeSet<Uuid>,
+        attributes: &BTreeSet<Attribute>,
+    ) -> Result<(), OperationError> {
+        if ident.is_internal() || targets.is_empty() {
+            return Ok(());
+        }
+
+        let curtime_odt = self.get_curtime_odt();
+        let delete_after_odt = curtime_odt + DEFAULT_AUDIT_RECORD_RETENTION;
+
+        let mut e_record: EntryInitNew = Entry::new();
+        e_record.set_ava_set(
+            &Attribute::Class,
+            ValueSetIutf8::new(EntryClass::AuditRecord.into()),
+        );
+        e_record.set_ava_set(&Attribute::AuditActor, ValueSetUuid::new(ident.get_uuid()));
+        e_record.set_ava_set(
+            &Attribute::AuditOperation,
+            ValueSetIutf8::new(operation.as_str()),
+        );
+        if let Some(vs) = ValueSetUuid::from_iter(targets) {
+            e_record.set_ava_set(&Attribute::AuditTarget, vs);
+        }
+        if !attributes.is_empty() {
+            if let Some(vs) = ValueSetIutf8::from_iter(attributes.iter().map(|a| a.as_str())) {
+                e_record.set_ava_set(&Attribute::AuditAttribute, vs);
+            }
+        }
+        e_record.set_ava_set(&Attribute::AuditTime, ValueSetDateTime::new(curtime_odt));
+        e_record.set_ava_set(
+            &Attribute::DeleteAfter,
+            ValueSetDateTime::new(delete_after_odt),
+        );
+
+        self.internal_create(vec![e_record])
+    }
+}
+
+impl QueryServerReadTransaction<'_> {
+    /// Search the audit records visible to `ident`, returning the page of results
+    /// requested by the query, oldest first.
+    #[instrument(level = "debug", skip_all)]
+    pub fn audit_search(
+        &mut self,
+        ident: &Identity,
+        query: &AuditQuery,
+    ) -> Result<AuditRecordPage, OperationError> {
+        let mut conditions = vec![f_eq(Attribute::Class, EntryClass::AuditRecord.into())];
+
+        if let Some(actor) = query.actor {
+            conditions.push(f_eq(Attribute::AuditActor, PartialValue::Uuid(actor)));
+        }
+        if let Some(target) = query.target {
+            conditions.push(f_eq(Attribute::AuditTarget, PartialValue::Uuid(target)));
+        }
+        if let Some(operation) = query.operation {
+            conditions.push(f_eq(
+                Attribute::AuditOperation,
+                PartialValue::new_iutf8(operation.as_str()),
+            ));
+        }
+        if let Some(not_before) = query.not_before {
+            conditions.push(f_andnot(f_lt(
+                Attribute::AuditTime,
+                PartialValue::DateTime(not_before),
+            )));
+        }
+        if let Some(not_after) = query.not_after {
+            conditions.push(f_lt(
+                Attribute::AuditTime,
+                PartialValue::DateTime(not_after),
+            ));
+        }
+
+        let filter = filter!(f_and(conditions));
+
+        let entries = self.impersonate_search_ext(filter.clone(), filter, ident)?;
+
+        let mut records: Vec<AuditRecord> = entries
+            .iter()
+            .filter_map(|entry| {
+                let record = audit_record_from_entry(entry);
+                if record.is_none() {
+                    warn!(uuid = ?entry.get_uuid(), "audit record is incomplete or not readable");
+                }
+                record
+            })
+            .collect();
+
+        records.sort_unstable_by(|a, b| a.time.cmp(&b.time).then_with(|| a.uuid.cmp(&b.uuid)));
+
+        let limit = query
+            .limit
+            .unwrap_or(AUDIT_QUERY_PAGE_LIMIT)
+            .clamp(1, AUDIT_QUERY_PAGE_LIMIT);
+        let next_offset = query
+            .offset
+            .checked_add(limit)
+            .filter(|next| *next < records.len());
+
+        let records = records.into_iter().skip(query.offset).take(limit).collect();
+
+        Ok(AuditRecordPage {
+            records,
+            next_offset,
+        })
+    }
+}
+
+fn audit_record_from_entry(entry: &Entry<EntryReduced, EntryCommitted>) -> Option<AuditRecord> {
+    let actor = entry.get_ava_single_uuid(Attribute::AuditActor)?;
+    let operation = entry
+        .get_ava_single_iutf8(Attribute::AuditOperation)
+        .and_then(|op| AuditOperation::from_str(op).ok())?;
+    let targets = entry
+        .get_ava_set(Attribute::AuditTarget)
+        .and_then(|vs| vs.as_uuid_set())
+        .map(|set| set.iter().copied().collect())?;
+    let attributes = entry
+        .get_ava_as_iutf8(Attribute::AuditAttribute)
+        .map(|set| set.iter().cloned().collect())
+        .unwrap_or_default();
+    let time: OffsetDateTime = entry.get_ava_single_datetime(Attribute::AuditTime)?;
+
+    Some(AuditRecord {
+        uuid: entry.get_uuid(),
+        actor,
+        operation,
+        targets,
+        attributes,
+        time,
+    })
+}
@@ -9,6 +9,9 @@ pub type ModSetInvalid = BTreeMap<Uuid, ModifyList<ModifyInvalid>>;
 pub struct BatchModifyEvent {
     pub ident: Identity,
     pub modset: ModSetValid,
+    /// The cid that each listed entry must have last been changed at for the batch to be
+    /// applied. Entries that are not listed have no such precondition.
+    pub expected_cids: BTreeMap<Uuid, Cid>,
 }
 
 impl QueryServerWriteTransaction<'_> {
@@ -95,9 +98,29 @@ impl QueryServerWriteTransaction<'_> {
                 e
             })?;
         if !op_allow {
+            self.submit_access_denied(&me.ident, pre_candidates.iter().map(|e| e.get_uuid()));
             return Err(OperationError::AccessDenied);
         }
 
+        // Reject the batch if any entry has been changed since the version the caller expected.
+        let conflicts: Vec<_> = pre_candidates
+            .iter()
+            .filter_map(|e| {
+                let expected_cid = me.expected_cids.get(&e.get_uuid())?;
+                let current = e.get_last_changed();
+                (&current != expected_cid).then_some((e.get_uuid(), current))
+            })
+            .collect();
+        if let Some((_, current)) = conflicts.first() {
+            request_error!(
+                ?conflicts,
+                "batch modify: entries have changed since the expected versions"
+            );
+            return Err(OperationError::MD0001EntryVersionConflict(
+                current.to_string(),
+            ));
+        }
+
         // Clone a set of writeables.
         // Apply the modlist -> Remember, we have a set of origs
         // and the new modified ents.
@@ -245,6 +268,17 @@ impl QueryServerWriteTransaction<'_> {
             self.changed_flags.insert(ChangeFlag::FEATURE)
         }
 
+        if !self.changed_flags.contains(ChangeFlag::WEBHOOK)
+            && norm_cand
+                .iter()
+                .chain(pre_candidates.iter().map(|e| e.as_ref()))
+                .any(|e| {
+                    e.attribute_equality(Attribute::Class, &EntryClass::WebhookSubscription.into())
+                })
+        {
+            self.changed_flags.insert(ChangeFlag::WEBHOOK)
+        }
+
         if !self.changed_flags.contains(ChangeFlag::DOMAIN)
             && norm_cand
                 .iter()
@@ -318,6 +352,7 @@ impl QueryServerWriteTransaction<'_> {
         let bme = BatchModifyEvent {
             ident: Identity::from_internal(),
             modset,
+            expected_cids: BTreeMap::new(),
         };
         self.batch_modify(&bme)
     }
@@ -325,7 +360,9 @@ impl QueryServerWriteTransaction<'_> {
 
 #[cfg(test)]
 mod tests {
+    use super::{BatchModifyEvent, ModSetValid};
     use crate::prelude::*;
+    use std::collections::BTreeMap;
 
     #[qs_test]
     async fn test_batch_modify_basic(server: &QueryServer) {
@@ -374,4 +411,83 @@ mod tests {
         assert_eq!(ent_a.get_ava_single_utf8(Attribute::Description), Some("a"));
         assert_eq!(ent_b.get_ava_single_utf8(Attribute::Description), Some("b"));
     }
+
+    #[qs_test]
+    async fn test_batch_modify_expected_cids(server: &QueryServer) {
+        let uuid_a = Uuid::new_v4();
+        let uuid_b = Uuid::new_v4();
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        assert!(server_txn
+            .internal_create(vec![
+                entry_init!(
+                    (Attribute::Class, EntryClass::Object.to_value()),
+                    (Attribute::Uuid, Value::Uuid(uuid_a))
+                ),
+                entry_init!(
+                    (Attribute::Class, EntryClass::Object.to_value()),
+                    (Attribute::Uuid, Value::Uuid(uuid_b))
+                ),
+            ])
+            .is_ok());
+        let created_cid = server_txn.get_txn_cid().clone();
+        assert!(server_txn.commit().is_ok());
+
+        // Change only b, so that the version of b that was read is now stale.
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        assert!(server_txn
+            .internal_modify_uuid(
+                uuid_b,
+                &ModifyList::new_purge_and_set(Attribute::Description, Value::new_utf8s("b"))
+            )
+            .is_ok());
+        let modified_cid = server_txn.get_txn_cid().clone();
+        assert!(server_txn.commit().is_ok());
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        let modset: ModSetValid = [uuid_a, uuid_b]
+            .into_iter()
+            .map(|u| {
+                (
+                    u,
+                    ModifyList::new_purge_and_set(Attribute::Description, Value::new_utf8s("c"))
+                        .into_valid(),
+                )
+            })
+            .collect();
+
+        // a is current but b is stale, so nothing is applied.
+        let bme = BatchModifyEvent {
+            ident: Identity::from_internal(),
+            modset: modset.clone(),
+            expected_cids: BTreeMap::from([
+                (uuid_a, created_cid.clone()),
+                (uuid_b, created_cid.clone()),
+            ]),
+        };
+        assert_eq!(
+            server_txn.batch_modify(&bme),
+            Err(OperationError::MD0001EntryVersionConflict(
+                modified_cid.to_string()
+            ))
+        );
+        let ent_a = server_txn
+            .internal_search_uuid(uuid_a)
+            .expect("Failed to get entry.");
+        assert_eq!(ent_a.get_ava_single_utf8(Attribute::Description), None);
+
+        // With both versions current, the batch is applied.
+        let bme = BatchModifyEvent {
+            ident: Identity::from_internal(),
+            modset,
+            expected_cids: BTreeMap::from([(uuid_a, created_cid), (uuid_b, modified_cid)]),
+        };
+        assert!(server_txn.batch_modify(&bme).is_ok());
+
+        let ent_b = server_txn
+            .internal_search_uuid(uuid_b)
+            .expect("Failed to get entry.");
+        assert_eq!(ent_b.get_ava_single_utf8(Attribute::Description), Some("c"));
+        assert!(server_txn.commit().is_ok());
+    }
 }
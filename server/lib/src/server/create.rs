@@ -1,6 +1,9 @@
+use crate::event::BatchCreateEvent;
 use crate::prelude::*;
 use crate::server::CreateEvent;
 use crate::server::{ChangeFlag, Plugins};
+use kanidm_proto::constants::BATCH_CREATE_MAX_ENTRIES;
+use kanidm_proto::internal::BatchCreateStatus;
 
 impl QueryServerWriteTransaction<'_> {
     #[instrument(level = "debug", skip_all)]
@@ -17,71 +20,7 @@ impl QueryServerWriteTransaction<'_> {
             return Err(OperationError::EmptyRequest);
         }
 
-        // TODO #67: Do we need limits on number of creates, or do we constraint
-        // based on request size in the frontend?
-
-        // Copy the entries to a writeable form, this involves assigning a
-        // change id so we can track what's happening.
-        let candidates: Vec<Entry<EntryInit, EntryNew>> = ce.entries.clone();
-
-        // Do we have rights to perform these creates?
-        // create_allow_operation
-        let access = self.get_accesscontrols();
-        let op_allow = access
-            .create_allow_operation(ce, &candidates)
-            .map_err(|e| {
-                admin_error!("Failed to check create access {:?}", e);
-                e
-            })?;
-        if !op_allow {
-            return Err(OperationError::AccessDenied);
-        }
-
-        // Before we assign replication metadata, we need to assert these entries
-        // are valid to create within the set of replication transitions. This
-        // means they *can not* be recycled or tombstones!
-        if candidates.iter().any(|e| e.mask_recycled_ts().is_none()) {
-            warn!("Refusing to create invalid entries that are attempting to bypass replication state machine.");
-            return Err(OperationError::AccessDenied);
-        }
-
-        // Assign our replication metadata now, since we can proceed with this operation.
-        let mut candidates: Vec<Entry<EntryInvalid, EntryNew>> = candidates
-            .into_iter()
-            .map(|e| e.assign_cid(self.cid.clone(), &self.schema))
-            .collect();
-
-        // run any pre plugins, giving them the list of mutable candidates.
-        // pre-plugins are defined here in their correct order of calling!
-        // I have no intent to make these dynamic or configurable.
-        Plugins::run_pre_create_transform(self, &mut candidates, ce).map_err(|e| {
-            admin_error!("Create operation failed (pre_transform plugin), {:?}", e);
-            e
-        })?;
-
-        // Now, normalise AND validate!
-        let norm_cand = candidates
-            .into_iter()
-            .map(|e| {
-                e.validate(&self.schema)
-                    .map_err(|e| {
-                        admin_error!("Schema Violation in create validate {:?}", e);
-                        OperationError::SchemaViolation(e)
-                    })
-                    .map(|e| {
-                        // Then seal the changes?
-                        e.seal(&self.schema)
-                    })
-            })
-            .collect::<Result<Vec<EntrySealedNew>, _>>()?;
-
-        // Run any pre-create plugins now with schema validated entries.
-        // This is important for normalisation of certain types i.e. class
-        // or attributes for these checks.
-        Plugins::run_pre_create(self, &norm_cand, ce).map_err(|e| {
-            admin_error!("Create operation failed (plugin), {:?}", e);
-            e
-        })?;
+        let norm_cand = self.create_preflight(ce)?;
 
         // We may change from ce.entries later to something else?
         let commit_cand = self.be_txn.create(&self.cid, norm_cand).map_err(|e| {
@@ -145,6 +84,14 @@ impl QueryServerWriteTransaction<'_> {
             self.changed_flags.insert(ChangeFlag::FEATURE)
         }
 
+        if !self.changed_flags.contains(ChangeFlag::WEBHOOK)
+            && commit_cand.iter().any(|e| {
+                e.attribute_equality(Attribute::Class, &EntryClass::WebhookSubscription.into())
+            })
+        {
+            self.changed_flags.insert(ChangeFlag::WEBHOOK)
+        }
+
         if !self.changed_flags.contains(ChangeFlag::DOMAIN)
             && commit_cand
                 .iter()
@@ -199,6 +146,181 @@ impl QueryServerWriteTransaction<'_> {
         }
     }
 
+    /// Check and prepare the entries of a create event for writing. Nothing is written
+    /// to the database by this step.
+    fn create_preflight(
+        &mut self,
+        ce: &CreateEvent,
+    ) -> Result<Vec<EntrySealedNew>, OperationError> {
+        // TODO #67: Do we need limits on number of creates, or do we constraint
+        // based on request size in the frontend?
+
+        // Copy the entries to a writeable form, this involves assigning a
+        // change id so we can track what's happening.
+        let candidates: Vec<Entry<EntryInit, EntryNew>> = ce.entries.clone();
+
+        // Do we have rights to perform these creates?
+        // create_allow_operation
+        let access = self.get_accesscontrols();
+        let op_allow = access
+            .create_allow_operation(ce, &candidates)
+            .map_err(|e| {
+                admin_error!("Failed to check create access {:?}", e);
+                e
+            })?;
+        if !op_allow {
+            self.submit_access_denied(&ce.ident, []);
+            return Err(OperationError::AccessDenied);
+        }
+
+        // Before we assign replication metadata, we need to assert these entries
+        // are valid to create within the set of replication transitions. This
+        // means they *can not* be recycled or tombstones!
+        if candidates.iter().any(|e| e.mask_recycled_ts().is_none()) {
+            warn!("Refusing to create invalid entries that are attempting to bypass replication state machine.");
+            return Err(OperationError::AccessDenied);
+        }
+
+        // Assign our replication metadata now, since we can proceed with this operation.
+        let mut candidates: Vec<Entry<EntryInvalid, EntryNew>> = candidates
+            .into_iter()
+            .map(|e| e.assign_cid(self.cid.clone(), &self.schema))
+            .collect();
+
+        // run any pre plugins, giving them the list of mutable candidates.
+        // pre-plugins are defined here in their correct order of calling!
+        // I have no intent to make these dynamic or configurable.
+        Plugins::run_pre_create_transform(self, &mut candidates, ce).map_err(|e| {
+            admin_error!("Create operation failed (pre_transform plugin), {:?}", e);
+            e
+        })?;
+
+        // Now, normalise AND validate!
+        let norm_cand = candidates
+            .into_iter()
+            .map(|e| {
+                e.validate(&self.schema)
+                    .map_err(|e| {
+                        admin_error!("Schema Violation in create validate {:?}", e);
+                        OperationError::SchemaViolation(e)
+                    })
+                    .map(|e| {
+                        // Then seal the changes?
+                        e.seal(&self.schema)
+                    })
+            })
+            .collect::<Result<Vec<EntrySealedNew>, _>>()?;
+
+        // Run any pre-create plugins now with schema validated entries.
+        // This is important for normalisation of certain types i.e. class
+        // or attributes for these checks.
+        Plugins::run_pre_create(self, &norm_cand, ce).map_err(|e| {
+            admin_error!("Create operation failed (plugin), {:?}", e);
+            e
+        })?;
+
+        Ok(norm_cand)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    /// Create a batch of entries, reporting the outcome of each in the order they were
+    /// requested. Each entry is checked alone, and then against the other entries of the
+    /// batch for uniqueness, before any entry is written. If `all_or_nothing` is set and any
+    /// entry fails then nothing is written, and the valid entries are reported as skipped.
+    ///
+    /// Errors that can only be found once the entries are written, such as a reference to an
+    /// entry that does not exist, fail the whole batch.
+    pub fn create_batch(
+        &mut self,
+        bce: BatchCreateEvent,
+    ) -> Result<Vec<BatchCreateStatus>, OperationError> {
+        if !bce.ident.is_internal() {
+            security_info!(name = %bce.ident, "batch create initiator");
+        }
+
+        if bce.entries.is_empty() {
+            request_error!("create_batch: empty create request");
+            return Err(OperationError::EmptyRequest);
+        }
+
+        if bce.entries.len() > BATCH_CREATE_MAX_ENTRIES {
+            request_error!(
+                entries = bce.entries.len(),
+                limit = BATCH_CREATE_MAX_ENTRIES,
+                "create_batch: too many entries in request"
+            );
+            return Err(OperationError::ResourceLimit);
+        }
+
+        let BatchCreateEvent {
+            ident,
+            entries,
+            all_or_nothing,
+        } = bce;
+
+        // Check each entry on its own so that any error can be attributed to it.
+        let mut checked: Vec<Result<(EntryInitNew, EntrySealedNew), OperationError>> = entries
+            .into_iter()
+            .map(|entry| {
+                let entry = entry?;
+                let ce = CreateEvent::new_impersonate_identity(ident.clone(), vec![entry.clone()]);
+                self.create_preflight(&ce)?
+                    .pop()
+                    .map(|sealed| (entry, sealed))
+                    .ok_or(OperationError::InvalidState)
+            })
+            .collect();
+
+        // Entries that were valid alone may still conflict with each other.
+        let conflicts = Plugins::find_create_unique_conflicts(
+            self,
+            checked
+                .iter()
+                .filter_map(|r| r.as_ref().ok())
+                .map(|(_, e)| e),
+        )?;
+
+        for result in checked.iter_mut() {
+            let conflict = match result {
+                Ok((_, sealed)) => conflicts.get(&sealed.get_uuid()),
+                Err(_) => None,
+            };
+            if let Some(attrs) = conflict {
+                *result = Err(OperationError::AttributeUniqueness(
+                    attrs.iter().cloned().collect(),
+                ));
+            }
+        }
+
+        let any_failed = checked.iter().any(|r| r.is_err());
+
+        let entries: Vec<EntryInitNew> = if all_or_nothing && any_failed {
+            Vec::with_capacity(0)
+        } else {
+            checked
+                .iter()
+                .filter_map(|r| r.as_ref().ok())
+                .map(|(entry, _)| entry.clone())
+                .collect()
+        };
+
+        if !entries.is_empty() {
+            let ce = CreateEvent::new_impersonate_identity(ident, entries);
+            self.create(&ce)?;
+        }
+
+        let created = !(all_or_nothing && any_failed);
+
+        Ok(checked
+            .into_iter()
+            .map(|result| match result {
+                Ok((_, sealed)) if created => BatchCreateStatus::Created(sealed.get_uuid()),
+                Ok(_) => BatchCreateStatus::Skipped,
+                Err(e) => BatchCreateStatus::Failed(e),
+            })
+            .collect())
+    }
+
     pub fn internal_create(&mut self, entries: Vec<EntryInitNew>) -> Result<(), OperationError> {
         let ce = CreateEvent::new_internal(entries);
         self.create(&ce).map(|_| ())
@@ -216,7 +338,9 @@ impl QueryServerWriteTransaction<'_> {
 
 #[cfg(test)]
 mod tests {
+    use crate::event::BatchCreateEvent;
     use crate::prelude::*;
+    use kanidm_proto::internal::BatchCreateStatus;
     use std::sync::Arc;
 
     #[qs_test]
@@ -338,4 +462,102 @@ mod tests {
         assert!(server_a_txn.commit().is_ok());
         assert!(server_b_txn.commit().is_ok());
     }
+
+    fn mixed_validity_batch() -> Vec<EntryInitNew> {
+        let person = |name: &str, uuid: Uuid| {
+            entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Person.to_value()),
+                (Attribute::Class, EntryClass::Account.to_value()),
+                (Attribute::Name, Value::new_iname(name)),
+                (Attribute::Uuid, Value::Uuid(uuid)),
+                (Attribute::DisplayName, Value::new_utf8s(name))
+            )
+        };
+
+        let mut invalid = person(
+            "batch_invalid",
+            uuid!("3c4a0d5e-4f20-4b4c-9f45-4b1b1e2c0a02"),
+        );
+        invalid.remove_ava(Attribute::DisplayName);
+
+        vec![
+            person("batch_valid", uuid!("3c4a0d5e-4f20-4b4c-9f45-4b1b1e2c0a01")),
+            invalid,
+            // These two conflict with each other, but not with anything existing.
+            person("batch_dup", uuid!("3c4a0d5e-4f20-4b4c-9f45-4b1b1e2c0a03")),
+            person("batch_dup", uuid!("3c4a0d5e-4f20-4b4c-9f45-4b1b1e2c0a04")),
+        ]
+    }
+
+    #[qs_test]
+    async fn test_create_batch_partial(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+
+        let bce = BatchCreateEvent::new_internal(mixed_validity_batch(), false);
+        let results = server_txn.create_batch(bce).expect("batch create failed");
+
+        assert_eq!(results.len(), 4);
+        assert!(matches!(
+            results[0],
+            BatchCreateStatus::Created(u) if u == uuid!("3c4a0d5e-4f20-4b4c-9f45-4b1b1e2c0a01")
+        ));
+        assert!(matches!(
+            results[1],
+            BatchCreateStatus::Failed(OperationError::SchemaViolation(_))
+        ));
+        assert!(matches!(
+            results[2],
+            BatchCreateStatus::Failed(OperationError::AttributeUniqueness(_))
+        ));
+        assert!(matches!(
+            results[3],
+            BatchCreateStatus::Failed(OperationError::AttributeUniqueness(_))
+        ));
+
+        assert!(server_txn
+            .internal_search_uuid(uuid!("3c4a0d5e-4f20-4b4c-9f45-4b1b1e2c0a01"))
+            .is_ok());
+        assert!(server_txn
+            .internal_search_uuid(uuid!("3c4a0d5e-4f20-4b4c-9f45-4b1b1e2c0a03"))
+            .is_err());
+
+        assert!(server_txn.commit().is_ok());
+    }
+
+    #[qs_test]
+    async fn test_create_batch_all_or_nothing(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+
+        let bce = BatchCreateEvent::new_internal(mixed_validity_batch(), true);
+        let results = server_txn.create_batch(bce).expect("batch create failed");
+
+        assert_eq!(results.len(), 4);
+        assert!(matches!(results[0], BatchCreateStatus::Skipped));
+        assert!(matches!(
+            results[1],
+            BatchCreateStatus::Failed(OperationError::SchemaViolation(_))
+        ));
+        assert!(matches!(
+            results[2],
+            BatchCreateStatus::Failed(OperationError::AttributeUniqueness(_))
+        ));
+        assert!(matches!(
+            results[3],
+            BatchCreateStatus::Failed(OperationError::AttributeUniqueness(_))
+        ));
+
+        // Nothing was created.
+        assert!(server_txn
+            .internal_search_uuid(uuid!("3c4a0d5e-4f20-4b4c-9f45-4b1b1e2c0a01"))
+            .is_err());
+
+        // A batch where every entry is valid is created in full.
+        let valid: Vec<_> = mixed_validity_batch().into_iter().take(1).collect();
+        let bce = BatchCreateEvent::new_internal(valid, true);
+        let results = server_txn.create_batch(bce).expect("batch create failed");
+        assert!(matches!(results[..], [BatchCreateStatus::Created(_)]));
+
+        assert!(server_txn.commit().is_ok());
+    }
 }
@@ -0,0 +1,283 @@
+//! Creation templates hold the default values and group memberships that an organisation
+//! applies to every new person or service account. A template is selected when the
+//! account is created, and is applied within the same write transaction as the create so
+//! that an account is never left with only part of its template.
+
+use crate::prelude::*;
+use crate::schema::SchemaTransaction;
+use std::collections::BTreeSet;
+
+/// Within the value of a template default, this is replaced by the name of the new entry.
+const TEMPLATE_NAME_PLACEHOLDER: &str = "{name}";
+
+/// The name substituted for the placeholder when a template is validated on save.
+const TEMPLATE_VALIDATION_NAME: &str = "template";
+
+/// Attributes that identify an entry, and so can never be defaulted by a template.
+const TEMPLATE_DENIED_ATTRS: [Attribute; 3] = [Attribute::Name, Attribute::Spn, Attribute::Uuid];
+
+/// Parse a template default of the form `attribute: value`, resolving the value with
+/// the syntax of the attribute for an entry called `name`.
+pub(crate) fn resolve_template_default(
+    qs: &mut QueryServerWriteTransaction,
+    default: &str,
+    name: &str,
+) -> Result<(Attribute, Value), OperationError> {
+    let Some((attr, value)) = default.split_once(':') else {
+        return Err(OperationError::CT0003CreationTemplateDefaultInvalid);
+    };
+
+    let attr = Attribute::from(attr.trim());
+    let value = value.trim().replace(TEMPLATE_NAME_PLACEHOLDER, name);
+
+    if value.is_empty() || TEMPLATE_DENIED_ATTRS.contains(&attr) {
+        return Err(OperationError::CT0003CreationTemplateDefaultInvalid);
+    }
+
+    let value = qs.clone_value(&attr, &value).map_err(|err| {
+        admin_warn!(?err, %attr, "Unable to parse creation template default");
+        OperationError::CT0003CreationTemplateDefaultInvalid
+    })?;
+
+    qs.get_schema()
+        .get_attributes()
+        .get(&attr)
+        .ok_or(OperationError::CT0003CreationTemplateDefaultInvalid)?
+        .validate_value(&attr, &value)
+        .map_err(|err| {
+            admin_warn!(?err, %attr, "Creation template default does not conform to schema");
+            OperationError::CT0003CreationTemplateDefaultInvalid
+        })?;
+
+    Ok((attr, value))
+}
+
+/// Check that every default of a template can be resolved, so that invalid templates are
+/// refused when they are saved rather than when they are first used.
+pub(crate) fn validate_template_defaults<VALID, STATE>(
+    qs: &mut QueryServerWriteTransaction,
+    entry: &Entry<VALID, STATE>,
+) -> Result<(), OperationError> {
+    let defaults: Vec<String> = entry
+        .get_ava_set(Attribute::TemplateDefault)
+        .and_then(|vs| vs.as_utf8_iter())
+        .map(|iter| iter.map(str::to_string).collect())
+        .unwrap_or_default();
+
+    for default in defaults {
+        resolve_template_default(qs, &default, TEMPLATE_VALIDATION_NAME)?;
+    }
+    Ok(())
+}
+
+impl QueryServerWriteTransaction<'_> {
+    /// Create the entries of this event with the defaults of the named template, then add
+    /// them to the groups of the template. Classes from the template are added to the
+    /// entry, while any other default is only applied if the request did not provide a
+    /// value for that attribute.
+    #[instrument(level = "debug", skip_all)]
+    pub fn create_with_template(
+        &mut self,
+        ce: &CreateEvent,
+        template: &str,
+    ) -> Result<(), OperationError> {
+        let template_entry = self
+            .internal_search(filter!(f_and!([
+                f_eq(Attribute::Class, EntryClass::CreationTemplate.into()),
+                f_eq(Attribute::Name, PartialValue::new_iname(template))
+            ])))?
+            .pop()
+            .ok_or(OperationError::CT0001CreationTemplateNotFound)?;
+
+        let defaults: Vec<String> = template_entry
+            .get_ava_set(Attribute::TemplateDefault)
+            .and_then(|vs| vs.as_utf8_iter())
+            .map(|iter| iter.map(str::to_string).collect())
+            .unwrap_or_default();
+
+        // Resolve the groups before anything is created, so that a template naming a
+        // group that does not exist fails the create cleanly.
+        let group_names: Vec<String> = template_entry
+            .get_ava_iter_iname(Attribute::TemplateGroup)
+            .map(|iter| iter.map(str::to_string).collect())
+            .unwrap_or_default();
+
+        let mut groups = BTreeSet::new();
+        for group_name in group_names {
+            let group = self
+                .name_to_uuid(&group_name)
+                .and_then(|uuid| self.internal_search_uuid(uuid))
+                .ok()
+                .filter(|e| e.attribute_equality(Attribute::Class, &EntryClass::Group.into()))
+                .ok_or_else(|| {
+                    admin_warn!(%template, group = %group_name, "Creation template group not found");
+                    OperationError::CT0002CreationTemplateGroupNotFound
+                })?;
+            groups.insert(group.get_uuid());
+        }
+
+        let mut entries = ce.entries.clone();
+        for entry in entries.iter_mut() {
+            let name = entry
+                .get_ava_single_iname(Attribute::Name)
+                .unwrap_or_default()
+                .to_string();
+            let provided: BTreeSet<Attribute> = entry.attr_keys().cloned().collect();
+
+            for default in defaults.iter() {
+                let (attr, value) = resolve_template_default(self, default, &name)?;
+                if attr == Attribute::Class || !provided.contains(&attr) {
+                    entry.add_ava(attr, value);
+                }
+            }
+        }
+
+        let ce = CreateEvent {
+            ident: ce.ident.clone(),
+            entries,
+            return_created_uuids: true,
+        };
+
+        let created = self.create(&ce)?.unwrap_or_default();
+
+        if groups.is_empty() || created.is_empty() {
+            return Ok(());
+        }
+
+        // Memberships are added internally, as the template was written by an administrator
+        // who is trusted to manage these groups.
+        self.internal_batch_modify(groups.into_iter().map(|group| {
+            let modlist = ModifyList::new_list(
+                created
+                    .iter()
+                    .map(|uuid| Modify::Present(Attribute::Member, Value::Refer(*uuid)))
+                    .collect(),
+            );
+            (group, modlist)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn template_entry(groups: &[&str]) -> EntryInitNew {
+        let mut e = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::CreationTemplate.to_value()),
+            (Attribute::Name, Value::new_iname("staff")),
+            (
+                Attribute::TemplateDefault,
+                Value::new_utf8s("class: posixaccount")
+            ),
+            (
+                Attribute::TemplateDefault,
+                Value::new_utf8s("loginshell: /bin/zsh")
+            ),
+            (
+                Attribute::TemplateDefault,
+                Value::new_utf8s("mail: {name}@example.com")
+            )
+        );
+        for group in groups {
+            e.add_ava(Attribute::TemplateGroup, Value::new_iname(group));
+        }
+        e
+    }
+
+    fn group_entry(name: &str, uuid: Uuid) -> EntryInitNew {
+        entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Name, Value::new_iname(name)),
+            (Attribute::Uuid, Value::Uuid(uuid))
+        )
+    }
+
+    fn person_create_event(name: &str) -> CreateEvent {
+        CreateEvent::new_internal(vec![entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::Person.to_value()),
+            (Attribute::Name, Value::new_iname(name)),
+            (Attribute::DisplayName, Value::new_utf8s(name))
+        )])
+    }
+
+    #[qs_test]
+    async fn test_create_with_template(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+
+        let group_a = Uuid::new_v4();
+        let group_b = Uuid::new_v4();
+
+        assert!(server_txn
+            .internal_create(vec![
+                group_entry("staff_a", group_a),
+                group_entry("staff_b", group_b),
+                template_entry(&["staff_a", "staff_b"]),
+            ])
+            .is_ok());
+
+        assert_eq!(
+            server_txn.create_with_template(&person_create_event("testperson"), "missing"),
+            Err(OperationError::CT0001CreationTemplateNotFound)
+        );
+
+        assert!(server_txn
+            .create_with_template(&person_create_event("testperson"), "staff")
+            .is_ok());
+
+        let person = server_txn
+            .internal_search(filter!(f_eq(
+                Attribute::Name,
+                PartialValue::new_iname("testperson")
+            )))
+            .unwrap()
+            .pop()
+            .expect("person was not created");
+
+        assert!(person.attribute_equality(Attribute::Class, &EntryClass::PosixAccount.into()));
+        assert_eq!(
+            person.get_ava_single_iutf8(Attribute::LoginShell),
+            Some("/bin/zsh")
+        );
+        assert!(person
+            .get_ava_iter_mail(Attribute::Mail)
+            .is_some_and(|mut mail| mail.any(|m| m == "testperson@example.com")));
+
+        let memberof = person
+            .get_ava_refer(Attribute::MemberOf)
+            .expect("person has no memberships");
+        assert!(memberof.contains(&group_a));
+        assert!(memberof.contains(&group_b));
+
+        assert!(server_txn.commit().is_ok());
+    }
+
+    #[qs_test]
+    async fn test_create_with_template_missing_group(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+
+        assert!(server_txn
+            .internal_create(vec![template_entry(&["does_not_exist"])])
+            .is_ok());
+
+        assert_eq!(
+            server_txn.create_with_template(&person_create_event("testperson"), "staff"),
+            Err(OperationError::CT0002CreationTemplateGroupNotFound)
+        );
+
+        // Nothing was created.
+        assert!(server_txn
+            .internal_search(filter!(f_eq(
+                Attribute::Name,
+                PartialValue::new_iname("testperson")
+            )))
+            .unwrap()
+            .is_empty());
+
+        assert!(server_txn.commit().is_ok());
+    }
+}
@@ -1,7 +1,8 @@
 use crate::prelude::*;
+use crate::schema::{ReferenceDeletePolicy, SchemaTransaction};
 use crate::server::DeleteEvent;
 use crate::server::{ChangeFlag, Plugins};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 impl QueryServerWriteTransaction<'_> {
     #[allow(clippy::cognitive_complexity)]
@@ -35,6 +36,7 @@ impl QueryServerWriteTransaction<'_> {
                 e
             })?;
         if !op_allow {
+            self.submit_access_denied(&de.ident, pre_candidates.iter().map(|e| e.get_uuid()));
             return Err(OperationError::AccessDenied);
         }
 
@@ -52,30 +54,63 @@ impl QueryServerWriteTransaction<'_> {
 
         // ======= Access Control and Invariants Checked !!! ========
 
-        // We now extend pre-candidates with anything that will be cascade-deleted.
-        let references_filt = filter!(f_or(
-            pre_candidates
-                .iter()
-                .map(|entry| { f_eq(Attribute::Refers, PartialValue::Refer(entry.get_uuid())) })
-                .collect(),
-        ));
-
-        let mut pre_cascade_delete_candidates = self
-            .internal_search(references_filt)
-            .inspect_err(|err| error!(?err, "unable to find reference entries"))?;
-
-        #[cfg(any(test, debug_assertions))]
-        {
-            use std::collections::BTreeSet;
+        // We now extend pre-candidates with anything that will be cascade-deleted. This is
+        // any reference entry that refers to a deleted entry, as well as any entry that
+        // references a deleted entry through an attribute with the cascade delete policy.
+        // Cascaded entries may be referenced in turn, so we repeat until nothing new is found.
+        let cascade_attrs: Vec<Attribute> = self
+            .get_schema()
+            .get_reference_types()
+            .values()
+            .filter(|a| a.on_delete == ReferenceDeletePolicy::Cascade)
+            .map(|a| a.name.clone())
+            .collect();
 
-            let candidate_uuids: BTreeSet<_> =
-                pre_candidates.iter().map(|e| e.get_uuid()).collect();
-            let ref_candidate_uuids: BTreeSet<_> = pre_cascade_delete_candidates
-                .iter()
-                .map(|e| e.get_uuid())
-                .collect();
+        let mut delete_uuids: BTreeSet<Uuid> =
+            pre_candidates.iter().map(|e| e.get_uuid()).collect();
+        let mut pending: Vec<Uuid> = delete_uuids.iter().copied().collect();
+        let mut pre_cascade_delete_candidates = Vec::with_capacity(0);
+
+        while !pending.is_empty() {
+            let references_filt = filter!(f_or(
+                pending
+                    .iter()
+                    .flat_map(|u| {
+                        std::iter::once(Attribute::Refers)
+                            .chain(cascade_attrs.iter().cloned())
+                            .map(move |attr| f_eq(attr, PartialValue::Refer(*u)))
+                    })
+                    .collect(),
+            ));
+
+            let found = self
+                .internal_search(references_filt)
+                .inspect_err(|err| error!(?err, "unable to find reference entries"))?;
+
+            pending.clear();
+            for entry in found {
+                if delete_uuids.insert(entry.get_uuid()) {
+                    pending.push(entry.get_uuid());
+                    pre_cascade_delete_candidates.push(entry);
+                }
+            }
+        }
 
-            assert!(candidate_uuids.is_disjoint(&ref_candidate_uuids));
+        // The initiator must be able to delete the cascaded entries too, else a cascade
+        // could remove entries that they have no access to.
+        let access = self.get_accesscontrols();
+        let op_allow = access
+            .delete_allow_operation(de, &pre_cascade_delete_candidates)
+            .map_err(|e| {
+                admin_error!("Failed to check cascade delete access {:?}", e);
+                e
+            })?;
+        if !op_allow {
+            self.submit_access_denied(
+                &de.ident,
+                pre_cascade_delete_candidates.iter().map(|e| e.get_uuid()),
+            );
+            return Err(OperationError::AccessDenied);
         }
 
         let mut cascade_delete_candidates: Vec<Entry<EntryInvalid, EntryCommitted>> =
@@ -90,7 +125,10 @@ impl QueryServerWriteTransaction<'_> {
                 // These entries are the ones that are being deleted by cascade, so we mark them
                 // as such.
                 .map(|mut entry| {
-                    if let Some(refer_uuid) = entry.get_ava_single_refer(Attribute::Refers) {
+                    if let Some(refer_uuid) = entry
+                        .get_ava_single_refer(Attribute::Refers)
+                        .filter(|u| delete_uuids.contains(u))
+                    {
                         // Stash the entry that triggered our deleted in this attribute. This
                         // allows us to restore this linkage on revive, and also being a uuid instead
                         // of a refers means that refint won't clean this linkage.
@@ -257,6 +295,14 @@ impl QueryServerWriteTransaction<'_> {
             self.changed_flags.insert(ChangeFlag::FEATURE)
         }
 
+        if !self.changed_flags.contains(ChangeFlag::WEBHOOK)
+            && del_cand.iter().any(|e| {
+                e.attribute_equality(Attribute::Class, &EntryClass::WebhookSubscription.into())
+            })
+        {
+            self.changed_flags.insert(ChangeFlag::WEBHOOK)
+        }
+
         if !self.changed_flags.contains(ChangeFlag::DOMAIN)
             && del_cand
                 .iter()
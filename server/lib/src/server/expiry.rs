@@ -0,0 +1,383 @@
+//! Scheduled deactivation of accounts that reach their `account_expire` time.
+//!
+//! Authentication of an expired account is already refused, but any sessions and tokens
+//! that were issued before the account expired remain on the entry. This task revokes them
+//! once the account expires, and optionally notifies (via security events) that an account
+//! is approaching its expiry so that it can be extended if needed.
+
+use crate::prelude::*;
+use crate::server::security_event::{SecurityEvent, SecurityEventKind, SecurityEventOutcome};
+use crate::value::SessionState;
+use time::OffsetDateTime;
+
+/// When to notify that an account is approaching its expiry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountExpiryNotice {
+    /// How long before the account expires that the first notice is sent.
+    pub window: Duration,
+    /// How often the notice is repeated until the account expires. If zero, only the
+    /// first notice is sent.
+    pub interval: Duration,
+}
+
+impl AccountExpiryNotice {
+    /// Determine if a notice for an account expiring at `expire` fell due in the period
+    /// `(since, now]`. If more than one fell due in the period only one is sent.
+    fn is_due(&self, expire: Duration, since: Duration, now: Duration) -> bool {
+        let window = self.window.as_secs();
+        let interval = self.interval.as_secs();
+        let expire = expire.as_secs();
+        let start = expire.saturating_sub(window);
+
+        let now = now.as_secs();
+        if now < start || window == 0 {
+            return false;
+        }
+
+        // The last notice is the final one that falls before the expiry itself.
+        let last_notice = if interval == 0 {
+            0
+        } else {
+            window.div_ceil(interval) - 1
+        };
+        let notice = (now - start)
+            .checked_div(interval)
+            .unwrap_or(0)
+            .min(last_notice);
+        let due = start + notice * interval;
+
+        due > since.as_secs()
+    }
+}
+
+/// The outcome of processing account expiry.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AccountExpiryReport {
+    /// The number of accounts that were notified of their upcoming expiry.
+    pub notified: usize,
+    /// The number of expired accounts that had their sessions and tokens revoked.
+    pub deactivated: usize,
+}
+
+impl QueryServerWriteTransaction<'_> {
+    /// Notify of accounts approaching their expiry, and revoke every session and token of
+    /// accounts that have expired. `since` is the time that this was last processed, so that
+    /// each notice is only sent once. Revocation is idempotent, and is applied in a single
+    /// write.
+    #[instrument(level = "debug", skip_all)]
+    pub fn process_account_expiry(
+        &mut self,
+        since: Duration,
+        notice: Option<&AccountExpiryNotice>,
+    ) -> Result<AccountExpiryReport, OperationError> {
+        let ct = self.get_curtime();
+        let window = notice.map(|n| n.window).unwrap_or_default();
+
+        let filter = filter!(f_and!([
+            f_eq(Attribute::Class, EntryClass::Account.into()),
+            f_pres(Attribute::AccountExpire),
+            // A notice is due from the start of the window, inclusive.
+            f_andnot(f_gt(
+                Attribute::AccountExpire,
+                PartialValue::DateTime(OffsetDateTime::UNIX_EPOCH + ct + window)
+            ))
+        ]));
+
+        let entries = self.internal_search(filter)?;
+
+        let mut report = AccountExpiryReport::default();
+        let mut modset = Vec::with_capacity(entries.len());
+
+        for entry in entries.iter() {
+            let Some(expire) = entry.get_ava_single_datetime(Attribute::AccountExpire) else {
+                continue;
+            };
+            let expire =
+                Duration::try_from(expire - OffsetDateTime::UNIX_EPOCH).unwrap_or_default();
+            let uuid = entry.get_uuid();
+
+            // The expiry time itself is still within the validity window of the account.
+            if ct <= expire {
+                if notice.is_some_and(|n| n.is_due(expire, since, ct)) {
+                    security_info!(account = %uuid, "account is approaching its expiry");
+                    self.submit_security_event(
+                        SecurityEvent::new(
+                            SecurityEventKind::AccountExpiryNotice,
+                            SecurityEventOutcome::Success,
+                            ct,
+                        )
+                        .with_targets([uuid]),
+                    );
+                    report.notified += 1;
+                }
+                continue;
+            }
+
+            let modlist = Self::revoke_all_sessions_modlist(entry);
+            if !modlist.is_empty() {
+                modset.push((uuid, modlist));
+            }
+        }
+
+        if modset.is_empty() {
+            return Ok(report);
+        }
+
+        let targets: Vec<Uuid> = modset.iter().map(|(uuid, _)| *uuid).collect();
+        self.internal_batch_modify(modset.into_iter())?;
+
+        report.deactivated = targets.len();
+        security_info!(?targets, "revoked sessions of expired accounts");
+        for uuid in targets {
+            self.submit_security_event(
+                SecurityEvent::new(
+                    SecurityEventKind::AccountDeactivated,
+                    SecurityEventOutcome::Success,
+                    ct,
+                )
+                .with_targets([uuid]),
+            );
+        }
+
+        Ok(report)
+    }
+
+    /// Remove every session and api token that is still active on `entry`. Removal of a
+    /// session marks it as revoked, which also revokes the oauth2 sessions that it is the
+    /// parent of.
+    fn revoke_all_sessions_modlist(entry: &EntrySealedCommitted) -> ModifyList<ModifyInvalid> {
+        let uat_sessions = entry
+            .get_ava_as_session_map(Attribute::UserAuthTokenSession)
+            .into_iter()
+            .flatten()
+            .filter(|(_, session)| !matches!(session.state, SessionState::RevokedAt(_)))
+            .map(|(id, _)| (Attribute::UserAuthTokenSession, *id));
+
+        let oauth2_sessions = entry
+            .get_ava_as_oauth2session_map(Attribute::OAuth2Session)
+            .into_iter()
+            .flatten()
+            .filter(|(_, session)| !matches!(session.state, SessionState::RevokedAt(_)))
+            .map(|(id, _)| (Attribute::OAuth2Session, *id));
+
+        let api_tokens = entry
+            .get_ava_as_apitoken_map(Attribute::ApiTokenSession)
+            .into_iter()
+            .flatten()
+            .map(|(id, _)| (Attribute::ApiTokenSession, *id));
+
+        ModifyList::new_list(
+            uat_sessions
+                .chain(oauth2_sessions)
+                .chain(api_tokens)
+                .map(|(attr, id)| Modify::Removed(attr, PartialValue::Refer(id)))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AccountExpiryNotice;
+    use crate::credential::Credential;
+    use crate::prelude::*;
+    use crate::server::security_event::SecurityEventKind;
+    use crate::value::{ApiToken, AuthType, Session, SessionState};
+    use kanidm_lib_crypto::CryptoPolicy;
+    use time::OffsetDateTime;
+
+    const HOUR: u64 = 60 * 60;
+    const DAY: u64 = 24 * HOUR;
+
+    #[test]
+    fn test_account_expiry_notice_is_due() {
+        let notice = AccountExpiryNotice {
+            window: Duration::from_secs(3 * DAY),
+            interval: Duration::from_secs(DAY),
+        };
+        let expire = Duration::from_secs(10 * DAY);
+        let is_due = |since: u64, now: u64| {
+            notice.is_due(expire, Duration::from_secs(since), Duration::from_secs(now))
+        };
+
+        // Before the window.
+        assert!(!is_due(0, 7 * DAY - 1));
+        // Each notice is due once.
+        assert!(is_due(7 * DAY - 1, 7 * DAY));
+        assert!(!is_due(7 * DAY, 8 * DAY - 1));
+        assert!(is_due(8 * DAY - 1, 8 * DAY));
+        assert!(is_due(8 * DAY, 9 * DAY));
+        // There is no notice at the expiry itself.
+        assert!(!is_due(9 * DAY, 10 * DAY));
+        assert!(!is_due(9 * DAY, 11 * DAY));
+        // Missed notices are only sent once.
+        assert!(is_due(0, 11 * DAY));
+
+        // Without an interval, only the first notice is sent.
+        let notice = AccountExpiryNotice {
+            window: Duration::from_secs(3 * DAY),
+            interval: Duration::ZERO,
+        };
+        assert!(notice.is_due(
+            expire,
+            Duration::from_secs(7 * DAY - 1),
+            Duration::from_secs(7 * DAY)
+        ));
+        assert!(!notice.is_due(
+            expire,
+            Duration::from_secs(7 * DAY),
+            Duration::from_secs(9 * DAY)
+        ));
+    }
+
+    #[qs_test]
+    async fn test_account_expiry_notify_and_deactivate(server: &QueryServer) {
+        let mut event_rx = server
+            .security_events()
+            .subscribe(64)
+            .expect("failed to subscribe");
+
+        let t0 = duration_from_epoch_now();
+        let t0_odt = OffsetDateTime::UNIX_EPOCH + t0;
+        let expire = t0 + Duration::from_secs(10 * DAY);
+        let expire_odt = OffsetDateTime::UNIX_EPOCH + expire;
+
+        let notice = AccountExpiryNotice {
+            window: Duration::from_secs(3 * DAY),
+            interval: Duration::from_secs(DAY),
+        };
+
+        let cred = Credential::new_password_only(
+            &CryptoPolicy::minimum(),
+            "test_password",
+            OffsetDateTime::UNIX_EPOCH,
+        )
+        .expect("failed to create credential");
+
+        let person_uuid = Uuid::new_v4();
+        let session_id = Uuid::new_v4();
+        let svc_uuid = Uuid::new_v4();
+        let token_id = Uuid::new_v4();
+
+        let mut server_txn = server.write(t0).await.expect("txn");
+        let e_person = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Person.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Name, Value::new_iname("expiring_person")),
+            (Attribute::Uuid, Value::Uuid(person_uuid)),
+            (Attribute::DisplayName, Value::new_utf8s("Expiring Person")),
+            (Attribute::AccountExpire, Value::DateTime(expire_odt)),
+            (
+                Attribute::PrimaryCredential,
+                Value::Cred("primary".to_string(), cred.clone())
+            ),
+            (
+                Attribute::UserAuthTokenSession,
+                Value::Session(
+                    session_id,
+                    Session {
+                        label: "label".to_string(),
+                        state: SessionState::NeverExpires,
+                        issued_at: t0_odt,
+                        issued_by: IdentityId::User(person_uuid),
+                        cred_id: cred.uuid,
+                        scope: SessionScope::ReadOnly,
+                        type_: AuthType::Passkey,
+                        ext_metadata: Default::default(),
+                        source_ip: None,
+                        user_agent: None,
+                    },
+                )
+            )
+        );
+        let e_svc = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::ServiceAccount.to_value()),
+            (Attribute::Name, Value::new_iname("expiring_service")),
+            (Attribute::Uuid, Value::Uuid(svc_uuid)),
+            (Attribute::DisplayName, Value::new_utf8s("Expiring Service")),
+            (Attribute::AccountExpire, Value::DateTime(expire_odt)),
+            (
+                Attribute::ApiTokenSession,
+                Value::ApiToken(
+                    token_id,
+                    ApiToken {
+                        label: "label".to_string(),
+                        expiry: None,
+                        issued_at: t0_odt,
+                        issued_by: IdentityId::User(svc_uuid),
+                        scope: ApiTokenScope::ReadOnly,
+                        operation_scopes: Default::default(),
+                    },
+                )
+            )
+        );
+        server_txn
+            .internal_create(vec![e_person, e_svc])
+            .expect("failed to create accounts");
+        server_txn.commit().expect("failed to commit");
+
+        // Advance the clock in steps past the expiry, as the scheduled task would.
+        let mut since = t0;
+        let mut notified = 0;
+        let mut deactivated = 0;
+        for step in 1..=48 {
+            let ct = t0 + Duration::from_secs(step * 6 * HOUR);
+            let mut server_txn = server.write(ct).await.expect("txn");
+            let report = server_txn
+                .process_account_expiry(since, Some(&notice))
+                .expect("failed to process account expiry");
+            server_txn.commit().expect("failed to commit");
+            since = ct;
+
+            notified += report.notified;
+            deactivated += report.deactivated;
+
+            if ct <= expire {
+                assert_eq!(report.deactivated, 0);
+            }
+        }
+
+        // Three notices for each account, and each is only deactivated once.
+        assert_eq!(notified, 6);
+        assert_eq!(deactivated, 2);
+
+        let mut kinds = Vec::new();
+        while let Ok(event) = event_rx.try_recv() {
+            kinds.push((event.kind, event.targets));
+        }
+        let count = |kind: SecurityEventKind, uuid: Uuid| {
+            kinds
+                .iter()
+                .filter(|(k, targets)| *k == kind && targets == &vec![uuid])
+                .count()
+        };
+        assert_eq!(
+            count(SecurityEventKind::AccountExpiryNotice, person_uuid),
+            3
+        );
+        assert_eq!(count(SecurityEventKind::AccountExpiryNotice, svc_uuid), 3);
+        assert_eq!(count(SecurityEventKind::AccountDeactivated, person_uuid), 1);
+        assert_eq!(count(SecurityEventKind::AccountDeactivated, svc_uuid), 1);
+
+        // The sessions and tokens are gone.
+        let mut server_txn = server.read().await.expect("txn");
+        let person = server_txn
+            .internal_search_uuid(person_uuid)
+            .expect("failed to find person");
+        let session = person
+            .get_ava_as_session_map(Attribute::UserAuthTokenSession)
+            .and_then(|sessions| sessions.get(&session_id))
+            .expect("no session map found");
+        assert!(matches!(session.state, SessionState::RevokedAt(_)));
+
+        let svc = server_txn
+            .internal_search_uuid(svc_uuid)
+            .expect("failed to find service account");
+        assert!(svc
+            .get_ava_as_apitoken_map(Attribute::ApiTokenSession)
+            .is_none_or(|tokens| !tokens.contains_key(&token_id)));
+    }
+}
@@ -5,7 +5,7 @@
 
 use crate::be::Limits;
 use crate::prelude::*;
-use crate::value::Session;
+use crate::value::{Acr, Session};
 use kanidm_proto::internal::{ApiTokenPurpose, UatPurpose};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
@@ -21,11 +21,24 @@ pub enum Source {
     Ldaps(IpAddr),
 }
 
+impl Source {
+    /// The address of the remote client, if this did not originate internally.
+    pub fn ip_addr(&self) -> Option<IpAddr> {
+        match self {
+            Source::Internal => None,
+            Source::Https(ip) | Source::Ldaps(ip) => Some(*ip),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AccessScope {
     ReadOnly,
     ReadWrite,
     Synchronise,
+    /// The session may only be used to update the credentials of the account, such as
+    /// when the credential used to authenticate has expired.
+    CredentialUpdate,
 }
 
 impl std::fmt::Display for AccessScope {
@@ -34,6 +47,7 @@ impl std::fmt::Display for AccessScope {
             AccessScope::ReadOnly => write!(f, "read only"),
             AccessScope::ReadWrite => write!(f, "read write"),
             AccessScope::Synchronise => write!(f, "synchronise"),
+            AccessScope::CredentialUpdate => write!(f, "credential update"),
         }
     }
 }
@@ -53,6 +67,7 @@ impl From<&UatPurpose> for AccessScope {
         match purpose {
             UatPurpose::ReadOnly => AccessScope::ReadOnly,
             UatPurpose::ReadWrite { .. } => AccessScope::ReadWrite,
+            UatPurpose::CredentialUpdate => AccessScope::CredentialUpdate,
         }
     }
 }
@@ -151,6 +166,7 @@ pub struct Identity {
     pub(crate) scope: AccessScope,
     limits: Limits,
     last_verified_at: Option<OffsetDateTime>,
+    acr: Option<(Acr, OffsetDateTime)>,
 }
 
 impl std::fmt::Display for Identity {
@@ -189,9 +205,15 @@ impl Identity {
             scope,
             limits,
             last_verified_at,
+            acr: None,
         }
     }
 
+    pub(crate) fn with_acr(mut self, acr: Option<(Acr, OffsetDateTime)>) -> Self {
+        self.acr = acr;
+        self
+    }
+
     #[allow(dead_code)]
     pub(crate) fn source(&self) -> &Source {
         &self.source
@@ -212,6 +234,17 @@ impl Identity {
         self.last_verified_at
     }
 
+    /// The authentication context class that the credential used to verify this
+    /// session satisfies. This is `None` for internal and non-interactive identities.
+    pub(crate) fn acr(&self) -> Option<Acr> {
+        self.acr.map(|(acr, _)| acr)
+    }
+
+    /// The time at which the credential satisfying [Self::acr] was last verified.
+    pub(crate) fn acr_verified_at(&self) -> Option<OffsetDateTime> {
+        self.acr.map(|(_, verified_at)| verified_at)
+    }
+
     pub(crate) fn migration() -> Self {
         Identity {
             origin: IdentType::Internal(InternalRole::Migration),
@@ -220,6 +253,7 @@ impl Identity {
             scope: AccessScope::ReadWrite,
             limits: Limits::unlimited(),
             last_verified_at: None,
+            acr: None,
         }
     }
 
@@ -231,6 +265,7 @@ impl Identity {
             scope: AccessScope::ReadOnly,
             limits: Limits::unlimited(),
             last_verified_at: None,
+            acr: None,
         }
     }
 
@@ -242,6 +277,7 @@ impl Identity {
             scope: AccessScope::ReadWrite,
             limits: Limits::unlimited(),
             last_verified_at: None,
+            acr: None,
         }
     }
 
@@ -253,10 +289,10 @@ impl Identity {
             scope: AccessScope::ReadWrite,
             limits: Limits::unlimited(),
             last_verified_at: None,
+            acr: None,
         }
     }
 
-    #[cfg(test)]
     pub(crate) fn from_impersonate_entry_readonly(
         entry: Arc<Entry<EntrySealed, EntryCommitted>>,
     ) -> Self {
@@ -267,6 +303,7 @@ impl Identity {
             scope: AccessScope::ReadOnly,
             limits: Limits::unlimited(),
             last_verified_at: None,
+            acr: None,
         }
     }
 
@@ -280,6 +317,7 @@ impl Identity {
             scope: AccessScope::ReadWrite,
             limits: Limits::unlimited(),
             last_verified_at: None,
+            acr: None,
         }
     }
 
@@ -0,0 +1,177 @@
+//! Scheduled removal of group memberships that reach their `member_expiry` time.
+//!
+//! Removing an expired member is a normal modification of the group, so memberof is
+//! recalculated and the removal replicates as any other change. Each server performs the
+//! removal independently, and as the removal is state based the result is the same
+//! regardless of which server removes the membership first.
+
+use crate::prelude::*;
+use crate::server::security_event::{SecurityEvent, SecurityEventKind, SecurityEventOutcome};
+use time::OffsetDateTime;
+
+impl QueryServerWriteTransaction<'_> {
+    /// Remove every group membership that has expired, returning the number of
+    /// memberships that were removed. All removals are applied in a single write.
+    #[instrument(level = "debug", skip_all)]
+    pub fn process_member_expiry(&mut self) -> Result<usize, OperationError> {
+        let ct = self.get_curtime();
+        let now = OffsetDateTime::UNIX_EPOCH + ct;
+
+        let filter = filter!(f_and!([
+            f_eq(Attribute::Class, EntryClass::Group.into()),
+            f_pres(Attribute::MemberExpiry)
+        ]));
+
+        let entries = self.internal_search(filter)?;
+
+        let expired: Vec<(Uuid, Vec<Uuid>)> = entries
+            .iter()
+            .filter_map(|entry| {
+                let members: Vec<Uuid> = entry
+                    .get_ava_member_expiry(Attribute::MemberExpiry)?
+                    .iter()
+                    .filter(|(_, expire_at)| **expire_at <= now)
+                    .map(|(member, _)| *member)
+                    .collect();
+                (!members.is_empty()).then(|| (entry.get_uuid(), members))
+            })
+            .collect();
+
+        if expired.is_empty() {
+            return Ok(0);
+        }
+
+        self.internal_batch_modify(expired.iter().map(|(group, members)| {
+            let modlist = ModifyList::new_list(
+                members
+                    .iter()
+                    .flat_map(|member| {
+                        [
+                            Modify::Removed(Attribute::Member, PartialValue::Refer(*member)),
+                            Modify::Removed(
+                                Attribute::MemberExpiry,
+                                PartialValue::MemberExpiry(*member),
+                            ),
+                        ]
+                    })
+                    .collect(),
+            );
+            (*group, modlist)
+        }))?;
+
+        let mut removed = 0;
+        for (group, members) in expired {
+            security_info!(%group, ?members, "removed expired group memberships");
+            removed += members.len();
+            self.submit_security_event(
+                SecurityEvent::new(
+                    SecurityEventKind::GroupMembershipExpired,
+                    SecurityEventOutcome::Success,
+                    ct,
+                )
+                .with_targets(std::iter::once(group).chain(members)),
+            );
+        }
+
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::server::security_event::SecurityEventKind;
+    use time::OffsetDateTime;
+
+    const HOUR: u64 = 60 * 60;
+
+    #[qs_test]
+    async fn test_member_expiry_removes_expired_members(server: &QueryServer) {
+        let mut event_rx = server
+            .security_events()
+            .subscribe(64)
+            .expect("failed to subscribe");
+
+        let t0 = duration_from_epoch_now();
+        let expire_odt = OffsetDateTime::UNIX_EPOCH + t0 + Duration::from_secs(48 * HOUR);
+
+        let group_uuid = Uuid::new_v4();
+        let expiring_uuid = Uuid::new_v4();
+        let permanent_uuid = Uuid::new_v4();
+
+        let person = |name: &str, uuid: Uuid| {
+            entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Person.to_value()),
+                (Attribute::Class, EntryClass::Account.to_value()),
+                (Attribute::Name, Value::new_iname(name)),
+                (Attribute::Uuid, Value::Uuid(uuid)),
+                (Attribute::DisplayName, Value::new_utf8s(name))
+            )
+        };
+
+        let e_group = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Name, Value::new_iname("expiring_group")),
+            (Attribute::Uuid, Value::Uuid(group_uuid)),
+            (Attribute::Member, Value::Refer(expiring_uuid)),
+            (Attribute::Member, Value::Refer(permanent_uuid)),
+            (
+                Attribute::MemberExpiry,
+                Value::MemberExpiry(expiring_uuid, expire_odt)
+            )
+        );
+
+        let mut server_txn = server.write(t0).await.expect("txn");
+        server_txn
+            .internal_create(vec![
+                person("expiring_person", expiring_uuid),
+                person("permanent_person", permanent_uuid),
+                e_group,
+            ])
+            .expect("failed to create entries");
+        server_txn.commit().expect("failed to commit");
+
+        // Before the expiry nothing changes.
+        let mut server_txn = server
+            .write(t0 + Duration::from_secs(47 * HOUR))
+            .await
+            .expect("txn");
+        assert_eq!(server_txn.process_member_expiry(), Ok(0));
+        server_txn.commit().expect("failed to commit");
+
+        let mut server_txn = server
+            .write(t0 + Duration::from_secs(48 * HOUR))
+            .await
+            .expect("txn");
+        assert_eq!(server_txn.process_member_expiry(), Ok(1));
+        server_txn.commit().expect("failed to commit");
+
+        let mut server_txn = server.read().await.expect("txn");
+        let group = server_txn
+            .internal_search_uuid(group_uuid)
+            .expect("failed to find group");
+        let members = group
+            .get_ava_refer(Attribute::Member)
+            .expect("no members found");
+        assert!(!members.contains(&expiring_uuid));
+        assert!(members.contains(&permanent_uuid));
+        assert!(group.get_ava_set(Attribute::MemberExpiry).is_none());
+
+        // memberof follows the removal of the membership.
+        let mut memberof = |uuid: Uuid| {
+            server_txn
+                .internal_search_uuid(uuid)
+                .expect("failed to find person")
+                .get_ava_refer(Attribute::MemberOf)
+                .is_some_and(|mo| mo.contains(&group_uuid))
+        };
+        assert!(!memberof(expiring_uuid));
+        assert!(memberof(permanent_uuid));
+
+        let event = event_rx.try_recv().expect("no security event");
+        assert_eq!(event.kind, SecurityEventKind::GroupMembershipExpired);
+        assert_eq!(event.targets, vec![group_uuid, expiring_uuid]);
+    }
+}
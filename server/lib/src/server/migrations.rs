@@ -1,6 +1,7 @@
 use crate::prelude::*;
 
 use crate::migration_data;
+use crate::schema::SchemaTransaction;
 use kanidm_proto::internal::{
     DomainUpgradeCheckItem as ProtoDomainUpgradeCheckItem,
     DomainUpgradeCheckReport as ProtoDomainUpgradeCheckReport,
@@ -8,6 +9,7 @@ use kanidm_proto::internal::{
 };
 
 use super::ServerPhase;
+use unicode_normalization::is_nfc;
 
 impl QueryServer {
     #[instrument(level = "info", name = "system_initialisation", skip_all)]
@@ -889,6 +891,77 @@ impl QueryServerWriteTransaction<'_> {
         Ok(())
     }
 
+    /// Iname values written by older releases may not be composed (NFC), or may not meet the
+    /// structural rules that new values must now meet. Values that aren't composed are
+    /// rewritten in their normalised form. Anything that can't be fixed is reported, and
+    /// must be renamed by an administrator before the entry can be modified again.
+    #[instrument(level = "info", skip_all)]
+    pub(crate) fn migrate_iname_values(&mut self) -> Result<(), OperationError> {
+        let iname_attrs: Vec<Attribute> = self
+            .get_schema()
+            .get_attributes()
+            .values()
+            .filter(|a| a.syntax == SyntaxType::Utf8StringIname && !a.phantom)
+            .map(|a| a.name.clone())
+            .collect();
+
+        if iname_attrs.is_empty() {
+            return Ok(());
+        }
+
+        let filter = filter!(f_or(iname_attrs.iter().cloned().map(f_pres).collect()));
+        let entries = self.internal_search(filter)?;
+
+        for entry in entries {
+            let mut modlist = Vec::new();
+
+            for attr in iname_attrs.iter() {
+                let Some(values) = entry.get_ava_iter_iname(attr) else {
+                    continue;
+                };
+                let values: Vec<&str> = values.collect();
+
+                for value in values.iter() {
+                    if !ValueSetIname::is_valid_iname(value) {
+                        error!(
+                            entry = %entry.get_display_id(),
+                            %attr,
+                            "iname value is no longer valid, this entry must be renamed"
+                        );
+                    }
+                }
+
+                // A partial value is normalised, so it can't match the value to remove. The
+                // attribute is instead replaced with the normalised form of every value.
+                if values.iter().any(|value| !is_nfc(value)) {
+                    modlist.push(Modify::Purged(attr.clone()));
+                    modlist.extend(
+                        values
+                            .iter()
+                            .map(|value| Modify::Present(attr.clone(), Value::new_iname(value))),
+                    );
+                }
+            }
+
+            if modlist.is_empty() {
+                continue;
+            }
+
+            // A normalised name may collide with an existing one, in which case one of them
+            // must be renamed. This shouldn't prevent the rest of the migration.
+            match self.internal_modify_uuid(entry.get_uuid(), &ModifyList::new_list(modlist)) {
+                Ok(()) => info!(entry = %entry.get_display_id(), "normalised iname values"),
+                Err(err) => error!(
+                    ?err,
+                    entry = %entry.get_display_id(),
+                    "unable to normalise iname values, this entry must be renamed"
+                ),
+            }
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn migrate_schema_1_11(&mut self) -> Result<(), OperationError> {
         self.schema.extend_in_memory(
             migration_data::dl15::phase_1_schema_attrs(),
@@ -966,6 +1039,9 @@ impl QueryServerWriteTransaction<'_> {
             migration_data::dl15::phase_7_builtin_access_control_profiles(),
         )?;
 
+        // Generate the self write access control from the domain self write policy.
+        self.reload_self_write_policy()?;
+
         self.internal_delete_batch(
             "phase 8 - delete UUIDS",
             migration_data::dl15::phase_8_delete_uuids(),
@@ -984,6 +1060,8 @@ impl QueryServerWriteTransaction<'_> {
         );
         self.internal_modify(&filter, &modlist)?;
 
+        self.migrate_iname_values()?;
+
         Ok(())
     }
 
@@ -1560,6 +1638,87 @@ mod tests {
         write_txn.commit().expect("Unable to commit");
     }
 
+    #[qs_test(domain_level=DOMAIN_LEVEL_14)]
+    async fn test_migrations_dl14_dl1_11_iname_values(server: &QueryServer) {
+        let mut write_txn = server.write(duration_from_epoch_now()).await.unwrap();
+
+        let composed_uuid = Uuid::new_v4();
+        let invalid_uuid = Uuid::new_v4();
+
+        write_txn
+            .internal_create(vec![
+                entry_init!(
+                    (Attribute::Class, EntryClass::Object.to_value()),
+                    (Attribute::Class, EntryClass::Group.to_value()),
+                    (Attribute::Name, Value::new_iname("composed_group")),
+                    (Attribute::Uuid, Value::Uuid(composed_uuid))
+                ),
+                entry_init!(
+                    (Attribute::Class, EntryClass::Object.to_value()),
+                    (Attribute::Class, EntryClass::Group.to_value()),
+                    (Attribute::Name, Value::new_iname("invalid_group")),
+                    (Attribute::Uuid, Value::Uuid(invalid_uuid))
+                ),
+            ])
+            .expect("Failed to create groups");
+
+        // Older releases could store names that are no longer valid. Write these directly
+        // to the backend, as they would be refused by validation.
+        // The kelvin sign is not in NFC, and composes to an ascii name.
+        let decomposed = "\u{212A}elvin";
+        let cid = write_txn.get_cid().clone();
+        let (pre, post): (Vec<_>, Vec<_>) = [(composed_uuid, decomposed), (invalid_uuid, "...")]
+            .into_iter()
+            .map(|(uuid, name)| {
+                let pre = write_txn
+                    .internal_search_uuid(uuid)
+                    .expect("Failed to find group");
+                let mut post = pre
+                    .as_ref()
+                    .clone()
+                    .invalidate(cid.clone(), write_txn.trim_cid());
+                post.set_ava_set(
+                    &Attribute::Name,
+                    ValueSetIname::from_dbvs2(vec![name.to_string()]).expect("Invalid valueset"),
+                );
+                let spn = post
+                    .generate_spn(write_txn.get_domain_name())
+                    .expect("Failed to generate spn");
+                post.set_ava_set(&Attribute::Spn, spn);
+                (pre, post.into_sealed_committed())
+            })
+            .unzip();
+
+        write_txn
+            .get_be_txn()
+            .modify(&cid, &pre, &post)
+            .expect("Failed to write groups");
+
+        write_txn
+            .internal_apply_domain_migration(DOMAIN_LEVEL_1_11)
+            .expect("Unable to set domain level to version 1_11");
+
+        // The decomposed name is now composed, and is indexed by that form.
+        let group = write_txn
+            .internal_search_uuid(composed_uuid)
+            .expect("Failed to find group");
+        assert_eq!(group.get_ava_single_iname(Attribute::Name), Some("kelvin"));
+        assert_eq!(
+            write_txn
+                .name_to_uuid("kelvin")
+                .expect("Failed to resolve name"),
+            composed_uuid
+        );
+
+        // The invalid name can't be fixed, so it's left for an administrator to rename.
+        let group = write_txn
+            .internal_search_uuid(invalid_uuid)
+            .expect("Failed to find group");
+        assert_eq!(group.get_ava_single_iname(Attribute::Name), Some("..."));
+
+        write_txn.commit().expect("Unable to commit");
+    }
+
     #[qs_test(domain_level=DOMAIN_LEVEL_14)]
     async fn test_migrations_dl14_dl1_11(server: &QueryServer) {
         let mut write_txn = server.write(duration_from_epoch_now()).await.unwrap();
@@ -12,11 +12,15 @@ use self::keys::{
     KeyObject, KeyProvider, KeyProviders, KeyProvidersReadTransaction, KeyProvidersTransaction,
     KeyProvidersWriteTransaction,
 };
+use self::security_event::{SecurityEvent, SecurityEventQueue};
+use self::webhook::{WebhookDelivery, WebhookQueue, WebhookSubscriptionCache};
 use crate::be::{Backend, BackendReadTransaction, BackendTransaction, BackendWriteTransaction};
 use crate::filter::{
     Filter, FilterInvalid, FilterValid, FilterValidResolved, ResolveFilterCache,
     ResolveFilterCacheReadTxn,
 };
+use crate::idm::unix::UnixHomeDirectoryTemplate;
+use crate::metrics::METRICS;
 use crate::plugins::{
     self,
     dyngroup::{DynGroup, DynGroupCache},
@@ -27,11 +31,13 @@ use crate::repl::cid::Cid;
 use crate::repl::proto::ReplRuvRange;
 use crate::repl::ruv::ReplicationUpdateVectorTransaction;
 use crate::schema::{
-    Schema, SchemaAttribute, SchemaClass, SchemaReadTransaction, SchemaTransaction,
-    SchemaWriteTransaction,
+    Schema, SchemaAttribute, SchemaClass, SchemaReadTransaction, SchemaReloadMode,
+    SchemaTransaction, SchemaWriteTransaction,
 };
 use crate::value::{CredentialType, EXTRACT_VAL_DN};
 use crate::valueset::*;
+use compact_jwt::compact::JweCompact;
+use compact_jwt::jwe::JweBuilder;
 use concread::arcache::{ARCacheBuilder, ARCacheReadTxn, ARCacheWriteTxn};
 use concread::cowcell::*;
 use crypto_glue::{hmac_s256::HmacSha256Key, s256::Sha256Output};
@@ -41,6 +47,8 @@ use kanidm_proto::scim_v1::{
     server::{ScimListResponse, ScimOAuth2ClaimMap, ScimOAuth2ScopeMap, ScimReference},
     JsonValue, ScimEntryGetQuery, ScimFilter,
 };
+use kanidm_proto::v1::GroupMemberCounts;
+use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
 use std::num::NonZeroU64;
 use std::str::FromStr;
@@ -51,20 +59,61 @@ use tracing::trace;
 
 pub(crate) mod access;
 pub mod assert;
+pub(crate) mod audit;
 pub mod batch_modify;
 pub mod create;
+pub(crate) mod creation_template;
 pub mod delete;
+pub mod expiry;
 pub mod identity;
+pub(crate) mod member_expiry;
 pub(crate) mod keys;
 pub(crate) mod migrations;
 pub mod modify;
 pub(crate) mod recycle;
 pub mod scim;
+pub mod security_event;
 pub(crate) mod utils;
+pub mod webhook;
 
 const RESOLVE_FILTER_CACHE_MAX: usize = 256;
 const RESOLVE_FILTER_CACHE_LOCAL: usize = 8;
 
+/// Attributes that the domain self write policy may never grant to a person.
+const SELF_WRITE_DENIED_ATTRS: &[Attribute] = &[
+    // Identity
+    Attribute::Class,
+    Attribute::Uuid,
+    Attribute::Name,
+    Attribute::Spn,
+    Attribute::LegalName,
+    Attribute::Mail,
+    Attribute::GidNumber,
+    // Validity
+    Attribute::AccountExpire,
+    Attribute::AccountValidFrom,
+    // Credentials and sessions
+    Attribute::PrimaryCredential,
+    Attribute::PassKeys,
+    Attribute::AttestedPasskeys,
+    Attribute::UnixPassword,
+    Attribute::RadiusSecret,
+    Attribute::ApplicationPassword,
+    Attribute::CredentialUpdateIntentToken,
+    Attribute::ApiTokenSession,
+    Attribute::UserAuthTokenSession,
+    Attribute::OAuth2Session,
+    // Managed by the server
+    Attribute::MemberOf,
+    Attribute::DirectMemberOf,
+    Attribute::Member,
+    Attribute::EntryManagedBy,
+    Attribute::SyncParentUuid,
+    Attribute::SyncExternalId,
+    Attribute::LastModifiedCid,
+    Attribute::CreatedAtCid,
+];
+
 #[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Eq)]
 pub(crate) enum ServerPhase {
     Bootstrap,
@@ -86,6 +135,10 @@ pub struct DomainInfo {
     pub(crate) d_ldap_allow_unix_pw_bind: bool,
     pub(crate) d_allow_easter_eggs: bool,
     pub(crate) d_allow_account_recovery: bool,
+    pub(crate) d_deny_membership_cycles: bool,
+    pub(crate) d_unix_home_directory_template: Option<UnixHomeDirectoryTemplate>,
+    pub(crate) d_unix_default_shell: Option<String>,
+    pub(crate) d_self_write_attrs: BTreeSet<Attribute>,
     // In future this should be image reference instead of the image itself.
     d_image: Option<ImageValue>,
 }
@@ -119,6 +172,15 @@ impl DomainInfo {
         self.d_allow_account_recovery
     }
 
+    pub fn deny_membership_cycles(&self) -> bool {
+        self.d_deny_membership_cycles
+    }
+
+    /// The attributes that people may modify on their own entry.
+    pub fn self_write_attrs(&self) -> &BTreeSet<Attribute> {
+        &self.d_self_write_attrs
+    }
+
     #[cfg(feature = "test")]
     pub fn new_test() -> CowCell<Self> {
         concread::cowcell::CowCell::new(Self {
@@ -131,6 +193,10 @@ impl DomainInfo {
             d_ldap_allow_unix_pw_bind: false,
             d_allow_easter_eggs: false,
             d_allow_account_recovery: false,
+            d_deny_membership_cycles: false,
+            d_unix_home_directory_template: None,
+            d_unix_default_shell: None,
+            d_self_write_attrs: BTreeSet::new(),
             d_image: None,
         })
     }
@@ -169,6 +235,9 @@ pub struct QueryServer {
     dyngroup_cache: Arc<CowCell<DynGroupCache>>,
     cid_max: Arc<CowCell<Cid>>,
     key_providers: Arc<KeyProviders>,
+    security_events: Arc<SecurityEventQueue>,
+    webhooks: Arc<WebhookQueue>,
+    webhook_subscriptions: Arc<CowCell<WebhookSubscriptionCache>>,
 }
 
 pub struct QueryServerReadTransaction<'a> {
@@ -188,6 +257,7 @@ pub struct QueryServerReadTransaction<'a> {
     // cid_max: CowCellReadTxn<Cid>,
     trim_cid: Cid,
     txn_name_to_uuid: BTreeMap<String, Uuid>,
+    security_events: Arc<SecurityEventQueue>,
 }
 
 unsafe impl Sync for QueryServerReadTransaction<'_> {}
@@ -207,6 +277,7 @@ bitflags::bitflags! {
         const APPLICATION    =              0b0000_0000_1000_0000;
         const OAUTH2_CLIENT            =    0b0000_0001_0000_0000;
         const FEATURE                  =    0b0000_0010_0000_0000;
+        const WEBHOOK                  =    0b0000_0100_0000_0000;
     }
 }
 
@@ -247,6 +318,11 @@ pub struct QueryServerWriteTransaction<'a> {
     >,
     dyngroup_cache: CowCellWriteTxn<'a, DynGroupCache>,
     txn_name_to_uuid: BTreeMap<String, Uuid>,
+    security_events: Arc<SecurityEventQueue>,
+    webhooks: Arc<WebhookQueue>,
+    // Deliveries are held until the transaction commits.
+    pending_webhooks: Vec<WebhookDelivery>,
+    pub(super) webhook_subscriptions: CowCellWriteTxn<'a, WebhookSubscriptionCache>,
 }
 
 impl QueryServerWriteTransaction<'_> {
@@ -255,6 +331,50 @@ impl QueryServerWriteTransaction<'_> {
     }
 }
 
+/// The position of a paged search, sealed into the opaque cursor that is given to the client.
+#[derive(Serialize, Deserialize)]
+struct SearchPageCursor {
+    /// The backend id of the last entry that was returned to the client.
+    after: u64,
+}
+
+/// Seal the backend id of the last entry of a page into an opaque cursor.
+fn seal_search_page_cursor(
+    key_object: &KeyObject,
+    after: u64,
+    ct: Duration,
+) -> Result<String, OperationError> {
+    let cursor_data = serde_json::to_vec(&SearchPageCursor { after }).map_err(|e| {
+        admin_error!(err = ?e, "Unable to encode search page cursor");
+        OperationError::SerdeJsonError
+    })?;
+
+    key_object
+        .jwe_a128gcm_encrypt(&JweBuilder::from(cursor_data).build(), ct)
+        .map(|jwe| jwe.to_string())
+}
+
+/// Open a cursor that was sealed by [`seal_search_page_cursor`], returning the backend id
+/// that the next page resumes after.
+fn open_search_page_cursor(key_object: &KeyObject, cursor: &str) -> Result<u64, OperationError> {
+    let jwec = JweCompact::from_str(cursor).map_err(|e| {
+        request_error!(err = ?e, "search page cursor is malformed");
+        OperationError::InvalidRequestState
+    })?;
+
+    key_object
+        .jwe_decrypt(&jwec)
+        .and_then(|jwe| {
+            jwe.from_json::<SearchPageCursor>()
+                .map_err(|_| OperationError::SerdeJsonError)
+        })
+        .map(|cursor| cursor.after)
+        .map_err(|e| {
+            request_error!(err = ?e, "search page cursor is invalid");
+            OperationError::InvalidRequestState
+        })
+}
+
 /// The `QueryServerTransaction` trait provides a set of common read only operations to be
 /// shared between [`QueryServerReadTransaction`] and [`QueryServerWriteTransaction`]s.
 ///
@@ -277,6 +397,13 @@ pub trait QueryServerTransaction<'a> {
     type KeyProvidersTransactionType: KeyProvidersTransaction;
     fn get_key_providers(&self) -> &Self::KeyProvidersTransactionType;
 
+    fn get_security_events(&self) -> &SecurityEventQueue;
+
+    /// Submit a security event to any subscriber. This never blocks.
+    fn submit_security_event(&self, event: SecurityEvent) {
+        self.get_security_events().submit(event)
+    }
+
     fn pw_badlist(&self) -> &HashSet<String>;
 
     fn denied_names(&self) -> &HashSet<String>;
@@ -355,6 +482,7 @@ pub trait QueryServerTransaction<'a> {
         } else {
             security_info!(initiator = %se.ident, "search");
             admin_debug!(external_filter = ?se.filter, "search");
+            METRICS.search_operations.inc();
         }
 
         // This is an important security step because it prevents us from
@@ -404,6 +532,112 @@ pub trait QueryServerTransaction<'a> {
         })
     }
 
+    /// Conduct a search as [`fn search`] does, returning a single page of at most `limit`
+    /// entries. `cursor` is the cursor returned with the prior page, or `None` to begin from
+    /// the first page. If more entries remain, the cursor of the following page is returned.
+    ///
+    /// The cursor refers to the last entry of the page after access controls are applied,
+    /// and is sealed with the domain key so that it is opaque to the client and can't be
+    /// altered to refer to other entries.
+    ///
+    /// [`fn search`]: trait.QueryServerTransaction.html#method.search
+    #[instrument(level = "debug", skip_all)]
+    fn search_page(
+        &mut self,
+        se: &SearchEvent,
+        cursor: Option<&str>,
+        limit: usize,
+        ct: Duration,
+    ) -> Result<(Vec<Arc<EntrySealedCommitted>>, Option<String>), OperationError> {
+        if !se.ident.is_internal() {
+            security_info!(initiator = %se.ident, "search page");
+            admin_debug!(external_filter = ?se.filter, "search page");
+            METRICS.search_operations.inc();
+        }
+
+        let mut after = match cursor {
+            Some(cursor) => {
+                let key_object = self.get_domain_key_object_handle()?;
+                open_search_page_cursor(&key_object, cursor)?
+            }
+            None => 0,
+        };
+
+        let (be_txn, resolve_filter_cache) = self.get_resolve_filter_cache_and_be_txn();
+
+        let idxmeta = be_txn.get_idxmeta_ref();
+
+        let vfr = se
+            .filter
+            .resolve(&se.ident, Some(idxmeta), resolve_filter_cache)
+            .map_err(|e| {
+                admin_error!(?e, "search filter resolve failure");
+                e
+            })?;
+
+        let lims = se.ident.limits();
+
+        // Access controls may deny some of the entries of a backend page, so keep reading
+        // until the page is full or the backend has no more entries.
+        let mut entries = Vec::with_capacity(limit);
+        let more = loop {
+            let (res, next) = self
+                .get_be_txn()
+                .search_page(lims, &vfr, after, limit - entries.len())
+                .map_err(|e| {
+                    admin_error!(?e, "backend failure");
+                    e
+                })?;
+
+            let access = self.get_accesscontrols();
+            let res = access.search_filter_entries(se, res).map_err(|e| {
+                admin_error!(?e, "Unable to access filter entries");
+                e
+            })?;
+            entries.extend(res);
+
+            match next {
+                Some(next) if entries.len() < limit => after = next,
+                next => break next.is_some(),
+            }
+        };
+
+        let next = match entries.last() {
+            Some(last) if more => {
+                let key_object = self.get_domain_key_object_handle()?;
+                Some(seal_search_page_cursor(&key_object, last.get_id(), ct)?)
+            }
+            _ => None,
+        };
+
+        Ok((entries, next))
+    }
+
+    /// Conduct a search as [`fn search_ext`] does, returning a single page of entries. See
+    /// [`fn search_page`] for the handling of the cursor.
+    ///
+    /// [`fn search_ext`]: trait.QueryServerTransaction.html#method.search_ext
+    /// [`fn search_page`]: trait.QueryServerTransaction.html#method.search_page
+    #[instrument(level = "debug", skip_all)]
+    fn search_ext_page(
+        &mut self,
+        se: &SearchEvent,
+        cursor: Option<&str>,
+        limit: usize,
+        ct: Duration,
+    ) -> Result<(Vec<EntryReducedCommitted>, Option<String>), OperationError> {
+        let (entries, next) = self.search_page(se, cursor, limit, ct)?;
+
+        let access = self.get_accesscontrols();
+        access
+            .search_filter_entry_attributes(se, entries)
+            .map(|entries| (entries, next))
+            .map_err(|e| {
+                admin_error!(?e, "Failed to filter entry attributes");
+                e
+            })
+    }
+
     #[instrument(level = "debug", skip_all)]
     fn exists(&mut self, ee: &ExistsEvent) -> Result<bool, OperationError> {
         let (be_txn, resolve_filter_cache) = self.get_resolve_filter_cache_and_be_txn();
@@ -532,6 +766,47 @@ pub trait QueryServerTransaction<'a> {
         self.internal_exists(&filter)
     }
 
+    /// Count the members of a group that has been reduced for the requester. Direct members
+    /// are counted from the member attributes that remain on the reduced entry, and nested
+    /// members are searched for as the requester, so the counts never disclose more than
+    /// the requester could already read.
+    fn group_member_counts(
+        &mut self,
+        ident: &Identity,
+        group: &Entry<EntryReduced, EntryCommitted>,
+    ) -> Result<GroupMemberCounts, OperationError> {
+        let member = group
+            .get_ava_set(Attribute::Member)
+            .map(|vs| vs.len() as u64)
+            .unwrap_or_default();
+        let dyn_member = group
+            .get_ava_set(Attribute::DynMember)
+            .map(|vs| vs.len() as u64)
+            .unwrap_or_default();
+
+        // Every entry that is a member, directly or through another group, has this
+        // group in its memberof. Only the members whose memberof the requester may
+        // search are counted.
+        let nested = if member > 0 || dyn_member > 0 {
+            let f_valid = filter!(f_eq(
+                Attribute::MemberOf,
+                PartialValue::Refer(group.get_uuid())
+            ))
+            .validate(self.get_schema())
+            .map_err(OperationError::SchemaViolation)?;
+            let se = SearchEvent::new_impersonate(ident, f_valid.clone(), f_valid);
+            self.search(&se)?.len() as u64
+        } else {
+            0
+        };
+
+        Ok(GroupMemberCounts {
+            member,
+            dyn_member,
+            nested,
+        })
+    }
+
     #[instrument(level = "debug", skip_all)]
     fn internal_search(
         &mut self,
@@ -722,7 +997,7 @@ pub trait QueryServerTransaction<'a> {
         match schema.get_attributes().get(attr) {
             Some(schema_a) => {
                 match schema_a.syntax {
-                    SyntaxType::Utf8String => Ok(Value::new_utf8(value.to_string())),
+                    SyntaxType::Utf8String | SyntaxType::Custom => Ok(Value::new_utf8(value.to_string())),
                     SyntaxType::Utf8StringInsensitive => Ok(Value::new_iutf8(value)),
                     SyntaxType::Utf8StringIname => Ok(Value::new_iname(value)),
                     SyntaxType::Boolean => Value::new_bools(value)
@@ -801,6 +1076,16 @@ pub trait QueryServerTransaction<'a> {
                     SyntaxType::Json => Err(OperationError::InvalidAttribute("Json values can not be supplied through modification".to_string())),
                     SyntaxType::Sha256 => Err(OperationError::InvalidAttribute("SHA256 values can not be supplied through modification".to_string())),
                     SyntaxType::Message => Err(OperationError::InvalidAttribute("Message values can not be supplied through modification".to_string())),
+                    SyntaxType::CredentialLockout => Err(OperationError::InvalidAttribute("Credential lockouts are generated and not able to be set.".to_string())),
+                    SyntaxType::PasskeyUsage => Err(OperationError::InvalidAttribute("Passkey usage is generated and not able to be set.".to_string())),
+                    SyntaxType::MemberExpiry => {
+                        // Formatted as "<member>: <rfc3339 timestamp>"
+                        let (member, expire_at) = value.split_once(": ")
+                            .ok_or_else(|| OperationError::InvalidAttribute("Invalid member expiry syntax, expected '<member>: <rfc3339>'".to_string()))?;
+                        let un = self.name_to_uuid(member.trim())?;
+                        Value::new_member_expiry_s(un, expire_at.trim())
+                            .ok_or_else(|| OperationError::InvalidAttribute("Invalid member expiry DateTime (rfc3339) syntax".to_string()))
+                    }
                 }
             }
             None => {
@@ -822,7 +1107,7 @@ pub trait QueryServerTransaction<'a> {
         match schema.get_attributes().get(attr) {
             Some(schema_a) => {
                 match schema_a.syntax {
-                    SyntaxType::Utf8String | SyntaxType::TotpSecret => {
+                    SyntaxType::Utf8String | SyntaxType::TotpSecret | SyntaxType::Custom => {
                         Ok(PartialValue::new_utf8(value.to_string()))
                     }
                     SyntaxType::Utf8StringInsensitive
@@ -951,6 +1236,22 @@ pub trait QueryServerTransaction<'a> {
                     SyntaxType::Json => Err(OperationError::InvalidAttribute(
                         "Json values can not be validated by this interface".to_string(),
                     )),
+                    SyntaxType::CredentialLockout => PartialValue::new_credential_lockout_s(value)
+                        .ok_or_else(|| {
+                            OperationError::InvalidAttribute(
+                                "Invalid Credential Lockout ID (uuid) syntax".to_string(),
+                            )
+                        }),
+                    SyntaxType::PasskeyUsage => PartialValue::new_passkey_usage_s(value)
+                        .ok_or_else(|| {
+                            OperationError::InvalidAttribute(
+                                "Invalid Passkey Usage ID (uuid) syntax".to_string(),
+                            )
+                        }),
+                    SyntaxType::MemberExpiry => {
+                        let un = self.name_to_uuid(value).unwrap_or(UUID_DOES_NOT_EXIST);
+                        Ok(PartialValue::MemberExpiry(un))
+                    }
                     SyntaxType::Message => Err(OperationError::InvalidAttribute(
                         "Message values can not be validated by this interface".to_string(),
                     )),
@@ -993,6 +1294,7 @@ pub trait QueryServerTransaction<'a> {
                              group_uuid,
                              claim,
                              join_char,
+                             template,
                              values,
                          }| {
                             self.uuid_to_spn(group_uuid)
@@ -1004,6 +1306,7 @@ pub trait QueryServerTransaction<'a> {
                                     group_uuid,
                                     claim,
                                     join_char,
+                                    template,
                                     values,
                                 })
                         },
@@ -1050,7 +1353,7 @@ pub trait QueryServerTransaction<'a> {
         debug!(schema_syntax = ?schema_a.syntax, ?value);
 
         match schema_a.syntax {
-            SyntaxType::Utf8String => {
+            SyntaxType::Utf8String | SyntaxType::Custom => {
                 let JsonValue::String(value) = value else {
                     return Err(OperationError::InvalidAttribute(attr.to_string()));
                 };
@@ -1143,6 +1446,7 @@ pub trait QueryServerTransaction<'a> {
                          group_name,
                          claim,
                          join_char,
+                         template,
                          claim_values,
                      }| {
                         let group_uuid =
@@ -1158,6 +1462,7 @@ pub trait QueryServerTransaction<'a> {
                             group_uuid,
                             claim,
                             join_char,
+                            template,
                             claim_values,
                         }
                     },
@@ -1425,6 +1730,10 @@ impl<'a> QueryServerTransaction<'a> for QueryServerReadTransaction<'a> {
         &self.key_providers
     }
 
+    fn get_security_events(&self) -> &SecurityEventQueue {
+        &self.security_events
+    }
+
     fn get_resolve_filter_cache(&mut self) -> Option<&mut ResolveFilterCacheReadTxn<'a>> {
         Some(&mut self.resolve_filter_cache)
     }
@@ -1565,6 +1874,14 @@ impl QueryServerReadTransaction<'_> {
         results
     }
 
+    /// Find issues in the data content of the server that don't make it inconsistent,
+    /// but that an administrator may want to review.
+    pub(crate) fn verify_warnings(&mut self) -> Vec<ConsistencyError> {
+        let mut results = Vec::with_capacity(0);
+        Plugins::run_verify_warnings(self, &mut results);
+        results
+    }
+
     #[instrument(level = "debug", skip_all)]
     pub fn scim_entry_id_get_ext(
         &mut self,
@@ -1598,7 +1915,13 @@ impl QueryServerReadTransaction<'_> {
 
         let mut vs = self.search_ext(&se)?;
         match vs.pop() {
-            Some(entry) if vs.is_empty() => entry.to_scim_kanidm(self),
+            Some(entry) if vs.is_empty() => {
+                let mut scim_entry = entry.to_scim_kanidm(self)?;
+                if query.ext_attr_meta {
+                    scim_entry.ext_attr_meta = Some(entry.to_scim_attr_meta());
+                }
+                Ok(scim_entry)
+            }
             _ => {
                 if vs.is_empty() {
                     Err(OperationError::NoMatchingEntries)
@@ -1709,8 +2032,14 @@ impl QueryServerReadTransaction<'_> {
 
         let resources = paginated_result_set
             .into_iter()
-            .map(|entry| entry.to_scim_kanidm(self))
-            .collect::<Result<Vec<_>, _>>()?;
+            .map(|entry| {
+                let mut scim_entry = entry.to_scim_kanidm(self)?;
+                if query.ext_attr_meta {
+                    scim_entry.ext_attr_meta = Some(entry.to_scim_attr_meta());
+                }
+                Ok(scim_entry)
+            })
+            .collect::<Result<Vec<_>, OperationError>>()?;
 
         Ok(ScimListResponse {
             // Requires other schema changes in future.
@@ -1770,6 +2099,10 @@ impl<'a> QueryServerTransaction<'a> for QueryServerWriteTransaction<'a> {
         &self.key_providers
     }
 
+    fn get_security_events(&self) -> &SecurityEventQueue {
+        &self.security_events
+    }
+
     fn get_resolve_filter_cache(&mut self) -> Option<&mut ResolveFilterCacheReadTxn<'a>> {
         if self.resolve_filter_cache_clear || *self.phase < ServerPhase::SchemaReady {
             None
@@ -1878,6 +2211,10 @@ impl QueryServer {
             d_ldap_allow_unix_pw_bind: false,
             d_allow_easter_eggs: false,
             d_allow_account_recovery: false,
+            d_deny_membership_cycles: false,
+            d_unix_home_directory_template: None,
+            d_unix_default_shell: None,
+            d_self_write_attrs: BTreeSet::new(),
             d_image: None,
         }));
 
@@ -1926,9 +2263,22 @@ impl QueryServer {
             dyngroup_cache,
             cid_max,
             key_providers,
+            security_events: Arc::new(SecurityEventQueue::default()),
+            webhooks: Arc::new(WebhookQueue::default()),
+            webhook_subscriptions: Arc::new(CowCell::new(WebhookSubscriptionCache::default())),
         })
     }
 
+    /// The queue that security events are submitted to, allowing them to be exported.
+    pub fn security_events(&self) -> &Arc<SecurityEventQueue> {
+        &self.security_events
+    }
+
+    /// The queue that webhook deliveries are submitted to once their transaction commits.
+    pub fn webhooks(&self) -> &Arc<WebhookQueue> {
+        &self.webhooks
+    }
+
     pub fn try_quiesce(&self) {
         self.be.try_quiesce();
         self.accesscontrols.try_quiesce();
@@ -2018,6 +2368,7 @@ impl QueryServer {
             resolve_filter_cache: self.resolve_filter_cache.read(),
             trim_cid,
             txn_name_to_uuid: Default::default(),
+            security_events: self.security_events.clone(),
         })
     }
 
@@ -2128,6 +2479,10 @@ impl QueryServer {
             dyngroup_cache: self.dyngroup_cache.write(),
             key_providers: self.key_providers.write(),
             txn_name_to_uuid: Default::default(),
+            security_events: self.security_events.clone(),
+            webhooks: self.webhooks.clone(),
+            pending_webhooks: Vec::new(),
+            webhook_subscriptions: self.webhook_subscriptions.write(),
         })
     }
 
@@ -2162,6 +2517,15 @@ impl QueryServer {
             Err(_) => vec![Err(ConsistencyError::Unknown)],
         }
     }
+
+    /// Find issues that don't fail verification, but should be reported. This should be
+    /// run after [`QueryServer::verify`] so that the schema is fully loaded.
+    pub async fn verify_warnings(&self) -> Vec<ConsistencyError> {
+        match self.read().await {
+            Ok(mut r_txn) => r_txn.verify_warnings(),
+            Err(_) => vec![ConsistencyError::Unknown],
+        }
+    }
 }
 
 impl<'a> QueryServerWriteTransaction<'a> {
@@ -2246,20 +2610,34 @@ impl<'a> QueryServerWriteTransaction<'a> {
                 e
             })?;
             // load them.
-            let attributetypes: Result<Vec<_>, _> =
-                res.iter().map(|e| SchemaAttribute::try_from(e)).collect();
-
-            let attributetypes = attributetypes.map_err(|e| {
-                error!("reload schema attributetypes {:?}", e);
-                e
-            })?;
-
-            self.schema
-                .update_attributes(attributetypes.into_iter())
-                .map_err(|e| {
-                    error!("reload schema update attributetypes {:?}", e);
-                    e
-                })?;
+            match self.schema.reload_mode() {
+                SchemaReloadMode::Strict => {
+                    let attributetypes: Result<Vec<_>, _> =
+                        res.iter().map(|e| SchemaAttribute::try_from(e)).collect();
+
+                    let attributetypes = attributetypes.map_err(|e| {
+                        error!("reload schema attributetypes {:?}", e);
+                        e
+                    })?;
+
+                    self.schema
+                        .update_attributes(attributetypes.into_iter())
+                        .map_err(|e| {
+                            error!("reload schema update attributetypes {:?}", e);
+                            e
+                        })?;
+                }
+                SchemaReloadMode::Lenient => {
+                    let (loaded, failures) = self.schema.update_attributes_lenient(&res);
+                    if !failures.is_empty() {
+                        error!(
+                            loaded,
+                            ?failures,
+                            "reload schema skipped malformed attributetypes"
+                        );
+                    }
+                }
+            }
 
             // find all classes
             let filt = filter!(f_eq(Attribute::Class, EntryClass::ClassType.into()));
@@ -2557,6 +2935,10 @@ impl<'a> QueryServerWriteTransaction<'a> {
             .get_ava_single_bool(Attribute::DomainAllowAccountRecovery)
             .unwrap_or_default();
 
+        let domain_deny_membership_cycles = domain_info
+            .get_ava_single_bool(Attribute::DomainDenyMembershipCycles)
+            .unwrap_or_default();
+
         // We have to set the domain version here so that features which check for it
         // will now see it's been increased. This also prevents recursion during reloads
         // inside of a domain migration.
@@ -2570,6 +2952,7 @@ impl<'a> QueryServerWriteTransaction<'a> {
         mut_d_info.d_devel_taint = domain_info_devel_taint;
         mut_d_info.d_allow_easter_eggs = domain_allow_easter_eggs;
         mut_d_info.d_allow_account_recovery = domain_allow_account_recovery;
+        mut_d_info.d_deny_membership_cycles = domain_deny_membership_cycles;
 
         debug!(?mut_d_info);
 
@@ -2689,10 +3072,30 @@ impl<'a> QueryServerWriteTransaction<'a> {
 
         let domain_image = domain_entry.get_ava_single_image(Attribute::Image);
 
+        // The template is validated on write, so this can only fail if the entry was
+        // replicated from a server with different rules.
+        let unix_home_directory_template = domain_entry
+            .get_ava_single_utf8(Attribute::UnixHomeDirectoryTemplate)
+            .and_then(|template| {
+                template
+                    .parse::<UnixHomeDirectoryTemplate>()
+                    .inspect_err(|_| warn!("Ignoring invalid domain unix home directory template"))
+                    .ok()
+            });
+
+        let unix_default_shell = domain_entry
+            .get_ava_single_iutf8(Attribute::UnixDefaultShell)
+            .map(str::to_string);
+
+        let self_write_attrs = self.validate_self_write_attrs(&domain_entry)?;
+
         let domain_uuid = self.be_txn.get_db_d_uuid()?;
 
         let mut_d_info = self.d_info.get_mut();
         mut_d_info.d_ldap_allow_unix_pw_bind = domain_ldap_allow_unix_pw_bind;
+        mut_d_info.d_unix_home_directory_template = unix_home_directory_template;
+        mut_d_info.d_unix_default_shell = unix_default_shell;
+        mut_d_info.d_self_write_attrs = self_write_attrs;
         if mut_d_info.d_uuid != domain_uuid {
             admin_warn!(
                 "Using domain uuid from the database {} - was {} in memory",
@@ -2717,6 +3120,91 @@ impl<'a> QueryServerWriteTransaction<'a> {
         Ok(())
     }
 
+    /// The attributes of the domain self write policy. Every attribute must be defined by the
+    /// schema, and may not be one that the policy can never grant, as it identifies the
+    /// account, controls its validity or credentials, or is managed by the server.
+    pub(crate) fn validate_self_write_attrs<VALID, STATE>(
+        &self,
+        domain_entry: &Entry<VALID, STATE>,
+    ) -> Result<BTreeSet<Attribute>, OperationError> {
+        let schema_attributes = self.get_schema().get_attributes();
+
+        domain_entry
+            .get_ava_iter_iutf8(Attribute::DomainSelfWriteAttrs)
+            .into_iter()
+            .flatten()
+            .map(|attr| {
+                let attr = Attribute::from(attr);
+                match schema_attributes.get(&attr) {
+                    None => {
+                        error!(%attr, "Unknown attribute in domain self write policy");
+                        Err(OperationError::InvalidAttributeName(attr.to_string()))
+                    }
+                    Some(schema_attr)
+                        if schema_attr.phantom || SELF_WRITE_DENIED_ATTRS.contains(&attr) =>
+                    {
+                        error!(
+                            %attr,
+                            "Attribute may not be granted by the domain self write policy"
+                        );
+                        Err(OperationError::PL0009SelfWriteAttributeDenied)
+                    }
+                    Some(_) => Ok(attr),
+                }
+            })
+            .collect()
+    }
+
+    /// Regenerate the attributes granted by the builtin self write policy access control
+    /// from the domain self write policy. This is a no-op until the access control has been
+    /// created by a migration.
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) fn reload_self_write_policy(&mut self) -> Result<(), OperationError> {
+        let acp_entry = match self.internal_search_uuid(UUID_IDM_ACP_SELF_WRITE_POLICY) {
+            Ok(entry) => entry,
+            Err(OperationError::NoMatchingEntries) => return Ok(()),
+            Err(err) => return Err(err),
+        };
+
+        let domain_entry = self.get_db_domain()?;
+
+        let policy_attrs = self.validate_self_write_attrs(&domain_entry)?;
+        let policy: BTreeSet<&str> = policy_attrs.iter().map(Attribute::as_str).collect();
+
+        let present: BTreeSet<&str> = acp_entry
+            .get_ava_iter_iutf8(Attribute::AcpModifyPresentAttr)
+            .map(|attrs| attrs.collect())
+            .unwrap_or_default();
+
+        let removed: BTreeSet<&str> = acp_entry
+            .get_ava_iter_iutf8(Attribute::AcpModifyRemovedAttr)
+            .map(|attrs| attrs.collect())
+            .unwrap_or_default();
+
+        if present == policy && removed == policy {
+            trace!("Self write policy access control is up to date");
+            return Ok(());
+        }
+
+        let mut modlist = ModifyList::new_list(vec![
+            m_purge(Attribute::AcpModifyPresentAttr),
+            m_purge(Attribute::AcpModifyRemovedAttr),
+        ]);
+        for attr in policy {
+            modlist.push_mod(Modify::Present(
+                Attribute::AcpModifyPresentAttr,
+                Value::new_iutf8(attr),
+            ));
+            modlist.push_mod(Modify::Present(
+                Attribute::AcpModifyRemovedAttr,
+                Value::new_iutf8(attr),
+            ));
+        }
+
+        info!("Regenerating the self write policy access control from the domain policy");
+        self.internal_modify_uuid(UUID_IDM_ACP_SELF_WRITE_POLICY, &modlist)
+    }
+
     /// Reloads feature configurations if they have changed in this operation
     #[instrument(level = "debug", skip_all)]
     pub(crate) fn reload_feature_config(&mut self) -> Result<(), OperationError> {
@@ -2799,8 +3287,8 @@ impl<'a> QueryServerWriteTransaction<'a> {
         self.internal_modify(&filt, &modl)
     }
 
-    /// Initiate a domain rename process. This is generally an internal function but it's
-    /// exposed to the cli for admins to be able to initiate the process.
+    /// Rename the domain and regenerate every spn within this transaction. Admins should
+    /// use [`domain_rename`](Self::domain_rename) which migrates spns in chunks.
     ///
     /// # Safety
     /// This is UNSAFE because while it may change the domain name, it doesn't update
@@ -2819,6 +3307,111 @@ impl<'a> QueryServerWriteTransaction<'a> {
         self.internal_modify(&filt, &modl)
     }
 
+    /// Begin an online domain rename. The domain entry is updated immediately and the
+    /// previous name is recorded so that spns can be regenerated in chunks by
+    /// [`domain_rename_migrate`](Self::domain_rename_migrate). A new rename is refused
+    /// until that migration has completed.
+    pub fn domain_rename(&mut self, new_domain_name: &str) -> Result<(), OperationError> {
+        let domain_entry = self.get_db_domain()?;
+
+        if let Some(previous) = domain_entry.get_ava_single_iname(Attribute::DomainRenameFrom) {
+            error!(
+                ?previous,
+                "Refusing to rename domain, a previous rename is still migrating"
+            );
+            return Err(OperationError::MG0011DomainRenameInProgress);
+        }
+
+        let old_domain_name = self.get_domain_name().to_string();
+        if old_domain_name == new_domain_name {
+            debug!("Domain name not changing");
+            return Ok(());
+        }
+
+        let modl = ModifyList::new_list(vec![
+            m_purge(Attribute::DomainName),
+            m_pres(Attribute::DomainName, &Value::new_iname(new_domain_name)),
+            m_purge(Attribute::DomainRenameFrom),
+            m_pres(
+                Attribute::DomainRenameFrom,
+                &Value::new_iname(&old_domain_name),
+            ),
+        ]);
+        let filt = filter_all!(f_eq(Attribute::Uuid, PVUUID_DOMAIN_INFO.clone()));
+        self.internal_modify(&filt, &modl)?;
+
+        // OAuth2 clients derive their presented identities from spns, so ensure they
+        // are rebuilt with the new domain name.
+        self.changed_flags.insert(ChangeFlag::OAUTH2);
+        Ok(())
+    }
+
+    /// Regenerate the spns of the next page of at most `limit` groups and accounts that
+    /// still carry the previous domain name. `cursor` is the cursor returned by the prior
+    /// call, or `None` to begin from the first page. The cursor of the following page is
+    /// returned while entries remain to be read, and when none remain the rename marker is
+    /// removed from the domain entry.
+    ///
+    /// Pages are read in entry id order, so each entry is only loaded once per pass. Entries
+    /// created during the migration are given the new name by the spn plugin, so an
+    /// interrupted migration can safely be resumed from the first page.
+    pub fn domain_rename_migrate(
+        &mut self,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<Option<String>, OperationError> {
+        let domain_entry = self.get_db_domain()?;
+
+        if domain_entry
+            .get_ava_single_iname(Attribute::DomainRenameFrom)
+            .is_none()
+        {
+            return Ok(None);
+        }
+
+        let domain_name = self.get_domain_name().to_string();
+
+        let filt = filter!(f_or!([
+            f_eq(Attribute::Class, EntryClass::Group.into()),
+            f_eq(Attribute::Class, EntryClass::Account.into()),
+        ]))
+        .validate(self.get_schema())
+        .map_err(OperationError::SchemaViolation)?;
+        let se = SearchEvent::new_internal(filt);
+
+        let (entries, next) = self.search_page(&se, cursor, limit, self.curtime)?;
+
+        let stale: Vec<_> = entries
+            .iter()
+            .filter(|e| {
+                e.generate_spn(&domain_name)
+                    .map(|spn| e.get_ava_set(Attribute::Spn) != Some(&spn))
+                    .unwrap_or(false)
+            })
+            .map(|e| f_eq(Attribute::Uuid, PartialValue::Uuid(e.get_uuid())))
+            .collect();
+
+        if !stale.is_empty() {
+            admin_info!(
+                count = stale.len(),
+                more = next.is_some(),
+                "Regenerating spns for domain rename"
+            );
+
+            // Purge the spn and allow the spn plugin to regenerate it.
+            self.internal_modify(&filter!(f_or(stale)), &modlist!([m_purge(Attribute::Spn)]))?;
+        }
+
+        if next.is_none() {
+            admin_info!("Domain rename migration complete");
+            let filt = filter_all!(f_eq(Attribute::Uuid, PVUUID_DOMAIN_INFO.clone()));
+            self.internal_modify(&filt, &modlist!([m_purge(Attribute::DomainRenameFrom)]))?;
+            self.changed_flags.insert(ChangeFlag::OAUTH2);
+        }
+
+        Ok(next)
+    }
+
     pub fn reindex(&mut self, immediate: bool) -> Result<(), OperationError> {
         // initiate a be reindex here. This could have been from first run checking
         // the versions, or it could just be from the cli where an admin needs to do an
@@ -2899,6 +3492,12 @@ impl<'a> QueryServerWriteTransaction<'a> {
             }
         }
 
+        // The self write policy access control is generated from the domain entry, so
+        // this must occur before access controls are reloaded.
+        if self.changed_flags.intersects(ChangeFlag::DOMAIN) {
+            self.reload_self_write_policy()?;
+        }
+
         // We need to reload cryptographic providers before anything else so that
         // sync agreements and the domain can access their key material.
         if self
@@ -2942,11 +3541,21 @@ impl<'a> QueryServerWriteTransaction<'a> {
             self.reload_feature_config()?;
         }
 
+        // Subscription filters are validated against schema, so they are reloaded when
+        // either changes.
+        if self
+            .changed_flags
+            .intersects(ChangeFlag::SCHEMA | ChangeFlag::WEBHOOK)
+        {
+            self.invalidate_webhook_subscriptions();
+        }
+
         // Clear flags
         self.changed_flags.remove(
             ChangeFlag::DOMAIN
                 | ChangeFlag::SCHEMA
                 | ChangeFlag::FEATURE
+                | ChangeFlag::WEBHOOK
                 | ChangeFlag::SYSTEM_CONFIG
                 | ChangeFlag::ACP
                 | ChangeFlag::SYNC_AGREEMENT
@@ -2991,6 +3600,10 @@ impl<'a> QueryServerWriteTransaction<'a> {
             resolve_filter_cache_clear,
             mut resolve_filter_cache_write,
             txn_name_to_uuid: _,
+            security_events: _,
+            webhooks,
+            pending_webhooks,
+            webhook_subscriptions,
         } = self;
         debug_assert!(!committed);
 
@@ -3020,9 +3633,16 @@ impl<'a> QueryServerWriteTransaction<'a> {
             .map(|_| feature_config.commit())
             .map(|_| phase.commit())
             .map(|_| dyngroup_cache.commit())
+            .map(|_| webhook_subscriptions.commit())
             .and_then(|_| key_providers.commit())
             .and_then(|_| accesscontrols.commit())
-            .and_then(|_| be_txn.commit())
+            .and_then(|_| be_txn.commit())?;
+
+        // Only now that the changes are durable can anyone be told about them.
+        pending_webhooks
+            .into_iter()
+            .for_each(|delivery| webhooks.submit(delivery));
+        Ok(())
     }
 
     pub(crate) fn get_txn_cid(&self) -> &Cid {
@@ -3037,6 +3657,8 @@ mod tests {
         server::{ScimListResponse, ScimReference},
         JsonValue, ScimEntryGetQuery, ScimFilter,
     };
+    use kanidm_proto::v1::GroupMemberCounts;
+    use std::collections::BTreeSet;
     use std::num::NonZeroU64;
 
     #[qs_test]
@@ -3384,6 +4006,276 @@ mod tests {
         // Commit.
     }
 
+    #[qs_test]
+    async fn test_entry_attr_last_modified(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        let t_uuid = Uuid::new_v4();
+        assert!(server_txn
+            .internal_create(vec![entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Account.to_value()),
+                (Attribute::Class, EntryClass::Person.to_value()),
+                (Attribute::Name, Value::new_iname("testperson1")),
+                (Attribute::Uuid, Value::Uuid(t_uuid)),
+                (Attribute::Description, Value::new_utf8s("testperson1")),
+                (Attribute::DisplayName, Value::new_utf8s("testperson1"))
+            )])
+            .is_ok());
+        server_txn.commit().expect("Failed to commit");
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        let before = server_txn
+            .internal_search_uuid(t_uuid)
+            .expect("Unable to access entry");
+        assert!(server_txn
+            .internal_modify_uuid(
+                t_uuid,
+                &ModifyList::new_purge_and_set(Attribute::Description, Value::new_utf8s("changed"))
+            )
+            .is_ok());
+        let after = server_txn
+            .internal_search_uuid(t_uuid)
+            .expect("Unable to access entry");
+
+        // Only the modified attribute has a new cid.
+        assert_ne!(
+            before.get_ava_last_modified(Attribute::Description),
+            after.get_ava_last_modified(Attribute::Description)
+        );
+        for attr in [Attribute::Name, Attribute::DisplayName] {
+            assert!(after.get_ava_last_modified(&attr).is_some());
+            assert_eq!(
+                before.get_ava_last_modified(&attr),
+                after.get_ava_last_modified(&attr)
+            );
+        }
+
+        // The cids are available to SCIM on request.
+        let reduced = after.as_ref().clone().into_reduced();
+        let attr_meta = reduced.to_scim_attr_meta();
+        assert_eq!(
+            attr_meta
+                .get(&Attribute::Description)
+                .map(|meta| meta.version.clone()),
+            after
+                .get_ava_last_modified(Attribute::Description)
+                .map(|cid| cid.to_string())
+        );
+        let scim_entry = reduced
+            .to_scim_kanidm(&mut server_txn)
+            .expect("Unable to convert entry to scim");
+        assert!(scim_entry.ext_attr_meta.is_none());
+
+        server_txn.commit().expect("Failed to commit");
+    }
+
+    #[qs_test]
+    async fn test_search_page_cursor(server: &QueryServer) {
+        let ct = duration_from_epoch_now();
+        let mut server_txn = server.write(ct).await.unwrap();
+
+        let group = |name: String| {
+            entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Group.to_value()),
+                (Attribute::Name, Value::new_iname(&name))
+            )
+        };
+
+        let created: Vec<_> = (0..1000)
+            .map(|i| group(format!("testgroup_page_{i}")))
+            .collect();
+        assert!(server_txn.internal_create(created).is_ok());
+
+        let se = SearchEvent::new_internal_invalid(filter!(f_eq(
+            Attribute::Class,
+            EntryClass::Group.into()
+        )));
+
+        let mut seen: Vec<Uuid> = Vec::new();
+        let mut cursor: Option<String> = None;
+        let mut first_cursor = None;
+        let mut pages = 0;
+        loop {
+            let (page, next) = server_txn
+                .search_page(&se, cursor.as_deref(), 37, ct)
+                .expect("failed to search page");
+            assert!(page.len() <= 37);
+            seen.extend(page.iter().map(|e| e.get_uuid()));
+            pages += 1;
+
+            // Part way through the walk, another entry is created.
+            if pages == 10 {
+                assert!(server_txn
+                    .internal_create(vec![group("testgroup_page_late".to_string())])
+                    .is_ok());
+            }
+
+            if first_cursor.is_none() {
+                first_cursor.clone_from(&next);
+            }
+
+            match next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        // Every group was returned exactly once, including the late entry.
+        let unique: BTreeSet<_> = seen.iter().copied().collect();
+        assert_eq!(unique.len(), seen.len());
+
+        let all = server_txn
+            .internal_search(filter!(f_eq(Attribute::Class, EntryClass::Group.into())))
+            .expect("failed to search");
+        assert_eq!(all.len(), seen.len());
+        assert!(all.iter().all(|e| unique.contains(&e.get_uuid())));
+
+        // The cursor is opaque, and one that was not issued by the server is rejected.
+        let first_cursor = first_cursor.expect("no cursor was returned");
+        assert!(!first_cursor.contains(&seen[36].to_string()));
+
+        // Alter the first character of the ciphertext.
+        let mut parts: Vec<String> = first_cursor.split('.').map(str::to_string).collect();
+        let flipped = if parts[3].starts_with('A') { "B" } else { "A" };
+        parts[3].replace_range(0..1, flipped);
+        let tampered = parts.join(".");
+
+        let uuid_cursor = Uuid::new_v4().to_string();
+        for cursor in [tampered.as_str(), "not a cursor", uuid_cursor.as_str()] {
+            assert_eq!(
+                server_txn.search_page(&se, Some(cursor), 37, ct),
+                Err(OperationError::InvalidRequestState)
+            );
+        }
+    }
+
+    #[qs_test]
+    async fn test_group_member_counts(server: &QueryServer) {
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+
+        let person_a = Uuid::new_v4();
+        let person_b = Uuid::new_v4();
+        let group_inner = Uuid::new_v4();
+        let group_outer = Uuid::new_v4();
+        let group_dyn = Uuid::new_v4();
+        let group_empty = Uuid::new_v4();
+
+        let person = |name: &str, uuid: Uuid| {
+            entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Account.to_value()),
+                (Attribute::Class, EntryClass::Person.to_value()),
+                (Attribute::Name, Value::new_iname(name)),
+                (Attribute::Uuid, Value::Uuid(uuid)),
+                (
+                    Attribute::Description,
+                    Value::new_utf8s("member_count_person")
+                ),
+                (Attribute::DisplayName, Value::new_utf8s(name))
+            )
+        };
+
+        assert!(server_txn
+            .internal_create(vec![
+                person("testperson_a", person_a),
+                person("testperson_b", person_b),
+                entry_init!(
+                    (Attribute::Class, EntryClass::Object.to_value()),
+                    (Attribute::Class, EntryClass::Group.to_value()),
+                    (Attribute::Name, Value::new_iname("testgroup_inner")),
+                    (Attribute::Uuid, Value::Uuid(group_inner)),
+                    (Attribute::Member, Value::Refer(person_a))
+                ),
+                entry_init!(
+                    (Attribute::Class, EntryClass::Object.to_value()),
+                    (Attribute::Class, EntryClass::Group.to_value()),
+                    (Attribute::Name, Value::new_iname("testgroup_outer")),
+                    (Attribute::Uuid, Value::Uuid(group_outer)),
+                    (Attribute::Member, Value::Refer(group_inner)),
+                    (Attribute::Member, Value::Refer(person_b))
+                ),
+                entry_init!(
+                    (Attribute::Class, EntryClass::Object.to_value()),
+                    (Attribute::Class, EntryClass::Group.to_value()),
+                    (Attribute::Name, Value::new_iname("testgroup_empty")),
+                    (Attribute::Uuid, Value::Uuid(group_empty))
+                ),
+                entry_init!(
+                    (Attribute::Class, EntryClass::Object.to_value()),
+                    (Attribute::Class, EntryClass::Group.to_value()),
+                    (Attribute::Class, EntryClass::DynGroup.to_value()),
+                    (Attribute::Name, Value::new_iname("testgroup_dyn")),
+                    (Attribute::Uuid, Value::Uuid(group_dyn)),
+                    (
+                        Attribute::DynGroupFilter,
+                        Value::JsonFilt(ProtoFilter::Eq(
+                            Attribute::Description.to_string(),
+                            "member_count_person".to_string()
+                        ))
+                    )
+                ),
+            ])
+            .is_ok());
+
+        let person_a_entry = server_txn
+            .internal_search_uuid(person_a)
+            .expect("Unable to access person");
+        let person_a_ident = Identity::from_impersonate_entry_readonly(person_a_entry);
+
+        let mut counts = |ident: &Identity, uuid: Uuid| {
+            let group = server_txn
+                .internal_search_uuid(uuid)
+                .expect("Unable to access group")
+                .as_ref()
+                .clone()
+                .into_reduced();
+            server_txn
+                .group_member_counts(ident, &group)
+                .expect("Unable to count members")
+        };
+        let internal = Identity::from_internal();
+
+        assert_eq!(
+            counts(&internal, group_inner),
+            GroupMemberCounts {
+                member: 1,
+                dyn_member: 0,
+                nested: 1,
+            }
+        );
+        // Members of the inner group are nested members of the outer group.
+        assert_eq!(
+            counts(&internal, group_outer),
+            GroupMemberCounts {
+                member: 2,
+                dyn_member: 0,
+                nested: 3,
+            }
+        );
+        assert_eq!(
+            counts(&internal, group_dyn),
+            GroupMemberCounts {
+                member: 0,
+                dyn_member: 2,
+                nested: 2,
+            }
+        );
+        // A group with no members doesn't need to search for nested members.
+        assert_eq!(counts(&internal, group_empty), GroupMemberCounts::default());
+
+        // A person may only read their own memberof, so the other members of the outer
+        // group are not counted for them.
+        assert_eq!(
+            counts(&person_a_ident, group_outer),
+            GroupMemberCounts {
+                member: 2,
+                dyn_member: 0,
+                nested: 1,
+            }
+        );
+    }
+
     #[qs_test]
     async fn test_scim_entry_structure(server: &QueryServer) {
         let mut read_txn = server.read().await.unwrap();
@@ -90,9 +90,29 @@ impl QueryServerWriteTransaction<'_> {
                 e
             })?;
         if !op_allow {
+            self.submit_access_denied(&me.ident, pre_candidates.iter().map(|e| e.get_uuid()));
             return Err(OperationError::AccessDenied);
         }
 
+        // If the caller expects a specific version of the entries, reject the change when
+        // any of them has since been changed.
+        if let Some(expected_cid) = &me.expected_cid {
+            if let Some(current) = pre_candidates
+                .iter()
+                .map(|e| e.get_last_changed())
+                .find(|cid| cid != expected_cid)
+            {
+                request_error!(
+                    ?expected_cid,
+                    ?current,
+                    "modify: entry has changed since the expected version"
+                );
+                return Err(OperationError::MD0001EntryVersionConflict(
+                    current.to_string(),
+                ));
+            }
+        }
+
         // Clone a set of writeables.
         // Apply the modlist -> Remember, we have a set of origs
         // and the new modified ents.
@@ -270,6 +290,23 @@ impl QueryServerWriteTransaction<'_> {
             self.changed_flags.insert(ChangeFlag::FEATURE)
         }
 
+        if !self.changed_flags.contains(ChangeFlag::WEBHOOK)
+            && norm_cand
+                .iter()
+                .zip(pre_candidates.iter().map(|e| e.as_ref()))
+                .any(|(post, pre)| {
+                    post.attribute_equality(
+                        Attribute::Class,
+                        &EntryClass::WebhookSubscription.into(),
+                    ) || pre.attribute_equality(
+                        Attribute::Class,
+                        &EntryClass::WebhookSubscription.into(),
+                    )
+                })
+        {
+            self.changed_flags.insert(ChangeFlag::WEBHOOK)
+        }
+
         if !self.changed_flags.contains(ChangeFlag::DOMAIN)
             && norm_cand
                 .iter()
@@ -482,6 +519,14 @@ impl QueryServerWriteTransaction<'_> {
             self.changed_flags.insert(ChangeFlag::FEATURE)
         }
 
+        if !self.changed_flags.contains(ChangeFlag::WEBHOOK)
+            && norm_cand.iter().any(|e| {
+                e.attribute_equality(Attribute::Class, &EntryClass::WebhookSubscription.into())
+            })
+        {
+            self.changed_flags.insert(ChangeFlag::WEBHOOK)
+        }
+
         if !self.changed_flags.contains(ChangeFlag::DOMAIN)
             && norm_cand
                 .iter()
@@ -781,6 +826,51 @@ mod tests {
             .is_ok());
     }
 
+    #[qs_test]
+    async fn test_modify_expected_cid(server: &QueryServer) {
+        let t_uuid = Uuid::new_v4();
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        assert!(server_txn
+            .internal_create(vec![entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Uuid, Value::Uuid(t_uuid))
+            ),])
+            .is_ok());
+        let created_cid = server_txn.get_txn_cid().clone();
+        assert!(server_txn.commit().is_ok());
+
+        let modify_event = |expected_cid: &Cid, desc: &str| {
+            ModifyEvent::new_internal_invalid(
+                filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(t_uuid))),
+                ModifyList::new_purge_and_set(Attribute::Description, Value::new_utf8s(desc)),
+            )
+            .with_expected_cid(Some(expected_cid.clone()))
+        };
+
+        // The version that was read is current, so the change is applied.
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        assert!(server_txn.modify(&modify_event(&created_cid, "a")).is_ok());
+        let modified_cid = server_txn.get_txn_cid().clone();
+        assert!(server_txn.commit().is_ok());
+
+        // The entry has since changed, so the stale version is rejected.
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        assert_eq!(
+            server_txn.modify(&modify_event(&created_cid, "b")),
+            Err(OperationError::MD0001EntryVersionConflict(
+                modified_cid.to_string()
+            ))
+        );
+        assert!(server_txn.modify(&modify_event(&modified_cid, "b")).is_ok());
+
+        let entry = server_txn
+            .internal_search_uuid(t_uuid)
+            .expect("Failed to get entry.");
+        assert_eq!(entry.get_ava_single_utf8(Attribute::Description), Some("b"));
+        assert!(server_txn.commit().is_ok());
+    }
+
     #[qs_test]
     async fn test_modify_invalid_class(server: &QueryServer) {
         // Test modifying an entry and adding an extra class, that would cause the entry
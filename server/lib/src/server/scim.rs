@@ -8,11 +8,10 @@ use crypto_glue::s256::Sha256Output;
 use kanidm_proto::scim_v1::client::{
     ScimEntryAssertion, ScimEntryPostGeneric, ScimEntryPutGeneric,
 };
-use kanidm_proto::scim_v1::JsonValue;
-use std::collections::{
-    // BTreeSet,
-    BTreeMap,
+use kanidm_proto::scim_v1::{
+    JsonValue, ScimBulkMethod, ScimBulkOperation, ScimBulkRequest, SCIM_BULK_ID_PREFIX,
 };
+use std::collections::{BTreeMap, BTreeSet};
 
 #[derive(Debug)]
 pub struct ScimEntryPutEvent {
@@ -145,6 +144,280 @@ impl ScimAssertEvent {
     }
 }
 
+/// The default maximum number of operations accepted in a single SCIM bulk request.
+pub const SCIM_BULK_DEFAULT_MAX_OPERATIONS: usize = 1000;
+
+#[derive(Debug)]
+pub(crate) enum ScimBulkAction {
+    Create {
+        entry: ScimEntryPostGeneric,
+    },
+    Put {
+        target: String,
+        attrs: BTreeMap<Attribute, Option<JsonValue>>,
+    },
+    Delete {
+        target: String,
+    },
+}
+
+#[derive(Debug)]
+pub(crate) struct ScimBulkItem {
+    pub(crate) method: ScimBulkMethod,
+    pub(crate) bulk_id: Option<String>,
+    /// The action to perform. This is None if the operation was rejected
+    /// before it could be applied.
+    pub(crate) action: Option<ScimBulkAction>,
+    /// The index of other operations in this request that this item references
+    /// by bulkId.
+    pub(crate) depends_on: BTreeSet<usize>,
+}
+
+/// A parsed SCIM bulk request. The identity performing the change is resolved within the
+/// write transaction that applies the operations.
+#[derive(Debug)]
+pub struct ScimBulkEvent {
+    pub(crate) items: Vec<ScimBulkItem>,
+    /// Operations that were rejected during parsing, keyed by their index.
+    pub(crate) rejected: BTreeMap<usize, OperationError>,
+    pub(crate) fail_on_errors: Option<usize>,
+}
+
+/// The operations of a SCIM bulk request that were applied by a single pass.
+#[derive(Debug)]
+pub(crate) struct ScimBulkApplied {
+    /// The resulting entry of each operation that succeeded, keyed by index.
+    pub(crate) entries: BTreeMap<usize, Option<ScimEntryKanidm>>,
+    /// The number of operations that were processed before the failOnErrors limit was reached.
+    pub(crate) processed: usize,
+}
+
+/// The outcome of a single operation from a SCIM bulk request.
+#[derive(Debug)]
+pub struct ScimBulkItemResult {
+    pub method: ScimBulkMethod,
+    pub bulk_id: Option<String>,
+    /// The resulting entry on a successful create or put, None for a delete.
+    pub outcome: Result<Option<ScimEntryKanidm>, OperationError>,
+}
+
+impl ScimBulkEvent {
+    pub fn try_from(
+        request: ScimBulkRequest,
+        max_operations: usize,
+    ) -> Result<Self, OperationError> {
+        let ScimBulkRequest {
+            fail_on_errors,
+            operations,
+            ..
+        } = request;
+
+        if operations.len() > max_operations {
+            error!(
+                operations = operations.len(),
+                max_operations, "SCIM bulk request exceeds the maximum number of operations"
+            );
+            return Err(OperationError::SC0034BulkTooManyOperations);
+        }
+
+        let mut rejected = BTreeMap::new();
+
+        // First, parse the operations and assign a uuid to any entry that will be
+        // created with a bulkId so that other operations can reference it.
+        let mut bulk_ids: BTreeMap<String, (usize, Uuid)> = BTreeMap::new();
+
+        let mut parsed = Vec::with_capacity(operations.len());
+        for (idx, operation) in operations.into_iter().enumerate() {
+            let ScimBulkOperation {
+                method,
+                bulk_id,
+                path,
+                data,
+            } = operation;
+
+            let action = Self::parse_operation(method, &path, data);
+
+            let action = match (action, bulk_id.as_ref()) {
+                (Ok(ScimBulkAction::Create { mut entry }), Some(bulk_id)) => {
+                    if bulk_ids.contains_key(bulk_id) {
+                        Err(OperationError::SC0036BulkIdDuplicate)
+                    } else {
+                        Self::assign_uuid(&mut entry).map(|uuid| {
+                            bulk_ids.insert(bulk_id.clone(), (idx, uuid));
+                            ScimBulkAction::Create { entry }
+                        })
+                    }
+                }
+                (action, _) => action,
+            };
+
+            parsed.push((method, bulk_id, action));
+        }
+
+        // Now we can resolve bulkId references into the uuids we assigned.
+        let items = parsed
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (method, bulk_id, action))| {
+                let mut depends_on = BTreeSet::new();
+
+                let action = action.and_then(|mut action| {
+                    let resolved = match &mut action {
+                        ScimBulkAction::Create { entry } => {
+                            entry.attrs.values_mut().try_for_each(|value| {
+                                Self::resolve_json(value, &bulk_ids, &mut depends_on)
+                            })
+                        }
+                        ScimBulkAction::Put { target, attrs } => {
+                            Self::resolve_target(target, &bulk_ids, &mut depends_on)?;
+                            attrs.values_mut().flatten().try_for_each(|value| {
+                                Self::resolve_json(value, &bulk_ids, &mut depends_on)
+                            })
+                        }
+                        ScimBulkAction::Delete { target } => {
+                            Self::resolve_target(target, &bulk_ids, &mut depends_on)
+                        }
+                    };
+                    resolved.map(|()| action)
+                });
+
+                let action = match action {
+                    Ok(action) => Some(action),
+                    Err(err) => {
+                        rejected.insert(idx, err);
+                        None
+                    }
+                };
+
+                ScimBulkItem {
+                    method,
+                    bulk_id,
+                    action,
+                    depends_on,
+                }
+            })
+            .collect();
+
+        Ok(ScimBulkEvent {
+            items,
+            rejected,
+            fail_on_errors: fail_on_errors.map(|n| n as usize),
+        })
+    }
+
+    fn parse_operation(
+        method: ScimBulkMethod,
+        path: &str,
+        data: Option<JsonValue>,
+    ) -> Result<ScimBulkAction, OperationError> {
+        let path = path.strip_prefix("/scim/v1").unwrap_or(path);
+        let mut components = path.trim_start_matches('/').splitn(2, '/');
+
+        let resource = components.next();
+        let target = components.next().filter(|id| !id.is_empty());
+
+        if resource != Some("Entry") {
+            error!(
+                ?path,
+                "SCIM bulk operation path is not a supported resource"
+            );
+            return Err(OperationError::SC0037BulkOperationInvalid);
+        }
+
+        match (method, target, data) {
+            (ScimBulkMethod::Post, None, Some(data)) => serde_json::from_value(data)
+                .map(|entry| ScimBulkAction::Create { entry })
+                .map_err(|err| {
+                    error!(?err, "SCIM bulk POST data is invalid");
+                    OperationError::SC0037BulkOperationInvalid
+                }),
+            (ScimBulkMethod::Put, Some(target), Some(data)) => serde_json::from_value(data)
+                .map(|attrs| ScimBulkAction::Put {
+                    target: target.to_string(),
+                    attrs,
+                })
+                .map_err(|err| {
+                    error!(?err, "SCIM bulk PUT data is invalid");
+                    OperationError::SC0037BulkOperationInvalid
+                }),
+            (ScimBulkMethod::Delete, Some(target), _) => Ok(ScimBulkAction::Delete {
+                target: target.to_string(),
+            }),
+            (method, target, _) => {
+                error!(
+                    ?method,
+                    ?target,
+                    "SCIM bulk operation is missing a target or data"
+                );
+                Err(OperationError::SC0037BulkOperationInvalid)
+            }
+        }
+    }
+
+    fn assign_uuid(entry: &mut ScimEntryPostGeneric) -> Result<Uuid, OperationError> {
+        match entry.attrs.get(&Attribute::Uuid) {
+            Some(JsonValue::String(uuid)) => Uuid::parse_str(uuid).map_err(|err| {
+                error!(?err, "SCIM bulk POST contains an invalid uuid");
+                OperationError::SC0004UuidSyntaxInvalid
+            }),
+            Some(_) => Err(OperationError::SC0004UuidSyntaxInvalid),
+            None => {
+                let uuid = Uuid::new_v4();
+                entry
+                    .attrs
+                    .insert(Attribute::Uuid, JsonValue::String(uuid.to_string()));
+                Ok(uuid)
+            }
+        }
+    }
+
+    fn resolve_bulk_id(
+        value: &str,
+        bulk_ids: &BTreeMap<String, (usize, Uuid)>,
+        depends_on: &mut BTreeSet<usize>,
+    ) -> Result<Option<Uuid>, OperationError> {
+        let Some(bulk_id) = value.strip_prefix(SCIM_BULK_ID_PREFIX) else {
+            return Ok(None);
+        };
+
+        let (idx, uuid) = bulk_ids.get(bulk_id).ok_or_else(|| {
+            error!(?bulk_id, "SCIM bulk operation references an unknown bulkId");
+            OperationError::SC0035BulkIdUnresolvable
+        })?;
+
+        depends_on.insert(*idx);
+        Ok(Some(*uuid))
+    }
+
+    fn resolve_target(
+        target: &mut String,
+        bulk_ids: &BTreeMap<String, (usize, Uuid)>,
+        depends_on: &mut BTreeSet<usize>,
+    ) -> Result<(), OperationError> {
+        if let Some(uuid) = Self::resolve_bulk_id(target, bulk_ids, depends_on)? {
+            *target = uuid.to_string();
+        }
+        Ok(())
+    }
+
+    fn resolve_json(
+        value: &mut JsonValue,
+        bulk_ids: &BTreeMap<String, (usize, Uuid)>,
+        depends_on: &mut BTreeSet<usize>,
+    ) -> Result<(), OperationError> {
+        match value {
+            JsonValue::String(s) => Self::resolve_target(s, bulk_ids, depends_on),
+            JsonValue::Array(values) => values
+                .iter_mut()
+                .try_for_each(|value| Self::resolve_json(value, bulk_ids, depends_on)),
+            JsonValue::Object(map) => map
+                .values_mut()
+                .try_for_each(|value| Self::resolve_json(value, bulk_ids, depends_on)),
+            JsonValue::Null | JsonValue::Bool(_) | JsonValue::Number(_) => Ok(()),
+        }
+    }
+}
+
 impl QueryServerWriteTransaction<'_> {
     /// SCIM PUT is the handler where a single entry is updated. In a SCIM PUT request
     /// the request defines the state of an attribute in entirety for the update. This
@@ -175,6 +448,7 @@ impl QueryServerWriteTransaction<'_> {
         let modify_event = BatchModifyEvent {
             ident: ident.clone(),
             modset,
+            expected_cids: BTreeMap::new(),
         };
 
         // dispatch to batch modify
@@ -362,6 +636,102 @@ impl QueryServerWriteTransaction<'_> {
         self.assert(assert_event)
     }
 
+    /// Apply the operations of a SCIM bulk request in order, each exactly once. Operations in
+    /// `failed` are known to fail, and are skipped along with any operation that depends on
+    /// one of them. Processing stops when the failOnErrors limit of the request is reached.
+    ///
+    /// If an operation fails while being applied it is added to `failed` and None is returned,
+    /// as it may have partially altered this transaction. The caller must discard the
+    /// transaction, and may replay the request without the failed operation.
+    pub(crate) fn scim_bulk_apply(
+        &mut self,
+        ident: &Identity,
+        scim_bulk: &ScimBulkEvent,
+        failed: &mut BTreeMap<usize, OperationError>,
+    ) -> Option<ScimBulkApplied> {
+        let mut entries = BTreeMap::new();
+        let mut failures = 0;
+        let fail_limit = scim_bulk
+            .fail_on_errors
+            .filter(|fail_on_errors| *fail_on_errors > 0)
+            .unwrap_or(usize::MAX);
+
+        for (idx, item) in scim_bulk.items.iter().enumerate() {
+            // If this operation depends on a failed operation, it must fail too.
+            if !failed.contains_key(&idx)
+                && item.depends_on.iter().any(|dep| failed.contains_key(dep))
+            {
+                failed.insert(idx, OperationError::SC0035BulkIdUnresolvable);
+            }
+
+            let action = match item.action.as_ref() {
+                Some(action) if !failed.contains_key(&idx) => action,
+                _ => {
+                    // Nothing was applied, so processing can continue past this operation.
+                    failed
+                        .entry(idx)
+                        .or_insert(OperationError::SC0037BulkOperationInvalid);
+                    failures += 1;
+                    if failures >= fail_limit {
+                        return Some(ScimBulkApplied {
+                            entries,
+                            processed: idx + 1,
+                        });
+                    }
+                    continue;
+                }
+            };
+
+            match self.scim_bulk_apply_action(ident.clone(), action) {
+                Ok(entry) => {
+                    entries.insert(idx, entry);
+                }
+                Err(err) => {
+                    debug!(?idx, ?err, "SCIM bulk operation failed");
+                    failed.insert(idx, err);
+                    return None;
+                }
+            }
+        }
+
+        Some(ScimBulkApplied {
+            entries,
+            processed: scim_bulk.items.len(),
+        })
+    }
+
+    fn scim_bulk_apply_action(
+        &mut self,
+        ident: Identity,
+        action: &ScimBulkAction,
+    ) -> Result<Option<ScimEntryKanidm>, OperationError> {
+        match action {
+            ScimBulkAction::Create { entry } => {
+                ScimCreateEvent::try_from(ident, &[], entry.clone(), self)
+                    .and_then(|create_event| self.scim_create(create_event))
+                    .map(Some)
+            }
+            ScimBulkAction::Put { target, attrs } => self
+                .name_to_uuid(target)
+                .and_then(|id| {
+                    let put_generic = ScimEntryPutGeneric {
+                        id,
+                        query: Default::default(),
+                        attrs: attrs.clone(),
+                    };
+                    ScimEntryPutEvent::try_from(ident, put_generic, self)
+                })
+                .and_then(|put_event| self.scim_put(put_event))
+                .map(Some),
+            ScimBulkAction::Delete { target } => self
+                .name_to_uuid(target)
+                .and_then(|target| {
+                    self.scim_delete(ScimDeleteEvent::new(ident, target, EntryClass::Object))
+                })
+                .map(|()| None),
+        }
+    }
+
     pub(crate) fn resolve_scim_json_put(
         &mut self,
         attr: &Attribute,
@@ -406,7 +776,7 @@ impl QueryServerWriteTransaction<'_> {
         value: JsonValue,
     ) -> Result<ValueSet, OperationError> {
         let resolve_status = match schema_a.syntax {
-            SyntaxType::Utf8String => ValueSetUtf8::from_scim_json_put(value),
+            SyntaxType::Utf8String | SyntaxType::Custom => ValueSetUtf8::from_scim_json_put(value),
             SyntaxType::Utf8StringInsensitive => ValueSetIutf8::from_scim_json_put(value),
             SyntaxType::Uuid => ValueSetUuid::from_scim_json_put(value),
             SyntaxType::Boolean => ValueSetBool::from_scim_json_put(value),
@@ -442,6 +812,15 @@ impl QueryServerWriteTransaction<'_> {
             SyntaxType::Message => Err(OperationError::InvalidAttribute(
                 "Message values are not able to be set.".to_string(),
             )),
+            SyntaxType::CredentialLockout => Err(OperationError::InvalidAttribute(
+                "Credential lockouts are not able to be set.".to_string(),
+            )),
+            SyntaxType::PasskeyUsage => Err(OperationError::InvalidAttribute(
+                "Passkey usage is not able to be set.".to_string(),
+            )),
+            SyntaxType::MemberExpiry => Err(OperationError::InvalidAttribute(
+                "Member expiry is not able to be set.".to_string(),
+            )),
             // Can't be set currently as these are only internally generated for key-id's
             // SyntaxType::HexString => ValueSetHexString::from_scim_json_put(value),
             SyntaxType::HexString => Err(OperationError::InvalidAttribute(
@@ -587,13 +966,16 @@ mod tests {
 
         // Set attrs
         let test_mails = vec![
+            // Addresses set by an administrator are trusted as verified.
             ScimMail {
                 primary: true,
                 value: "test@test.test".to_string(),
+                verified: true,
             },
             ScimMail {
                 primary: false,
                 value: "test2@test.test".to_string(),
+                verified: true,
             },
         ];
         let put = ScimEntryPutKanidm {
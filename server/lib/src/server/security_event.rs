@@ -0,0 +1,298 @@
+//! Security events are a versioned, structured record of security relevant outcomes such as
+//! authentications, credential changes, access control denials and replication conflicts.
+//! They are intended to be exported to external systems (such as a SIEM) in near real time.
+//!
+//! Events are submitted to a bounded queue that is only present when something has
+//! subscribed to it. Submission never blocks - if the subscriber can't keep up the event
+//! is dropped and counted, so that a slow consumer can never stall request handling.
+
+use crate::idm::account::AccountValidity;
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use time::OffsetDateTime;
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+/// The version of the [`SecurityEvent`] format. This must be incremented when the meaning
+/// of a field changes or a field is removed, so that consumers can detect the change.
+pub const SECURITY_EVENT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SecurityEventKind {
+    Authentication,
+    CredentialChange,
+    AccessDenied,
+    ReplicationConflict,
+    AccountExpired,
+    AccountNotYetValid,
+    AccountExpiryNotice,
+    AccountDeactivated,
+    GroupMembershipExpired,
+}
+
+impl SecurityEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SecurityEventKind::Authentication => "authentication",
+            SecurityEventKind::CredentialChange => "credential_change",
+            SecurityEventKind::AccessDenied => "access_denied",
+            SecurityEventKind::ReplicationConflict => "replication_conflict",
+            SecurityEventKind::AccountExpired => "account_expired",
+            SecurityEventKind::AccountNotYetValid => "account_not_yet_valid",
+            SecurityEventKind::AccountExpiryNotice => "account_expiry_notice",
+            SecurityEventKind::AccountDeactivated => "account_deactivated",
+            SecurityEventKind::GroupMembershipExpired => "group_membership_expired",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SecurityEventOutcome {
+    Success,
+    Failure,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SecurityEvent {
+    /// See [`SECURITY_EVENT_VERSION`].
+    pub version: u32,
+    #[serde(with = "time::serde::rfc3339")]
+    pub time: OffsetDateTime,
+    pub kind: SecurityEventKind,
+    pub outcome: SecurityEventOutcome,
+    /// The identity that caused the event, if known.
+    pub actor: Option<Uuid>,
+    /// The entries that the event affected.
+    pub targets: Vec<Uuid>,
+    /// The address of the client that caused the event, if it came from the network.
+    pub source: Option<IpAddr>,
+}
+
+impl SecurityEvent {
+    pub fn new(kind: SecurityEventKind, outcome: SecurityEventOutcome, ct: Duration) -> Self {
+        SecurityEvent {
+            version: SECURITY_EVENT_VERSION,
+            time: OffsetDateTime::UNIX_EPOCH + ct,
+            kind,
+            outcome,
+            actor: None,
+            targets: Vec::new(),
+            source: None,
+        }
+    }
+
+    /// An event recording that `account` was refused because it is outside of its validity
+    /// window, or `None` if the account is valid.
+    pub(crate) fn account_validity(
+        account: Uuid,
+        validity: AccountValidity,
+        ct: Duration,
+    ) -> Option<Self> {
+        let kind = match validity {
+            AccountValidity::Valid => return None,
+            AccountValidity::NotYetValid => SecurityEventKind::AccountNotYetValid,
+            AccountValidity::Expired => SecurityEventKind::AccountExpired,
+        };
+
+        Some(
+            SecurityEvent::new(kind, SecurityEventOutcome::Failure, ct)
+                .with_actor(account)
+                .with_targets([account]),
+        )
+    }
+
+    /// Set the actor and source of this event from the identity that caused it.
+    pub(crate) fn with_ident(mut self, ident: &Identity) -> Self {
+        if !ident.is_internal() {
+            self.actor = Some(ident.get_uuid());
+        }
+        self.with_source(ident.source())
+    }
+
+    pub(crate) fn with_source(mut self, source: &Source) -> Self {
+        self.source = source.ip_addr();
+        self
+    }
+
+    pub(crate) fn with_actor(mut self, actor: Uuid) -> Self {
+        self.actor = Some(actor);
+        self
+    }
+
+    pub(crate) fn with_targets<I: IntoIterator<Item = Uuid>>(mut self, targets: I) -> Self {
+        self.targets.extend(targets);
+        self
+    }
+}
+
+/// The queue that security events are submitted to. This is shared by all clones of the
+/// [`QueryServer`] so that any transaction can submit events.
+#[derive(Debug, Default)]
+pub struct SecurityEventQueue {
+    tx: OnceLock<mpsc::Sender<SecurityEvent>>,
+    dropped: AtomicU64,
+}
+
+impl SecurityEventQueue {
+    /// Create the receiver for security events, holding at most `capacity` events before
+    /// further events are dropped. There can only be one subscriber, so this returns `None`
+    /// if one already exists.
+    pub fn subscribe(&self, capacity: usize) -> Option<mpsc::Receiver<SecurityEvent>> {
+        let (tx, rx) = mpsc::channel(capacity.max(1));
+        self.tx.set(tx).ok().map(|()| rx)
+    }
+
+    /// The number of events that have been dropped because the subscriber was not keeping
+    /// up, or had gone away.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub fn submit(&self, event: SecurityEvent) {
+        let Some(tx) = self.tx.get() else {
+            return;
+        };
+
+        match tx.try_send(event) {
+            Ok(()) => {}
+            Err(TrySendError::Full(event)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                debug!(kind = %event.kind.as_str(), "security event queue is full, dropping event");
+            }
+            Err(TrySendError::Closed(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl QueryServerWriteTransaction<'_> {
+    /// Record that access controls denied `ident` an operation on `targets`.
+    pub(crate) fn submit_access_denied<I: IntoIterator<Item = Uuid>>(
+        &self,
+        ident: &Identity,
+        targets: I,
+    ) {
+        self.submit_security_event(
+            SecurityEvent::new(
+                SecurityEventKind::AccessDenied,
+                SecurityEventOutcome::Failure,
+                self.curtime,
+            )
+            .with_ident(ident)
+            .with_targets(targets),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_security_event_queue_drops_when_full() {
+        let queue = SecurityEventQueue::default();
+
+        // Without a subscriber, events are discarded without being counted.
+        queue.submit(SecurityEvent::new(
+            SecurityEventKind::Authentication,
+            SecurityEventOutcome::Success,
+            Duration::ZERO,
+        ));
+        assert_eq!(queue.dropped(), 0);
+
+        let mut rx = queue.subscribe(2).expect("failed to subscribe");
+        assert!(queue.subscribe(2).is_none());
+
+        for _ in 0..5 {
+            queue.submit(SecurityEvent::new(
+                SecurityEventKind::Authentication,
+                SecurityEventOutcome::Failure,
+                Duration::ZERO,
+            ));
+        }
+
+        assert_eq!(queue.dropped(), 3);
+        assert!(rx.try_recv().is_ok());
+        assert!(rx.try_recv().is_ok());
+        assert!(rx.try_recv().is_err());
+
+        drop(rx);
+        queue.submit(SecurityEvent::new(
+            SecurityEventKind::Authentication,
+            SecurityEventOutcome::Failure,
+            Duration::ZERO,
+        ));
+        assert_eq!(queue.dropped(), 4);
+    }
+
+    #[test]
+    fn test_security_event_serialisation() {
+        let actor = Uuid::new_v4();
+        let target = Uuid::new_v4();
+        let event = SecurityEvent::new(
+            SecurityEventKind::AccessDenied,
+            SecurityEventOutcome::Failure,
+            Duration::from_secs(60),
+        )
+        .with_actor(actor)
+        .with_targets([target])
+        .with_source(&Source::Https([192, 0, 2, 1].into()));
+
+        let json = serde_json::to_value(&event).expect("failed to serialise");
+        assert_eq!(json["version"], SECURITY_EVENT_VERSION);
+        assert_eq!(json["time"], "1970-01-01T00:01:00Z");
+        assert_eq!(json["kind"], "access_denied");
+        assert_eq!(json["outcome"], "failure");
+        assert_eq!(json["actor"], actor.to_string());
+        assert_eq!(json["targets"][0], target.to_string());
+        assert_eq!(json["source"], "192.0.2.1");
+    }
+
+    #[qs_test]
+    async fn test_security_event_access_denied(server: &QueryServer) {
+        let mut event_rx = server
+            .security_events()
+            .subscribe(8)
+            .expect("failed to subscribe");
+
+        let ct = duration_from_epoch_now();
+        let mut server_txn = server.write(ct).await.expect("txn");
+
+        let group_uuid = Uuid::new_v4();
+        let e_group = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Group.to_value()),
+            (Attribute::Name, Value::new_iname("security_event_group")),
+            (Attribute::Uuid, Value::Uuid(group_uuid))
+        );
+        server_txn
+            .internal_create(vec![e_group])
+            .expect("failed to create group");
+
+        // Admin can read groups, but not change them.
+        let admin = server_txn
+            .internal_search_uuid(UUID_ADMIN)
+            .expect("failed to find admin");
+        let me = ModifyEvent::new_impersonate_identity(
+            Identity::from_impersonate_entry_readwrite(admin),
+            filter!(f_eq(Attribute::Uuid, PartialValue::Uuid(group_uuid))),
+            ModifyList::new_purge_and_set(Attribute::Description, Value::new_utf8s("denied")),
+        );
+        assert_eq!(server_txn.modify(&me), Err(OperationError::AccessDenied));
+
+        let event = event_rx
+            .try_recv()
+            .expect("no security event was submitted");
+        assert_eq!(event.kind, SecurityEventKind::AccessDenied);
+        assert_eq!(event.outcome, SecurityEventOutcome::Failure);
+        assert_eq!(event.actor, Some(UUID_ADMIN));
+        assert_eq!(event.targets, vec![group_uuid]);
+        assert_eq!(event.time, OffsetDateTime::UNIX_EPOCH + ct);
+        assert!(event_rx.try_recv().is_err());
+    }
+}
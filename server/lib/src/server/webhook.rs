@@ -0,0 +1,690 @@
+//! Webhooks notify external systems when entries are created, modified or deleted.
+//!
+//! A webhook subscription names the url to deliver to, the kinds of change it is interested
+//! in, and optionally a filter limiting the entries it is notified about. As each write
+//! operation completes the changed entries are matched against the subscriptions, and the
+//! resulting deliveries are held by the transaction. They are only submitted to the webhook
+//! queue once the transaction has committed, so a subscriber is never notified of a change
+//! that was rolled back.
+//!
+//! Only the names of the changed attributes are delivered, never their values. A receiver
+//! that needs the values should read the entry with its own credentials so that access
+//! controls still apply.
+//!
+//! As with security events, submission never blocks. If the delivery task can't keep up the
+//! delivery is dropped and counted.
+
+use crate::filter::{FilterValid, FilterValidResolved};
+use crate::prelude::*;
+use crate::server::access::AccessControlsTransaction;
+use crate::server::ChangeFlag;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+/// The version of the [`WebhookPayload`] format. This must be incremented when the meaning
+/// of a field changes or a field is removed, so that receivers can detect the change.
+pub const WEBHOOK_PAYLOAD_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventKind {
+    Create,
+    Modify,
+    Delete,
+}
+
+impl WebhookEventKind {
+    pub const ALL: [WebhookEventKind; 3] = [
+        WebhookEventKind::Create,
+        WebhookEventKind::Modify,
+        WebhookEventKind::Delete,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEventKind::Create => "create",
+            WebhookEventKind::Modify => "modify",
+            WebhookEventKind::Delete => "delete",
+        }
+    }
+}
+
+/// The body delivered to a webhook for a single changed entry.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WebhookPayload {
+    pub version: u32,
+    /// The uuid of the subscription that this delivery is for.
+    pub subscription: Uuid,
+    pub kind: WebhookEventKind,
+    /// The uuid of the entry that changed.
+    pub entry: Uuid,
+    /// The names of the attributes changed by this operation. This is empty for a delete.
+    pub attributes: Vec<String>,
+    /// The change identifier of the transaction that made the change.
+    pub cid: String,
+}
+
+/// A payload and where to deliver it.
+#[derive(Clone)]
+pub struct WebhookDelivery {
+    pub url: Url,
+    /// The secret the payload is signed with.
+    pub secret: String,
+    pub payload: WebhookPayload,
+}
+
+impl fmt::Debug for WebhookDelivery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebhookDelivery")
+            .field("url", &self.url.as_str())
+            .field("payload", &self.payload)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The queue that committed webhook deliveries are submitted to. This is shared by all
+/// clones of the [`QueryServer`] so that any transaction can submit deliveries.
+#[derive(Debug, Default)]
+pub struct WebhookQueue {
+    tx: OnceLock<mpsc::Sender<WebhookDelivery>>,
+    dropped: AtomicU64,
+    dead_lettered: AtomicU64,
+}
+
+impl WebhookQueue {
+    /// Create the receiver for webhook deliveries, holding at most `capacity` deliveries
+    /// before further deliveries are dropped. There can only be one subscriber, so this
+    /// returns `None` if one already exists.
+    pub fn subscribe(&self, capacity: usize) -> Option<mpsc::Receiver<WebhookDelivery>> {
+        let (tx, rx) = mpsc::channel(capacity.max(1));
+        self.tx.set(tx).ok().map(|()| rx)
+    }
+
+    /// If there is nothing to deliver webhooks, there is no need to match changes
+    /// against subscriptions.
+    pub fn is_subscribed(&self) -> bool {
+        self.tx.get().is_some_and(|tx| !tx.is_closed())
+    }
+
+    /// The number of deliveries that have been dropped because the subscriber was not
+    /// keeping up, or had gone away.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Record that a delivery was abandoned after it could not be delivered.
+    pub fn dead_letter(&self, delivery: &WebhookDelivery) {
+        self.dead_lettered.fetch_add(1, Ordering::Relaxed);
+        error!(
+            url = %delivery.url,
+            subscription = %delivery.payload.subscription,
+            entry = %delivery.payload.entry,
+            cid = %delivery.payload.cid,
+            "webhook delivery failed, abandoning"
+        );
+    }
+
+    /// The number of deliveries that were abandoned after exhausting their retries.
+    pub fn dead_lettered(&self) -> u64 {
+        self.dead_lettered.load(Ordering::Relaxed)
+    }
+
+    pub fn submit(&self, delivery: WebhookDelivery) {
+        let Some(tx) = self.tx.get() else {
+            return;
+        };
+
+        match tx.try_send(delivery) {
+            Ok(()) => {}
+            Err(TrySendError::Full(delivery)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                debug!(?delivery, "webhook queue is full, dropping delivery");
+            }
+            Err(TrySendError::Closed(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct WebhookSubscription {
+    uuid: Uuid,
+    url: Url,
+    secret: String,
+    kinds: Vec<WebhookEventKind>,
+    /// The identity whose access the subscription is delivered with. None if the
+    /// subscription was authored by the server itself.
+    author: Option<Uuid>,
+    /// The filter as it was written, used to check the access of the author.
+    filter_orig: Filter<FilterValid>,
+    filter: Filter<FilterValidResolved>,
+}
+
+/// The webhook subscriptions, loaded when they are first needed and dropped whenever a
+/// subscription or the schema changes.
+pub(crate) type WebhookSubscriptionCache = Option<Arc<Vec<WebhookSubscription>>>;
+
+impl QueryServerWriteTransaction<'_> {
+    pub(super) fn invalidate_webhook_subscriptions(&mut self) {
+        *self.webhook_subscriptions = None;
+    }
+
+    fn webhook_subscriptions(&mut self) -> Result<Arc<Vec<WebhookSubscription>>, OperationError> {
+        // A subscription changed earlier in this transaction must be matched in its new form.
+        if self.changed_flags.contains(ChangeFlag::WEBHOOK) {
+            self.changed_flags.remove(ChangeFlag::WEBHOOK);
+            self.invalidate_webhook_subscriptions();
+        }
+
+        if let Some(subscriptions) = self.webhook_subscriptions.as_ref() {
+            return Ok(subscriptions.clone());
+        }
+
+        let subscriptions = Arc::new(self.load_webhook_subscriptions()?);
+        *self.webhook_subscriptions = Some(subscriptions.clone());
+        Ok(subscriptions)
+    }
+
+    fn load_webhook_subscriptions(&mut self) -> Result<Vec<WebhookSubscription>, OperationError> {
+        let entries = self.internal_search(filter!(f_eq(
+            Attribute::Class,
+            EntryClass::WebhookSubscription.into()
+        )))?;
+
+        let ident_internal = Identity::from_internal();
+        let mut subscriptions = Vec::with_capacity(entries.len());
+
+        for entry in entries {
+            let uuid = entry.get_uuid();
+            let (Some(url), Some(secret)) = (
+                entry.get_ava_single_url(Attribute::WebhookUrl),
+                entry.get_ava_single_secret(Attribute::WebhookSecret),
+            ) else {
+                continue;
+            };
+
+            // Without an author the access to deliver with is unknown, so nothing is delivered.
+            let author = match entry.get_ava_single_uuid(Attribute::WebhookAuthor) {
+                Some(UUID_SYSTEM) => None,
+                Some(author) => Some(author),
+                None => {
+                    admin_warn!(subscription = %uuid, "Webhook subscription has no author, ignoring subscription");
+                    continue;
+                }
+            };
+
+            let kinds = entry
+                .get_ava_iter_iutf8(Attribute::WebhookEvent)
+                .map(|iter| {
+                    iter.filter_map(|kind| {
+                        WebhookEventKind::ALL
+                            .into_iter()
+                            .find(|k| k.as_str() == kind)
+                    })
+                    .collect()
+                })
+                .unwrap_or_default();
+
+            // A subscription without a filter is interested in every entry, which is checked
+            // against the access of the author in the same way as any other filter.
+            let filter = match entry.get_ava_single_protofilter(Attribute::WebhookFilter) {
+                Some(proto_filter) => Filter::from_rw(&ident_internal, proto_filter, self),
+                None => Ok(filter!(f_pres(Attribute::Class))),
+            };
+
+            // A subscription with a filter that no longer validates, such as one naming an
+            // attribute that has since been removed from schema, must not prevent writes.
+            let filter = filter
+                .and_then(|f| {
+                    f.validate(self.get_schema())
+                        .map_err(OperationError::SchemaViolation)
+                })
+                .and_then(|f| {
+                    f.resolve(&ident_internal, None, self.get_resolve_filter_cache())
+                        .map(|resolved| (f, resolved))
+                });
+
+            let (filter_orig, filter) = match filter {
+                Ok(filters) => filters,
+                Err(err) => {
+                    admin_warn!(?err, subscription = %uuid, "Invalid webhook filter, ignoring subscription");
+                    continue;
+                }
+            };
+
+            subscriptions.push(WebhookSubscription {
+                uuid,
+                url: url.clone(),
+                secret: secret.to_string(),
+                kinds,
+                author,
+                filter_orig,
+                filter,
+            });
+        }
+
+        Ok(subscriptions)
+    }
+
+    /// The identity that a subscription is delivered with. None if the author no longer
+    /// exists, in which case nothing can be delivered.
+    fn webhook_author_ident(
+        &mut self,
+        subscription: &WebhookSubscription,
+    ) -> Result<Option<Identity>, OperationError> {
+        let Some(author) = subscription.author else {
+            return Ok(Some(Identity::from_internal()));
+        };
+
+        match self.internal_search_uuid(author) {
+            Ok(entry) => Ok(Some(Identity::from_impersonate_entry_readonly(entry))),
+            Err(OperationError::NoMatchingEntries) => {
+                admin_warn!(subscription = %subscription.uuid, %author, "Webhook subscription author no longer exists");
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Match the entries changed by an operation against the webhook subscriptions, holding
+    /// the resulting deliveries until the transaction commits.
+    ///
+    /// Subscriptions are matched with the access of their author as it is now, so that a
+    /// subscription is never told of an entry, or an attribute, that its author can't read.
+    pub(crate) fn queue_webhooks(
+        &mut self,
+        kind: WebhookEventKind,
+        cand: &[EntrySealedCommitted],
+    ) -> Result<(), OperationError> {
+        if cand.is_empty() || !self.webhooks.is_subscribed() {
+            return Ok(());
+        }
+
+        let subscriptions = self.webhook_subscriptions()?;
+        if subscriptions.is_empty() {
+            return Ok(());
+        }
+
+        let cid = self.get_txn_cid().clone();
+
+        let changed: Vec<(Arc<EntrySealedCommitted>, BTreeSet<Attribute>)> = cand
+            .iter()
+            .map(|entry| {
+                let attributes = match kind {
+                    WebhookEventKind::Delete => BTreeSet::new(),
+                    WebhookEventKind::Create | WebhookEventKind::Modify => entry
+                        .get_changestate()
+                        .attr_cid_iter()
+                        // This always changes, so is never interesting to a receiver.
+                        .filter(|(attr, attr_cid)| {
+                            **attr_cid == cid && **attr != Attribute::LastModifiedCid
+                        })
+                        .map(|(attr, _)| attr.clone())
+                        .collect(),
+                };
+                let entry = match kind {
+                    // A deleted entry is in the recycle bin, which access controls never
+                    // target, so it is matched as it was before it was deleted.
+                    WebhookEventKind::Delete => entry.clone().into_revived_view(&cid),
+                    WebhookEventKind::Create | WebhookEventKind::Modify => entry.clone(),
+                };
+                (Arc::new(entry), attributes)
+            })
+            .collect();
+
+        for subscription in subscriptions.iter() {
+            if !subscription.kinds.contains(&kind) {
+                continue;
+            }
+
+            let mut author = None;
+
+            for (entry, attributes) in changed.iter() {
+                if !entry.entry_match_no_index(&subscription.filter) {
+                    continue;
+                }
+
+                // The author is only needed once an entry matches.
+                let ident = match &author {
+                    Some(ident) => ident,
+                    None => author.insert(self.webhook_author_ident(subscription)?),
+                };
+                let Some(ident) = ident else {
+                    break;
+                };
+
+                let Some(attributes) = self.get_accesscontrols().filter_entry_readable_attrs(
+                    ident,
+                    &subscription.filter_orig,
+                    entry,
+                    attributes,
+                ) else {
+                    continue;
+                };
+
+                self.pending_webhooks.push(WebhookDelivery {
+                    url: subscription.url.clone(),
+                    secret: subscription.secret.clone(),
+                    payload: WebhookPayload {
+                        version: WEBHOOK_PAYLOAD_VERSION,
+                        subscription: subscription.uuid,
+                        kind,
+                        entry: entry.get_uuid(),
+                        attributes: attributes.iter().map(|attr| attr.to_string()).collect(),
+                        cid: cid.to_string(),
+                    },
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscription(events: &[WebhookEventKind]) -> EntryInitNew {
+        let mut e = entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::WebhookSubscription.to_value()),
+            (Attribute::Name, Value::new_iname("test_webhook")),
+            (
+                Attribute::WebhookUrl,
+                Value::Url(Url::parse("https://webhook.example.com/notify").unwrap())
+            ),
+            (
+                Attribute::WebhookFilter,
+                Value::JsonFilt(ProtoFilter::Eq(
+                    Attribute::Class.to_string(),
+                    EntryClass::Person.to_string()
+                ))
+            )
+        );
+        for event in events {
+            e.add_ava(Attribute::WebhookEvent, Value::new_iutf8(event.as_str()));
+        }
+        e
+    }
+
+    fn person(name: &str, uuid: Uuid) -> EntryInitNew {
+        entry_init!(
+            (Attribute::Class, EntryClass::Object.to_value()),
+            (Attribute::Class, EntryClass::Account.to_value()),
+            (Attribute::Class, EntryClass::Person.to_value()),
+            (Attribute::Name, Value::new_iname(name)),
+            (Attribute::Uuid, Value::Uuid(uuid)),
+            (Attribute::DisplayName, Value::new_utf8s(name))
+        )
+    }
+
+    #[qs_test]
+    async fn test_webhook_deliveries_after_commit(server: &QueryServer) {
+        let mut rx = server
+            .webhooks()
+            .subscribe(16)
+            .expect("failed to subscribe");
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        assert!(server_txn
+            .internal_create(vec![subscription(&[
+                WebhookEventKind::Create,
+                WebhookEventKind::Modify
+            ])])
+            .is_ok());
+
+        // The secret is generated as clients can't supply it.
+        let secret = server_txn
+            .internal_search(filter!(f_eq(
+                Attribute::Name,
+                PartialValue::new_iname("test_webhook")
+            )))
+            .unwrap()
+            .pop()
+            .and_then(|e| {
+                e.get_ava_single_secret(Attribute::WebhookSecret)
+                    .map(str::to_string)
+            })
+            .expect("webhook secret was not generated");
+        server_txn.commit().expect("failed to commit");
+
+        // An aborted transaction delivers nothing.
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        assert!(server_txn
+            .internal_create(vec![person("aborted", Uuid::new_v4())])
+            .is_ok());
+        drop(server_txn);
+        assert!(rx.try_recv().is_err());
+
+        let person_uuid = Uuid::new_v4();
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        assert!(server_txn
+            .internal_create(vec![
+                person("testperson", person_uuid),
+                // Doesn't match the filter of the subscription.
+                entry_init!(
+                    (Attribute::Class, EntryClass::Object.to_value()),
+                    (Attribute::Class, EntryClass::Group.to_value()),
+                    (Attribute::Name, Value::new_iname("testgroup"))
+                ),
+            ])
+            .is_ok());
+
+        // Nothing is delivered until the commit.
+        assert!(rx.try_recv().is_err());
+        let cid = server_txn.get_txn_cid().to_string();
+        server_txn.commit().expect("failed to commit");
+
+        let delivery = rx.try_recv().expect("no webhook delivery");
+        assert_eq!(delivery.payload.kind, WebhookEventKind::Create);
+        assert_eq!(delivery.payload.entry, person_uuid);
+        assert_eq!(delivery.payload.cid, cid);
+        assert!(delivery
+            .payload
+            .attributes
+            .contains(&Attribute::DisplayName.to_string()));
+        assert_eq!(delivery.secret, secret);
+        assert!(rx.try_recv().is_err());
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        assert!(server_txn
+            .internal_modify_uuid(
+                person_uuid,
+                &ModifyList::new_purge_and_set(
+                    Attribute::DisplayName,
+                    Value::new_utf8s("Test Person")
+                ),
+            )
+            .is_ok());
+        server_txn.commit().expect("failed to commit");
+
+        let delivery = rx.try_recv().expect("no webhook delivery");
+        assert_eq!(delivery.payload.kind, WebhookEventKind::Modify);
+        assert!(delivery
+            .payload
+            .attributes
+            .contains(&Attribute::DisplayName.to_string()));
+
+        // The subscription isn't interested in deletes.
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        assert!(server_txn.internal_delete_uuid(person_uuid).is_ok());
+        server_txn.commit().expect("failed to commit");
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[qs_test]
+    async fn test_webhook_author_access(server: &QueryServer) {
+        let mut rx = server
+            .webhooks()
+            .subscribe(16)
+            .expect("failed to subscribe");
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        let idm_admin = server_txn
+            .internal_search_uuid(UUID_IDM_ADMIN)
+            .expect("failed");
+        let ident = Identity::from_impersonate_entry_readwrite(idm_admin.clone());
+
+        let unfiltered = |name: &str| {
+            entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::WebhookSubscription.to_value()),
+                (Attribute::Name, Value::new_iname(name)),
+                (
+                    Attribute::WebhookUrl,
+                    Value::Url(Url::parse("https://webhook.example.com/notify").unwrap())
+                ),
+                (
+                    Attribute::WebhookEvent,
+                    Value::new_iutf8(WebhookEventKind::Modify.as_str())
+                )
+            )
+        };
+
+        // Access controls are only readable by access control admins. idm_admin can
+        // subscribe to them, but the changes are never delivered.
+        let mut acp_webhook = unfiltered("acp_webhook");
+        acp_webhook.add_ava(
+            Attribute::WebhookFilter,
+            Value::JsonFilt(ProtoFilter::Eq(
+                Attribute::Class.to_string(),
+                EntryClass::AccessControlProfile.to_string(),
+            )),
+        );
+        // Without a filter every entry that idm_admin can read is matched.
+        let ce = CreateEvent::new_impersonate_identity(
+            ident,
+            vec![acp_webhook, unfiltered("open_webhook")],
+        );
+        assert!(server_txn.create(&ce).is_ok());
+
+        // The author is always the creator, and internal subscriptions have no author.
+        assert!(server_txn
+            .internal_create(vec![subscription(&[WebhookEventKind::Modify])])
+            .is_ok());
+        let author = |server_txn: &mut QueryServerWriteTransaction, name: &str| {
+            server_txn
+                .internal_search(filter!(f_eq(
+                    Attribute::Name,
+                    PartialValue::new_iname(name)
+                )))
+                .unwrap()
+                .pop()
+                .and_then(|e| e.get_ava_single_uuid(Attribute::WebhookAuthor))
+        };
+        assert_eq!(author(&mut server_txn, "acp_webhook"), Some(UUID_IDM_ADMIN));
+        assert_eq!(
+            author(&mut server_txn, "open_webhook"),
+            Some(UUID_IDM_ADMIN)
+        );
+        assert_eq!(author(&mut server_txn, "test_webhook"), Some(UUID_SYSTEM));
+
+        let group_uuid = Uuid::new_v4();
+        assert!(server_txn
+            .internal_create(vec![entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Group.to_value()),
+                (Attribute::Name, Value::new_iname("testgroup")),
+                (Attribute::Uuid, Value::Uuid(group_uuid))
+            )])
+            .is_ok());
+        server_txn.commit().expect("failed to commit");
+        assert!(rx.try_recv().is_err());
+
+        // The subscriptions created in the last transaction are matched in the next.
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        assert!(server_txn
+            .internal_modify_uuid(
+                UUID_IDM_ACP_SELF_READ,
+                &ModifyList::new_purge_and_set(Attribute::Description, Value::new_utf8s("changed")),
+            )
+            .is_ok());
+        assert!(server_txn
+            .internal_modify_uuid(
+                group_uuid,
+                &ModifyList::new_purge_and_set(Attribute::Description, Value::new_utf8s("changed")),
+            )
+            .is_ok());
+        server_txn.commit().expect("failed to commit");
+
+        // Only the group is delivered to open_webhook, the system subscription is only
+        // interested in persons.
+        let delivery = rx.try_recv().expect("no webhook delivery");
+        assert_eq!(delivery.payload.entry, group_uuid);
+        assert!(delivery
+            .payload
+            .attributes
+            .contains(&Attribute::Description.to_string()));
+        assert!(rx.try_recv().is_err());
+
+        // Removing the filter doesn't widen what idm_admin receives.
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        let me = ModifyEvent::new_impersonate_entry(
+            idm_admin,
+            filter!(f_eq(
+                Attribute::Name,
+                PartialValue::new_iname("acp_webhook")
+            )),
+            ModifyList::new_purge(Attribute::WebhookFilter),
+        );
+        assert!(server_txn.modify(&me).is_ok());
+        assert_eq!(author(&mut server_txn, "acp_webhook"), Some(UUID_IDM_ADMIN));
+
+        // An internal change to a subscription keeps the author, and takes effect in
+        // the next transaction.
+        assert!(server_txn
+            .internal_modify(
+                &filter!(f_eq(
+                    Attribute::Name,
+                    PartialValue::new_iname("open_webhook")
+                )),
+                &ModifyList::new_purge_and_set(
+                    Attribute::WebhookEvent,
+                    Value::new_iutf8(WebhookEventKind::Create.as_str())
+                ),
+            )
+            .is_ok());
+        assert_eq!(
+            author(&mut server_txn, "open_webhook"),
+            Some(UUID_IDM_ADMIN)
+        );
+        server_txn.commit().expect("failed to commit");
+
+        // The change to open_webhook is itself readable by idm_admin.
+        while let Ok(delivery) = rx.try_recv() {
+            assert_ne!(delivery.payload.entry, UUID_IDM_ACP_SELF_READ);
+        }
+
+        let mut server_txn = server.write(duration_from_epoch_now()).await.unwrap();
+        assert!(server_txn
+            .internal_modify_uuid(
+                UUID_IDM_ACP_SELF_READ,
+                &ModifyList::new_purge_and_set(
+                    Attribute::Description,
+                    Value::new_utf8s("changed again")
+                ),
+            )
+            .is_ok());
+        assert!(server_txn
+            .internal_modify_uuid(
+                group_uuid,
+                &ModifyList::new_purge_and_set(
+                    Attribute::Description,
+                    Value::new_utf8s("changed again")
+                ),
+            )
+            .is_ok());
+        server_txn.commit().expect("failed to commit");
+
+        // acp_webhook now matches the group, but not the access control.
+        let delivery = rx.try_recv().expect("no webhook delivery");
+        assert_eq!(delivery.payload.entry, group_uuid);
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -102,6 +102,7 @@ pub async fn setup_idm_test(
         qs,
         &Url::from_str("https://idm.example.com").expect("Failed to parse URL"),
         true,
+        None,
         duration_from_epoch_now(),
     )
     .await
@@ -5,6 +5,7 @@ use hashbrown::HashSet;
 use rand::distr::{Distribution, Uniform};
 use rand::{rng, Rng, RngExt};
 use std::ops::Range;
+use unicode_normalization::{is_nfc, UnicodeNormalization};
 
 #[derive(Debug)]
 pub struct DistinctAlpha;
@@ -162,6 +163,18 @@ pub(crate) fn trigraph_iter(value: &str) -> impl Iterator<Item = &str> {
         .chain(GraphemeClusterIter::new(value, 1))
 }
 
+/// Normalise a name for case insensitive comparison. Unicode is composed (NFC) before
+/// lowercasing so that composed and decomposed forms of the same name are equal.
+pub(crate) fn normalise_iname(value: &str) -> String {
+    let lower = value.nfc().collect::<String>().to_lowercase();
+    // Lowercasing can itself produce decomposed sequences.
+    if is_nfc(&lower) {
+        lower
+    } else {
+        lower.nfc().collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::prelude::*;
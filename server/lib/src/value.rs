@@ -10,6 +10,7 @@ use crate::prelude::*;
 use crate::repl::cid::Cid;
 use crate::server::identity::IdentityId;
 use crate::server::keys::KeyId;
+use crate::utils::normalise_iname;
 use crate::valueset::image::ImageValueThings;
 use crate::valueset::uuid_to_proto_string;
 use compact_jwt::{crypto::JwsRs256Signer, JwsEs256Signer};
@@ -20,7 +21,9 @@ use crypto_glue::{
 };
 use hashbrown::HashSet;
 use kanidm_proto::internal::ImageValue;
-use kanidm_proto::internal::{ApiTokenPurpose, Filter as ProtoFilter, UiHint};
+use kanidm_proto::internal::{
+    ApiTokenOperationScope, ApiTokenPurpose, Filter as ProtoFilter, UiHint,
+};
 use kanidm_proto::scim_v1::ScimOauth2ClaimMapJoinChar;
 use kanidm_proto::v1::UatPurposeStatus;
 use num_enum::TryFromPrimitive;
@@ -33,6 +36,7 @@ use std::convert::TryFrom;
 use std::fmt;
 use std::fmt::Formatter;
 use std::hash::Hash;
+use std::net::IpAddr;
 use std::str::FromStr;
 use std::time::Duration;
 use time::OffsetDateTime;
@@ -287,61 +291,78 @@ pub enum SyntaxType {
     Sha256 = 44,
     Int64 = 45,
     Uint64 = 46,
+    CredentialLockout = 47,
+    PasskeyUsage = 48,
+    MemberExpiry = 49,
+    /// Strings that are validated by a [`CustomSyntaxValidator`](crate::schema::CustomSyntaxValidator)
+    /// registered for the attribute.
+    Custom = 50,
 }
 
 impl TryFrom<&str> for SyntaxType {
     type Error = ();
 
     fn try_from(value: &str) -> Result<SyntaxType, Self::Error> {
-        let n_value = value.to_uppercase();
+        // Case and separators are ignored, so that "REFERENCE_UUID", "reference-uuid"
+        // and "referenceuuid" are all accepted.
+        let n_value: String = value
+            .chars()
+            .filter(|c| !matches!(c, '_' | '-'))
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
         match n_value.as_str() {
             "UTF8STRING" => Ok(SyntaxType::Utf8String),
-            "UTF8STRING_INSENSITIVE" => Ok(SyntaxType::Utf8StringInsensitive),
-            "UTF8STRING_INAME" => Ok(SyntaxType::Utf8StringIname),
+            "UTF8STRINGINSENSITIVE" => Ok(SyntaxType::Utf8StringInsensitive),
+            "UTF8STRINGINAME" => Ok(SyntaxType::Utf8StringIname),
             "UUID" => Ok(SyntaxType::Uuid),
             "BOOLEAN" => Ok(SyntaxType::Boolean),
-            "SYNTAX_ID" => Ok(SyntaxType::SyntaxId),
-            "INDEX_ID" => Ok(SyntaxType::IndexId),
-            "REFERENCE_UUID" => Ok(SyntaxType::ReferenceUuid),
-            "JSON_FILTER" => Ok(SyntaxType::JsonFilter),
+            "SYNTAXID" => Ok(SyntaxType::SyntaxId),
+            "INDEXID" => Ok(SyntaxType::IndexId),
+            "REFERENCEUUID" => Ok(SyntaxType::ReferenceUuid),
+            "JSONFILTER" => Ok(SyntaxType::JsonFilter),
             "CREDENTIAL" => Ok(SyntaxType::Credential),
             // Compatibility for older syntax name.
-            "RADIUS_UTF8STRING" | "SECRET_UTF8STRING" => Ok(SyntaxType::SecretUtf8String),
+            "RADIUSUTF8STRING" | "SECRETUTF8STRING" => Ok(SyntaxType::SecretUtf8String),
             "SSHKEY" => Ok(SyntaxType::SshKey),
-            "SECURITY_PRINCIPAL_NAME" => Ok(SyntaxType::SecurityPrincipalName),
+            "SECURITYPRINCIPALNAME" => Ok(SyntaxType::SecurityPrincipalName),
             "UINT32" => Ok(SyntaxType::Uint32),
             "CID" => Ok(SyntaxType::Cid),
             "NSUNIQUEID" => Ok(SyntaxType::NsUniqueId),
             "DATETIME" => Ok(SyntaxType::DateTime),
-            "EMAIL_ADDRESS" => Ok(SyntaxType::EmailAddress),
+            "EMAILADDRESS" => Ok(SyntaxType::EmailAddress),
             "URL" => Ok(SyntaxType::Url),
-            "OAUTH_SCOPE" => Ok(SyntaxType::OauthScope),
-            "OAUTH_SCOPE_MAP" => Ok(SyntaxType::OauthScopeMap),
-            "PRIVATE_BINARY" => Ok(SyntaxType::PrivateBinary),
-            "INTENT_TOKEN" => Ok(SyntaxType::IntentToken),
+            "OAUTHSCOPE" => Ok(SyntaxType::OauthScope),
+            "OAUTHSCOPEMAP" => Ok(SyntaxType::OauthScopeMap),
+            "PRIVATEBINARY" => Ok(SyntaxType::PrivateBinary),
+            "INTENTTOKEN" => Ok(SyntaxType::IntentToken),
             "PASSKEY" => Ok(SyntaxType::Passkey),
-            "ATTESTED_PASSKEY" => Ok(SyntaxType::AttestedPasskey),
+            "ATTESTEDPASSKEY" => Ok(SyntaxType::AttestedPasskey),
             "SESSION" => Ok(SyntaxType::Session),
-            "JWS_KEY_ES256" => Ok(SyntaxType::JwsKeyEs256),
-            "JWS_KEY_RS256" => Ok(SyntaxType::JwsKeyRs256),
+            "JWSKEYES256" => Ok(SyntaxType::JwsKeyEs256),
+            "JWSKEYRS256" => Ok(SyntaxType::JwsKeyRs256),
             "OAUTH2SESSION" => Ok(SyntaxType::Oauth2Session),
             "UIHINT" => Ok(SyntaxType::UiHint),
             "TOTPSECRET" => Ok(SyntaxType::TotpSecret),
             "APITOKEN" => Ok(SyntaxType::ApiToken),
-            "AUDIT_LOG_STRING" => Ok(SyntaxType::AuditLogString),
-            "EC_KEY_PRIVATE" => Ok(SyntaxType::EcKeyPrivate),
-            "CREDENTIAL_TYPE" => Ok(SyntaxType::CredentialType),
-            "WEBAUTHN_ATTESTATION_CA_LIST" => Ok(SyntaxType::WebauthnAttestationCaList),
-            "OAUTH_CLAIM_MAP" => Ok(SyntaxType::OauthClaimMap),
-            "KEY_INTERNAL" => Ok(SyntaxType::KeyInternal),
-            "HEX_STRING" => Ok(SyntaxType::HexString),
+            "AUDITLOGSTRING" => Ok(SyntaxType::AuditLogString),
+            "ECKEYPRIVATE" => Ok(SyntaxType::EcKeyPrivate),
+            "IMAGE" => Ok(SyntaxType::Image),
+            "CREDENTIALTYPE" => Ok(SyntaxType::CredentialType),
+            "WEBAUTHNATTESTATIONCALIST" => Ok(SyntaxType::WebauthnAttestationCaList),
+            "OAUTHCLAIMMAP" => Ok(SyntaxType::OauthClaimMap),
+            "KEYINTERNAL" => Ok(SyntaxType::KeyInternal),
+            "HEXSTRING" => Ok(SyntaxType::HexString),
             "CERTIFICATE" => Ok(SyntaxType::Certificate),
-            "APPLICATION_PASSWORD" => Ok(SyntaxType::ApplicationPassword),
+            "APPLICATIONPASSWORD" => Ok(SyntaxType::ApplicationPassword),
             "JSON" => Ok(SyntaxType::Json),
             "MESSAGE" => Ok(SyntaxType::Message),
             "SHA256" => Ok(SyntaxType::Sha256),
             "INT64" => Ok(SyntaxType::Int64),
             "UINT64" => Ok(SyntaxType::Uint64),
+            "CREDENTIALLOCKOUT" => Ok(SyntaxType::CredentialLockout),
+            "PASSKEYUSAGE" => Ok(SyntaxType::PasskeyUsage),
+            "MEMBEREXPIRY" => Ok(SyntaxType::MemberExpiry),
+            "CUSTOM" => Ok(SyntaxType::Custom),
             _ => Err(()),
         }
     }
@@ -397,6 +418,10 @@ impl fmt::Display for SyntaxType {
             SyntaxType::Sha256 => "SHA256",
             SyntaxType::Int64 => "INT64",
             SyntaxType::Uint64 => "UINT64",
+            SyntaxType::CredentialLockout => "CREDENTIAL_LOCKOUT",
+            SyntaxType::PasskeyUsage => "PASSKEY_USAGE",
+            SyntaxType::MemberExpiry => "MEMBER_EXPIRY",
+            SyntaxType::Custom => "CUSTOM",
         })
     }
 }
@@ -471,7 +496,105 @@ impl SyntaxType {
             SyntaxType::Json => &[],
             SyntaxType::Message => &[],
             SyntaxType::Sha256 => &[IndexType::Equality],
-        }
+            SyntaxType::CredentialLockout => &[],
+            SyntaxType::PasskeyUsage => &[],
+            SyntaxType::MemberExpiry => &[IndexType::Equality],
+            SyntaxType::Custom => &[IndexType::Equality, IndexType::Presence],
+        }
+    }
+
+    /// Determine if existing values of this syntax remain valid when an attribute is
+    /// redefined with the syntax `other`. Transitions that could lose or corrupt the
+    /// stored data are refused.
+    pub fn can_migrate_to(&self, other: SyntaxType) -> bool {
+        if *self == other {
+            return true;
+        }
+
+        matches!(
+            (self, other),
+            // Insensitive strings are already normalised to lowercase.
+            (SyntaxType::Utf8StringInsensitive, SyntaxType::Utf8StringIname)
+                // Relaxing the constraints on a string keeps the values intact.
+                | (SyntaxType::Utf8StringIname, SyntaxType::Utf8StringInsensitive)
+                | (
+                    SyntaxType::Utf8StringIname | SyntaxType::Utf8StringInsensitive,
+                    SyntaxType::Utf8String
+                )
+                // Widening an integer can always represent the prior value.
+                | (SyntaxType::Uint32, SyntaxType::Uint64 | SyntaxType::Int64)
+        )
+    }
+
+    /// Iterate over every syntax that this server supports.
+    pub fn all() -> impl Iterator<Item = SyntaxType> {
+        // Syntax identifiers are contiguous from zero.
+        (0..).map_while(|id: u16| SyntaxType::try_from_primitive(id).ok())
+    }
+
+    /// If values of this syntax can be compared for ordering in a filter.
+    pub fn is_orderable(&self) -> bool {
+        self.index_types().contains(&IndexType::Ordering)
+    }
+
+    /// If values of this syntax can be matched by a substring, prefix or suffix in a filter.
+    pub fn supports_substring(&self) -> bool {
+        matches!(
+            self,
+            SyntaxType::Utf8String
+                | SyntaxType::Utf8StringInsensitive
+                | SyntaxType::Utf8StringIname
+                | SyntaxType::EmailAddress
+                | SyntaxType::HexString
+        )
+    }
+
+    /// If values of this syntax are secret, and are never disclosed to clients.
+    pub fn is_secret(&self) -> bool {
+        matches!(
+            self,
+            SyntaxType::Credential
+                | SyntaxType::SecretUtf8String
+                | SyntaxType::PrivateBinary
+                | SyntaxType::Passkey
+                | SyntaxType::AttestedPasskey
+                | SyntaxType::JwsKeyEs256
+                | SyntaxType::JwsKeyRs256
+                | SyntaxType::TotpSecret
+                | SyntaxType::EcKeyPrivate
+        )
+    }
+
+    /// If values of this syntax refer to other entries, and so must be tracked by
+    /// referential integrity.
+    pub fn is_reference_like(&self) -> bool {
+        matches!(
+            self,
+            SyntaxType::ReferenceUuid
+                | SyntaxType::OauthScopeMap
+                | SyntaxType::OauthClaimMap
+                // So that when an rs is removed we trigger removal of the sessions.
+                | SyntaxType::Oauth2Session
+                // When an application is removed we trigger removal of passwords
+                | SyntaxType::ApplicationPassword
+        )
+        // May not need to be a ref type since it doesn't have external links/impact?
+        // || *self == SyntaxType::Session
+    }
+
+    /// If an attribute of this syntax is able to hold more than one value.
+    pub fn is_multivalue_capable(&self) -> bool {
+        !matches!(self, SyntaxType::Json | SyntaxType::Message)
+    }
+
+    /// If the values of this syntax are plain strings.
+    pub fn is_string(&self) -> bool {
+        matches!(
+            self,
+            SyntaxType::Utf8String
+                | SyntaxType::Utf8StringInsensitive
+                | SyntaxType::Utf8StringIname
+        )
     }
 }
 
@@ -616,6 +739,9 @@ pub enum PartialValue {
     Sha256(Sha256Output),
     Int64(i64),
     Uint64(u64),
+    CredentialLockout(Uuid),
+    PasskeyUsage(Uuid),
+    MemberExpiry(Uuid),
 }
 
 impl From<SyntaxType> for PartialValue {
@@ -656,7 +782,7 @@ impl From<u32> for PartialValue {
 
 impl From<OffsetDateTime> for PartialValue {
     fn from(i: OffsetDateTime) -> Self {
-        PartialValue::DateTime(i)
+        PartialValue::DateTime(i.to_offset(time::UtcOffset::UTC))
     }
 }
 
@@ -690,7 +816,7 @@ impl PartialValue {
     }
 
     pub fn new_iname(s: &str) -> Self {
-        PartialValue::Iname(s.to_lowercase())
+        PartialValue::Iname(normalise_iname(s))
     }
 
     pub fn is_iutf8(&self) -> bool {
@@ -939,6 +1065,20 @@ impl PartialValue {
         Uuid::parse_str(us).map(PartialValue::AttestedPasskey).ok()
     }
 
+    pub fn new_credential_lockout_s(us: &str) -> Option<Self> {
+        Uuid::parse_str(us)
+            .map(PartialValue::CredentialLockout)
+            .ok()
+    }
+
+    pub fn new_passkey_usage_s(us: &str) -> Option<Self> {
+        Uuid::parse_str(us).map(PartialValue::PasskeyUsage).ok()
+    }
+
+    pub fn new_member_expiry_s(us: &str) -> Option<Self> {
+        Uuid::parse_str(us).map(PartialValue::MemberExpiry).ok()
+    }
+
     pub fn new_hex_string_s(hexstr: &str) -> Option<Self> {
         let hexstr_lower = hexstr.to_lowercase();
         if HEXSTR_RE.is_match(&hexstr_lower) {
@@ -978,6 +1118,9 @@ impl PartialValue {
             | PartialValue::RestrictedString(s) => s.clone(),
             PartialValue::Passkey(u)
             | PartialValue::AttestedPasskey(u)
+            | PartialValue::CredentialLockout(u)
+            | PartialValue::PasskeyUsage(u)
+            | PartialValue::MemberExpiry(u)
             | PartialValue::Refer(u)
             | PartialValue::Uuid(u) => u.as_hyphenated().to_string(),
             PartialValue::Bool(b) => b.to_string(),
@@ -1039,6 +1182,32 @@ impl PartialValue {
             _ => None,
         }
     }
+
+    /// The key of this value in an ordering index, if values of this kind have one. Where
+    /// this is `None` the ordering index can't be used to resolve a lessthan term.
+    pub fn get_idx_ord_key(&self) -> Option<String> {
+        match self {
+            PartialValue::Uint32(u) => Some(idx_ord_key_uint32(*u)),
+            PartialValue::DateTime(odt) => {
+                debug_assert_eq!(odt.offset(), time::UtcOffset::UTC);
+                Some(idx_ord_key_datetime(odt))
+            }
+            _ => None,
+        }
+    }
+}
+
+// Ordering index keys are fixed width big-endian hex, so that the keys sort as strings
+// in the same order as the values they were generated from.
+
+pub(crate) fn idx_ord_key_uint32(u: u32) -> String {
+    format!("{u:08x}")
+}
+
+pub(crate) fn idx_ord_key_datetime(odt: &OffsetDateTime) -> String {
+    // Flip the sign bit so that times before the epoch sort first.
+    let nanos = (odt.unix_timestamp_nanos() as u128) ^ (1 << 127);
+    format!("{nanos:032x}")
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -1077,6 +1246,7 @@ pub struct ApiToken {
     pub issued_at: OffsetDateTime,
     pub issued_by: IdentityId,
     pub scope: ApiTokenScope,
+    pub operation_scopes: BTreeSet<ApiTokenOperationScope>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -1184,6 +1354,68 @@ impl fmt::Display for AuthType {
     }
 }
 
+/// The Authentication Context Class Reference that a session satisfies. These are
+/// ordered from weakest to strongest so that a requirement can be compared with the
+/// class a session was established with.
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Acr {
+    Password,
+    #[serde(rename = "mfa")]
+    MultiFactor,
+    Passkey,
+    AttestedPasskey,
+}
+
+impl Acr {
+    pub const ALL: [Acr; 4] = [
+        Acr::Password,
+        Acr::MultiFactor,
+        Acr::Passkey,
+        Acr::AttestedPasskey,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Acr::Password => "password",
+            Acr::MultiFactor => "mfa",
+            Acr::Passkey => "passkey",
+            Acr::AttestedPasskey => "attested_passkey",
+        }
+    }
+
+    /// The class of authentication satisfied by a session of this type, if any.
+    pub fn from_auth_type(auth_type: AuthType) -> Option<Self> {
+        match auth_type {
+            AuthType::Password | AuthType::GeneratedPassword => Some(Acr::Password),
+            AuthType::PasswordTotp
+            | AuthType::PasswordBackupCode
+            | AuthType::PasswordSecurityKey => Some(Acr::MultiFactor),
+            AuthType::Passkey => Some(Acr::Passkey),
+            AuthType::AttestedPasskey => Some(Acr::AttestedPasskey),
+            AuthType::Anonymous | AuthType::OAuth2Trust => None,
+        }
+    }
+}
+
+impl fmt::Display for Acr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Acr {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Acr::ALL
+            .iter()
+            .find(|acr| acr.as_str() == s)
+            .copied()
+            .ok_or(())
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Default)]
 pub enum SessionExtMetadata {
     #[default]
@@ -1220,6 +1452,11 @@ pub struct Session {
     pub scope: SessionScope,
     pub type_: AuthType,
     pub ext_metadata: SessionExtMetadata,
+    /// The address of the client that established this session, if known.
+    pub source_ip: Option<IpAddr>,
+    /// The user agent of the client that established this session. This is truncated
+    /// to [`SESSION_USER_AGENT_MAX_LEN`] so that it can't grow the entry without bound.
+    pub user_agent: Option<String>,
 }
 
 impl fmt::Debug for Session {
@@ -1236,8 +1473,8 @@ impl fmt::Debug for Session {
         };
         write!(
             f,
-            "state: {}, issued at: {}, issued by: {}, credential id: {}, scope: {:?}",
-            expiry, self.issued_at, issuer, self.cred_id, self.scope
+            "state: {}, issued at: {}, issued by: {}, credential id: {}, scope: {:?}, source ip: {:?}",
+            expiry, self.issued_at, issuer, self.cred_id, self.scope, self.source_ip
         )
     }
 }
@@ -1248,6 +1485,8 @@ pub enum OauthClaimMapJoin {
     SpaceSeparatedValue,
     #[default]
     JsonArray,
+    /// All values are emitted as one semicolon separated string.
+    Single,
 }
 
 impl From<OauthClaimMapJoin> for ScimOauth2ClaimMapJoinChar {
@@ -1260,6 +1499,7 @@ impl From<OauthClaimMapJoin> for ScimOauth2ClaimMapJoinChar {
                 ScimOauth2ClaimMapJoinChar::SpaceSeparatedValue
             }
             OauthClaimMapJoin::JsonArray => ScimOauth2ClaimMapJoinChar::JsonArray,
+            OauthClaimMapJoin::Single => ScimOauth2ClaimMapJoinChar::Single,
         }
     }
 }
@@ -1274,6 +1514,7 @@ impl From<ScimOauth2ClaimMapJoinChar> for OauthClaimMapJoin {
                 OauthClaimMapJoin::SpaceSeparatedValue
             }
             ScimOauth2ClaimMapJoinChar::JsonArray => OauthClaimMapJoin::JsonArray,
+            ScimOauth2ClaimMapJoinChar::Single => OauthClaimMapJoin::Single,
         }
     }
 }
@@ -1285,6 +1526,7 @@ impl OauthClaimMapJoin {
             OauthClaimMapJoin::SpaceSeparatedValue => " ",
             // Should this be something else?
             OauthClaimMapJoin::JsonArray => ";",
+            OauthClaimMapJoin::Single => ";",
         }
     }
 }
@@ -1299,6 +1541,7 @@ impl From<DbValueOauthClaimMapJoinV1> for OauthClaimMapJoin {
                 OauthClaimMapJoin::SpaceSeparatedValue
             }
             DbValueOauthClaimMapJoinV1::JsonArray => OauthClaimMapJoin::JsonArray,
+            DbValueOauthClaimMapJoinV1::Single => OauthClaimMapJoin::Single,
         }
     }
 }
@@ -1313,6 +1556,7 @@ impl From<OauthClaimMapJoin> for DbValueOauthClaimMapJoinV1 {
                 DbValueOauthClaimMapJoinV1::SpaceSeparatedValue
             }
             OauthClaimMapJoin::JsonArray => DbValueOauthClaimMapJoinV1::JsonArray,
+            OauthClaimMapJoin::Single => DbValueOauthClaimMapJoinV1::Single,
         }
     }
 }
@@ -1371,6 +1615,14 @@ impl fmt::Display for KeyStatus {
     }
 }
 
+/// When a passkey was last used to authenticate, and the highest sign count that it
+/// has presented.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PasskeyUsage {
+    pub last_used: OffsetDateTime,
+    pub counter: u32,
+}
+
 /// A value is a complete unit of data for an attribute. It is made up of a PartialValue, which is
 /// used for selection, filtering, searching, matching etc. It also contains supplemental data
 /// which may be stored inside of the Value, such as credential secrets, blobs etc.
@@ -1430,6 +1682,7 @@ pub enum Value {
 
     OauthClaimValue(String, Uuid, BTreeSet<String>),
     OauthClaimMap(String, OauthClaimMapJoin),
+    OauthClaimTemplate(String, Option<String>),
 
     KeyInternal {
         id: KeyId,
@@ -1446,6 +1699,9 @@ pub enum Value {
     ApplicationPassword(ApplicationPassword),
     Json(JsonValue),
     Sha256(Sha256Output),
+    CredentialLockout(Uuid, OffsetDateTime),
+    PasskeyUsage(Uuid, PasskeyUsage),
+    MemberExpiry(Uuid, OffsetDateTime),
 }
 
 impl PartialEq for Value {
@@ -1480,6 +1736,12 @@ impl PartialEq for Value {
             (Value::Int64(a), Value::Int64(b)) => a.eq(b),
             // Uint64
             (Value::Uint64(a), Value::Uint64(b)) => a.eq(b),
+            // CredentialLockout
+            (Value::CredentialLockout(a, c), Value::CredentialLockout(b, d)) => a.eq(b) && c.eq(d),
+            // PasskeyUsage
+            (Value::PasskeyUsage(a, c), Value::PasskeyUsage(b, d)) => a.eq(b) && c.eq(d),
+            // MemberExpiry
+            (Value::MemberExpiry(a, c), Value::MemberExpiry(b, d)) => a.eq(b) && c.eq(d),
             // Cid
             (Value::Cid(a), Value::Cid(b)) => a.eq(b),
             // DateTime
@@ -1544,7 +1806,7 @@ impl From<ProtoFilter> for Value {
 
 impl From<OffsetDateTime> for Value {
     fn from(i: OffsetDateTime) -> Self {
-        Value::DateTime(i)
+        Value::new_datetime(i)
     }
 }
 
@@ -1628,7 +1890,7 @@ impl Value {
     }
 
     pub fn new_iname(s: &str) -> Self {
-        Value::Iname(s.to_lowercase())
+        Value::Iname(normalise_iname(s))
     }
 
     pub fn is_iname(&self) -> bool {
@@ -1858,8 +2120,16 @@ impl Value {
             .map(Value::DateTime)
     }
 
+    /// A datetime value. This is always stored in UTC, regardless of the offset of `dt`.
     pub fn new_datetime(dt: OffsetDateTime) -> Self {
-        Value::DateTime(dt)
+        Value::DateTime(dt.to_offset(time::UtcOffset::UTC))
+    }
+
+    /// A membership of `refer` that expires at the rfc3339 timestamp `s`.
+    pub fn new_member_expiry_s(refer: Uuid, s: &str) -> Option<Self> {
+        OffsetDateTime::parse(s, &Rfc3339)
+            .ok()
+            .map(|odt| Value::MemberExpiry(refer, odt.to_offset(time::UtcOffset::UTC)))
     }
 
     pub fn to_datetime(&self) -> Option<OffsetDateTime> {
@@ -2280,6 +2550,13 @@ impl Value {
             Value::OauthScopeMap(_, m) => m.iter().all(|s| OAUTHSCOPE_RE.is_match(s)),
 
             Value::OauthClaimMap(name, _) => OAUTH_CLAIMNAME_RE.is_match(name),
+            Value::OauthClaimTemplate(name, template) => {
+                OAUTH_CLAIMNAME_RE.is_match(name)
+                    && template
+                        .as_deref()
+                        .map(Value::validate_oauth_claim_template)
+                        .unwrap_or(true)
+            }
             Value::OauthClaimValue(name, _, value) => {
                 OAUTH_CLAIMNAME_RE.is_match(name)
                     && value.iter().all(|s| OAUTH_CLAIMNAME_RE.is_match(s))
@@ -2323,11 +2600,19 @@ impl Value {
             | Value::CredentialType(_)
             | Value::Json(_)
             | Value::Sha256(_)
+            | Value::CredentialLockout(_, _)
+            | Value::PasskeyUsage(_, _)
+            | Value::MemberExpiry(_, _)
             | Value::WebauthnAttestationCaList(_) => true,
         }
     }
 
     pub(crate) fn validate_iname(s: &str) -> bool {
+        if !ValueSetIname::is_valid_iname(s) {
+            error!("iname values must contain an alphanumeric character and no control characters");
+            return false;
+        }
+
         match Uuid::parse_str(s) {
             // It is a uuid, disallow.
             Ok(_) => {
@@ -2383,6 +2668,12 @@ impl Value {
             true
         }
     }
+
+    /// A claim template is emitted verbatim into tokens after substitution, so it must
+    /// be a non-empty single line of text.
+    pub(crate) fn validate_oauth_claim_template(s: &str) -> bool {
+        !s.is_empty() && Value::validate_str_escapes(s) && Value::validate_singleline(s)
+    }
 }
 
 #[cfg(test)]
@@ -2423,6 +2714,24 @@ mod tests {
 
         let r6 = SyntaxType::try_from("zzzzantheou");
         assert_eq!(r6, Err(()));
+
+        let r7 = SyntaxType::try_from("utf8string");
+        assert_eq!(r7, Ok(SyntaxType::Utf8String));
+
+        let r8 = SyntaxType::try_from("UTF8STRING");
+        assert_eq!(r8, Ok(SyntaxType::Utf8String));
+
+        let r9 = SyntaxType::try_from("utf8-string");
+        assert_eq!(r9, Ok(SyntaxType::Utf8String));
+
+        let r10 = SyntaxType::try_from("reference-uuid");
+        assert_eq!(r10, Ok(SyntaxType::ReferenceUuid));
+
+        let r11 = SyntaxType::try_from("oauth_scope-map");
+        assert_eq!(r11, Ok(SyntaxType::OauthScopeMap));
+
+        let r12 = SyntaxType::try_from("reference uuid");
+        assert_eq!(r12, Err(()));
     }
 
     #[test]
@@ -2647,6 +2956,54 @@ mod tests {
         assert!(KeyStatus::Retained < KeyStatus::Revoked);
     }
 
+    #[test]
+    fn test_value_syntax_can_migrate_to() {
+        // Unchanged syntax is always permitted.
+        for syntax in SyntaxType::all() {
+            assert!(syntax.can_migrate_to(syntax));
+        }
+
+        assert!(SyntaxType::Utf8StringInsensitive.can_migrate_to(SyntaxType::Utf8StringIname));
+        assert!(SyntaxType::Utf8StringIname.can_migrate_to(SyntaxType::Utf8StringInsensitive));
+        assert!(SyntaxType::Utf8StringIname.can_migrate_to(SyntaxType::Utf8String));
+        assert!(SyntaxType::Uint32.can_migrate_to(SyntaxType::Uint64));
+        assert!(SyntaxType::Uint32.can_migrate_to(SyntaxType::Int64));
+
+        // Case would be lost.
+        assert!(!SyntaxType::Utf8String.can_migrate_to(SyntaxType::Utf8StringInsensitive));
+        assert!(!SyntaxType::Utf8String.can_migrate_to(SyntaxType::Uuid));
+        assert!(!SyntaxType::Uuid.can_migrate_to(SyntaxType::Utf8String));
+        assert!(!SyntaxType::Uuid.can_migrate_to(SyntaxType::ReferenceUuid));
+        // Narrowing may truncate.
+        assert!(!SyntaxType::Uint64.can_migrate_to(SyntaxType::Uint32));
+        assert!(!SyntaxType::Int64.can_migrate_to(SyntaxType::Uint64));
+        assert!(!SyntaxType::Boolean.can_migrate_to(SyntaxType::Uint32));
+        assert!(!SyntaxType::SecretUtf8String.can_migrate_to(SyntaxType::Utf8String));
+    }
+
+    #[test]
+    fn test_value_syntax_is_reference_like() {
+        let expected = [
+            SyntaxType::ReferenceUuid,
+            SyntaxType::OauthScopeMap,
+            SyntaxType::OauthClaimMap,
+            SyntaxType::Oauth2Session,
+            SyntaxType::ApplicationPassword,
+        ];
+
+        // If this fails a syntax was added. Decide if it refers to other entries, and
+        // update is_reference_like and the expected set above to match.
+        assert_eq!(SyntaxType::all().count(), 51);
+
+        for syntax in SyntaxType::all() {
+            assert_eq!(
+                syntax.is_reference_like(),
+                expected.contains(&syntax),
+                "{syntax:?}"
+            );
+        }
+    }
+
     #[test]
     fn test_value_session_state_order() {
         assert!(
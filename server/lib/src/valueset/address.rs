@@ -1,4 +1,4 @@
-use crate::be::dbvalue::DbValueAddressV1;
+use crate::be::dbvalue::{DbValueAddressV1, DbValueEmailAddressV1};
 use crate::prelude::*;
 use crate::schema::SchemaAttribute;
 use crate::utils::trigraph_iter;
@@ -256,13 +256,19 @@ impl ValueSetT for ValueSetAddress {
 pub struct ValueSetEmailAddress {
     primary: String,
     set: BTreeSet<String>,
+    // The subset of addresses that the owner has proven control of.
+    verified: BTreeSet<String>,
 }
 
 impl ValueSetEmailAddress {
     pub fn new(primary: String) -> Box<Self> {
         let mut set = BTreeSet::new();
         set.insert(primary.clone());
-        Box::new(ValueSetEmailAddress { primary, set })
+        Box::new(ValueSetEmailAddress {
+            primary,
+            set,
+            verified: BTreeSet::new(),
+        })
     }
 
     pub fn push(&mut self, a: String, primary: bool) -> bool {
@@ -272,11 +278,47 @@ impl ValueSetEmailAddress {
         self.set.insert(a)
     }
 
+    /// Addresses stored before verification was tracked were only able to be set by
+    /// administrators or sync providers, so they are considered verified.
     pub fn from_dbvs2(primary: String, data: Vec<String>) -> Result<ValueSet, OperationError> {
         let set: BTreeSet<_> = data.into_iter().collect();
+        let verified = set.clone();
 
         if set.contains(&primary) {
-            Ok(Box::new(ValueSetEmailAddress { primary, set }))
+            Ok(Box::new(ValueSetEmailAddress {
+                primary,
+                set,
+                verified,
+            }))
+        } else {
+            Err(OperationError::InvalidValueState)
+        }
+    }
+
+    pub fn from_dbvs2_v2(
+        primary: String,
+        data: Vec<DbValueEmailAddressV1>,
+    ) -> Result<ValueSet, OperationError> {
+        let mut set = BTreeSet::new();
+        let mut verified = BTreeSet::new();
+
+        for DbValueEmailAddressV1 {
+            address,
+            verified: is_verified,
+        } in data
+        {
+            if is_verified {
+                verified.insert(address.clone());
+            }
+            set.insert(address);
+        }
+
+        if set.contains(&primary) {
+            Ok(Box::new(ValueSetEmailAddress {
+                primary,
+                set,
+                verified,
+            }))
         } else {
             Err(OperationError::InvalidValueState)
         }
@@ -288,6 +330,7 @@ impl ValueSetEmailAddress {
         if set.contains(primary) {
             Ok(Box::new(ValueSetEmailAddress {
                 primary: primary.to_string(),
+                verified: set.clone(),
                 set,
             }))
         } else {
@@ -313,13 +356,22 @@ impl ValueSetEmailAddress {
             })
             .collect();
 
+        let verified = BTreeSet::new();
+
         if let Some(primary) = primary {
-            Some(Box::new(ValueSetEmailAddress { primary, set }))
+            Some(Box::new(ValueSetEmailAddress {
+                primary,
+                set,
+                verified,
+            }))
         } else {
-            set.iter()
-                .next()
-                .cloned()
-                .map(|primary| Box::new(ValueSetEmailAddress { primary, set }))
+            set.iter().next().cloned().map(|primary| {
+                Box::new(ValueSetEmailAddress {
+                    primary,
+                    set,
+                    verified,
+                })
+            })
         }
     }
 }
@@ -335,9 +387,11 @@ impl ValueSetScimPut for ValueSetEmailAddress {
         let set: BTreeSet<_> = scim_mails
             .into_iter()
             .map(
+                // Verification state is controlled by the server, not the client.
                 |ScimMail {
                      value,
                      primary: is_primary,
+                     verified: _,
                  }| {
                     if is_primary {
                         primary = Some(value.clone());
@@ -357,7 +411,11 @@ impl ValueSetScimPut for ValueSetEmailAddress {
             })?;
 
         Ok(ValueSetResolveStatus::Resolved(Box::new(
-            ValueSetEmailAddress { primary, set },
+            ValueSetEmailAddress {
+                primary,
+                set,
+                verified: BTreeSet::new(),
+            },
         )))
     }
 }
@@ -381,11 +439,13 @@ impl ValueSetT for ValueSetEmailAddress {
 
     fn clear(&mut self) {
         self.set.clear();
+        self.verified.clear();
     }
 
     fn remove(&mut self, pv: &PartialValue, _cid: &Cid) -> bool {
         match pv {
             PartialValue::EmailAddress(a) => {
+                self.verified.remove(a);
                 let r = self.set.remove(a);
                 if &self.primary == a {
                     // if we can, inject another former address into primary.
@@ -482,6 +542,7 @@ impl ValueSetT for ValueSetEmailAddress {
 
     fn validate(&self, _schema_attr: &SchemaAttribute) -> bool {
         self.set.contains(&self.primary)
+            && self.verified.is_subset(&self.set)
             && self
                 .set
                 .iter()
@@ -512,6 +573,7 @@ impl ValueSetT for ValueSetEmailAddress {
                     ScimMail {
                         primary,
                         value: mail.clone(),
+                        verified: self.verified.contains(mail),
                     }
                 })
                 .collect::<Vec<_>>(),
@@ -519,7 +581,26 @@ impl ValueSetT for ValueSetEmailAddress {
     }
 
     fn to_db_valueset_v2(&self) -> DbValueSetV2 {
-        DbValueSetV2::EmailAddress(self.primary.clone(), self.set.iter().cloned().collect())
+        // The original encoding is read back with every address verified, so it is used
+        // whenever that is true. This is also what is replicated, and older replicas can
+        // only read the original encoding.
+        if self.verified == self.set {
+            return DbValueSetV2::EmailAddress(
+                self.primary.clone(),
+                self.set.iter().cloned().collect(),
+            );
+        }
+
+        DbValueSetV2::EmailAddressV2(
+            self.primary.clone(),
+            self.set
+                .iter()
+                .map(|address| DbValueEmailAddressV1 {
+                    address: address.clone(),
+                    verified: self.verified.contains(address),
+                })
+                .collect(),
+        )
     }
 
     fn to_partialvalue_iter(&self) -> Box<dyn Iterator<Item = PartialValue> + '_> {
@@ -535,7 +616,9 @@ impl ValueSetT for ValueSetEmailAddress {
 
     fn equal(&self, other: &ValueSet) -> bool {
         if let Some((p_b, set_b)) = other.as_emailaddress_set() {
-            &self.set == set_b && &self.primary == p_b
+            &self.set == set_b
+                && &self.primary == p_b
+                && Some(&self.verified) == other.as_email_verified_set()
         } else {
             debug_assert!(false);
             false
@@ -544,6 +627,9 @@ impl ValueSetT for ValueSetEmailAddress {
 
     fn merge(&mut self, other: &ValueSet) -> Result<(), OperationError> {
         if let Some((_p, set_b)) = other.as_emailaddress_set() {
+            if let Some(verified_b) = other.as_email_verified_set() {
+                self.verified.extend(verified_b.iter().cloned());
+            }
             mergesets!(self.set, set_b)
         } else {
             debug_assert!(false);
@@ -559,6 +645,14 @@ impl ValueSetT for ValueSetEmailAddress {
         }
     }
 
+    fn as_email_verified_set(&self) -> Option<&BTreeSet<String>> {
+        Some(&self.verified)
+    }
+
+    fn as_email_verified_set_mut(&mut self) -> Option<&mut BTreeSet<String>> {
+        Some(&mut self.verified)
+    }
+
     fn to_email_address_primary_str(&self) -> Option<&str> {
         if self.set.is_empty() {
             None
@@ -583,6 +677,7 @@ pub struct ValueSetPhoneNumber {
 #[cfg(test)]
 mod tests {
     use super::{ValueSetAddress, ValueSetEmailAddress};
+    use crate::be::dbvalue::DbValueSetV2;
     use crate::repl::cid::Cid;
     use crate::value::{Address, PartialValue, Value};
     use crate::valueset::{self, ValueSet};
@@ -676,11 +771,13 @@ mod tests {
         let data = r#"[
           {
             "primary": false,
-            "value": "alice@example.com"
+            "value": "alice@example.com",
+            "verified": false
           },
           {
             "primary": true,
-            "value": "claire@example.com"
+            "value": "claire@example.com",
+            "verified": false
           }
         ]"#;
         crate::valueset::scim_json_reflexive(&vs, data);
@@ -689,6 +786,70 @@ mod tests {
         crate::valueset::scim_json_put_reflexive::<ValueSetEmailAddress>(&vs, &[])
     }
 
+    #[test]
+    fn test_valueset_emailaddress_verified() {
+        let mut vs: ValueSet = ValueSetEmailAddress::new("claire@example.com".to_string());
+        assert!(vs
+            .insert_checked(Value::new_email_address_s("alice@example.com").expect("Invalid Email"))
+            .is_ok());
+
+        // New addresses are not verified.
+        assert!(vs.as_email_verified_set().unwrap().is_empty());
+
+        assert!(vs
+            .as_email_verified_set_mut()
+            .unwrap()
+            .insert("alice@example.com".to_string()));
+
+        // Verified state persists through the db. An unverified address needs the newer
+        // encoding.
+        assert!(matches!(
+            vs.to_db_valueset_v2(),
+            DbValueSetV2::EmailAddressV2(..)
+        ));
+        let vs2 = valueset::from_db_valueset_v2(vs.to_db_valueset_v2())
+            .expect("Failed to construct vs2 from dbvalue");
+        assert_eq!(&vs, &vs2);
+        assert!(vs2
+            .as_email_verified_set()
+            .unwrap()
+            .contains("alice@example.com"));
+        assert!(!vs2
+            .as_email_verified_set()
+            .unwrap()
+            .contains("claire@example.com"));
+
+        // Once every address is verified, the encoding that older replicas understand
+        // is used again.
+        let mut vs_all = vs.clone();
+        assert!(vs_all
+            .as_email_verified_set_mut()
+            .unwrap()
+            .insert("claire@example.com".to_string()));
+        assert!(matches!(
+            vs_all.to_db_valueset_v2(),
+            DbValueSetV2::EmailAddress(..)
+        ));
+
+        // Removing an address removes its verified state.
+        assert!(vs.remove(
+            &PartialValue::new_email_address_s("alice@example.com"),
+            &Cid::new_zero()
+        ));
+        assert!(vs.as_email_verified_set().unwrap().is_empty());
+
+        // Addresses stored before verification was tracked are trusted.
+        let vs3 = valueset::from_db_valueset_v2(DbValueSetV2::EmailAddress(
+            "claire@example.com".to_string(),
+            vec!["claire@example.com".to_string()],
+        ))
+        .expect("Failed to construct vs3 from dbvalue");
+        assert!(vs3
+            .as_email_verified_set()
+            .unwrap()
+            .contains("claire@example.com"));
+    }
+
     #[test]
     fn test_scim_address() {
         let vs: ValueSet = ValueSetAddress::new(Address {
@@ -4,6 +4,7 @@ use crate::prelude::*;
 use crate::schema::SchemaAttribute;
 use crate::valueset::ScimResolveStatus;
 use std::collections::BTreeMap;
+use time::OffsetDateTime;
 
 use kanidm_proto::scim_v1::server::ScimApplicationPasswordReference;
 
@@ -36,14 +37,20 @@ impl ValueSetApplicationPassword {
                     application_refer,
                     label,
                     password,
+                    created,
                 } => {
                     let password = Password::try_from(password)
                         .map_err(|()| OperationError::InvalidValueState)?;
+                    let created = created
+                        .map(|secs| OffsetDateTime::from_unix_timestamp(secs as i64))
+                        .transpose()
+                        .map_err(|_| OperationError::InvalidValueState)?;
                     ApplicationPassword {
                         uuid: refer,
                         application: application_refer,
                         label,
                         password,
+                        created,
                     }
                 }
             };
@@ -65,6 +72,7 @@ impl ValueSetApplicationPassword {
                     application_refer: ap.application,
                     label: ap.label.clone(),
                     password: ap.password.to_dbpasswordv1(),
+                    created: ap.created.map(|odt| odt.unix_timestamp() as u64),
                 })
             })
             .collect()
@@ -83,6 +91,7 @@ impl ValueSetT for ValueSetApplicationPassword {
                 {
                     // Overwrite on duplicated labels for the same application.
                     application_entry.password = ap.password;
+                    application_entry.created = ap.created;
                 } else {
                     // Or just add it.
                     application_entries.push(ap);
@@ -193,6 +202,7 @@ impl ValueSetT for ValueSetApplicationPassword {
                     uuid: app_pwd.uuid,
                     application_uuid: app_pwd.application,
                     label: app_pwd.label.clone(),
+                    created: app_pwd.created,
                 })
                 .collect::<Vec<_>>(),
         )))
@@ -271,6 +281,7 @@ mod tests {
             label: "apppwd1".to_string(),
             password: Password::new_pbkdf2(&CryptoPolicy::minimum(), "apppwd1")
                 .expect("Failed to create password"),
+            created: None,
         };
 
         let ap2: ApplicationPassword = ApplicationPassword {
@@ -279,6 +290,7 @@ mod tests {
             label: "apppwd2".to_string(),
             password: Password::new_pbkdf2(&CryptoPolicy::minimum(), "apppwd2")
                 .expect("Failed to create password"),
+            created: None,
         };
 
         let ap3: ApplicationPassword = ApplicationPassword {
@@ -287,6 +299,7 @@ mod tests {
             label: "apppwd3".to_string(),
             password: Password::new_pbkdf2(&CryptoPolicy::minimum(), "apppwd3")
                 .expect("Failed to create password"),
+            created: None,
         };
 
         let mut vs: ValueSet = ValueSetApplicationPassword::new(ap1);
@@ -338,6 +351,7 @@ mod tests {
             label: "apppwd1".to_string(),
             password: Password::new_pbkdf2(&CryptoPolicy::minimum(), "apppwd1")
                 .expect("Failed to create password"),
+            created: None,
         };
 
         let ap2: ApplicationPassword = ApplicationPassword {
@@ -346,6 +360,7 @@ mod tests {
             label: "apppwd2".to_string(),
             password: Password::new_pbkdf2(&CryptoPolicy::minimum(), "apppwd2")
                 .expect("Failed to create password"),
+            created: None,
         };
 
         let ap3: ApplicationPassword = ApplicationPassword {
@@ -354,6 +369,7 @@ mod tests {
             label: "apppwd3".to_string(),
             password: Password::new_pbkdf2(&CryptoPolicy::minimum(), "apppwd3")
                 .expect("Failed to create password"),
+            created: None,
         };
 
         let mut vs: ValueSet = ValueSetApplicationPassword::new(ap1);
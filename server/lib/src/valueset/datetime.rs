@@ -1,5 +1,6 @@
 use crate::prelude::*;
 use crate::schema::SchemaAttribute;
+use crate::value::idx_ord_key_datetime;
 use crate::valueset::{
     DbValueSetV2, ScimResolveStatus, ValueSet, ValueSetResolveStatus, ValueSetScimPut,
 };
@@ -12,15 +13,20 @@ pub struct ValueSetDateTime {
     set: SmolSet<[OffsetDateTime; 1]>,
 }
 
+// Values are always held in UTC, so that they format, index and sort consistently.
+fn to_utc(odt: OffsetDateTime) -> OffsetDateTime {
+    odt.to_offset(time::UtcOffset::UTC)
+}
+
 impl ValueSetDateTime {
     pub fn new(b: OffsetDateTime) -> Box<Self> {
         let mut set = SmolSet::new();
-        set.insert(b);
+        set.insert(to_utc(b));
         Box::new(ValueSetDateTime { set })
     }
 
     pub fn push(&mut self, b: OffsetDateTime) -> bool {
-        self.set.insert(b)
+        self.set.insert(to_utc(b))
     }
 
     pub fn from_dbvs2(data: Vec<String>) -> Result<ValueSet, OperationError> {
@@ -28,7 +34,7 @@ impl ValueSetDateTime {
             .into_iter()
             .map(|s| {
                 OffsetDateTime::parse(&s, &Rfc3339)
-                    .map(|odt| odt.to_offset(time::UtcOffset::UTC))
+                    .map(to_utc)
                     .map_err(|_| OperationError::InvalidValueState)
             })
             .collect::<Result<_, _>>()?;
@@ -42,7 +48,7 @@ impl ValueSetDateTime {
     where
         T: IntoIterator<Item = OffsetDateTime>,
     {
-        let set = iter.into_iter().collect();
+        let set = iter.into_iter().map(to_utc).collect();
         Some(Box::new(ValueSetDateTime { set }))
     }
 }
@@ -55,7 +61,7 @@ impl ValueSetScimPut for ValueSetDateTime {
         })?;
 
         let mut set = SmolSet::new();
-        set.insert(date_time);
+        set.insert(to_utc(date_time));
 
         Ok(ValueSetResolveStatus::Resolved(Box::new(
             ValueSetDateTime { set },
@@ -66,7 +72,7 @@ impl ValueSetScimPut for ValueSetDateTime {
 impl ValueSetT for ValueSetDateTime {
     fn insert_checked(&mut self, value: Value) -> Result<bool, OperationError> {
         match value {
-            Value::DateTime(u) => Ok(self.set.insert(u)),
+            Value::DateTime(u) => Ok(self.set.insert(to_utc(u))),
             _ => {
                 debug_assert!(false);
                 Err(OperationError::InvalidValueState)
@@ -127,12 +133,20 @@ impl ValueSetT for ValueSetDateTime {
             .collect()
     }
 
+    fn generate_idx_ord_keys(&self) -> Vec<String> {
+        self.set.iter().map(idx_ord_key_datetime).collect()
+    }
+
     fn syntax(&self) -> SyntaxType {
         SyntaxType::DateTime
     }
 
     fn validate(&self, _schema_attr: &SchemaAttribute) -> bool {
-        true
+        // Values with other offsets are formatted and indexed with that offset, so they
+        // would not compare or sort correctly against other values.
+        self.set
+            .iter()
+            .all(|odt| odt.offset() == time::UtcOffset::UTC)
     }
 
     fn to_proto_string_clone_iter(&self) -> Box<dyn Iterator<Item = String> + '_> {
@@ -204,8 +218,9 @@ impl ValueSetT for ValueSetDateTime {
 #[cfg(test)]
 mod tests {
     use super::ValueSetDateTime;
-    use crate::prelude::ValueSet;
+    use crate::prelude::{Value, ValueSet, ValueSetT};
     use std::time::Duration;
+    use time::format_description::well_known::Rfc3339;
     use time::OffsetDateTime;
 
     #[test]
@@ -218,4 +233,39 @@ mod tests {
         // Test that we can parse json values into a valueset.
         crate::valueset::scim_json_put_reflexive::<ValueSetDateTime>(&vs, &[])
     }
+
+    #[test]
+    fn test_datetime_insert_utc() {
+        let local =
+            OffsetDateTime::parse("2020-09-25T11:22:02+10:00", &Rfc3339).expect("Must be valid");
+
+        let mut vs = ValueSetDateTime::new(local);
+        assert!(!vs.push(local));
+        assert!(!vs
+            .insert_checked(Value::DateTime(local))
+            .expect("Failed to insert"));
+
+        let mut vs: ValueSet = vs;
+        assert!(vs
+            .insert_checked(Value::new_datetime(local + Duration::from_secs(1)))
+            .expect("Failed to insert"));
+
+        let odts: Vec<_> = vs
+            .as_datetime_set()
+            .expect("Not a datetime set")
+            .iter()
+            .collect();
+        assert_eq!(odts.len(), 2);
+        assert!(odts.iter().all(|odt| odt.offset() == time::UtcOffset::UTC));
+
+        let mut keys = vs.generate_idx_eq_keys();
+        keys.sort_unstable();
+        assert_eq!(
+            keys,
+            vec![
+                "2020-09-25T01:22:02Z".to_string(),
+                "2020-09-25T01:22:03Z".to_string()
+            ]
+        );
+    }
 }
@@ -1,32 +1,275 @@
 use crate::prelude::*;
 use crate::schema::SchemaAttribute;
-use crate::utils::trigraph_iter;
+use crate::utils::{normalise_iname, trigraph_iter};
 use crate::valueset::ScimResolveStatus;
-use crate::valueset::{DbValueSetV2, ValueSet, ValueSetResolveStatus, ValueSetScimPut};
-use kanidm_proto::scim_v1::JsonValue;
+use crate::valueset::{
+    DbValueSetV2, ValueSet, ValueSetResolveStatus, ValueSetScimPatch, ValueSetScimPut,
+    DB_VALUESET_OVERHEAD,
+};
+use kanidm_proto::scim_v1::{JsonValue, ScimPatchOp};
 use std::cmp::Ordering;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use unicode_normalization::is_nfc;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Sets with more values than this maintain a bloom filter, so that checks for values
+/// that are absent can be rejected without searching the set.
+const BLOOM_THRESHOLD: usize = 1024;
+/// The number of bits in the bloom filter for each key that it is sized to hold.
+const BLOOM_BITS_PER_KEY: usize = 10;
+/// The number of bits set in the bloom filter for each key.
+const BLOOM_HASHES: u64 = 4;
+/// The number of chars in each window of a value that is added to the bloom filter, so
+/// that substrings can be rejected too.
+const BLOOM_WINDOW: usize = 3;
+/// The chars that separate the words of a name, such as in `john.smith`.
+const WORD_DELIMITERS: [char; 3] = ['-', '_', '.'];
+
+#[derive(Clone, Copy, Hash)]
+enum BloomKey<'a> {
+    Value(&'a str),
+    Window(&'a str),
+}
+
+/// A bloom filter of the values of a set, and of the windows of chars within those values.
+/// It may report that a key is present when it is not, but never the reverse, so a possible
+/// hit must always be confirmed against the set. Removing a value from the set leaves its
+/// bits in place, which only makes false positives more likely.
+#[derive(Debug, Clone)]
+struct InameBloom {
+    bits: Vec<u64>,
+    keys: usize,
+    capacity: usize,
+}
+
+impl InameBloom {
+    fn new(capacity: usize) -> Self {
+        let words = (capacity * BLOOM_BITS_PER_KEY).div_ceil(u64::BITS as usize);
+        InameBloom {
+            bits: vec![0; words.max(1)],
+            keys: 0,
+            capacity,
+        }
+    }
 
-use std::collections::BTreeSet;
+    fn build(set: &BTreeSet<String>) -> Self {
+        let keys: usize = set.iter().map(|v| 1 + windows(v).count()).sum();
+        // Leave room for the set to grow before the filter must be rebuilt.
+        let mut bloom = InameBloom::new(keys * 2);
+        set.iter().for_each(|v| bloom.insert_value(v));
+        bloom
+    }
+
+    /// The bit positions of a key. These are derived from a single hash by double hashing.
+    fn positions(&self, key: BloomKey) -> impl Iterator<Item = usize> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        let hash = hasher.finish();
+        let (h1, h2) = (hash & u32::MAX as u64, hash >> 32);
+        let nbits = self.bits.len() as u64 * u64::BITS as u64;
+        (0..BLOOM_HASHES).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % nbits) as usize)
+    }
+
+    fn set_key(&mut self, key: BloomKey) {
+        for pos in self.positions(key).collect::<Vec<_>>() {
+            if let Some(word) = self.bits.get_mut(pos / u64::BITS as usize) {
+                *word |= 1 << (pos % u64::BITS as usize);
+            }
+        }
+        self.keys += 1;
+    }
+
+    fn may_contain_key(&self, key: BloomKey) -> bool {
+        self.positions(key).all(|pos| {
+            self.bits
+                .get(pos / u64::BITS as usize)
+                // If the position is somehow out of range, it must be treated as a hit.
+                .is_none_or(|word| word & (1 << (pos % u64::BITS as usize)) != 0)
+        })
+    }
+
+    fn insert_value(&mut self, value: &str) {
+        self.set_key(BloomKey::Value(value));
+        for w in windows(value) {
+            self.set_key(BloomKey::Window(w));
+        }
+    }
+
+    fn may_contain(&self, value: &str) -> bool {
+        self.may_contain_key(BloomKey::Value(value))
+    }
+
+    /// If false, no value of the set contains `sub`. Substrings that are shorter than a
+    /// window can't be rejected.
+    fn may_contain_substring(&self, sub: &str) -> bool {
+        windows(sub).all(|w| self.may_contain_key(BloomKey::Window(w)))
+    }
+
+    fn is_full(&self) -> bool {
+        self.keys > self.capacity
+    }
+}
+
+/// The windows of [BLOOM_WINDOW] chars within a value. Windows are of chars rather than
+/// graphemes, since a substring of a value may be split into graphemes differently to the
+/// value itself.
+fn windows(value: &str) -> impl Iterator<Item = &str> {
+    let bounds: Vec<usize> = value
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(value.len()))
+        .collect();
+    (0..bounds.len().saturating_sub(BLOOM_WINDOW)).filter_map(move |i| {
+        let start = *bounds.get(i)?;
+        let end = *bounds.get(i + BLOOM_WINDOW)?;
+        value.get(start..end)
+    })
+}
 
 #[derive(Debug, Clone)]
 pub struct ValueSetIname {
     set: BTreeSet<String>,
+    /// The case preserved form of values, keyed by their lowercase form. This is only
+    /// held in memory and is never persisted - all comparisons and indexing must use
+    /// the lowercase values in `set`.
+    display: BTreeMap<String, String>,
+    /// Only present once the set is larger than [BLOOM_THRESHOLD].
+    bloom: Option<InameBloom>,
 }
 
 impl ValueSetIname {
     pub fn new(s: &str) -> Box<Self> {
-        let mut set = BTreeSet::new();
-        set.insert(s.to_lowercase());
-        Box::new(ValueSetIname { set })
+        let mut vs = ValueSetIname {
+            set: BTreeSet::new(),
+            display: BTreeMap::new(),
+            bloom: None,
+        };
+        vs.push(s);
+        Box::new(vs)
     }
 
     pub fn push(&mut self, s: &str) -> bool {
-        self.set.insert(s.to_lowercase())
+        let lower = normalise_iname(s);
+        if self.set.contains(&lower) {
+            return false;
+        }
+        if lower != s {
+            self.display.insert(lower.clone(), s.to_string());
+        }
+        self.insert_lower(lower)
+    }
+
+    /// Insert a value that is already normalised, keeping the bloom filter up to date.
+    fn insert_lower(&mut self, lower: String) -> bool {
+        if let Some(bloom) = self.bloom.as_mut() {
+            if !self.set.contains(&lower) {
+                bloom.insert_value(&lower);
+            }
+        }
+        let inserted = self.set.insert(lower);
+        self.refresh_bloom();
+        inserted
+    }
+
+    /// Build the bloom filter once the set is large enough to need one, or rebuild it once
+    /// it holds more keys than it was sized for.
+    fn refresh_bloom(&mut self) {
+        if self.set.len() <= BLOOM_THRESHOLD {
+            return;
+        }
+        if self.bloom.as_ref().is_none_or(|bloom| bloom.is_full()) {
+            self.bloom = Some(InameBloom::build(&self.set));
+        }
+    }
+
+    fn with_set(set: BTreeSet<String>) -> Box<Self> {
+        let mut vs = ValueSetIname {
+            set,
+            display: BTreeMap::new(),
+            bloom: None,
+        };
+        vs.refresh_bloom();
+        Box::new(vs)
     }
 
     pub fn from_dbvs2(data: Vec<String>) -> Result<ValueSet, OperationError> {
         let set = data.into_iter().collect();
-        Ok(Box::new(ValueSetIname { set }))
+        Ok(ValueSetIname::with_set(set))
+    }
+
+    /// The structural rules that every iname must meet, regardless of which characters are
+    /// otherwise permitted. A value must not be empty, must contain at least one alphanumeric
+    /// char, and must not contain any control chars. This never fails on any input, so it is
+    /// the first check applied to untrusted values such as those from an import.
+    pub fn is_valid_iname(s: &str) -> bool {
+        !s.is_empty() && s.chars().any(char::is_alphanumeric) && !s.chars().any(char::is_control)
+    }
+
+    /// Given the lowercase form of a value in this set, return the form it was
+    /// originally provided in. If no alternate casing was retained, the lowercase
+    /// value is returned. None is returned if the value is not present.
+    pub fn display_form(&self, lowercased: &str) -> Option<&str> {
+        self.set.get(lowercased).map(|s| {
+            self.display
+                .get(lowercased)
+                .map(|d| d.as_str())
+                .unwrap_or(s.as_str())
+        })
+    }
+
+    /// Check if any value in this set matches `f`. Values are provided in their
+    /// lowercase form.
+    pub fn any_matches(&self, f: impl Fn(&str) -> bool) -> bool {
+        self.set.iter().any(|s| f(s.as_str()))
+    }
+
+    /// Check if any value in this set has a word that begins with `pv`, where the words of
+    /// a value are separated by [WORD_DELIMITERS]. Unlike a substring match, `smith` matches
+    /// `john.smith` but not `blacksmithery`. If `pv` itself contains delimiters, it must
+    /// begin at a word and may span the words that follow.
+    pub fn word_contains(&self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::Iname(s2) if !s2.is_empty() => {
+                self.bloom
+                    .as_ref()
+                    .is_none_or(|bloom| bloom.may_contain_substring(s2))
+                    && self.set.iter().any(|s1| {
+                        s1.match_indices(s2.as_str()).any(|(idx, _)| {
+                            s1[..idx]
+                                .chars()
+                                .next_back()
+                                .is_none_or(|c| WORD_DELIMITERS.contains(&c))
+                        })
+                    })
+            }
+            _ => false,
+        }
+    }
+
+    /// The lexicographically smallest value in this set, if any.
+    pub fn first(&self) -> Option<&str> {
+        self.set.first().map(|s| s.as_str())
+    }
+
+    /// The lexicographically largest value in this set, if any.
+    pub fn last(&self) -> Option<&str> {
+        self.set.last().map(|s| s.as_str())
+    }
+
+    /// As [ValueSetT::equal], but values in `ignore` are disregarded in both sets. This
+    /// allows system managed placeholder names to be excluded when comparing.
+    pub fn equal_ignoring(&self, other: &ValueSet, ignore: &BTreeSet<String>) -> bool {
+        let Some(other) = other.as_iname_set() else {
+            debug_assert!(false);
+            return false;
+        };
+
+        let ignore: BTreeSet<String> = ignore.iter().map(|s| normalise_iname(s)).collect();
+        self.set
+            .iter()
+            .filter(|s| !ignore.contains(*s))
+            .eq(other.iter().filter(|s| !ignore.contains(*s)))
     }
 
     // We need to allow this, because rust doesn't allow us to impl FromIterator on foreign
@@ -36,8 +279,19 @@ impl ValueSetIname {
     where
         T: IntoIterator<Item = &'a str>,
     {
-        let set = iter.into_iter().map(str::to_string).collect();
-        Some(Box::new(ValueSetIname { set }))
+        let set = iter.into_iter().map(normalise_iname).collect();
+        Some(ValueSetIname::with_set(set))
+    }
+
+    /// Build a set from owned values that have already been validated and normalised,
+    /// such as those read from another iname valueset. The values are inserted as is,
+    /// so callers must not pass unnormalised input.
+    pub fn from_iter_owned<T>(iter: T) -> Box<Self>
+    where
+        T: IntoIterator<Item = String>,
+    {
+        let set = iter.into_iter().collect();
+        ValueSetIname::with_set(set)
     }
 }
 
@@ -48,19 +302,64 @@ impl ValueSetScimPut for ValueSetIname {
             OperationError::SC0016InameSyntaxInvalid
         })?;
 
-        let mut set = BTreeSet::new();
-        set.insert(value.to_lowercase());
+        Ok(ValueSetResolveStatus::Resolved(ValueSetIname::new(&value)))
+    }
+}
 
-        Ok(ValueSetResolveStatus::Resolved(Box::new(ValueSetIname {
-            set,
-        })))
+impl ValueSetScimPatch for ValueSetIname {
+    fn apply_scim_json_patch(
+        &mut self,
+        op: ScimPatchOp,
+        value: JsonValue,
+    ) -> Result<(), OperationError> {
+        // A patch may name a single value, or many. A remove with no value at all
+        // removes every value of the attribute.
+        let values = match value {
+            JsonValue::Null if op == ScimPatchOp::Remove => {
+                self.clear();
+                return Ok(());
+            }
+            JsonValue::Array(_) => serde_json::from_value::<Vec<String>>(value),
+            _ => serde_json::from_value::<String>(value).map(|v| vec![v]),
+        }
+        .map_err(|err| {
+            error!(?err, "SCIM Iname Syntax Invalid");
+            OperationError::SC0016InameSyntaxInvalid
+        })?;
+
+        match op {
+            ScimPatchOp::Add => {
+                values.iter().for_each(|v| {
+                    self.push(v);
+                });
+            }
+            ScimPatchOp::Remove => {
+                for v in values {
+                    let lower = normalise_iname(&v);
+                    self.display.remove(&lower);
+                    self.set.remove(&lower);
+                }
+            }
+            ScimPatchOp::Replace => {
+                self.clear();
+                values.iter().for_each(|v| {
+                    self.push(v);
+                });
+            }
+        }
+
+        Ok(())
     }
 }
 
 impl ValueSetT for ValueSetIname {
     fn insert_checked(&mut self, value: Value) -> Result<bool, OperationError> {
         match value {
-            Value::Iname(s) => Ok(self.set.insert(s)),
+            Value::Iname(s) if !Self::is_valid_iname(&s) => {
+                error!(?s, "refusing to insert invalid iname");
+                Err(OperationError::SC0016InameSyntaxInvalid)
+            }
+            Value::Iname(s) => Ok(self.push(&s)),
             _ => {
                 debug_assert!(false);
                 Err(OperationError::InvalidValueState)
@@ -70,11 +369,16 @@ impl ValueSetT for ValueSetIname {
 
     fn clear(&mut self) {
         self.set.clear();
+        self.display.clear();
+        self.bloom = None;
     }
 
     fn remove(&mut self, pv: &PartialValue, _cid: &Cid) -> bool {
         match pv {
-            PartialValue::Iname(s) => self.set.remove(s),
+            PartialValue::Iname(s) => {
+                self.display.remove(s);
+                self.set.remove(s)
+            }
             _ => {
                 debug_assert!(false);
                 true
@@ -84,14 +388,22 @@ impl ValueSetT for ValueSetIname {
 
     fn contains(&self, pv: &PartialValue) -> bool {
         match pv {
-            PartialValue::Iname(s) => self.set.contains(s.as_str()),
+            PartialValue::Iname(s) => {
+                self.bloom.as_ref().is_none_or(|bloom| bloom.may_contain(s))
+                    && self.set.contains(s.as_str())
+            }
             _ => false,
         }
     }
 
     fn substring(&self, pv: &PartialValue) -> bool {
         match pv {
-            PartialValue::Iname(s2) => self.set.iter().any(|s1| s1.contains(s2)),
+            PartialValue::Iname(s2) => {
+                self.bloom
+                    .as_ref()
+                    .is_none_or(|bloom| bloom.may_contain_substring(s2))
+                    && self.set.iter().any(|s1| s1.contains(s2))
+            }
             _ => {
                 debug_assert!(false);
                 false
@@ -147,7 +459,11 @@ impl ValueSetT for ValueSetIname {
 
     fn validate(&self, _schema_attr: &SchemaAttribute) -> bool {
         self.set.iter().all(|s| {
-            Value::validate_str_escapes(s)
+            // Values must already be composed, else visually identical names could
+            // be stored as distinct values.
+            Self::is_valid_iname(s)
+                && is_nfc(s)
+                && Value::validate_str_escapes(s)
                 && Value::validate_singleline(s)
                 && Value::validate_iname(s.as_str())
         })
@@ -172,6 +488,13 @@ impl ValueSetT for ValueSetIname {
         DbValueSetV2::Iname(self.set.iter().cloned().collect())
     }
 
+    fn estimated_db_size(&self) -> usize {
+        // Valid inames never require escaping, so each is stored as its bytes in quotes,
+        // separated by commas.
+        let values: usize = self.set.iter().map(|s| s.len() + 2).sum();
+        DB_VALUESET_OVERHEAD + values + self.set.len().saturating_sub(1)
+    }
+
     fn to_partialvalue_iter(&self) -> Box<dyn Iterator<Item = PartialValue> + '_> {
         Box::new(self.set.iter().map(|i| PartialValue::new_iname(i.as_str())))
     }
@@ -199,8 +522,16 @@ impl ValueSetT for ValueSetIname {
     }
 
     fn merge(&mut self, other: &ValueSet) -> Result<(), OperationError> {
-        if let Some(b) = other.as_iname_set() {
-            mergesets!(self.set, b)
+        if let (Some(b), Some(display)) = (other.as_iname_set(), other.as_iname_display_map()) {
+            b.iter().for_each(|v| {
+                // As with push, the first form of a value that was provided is retained.
+                if self.insert_lower(v.clone()) {
+                    if let Some(d) = display.get(v) {
+                        self.display.insert(v.clone(), d.clone());
+                    }
+                }
+            });
+            Ok(())
         } else {
             debug_assert!(false);
             Err(OperationError::InvalidValueState)
@@ -219,10 +550,18 @@ impl ValueSetT for ValueSetIname {
         Some(&self.set)
     }
 
+    fn as_iname_display_map(&self) -> Option<&BTreeMap<String, String>> {
+        Some(&self.display)
+    }
+
     fn as_iname_iter(&self) -> Option<Box<dyn Iterator<Item = &str> + '_>> {
         Some(Box::new(self.set.iter().map(|s| s.as_str())))
     }
 
+    fn any_str_matches(&self, f: &dyn Fn(&str) -> bool) -> bool {
+        self.any_matches(f)
+    }
+
     fn migrate_iutf8_iname(&self) -> Result<Option<ValueSet>, OperationError> {
         Ok(None)
     }
@@ -230,8 +569,15 @@ impl ValueSetT for ValueSetIname {
 
 #[cfg(test)]
 mod tests {
-    use super::ValueSetIname;
-    use crate::prelude::ValueSet;
+    use super::{ValueSetIname, BLOOM_THRESHOLD};
+    use crate::prelude::{
+        Attribute, PartialValue, SchemaAttribute, SyntaxType, Value, ValueSet, ValueSetT,
+        ValueSetUuid,
+    };
+    use crate::valueset::ValueSetScimPatch;
+    use kanidm_proto::scim_v1::ScimPatchOp;
+    use serde_json::json;
+    use std::collections::BTreeSet;
 
     #[test]
     fn test_scim_iname() {
@@ -241,4 +587,352 @@ mod tests {
         // Test that we can parse json values into a valueset.
         crate::valueset::scim_json_put_reflexive::<ValueSetIname>(&vs, &[])
     }
+
+    #[test]
+    fn test_scim_iname_patch_add() {
+        let mut vs = ValueSetIname::new("stevo");
+
+        vs.apply_scim_json_patch(ScimPatchOp::Add, json!("William"))
+            .expect("Failed to apply patch");
+        // Values that differ only by case are not duplicated.
+        vs.apply_scim_json_patch(ScimPatchOp::Add, json!(["STEVO", "claire"]))
+            .expect("Failed to apply patch");
+
+        assert_eq!(
+            vs.as_iname_set().cloned(),
+            Some(BTreeSet::from([
+                "claire".to_string(),
+                "stevo".to_string(),
+                "william".to_string(),
+            ]))
+        );
+        assert_eq!(vs.display_form("william"), Some("William"));
+
+        assert!(vs
+            .apply_scim_json_patch(ScimPatchOp::Add, json!(1))
+            .is_err());
+    }
+
+    #[test]
+    fn test_scim_iname_patch_remove() {
+        let mut vs = ValueSetIname::new("Stevo");
+        assert!(vs.push("william"));
+        assert!(vs.push("claire"));
+
+        // Removal matches regardless of case.
+        vs.apply_scim_json_patch(ScimPatchOp::Remove, json!("STEVO"))
+            .expect("Failed to apply patch");
+        assert_eq!(
+            vs.as_iname_set().cloned(),
+            Some(BTreeSet::from([
+                "claire".to_string(),
+                "william".to_string()
+            ]))
+        );
+        assert_eq!(vs.display_form("stevo"), None);
+
+        // Absent values are ignored.
+        vs.apply_scim_json_patch(ScimPatchOp::Remove, json!(["William", "bob"]))
+            .expect("Failed to apply patch");
+        assert_eq!(
+            vs.as_iname_set().cloned(),
+            Some(BTreeSet::from(["claire".to_string()]))
+        );
+
+        vs.apply_scim_json_patch(ScimPatchOp::Replace, json!(["Anna", "ben"]))
+            .expect("Failed to apply patch");
+        assert_eq!(
+            vs.as_iname_set().cloned(),
+            Some(BTreeSet::from(["anna".to_string(), "ben".to_string()]))
+        );
+
+        // Without a value, all values are removed.
+        vs.apply_scim_json_patch(ScimPatchOp::Remove, json!(null))
+            .expect("Failed to apply patch");
+        assert!(vs.is_empty());
+    }
+
+    #[test]
+    fn test_iname_is_valid_corpus() {
+        let corpus = [
+            ("", false),
+            ("...", false),
+            ("-_.", false),
+            (" ", false),
+            ("\u{0}", false),
+            ("\n", false),
+            ("abc\n", false),
+            ("a\u{7}b", false),
+            ("\u{200B}", false),
+            ("\u{1F642}", false),
+            ("a", true),
+            ("1", true),
+            ("a.", true),
+            ("...a", true),
+            ("claire", true),
+            ("William", true),
+            ("ren\u{00E9}", true),
+        ];
+
+        for (input, expect) in corpus {
+            assert_eq!(
+                ValueSetIname::is_valid_iname(input),
+                expect,
+                "unexpected result for {input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_iname_invalid_rejected() {
+        let schema_attr =
+            SchemaAttribute::builder(Attribute::Name, SyntaxType::Utf8StringIname).build();
+
+        let mut vs: ValueSet = ValueSetIname::new("claire");
+        assert!(vs.insert_checked(Value::Iname(String::new())).is_err());
+        assert!(vs.insert_checked(Value::Iname("...".to_string())).is_err());
+        assert!(vs
+            .insert_checked(Value::Iname("a\u{0}".to_string()))
+            .is_err());
+        assert_eq!(vs.insert_checked(Value::Iname("a".to_string())), Ok(true));
+        assert_eq!(vs.len(), 2);
+        assert!(vs.validate(&schema_attr));
+
+        // Values that bypass insert_checked are still caught by validation.
+        let vs = ValueSetIname::new("...");
+        assert!(!vs.validate(&schema_attr));
+        let vs = ValueSetIname::new("");
+        assert!(!vs.validate(&schema_attr));
+    }
+
+    #[test]
+    fn test_iname_display_form() {
+        let mut vs = ValueSetIname::new("Stevo");
+        assert!(vs.push("william"));
+        // Casing doesn't create a distinct value.
+        assert!(!vs.push("STEVO"));
+
+        assert_eq!(vs.display_form("stevo"), Some("Stevo"));
+        assert_eq!(vs.display_form("william"), Some("william"));
+        assert_eq!(vs.display_form("Stevo"), None);
+        assert_eq!(vs.display_form("claire"), None);
+
+        // Comparisons still operate on the lowercase form.
+        let vs: ValueSet = vs;
+        assert!(vs.contains(&PartialValue::new_iname("stevo")));
+        assert_eq!(vs.to_iname_single(), None);
+        assert_eq!(
+            vs.to_db_valueset_v2(),
+            crate::valueset::DbValueSetV2::Iname(vec!["stevo".to_string(), "william".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_iname_display_form_insert_merge() {
+        let mut vs: ValueSet = ValueSetIname::new("Stevo");
+        assert!(vs
+            .insert_checked(Value::Iname("William".to_string()))
+            .expect("Failed to insert"));
+        // An existing value keeps the form it was first provided in.
+        assert!(!vs
+            .insert_checked(Value::Iname("STEVO".to_string()))
+            .expect("Failed to insert"));
+
+        let mut other = ValueSetIname::new("Claire");
+        assert!(other.push("WILLIAM"));
+        assert!(other.push("dave"));
+        let other: ValueSet = other;
+
+        vs.merge(&other).expect("Failed to merge");
+
+        let display = vs.as_iname_display_map().expect("Missing display forms");
+        assert_eq!(display.get("stevo").map(String::as_str), Some("Stevo"));
+        assert_eq!(display.get("william").map(String::as_str), Some("William"));
+        assert_eq!(display.get("claire").map(String::as_str), Some("Claire"));
+        assert_eq!(display.get("dave"), None);
+        assert_eq!(vs.len(), 4);
+    }
+
+    #[test]
+    fn test_iname_estimated_db_size() {
+        let mut vs = ValueSetIname::new("stevo");
+        assert!(vs.push("william"));
+        assert!(vs.push("claire"));
+
+        let vs: ValueSet = vs;
+        let stored = serde_json::to_vec(&vs.to_db_valueset_v2()).expect("Failed to serialise");
+        assert_eq!(vs.estimated_db_size(), stored.len());
+
+        // The estimate follows the stored, lowercase form.
+        let vs: ValueSet = ValueSetIname::new("Stevo");
+        let stored = serde_json::to_vec(&vs.to_db_valueset_v2()).expect("Failed to serialise");
+        assert_eq!(vs.estimated_db_size(), stored.len());
+
+        let mut vs: ValueSet = ValueSetIname::new("stevo");
+        vs.clear();
+        let stored = serde_json::to_vec(&vs.to_db_valueset_v2()).expect("Failed to serialise");
+        assert_eq!(vs.estimated_db_size(), stored.len());
+    }
+
+    #[test]
+    fn test_iname_first_last() {
+        let mut vs = ValueSetIname::new("stevo");
+        assert_eq!(vs.first(), Some("stevo"));
+        assert_eq!(vs.last(), Some("stevo"));
+
+        assert!(vs.push("William"));
+        assert!(vs.push("claire"));
+        // Ordering is by the lowercase form.
+        assert_eq!(vs.first(), Some("claire"));
+        assert_eq!(vs.last(), Some("william"));
+
+        let empty = ValueSetIname::from_iter(std::iter::empty()).expect("empty set");
+        assert_eq!(empty.first(), None);
+        assert_eq!(empty.last(), None);
+    }
+
+    #[test]
+    fn test_iname_unicode_normalisation() {
+        let composed = "Ren\u{00E9}";
+        let decomposed = "Ren\u{0065}\u{0301}";
+        assert_ne!(composed.to_lowercase(), decomposed.to_lowercase());
+
+        // Visually identical names collapse to a single composed value.
+        let mut vs = ValueSetIname::new(composed);
+        assert!(!vs.push(decomposed));
+        assert_eq!(vs.first(), Some("ren\u{00E9}"));
+
+        let mut vs: ValueSet = vs;
+        assert_eq!(
+            vs.insert_checked(Value::Iname(decomposed.to_string())),
+            Ok(false)
+        );
+        assert_eq!(vs.len(), 1);
+        assert!(vs.contains(&PartialValue::new_iname(decomposed)));
+        assert!(vs.contains(&PartialValue::new_iname(composed)));
+    }
+
+    #[test]
+    fn test_iname_equal_ignoring() {
+        let vs = ValueSetIname::from_iter(["claire", "placeholder", "william"]).expect("valueset");
+        let other: ValueSet = ValueSetIname::from_iter(["claire", "william"]).expect("valueset");
+        let ignore = BTreeSet::from(["Placeholder".to_string()]);
+
+        assert!(!vs.equal(&other));
+        assert!(vs.equal_ignoring(&other, &ignore));
+        let same: ValueSet = vs.clone();
+        assert!(vs.equal_ignoring(&same, &BTreeSet::new()));
+
+        // Values that are not ignored still count.
+        let other: ValueSet = ValueSetIname::from_iter(["claire"]).expect("valueset");
+        assert!(!vs.equal_ignoring(&other, &ignore));
+    }
+
+    #[test]
+    fn test_iname_from_iter_owned() {
+        let names = ["claire", "stevo", "william", "stevo"];
+
+        let borrowed = ValueSetIname::from_iter(names).expect("valueset");
+        let owned = ValueSetIname::from_iter_owned(names.into_iter().map(str::to_string));
+
+        assert_eq!(owned.set, borrowed.set);
+        assert_eq!(owned.len(), 3);
+        assert_eq!(owned.to_db_valueset_v2(), borrowed.to_db_valueset_v2());
+    }
+
+    #[test]
+    fn test_iname_any_matches() {
+        let vs = ValueSetIname::from_iter(["Claire", "william"]).expect("valueset");
+
+        // Values are matched in their lowercase form.
+        assert!(vs.any_matches(|s| s.starts_with("cl")));
+        assert!(!vs.any_matches(|s| s.starts_with("Cl")));
+        assert!(!vs.any_matches(|s| s == "stevo"));
+
+        let vs: ValueSet = vs;
+        assert!(vs.any_str_matches(&|s| s.ends_with("iam")));
+        assert!(!vs.any_str_matches(&|s| s.is_empty()));
+
+        // Sets that don't hold strings never match.
+        let vs: ValueSet = ValueSetUuid::new(uuid::Uuid::new_v4());
+        assert!(!vs.any_str_matches(&|_| true));
+    }
+
+    #[test]
+    fn test_iname_word_contains() {
+        let vs = ValueSetIname::from_iter(["john.smith", "blacksmithery", "mary-jane_watson"])
+            .expect("valueset");
+        let word_contains = |s: &str| vs.word_contains(&PartialValue::new_iname(s));
+
+        // Whole words and word prefixes match.
+        assert!(word_contains("smith"));
+        assert!(word_contains("smi"));
+        assert!(word_contains("john"));
+        assert!(word_contains("jane"));
+        assert!(word_contains("wat"));
+        // A query can span words, but must still begin at one.
+        assert!(word_contains("john.sm"));
+        assert!(word_contains("jane_watson"));
+        assert!(!word_contains("hn.smith"));
+
+        // Only matched as a substring, which is what this avoids.
+        assert!(vs.substring(&PartialValue::new_iname("ery")));
+        assert!(!word_contains("ery"));
+        assert!(!word_contains("mith"));
+
+        let vs = ValueSetIname::new("blacksmithery");
+        assert!(vs.substring(&PartialValue::new_iname("smith")));
+        assert!(!vs.word_contains(&PartialValue::new_iname("smith")));
+        assert!(vs.word_contains(&PartialValue::new_iname("black")));
+
+        // An empty query matches nothing, and other partial values never match.
+        assert!(!vs.word_contains(&PartialValue::new_iname("")));
+        assert!(!vs.word_contains(&PartialValue::Uuid(uuid::Uuid::new_v4())));
+    }
+
+    #[test]
+    fn test_iname_bloom_no_false_negatives() {
+        let small = ValueSetIname::from_iter(["claire", "william"]).expect("valueset");
+        assert!(small.bloom.is_none());
+
+        let names: Vec<String> = (0..BLOOM_THRESHOLD * 2)
+            .map(|i| format!("user_{i}_\u{00E9}t\u{00E9}"))
+            .collect();
+        let mut vs = ValueSetIname::from_iter(names.iter().map(|s| s.as_str())).expect("valueset");
+        assert!(vs.bloom.is_some());
+
+        // Grow the set past the size the filter was built for, so that it's rebuilt.
+        let more: Vec<String> = (0..BLOOM_THRESHOLD * 8)
+            .map(|i| format!("extra_{i}"))
+            .collect();
+        for name in more.iter() {
+            assert!(vs.push(name));
+        }
+
+        let mut vs: ValueSet = vs;
+        let cid = crate::repl::cid::Cid::new_zero();
+        assert!(vs.remove(&PartialValue::new_iname("extra_0"), &cid));
+        assert!(!vs.contains(&PartialValue::new_iname("extra_0")));
+        assert_eq!(vs.insert_checked(Value::new_iname("extra_0")), Ok(true));
+
+        for name in names.iter().chain(more.iter()) {
+            assert!(vs.contains(&PartialValue::new_iname(name)));
+        }
+
+        // Substring checks scan the set, so only a sample of the values is checked. This
+        // includes substrings that start and end next to the multibyte chars.
+        for name in names.iter().chain(more.iter()).step_by(97) {
+            for (start, _) in name.char_indices() {
+                let ends = name.char_indices().map(|(i, _)| i).skip(1);
+                for end in ends.chain([name.len()]).filter(|end| *end > start) {
+                    let sub = name.get(start..end).expect("char boundary");
+                    assert!(vs.substring(&PartialValue::new_iname(sub)));
+                }
+            }
+        }
+
+        // Absent values are still rejected.
+        assert!(!vs.contains(&PartialValue::new_iname("user_missing")));
+        assert!(!vs.substring(&PartialValue::new_iname("missing")));
+    }
 }
@@ -212,6 +212,10 @@ impl ValueSetT for ValueSetIutf8 {
         Some(Box::new(self.set.iter().map(|s| s.as_str())))
     }
 
+    fn any_str_matches(&self, f: &dyn Fn(&str) -> bool) -> bool {
+        self.set.iter().any(|s| f(s.as_str()))
+    }
+
     fn migrate_iutf8_iname(&self) -> Result<Option<ValueSet>, OperationError> {
         let vsi: Option<ValueSet> =
             ValueSetIname::from_iter(self.set.iter().map(|s| s.as_str())).map(|vs| vs as _);
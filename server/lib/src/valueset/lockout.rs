@@ -0,0 +1,209 @@
+use crate::be::dbvalue::{DbValueCredentialLockoutV1, DbValueSetV2};
+use crate::prelude::*;
+use crate::schema::SchemaAttribute;
+use crate::valueset::{ScimResolveStatus, ValueSet};
+use std::collections::btree_map::Entry as BTreeEntry;
+use std::collections::BTreeMap;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+/// The credentials of an account that have been locked due to repeated authentication
+/// failures, keyed by the credential uuid, with the time that the lock expires.
+#[derive(Debug, Clone)]
+pub struct ValueSetCredentialLockout {
+    map: BTreeMap<Uuid, OffsetDateTime>,
+}
+
+impl ValueSetCredentialLockout {
+    pub fn new(cred_id: Uuid, unlock_at: OffsetDateTime) -> Box<Self> {
+        let mut map = BTreeMap::new();
+        map.insert(cred_id, unlock_at);
+        Box::new(ValueSetCredentialLockout { map })
+    }
+
+    pub fn from_dbvs2(data: Vec<DbValueCredentialLockoutV1>) -> Result<ValueSet, OperationError> {
+        let map = data
+            .into_iter()
+            .map(|DbValueCredentialLockoutV1 { refer, unlock_at }| {
+                OffsetDateTime::from_unix_timestamp(unlock_at as i64)
+                    .map(|odt| (refer, odt))
+                    .map_err(|_| OperationError::InvalidValueState)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Box::new(ValueSetCredentialLockout { map }))
+    }
+
+    /// Insert the lockout, retaining the later unlock time if this credential
+    /// is already locked.
+    fn insert_lockout(&mut self, cred_id: Uuid, unlock_at: OffsetDateTime) -> bool {
+        match self.map.entry(cred_id) {
+            BTreeEntry::Vacant(e) => {
+                e.insert(unlock_at);
+                true
+            }
+            BTreeEntry::Occupied(mut e) => {
+                if unlock_at > *e.get() {
+                    e.insert(unlock_at);
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+}
+
+impl ValueSetT for ValueSetCredentialLockout {
+    fn insert_checked(&mut self, value: Value) -> Result<bool, OperationError> {
+        match value {
+            Value::CredentialLockout(u, t) => Ok(self.insert_lockout(u, t)),
+            _ => {
+                debug_assert!(false);
+                Err(OperationError::InvalidValueState)
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    fn remove(&mut self, pv: &PartialValue, _cid: &Cid) -> bool {
+        match pv {
+            PartialValue::CredentialLockout(u) => self.map.remove(u).is_some(),
+            _ => {
+                debug_assert!(false);
+                true
+            }
+        }
+    }
+
+    fn contains(&self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::CredentialLockout(u) => self.map.contains_key(u),
+            _ => false,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn generate_idx_eq_keys(&self) -> Vec<String> {
+        self.map
+            .keys()
+            .map(|u| u.as_hyphenated().to_string())
+            .collect()
+    }
+
+    fn syntax(&self) -> SyntaxType {
+        SyntaxType::CredentialLockout
+    }
+
+    fn validate(&self, _schema_attr: &SchemaAttribute) -> bool {
+        self.map
+            .values()
+            .all(|odt| odt.offset() == time::UtcOffset::UTC)
+    }
+
+    fn to_proto_string_clone_iter(&self) -> Box<dyn Iterator<Item = String> + '_> {
+        Box::new(self.map.iter().map(|(u, odt)| {
+            #[allow(clippy::expect_used)]
+            let unlock_at = odt
+                .format(&Rfc3339)
+                .expect("Failed to format timestamp into RFC3339");
+            format!("{}: {}", u.as_hyphenated(), unlock_at)
+        }))
+    }
+
+    fn to_scim_value(&self) -> Option<ScimResolveStatus> {
+        None
+    }
+
+    fn to_db_valueset_v2(&self) -> DbValueSetV2 {
+        DbValueSetV2::CredentialLockout(
+            self.map
+                .iter()
+                .map(|(u, odt)| DbValueCredentialLockoutV1 {
+                    refer: *u,
+                    unlock_at: odt.unix_timestamp() as u64,
+                })
+                .collect(),
+        )
+    }
+
+    fn to_partialvalue_iter(&self) -> Box<dyn Iterator<Item = PartialValue> + '_> {
+        Box::new(
+            self.map
+                .keys()
+                .copied()
+                .map(PartialValue::CredentialLockout),
+        )
+    }
+
+    fn to_value_iter(&self) -> Box<dyn Iterator<Item = Value> + '_> {
+        Box::new(
+            self.map
+                .iter()
+                .map(|(u, odt)| Value::CredentialLockout(*u, *odt)),
+        )
+    }
+
+    fn equal(&self, other: &ValueSet) -> bool {
+        if let Some(other) = other.as_credential_lockout_map() {
+            &self.map == other
+        } else {
+            debug_assert!(false);
+            false
+        }
+    }
+
+    fn merge(&mut self, other: &ValueSet) -> Result<(), OperationError> {
+        if let Some(b) = other.as_credential_lockout_map() {
+            for (u, odt) in b.iter() {
+                self.insert_lockout(*u, *odt);
+            }
+            Ok(())
+        } else {
+            debug_assert!(false);
+            Err(OperationError::InvalidValueState)
+        }
+    }
+
+    fn as_credential_lockout_map(&self) -> Option<&BTreeMap<Uuid, OffsetDateTime>> {
+        Some(&self.map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValueSetCredentialLockout;
+    use crate::prelude::*;
+    use time::OffsetDateTime;
+
+    #[test]
+    fn test_valueset_credential_lockout() {
+        let cred_id = Uuid::new_v4();
+        let early = OffsetDateTime::UNIX_EPOCH + time::Duration::seconds(60);
+        let late = OffsetDateTime::UNIX_EPOCH + time::Duration::seconds(120);
+
+        let mut vs: ValueSet = ValueSetCredentialLockout::new(cred_id, late);
+        // An earlier unlock time never shortens an existing lock.
+        assert_eq!(
+            vs.insert_checked(Value::CredentialLockout(cred_id, early)),
+            Ok(false)
+        );
+        assert_eq!(
+            vs.as_credential_lockout_map()
+                .and_then(|map| map.get(&cred_id)),
+            Some(&late)
+        );
+
+        let restored = crate::valueset::from_db_valueset_v2(vs.to_db_valueset_v2())
+            .expect("Failed to restore valueset");
+        assert!(restored.equal(&vs));
+
+        assert!(vs.remove(&PartialValue::CredentialLockout(cred_id), &Cid::new_zero()));
+        assert!(vs.is_empty());
+    }
+}
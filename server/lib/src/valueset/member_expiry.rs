@@ -0,0 +1,189 @@
+use crate::be::dbvalue::{DbValueMemberExpiryV1, DbValueSetV2};
+use crate::prelude::*;
+use crate::schema::SchemaAttribute;
+use crate::valueset::{ScimResolveStatus, ValueSet};
+use std::collections::BTreeMap;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+/// The members of a group that are only members for a limited time, keyed by the
+/// member uuid, with the time that the membership expires.
+#[derive(Debug, Clone)]
+pub struct ValueSetMemberExpiry {
+    map: BTreeMap<Uuid, OffsetDateTime>,
+}
+
+impl ValueSetMemberExpiry {
+    pub fn new(refer: Uuid, expire_at: OffsetDateTime) -> Box<Self> {
+        let mut map = BTreeMap::new();
+        map.insert(refer, expire_at);
+        Box::new(ValueSetMemberExpiry { map })
+    }
+
+    pub fn from_dbvs2(data: Vec<DbValueMemberExpiryV1>) -> Result<ValueSet, OperationError> {
+        let map = data
+            .into_iter()
+            .map(|DbValueMemberExpiryV1 { refer, expire_at }| {
+                OffsetDateTime::from_unix_timestamp(expire_at as i64)
+                    .map(|odt| (refer, odt))
+                    .map_err(|_| OperationError::InvalidValueState)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Box::new(ValueSetMemberExpiry { map }))
+    }
+
+    /// Insert the expiry, replacing any existing expiry of this member so that
+    /// a membership can be extended or shortened.
+    fn insert_expiry(&mut self, refer: Uuid, expire_at: OffsetDateTime) -> bool {
+        self.map.insert(refer, expire_at) != Some(expire_at)
+    }
+}
+
+impl ValueSetT for ValueSetMemberExpiry {
+    fn insert_checked(&mut self, value: Value) -> Result<bool, OperationError> {
+        match value {
+            Value::MemberExpiry(u, t) => Ok(self.insert_expiry(u, t)),
+            _ => {
+                debug_assert!(false);
+                Err(OperationError::InvalidValueState)
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    fn remove(&mut self, pv: &PartialValue, _cid: &Cid) -> bool {
+        match pv {
+            PartialValue::MemberExpiry(u) => self.map.remove(u).is_some(),
+            _ => {
+                debug_assert!(false);
+                true
+            }
+        }
+    }
+
+    fn contains(&self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::MemberExpiry(u) => self.map.contains_key(u),
+            _ => false,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn generate_idx_eq_keys(&self) -> Vec<String> {
+        self.map
+            .keys()
+            .map(|u| u.as_hyphenated().to_string())
+            .collect()
+    }
+
+    fn syntax(&self) -> SyntaxType {
+        SyntaxType::MemberExpiry
+    }
+
+    fn validate(&self, _schema_attr: &SchemaAttribute) -> bool {
+        self.map
+            .values()
+            .all(|odt| odt.offset() == time::UtcOffset::UTC)
+    }
+
+    fn to_proto_string_clone_iter(&self) -> Box<dyn Iterator<Item = String> + '_> {
+        Box::new(self.map.iter().map(|(u, odt)| {
+            #[allow(clippy::expect_used)]
+            let expire_at = odt
+                .format(&Rfc3339)
+                .expect("Failed to format timestamp into RFC3339");
+            format!("{}: {}", u.as_hyphenated(), expire_at)
+        }))
+    }
+
+    fn to_scim_value(&self) -> Option<ScimResolveStatus> {
+        None
+    }
+
+    fn to_db_valueset_v2(&self) -> DbValueSetV2 {
+        DbValueSetV2::MemberExpiry(
+            self.map
+                .iter()
+                .map(|(u, odt)| DbValueMemberExpiryV1 {
+                    refer: *u,
+                    expire_at: odt.unix_timestamp() as u64,
+                })
+                .collect(),
+        )
+    }
+
+    fn to_partialvalue_iter(&self) -> Box<dyn Iterator<Item = PartialValue> + '_> {
+        Box::new(self.map.keys().copied().map(PartialValue::MemberExpiry))
+    }
+
+    fn to_value_iter(&self) -> Box<dyn Iterator<Item = Value> + '_> {
+        Box::new(
+            self.map
+                .iter()
+                .map(|(u, odt)| Value::MemberExpiry(*u, *odt)),
+        )
+    }
+
+    fn equal(&self, other: &ValueSet) -> bool {
+        if let Some(other) = other.as_member_expiry_map() {
+            &self.map == other
+        } else {
+            debug_assert!(false);
+            false
+        }
+    }
+
+    fn merge(&mut self, other: &ValueSet) -> Result<(), OperationError> {
+        if let Some(b) = other.as_member_expiry_map() {
+            for (u, odt) in b.iter() {
+                self.insert_expiry(*u, *odt);
+            }
+            Ok(())
+        } else {
+            debug_assert!(false);
+            Err(OperationError::InvalidValueState)
+        }
+    }
+
+    fn as_member_expiry_map(&self) -> Option<&BTreeMap<Uuid, OffsetDateTime>> {
+        Some(&self.map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValueSetMemberExpiry;
+    use crate::prelude::*;
+    use time::OffsetDateTime;
+
+    #[test]
+    fn test_valueset_member_expiry() {
+        let member = Uuid::new_v4();
+        let early = OffsetDateTime::UNIX_EPOCH + time::Duration::seconds(60);
+        let late = OffsetDateTime::UNIX_EPOCH + time::Duration::seconds(120);
+
+        let mut vs: ValueSet = ValueSetMemberExpiry::new(member, late);
+        // A new expiry replaces the existing one, allowing a membership to be shortened.
+        assert_eq!(
+            vs.insert_checked(Value::MemberExpiry(member, early)),
+            Ok(true)
+        );
+        assert_eq!(
+            vs.as_member_expiry_map().and_then(|map| map.get(&member)),
+            Some(&early)
+        );
+
+        let restored = crate::valueset::from_db_valueset_v2(vs.to_db_valueset_v2())
+            .expect("Failed to restore valueset");
+        assert!(restored.equal(&vs));
+
+        assert!(vs.remove(&PartialValue::MemberExpiry(member), &Cid::new_zero()));
+        assert!(vs.is_empty());
+    }
+}
@@ -5,7 +5,8 @@ use crate::repl::cid::Cid;
 use crate::schema::SchemaAttribute;
 use crate::server::keys::KeyId;
 use crate::value::{
-    Address, ApiToken, CredentialType, IntentTokenState, Oauth2Session, OauthClaimMapJoin, Session,
+    Address, ApiToken, CredentialType, IntentTokenState, Oauth2Session, OauthClaimMapJoin,
+    PasskeyUsage, Session,
 };
 use compact_jwt::{crypto::JwsRs256Signer, JwsEs256Signer};
 use crypto_glue::{s256::Sha256Output, x509::Certificate};
@@ -14,7 +15,7 @@ use hashbrown::HashSet;
 use kanidm_proto::internal::ImageValue;
 use kanidm_proto::internal::{Filter as ProtoFilter, UiHint};
 use kanidm_proto::scim_v1::JsonValue;
-use kanidm_proto::scim_v1::ScimOauth2ClaimMapJoinChar;
+use kanidm_proto::scim_v1::{ScimOauth2ClaimMapJoinChar, ScimPatchOp};
 use kanidm_proto::v1::OutboundMessage;
 use smolset::SmolSet;
 use sshkey_attest::proto::PublicKey as SshPublicKey;
@@ -46,11 +47,14 @@ pub use self::iutf8::ValueSetIutf8;
 pub use self::json::{ValueSetJson, ValueSetJsonFilter};
 pub use self::jws::{ValueSetJwsKeyEs256, ValueSetJwsKeyRs256};
 pub use self::key_internal::{KeyInternalData, ValueSetKeyInternal};
+pub use self::lockout::ValueSetCredentialLockout;
+pub use self::member_expiry::ValueSetMemberExpiry;
 pub use self::message::ValueSetMessage;
 pub use self::nsuniqueid::ValueSetNsUniqueId;
 pub use self::oauth::{
     OauthClaimMapping, ValueSetOauthClaimMap, ValueSetOauthScope, ValueSetOauthScopeMap,
 };
+pub use self::passkey_usage::ValueSetPasskeyUsage;
 pub use self::restricted::ValueSetRestricted;
 pub use self::s256::ValueSetSha256;
 pub use self::secret::ValueSetSecret;
@@ -84,9 +88,12 @@ mod iutf8;
 mod json;
 mod jws;
 mod key_internal;
+mod lockout;
+mod member_expiry;
 mod message;
 mod nsuniqueid;
 mod oauth;
+mod passkey_usage;
 mod restricted;
 mod s256;
 mod secret;
@@ -104,6 +111,11 @@ mod uuid;
 
 pub type ValueSet = Box<dyn ValueSetT + Send + Sync + 'static>;
 
+/// The bytes used by the syntax tag and container of a stored valueset.
+pub const DB_VALUESET_OVERHEAD: usize = 9;
+/// The bytes assumed for each value when a valueset doesn't provide a precise estimate.
+pub const DB_VALUE_SIZE_ESTIMATE: usize = 64;
+
 dyn_clone::clone_trait_object!(ValueSetT);
 
 pub trait ValueSetT: std::fmt::Debug + DynClone {
@@ -189,6 +201,13 @@ pub trait ValueSetT: std::fmt::Debug + DynClone {
         self.len() == 0
     }
 
+    /// Estimate the number of bytes this valueset will occupy when stored, for the
+    /// enforcement of storage quotas. Unless a valueset provides a precise value, this is
+    /// a rough estimate per value.
+    fn estimated_db_size(&self) -> usize {
+        DB_VALUESET_OVERHEAD + self.len() * DB_VALUE_SIZE_ESTIMATE
+    }
+
     fn migrate_iutf8_iname(&self) -> Result<Option<ValueSet>, OperationError> {
         debug_assert!(false);
         Ok(None)
@@ -198,6 +217,12 @@ pub trait ValueSetT: std::fmt::Debug + DynClone {
         None
     }
 
+    /// Check if any value of this set matches `f`. Valuesets that don't hold strings
+    /// never match.
+    fn any_str_matches(&self, _f: &dyn Fn(&str) -> bool) -> bool {
+        false
+    }
+
     fn as_ref_uuid_iter(&self) -> Option<Box<dyn Iterator<Item = Uuid> + '_>> {
         None
     }
@@ -267,6 +292,11 @@ pub trait ValueSetT: std::fmt::Debug + DynClone {
         None
     }
 
+    fn as_iname_display_map(&self) -> Option<&BTreeMap<String, String>> {
+        debug_assert!(false);
+        None
+    }
+
     fn as_uuid_set(&self) -> Option<&SmolSet<[Uuid; 1]>> {
         None
     }
@@ -380,6 +410,16 @@ pub trait ValueSetT: std::fmt::Debug + DynClone {
         None
     }
 
+    fn as_email_verified_set(&self) -> Option<&BTreeSet<String>> {
+        debug_assert!(false);
+        None
+    }
+
+    fn as_email_verified_set_mut(&mut self) -> Option<&mut BTreeSet<String>> {
+        debug_assert!(false);
+        None
+    }
+
     fn as_sshkey_map(&self) -> Option<&BTreeMap<String, SshPublicKey>> {
         None
     }
@@ -698,6 +738,21 @@ pub trait ValueSetT: std::fmt::Debug + DynClone {
         None
     }
 
+    fn as_credential_lockout_map(&self) -> Option<&BTreeMap<Uuid, OffsetDateTime>> {
+        debug_assert!(false);
+        None
+    }
+
+    fn as_passkey_usage_map(&self) -> Option<&BTreeMap<Uuid, PasskeyUsage>> {
+        debug_assert!(false);
+        None
+    }
+
+    fn as_member_expiry_map(&self) -> Option<&BTreeMap<Uuid, OffsetDateTime>> {
+        debug_assert!(false);
+        None
+    }
+
     fn repl_merge_valueset(
         &self,
         _older: &ValueSet,
@@ -726,6 +781,16 @@ pub trait ValueSetScimPut {
     fn from_scim_json_put(value: JsonValue) -> Result<ValueSetResolveStatus, OperationError>;
 }
 
+pub trait ValueSetScimPatch {
+    /// Apply a single SCIM patch operation to the values of this set. Unlike
+    /// [ValueSetScimPut::from_scim_json_put], values not named by the patch are retained.
+    fn apply_scim_json_patch(
+        &mut self,
+        op: ScimPatchOp,
+        value: JsonValue,
+    ) -> Result<(), OperationError>;
+}
+
 impl PartialEq for ValueSet {
     fn eq(&self, other: &ValueSet) -> bool {
         self.equal(other)
@@ -736,6 +801,7 @@ pub struct UnresolvedScimValueOauth2ClaimMap {
     pub group_uuid: Uuid,
     pub claim: String,
     pub join_char: ScimOauth2ClaimMapJoinChar,
+    pub template: Option<String>,
     pub values: BTreeSet<String>,
 }
 
@@ -826,6 +892,7 @@ pub struct UnresolvedValueSetOauth2ClaimMap {
     pub group_name: String,
     pub claim: String,
     pub join_char: OauthClaimMapJoin,
+    pub template: Option<String>,
     pub claim_values: BTreeSet<String>,
 }
 
@@ -833,6 +900,7 @@ pub struct ResolvedValueSetOauth2ClaimMap {
     pub group_uuid: Uuid,
     pub claim: String,
     pub join_char: OauthClaimMapJoin,
+    pub template: Option<String>,
     pub claim_values: BTreeSet<String>,
 }
 
@@ -894,6 +962,9 @@ pub fn from_result_value_iter(
         Value::Image(imagevalue) => image::ValueSetImage::new(imagevalue),
         Value::CredentialType(c) => ValueSetCredentialType::new(c),
         Value::Certificate(c) => ValueSetCertificate::new(c)?,
+        Value::CredentialLockout(u, t) => ValueSetCredentialLockout::new(u, t),
+        Value::PasskeyUsage(u, pu) => ValueSetPasskeyUsage::new(u, pu),
+        Value::MemberExpiry(u, t) => ValueSetMemberExpiry::new(u, t),
         Value::WebauthnAttestationCaList(_)
         | Value::PhoneNumber(_, _)
         | Value::ApplicationPassword(_)
@@ -904,6 +975,7 @@ pub fn from_result_value_iter(
         | Value::ApiToken(_, _)
         | Value::Oauth2Session(_, _)
         | Value::OauthClaimMap(_, _)
+        | Value::OauthClaimTemplate(_, _)
         | Value::OauthClaimValue(_, _, _)
         | Value::JwsKeyEs256(_)
         | Value::JwsKeyRs256(_)
@@ -975,6 +1047,9 @@ pub fn from_value_iter(mut iter: impl Iterator<Item = Value>) -> Result<ValueSet
             ValueSetWebauthnAttestationCaList::new(ca_list)
         }
         Value::OauthClaimMap(name, join) => ValueSetOauthClaimMap::new(name, join),
+        Value::OauthClaimTemplate(name, template) => {
+            ValueSetOauthClaimMap::new_template(name, template)
+        }
         Value::OauthClaimValue(name, group, claims) => {
             ValueSetOauthClaimMap::new_value(name, group, claims)
         }
@@ -994,6 +1069,9 @@ pub fn from_value_iter(mut iter: impl Iterator<Item = Value>) -> Result<ValueSet
             return Err(OperationError::InvalidValueState);
         }
         Value::ApplicationPassword(ap) => ValueSetApplicationPassword::new(ap),
+        Value::CredentialLockout(u, t) => ValueSetCredentialLockout::new(u, t),
+        Value::PasskeyUsage(u, pu) => ValueSetPasskeyUsage::new(u, pu),
+        Value::MemberExpiry(u, t) => ValueSetMemberExpiry::new(u, t),
         Value::Sha256(_) => {
             debug_assert!(false);
             return Err(OperationError::InvalidValueState);
@@ -1040,6 +1118,9 @@ pub fn from_db_valueset_v2(dbvs: DbValueSetV2) -> Result<ValueSet, OperationErro
         DbValueSetV2::PublicBinary(set) => ValueSetPublicBinary::from_dbvs2(set),
         DbValueSetV2::IntentToken(set) => ValueSetIntentToken::from_dbvs2(set),
         DbValueSetV2::EmailAddress(primary, set) => ValueSetEmailAddress::from_dbvs2(primary, set),
+        DbValueSetV2::EmailAddressV2(primary, set) => {
+            ValueSetEmailAddress::from_dbvs2_v2(primary, set)
+        }
         DbValueSetV2::Passkey(set) => ValueSetPasskey::from_dbvs2(set),
         DbValueSetV2::AttestedPasskey(set) => ValueSetAttestedPasskey::from_dbvs2(set),
         DbValueSetV2::Session(set) => ValueSetSession::from_dbvs2(&set),
@@ -1066,6 +1147,9 @@ pub fn from_db_valueset_v2(dbvs: DbValueSetV2) -> Result<ValueSet, OperationErro
         DbValueSetV2::ApplicationPassword(set) => ValueSetApplicationPassword::from_dbvs2(set),
         DbValueSetV2::Json(object) => Ok(ValueSetJson::new(object)),
         DbValueSetV2::Sha256(set) => ValueSetSha256::from_dbvs2(set),
+        DbValueSetV2::CredentialLockout(set) => ValueSetCredentialLockout::from_dbvs2(set),
+        DbValueSetV2::PasskeyUsage(set) => ValueSetPasskeyUsage::from_dbvs2(set),
+        DbValueSetV2::MemberExpiry(set) => ValueSetMemberExpiry::from_dbvs2(set),
         DbValueSetV2::Message(object) => Ok(ValueSetMessage::new(object)),
         DbValueSetV2::EcKeyPrivate(_key) => Err(OperationError::InvalidState),
     }
@@ -438,6 +438,7 @@ impl ValueSetT for ValueSetOauthScopeMap {
 #[derive(Debug, Clone, PartialEq)]
 pub struct OauthClaimMapping {
     join: OauthClaimMapJoin,
+    template: Option<String>,
     values: BTreeMap<Uuid, BTreeSet<String>>,
 }
 
@@ -446,6 +447,10 @@ impl OauthClaimMapping {
         self.join
     }
 
+    pub(crate) fn template(&self) -> Option<&str> {
+        self.template.as_deref()
+    }
+
     pub(crate) fn values(&self) -> &BTreeMap<Uuid, BTreeSet<String>> {
         &self.values
     }
@@ -461,6 +466,18 @@ impl ValueSetOauthClaimMap {
     pub(crate) fn new(claim: String, join: OauthClaimMapJoin) -> Box<Self> {
         let mapping = OauthClaimMapping {
             join,
+            template: None,
+            values: BTreeMap::default(),
+        };
+        let mut map = BTreeMap::new();
+        map.insert(claim, mapping);
+        Box::new(ValueSetOauthClaimMap { map })
+    }
+
+    pub(crate) fn new_template(claim: String, template: Option<String>) -> Box<Self> {
+        let mapping = OauthClaimMapping {
+            join: OauthClaimMapJoin::default(),
+            template,
             values: BTreeMap::default(),
         };
         let mut map = BTreeMap::new();
@@ -474,6 +491,7 @@ impl ValueSetOauthClaimMap {
 
         let mapping = OauthClaimMapping {
             join: OauthClaimMapJoin::default(),
+            template: None,
             values,
         };
 
@@ -486,11 +504,17 @@ impl ValueSetOauthClaimMap {
         let map = data
             .into_iter()
             .map(|db_claim_map| match db_claim_map {
-                DbValueOauthClaimMap::V1 { name, join, values } => (
-                    name.clone(),
+                DbValueOauthClaimMap::V1 {
+                    name,
+                    join,
+                    template,
+                    values,
+                } => (
+                    name,
                     OauthClaimMapping {
                         join: join.into(),
-                        values: values.clone(),
+                        template,
+                        values,
                     },
                 ),
             })
@@ -505,6 +529,7 @@ impl ValueSetOauthClaimMap {
             group_uuid,
             claim,
             join_char,
+            template,
             claim_values,
         } in resolved.into_iter()
         {
@@ -515,6 +540,7 @@ impl ValueSetOauthClaimMap {
 
                     let claim_map = OauthClaimMapping {
                         join: join_char,
+                        template,
                         values,
                     };
                     e.insert(claim_map);
@@ -565,6 +591,7 @@ impl ValueSetScimPut for ValueSetOauthClaimMap {
             group_uuid,
             claim,
             join_char,
+            template,
             values: claim_values,
         } in claim_maps.into_iter()
         {
@@ -579,12 +606,14 @@ impl ValueSetScimPut for ValueSetOauthClaimMap {
                     group_uuid,
                     claim,
                     join_char,
+                    template,
                     claim_values,
                 }),
                 (None, Some(group_name)) => unresolved.push(UnresolvedValueSetOauth2ClaimMap {
                     group_name,
                     claim,
                     join_char,
+                    template,
                     claim_values,
                 }),
             }
@@ -612,6 +641,7 @@ impl ValueSetT for ValueSetOauthClaimMap {
 
                         let claim_map = OauthClaimMapping {
                             join: OauthClaimMapJoin::default(),
+                            template: None,
                             values,
                         };
                         e.insert(claim_map);
@@ -639,6 +669,7 @@ impl ValueSetT for ValueSetOauthClaimMap {
                         // Create a new empty claim mapping.
                         let claim_map = OauthClaimMapping {
                             join,
+                            template: None,
                             values: BTreeMap::default(),
                         };
                         e.insert(claim_map);
@@ -651,6 +682,25 @@ impl ValueSetT for ValueSetOauthClaimMap {
                     }
                 }
             }
+            Value::OauthClaimTemplate(name, template) => {
+                match self.map.entry(name) {
+                    BTreeEntry::Vacant(e) => {
+                        // Create a new empty claim mapping.
+                        let claim_map = OauthClaimMapping {
+                            join: OauthClaimMapJoin::default(),
+                            template,
+                            values: BTreeMap::default(),
+                        };
+                        e.insert(claim_map);
+                        Ok(true)
+                    }
+                    BTreeEntry::Occupied(mut e) => {
+                        // Just update (or clear) the value template.
+                        e.get_mut().template = template;
+                        Ok(true)
+                    }
+                }
+            }
             _ => Err(OperationError::InvalidValueState),
         }
     }
@@ -772,6 +822,11 @@ impl ValueSetT for ValueSetOauthClaimMap {
                         .flat_map(|claim_values| claim_values.iter())
                 })
                 .all(|s| OAUTH_CLAIMNAME_RE.is_match(s))
+            && self
+                .map
+                .values()
+                .filter_map(|mapping| mapping.template.as_deref())
+                .all(Value::validate_oauth_claim_template)
     }
 
     fn to_proto_string_clone_iter(&self) -> Box<dyn Iterator<Item = String> + '_> {
@@ -801,6 +856,7 @@ impl ValueSetT for ValueSetOauthClaimMap {
                         group_uuid: *group_uuid,
                         claim: claim_name.to_string(),
                         join_char: mappings.join.into(),
+                        template: mappings.template.clone(),
                         values: claim_values.clone(),
                     }
                 })
@@ -819,6 +875,7 @@ impl ValueSetT for ValueSetOauthClaimMap {
                 .map(|(name, mapping)| DbValueOauthClaimMap::V1 {
                     name: name.clone(),
                     join: mapping.join.into(),
+                    template: mapping.template.clone(),
                     values: mapping.values.clone(),
                 })
                 .collect(),
@@ -876,7 +933,9 @@ impl ValueSetT for ValueSetOauthClaimMap {
 #[cfg(test)]
 mod tests {
     use super::{ValueSetOauthClaimMap, ValueSetOauthScope, ValueSetOauthScopeMap};
+    use crate::be::dbvalue::DbValueSetV2;
     use crate::prelude::*;
+    use crate::value::OauthClaimMapJoin;
     use std::collections::BTreeSet;
 
     #[test]
@@ -981,4 +1040,64 @@ mod tests {
 
         assert!(write_txn.commit().is_ok());
     }
+
+    #[qs_test]
+    async fn test_scim_oauth2_claim_map_template(server: &QueryServer) {
+        let mut write_txn = server.write(duration_from_epoch_now()).await.unwrap();
+
+        let g_uuid = uuid::uuid!("4d21d04a-dc0e-42eb-b850-34dd180b107f");
+        assert!(write_txn
+            .internal_create(vec![entry_init!(
+                (Attribute::Class, EntryClass::Object.to_value()),
+                (Attribute::Class, EntryClass::Group.to_value()),
+                (Attribute::Name, Value::new_iname("testgroup")),
+                (Attribute::Uuid, Value::Uuid(g_uuid))
+            ),])
+            .is_ok());
+
+        let set = ["read".to_string(), "write".to_string()].into();
+        let mut vs: ValueSet = ValueSetOauthClaimMap::new_value("claim".to_string(), g_uuid, set);
+        assert!(vs
+            .insert_checked(Value::OauthClaimMap(
+                "claim".to_string(),
+                OauthClaimMapJoin::Single
+            ))
+            .is_ok());
+        assert!(vs
+            .insert_checked(Value::OauthClaimTemplate(
+                "claim".to_string(),
+                Some("ROLE_{value}".to_string())
+            ))
+            .is_ok());
+
+        let data = r#"
+[
+  {
+    "claim": "claim",
+    "group": "testgroup@example.com",
+    "groupUuid": "4d21d04a-dc0e-42eb-b850-34dd180b107f",
+    "joinChar": "single",
+    "template": "ROLE_{value}",
+    "values": ["read", "write"]
+  }
+]
+        "#;
+        crate::valueset::scim_json_reflexive_unresolved(&mut write_txn, &vs, data);
+
+        crate::valueset::scim_json_put_reflexive_unresolved::<ValueSetOauthClaimMap>(
+            &mut write_txn,
+            &vs,
+            &[],
+        );
+
+        // The template must survive a round trip through the database format.
+        let db_vs = ValueSetOauthClaimMap::from_dbvs2(match vs.to_db_valueset_v2() {
+            DbValueSetV2::OauthClaimMap(set) => set,
+            _ => unreachable!(),
+        })
+        .expect("Failed to load db valueset");
+        assert!(db_vs.equal(&vs));
+
+        assert!(write_txn.commit().is_ok());
+    }
 }
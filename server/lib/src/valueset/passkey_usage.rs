@@ -0,0 +1,243 @@
+use crate::be::dbvalue::{DbValuePasskeyUsageV1, DbValueSetV2};
+use crate::prelude::*;
+use crate::schema::SchemaAttribute;
+use crate::value::PasskeyUsage;
+use crate::valueset::{ScimResolveStatus, ValueSet};
+use kanidm_proto::scim_v1::server::ScimPasskeyUsage;
+use std::collections::btree_map::Entry as BTreeEntry;
+use std::collections::BTreeMap;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+/// When each passkey of an account was last used, keyed by the passkey uuid. This is
+/// local to each server, so it is updated on every authentication without causing
+/// replication traffic.
+#[derive(Debug, Clone)]
+pub struct ValueSetPasskeyUsage {
+    map: BTreeMap<Uuid, PasskeyUsage>,
+}
+
+impl ValueSetPasskeyUsage {
+    pub fn new(pk_id: Uuid, usage: PasskeyUsage) -> Box<Self> {
+        let mut map = BTreeMap::new();
+        map.insert(pk_id, usage);
+        Box::new(ValueSetPasskeyUsage { map })
+    }
+
+    pub fn from_dbvs2(data: Vec<DbValuePasskeyUsageV1>) -> Result<ValueSet, OperationError> {
+        let map = data
+            .into_iter()
+            .map(
+                |DbValuePasskeyUsageV1 {
+                     refer,
+                     last_used,
+                     counter,
+                 }| {
+                    OffsetDateTime::from_unix_timestamp(last_used as i64)
+                        .map(|last_used| (refer, PasskeyUsage { last_used, counter }))
+                        .map_err(|_| OperationError::InvalidValueState)
+                },
+            )
+            .collect::<Result<_, _>>()?;
+        Ok(Box::new(ValueSetPasskeyUsage { map }))
+    }
+
+    /// Insert the usage, retaining the latest use and the highest sign count seen
+    /// for this passkey.
+    fn insert_usage(&mut self, pk_id: Uuid, usage: PasskeyUsage) -> bool {
+        match self.map.entry(pk_id) {
+            BTreeEntry::Vacant(e) => {
+                e.insert(usage);
+                true
+            }
+            BTreeEntry::Occupied(mut e) => {
+                let existing = *e.get();
+                let merged = PasskeyUsage {
+                    last_used: existing.last_used.max(usage.last_used),
+                    counter: existing.counter.max(usage.counter),
+                };
+                if merged != existing {
+                    e.insert(merged);
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+}
+
+impl ValueSetT for ValueSetPasskeyUsage {
+    fn insert_checked(&mut self, value: Value) -> Result<bool, OperationError> {
+        match value {
+            Value::PasskeyUsage(u, pu) => Ok(self.insert_usage(u, pu)),
+            _ => {
+                debug_assert!(false);
+                Err(OperationError::InvalidValueState)
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    fn remove(&mut self, pv: &PartialValue, _cid: &Cid) -> bool {
+        match pv {
+            PartialValue::PasskeyUsage(u) => self.map.remove(u).is_some(),
+            _ => {
+                debug_assert!(false);
+                true
+            }
+        }
+    }
+
+    fn contains(&self, pv: &PartialValue) -> bool {
+        match pv {
+            PartialValue::PasskeyUsage(u) => self.map.contains_key(u),
+            _ => false,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn generate_idx_eq_keys(&self) -> Vec<String> {
+        self.map
+            .keys()
+            .map(|u| u.as_hyphenated().to_string())
+            .collect()
+    }
+
+    fn syntax(&self) -> SyntaxType {
+        SyntaxType::PasskeyUsage
+    }
+
+    fn validate(&self, _schema_attr: &SchemaAttribute) -> bool {
+        self.map
+            .values()
+            .all(|pu| pu.last_used.offset() == time::UtcOffset::UTC)
+    }
+
+    fn to_proto_string_clone_iter(&self) -> Box<dyn Iterator<Item = String> + '_> {
+        Box::new(self.map.iter().map(|(u, pu)| {
+            #[allow(clippy::expect_used)]
+            let last_used = pu
+                .last_used
+                .format(&Rfc3339)
+                .expect("Failed to format timestamp into RFC3339");
+            format!(
+                "{}: {} (sign count {})",
+                u.as_hyphenated(),
+                last_used,
+                pu.counter
+            )
+        }))
+    }
+
+    fn to_scim_value(&self) -> Option<ScimResolveStatus> {
+        Some(ScimResolveStatus::Resolved(ScimValueKanidm::from(
+            self.map
+                .iter()
+                .map(|(u, pu)| ScimPasskeyUsage {
+                    id: *u,
+                    last_used: pu.last_used,
+                    sign_count: pu.counter,
+                })
+                .collect::<Vec<_>>(),
+        )))
+    }
+
+    fn to_db_valueset_v2(&self) -> DbValueSetV2 {
+        DbValueSetV2::PasskeyUsage(
+            self.map
+                .iter()
+                .map(|(u, pu)| DbValuePasskeyUsageV1 {
+                    refer: *u,
+                    last_used: pu.last_used.unix_timestamp() as u64,
+                    counter: pu.counter,
+                })
+                .collect(),
+        )
+    }
+
+    fn to_partialvalue_iter(&self) -> Box<dyn Iterator<Item = PartialValue> + '_> {
+        Box::new(self.map.keys().copied().map(PartialValue::PasskeyUsage))
+    }
+
+    fn to_value_iter(&self) -> Box<dyn Iterator<Item = Value> + '_> {
+        Box::new(self.map.iter().map(|(u, pu)| Value::PasskeyUsage(*u, *pu)))
+    }
+
+    fn equal(&self, other: &ValueSet) -> bool {
+        if let Some(other) = other.as_passkey_usage_map() {
+            &self.map == other
+        } else {
+            debug_assert!(false);
+            false
+        }
+    }
+
+    fn merge(&mut self, other: &ValueSet) -> Result<(), OperationError> {
+        if let Some(b) = other.as_passkey_usage_map() {
+            for (u, pu) in b.iter() {
+                self.insert_usage(*u, *pu);
+            }
+            Ok(())
+        } else {
+            debug_assert!(false);
+            Err(OperationError::InvalidValueState)
+        }
+    }
+
+    fn as_passkey_usage_map(&self) -> Option<&BTreeMap<Uuid, PasskeyUsage>> {
+        Some(&self.map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValueSetPasskeyUsage;
+    use crate::prelude::*;
+    use crate::value::PasskeyUsage;
+    use time::OffsetDateTime;
+
+    #[test]
+    fn test_valueset_passkey_usage() {
+        let pk_id = Uuid::new_v4();
+        let early = PasskeyUsage {
+            last_used: OffsetDateTime::UNIX_EPOCH + time::Duration::seconds(60),
+            counter: 8,
+        };
+        let late = PasskeyUsage {
+            last_used: OffsetDateTime::UNIX_EPOCH + time::Duration::seconds(120),
+            counter: 4,
+        };
+
+        let mut vs: ValueSet = ValueSetPasskeyUsage::new(pk_id, early);
+        // The latest use and the highest sign count are both retained.
+        assert_eq!(
+            vs.insert_checked(Value::PasskeyUsage(pk_id, late)),
+            Ok(true)
+        );
+        assert_eq!(
+            vs.as_passkey_usage_map().and_then(|map| map.get(&pk_id)),
+            Some(&PasskeyUsage {
+                last_used: late.last_used,
+                counter: 8,
+            })
+        );
+        assert_eq!(
+            vs.insert_checked(Value::PasskeyUsage(pk_id, early)),
+            Ok(false)
+        );
+
+        let restored = crate::valueset::from_db_valueset_v2(vs.to_db_valueset_v2())
+            .expect("Failed to restore valueset");
+        assert!(restored.equal(&vs));
+
+        assert!(vs.remove(&PartialValue::PasskeyUsage(pk_id), &Cid::new_zero()));
+        assert!(vs.is_empty());
+    }
+}
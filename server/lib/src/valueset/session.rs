@@ -1,7 +1,7 @@
 use crate::be::dbvalue::{
-    DbCidV1, DbValueAccessScopeV1, DbValueApiToken, DbValueApiTokenScopeV1, DbValueAuthTypeV1,
-    DbValueIdentityId, DbValueOauth2Session, DbValueSession, DbValueSessionExtMetadataV1,
-    DbValueSessionStateV1,
+    DbCidV1, DbValueAccessScopeV1, DbValueApiToken, DbValueApiTokenOperationScopeV1,
+    DbValueApiTokenScopeV1, DbValueAuthTypeV1, DbValueIdentityId, DbValueOauth2Session,
+    DbValueSession, DbValueSessionExtMetadataV1, DbValueSessionStateV1,
 };
 use crate::prelude::*;
 use crate::repl::cid::Cid;
@@ -11,6 +11,7 @@ use crate::value::{
     SessionState,
 };
 use crate::valueset::{uuid_to_proto_string, DbValueSetV2, ScimResolveStatus, ValueSet};
+use kanidm_proto::internal::ApiTokenOperationScope;
 use kanidm_proto::scim_v1::server::ScimApiToken;
 use kanidm_proto::scim_v1::server::ScimAuthSession;
 use kanidm_proto::scim_v1::server::ScimOAuth2Session;
@@ -98,6 +99,8 @@ impl ValueSetSession {
                         refresh_token: refresh_token.clone(),
                     },
                 },
+                source_ip: m.source_ip,
+                user_agent: m.user_agent.clone(),
             })
             .collect()
     }
@@ -123,6 +126,8 @@ impl ValueSetSession {
                         scope,
                         type_,
                         ext_metadata,
+                        source_ip,
+                        user_agent,
                     } => {
                         // Convert things.
                         let issued_at = OffsetDateTime::parse(issued_at, &Rfc3339)
@@ -211,6 +216,8 @@ impl ValueSetSession {
                                 scope,
                                 type_,
                                 ext_metadata,
+                                source_ip: *source_ip,
+                                user_agent: user_agent.clone(),
                             },
                         ))
                     }
@@ -407,6 +414,8 @@ impl ValueSetT for ValueSetSession {
                         credential_id: session.cred_id,
                         auth_type: session.type_.to_string(),
                         session_scope: session.scope.to_string(),
+                        source_ip: session.source_ip.map(|ip| ip.to_string()),
+                        user_agent: session.user_agent.clone(),
                     }
                 })
                 .collect::<Vec<_>>(),
@@ -1065,6 +1074,7 @@ impl ValueSetApiToken {
                         issued_at,
                         issued_by,
                         scope,
+                        operation_scopes,
                     } => {
                         // Convert things.
                         let issued_at = OffsetDateTime::parse(&issued_at, &Rfc3339)
@@ -1114,6 +1124,33 @@ impl ValueSetApiToken {
                             DbValueApiTokenScopeV1::Synchronise => ApiTokenScope::Synchronise,
                         };
 
+                        let operation_scopes = operation_scopes
+                            .into_iter()
+                            .map(|op_scope| match op_scope {
+                                DbValueApiTokenOperationScopeV1::PersonRead => {
+                                    ApiTokenOperationScope::PersonRead
+                                }
+                                DbValueApiTokenOperationScopeV1::PersonWrite => {
+                                    ApiTokenOperationScope::PersonWrite
+                                }
+                                DbValueApiTokenOperationScopeV1::GroupRead => {
+                                    ApiTokenOperationScope::GroupRead
+                                }
+                                DbValueApiTokenOperationScopeV1::GroupWrite => {
+                                    ApiTokenOperationScope::GroupWrite
+                                }
+                                DbValueApiTokenOperationScopeV1::ServiceAccountRead => {
+                                    ApiTokenOperationScope::ServiceAccountRead
+                                }
+                                DbValueApiTokenOperationScopeV1::ServiceAccountWrite => {
+                                    ApiTokenOperationScope::ServiceAccountWrite
+                                }
+                                DbValueApiTokenOperationScopeV1::RadiusRead => {
+                                    ApiTokenOperationScope::RadiusRead
+                                }
+                            })
+                            .collect();
+
                         Some((
                             refer,
                             ApiToken {
@@ -1122,6 +1159,7 @@ impl ValueSetApiToken {
                                 issued_at,
                                 issued_by,
                                 scope,
+                                operation_scopes,
                             },
                         ))
                     }
@@ -1237,6 +1275,11 @@ impl ValueSetT for ValueSetApiToken {
                     issued_at: token.issued_at,
                     expires: token.expiry,
                     scope: token.scope.to_string(),
+                    operation_scopes: token
+                        .operation_scopes
+                        .iter()
+                        .map(|op_scope| op_scope.to_string())
+                        .collect(),
                 })
                 .collect::<Vec<_>>(),
         )))
@@ -1272,6 +1315,33 @@ impl ValueSetT for ValueSetApiToken {
                         ApiTokenScope::ReadWrite => DbValueApiTokenScopeV1::ReadWrite,
                         ApiTokenScope::Synchronise => DbValueApiTokenScopeV1::Synchronise,
                     },
+                    operation_scopes: m
+                        .operation_scopes
+                        .iter()
+                        .map(|op_scope| match op_scope {
+                            ApiTokenOperationScope::PersonRead => {
+                                DbValueApiTokenOperationScopeV1::PersonRead
+                            }
+                            ApiTokenOperationScope::PersonWrite => {
+                                DbValueApiTokenOperationScopeV1::PersonWrite
+                            }
+                            ApiTokenOperationScope::GroupRead => {
+                                DbValueApiTokenOperationScopeV1::GroupRead
+                            }
+                            ApiTokenOperationScope::GroupWrite => {
+                                DbValueApiTokenOperationScopeV1::GroupWrite
+                            }
+                            ApiTokenOperationScope::ServiceAccountRead => {
+                                DbValueApiTokenOperationScopeV1::ServiceAccountRead
+                            }
+                            ApiTokenOperationScope::ServiceAccountWrite => {
+                                DbValueApiTokenOperationScopeV1::ServiceAccountWrite
+                            }
+                            ApiTokenOperationScope::RadiusRead => {
+                                DbValueApiTokenOperationScopeV1::RadiusRead
+                            }
+                        })
+                        .collect(),
                 })
                 .collect(),
         )
@@ -1337,6 +1407,8 @@ mod tests {
                 scope: SessionScope::ReadOnly,
                 type_: AuthType::Passkey,
                 ext_metadata: Default::default(),
+                source_ip: None,
+                user_agent: None,
             },
         );
 
@@ -1372,6 +1444,8 @@ mod tests {
                 scope: SessionScope::ReadOnly,
                 type_: AuthType::Passkey,
                 ext_metadata: Default::default(),
+                source_ip: None,
+                user_agent: None,
             },
         );
 
@@ -1387,6 +1461,8 @@ mod tests {
                 scope: SessionScope::ReadOnly,
                 type_: AuthType::Passkey,
                 ext_metadata: Default::default(),
+                source_ip: None,
+                user_agent: None,
             },
         );
 
@@ -1417,6 +1493,8 @@ mod tests {
                 scope: SessionScope::ReadOnly,
                 type_: AuthType::Passkey,
                 ext_metadata: Default::default(),
+                source_ip: None,
+                user_agent: None,
             },
         );
 
@@ -1432,6 +1510,8 @@ mod tests {
                 scope: SessionScope::ReadOnly,
                 type_: AuthType::Passkey,
                 ext_metadata: Default::default(),
+                source_ip: None,
+                user_agent: None,
             },
         );
 
@@ -1465,6 +1545,8 @@ mod tests {
                 scope: SessionScope::ReadOnly,
                 type_: AuthType::Passkey,
                 ext_metadata: Default::default(),
+                source_ip: None,
+                user_agent: None,
             },
         );
 
@@ -1481,6 +1563,8 @@ mod tests {
                     scope: SessionScope::ReadOnly,
                     type_: AuthType::Passkey,
                     ext_metadata: Default::default(),
+                    source_ip: None,
+                    user_agent: None,
                 },
             ),
             (
@@ -1495,6 +1579,8 @@ mod tests {
                     scope: SessionScope::ReadOnly,
                     type_: AuthType::Passkey,
                     ext_metadata: Default::default(),
+                    source_ip: None,
+                    user_agent: None,
                 },
             ),
         ])
@@ -1532,6 +1618,8 @@ mod tests {
                 scope: SessionScope::ReadOnly,
                 type_: AuthType::Passkey,
                 ext_metadata: Default::default(),
+                source_ip: None,
+                user_agent: None,
             },
         );
 
@@ -1548,6 +1636,8 @@ mod tests {
                     scope: SessionScope::ReadOnly,
                     type_: AuthType::Passkey,
                     ext_metadata: Default::default(),
+                    source_ip: None,
+                    user_agent: None,
                 },
             ),
             (
@@ -1562,6 +1652,8 @@ mod tests {
                     scope: SessionScope::ReadOnly,
                     type_: AuthType::Passkey,
                     ext_metadata: Default::default(),
+                    source_ip: None,
+                    user_agent: None,
                 },
             ),
         ])
@@ -1603,6 +1695,8 @@ mod tests {
                     scope: SessionScope::ReadOnly,
                     type_: AuthType::Passkey,
                     ext_metadata: Default::default(),
+                    source_ip: None,
+                    user_agent: None,
                 },
             ),
             (
@@ -1617,6 +1711,8 @@ mod tests {
                     scope: SessionScope::ReadOnly,
                     type_: AuthType::Passkey,
                     ext_metadata: Default::default(),
+                    source_ip: None,
+                    user_agent: None,
                 },
             ),
             (
@@ -1631,6 +1727,8 @@ mod tests {
                     scope: SessionScope::ReadOnly,
                     type_: AuthType::Passkey,
                     ext_metadata: Default::default(),
+                    source_ip: None,
+                    user_agent: None,
                 },
             ),
         ])
@@ -1663,6 +1761,8 @@ mod tests {
                 scope: SessionScope::ReadOnly,
                 type_: AuthType::Passkey,
                 ext_metadata: Default::default(),
+                source_ip: None,
+                user_agent: None,
             },
         ))
         .chain((0..SESSION_MAXIMUM).map(|_| {
@@ -1678,6 +1778,8 @@ mod tests {
                     scope: SessionScope::ReadOnly,
                     type_: AuthType::Passkey,
                     ext_metadata: Default::default(),
+                    source_ip: None,
+                    user_agent: None,
                 },
             )
         }));
@@ -1983,6 +2085,8 @@ mod tests {
                 scope: SessionScope::ReadOnly,
                 type_: AuthType::Passkey,
                 ext_metadata: Default::default(),
+                source_ip: None,
+                user_agent: None,
             },
         );
 
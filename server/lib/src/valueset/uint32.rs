@@ -1,5 +1,6 @@
 use crate::prelude::*;
 use crate::schema::SchemaAttribute;
+use crate::value::idx_ord_key_uint32;
 use crate::valueset::{
     DbValueSetV2, ScimResolveStatus, ValueSet, ValueSetResolveStatus, ValueSetScimPut,
 };
@@ -114,6 +115,10 @@ impl ValueSetT for ValueSetUint32 {
         self.set.iter().map(|b| b.to_string()).collect()
     }
 
+    fn generate_idx_ord_keys(&self) -> Vec<String> {
+        self.set.iter().copied().map(idx_ord_key_uint32).collect()
+    }
+
     fn syntax(&self) -> SyntaxType {
         SyntaxType::Uint32
     }
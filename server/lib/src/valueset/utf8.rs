@@ -226,6 +226,10 @@ impl ValueSetT for ValueSetUtf8 {
     fn as_utf8_iter(&self) -> Option<Box<dyn Iterator<Item = &str> + '_>> {
         Some(Box::new(self.set.iter().map(|s| s.as_str())))
     }
+
+    fn any_str_matches(&self, f: &dyn Fn(&str) -> bool) -> bool {
+        self.set.iter().any(|s| f(s.as_str()))
+    }
 }
 
 #[cfg(test)]
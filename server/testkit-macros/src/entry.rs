@@ -14,6 +14,7 @@ const ALLOWED_ATTRIBUTES: &[&str] = &[
     "output_mode",
     "log_level",
     "ldap",
+    "metrics_allow_list",
     "with_test_env",
     "role",
 ];
@@ -0,0 +1,83 @@
+use cidr::IpCidr;
+use kanidm_client::KanidmClient;
+use kanidmd_testkit::{ADMIN_TEST_PASSWORD, ADMIN_TEST_USER};
+use std::net::{IpAddr, Ipv4Addr};
+
+const DEFAULT_IP_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+const OTHER_IP_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+
+async fn get_metrics(rsclient: &KanidmClient) -> String {
+    let response = rsclient
+        .client()
+        .get(rsclient.make_url("/metrics"))
+        .send()
+        .await
+        .expect("Failed to query /metrics");
+    assert_eq!(response.status(), 200);
+    response.text().await.expect("Failed to read metrics")
+}
+
+fn sample(metrics: &str, name: &str) -> u64 {
+    metrics
+        .lines()
+        .find_map(|line| line.strip_prefix(name)?.strip_prefix(' '))
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_else(|| panic!("Metric {name} is missing"))
+}
+
+#[kanidmd_testkit::test]
+async fn test_metrics_disabled_by_default(rsclient: &KanidmClient) {
+    let response = rsclient
+        .client()
+        .get(rsclient.make_url("/metrics"))
+        .send()
+        .await
+        .expect("Failed to query /metrics");
+    assert_eq!(response.status(), 404);
+}
+
+#[kanidmd_testkit::test(metrics_allow_list = vec![IpCidr::from(OTHER_IP_ADDRESS)])]
+async fn test_metrics_denied_outside_allow_list(rsclient: &KanidmClient) {
+    let response = rsclient
+        .client()
+        .get(rsclient.make_url("/metrics"))
+        .send()
+        .await
+        .expect("Failed to query /metrics");
+    assert_eq!(response.status(), 403);
+}
+
+#[kanidmd_testkit::test(metrics_allow_list = vec![IpCidr::from(DEFAULT_IP_ADDRESS)])]
+async fn test_metrics_counters(rsclient: &KanidmClient) {
+    let before = get_metrics(rsclient).await;
+    assert!(before.contains("# TYPE kanidm_http_request_duration_seconds histogram"));
+
+    // Other servers in this process share the metrics, so only check that they increased.
+    let res = rsclient
+        .auth_simple_password(ADMIN_TEST_USER, "not the password")
+        .await;
+    assert!(res.is_err());
+
+    rsclient
+        .auth_simple_password(ADMIN_TEST_USER, ADMIN_TEST_PASSWORD)
+        .await
+        .expect("Failed to authenticate");
+    rsclient
+        .idm_group_list()
+        .await
+        .expect("Failed to list groups");
+
+    let after = get_metrics(rsclient).await;
+
+    for name in [
+        "kanidm_auth_total{outcome=\"failure\"}",
+        "kanidm_auth_total{outcome=\"success\"}",
+        "kanidm_search_operations_total",
+        "kanidm_http_request_duration_seconds_count",
+    ] {
+        assert!(
+            sample(&after, name) > sample(&before, name),
+            "{name} did not increase"
+        );
+    }
+}
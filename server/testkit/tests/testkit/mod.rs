@@ -7,6 +7,7 @@ mod identity_verification_tests;
 mod integration;
 mod ip_addr_extractors;
 mod ldap_basic;
+mod metrics;
 mod oauth2_test;
 mod person;
 mod proto_v1_test;
@@ -463,7 +463,7 @@ async fn test_oauth2_openid_basic_flow_impl(
     assert!(tir.sub.is_some());
     assert_eq!(tir.aud.as_deref(), Some(TEST_INTEGRATION_RS_ID));
     assert!(tir.iss.is_some());
-    assert!(!tir.jti.to_string().is_empty());
+    assert!(tir.jti.is_some());
 
     // Step 5 - check that the id_token (openid) matches the userinfo endpoint.
     let oidc_unverified =
@@ -590,6 +590,16 @@ async fn test_oauth2_openid_basic_flow_impl(
     assert_eq!(tir.username.as_deref(), Some("test_integration@localhost"));
     assert_eq!(tir.token_type, Some(AccessTokenType::Bearer));
 
+    // Even though openid was granted, the token has no user so userinfo must refuse it.
+    let response = client
+        .get(rsclient.make_url(&format!("/oauth2/openid/{TEST_INTEGRATION_RS_ID}/userinfo")))
+        .bearer_auth(atr.access_token.clone())
+        .send()
+        .await
+        .expect("Failed to send userinfo request.");
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
     // revoke the token!
     let mut req = TokenRevokeRequest {
         token: atr.access_token,
@@ -2,7 +2,7 @@
 use compact_jwt::dangernoverify::JwsDangerReleaseWithoutVerify;
 use compact_jwt::{traits::JwsVerifiable, JwsCompact, JwsEs256Verifier, JwsVerifier};
 use hyper::header::CONTENT_TYPE;
-use kanidm_client::{ClientError, KanidmClient};
+use kanidm_client::{ClientError, KanidmClient, KanidmClientBuilder};
 use kanidm_proto::constants::{ATTR_GIDNUMBER, KSESSIONID};
 use kanidm_proto::internal::{
     ApiToken, CURegState, Filter, ImageValue, Modify, ModifyList, UatPurpose, UserAuthToken,
@@ -1605,6 +1605,20 @@ async fn test_server_user_auth_token_lifecycle(rsclient: &KanidmClient) {
         .expect("Failed to list user auth tokens");
 
     assert_eq!(sessions[0].session_id, token.session_id);
+    // The session records where the login came from.
+    assert_eq!(sessions[0].source_ip.as_deref(), Some("127.0.0.1"));
+    assert_eq!(
+        sessions[0].user_agent.as_deref(),
+        Some(KanidmClientBuilder::user_agent())
+    );
+
+    // The user can see their own sessions too.
+    let self_sessions = rsclient
+        .idm_self_session_list()
+        .await
+        .expect("Failed to list own sessions");
+    assert_eq!(self_sessions.len(), 1);
+    assert_eq!(self_sessions[0].session_id, token.session_id);
 
     // idm_account_destroy_user_auth_token
     rsclient
@@ -1835,7 +1849,7 @@ async fn test_server_user_auth_unprivileged(rsclient: &KanidmClient) {
     .expect("Failed to start session");
 
     match uat.purpose {
-        UatPurpose::ReadOnly => panic!("Unexpected uat purpose"),
+        UatPurpose::ReadOnly | UatPurpose::CredentialUpdate => panic!("Unexpected uat purpose"),
         UatPurpose::ReadWrite { expiry } => {
             assert!(expiry.is_none())
         }
@@ -1853,7 +1867,7 @@ async fn test_server_user_auth_privileged_shortcut(rsclient: &KanidmClient) {
         .expect("Failed to start session");
 
     match uat.purpose {
-        UatPurpose::ReadOnly => panic!("Unexpected uat purpose"),
+        UatPurpose::ReadOnly | UatPurpose::CredentialUpdate => panic!("Unexpected uat purpose"),
         UatPurpose::ReadWrite { expiry } => {
             assert!(expiry.is_some())
         }
@@ -1,4 +1,8 @@
-use kanidm_client::KanidmClient;
+use kanidm_client::{ClientError, KanidmClient, StatusCode};
+use kanidm_proto::internal::ApiTokenOperationScope;
+use kanidmd_lib::constants::NAME_IDM_ADMINS;
+use kanidmd_testkit::{ADMIN_TEST_PASSWORD, ADMIN_TEST_USER};
+use std::collections::BTreeSet;
 
 /// This literally tests that the thing exists and responds in a way we expect, probably worth testing it better...
 #[kanidmd_testkit::test]
@@ -28,3 +32,104 @@ async fn test_v1_service_account_id_attr_attr_delete(rsclient: &KanidmClient) {
     let body = response.text().await.unwrap();
     eprintln!("{body}");
 }
+
+#[kanidmd_testkit::test]
+async fn test_v1_service_account_api_token_operation_scopes(rsclient: &KanidmClient) {
+    let res = rsclient
+        .auth_simple_password(ADMIN_TEST_USER, ADMIN_TEST_PASSWORD)
+        .await;
+    assert!(res.is_ok());
+
+    let test_service_account_username = "test_service";
+
+    rsclient
+        .idm_service_account_create(
+            test_service_account_username,
+            "Test Service",
+            NAME_IDM_ADMINS,
+        )
+        .await
+        .expect("Failed to create service account");
+
+    let operation_scopes = BTreeSet::from([ApiTokenOperationScope::GroupRead]);
+
+    let scoped_token = rsclient
+        .idm_service_account_generate_scoped_api_token(
+            test_service_account_username,
+            "scoped token",
+            None,
+            false,
+            operation_scopes.clone(),
+            false,
+        )
+        .await
+        .expect("Failed to create service account api token");
+
+    let unscoped_token = rsclient
+        .idm_service_account_generate_api_token(
+            test_service_account_username,
+            "unscoped token",
+            None,
+            false,
+            false,
+        )
+        .await
+        .expect("Failed to create service account api token");
+
+    let tokens = rsclient
+        .idm_service_account_list_api_token(test_service_account_username)
+        .await
+        .expect("Failed to list service account api tokens");
+    assert!(tokens
+        .iter()
+        .any(|token| token.operation_scopes == operation_scopes));
+
+    let scoped_client = rsclient
+        .new_session()
+        .expect("Failed to create new session");
+    scoped_client.set_token(scoped_token).await;
+
+    // The token can inspect its own scopes.
+    let token = scoped_client
+        .whoami_api_token()
+        .await
+        .expect("Failed to inspect api token");
+    assert_eq!(token.operation_scopes, operation_scopes);
+
+    // Within scope.
+    assert!(scoped_client.idm_group_list().await.is_ok());
+
+    // Out of scope, even though the account could otherwise perform these.
+    let err = scoped_client
+        .idm_person_account_list()
+        .await
+        .expect_err("Scoped token should not be able to list persons");
+    assert!(matches!(
+        err,
+        ClientError::Http(StatusCode::FORBIDDEN, _, _)
+    ));
+
+    let err = scoped_client
+        .idm_service_account_list_api_token(test_service_account_username)
+        .await
+        .expect_err("Scoped token should not be able to list api tokens");
+    assert!(matches!(
+        err,
+        ClientError::Http(StatusCode::FORBIDDEN, _, _)
+    ));
+
+    // Unscoped tokens are unaffected.
+    let unscoped_client = rsclient
+        .new_session()
+        .expect("Failed to create new session");
+    unscoped_client.set_token(unscoped_token).await;
+
+    let token = unscoped_client
+        .whoami_api_token()
+        .await
+        .expect("Failed to inspect api token");
+    assert!(token.operation_scopes.is_empty());
+
+    assert!(unscoped_client.idm_group_list().await.is_ok());
+    assert!(unscoped_client.idm_person_account_list().await.is_ok());
+}
@@ -77,6 +77,14 @@ impl DomainOpt {
                     Err(e) => handle_client_error(e, opt.output_mode),
                 }
             }
+            DomainOpt::SetSelfWriteAttrs { attrs } => {
+                let client = opt.to_client(OpType::Write).await;
+                let attrs: Vec<&str> = attrs.iter().map(String::as_str).collect();
+                match client.idm_set_domain_self_write_attrs(&attrs).await {
+                    Ok(_) => println!("Success"),
+                    Err(e) => handle_client_error(e, opt.output_mode),
+                }
+            }
             DomainOpt::Show => {
                 let client = opt.to_client(OpType::Read).await;
                 match client.idm_domain_get().await {
@@ -77,6 +77,115 @@ impl GroupAccountPolicyOpt {
                         .print_message("Successfully reset password minimum length.");
                 }
             }
+            GroupAccountPolicyOpt::PasswordMinimumScore { name, score } => {
+                let client = opt.to_client(OpType::Write).await;
+                if let Err(e) = client
+                    .group_account_policy_password_minimum_score_set(name, *score)
+                    .await
+                {
+                    handle_group_account_policy_error(e, opt.output_mode);
+                } else {
+                    opt.output_mode
+                        .print_message("Updated password minimum score.");
+                }
+            }
+            GroupAccountPolicyOpt::ResetPasswordMinimumScore { name } => {
+                let client = opt.to_client(OpType::Write).await;
+                if let Err(e) = client
+                    .group_account_policy_password_minimum_score_reset(name)
+                    .await
+                {
+                    handle_group_account_policy_error(e, opt.output_mode);
+                } else {
+                    opt.output_mode
+                        .print_message("Successfully reset password minimum score.");
+                }
+            }
+            GroupAccountPolicyOpt::PasswordBreachCheck { name, enable } => {
+                let client = opt.to_client(OpType::Write).await;
+                if let Err(e) = client
+                    .group_account_policy_password_breach_check(name, *enable)
+                    .await
+                {
+                    handle_group_account_policy_error(e, opt.output_mode);
+                } else {
+                    opt.output_mode
+                        .print_message("Updated password breach check policy.");
+                }
+            }
+            GroupAccountPolicyOpt::PasswordHistoryLength { name, length } => {
+                let client = opt.to_client(OpType::Write).await;
+                if let Err(e) = client
+                    .group_account_policy_password_history_length_set(name, *length)
+                    .await
+                {
+                    handle_group_account_policy_error(e, opt.output_mode);
+                } else {
+                    opt.output_mode
+                        .print_message("Updated password history length.");
+                }
+            }
+            GroupAccountPolicyOpt::ResetPasswordHistoryLength { name } => {
+                let client = opt.to_client(OpType::Write).await;
+                if let Err(e) = client
+                    .group_account_policy_password_history_length_reset(name)
+                    .await
+                {
+                    handle_group_account_policy_error(e, opt.output_mode);
+                } else {
+                    opt.output_mode
+                        .print_message("Successfully reset password history length.");
+                }
+            }
+            GroupAccountPolicyOpt::AuthLockout {
+                name,
+                max_attempts,
+                duration,
+            } => {
+                let client = opt.to_client(OpType::Write).await;
+                if let Err(e) = client
+                    .group_account_policy_auth_lockout_set(name, *max_attempts, *duration)
+                    .await
+                {
+                    handle_group_account_policy_error(e, opt.output_mode);
+                } else {
+                    opt.output_mode
+                        .print_message("Updated authentication lockout policy.");
+                }
+            }
+            GroupAccountPolicyOpt::ResetAuthLockout { name } => {
+                let client = opt.to_client(OpType::Write).await;
+                if let Err(e) = client.group_account_policy_auth_lockout_reset(name).await {
+                    handle_group_account_policy_error(e, opt.output_mode);
+                } else {
+                    opt.output_mode
+                        .print_message("Successfully reset authentication lockout policy.");
+                }
+            }
+            GroupAccountPolicyOpt::CredentialMaxAge { name, max_age } => {
+                let client = opt.to_client(OpType::Write).await;
+                if let Err(e) = client
+                    .group_account_policy_credential_max_age_set(name, *max_age)
+                    .await
+                {
+                    handle_group_account_policy_error(e, opt.output_mode);
+                } else {
+                    opt.output_mode
+                        .print_message("Updated credential maximum age.");
+                }
+            }
+            GroupAccountPolicyOpt::ResetCredentialMaxAge { name } => {
+                let client = opt.to_client(OpType::Write).await;
+                if let Err(e) = client
+                    .group_account_policy_credential_max_age_reset(name)
+                    .await
+                {
+                    handle_group_account_policy_error(e, opt.output_mode);
+                } else {
+                    opt.output_mode
+                        .print_message("Successfully reset credential maximum age.");
+                }
+            }
             GroupAccountPolicyOpt::PrivilegedSessionExpiry { name, expiry } => {
                 let client = opt.to_client(OpType::Write).await;
                 if let Err(e) = client
@@ -108,10 +108,27 @@ impl GroupOpt {
                 let client = opt.to_client(OpType::Write).await;
                 let new_members: Vec<&str> = gcopt.members.iter().map(String::as_str).collect();
 
-                match client
-                    .idm_group_add_members(gcopt.name.as_str(), &new_members)
-                    .await
-                {
+                let result = match gcopt.expires_in {
+                    Some(expires_in) => {
+                        #[allow(clippy::disallowed_methods)]
+                        // Allowed as this should represent the current time from the callers machine.
+                        let expiry = time::OffsetDateTime::now_utc() + expires_in;
+                        client
+                            .idm_group_add_members_expiring(
+                                gcopt.name.as_str(),
+                                &new_members,
+                                expiry,
+                            )
+                            .await
+                    }
+                    None => {
+                        client
+                            .idm_group_add_members(gcopt.name.as_str(), &new_members)
+                            .await
+                    }
+                };
+
+                match result {
                     Ok(_) => opt.output_mode.print_message(format!(
                         "Successfully added {:?} to group \"{}\"",
                         &new_members,
@@ -139,6 +139,24 @@ impl SelfOpt {
 
                 run_identity_verification_no_tui(IdentifyUserState::Start, client, spn, None).await;
             } // end PersonOpt::Validity
+            SelfOpt::MailVerify { mail } => {
+                let client = opt.to_client(OpType::Write).await;
+
+                match client.idm_self_mail_verify_send(mail).await {
+                    Ok(()) => opt
+                        .output_mode
+                        .print_message(format!("A verification message was sent to {mail}")),
+                    Err(e) => handle_client_error(e, opt.output_mode),
+                }
+            }
+            SelfOpt::MailConfirm { token } => {
+                let client = opt.to_unauth_client();
+
+                match client.mail_verify_confirm(token).await {
+                    Ok(()) => opt.output_mode.print_message("Success"),
+                    Err(e) => handle_client_error(e, opt.output_mode),
+                }
+            }
         }
     }
 }
@@ -153,6 +171,7 @@ impl SystemOpt {
             SystemOpt::Domain { commands } => commands.exec(opt).await,
             SystemOpt::Message { commands } => commands.exec(opt).await,
             SystemOpt::Synch { commands } => commands.exec(opt).await,
+            SystemOpt::Webhook { commands } => commands.exec(opt).await,
         }
     }
 }
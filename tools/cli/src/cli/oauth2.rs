@@ -297,6 +297,16 @@ impl Oauth2Opt {
                     Err(e) => handle_client_error(e, opt.output_mode),
                 }
             }
+            Oauth2Opt::SetAccessTokenExpiry { name, expiry } => {
+                let client = opt.to_client(OpType::Write).await;
+                match client
+                    .idm_oauth2_rs_set_access_token_expiry(name.as_str(), *expiry)
+                    .await
+                {
+                    Ok(_) => opt.output_mode.print_message("Success"),
+                    Err(e) => handle_client_error(e, opt.output_mode),
+                }
+            }
             Oauth2Opt::EnablePkce(nopt) => {
                 let client = opt.to_client(OpType::Write).await;
                 match client.idm_oauth2_rs_enable_pkce(nopt.name.as_str()).await {
@@ -390,6 +400,8 @@ impl Oauth2Opt {
                 name,
                 claim_name,
                 join,
+                template,
+                clear_template,
             } => {
                 let client = opt.to_client(OpType::Write).await;
 
@@ -397,12 +409,33 @@ impl Oauth2Opt {
                     Oauth2ClaimMapJoin::Csv => ProtoOauth2ClaimMapJoin::Csv,
                     Oauth2ClaimMapJoin::Ssv => ProtoOauth2ClaimMapJoin::Ssv,
                     Oauth2ClaimMapJoin::Array => ProtoOauth2ClaimMapJoin::Array,
+                    Oauth2ClaimMapJoin::Single => ProtoOauth2ClaimMapJoin::Single,
                 };
 
-                match client
+                if let Err(e) = client
                     .idm_oauth2_rs_update_claim_map_join(name.as_str(), claim_name.as_str(), join)
                     .await
                 {
+                    return handle_client_error(e, opt.output_mode);
+                }
+
+                let result = if let Some(template) = template {
+                    client
+                        .idm_oauth2_rs_update_claim_map_template(
+                            name.as_str(),
+                            claim_name.as_str(),
+                            template.as_str(),
+                        )
+                        .await
+                } else if *clear_template {
+                    client
+                        .idm_oauth2_rs_delete_claim_map_template(name.as_str(), claim_name.as_str())
+                        .await
+                } else {
+                    Ok(())
+                };
+
+                match result {
                     Ok(_) => opt.output_mode.print_message("Success"),
                     Err(e) => handle_client_error(e, opt.output_mode),
                 }
@@ -468,6 +501,26 @@ impl Oauth2Opt {
                     Err(e) => handle_client_error(e, opt.output_mode),
                 }
             }
+            Oauth2Opt::EnableRequirePushedAuthorisation { name } => {
+                let client = opt.to_client(OpType::Write).await;
+                match client
+                    .idm_oauth2_rs_enable_require_pushed_authorisation(name.as_str())
+                    .await
+                {
+                    Ok(_) => opt.output_mode.print_message("Success"),
+                    Err(e) => handle_client_error(e, opt.output_mode),
+                }
+            }
+            Oauth2Opt::DisableRequirePushedAuthorisation { name } => {
+                let client = opt.to_client(OpType::Write).await;
+                match client
+                    .idm_oauth2_rs_disable_require_pushed_authorisation(name.as_str())
+                    .await
+                {
+                    Ok(_) => opt.output_mode.print_message("Success"),
+                    Err(e) => handle_client_error(e, opt.output_mode),
+                }
+            }
             Oauth2Opt::RotateCryptographicKeys { name, rotate_at } => {
                 let client = opt.to_client(OpType::Write).await;
                 match client
@@ -10,20 +10,23 @@ use dialoguer::{Confirm, Input, Password, Select};
 use kanidm_client::{ClientError, KanidmClient};
 use kanidm_proto::attribute::Attribute;
 use kanidm_proto::constants::{
-    ATTR_ACCOUNT_EXPIRE, ATTR_ACCOUNT_SOFTLOCK_EXPIRE, ATTR_ACCOUNT_VALID_FROM, ATTR_GIDNUMBER,
+    ATTR_ACCOUNT_EXPIRE, ATTR_ACCOUNT_SOFTLOCK_EXPIRE, ATTR_ACCOUNT_VALID_FROM,
+    ATTR_CREDENTIAL_LOCKOUT, ATTR_GIDNUMBER, ATTR_NAME,
 };
 use kanidm_proto::internal::OperationError::{
     DuplicateKey, DuplicateLabel, InvalidLabel, NoMatchingEntries, PasswordQuality,
 };
 use kanidm_proto::internal::{
-    CUCredState, CUExtPortal, CUIntentToken, CURegState, CURegWarning, CUSessionToken, CUStatus,
-    SshPublicKey, TotpSecret,
+    BatchCreateStatus, CUCredState, CUExtPortal, CUIntentToken, CURegState, CURegWarning,
+    CUSessionToken, CUStatus, PasskeyDetail, SshPublicKey, TotpSecret,
 };
 use kanidm_proto::internal::{CredentialDetail, CredentialDetailType};
 use kanidm_proto::messages::{AccountChangeMessage, ConsoleOutputMode, MessageStatus};
 use kanidm_proto::scim_v1::{client::ScimSshPublicKeys, ScimEntryGetQuery};
+use kanidm_proto::v1::Entry;
 use qrcode::render::unicode;
 use qrcode::QrCode;
+use std::collections::BTreeMap;
 use std::fmt::{self, Debug};
 use std::str::FromStr;
 use time::format_description::well_known::Rfc3339;
@@ -345,13 +348,26 @@ impl PersonOpt {
             }
             PersonOpt::Create(acopt) => {
                 let client = opt.to_client(OpType::Write).await;
-                match client
-                    .idm_person_account_create(
-                        acopt.aopts.account_id.as_str(),
-                        acopt.display_name.as_str(),
-                    )
-                    .await
-                {
+                let result = match &acopt.template {
+                    Some(template) => {
+                        client
+                            .idm_person_account_create_from_template(
+                                acopt.aopts.account_id.as_str(),
+                                acopt.display_name.as_str(),
+                                template.as_str(),
+                            )
+                            .await
+                    }
+                    None => {
+                        client
+                            .idm_person_account_create(
+                                acopt.aopts.account_id.as_str(),
+                                acopt.display_name.as_str(),
+                            )
+                            .await
+                    }
+                };
+                match result {
                     Ok(_) => {
                         println!(
                             "Successfully created display_name=\"{}\" username={}",
@@ -362,6 +378,64 @@ impl PersonOpt {
                     Err(e) => handle_client_error(e, opt.output_mode),
                 }
             }
+            PersonOpt::Import {
+                file,
+                all_or_nothing,
+            } => {
+                let persons: Vec<BTreeMap<String, Vec<String>>> = match std::fs::read(file)
+                    .map_err(|e| e.to_string())
+                    .and_then(|data| serde_json::from_slice(&data).map_err(|e| e.to_string()))
+                {
+                    Ok(p) => p,
+                    Err(err) => {
+                        error!(?err, ?file, "Unable to read persons to import");
+                        return;
+                    }
+                };
+
+                let names: Vec<String> = persons
+                    .iter()
+                    .map(|attrs| {
+                        attrs
+                            .get(ATTR_NAME)
+                            .and_then(|v| v.first())
+                            .cloned()
+                            .unwrap_or_default()
+                    })
+                    .collect();
+
+                let entries = persons.into_iter().map(|attrs| Entry { attrs }).collect();
+
+                let client = opt.to_client(OpType::Write).await;
+                let results = match client
+                    .idm_person_account_create_batch(entries, *all_or_nothing)
+                    .await
+                {
+                    Ok(r) => r,
+                    Err(e) => return handle_client_error(e, opt.output_mode),
+                };
+
+                match opt.output_mode {
+                    OutputMode::Json => {
+                        let r_attrs =
+                            serde_json::to_string(&results).expect("Failed to serialise json");
+                        println!("{r_attrs}");
+                    }
+                    OutputMode::Text => {
+                        for (name, status) in names.iter().zip(results.iter()) {
+                            match status {
+                                BatchCreateStatus::Created(uuid) => {
+                                    println!("created: {name} ({uuid})")
+                                }
+                                BatchCreateStatus::Failed(err) => {
+                                    println!("failed: {name} -> {err:?}")
+                                }
+                                BatchCreateStatus::Skipped => println!("skipped: {name}"),
+                            }
+                        }
+                    }
+                }
+            }
             PersonOpt::Validity { commands } => match commands {
                 AccountValidity::Show(ano) => {
                     let client = opt.to_client(OpType::Read).await;
@@ -721,6 +795,16 @@ impl AccountCredential {
                     _ => println!("Success"),
                 };
             }
+            AccountCredential::LockoutReset { account_id } => {
+                let client = opt.to_client(OpType::Write).await;
+                match client
+                    .idm_person_account_purge_attr(account_id.as_str(), ATTR_CREDENTIAL_LOCKOUT)
+                    .await
+                {
+                    Err(e) => handle_client_error(e, opt.output_mode),
+                    _ => println!("Success"),
+                };
+            }
         }
     }
 }
@@ -1318,6 +1402,16 @@ fn display_warnings(warnings: &[CURegWarning]) {
     }
 }
 
+fn display_passkey(pk: &PasskeyDetail) {
+    match pk
+        .last_used
+        .and_then(|last_used| last_used.format(&Rfc3339).ok())
+    {
+        Some(last_used) => println!("  {} ({}) last used {}", pk.tag, pk.uuid, last_used),
+        None => println!("  {} ({}) never used", pk.tag, pk.uuid),
+    }
+}
+
 fn display_status(status: CUStatus) {
     let CUStatus {
         spn,
@@ -1386,7 +1480,7 @@ fn display_status(status: CUStatus) {
                 println!("  not set");
             } else {
                 for pk in passkeys {
-                    println!("  {} ({})", pk.tag, pk.uuid);
+                    display_passkey(&pk);
                 }
             }
         }
@@ -1395,7 +1489,7 @@ fn display_status(status: CUStatus) {
                 println!("  unable to modify - access denied");
             } else {
                 for pk in passkeys {
-                    println!("  {} ({})", pk.tag, pk.uuid);
+                    display_passkey(&pk);
                 }
             }
         }
@@ -1414,7 +1508,7 @@ fn display_status(status: CUStatus) {
                 println!("  not set");
             } else {
                 for pk in attested_passkeys {
-                    println!("  {} ({})", pk.tag, pk.uuid);
+                    display_passkey(&pk);
                 }
             }
 
@@ -1429,7 +1523,7 @@ fn display_status(status: CUStatus) {
                 println!("  unable to modify - attestation policy not configured");
             } else {
                 for pk in attested_passkeys {
-                    println!("  {} ({})", pk.tag, pk.uuid);
+                    display_passkey(&pk);
                 }
             }
         }
@@ -72,6 +72,7 @@ impl ServiceAccountOpt {
                     label,
                     expiry,
                     read_write,
+                    scopes,
                     compact,
                 } => {
                     let expiry_odt = if let Some(t) = expiry {
@@ -98,11 +99,12 @@ impl ServiceAccountOpt {
                     let client = opt.to_client(OpType::Write).await;
 
                     match client
-                        .idm_service_account_generate_api_token(
+                        .idm_service_account_generate_scoped_api_token(
                             aopts.account_id.as_str(),
                             label,
                             expiry_odt,
                             *read_write,
+                            scopes.iter().copied().collect(),
                             *compact,
                         )
                         .await
@@ -324,16 +326,31 @@ impl ServiceAccountOpt {
                 aopts,
                 display_name,
                 entry_managed_by,
+                template,
             } => {
                 let client = opt.to_client(OpType::Write).await;
-                if let Err(e) = client
-                    .idm_service_account_create(
-                        aopts.account_id.as_str(),
-                        display_name.as_str(),
-                        entry_managed_by.as_str(),
-                    )
-                    .await
-                {
+                let result = match template {
+                    Some(template) => {
+                        client
+                            .idm_service_account_create_from_template(
+                                aopts.account_id.as_str(),
+                                display_name.as_str(),
+                                entry_managed_by.as_str(),
+                                template.as_str(),
+                            )
+                            .await
+                    }
+                    None => {
+                        client
+                            .idm_service_account_create(
+                                aopts.account_id.as_str(),
+                                display_name.as_str(),
+                                entry_managed_by.as_str(),
+                            )
+                            .await
+                    }
+                };
+                if let Err(e) = result {
                     handle_client_error(e, opt.output_mode)
                 }
             }
@@ -1,7 +1,7 @@
 use crate::common::prompt_for_username_get_username;
 use crate::common::ToClientError;
 use crate::OpType;
-use crate::{KanidmClientParser, LoginOpt, LogoutOpt, SessionOpt};
+use crate::{handle_client_error, KanidmClientParser, LoginOpt, LogoutOpt, SessionOpt};
 use compact_jwt::{
     traits::JwsVerifiable, Jwk, JwsCompact, JwsEs256Verifier, JwsVerifier, JwtError,
 };
@@ -711,7 +711,35 @@ impl LogoutOpt {
 impl SessionOpt {
     pub async fn exec(&self, opt: KanidmClientParser) {
         match self {
-            SessionOpt::List => {
+            SessionOpt::List { cached: false } => {
+                let client = opt.to_client(OpType::Read).await;
+                match client.idm_self_session_list().await {
+                    Ok(sessions) => {
+                        if sessions.is_empty() {
+                            println!("No sessions exist");
+                        } else {
+                            for session in sessions {
+                                println!("---");
+                                println!("{session}");
+                            }
+                        }
+                    }
+                    Err(e) => handle_client_error(e, opt.output_mode),
+                }
+            }
+            SessionOpt::Revoke { session_id } => {
+                let client = opt.to_client(OpType::Write).await;
+                match client.idm_self_session_revoke(*session_id).await {
+                    Ok(()) => opt
+                        .output_mode
+                        .print_message(format!("Revoked session {session_id}")),
+                    Err(e) => {
+                        error!("Error revoking session");
+                        handle_client_error(e, opt.output_mode);
+                    }
+                }
+            }
+            SessionOpt::List { cached: true } => {
                 let token_store = read_tokens(&opt.get_token_cache_path()).unwrap_or_else(|_| {
                     error!("Error retrieving authentication token store");
                     std::process::exit(1);
@@ -2,3 +2,4 @@ pub mod api;
 pub mod badlist;
 pub mod denied_names;
 pub mod message;
+pub mod webhook;
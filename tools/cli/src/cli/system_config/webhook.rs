@@ -0,0 +1,77 @@
+use crate::OpType;
+use crate::{handle_client_error, KanidmClientParser, OutputMode, WebhookOpt};
+use serde_json::json;
+
+impl WebhookOpt {
+    pub async fn exec(&self, opt: KanidmClientParser) {
+        match self {
+            WebhookOpt::List => {
+                let client = opt.to_client(OpType::Read).await;
+                match client.idm_webhook_list().await {
+                    Ok(r) => match opt.output_mode {
+                        OutputMode::Json => {
+                            let r_attrs: Vec<_> = r.iter().map(|entry| &entry.attrs).collect();
+                            println!(
+                                "{}",
+                                serde_json::to_string(&r_attrs).expect("Failed to serialise json")
+                            );
+                        }
+                        OutputMode::Text => r.iter().for_each(|ent| println!("{ent}")),
+                    },
+                    Err(e) => handle_client_error(e, opt.output_mode),
+                }
+            }
+            WebhookOpt::Get(nopt) => {
+                let client = opt.to_client(OpType::Read).await;
+                match client.idm_webhook_get(nopt.name.as_str()).await {
+                    Ok(Some(e)) => opt.output_mode.print_message(e),
+                    Ok(None) => opt.output_mode.print_message("No matching entries"),
+                    Err(e) => handle_client_error(e, opt.output_mode),
+                }
+            }
+            WebhookOpt::Create {
+                name,
+                url,
+                events,
+                filter,
+                description,
+            } => {
+                let client = opt.to_client(OpType::Write).await;
+                let events: Vec<&str> = events.iter().map(String::as_str).collect();
+                match client
+                    .idm_webhook_create(
+                        name.as_str(),
+                        url.as_str(),
+                        &events,
+                        filter.as_deref(),
+                        description.as_deref(),
+                    )
+                    .await
+                {
+                    Ok(_) => opt.output_mode.print_message("Success"),
+                    Err(e) => handle_client_error(e, opt.output_mode),
+                }
+            }
+            WebhookOpt::ShowSecret(nopt) => {
+                let client = opt.to_client(OpType::Read).await;
+                match client.idm_webhook_get_secret(nopt.name.as_str()).await {
+                    Ok(Some(secret)) => match opt.output_mode {
+                        OutputMode::Text => println!("{secret}"),
+                        OutputMode::Json => println!("{}", json!({ "secret": secret })),
+                    },
+                    Ok(None) => {
+                        opt.output_mode.print_message("No secret configured");
+                    }
+                    Err(e) => handle_client_error(e, opt.output_mode),
+                }
+            }
+            WebhookOpt::Delete(nopt) => {
+                let client = opt.to_client(OpType::Write).await;
+                match client.idm_webhook_delete(nopt.name.as_str()).await {
+                    Ok(_) => opt.output_mode.print_message("Success"),
+                    Err(e) => handle_client_error(e, opt.output_mode),
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use clap::{builder::PossibleValue, Args, Subcommand, ValueEnum};
 use kanidm_proto::constants::CLIENT_TOKEN_CACHE;
-use kanidm_proto::internal::ImageType;
+use kanidm_proto::internal::{ApiTokenOperationScope, ImageType};
 use kanidm_proto::scim_v1::ScimFilter;
 use std::fmt;
 use time::format_description::well_known::Rfc3339;
@@ -16,6 +16,30 @@ fn parse_rfc3339(input: &str) -> Result<OffsetDateTime, time::error::Parse> {
     }
 }
 
+/// Parse a duration such as "30m", "48h" or "7d".
+fn parse_duration(input: &str) -> Result<time::Duration, String> {
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (value, unit) = input.split_at(split);
+    let value: i64 = value
+        .parse()
+        .map_err(|_| format!("invalid duration {input:?}"))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return Err(format!(
+                "invalid duration unit in {input:?}, expected one of s, m, h, d or w"
+            ))
+        }
+    };
+    Ok(time::Duration::seconds(value.saturating_mul(seconds)))
+}
+
 #[derive(Debug, Args, Clone)]
 pub struct Named {
     pub name: String,
@@ -82,6 +106,16 @@ pub struct GroupNamedMembers {
     members: Vec<String>,
 }
 
+#[derive(Debug, Args, Clone)]
+pub struct GroupAddMembersOpt {
+    name: String,
+    #[clap(required = true, num_args(1..))]
+    members: Vec<String>,
+    /// Remove the members from the group after this duration, such as "30m", "48h" or "7d".
+    #[clap(long = "expires-in", value_parser = parse_duration)]
+    expires_in: Option<time::Duration>,
+}
+
 #[derive(Debug, Args, Clone)]
 pub struct GroupPosixOpt {
     name: String,
@@ -150,6 +184,32 @@ pub enum GroupAccountPolicyOpt {
     /// Set the minimum character length of passwords for accounts.
     #[clap(name = "password-minimum-length")]
     PasswordMinimumLength { name: String, length: u32 },
+    /// Set the minimum zxcvbn strength score (0 to 4) of passwords for accounts.
+    #[clap(name = "password-minimum-score")]
+    PasswordMinimumScore { name: String, score: u32 },
+    /// Sets whether passwords are checked against the server's breached password list.
+    #[clap(name = "password-breach-check")]
+    PasswordBreachCheck {
+        name: String,
+        #[clap(name = "enable", action = clap::ArgAction::Set)]
+        enable: bool,
+    },
+    /// Set the number of recent passwords, including the current one, that may not be
+    /// reused. Set to 0 to only prevent reuse of the current password.
+    #[clap(name = "password-history-length")]
+    PasswordHistoryLength { name: String, length: u32 },
+    /// Lock a credential for `duration` seconds once it has failed authentication
+    /// `max-attempts` times.
+    #[clap(name = "auth-lockout")]
+    AuthLockout {
+        name: String,
+        max_attempts: u32,
+        duration: u32,
+    },
+    /// Set the maximum age in seconds of passwords and api tokens. Members authenticating
+    /// with an older password may only update their credentials.
+    #[clap(name = "credential-max-age")]
+    CredentialMaxAge { name: String, max_age: u32 },
 
     /// Set the maximum time for privilege session expiry in seconds.
     #[clap(name = "privilege-expiry")]
@@ -189,6 +249,18 @@ pub enum GroupAccountPolicyOpt {
     /// Reset the minimum character length of passwords to its default value.
     #[clap(name = "reset-password-minimum-length")]
     ResetPasswordMinimumLength { name: String },
+    /// Reset the minimum zxcvbn strength score of passwords to its default value.
+    #[clap(name = "reset-password-minimum-score")]
+    ResetPasswordMinimumScore { name: String },
+    /// Reset the number of recent passwords that may not be reused to its default value.
+    #[clap(name = "reset-password-history-length")]
+    ResetPasswordHistoryLength { name: String },
+    /// Remove the authentication lockout policy from this group.
+    #[clap(name = "reset-auth-lockout")]
+    ResetAuthLockout { name: String },
+    /// Remove the maximum age of passwords and api tokens from this group.
+    #[clap(name = "reset-credential-max-age")]
+    ResetCredentialMaxAge { name: String },
     /// Reset the maximum time for privilege session expiry to its default value.
     #[clap(name = "reset-privilege-expiry")]
     ResetPrivilegedSessionExpiry { name: String },
@@ -269,7 +341,7 @@ pub enum GroupOpt {
     PurgeMembers(Named),
     /// Add new members to a group
     #[clap(name = "add-members")]
-    AddMembers(GroupNamedMembers),
+    AddMembers(GroupAddMembersOpt),
     /// Remove the named members from this group
     #[clap(name = "remove-members")]
     RemoveMembers(GroupNamedMembers),
@@ -376,6 +448,10 @@ pub struct AccountCreateOpt {
     aopts: AccountCommonOpt,
     #[clap(name = "display-name")]
     display_name: String,
+    /// The name of a creation template whose defaults and group memberships are applied
+    /// to the new account.
+    #[clap(long)]
+    template: Option<String>,
 }
 
 #[derive(Debug, Subcommand, Clone)]
@@ -425,7 +501,10 @@ pub enum AccountCredential {
         /// - An RFC3339 time of the format "YYYY-MM-DDTHH:MM:SS+TZ", "2020-09-25T11:22:02+10:00"
         /// - "now" to reset immediately
         datetime: String,
-    }
+    },
+    /// Remove any lockouts applied to this account's credentials by account policy.
+    #[clap(name = "lockout-reset")]
+    LockoutReset { account_id: String },
 }
 
 /// RADIUS secret management
@@ -597,6 +676,16 @@ pub enum PersonOpt {
     /// Create a new person's account
     #[clap(name = "create")]
     Create(AccountCreateOpt),
+    /// Create many persons from a JSON file. The file holds a list of persons, each mapping
+    /// attribute names to their values, such as `[{"name": ["alice"], "displayname": ["Alice"]}]`
+    #[clap(name = "import")]
+    Import {
+        #[clap(long = "file")]
+        file: PathBuf,
+        /// Create none of the persons if any of them can not be created
+        #[clap(long = "all-or-nothing")]
+        all_or_nothing: bool,
+    },
     /// Delete a person's account
     #[clap(name = "delete")]
     Delete(AccountNamedOpt),
@@ -648,6 +737,14 @@ pub enum ServiceAccountApiToken {
         #[clap(short = 'w', long = "readwrite")]
         read_write: bool,
 
+        /// Restrict this token to a class of operations, such as "person:read" or
+        /// "group:write". May be provided multiple times. If not provided the token is not
+        /// restricted to any class of operations. Valid scopes are person:read, person:write,
+        /// group:read, group:write, service_account:read, service_account:write and
+        /// radius:read.
+        #[clap(long = "scope")]
+        scopes: Vec<ApiTokenOperationScope>,
+
         /// Generate the token in a compact form (less than 128 ascii chars) to account for
         /// systems that may have length limits on tokens/credentials. This format of token
         /// after creation *may* not be valid on all servers until replication converges. It
@@ -745,6 +842,10 @@ pub enum ServiceAccountOpt {
         display_name: String,
         #[clap(name = "entry-managed-by")]
         entry_managed_by: String,
+        /// The name of a creation template whose defaults and group memberships are
+        /// applied to the new service account.
+        #[clap(long)]
+        template: Option<String>,
     },
     /// Update a specific service account's attributes
     #[clap(name = "update")]
@@ -791,31 +892,35 @@ pub struct LogoutOpt {
 #[derive(Debug, Subcommand, Clone)]
 pub enum SessionOpt {
     #[clap(name = "list")]
-    /// List current active sessions
-    List,
+    /// List the active sessions of the logged in account, including those on other devices
+    List {
+        /// Only list the sessions cached by this cli, without contacting the server
+        #[clap(long)]
+        cached: bool,
+    },
+    #[clap(name = "revoke")]
+    /// Revoke a session of the logged in account, such as one on a lost device. OAuth2
+    /// sessions that were started from it are revoked too.
+    Revoke {
+        /// The id of the session to revoke, as shown by `session list`
+        #[clap(name = "session-id")]
+        session_id: Uuid,
+    },
     #[clap(name = "cleanup")]
-    /// Remove sessions that have expired or are invalid.
+    /// Remove cached sessions that have expired or are invalid.
     Cleanup,
 }
 
 #[derive(Debug, Subcommand, Clone)]
 pub enum RawOpt {
     #[clap(name = "search")]
-    Search {
-        filter: ScimFilter
-    },
+    Search { filter: ScimFilter },
     #[clap(name = "create")]
-    Create {
-        file: PathBuf
-    },
+    Create { file: PathBuf },
     #[clap(name = "update")]
-    Update {
-        file: PathBuf
-    },
+    Update { file: PathBuf },
     #[clap(name = "delete")]
-    Delete {
-        id: String
-    },
+    Delete { id: String },
 }
 
 #[derive(Debug, Subcommand, Clone)]
@@ -825,6 +930,18 @@ pub enum SelfOpt {
     IdentifyUser,
     /// Show the current authenticated user's identity
     Whoami,
+    /// Send a verification message to one of your unverified email addresses
+    #[clap(name = "mail-verify")]
+    MailVerify {
+        #[clap(name = "mail")]
+        mail: String,
+    },
+    /// Confirm an email address with the token from a verification message
+    #[clap(name = "mail-confirm")]
+    MailConfirm {
+        #[clap(name = "token")]
+        token: String,
+    },
 }
 
 #[derive(Debug, Args, Clone)]
@@ -866,6 +983,7 @@ pub enum Oauth2ClaimMapJoin {
     Csv,
     Ssv,
     Array,
+    Single,
 }
 
 impl Oauth2ClaimMapJoin {
@@ -874,13 +992,14 @@ impl Oauth2ClaimMapJoin {
             Self::Csv => "csv",
             Self::Ssv => "ssv",
             Self::Array => "array",
+            Self::Single => "single",
         }
     }
 }
 
 impl ValueEnum for Oauth2ClaimMapJoin {
     fn value_variants<'a>() -> &'a [Self] {
-        &[Self::Csv, Self::Ssv, Self::Array]
+        &[Self::Csv, Self::Ssv, Self::Array, Self::Single]
     }
 
     fn to_possible_value(&self) -> Option<PossibleValue> {
@@ -946,12 +1065,21 @@ pub enum Oauth2Opt {
         values: Vec<String>,
     },
     #[clap(name = "update-claim-map-join")]
+    /// Update how the values of a custom claim are joined and formatted when emitted
     UpdateClaimMapJoin {
         name: String,
         claim_name: String,
         /// The join strategy. Valid values are csv (comma separated value), ssv (space
-        /// separated value) and array.
+        /// separated value), array and single (one semicolon separated value).
         join: Oauth2ClaimMapJoin,
+        /// A template applied to each emitted value, where `{group}` is replaced with
+        /// the name of the group granting the claim and `{value}` with the claim value.
+        /// For example `ROLE_{group}`.
+        #[clap(long)]
+        template: Option<String>,
+        /// Remove any template from this claim so values are emitted as is.
+        #[clap(long, conflicts_with = "template")]
+        clear_template: bool,
     },
     #[clap(name = "delete-claim-map")]
     /// Remove a mapping from groups to a custom claim
@@ -1012,10 +1140,11 @@ pub enum Oauth2Opt {
 
     /// Set the refresh token expiry in seconds. An empty value will reset the value to default.
     #[clap(name = "set-refresh-token-expiry")]
-    SetRefreshTokenExpiry {
-        name: String,
-        expiry: Option<u32>,
-    },
+    SetRefreshTokenExpiry { name: String, expiry: Option<u32> },
+
+    /// Set the access token expiry in seconds. An empty value will reset the value to default.
+    #[clap(name = "set-access-token-expiry")]
+    SetAccessTokenExpiry { name: String, expiry: Option<u32> },
 
     /// Add a supplemental URL as a redirection target. For example a phone app
     /// may use a redirect URL such as `app://my-cool-app` to trigger a native
@@ -1056,6 +1185,13 @@ pub enum Oauth2Opt {
     EnableStrictRedirectUri { name: String },
     #[clap(name = "disable-strict-redirect-url")]
     DisableStrictRedirectUri { name: String },
+    /// Require the client to use pushed authorisation requests (RFC 9126). When enabled,
+    /// authorisation requests that are not pushed to the server first are rejected.
+    #[clap(name = "enable-require-pushed-authorisation")]
+    EnableRequirePushedAuthorisation { name: String },
+    /// Allow the client to send authorisation requests without pushing them first.
+    #[clap(name = "disable-require-pushed-authorisation")]
+    DisableRequirePushedAuthorisation { name: String },
     #[clap(name = "enable-localhost-redirects")]
     /// Allow public clients to redirect to localhost.
     EnablePublicLocalhost { name: String },
@@ -1189,6 +1325,14 @@ pub enum DomainOpt {
         #[clap(name = "allow", action = clap::ArgAction::Set)]
         enable: bool,
     },
+    /// Set the attributes that people are allowed to modify on their own entry, such as
+    /// `displayname`. Pass no attributes to prevent people modifying their own entry.
+    /// Identity, validity, credential and server managed attributes such as `legalname`,
+    /// `mail` and `account_expire` can never be granted.
+    SetSelfWriteAttrs {
+        #[clap(name = "attrs")]
+        attrs: Vec<String>,
+    },
     #[clap(name = "show")]
     /// Show information about this system's domain
     Show,
@@ -1217,22 +1361,18 @@ pub enum MessageOpt {
 
     #[clap(name = "get")]
     /// Display the message identified by its message ID.
-    Get {
-        message_id: Uuid
-    },
+    Get { message_id: Uuid },
 
     #[clap(name = "mark-as-sent")]
     /// Mark the message with this message ID as sent. This will prevent it
     /// being sent by any mail sender.
-    MarkAsSent {
-        message_id: Uuid
-    },
+    MarkAsSent { message_id: Uuid },
 
     #[clap(name = "send-test-message")]
     SendTestMessage {
         /// The account name of the person who this message should be sent to.
         to: String,
-    }
+    },
 }
 
 #[derive(Debug, Subcommand, Clone)]
@@ -1395,6 +1535,40 @@ pub enum SchemaOpt {
     },
 }
 
+#[derive(Debug, Subcommand, Clone)]
+pub enum WebhookOpt {
+    #[clap(name = "list")]
+    /// List all webhook subscriptions
+    List,
+    #[clap(name = "get")]
+    /// Display a selected webhook subscription
+    Get(Named),
+    #[clap(name = "create")]
+    /// Create a webhook subscription. The secret that deliveries are signed with is
+    /// generated by the server, and can be displayed with `show-secret`.
+    Create {
+        #[clap(name = "name")]
+        name: String,
+        /// The url that notifications are delivered to
+        #[clap(name = "url")]
+        url: String,
+        /// The kinds of change to notify about. One or more of create, modify and delete.
+        #[clap(long = "event", required = true)]
+        events: Vec<String>,
+        /// A JSON filter limiting the entries that are notified about
+        #[clap(long)]
+        filter: Option<String>,
+        #[clap(long)]
+        description: Option<String>,
+    },
+    #[clap(name = "show-secret")]
+    /// Show the secret that deliveries to this webhook are signed with
+    ShowSecret(Named),
+    #[clap(name = "delete")]
+    /// Delete a webhook subscription
+    Delete(Named),
+}
+
 #[derive(Debug, Subcommand, Clone)]
 pub enum SystemOpt {
     #[clap(name = "pw-badlist")]
@@ -1433,6 +1607,12 @@ pub enum SystemOpt {
         #[clap(subcommand)]
         commands: MessageOpt,
     },
+    #[clap(name = "webhook")]
+    /// Manage webhook notifications of entry changes
+    Webhook {
+        #[clap(subcommand)]
+        commands: WebhookOpt,
+    },
     #[clap(name = "api")]
     /// API related things
     Api {
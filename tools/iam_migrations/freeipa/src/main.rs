@@ -531,15 +531,27 @@ async fn run_sync(
         info!("dry-run complete");
         info!("Success!");
         Ok(())
-    } else if let Err(e) = rsclient.scim_v1_sync_update(&scim_sync_request).await {
-        error!(
-            error=?e,
-            "Failed to submit SCIM sync update - see the kanidmd server log for more details."
-        );
-        Err(SyncError::SyncUpdate)
     } else {
-        info!("Success!");
-        Ok(())
+        match rsclient.scim_v1_sync_update(&scim_sync_request).await {
+            Ok(response) => {
+                for (entry, references) in response.dangling_references.iter() {
+                    warn!(
+                        %entry,
+                        ?references,
+                        "Skipped references that do not resolve to an entry in Kanidm"
+                    );
+                }
+                info!("Success!");
+                Ok(())
+            }
+            Err(e) => {
+                error!(
+                    error=?e,
+                    "Failed to submit SCIM sync update - see the kanidmd server log for more details."
+                );
+                Err(SyncError::SyncUpdate)
+            }
+        }
     }
     // done!
 }
@@ -123,6 +123,41 @@ If you did not request this message, please revoke the reset using the following
 {revoke_url}
 
 
+msg_id: {message_id}
+            "#,
+                    message_id = ctx.message_id,
+                ),
+            )
+        }
+        OutboundMessage::MailVerificationV1 {
+            display_name,
+            mail,
+            token,
+            expiry_time,
+        } => {
+            let mut verify_url = ctx.instance_url.clone();
+            verify_url.set_path("/ui/mail/verify");
+            verify_url.query_pairs_mut().append_pair("token", token);
+
+            // TODO - local users timezone preference.
+            let pretty_expiry_time = expiry_time
+                .format(&Rfc2822)
+                .unwrap_or("ERROR - invalid expiration time".into());
+
+            (
+                format!("{0}: Verify Your Email Address", ctx.instance_display_name),
+                format!(
+                    r#"Hi {display_name},
+
+Please confirm that {mail} belongs to you by following this link:
+
+{verify_url}
+
+This link will expire at {pretty_expiry_time}
+
+If you did not request this message, you can ignore it.
+
+
 msg_id: {message_id}
             "#,
                     message_id = ctx.message_id,
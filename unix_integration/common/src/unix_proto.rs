@@ -131,6 +131,7 @@ pub enum ClientRequest {
     },
     PamAccountAllowed(String),
     PamAccountBeginSession(String),
+    AutomountMap(String),
     InvalidateCache,
     ClearCache,
     Status,
@@ -159,6 +160,7 @@ impl ClientRequest {
                 format!("PamAccountAllowed({id})")
             }
             ClientRequest::PamAccountBeginSession(_) => "PamAccountBeginSession".to_string(),
+            ClientRequest::AutomountMap(map_name) => format!("AutomountMap({map_name})"),
             ClientRequest::InvalidateCache => "InvalidateCache".to_string(),
             ClientRequest::ClearCache => "ClearCache".to_string(),
             ClientRequest::Status => "Status".to_string(),
@@ -172,6 +174,13 @@ pub struct ProviderStatus {
     pub online: bool,
 }
 
+/// A single key of an automount map, as consumed by autofs.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AutomountEntry {
+    pub key: String,
+    pub information: String,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub enum ClientResponse {
     SshKeys(Vec<String>),
@@ -188,6 +197,8 @@ pub enum ClientResponse {
 
     ProviderStatus(Vec<ProviderStatus>),
 
+    AutomountMap(Vec<AutomountEntry>),
+
     Ok,
     Error(OperationError),
 }
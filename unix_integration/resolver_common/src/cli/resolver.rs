@@ -204,6 +204,14 @@ async fn handle_client(
                         error!("unable to load keys, returning empty set.");
                         ClientResponse::SshKeys(vec![])
                     }),
+                ClientRequest::AutomountMap(map_name) => cachelayer
+                    .get_automount_map(map_name.as_str())
+                    .await
+                    .map(ClientResponse::AutomountMap)
+                    .unwrap_or_else(|_| {
+                        error!("unable to load automount map, returning empty.");
+                        ClientResponse::AutomountMap(Vec::new())
+                    }),
                 ClientRequest::NssAccounts => cachelayer
                     .get_nssaccounts()
                     .await
@@ -1048,6 +1048,7 @@ mod tests {
             gidnumber: 2000,
             uuid: uuid::uuid!("0302b99c-f0f6-41ab-9492-852692b0fd16"),
             shell: None,
+            home_directory: Some("/exports/testuser".to_string()),
             groups: Vec::new(),
             sshkeys: vec!["key-a".to_string()],
             valid: true,
@@ -1077,6 +1078,11 @@ mod tests {
         // test we can get it.
         let r1 = dbtxn.get_account(&id_name).unwrap();
         assert!(r1.is_some());
+        // The rendered home directory is cached with the token.
+        assert_eq!(
+            r1.and_then(|(tok, _)| tok.home_directory).as_deref(),
+            Some("/exports/testuser")
+        );
         let r2 = dbtxn.get_account(&id_spn).unwrap();
         assert!(r2.is_some());
         let r3 = dbtxn.get_account(&id_uuid).unwrap();
@@ -1229,6 +1235,7 @@ mod tests {
             gidnumber: 2000,
             uuid: uuid::uuid!("0302b99c-f0f6-41ab-9492-852692b0fd16"),
             shell: None,
+            home_directory: None,
             groups: vec![gt1.clone(), gt2],
             sshkeys: vec!["key-a".to_string()],
             valid: true,
@@ -1356,6 +1363,7 @@ mod tests {
             gidnumber: 2000,
             uuid: uuid::uuid!("0302b99c-f0f6-41ab-9492-852692b0fd16"),
             shell: None,
+            home_directory: None,
             groups: Vec::new(),
             sshkeys: vec!["key-a".to_string()],
             valid: true,
@@ -1370,6 +1378,7 @@ mod tests {
             gidnumber: 2001,
             uuid: uuid::uuid!("799123b2-3802-4b19-b0b8-1ffae2aa9a4b"),
             shell: None,
+            home_directory: None,
             groups: Vec::new(),
             sshkeys: vec!["key-a".to_string()],
             valid: true,
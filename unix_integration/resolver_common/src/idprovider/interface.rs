@@ -3,7 +3,7 @@ use kanidm_hsm_crypto::provider::BoxedDynTpm;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sparkle_unix_common::unix_proto::{
-    DeviceAuthorizationResponse, PamAuthRequest, PamAuthResponse,
+    AutomountEntry, DeviceAuthorizationResponse, PamAuthRequest, PamAuthResponse,
 };
 use std::collections::BTreeMap;
 use std::fmt;
@@ -119,6 +119,9 @@ pub struct UserToken {
     pub gidnumber: u32,
     pub displayname: String,
     pub shell: Option<String>,
+    // The home directory as rendered by the provider, if it supplies one.
+    #[serde(default)]
+    pub home_directory: Option<String>,
     pub groups: Vec<GroupToken>,
 
     // Could there be a better type here?
@@ -319,4 +322,13 @@ pub trait IdProvider {
         _tpm: &mut BoxedDynTpm,
         _now: SystemTime,
     ) -> Result<GroupTokenState, IdpError>;
+
+    /// Retrieve the keys of the named automount map. Automount maps are not cached, so
+    /// an offline provider, or one that does not know this map, returns no keys.
+    async fn unix_automount_map_get(
+        &self,
+        _map_name: &str,
+        _tpm: &mut BoxedDynTpm,
+        _now: SystemTime,
+    ) -> Result<Vec<AutomountEntry>, IdpError>;
 }
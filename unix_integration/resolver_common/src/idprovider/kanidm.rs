@@ -7,7 +7,7 @@ use super::interface::{
         DEFAULT_CACHE_TIMEOUT_JITTER_MS, DEFAULT_OFFLINE_PROVIDER_CHECK_TIME,
     },
     unix_common::unix_config::{GroupMap, KanidmConfig},
-    unix_common::unix_proto::PamAuthRequest,
+    unix_common::unix_proto::{AutomountEntry, PamAuthRequest},
     AuthCredHandler, AuthRequest, AuthResult, GroupToken, GroupTokenState, Id, IdProvider,
     IdpError, ProviderOrigin, UserToken, UserTokenState,
 };
@@ -18,6 +18,7 @@ use kanidm_client::{ClientError, KanidmClient, StatusCode};
 use kanidm_lib_crypto::CryptoPolicy;
 use kanidm_lib_crypto::DbPasswordV1;
 use kanidm_lib_crypto::Password;
+use kanidm_proto::constants::{ATTR_AUTOMOUNT_INFORMATION, ATTR_AUTOMOUNT_KEY};
 use kanidm_proto::internal::OperationError;
 use kanidm_proto::v1::{UnixGroupToken, UnixUserToken};
 use std::collections::BTreeSet;
@@ -139,6 +140,7 @@ impl From<UnixUserToken> for UserToken {
             gidnumber,
             uuid,
             shell,
+            home_directory,
             groups,
             sshkeys,
             valid,
@@ -156,6 +158,7 @@ impl From<UnixUserToken> for UserToken {
             gidnumber,
             displayname,
             shell,
+            home_directory,
             groups,
             sshkeys,
             valid,
@@ -746,6 +749,52 @@ impl IdProvider for KanidmProvider {
         }
     }
 
+    async fn unix_automount_map_get(
+        &self,
+        map_name: &str,
+        tpm: &mut BoxedDynTpm,
+        now: SystemTime,
+    ) -> Result<Vec<AutomountEntry>, IdpError> {
+        let mut inner = self.inner.lock().await;
+
+        if !inner.check_online(tpm, now).await {
+            return Ok(Vec::with_capacity(0));
+        }
+
+        match inner.client.idm_automount_key_list(map_name).await {
+            Ok(entries) => Ok(entries
+                .into_iter()
+                .filter_map(|mut entry| {
+                    let key = entry.attrs.remove(ATTR_AUTOMOUNT_KEY)?.pop()?;
+                    let information = entry.attrs.remove(ATTR_AUTOMOUNT_INFORMATION)?.pop()?;
+                    Some(AutomountEntry { key, information })
+                })
+                .collect()),
+            Err(ClientError::Transport(err)) => {
+                error!(?err, "transport error");
+                inner.state = CacheState::OfflineNextCheck(next_offline_check(now));
+                Ok(Vec::with_capacity(0))
+            }
+            Err(ClientError::Http(StatusCode::UNAUTHORIZED, reason, opid)) => {
+                error!(?reason, ?opid, "authentication error, moving to offline");
+                inner.state = CacheState::OfflineNextCheck(next_offline_check(now));
+                Ok(Vec::with_capacity(0))
+            }
+            Err(ClientError::Http(
+                StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND,
+                Some(OperationError::NoMatchingEntries),
+                opid,
+            )) => {
+                debug!(?opid, ?map_name, "automount map not found");
+                Ok(Vec::with_capacity(0))
+            }
+            Err(err) => {
+                error!(?err, "client error");
+                Err(IdpError::BadRequest)
+            }
+        }
+    }
+
     async fn unix_user_authorise(&self, token: &UserToken) -> Result<Option<bool>, IdpError> {
         let inner = self.inner.lock().await;
 
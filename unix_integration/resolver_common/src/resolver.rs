@@ -27,8 +27,8 @@ use sparkle_unix_common::constants::{
 use sparkle_unix_common::unix_config::{HomeAttr, UidAttr};
 use sparkle_unix_common::unix_passwd::{EtcGroup, EtcShadow, EtcUser};
 use sparkle_unix_common::unix_proto::{
-    HomeDirectoryInfo, NssGroup, NssUser, PamAuthRequest, PamAuthResponse, PamServiceInfo,
-    ProviderStatus,
+    AutomountEntry, HomeDirectoryInfo, NssGroup, NssUser, PamAuthRequest, PamAuthResponse,
+    PamServiceInfo, ProviderStatus,
 };
 use std::fmt::Display;
 use std::num::NonZeroUsize;
@@ -760,6 +760,29 @@ impl Resolver {
             .unwrap_or_else(|| Vec::with_capacity(0)))
     }
 
+    /// Retrieve the keys of an automount map from the first provider that knows of it,
+    /// in priority order.
+    pub async fn get_automount_map(&self, map_name: &str) -> Result<Vec<AutomountEntry>, ()> {
+        let current_time = SystemTime::now();
+        let mut hsm_lock = self.hsm.lock().await;
+
+        for client in self.clients.iter() {
+            let mut entries = client
+                .unix_automount_map_get(map_name, hsm_lock.deref_mut(), current_time)
+                .await
+                .map_err(|err| {
+                    error!(?err, ?map_name, "failed to retrieve automount map");
+                })?;
+
+            if !entries.is_empty() {
+                entries.sort_unstable();
+                return Ok(entries);
+            }
+        }
+
+        Ok(Vec::with_capacity(0))
+    }
+
     fn token_homedirectory_alias(&self, token: &UserToken) -> Option<String> {
         let is_primary_origin = token.provider == self.primary_origin;
         self.home_alias.map(|t| match t {
@@ -785,6 +808,11 @@ impl Resolver {
     }
 
     fn token_abs_homedirectory(&self, token: &UserToken) -> String {
+        // A home directory rendered by the provider is authoritative.
+        if let Some(home_directory) = &token.home_directory {
+            return home_directory.clone();
+        }
+
         self.home_prefix
             .join(self.token_homedirectory(token))
             .to_string_lossy()
@@ -20,6 +20,7 @@ use sparkle_unix_common::constants::{
 };
 use sparkle_unix_common::unix_config::{GroupMap, KanidmConfig};
 use sparkle_unix_common::unix_passwd::{CryptPw, EtcGroup, EtcShadow, EtcUser};
+use sparkle_unix_common::unix_proto::AutomountEntry;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::atomic::Ordering;
@@ -1374,3 +1375,67 @@ async fn test_cache_extend_group_members() {
     // Assert no outstanding async requests
     assert!(async_refresh_rx.is_empty());
 }
+
+/// Creates the auto.master automount map with two keys.
+async fn test_automount_fixture(rsclient: KanidmClient) {
+    rsclient
+        .idm_automount_map_create("auto.master", Some("Master automount map"))
+        .await
+        .unwrap();
+    rsclient
+        .idm_automount_key_create("auto.master", "/home", "auto.home")
+        .await
+        .unwrap();
+    rsclient
+        .idm_automount_key_create(
+            "auto.master",
+            "/shares",
+            "-fstype=nfs4 nfs.example.com:/shares",
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_cache_automount_map() {
+    let (cachelayer, async_refresh_rx, _adminclient) =
+        setup_test(fixture(test_automount_fixture)).await;
+
+    // Automount maps are not cached, so offline we have no keys.
+    cachelayer.mark_offline().await;
+    let entries = cachelayer
+        .get_automount_map("auto.master")
+        .await
+        .expect("Failed to get automount map");
+    assert!(entries.is_empty());
+
+    cachelayer.mark_next_check_now(SystemTime::now()).await;
+    assert!(cachelayer.test_connection().await);
+
+    let entries = cachelayer
+        .get_automount_map("auto.master")
+        .await
+        .expect("Failed to get automount map");
+    assert_eq!(
+        entries,
+        vec![
+            AutomountEntry {
+                key: "/home".to_string(),
+                information: "auto.home".to_string(),
+            },
+            AutomountEntry {
+                key: "/shares".to_string(),
+                information: "-fstype=nfs4 nfs.example.com:/shares".to_string(),
+            },
+        ]
+    );
+
+    // An unknown map has no keys.
+    let entries = cachelayer
+        .get_automount_map("auto.nonexist")
+        .await
+        .expect("Failed to get automount map");
+    assert!(entries.is_empty());
+
+    assert!(async_refresh_rx.is_empty());
+}